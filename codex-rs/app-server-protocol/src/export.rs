@@ -1,3 +1,4 @@
+use crate::APP_SERVER_PROTOCOL_VERSION;
 use crate::ClientNotification;
 use crate::ClientRequest;
 use crate::ServerNotification;
@@ -260,6 +261,10 @@ fn build_schema_bundle(schemas: Vec<GeneratedSchema>) -> Result<Value> {
         "title".to_string(),
         Value::String("CodexAppServerProtocol".into()),
     );
+    root.insert(
+        "x-protocol-version".to_string(),
+        Value::from(APP_SERVER_PROTOCOL_VERSION),
+    );
     root.insert("type".to_string(), Value::String("object".into()));
     root.insert("definitions".to_string(), Value::Object(definitions));
 
@@ -13,6 +13,11 @@ use serde::Serialize;
 use strum_macros::Display;
 use ts_rs::TS;
 
+/// Version of the app-server protocol, reported by `initialize` and in the
+/// generated JSON Schema bundle. Bumped whenever a released method,
+/// notification or field changes incompatibly; additive changes keep it.
+pub const APP_SERVER_PROTOCOL_VERSION: u32 = 2;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, TS)]
 #[ts(type = "string")]
 pub struct GitSha(pub String);
@@ -31,6 +31,10 @@ use crate::protocol::common::GitSha;
 #[serde(rename_all = "camelCase")]
 pub struct InitializeParams {
     pub client_info: ClientInfo,
+    /// Protocol version the client was built against. The server rejects
+    /// versions newer than its own; omit to accept whatever it speaks.
+    #[serde(default)]
+    pub protocol_version: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, JsonSchema, TS)]
@@ -45,6 +49,8 @@ pub struct ClientInfo {
 #[serde(rename_all = "camelCase")]
 pub struct InitializeResponse {
     pub user_agent: String,
+    /// Protocol version the server speaks.
+    pub protocol_version: u32,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, JsonSchema, TS)]
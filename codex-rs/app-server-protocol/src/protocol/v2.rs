@@ -1382,9 +1382,20 @@ pub struct TurnInterruptResponse {}
 #[ts(tag = "type")]
 #[ts(export_to = "v2/")]
 pub enum UserInput {
-    Text { text: String },
-    Image { url: String },
-    LocalImage { path: PathBuf },
+    Text {
+        text: String,
+    },
+    Image {
+        url: String,
+    },
+    LocalImage {
+        path: PathBuf,
+    },
+    /// File mentioned with `@path`, relative to the thread's cwd. Its
+    /// contents are attached to the turn by the server.
+    FileMention {
+        path: PathBuf,
+    },
 }
 
 impl UserInput {
@@ -1393,6 +1404,7 @@ impl UserInput {
             UserInput::Text { text } => CoreUserInput::Text { text },
             UserInput::Image { url } => CoreUserInput::Image { image_url: url },
             UserInput::LocalImage { path } => CoreUserInput::LocalImage { path },
+            UserInput::FileMention { path } => CoreUserInput::FileMention { path },
         }
     }
 }
@@ -1403,6 +1415,7 @@ impl From<CoreUserInput> for UserInput {
             CoreUserInput::Text { text } => UserInput::Text { text },
             CoreUserInput::Image { image_url } => UserInput::Image { url: image_url },
             CoreUserInput::LocalImage { path } => UserInput::LocalImage { path },
+            CoreUserInput::FileMention { path } => UserInput::FileMention { path },
             _ => unreachable!("unsupported user input variant"),
         }
     }
@@ -1988,6 +2001,9 @@ mod tests {
                 CoreUserInput::LocalImage {
                     path: PathBuf::from("local/image.png"),
                 },
+                CoreUserInput::FileMention {
+                    path: PathBuf::from("src/lib.rs"),
+                },
             ],
         });
 
@@ -2005,6 +2021,9 @@ mod tests {
                     UserInput::LocalImage {
                         path: PathBuf::from("local/image.png"),
                     },
+                    UserInput::FileMention {
+                        path: PathBuf::from("src/lib.rs"),
+                    },
                 ],
             }
         );
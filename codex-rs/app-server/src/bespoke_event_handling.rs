@@ -107,6 +107,7 @@ pub(crate) async fn apply_bespoke_event_handling(
             changes,
             reason,
             grant_root,
+            proposed_permissions: _,
         }) => match api_version {
             ApiVersion::V1 => {
                 let params = ApplyPatchApprovalParams {
@@ -183,6 +184,7 @@ pub(crate) async fn apply_bespoke_event_handling(
             reason,
             proposed_execpolicy_amendment,
             parsed_cmd,
+            proposed_permissions: _,
         }) => match api_version {
             ApiVersion::V1 => {
                 let params = ExecCommandApprovalParams {
@@ -910,7 +912,9 @@ async fn handle_token_count_event(
     token_count_event: TokenCountEvent,
     outgoing: &OutgoingMessageSender,
 ) {
-    let TokenCountEvent { info, rate_limits } = token_count_event;
+    let TokenCountEvent {
+        info, rate_limits, ..
+    } = token_count_event;
     if let Some(token_usage) = info.map(ThreadTokenUsage::from) {
         let notification = ThreadTokenUsageUpdatedNotification {
             thread_id: conversation_id.to_string(),
@@ -1571,6 +1575,8 @@ mod tests {
             TokenCountEvent {
                 info: Some(info),
                 rate_limits: Some(rate_limits),
+                pinned_context_tokens: None,
+                spend: None,
             },
             &outgoing,
         )
@@ -1624,6 +1630,8 @@ mod tests {
             TokenCountEvent {
                 info: None,
                 rate_limits: None,
+                pinned_context_tokens: None,
+                spend: None,
             },
             &outgoing,
         )
@@ -2,6 +2,7 @@ use crate::bespoke_event_handling::apply_bespoke_event_handling;
 use crate::error_code::INTERNAL_ERROR_CODE;
 use crate::error_code::INVALID_REQUEST_ERROR_CODE;
 use crate::fuzzy_file_search::run_fuzzy_file_search;
+use crate::metrics::ServerMetrics;
 use crate::models::supported_models;
 use crate::outgoing_message::OutgoingMessageSender;
 use crate::outgoing_message::OutgoingNotification;
@@ -116,6 +117,7 @@ use codex_core::NewConversation;
 use codex_core::RolloutRecorder;
 use codex_core::SessionMeta;
 use codex_core::auth::CLIENT_ID;
+use codex_core::auth::accounts;
 use codex_core::auth::login_with_api_key;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
@@ -138,6 +140,7 @@ use codex_core::protocol::ReviewRequest;
 use codex_core::protocol::ReviewTarget as CoreReviewTarget;
 use codex_core::protocol::SessionConfiguredEvent;
 use codex_core::read_head_for_summary;
+use codex_core::rollout_index_path;
 use codex_core::sandboxing::SandboxPermissions;
 use codex_feedback::CodexFeedback;
 use codex_login::ServerOptions as LoginServerOptions;
@@ -223,6 +226,7 @@ pub(crate) struct CodexMessageProcessor {
     turn_summary_store: TurnSummaryStore,
     pending_fuzzy_searches: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
     feedback: CodexFeedback,
+    metrics: Arc<ServerMetrics>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -264,6 +268,7 @@ impl CodexMessageProcessor {
         config: Arc<Config>,
         cli_overrides: Vec<(String, TomlValue)>,
         feedback: CodexFeedback,
+        metrics: Arc<ServerMetrics>,
     ) -> Self {
         Self {
             auth_manager,
@@ -278,6 +283,7 @@ impl CodexMessageProcessor {
             turn_summary_store: Arc::new(Mutex::new(HashMap::new())),
             pending_fuzzy_searches: Arc::new(Mutex::new(HashMap::new())),
             feedback,
+            metrics,
         }
     }
 
@@ -551,7 +557,7 @@ impl CodexMessageProcessor {
         }
 
         match login_with_api_key(
-            &self.config.codex_home,
+            &accounts::auth_home(&self.config),
             &params.api_key,
             self.config.cli_auth_credentials_store_mode,
         ) {
@@ -634,7 +640,7 @@ impl CodexMessageProcessor {
         Ok(LoginServerOptions {
             open_browser: false,
             ..LoginServerOptions::new(
-                config.codex_home.clone(),
+                accounts::auth_home(config),
                 CLIENT_ID.to_string(),
                 config.forced_chatgpt_workspace_id.clone(),
                 config.cli_auth_credentials_store_mode,
@@ -1298,6 +1304,8 @@ impl CodexMessageProcessor {
                     session_configured,
                     ..
                 } = conversation_id;
+                self.metrics
+                    .session_started(conversation_id, &session_configured.sandbox_policy);
                 let response = NewConversationResponse {
                     conversation_id,
                     model: session_configured.model,
@@ -1348,6 +1356,8 @@ impl CodexMessageProcessor {
                     session_configured,
                     ..
                 } = new_conv;
+                self.metrics
+                    .session_started(conversation_id, &session_configured.sandbox_policy);
                 let rollout_path = session_configured.rollout_path.clone();
                 let fallback_provider = self.config.model_provider_id.as_str();
 
@@ -1594,7 +1604,7 @@ impl CodexMessageProcessor {
             }
             InitialHistory::Forked(history.into_iter().map(RolloutItem::ResponseItem).collect())
         } else if let Some(path) = path {
-            match RolloutRecorder::get_rollout_history(&path).await {
+            match RolloutRecorder::get_resume_history(&path).await {
                 Ok(initial_history) => initial_history,
                 Err(err) => {
                     self.send_invalid_request_error(
@@ -1646,7 +1656,7 @@ impl CodexMessageProcessor {
                 }
             };
 
-            match RolloutRecorder::get_rollout_history(&path).await {
+            match RolloutRecorder::get_resume_history(&path).await {
                 Ok(initial_history) => initial_history,
                 Err(err) => {
                     self.send_invalid_request_error(
@@ -1675,6 +1685,8 @@ impl CodexMessageProcessor {
                 session_configured,
                 ..
             }) => {
+                self.metrics
+                    .session_started(conversation_id, &session_configured.sandbox_policy);
                 let SessionConfiguredEvent {
                     rollout_path,
                     initial_messages,
@@ -2245,7 +2257,7 @@ impl CodexMessageProcessor {
         };
 
         let conversation_history = if let Some(path) = path {
-            match RolloutRecorder::get_rollout_history(&path).await {
+            match RolloutRecorder::get_resume_history(&path).await {
                 Ok(initial_history) => initial_history,
                 Err(err) => {
                     self.send_invalid_request_error(
@@ -2264,7 +2276,7 @@ impl CodexMessageProcessor {
             .await
             {
                 Ok(Some(found_path)) => {
-                    match RolloutRecorder::get_rollout_history(&found_path).await {
+                    match RolloutRecorder::get_resume_history(&found_path).await {
                         Ok(initial_history) => initial_history,
                         Err(err) => {
                             self.send_invalid_request_error(
@@ -2326,6 +2338,8 @@ impl CodexMessageProcessor {
                 session_configured,
                 ..
             }) => {
+                self.metrics
+                    .session_started(conversation_id, &session_configured.sandbox_policy);
                 self.outgoing
                     .send_server_notification(ServerNotification::SessionConfigured(
                         SessionConfiguredNotification {
@@ -2479,6 +2493,7 @@ impl CodexMessageProcessor {
             .await
         {
             info!("conversation {conversation_id} was active; shutting down");
+            self.metrics.session_ended(&conversation_id);
             let conversation_clone = conversation.clone();
             let notify = Arc::new(tokio::sync::Notify::new());
             let notify_clone = notify.clone();
@@ -2535,7 +2550,15 @@ impl CodexMessageProcessor {
                 .codex_home
                 .join(codex_core::ARCHIVED_SESSIONS_SUBDIR);
             tokio::fs::create_dir_all(&archive_folder).await?;
-            tokio::fs::rename(&canonical_rollout_path, &archive_folder.join(&file_name)).await?;
+            let archived_path = archive_folder.join(&file_name);
+            tokio::fs::rename(&canonical_rollout_path, &archived_path).await?;
+            // Older rollouts have no index, and a missing one is rebuilt on
+            // resume, so failing to move it is not an error.
+            let _ = tokio::fs::rename(
+                rollout_index_path(&canonical_rollout_path),
+                rollout_index_path(&archived_path),
+            )
+            .await;
             Ok(())
         }
         .await;
@@ -2893,6 +2916,9 @@ impl CodexMessageProcessor {
                 data: None,
             })?;
 
+        self.metrics
+            .session_started(conversation_id, &session_configured.sandbox_policy);
+
         if let Err(err) = self
             .attach_conversation_listener(conversation_id, false, ApiVersion::V2)
             .await
@@ -3019,6 +3045,14 @@ impl CodexMessageProcessor {
         let _ = conversation.submit(Op::Interrupt).await;
     }
 
+    /// Stops every listener attached through this processor. The threads keep
+    /// running and can be attached again from another connection.
+    pub(crate) fn detach_conversation_listeners(&mut self) {
+        for (_, cancel_tx) in self.conversation_listeners.drain() {
+            let _ = cancel_tx.send(());
+        }
+    }
+
     async fn add_conversation_listener(
         &mut self,
         request_id: RequestId,
@@ -3095,6 +3129,7 @@ impl CodexMessageProcessor {
         let outgoing_for_task = self.outgoing.clone();
         let pending_interrupts = self.pending_interrupts.clone();
         let turn_summary_store = self.turn_summary_store.clone();
+        let metrics = self.metrics.clone();
         let api_version_for_task = api_version;
         tokio::spawn(async move {
             loop {
@@ -3112,6 +3147,8 @@ impl CodexMessageProcessor {
                             }
                         };
 
+                        metrics.observe_event(&conversation_id, &event.msg);
+
                         if let EventMsg::RawResponseItem(_) = &event.msg
                             && !experimental_raw_events {
                                 continue;
@@ -1,16 +1,24 @@
 #![deny(clippy::print_stdout, clippy::print_stderr)]
 
 use codex_common::CliConfigOverrides;
+use codex_core::AuthManager;
+use codex_core::ConversationManager;
 use codex_core::config::Config;
+use codex_core::default_client::init_http_client_options;
 use std::io::ErrorKind;
 use std::io::Result as IoResult;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use crate::message_processor::MessageProcessor;
+use crate::metrics::ServerMetrics;
+use crate::metrics::spawn_metrics_endpoint;
 use crate::outgoing_message::OutgoingMessage;
 use crate::outgoing_message::OutgoingMessageSender;
+use crate::status_socket::spawn_status_socket;
 use codex_app_server_protocol::JSONRPCMessage;
 use codex_feedback::CodexFeedback;
+use codex_protocol::protocol::SessionSource;
 use tokio::io::AsyncBufReadExt;
 use tokio::io::AsyncWriteExt;
 use tokio::io::BufReader;
@@ -33,14 +41,123 @@ mod config_api;
 mod error_code;
 mod fuzzy_file_search;
 mod message_processor;
+mod metrics;
 mod models;
 mod outgoing_message;
+mod status_socket;
+mod websocket;
 
 /// Size of the bounded channels used to communicate between tasks. The value
 /// is a balance between throughput and memory usage – 128 messages should be
 /// plenty for an interactive CLI.
 const CHANNEL_CAPACITY: usize = 128;
 
+/// Everything a [`MessageProcessor`] needs besides its outgoing channel,
+/// loaded once per server process.
+pub(crate) struct ServerContext {
+    codex_linux_sandbox_exe: Option<PathBuf>,
+    config: Arc<Config>,
+    cli_overrides: Vec<(String, TomlValue)>,
+    feedback: CodexFeedback,
+    metrics: Arc<ServerMetrics>,
+    auth_manager: Arc<AuthManager>,
+    conversation_manager: Arc<ConversationManager>,
+}
+
+impl ServerContext {
+    /// Loads the config and installs logging, tracing and the metrics
+    /// endpoint.
+    async fn load(
+        codex_linux_sandbox_exe: Option<PathBuf>,
+        cli_config_overrides: CliConfigOverrides,
+    ) -> IoResult<Self> {
+        // Parse CLI overrides once and derive the base Config eagerly so later
+        // components do not need to work with raw TOML values.
+        let cli_kv_overrides = cli_config_overrides.parse_overrides().map_err(|e| {
+            std::io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("error parsing -c overrides: {e}"),
+            )
+        })?;
+        let config = Config::load_with_cli_overrides(cli_kv_overrides.clone())
+            .await
+            .map_err(|e| {
+                std::io::Error::new(ErrorKind::InvalidData, format!("error loading config: {e}"))
+            })?;
+        // Sessions may load other configs, but `[http]` is fixed here, for the
+        // whole server.
+        init_http_client_options(config.http.clone());
+
+        let feedback = CodexFeedback::new();
+
+        let otel = codex_core::otel_init::build_provider(&config, env!("CARGO_PKG_VERSION"))
+            .map_err(|e| {
+                std::io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("error loading otel config: {e}"),
+                )
+            })?;
+
+        // Install a simple subscriber so `tracing` output is visible.  Users can
+        // control the log level with `RUST_LOG`.
+        let stderr_fmt = tracing_subscriber::fmt::layer()
+            .with_writer(std::io::stderr)
+            .with_span_events(tracing_subscriber::fmt::format::FmtSpan::FULL)
+            .with_filter(EnvFilter::from_default_env());
+
+        let feedback_layer = tracing_subscriber::fmt::layer()
+            .with_writer(feedback.make_writer())
+            .with_ansi(false)
+            .with_target(false)
+            .with_filter(Targets::new().with_default(Level::TRACE));
+
+        let otel_logger_layer = otel.as_ref().and_then(|o| o.logger_layer());
+
+        let otel_tracing_layer = otel.as_ref().and_then(|o| o.tracing_layer());
+
+        let _ = tracing_subscriber::registry()
+            .with(stderr_fmt)
+            .with(feedback_layer)
+            .with(otel_logger_layer)
+            .with(otel_tracing_layer)
+            .try_init();
+
+        let metrics = Arc::new(ServerMetrics::default());
+        spawn_metrics_endpoint(&config.app_server_metrics, metrics.clone())?;
+        spawn_status_socket(&config)?;
+
+        let auth_manager = AuthManager::shared_from_config(&config, false);
+        let conversation_manager = Arc::new(ConversationManager::new(
+            auth_manager.clone(),
+            SessionSource::VSCode,
+        ));
+
+        Ok(Self {
+            codex_linux_sandbox_exe,
+            config: Arc::new(config),
+            cli_overrides: cli_kv_overrides,
+            feedback,
+            metrics,
+            auth_manager,
+            conversation_manager,
+        })
+    }
+
+    /// A processor for one client connection.
+    pub(crate) fn message_processor(&self, outgoing: OutgoingMessageSender) -> MessageProcessor {
+        MessageProcessor::new(
+            outgoing,
+            self.codex_linux_sandbox_exe.clone(),
+            Arc::clone(&self.config),
+            self.cli_overrides.clone(),
+            self.feedback.clone(),
+            self.metrics.clone(),
+            self.auth_manager.clone(),
+            self.conversation_manager.clone(),
+        )
+    }
+}
+
 pub async fn run_main(
     codex_linux_sandbox_exe: Option<PathBuf>,
     cli_config_overrides: CliConfigOverrides,
@@ -72,73 +189,14 @@ pub async fn run_main(
         }
     });
 
-    // Parse CLI overrides once and derive the base Config eagerly so later
-    // components do not need to work with raw TOML values.
-    let cli_kv_overrides = cli_config_overrides.parse_overrides().map_err(|e| {
-        std::io::Error::new(
-            ErrorKind::InvalidInput,
-            format!("error parsing -c overrides: {e}"),
-        )
-    })?;
-    let config = Config::load_with_cli_overrides(cli_kv_overrides.clone())
-        .await
-        .map_err(|e| {
-            std::io::Error::new(ErrorKind::InvalidData, format!("error loading config: {e}"))
-        })?;
-
-    let feedback = CodexFeedback::new();
-
-    let otel =
-        codex_core::otel_init::build_provider(&config, env!("CARGO_PKG_VERSION")).map_err(|e| {
-            std::io::Error::new(
-                ErrorKind::InvalidData,
-                format!("error loading otel config: {e}"),
-            )
-        })?;
-
-    // Install a simple subscriber so `tracing` output is visible.  Users can
-    // control the log level with `RUST_LOG`.
-    let stderr_fmt = tracing_subscriber::fmt::layer()
-        .with_writer(std::io::stderr)
-        .with_span_events(tracing_subscriber::fmt::format::FmtSpan::FULL)
-        .with_filter(EnvFilter::from_default_env());
-
-    let feedback_layer = tracing_subscriber::fmt::layer()
-        .with_writer(feedback.make_writer())
-        .with_ansi(false)
-        .with_target(false)
-        .with_filter(Targets::new().with_default(Level::TRACE));
-
-    let otel_logger_layer = otel.as_ref().and_then(|o| o.logger_layer());
-
-    let otel_tracing_layer = otel.as_ref().and_then(|o| o.tracing_layer());
-
-    let _ = tracing_subscriber::registry()
-        .with(stderr_fmt)
-        .with(feedback_layer)
-        .with(otel_logger_layer)
-        .with(otel_tracing_layer)
-        .try_init();
+    let context = ServerContext::load(codex_linux_sandbox_exe, cli_config_overrides).await?;
 
     // Task: process incoming messages.
     let processor_handle = tokio::spawn({
-        let outgoing_message_sender = OutgoingMessageSender::new(outgoing_tx);
-        let cli_overrides: Vec<(String, TomlValue)> = cli_kv_overrides.clone();
-        let mut processor = MessageProcessor::new(
-            outgoing_message_sender,
-            codex_linux_sandbox_exe,
-            std::sync::Arc::new(config),
-            cli_overrides,
-            feedback.clone(),
-        );
+        let mut processor = context.message_processor(OutgoingMessageSender::new(outgoing_tx));
         async move {
             while let Some(msg) = incoming_rx.recv().await {
-                match msg {
-                    JSONRPCMessage::Request(r) => processor.process_request(r).await,
-                    JSONRPCMessage::Response(r) => processor.process_response(r).await,
-                    JSONRPCMessage::Notification(n) => processor.process_notification(n).await,
-                    JSONRPCMessage::Error(e) => processor.process_error(e),
-                }
+                processor.process_message(msg).await;
             }
 
             info!("processor task exited (channel closed)");
@@ -175,3 +233,21 @@ pub async fn run_main(
 
     Ok(())
 }
+
+/// Serves the app-server protocol over WebSocket at `listen` (`ws://HOST:PORT`
+/// or `HOST:PORT`) until the process is stopped. Clients must present the
+/// token from `CODEX_APP_SERVER_TOKEN`; browser pages must also come from one
+/// of `allowed_origins`.
+pub async fn run_websocket_server(
+    codex_linux_sandbox_exe: Option<PathBuf>,
+    cli_config_overrides: CliConfigOverrides,
+    listen: &str,
+    allowed_origins: Vec<String>,
+) -> IoResult<()> {
+    let access = websocket::Access {
+        token: websocket::token_from_env()?,
+        allowed_origins,
+    };
+    let context = ServerContext::load(codex_linux_sandbox_exe, cli_config_overrides).await?;
+    websocket::serve(listen, access, Arc::new(context)).await
+}
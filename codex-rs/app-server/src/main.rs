@@ -1,10 +1,35 @@
+use clap::Parser;
 use codex_app_server::run_main;
+use codex_app_server::run_websocket_server;
 use codex_arg0::arg0_dispatch_or_else;
 use codex_common::CliConfigOverrides;
 
+#[derive(Debug, Parser)]
+struct Cli {
+    /// Serve the protocol over WebSocket at this address instead of stdio.
+    #[arg(long, value_name = "ADDR")]
+    listen: Option<String>,
+
+    /// Let web pages from this origin connect to `--listen`. Repeatable.
+    #[arg(long = "allow-origin", value_name = "ORIGIN", requires = "listen")]
+    allow_origin: Vec<String>,
+}
+
 fn main() -> anyhow::Result<()> {
     arg0_dispatch_or_else(|codex_linux_sandbox_exe| async move {
-        run_main(codex_linux_sandbox_exe, CliConfigOverrides::default()).await?;
+        let cli = Cli::parse();
+        match cli.listen {
+            Some(listen) => {
+                run_websocket_server(
+                    codex_linux_sandbox_exe,
+                    CliConfigOverrides::default(),
+                    &listen,
+                    cli.allow_origin,
+                )
+                .await?;
+            }
+            None => run_main(codex_linux_sandbox_exe, CliConfigOverrides::default()).await?,
+        }
         Ok(())
     })
 }
@@ -4,7 +4,9 @@ use std::sync::Arc;
 use crate::codex_message_processor::CodexMessageProcessor;
 use crate::config_api::ConfigApi;
 use crate::error_code::INVALID_REQUEST_ERROR_CODE;
+use crate::metrics::ServerMetrics;
 use crate::outgoing_message::OutgoingMessageSender;
+use codex_app_server_protocol::APP_SERVER_PROTOCOL_VERSION;
 use codex_app_server_protocol::ClientInfo;
 use codex_app_server_protocol::ClientRequest;
 use codex_app_server_protocol::ConfigBatchWriteParams;
@@ -13,6 +15,7 @@ use codex_app_server_protocol::ConfigValueWriteParams;
 use codex_app_server_protocol::InitializeResponse;
 use codex_app_server_protocol::JSONRPCError;
 use codex_app_server_protocol::JSONRPCErrorError;
+use codex_app_server_protocol::JSONRPCMessage;
 use codex_app_server_protocol::JSONRPCNotification;
 use codex_app_server_protocol::JSONRPCRequest;
 use codex_app_server_protocol::JSONRPCResponse;
@@ -23,7 +26,6 @@ use codex_core::config::Config;
 use codex_core::default_client::USER_AGENT_SUFFIX;
 use codex_core::default_client::get_codex_user_agent;
 use codex_feedback::CodexFeedback;
-use codex_protocol::protocol::SessionSource;
 use toml::Value as TomlValue;
 
 pub(crate) struct MessageProcessor {
@@ -35,24 +37,20 @@ pub(crate) struct MessageProcessor {
 
 impl MessageProcessor {
     /// Create a new `MessageProcessor`, retaining a handle to the outgoing
-    /// `Sender` so handlers can enqueue messages to be written to stdout.
+    /// `Sender` so handlers can enqueue messages for the client. Processors
+    /// created from the same `conversation_manager` see the same threads.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         outgoing: OutgoingMessageSender,
         codex_linux_sandbox_exe: Option<PathBuf>,
         config: Arc<Config>,
         cli_overrides: Vec<(String, TomlValue)>,
         feedback: CodexFeedback,
+        metrics: Arc<ServerMetrics>,
+        auth_manager: Arc<AuthManager>,
+        conversation_manager: Arc<ConversationManager>,
     ) -> Self {
         let outgoing = Arc::new(outgoing);
-        let auth_manager = AuthManager::shared(
-            config.codex_home.clone(),
-            false,
-            config.cli_auth_credentials_store_mode,
-        );
-        let conversation_manager = Arc::new(ConversationManager::new(
-            auth_manager.clone(),
-            SessionSource::VSCode,
-        ));
         let codex_message_processor = CodexMessageProcessor::new(
             auth_manager,
             conversation_manager,
@@ -61,6 +59,7 @@ impl MessageProcessor {
             Arc::clone(&config),
             cli_overrides.clone(),
             feedback,
+            metrics,
         );
         let config_api = ConfigApi::new(config.codex_home.clone(), cli_overrides);
 
@@ -72,6 +71,21 @@ impl MessageProcessor {
         }
     }
 
+    /// Detaches this client from the threads it was following; called when
+    /// its connection closes.
+    pub(crate) fn disconnect(&mut self) {
+        self.codex_message_processor.detach_conversation_listeners();
+    }
+
+    pub(crate) async fn process_message(&mut self, message: JSONRPCMessage) {
+        match message {
+            JSONRPCMessage::Request(r) => self.process_request(r).await,
+            JSONRPCMessage::Response(r) => self.process_response(r).await,
+            JSONRPCMessage::Notification(n) => self.process_notification(n).await,
+            JSONRPCMessage::Error(e) => self.process_error(e),
+        }
+    }
+
     pub(crate) async fn process_request(&mut self, request: JSONRPCRequest) {
         let request_id = request.id.clone();
         let request_json = match serde_json::to_value(&request) {
@@ -113,6 +127,21 @@ impl MessageProcessor {
                     self.outgoing.send_error(request_id, error).await;
                     return;
                 } else {
+                    if let Some(requested) = params
+                        .protocol_version
+                        .filter(|version| *version > APP_SERVER_PROTOCOL_VERSION)
+                    {
+                        let error = JSONRPCErrorError {
+                            code: INVALID_REQUEST_ERROR_CODE,
+                            message: format!(
+                                "Unsupported protocol version {requested}; this server speaks version {APP_SERVER_PROTOCOL_VERSION}"
+                            ),
+                            data: None,
+                        };
+                        self.outgoing.send_error(request_id, error).await;
+                        return;
+                    }
+
                     let ClientInfo {
                         name,
                         title: _title,
@@ -124,7 +153,10 @@ impl MessageProcessor {
                     }
 
                     let user_agent = get_codex_user_agent();
-                    let response = InitializeResponse { user_agent };
+                    let response = InitializeResponse {
+                        user_agent,
+                        protocol_version: APP_SERVER_PROTOCOL_VERSION,
+                    };
                     self.outgoing.send_response(request_id, response).await;
 
                     self.initialized = true;
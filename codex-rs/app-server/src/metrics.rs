@@ -0,0 +1,385 @@
+//! Prometheus metrics for long-running app-server processes.
+//!
+//! Counters are fed from the conversation event listeners and rendered in the
+//! Prometheus text exposition format. When `[app_server_metrics]` is enabled
+//! they are served over plain HTTP at `/metrics`.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use codex_core::config::types::AppServerMetricsConfig;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::ExecCommandSource;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::ConversationId;
+use tiny_http::Header;
+use tiny_http::Method;
+use tiny_http::Response;
+use tiny_http::Server;
+use tiny_http::StatusCode;
+use tracing::info;
+use tracing::warn;
+
+/// Upper bounds, in seconds, of the tool latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0,
+];
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+#[derive(Default)]
+pub(crate) struct ServerMetrics {
+    state: Mutex<MetricsState>,
+}
+
+#[derive(Default)]
+struct MetricsState {
+    /// Live sessions and the sandbox policy each was configured with.
+    sessions: HashMap<ConversationId, &'static str>,
+    sessions_started: BTreeMap<&'static str, u64>,
+    tool_calls: BTreeMap<(String, &'static str), u64>,
+    tool_latency: BTreeMap<String, Histogram>,
+    turns: BTreeMap<&'static str, u64>,
+    errors: BTreeMap<&'static str, u64>,
+}
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+impl ServerMetrics {
+    pub(crate) fn session_started(&self, conversation_id: ConversationId, policy: &SandboxPolicy) {
+        let policy = sandbox_policy_label(policy);
+        let mut state = self.lock();
+        state.sessions.insert(conversation_id, policy);
+        *state.sessions_started.entry(policy).or_default() += 1;
+    }
+
+    pub(crate) fn session_ended(&self, conversation_id: &ConversationId) {
+        self.lock().sessions.remove(conversation_id);
+    }
+
+    pub(crate) fn observe_event(&self, conversation_id: &ConversationId, msg: &EventMsg) {
+        match msg {
+            EventMsg::ExecCommandEnd(event) => {
+                let tool = match event.source {
+                    ExecCommandSource::UserShell => "user_shell",
+                    ExecCommandSource::Agent
+                    | ExecCommandSource::UnifiedExecStartup
+                    | ExecCommandSource::UnifiedExecInteraction => "shell",
+                };
+                self.tool_call(tool, event.exit_code == 0, Some(event.duration));
+            }
+            EventMsg::McpToolCallEnd(event) => {
+                let tool = format!("{}/{}", event.invocation.server, event.invocation.tool);
+                self.tool_call(&tool, event.is_success(), Some(event.duration));
+            }
+            EventMsg::PatchApplyEnd(event) => self.tool_call("apply_patch", event.success, None),
+            EventMsg::TaskComplete(_) => self.count_turn("completed"),
+            EventMsg::TurnAborted(_) => self.count_turn("aborted"),
+            EventMsg::Error(_) => self.count_error("error"),
+            EventMsg::StreamError(_) => self.count_error("stream_error"),
+            EventMsg::TurnTimedOut(_) => self.count_error("turn_timed_out"),
+            EventMsg::ToolCallLoopDetected(_) => self.count_error("tool_call_loop"),
+            EventMsg::ShutdownComplete => self.session_ended(conversation_id),
+            _ => {}
+        }
+    }
+
+    fn tool_call(&self, tool: &str, success: bool, duration: Option<Duration>) {
+        let status = if success { "success" } else { "failure" };
+        let mut state = self.lock();
+        *state
+            .tool_calls
+            .entry((tool.to_string(), status))
+            .or_default() += 1;
+        if let Some(duration) = duration {
+            state
+                .tool_latency
+                .entry(tool.to_string())
+                .or_default()
+                .observe(duration.as_secs_f64());
+        }
+    }
+
+    fn count_turn(&self, status: &'static str) {
+        *self.lock().turns.entry(status).or_default() += 1;
+    }
+
+    fn count_error(&self, kind: &'static str) {
+        *self.lock().errors.entry(kind).or_default() += 1;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MetricsState> {
+        // Counters stay usable even if a writer panicked mid-update.
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Renders every metric in the Prometheus text exposition format.
+    pub(crate) fn render(&self) -> String {
+        let state = self.lock();
+        let mut out = String::new();
+
+        header(
+            &mut out,
+            "codex_app_server_active_sessions",
+            "gauge",
+            "Sessions currently running in this server",
+        );
+        let _ = writeln!(
+            out,
+            "codex_app_server_active_sessions {}",
+            state.sessions.len()
+        );
+
+        let mut by_policy: BTreeMap<&'static str, u64> = BTreeMap::new();
+        for policy in state.sessions.values() {
+            *by_policy.entry(*policy).or_default() += 1;
+        }
+        header(
+            &mut out,
+            "codex_app_server_active_sessions_by_sandbox_policy",
+            "gauge",
+            "Running sessions by sandbox policy",
+        );
+        for (policy, count) in &by_policy {
+            let _ = writeln!(
+                out,
+                "codex_app_server_active_sessions_by_sandbox_policy{{sandbox_policy=\"{policy}\"}} {count}"
+            );
+        }
+
+        header(
+            &mut out,
+            "codex_app_server_sessions_started_total",
+            "counter",
+            "Sessions started, by sandbox policy",
+        );
+        for (policy, count) in &state.sessions_started {
+            let _ = writeln!(
+                out,
+                "codex_app_server_sessions_started_total{{sandbox_policy=\"{policy}\"}} {count}"
+            );
+        }
+
+        header(
+            &mut out,
+            "codex_app_server_tool_calls_total",
+            "counter",
+            "Tool calls by tool and outcome",
+        );
+        for ((tool, status), count) in &state.tool_calls {
+            let tool = escape_label(tool);
+            let _ = writeln!(
+                out,
+                "codex_app_server_tool_calls_total{{tool=\"{tool}\",status=\"{status}\"}} {count}"
+            );
+        }
+
+        header(
+            &mut out,
+            "codex_app_server_tool_duration_seconds",
+            "histogram",
+            "Execution time of tool calls",
+        );
+        for (tool, histogram) in &state.tool_latency {
+            let tool = escape_label(tool);
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+                let _ = writeln!(
+                    out,
+                    "codex_app_server_tool_duration_seconds_bucket{{tool=\"{tool}\",le=\"{bound}\"}} {count}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "codex_app_server_tool_duration_seconds_bucket{{tool=\"{tool}\",le=\"+Inf\"}} {}",
+                histogram.count
+            );
+            let _ = writeln!(
+                out,
+                "codex_app_server_tool_duration_seconds_sum{{tool=\"{tool}\"}} {}",
+                histogram.sum
+            );
+            let _ = writeln!(
+                out,
+                "codex_app_server_tool_duration_seconds_count{{tool=\"{tool}\"}} {}",
+                histogram.count
+            );
+        }
+
+        header(
+            &mut out,
+            "codex_app_server_turns_total",
+            "counter",
+            "Turns that finished, by status",
+        );
+        for (status, count) in &state.turns {
+            let _ = writeln!(
+                out,
+                "codex_app_server_turns_total{{status=\"{status}\"}} {count}"
+            );
+        }
+
+        header(
+            &mut out,
+            "codex_app_server_errors_total",
+            "counter",
+            "Error events reported to clients, by kind",
+        );
+        for (kind, count) in &state.errors {
+            let _ = writeln!(
+                out,
+                "codex_app_server_errors_total{{kind=\"{kind}\"}} {count}"
+            );
+        }
+
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn sandbox_policy_label(policy: &SandboxPolicy) -> &'static str {
+    match policy {
+        SandboxPolicy::DangerFullAccess => "danger-full-access",
+        SandboxPolicy::ReadOnly => "read-only",
+        SandboxPolicy::ExternalSandbox { .. } => "external-sandbox",
+        SandboxPolicy::WorkspaceWrite { .. } => "workspace-write",
+    }
+}
+
+/// Serves `GET /metrics` on a background thread when enabled in `config`.
+pub(crate) fn spawn_metrics_endpoint(
+    config: &AppServerMetricsConfig,
+    metrics: Arc<ServerMetrics>,
+) -> std::io::Result<()> {
+    if !config.enabled {
+        return Ok(());
+    }
+    let server = Server::http(config.bind_address.as_str()).map_err(|err| {
+        std::io::Error::other(format!(
+            "failed to bind metrics endpoint to {}: {err}",
+            config.bind_address
+        ))
+    })?;
+    info!(
+        "serving Prometheus metrics at http://{}/metrics",
+        server.server_addr()
+    );
+
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let response = if request.method() == &Method::Get && request.url() == "/metrics" {
+                let mut response = Response::from_string(metrics.render());
+                if let Ok(header) = Header::from_bytes("Content-Type", CONTENT_TYPE) {
+                    response.add_header(header);
+                }
+                response
+            } else {
+                Response::from_string("not found").with_status_code(StatusCode(404))
+            };
+            if let Err(err) = request.respond(response) {
+                warn!("failed to respond to metrics request: {err}");
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::protocol::PatchApplyEndEvent;
+    use codex_core::protocol::StreamErrorEvent;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn renders_sessions_tools_and_errors() {
+        let metrics = ServerMetrics::default();
+        let first = ConversationId::new();
+        let second = ConversationId::new();
+        metrics.session_started(first, &SandboxPolicy::ReadOnly);
+        metrics.session_started(second, &SandboxPolicy::DangerFullAccess);
+        metrics.session_ended(&second);
+        metrics.tool_call("shell", true, Some(Duration::from_millis(200)));
+        metrics.tool_call("shell", false, Some(Duration::from_secs(3)));
+        metrics.observe_event(
+            &first,
+            &EventMsg::PatchApplyEnd(PatchApplyEndEvent {
+                call_id: "call-1".to_string(),
+                turn_id: "turn-1".to_string(),
+                stdout: String::new(),
+                stderr: String::new(),
+                success: true,
+                changes: HashMap::new(),
+            }),
+        );
+        metrics.observe_event(
+            &first,
+            &EventMsg::StreamError(StreamErrorEvent {
+                message: "reconnecting".to_string(),
+                codex_error_info: None,
+            }),
+        );
+
+        let rendered = metrics.render();
+        let samples: Vec<&str> = rendered
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter(|line| {
+                !line.starts_with("codex_app_server_tool_duration_seconds_bucket")
+                    || line.contains("le=\"0.25\"")
+                    || line.contains("le=\"+Inf\"")
+            })
+            .collect();
+        assert_eq!(
+            samples,
+            vec![
+                "codex_app_server_active_sessions 1",
+                "codex_app_server_active_sessions_by_sandbox_policy{sandbox_policy=\"read-only\"} 1",
+                "codex_app_server_sessions_started_total{sandbox_policy=\"danger-full-access\"} 1",
+                "codex_app_server_sessions_started_total{sandbox_policy=\"read-only\"} 1",
+                "codex_app_server_tool_calls_total{tool=\"apply_patch\",status=\"success\"} 1",
+                "codex_app_server_tool_calls_total{tool=\"shell\",status=\"failure\"} 1",
+                "codex_app_server_tool_calls_total{tool=\"shell\",status=\"success\"} 1",
+                "codex_app_server_tool_duration_seconds_bucket{tool=\"shell\",le=\"0.25\"} 1",
+                "codex_app_server_tool_duration_seconds_bucket{tool=\"shell\",le=\"+Inf\"} 2",
+                "codex_app_server_tool_duration_seconds_sum{tool=\"shell\"} 3.2",
+                "codex_app_server_tool_duration_seconds_count{tool=\"shell\"} 2",
+                "codex_app_server_errors_total{kind=\"stream_error\"} 1",
+            ]
+        );
+    }
+}
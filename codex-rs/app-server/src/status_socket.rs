@@ -0,0 +1,177 @@
+//! Unix socket serving the `codex status --json` report.
+//!
+//! When `[app_server_status]` is enabled, every connection receives the
+//! current report as one line of JSON and is then closed, so editors and
+//! shell prompts can read agent state without starting a process.
+
+use std::path::PathBuf;
+
+use codex_core::config::Config;
+
+const DEFAULT_SOCKET_FILENAME: &str = "app-server-status.sock";
+
+/// Where the socket is created: `socket` from config, relative paths taken
+/// from the Codex home, or `$CODEX_HOME/app-server-status.sock`.
+fn socket_path(config: &Config) -> PathBuf {
+    match &config.app_server_status.socket {
+        Some(path) => config.codex_home.join(path),
+        None => config.codex_home.join(DEFAULT_SOCKET_FILENAME),
+    }
+}
+
+#[cfg(unix)]
+pub(crate) fn spawn_status_socket(config: &Config) -> std::io::Result<()> {
+    use codex_core::session_status::status_report;
+    use tracing::info;
+
+    if !config.app_server_status.enabled {
+        return Ok(());
+    }
+    let path = socket_path(config);
+    let listener = bind(&path)?;
+    info!("serving status at {}", path.display());
+
+    let config = config.clone();
+    std::thread::spawn(move || serve(listener, move || status_report(&config)));
+    Ok(())
+}
+
+/// Binds the socket at `path`, readable and writable by the owner only.
+///
+/// A socket left behind by a previous server would make bind fail, so one is
+/// removed first; any other file at `path` is an error rather than something
+/// to delete.
+#[cfg(unix)]
+fn bind(path: &std::path::Path) -> std::io::Result<std::os::unix::net::UnixListener> {
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixListener;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)?,
+        Ok(_) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!(
+                    "refusing to replace {} with the status socket: not a socket",
+                    path.display()
+                ),
+            ));
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+    let listener = UnixListener::bind(path).map_err(|err| {
+        std::io::Error::other(format!(
+            "failed to bind status socket {}: {err}",
+            path.display()
+        ))
+    })?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+/// Answers each connection on `listener` with `report()` as one JSON line.
+#[cfg(unix)]
+fn serve(
+    listener: std::os::unix::net::UnixListener,
+    report: impl Fn() -> codex_core::session_status::StatusReport,
+) {
+    use std::io::Write;
+
+    use tracing::warn;
+
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                warn!("failed to accept status connection: {err}");
+                continue;
+            }
+        };
+        let mut line = match serde_json::to_vec(&report()) {
+            Ok(line) => line,
+            Err(err) => {
+                warn!("failed to serialize status report: {err}");
+                continue;
+            }
+        };
+        line.push(b'\n');
+        if let Err(err) = stream.write_all(&line) {
+            warn!("failed to write status report: {err}");
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn spawn_status_socket(config: &Config) -> std::io::Result<()> {
+    if config.app_server_status.enabled {
+        tracing::warn!(
+            "app_server_status is only supported on Unix; not serving {}",
+            socket_path(config).display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use codex_core::session_status::StatusReport;
+    use pretty_assertions::assert_eq;
+    use std::io::Read;
+    use std::os::unix::net::UnixListener;
+    use std::os::unix::net::UnixStream;
+    use tempfile::TempDir;
+
+    #[test]
+    fn connections_receive_the_status_report() {
+        let dir = TempDir::new().expect("tempdir");
+        let path = dir.path().join(DEFAULT_SOCKET_FILENAME);
+        let listener = UnixListener::bind(&path).expect("bind status socket");
+        let report = StatusReport {
+            active_sessions: Vec::new(),
+            model: Some("gpt-5.1-codex".to_string()),
+            cost_today_usd: 1.25,
+            tokens_today: 4200,
+        };
+        let served = report.clone();
+        std::thread::spawn(move || serve(listener, move || served.clone()));
+
+        for _ in 0..2 {
+            let mut stream = UnixStream::connect(&path).expect("connect to status socket");
+            let mut reply = String::new();
+            stream.read_to_string(&mut reply).expect("read status");
+            assert!(reply.ends_with('\n'));
+            let received: StatusReport = serde_json::from_str(&reply).expect("status json");
+            assert_eq!(received, report);
+        }
+    }
+
+    #[test]
+    fn bind_replaces_a_stale_socket_and_restricts_access() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().expect("tempdir");
+        let path = dir.path().join(DEFAULT_SOCKET_FILENAME);
+        drop(UnixListener::bind(&path).expect("bind stale socket"));
+
+        let _listener = bind(&path).expect("bind over stale socket");
+        let mode = std::fs::metadata(&path)
+            .expect("socket metadata")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn bind_leaves_other_files_alone() {
+        let dir = TempDir::new().expect("tempdir");
+        let path = dir.path().join(DEFAULT_SOCKET_FILENAME);
+        std::fs::write(&path, "notes").expect("write file");
+
+        let err = bind(&path).expect_err("bind must not replace a regular file");
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read_to_string(&path).expect("read file"), "notes");
+    }
+}
@@ -0,0 +1,256 @@
+//! WebSocket transport for `codex app-server --listen`.
+//!
+//! Each connection speaks the same JSON-RPC protocol as stdio, one message per
+//! text frame, and performs its own `initialize` handshake. Connections share
+//! one conversation manager, so threads outlive the client that started them
+//! and can be resumed from another connection. Clients authenticate during
+//! the upgrade with `Authorization: Bearer <token>`, or with a `token` query
+//! parameter for browsers, which cannot set headers on WebSocket requests.
+//! Browsers also send `Origin`; upgrades that carry one are refused unless the
+//! origin was allowed with `--allow-origin`, so other sites a user visits
+//! cannot talk to the server.
+
+use std::borrow::Cow;
+use std::io::ErrorKind;
+use std::io::Result as IoResult;
+use std::sync::Arc;
+
+use codex_app_server_protocol::JSONRPCMessage;
+use futures::SinkExt;
+use futures::StreamExt;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::handshake::server::ErrorResponse;
+use tokio_tungstenite::tungstenite::handshake::server::Request;
+use tokio_tungstenite::tungstenite::handshake::server::Response;
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+use crate::CHANNEL_CAPACITY;
+use crate::ServerContext;
+use crate::outgoing_message::OutgoingMessage;
+use crate::outgoing_message::OutgoingMessageSender;
+
+/// Environment variable holding the token clients must present.
+pub(crate) const TOKEN_ENV_VAR: &str = "CODEX_APP_SERVER_TOKEN";
+
+pub(crate) fn token_from_env() -> IoResult<String> {
+    match std::env::var(TOKEN_ENV_VAR) {
+        Ok(token) if !token.trim().is_empty() => Ok(token),
+        _ => Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("set {TOKEN_ENV_VAR} to the token clients must present to --listen"),
+        )),
+    }
+}
+
+/// What an upgrade request must satisfy to be accepted.
+pub(crate) struct Access {
+    pub(crate) token: String,
+    /// Origins (`scheme://host[:port]`) whose pages may connect.
+    pub(crate) allowed_origins: Vec<String>,
+}
+
+pub(crate) async fn serve(
+    listen: &str,
+    access: Access,
+    context: Arc<ServerContext>,
+) -> IoResult<()> {
+    let address = listen.strip_prefix("ws://").unwrap_or(listen);
+    let listener = TcpListener::bind(address).await.map_err(|err| {
+        std::io::Error::new(err.kind(), format!("failed to listen on {address}: {err}"))
+    })?;
+    info!(
+        "serving the app-server protocol at ws://{}",
+        listener.local_addr()?
+    );
+
+    let access = Arc::new(access);
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let access = Arc::clone(&access);
+        let context = Arc::clone(&context);
+        tokio::spawn(async move {
+            match handle_connection(stream, &access, &context).await {
+                Ok(()) => info!("websocket client {peer} disconnected"),
+                Err(err) => warn!("websocket client {peer} failed: {err}"),
+            }
+        });
+    }
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    access: &Access,
+    context: &ServerContext,
+) -> Result<(), tungstenite::Error> {
+    let authorize = |request: &Request, response: Response| {
+        let (status, reason) = if !is_authorized(request, &access.token) {
+            (StatusCode::UNAUTHORIZED, "unauthorized")
+        } else if !is_allowed_origin(request, &access.allowed_origins) {
+            (StatusCode::FORBIDDEN, "origin not allowed")
+        } else {
+            return Ok(response);
+        };
+        let mut response = ErrorResponse::new(Some(reason.to_string()));
+        *response.status_mut() = status;
+        Err(response)
+    };
+    let socket = tokio_tungstenite::accept_hdr_async(stream, authorize).await?;
+    let (mut sink, mut source) = socket.split();
+
+    let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<OutgoingMessage>(CHANNEL_CAPACITY);
+    let mut processor = context.message_processor(OutgoingMessageSender::new(outgoing_tx));
+
+    let writer = tokio::spawn(async move {
+        while let Some(outgoing_message) = outgoing_rx.recv().await {
+            let json = match serde_json::to_string(&outgoing_message) {
+                Ok(json) => json,
+                Err(e) => {
+                    error!("Failed to serialize OutgoingMessage: {e}");
+                    continue;
+                }
+            };
+            if sink.send(Message::Text(json.into())).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut result = Ok(());
+    while let Some(frame) = source.next().await {
+        match frame {
+            Ok(Message::Text(text)) => match serde_json::from_str::<JSONRPCMessage>(&text) {
+                Ok(msg) => processor.process_message(msg).await,
+                Err(e) => error!("Failed to deserialize JSONRPCMessage: {e}"),
+            },
+            Ok(Message::Close(_)) => break,
+            // Pings are answered by tungstenite itself.
+            Ok(_) => {}
+            Err(err) => {
+                result = Err(err);
+                break;
+            }
+        }
+    }
+
+    // Conversation listeners hold their own handles to the outgoing channel,
+    // so stop them explicitly rather than waiting for the next event to fail
+    // to send. The threads keep running for whichever client resumes them
+    // next.
+    processor.disconnect();
+    writer.abort();
+    result
+}
+
+/// Whether the upgrade request carries `token`, as a bearer token or as the
+/// percent-encoded `token` query parameter.
+fn is_authorized(request: &Request, token: &str) -> bool {
+    let bearer = request
+        .headers()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(Cow::Borrowed);
+    let query = request
+        .uri()
+        .query()
+        .and_then(|query| {
+            query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("token="))
+        })
+        .and_then(|value| urlencoding::decode(value).ok());
+    [bearer, query]
+        .into_iter()
+        .flatten()
+        .any(|presented| constant_time_eq(presented.as_bytes(), token.as_bytes()))
+}
+
+/// Requests without `Origin` come from programs rather than web pages and
+/// are accepted; a page may only connect from an allowed origin.
+fn is_allowed_origin(request: &Request, allowed_origins: &[String]) -> bool {
+    let Some(origin) = request.headers().get("origin") else {
+        return true;
+    };
+    origin.to_str().is_ok_and(|origin| {
+        allowed_origins
+            .iter()
+            .any(|allowed| allowed.trim_end_matches('/').eq_ignore_ascii_case(origin))
+    })
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(uri: &str, authorization: Option<&str>) -> Request {
+        let mut builder = Request::builder().uri(uri);
+        if let Some(authorization) = authorization {
+            builder = builder.header("Authorization", authorization);
+        }
+        builder.body(()).expect("request")
+    }
+
+    fn request_from(origin: &str) -> Request {
+        Request::builder()
+            .uri("/")
+            .header("Origin", origin)
+            .body(())
+            .expect("request")
+    }
+
+    #[test]
+    fn accepts_bearer_header_or_query_token() {
+        assert!(is_authorized(
+            &request("/", Some("Bearer secret")),
+            "secret"
+        ));
+        assert!(is_authorized(
+            &request("/?client=web&token=secret", None),
+            "secret"
+        ));
+        assert!(!is_authorized(
+            &request("/", Some("Bearer other")),
+            "secret"
+        ));
+        assert!(!is_authorized(&request("/?token=secre", None), "secret"));
+        assert!(!is_authorized(&request("/", None), "secret"));
+    }
+
+    #[test]
+    fn query_token_is_percent_decoded() {
+        assert!(is_authorized(&request("/?token=a%2Bb%3Dc", None), "a+b=c"));
+        assert!(!is_authorized(
+            &request("/?token=a%2Bb%3Dc", None),
+            "a%2Bb%3Dc"
+        ));
+    }
+
+    #[test]
+    fn pages_must_come_from_an_allowed_origin() {
+        let allowed = vec!["https://editor.example.com/".to_string()];
+        assert!(is_allowed_origin(&request("/", None), &[]));
+        assert!(is_allowed_origin(
+            &request_from("https://editor.example.com"),
+            &allowed
+        ));
+        assert!(!is_allowed_origin(
+            &request_from("https://evil.example.com"),
+            &allowed
+        ));
+        assert!(!is_allowed_origin(
+            &request_from("https://editor.example.com"),
+            &[]
+        ));
+    }
+}
@@ -138,14 +138,7 @@ impl McpProcess {
 
     /// Performs the initialization handshake with the MCP server.
     pub async fn initialize(&mut self) -> anyhow::Result<()> {
-        let params = Some(serde_json::to_value(InitializeParams {
-            client_info: ClientInfo {
-                name: "codex-app-server-tests".to_string(),
-                title: None,
-                version: "0.1.0".to_string(),
-            },
-        })?);
-        let req_id = self.send_request("initialize", params).await?;
+        let req_id = self.send_initialize_request(None).await?;
         let initialized = self.read_jsonrpc_message().await?;
         let JSONRPCMessage::Response(response) = initialized else {
             unreachable!("expected JSONRPCMessage::Response for initialize, got {initialized:?}");
@@ -165,6 +158,22 @@ impl McpProcess {
         Ok(())
     }
 
+    /// Send an `initialize` JSON-RPC request without completing the handshake.
+    pub async fn send_initialize_request(
+        &mut self,
+        protocol_version: Option<u32>,
+    ) -> anyhow::Result<i64> {
+        let params = Some(serde_json::to_value(InitializeParams {
+            client_info: ClientInfo {
+                name: "codex-app-server-tests".to_string(),
+                title: None,
+                version: "0.1.0".to_string(),
+            },
+            protocol_version,
+        })?);
+        self.send_request("initialize", params).await
+    }
+
     /// Send a `newConversation` JSON-RPC request.
     pub async fn send_new_conversation_request(
         &mut self,
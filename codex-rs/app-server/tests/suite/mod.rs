@@ -4,6 +4,7 @@ mod codex_message_processor_flow;
 mod config;
 mod create_conversation;
 mod fuzzy_file_search;
+mod initialize;
 mod interrupt;
 mod list_resume;
 mod login;
@@ -12,3 +13,4 @@ mod set_default_model;
 mod user_agent;
 mod user_info;
 mod v2;
+mod websocket;
@@ -0,0 +1,181 @@
+use std::net::TcpListener;
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::Context;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use codex_app_server_protocol::ClientInfo;
+use codex_app_server_protocol::InitializeParams;
+use codex_app_server_protocol::JSONRPCMessage;
+use codex_app_server_protocol::JSONRPCRequest;
+use codex_app_server_protocol::RequestId;
+use futures::SinkExt;
+use futures::StreamExt;
+use pretty_assertions::assert_eq;
+use std::process::Command as StdCommand;
+use tempfile::TempDir;
+use tokio::net::TcpStream;
+use tokio::process::Child;
+use tokio::process::Command;
+use tokio::time::timeout;
+use tokio_tungstenite::MaybeTlsStream;
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::StatusCode;
+
+const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(10);
+const TOKEN: &str = "s3cret+token/=";
+const ALLOWED_ORIGIN: &str = "https://editor.example.com";
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// A `codex-app-server --listen` child on a free local port.
+struct Server {
+    #[allow(dead_code)]
+    process: Child,
+    port: u16,
+}
+
+impl Server {
+    async fn start(codex_home: &TempDir) -> Result<Self> {
+        let port = TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+        let program = StdCommand::cargo_bin("codex-app-server")
+            .context("should find binary for codex-app-server")?
+            .get_program()
+            .to_owned();
+        let process = Command::new(program)
+            .arg("--listen")
+            .arg(format!("ws://127.0.0.1:{port}"))
+            .arg("--allow-origin")
+            .arg(ALLOWED_ORIGIN)
+            .env("CODEX_HOME", codex_home.path())
+            .env("CODEX_APP_SERVER_TOKEN", TOKEN)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .context("codex-app-server should start")?;
+        let server = Self { process, port };
+
+        // Wait for the listener: until then, connections are refused outright.
+        timeout(DEFAULT_READ_TIMEOUT, async {
+            while TcpStream::connect(("127.0.0.1", server.port))
+                .await
+                .is_err()
+            {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await?;
+        Ok(server)
+    }
+
+    async fn connect(
+        &self,
+        query: &str,
+        headers: &[(&'static str, &str)],
+    ) -> Result<Socket, tungstenite::Error> {
+        let mut request = format!("ws://127.0.0.1:{}/{query}", self.port).into_client_request()?;
+        for (name, value) in headers {
+            request.headers_mut().insert(
+                *name,
+                value.parse().expect("header values in tests are valid"),
+            );
+        }
+        let (socket, _) = connect_async(request).await?;
+        Ok(socket)
+    }
+}
+
+fn rejected_with(result: Result<Socket, tungstenite::Error>) -> Option<StatusCode> {
+    match result {
+        Err(tungstenite::Error::Http(response)) => Some(response.status()),
+        _ => None,
+    }
+}
+
+async fn initialize(socket: &mut Socket) -> Result<()> {
+    let request = JSONRPCMessage::Request(JSONRPCRequest {
+        id: RequestId::Integer(1),
+        method: "initialize".to_string(),
+        params: Some(serde_json::to_value(InitializeParams {
+            client_info: ClientInfo {
+                name: "codex-app-server-tests".to_string(),
+                title: None,
+                version: "0.1.0".to_string(),
+            },
+            protocol_version: None,
+        })?),
+    });
+    socket
+        .send(Message::Text(serde_json::to_string(&request)?.into()))
+        .await?;
+
+    loop {
+        let frame = timeout(DEFAULT_READ_TIMEOUT, socket.next())
+            .await?
+            .context("server closed the connection")??;
+        let Message::Text(text) = frame else {
+            continue;
+        };
+        if let JSONRPCMessage::Response(response) = serde_json::from_str(&text)? {
+            assert_eq!(response.id, RequestId::Integer(1));
+            return Ok(());
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn websocket_clients_authenticate_during_the_upgrade() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let server = Server::start(&codex_home).await?;
+
+    assert_eq!(
+        rejected_with(server.connect("", &[]).await),
+        Some(StatusCode::UNAUTHORIZED)
+    );
+    assert_eq!(
+        rejected_with(server.connect("?token=wrong", &[]).await),
+        Some(StatusCode::UNAUTHORIZED)
+    );
+
+    let bearer = format!("Bearer {TOKEN}");
+    let mut socket = server
+        .connect("", &[("authorization", bearer.as_str())])
+        .await?;
+    initialize(&mut socket).await?;
+    socket.close(None).await?;
+
+    // Browsers percent-encode the token, and the server outlives each client.
+    let query = format!("?token={}", urlencoding::encode(TOKEN));
+    let mut socket = server.connect(&query, &[]).await?;
+    initialize(&mut socket).await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn websocket_pages_must_come_from_an_allowed_origin() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let server = Server::start(&codex_home).await?;
+    let query = format!("?token={}", urlencoding::encode(TOKEN));
+
+    assert_eq!(
+        rejected_with(
+            server
+                .connect(&query, &[("origin", "https://evil.example.com")])
+                .await
+        ),
+        Some(StatusCode::FORBIDDEN)
+    );
+
+    let mut socket = server
+        .connect(&query, &[("origin", ALLOWED_ORIGIN)])
+        .await?;
+    initialize(&mut socket).await?;
+    Ok(())
+}
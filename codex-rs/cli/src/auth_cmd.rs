@@ -0,0 +1,258 @@
+use std::io::IsTerminal;
+use std::io::Read;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use codex_app_server_protocol::AuthMode;
+use codex_cli::login::safe_format_key;
+use codex_common::CliConfigOverrides;
+use codex_core::CodexAuth;
+use codex_core::auth::accounts;
+use codex_core::config::Config;
+use codex_core::credential_store::CredentialStore;
+use serde::Serialize;
+
+/// Subcommands:
+/// - `list`     — show stored secrets and where they are kept
+/// - `set`      — store a secret read from stdin
+/// - `remove`   — delete a stored secret
+/// - `accounts` — show named logins and which one is active
+/// - `use`      — choose the login used when the profile names none
+///
+/// A provider's API key is stored under the name of its `env_key` variable
+/// and used whenever that variable is unset. Named logins are created with
+/// `codex login --account <name>`.
+#[derive(Debug, clap::Parser)]
+pub struct AuthCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub subcommand: AuthSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum AuthSubcommand {
+    List(ListArgs),
+    Set(SetArgs),
+    Remove(RemoveArgs),
+    Accounts(AccountsArgs),
+    Use(UseArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ListArgs {
+    /// Output the entries as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct SetArgs {
+    /// Name to store the secret under, e.g. `OPENROUTER_API_KEY`.
+    #[arg(value_name = "NAME")]
+    pub name: String,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct RemoveArgs {
+    /// Name of the secret to remove.
+    #[arg(value_name = "NAME")]
+    pub name: String,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct AccountsArgs {
+    /// Output the accounts as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct UseArgs {
+    /// Account to use, or `default` for the original login.
+    #[arg(value_name = "ACCOUNT")]
+    pub account: String,
+}
+
+#[derive(Debug, Serialize)]
+struct AccountSummary {
+    name: String,
+    active: bool,
+    identity: Option<String>,
+}
+
+impl AuthCli {
+    pub async fn run(self) -> Result<()> {
+        let overrides = self
+            .config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?;
+        let config = Config::load_with_cli_overrides(overrides)
+            .await
+            .context("failed to load configuration")?;
+        let store = CredentialStore::new(
+            config.codex_home.clone(),
+            config.cli_auth_credentials_store_mode,
+        );
+
+        match self.subcommand {
+            AuthSubcommand::List(args) => run_list(&config, &store, args),
+            AuthSubcommand::Set(args) => run_set(&config, &store, args),
+            AuthSubcommand::Remove(args) => run_remove(&store, args),
+            AuthSubcommand::Accounts(args) => run_accounts(&config, args).await,
+            AuthSubcommand::Use(args) => run_use(&config, args),
+        }
+    }
+}
+
+fn run_list(config: &Config, store: &CredentialStore, args: ListArgs) -> Result<()> {
+    let entries = store.list().context("failed to read stored credentials")?;
+
+    if args.json {
+        let output = serde_json::to_string_pretty(&entries)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No credentials stored. Add one with `codex auth set <NAME>`.");
+        return Ok(());
+    }
+
+    for entry in &entries {
+        let mut line = format!("{}  {}", entry.name, entry.location);
+        let providers = providers_using(config, &entry.name);
+        if !providers.is_empty() {
+            line.push_str(&format!("  (used by {})", providers.join(", ")));
+        }
+        println!("{line}");
+    }
+    Ok(())
+}
+
+fn run_set(config: &Config, store: &CredentialStore, args: SetArgs) -> Result<()> {
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        bail!(
+            "`codex auth set` expects the secret on stdin, e.g. `printenv {name} | codex auth set {name}`",
+            name = args.name
+        );
+    }
+    let mut buffer = String::new();
+    stdin
+        .read_to_string(&mut buffer)
+        .context("failed to read the secret from stdin")?;
+    let secret = buffer.trim();
+    if secret.is_empty() {
+        bail!("no secret provided on stdin");
+    }
+
+    let location = store
+        .set(&args.name, secret)
+        .with_context(|| format!("failed to store {}", args.name))?;
+    println!("Stored {} in the {location}.", args.name);
+    if providers_using(config, &args.name).is_empty() {
+        println!(
+            "No model provider has env_key = \"{}\"; set it on a provider to use this key.",
+            args.name
+        );
+    }
+    Ok(())
+}
+
+fn run_remove(store: &CredentialStore, args: RemoveArgs) -> Result<()> {
+    if store
+        .remove(&args.name)
+        .with_context(|| format!("failed to remove {}", args.name))?
+    {
+        println!("Removed {}.", args.name);
+    } else {
+        println!("No credential named {} is stored.", args.name);
+    }
+    Ok(())
+}
+
+async fn run_accounts(config: &Config, args: AccountsArgs) -> Result<()> {
+    let active = accounts::resolve_account(&config.codex_home, config.account.as_deref());
+    let mut summaries = Vec::new();
+    for name in accounts::list_accounts(&config.codex_home).context("failed to list accounts")? {
+        let auth = CodexAuth::from_auth_storage(
+            &accounts::account_home(&config.codex_home, &name),
+            config.cli_auth_credentials_store_mode,
+        )
+        .with_context(|| format!("failed to read credentials for account {name}"))?;
+        let identity = match auth {
+            Some(auth) => Some(describe_identity(&auth).await),
+            None => None,
+        };
+        summaries.push(AccountSummary {
+            active: name == active,
+            name,
+            identity,
+        });
+    }
+
+    if args.json {
+        let output = serde_json::to_string_pretty(&summaries)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    for summary in &summaries {
+        let marker = if summary.active { "*" } else { " " };
+        let identity = summary.identity.as_deref().unwrap_or("not logged in");
+        println!("{marker} {}  {identity}", summary.name);
+    }
+    if config.account.is_some() {
+        println!("Config selects the active account; `codex auth use` applies when it doesn't.");
+    }
+    Ok(())
+}
+
+fn run_use(config: &Config, args: UseArgs) -> Result<()> {
+    accounts::validate_account_name(&args.account).map_err(anyhow::Error::msg)?;
+    let known = accounts::list_accounts(&config.codex_home).context("failed to list accounts")?;
+    if !known.contains(&args.account) {
+        bail!(
+            "no account named {name}; create it with `codex login --account {name}`",
+            name = args.account
+        );
+    }
+    accounts::select_account(&config.codex_home, &args.account)
+        .context("failed to save the selected account")?;
+    println!("Using account {}.", args.account);
+    if let Some(configured) = &config.account
+        && configured != &args.account
+    {
+        println!("Note: config selects account {configured}, which takes precedence here.");
+    }
+    Ok(())
+}
+
+/// `ChatGPT (me@example.com)` or `API key sk-proj-***ABCDE`.
+async fn describe_identity(auth: &CodexAuth) -> String {
+    match auth.mode {
+        AuthMode::ChatGPT => match auth.get_account_email() {
+            Some(email) => format!("ChatGPT ({email})"),
+            None => "ChatGPT".to_string(),
+        },
+        AuthMode::ApiKey => match auth.get_token().await {
+            Ok(key) => format!("API key {}", safe_format_key(&key)),
+            Err(_) => "API key".to_string(),
+        },
+    }
+}
+
+/// Ids of the providers whose `env_key` is `name`, sorted.
+fn providers_using(config: &Config, name: &str) -> Vec<String> {
+    let mut providers: Vec<String> = config
+        .model_providers
+        .iter()
+        .filter(|(_, provider)| provider.env_key.as_deref() == Some(name))
+        .map(|(id, _)| id.clone())
+        .collect();
+    providers.sort();
+    providers
+}
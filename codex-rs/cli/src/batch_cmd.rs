@@ -0,0 +1,472 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Instant;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use codex_common::CliConfigOverrides;
+use codex_exec::exec_events::ThreadEvent;
+use codex_exec::exec_events::ThreadItemDetails;
+use codex_exec::exec_events::Usage;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::task::JoinSet;
+
+/// Run a queue of headless tasks, each in its own git worktree.
+#[derive(Debug, clap::Parser)]
+pub struct BatchCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// JSON Lines file with one task spec per line.
+    #[arg(value_name = "TASKS_FILE")]
+    pub tasks_file: PathBuf,
+
+    /// Directory that receives one `<id>.json` result (and `<id>.log`) per task.
+    #[arg(
+        long = "out",
+        short = 'o',
+        value_name = "DIR",
+        default_value = "codex-batch"
+    )]
+    pub out_dir: PathBuf,
+
+    /// Number of tasks to run at the same time.
+    #[arg(long, short = 'j', default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+    pub jobs: u16,
+
+    /// Keep each task's worktree under `<out>/workspaces` instead of removing it.
+    #[arg(long)]
+    pub keep_workspaces: bool,
+}
+
+/// One line of the tasks file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TaskSpec {
+    /// Names the result files; defaults to `task-<line>`.
+    id: Option<String>,
+    prompt: String,
+    /// Git repository to work in, relative to the tasks file. Defaults to the
+    /// directory containing it.
+    repo: Option<PathBuf>,
+    /// Config profile to run the task with.
+    profile: Option<String>,
+    #[serde(default)]
+    budget: TaskBudget,
+}
+
+/// Per-task `[budget]` overrides.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TaskBudget {
+    max_tokens: Option<i64>,
+    max_cost_usd: Option<f64>,
+    max_turns: Option<u32>,
+    max_task_duration_secs: Option<u64>,
+}
+
+impl TaskBudget {
+    fn config_overrides(&self) -> Vec<String> {
+        let mut overrides = Vec::new();
+        if let Some(max_tokens) = self.max_tokens {
+            overrides.push(format!("budget.max_tokens={max_tokens}"));
+        }
+        if let Some(max_cost_usd) = self.max_cost_usd {
+            overrides.push(format!("budget.max_cost_usd={max_cost_usd:?}"));
+        }
+        if let Some(max_turns) = self.max_turns {
+            overrides.push(format!("budget.max_turns={max_turns}"));
+        }
+        if let Some(secs) = self.max_task_duration_secs {
+            overrides.push(format!("budget.max_task_duration_secs={secs}"));
+        }
+        overrides
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Task {
+    id: String,
+    spec: TaskSpec,
+    repo: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum TaskStatus {
+    Succeeded,
+    Failed,
+}
+
+/// Written to `<out>/<id>.json` when a task finishes.
+#[derive(Debug, Serialize)]
+struct TaskResult {
+    id: String,
+    status: TaskStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thread_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exit_code: Option<i32>,
+    duration_secs: f64,
+    /// Token usage summed over every turn of the task.
+    usage: Usage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    final_message: Option<String>,
+    /// Everything the task changed, as a diff against the commit it started from.
+    diff: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    workspace: Option<PathBuf>,
+}
+
+impl BatchCli {
+    pub async fn run(self) -> Result<()> {
+        let contents = std::fs::read_to_string(&self.tasks_file)
+            .with_context(|| format!("failed to read {}", self.tasks_file.display()))?;
+        let base_dir = self
+            .tasks_file
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let tasks = parse_tasks(&contents, base_dir)
+            .with_context(|| format!("invalid tasks file {}", self.tasks_file.display()))?;
+        if tasks.is_empty() {
+            bail!("{} contains no tasks", self.tasks_file.display());
+        }
+
+        std::fs::create_dir_all(&self.out_dir)
+            .with_context(|| format!("failed to create {}", self.out_dir.display()))?;
+        let out_dir = self.out_dir.canonicalize()?;
+        let codex = std::env::current_exe().context("failed to locate the codex executable")?;
+        let runner = TaskRunner {
+            codex,
+            out_dir,
+            config_overrides: self.config_overrides.raw_overrides,
+            keep_workspaces: self.keep_workspaces,
+        };
+
+        let total = tasks.len();
+        let mut failed = 0;
+        let mut pending = tasks.into_iter();
+        let mut running = JoinSet::new();
+        loop {
+            while running.len() < usize::from(self.jobs)
+                && let Some(task) = pending.next()
+            {
+                let runner = runner.clone();
+                println!("started  {}", task.id);
+                running.spawn(async move { runner.run(task).await });
+            }
+            let Some(finished) = running.join_next().await else {
+                break;
+            };
+            let (result, result_path) = finished.context("batch task panicked")??;
+            if result.status == TaskStatus::Failed {
+                failed += 1;
+            }
+            println!(
+                "{:<8} {} ({:.0}s, {} tokens) -> {}",
+                match result.status {
+                    TaskStatus::Succeeded => "done",
+                    TaskStatus::Failed => "failed",
+                },
+                result.id,
+                result.duration_secs,
+                result.usage.input_tokens + result.usage.output_tokens,
+                result_path.display()
+            );
+        }
+
+        if failed > 0 {
+            bail!("{failed} of {total} tasks failed");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TaskRunner {
+    codex: PathBuf,
+    out_dir: PathBuf,
+    config_overrides: Vec<String>,
+    keep_workspaces: bool,
+}
+
+impl TaskRunner {
+    /// Runs `task` and writes its result file. Task failures are recorded in
+    /// the result; only failing to write it is an error.
+    async fn run(&self, task: Task) -> Result<(TaskResult, PathBuf)> {
+        let started = Instant::now();
+        let workspace = self.out_dir.join("workspaces").join(&task.id);
+        let mut result = match self.run_in_workspace(&task, &workspace).await {
+            Ok(result) => result,
+            Err(err) => TaskResult {
+                id: task.id.clone(),
+                status: TaskStatus::Failed,
+                error: Some(format!("{err:#}")),
+                thread_id: None,
+                exit_code: None,
+                duration_secs: 0.0,
+                usage: Usage::default(),
+                final_message: None,
+                diff: String::new(),
+                workspace: None,
+            },
+        };
+        result.duration_secs = started.elapsed().as_secs_f64();
+        if self.keep_workspaces && workspace.exists() {
+            result.workspace = Some(workspace);
+        } else {
+            remove_worktree(&task.repo, &workspace).await;
+        }
+
+        let result_path = self.out_dir.join(format!("{}.json", task.id));
+        let json = serde_json::to_string_pretty(&result)?;
+        std::fs::write(&result_path, json)
+            .with_context(|| format!("failed to write {}", result_path.display()))?;
+        Ok((result, result_path))
+    }
+
+    async fn run_in_workspace(&self, task: &Task, workspace: &Path) -> Result<TaskResult> {
+        // Start from a clean worktree at the repository's HEAD; uncommitted
+        // changes in the repository are not carried over.
+        remove_worktree(&task.repo, workspace).await;
+        git(
+            &task.repo,
+            &[
+                "worktree",
+                "add",
+                "--detach",
+                &workspace.to_string_lossy(),
+                "HEAD",
+            ],
+        )
+        .await
+        .context("failed to create the task worktree")?;
+
+        let log_path = self.out_dir.join(format!("{}.log", task.id));
+        let log = std::fs::File::create(&log_path)
+            .with_context(|| format!("failed to create {}", log_path.display()))?;
+        let mut command = tokio::process::Command::new(&self.codex);
+        command.arg("exec").arg("--json").arg("--full-auto");
+        command.arg("--cd").arg(workspace);
+        if let Some(profile) = &task.spec.profile {
+            command.arg("--profile").arg(profile);
+        }
+        for config_override in self
+            .config_overrides
+            .iter()
+            .cloned()
+            .chain(task.spec.budget.config_overrides())
+        {
+            command.arg("-c").arg(config_override);
+        }
+        command
+            .arg("--")
+            .arg(&task.spec.prompt)
+            .stdin(Stdio::null())
+            .stderr(Stdio::from(log));
+        let output = command.output().await.context("failed to run codex exec")?;
+
+        let mut result = summarize_events(&task.id, &String::from_utf8_lossy(&output.stdout));
+        result.exit_code = output.status.code();
+        if !output.status.success() && result.error.is_none() {
+            result.error = Some(format!(
+                "codex exec exited with {}; see {}",
+                output.status,
+                log_path.display()
+            ));
+        }
+        if result.error.is_some() {
+            result.status = TaskStatus::Failed;
+        }
+
+        // Stage everything so new files show up in the diff too.
+        git(workspace, &["add", "--all"]).await?;
+        result.diff = git(workspace, &["diff", "--cached", "--binary", "HEAD"]).await?;
+        Ok(result)
+    }
+}
+
+/// Reads the `codex exec --json` event stream of one task.
+fn summarize_events(id: &str, jsonl: &str) -> TaskResult {
+    let mut result = TaskResult {
+        id: id.to_string(),
+        status: TaskStatus::Succeeded,
+        error: None,
+        thread_id: None,
+        exit_code: None,
+        duration_secs: 0.0,
+        usage: Usage::default(),
+        final_message: None,
+        diff: String::new(),
+        workspace: None,
+    };
+    for line in jsonl.lines() {
+        let Ok(event) = serde_json::from_str::<ThreadEvent>(line) else {
+            continue;
+        };
+        match event {
+            ThreadEvent::ThreadStarted(event) => result.thread_id = Some(event.thread_id),
+            ThreadEvent::TurnCompleted(event) => {
+                result.usage.input_tokens += event.usage.input_tokens;
+                result.usage.cached_input_tokens += event.usage.cached_input_tokens;
+                result.usage.output_tokens += event.usage.output_tokens;
+            }
+            ThreadEvent::TurnFailed(event) => result.error = Some(event.error.message),
+            ThreadEvent::Error(event) => result.error = Some(event.message),
+            ThreadEvent::ItemCompleted(event) => {
+                if let ThreadItemDetails::AgentMessage(message) = event.item.details {
+                    result.final_message = Some(message.text);
+                }
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+fn parse_tasks(contents: &str, base_dir: &Path) -> Result<Vec<Task>> {
+    let mut tasks = Vec::new();
+    let mut ids = HashSet::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let spec: TaskSpec = serde_json::from_str(line)
+            .with_context(|| format!("line {line_number}: invalid task spec"))?;
+        let id = spec
+            .id
+            .clone()
+            .unwrap_or_else(|| format!("task-{line_number}"));
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            || id.starts_with('.')
+        {
+            bail!(
+                "line {line_number}: task id `{id}` may only contain letters, digits, `-`, `_` and `.`"
+            );
+        }
+        if !ids.insert(id.clone()) {
+            bail!("line {line_number}: duplicate task id `{id}`");
+        }
+        if spec.prompt.trim().is_empty() {
+            bail!("line {line_number}: task `{id}` has an empty prompt");
+        }
+        let repo = match &spec.repo {
+            Some(repo) => base_dir.join(repo),
+            None => base_dir.to_path_buf(),
+        };
+        tasks.push(Task { id, spec, repo });
+    }
+    Ok(tasks)
+}
+
+async fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = tokio::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .await
+        .context("failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed in {}: {}",
+            args.join(" "),
+            dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Best-effort removal of a worktree left by this or an earlier run.
+async fn remove_worktree(repo: &Path, workspace: &Path) {
+    if !workspace.exists() {
+        return;
+    }
+    let _ = git(
+        repo,
+        &[
+            "worktree",
+            "remove",
+            "--force",
+            &workspace.to_string_lossy(),
+        ],
+    )
+    .await;
+    let _ = std::fs::remove_dir_all(workspace);
+    let _ = git(repo, &["worktree", "prune"]).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_specs_with_defaults() {
+        let contents = r#"
+# nightly maintenance
+{"id": "deps", "prompt": "Update dependencies", "repo": "app", "profile": "nightly", "budget": {"max_tokens": 200000, "max_cost_usd": 2.0}}
+{"prompt": "Fix lint warnings"}
+"#;
+        let tasks = parse_tasks(contents, Path::new("/jobs")).expect("valid tasks");
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].id, "deps");
+        assert_eq!(tasks[0].repo, PathBuf::from("/jobs/app"));
+        assert_eq!(
+            tasks[0].spec.budget.config_overrides(),
+            vec![
+                "budget.max_tokens=200000".to_string(),
+                "budget.max_cost_usd=2.0".to_string(),
+            ]
+        );
+        assert_eq!(tasks[1].id, "task-4");
+        assert_eq!(tasks[1].repo, PathBuf::from("/jobs"));
+    }
+
+    #[test]
+    fn rejects_duplicate_and_unsafe_ids() {
+        let duplicate = "{\"id\": \"a\", \"prompt\": \"x\"}\n{\"id\": \"a\", \"prompt\": \"y\"}";
+        let err = parse_tasks(duplicate, Path::new(".")).expect_err("duplicate id");
+        assert_eq!(err.to_string(), "line 2: duplicate task id `a`");
+
+        let unsafe_id = "{\"id\": \"../a\", \"prompt\": \"x\"}";
+        assert!(parse_tasks(unsafe_id, Path::new(".")).is_err());
+    }
+
+    #[test]
+    fn summarizes_exec_events() {
+        let jsonl = [
+            r#"{"type":"thread.started","thread_id":"t-1"}"#,
+            r#"{"type":"turn.completed","usage":{"input_tokens":100,"cached_input_tokens":20,"output_tokens":10}}"#,
+            r#"{"type":"item.completed","item":{"id":"item_0","type":"agent_message","text":"Done."}}"#,
+            r#"{"type":"turn.completed","usage":{"input_tokens":50,"cached_input_tokens":0,"output_tokens":5}}"#,
+        ]
+        .join("\n");
+        let result = summarize_events("deps", &jsonl);
+        assert_eq!(result.status, TaskStatus::Succeeded);
+        assert_eq!(result.thread_id.as_deref(), Some("t-1"));
+        assert_eq!(result.final_message.as_deref(), Some("Done."));
+        assert_eq!(
+            result.usage,
+            Usage {
+                input_tokens: 150,
+                cached_input_tokens: 20,
+                output_tokens: 15,
+            }
+        );
+        assert_eq!(result.error, None);
+    }
+}
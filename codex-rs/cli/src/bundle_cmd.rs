@@ -0,0 +1,170 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::git_info::collect_git_info;
+use codex_core::task_bundle::BundlePin;
+use codex_core::task_bundle::BundleProfile;
+use codex_core::task_bundle::TASK_BUNDLE_EXTENSION;
+use codex_core::task_bundle::TaskBundle;
+use codex_core::task_bundle::sandbox_mode_of;
+
+/// Subcommands:
+/// - `create` — write a task bundle for `codex run --bundle`
+/// - `check`  — check whether a task bundle can run here
+#[derive(Debug, clap::Parser)]
+pub struct BundleCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub subcommand: BundleSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum BundleSubcommand {
+    /// Write a task bundle with the prompt, the current model and sandbox
+    /// settings, pinned context and the requirements to run it.
+    Create(CreateArgs),
+
+    /// Check a task bundle's requirements against this machine and the
+    /// current directory.
+    Check(CheckArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct CreateArgs {
+    /// The prompt to run.
+    #[arg(value_name = "PROMPT", required_unless_present = "prompt_file")]
+    pub prompt: Option<String>,
+
+    /// Read the prompt from a file.
+    #[arg(long, value_name = "FILE", conflicts_with = "prompt")]
+    pub prompt_file: Option<PathBuf>,
+
+    /// File to pin before the first turn, relative to the working directory.
+    /// May be repeated.
+    #[arg(long = "pin", value_name = "FILE")]
+    pub pins: Vec<PathBuf>,
+
+    /// Instruction to pin before the first turn. May be repeated.
+    #[arg(long = "instruction", value_name = "TEXT")]
+    pub instructions: Vec<String>,
+
+    /// Program that must be on `PATH`. May be repeated.
+    #[arg(long = "require-command", value_name = "COMMAND")]
+    pub commands: Vec<String>,
+
+    /// Environment variable that must be set. May be repeated.
+    #[arg(long = "require-env", value_name = "VAR")]
+    pub env: Vec<String>,
+
+    /// Record the current `HEAD` commit; running elsewhere prints a warning.
+    #[arg(long)]
+    pub pin_commit: bool,
+
+    /// Where to write the bundle.
+    #[arg(short, long, value_name = "FILE", default_value = "task.codexbundle")]
+    pub output: PathBuf,
+
+    /// Overwrite the output file if it exists.
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct CheckArgs {
+    /// The bundle to check.
+    #[arg(value_name = "FILE")]
+    pub bundle: PathBuf,
+}
+
+impl BundleCli {
+    pub async fn run(self) -> Result<()> {
+        let overrides = self
+            .config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?;
+        let config = Config::load_with_cli_overrides(overrides)
+            .await
+            .context("failed to load configuration")?;
+
+        match self.subcommand {
+            BundleSubcommand::Create(args) => run_create(&config, args).await,
+            BundleSubcommand::Check(args) => run_check(&config, args).await,
+        }
+    }
+}
+
+async fn run_create(config: &Config, args: CreateArgs) -> Result<()> {
+    if args.output.exists() && !args.force {
+        anyhow::bail!(
+            "{} already exists; pass --force to overwrite it",
+            args.output.display()
+        );
+    }
+    let prompt = match (args.prompt, args.prompt_file) {
+        (Some(prompt), _) => prompt,
+        (None, Some(path)) => std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?,
+        (None, None) => anyhow::bail!("provide a prompt or --prompt-file"),
+    };
+    if prompt.trim().is_empty() {
+        anyhow::bail!("the prompt is empty");
+    }
+
+    let mut bundle = TaskBundle::new(prompt, BundleProfile::from_config(config));
+    for path in &args.pins {
+        let pin = BundlePin::file(&config.cwd, path)
+            .with_context(|| format!("failed to pin {}", path.display()))?;
+        bundle.pinned.push(pin);
+    }
+    bundle.pinned.extend(
+        args.instructions
+            .into_iter()
+            .map(|text| BundlePin::Instruction { text }),
+    );
+    bundle.requirements.commands = args.commands;
+    bundle.requirements.env = args.env;
+    if args.pin_commit {
+        let commit = collect_git_info(&config.cwd)
+            .await
+            .and_then(|info| info.commit_hash)
+            .with_context(|| format!("{} is not a git checkout", config.cwd.display()))?;
+        bundle.requirements.git_commit = Some(commit);
+    }
+
+    bundle
+        .save(&args.output)
+        .with_context(|| format!("failed to write {}", args.output.display()))?;
+    if args.output.extension().and_then(|ext| ext.to_str()) != Some(TASK_BUNDLE_EXTENSION) {
+        eprintln!("Note: task bundles usually end in .{TASK_BUNDLE_EXTENSION}");
+    }
+    println!(
+        "Wrote {}. Run it with `codex run --bundle {}`.",
+        args.output.display(),
+        args.output.display()
+    );
+    Ok(())
+}
+
+async fn run_check(config: &Config, args: CheckArgs) -> Result<()> {
+    let bundle = TaskBundle::load(&args.bundle)?;
+    let check = bundle.check(&config.cwd).await;
+    for warning in &check.warnings {
+        println!("Warning: {warning}");
+    }
+    if let Err(problem) = bundle.sandbox_mode(sandbox_mode_of(config.sandbox_policy.get()), false) {
+        println!("Warning: {problem}");
+    }
+    if !check.problems.is_empty() {
+        for problem in &check.problems {
+            println!("Problem: {problem}");
+        }
+        anyhow::bail!("{} cannot run here", args.bundle.display());
+    }
+    println!("{} can run here.", args.bundle.display());
+    Ok(())
+}
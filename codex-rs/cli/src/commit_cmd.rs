@@ -0,0 +1,150 @@
+use std::io::BufRead;
+use std::io::IsTerminal;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::Context;
+use anyhow::Result;
+use codex_common::CliConfigOverrides;
+use codex_core::AuthManager;
+use codex_core::commit_message::commit_staged;
+use codex_core::commit_message::conventional_commit_problem;
+use codex_core::commit_message::generate_commit_message;
+use codex_core::commit_message::staged_changes;
+use codex_core::config::Config;
+
+/// Write a commit message for the staged changes and, once confirmed,
+/// commit them.
+#[derive(Debug, clap::Parser)]
+pub struct CommitCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// Commit without asking for confirmation.
+    #[arg(short = 'y', long, conflicts_with_all = ["edit", "print"])]
+    pub yes: bool,
+
+    /// Open the message in git's editor before committing.
+    #[arg(short = 'e', long, conflicts_with = "print")]
+    pub edit: bool,
+
+    /// Only print the message. This is also what happens when stdin is not a
+    /// terminal and neither --yes nor --edit is given.
+    #[arg(long)]
+    pub print: bool,
+}
+
+impl CommitCli {
+    pub async fn run(self) -> Result<()> {
+        let overrides = self
+            .config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?;
+        let config = Config::load_with_cli_overrides(overrides)
+            .await
+            .context("failed to load configuration")?;
+
+        let Some(changes) = staged_changes(&config.cwd)
+            .await
+            .context("failed to read the staged changes")?
+        else {
+            anyhow::bail!("Nothing is staged. Stage changes with `git add` first.");
+        };
+
+        eprintln!("Writing a commit message for the staged changes…");
+        let auth_manager = AuthManager::shared_from_config(&config, true);
+        let message = generate_commit_message(&config, auth_manager, &changes)
+            .await
+            .context("failed to generate a commit message")?;
+        println!("{message}");
+        if config.commit.template.is_none()
+            && let Some(problem) = conventional_commit_problem(&message)
+        {
+            eprintln!("Warning: not a Conventional Commits message: {problem}");
+        }
+
+        let interactive = std::io::stdin().is_terminal();
+        if self.print || (!interactive && !self.yes && !self.edit) {
+            return Ok(());
+        }
+        let edit = if self.yes || self.edit {
+            self.edit
+        } else {
+            match confirm()? {
+                Confirmation::Commit => false,
+                Confirmation::Edit => true,
+                Confirmation::Cancel => {
+                    eprintln!("Not committed.");
+                    return Ok(());
+                }
+            }
+        };
+
+        if edit {
+            commit_with_editor(&config.cwd, &message)
+        } else {
+            let summary = commit_staged(&config.cwd, &message)
+                .await
+                .context("git commit failed")?;
+            println!("{summary}");
+            Ok(())
+        }
+    }
+}
+
+enum Confirmation {
+    Commit,
+    Edit,
+    Cancel,
+}
+
+fn confirm() -> Result<Confirmation> {
+    let stdin = std::io::stdin();
+    loop {
+        eprint!("Commit with this message? [Y]es, [e]dit, [n]o: ");
+        std::io::stderr().flush()?;
+        let mut answer = String::new();
+        if stdin.lock().read_line(&mut answer)? == 0 {
+            return Ok(Confirmation::Cancel);
+        }
+        match answer.trim().to_ascii_lowercase().as_str() {
+            "" | "y" | "yes" => return Ok(Confirmation::Commit),
+            "e" | "edit" => return Ok(Confirmation::Edit),
+            "n" | "no" => return Ok(Confirmation::Cancel),
+            _ => {}
+        }
+    }
+}
+
+/// Lets git open its editor on the message, so `core.editor`, `GIT_EDITOR`
+/// and the usual comment stripping apply.
+fn commit_with_editor(cwd: &Path, message: &str) -> Result<()> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path", "CODEX_COMMIT_EDITMSG"])
+        .current_dir(cwd)
+        .output()
+        .context("failed to run git")?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    let path = cwd.join(PathBuf::from(
+        String::from_utf8_lossy(&output.stdout).trim(),
+    ));
+    std::fs::write(&path, message)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    let status = Command::new("git")
+        .arg("commit")
+        .arg("--edit")
+        .arg("--file")
+        .arg(&path)
+        .current_dir(cwd)
+        .status()
+        .context("failed to run git commit")?;
+    let _ = std::fs::remove_file(&path);
+    if !status.success() {
+        anyhow::bail!("git commit exited with {status}");
+    }
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use codex_common::CliConfigOverrides;
+use codex_core::RolloutRecorder;
+use codex_core::config::Config;
+use codex_core::find_conversation_path_by_id_str;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::SessionDiffEvent;
+
+/// Print the cumulative diff of the files Codex changed in a recorded session.
+#[derive(Debug, clap::Parser)]
+pub struct DiffCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// Session id (UUID) to show. Defaults to the most recent session.
+    #[arg(value_name = "SESSION_ID")]
+    pub session_id: Option<String>,
+}
+
+impl DiffCli {
+    pub async fn run(self) -> Result<()> {
+        let overrides = self
+            .config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?;
+        let config = Config::load_with_cli_overrides(overrides)
+            .await
+            .context("failed to load configuration")?;
+
+        let path = match self.session_id.as_deref() {
+            Some(id) => find_conversation_path_by_id_str(&config.codex_home, id)
+                .await
+                .context("failed to look up session")?
+                .with_context(|| format!("no recorded session with id {id}"))?,
+            None => {
+                let page = RolloutRecorder::list_conversations(
+                    &config.codex_home,
+                    1,
+                    None,
+                    &[],
+                    None,
+                    &config.model_provider_id,
+                )
+                .await
+                .context("failed to list sessions")?;
+                match page.items.into_iter().next() {
+                    Some(item) => item.path,
+                    None => bail!("no recorded sessions found"),
+                }
+            }
+        };
+
+        let history = RolloutRecorder::get_rollout_history(&path)
+            .await
+            .with_context(|| format!("failed to read session {}", path.display()))?;
+        let unified_diff = history
+            .get_event_msgs()
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+            .find_map(|msg| match msg {
+                EventMsg::SessionDiff(SessionDiffEvent { unified_diff }) => Some(unified_diff),
+                _ => None,
+            });
+
+        match unified_diff {
+            Some(unified_diff) => print!("{unified_diff}"),
+            None => println!("No changes recorded in session {}.", path.display()),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use anyhow::bail;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::default_client::init_http_client_options;
+use codex_core::doctor::CheckStatus;
+use codex_core::doctor::DoctorCheck;
+use codex_core::doctor::run_checks;
+use owo_colors::OwoColorize;
+use supports_color::Stream;
+
+/// Check the sandbox, model provider, MCP servers, hooks and config.
+#[derive(Debug, clap::Parser)]
+pub struct DoctorCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// Config profile to check.
+    #[arg(long = "profile", short = 'p')]
+    pub config_profile: Option<String>,
+
+    /// Output the report as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl DoctorCli {
+    pub async fn run(self, codex_linux_sandbox_exe: Option<PathBuf>) -> Result<()> {
+        let overrides = self
+            .config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?;
+        let harness_overrides = ConfigOverrides {
+            config_profile: self.config_profile,
+            codex_linux_sandbox_exe,
+            ..Default::default()
+        };
+        let mut checks = Vec::new();
+        match Config::load_with_cli_overrides_and_harness_overrides(overrides, harness_overrides)
+            .await
+        {
+            Ok(config) => {
+                init_http_client_options(config.http.clone());
+                checks.push(DoctorCheck {
+                    name: "config".to_string(),
+                    status: CheckStatus::Ok,
+                    detail: format!("loaded from {}", config.codex_home.display()),
+                    hint: None,
+                });
+                checks.extend(run_checks(&config).await);
+            }
+            Err(err) => checks.push(DoctorCheck {
+                name: "config".to_string(),
+                status: CheckStatus::Fail,
+                detail: err.to_string(),
+                hint: Some(
+                    "Fix config.toml; the remaining checks need a valid config.".to_string(),
+                ),
+            }),
+        }
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&checks)?);
+        } else {
+            print_report(&checks);
+        }
+
+        let failed = checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Fail)
+            .count();
+        if failed > 0 {
+            bail!("{failed} check(s) failed");
+        }
+        Ok(())
+    }
+}
+
+fn print_report(checks: &[DoctorCheck]) {
+    let color = supports_color::on(Stream::Stdout).is_some();
+    let width = checks
+        .iter()
+        .map(|check| check.name.len())
+        .max()
+        .unwrap_or_default();
+    for check in checks {
+        let marker = match (check.status, color) {
+            (CheckStatus::Ok, true) => "✓".green().to_string(),
+            (CheckStatus::Warn, true) => "!".yellow().to_string(),
+            (CheckStatus::Fail, true) => "✗".red().to_string(),
+            (CheckStatus::Ok, false) => "✓".to_string(),
+            (CheckStatus::Warn, false) => "!".to_string(),
+            (CheckStatus::Fail, false) => "✗".to_string(),
+        };
+        println!("{marker} {:<width$}  {}", check.name, check.detail);
+        if let Some(hint) = &check.hint {
+            println!("  {:<width$}  → {hint}", "");
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use codex_common::CliConfigOverrides;
+use codex_core::claude_import::convert_claude_hooks;
+use codex_core::claude_import::default_claude_home;
+use codex_core::claude_import::import_claude_sessions;
+use codex_core::config::Config;
+use codex_core::config::edit::ConfigEditsBuilder;
+
+/// Subcommands:
+/// - `claude-code` — import Claude Code sessions and hooks
+#[derive(Debug, clap::Parser)]
+pub struct ImportCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub subcommand: ImportSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum ImportSubcommand {
+    /// Import Claude Code sessions as resumable Codex sessions and its hooks
+    /// as `[[hooks]]` entries.
+    ClaudeCode(ClaudeCodeArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ClaudeCodeArgs {
+    /// Claude Code's data directory. Defaults to `~/.claude`.
+    #[arg(long, value_name = "DIR")]
+    pub claude_home: Option<PathBuf>,
+
+    /// Settings file to read hooks from. Defaults to `settings.json` in the
+    /// Claude Code data directory.
+    #[arg(long, value_name = "FILE")]
+    pub settings: Option<PathBuf>,
+
+    /// Skip sessions and only import hooks.
+    #[arg(long, conflicts_with = "no_hooks")]
+    pub no_sessions: bool,
+
+    /// Skip hooks and only import sessions.
+    #[arg(long)]
+    pub no_hooks: bool,
+
+    /// Report what would be imported without writing anything.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl ImportCli {
+    pub async fn run(self) -> Result<()> {
+        let overrides = self
+            .config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?;
+        let config = Config::load_with_cli_overrides(overrides)
+            .await
+            .context("failed to load configuration")?;
+
+        match self.subcommand {
+            ImportSubcommand::ClaudeCode(args) => run_claude_code(&config, args).await,
+        }
+    }
+}
+
+async fn run_claude_code(config: &Config, args: ClaudeCodeArgs) -> Result<()> {
+    let claude_home = match args.claude_home {
+        Some(dir) => dir,
+        None => default_claude_home().context("could not determine the home directory")?,
+    };
+    if !args.no_sessions {
+        import_sessions(config, &claude_home, args.dry_run).await?;
+    }
+    if !args.no_hooks {
+        let settings = args
+            .settings
+            .unwrap_or_else(|| claude_home.join("settings.json"));
+        import_hooks(config, &settings, args.dry_run).await?;
+    }
+    Ok(())
+}
+
+async fn import_sessions(config: &Config, claude_home: &Path, dry_run: bool) -> Result<()> {
+    let projects_dir = claude_home.join("projects");
+    if !projects_dir.is_dir() {
+        println!(
+            "No Claude Code sessions found in {}.",
+            projects_dir.display()
+        );
+        return Ok(());
+    }
+    let report = import_claude_sessions(config, &projects_dir, dry_run)
+        .await
+        .with_context(|| format!("failed to import sessions from {}", projects_dir.display()))?;
+    for (path, err) in &report.failed {
+        eprintln!("Failed to import {}: {err}", path.display());
+    }
+    let imported = if dry_run { "Would import" } else { "Imported" };
+    println!(
+        "{imported} {} session(s); {} already imported, {} empty.",
+        report.imported.len(),
+        report.already_imported,
+        report.empty
+    );
+    if !dry_run && !report.imported.is_empty() {
+        println!("Run `codex resume` in a project, or `codex resume --all`, to pick one up.");
+    }
+    Ok(())
+}
+
+async fn import_hooks(config: &Config, settings: &Path, dry_run: bool) -> Result<()> {
+    let text = match std::fs::read_to_string(settings) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            println!("No Claude Code settings found at {}.", settings.display());
+            return Ok(());
+        }
+        Err(err) => {
+            return Err(err).with_context(|| format!("failed to read {}", settings.display()));
+        }
+    };
+    let settings_json: serde_json::Value = serde_json::from_str(&text)
+        .with_context(|| format!("failed to parse {}", settings.display()))?;
+    let mut import = convert_claude_hooks(&settings_json);
+    let before = import.hooks.len();
+    import.hooks.retain(|hook| !config.hooks.contains(hook));
+    let already_imported = before - import.hooks.len();
+
+    for note in &import.notes {
+        println!("Skipped {note}");
+    }
+    for hook in &import.hooks {
+        let events: Vec<&str> = hook.events.iter().map(|event| event.as_str()).collect();
+        let matcher = hook
+            .matcher
+            .as_deref()
+            .map(|matcher| format!(" matching `{matcher}`"))
+            .unwrap_or_default();
+        println!("  {}{matcher}", events.join(", "));
+    }
+    if !dry_run && !import.hooks.is_empty() {
+        ConfigEditsBuilder::new(&config.codex_home)
+            .append_hooks(&import.hooks)
+            .apply()
+            .await
+            .with_context(|| format!("failed to write hooks to {}", config.codex_home.display()))?;
+    }
+    let added = if dry_run { "Would add" } else { "Added" };
+    println!(
+        "{added} {} hook(s) to config.toml; {already_imported} already present.",
+        import.hooks.len()
+    );
+    Ok(())
+}
@@ -3,9 +3,11 @@ use codex_common::CliConfigOverrides;
 use codex_core::CodexAuth;
 use codex_core::auth::AuthCredentialsStoreMode;
 use codex_core::auth::CLIENT_ID;
+use codex_core::auth::accounts;
 use codex_core::auth::login_with_api_key;
 use codex_core::auth::logout;
 use codex_core::config::Config;
+use codex_core::default_client::init_http_client_options;
 use codex_login::ServerOptions;
 use codex_login::run_device_code_login;
 use codex_login::run_login_server;
@@ -35,8 +37,12 @@ pub async fn login_with_chatgpt(
     server.block_until_done().await
 }
 
-pub async fn run_login_with_chatgpt(cli_config_overrides: CliConfigOverrides) -> ! {
+pub async fn run_login_with_chatgpt(
+    cli_config_overrides: CliConfigOverrides,
+    account: Option<String>,
+) -> ! {
     let config = load_config_or_exit(cli_config_overrides).await;
+    let (account, auth_home) = prepare_account_or_exit(&config, account);
 
     if matches!(config.forced_login_method, Some(ForcedLoginMethod::Api)) {
         eprintln!("ChatGPT login is disabled. Use API key login instead.");
@@ -46,14 +52,14 @@ pub async fn run_login_with_chatgpt(cli_config_overrides: CliConfigOverrides) ->
     let forced_chatgpt_workspace_id = config.forced_chatgpt_workspace_id.clone();
 
     match login_with_chatgpt(
-        config.codex_home,
+        auth_home,
         forced_chatgpt_workspace_id,
         config.cli_auth_credentials_store_mode,
     )
     .await
     {
         Ok(_) => {
-            eprintln!("Successfully logged in");
+            eprintln!("Successfully logged in{}", account_suffix(&account));
             std::process::exit(0);
         }
         Err(e) => {
@@ -65,22 +71,20 @@ pub async fn run_login_with_chatgpt(cli_config_overrides: CliConfigOverrides) ->
 
 pub async fn run_login_with_api_key(
     cli_config_overrides: CliConfigOverrides,
+    account: Option<String>,
     api_key: String,
 ) -> ! {
     let config = load_config_or_exit(cli_config_overrides).await;
+    let (account, auth_home) = prepare_account_or_exit(&config, account);
 
     if matches!(config.forced_login_method, Some(ForcedLoginMethod::Chatgpt)) {
         eprintln!("API key login is disabled. Use ChatGPT login instead.");
         std::process::exit(1);
     }
 
-    match login_with_api_key(
-        &config.codex_home,
-        &api_key,
-        config.cli_auth_credentials_store_mode,
-    ) {
+    match login_with_api_key(&auth_home, &api_key, config.cli_auth_credentials_store_mode) {
         Ok(_) => {
-            eprintln!("Successfully logged in");
+            eprintln!("Successfully logged in{}", account_suffix(&account));
             std::process::exit(0);
         }
         Err(e) => {
@@ -120,17 +124,19 @@ pub fn read_api_key_from_stdin() -> String {
 /// Login using the OAuth device code flow.
 pub async fn run_login_with_device_code(
     cli_config_overrides: CliConfigOverrides,
+    account: Option<String>,
     issuer_base_url: Option<String>,
     client_id: Option<String>,
 ) -> ! {
     let config = load_config_or_exit(cli_config_overrides).await;
+    let (account, auth_home) = prepare_account_or_exit(&config, account);
     if matches!(config.forced_login_method, Some(ForcedLoginMethod::Api)) {
         eprintln!("ChatGPT login is disabled. Use API key login instead.");
         std::process::exit(1);
     }
     let forced_chatgpt_workspace_id = config.forced_chatgpt_workspace_id.clone();
     let mut opts = ServerOptions::new(
-        config.codex_home,
+        auth_home,
         client_id.unwrap_or(CLIENT_ID.to_string()),
         forced_chatgpt_workspace_id,
         config.cli_auth_credentials_store_mode,
@@ -140,7 +146,7 @@ pub async fn run_login_with_device_code(
     }
     match run_device_code_login(opts).await {
         Ok(()) => {
-            eprintln!("Successfully logged in");
+            eprintln!("Successfully logged in{}", account_suffix(&account));
             std::process::exit(0);
         }
         Err(e) => {
@@ -150,14 +156,23 @@ pub async fn run_login_with_device_code(
     }
 }
 
-pub async fn run_login_status(cli_config_overrides: CliConfigOverrides) -> ! {
+pub async fn run_login_status(
+    cli_config_overrides: CliConfigOverrides,
+    account: Option<String>,
+) -> ! {
     let config = load_config_or_exit(cli_config_overrides).await;
+    let account = resolve_account_or_exit(&config, account);
+    let auth_home = accounts::account_home(&config.codex_home, &account);
+    let suffix = account_suffix(&account);
 
-    match CodexAuth::from_auth_storage(&config.codex_home, config.cli_auth_credentials_store_mode) {
+    match CodexAuth::from_auth_storage(&auth_home, config.cli_auth_credentials_store_mode) {
         Ok(Some(auth)) => match auth.mode {
             AuthMode::ApiKey => match auth.get_token().await {
                 Ok(api_key) => {
-                    eprintln!("Logged in using an API key - {}", safe_format_key(&api_key));
+                    eprintln!(
+                        "Logged in{suffix} using an API key - {}",
+                        safe_format_key(&api_key)
+                    );
                     std::process::exit(0);
                 }
                 Err(e) => {
@@ -166,12 +181,12 @@ pub async fn run_login_status(cli_config_overrides: CliConfigOverrides) -> ! {
                 }
             },
             AuthMode::ChatGPT => {
-                eprintln!("Logged in using ChatGPT");
+                eprintln!("Logged in{suffix} using ChatGPT");
                 std::process::exit(0);
             }
         },
         Ok(None) => {
-            eprintln!("Not logged in");
+            eprintln!("Not logged in{suffix}");
             std::process::exit(1);
         }
         Err(e) => {
@@ -181,16 +196,18 @@ pub async fn run_login_status(cli_config_overrides: CliConfigOverrides) -> ! {
     }
 }
 
-pub async fn run_logout(cli_config_overrides: CliConfigOverrides) -> ! {
+pub async fn run_logout(cli_config_overrides: CliConfigOverrides, account: Option<String>) -> ! {
     let config = load_config_or_exit(cli_config_overrides).await;
+    let account = resolve_account_or_exit(&config, account);
+    let auth_home = accounts::account_home(&config.codex_home, &account);
 
-    match logout(&config.codex_home, config.cli_auth_credentials_store_mode) {
+    match logout(&auth_home, config.cli_auth_credentials_store_mode) {
         Ok(true) => {
-            eprintln!("Successfully logged out");
+            eprintln!("Successfully logged out{}", account_suffix(&account));
             std::process::exit(0);
         }
         Ok(false) => {
-            eprintln!("Not logged in");
+            eprintln!("Not logged in{}", account_suffix(&account));
             std::process::exit(0);
         }
         Err(e) => {
@@ -210,7 +227,10 @@ async fn load_config_or_exit(cli_config_overrides: CliConfigOverrides) -> Config
     };
 
     match Config::load_with_cli_overrides(cli_overrides).await {
-        Ok(config) => config,
+        Ok(config) => {
+            init_http_client_options(config.http.clone());
+            config
+        }
         Err(e) => {
             eprintln!("Error loading configuration: {e}");
             std::process::exit(1);
@@ -218,7 +238,42 @@ async fn load_config_or_exit(cli_config_overrides: CliConfigOverrides) -> Config
     }
 }
 
-fn safe_format_key(key: &str) -> String {
+/// `account`, or else the account the config or `codex auth use` selects.
+fn resolve_account_or_exit(config: &Config, account: Option<String>) -> String {
+    if let Some(account) = &account
+        && let Err(e) = accounts::validate_account_name(account)
+    {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+    accounts::resolve_account(
+        &config.codex_home,
+        account.as_deref().or(config.account.as_deref()),
+    )
+}
+
+/// Resolves the account to log in to and creates its directory, so it is
+/// listed by `codex auth accounts` even when credentials go to the keyring.
+fn prepare_account_or_exit(config: &Config, account: Option<String>) -> (String, PathBuf) {
+    let account = resolve_account_or_exit(config, account);
+    match accounts::create_account(&config.codex_home, &account) {
+        Ok(auth_home) => (account, auth_home),
+        Err(e) => {
+            eprintln!("Error creating account {account}: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn account_suffix(account: &str) -> String {
+    if account == accounts::DEFAULT_ACCOUNT {
+        String::new()
+    } else {
+        format!(" (account `{account}`)")
+    }
+}
+
+pub fn safe_format_key(key: &str) -> String {
     if key.len() <= 13 {
         return "***".to_string();
     }
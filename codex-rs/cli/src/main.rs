@@ -20,6 +20,7 @@ use codex_common::CliConfigOverrides;
 use codex_exec::Cli as ExecCli;
 use codex_exec::Command as ExecCommand;
 use codex_exec::ReviewArgs;
+use codex_exec::RunArgs;
 use codex_execpolicy::ExecPolicyCheckCommand;
 use codex_responses_api_proxy::Args as ResponsesApiProxyArgs;
 use codex_tui::AppExitInfo;
@@ -30,11 +31,33 @@ use owo_colors::OwoColorize;
 use std::path::PathBuf;
 use supports_color::Stream;
 
+mod auth_cmd;
+mod batch_cmd;
+mod bundle_cmd;
+mod commit_cmd;
+mod diff_cmd;
+mod doctor_cmd;
+mod import_cmd;
 mod mcp_cmd;
+mod memory_cmd;
+mod sessions_cmd;
+mod status_cmd;
+mod usage_cmd;
 #[cfg(not(windows))]
 mod wsl_paths;
 
+use crate::auth_cmd::AuthCli;
+use crate::batch_cmd::BatchCli;
+use crate::bundle_cmd::BundleCli;
+use crate::commit_cmd::CommitCli;
+use crate::diff_cmd::DiffCli;
+use crate::doctor_cmd::DoctorCli;
+use crate::import_cmd::ImportCli;
 use crate::mcp_cmd::McpCli;
+use crate::memory_cmd::MemoryCli;
+use crate::sessions_cmd::SessionsCli;
+use crate::status_cmd::StatusCli;
+use crate::usage_cmd::UsageCli;
 
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
@@ -84,12 +107,24 @@ enum Subcommand {
     /// Run a code review non-interactively.
     Review(ReviewArgs),
 
+    /// Run a custom command from `commands/*.md` or a task bundle non-interactively.
+    Run(RunArgs),
+
+    /// Create and check task bundles for `codex run --bundle`.
+    Bundle(BundleCli),
+
+    /// Write a commit message for the staged changes and commit them.
+    Commit(CommitCli),
+
     /// Manage login.
     Login(LoginCommand),
 
     /// Remove stored authentication credentials.
     Logout(LogoutCommand),
 
+    /// Manage named accounts and provider API keys stored in the OS keychain.
+    Auth(AuthCli),
+
     /// [experimental] Run Codex as an MCP server and manage MCP servers.
     Mcp(McpCli),
 
@@ -131,6 +166,30 @@ enum Subcommand {
 
     /// Inspect feature flags.
     Features(FeaturesCli),
+
+    /// Show token usage and estimated spend from the usage ledger.
+    Usage(UsageCli),
+
+    /// Report running sessions, model, context left and today's spend for prompts and statuslines.
+    Status(StatusCli),
+
+    /// Inspect and expire memories saved by the `memory` feature.
+    Memory(MemoryCli),
+
+    /// Remove old sessions and shrink the ones that are kept.
+    Sessions(SessionsCli),
+
+    /// Import sessions and hooks from other coding agents.
+    Import(ImportCli),
+
+    /// Print every file change Codex made in a recorded session.
+    Diff(DiffCli),
+
+    /// [experimental] Run a queue of headless tasks, each in its own git worktree.
+    Batch(BatchCli),
+
+    /// Check the sandbox, model provider, MCP servers, hooks and config.
+    Doctor(DoctorCli),
 }
 
 #[derive(Debug, Parser)]
@@ -214,6 +273,10 @@ struct LoginCommand {
     #[arg(long = "device-auth")]
     use_device_code: bool,
 
+    /// Named account to log in to instead of the active one, e.g. `work`.
+    #[arg(long = "account", value_name = "NAME", global = true)]
+    account: Option<String>,
+
     /// EXPERIMENTAL: Use custom OAuth issuer base URL (advanced)
     /// Override the OAuth issuer base URL (advanced)
     #[arg(long = "experimental_issuer", value_name = "URL", hide = true)]
@@ -237,6 +300,10 @@ enum LoginSubcommand {
 struct LogoutCommand {
     #[clap(skip)]
     config_overrides: CliConfigOverrides,
+
+    /// Named account to log out of instead of the active one.
+    #[arg(long = "account", value_name = "NAME")]
+    account: Option<String>,
 }
 
 #[derive(Debug, Parser)]
@@ -244,6 +311,17 @@ struct AppServerCommand {
     /// Omit to run the app server; specify a subcommand for tooling.
     #[command(subcommand)]
     subcommand: Option<AppServerSubcommand>,
+
+    /// Serve the protocol over WebSocket at this address (for example
+    /// `ws://127.0.0.1:4500`) instead of stdio. Clients must present the
+    /// token in `CODEX_APP_SERVER_TOKEN`.
+    #[arg(long, value_name = "ADDR")]
+    listen: Option<String>,
+
+    /// Let web pages from this origin (for example `https://app.example.com`)
+    /// connect to `--listen`. Repeatable; pages from other origins are refused.
+    #[arg(long = "allow-origin", value_name = "ORIGIN", requires = "listen")]
+    allow_origin: Vec<String>,
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -470,6 +548,15 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             );
             codex_exec::run_main(exec_cli, codex_linux_sandbox_exe).await?;
         }
+        Some(Subcommand::Run(run_args)) => {
+            let mut exec_cli = ExecCli::try_parse_from(["codex", "exec"])?;
+            exec_cli.command = Some(ExecCommand::Run(run_args));
+            prepend_config_flags(
+                &mut exec_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            codex_exec::run_main(exec_cli, codex_linux_sandbox_exe).await?;
+        }
         Some(Subcommand::McpServer) => {
             codex_mcp_server::run_main(codex_linux_sandbox_exe, root_config_overrides).await?;
         }
@@ -478,10 +565,99 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             prepend_config_flags(&mut mcp_cli.config_overrides, root_config_overrides.clone());
             mcp_cli.run().await?;
         }
+        Some(Subcommand::Usage(mut usage_cli)) => {
+            prepend_config_flags(
+                &mut usage_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            usage_cli.run().await?;
+        }
+        Some(Subcommand::Status(mut status_cli)) => {
+            prepend_config_flags(
+                &mut status_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            status_cli.run().await?;
+        }
+        Some(Subcommand::Diff(mut diff_cli)) => {
+            prepend_config_flags(
+                &mut diff_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            diff_cli.run().await?;
+        }
+        Some(Subcommand::Batch(mut batch_cli)) => {
+            prepend_config_flags(
+                &mut batch_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            batch_cli.run().await?;
+        }
+        Some(Subcommand::Doctor(mut doctor_cli)) => {
+            prepend_config_flags(
+                &mut doctor_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            doctor_cli.run(codex_linux_sandbox_exe).await?;
+        }
+        Some(Subcommand::Auth(mut auth_cli)) => {
+            prepend_config_flags(
+                &mut auth_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            auth_cli.run().await?;
+        }
+        Some(Subcommand::Memory(mut memory_cli)) => {
+            prepend_config_flags(
+                &mut memory_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            memory_cli.run().await?;
+        }
+        Some(Subcommand::Sessions(mut sessions_cli)) => {
+            prepend_config_flags(
+                &mut sessions_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            sessions_cli.run().await?;
+        }
+        Some(Subcommand::Bundle(mut bundle_cli)) => {
+            prepend_config_flags(
+                &mut bundle_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            bundle_cli.run().await?;
+        }
+        Some(Subcommand::Commit(mut commit_cli)) => {
+            prepend_config_flags(
+                &mut commit_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            commit_cli.run().await?;
+        }
+        Some(Subcommand::Import(mut import_cli)) => {
+            prepend_config_flags(
+                &mut import_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            import_cli.run().await?;
+        }
         Some(Subcommand::AppServer(app_server_cli)) => match app_server_cli.subcommand {
-            None => {
-                codex_app_server::run_main(codex_linux_sandbox_exe, root_config_overrides).await?;
-            }
+            None => match app_server_cli.listen {
+                Some(listen) => {
+                    codex_app_server::run_websocket_server(
+                        codex_linux_sandbox_exe,
+                        root_config_overrides,
+                        &listen,
+                        app_server_cli.allow_origin,
+                    )
+                    .await?;
+                }
+                None => {
+                    codex_app_server::run_main(codex_linux_sandbox_exe, root_config_overrides)
+                        .await?;
+                }
+            },
             Some(AppServerSubcommand::GenerateTs(gen_cli)) => {
                 codex_app_server_protocol::generate_ts(
                     &gen_cli.out_dir,
@@ -516,12 +692,13 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             );
             match login_cli.action {
                 Some(LoginSubcommand::Status) => {
-                    run_login_status(login_cli.config_overrides).await;
+                    run_login_status(login_cli.config_overrides, login_cli.account).await;
                 }
                 None => {
                     if login_cli.use_device_code {
                         run_login_with_device_code(
                             login_cli.config_overrides,
+                            login_cli.account,
                             login_cli.issuer_base_url,
                             login_cli.client_id,
                         )
@@ -533,9 +710,14 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
                         std::process::exit(1);
                     } else if login_cli.with_api_key {
                         let api_key = read_api_key_from_stdin();
-                        run_login_with_api_key(login_cli.config_overrides, api_key).await;
+                        run_login_with_api_key(
+                            login_cli.config_overrides,
+                            login_cli.account,
+                            api_key,
+                        )
+                        .await;
                     } else {
-                        run_login_with_chatgpt(login_cli.config_overrides).await;
+                        run_login_with_chatgpt(login_cli.config_overrides, login_cli.account).await;
                     }
                 }
             }
@@ -545,7 +727,7 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
                 &mut logout_cli.config_overrides,
                 root_config_overrides.clone(),
             );
-            run_logout(logout_cli.config_overrides).await;
+            run_logout(logout_cli.config_overrides, logout_cli.account).await;
         }
         Some(Subcommand::Completion(completion_cli)) => {
             print_completion(completion_cli);
@@ -0,0 +1,140 @@
+use anyhow::Context;
+use anyhow::Result;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::memory_store::MemoryEntry;
+use codex_core::memory_store::now_unix_seconds;
+use codex_core::memory_store::project_key;
+use codex_core::memory_store::read_entries;
+use codex_core::memory_store::remove_entries;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Subcommands:
+/// - `list`   — show memories for the current project (or all with `--all`)
+/// - `delete` — remove memories by id
+/// - `expire` — remove expired memories, optionally also old ones
+#[derive(Debug, clap::Parser)]
+pub struct MemoryCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub subcommand: MemorySubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum MemorySubcommand {
+    List(ListArgs),
+    Delete(DeleteArgs),
+    Expire(ExpireArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ListArgs {
+    /// Include memories from every project, including expired ones.
+    #[arg(long)]
+    pub all: bool,
+
+    /// Output the memories as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct DeleteArgs {
+    /// Ids of the memories to delete.
+    #[arg(required = true, value_name = "ID")]
+    pub ids: Vec<String>,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ExpireArgs {
+    /// Also remove memories created more than this many days ago.
+    #[arg(long, value_name = "DAYS")]
+    pub older_than_days: Option<u64>,
+}
+
+impl MemoryCli {
+    pub async fn run(self) -> Result<()> {
+        let overrides = self
+            .config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?;
+        let config = Config::load_with_cli_overrides(overrides)
+            .await
+            .context("failed to load configuration")?;
+
+        match self.subcommand {
+            MemorySubcommand::List(args) => run_list(&config, args),
+            MemorySubcommand::Delete(args) => run_delete(&config, args),
+            MemorySubcommand::Expire(args) => run_expire(&config, args),
+        }
+    }
+}
+
+fn run_list(config: &Config, args: ListArgs) -> Result<()> {
+    let entries = read_entries(&config.codex_home)
+        .with_context(|| format!("failed to read memories in {}", config.codex_home.display()))?;
+    let now = now_unix_seconds();
+    let project = project_key(&config.cwd);
+    let mut entries: Vec<MemoryEntry> = if args.all {
+        entries
+    } else {
+        entries
+            .into_iter()
+            .filter(|entry| entry.project == project && !entry.is_expired(now))
+            .collect()
+    };
+    entries.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+    if args.json {
+        let output = serde_json::to_string_pretty(&entries)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        if args.all {
+            println!("No memories saved.");
+        } else {
+            println!("No memories saved for {project}.");
+        }
+        return Ok(());
+    }
+
+    for entry in &entries {
+        let mut line = format!("{}  {}  {}", entry.id, entry.created_date(), entry.text);
+        if !entry.tags.is_empty() {
+            line.push_str(&format!("  #{}", entry.tags.join(" #")));
+        }
+        if entry.is_expired(now) {
+            line.push_str("  (expired)");
+        }
+        if args.all {
+            line.push_str(&format!("  [{}]", entry.project));
+        }
+        println!("{line}");
+    }
+    Ok(())
+}
+
+fn run_delete(config: &Config, args: DeleteArgs) -> Result<()> {
+    let removed = remove_entries(&config.codex_home, |entry| args.ids.contains(&entry.id))
+        .context("failed to delete memories")?;
+    println!("Deleted {removed} memory(ies).");
+    Ok(())
+}
+
+fn run_expire(config: &Config, args: ExpireArgs) -> Result<()> {
+    let now = now_unix_seconds();
+    let cutoff = args
+        .older_than_days
+        .map(|days| now.saturating_sub(days.saturating_mul(SECONDS_PER_DAY)));
+    let removed = remove_entries(&config.codex_home, |entry| {
+        entry.is_expired(now) || cutoff.is_some_and(|cutoff| entry.created_at < cutoff)
+    })
+    .context("failed to expire memories")?;
+    println!("Removed {removed} memory(ies).");
+    Ok(())
+}
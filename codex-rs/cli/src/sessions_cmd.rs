@@ -0,0 +1,131 @@
+use anyhow::Context;
+use anyhow::Result;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::gc_sessions;
+use codex_core::storage_encryption::writer_cipher;
+
+/// Subcommands:
+/// - `gc` — remove sessions outside `[session_retention]` and deduplicate
+///   repeated tool outputs in the rest
+#[derive(Debug, clap::Parser)]
+pub struct SessionsCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub subcommand: SessionsSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum SessionsSubcommand {
+    Gc(GcArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct GcArgs {
+    /// Report what would be removed and saved without changing anything.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Keep at most this many sessions (overrides `session_retention.max_sessions`).
+    #[arg(long, value_name = "N")]
+    pub max_sessions: Option<usize>,
+
+    /// Remove sessions older than this (overrides `session_retention.max_age_days`).
+    #[arg(long, value_name = "DAYS")]
+    pub max_age_days: Option<u64>,
+
+    /// Keep at most this many bytes of sessions (overrides `session_retention.max_disk_bytes`).
+    #[arg(long, value_name = "BYTES")]
+    pub max_disk_bytes: Option<u64>,
+
+    /// Leave repeated tool outputs in existing sessions as they are.
+    #[arg(long)]
+    pub no_dedupe: bool,
+}
+
+impl SessionsCli {
+    pub async fn run(self) -> Result<()> {
+        let overrides = self
+            .config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?;
+        let config = Config::load_with_cli_overrides(overrides)
+            .await
+            .context("failed to load configuration")?;
+
+        match self.subcommand {
+            SessionsSubcommand::Gc(args) => run_gc(&config, args).await,
+        }
+    }
+}
+
+async fn run_gc(config: &Config, args: GcArgs) -> Result<()> {
+    let mut retention = config.session_retention.clone();
+    if args.max_sessions.is_some() {
+        retention.max_sessions = args.max_sessions;
+    }
+    if args.max_age_days.is_some() {
+        retention.max_age_days = args.max_age_days;
+    }
+    if args.max_disk_bytes.is_some() {
+        retention.max_disk_bytes = args.max_disk_bytes;
+    }
+    let cipher = writer_cipher(config).context("failed to load the storage encryption key")?;
+
+    let report = gc_sessions(
+        &config.codex_home,
+        &retention,
+        !args.no_dedupe,
+        cipher,
+        args.dry_run,
+    )
+    .await
+    .with_context(|| format!("failed to clean up {}", config.codex_home.display()))?;
+
+    let (removed, deduplicated) = if args.dry_run {
+        ("Would remove", "Would deduplicate")
+    } else {
+        ("Removed", "Deduplicated")
+    };
+    for path in &report.removed {
+        println!("{removed} {}", path.display());
+    }
+    println!(
+        "{removed} {} session(s) ({}); kept {}.",
+        report.removed.len(),
+        format_bytes(report.removed_bytes),
+        report.kept
+    );
+    if !args.no_dedupe {
+        println!(
+            "{deduplicated} tool outputs in {} session(s), saving {}.",
+            report.deduplicated_files,
+            format_bytes(report.deduplicated_bytes)
+        );
+    }
+    if !retention.has_limits() {
+        println!(
+            "No limits are set; configure [session_retention] or pass --max-sessions, --max-age-days or --max-disk-bytes to remove old sessions."
+        );
+    }
+    Ok(())
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    format!("{value:.1} {unit}")
+}
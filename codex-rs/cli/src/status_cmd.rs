@@ -0,0 +1,60 @@
+use anyhow::Context;
+use anyhow::Result;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::session_status::StatusReport;
+use codex_core::session_status::status_report;
+
+/// Report running sessions, the current model, context left and today's
+/// spend, for shell prompts and editor statuslines.
+#[derive(Debug, clap::Parser)]
+pub struct StatusCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// Print the report as a single line of JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl StatusCli {
+    pub async fn run(self) -> Result<()> {
+        let overrides = self
+            .config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?;
+        let config = Config::load_with_cli_overrides(overrides)
+            .await
+            .context("failed to load configuration")?;
+
+        let report = status_report(&config);
+        if self.json {
+            println!("{}", serde_json::to_string(&report)?);
+        } else {
+            println!("{}", status_line(&report));
+        }
+        Ok(())
+    }
+}
+
+/// e.g. `2 sessions (1 busy) · gpt-5.1-codex · 63% context left · $1.24 today`.
+fn status_line(report: &StatusReport) -> String {
+    let sessions = &report.active_sessions;
+    let busy = sessions.iter().filter(|session| session.busy).count();
+    let mut parts = vec![match sessions.len() {
+        0 => "no sessions".to_string(),
+        1 if busy == 1 => "1 session (busy)".to_string(),
+        1 => "1 session".to_string(),
+        count if busy > 0 => format!("{count} sessions ({busy} busy)"),
+        count => format!("{count} sessions"),
+    }];
+    parts.extend(report.model.clone());
+    if let Some(percent) = sessions
+        .first()
+        .and_then(|session| session.context_left_percent)
+    {
+        parts.push(format!("{percent}% context left"));
+    }
+    parts.push(format!("${:.2} today", report.cost_today_usd));
+    parts.join(" · ")
+}
@@ -0,0 +1,129 @@
+use anyhow::Context;
+use anyhow::Result;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::usage_ledger::DailyUsage;
+use codex_core::usage_ledger::UsageTotals;
+use codex_core::usage_ledger::read_records;
+use codex_core::usage_ledger::start_of_local_day_days_ago;
+use codex_core::usage_ledger::summarize_by_day;
+
+/// Summarize token usage and estimated spend recorded in `~/.codex/usage.jsonl`.
+#[derive(Debug, clap::Parser)]
+pub struct UsageCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// Number of days to include, counting today.
+    #[arg(long, default_value_t = 7, value_parser = clap::value_parser!(u32).range(1..))]
+    pub days: u32,
+
+    /// Output the per-day summary as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl UsageCli {
+    pub async fn run(self) -> Result<()> {
+        let overrides = self
+            .config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?;
+        let config = Config::load_with_cli_overrides(overrides)
+            .await
+            .context("failed to load configuration")?;
+
+        let records = read_records(&config.codex_home).with_context(|| {
+            format!(
+                "failed to read usage ledger in {}",
+                config.codex_home.display()
+            )
+        })?;
+        let since = start_of_local_day_days_ago(self.days - 1);
+        let days = summarize_by_day(&records, since, &config.model_pricing);
+
+        if self.json {
+            let output = serde_json::to_string_pretty(&days)?;
+            println!("{output}");
+            return Ok(());
+        }
+
+        if days.is_empty() {
+            println!("No usage recorded in the last {} day(s).", self.days);
+            return Ok(());
+        }
+        print_table(&days);
+        Ok(())
+    }
+}
+
+fn print_table(days: &[DailyUsage]) {
+    let mut rows = vec![[
+        "Date".to_string(),
+        "Model".to_string(),
+        "Requests".to_string(),
+        "Input".to_string(),
+        "Cached".to_string(),
+        "Output".to_string(),
+        "Cost".to_string(),
+    ]];
+    let mut total = UsageTotals::default();
+    for day in days {
+        for (model, totals) in &day.by_model {
+            rows.push(row(&day.date, model, totals));
+        }
+        accumulate(&mut total, &day.totals);
+    }
+    rows.push(row("Total", "", &total));
+
+    let mut widths = [0usize; 7];
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.len());
+        }
+    }
+    for row in &rows {
+        let line = row
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                if i < 2 {
+                    format!("{cell:<width$}", width = widths[i])
+                } else {
+                    format!("{cell:>width$}", width = widths[i])
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+
+    if total.unpriced_requests > 0 {
+        println!(
+            "\n{} request(s) used models without pricing and are excluded from cost. Add a [model_pricing.<model>] table to config.toml to price them.",
+            total.unpriced_requests
+        );
+    }
+}
+
+fn row(date: &str, model: &str, totals: &UsageTotals) -> [String; 7] {
+    [
+        date.to_string(),
+        model.to_string(),
+        totals.requests.to_string(),
+        totals.input_tokens.to_string(),
+        totals.cached_input_tokens.to_string(),
+        totals.output_tokens.to_string(),
+        format!("${:.4}", totals.cost_usd),
+    ]
+}
+
+fn accumulate(total: &mut UsageTotals, day: &UsageTotals) {
+    total.requests += day.requests;
+    total.input_tokens += day.input_tokens;
+    total.cached_input_tokens += day.cached_input_tokens;
+    total.output_tokens += day.output_tokens;
+    total.reasoning_output_tokens += day.reasoning_output_tokens;
+    total.cost_usd += day.cost_usd;
+    total.unpriced_requests += day.unpriced_requests;
+}
@@ -1,3 +1,4 @@
+use crate::aws::AwsCredentials;
 use codex_client::Request;
 
 /// Provides bearer and account identity information for API requests.
@@ -10,6 +11,26 @@ pub trait AuthProvider: Send + Sync {
     fn account_id(&self) -> Option<String> {
         None
     }
+    /// Keys used to sign Bedrock requests. When absent, Bedrock requests
+    /// carry the bearer token instead, as Bedrock API keys do.
+    fn aws_credentials(&self) -> Option<AwsCredentials> {
+        None
+    }
+}
+
+/// For providers that take the API key in a dedicated header (Anthropic's
+/// `x-api-key`, Gemini's `x-goog-api-key`) rather than a bearer token.
+pub(crate) fn add_api_key_header<A: AuthProvider>(
+    auth: &A,
+    mut req: Request,
+    header_name: &'static str,
+) -> Request {
+    if let Some(token) = auth.bearer_token()
+        && let Ok(header) = token.parse()
+    {
+        let _ = req.headers.insert(header_name, header);
+    }
+    req
 }
 
 pub(crate) fn add_auth_headers<A: AuthProvider>(auth: &A, mut req: Request) -> Request {
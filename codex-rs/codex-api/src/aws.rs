@@ -0,0 +1,259 @@
+//! AWS Signature Version 4 request signing, for Amazon Bedrock.
+//!
+//! Only what Bedrock needs is implemented: the `host`, `x-amz-date` and
+//! (for temporary credentials) `x-amz-security-token` headers are signed,
+//! and the payload hash covers the JSON body exactly as the transport
+//! serializes it.
+
+use chrono::DateTime;
+use chrono::Utc;
+use codex_client::Request;
+use http::HeaderValue;
+use sha2::Digest;
+use sha2::Sha256;
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+pub(crate) const BEDROCK_SERVICE: &str = "bedrock";
+
+/// Static or temporary AWS credentials.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl std::fmt::Debug for AwsCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AwsCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Region of a Bedrock runtime endpoint, e.g. `us-east-1` for
+/// `bedrock-runtime.us-east-1.amazonaws.com` or a VPC endpoint under it.
+pub fn region_from_base_url(base_url: &str) -> Option<String> {
+    let uri: http::Uri = base_url.parse().ok()?;
+    let mut labels = uri.host()?.split('.');
+    labels.find(|label| *label == "bedrock-runtime")?;
+    labels
+        .next()
+        .filter(|region| !region.is_empty())
+        .map(str::to_string)
+}
+
+/// Adds the SigV4 `Authorization` header (and the headers it covers) to
+/// `req`, signed at `now`.
+pub(crate) fn sign_request(
+    req: &mut Request,
+    credentials: &AwsCredentials,
+    region: &str,
+    service: &str,
+    now: DateTime<Utc>,
+) {
+    let Ok(uri) = req.url.parse::<http::Uri>() else {
+        return;
+    };
+    let Some(authority) = uri.authority() else {
+        return;
+    };
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+
+    let payload = req
+        .body
+        .as_ref()
+        .and_then(|body| serde_json::to_vec(body).ok())
+        .unwrap_or_default();
+    let payload_hash = hex_sha256(&payload);
+
+    let mut signed_headers = vec![
+        ("host", authority.as_str().to_ascii_lowercase()),
+        ("x-amz-date", amz_date.clone()),
+    ];
+    if let Some(token) = &credentials.session_token {
+        signed_headers.push(("x-amz-security-token", token.clone()));
+    }
+    let canonical_headers: String = signed_headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+        .collect();
+    let signed_header_names = signed_headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let canonical_request = format!(
+        "{method}\n{path}\n{query}\n{canonical_headers}\n{signed_header_names}\n{payload_hash}",
+        method = req.method.as_str(),
+        path = canonical_path(uri.path()),
+        query = canonical_query(uri.query().unwrap_or_default()),
+    );
+    let scope = format!("{date}/{region}/{service}/aws4_request");
+    let string_to_sign = format!(
+        "{ALGORITHM}\n{amz_date}\n{scope}\n{}",
+        hex_sha256(canonical_request.as_bytes())
+    );
+
+    let mut key = hmac_sha256(
+        format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+        date.as_bytes(),
+    );
+    for part in [region, service, "aws4_request"] {
+        key = hmac_sha256(&key, part.as_bytes());
+    }
+    let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+    let authorization = format!(
+        "{ALGORITHM} Credential={}/{scope}, SignedHeaders={signed_header_names}, Signature={signature}",
+        credentials.access_key_id
+    );
+
+    for (name, value) in signed_headers.into_iter().skip(1) {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            req.headers.insert(name, value);
+        }
+    }
+    if let Ok(value) = HeaderValue::from_str(&authorization) {
+        req.headers.insert(http::header::AUTHORIZATION, value);
+    }
+}
+
+/// Every service but S3 signs the path with each segment encoded once more
+/// than it was sent, so `%3A` in a model id becomes `%253A`.
+fn canonical_path(path: &str) -> String {
+    if path.is_empty() {
+        return "/".to_string();
+    }
+    path.split('/')
+        .map(uri_encode)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn canonical_query(query: &str) -> String {
+    let mut pairs: Vec<(String, String)> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (uri_encode(name), uri_encode(value))
+        })
+        .collect();
+    pairs.sort();
+    pairs
+        .into_iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn uri_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Percent-encodes a single path segment, e.g. a Bedrock model id.
+pub(crate) fn encode_path_segment(value: &str) -> String {
+    uri_encode(value)
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    const BLOCK_SIZE: usize = 64;
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new();
+    inner.update(block.map(|byte| byte ^ 0x36));
+    inner.update(message);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|byte| byte ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().to_vec()
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use http::Method;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn hmac_matches_rfc_4231_test_case_2() {
+        assert_eq!(
+            hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    /// The `get-vanilla-query-order-key-case` case from the AWS SigV4 test
+    /// suite, which signs only `host` and `x-amz-date`.
+    #[test]
+    fn signs_the_aws_test_suite_request() {
+        let mut req = Request::new(
+            Method::GET,
+            "https://example.amazonaws.com/?Param2=value2&Param1=value1".to_string(),
+        );
+        let credentials = AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        };
+        let now = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+        sign_request(&mut req, &credentials, "us-east-1", "service", now);
+
+        assert_eq!(
+            req.headers
+                .get(http::header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok()),
+            Some(
+                "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, SignedHeaders=host;x-amz-date, Signature=b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"
+            )
+        );
+        assert_eq!(
+            req.headers
+                .get("x-amz-date")
+                .and_then(|value| value.to_str().ok()),
+            Some("20150830T123600Z")
+        );
+    }
+
+    #[test]
+    fn reads_the_region_from_runtime_endpoints() {
+        assert_eq!(
+            region_from_base_url("https://bedrock-runtime.eu-west-1.amazonaws.com"),
+            Some("eu-west-1".to_string())
+        );
+        assert_eq!(
+            region_from_base_url(
+                "https://vpce-0abc-1234.bedrock-runtime.us-east-2.vpce.amazonaws.com"
+            ),
+            Some("us-east-2".to_string())
+        );
+        assert_eq!(region_from_base_url("https://example.com"), None);
+        assert_eq!(
+            canonical_path("/model/a.b-v1%3A0/invoke"),
+            "/model/a.b-v1%253A0/invoke"
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use crate::auth::AuthProvider;
+use crate::common::Prompt as ApiPrompt;
+use crate::common::ResponseStream;
+use crate::endpoint::streaming::StreamingClient;
+use crate::error::ApiError;
+use crate::provider::Provider;
+use crate::provider::WireApi;
+use crate::requests::AnthropicRequest;
+use crate::requests::AnthropicRequestBuilder;
+use crate::sse::anthropic::spawn_anthropic_stream;
+use crate::sse::bedrock::spawn_bedrock_stream;
+use crate::telemetry::SseTelemetry;
+use codex_client::HttpTransport;
+use codex_client::RequestTelemetry;
+use codex_protocol::openai_models::ReasoningEffort;
+use http::HeaderMap;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Streams turns from an Anthropic Messages API endpoint, or from Anthropic
+/// models on Amazon Bedrock when the provider's wire is [`WireApi::Bedrock`].
+pub struct AnthropicClient<T: HttpTransport, A: AuthProvider> {
+    streaming: StreamingClient<T, A>,
+}
+
+/// Per-request knobs the Messages API needs beyond the prompt itself.
+#[derive(Default)]
+pub struct AnthropicOptions {
+    pub max_tokens: Option<u64>,
+    pub effort: Option<ReasoningEffort>,
+    pub conversation_id: Option<String>,
+    /// Adds prompt-cache breakpoints; see [`AnthropicRequestBuilder::cache_control`].
+    pub cache_control: bool,
+}
+
+impl<T: HttpTransport, A: AuthProvider> AnthropicClient<T, A> {
+    pub fn new(transport: T, provider: Provider, auth: A) -> Self {
+        Self {
+            streaming: StreamingClient::new(transport, provider, auth),
+        }
+    }
+
+    pub fn with_telemetry(
+        self,
+        request: Option<Arc<dyn RequestTelemetry>>,
+        sse: Option<Arc<dyn SseTelemetry>>,
+    ) -> Self {
+        Self {
+            streaming: self.streaming.with_telemetry(request, sse),
+        }
+    }
+
+    pub async fn stream_request(
+        &self,
+        request: AnthropicRequest,
+    ) -> Result<ResponseStream, ApiError> {
+        let spawner = match self.streaming.provider().wire {
+            WireApi::Bedrock => spawn_bedrock_stream,
+            _ => spawn_anthropic_stream,
+        };
+        self.streaming
+            .stream(&request.path, request.body, request.headers, spawner)
+            .await
+    }
+
+    pub async fn stream_prompt(
+        &self,
+        model: &str,
+        prompt: &ApiPrompt,
+        options: AnthropicOptions,
+    ) -> Result<ResponseStream, ApiError> {
+        let AnthropicOptions {
+            max_tokens,
+            effort,
+            conversation_id,
+            cache_control,
+        } = options;
+
+        let request =
+            AnthropicRequestBuilder::new(model, &prompt.instructions, &prompt.input, &prompt.tools)
+                .max_tokens(max_tokens)
+                .effort(effort)
+                .conversation_id(conversation_id)
+                .cache_control(cache_control)
+                .build(self.streaming.provider())?;
+
+        self.stream_request(request).await
+    }
+
+    /// Posts `body` to the Messages API as is.
+    pub async fn stream(
+        &self,
+        body: Value,
+        extra_headers: HeaderMap,
+    ) -> Result<ResponseStream, ApiError> {
+        self.streaming
+            .stream("messages", body, extra_headers, spawn_anthropic_stream)
+            .await
+    }
+}
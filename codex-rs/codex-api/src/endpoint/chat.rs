@@ -8,12 +8,15 @@ use crate::error::ApiError;
 use crate::provider::Provider;
 use crate::provider::WireApi;
 use crate::sse::chat::spawn_chat_stream;
+use crate::sse::chat::spawn_local_chat_stream;
+use crate::sse::tool_emulation::spawn_tool_call_emulation;
 use crate::telemetry::SseTelemetry;
 use codex_client::HttpTransport;
 use codex_client::RequestTelemetry;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::ReasoningItemContent;
 use codex_protocol::models::ResponseItem;
+use codex_protocol::openai_models::ReasoningEffort;
 use codex_protocol::protocol::SessionSource;
 use futures::Stream;
 use http::HeaderMap;
@@ -26,12 +29,18 @@ use std::task::Poll;
 
 pub struct ChatClient<T: HttpTransport, A: AuthProvider> {
     streaming: StreamingClient<T, A>,
+    local_quirks: bool,
+    emulate_tool_calls: bool,
+    reasoning_effort: Option<ReasoningEffort>,
 }
 
 impl<T: HttpTransport, A: AuthProvider> ChatClient<T, A> {
     pub fn new(transport: T, provider: Provider, auth: A) -> Self {
         Self {
             streaming: StreamingClient::new(transport, provider, auth),
+            local_quirks: false,
+            emulate_tool_calls: false,
+            reasoning_effort: None,
         }
     }
 
@@ -42,9 +51,30 @@ impl<T: HttpTransport, A: AuthProvider> ChatClient<T, A> {
     ) -> Self {
         Self {
             streaming: self.streaming.with_telemetry(request, sse),
+            ..self
         }
     }
 
+    /// Parses the stream leniently to cope with local OpenAI-compatible
+    /// servers that deviate from the Chat Completions format.
+    pub fn with_local_quirks(mut self, enabled: bool) -> Self {
+        self.local_quirks = enabled;
+        self
+    }
+
+    /// Emulates tool calling through structured JSON replies for models
+    /// served without native function-calling support.
+    pub fn with_tool_call_emulation(mut self, enabled: bool) -> Self {
+        self.emulate_tool_calls = enabled;
+        self
+    }
+
+    /// Asks reasoning models for `effort`; `None` leaves the parameter out.
+    pub fn with_reasoning_effort(mut self, effort: Option<ReasoningEffort>) -> Self {
+        self.reasoning_effort = effort;
+        self
+    }
+
     pub async fn stream_request(&self, request: ChatRequest) -> Result<ResponseStream, ApiError> {
         self.stream(request.body, request.headers).await
     }
@@ -62,6 +92,9 @@ impl<T: HttpTransport, A: AuthProvider> ChatClient<T, A> {
             ChatRequestBuilder::new(model, &prompt.instructions, &prompt.input, &prompt.tools)
                 .conversation_id(conversation_id)
                 .session_source(session_source)
+                .emulate_tool_calls(self.emulate_tool_calls)
+                .reasoning_effort(self.reasoning_effort)
+                .output_schema(prompt.output_schema.as_ref())
                 .build(self.streaming.provider())?;
 
         self.stream_request(request).await
@@ -79,9 +112,20 @@ impl<T: HttpTransport, A: AuthProvider> ChatClient<T, A> {
         body: Value,
         extra_headers: HeaderMap,
     ) -> Result<ResponseStream, ApiError> {
-        self.streaming
-            .stream(self.path(), body, extra_headers, spawn_chat_stream)
-            .await
+        let spawner = if self.local_quirks {
+            spawn_local_chat_stream
+        } else {
+            spawn_chat_stream
+        };
+        let stream = self
+            .streaming
+            .stream(self.path(), body, extra_headers, spawner)
+            .await?;
+        if self.emulate_tool_calls {
+            Ok(spawn_tool_call_emulation(stream))
+        } else {
+            Ok(stream)
+        }
     }
 }
 
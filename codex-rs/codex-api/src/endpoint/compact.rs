@@ -39,9 +39,9 @@ impl<T: HttpTransport, A: AuthProvider> CompactClient<T, A> {
     fn path(&self) -> Result<&'static str, ApiError> {
         match self.provider.wire {
             WireApi::Compact | WireApi::Responses => Ok("responses/compact"),
-            WireApi::Chat => Err(ApiError::Stream(
-                "compact endpoint requires responses wire api".to_string(),
-            )),
+            WireApi::Chat | WireApi::Anthropic | WireApi::Gemini | WireApi::Bedrock => Err(
+                ApiError::Stream("compact endpoint requires responses wire api".to_string()),
+            ),
         }
     }
 
@@ -134,6 +134,7 @@ mod tests {
                 retry_429: false,
                 retry_5xx: true,
                 retry_transport: true,
+                max_retry_time: None,
             },
             stream_idle_timeout: Duration::from_secs(1),
         }
@@ -0,0 +1,82 @@
+use crate::auth::AuthProvider;
+use crate::common::Prompt as ApiPrompt;
+use crate::common::ResponseStream;
+use crate::endpoint::streaming::StreamingClient;
+use crate::error::ApiError;
+use crate::provider::Provider;
+use crate::requests::GeminiRequest;
+use crate::requests::GeminiRequestBuilder;
+use crate::sse::gemini::spawn_gemini_stream;
+use crate::telemetry::SseTelemetry;
+use codex_client::HttpTransport;
+use codex_client::RequestTelemetry;
+use codex_protocol::openai_models::ReasoningEffort;
+use std::sync::Arc;
+
+/// Streams turns from the Gemini `streamGenerateContent` endpoint.
+pub struct GeminiClient<T: HttpTransport, A: AuthProvider> {
+    streaming: StreamingClient<T, A>,
+}
+
+/// Per-request generation settings for Gemini.
+#[derive(Default)]
+pub struct GeminiOptions {
+    pub max_output_tokens: Option<u64>,
+    pub effort: Option<ReasoningEffort>,
+    /// `(category, threshold)` pairs passed through as `safetySettings`.
+    pub safety_settings: Vec<(String, String)>,
+    pub conversation_id: Option<String>,
+}
+
+impl<T: HttpTransport, A: AuthProvider> GeminiClient<T, A> {
+    pub fn new(transport: T, provider: Provider, auth: A) -> Self {
+        Self {
+            streaming: StreamingClient::new(transport, provider, auth),
+        }
+    }
+
+    pub fn with_telemetry(
+        self,
+        request: Option<Arc<dyn RequestTelemetry>>,
+        sse: Option<Arc<dyn SseTelemetry>>,
+    ) -> Self {
+        Self {
+            streaming: self.streaming.with_telemetry(request, sse),
+        }
+    }
+
+    pub async fn stream_request(&self, request: GeminiRequest) -> Result<ResponseStream, ApiError> {
+        self.streaming
+            .stream(
+                &request.path,
+                request.body,
+                request.headers,
+                spawn_gemini_stream,
+            )
+            .await
+    }
+
+    pub async fn stream_prompt(
+        &self,
+        model: &str,
+        prompt: &ApiPrompt,
+        options: GeminiOptions,
+    ) -> Result<ResponseStream, ApiError> {
+        let GeminiOptions {
+            max_output_tokens,
+            effort,
+            safety_settings,
+            conversation_id,
+        } = options;
+
+        let request =
+            GeminiRequestBuilder::new(model, &prompt.instructions, &prompt.input, &prompt.tools)
+                .max_output_tokens(max_output_tokens)
+                .effort(effort)
+                .safety_settings(safety_settings)
+                .conversation_id(conversation_id)
+                .build()?;
+
+        self.stream_request(request).await
+    }
+}
@@ -1,5 +1,7 @@
+pub mod anthropic;
 pub mod chat;
 pub mod compact;
+pub mod gemini;
 pub mod models;
 pub mod responses;
 mod streaming;
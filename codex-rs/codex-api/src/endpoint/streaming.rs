@@ -1,10 +1,17 @@
 use crate::auth::AuthProvider;
+use crate::auth::add_api_key_header;
 use crate::auth::add_auth_headers;
+use crate::aws::AwsCredentials;
+use crate::aws::BEDROCK_SERVICE;
+use crate::aws::region_from_base_url;
+use crate::aws::sign_request;
 use crate::common::ResponseStream;
 use crate::error::ApiError;
 use crate::provider::Provider;
+use crate::provider::WireApi;
 use crate::telemetry::SseTelemetry;
 use crate::telemetry::run_with_request_telemetry;
+use chrono::Utc;
 use codex_client::HttpTransport;
 use codex_client::RequestTelemetry;
 use codex_client::StreamResponse;
@@ -54,15 +61,35 @@ impl<T: HttpTransport, A: AuthProvider> StreamingClient<T, A> {
         extra_headers: HeaderMap,
         spawner: fn(StreamResponse, Duration, Option<Arc<dyn SseTelemetry>>) -> ResponseStream,
     ) -> Result<ResponseStream, ApiError> {
+        let (signing, accept) = match self.provider.wire {
+            WireApi::Bedrock => (
+                self.bedrock_signing()?,
+                "application/vnd.amazon.eventstream",
+            ),
+            _ => (None, "text/event-stream"),
+        };
         let builder = || {
             let mut req = self.provider.build_request(Method::POST, path);
             req.headers.extend(extra_headers.clone());
-            req.headers.insert(
-                http::header::ACCEPT,
-                http::HeaderValue::from_static("text/event-stream"),
-            );
+            req.headers
+                .insert(http::header::ACCEPT, http::HeaderValue::from_static(accept));
             req.body = Some(body.clone());
-            add_auth_headers(&self.auth, req)
+            match self.provider.wire {
+                WireApi::Anthropic => add_api_key_header(&self.auth, req, "x-api-key"),
+                WireApi::Gemini => add_api_key_header(&self.auth, req, "x-goog-api-key"),
+                // Signed per attempt so retries carry a fresh timestamp.
+                WireApi::Bedrock => match &signing {
+                    Some((credentials, region)) => {
+                        let mut req = req;
+                        sign_request(&mut req, credentials, region, BEDROCK_SERVICE, Utc::now());
+                        req
+                    }
+                    None => add_auth_headers(&self.auth, req),
+                },
+                WireApi::Responses | WireApi::Chat | WireApi::Compact => {
+                    add_auth_headers(&self.auth, req)
+                }
+            }
         };
 
         let stream_response = run_with_request_telemetry(
@@ -79,4 +106,19 @@ impl<T: HttpTransport, A: AuthProvider> StreamingClient<T, A> {
             self.sse_telemetry.clone(),
         ))
     }
+
+    /// Credentials and region to sign Bedrock requests with, or `None` when
+    /// the provider authenticates with a Bedrock API key instead.
+    fn bedrock_signing(&self) -> Result<Option<(AwsCredentials, String)>, ApiError> {
+        let Some(credentials) = self.auth.aws_credentials() else {
+            return Ok(None);
+        };
+        let region = region_from_base_url(&self.provider.base_url).ok_or_else(|| {
+            ApiError::Stream(format!(
+                "cannot tell the AWS region from the Bedrock base_url {}",
+                self.provider.base_url
+            ))
+        })?;
+        Ok(Some((credentials, region)))
+    }
 }
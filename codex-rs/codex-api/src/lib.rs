@@ -1,4 +1,5 @@
 pub mod auth;
+pub mod aws;
 pub mod common;
 pub mod endpoint;
 pub mod error;
@@ -8,28 +9,40 @@ pub mod requests;
 pub mod sse;
 pub mod telemetry;
 
+pub use codex_client::Cassette;
+pub use codex_client::CassetteMode;
+pub use codex_client::CassetteTransport;
 pub use codex_client::RequestTelemetry;
 pub use codex_client::ReqwestTransport;
 pub use codex_client::TransportError;
 
 pub use crate::auth::AuthProvider;
+pub use crate::aws::AwsCredentials;
 pub use crate::common::CompactionInput;
 pub use crate::common::Prompt;
 pub use crate::common::ResponseEvent;
 pub use crate::common::ResponseStream;
 pub use crate::common::ResponsesApiRequest;
 pub use crate::common::create_text_param_for_request;
+pub use crate::endpoint::anthropic::AnthropicClient;
+pub use crate::endpoint::anthropic::AnthropicOptions;
 pub use crate::endpoint::chat::AggregateStreamExt;
 pub use crate::endpoint::chat::ChatClient;
 pub use crate::endpoint::compact::CompactClient;
+pub use crate::endpoint::gemini::GeminiClient;
+pub use crate::endpoint::gemini::GeminiOptions;
 pub use crate::endpoint::models::ModelsClient;
 pub use crate::endpoint::responses::ResponsesClient;
 pub use crate::endpoint::responses::ResponsesOptions;
 pub use crate::error::ApiError;
 pub use crate::provider::Provider;
 pub use crate::provider::WireApi;
+pub use crate::requests::AnthropicRequest;
+pub use crate::requests::AnthropicRequestBuilder;
 pub use crate::requests::ChatRequest;
 pub use crate::requests::ChatRequestBuilder;
+pub use crate::requests::GeminiRequest;
+pub use crate::requests::GeminiRequestBuilder;
 pub use crate::requests::ResponsesRequest;
 pub use crate::requests::ResponsesRequestBuilder;
 pub use crate::sse::stream_from_fixture;
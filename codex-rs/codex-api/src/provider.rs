@@ -12,6 +12,13 @@ pub enum WireApi {
     Responses,
     Chat,
     Compact,
+    /// Anthropic Messages API (`/v1/messages`).
+    Anthropic,
+    /// Gemini API (`models/{model}:streamGenerateContent`).
+    Gemini,
+    /// Anthropic models on Amazon Bedrock
+    /// (`model/{model}/invoke-with-response-stream`), signed with SigV4.
+    Bedrock,
 }
 
 /// High-level retry configuration for a provider.
@@ -25,8 +32,18 @@ pub struct RetryConfig {
     pub retry_429: bool,
     pub retry_5xx: bool,
     pub retry_transport: bool,
+    /// Cap on the total time spent waiting between attempts.
+    pub max_retry_time: Option<Duration>,
 }
 
+/// Error codes reported with a retryable status that no amount of waiting
+/// will fix.
+const TERMINAL_ERROR_CODES: &[&str] = &[
+    "insufficient_quota",
+    "usage_limit_reached",
+    "usage_not_included",
+];
+
 impl RetryConfig {
     pub fn to_policy(&self) -> RetryPolicy {
         RetryPolicy {
@@ -36,7 +53,12 @@ impl RetryConfig {
                 retry_429: self.retry_429,
                 retry_5xx: self.retry_5xx,
                 retry_transport: self.retry_transport,
+                terminal_markers: TERMINAL_ERROR_CODES
+                    .iter()
+                    .map(|code| (*code).to_string())
+                    .collect(),
             },
+            max_retry_time: self.max_retry_time,
         }
     }
 }
@@ -74,7 +96,7 @@ impl Provider {
                 .map(|(k, v)| format!("{k}={v}"))
                 .collect::<Vec<_>>()
                 .join("&");
-            url.push('?');
+            url.push(if url.contains('?') { '&' } else { '?' });
             url.push_str(&qs);
         }
 
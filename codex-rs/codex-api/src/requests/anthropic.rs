@@ -0,0 +1,583 @@
+use crate::aws::encode_path_segment;
+use crate::error::ApiError;
+use crate::provider::Provider;
+use crate::provider::WireApi;
+use crate::requests::headers::build_conversation_headers;
+use crate::requests::headers::insert_header;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::FunctionCallOutputContentItem;
+use codex_protocol::models::ReasoningItemContent;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::openai_models::ReasoningEffort;
+use http::HeaderMap;
+use serde_json::Map;
+use serde_json::Value;
+use serde_json::json;
+
+/// Used when neither the caller nor the config sets `max_tokens`, which the
+/// Messages API requires.
+pub const DEFAULT_ANTHROPIC_MAX_TOKENS: u64 = 16_384;
+const ANTHROPIC_VERSION_HEADER: &str = "anthropic-version";
+/// Sent unless the provider config pins a version through its own headers.
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+/// Bedrock takes the version in the body and the model in the path.
+const BEDROCK_ANTHROPIC_VERSION: &str = "bedrock-2023-05-31";
+/// Smallest thinking budget the Messages API accepts.
+const MIN_THINKING_BUDGET: u64 = 1_024;
+/// Number of trailing user messages that get a cache breakpoint. Together with
+/// the tools and system breakpoints this stays within the API's limit of four.
+const CACHED_USER_MESSAGES: usize = 2;
+
+/// Assembled request body plus headers for Anthropic Messages streaming calls.
+pub struct AnthropicRequest {
+    /// Endpoint path relative to the provider's base URL.
+    pub path: String,
+    pub body: Value,
+    pub headers: HeaderMap,
+}
+
+pub struct AnthropicRequestBuilder<'a> {
+    model: &'a str,
+    instructions: &'a str,
+    input: &'a [ResponseItem],
+    tools: &'a [Value],
+    max_tokens: Option<u64>,
+    effort: Option<ReasoningEffort>,
+    conversation_id: Option<String>,
+    cache_control: bool,
+}
+
+impl<'a> AnthropicRequestBuilder<'a> {
+    /// `tools` are in the Chat Completions shape, as produced for
+    /// `ChatRequestBuilder`; they are converted here.
+    pub fn new(
+        model: &'a str,
+        instructions: &'a str,
+        input: &'a [ResponseItem],
+        tools: &'a [Value],
+    ) -> Self {
+        Self {
+            model,
+            instructions,
+            input,
+            tools,
+            max_tokens: None,
+            effort: None,
+            conversation_id: None,
+            cache_control: false,
+        }
+    }
+
+    pub fn max_tokens(mut self, max_tokens: Option<u64>) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Enables extended thinking with a budget derived from the effort.
+    pub fn effort(mut self, effort: Option<ReasoningEffort>) -> Self {
+        self.effort = effort;
+        self
+    }
+
+    pub fn conversation_id(mut self, id: Option<String>) -> Self {
+        self.conversation_id = id;
+        self
+    }
+
+    /// Marks `cache_control` breakpoints on the tools, the system prompt and
+    /// the last user messages so the stable prefix is served from the prompt
+    /// cache on later requests.
+    pub fn cache_control(mut self, enabled: bool) -> Self {
+        self.cache_control = enabled;
+        self
+    }
+
+    pub fn build(self, provider: &Provider) -> Result<AnthropicRequest, ApiError> {
+        let max_tokens = self.max_tokens.unwrap_or(DEFAULT_ANTHROPIC_MAX_TOKENS);
+        let mut messages = build_messages(self.input);
+        let mut tools: Vec<Value> = self.tools.iter().filter_map(convert_tool).collect();
+        if self.cache_control {
+            if let Some(tool) = tools.last_mut() {
+                mark_cache_breakpoint(tool);
+            }
+            messages
+                .iter_mut()
+                .rev()
+                .filter(|message| message["role"] == "user")
+                .take(CACHED_USER_MESSAGES)
+                .filter_map(|message| message["content"].as_array_mut()?.last_mut())
+                .for_each(mark_cache_breakpoint);
+        }
+        let mut body = json!({
+            "model": self.model,
+            "max_tokens": max_tokens,
+            "messages": messages,
+            "stream": true,
+        });
+        let Some(object) = body.as_object_mut() else {
+            return Err(ApiError::Stream(
+                "request body is not an object".to_string(),
+            ));
+        };
+        if !self.instructions.is_empty() {
+            let system = if self.cache_control {
+                let mut block = json!({"type": "text", "text": self.instructions});
+                mark_cache_breakpoint(&mut block);
+                json!([block])
+            } else {
+                json!(self.instructions)
+            };
+            object.insert("system".to_string(), system);
+        }
+        if !tools.is_empty() {
+            object.insert("tools".to_string(), json!(tools));
+        }
+        if let Some(budget) = self
+            .effort
+            .and_then(|effort| thinking_budget(effort, max_tokens))
+        {
+            object.insert(
+                "thinking".to_string(),
+                json!({"type": "enabled", "budget_tokens": budget}),
+            );
+        }
+
+        let mut headers = build_conversation_headers(self.conversation_id);
+        let path = if provider.wire == WireApi::Bedrock {
+            object.remove("model");
+            object.remove("stream");
+            object.insert(
+                "anthropic_version".to_string(),
+                json!(BEDROCK_ANTHROPIC_VERSION),
+            );
+            format!(
+                "model/{}/invoke-with-response-stream",
+                encode_path_segment(self.model)
+            )
+        } else {
+            if !provider.headers.contains_key(ANTHROPIC_VERSION_HEADER) {
+                insert_header(&mut headers, ANTHROPIC_VERSION_HEADER, ANTHROPIC_VERSION);
+            }
+            "messages".to_string()
+        };
+
+        Ok(AnthropicRequest {
+            path,
+            body,
+            headers,
+        })
+    }
+}
+
+fn mark_cache_breakpoint(block: &mut Value) {
+    if let Some(object) = block.as_object_mut() {
+        object.insert("cache_control".to_string(), json!({"type": "ephemeral"}));
+    }
+}
+
+/// Thinking-token budget for providers that size reasoning in tokens rather
+/// than by effort; `None` and `Minimal` turn thinking off.
+pub(crate) fn effort_thinking_budget(effort: ReasoningEffort) -> Option<u64> {
+    match effort {
+        ReasoningEffort::None | ReasoningEffort::Minimal => None,
+        ReasoningEffort::Low => Some(2_048),
+        ReasoningEffort::Medium => Some(4_096),
+        ReasoningEffort::High => Some(8_192),
+        ReasoningEffort::XHigh => Some(12_288),
+    }
+}
+
+/// Budgets stay below `max_tokens`, as the API requires; efforts that leave
+/// less than the minimum budget disable thinking.
+fn thinking_budget(effort: ReasoningEffort, max_tokens: u64) -> Option<u64> {
+    let budget = effort_thinking_budget(effort)?.min(max_tokens / 2);
+    (budget >= MIN_THINKING_BUDGET).then_some(budget)
+}
+
+/// `{"type":"function","function":{name,description,parameters}}` to
+/// `{name,description,input_schema}`.
+fn convert_tool(tool: &Value) -> Option<Value> {
+    let function = tool.get("function")?;
+    let name = function.get("name")?.as_str()?;
+    let mut converted = json!({
+        "name": name,
+        "input_schema": function
+            .get("parameters")
+            .cloned()
+            .unwrap_or_else(|| json!({"type": "object", "properties": {}})),
+    });
+    if let Some(description) = function.get("description").and_then(Value::as_str)
+        && let Some(object) = converted.as_object_mut()
+    {
+        object.insert("description".to_string(), json!(description));
+    }
+    Some(converted)
+}
+
+/// Folds the history into alternating user and assistant messages: tool
+/// calls and thinking join the assistant turn that produced them, and tool
+/// results open the following user turn.
+fn build_messages(input: &[ResponseItem]) -> Vec<Value> {
+    let mut messages: Vec<(&'static str, Vec<Value>)> = Vec::new();
+    let mut push = |role: &'static str, blocks: Vec<Value>| {
+        if blocks.is_empty() {
+            return;
+        }
+        match messages.last_mut() {
+            Some((last_role, content)) if *last_role == role => content.extend(blocks),
+            _ => messages.push((role, blocks)),
+        }
+    };
+
+    for item in input {
+        match item {
+            ResponseItem::Message { role, content, .. } => {
+                let role = if role == "assistant" {
+                    "assistant"
+                } else {
+                    "user"
+                };
+                let blocks = content
+                    .iter()
+                    .filter_map(|item| match item {
+                        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                            text_block(text)
+                        }
+                        ContentItem::InputImage { image_url } => Some(image_block(image_url)),
+                    })
+                    .collect();
+                push(role, blocks);
+            }
+            ResponseItem::Reasoning {
+                content,
+                encrypted_content: Some(signature),
+                ..
+            } => {
+                // Thinking is only replayed with the signature the API
+                // issued for it; redacted thinking has no text at all.
+                let block = match content {
+                    Some(content) => {
+                        let thinking: String = content
+                            .iter()
+                            .map(|entry| match entry {
+                                ReasoningItemContent::ReasoningText { text }
+                                | ReasoningItemContent::Text { text } => text.as_str(),
+                            })
+                            .collect();
+                        json!({"type": "thinking", "thinking": thinking, "signature": signature})
+                    }
+                    None => json!({"type": "redacted_thinking", "data": signature}),
+                };
+                push("assistant", vec![block]);
+            }
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => {
+                let input = match serde_json::from_str::<Value>(arguments) {
+                    Ok(Value::Object(object)) => Value::Object(object),
+                    _ => json!({}),
+                };
+                push(
+                    "assistant",
+                    vec![json!({"type": "tool_use", "id": call_id, "name": name, "input": input})],
+                );
+            }
+            ResponseItem::CustomToolCall {
+                call_id,
+                name,
+                input,
+                ..
+            } => push(
+                "assistant",
+                vec![json!({
+                    "type": "tool_use",
+                    "id": call_id,
+                    "name": name,
+                    "input": {"input": input},
+                })],
+            ),
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                let content = match &output.content_items {
+                    Some(items) => Value::Array(
+                        items
+                            .iter()
+                            .filter_map(|item| match item {
+                                FunctionCallOutputContentItem::InputText { text } => {
+                                    text_block(text)
+                                }
+                                FunctionCallOutputContentItem::InputImage { image_url } => {
+                                    Some(image_block(image_url))
+                                }
+                            })
+                            .collect(),
+                    ),
+                    None => json!(output.content),
+                };
+                let mut block = Map::new();
+                block.insert("type".to_string(), json!("tool_result"));
+                block.insert("tool_use_id".to_string(), json!(call_id));
+                block.insert("content".to_string(), content);
+                if output.success == Some(false) {
+                    block.insert("is_error".to_string(), json!(true));
+                }
+                push("user", vec![Value::Object(block)]);
+            }
+            ResponseItem::CustomToolCallOutput { call_id, output } => push(
+                "user",
+                vec![json!({"type": "tool_result", "tool_use_id": call_id, "content": output})],
+            ),
+            ResponseItem::Reasoning { .. }
+            | ResponseItem::LocalShellCall { .. }
+            | ResponseItem::WebSearchCall { .. }
+            | ResponseItem::GhostSnapshot { .. }
+            | ResponseItem::Compaction { .. }
+            | ResponseItem::Other => {}
+        }
+    }
+
+    messages
+        .into_iter()
+        .map(|(role, content)| json!({"role": role, "content": content}))
+        .collect()
+}
+
+/// The API rejects empty text blocks.
+fn text_block(text: &str) -> Option<Value> {
+    (!text.is_empty()).then(|| json!({"type": "text", "text": text}))
+}
+
+fn image_block(image_url: &str) -> Value {
+    if let Some(rest) = image_url.strip_prefix("data:")
+        && let Some((media_type, data)) = rest.split_once(";base64,")
+    {
+        return json!({
+            "type": "image",
+            "source": {"type": "base64", "media_type": media_type, "data": data},
+        });
+    }
+    json!({"type": "image", "source": {"type": "url", "url": image_url}})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::RetryConfig;
+    use crate::provider::WireApi;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    fn provider() -> Provider {
+        Provider {
+            name: "anthropic".to_string(),
+            base_url: "https://api.anthropic.com/v1".to_string(),
+            query_params: None,
+            wire: WireApi::Anthropic,
+            headers: HeaderMap::new(),
+            retry: RetryConfig {
+                max_attempts: 1,
+                base_delay: Duration::from_millis(10),
+                retry_429: false,
+                retry_5xx: true,
+                retry_transport: true,
+                max_retry_time: None,
+            },
+            stream_idle_timeout: Duration::from_secs(1),
+        }
+    }
+
+    fn message(role: &str, content: ContentItem) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![content],
+        }
+    }
+
+    #[test]
+    fn maps_history_tools_and_thinking() {
+        let input = vec![
+            message(
+                "user",
+                ContentItem::InputText {
+                    text: "list files".to_string(),
+                },
+            ),
+            ResponseItem::Reasoning {
+                id: String::new(),
+                summary: Vec::new(),
+                content: Some(vec![ReasoningItemContent::ReasoningText {
+                    text: "Use ls.".to_string(),
+                }]),
+                encrypted_content: Some("sig".to_string()),
+            },
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: r#"{"command":["ls"]}"#.to_string(),
+                call_id: "toolu_1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "toolu_1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "permission denied".to_string(),
+                    content_items: None,
+                    success: Some(false),
+                },
+            },
+            message(
+                "user",
+                ContentItem::InputImage {
+                    image_url: "data:image/png;base64,AAAA".to_string(),
+                },
+            ),
+        ];
+        let tools = vec![json!({
+            "type": "function",
+            "name": "shell",
+            "function": {
+                "name": "shell",
+                "description": "Runs a command",
+                "parameters": {"type": "object", "properties": {}},
+            },
+        })];
+
+        let request = AnthropicRequestBuilder::new("claude-test", "be brief", &input, &tools)
+            .max_tokens(Some(8_192))
+            .effort(Some(ReasoningEffort::High))
+            .build(&provider())
+            .expect("request");
+
+        assert_eq!(
+            request.body,
+            json!({
+                "model": "claude-test",
+                "max_tokens": 8192,
+                "stream": true,
+                "system": "be brief",
+                "thinking": {"type": "enabled", "budget_tokens": 4096},
+                "tools": [{
+                    "name": "shell",
+                    "description": "Runs a command",
+                    "input_schema": {"type": "object", "properties": {}},
+                }],
+                "messages": [
+                    {"role": "user", "content": [{"type": "text", "text": "list files"}]},
+                    {"role": "assistant", "content": [
+                        {"type": "thinking", "thinking": "Use ls.", "signature": "sig"},
+                        {"type": "tool_use", "id": "toolu_1", "name": "shell", "input": {"command": ["ls"]}},
+                    ]},
+                    {"role": "user", "content": [
+                        {"type": "tool_result", "tool_use_id": "toolu_1", "content": "permission denied", "is_error": true},
+                        {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "AAAA"}},
+                    ]},
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn cache_control_marks_tools_system_and_last_user_messages() {
+        let input = vec![
+            message(
+                "user",
+                ContentItem::InputText {
+                    text: "first".to_string(),
+                },
+            ),
+            message(
+                "assistant",
+                ContentItem::OutputText {
+                    text: "ok".to_string(),
+                },
+            ),
+            message(
+                "user",
+                ContentItem::InputText {
+                    text: "second".to_string(),
+                },
+            ),
+            message(
+                "assistant",
+                ContentItem::OutputText {
+                    text: "done".to_string(),
+                },
+            ),
+            message(
+                "user",
+                ContentItem::InputText {
+                    text: "third".to_string(),
+                },
+            ),
+        ];
+        let tools = vec![
+            json!({"type": "function", "function": {"name": "a"}}),
+            json!({"type": "function", "function": {"name": "b"}}),
+        ];
+
+        let request = AnthropicRequestBuilder::new("claude-test", "be brief", &input, &tools)
+            .cache_control(true)
+            .build(&provider())
+            .expect("request");
+
+        let ephemeral = json!({"type": "ephemeral"});
+        let body = &request.body;
+        assert_eq!(
+            body["system"],
+            json!([{"type": "text", "text": "be brief", "cache_control": ephemeral}])
+        );
+        assert_eq!(body["tools"][0].get("cache_control"), None);
+        assert_eq!(body["tools"][1]["cache_control"], ephemeral);
+        let marked: Vec<bool> = body["messages"]
+            .as_array()
+            .expect("messages")
+            .iter()
+            .map(|message| message["content"][0].get("cache_control").is_some())
+            .collect();
+        assert_eq!(marked, vec![false, false, true, false, true]);
+    }
+
+    #[test]
+    fn thinking_budget_stays_below_max_tokens() {
+        assert_eq!(thinking_budget(ReasoningEffort::XHigh, 16_384), Some(8_192));
+        assert_eq!(thinking_budget(ReasoningEffort::Low, 1_500), None);
+        assert_eq!(thinking_budget(ReasoningEffort::Minimal, 16_384), None);
+    }
+
+    #[test]
+    fn bedrock_moves_the_model_into_the_path() {
+        let mut provider = provider();
+        provider.wire = WireApi::Bedrock;
+        let input = vec![message(
+            "user",
+            ContentItem::InputText {
+                text: "hi".to_string(),
+            },
+        )];
+        let request = AnthropicRequestBuilder::new(
+            "us.anthropic.claude-sonnet-4-5-20250929-v1:0",
+            "",
+            &input,
+            &[],
+        )
+        .build(&provider)
+        .expect("request");
+
+        assert_eq!(
+            request.path,
+            "model/us.anthropic.claude-sonnet-4-5-20250929-v1%3A0/invoke-with-response-stream"
+        );
+        assert_eq!(
+            request.body,
+            json!({
+                "anthropic_version": "bedrock-2023-05-31",
+                "max_tokens": DEFAULT_ANTHROPIC_MAX_TOKENS,
+                "messages": [{"role": "user", "content": [{"type": "text", "text": "hi"}]}],
+            })
+        );
+        assert!(request.headers.get(ANTHROPIC_VERSION_HEADER).is_none());
+    }
+}
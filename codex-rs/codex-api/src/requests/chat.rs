@@ -3,10 +3,12 @@ use crate::provider::Provider;
 use crate::requests::headers::build_conversation_headers;
 use crate::requests::headers::insert_header;
 use crate::requests::headers::subagent_header;
+use crate::requests::tool_emulation::apply_tool_call_emulation;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::FunctionCallOutputContentItem;
 use codex_protocol::models::ReasoningItemContent;
 use codex_protocol::models::ResponseItem;
+use codex_protocol::openai_models::ReasoningEffort;
 use codex_protocol::protocol::SessionSource;
 use http::HeaderMap;
 use serde_json::Value;
@@ -26,6 +28,9 @@ pub struct ChatRequestBuilder<'a> {
     tools: &'a [Value],
     conversation_id: Option<String>,
     session_source: Option<SessionSource>,
+    emulate_tool_calls: bool,
+    reasoning_effort: Option<ReasoningEffort>,
+    output_schema: Option<&'a Value>,
 }
 
 impl<'a> ChatRequestBuilder<'a> {
@@ -42,6 +47,9 @@ impl<'a> ChatRequestBuilder<'a> {
             tools,
             conversation_id: None,
             session_source: None,
+            emulate_tool_calls: false,
+            reasoning_effort: None,
+            output_schema: None,
         }
     }
 
@@ -55,6 +63,28 @@ impl<'a> ChatRequestBuilder<'a> {
         self
     }
 
+    /// Describes the tools in the prompt and asks for a JSON reply instead of
+    /// sending native `tools`; see [`crate::sse::tool_emulation`] for the
+    /// matching stream adapter.
+    pub fn emulate_tool_calls(mut self, enabled: bool) -> Self {
+        self.emulate_tool_calls = enabled;
+        self
+    }
+
+    /// Sends `reasoning_effort`, which reasoning models behind the Chat
+    /// Completions API accept.
+    pub fn reasoning_effort(mut self, effort: Option<ReasoningEffort>) -> Self {
+        self.reasoning_effort = effort;
+        self
+    }
+
+    /// Asks for a final answer matching `schema` through `response_format`.
+    /// Tool-call emulation replaces it with its own format.
+    pub fn output_schema(mut self, schema: Option<&'a Value>) -> Self {
+        self.output_schema = schema;
+        self
+    }
+
     pub fn build(self, _provider: &Provider) -> Result<ChatRequest, ApiError> {
         let mut messages = Vec::<Value>::new();
         messages.push(json!({"role": "system", "content": self.instructions}));
@@ -148,8 +178,17 @@ impl<'a> ChatRequestBuilder<'a> {
         }
 
         let mut last_assistant_text: Option<String> = None;
+        // Image parts from tool outputs, held back until the run of `tool`
+        // messages answering one assistant turn is complete.
+        let mut tool_images: Vec<Value> = Vec::new();
 
         for (idx, item) in input.iter().enumerate() {
+            if !matches!(
+                item,
+                ResponseItem::FunctionCallOutput { .. } | ResponseItem::CustomToolCallOutput { .. }
+            ) {
+                flush_tool_images(&mut messages, &mut tool_images);
+            }
             match item {
                 ResponseItem::Message { role, content, .. } => {
                     let mut text = String::new();
@@ -247,19 +286,31 @@ impl<'a> ChatRequestBuilder<'a> {
                     messages.push(msg);
                 }
                 ResponseItem::FunctionCallOutput { call_id, output } => {
+                    // `tool` messages only take text, so images travel in a
+                    // user message after the tool results.
                     let content_value = if let Some(items) = &output.content_items {
-                        let mapped: Vec<Value> = items
-                            .iter()
-                            .map(|it| match it {
+                        let mut texts: Vec<&str> = Vec::new();
+                        let mut images = Vec::new();
+                        for item in items {
+                            match item {
                                 FunctionCallOutputContentItem::InputText { text } => {
-                                    json!({"type":"text","text": text})
+                                    texts.push(text)
                                 }
-                                FunctionCallOutputContentItem::InputImage { image_url } => {
-                                    json!({"type":"image_url","image_url": {"url": image_url}})
-                                }
-                            })
-                            .collect();
-                        json!(mapped)
+                                FunctionCallOutputContentItem::InputImage { image_url } => images
+                                    .push(
+                                        json!({"type":"image_url","image_url": {"url": image_url}}),
+                                    ),
+                            }
+                        }
+                        if !images.is_empty() {
+                            tool_images.push(json!({
+                                "type": "text",
+                                "text": format!("Images returned by tool call {call_id}:"),
+                            }));
+                            tool_images.extend(images);
+                            texts.push("(image output attached in the next message)");
+                        }
+                        json!(texts.join("\n"))
                     } else {
                         json!(output.content)
                     };
@@ -309,12 +360,42 @@ impl<'a> ChatRequestBuilder<'a> {
             }
         }
 
-        let payload = json!({
+        flush_tool_images(&mut messages, &mut tool_images);
+
+        let mut payload = json!({
             "model": self.model,
             "messages": messages,
             "stream": true,
+            // Reports token usage, including cached prompt tokens, in a final chunk.
+            "stream_options": {"include_usage": true},
             "tools": self.tools,
         });
+        if let Some(effort) = self.reasoning_effort
+            && let Some(obj) = payload.as_object_mut()
+        {
+            obj.insert(
+                "reasoning_effort".to_string(),
+                Value::String(effort.to_string()),
+            );
+        }
+        if let Some(schema) = self.output_schema
+            && let Some(obj) = payload.as_object_mut()
+        {
+            obj.insert(
+                "response_format".to_string(),
+                json!({
+                    "type": "json_schema",
+                    "json_schema": {
+                        "name": "codex_output_schema",
+                        "strict": true,
+                        "schema": schema,
+                    },
+                }),
+            );
+        }
+        if self.emulate_tool_calls {
+            apply_tool_call_emulation(&mut payload, self.tools);
+        }
 
         let mut headers = build_conversation_headers(self.conversation_id);
         if let Some(subagent) = subagent_header(&self.session_source) {
@@ -328,11 +409,18 @@ impl<'a> ChatRequestBuilder<'a> {
     }
 }
 
+fn flush_tool_images(messages: &mut Vec<Value>, tool_images: &mut Vec<Value>) {
+    if !tool_images.is_empty() {
+        messages.push(json!({"role": "user", "content": std::mem::take(tool_images)}));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::provider::RetryConfig;
     use crate::provider::WireApi;
+    use codex_protocol::models::FunctionCallOutputPayload;
     use codex_protocol::protocol::SessionSource;
     use codex_protocol::protocol::SubAgentSource;
     use http::HeaderValue;
@@ -352,6 +440,7 @@ mod tests {
                 retry_429: false,
                 retry_5xx: true,
                 retry_transport: true,
+                max_retry_time: None,
             },
             stream_idle_timeout: Duration::from_secs(1),
         }
@@ -385,4 +474,112 @@ mod tests {
             Some(&HeaderValue::from_static("review"))
         );
     }
+
+    #[test]
+    fn sends_reasoning_effort_only_when_set() {
+        let input = vec![ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: "hi".to_string(),
+            }],
+        }];
+        let with_effort = ChatRequestBuilder::new("o4-mini", "inst", &input, &[])
+            .reasoning_effort(Some(ReasoningEffort::High))
+            .build(&provider())
+            .expect("request");
+        assert_eq!(with_effort.body["reasoning_effort"], "high");
+
+        let without = ChatRequestBuilder::new("o4-mini", "inst", &input, &[])
+            .build(&provider())
+            .expect("request");
+        assert_eq!(without.body.get("reasoning_effort"), None);
+    }
+
+    #[test]
+    fn output_schema_is_sent_as_response_format() {
+        let input = vec![ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: "hi".to_string(),
+            }],
+        }];
+        let schema = json!({"type": "object", "properties": {"answer": {"type": "string"}}});
+        let req = ChatRequestBuilder::new("gpt-4o", "inst", &input, &[])
+            .output_schema(Some(&schema))
+            .build(&provider())
+            .expect("request");
+
+        assert_eq!(
+            req.body["response_format"],
+            json!({
+                "type": "json_schema",
+                "json_schema": {
+                    "name": "codex_output_schema",
+                    "strict": true,
+                    "schema": schema,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn moves_tool_output_images_after_the_tool_messages() {
+        let call = |call_id: &str| ResponseItem::FunctionCall {
+            id: None,
+            name: "screenshot".to_string(),
+            arguments: "{}".to_string(),
+            call_id: call_id.to_string(),
+        };
+        let output = |call_id: &str, content_items| ResponseItem::FunctionCallOutput {
+            call_id: call_id.to_string(),
+            output: FunctionCallOutputPayload {
+                content: String::new(),
+                content_items,
+                success: Some(true),
+            },
+        };
+        let input = vec![
+            call("call-1"),
+            call("call-2"),
+            output(
+                "call-1",
+                Some(vec![
+                    FunctionCallOutputContentItem::InputText {
+                        text: "captured".to_string(),
+                    },
+                    FunctionCallOutputContentItem::InputImage {
+                        image_url: "data:image/png;base64,AAA".to_string(),
+                    },
+                ]),
+            ),
+            output(
+                "call-2",
+                Some(vec![FunctionCallOutputContentItem::InputText {
+                    text: "done".to_string(),
+                }]),
+            ),
+        ];
+
+        let req = ChatRequestBuilder::new("gpt-test", "inst", &input, &[])
+            .build(&provider())
+            .expect("request");
+        let messages = req.body["messages"].as_array().expect("messages");
+        assert_eq!(
+            messages[3..],
+            [
+                json!({
+                    "role": "tool",
+                    "tool_call_id": "call-1",
+                    "content": "captured\n(image output attached in the next message)",
+                }),
+                json!({"role": "tool", "tool_call_id": "call-2", "content": "done"}),
+                json!({"role": "user", "content": [
+                    {"type": "text", "text": "Images returned by tool call call-1:"},
+                    {"type": "image_url", "image_url": {"url": "data:image/png;base64,AAA"}},
+                ]}),
+            ]
+        );
+    }
 }
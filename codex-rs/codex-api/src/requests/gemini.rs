@@ -0,0 +1,467 @@
+use crate::error::ApiError;
+use crate::requests::anthropic::effort_thinking_budget;
+use crate::requests::headers::build_conversation_headers;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::FunctionCallOutputContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::openai_models::ReasoningEffort;
+use http::HeaderMap;
+use serde_json::Map;
+use serde_json::Value;
+use serde_json::json;
+use std::collections::HashMap;
+
+/// JSON Schema keywords Gemini's OpenAPI-style schema accepts; everything
+/// else is dropped during translation.
+const SCHEMA_KEYWORDS: &[&str] = &[
+    "type",
+    "format",
+    "description",
+    "nullable",
+    "enum",
+    "items",
+    "properties",
+    "required",
+    "minItems",
+    "maxItems",
+    "minimum",
+    "maximum",
+    "anyOf",
+];
+
+/// Assembled request body plus headers for Gemini streaming calls.
+pub struct GeminiRequest {
+    pub path: String,
+    pub body: Value,
+    pub headers: HeaderMap,
+}
+
+pub struct GeminiRequestBuilder<'a> {
+    model: &'a str,
+    instructions: &'a str,
+    input: &'a [ResponseItem],
+    tools: &'a [Value],
+    max_output_tokens: Option<u64>,
+    effort: Option<ReasoningEffort>,
+    safety_settings: Vec<(String, String)>,
+    conversation_id: Option<String>,
+}
+
+impl<'a> GeminiRequestBuilder<'a> {
+    /// `tools` are in the Chat Completions shape, as produced for
+    /// `ChatRequestBuilder`; they are converted here.
+    pub fn new(
+        model: &'a str,
+        instructions: &'a str,
+        input: &'a [ResponseItem],
+        tools: &'a [Value],
+    ) -> Self {
+        Self {
+            model,
+            instructions,
+            input,
+            tools,
+            max_output_tokens: None,
+            effort: None,
+            safety_settings: Vec::new(),
+            conversation_id: None,
+        }
+    }
+
+    pub fn max_output_tokens(mut self, max_output_tokens: Option<u64>) -> Self {
+        self.max_output_tokens = max_output_tokens;
+        self
+    }
+
+    /// Requests thought summaries with a budget derived from the effort.
+    pub fn effort(mut self, effort: Option<ReasoningEffort>) -> Self {
+        self.effort = effort;
+        self
+    }
+
+    /// `(category, threshold)` pairs sent verbatim as `safetySettings`.
+    pub fn safety_settings(mut self, safety_settings: Vec<(String, String)>) -> Self {
+        self.safety_settings = safety_settings;
+        self
+    }
+
+    pub fn conversation_id(mut self, id: Option<String>) -> Self {
+        self.conversation_id = id;
+        self
+    }
+
+    pub fn build(self) -> Result<GeminiRequest, ApiError> {
+        let mut body = Map::new();
+        body.insert("contents".to_string(), json!(build_contents(self.input)));
+        if !self.instructions.is_empty() {
+            body.insert(
+                "systemInstruction".to_string(),
+                json!({"parts": [{"text": self.instructions}]}),
+            );
+        }
+        let declarations: Vec<Value> = self.tools.iter().filter_map(convert_tool).collect();
+        if !declarations.is_empty() {
+            body.insert(
+                "tools".to_string(),
+                json!([{"functionDeclarations": declarations}]),
+            );
+        }
+        if !self.safety_settings.is_empty() {
+            let settings: Vec<Value> = self
+                .safety_settings
+                .iter()
+                .map(|(category, threshold)| json!({"category": category, "threshold": threshold}))
+                .collect();
+            body.insert("safetySettings".to_string(), json!(settings));
+        }
+
+        let mut generation_config = Map::new();
+        if let Some(max_output_tokens) = self.max_output_tokens {
+            generation_config.insert("maxOutputTokens".to_string(), json!(max_output_tokens));
+        }
+        if let Some(budget) = self.effort.and_then(effort_thinking_budget) {
+            generation_config.insert(
+                "thinkingConfig".to_string(),
+                json!({"includeThoughts": true, "thinkingBudget": budget}),
+            );
+        }
+        if !generation_config.is_empty() {
+            body.insert(
+                "generationConfig".to_string(),
+                Value::Object(generation_config),
+            );
+        }
+
+        Ok(GeminiRequest {
+            path: format!("models/{}:streamGenerateContent?alt=sse", self.model),
+            body: Value::Object(body),
+            headers: build_conversation_headers(self.conversation_id),
+        })
+    }
+}
+
+/// `{"type":"function","function":{name,description,parameters}}` to a
+/// Gemini function declaration.
+fn convert_tool(tool: &Value) -> Option<Value> {
+    let function = tool.get("function")?;
+    let name = function.get("name")?.as_str()?;
+    let mut declaration = Map::new();
+    declaration.insert("name".to_string(), json!(name));
+    if let Some(description) = function.get("description").and_then(Value::as_str) {
+        declaration.insert("description".to_string(), json!(description));
+    }
+    // Gemini rejects an object schema without properties.
+    if let Some(parameters) = function.get("parameters").map(translate_schema)
+        && parameters
+            .get("properties")
+            .and_then(Value::as_object)
+            .is_some_and(|properties| !properties.is_empty())
+    {
+        declaration.insert("parameters".to_string(), parameters);
+    }
+    Some(Value::Object(declaration))
+}
+
+/// Keeps the keywords Gemini understands, recursing into nested schemas.
+fn translate_schema(schema: &Value) -> Value {
+    let Some(object) = schema.as_object() else {
+        return schema.clone();
+    };
+    let mut translated = Map::new();
+    for (key, value) in object {
+        if !SCHEMA_KEYWORDS.contains(&key.as_str()) {
+            continue;
+        }
+        let value = match key.as_str() {
+            "items" => translate_schema(value),
+            "anyOf" => Value::Array(
+                value
+                    .as_array()
+                    .map(|schemas| schemas.iter().map(translate_schema).collect())
+                    .unwrap_or_default(),
+            ),
+            "properties" => Value::Object(
+                value
+                    .as_object()
+                    .map(|properties| {
+                        properties
+                            .iter()
+                            .map(|(name, schema)| (name.clone(), translate_schema(schema)))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            ),
+            _ => value.clone(),
+        };
+        translated.insert(key.clone(), value);
+    }
+    Value::Object(translated)
+}
+
+/// Folds the history into alternating `user` and `model` contents. Function
+/// responses must name the function they answer, so call names are looked up
+/// by call id.
+fn build_contents(input: &[ResponseItem]) -> Vec<Value> {
+    let call_names: HashMap<&str, &str> = input
+        .iter()
+        .filter_map(|item| match item {
+            ResponseItem::FunctionCall { name, call_id, .. }
+            | ResponseItem::CustomToolCall { name, call_id, .. } => {
+                Some((call_id.as_str(), name.as_str()))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let mut contents: Vec<(&'static str, Vec<Value>)> = Vec::new();
+    // A thought signature belongs on the model part that follows it.
+    let mut pending_signature: Option<&str> = None;
+    let mut push = |role: &'static str, mut parts: Vec<Value>, signature: &mut Option<&str>| {
+        if parts.is_empty() {
+            return;
+        }
+        if role == "model"
+            && let Some(sig) = signature.take()
+            && let Some(Value::Object(first)) = parts.first_mut()
+        {
+            first.insert("thoughtSignature".to_string(), json!(sig));
+        }
+        match contents.last_mut() {
+            Some((last_role, existing)) if *last_role == role => existing.extend(parts),
+            _ => contents.push((role, parts)),
+        }
+    };
+
+    for item in input {
+        match item {
+            ResponseItem::Message { role, content, .. } => {
+                let role = if role == "assistant" { "model" } else { "user" };
+                let parts = content
+                    .iter()
+                    .filter_map(|item| match item {
+                        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                            text_part(text)
+                        }
+                        ContentItem::InputImage { image_url } => Some(image_part(image_url)),
+                    })
+                    .collect();
+                push(role, parts, &mut pending_signature);
+            }
+            ResponseItem::Reasoning {
+                encrypted_content: Some(signature),
+                ..
+            } => pending_signature = Some(signature),
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => {
+                let args = match serde_json::from_str::<Value>(arguments) {
+                    Ok(Value::Object(object)) => Value::Object(object),
+                    _ => json!({}),
+                };
+                push(
+                    "model",
+                    vec![json!({"functionCall": {"id": call_id, "name": name, "args": args}})],
+                    &mut pending_signature,
+                );
+            }
+            ResponseItem::CustomToolCall {
+                call_id,
+                name,
+                input,
+                ..
+            } => push(
+                "model",
+                vec![
+                    json!({"functionCall": {"id": call_id, "name": name, "args": {"input": input}}}),
+                ],
+                &mut pending_signature,
+            ),
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                let name = call_names
+                    .get(call_id.as_str())
+                    .copied()
+                    .unwrap_or_default();
+                let mut images = Vec::new();
+                let content = match &output.content_items {
+                    Some(items) => {
+                        let mut text = Vec::new();
+                        for item in items {
+                            match item {
+                                FunctionCallOutputContentItem::InputText { text: t } => {
+                                    text.push(t.as_str());
+                                }
+                                FunctionCallOutputContentItem::InputImage { image_url } => {
+                                    images.push(image_part(image_url));
+                                }
+                            }
+                        }
+                        text.join("\n")
+                    }
+                    None => output.content.clone(),
+                };
+                let response = if output.success == Some(false) {
+                    json!({"error": content})
+                } else {
+                    json!({"content": content})
+                };
+                let mut parts = vec![json!({
+                    "functionResponse": {"id": call_id, "name": name, "response": response},
+                })];
+                parts.extend(images);
+                push("user", parts, &mut pending_signature);
+            }
+            ResponseItem::CustomToolCallOutput { call_id, output } => {
+                let name = call_names
+                    .get(call_id.as_str())
+                    .copied()
+                    .unwrap_or_default();
+                push(
+                    "user",
+                    vec![json!({
+                        "functionResponse": {"id": call_id, "name": name, "response": {"content": output}},
+                    })],
+                    &mut pending_signature,
+                );
+            }
+            ResponseItem::Reasoning { .. }
+            | ResponseItem::LocalShellCall { .. }
+            | ResponseItem::WebSearchCall { .. }
+            | ResponseItem::GhostSnapshot { .. }
+            | ResponseItem::Compaction { .. }
+            | ResponseItem::Other => {}
+        }
+    }
+
+    contents
+        .into_iter()
+        .map(|(role, parts)| json!({"role": role, "parts": parts}))
+        .collect()
+}
+
+fn text_part(text: &str) -> Option<Value> {
+    (!text.is_empty()).then(|| json!({"text": text}))
+}
+
+/// Inline data URLs are sent as bytes; Gemini cannot fetch arbitrary URLs, so
+/// anything else is described in text.
+fn image_part(image_url: &str) -> Value {
+    if let Some(rest) = image_url.strip_prefix("data:")
+        && let Some((mime_type, data)) = rest.split_once(";base64,")
+    {
+        return json!({"inlineData": {"mimeType": mime_type, "data": data}});
+    }
+    json!({"text": format!("[image: {image_url}]")})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn maps_history_tools_and_safety_settings() {
+        let input = vec![
+            ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "list files".to_string(),
+                }],
+            },
+            ResponseItem::Reasoning {
+                id: String::new(),
+                summary: Vec::new(),
+                content: None,
+                encrypted_content: Some("sig".to_string()),
+            },
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: r#"{"command":["ls"]}"#.to_string(),
+                call_id: "call-1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "call-1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "permission denied".to_string(),
+                    content_items: None,
+                    success: Some(false),
+                },
+            },
+        ];
+        let tools = vec![json!({
+            "type": "function",
+            "name": "shell",
+            "function": {
+                "name": "shell",
+                "description": "Runs a command",
+                "strict": false,
+                "parameters": {
+                    "type": "object",
+                    "properties": {"command": {"type": "array", "items": {"type": "string"}}},
+                    "required": ["command"],
+                    "additionalProperties": false,
+                },
+            },
+        })];
+
+        let request = GeminiRequestBuilder::new("gemini-test", "be brief", &input, &tools)
+            .max_output_tokens(Some(8_192))
+            .safety_settings(vec![(
+                "HARM_CATEGORY_DANGEROUS_CONTENT".to_string(),
+                "BLOCK_ONLY_HIGH".to_string(),
+            )])
+            .build()
+            .expect("request");
+
+        assert_eq!(
+            request.path,
+            "models/gemini-test:streamGenerateContent?alt=sse"
+        );
+        assert_eq!(
+            request.body,
+            json!({
+                "systemInstruction": {"parts": [{"text": "be brief"}]},
+                "generationConfig": {"maxOutputTokens": 8192},
+                "safetySettings": [
+                    {"category": "HARM_CATEGORY_DANGEROUS_CONTENT", "threshold": "BLOCK_ONLY_HIGH"},
+                ],
+                "tools": [{"functionDeclarations": [{
+                    "name": "shell",
+                    "description": "Runs a command",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {"command": {"type": "array", "items": {"type": "string"}}},
+                        "required": ["command"],
+                    },
+                }]}],
+                "contents": [
+                    {"role": "user", "parts": [{"text": "list files"}]},
+                    {"role": "model", "parts": [{
+                        "functionCall": {"id": "call-1", "name": "shell", "args": {"command": ["ls"]}},
+                        "thoughtSignature": "sig",
+                    }]},
+                    {"role": "user", "parts": [{
+                        "functionResponse": {"id": "call-1", "name": "shell", "response": {"error": "permission denied"}},
+                    }]},
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn parameterless_tools_omit_the_schema() {
+        let tool = json!({
+            "type": "function",
+            "function": {"name": "list_mcp_resources", "parameters": {"type": "object", "properties": {}}},
+        });
+        assert_eq!(
+            convert_tool(&tool),
+            Some(json!({"name": "list_mcp_resources"}))
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Prompt-side half of tool-call emulation for Chat Completions models that
+//! were not trained on native function calling.
+//!
+//! Instead of sending `tools`, the tool list is described in the system
+//! message and the server is asked for a JSON reply (via `response_format`)
+//! that is either a plain message or a single tool call. Prior tool calls and
+//! their outputs are rewritten into ordinary assistant/user turns so servers
+//! that reject `tool_calls`/`tool` messages still accept the history. The
+//! stream-side half lives in `sse::tool_emulation`.
+
+use serde_json::Value;
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Rewrites a Chat Completions payload built with native tools into its
+/// emulated form. `tools` is the chat-shaped tool list that would otherwise
+/// have been sent as `tools`.
+pub(crate) fn apply_tool_call_emulation(payload: &mut Value, tools: &[Value]) {
+    let Some(obj) = payload.as_object_mut() else {
+        return;
+    };
+    obj.remove("tools");
+
+    let messages = obj
+        .remove("messages")
+        .and_then(|messages| match messages {
+            Value::Array(messages) => Some(messages),
+            _ => None,
+        })
+        .unwrap_or_default();
+    let mut messages = rewrite_messages(messages);
+    if !tools.is_empty() {
+        let prompt = tool_prompt(tools);
+        match messages.first_mut() {
+            Some(system) if system["role"] == "system" => {
+                let text = system["content"].as_str().unwrap_or_default();
+                system["content"] = json!(format!("{text}\n\n{prompt}"));
+            }
+            _ => messages.insert(0, json!({"role": "system", "content": prompt})),
+        }
+        obj.insert("response_format".to_string(), response_format(tools));
+    }
+    obj.insert("messages".to_string(), Value::Array(messages));
+}
+
+fn tool_name(tool: &Value) -> Option<&str> {
+    tool.get("function")
+        .and_then(|function| function.get("name"))
+        .or_else(|| tool.get("name"))
+        .and_then(Value::as_str)
+}
+
+fn tool_prompt(tools: &[Value]) -> String {
+    let mut prompt = String::from(
+        "# Tools\n\nYou can call the tools listed below. Every reply must be a single JSON object.\n\
+         To call a tool, reply with {\"type\": \"tool_call\", \"name\": <tool name>, \"arguments\": <JSON object matching the tool's parameters>}.\n\
+         To answer the user, reply with {\"type\": \"message\", \"content\": <your reply>}.\n\
+         Call at most one tool per reply; its output is sent back to you in the next user message.\n",
+    );
+    for tool in tools {
+        let Some(name) = tool_name(tool) else {
+            continue;
+        };
+        let function = tool.get("function").unwrap_or(tool);
+        let description = function
+            .get("description")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let parameters = function
+            .get("parameters")
+            .cloned()
+            .unwrap_or_else(|| json!({"type": "object", "properties": {}}));
+        prompt.push_str(&format!(
+            "\n## {name}\n{description}\nParameters: {parameters}\n"
+        ));
+    }
+    prompt
+}
+
+fn response_format(tools: &[Value]) -> Value {
+    let names: Vec<&str> = tools.iter().filter_map(tool_name).collect();
+    json!({
+        "type": "json_schema",
+        "json_schema": {
+            "name": "assistant_turn",
+            "schema": {
+                "type": "object",
+                "properties": {
+                    "type": {"type": "string", "enum": ["message", "tool_call"]},
+                    "content": {"type": "string"},
+                    "name": {"type": "string", "enum": names},
+                    "arguments": {"type": "object"},
+                },
+                "required": ["type"],
+            },
+        },
+    })
+}
+
+/// Turns `tool_calls` and `tool` messages into plain turns and merges
+/// adjacent messages that end up with the same role.
+fn rewrite_messages(messages: Vec<Value>) -> Vec<Value> {
+    let mut names_by_call_id: HashMap<String, String> = HashMap::new();
+    let mut rewritten: Vec<Value> = Vec::with_capacity(messages.len());
+    for message in messages {
+        let role = message["role"].as_str().unwrap_or_default().to_string();
+        let message = if let Some(calls) = message.get("tool_calls").and_then(Value::as_array) {
+            let content = calls
+                .iter()
+                .filter_map(|call| emulated_call_text(call, &mut names_by_call_id))
+                .collect::<Vec<_>>()
+                .join("\n");
+            json!({"role": "assistant", "content": content})
+        } else if role == "tool" {
+            let call_id = message["tool_call_id"].as_str().unwrap_or_default();
+            let name = names_by_call_id
+                .get(call_id)
+                .map(String::as_str)
+                .unwrap_or("tool");
+            let output = message["content"].as_str().unwrap_or_default();
+            json!({"role": "user", "content": format!("Output of the `{name}` tool call:\n{output}")})
+        } else {
+            let mut message = message;
+            if let Some(obj) = message.as_object_mut() {
+                obj.remove("reasoning");
+            }
+            message
+        };
+
+        match rewritten.last_mut() {
+            Some(previous)
+                if previous["role"] == message["role"] && message["role"] != "system" =>
+            {
+                merge_content(previous, &message["content"]);
+            }
+            _ => rewritten.push(message),
+        }
+    }
+    rewritten
+}
+
+fn emulated_call_text(
+    call: &Value,
+    names_by_call_id: &mut HashMap<String, String>,
+) -> Option<String> {
+    let (name, arguments) = if let Some(function) = call.get("function") {
+        let name = function.get("name")?.as_str()?;
+        let arguments = match function.get("arguments") {
+            Some(Value::String(raw)) => {
+                serde_json::from_str(raw).unwrap_or_else(|_| json!({"input": raw}))
+            }
+            Some(value) => value.clone(),
+            None => json!({}),
+        };
+        (name, arguments)
+    } else if let Some(custom) = call.get("custom") {
+        let name = custom.get("name")?.as_str()?;
+        (
+            name,
+            json!({"input": custom.get("input").cloned().unwrap_or_default()}),
+        )
+    } else {
+        return None;
+    };
+    if let Some(id) = call.get("id").and_then(Value::as_str) {
+        names_by_call_id.insert(id.to_string(), name.to_string());
+    }
+    Some(json!({"type": "tool_call", "name": name, "arguments": arguments}).to_string())
+}
+
+fn merge_content(previous: &mut Value, next: &Value) {
+    match (&mut previous["content"], next) {
+        (Value::String(prev), Value::String(next)) => {
+            if !prev.is_empty() {
+                prev.push_str("\n\n");
+            }
+            prev.push_str(next);
+        }
+        (prev, next) => {
+            let mut parts = content_parts(prev);
+            parts.extend(content_parts(next));
+            *prev = Value::Array(parts);
+        }
+    }
+}
+
+fn content_parts(content: &Value) -> Vec<Value> {
+    match content {
+        Value::Array(parts) => parts.clone(),
+        Value::String(text) if !text.is_empty() => vec![json!({"type": "text", "text": text})],
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn rewrites_tool_history_into_plain_turns() {
+        let tools = vec![json!({
+            "type": "function",
+            "name": "shell",
+            "function": {
+                "name": "shell",
+                "description": "Run a command",
+                "parameters": {"type": "object", "properties": {"command": {"type": "array"}}},
+            },
+        })];
+        let mut payload = json!({
+            "model": "qwen",
+            "stream": true,
+            "tools": tools,
+            "messages": [
+                {"role": "system", "content": "inst"},
+                {"role": "user", "content": "list files"},
+                {"role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call-1",
+                    "type": "function",
+                    "function": {"name": "shell", "arguments": "{\"command\":[\"ls\"]}"},
+                }]},
+                {"role": "tool", "tool_call_id": "call-1", "content": "README.md"},
+                {"role": "user", "content": [{"type": "text", "text": "and then?"}]},
+            ],
+        });
+
+        apply_tool_call_emulation(&mut payload, &tools);
+
+        assert_eq!(payload.get("tools"), None);
+        assert_eq!(payload["response_format"]["type"], json!("json_schema"));
+        assert_eq!(
+            payload["response_format"]["json_schema"]["schema"]["properties"]["name"]["enum"],
+            json!(["shell"])
+        );
+        let messages = payload["messages"].as_array().expect("messages");
+        let system = messages[0]["content"].as_str().expect("system text");
+        assert!(system.starts_with("inst\n\n# Tools"));
+        assert!(system.contains("## shell\nRun a command"));
+        let call: Value = serde_json::from_str(messages[2]["content"].as_str().expect("call text"))
+            .expect("call json");
+        assert_eq!(
+            call,
+            json!({"type": "tool_call", "name": "shell", "arguments": {"command": ["ls"]}})
+        );
+        assert_eq!(messages[2]["role"], json!("assistant"));
+        assert_eq!(
+            [&messages[1], &messages[3]],
+            [
+                &json!({"role": "user", "content": "list files"}),
+                &json!({"role": "user", "content": [
+                    {"type": "text", "text": "Output of the `shell` tool call:\nREADME.md"},
+                    {"type": "text", "text": "and then?"},
+                ]}),
+            ]
+        );
+        assert_eq!(messages.len(), 4);
+    }
+}
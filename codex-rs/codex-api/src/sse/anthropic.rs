@@ -0,0 +1,548 @@
+use crate::common::ResponseEvent;
+use crate::common::ResponseStream;
+use crate::error::ApiError;
+use crate::telemetry::SseTelemetry;
+use codex_client::StreamResponse;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ReasoningItemContent;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::TokenUsage;
+use eventsource_stream::Eventsource;
+use futures::Stream;
+use futures::StreamExt;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tokio::time::timeout;
+use tracing::debug;
+use tracing::trace;
+use tracing::warn;
+
+pub(crate) fn spawn_anthropic_stream(
+    stream_response: StreamResponse,
+    idle_timeout: Duration,
+    telemetry: Option<std::sync::Arc<dyn SseTelemetry>>,
+) -> ResponseStream {
+    let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent, ApiError>>(1600);
+    tokio::spawn(async move {
+        process_anthropic_sse(stream_response.bytes, tx_event, idle_timeout, telemetry).await;
+    });
+    ResponseStream { rx_event }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AnthropicEvent {
+    MessageStart {
+        message: MessageStart,
+    },
+    ContentBlockStart {
+        index: usize,
+        content_block: ContentBlock,
+    },
+    ContentBlockDelta {
+        index: usize,
+        delta: BlockDelta,
+    },
+    ContentBlockStop {
+        index: usize,
+    },
+    MessageDelta {
+        delta: MessageDelta,
+        #[serde(default)]
+        usage: Option<Usage>,
+    },
+    MessageStop,
+    Ping,
+    Error {
+        error: ErrorBody,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageStart {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Usage {
+    #[serde(default)]
+    input_tokens: Option<i64>,
+    #[serde(default)]
+    cache_creation_input_tokens: Option<i64>,
+    #[serde(default)]
+    cache_read_input_tokens: Option<i64>,
+    #[serde(default)]
+    output_tokens: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    Text {
+        #[serde(default)]
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+    },
+    Thinking {
+        #[serde(default)]
+        thinking: String,
+    },
+    RedactedThinking {
+        data: String,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum BlockDelta {
+    TextDelta {
+        text: String,
+    },
+    InputJsonDelta {
+        partial_json: String,
+    },
+    ThinkingDelta {
+        thinking: String,
+    },
+    SignatureDelta {
+        signature: String,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageDelta {
+    #[serde(default)]
+    stop_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    message: String,
+}
+
+/// A content block being streamed, keyed by its index in the message.
+enum BlockState {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: String,
+    },
+    Thinking {
+        thinking: String,
+        signature: String,
+    },
+    RedactedThinking {
+        data: String,
+    },
+}
+
+impl BlockState {
+    fn into_item(self) -> Option<ResponseItem> {
+        match self {
+            BlockState::Text { text } if text.is_empty() => None,
+            BlockState::Text { text } => Some(ResponseItem::Message {
+                id: None,
+                role: "assistant".to_string(),
+                content: vec![ContentItem::OutputText { text }],
+            }),
+            BlockState::ToolUse { id, name, input } => Some(ResponseItem::FunctionCall {
+                id: None,
+                name,
+                // Tools without parameters stream no input at all.
+                arguments: if input.trim().is_empty() {
+                    "{}".to_string()
+                } else {
+                    input
+                },
+                call_id: id,
+            }),
+            // The signature is what lets the API accept the block back on the
+            // next request, so it travels as the item's encrypted content.
+            BlockState::Thinking {
+                thinking,
+                signature,
+            } => Some(ResponseItem::Reasoning {
+                id: String::new(),
+                summary: Vec::new(),
+                content: Some(vec![ReasoningItemContent::ReasoningText { text: thinking }]),
+                encrypted_content: (!signature.is_empty()).then_some(signature),
+            }),
+            BlockState::RedactedThinking { data } => Some(ResponseItem::Reasoning {
+                id: String::new(),
+                summary: Vec::new(),
+                content: None,
+                encrypted_content: Some(data),
+            }),
+        }
+    }
+}
+
+pub async fn process_anthropic_sse<S>(
+    stream: S,
+    tx_event: mpsc::Sender<Result<ResponseEvent, ApiError>>,
+    idle_timeout: Duration,
+    telemetry: Option<std::sync::Arc<dyn SseTelemetry>>,
+) where
+    S: Stream<Item = Result<bytes::Bytes, codex_client::TransportError>> + Unpin,
+{
+    let mut stream = stream.eventsource();
+    let mut handler = AnthropicEventHandler::default();
+
+    loop {
+        let start = Instant::now();
+        let response = timeout(idle_timeout, stream.next()).await;
+        if let Some(t) = telemetry.as_ref() {
+            t.on_sse_poll(&response, start.elapsed());
+        }
+        let sse = match response {
+            Ok(Some(Ok(sse))) => sse,
+            Ok(Some(Err(e))) => {
+                let _ = tx_event.send(Err(ApiError::Stream(e.to_string()))).await;
+                return;
+            }
+            Ok(None) => {
+                let _ = tx_event
+                    .send(Err(ApiError::Stream(
+                        "stream closed before message_stop".into(),
+                    )))
+                    .await;
+                return;
+            }
+            Err(_) => {
+                let _ = tx_event
+                    .send(Err(ApiError::Stream("idle timeout waiting for SSE".into())))
+                    .await;
+                return;
+            }
+        };
+
+        trace!("SSE event: {}", sse.data);
+
+        if handler.handle(&sse.data, &tx_event).await {
+            return;
+        }
+    }
+}
+
+/// Turns Messages API events into [`ResponseEvent`]s, whether they arrived
+/// as SSE or inside Bedrock's event stream.
+#[derive(Default)]
+pub(crate) struct AnthropicEventHandler {
+    blocks: HashMap<usize, BlockState>,
+    response_id: String,
+    usage: Usage,
+}
+
+impl AnthropicEventHandler {
+    /// Handles one event's JSON and returns whether the stream has ended.
+    pub(crate) async fn handle(
+        &mut self,
+        data: &str,
+        tx_event: &mpsc::Sender<Result<ResponseEvent, ApiError>>,
+    ) -> bool {
+        if data.trim().is_empty() {
+            return false;
+        }
+
+        let event: AnthropicEvent = match serde_json::from_str(data) {
+            Ok(event) => event,
+            Err(err) => {
+                debug!("Failed to parse Anthropic event: {err}, data: {data}");
+                return false;
+            }
+        };
+
+        match event {
+            AnthropicEvent::MessageStart { message } => {
+                self.response_id = message.id;
+                if let Some(start_usage) = message.usage {
+                    self.usage = start_usage;
+                }
+                let _ = tx_event.send(Ok(ResponseEvent::Created)).await;
+            }
+            AnthropicEvent::ContentBlockStart {
+                index,
+                content_block,
+            } => {
+                let state = match content_block {
+                    ContentBlock::Text { text } => {
+                        let _ = tx_event
+                            .send(Ok(ResponseEvent::OutputItemAdded(ResponseItem::Message {
+                                id: None,
+                                role: "assistant".to_string(),
+                                content: vec![],
+                            })))
+                            .await;
+                        if !text.is_empty() {
+                            let _ = tx_event
+                                .send(Ok(ResponseEvent::OutputTextDelta(text.clone())))
+                                .await;
+                        }
+                        BlockState::Text { text }
+                    }
+                    ContentBlock::ToolUse { id, name } => BlockState::ToolUse {
+                        id,
+                        name,
+                        input: String::new(),
+                    },
+                    ContentBlock::Thinking { thinking } => {
+                        let _ = tx_event
+                            .send(Ok(ResponseEvent::OutputItemAdded(
+                                ResponseItem::Reasoning {
+                                    id: String::new(),
+                                    summary: Vec::new(),
+                                    content: Some(vec![]),
+                                    encrypted_content: None,
+                                },
+                            )))
+                            .await;
+                        BlockState::Thinking {
+                            thinking,
+                            signature: String::new(),
+                        }
+                    }
+                    ContentBlock::RedactedThinking { data } => {
+                        BlockState::RedactedThinking { data }
+                    }
+                    ContentBlock::Unknown => return false,
+                };
+                self.blocks.insert(index, state);
+            }
+            AnthropicEvent::ContentBlockDelta { index, delta } => {
+                match (self.blocks.get_mut(&index), delta) {
+                    (Some(BlockState::Text { text }), BlockDelta::TextDelta { text: delta }) => {
+                        text.push_str(&delta);
+                        let _ = tx_event
+                            .send(Ok(ResponseEvent::OutputTextDelta(delta)))
+                            .await;
+                    }
+                    (
+                        Some(BlockState::ToolUse { input, .. }),
+                        BlockDelta::InputJsonDelta { partial_json },
+                    ) => input.push_str(&partial_json),
+                    (
+                        Some(BlockState::Thinking { thinking, .. }),
+                        BlockDelta::ThinkingDelta { thinking: delta },
+                    ) => {
+                        thinking.push_str(&delta);
+                        let _ = tx_event
+                            .send(Ok(ResponseEvent::ReasoningContentDelta {
+                                delta,
+                                content_index: 0,
+                            }))
+                            .await;
+                    }
+                    (
+                        Some(BlockState::Thinking { signature, .. }),
+                        BlockDelta::SignatureDelta { signature: delta },
+                    ) => signature.push_str(&delta),
+                    (_, delta) => debug!("Ignoring Anthropic delta for block {index}: {delta:?}"),
+                }
+            }
+            AnthropicEvent::ContentBlockStop { index } => {
+                if let Some(item) = self.blocks.remove(&index).and_then(BlockState::into_item) {
+                    let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
+                }
+            }
+            AnthropicEvent::MessageDelta {
+                delta,
+                usage: delta_usage,
+            } => {
+                if let Some(output_tokens) = delta_usage.and_then(|usage| usage.output_tokens) {
+                    self.usage.output_tokens = Some(output_tokens);
+                }
+                if delta.stop_reason.as_deref() == Some("max_tokens") {
+                    warn!(
+                        "Anthropic response stopped at max_tokens; raise model_max_output_tokens to allow longer replies"
+                    );
+                }
+            }
+            AnthropicEvent::MessageStop => {
+                let _ = tx_event
+                    .send(Ok(ResponseEvent::Completed {
+                        response_id: std::mem::take(&mut self.response_id),
+                        token_usage: Some(std::mem::take(&mut self.usage).into()),
+                    }))
+                    .await;
+                return true;
+            }
+            AnthropicEvent::Error { error } => {
+                let message = if error.message.is_empty() {
+                    error.kind.clone()
+                } else {
+                    error.message
+                };
+                let error = match error.kind.as_str() {
+                    "overloaded_error" | "api_error" => ApiError::Retryable {
+                        message,
+                        delay: None,
+                    },
+                    _ => ApiError::Stream(message),
+                };
+                let _ = tx_event.send(Err(error)).await;
+                return true;
+            }
+            AnthropicEvent::Ping | AnthropicEvent::Unknown => {}
+        }
+        false
+    }
+}
+
+/// Anthropic reports cache reads and writes separately from `input_tokens`;
+/// the protocol counts them all as input.
+impl From<Usage> for TokenUsage {
+    fn from(usage: Usage) -> Self {
+        let cached_input_tokens = usage.cache_read_input_tokens.unwrap_or(0);
+        let input_tokens = usage.input_tokens.unwrap_or(0)
+            + usage.cache_creation_input_tokens.unwrap_or(0)
+            + cached_input_tokens;
+        let output_tokens = usage.output_tokens.unwrap_or(0);
+        TokenUsage {
+            input_tokens,
+            cached_input_tokens,
+            output_tokens,
+            reasoning_output_tokens: 0,
+            total_tokens: input_tokens + output_tokens,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use futures::TryStreamExt;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tokio_util::io::ReaderStream;
+
+    fn build_body(events: &[serde_json::Value]) -> String {
+        let mut body = String::new();
+        for e in events {
+            let name = e["type"].as_str().unwrap_or("message");
+            body.push_str(&format!("event: {name}\ndata: {e}\n\n"));
+        }
+        body
+    }
+
+    async fn collect_events(body: &str) -> Vec<Result<ResponseEvent, ApiError>> {
+        let reader = ReaderStream::new(std::io::Cursor::new(body.to_string()))
+            .map_err(|err| codex_client::TransportError::Network(err.to_string()));
+        let (tx, mut rx) = mpsc::channel::<Result<ResponseEvent, ApiError>>(16);
+        tokio::spawn(process_anthropic_sse(
+            reader,
+            tx,
+            Duration::from_millis(1000),
+            None,
+        ));
+
+        let mut out = Vec::new();
+        while let Some(ev) = rx.recv().await {
+            out.push(ev);
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn emits_thinking_text_and_tool_use_items() {
+        let body = build_body(&[
+            json!({"type": "message_start", "message": {"id": "msg_1", "usage": {
+                "input_tokens": 10, "cache_read_input_tokens": 90, "output_tokens": 1
+            }}}),
+            json!({"type": "content_block_start", "index": 0, "content_block": {"type": "thinking", "thinking": ""}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "Check the dir."}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "signature_delta", "signature": "sig"}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "content_block_start", "index": 1, "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "Listing"}}),
+            json!({"type": "content_block_stop", "index": 1}),
+            json!({"type": "content_block_start", "index": 2, "content_block": {"type": "tool_use", "id": "toolu_1", "name": "shell", "input": {}}}),
+            json!({"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": "{\"command\":"}}),
+            json!({"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": "[\"ls\"]}"}}),
+            json!({"type": "content_block_stop", "index": 2}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 20}}),
+            json!({"type": "message_stop"}),
+        ]);
+
+        let events: Vec<ResponseEvent> = collect_events(&body)
+            .await
+            .into_iter()
+            .map(|event| event.expect("stream error"))
+            .collect();
+
+        let done: Vec<&ResponseItem> = events
+            .iter()
+            .filter_map(|event| match event {
+                ResponseEvent::OutputItemDone(item) => Some(item),
+                _ => None,
+            })
+            .collect();
+        assert_matches!(
+            &done[..],
+            [
+                ResponseItem::Reasoning { content: Some(content), encrypted_content: Some(signature), .. },
+                ResponseItem::Message { content: text, .. },
+                ResponseItem::FunctionCall { call_id, name, arguments, .. },
+            ] if content == &vec![ReasoningItemContent::ReasoningText { text: "Check the dir.".to_string() }]
+                && signature == "sig"
+                && text == &vec![ContentItem::OutputText { text: "Listing".to_string() }]
+                && call_id == "toolu_1"
+                && name == "shell"
+                && arguments == "{\"command\":[\"ls\"]}"
+        );
+
+        let Some(ResponseEvent::Completed {
+            response_id,
+            token_usage: Some(token_usage),
+        }) = events.last()
+        else {
+            panic!("expected a completed event, got {events:?}");
+        };
+        assert_eq!(response_id, "msg_1");
+        assert_eq!(
+            (
+                token_usage.input_tokens,
+                token_usage.cached_input_tokens,
+                token_usage.output_tokens,
+                token_usage.total_tokens,
+            ),
+            (100, 90, 20, 120)
+        );
+    }
+
+    #[tokio::test]
+    async fn overloaded_error_is_retryable() {
+        let body = build_body(&[
+            json!({"type": "message_start", "message": {"id": "msg_1"}}),
+            json!({"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}),
+        ]);
+
+        let events = collect_events(&body).await;
+        assert_matches!(
+            &events[..],
+            [Ok(ResponseEvent::Created), Err(ApiError::Retryable { message, delay: None })]
+                if message == "Overloaded"
+        );
+    }
+}
@@ -0,0 +1,352 @@
+//! Amazon Bedrock's `InvokeModelWithResponseStream` replies in the binary
+//! `application/vnd.amazon.eventstream` framing rather than SSE. Each `chunk`
+//! event carries one base64-encoded Anthropic stream event, which goes
+//! through the same handler as the Messages API.
+
+use crate::common::ResponseEvent;
+use crate::common::ResponseStream;
+use crate::error::ApiError;
+use crate::sse::anthropic::AnthropicEventHandler;
+use crate::telemetry::SseTelemetry;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use bytes::Bytes;
+use bytes::BytesMut;
+use codex_client::StreamResponse;
+use futures::Stream;
+use futures::StreamExt;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tracing::debug;
+use tracing::trace;
+
+/// Total length, headers length and prelude CRC, as big-endian `u32`s.
+const PRELUDE_LEN: usize = 12;
+const MESSAGE_CRC_LEN: usize = 4;
+/// Exceptions that Bedrock documents as safe to retry.
+const RETRYABLE_EXCEPTIONS: &[&str] = &[
+    "throttlingException",
+    "internalServerException",
+    "serviceUnavailableException",
+    "modelStreamErrorException",
+];
+
+/// The event-stream framing isn't SSE, so `telemetry` (which observes SSE
+/// polls) is not called for Bedrock streams.
+pub(crate) fn spawn_bedrock_stream(
+    stream_response: StreamResponse,
+    idle_timeout: Duration,
+    _telemetry: Option<std::sync::Arc<dyn SseTelemetry>>,
+) -> ResponseStream {
+    let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent, ApiError>>(1600);
+    tokio::spawn(async move {
+        process_bedrock_stream(stream_response.bytes, tx_event, idle_timeout).await;
+    });
+    ResponseStream { rx_event }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkPayload {
+    bytes: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ExceptionPayload {
+    #[serde(default)]
+    message: String,
+}
+
+/// One decoded event-stream message; only string headers are kept.
+struct EventMessage {
+    headers: HashMap<String, String>,
+    payload: Bytes,
+}
+
+pub async fn process_bedrock_stream<S>(
+    mut stream: S,
+    tx_event: mpsc::Sender<Result<ResponseEvent, ApiError>>,
+    idle_timeout: Duration,
+) where
+    S: Stream<Item = Result<Bytes, codex_client::TransportError>> + Unpin,
+{
+    let mut buffer = BytesMut::new();
+    let mut handler = AnthropicEventHandler::default();
+
+    loop {
+        loop {
+            let message = match next_message(&mut buffer) {
+                Ok(Some(message)) => message,
+                Ok(None) => break,
+                Err(err) => {
+                    let _ = tx_event.send(Err(ApiError::Stream(err))).await;
+                    return;
+                }
+            };
+            if handle_message(message, &mut handler, &tx_event).await {
+                return;
+            }
+        }
+
+        match timeout(idle_timeout, stream.next()).await {
+            Ok(Some(Ok(chunk))) => buffer.extend_from_slice(&chunk),
+            Ok(Some(Err(e))) => {
+                let _ = tx_event.send(Err(ApiError::Stream(e.to_string()))).await;
+                return;
+            }
+            Ok(None) => {
+                let _ = tx_event
+                    .send(Err(ApiError::Stream(
+                        "stream closed before message_stop".into(),
+                    )))
+                    .await;
+                return;
+            }
+            Err(_) => {
+                let _ = tx_event
+                    .send(Err(ApiError::Stream(
+                        "idle timeout waiting for Bedrock event stream".into(),
+                    )))
+                    .await;
+                return;
+            }
+        }
+    }
+}
+
+/// Returns whether the stream has ended.
+async fn handle_message(
+    message: EventMessage,
+    handler: &mut AnthropicEventHandler,
+    tx_event: &mpsc::Sender<Result<ResponseEvent, ApiError>>,
+) -> bool {
+    let header = |name: &str| message.headers.get(name).map(String::as_str);
+    match header(":message-type") {
+        Some("event") if header(":event-type") == Some("chunk") => {
+            let data = serde_json::from_slice::<ChunkPayload>(&message.payload)
+                .ok()
+                .and_then(|chunk| BASE64.decode(chunk.bytes).ok())
+                .and_then(|bytes| String::from_utf8(bytes).ok());
+            match data {
+                Some(data) => {
+                    trace!("Bedrock event: {data}");
+                    handler.handle(&data, tx_event).await
+                }
+                None => {
+                    debug!("Failed to decode Bedrock chunk: {:?}", message.payload);
+                    false
+                }
+            }
+        }
+        Some("exception" | "error") => {
+            let kind = header(":exception-type")
+                .or_else(|| header(":error-code"))
+                .unwrap_or("unknown")
+                .to_string();
+            let mut text = serde_json::from_slice::<ExceptionPayload>(&message.payload)
+                .unwrap_or_default()
+                .message;
+            if text.is_empty() {
+                text = header(":error-message").unwrap_or_default().to_string();
+            }
+            let message = format!("{kind}: {text}");
+            let error = if RETRYABLE_EXCEPTIONS.contains(&kind.as_str()) {
+                ApiError::Retryable {
+                    message,
+                    delay: None,
+                }
+            } else {
+                ApiError::Stream(message)
+            };
+            let _ = tx_event.send(Err(error)).await;
+            true
+        }
+        other => {
+            debug!("Ignoring Bedrock event-stream message of type {other:?}");
+            false
+        }
+    }
+}
+
+/// Splits the next complete message off `buffer`, if one has arrived.
+/// Checksums are not verified; TLS already protects the bytes in transit.
+fn next_message(buffer: &mut BytesMut) -> Result<Option<EventMessage>, String> {
+    if buffer.len() < PRELUDE_LEN {
+        return Ok(None);
+    }
+    let total_len = read_u32(&buffer[0..4]);
+    let headers_len = read_u32(&buffer[4..8]);
+    if total_len < PRELUDE_LEN + headers_len + MESSAGE_CRC_LEN {
+        return Err(format!(
+            "malformed Bedrock event stream: message of {total_len} bytes with {headers_len} bytes of headers"
+        ));
+    }
+    if buffer.len() < total_len {
+        return Ok(None);
+    }
+
+    let frame = buffer.split_to(total_len).freeze();
+    let headers_end = PRELUDE_LEN + headers_len;
+    let headers = parse_headers(&frame[PRELUDE_LEN..headers_end])?;
+    let payload = frame.slice(headers_end..total_len - MESSAGE_CRC_LEN);
+    Ok(Some(EventMessage { headers, payload }))
+}
+
+fn read_u32(bytes: &[u8]) -> usize {
+    let mut word = [0u8; 4];
+    word.copy_from_slice(bytes);
+    u32::from_be_bytes(word) as usize
+}
+
+fn parse_headers(mut bytes: &[u8]) -> Result<HashMap<String, String>, String> {
+    let mut headers = HashMap::new();
+    while !bytes.is_empty() {
+        let name_len = usize::from(take(&mut bytes, 1)?[0]);
+        let name = String::from_utf8_lossy(take(&mut bytes, name_len)?).into_owned();
+        let value_type = take(&mut bytes, 1)?[0];
+        let value_len = match value_type {
+            // Booleans carry their value in the type.
+            0 | 1 => 0,
+            2 => 1,
+            3 => 2,
+            4 => 4,
+            5 | 8 => 8,
+            9 => 16,
+            6 | 7 => {
+                let len = take(&mut bytes, 2)?;
+                usize::from(u16::from_be_bytes([len[0], len[1]]))
+            }
+            other => return Err(format!("unknown Bedrock event header type {other}")),
+        };
+        let value = take(&mut bytes, value_len)?;
+        if value_type == 7 {
+            headers.insert(name, String::from_utf8_lossy(value).into_owned());
+        }
+    }
+    Ok(headers)
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], String> {
+    if bytes.len() < len {
+        return Err("truncated Bedrock event stream headers".to_string());
+    }
+    let (head, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(head)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use futures::TryStreamExt;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tokio_util::io::ReaderStream;
+
+    /// Encodes a message with string headers; checksums are left zero.
+    fn frame(headers: &[(&str, &str)], payload: &[u8]) -> Vec<u8> {
+        let mut encoded_headers = Vec::new();
+        for (name, value) in headers {
+            encoded_headers.push(name.len() as u8);
+            encoded_headers.extend_from_slice(name.as_bytes());
+            encoded_headers.push(7);
+            encoded_headers.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            encoded_headers.extend_from_slice(value.as_bytes());
+        }
+        let total_len = PRELUDE_LEN + encoded_headers.len() + payload.len() + MESSAGE_CRC_LEN;
+        let mut out = Vec::new();
+        out.extend_from_slice(&(total_len as u32).to_be_bytes());
+        out.extend_from_slice(&(encoded_headers.len() as u32).to_be_bytes());
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&encoded_headers);
+        out.extend_from_slice(payload);
+        out.extend_from_slice(&[0; 4]);
+        out
+    }
+
+    fn chunk(event: serde_json::Value) -> Vec<u8> {
+        let payload = json!({"bytes": BASE64.encode(event.to_string())}).to_string();
+        frame(
+            &[
+                (":message-type", "event"),
+                (":event-type", "chunk"),
+                (":content-type", "application/json"),
+            ],
+            payload.as_bytes(),
+        )
+    }
+
+    async fn collect_events(body: Vec<u8>) -> Vec<Result<ResponseEvent, ApiError>> {
+        let reader = ReaderStream::with_capacity(std::io::Cursor::new(body), 7)
+            .map_err(|err| codex_client::TransportError::Network(err.to_string()));
+        let (tx, mut rx) = mpsc::channel::<Result<ResponseEvent, ApiError>>(16);
+        tokio::spawn(process_bedrock_stream(
+            reader,
+            tx,
+            Duration::from_millis(1000),
+        ));
+
+        let mut out = Vec::new();
+        while let Some(ev) = rx.recv().await {
+            out.push(ev);
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn decodes_chunks_split_across_reads() {
+        let body = [
+            chunk(json!({"type": "message_start", "message": {"id": "msg_1"}})),
+            chunk(json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}})),
+            chunk(json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Hi"}})),
+            chunk(json!({"type": "content_block_stop", "index": 0})),
+            chunk(json!({"type": "message_stop"})),
+        ]
+        .concat();
+
+        let events: Vec<ResponseEvent> = collect_events(body)
+            .await
+            .into_iter()
+            .map(|event| event.expect("stream error"))
+            .collect();
+
+        assert_matches!(events.first(), Some(ResponseEvent::Created));
+        assert_matches!(
+            events.last(),
+            Some(ResponseEvent::Completed { response_id, .. }) if response_id == "msg_1"
+        );
+        let deltas: Vec<&str> = events
+            .iter()
+            .filter_map(|event| match event {
+                ResponseEvent::OutputTextDelta(delta) => Some(delta.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(deltas, vec!["Hi"]);
+    }
+
+    #[tokio::test]
+    async fn throttling_exception_is_retryable() {
+        let body = [
+            chunk(json!({"type": "message_start", "message": {"id": "msg_1"}})),
+            frame(
+                &[
+                    (":message-type", "exception"),
+                    (":exception-type", "throttlingException"),
+                ],
+                br#"{"message":"Too many requests"}"#,
+            ),
+        ]
+        .concat();
+
+        let events = collect_events(body).await;
+        assert_matches!(
+            events.last(),
+            Some(Err(ApiError::Retryable { message, .. }))
+                if message == "throttlingException: Too many requests"
+        );
+    }
+}
@@ -6,6 +6,7 @@ use codex_client::StreamResponse;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::ReasoningItemContent;
 use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::TokenUsage;
 use eventsource_stream::Eventsource;
 use futures::Stream;
 use futures::StreamExt;
@@ -30,6 +31,36 @@ pub(crate) fn spawn_chat_stream(
     ResponseStream { rx_event }
 }
 
+/// Like [`spawn_chat_stream`], but tolerant of the ways local
+/// OpenAI-compatible servers (Ollama, llama.cpp, LM Studio) deviate from the
+/// Chat Completions stream: tool calls finished with `stop` or with no finish
+/// reason at all are still emitted.
+pub(crate) fn spawn_local_chat_stream(
+    stream_response: StreamResponse,
+    idle_timeout: Duration,
+    telemetry: Option<std::sync::Arc<dyn SseTelemetry>>,
+) -> ResponseStream {
+    let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent, ApiError>>(1600);
+    tokio::spawn(async move {
+        process_chat_sse_with_quirks(
+            stream_response.bytes,
+            tx_event,
+            idle_timeout,
+            telemetry,
+            true,
+        )
+        .await;
+    });
+    ResponseStream { rx_event }
+}
+
+#[derive(Default, Debug)]
+struct ToolCallState {
+    id: Option<String>,
+    name: Option<String>,
+    arguments: String,
+}
+
 pub async fn process_chat_sse<S>(
     stream: S,
     tx_event: mpsc::Sender<Result<ResponseEvent, ApiError>>,
@@ -38,14 +69,19 @@ pub async fn process_chat_sse<S>(
 ) where
     S: Stream<Item = Result<bytes::Bytes, codex_client::TransportError>> + Unpin,
 {
-    let mut stream = stream.eventsource();
+    process_chat_sse_with_quirks(stream, tx_event, idle_timeout, telemetry, false).await;
+}
 
-    #[derive(Default, Debug)]
-    struct ToolCallState {
-        id: Option<String>,
-        name: Option<String>,
-        arguments: String,
-    }
+async fn process_chat_sse_with_quirks<S>(
+    stream: S,
+    tx_event: mpsc::Sender<Result<ResponseEvent, ApiError>>,
+    idle_timeout: Duration,
+    telemetry: Option<std::sync::Arc<dyn SseTelemetry>>,
+    local_quirks: bool,
+) where
+    S: Stream<Item = Result<bytes::Bytes, codex_client::TransportError>> + Unpin,
+{
+    let mut stream = stream.eventsource();
 
     let mut tool_calls: HashMap<usize, ToolCallState> = HashMap::new();
     let mut tool_call_order: Vec<usize> = Vec::new();
@@ -55,7 +91,7 @@ pub async fn process_chat_sse<S>(
     let mut last_tool_call_index: Option<usize> = None;
     let mut assistant_item: Option<ResponseItem> = None;
     let mut reasoning_item: Option<ResponseItem> = None;
-    let mut completed_sent = false;
+    let mut token_usage: Option<TokenUsage> = None;
 
     loop {
         let start = Instant::now();
@@ -64,31 +100,41 @@ pub async fn process_chat_sse<S>(
             t.on_sse_poll(&response, start.elapsed());
         }
         let sse = match response {
-            Ok(Some(Ok(sse))) => sse,
+            Ok(Some(Ok(sse))) if sse.data.trim() != "[DONE]" => sse,
             Ok(Some(Err(e))) => {
                 let _ = tx_event.send(Err(ApiError::Stream(e.to_string()))).await;
                 return;
             }
-            Ok(None) => {
+            // `Completed` waits for the end of the stream because the usage
+            // chunk requested with `stream_options.include_usage` arrives
+            // after the chunk carrying the finish reason.
+            Ok(Some(Ok(_))) | Ok(None) => {
                 if let Some(reasoning) = reasoning_item {
                     let _ = tx_event
                         .send(Ok(ResponseEvent::OutputItemDone(reasoning)))
                         .await;
                 }
+                if local_quirks {
+                    flush_tool_calls(
+                        &tx_event,
+                        &mut tool_calls,
+                        &mut tool_call_order,
+                        &mut tool_call_order_seen,
+                    )
+                    .await;
+                }
 
                 if let Some(assistant) = assistant_item {
                     let _ = tx_event
                         .send(Ok(ResponseEvent::OutputItemDone(assistant)))
                         .await;
                 }
-                if !completed_sent {
-                    let _ = tx_event
-                        .send(Ok(ResponseEvent::Completed {
-                            response_id: String::new(),
-                            token_usage: None,
-                        }))
-                        .await;
-                }
+                let _ = tx_event
+                    .send(Ok(ResponseEvent::Completed {
+                        response_id: String::new(),
+                        token_usage,
+                    }))
+                    .await;
                 return;
             }
             Err(_) => {
@@ -116,6 +162,10 @@ pub async fn process_chat_sse<S>(
             }
         };
 
+        if let Some(usage) = value.get("usage").and_then(parse_usage) {
+            token_usage = Some(usage);
+        }
+
         let Some(choices) = value.get("choices").and_then(|c| c.as_array()) else {
             continue;
         };
@@ -197,9 +247,16 @@ pub async fn process_chat_sse<S>(
                             {
                                 call_state.name.get_or_insert_with(|| fname.to_string());
                             }
-                            if let Some(arguments) = func.get("arguments").and_then(|a| a.as_str())
-                            {
-                                call_state.arguments.push_str(arguments);
+                            match func.get("arguments") {
+                                Some(serde_json::Value::String(arguments)) => {
+                                    call_state.arguments.push_str(arguments);
+                                }
+                                // Some local servers send the arguments as a
+                                // JSON object rather than an encoded string.
+                                Some(arguments @ serde_json::Value::Object(_)) => {
+                                    call_state.arguments.push_str(&arguments.to_string());
+                                }
+                                _ => {}
                             }
                         }
 
@@ -228,20 +285,23 @@ pub async fn process_chat_sse<S>(
                         .await;
                 }
 
+                // Ollama and some llama.cpp builds report `stop` even when the
+                // turn ended in tool calls.
+                if local_quirks {
+                    flush_tool_calls(
+                        &tx_event,
+                        &mut tool_calls,
+                        &mut tool_call_order,
+                        &mut tool_call_order_seen,
+                    )
+                    .await;
+                }
+
                 if let Some(assistant) = assistant_item.take() {
                     let _ = tx_event
                         .send(Ok(ResponseEvent::OutputItemDone(assistant)))
                         .await;
                 }
-                if !completed_sent {
-                    let _ = tx_event
-                        .send(Ok(ResponseEvent::Completed {
-                            response_id: String::new(),
-                            token_usage: None,
-                        }))
-                        .await;
-                    completed_sent = true;
-                }
                 continue;
             }
 
@@ -257,33 +317,63 @@ pub async fn process_chat_sse<S>(
                         .await;
                 }
 
-                for index in tool_call_order.drain(..) {
-                    let Some(state) = tool_calls.remove(&index) else {
-                        continue;
-                    };
-                    tool_call_order_seen.remove(&index);
-                    let ToolCallState {
-                        id,
-                        name,
-                        arguments,
-                    } = state;
-                    let Some(name) = name else {
-                        debug!("Skipping tool call at index {index} because name is missing");
-                        continue;
-                    };
-                    let item = ResponseItem::FunctionCall {
-                        id: None,
-                        name,
-                        arguments,
-                        call_id: id.unwrap_or_else(|| format!("tool-call-{index}")),
-                    };
-                    let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
-                }
+                flush_tool_calls(
+                    &tx_event,
+                    &mut tool_calls,
+                    &mut tool_call_order,
+                    &mut tool_call_order_seen,
+                )
+                .await;
             }
         }
     }
 }
 
+/// Reads the `usage` object of the final chunk. Cached prompt tokens are
+/// reported under `prompt_tokens_details` by OpenAI and compatible servers.
+fn parse_usage(usage: &serde_json::Value) -> Option<TokenUsage> {
+    let field = |pointer: &str| usage.pointer(pointer).and_then(serde_json::Value::as_i64);
+    let input_tokens = field("/prompt_tokens")?;
+    let output_tokens = field("/completion_tokens").unwrap_or(0);
+    Some(TokenUsage {
+        input_tokens,
+        cached_input_tokens: field("/prompt_tokens_details/cached_tokens").unwrap_or(0),
+        output_tokens,
+        reasoning_output_tokens: field("/completion_tokens_details/reasoning_tokens").unwrap_or(0),
+        total_tokens: field("/total_tokens").unwrap_or(input_tokens + output_tokens),
+    })
+}
+
+async fn flush_tool_calls(
+    tx_event: &mpsc::Sender<Result<ResponseEvent, ApiError>>,
+    tool_calls: &mut HashMap<usize, ToolCallState>,
+    tool_call_order: &mut Vec<usize>,
+    tool_call_order_seen: &mut HashSet<usize>,
+) {
+    for index in tool_call_order.drain(..) {
+        let Some(state) = tool_calls.remove(&index) else {
+            continue;
+        };
+        tool_call_order_seen.remove(&index);
+        let ToolCallState {
+            id,
+            name,
+            arguments,
+        } = state;
+        let Some(name) = name else {
+            debug!("Skipping tool call at index {index} because name is missing");
+            continue;
+        };
+        let item = ResponseItem::FunctionCall {
+            id: None,
+            name,
+            arguments,
+            call_id: id.unwrap_or_else(|| format!("tool-call-{index}")),
+        };
+        let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
+    }
+}
+
 async fn append_assistant_text(
     tx_event: &mpsc::Sender<Result<ResponseEvent, ApiError>>,
     assistant_item: &mut Option<ResponseItem>,
@@ -350,6 +440,7 @@ mod tests {
     use assert_matches::assert_matches;
     use codex_protocol::models::ResponseItem;
     use futures::TryStreamExt;
+    use pretty_assertions::assert_eq;
     use serde_json::json;
     use tokio::sync::mpsc;
     use tokio_util::io::ReaderStream;
@@ -363,14 +454,19 @@ mod tests {
     }
 
     async fn collect_events(body: &str) -> Vec<ResponseEvent> {
+        collect_events_with_quirks(body, false).await
+    }
+
+    async fn collect_events_with_quirks(body: &str, local_quirks: bool) -> Vec<ResponseEvent> {
         let reader = ReaderStream::new(std::io::Cursor::new(body.to_string()))
             .map_err(|err| codex_client::TransportError::Network(err.to_string()));
         let (tx, mut rx) = mpsc::channel::<Result<ResponseEvent, ApiError>>(16);
-        tokio::spawn(process_chat_sse(
+        tokio::spawn(process_chat_sse_with_quirks(
             reader,
             tx,
             Duration::from_millis(1000),
             None,
+            local_quirks,
         ));
 
         let mut out = Vec::new();
@@ -666,4 +762,66 @@ mod tests {
         }));
         assert_matches!(events.last(), Some(ResponseEvent::Completed { .. }));
     }
+
+    #[tokio::test]
+    async fn completed_carries_usage_chunk_sent_after_stop() {
+        let delta = json!({"choices": [{"delta": {"content": "hi"}}]});
+        let finish = json!({"choices": [{"delta": {}, "finish_reason": "stop"}]});
+        let usage = json!({
+            "choices": [],
+            "usage": {
+                "prompt_tokens": 1200,
+                "completion_tokens": 30,
+                "total_tokens": 1230,
+                "prompt_tokens_details": {"cached_tokens": 1024},
+                "completion_tokens_details": {"reasoning_tokens": 10}
+            }
+        });
+
+        let mut body = build_body(&[delta, finish, usage]);
+        body.push_str("event: message\ndata: [DONE]\n\n");
+        let events = collect_events(&body).await;
+
+        assert_matches!(
+            events.last(),
+            Some(ResponseEvent::Completed { token_usage: Some(usage), .. })
+                if usage.input_tokens == 1200
+                    && usage.cached_input_tokens == 1024
+                    && usage.output_tokens == 30
+                    && usage.reasoning_output_tokens == 10
+                    && usage.total_tokens == 1230
+        );
+        assert_eq!(
+            events
+                .iter()
+                .filter(|event| matches!(event, ResponseEvent::Completed { .. }))
+                .count(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn local_quirks_emit_tool_calls_finished_with_stop() {
+        let delta_tool = json!({
+            "choices": [{
+                "delta": {
+                    "tool_calls": [{
+                        "function": { "name": "do_a", "arguments": { "foo": 1 } }
+                    }]
+                },
+                "finish_reason": "stop"
+            }]
+        });
+
+        let body = build_body(&[delta_tool]);
+        let events = collect_events_with_quirks(&body, true).await;
+
+        assert_matches!(
+            &events[..],
+            [
+                ResponseEvent::OutputItemDone(ResponseItem::FunctionCall { call_id, name, arguments, .. }),
+                ResponseEvent::Completed { .. }
+            ] if call_id == "tool-call-0" && name == "do_a" && arguments == "{\"foo\":1}"
+        );
+    }
 }
@@ -0,0 +1,491 @@
+use crate::common::ResponseEvent;
+use crate::common::ResponseStream;
+use crate::error::ApiError;
+use crate::telemetry::SseTelemetry;
+use codex_client::StreamResponse;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ReasoningItemContent;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::TokenUsage;
+use eventsource_stream::Eventsource;
+use futures::Stream;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tokio::time::timeout;
+use tracing::debug;
+use tracing::trace;
+use tracing::warn;
+
+pub(crate) fn spawn_gemini_stream(
+    stream_response: StreamResponse,
+    idle_timeout: Duration,
+    telemetry: Option<std::sync::Arc<dyn SseTelemetry>>,
+) -> ResponseStream {
+    let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent, ApiError>>(1600);
+    tokio::spawn(async move {
+        process_gemini_sse(stream_response.bytes, tx_event, idle_timeout, telemetry).await;
+    });
+    ResponseStream { rx_event }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateContentChunk {
+    #[serde(default)]
+    response_id: Option<String>,
+    #[serde(default)]
+    candidates: Vec<Candidate>,
+    #[serde(default)]
+    prompt_feedback: Option<PromptFeedback>,
+    #[serde(default)]
+    usage_metadata: Option<UsageMetadata>,
+    #[serde(default)]
+    error: Option<ErrorBody>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Candidate {
+    #[serde(default)]
+    content: Option<CandidateContent>,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CandidateContent {
+    #[serde(default)]
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Part {
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    thought: bool,
+    #[serde(default)]
+    thought_signature: Option<String>,
+    #[serde(default)]
+    function_call: Option<FunctionCall>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FunctionCall {
+    #[serde(default)]
+    id: Option<String>,
+    name: String,
+    #[serde(default)]
+    args: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptFeedback {
+    #[serde(default)]
+    block_reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageMetadata {
+    #[serde(default)]
+    prompt_token_count: i64,
+    #[serde(default)]
+    cached_content_token_count: i64,
+    #[serde(default)]
+    candidates_token_count: i64,
+    #[serde(default)]
+    thoughts_token_count: i64,
+    #[serde(default)]
+    total_token_count: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    status: String,
+}
+
+/// Thought text and the signature Gemini attaches to it. The signature is
+/// kept as the reasoning item's encrypted content so it can be sent back.
+#[derive(Default)]
+struct ThoughtState {
+    text: Option<String>,
+    signature: Option<String>,
+}
+
+struct StreamState {
+    tx_event: mpsc::Sender<Result<ResponseEvent, ApiError>>,
+    thought: ThoughtState,
+    assistant_text: Option<String>,
+    response_id: String,
+    next_call_index: usize,
+}
+
+impl StreamState {
+    async fn send(&self, event: ResponseEvent) {
+        let _ = self.tx_event.send(Ok(event)).await;
+    }
+
+    async fn push_part(&mut self, part: Part) {
+        if let Some(signature) = part.thought_signature {
+            self.thought.signature = Some(signature);
+        }
+        if let Some(call) = part.function_call {
+            self.flush().await;
+            let call_id = call.id.unwrap_or_else(|| {
+                self.next_call_index += 1;
+                format!("{}-call-{}", self.response_id, self.next_call_index)
+            });
+            let arguments = call
+                .args
+                .map(|args| args.to_string())
+                .unwrap_or_else(|| "{}".to_string());
+            self.send(ResponseEvent::OutputItemDone(ResponseItem::FunctionCall {
+                id: None,
+                name: call.name,
+                arguments,
+                call_id,
+            }))
+            .await;
+            return;
+        }
+        let Some(text) = part.text.filter(|text| !text.is_empty()) else {
+            return;
+        };
+        if part.thought {
+            match &mut self.thought.text {
+                Some(existing) => existing.push_str(&text),
+                None => {
+                    self.send(ResponseEvent::OutputItemAdded(ResponseItem::Reasoning {
+                        id: String::new(),
+                        summary: Vec::new(),
+                        content: Some(vec![]),
+                        encrypted_content: None,
+                    }))
+                    .await;
+                    self.thought.text = Some(text.clone());
+                }
+            }
+            self.send(ResponseEvent::ReasoningContentDelta {
+                delta: text,
+                content_index: 0,
+            })
+            .await;
+        } else {
+            match &mut self.assistant_text {
+                Some(existing) => existing.push_str(&text),
+                None => {
+                    self.send(ResponseEvent::OutputItemAdded(ResponseItem::Message {
+                        id: None,
+                        role: "assistant".to_string(),
+                        content: vec![],
+                    }))
+                    .await;
+                    self.assistant_text = Some(text.clone());
+                }
+            }
+            self.send(ResponseEvent::OutputTextDelta(text)).await;
+        }
+    }
+
+    /// Completes the pending reasoning and assistant message, in that order.
+    async fn flush(&mut self) {
+        let ThoughtState { text, signature } = std::mem::take(&mut self.thought);
+        if text.is_some() || signature.is_some() {
+            self.send(ResponseEvent::OutputItemDone(ResponseItem::Reasoning {
+                id: String::new(),
+                summary: Vec::new(),
+                content: text.map(|text| vec![ReasoningItemContent::ReasoningText { text }]),
+                encrypted_content: signature,
+            }))
+            .await;
+        }
+        if let Some(text) = self.assistant_text.take() {
+            self.send(ResponseEvent::OutputItemDone(ResponseItem::Message {
+                id: None,
+                role: "assistant".to_string(),
+                content: vec![ContentItem::OutputText { text }],
+            }))
+            .await;
+        }
+    }
+}
+
+pub async fn process_gemini_sse<S>(
+    stream: S,
+    tx_event: mpsc::Sender<Result<ResponseEvent, ApiError>>,
+    idle_timeout: Duration,
+    telemetry: Option<std::sync::Arc<dyn SseTelemetry>>,
+) where
+    S: Stream<Item = Result<bytes::Bytes, codex_client::TransportError>> + Unpin,
+{
+    let mut stream = stream.eventsource();
+    let mut state = StreamState {
+        tx_event,
+        thought: ThoughtState::default(),
+        assistant_text: None,
+        response_id: String::new(),
+        next_call_index: 0,
+    };
+    let mut usage: Option<UsageMetadata> = None;
+    let mut finished = false;
+
+    loop {
+        let start = Instant::now();
+        let response = timeout(idle_timeout, stream.next()).await;
+        if let Some(t) = telemetry.as_ref() {
+            t.on_sse_poll(&response, start.elapsed());
+        }
+        let sse = match response {
+            Ok(Some(Ok(sse))) => sse,
+            Ok(Some(Err(e))) => {
+                let _ = state
+                    .tx_event
+                    .send(Err(ApiError::Stream(e.to_string())))
+                    .await;
+                return;
+            }
+            // Gemini ends the stream by closing it after the chunk that
+            // carries a finish reason.
+            Ok(None) => {
+                if !finished {
+                    let _ = state
+                        .tx_event
+                        .send(Err(ApiError::Stream(
+                            "stream closed before a finish reason".into(),
+                        )))
+                        .await;
+                    return;
+                }
+                state.flush().await;
+                state
+                    .send(ResponseEvent::Completed {
+                        response_id: state.response_id.clone(),
+                        token_usage: usage.map(Into::into),
+                    })
+                    .await;
+                return;
+            }
+            Err(_) => {
+                let _ = state
+                    .tx_event
+                    .send(Err(ApiError::Stream("idle timeout waiting for SSE".into())))
+                    .await;
+                return;
+            }
+        };
+
+        trace!("SSE event: {}", sse.data);
+
+        if sse.data.trim().is_empty() {
+            continue;
+        }
+
+        let chunk: GenerateContentChunk = match serde_json::from_str(&sse.data) {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                debug!(
+                    "Failed to parse Gemini SSE event: {err}, data: {}",
+                    &sse.data
+                );
+                continue;
+            }
+        };
+
+        if let Some(error) = chunk.error {
+            let message = format!("{}: {}", error.status, error.message);
+            let error = match error.status.as_str() {
+                "UNAVAILABLE" | "INTERNAL" => ApiError::Retryable {
+                    message,
+                    delay: None,
+                },
+                _ => ApiError::Stream(message),
+            };
+            let _ = state.tx_event.send(Err(error)).await;
+            return;
+        }
+        if let Some(reason) = chunk.prompt_feedback.and_then(|f| f.block_reason) {
+            let _ = state
+                .tx_event
+                .send(Err(ApiError::Stream(format!(
+                    "prompt blocked by Gemini safety settings ({reason})"
+                ))))
+                .await;
+            return;
+        }
+        if state.response_id.is_empty()
+            && let Some(id) = chunk.response_id
+        {
+            state.response_id = id;
+            state.send(ResponseEvent::Created).await;
+        }
+        if chunk.usage_metadata.is_some() {
+            usage = chunk.usage_metadata;
+        }
+
+        let Some(candidate) = chunk.candidates.into_iter().next() else {
+            continue;
+        };
+        for part in candidate.content.map(|c| c.parts).unwrap_or_default() {
+            state.push_part(part).await;
+        }
+        match candidate.finish_reason.as_deref() {
+            None | Some("FINISH_REASON_UNSPECIFIED") => {}
+            Some("STOP") => finished = true,
+            Some("MAX_TOKENS") => {
+                warn!(
+                    "Gemini response stopped at maxOutputTokens; raise model_max_output_tokens to allow longer replies"
+                );
+                finished = true;
+            }
+            Some(reason) => {
+                let _ = state
+                    .tx_event
+                    .send(Err(ApiError::Stream(format!(
+                        "Gemini stopped the response ({reason})"
+                    ))))
+                    .await;
+                return;
+            }
+        }
+    }
+}
+
+/// `candidatesTokenCount` excludes thinking, which the protocol counts as
+/// part of the output.
+impl From<UsageMetadata> for TokenUsage {
+    fn from(usage: UsageMetadata) -> Self {
+        let output_tokens = usage.candidates_token_count + usage.thoughts_token_count;
+        TokenUsage {
+            input_tokens: usage.prompt_token_count,
+            cached_input_tokens: usage.cached_content_token_count,
+            output_tokens,
+            reasoning_output_tokens: usage.thoughts_token_count,
+            total_tokens: if usage.total_token_count > 0 {
+                usage.total_token_count
+            } else {
+                usage.prompt_token_count + output_tokens
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use futures::TryStreamExt;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tokio_util::io::ReaderStream;
+
+    fn build_body(chunks: &[Value]) -> String {
+        chunks
+            .iter()
+            .map(|chunk| format!("data: {chunk}\n\n"))
+            .collect()
+    }
+
+    async fn collect_events(body: &str) -> Vec<Result<ResponseEvent, ApiError>> {
+        let reader = ReaderStream::new(std::io::Cursor::new(body.to_string()))
+            .map_err(|err| codex_client::TransportError::Network(err.to_string()));
+        let (tx, mut rx) = mpsc::channel::<Result<ResponseEvent, ApiError>>(16);
+        tokio::spawn(process_gemini_sse(
+            reader,
+            tx,
+            Duration::from_millis(1000),
+            None,
+        ));
+
+        let mut out = Vec::new();
+        while let Some(ev) = rx.recv().await {
+            out.push(ev);
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn emits_thoughts_text_and_function_calls() {
+        let body = build_body(&[
+            json!({"responseId": "resp-1", "candidates": [{"content": {"role": "model", "parts": [
+                {"text": "Check the dir.", "thought": true},
+            ]}}]}),
+            json!({"responseId": "resp-1", "candidates": [{"content": {"role": "model", "parts": [
+                {"text": "Listing"},
+                {"functionCall": {"name": "shell", "args": {"command": ["ls"]}}, "thoughtSignature": "sig"},
+            ]}, "finishReason": "STOP"}], "usageMetadata": {
+                "promptTokenCount": 100, "cachedContentTokenCount": 90,
+                "candidatesTokenCount": 15, "thoughtsTokenCount": 5, "totalTokenCount": 120
+            }}),
+        ]);
+
+        let events: Vec<ResponseEvent> = collect_events(&body)
+            .await
+            .into_iter()
+            .map(|event| event.expect("stream error"))
+            .collect();
+
+        let done: Vec<&ResponseItem> = events
+            .iter()
+            .filter_map(|event| match event {
+                ResponseEvent::OutputItemDone(item) => Some(item),
+                _ => None,
+            })
+            .collect();
+        assert_matches!(
+            &done[..],
+            [
+                ResponseItem::Reasoning { content: Some(content), encrypted_content: Some(signature), .. },
+                ResponseItem::Message { content: text, .. },
+                ResponseItem::FunctionCall { call_id, name, arguments, .. },
+            ] if content == &vec![ReasoningItemContent::ReasoningText { text: "Check the dir.".to_string() }]
+                && signature == "sig"
+                && text == &vec![ContentItem::OutputText { text: "Listing".to_string() }]
+                && call_id == "resp-1-call-1"
+                && name == "shell"
+                && arguments == r#"{"command":["ls"]}"#
+        );
+
+        let Some(ResponseEvent::Completed {
+            response_id,
+            token_usage: Some(token_usage),
+        }) = events.last()
+        else {
+            panic!("expected a completed event, got {events:?}");
+        };
+        assert_eq!(response_id, "resp-1");
+        assert_eq!(
+            (
+                token_usage.input_tokens,
+                token_usage.cached_input_tokens,
+                token_usage.output_tokens,
+                token_usage.reasoning_output_tokens,
+                token_usage.total_tokens,
+            ),
+            (100, 90, 20, 5, 120)
+        );
+    }
+
+    #[tokio::test]
+    async fn safety_stops_are_errors() {
+        let body = build_body(&[json!({"responseId": "resp-1", "candidates": [
+            {"content": {"parts": []}, "finishReason": "SAFETY"}
+        ]})]);
+
+        let events = collect_events(&body).await;
+        assert_matches!(
+            &events[..],
+            [Ok(ResponseEvent::Created), Err(ApiError::Stream(message))]
+                if message == "Gemini stopped the response (SAFETY)"
+        );
+    }
+}
@@ -1,5 +1,9 @@
+pub mod anthropic;
+pub mod bedrock;
 pub mod chat;
+pub mod gemini;
 pub mod responses;
+pub(crate) mod tool_emulation;
 
 pub use responses::process_sse;
 pub use responses::spawn_response_stream;
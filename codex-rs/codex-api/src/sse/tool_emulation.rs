@@ -0,0 +1,185 @@
+//! Stream-side half of tool-call emulation: the model answers with a JSON
+//! object (see `requests::tool_emulation`), so assistant text is held back
+//! until the message is complete and then surfaced either as a regular
+//! assistant message or as a `FunctionCall` item.
+
+use crate::common::ResponseEvent;
+use crate::common::ResponseStream;
+use crate::error::ApiError;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tracing::debug;
+
+pub(crate) fn spawn_tool_call_emulation(mut inner: ResponseStream) -> ResponseStream {
+    let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent, ApiError>>(1600);
+    tokio::spawn(async move {
+        while let Some(event) = inner.rx_event.recv().await {
+            let events = match event {
+                Ok(ResponseEvent::OutputItemAdded(ResponseItem::Message { role, .. }))
+                    if role == "assistant" =>
+                {
+                    continue;
+                }
+                Ok(ResponseEvent::OutputTextDelta(_)) => continue,
+                Ok(ResponseEvent::OutputItemDone(ResponseItem::Message {
+                    role, content, ..
+                })) if role == "assistant" => {
+                    let text: String = content
+                        .iter()
+                        .filter_map(|item| match item {
+                            ContentItem::OutputText { text } => Some(text.as_str()),
+                            _ => None,
+                        })
+                        .collect();
+                    decode_emulated_reply(&text)
+                }
+                other => vec![other],
+            };
+            for event in events {
+                if tx_event.send(event).await.is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    ResponseStream { rx_event }
+}
+
+/// Interprets one complete emulated reply. Anything that is not a
+/// recognizable tool call is passed through as assistant text so a model that
+/// ignores the requested format still produces a usable answer.
+fn decode_emulated_reply(text: &str) -> Vec<Result<ResponseEvent, ApiError>> {
+    let parsed = serde_json::from_str::<Value>(strip_code_fence(text)).ok();
+    let is_tool_call = |value: &Value| {
+        value.get("type").and_then(Value::as_str) == Some("tool_call")
+            || value.get("type").is_none() && value.get("arguments").is_some()
+    };
+    match parsed {
+        Some(value) if is_tool_call(&value) => {
+            if let Some(name) = value.get("name").and_then(Value::as_str) {
+                let arguments = match value.get("arguments") {
+                    Some(Value::String(raw)) => raw.clone(),
+                    Some(arguments) => arguments.to_string(),
+                    None => "{}".to_string(),
+                };
+                let item = ResponseItem::FunctionCall {
+                    id: None,
+                    name: name.to_string(),
+                    arguments,
+                    call_id: format!("call_{}", uuid::Uuid::new_v4().simple()),
+                };
+                return vec![Ok(ResponseEvent::OutputItemDone(item))];
+            }
+            debug!("emulated tool call without a name: {text}");
+            assistant_message(text.to_string())
+        }
+        Some(value) => match value.get("content").and_then(Value::as_str) {
+            Some(content) => assistant_message(content.to_string()),
+            None => assistant_message(text.to_string()),
+        },
+        None => assistant_message(text.to_string()),
+    }
+}
+
+fn strip_code_fence(text: &str) -> &str {
+    let trimmed = text.trim();
+    trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map_or(trimmed, str::trim)
+}
+
+fn assistant_message(text: String) -> Vec<Result<ResponseEvent, ApiError>> {
+    let empty = ResponseItem::Message {
+        id: None,
+        role: "assistant".to_string(),
+        content: Vec::new(),
+    };
+    let done = ResponseItem::Message {
+        id: None,
+        role: "assistant".to_string(),
+        content: vec![ContentItem::OutputText { text: text.clone() }],
+    };
+    vec![
+        Ok(ResponseEvent::OutputItemAdded(empty)),
+        Ok(ResponseEvent::OutputTextDelta(text)),
+        Ok(ResponseEvent::OutputItemDone(done)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use pretty_assertions::assert_eq;
+
+    fn assistant_done(text: &str) -> Result<ResponseEvent, ApiError> {
+        Ok(ResponseEvent::OutputItemDone(ResponseItem::Message {
+            id: None,
+            role: "assistant".to_string(),
+            content: vec![ContentItem::OutputText {
+                text: text.to_string(),
+            }],
+        }))
+    }
+
+    async fn collect(events: Vec<Result<ResponseEvent, ApiError>>) -> Vec<ResponseEvent> {
+        let (tx, rx_event) = mpsc::channel(16);
+        for event in events {
+            tx.send(event).await.expect("send");
+        }
+        drop(tx);
+        let mut stream = spawn_tool_call_emulation(ResponseStream { rx_event });
+        let mut out = Vec::new();
+        while let Some(event) = stream.rx_event.recv().await {
+            out.push(event.expect("event"));
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn turns_json_tool_call_into_function_call() {
+        let events = collect(vec![
+            Ok(ResponseEvent::OutputTextDelta("{\"type\":".to_string())),
+            assistant_done(
+                "```json\n{\"type\":\"tool_call\",\"name\":\"shell\",\"arguments\":{\"command\":[\"ls\"]}}\n```",
+            ),
+            Ok(ResponseEvent::Completed {
+                response_id: String::new(),
+                token_usage: None,
+            }),
+        ])
+        .await;
+
+        assert_eq!(events.len(), 2);
+        assert_matches!(
+            &events[0],
+            ResponseEvent::OutputItemDone(ResponseItem::FunctionCall { name, arguments, call_id, .. })
+                if name == "shell" && arguments == "{\"command\":[\"ls\"]}" && call_id.starts_with("call_")
+        );
+        assert_matches!(&events[1], ResponseEvent::Completed { .. });
+    }
+
+    #[tokio::test]
+    async fn unwraps_message_replies_and_passes_plain_text_through() {
+        for (reply, expected) in [
+            (
+                "{\"type\":\"message\",\"content\":\"All done.\"}",
+                "All done.",
+            ),
+            ("Not JSON at all", "Not JSON at all"),
+        ] {
+            let events = collect(vec![assistant_done(reply)]).await;
+            assert_eq!(events.len(), 3);
+            assert_matches!(&events[1], ResponseEvent::OutputTextDelta(delta) if delta == expected);
+            assert_matches!(
+                &events[2],
+                ResponseEvent::OutputItemDone(ResponseItem::Message { content, .. })
+                    if content == &vec![ContentItem::OutputText { text: expected.to_string() }]
+            );
+        }
+    }
+}
@@ -5,8 +5,13 @@ use std::time::Duration;
 use anyhow::Result;
 use async_trait::async_trait;
 use bytes::Bytes;
+use codex_api::AnthropicClient;
+use codex_api::AnthropicOptions;
 use codex_api::AuthProvider;
+use codex_api::AwsCredentials;
 use codex_api::ChatClient;
+use codex_api::GeminiClient;
+use codex_api::GeminiOptions;
 use codex_api::Provider;
 use codex_api::ResponsesClient;
 use codex_api::ResponsesOptions;
@@ -118,6 +123,23 @@ impl AuthProvider for StaticAuth {
     }
 }
 
+#[derive(Clone)]
+struct AwsAuth;
+
+impl AuthProvider for AwsAuth {
+    fn bearer_token(&self) -> Option<String> {
+        None
+    }
+
+    fn aws_credentials(&self) -> Option<AwsCredentials> {
+        Some(AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "secret".to_string(),
+            session_token: Some("session".to_string()),
+        })
+    }
+}
+
 fn provider(name: &str, wire: WireApi) -> Provider {
     Provider {
         name: name.to_string(),
@@ -131,6 +153,7 @@ fn provider(name: &str, wire: WireApi) -> Provider {
             retry_429: false,
             retry_5xx: false,
             retry_transport: true,
+            max_retry_time: None,
         },
         stream_idle_timeout: Duration::from_millis(10),
     }
@@ -284,6 +307,127 @@ async fn streaming_client_adds_auth_headers() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn anthropic_client_posts_messages_with_api_key_header() -> Result<()> {
+    let state = RecordingState::default();
+    let transport = RecordingTransport::new(state.clone());
+    let auth = StaticAuth::new("sk-ant-test", "acct-1");
+    let client = AnthropicClient::new(transport, provider("anthropic", WireApi::Anthropic), auth);
+
+    let prompt = codex_api::Prompt {
+        instructions: "Say hi".to_string(),
+        input: Vec::new(),
+        tools: Vec::<Value>::new(),
+        parallel_tool_calls: false,
+        output_schema: None,
+    };
+    let _stream = client
+        .stream_prompt("claude-test", &prompt, AnthropicOptions::default())
+        .await?;
+
+    let requests = state.take_stream_requests();
+    assert_path_ends_with(&requests, "/messages");
+    let req = &requests[0];
+    assert_eq!(
+        req.headers.get("x-api-key").and_then(|v| v.to_str().ok()),
+        Some("sk-ant-test")
+    );
+    assert_eq!(
+        req.headers
+            .get("anthropic-version")
+            .and_then(|v| v.to_str().ok()),
+        Some("2023-06-01")
+    );
+    assert!(req.headers.get(http::header::AUTHORIZATION).is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn bedrock_client_signs_invoke_with_response_stream() -> Result<()> {
+    let state = RecordingState::default();
+    let transport = RecordingTransport::new(state.clone());
+    let mut provider = provider("bedrock", WireApi::Bedrock);
+    provider.base_url = "https://bedrock-runtime.us-west-2.amazonaws.com".to_string();
+    let client = AnthropicClient::new(transport, provider, AwsAuth);
+
+    let prompt = codex_api::Prompt {
+        instructions: "Say hi".to_string(),
+        input: Vec::new(),
+        tools: Vec::<Value>::new(),
+        parallel_tool_calls: false,
+        output_schema: None,
+    };
+    let _stream = client
+        .stream_prompt(
+            "anthropic.claude-test-v1:0",
+            &prompt,
+            AnthropicOptions::default(),
+        )
+        .await?;
+
+    let requests = state.take_stream_requests();
+    assert_path_ends_with(
+        &requests,
+        "/model/anthropic.claude-test-v1%3A0/invoke-with-response-stream",
+    );
+    let req = &requests[0];
+    let authorization = req
+        .headers
+        .get(http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    assert!(
+        authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/")
+            && authorization.contains("/us-west-2/bedrock/aws4_request")
+            && authorization.contains("SignedHeaders=host;x-amz-date;x-amz-security-token"),
+        "unexpected authorization header: {authorization}"
+    );
+    assert_eq!(
+        req.headers
+            .get("x-amz-security-token")
+            .and_then(|v| v.to_str().ok()),
+        Some("session")
+    );
+    assert!(req.headers.get("anthropic-version").is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn gemini_client_streams_generate_content_with_goog_api_key() -> Result<()> {
+    let state = RecordingState::default();
+    let transport = RecordingTransport::new(state.clone());
+    let auth = StaticAuth::new("gemini-key", "acct-1");
+    let mut provider = provider("gemini", WireApi::Gemini);
+    provider.query_params = Some([("trace".to_string(), "1".to_string())].into());
+    let client = GeminiClient::new(transport, provider, auth);
+
+    let prompt = codex_api::Prompt {
+        instructions: "Say hi".to_string(),
+        input: Vec::new(),
+        tools: Vec::<Value>::new(),
+        parallel_tool_calls: false,
+        output_schema: None,
+    };
+    let _stream = client
+        .stream_prompt("gemini-test", &prompt, GeminiOptions::default())
+        .await?;
+
+    let requests = state.take_stream_requests();
+    assert_path_ends_with(
+        &requests,
+        "/models/gemini-test:streamGenerateContent?alt=sse&trace=1",
+    );
+    let req = &requests[0];
+    assert_eq!(
+        req.headers
+            .get("x-goog-api-key")
+            .and_then(|v| v.to_str().ok()),
+        Some("gemini-key")
+    );
+    assert!(req.headers.get(http::header::AUTHORIZATION).is_none());
+    Ok(())
+}
+
 #[tokio::test]
 async fn streaming_client_retries_on_transport_error() -> Result<()> {
     let transport = FlakyTransport::new();
@@ -43,6 +43,7 @@ fn provider(base_url: &str) -> Provider {
             retry_429: false,
             retry_5xx: true,
             retry_transport: true,
+            max_retry_time: None,
         },
         stream_idle_timeout: std::time::Duration::from_secs(1),
     }
@@ -0,0 +1,553 @@
+//! Record/replay of HTTP exchanges.
+//!
+//! A cassette is a JSONL file with one [`Interaction`] per line. In record mode
+//! [`CassetteTransport`] forwards requests to the wrapped transport and appends
+//! each exchange, including the raw streamed body, once the response has been
+//! consumed. In replay mode the wrapped transport is never called: requests are
+//! answered in order from the cassette, with streamed bodies re-chunked one SSE
+//! event at a time so that runs are deterministic.
+//!
+//! Request headers are never written, so credentials stay out of cassettes.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::Stream;
+use http::HeaderMap;
+use http::HeaderName;
+use http::HeaderValue;
+use http::StatusCode;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::warn;
+
+use crate::error::TransportError;
+use crate::request::Request;
+use crate::request::Response;
+use crate::transport::ByteStream;
+use crate::transport::HttpTransport;
+use crate::transport::StreamResponse;
+
+/// Response headers that are never written to a cassette.
+const SKIPPED_HEADERS: &[&str] = &["set-cookie"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+/// One recorded request/response exchange.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    /// Path and query of the request URL; the host is not recorded so that a
+    /// cassette replays against any base URL.
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<Value>,
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+/// Cassettes opened by this process, keyed by path, so that every transport
+/// built for the same file shares one recording or one replay cursor.
+static CASSETTES: LazyLock<Mutex<HashMap<PathBuf, Arc<Cassette>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    /// Interactions not yet served (replay) or the write lock (record).
+    pending: Mutex<VecDeque<Interaction>>,
+}
+
+impl Cassette {
+    /// Opens the cassette at `path`. The first record-mode open in a process
+    /// truncates the file; later opens of the same path return the shared
+    /// cassette.
+    pub fn open(mode: CassetteMode, path: &Path) -> io::Result<Arc<Self>> {
+        let mut cassettes = CASSETTES
+            .lock()
+            .map_err(|_| io::Error::other("cassette registry poisoned"))?;
+        if let Some(cassette) = cassettes.get(path) {
+            if cassette.mode != mode {
+                return Err(io::Error::other(format!(
+                    "cassette {} is already open in {:?} mode",
+                    path.display(),
+                    cassette.mode
+                )));
+            }
+            return Ok(Arc::clone(cassette));
+        }
+
+        let pending = match mode {
+            CassetteMode::Record => {
+                if let Some(parent) = path.parent()
+                    && !parent.as_os_str().is_empty()
+                {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::File::create(path)?;
+                VecDeque::new()
+            }
+            CassetteMode::Replay => std::fs::read_to_string(path)?
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| serde_json::from_str(line).map_err(io::Error::other))
+                .collect::<io::Result<_>>()?,
+        };
+        let cassette = Arc::new(Self {
+            path: path.to_path_buf(),
+            mode,
+            pending: Mutex::new(pending),
+        });
+        cassettes.insert(path.to_path_buf(), Arc::clone(&cassette));
+        Ok(cassette)
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn append(&self, interaction: &Interaction) {
+        let Ok(_guard) = self.pending.lock() else {
+            return;
+        };
+        let result = serde_json::to_string(interaction)
+            .map_err(io::Error::other)
+            .and_then(|line| {
+                let mut file = OpenOptions::new().append(true).open(&self.path)?;
+                writeln!(file, "{line}")
+            });
+        if let Err(err) = result {
+            warn!("failed to write cassette {}: {err}", self.path.display());
+        }
+    }
+
+    fn next_interaction(&self, req: &Request) -> Result<Interaction, TransportError> {
+        let path = url_path(&req.url);
+        let interaction = self
+            .pending
+            .lock()
+            .map_err(|_| TransportError::Network("cassette poisoned".to_string()))?
+            .pop_front()
+            .ok_or_else(|| {
+                TransportError::Network(format!(
+                    "cassette {} has no recorded response for {} {path}",
+                    self.path.display(),
+                    req.method
+                ))
+            })?;
+        if interaction.method != req.method.as_str() || interaction.path != path {
+            return Err(TransportError::Network(format!(
+                "cassette {} expected {} {} but got {} {path}",
+                self.path.display(),
+                interaction.method,
+                interaction.path,
+                req.method
+            )));
+        }
+        Ok(interaction)
+    }
+}
+
+/// Transport that records exchanges of `inner` to a cassette or replays them
+/// from one. Without a cassette it forwards to `inner` unchanged.
+#[derive(Clone, Debug)]
+pub struct CassetteTransport<T> {
+    inner: T,
+    cassette: Option<Arc<Cassette>>,
+}
+
+impl<T> CassetteTransport<T> {
+    pub fn new(inner: T, cassette: Option<Arc<Cassette>>) -> Self {
+        Self { inner, cassette }
+    }
+}
+
+#[async_trait]
+impl<T: HttpTransport> HttpTransport for CassetteTransport<T> {
+    async fn execute(&self, req: Request) -> Result<Response, TransportError> {
+        let Some(cassette) = &self.cassette else {
+            return self.inner.execute(req).await;
+        };
+        match cassette.mode {
+            CassetteMode::Replay => {
+                let interaction = cassette.next_interaction(&req)?;
+                let status = replayed_status(&interaction)?;
+                let headers = header_map(&interaction.headers);
+                if !status.is_success() {
+                    return Err(TransportError::Http {
+                        status,
+                        headers: Some(headers),
+                        body: Some(interaction.body),
+                    });
+                }
+                Ok(Response {
+                    status,
+                    headers,
+                    body: Bytes::from(interaction.body),
+                })
+            }
+            CassetteMode::Record => {
+                let mut interaction = pending_interaction(&req);
+                let result = self.inner.execute(req).await;
+                match &result {
+                    Ok(resp) => {
+                        interaction.status = resp.status.as_u16();
+                        interaction.headers = recorded_headers(&resp.headers);
+                        interaction.body = String::from_utf8_lossy(&resp.body).into_owned();
+                    }
+                    Err(err) => record_error(&mut interaction, err),
+                }
+                if interaction.status != 0 {
+                    cassette.append(&interaction);
+                }
+                result
+            }
+        }
+    }
+
+    async fn stream(&self, req: Request) -> Result<StreamResponse, TransportError> {
+        let Some(cassette) = &self.cassette else {
+            return self.inner.stream(req).await;
+        };
+        match cassette.mode {
+            CassetteMode::Replay => {
+                let interaction = cassette.next_interaction(&req)?;
+                let status = replayed_status(&interaction)?;
+                let headers = header_map(&interaction.headers);
+                if !status.is_success() {
+                    return Err(TransportError::Http {
+                        status,
+                        headers: Some(headers),
+                        body: Some(interaction.body),
+                    });
+                }
+                let chunks = split_events(&interaction.body)
+                    .into_iter()
+                    .map(|chunk| Ok::<_, TransportError>(Bytes::from(chunk)));
+                Ok(StreamResponse {
+                    status,
+                    headers,
+                    bytes: Box::pin(futures::stream::iter(chunks)),
+                })
+            }
+            CassetteMode::Record => {
+                let mut interaction = pending_interaction(&req);
+                match self.inner.stream(req).await {
+                    Ok(resp) => {
+                        interaction.status = resp.status.as_u16();
+                        interaction.headers = recorded_headers(&resp.headers);
+                        let bytes: ByteStream = Box::pin(RecordingStream {
+                            inner: resp.bytes,
+                            body: Vec::new(),
+                            pending: Some((Arc::clone(cassette), interaction)),
+                        });
+                        Ok(StreamResponse {
+                            status: resp.status,
+                            headers: resp.headers,
+                            bytes,
+                        })
+                    }
+                    Err(err) => {
+                        record_error(&mut interaction, &err);
+                        if interaction.status != 0 {
+                            cassette.append(&interaction);
+                        }
+                        Err(err)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Passes bytes through while keeping a copy, and writes the interaction once
+/// the stream ends or is dropped by its consumer.
+struct RecordingStream {
+    inner: ByteStream,
+    body: Vec<u8>,
+    pending: Option<(Arc<Cassette>, Interaction)>,
+}
+
+impl RecordingStream {
+    fn finish(&mut self) {
+        if let Some((cassette, mut interaction)) = self.pending.take() {
+            interaction.body = String::from_utf8_lossy(&self.body).into_owned();
+            cassette.append(&interaction);
+        }
+    }
+}
+
+impl Stream for RecordingStream {
+    type Item = Result<Bytes, TransportError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let polled = self.inner.as_mut().poll_next(cx);
+        match &polled {
+            Poll::Ready(Some(Ok(bytes))) => self.body.extend_from_slice(bytes),
+            Poll::Ready(None) => self.finish(),
+            Poll::Ready(Some(Err(_))) | Poll::Pending => {}
+        }
+        polled
+    }
+}
+
+impl Drop for RecordingStream {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+fn pending_interaction(req: &Request) -> Interaction {
+    Interaction {
+        method: req.method.to_string(),
+        path: url_path(&req.url),
+        request_body: req.body.clone(),
+        status: 0,
+        headers: BTreeMap::new(),
+        body: String::new(),
+    }
+}
+
+/// Fills in HTTP error responses; transport failures without a response are
+/// left unrecorded (status stays 0).
+fn record_error(interaction: &mut Interaction, err: &TransportError) {
+    if let TransportError::Http {
+        status,
+        headers,
+        body,
+    } = err
+    {
+        interaction.status = status.as_u16();
+        interaction.headers = headers.as_ref().map(recorded_headers).unwrap_or_default();
+        interaction.body = body.clone().unwrap_or_default();
+    }
+}
+
+fn replayed_status(interaction: &Interaction) -> Result<StatusCode, TransportError> {
+    StatusCode::from_u16(interaction.status)
+        .map_err(|err| TransportError::Network(format!("invalid status in cassette: {err}")))
+}
+
+fn recorded_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter(|(name, _)| !SKIPPED_HEADERS.contains(&name.as_str()))
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+fn header_map(headers: &BTreeMap<String, String>) -> HeaderMap {
+    headers
+        .iter()
+        .filter_map(|(name, value)| {
+            Some((
+                HeaderName::from_bytes(name.as_bytes()).ok()?,
+                HeaderValue::from_str(value).ok()?,
+            ))
+        })
+        .collect()
+}
+
+/// Strips scheme and host from `url`, keeping path and query.
+fn url_path(url: &str) -> String {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    match without_scheme.find('/') {
+        Some(idx) => without_scheme[idx..].to_string(),
+        None => "/".to_string(),
+    }
+}
+
+/// Splits an SSE body into events, each keeping its trailing blank line.
+fn split_events(body: &str) -> Vec<String> {
+    let mut events = Vec::new();
+    let mut rest = body;
+    while let Some(idx) = rest.find("\n\n") {
+        events.push(rest[..idx + 2].to_string());
+        rest = &rest[idx + 2..];
+    }
+    if !rest.is_empty() {
+        events.push(rest.to_string());
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use http::Method;
+    use pretty_assertions::assert_eq;
+
+    struct FixedTransport {
+        status: StatusCode,
+        chunks: Vec<&'static str>,
+    }
+
+    #[async_trait]
+    impl HttpTransport for FixedTransport {
+        async fn execute(&self, _req: Request) -> Result<Response, TransportError> {
+            Ok(Response {
+                status: self.status,
+                headers: HeaderMap::new(),
+                body: Bytes::from(self.chunks.concat()),
+            })
+        }
+
+        async fn stream(&self, _req: Request) -> Result<StreamResponse, TransportError> {
+            if !self.status.is_success() {
+                return Err(TransportError::Http {
+                    status: self.status,
+                    headers: None,
+                    body: Some(self.chunks.concat()),
+                });
+            }
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                "content-type",
+                HeaderValue::from_static("text/event-stream"),
+            );
+            headers.insert("set-cookie", HeaderValue::from_static("session=secret"));
+            let chunks = self
+                .chunks
+                .clone()
+                .into_iter()
+                .map(|c| Ok::<_, TransportError>(Bytes::from(c)));
+            Ok(StreamResponse {
+                status: self.status,
+                headers,
+                bytes: Box::pin(futures::stream::iter(chunks)),
+            })
+        }
+    }
+
+    fn request() -> Request {
+        let mut req = Request::new(
+            Method::POST,
+            "http://127.0.0.1:1234/v1/responses".to_string(),
+        )
+        .with_json(&serde_json::json!({"model": "m"}));
+        req.headers.insert(
+            "authorization",
+            HeaderValue::from_static("Bearer sk-secret"),
+        );
+        req
+    }
+
+    async fn collect(resp: StreamResponse) -> Vec<Bytes> {
+        resp.bytes
+            .map(|chunk| chunk.expect("chunk"))
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn records_streams_and_replays_them_event_by_event() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("cassette.jsonl");
+
+        let recorder = CassetteTransport::new(
+            FixedTransport {
+                status: StatusCode::OK,
+                chunks: vec!["data: {\"a\":1}\n\nda", "ta: {\"b\":2}\n\n"],
+            },
+            Some(Cassette::open(CassetteMode::Record, &path).expect("record")),
+        );
+        let recorded = collect(recorder.stream(request()).await.expect("stream")).await;
+        assert_eq!(recorded.concat(), b"data: {\"a\":1}\n\ndata: {\"b\":2}\n\n");
+
+        let contents = std::fs::read_to_string(&path).expect("read cassette");
+        assert!(!contents.contains("sk-secret"));
+        assert!(!contents.contains("session=secret"));
+
+        // Registry entries are per path, so replay from a copy of the file.
+        let replay_path = dir.path().join("replay.jsonl");
+        std::fs::copy(&path, &replay_path).expect("copy");
+        let replayer = CassetteTransport::new(
+            FixedTransport {
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                chunks: Vec::new(),
+            },
+            Some(Cassette::open(CassetteMode::Replay, &replay_path).expect("replay")),
+        );
+        let resp = replayer.stream(request()).await.expect("replayed");
+        assert_eq!(
+            resp.headers.get("content-type"),
+            Some(&HeaderValue::from_static("text/event-stream"))
+        );
+        assert_eq!(
+            collect(resp).await,
+            vec![
+                Bytes::from_static(b"data: {\"a\":1}\n\n"),
+                Bytes::from_static(b"data: {\"b\":2}\n\n"),
+            ]
+        );
+
+        let exhausted = replayer.stream(request()).await;
+        assert!(matches!(exhausted, Err(TransportError::Network(_))));
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_http_errors() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("cassette.jsonl");
+
+        let recorder = CassetteTransport::new(
+            FixedTransport {
+                status: StatusCode::SERVICE_UNAVAILABLE,
+                chunks: vec!["overloaded"],
+            },
+            Some(Cassette::open(CassetteMode::Record, &path).expect("record")),
+        );
+        assert!(recorder.stream(request()).await.is_err());
+
+        let replay_path = dir.path().join("replay.jsonl");
+        std::fs::copy(&path, &replay_path).expect("copy");
+        let replayer = CassetteTransport::new(
+            recorder.inner,
+            Some(Cassette::open(CassetteMode::Replay, &replay_path).expect("replay")),
+        );
+        match replayer.stream(request()).await {
+            Err(TransportError::Http { status, body, .. }) => {
+                assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+                assert_eq!(body.as_deref(), Some("overloaded"));
+            }
+            other => panic!("expected http error, got {:?}", other.map(|r| r.status)),
+        }
+    }
+
+    #[test]
+    fn url_path_drops_scheme_and_host() {
+        assert_eq!(
+            url_path("https://api.openai.com/v1/responses?x=1"),
+            "/v1/responses?x=1"
+        );
+        assert_eq!(url_path("http://localhost:8080"), "/");
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::OnceLock;
+use std::sync::PoisonError;
+
+use reqwest::Certificate;
+use reqwest::ClientBuilder;
+use reqwest::NoProxy;
+use reqwest::Proxy;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Proxy and TLS settings for outbound HTTP, from `[http]` in config.toml
+/// or a model provider's `http` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct HttpClientOptions {
+    /// Proxy URL for every request. When unset, `HTTPS_PROXY`, `HTTP_PROXY`
+    /// and `ALL_PROXY` from the environment apply.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Hosts that bypass the proxy, comma-separated as in `NO_PROXY`, which
+    /// applies when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_proxy: Option<String>,
+    /// PEM bundle of root certificates trusted in addition to the built-in
+    /// ones, e.g. a corporate TLS-inspection CA.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_certificate: Option<PathBuf>,
+}
+
+/// Process-wide settings, used by every client Codex builds. Like the
+/// `User-Agent` suffix, this is a global because HTTP clients are created
+/// in many places that never see the config. It is set once, at startup, so
+/// a server hosting several sessions does not switch proxies whenever one
+/// of them loads a different config.
+static HTTP_CLIENT_OPTIONS: OnceLock<HttpClientOptions> = OnceLock::new();
+
+/// Parsed CA bundles by path, so building a client does not read and parse
+/// the PEM file again.
+static CERTIFICATES: LazyLock<Mutex<HashMap<PathBuf, Vec<Certificate>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Sets the process-wide settings. Only the first call takes effect; it
+/// returns whether this one did.
+pub fn init_http_client_options(options: HttpClientOptions) -> bool {
+    HTTP_CLIENT_OPTIONS.set(options).is_ok()
+}
+
+pub fn http_client_options() -> HttpClientOptions {
+    HTTP_CLIENT_OPTIONS.get().cloned().unwrap_or_default()
+}
+
+/// Applies the process-wide settings to `builder`.
+pub fn apply_http_client_options(builder: ClientBuilder) -> ClientBuilder {
+    http_client_options().apply(builder)
+}
+
+impl HttpClientOptions {
+    /// `self` with every setting `overrides` provides replaced.
+    pub fn with_overrides(&self, overrides: &HttpClientOptions) -> Self {
+        Self {
+            proxy: overrides.proxy.clone().or_else(|| self.proxy.clone()),
+            no_proxy: overrides.no_proxy.clone().or_else(|| self.no_proxy.clone()),
+            ca_certificate: overrides
+                .ca_certificate
+                .clone()
+                .or_else(|| self.ca_certificate.clone()),
+        }
+    }
+
+    /// Checks that the proxy URL parses and the CA bundle can be read, so
+    /// mistakes surface when the config is loaded rather than as TLS errors.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(proxy) = &self.proxy {
+            Proxy::all(proxy).map_err(|err| format!("invalid proxy `{proxy}`: {err}"))?;
+        }
+        if let Some(path) = &self.ca_certificate {
+            load_certificates(path)?;
+        }
+        Ok(())
+    }
+
+    /// Configures `builder` to use the proxy and trust the extra roots.
+    /// Settings that fail to load are logged and skipped; [`Self::validate`]
+    /// reports them up front.
+    pub fn apply(&self, mut builder: ClientBuilder) -> ClientBuilder {
+        if let Some(path) = &self.ca_certificate {
+            match load_certificates(path) {
+                Ok(certificates) => {
+                    for certificate in certificates {
+                        builder = builder.add_root_certificate(certificate);
+                    }
+                }
+                Err(err) => tracing::warn!("{err}"),
+            }
+        }
+
+        // Without either setting, reqwest reads the proxy variables itself.
+        if self.proxy.is_none() && self.no_proxy.is_none() {
+            return builder;
+        }
+        let Some(url) = self.proxy.clone().or_else(proxy_from_env) else {
+            return builder;
+        };
+        let no_proxy = match &self.no_proxy {
+            Some(hosts) => NoProxy::from_string(hosts),
+            None => NoProxy::from_env(),
+        };
+        match Proxy::all(&url) {
+            Ok(proxy) => builder.proxy(proxy.no_proxy(no_proxy)),
+            Err(err) => {
+                tracing::warn!("ignoring invalid proxy `{url}`: {err}");
+                builder
+            }
+        }
+    }
+}
+
+fn proxy_from_env() -> Option<String> {
+    [
+        "HTTPS_PROXY",
+        "https_proxy",
+        "HTTP_PROXY",
+        "http_proxy",
+        "ALL_PROXY",
+        "all_proxy",
+    ]
+    .iter()
+    .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+}
+
+fn load_certificates(path: &Path) -> Result<Vec<Certificate>, String> {
+    if let Some(certificates) = cached_certificates().get(path) {
+        return Ok(certificates.clone());
+    }
+    let pem = std::fs::read(path)
+        .map_err(|err| format!("failed to read CA certificate {}: {err}", path.display()))?;
+    let certificates = Certificate::from_pem_bundle(&pem)
+        .map_err(|err| format!("invalid CA certificate {}: {err}", path.display()))?;
+    if certificates.is_empty() {
+        return Err(format!(
+            "no certificates found in CA certificate {}",
+            path.display()
+        ));
+    }
+    cached_certificates().insert(path.to_path_buf(), certificates.clone());
+    Ok(certificates)
+}
+
+fn cached_certificates() -> MutexGuard<'static, HashMap<PathBuf, Vec<Certificate>>> {
+    CERTIFICATES.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn overrides_replace_only_the_settings_they_set() {
+        let global = HttpClientOptions {
+            proxy: Some("http://proxy.corp:3128".to_string()),
+            no_proxy: Some("localhost".to_string()),
+            ca_certificate: Some(PathBuf::from("/etc/corp-ca.pem")),
+        };
+        let provider = HttpClientOptions {
+            proxy: Some("http://other:8080".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            global.with_overrides(&provider),
+            HttpClientOptions {
+                proxy: Some("http://other:8080".to_string()),
+                no_proxy: Some("localhost".to_string()),
+                ca_certificate: Some(PathBuf::from("/etc/corp-ca.pem")),
+            }
+        );
+    }
+
+    #[test]
+    fn validate_reports_missing_bundles() {
+        let options = HttpClientOptions {
+            ca_certificate: Some(PathBuf::from("/nonexistent/corp-ca.pem")),
+            ..Default::default()
+        };
+
+        let err = options.validate().expect_err("missing bundle");
+        assert!(
+            err.starts_with("failed to read CA certificate /nonexistent/corp-ca.pem"),
+            "{err}"
+        );
+    }
+}
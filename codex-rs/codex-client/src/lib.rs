@@ -1,20 +1,31 @@
+mod cassette;
 mod default_client;
 mod error;
+mod http_options;
 mod request;
 mod retry;
 mod sse;
 mod telemetry;
 mod transport;
 
+pub use crate::cassette::Cassette;
+pub use crate::cassette::CassetteMode;
+pub use crate::cassette::CassetteTransport;
+pub use crate::cassette::Interaction;
 pub use crate::default_client::CodexHttpClient;
 pub use crate::default_client::CodexRequestBuilder;
 pub use crate::error::StreamError;
 pub use crate::error::TransportError;
+pub use crate::http_options::HttpClientOptions;
+pub use crate::http_options::apply_http_client_options;
+pub use crate::http_options::http_client_options;
+pub use crate::http_options::init_http_client_options;
 pub use crate::request::Request;
 pub use crate::request::Response;
 pub use crate::retry::RetryOn;
 pub use crate::retry::RetryPolicy;
 pub use crate::retry::backoff;
+pub use crate::retry::retry_after;
 pub use crate::retry::run_with_retry;
 pub use crate::sse::sse_stream;
 pub use crate::telemetry::RequestTelemetry;
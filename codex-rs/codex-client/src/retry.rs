@@ -1,8 +1,10 @@
 use crate::error::TransportError;
 use crate::request::Request;
+use http::HeaderMap;
 use rand::Rng;
 use std::future::Future;
 use std::time::Duration;
+use std::time::Instant;
 use tokio::time::sleep;
 
 #[derive(Debug, Clone)]
@@ -10,6 +12,9 @@ pub struct RetryPolicy {
     pub max_attempts: u64,
     pub base_delay: Duration,
     pub retry_on: RetryOn,
+    /// Upper bound on the total time spent waiting between attempts. A retry
+    /// whose delay would exceed what is left fails with the last error instead.
+    pub max_retry_time: Option<Duration>,
 }
 
 #[derive(Debug, Clone)]
@@ -17,6 +22,10 @@ pub struct RetryOn {
     pub retry_429: bool,
     pub retry_5xx: bool,
     pub retry_transport: bool,
+    /// Error bodies containing any of these markers are terminal even when
+    /// their status is otherwise retryable (e.g. an exhausted quota reported
+    /// as 429).
+    pub terminal_markers: Vec<String>,
 }
 
 impl RetryOn {
@@ -25,14 +34,23 @@ impl RetryOn {
             return false;
         }
         match err {
-            TransportError::Http { status, .. } => {
-                (self.retry_429 && status.as_u16() == 429)
-                    || (self.retry_5xx && status.is_server_error())
+            TransportError::Http { status, body, .. } => {
+                let retryable = (self.retry_429 && status.as_u16() == 429)
+                    || (self.retry_5xx && status.is_server_error());
+                retryable && !self.is_terminal(body.as_deref())
             }
             TransportError::Timeout | TransportError::Network(_) => self.retry_transport,
             _ => false,
         }
     }
+
+    fn is_terminal(&self, body: Option<&str>) -> bool {
+        body.is_some_and(|body| {
+            self.terminal_markers
+                .iter()
+                .any(|marker| body.contains(marker.as_str()))
+        })
+    }
 }
 
 pub fn backoff(base: Duration, attempt: u64) -> Duration {
@@ -46,15 +64,81 @@ pub fn backoff(base: Duration, attempt: u64) -> Duration {
     Duration::from_millis((raw as f64 * jitter) as u64)
 }
 
+/// Longest server-requested delay honoured. Longer requests are clamped to
+/// it, so a bogus header cannot park a request indefinitely.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// Delay requested by the server through `Retry-After` (seconds),
+/// `retry-after-ms`, or, when a limit is exhausted, the OpenAI-style
+/// `x-ratelimit-reset-{requests,tokens}` headers.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+
+    if let Some(ms) = header("retry-after-ms").and_then(|v| v.trim().parse::<f64>().ok()) {
+        return Some(clamped_secs(ms / 1000.0));
+    }
+    if let Some(secs) = header("retry-after").and_then(|v| v.trim().parse::<f64>().ok()) {
+        return Some(clamped_secs(secs));
+    }
+    ["requests", "tokens"]
+        .into_iter()
+        .filter(|kind| header(&format!("x-ratelimit-remaining-{kind}")) == Some("0"))
+        .filter_map(|kind| header(&format!("x-ratelimit-reset-{kind}")).and_then(parse_reset))
+        .max()
+}
+
+/// Parses reset durations such as `20ms`, `1.5s` or `6m0s`.
+fn parse_reset(value: &str) -> Option<Duration> {
+    let mut total = Duration::ZERO;
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let split = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(split);
+        let number: f64 = number.parse().ok()?;
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        let secs = match unit {
+            "ms" => number / 1000.0,
+            "s" | "" => number,
+            "m" => number * 60.0,
+            "h" => number * 3600.0,
+            _ => return None,
+        };
+        total = total.saturating_add(clamped_secs(secs));
+        rest = tail;
+    }
+    Some(total.min(MAX_RETRY_AFTER))
+}
+
+/// `secs` as a duration between zero and [`MAX_RETRY_AFTER`]; huge or
+/// infinite values clamp to the maximum instead of panicking.
+fn clamped_secs(secs: f64) -> Duration {
+    Duration::try_from_secs_f64(secs.max(0.0))
+        .unwrap_or(MAX_RETRY_AFTER)
+        .min(MAX_RETRY_AFTER)
+}
+
+/// Runs `op` until it succeeds, fails with a terminal error, or the policy is
+/// exhausted. `on_wait` is called with the attempt that failed, the delay
+/// before the next one and the error, before sleeping.
 pub async fn run_with_retry<T, F, Fut>(
     policy: RetryPolicy,
     mut make_req: impl FnMut() -> Request,
     op: F,
+    on_wait: impl Fn(u64, Duration, &TransportError),
 ) -> Result<T, TransportError>
 where
     F: Fn(Request, u64) -> Fut,
     Fut: Future<Output = Result<T, TransportError>>,
 {
+    let mut waited = Duration::ZERO;
     for attempt in 0..=policy.max_attempts {
         let req = make_req();
         match op(req, attempt).await {
@@ -64,10 +148,194 @@ where
                     .retry_on
                     .should_retry(&err, attempt, policy.max_attempts) =>
             {
-                sleep(backoff(policy.base_delay, attempt + 1)).await;
+                let requested = match &err {
+                    TransportError::Http {
+                        headers: Some(headers),
+                        ..
+                    } => retry_after(headers),
+                    _ => None,
+                };
+                let delay = requested.unwrap_or_else(|| backoff(policy.base_delay, attempt + 1));
+                if policy
+                    .max_retry_time
+                    .is_some_and(|max| waited.saturating_add(delay) > max)
+                {
+                    return Err(err);
+                }
+                on_wait(attempt, delay, &err);
+                let started = Instant::now();
+                sleep(delay).await;
+                waited = waited.saturating_add(started.elapsed());
             }
             Err(err) => return Err(err),
         }
     }
     Err(TransportError::RetryLimit)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+    use http::Method;
+    use http::StatusCode;
+    use pretty_assertions::assert_eq;
+    use std::sync::Mutex;
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| {
+                (
+                    http::HeaderName::from_static(name),
+                    HeaderValue::from_static(value),
+                )
+            })
+            .collect()
+    }
+
+    fn policy(max_retry_time: Option<Duration>) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            retry_on: RetryOn {
+                retry_429: true,
+                retry_5xx: true,
+                retry_transport: true,
+                terminal_markers: vec!["insufficient_quota".to_string()],
+            },
+            max_retry_time,
+        }
+    }
+
+    fn rate_limited(retry_after: &'static str, body: &str) -> TransportError {
+        TransportError::Http {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            headers: Some(headers(&[("retry-after", retry_after)])),
+            body: Some(body.to_string()),
+        }
+    }
+
+    #[test]
+    fn retry_after_prefers_explicit_headers_over_reset_hints() {
+        assert_eq!(
+            retry_after(&headers(&[("retry-after", "20")])),
+            Some(Duration::from_secs(20))
+        );
+        assert_eq!(
+            retry_after(&headers(&[
+                ("retry-after-ms", "250"),
+                ("retry-after", "20")
+            ])),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(
+            retry_after(&headers(&[
+                ("x-ratelimit-remaining-requests", "0"),
+                ("x-ratelimit-reset-requests", "1m30s"),
+                ("x-ratelimit-remaining-tokens", "100"),
+                ("x-ratelimit-reset-tokens", "6m0s"),
+            ])),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(
+            retry_after(&headers(&[(
+                "retry-after",
+                "Wed, 21 Oct 2015 07:28:00 GMT"
+            )])),
+            None
+        );
+    }
+
+    #[test]
+    fn huge_retry_hints_clamp_instead_of_panicking() {
+        for value in ["1e30", "inf", "1e308"] {
+            let value = HeaderValue::from_str(value).expect("header value");
+            let mut map = HeaderMap::new();
+            map.insert("retry-after", value.clone());
+            assert_eq!(retry_after(&map), Some(MAX_RETRY_AFTER));
+            let mut map = HeaderMap::new();
+            map.insert("retry-after-ms", value);
+            assert_eq!(retry_after(&map), Some(MAX_RETRY_AFTER));
+        }
+        assert_eq!(
+            retry_after(&headers(&[
+                ("x-ratelimit-remaining-tokens", "0"),
+                (
+                    "x-ratelimit-reset-tokens",
+                    "9000000000000000000000h9000000000000000000000h"
+                ),
+            ])),
+            Some(MAX_RETRY_AFTER)
+        );
+        assert_eq!(
+            retry_after(&headers(&[("retry-after", "-5")])),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn terminal_markers_stop_retries() {
+        let on = policy(None).retry_on;
+        assert!(on.should_retry(&rate_limited("1", "slow down"), 0, 3));
+        assert!(!on.should_retry(
+            &rate_limited("1", r#"{"error":{"code":"insufficient_quota"}}"#),
+            0,
+            3
+        ));
+        let bad_request = TransportError::Http {
+            status: StatusCode::BAD_REQUEST,
+            headers: None,
+            body: None,
+        };
+        assert!(!on.should_retry(&bad_request, 0, 3));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_for_retry_after_and_reports_it() {
+        let calls = AtomicU64::new(0);
+        let waits = Mutex::new(Vec::new());
+        let result = run_with_retry(
+            policy(None),
+            || Request::new(Method::POST, "http://localhost/v1/responses".to_string()),
+            |_req, _attempt| {
+                let call = calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if call == 0 {
+                        Err(rate_limited("20", "slow down"))
+                    } else {
+                        Ok(call)
+                    }
+                }
+            },
+            |attempt, delay, _err| waits.lock().unwrap().push((attempt, delay)),
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(*waits.lock().unwrap(), vec![(0, Duration::from_secs(20))]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_when_retry_after_exceeds_the_time_cap() {
+        let calls = AtomicU64::new(0);
+        let result: Result<(), _> = run_with_retry(
+            policy(Some(Duration::from_secs(10))),
+            || Request::new(Method::POST, "http://localhost/v1/responses".to_string()),
+            |_req, _attempt| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async { Err(rate_limited("20", "slow down")) }
+            },
+            |_, _, _| {},
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(TransportError::Http { status, .. }) if status == StatusCode::TOO_MANY_REQUESTS
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
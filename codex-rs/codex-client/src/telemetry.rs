@@ -11,4 +11,8 @@ pub trait RequestTelemetry: Send + Sync {
         error: Option<&TransportError>,
         duration: Duration,
     );
+
+    /// Called before sleeping ahead of a retry. `attempt` is the attempt that
+    /// failed with `error`, and `delay` the time until the next one.
+    fn on_retry_wait(&self, _attempt: u64, _delay: Duration, _error: &TransportError) {}
 }
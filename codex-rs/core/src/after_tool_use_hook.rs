@@ -0,0 +1,211 @@
+//! `AfterToolUse` hooks, run once a tool call has produced its result.
+//!
+//! Codex runs each hook with the finished call serialized as JSON in its
+//! final argument. A hook that prints `{"decision": "block", "reason": "..."}`
+//! on stdout sends the reason back to the model with the tool result, so the
+//! model can act on it, e.g. "the file you wrote fails prettier; fix the
+//! formatting". Any other output, a non-zero exit or a timeout leaves the
+//! result unchanged.
+
+use std::time::Duration;
+
+use codex_protocol::models::ResponseInputItem;
+use mcp_types::ContentBlock;
+use mcp_types::TextContent;
+use serde::Serialize;
+use tracing::error;
+use tracing::warn;
+
+use crate::hooks;
+use crate::hooks::HookCommand;
+use crate::hooks::HookOutput;
+use crate::hooks::HookSandbox;
+
+/// How long a tool result waits for each hook.
+pub(crate) const AFTER_TOOL_USE_HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The finished tool call handed to the hook.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename = "after-tool-use", rename_all = "kebab-case")]
+pub(crate) struct AfterToolUse<'a> {
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: &'a str,
+    pub(crate) call_id: &'a str,
+    pub(crate) tool_name: &'a str,
+    pub(crate) input: &'a str,
+    pub(crate) output: String,
+    pub(crate) success: bool,
+}
+
+/// Runs the hook for at most `timeout` and returns the reason it blocked
+/// the result with, if any.
+pub(crate) async fn run(
+    hook: &HookCommand,
+    call: &AfterToolUse<'_>,
+    sandbox: &HookSandbox,
+    timeout: Duration,
+) -> Option<String> {
+    let Some(json) = hooks::payload(call) else {
+        error!("failed to serialise tool call for after-tool-use hook");
+        return None;
+    };
+    match hooks::output(hook, json, sandbox, timeout).await {
+        Ok(stdout) => parse_feedback(&stdout),
+        Err(err) => {
+            warn!("after-tool-use hook {err}; keeping the tool result");
+            None
+        }
+    }
+}
+
+fn parse_feedback(stdout: &str) -> Option<String> {
+    let response = match HookOutput::parse(stdout) {
+        Ok(response) => response?,
+        Err(err) => {
+            warn!("after-tool-use hook {err}; keeping the tool result");
+            return None;
+        }
+    };
+    if response.decision.as_deref() != Some("block") {
+        return None;
+    }
+    let reason = response.reason.unwrap_or_default();
+    let reason = reason.trim();
+    Some(if reason.is_empty() {
+        "An after-tool-use hook blocked this result.".to_string()
+    } else {
+        reason.to_string()
+    })
+}
+
+/// The text of a tool result, as shown to the hook, and whether it succeeded.
+pub(crate) fn result_text(response: &ResponseInputItem) -> (String, bool) {
+    match response {
+        ResponseInputItem::FunctionCallOutput { output, .. } => {
+            (output.content.clone(), output.success.unwrap_or(true))
+        }
+        ResponseInputItem::CustomToolCallOutput { output, .. } => (output.clone(), true),
+        ResponseInputItem::McpToolCallOutput { result, .. } => match result {
+            Ok(result) => (
+                serde_json::to_string(&result.content).unwrap_or_default(),
+                !result.is_error.unwrap_or(false),
+            ),
+            Err(err) => (err.clone(), false),
+        },
+        ResponseInputItem::Message { .. } => (String::new(), true),
+    }
+}
+
+/// Appends hook feedback to the tool result the model will read.
+pub(crate) fn append_feedback(response: &mut ResponseInputItem, feedback: &[String]) {
+    if feedback.is_empty() {
+        return;
+    }
+    let note = format!("Hook feedback:\n{}", feedback.join("\n"));
+    match response {
+        ResponseInputItem::FunctionCallOutput { output, .. } => {
+            output.content = format!("{}\n\n{note}", output.content);
+        }
+        ResponseInputItem::CustomToolCallOutput { output, .. } => {
+            *output = format!("{output}\n\n{note}");
+        }
+        ResponseInputItem::McpToolCallOutput { result, .. } => match result {
+            Ok(result) => result.content.push(ContentBlock::TextContent(TextContent {
+                annotations: None,
+                text: note,
+                r#type: "text".to_string(),
+            })),
+            Err(err) => *err = format!("{err}\n\n{note}"),
+        },
+        ResponseInputItem::Message { .. } => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use codex_protocol::protocol::SandboxPolicy;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn only_block_decisions_give_feedback() {
+        assert_eq!(
+            parse_feedback(r#"{"decision": "block", "reason": "fails prettier"}"#),
+            Some("fails prettier".to_string())
+        );
+        assert_eq!(
+            parse_feedback(r#"{"decision": "block"}"#),
+            Some("An after-tool-use hook blocked this result.".to_string())
+        );
+        assert_eq!(parse_feedback(r#"{"decision": "allow"}"#), None);
+        assert_eq!(parse_feedback("block"), None);
+        assert_eq!(parse_feedback(""), None);
+    }
+
+    #[test]
+    fn feedback_is_appended_to_function_output() {
+        let mut response = ResponseInputItem::FunctionCallOutput {
+            call_id: "call-1".to_string(),
+            output: FunctionCallOutputPayload {
+                content: "wrote src/app.ts".to_string(),
+                success: Some(true),
+                ..Default::default()
+            },
+        };
+
+        append_feedback(&mut response, &["fails prettier".to_string()]);
+
+        assert_eq!(
+            result_text(&response),
+            (
+                "wrote src/app.ts\n\nHook feedback:\nfails prettier".to_string(),
+                true
+            )
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn hook_output_blocks_with_reason() {
+        let call = AfterToolUse {
+            thread_id: "thread-1".to_string(),
+            turn_id: "turn-1",
+            call_id: "call-1",
+            tool_name: "apply_patch",
+            input: "*** Begin Patch",
+            output: "Success".to_string(),
+            success: true,
+        };
+        let sandbox = HookSandbox {
+            policy: SandboxPolicy::DangerFullAccess,
+            cwd: std::env::temp_dir(),
+            codex_linux_sandbox_exe: None,
+        };
+        let hook = |script: &str| HookCommand {
+            command: ["sh", "-c", script, "hook"].map(String::from).to_vec(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            run(
+                &hook(r#"echo '{"decision":"block","reason":"run prettier"}'"#),
+                &call,
+                &sandbox,
+                AFTER_TOOL_USE_HOOK_TIMEOUT
+            )
+            .await,
+            Some("run prettier".to_string())
+        );
+        assert_eq!(
+            run(
+                &hook("exit 1"),
+                &call,
+                &sandbox,
+                AFTER_TOOL_USE_HOOK_TIMEOUT
+            )
+            .await,
+            None
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use chrono::DateTime;
 use chrono::Utc;
 use codex_api::AuthProvider as ApiAuthProvider;
+use codex_api::AwsCredentials;
 use codex_api::TransportError;
 use codex_api::error::ApiError;
 use codex_api::rate_limits::parse_rate_limit;
@@ -13,6 +14,8 @@ use crate::error::RetryLimitReachedError;
 use crate::error::UnexpectedResponseError;
 use crate::error::UsageLimitReachedError;
 use crate::model_provider_info::ModelProviderInfo;
+use crate::model_provider_info::WireApi;
+use crate::provider_auth;
 use crate::token_data::PlanType;
 
 pub(crate) fn map_api_error(err: ApiError) -> CodexErr {
@@ -104,10 +107,23 @@ pub(crate) async fn auth_provider_from_auth(
     auth: Option<CodexAuth>,
     provider: &ModelProviderInfo,
 ) -> crate::error::Result<CoreAuthProvider> {
+    if let Some(command) = provider
+        .azure
+        .as_ref()
+        .and_then(|azure| azure.token_command.as_deref())
+    {
+        return Ok(CoreAuthProvider {
+            token: Some(provider_auth::azure_token(command).await?),
+            account_id: None,
+            aws_credentials: None,
+        });
+    }
+
     if let Some(api_key) = provider.api_key()? {
         return Ok(CoreAuthProvider {
             token: Some(api_key),
             account_id: None,
+            aws_credentials: None,
         });
     }
 
@@ -115,6 +131,20 @@ pub(crate) async fn auth_provider_from_auth(
         return Ok(CoreAuthProvider {
             token: Some(token),
             account_id: None,
+            aws_credentials: None,
+        });
+    }
+
+    // Without a Bedrock API key, requests are signed with AWS keys instead.
+    if provider.wire_api == WireApi::Bedrock {
+        let profile = provider
+            .bedrock
+            .as_ref()
+            .and_then(|bedrock| bedrock.profile.as_deref());
+        return Ok(CoreAuthProvider {
+            token: None,
+            account_id: None,
+            aws_credentials: Some(provider_auth::aws_credentials(profile)?),
         });
     }
 
@@ -123,11 +153,13 @@ pub(crate) async fn auth_provider_from_auth(
         Ok(CoreAuthProvider {
             token: Some(token),
             account_id: auth.get_account_id(),
+            aws_credentials: None,
         })
     } else {
         Ok(CoreAuthProvider {
             token: None,
             account_id: None,
+            aws_credentials: None,
         })
     }
 }
@@ -149,6 +181,7 @@ struct UsageErrorBody {
 pub(crate) struct CoreAuthProvider {
     token: Option<String>,
     account_id: Option<String>,
+    aws_credentials: Option<AwsCredentials>,
 }
 
 impl ApiAuthProvider for CoreAuthProvider {
@@ -159,4 +192,8 @@ impl ApiAuthProvider for CoreAuthProvider {
     fn account_id(&self) -> Option<String> {
         self.account_id.clone()
     }
+
+    fn aws_credentials(&self) -> Option<AwsCredentials> {
+        self.aws_credentials.clone()
+    }
 }
@@ -1,10 +1,24 @@
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::config::types::GeneratedFilesMode;
+use crate::config::types::SecretScanMode;
+use crate::config::types::ToolRuleDecision;
 use crate::function_tool::FunctionCallError;
+use crate::generated_files;
+use crate::generated_files::GeneratedFileGuard;
+use crate::patch_review::rejection_note;
+use crate::patch_review::retain_hunks;
+use crate::permissions::write_permissions_for;
+use crate::protocol::AskForApproval;
+use crate::protocol::EventMsg;
 use crate::protocol::FileChange;
 use crate::protocol::ReviewDecision;
+use crate::protocol::WarningEvent;
 use crate::safety::SafetyCheck;
 use crate::safety::assess_patch_safety;
+use crate::secret_scan;
+use crate::secret_scan::SecretScanner;
+use crate::tool_rules;
 use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::ApplyPatchFileChange;
 use std::collections::HashMap;
@@ -31,6 +45,9 @@ pub(crate) enum InternalApplyPatchInvocation {
 pub(crate) struct ApplyPatchExec {
     pub(crate) action: ApplyPatchAction,
     pub(crate) user_explicitly_approved_this_action: bool,
+    /// Appended to the tool output, e.g. when the user rejected some hunks
+    /// or the patch wrote generated files.
+    pub(crate) review_note: Option<String>,
 }
 
 pub(crate) async fn apply_patch(
@@ -39,45 +56,140 @@ pub(crate) async fn apply_patch(
     call_id: &str,
     action: ApplyPatchAction,
 ) -> InternalApplyPatchInvocation {
-    match assess_patch_safety(
+    let secret_scan = &turn_context.client.config().secret_scan;
+    if secret_scan.mode != SecretScanMode::Off
+        && let Ok(scanner) = SecretScanner::from_config(secret_scan)
+    {
+        let findings = scanner.scan(&action);
+        if !findings.is_empty() {
+            let found = secret_scan::describe(&findings, &action.cwd);
+            if secret_scan.mode == SecretScanMode::Block {
+                return InternalApplyPatchInvocation::Output(Err(FunctionCallError::HookBlocked(
+                    format!(
+                        "patch rejected: it adds what looks like a secret:\n{found}\nRead secrets from the environment or an untracked file instead of writing them into the workspace."
+                    ),
+                )));
+            }
+            let message = format!("This edit adds what looks like a secret:\n{found}");
+            sess.send_event(turn_context, EventMsg::Warning(WarningEvent { message }))
+                .await;
+        }
+    }
+
+    let generated_config = &turn_context.client.config().generated_files;
+    let mut generated_note = None;
+    if generated_config.mode != GeneratedFilesMode::Off {
+        let found = GeneratedFileGuard::from_config(generated_config).check(&action);
+        if !found.is_empty() {
+            let found = generated_files::describe(&found, &action.cwd);
+            if generated_config.mode == GeneratedFilesMode::Block {
+                return InternalApplyPatchInvocation::Output(Err(FunctionCallError::HookBlocked(
+                    format!(
+                        "patch rejected: it writes generated files:\n{found}\n{}",
+                        generated_files::GUIDANCE
+                    ),
+                )));
+            }
+            let message = format!("This edit writes generated files:\n{found}");
+            sess.send_event(turn_context, EventMsg::Warning(WarningEvent { message }))
+                .await;
+            generated_note = Some(format!(
+                "Note: this patch wrote generated files:\n{found}\n{}",
+                generated_files::GUIDANCE
+            ));
+        }
+    }
+
+    // With `review_edits`, or an `ask` rule, patches the policy would allow
+    // still wait for the user, unless nobody can be asked. An `allow` rule
+    // lets the patch through like a grant.
+    let rule = tool_rules::verdict_for(turn_context, call_id);
+    let rule_decision = rule.as_ref().map(|verdict| verdict.decision);
+    let review_edits = (turn_context.client.config().review_edits
+        || rule_decision == Some(ToolRuleDecision::Ask))
+        && turn_context.approval_policy != AskForApproval::Never;
+    // Patches that only write paths the project granted need no prompt; the
+    // others offer to grant their directories.
+    let written = written_paths(&action);
+    let (granted, proposed_permissions) = {
+        let store = sess.services.permissions.read().await;
+        (
+            rule_decision == Some(ToolRuleDecision::Allow)
+                || (!written.is_empty() && written.iter().all(|path| store.allows_write(path))),
+            write_permissions_for(store.project_root(), written.iter().map(PathBuf::as_path)),
+        )
+    };
+    let invocation = match assess_patch_safety(
         &action,
         turn_context.approval_policy,
         &turn_context.sandbox_policy,
         &turn_context.cwd,
     ) {
+        _ if granted && !review_edits => {
+            InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
+                action,
+                user_explicitly_approved_this_action: false,
+                review_note: None,
+            })
+        }
         SafetyCheck::AutoApprove {
             user_explicitly_approved,
             ..
-        } => InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
+        } if !review_edits => InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
             action,
             user_explicitly_approved_this_action: user_explicitly_approved,
+            review_note: None,
         }),
-        SafetyCheck::AskUser => {
+        SafetyCheck::AutoApprove { .. } | SafetyCheck::AskUser => {
             // Compute a readable summary of path changes to include in the
-            // approval request so the user can make an informed decision.
-            //
-            // Note that it might be worth expanding this approval request to
-            // give the user the option to expand the set of writable roots so
-            // that similar patches can be auto-approved in the future during
-            // this session.
+            // approval request so the user can make an informed decision,
+            // and offer to grant write access to the touched directories so
+            // similar patches go ahead without asking.
             let rx_approve = sess
                 .request_patch_approval(
                     turn_context,
                     call_id.to_owned(),
                     convert_apply_patch_to_protocol(&action),
+                    rule.filter(|verdict| verdict.decision == ToolRuleDecision::Ask)
+                        .map(|verdict| verdict.prompt_reason()),
                     None,
-                    None,
+                    proposed_permissions,
                 )
                 .await;
-            match rx_approve.await.unwrap_or_default() {
+            let decision = sess
+                .await_approval(call_id, async { rx_approve.await.unwrap_or_default() })
+                .await;
+            match decision {
                 ReviewDecision::Approved
                 | ReviewDecision::ApprovedExecpolicyAmendment { .. }
-                | ReviewDecision::ApprovedForSession => {
+                | ReviewDecision::ApprovedPrefixForProject { .. }
+                | ReviewDecision::ApprovedForSession
+                | ReviewDecision::ApprovedPermissions { .. } => {
                     InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
                         action,
                         user_explicitly_approved_this_action: true,
+                        review_note: None,
                     })
                 }
+                ReviewDecision::PartiallyApproved { rejected_hunks } => {
+                    match retain_hunks(&action, &rejected_hunks) {
+                        Ok(Some(retained)) => {
+                            InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
+                                action: retained,
+                                user_explicitly_approved_this_action: true,
+                                review_note: Some(rejection_note(&rejected_hunks, &action.cwd)),
+                            })
+                        }
+                        Ok(None) => InternalApplyPatchInvocation::Output(Err(
+                            FunctionCallError::RespondToModel("patch rejected by user".to_string()),
+                        )),
+                        Err(err) => InternalApplyPatchInvocation::Output(Err(
+                            FunctionCallError::RespondToModel(format!(
+                                "failed to apply the hunks the user approved: {err}"
+                            )),
+                        )),
+                    }
+                }
                 ReviewDecision::Denied | ReviewDecision::Abort => {
                     InternalApplyPatchInvocation::Output(Err(FunctionCallError::RespondToModel(
                         "patch rejected by user".to_string(),
@@ -88,7 +200,33 @@ pub(crate) async fn apply_patch(
         SafetyCheck::Reject { reason } => InternalApplyPatchInvocation::Output(Err(
             FunctionCallError::RespondToModel(format!("patch rejected: {reason}")),
         )),
+    };
+    match invocation {
+        InternalApplyPatchInvocation::DelegateToExec(mut exec) => {
+            exec.review_note = match (exec.review_note, generated_note) {
+                (Some(review), Some(generated)) => Some(format!("{review}\n\n{generated}")),
+                (review, generated) => review.or(generated),
+            };
+            InternalApplyPatchInvocation::DelegateToExec(exec)
+        }
+        invocation => invocation,
+    }
+}
+
+/// Every path `action` writes, including move destinations.
+fn written_paths(action: &ApplyPatchAction) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for (path, change) in action.changes() {
+        paths.push(path.clone());
+        if let ApplyPatchFileChange::Update {
+            move_path: Some(dest),
+            ..
+        } = change
+        {
+            paths.push(dest.clone());
+        }
     }
+    paths
 }
 
 pub(crate) fn convert_apply_patch_to_protocol(
@@ -1,3 +1,4 @@
+pub mod accounts;
 mod storage;
 
 use chrono::Utc;
@@ -346,12 +347,8 @@ pub fn load_auth_dot_json(
 }
 
 pub async fn enforce_login_restrictions(config: &Config) -> std::io::Result<()> {
-    let Some(auth) = load_auth(
-        &config.codex_home,
-        true,
-        config.cli_auth_credentials_store_mode,
-    )?
-    else {
+    let auth_home = accounts::auth_home(config);
+    let Some(auth) = load_auth(&auth_home, true, config.cli_auth_credentials_store_mode)? else {
         return Ok(());
     };
 
@@ -371,7 +368,7 @@ pub async fn enforce_login_restrictions(config: &Config) -> std::io::Result<()>
 
         if let Some(message) = method_violation {
             return logout_with_message(
-                &config.codex_home,
+                &auth_home,
                 message,
                 config.cli_auth_credentials_store_mode,
             );
@@ -387,7 +384,7 @@ pub async fn enforce_login_restrictions(config: &Config) -> std::io::Result<()>
             Ok(data) => data,
             Err(err) => {
                 return logout_with_message(
-                    &config.codex_home,
+                    &auth_home,
                     format!(
                         "Failed to load ChatGPT credentials while enforcing workspace restrictions: {err}. Logging out."
                     ),
@@ -408,7 +405,7 @@ pub async fn enforce_login_restrictions(config: &Config) -> std::io::Result<()>
                 ),
             };
             return logout_with_message(
-                &config.codex_home,
+                &auth_home,
                 message,
                 config.cli_auth_credentials_store_mode,
             );
@@ -1063,6 +1060,10 @@ mod tests {
 #[derive(Debug)]
 pub struct AuthManager {
     codex_home: PathBuf,
+    /// Named account in use, or `None` for the default one.
+    account: Option<String>,
+    /// Where the account's credentials are stored; see [`accounts`].
+    auth_home: PathBuf,
     inner: RwLock<CachedAuth>,
     enable_codex_api_key_env: bool,
     auth_credentials_store_mode: AuthCredentialsStoreMode,
@@ -1072,14 +1073,41 @@ impl AuthManager {
     /// Create a new manager loading the initial auth using the provided
     /// preferred auth method. Errors loading auth are swallowed; `auth()` will
     /// simply return `None` in that case so callers can treat it as an
-    /// unauthenticated state.
+    /// unauthenticated state. Uses the account chosen with `codex auth use`;
+    /// see [`Self::from_config`] to honor the one config names.
     pub fn new(
         codex_home: PathBuf,
         enable_codex_api_key_env: bool,
         auth_credentials_store_mode: AuthCredentialsStoreMode,
     ) -> Self {
+        Self::for_account(
+            codex_home,
+            None,
+            enable_codex_api_key_env,
+            auth_credentials_store_mode,
+        )
+    }
+
+    /// Like [`Self::new`], for the account the config's profile selects.
+    pub fn from_config(config: &Config, enable_codex_api_key_env: bool) -> Self {
+        Self::for_account(
+            config.codex_home.clone(),
+            config.account.as_deref(),
+            enable_codex_api_key_env,
+            config.cli_auth_credentials_store_mode,
+        )
+    }
+
+    fn for_account(
+        codex_home: PathBuf,
+        configured_account: Option<&str>,
+        enable_codex_api_key_env: bool,
+        auth_credentials_store_mode: AuthCredentialsStoreMode,
+    ) -> Self {
+        let account = accounts::resolve_account(&codex_home, configured_account);
+        let auth_home = accounts::account_home(&codex_home, &account);
         let auth = load_auth(
-            &codex_home,
+            &auth_home,
             enable_codex_api_key_env,
             auth_credentials_store_mode,
         )
@@ -1087,6 +1115,8 @@ impl AuthManager {
         .flatten();
         Self {
             codex_home,
+            account: (account != accounts::DEFAULT_ACCOUNT).then_some(account),
+            auth_home,
             inner: RwLock::new(CachedAuth { auth }),
             enable_codex_api_key_env,
             auth_credentials_store_mode,
@@ -1105,7 +1135,9 @@ impl AuthManager {
             .expect("lock test codex homes")
             .push(temp_dir);
         Arc::new(Self {
+            auth_home: codex_home.clone(),
             codex_home,
+            account: None,
             inner: RwLock::new(cached),
             enable_codex_api_key_env: false,
             auth_credentials_store_mode: AuthCredentialsStoreMode::File,
@@ -1117,7 +1149,9 @@ impl AuthManager {
     pub fn from_auth_for_testing_with_home(auth: CodexAuth, codex_home: PathBuf) -> Arc<Self> {
         let cached = CachedAuth { auth: Some(auth) };
         Arc::new(Self {
+            auth_home: codex_home.clone(),
             codex_home,
+            account: None,
             inner: RwLock::new(cached),
             enable_codex_api_key_env: false,
             auth_credentials_store_mode: AuthCredentialsStoreMode::File,
@@ -1133,11 +1167,16 @@ impl AuthManager {
         &self.codex_home
     }
 
+    /// Named account in use, or `None` for the default one.
+    pub fn account(&self) -> Option<&str> {
+        self.account.as_deref()
+    }
+
     /// Force a reload of the auth information from auth.json. Returns
     /// whether the auth value changed.
     pub fn reload(&self) -> bool {
         let new_auth = load_auth(
-            &self.codex_home,
+            &self.auth_home,
             self.enable_codex_api_key_env,
             self.auth_credentials_store_mode,
         )
@@ -1173,6 +1212,11 @@ impl AuthManager {
         ))
     }
 
+    /// [`Self::from_config`] returning an `Arc` wrapper.
+    pub fn shared_from_config(config: &Config, enable_codex_api_key_env: bool) -> Arc<Self> {
+        Arc::new(Self::from_config(config, enable_codex_api_key_env))
+    }
+
     /// Attempt to refresh the current auth token (if any). On success, reload
     /// the auth state from disk so other components observe refreshed token.
     /// If the token refresh fails in a permanent (non‑transient) way, logs out
@@ -1200,7 +1244,7 @@ impl AuthManager {
     /// reloads the in‑memory auth cache so callers immediately observe the
     /// unauthenticated state.
     pub fn logout(&self) -> std::io::Result<bool> {
-        let removed = super::auth::logout(&self.codex_home, self.auth_credentials_store_mode)?;
+        let removed = super::auth::logout(&self.auth_home, self.auth_credentials_store_mode)?;
         // Always reload to clear any cached auth (even if file absent).
         self.reload();
         Ok(removed)
@@ -0,0 +1,159 @@
+//! Named logins kept side by side, e.g. a work ChatGPT account and a personal
+//! API key.
+//!
+//! The `default` account is the one stored directly in the Codex home, as
+//! before accounts existed. Every other account keeps its own credentials in
+//! `$CODEX_HOME/accounts/<name>`, using the same storage backends, so each
+//! refreshes its tokens independently. The account in use is the one the
+//! active profile (or `account` in config.toml) names, else the one chosen
+//! with `codex auth use`, else `default`.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::config::Config;
+
+pub const DEFAULT_ACCOUNT: &str = "default";
+
+const ACCOUNTS_DIR: &str = "accounts";
+/// Records the account chosen with `codex auth use`.
+const ACTIVE_FILE: &str = "active";
+
+/// Account names become directory names, so keep them simple.
+pub fn validate_account_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "invalid account name `{name}`: use letters, digits, `-` and `_`"
+        ))
+    }
+}
+
+/// Directory holding `name`'s credentials. It may not exist yet.
+pub fn account_home(codex_home: &Path, name: &str) -> PathBuf {
+    if name == DEFAULT_ACCOUNT {
+        codex_home.to_path_buf()
+    } else {
+        codex_home.join(ACCOUNTS_DIR).join(name)
+    }
+}
+
+/// The account chosen with `codex auth use`, if any.
+pub fn selected_account(codex_home: &Path) -> Option<String> {
+    let contents = std::fs::read_to_string(codex_home.join(ACCOUNTS_DIR).join(ACTIVE_FILE)).ok()?;
+    let name = contents.trim();
+    validate_account_name(name).ok()?;
+    Some(name.to_string())
+}
+
+/// Makes `name` the account used when config doesn't name one.
+pub fn select_account(codex_home: &Path, name: &str) -> io::Result<()> {
+    let path = codex_home.join(ACCOUNTS_DIR).join(ACTIVE_FILE);
+    if name == DEFAULT_ACCOUNT {
+        return match std::fs::remove_file(&path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        };
+    }
+    std::fs::create_dir_all(codex_home.join(ACCOUNTS_DIR))?;
+    std::fs::write(path, format!("{name}\n"))
+}
+
+/// The account in use: `configured`, else the selected one, else `default`.
+pub fn resolve_account(codex_home: &Path, configured: Option<&str>) -> String {
+    configured
+        .map(str::to_string)
+        .or_else(|| selected_account(codex_home))
+        .unwrap_or_else(|| DEFAULT_ACCOUNT.to_string())
+}
+
+/// Where the credentials of the account `config` resolves to are stored.
+pub fn auth_home(config: &Config) -> PathBuf {
+    account_home(
+        &config.codex_home,
+        &resolve_account(&config.codex_home, config.account.as_deref()),
+    )
+}
+
+/// `default` followed by every named account, sorted.
+pub fn list_accounts(codex_home: &Path) -> io::Result<Vec<String>> {
+    let mut names = Vec::new();
+    match std::fs::read_dir(codex_home.join(ACCOUNTS_DIR)) {
+        Ok(entries) => {
+            for entry in entries {
+                let entry = entry?;
+                if !entry.file_type()?.is_dir() {
+                    continue;
+                }
+                if let Some(name) = entry.file_name().to_str()
+                    && validate_account_name(name).is_ok()
+                    && name != DEFAULT_ACCOUNT
+                {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+    names.sort();
+    names.insert(0, DEFAULT_ACCOUNT.to_string());
+    Ok(names)
+}
+
+/// Creates the directory for `name` so it is listed even when its
+/// credentials live in the keychain.
+pub fn create_account(codex_home: &Path, name: &str) -> io::Result<PathBuf> {
+    validate_account_name(name)
+        .map_err(|message| io::Error::new(io::ErrorKind::InvalidInput, message))?;
+    let home = account_home(codex_home, name);
+    std::fs::create_dir_all(&home)?;
+    Ok(home)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    #[test]
+    fn selection_falls_back_to_default() -> io::Result<()> {
+        let home = tempdir()?;
+        let codex_home = home.path();
+        assert_eq!(resolve_account(codex_home, None), DEFAULT_ACCOUNT);
+
+        create_account(codex_home, "work")?;
+        create_account(codex_home, "personal")?;
+        assert_eq!(
+            list_accounts(codex_home)?,
+            vec!["default", "personal", "work"]
+        );
+
+        select_account(codex_home, "work")?;
+        assert_eq!(resolve_account(codex_home, None), "work");
+        assert_eq!(resolve_account(codex_home, Some("personal")), "personal");
+        assert_eq!(
+            account_home(codex_home, "work"),
+            codex_home.join("accounts/work")
+        );
+
+        select_account(codex_home, DEFAULT_ACCOUNT)?;
+        assert_eq!(resolve_account(codex_home, None), DEFAULT_ACCOUNT);
+        assert_eq!(account_home(codex_home, DEFAULT_ACCOUNT), codex_home);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_names_that_are_not_plain_directory_names() {
+        assert!(validate_account_name("work-2").is_ok());
+        assert!(validate_account_name("../work").is_err());
+        assert!(validate_account_name("").is_err());
+    }
+}
@@ -0,0 +1,135 @@
+//! Enforcement of the `[budget]` limits from `config.toml`.
+//!
+//! Budgets are checked before every model request. Session-wide limits
+//! (tokens, cost, turns) latch once exceeded so that later tasks in the same
+//! session are refused too; the per-task wall-clock limit resets with each
+//! task.
+
+use std::time::Duration;
+
+use codex_protocol::protocol::BudgetExceededEvent;
+use codex_protocol::protocol::BudgetLimit;
+use tracing::error;
+
+use crate::config::types::Budget;
+use crate::hooks;
+use crate::hooks::HookCommand;
+use crate::hooks::HookSandbox;
+
+/// Consumption observed so far, compared against a [`Budget`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BudgetUsage {
+    pub(crate) total_tokens: i64,
+    pub(crate) cost_usd: f64,
+    pub(crate) turns: u32,
+    pub(crate) task_elapsed: Duration,
+}
+
+/// Returns the first limit in `budget` that `usage` has reached, if any.
+pub(crate) fn check(budget: &Budget, usage: &BudgetUsage) -> Option<BudgetExceededEvent> {
+    if let Some(max) = budget.max_tokens
+        && usage.total_tokens >= max
+    {
+        return Some(BudgetExceededEvent {
+            limit: BudgetLimit::Tokens,
+            configured: max as f64,
+            observed: usage.total_tokens as f64,
+            message: format!(
+                "Token budget exhausted: {} of {max} tokens used.",
+                usage.total_tokens
+            ),
+        });
+    }
+    if let Some(max) = budget.max_cost_usd
+        && usage.cost_usd >= max
+    {
+        return Some(BudgetExceededEvent {
+            limit: BudgetLimit::CostUsd,
+            configured: max,
+            observed: usage.cost_usd,
+            message: format!(
+                "Cost budget exhausted: ${:.2} of ${max:.2} spent.",
+                usage.cost_usd
+            ),
+        });
+    }
+    if let Some(max) = budget.max_turns
+        && usage.turns >= max
+    {
+        return Some(BudgetExceededEvent {
+            limit: BudgetLimit::Turns,
+            configured: f64::from(max),
+            observed: f64::from(usage.turns),
+            message: format!(
+                "Turn budget exhausted: {} of {max} model requests issued.",
+                usage.turns
+            ),
+        });
+    }
+    if let Some(max) = budget.max_task_duration_secs
+        && usage.task_elapsed >= Duration::from_secs(max)
+    {
+        let elapsed = usage.task_elapsed.as_secs();
+        return Some(BudgetExceededEvent {
+            limit: BudgetLimit::TaskDuration,
+            configured: max as f64,
+            observed: usage.task_elapsed.as_secs_f64(),
+            message: format!("Task time budget exhausted: ran for {elapsed}s (limit {max}s)."),
+        });
+    }
+    None
+}
+
+/// Run the configured `on_exceeded` program with the event serialized as its
+/// final argument. Fire-and-forget, like the `notify` hook.
+pub(crate) fn run_on_exceeded_hook(
+    hook: &HookCommand,
+    event: &BudgetExceededEvent,
+    sandbox: &HookSandbox,
+) {
+    let Some(json) = hooks::payload(event) else {
+        error!("failed to serialise budget event");
+        return;
+    };
+    hooks::spawn(hook, json, sandbox);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn unlimited_budget_never_trips() {
+        let usage = BudgetUsage {
+            total_tokens: i64::MAX,
+            cost_usd: 1e9,
+            turns: u32::MAX,
+            task_elapsed: Duration::from_secs(u64::MAX / 2),
+        };
+        assert_eq!(check(&Budget::default(), &usage), None);
+    }
+
+    #[test]
+    fn reports_first_exhausted_limit() {
+        let budget = Budget {
+            max_tokens: Some(10_000),
+            max_turns: Some(3),
+            ..Default::default()
+        };
+        let usage = BudgetUsage {
+            total_tokens: 2_000,
+            turns: 3,
+            ..Default::default()
+        };
+        assert_eq!(
+            check(&budget, &usage),
+            Some(BudgetExceededEvent {
+                limit: BudgetLimit::Turns,
+                configured: 3.0,
+                observed: 3.0,
+                message: "Turn budget exhausted: 3 of 3 model requests issued.".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,670 @@
+//! Imports Claude Code sessions and hooks, so people moving to Codex keep
+//! their history and guard scripts.
+//!
+//! Sessions live in `~/.claude/projects/<project>/<session id>.jsonl`, one
+//! message per line. Each becomes a Codex rollout with the same session id,
+//! which makes importing again skip sessions that were already imported.
+//! Tool calls keep Claude's tool names so the transcript reads as it did.
+//!
+//! Hooks come from the `hooks` table of Claude's `settings.json` and become
+//! `[[hooks]]` entries. Claude hooks read their event from stdin while Codex
+//! passes it as the last argument, so each command is wrapped in a small
+//! `sh` script that pipes the argument to it. Events Codex has no
+//! counterpart for are reported and skipped.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::ConversationId;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::FunctionCallOutputPayload;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::AgentMessageEvent;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use codex_protocol::protocol::SessionMeta;
+use codex_protocol::protocol::SessionMetaLine;
+use codex_protocol::protocol::SessionSource;
+use codex_protocol::protocol::UserMessageEvent;
+use serde_json::Value;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use tracing::warn;
+
+use crate::config::Config;
+use crate::config::types::HookConfig;
+use crate::config::types::HookEvent;
+use crate::find_conversation_path_by_id_str;
+use crate::rollout::recorder::write_rollout;
+use crate::session_summary::summarize;
+
+/// `originator` recorded in the metadata of imported sessions.
+const IMPORT_ORIGINATOR: &str = "claude_code_import";
+
+/// Claude Code's own notes in a transcript (slash commands and their
+/// output), which are not part of the conversation.
+const LOCAL_COMMAND_PREFIXES: [&str; 4] = [
+    "<command-name>",
+    "<command-message>",
+    "<local-command-stdout>",
+    "<local-command-stderr>",
+];
+
+/// `~/.claude`, where Claude Code keeps its settings and sessions.
+pub fn default_claude_home() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".claude"))
+}
+
+/// Outcome of [`import_claude_sessions`].
+#[derive(Debug, Default)]
+pub struct ClaudeSessionImport {
+    /// Session files converted, or that would be with `dry_run`.
+    pub imported: Vec<PathBuf>,
+    /// Sessions imported on an earlier run.
+    pub already_imported: usize,
+    /// Files without any messages, such as sessions that were never used.
+    pub empty: usize,
+    /// Files that could not be read or written, with the reason.
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// Converts every session under `projects_dir` (usually
+/// `~/.claude/projects`) into a rollout under `config.codex_home`.
+pub async fn import_claude_sessions(
+    config: &Config,
+    projects_dir: &Path,
+    dry_run: bool,
+) -> io::Result<ClaudeSessionImport> {
+    let mut report = ClaudeSessionImport::default();
+    for path in session_files(projects_dir)? {
+        let text = match tokio::fs::read_to_string(&path).await {
+            Ok(text) => text,
+            Err(err) => {
+                report.failed.push((path, err.to_string()));
+                continue;
+            }
+        };
+        let Some(session) = convert_session(&text, &path) else {
+            report.empty += 1;
+            continue;
+        };
+        let id = session.conversation_id.to_string();
+        if find_conversation_path_by_id_str(&config.codex_home, &id)
+            .await?
+            .is_some()
+        {
+            report.already_imported += 1;
+            continue;
+        }
+        if !dry_run
+            && let Err(err) = write_rollout(
+                config,
+                session.conversation_id,
+                session.started_at,
+                session.lines,
+            )
+            .await
+        {
+            report.failed.push((path, err.to_string()));
+            continue;
+        }
+        report.imported.push(path);
+    }
+    Ok(report)
+}
+
+/// The `*.jsonl` files one level below `projects_dir`, oldest name first.
+fn session_files(projects_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for project in std::fs::read_dir(projects_dir)? {
+        let project = project?.path();
+        if !project.is_dir() {
+            continue;
+        }
+        for entry in std::fs::read_dir(&project)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "jsonl") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// A Claude Code session converted into rollout lines.
+pub(crate) struct ImportedSession {
+    pub(crate) conversation_id: ConversationId,
+    pub(crate) started_at: OffsetDateTime,
+    /// Starts with the session's `SessionMeta`.
+    pub(crate) lines: Vec<RolloutLine>,
+}
+
+/// Converts the Claude Code transcript `text`, read from `path`. `None` when
+/// it holds no messages.
+pub(crate) fn convert_session(text: &str, path: &Path) -> Option<ImportedSession> {
+    let mut session_id = None;
+    let mut cwd = None;
+    let mut started = None;
+    let mut claude_title = None;
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let Ok(entry) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        let kind = entry.get("type").and_then(Value::as_str);
+        if kind == Some("summary") {
+            if claude_title.is_none() {
+                claude_title = string_field(&entry, "summary");
+            }
+            continue;
+        }
+        if !matches!(kind, Some("user" | "assistant"))
+            // Subagent transcripts and Claude's own bookkeeping.
+            || entry.get("isSidechain").and_then(Value::as_bool) == Some(true)
+            || entry.get("isMeta").and_then(Value::as_bool) == Some(true)
+        {
+            continue;
+        }
+        let Some(timestamp) = string_field(&entry, "timestamp") else {
+            continue;
+        };
+        session_id = session_id.or_else(|| string_field(&entry, "sessionId"));
+        cwd = cwd.or_else(|| string_field(&entry, "cwd"));
+        if started.is_none() {
+            started = Some(timestamp.clone());
+        }
+        let Some(message) = entry.get("message") else {
+            continue;
+        };
+        let items = if kind == Some("user") {
+            user_items(message)
+        } else {
+            assistant_items(message)
+        };
+        lines.extend(items.into_iter().map(|item| RolloutLine {
+            timestamp: timestamp.clone(),
+            item,
+        }));
+    }
+    if lines.is_empty() {
+        return None;
+    }
+
+    let session_id = session_id.or_else(|| {
+        path.file_stem()
+            .and_then(|stem| stem.to_str())
+            .map(str::to_string)
+    });
+    let conversation_id = session_id
+        .and_then(|id| ConversationId::from_string(&id).ok())
+        .unwrap_or_default();
+    let started = started.unwrap_or_default();
+    let started_at = OffsetDateTime::parse(&started, &Rfc3339).unwrap_or_else(|err| {
+        warn!(
+            "unrecognized timestamp {started:?} in {}: {err}",
+            path.display()
+        );
+        OffsetDateTime::now_utc()
+    });
+
+    let response_items: Vec<ResponseItem> = lines
+        .iter()
+        .filter_map(|line| match &line.item {
+            RolloutItem::ResponseItem(item) => Some(item.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut summary = summarize(&response_items);
+    if let (Some(summary), Some(title)) = (summary.as_mut(), claude_title) {
+        summary.title = title;
+    }
+    let last_timestamp = lines
+        .last()
+        .map(|line| line.timestamp.clone())
+        .unwrap_or_default();
+
+    let meta = RolloutLine {
+        timestamp: started.clone(),
+        item: RolloutItem::SessionMeta(SessionMetaLine {
+            meta: SessionMeta {
+                id: conversation_id,
+                timestamp: started,
+                cwd: cwd.map(PathBuf::from).unwrap_or_default(),
+                originator: IMPORT_ORIGINATOR.to_string(),
+                cli_version: env!("CARGO_PKG_VERSION").to_string(),
+                instructions: None,
+                source: SessionSource::Cli,
+                model_provider: None,
+            },
+            git: None,
+        }),
+    };
+    let mut all_lines = Vec::with_capacity(lines.len() + 2);
+    all_lines.push(meta);
+    all_lines.extend(lines);
+    if let Some(summary) = summary {
+        all_lines.push(RolloutLine {
+            timestamp: last_timestamp,
+            item: RolloutItem::SessionSummary(summary),
+        });
+    }
+    Some(ImportedSession {
+        conversation_id,
+        started_at,
+        lines: all_lines,
+    })
+}
+
+fn string_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+/// Content blocks of a Claude message; plain string content is one text
+/// block.
+fn blocks(message: &Value) -> Vec<Value> {
+    match message.get("content") {
+        Some(Value::String(text)) => vec![serde_json::json!({ "type": "text", "text": text })],
+        Some(Value::Array(blocks)) => blocks.clone(),
+        _ => Vec::new(),
+    }
+}
+
+fn user_items(message: &Value) -> Vec<RolloutItem> {
+    let mut items = Vec::new();
+    let mut text = Vec::new();
+    for block in blocks(message) {
+        match block.get("type").and_then(Value::as_str) {
+            Some("text") => {
+                if let Some(block_text) = string_field(&block, "text")
+                    && !LOCAL_COMMAND_PREFIXES
+                        .iter()
+                        .any(|prefix| block_text.trim_start().starts_with(prefix))
+                {
+                    text.push(block_text);
+                }
+            }
+            Some("tool_result") => {
+                let Some(call_id) = string_field(&block, "tool_use_id") else {
+                    continue;
+                };
+                let is_error = block.get("is_error").and_then(Value::as_bool) == Some(true);
+                items.push(RolloutItem::ResponseItem(
+                    ResponseItem::FunctionCallOutput {
+                        call_id,
+                        output: FunctionCallOutputPayload {
+                            content: tool_result_text(block.get("content")),
+                            success: Some(!is_error),
+                            ..Default::default()
+                        },
+                    },
+                ));
+            }
+            _ => {}
+        }
+    }
+    if !text.is_empty() {
+        let message = text.join("\n");
+        items.push(RolloutItem::ResponseItem(ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: message.clone(),
+            }],
+        }));
+        items.push(RolloutItem::EventMsg(EventMsg::UserMessage(
+            UserMessageEvent {
+                message,
+                images: None,
+            },
+        )));
+    }
+    items
+}
+
+fn tool_result_text(content: Option<&Value>) -> String {
+    match content {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(blocks)) => blocks
+            .iter()
+            .filter_map(|block| block.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn assistant_items(message: &Value) -> Vec<RolloutItem> {
+    let mut items = Vec::new();
+    for block in blocks(message) {
+        match block.get("type").and_then(Value::as_str) {
+            Some("text") => {
+                let Some(text) = string_field(&block, "text").filter(|text| !text.is_empty())
+                else {
+                    continue;
+                };
+                items.push(RolloutItem::ResponseItem(ResponseItem::Message {
+                    id: None,
+                    role: "assistant".to_string(),
+                    content: vec![ContentItem::OutputText { text: text.clone() }],
+                }));
+                items.push(RolloutItem::EventMsg(EventMsg::AgentMessage(
+                    AgentMessageEvent { message: text },
+                )));
+            }
+            Some("tool_use") => {
+                let (Some(call_id), Some(name)) =
+                    (string_field(&block, "id"), string_field(&block, "name"))
+                else {
+                    continue;
+                };
+                let arguments = block
+                    .get("input")
+                    .map(Value::to_string)
+                    .unwrap_or_else(|| "{}".to_string());
+                items.push(RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                    id: None,
+                    name,
+                    arguments,
+                    call_id,
+                }));
+            }
+            // Thinking blocks are signed for Claude models and mean nothing
+            // to other models.
+            _ => {}
+        }
+    }
+    items
+}
+
+/// Hooks converted from a Claude Code `settings.json`.
+#[derive(Debug, Default, PartialEq)]
+pub struct ClaudeHooksImport {
+    pub hooks: Vec<HookConfig>,
+    /// Hooks that could not be carried over and why, one per line.
+    pub notes: Vec<String>,
+}
+
+/// Converts the `hooks` table of a Claude Code `settings.json`.
+pub fn convert_claude_hooks(settings: &Value) -> ClaudeHooksImport {
+    let mut import = ClaudeHooksImport::default();
+    let Some(events) = settings.get("hooks").and_then(Value::as_object) else {
+        return import;
+    };
+    for (event_name, groups) in events {
+        let Some(event) = codex_event(event_name) else {
+            import
+                .notes
+                .push(format!("{event_name} hooks: Codex has no matching event"));
+            continue;
+        };
+        for group in groups.as_array().into_iter().flatten() {
+            let claude_matcher = group
+                .get("matcher")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let matcher = match event {
+                HookEvent::AfterToolUse => codex_tool_matcher(claude_matcher),
+                HookEvent::PreApproval => {
+                    // Codex matches approvals on the command line or the
+                    // changed paths, not on the tool.
+                    if !asks_for_approval(claude_matcher) {
+                        import.notes.push(format!(
+                            "{event_name} hook for `{claude_matcher}`: Codex only runs hooks before approving shell commands and patches"
+                        ));
+                        continue;
+                    }
+                    None
+                }
+                _ => None,
+            };
+            for hook in group
+                .get("hooks")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                let Some(command) = hook
+                    .get("command")
+                    .and_then(Value::as_str)
+                    .filter(|_| hook.get("type").and_then(Value::as_str) == Some("command"))
+                else {
+                    import.notes.push(format!(
+                        "{event_name} hook: only `command` hooks can be imported"
+                    ));
+                    continue;
+                };
+                import.hooks.push(HookConfig {
+                    events: vec![event],
+                    matcher: matcher.clone(),
+                    command: stdin_adapter(command, event),
+                    timeout_ms: hook
+                        .get("timeout")
+                        .and_then(Value::as_u64)
+                        .map(|seconds| seconds.saturating_mul(1000)),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+    import
+}
+
+fn codex_event(claude_event: &str) -> Option<HookEvent> {
+    match claude_event {
+        // Codex asks before running anything outside the sandbox; that is
+        // the point where a guard script can still stop a tool call.
+        "PreToolUse" => Some(HookEvent::PreApproval),
+        "PostToolUse" => Some(HookEvent::AfterToolUse),
+        "Stop" => Some(HookEvent::AgentTurnComplete),
+        "SessionEnd" => Some(HookEvent::SessionEnd),
+        _ => None,
+    }
+}
+
+/// Claude tool names and the Codex tools that do the same job.
+const TOOL_NAMES: [(&str, &[&str]); 9] = [
+    (
+        "Bash",
+        &["shell", "shell_command", "exec_command", "local_shell"],
+    ),
+    ("Edit", &["apply_patch"]),
+    ("MultiEdit", &["apply_patch"]),
+    ("Write", &["apply_patch"]),
+    ("NotebookEdit", &["apply_patch"]),
+    ("Read", &["read_file"]),
+    ("Grep", &["grep_files"]),
+    ("Glob", &["list_dir"]),
+    ("LS", &["list_dir"]),
+];
+
+fn tool_alternatives(claude_matcher: &str) -> Option<Vec<&str>> {
+    let names: Vec<&str> = claude_matcher.split('|').map(str::trim).collect();
+    names
+        .iter()
+        .all(|name| {
+            !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        })
+        .then_some(names)
+}
+
+/// The `AfterToolUse` matcher for a Claude tool matcher: tool names are
+/// translated, anything else is kept as a regex.
+fn codex_tool_matcher(claude_matcher: &str) -> Option<String> {
+    if matches!(claude_matcher.trim(), "" | "*" | ".*") {
+        return None;
+    }
+    let Some(names) = tool_alternatives(claude_matcher) else {
+        return Some(claude_matcher.to_string());
+    };
+    let mut codex_names: Vec<&str> = Vec::new();
+    for name in names {
+        let translated = match TOOL_NAMES.iter().find(|(claude, _)| *claude == name) {
+            Some((_, codex)) => codex.to_vec(),
+            None => vec![name],
+        };
+        for codex_name in translated {
+            if !codex_names.contains(&codex_name) {
+                codex_names.push(codex_name);
+            }
+        }
+    }
+    Some(format!("^({})$", codex_names.join("|")))
+}
+
+/// Whether a `PreToolUse` hook for `claude_matcher` covers tools that Codex
+/// asks approval for: shell commands and file edits.
+fn asks_for_approval(claude_matcher: &str) -> bool {
+    if matches!(claude_matcher.trim(), "" | "*" | ".*") {
+        return true;
+    }
+    tool_alternatives(claude_matcher).is_some_and(|names| {
+        names.iter().any(|name| {
+            matches!(
+                *name,
+                "Bash" | "Edit" | "MultiEdit" | "Write" | "NotebookEdit"
+            )
+        })
+    })
+}
+
+/// Runs the Claude hook `command` with the event JSON, which Codex passes as
+/// the last argument, on stdin. `$CLAUDE_PROJECT_DIR` is set to the session's
+/// working directory. A guard that blocks a tool by exiting with status 2
+/// denies the approval instead.
+fn stdin_adapter(command: &str, event: HookEvent) -> Vec<String> {
+    let mut script = format!(
+        "CLAUDE_PROJECT_DIR=\"{{cwd}}\"; export CLAUDE_PROJECT_DIR\nprintf '%s' \"$1\" | {{\n{command}\n}}"
+    );
+    if event == HookEvent::PreApproval {
+        script.push_str("\n[ $? -eq 2 ] && echo deny\nexit 0");
+    }
+    vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        script,
+        "codex-hook".to_string(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    const SESSION: &str = r#"{"type":"summary","summary":"Fix flaky login test","leafUuid":"b"}
+{"type":"user","sessionId":"67e55044-10b1-426f-9247-bb680e5fe0c8","cwd":"/repo","timestamp":"2025-06-01T10:00:00.000Z","isSidechain":false,"message":{"role":"user","content":"The login test is flaky"}}
+{"type":"assistant","sessionId":"67e55044-10b1-426f-9247-bb680e5fe0c8","cwd":"/repo","timestamp":"2025-06-01T10:00:05.000Z","message":{"role":"assistant","content":[{"type":"thinking","thinking":"hmm","signature":"x"},{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"npm test"}}]}}
+{"type":"user","sessionId":"67e55044-10b1-426f-9247-bb680e5fe0c8","cwd":"/repo","timestamp":"2025-06-01T10:00:09.000Z","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"1 failing","is_error":true}]}}
+{"type":"user","sessionId":"67e55044-10b1-426f-9247-bb680e5fe0c8","cwd":"/repo","timestamp":"2025-06-01T10:00:10.000Z","isSidechain":true,"message":{"role":"user","content":"subagent prompt"}}
+{"type":"user","sessionId":"67e55044-10b1-426f-9247-bb680e5fe0c8","cwd":"/repo","timestamp":"2025-06-01T10:00:11.000Z","message":{"role":"user","content":"<command-name>/clear</command-name>"}}
+{"type":"assistant","sessionId":"67e55044-10b1-426f-9247-bb680e5fe0c8","cwd":"/repo","timestamp":"2025-06-01T10:00:20.000Z","message":{"role":"assistant","content":[{"type":"text","text":"The test waits on a timer; fixed."}]}}
+"#;
+
+    fn items_json(lines: &[RolloutLine]) -> Vec<Value> {
+        lines
+            .iter()
+            .map(|line| serde_json::to_value(&line.item).expect("serialize"))
+            .collect()
+    }
+
+    #[test]
+    fn converts_a_claude_transcript_into_rollout_lines() {
+        let session =
+            convert_session(SESSION, Path::new("session.jsonl")).expect("session has messages");
+        assert_eq!(
+            session.conversation_id.to_string(),
+            "67e55044-10b1-426f-9247-bb680e5fe0c8"
+        );
+        assert_eq!(
+            session.started_at,
+            OffsetDateTime::parse("2025-06-01T10:00:00Z", &Rfc3339).expect("timestamp")
+        );
+
+        let items = items_json(&session.lines);
+        assert_eq!(items[0]["type"], "session_meta");
+        assert_eq!(items[0]["payload"]["cwd"], "/repo");
+        assert_eq!(items[0]["payload"]["originator"], IMPORT_ORIGINATOR);
+        assert_eq!(
+            items[1..],
+            [
+                json!({"type": "response_item", "payload": {"type": "message", "role": "user", "content": [{"type": "input_text", "text": "The login test is flaky"}]}}),
+                json!({"type": "event_msg", "payload": {"type": "user_message", "message": "The login test is flaky"}}),
+                json!({"type": "response_item", "payload": {"type": "function_call", "name": "Bash", "arguments": "{\"command\":\"npm test\"}", "call_id": "toolu_1"}}),
+                json!({"type": "response_item", "payload": {"type": "function_call_output", "call_id": "toolu_1", "output": "1 failing"}}),
+                json!({"type": "response_item", "payload": {"type": "message", "role": "assistant", "content": [{"type": "output_text", "text": "The test waits on a timer; fixed."}]}}),
+                json!({"type": "event_msg", "payload": {"type": "agent_message", "message": "The test waits on a timer; fixed."}}),
+                json!({"type": "session_summary", "payload": {"title": "Fix flaky login test", "summary": "The login test is flaky → The test waits on a timer; fixed."}}),
+            ]
+        );
+    }
+
+    #[test]
+    fn transcripts_without_messages_are_not_imported() {
+        let text = r#"{"type":"summary","summary":"Nothing here","leafUuid":"a"}"#;
+        assert!(convert_session(text, Path::new("empty.jsonl")).is_none());
+    }
+
+    #[test]
+    fn converts_claude_hooks_into_codex_hooks() {
+        let settings = json!({
+            "hooks": {
+                "PostToolUse": [{
+                    "matcher": "Edit|Write",
+                    "hooks": [{"type": "command", "command": "npx prettier --check", "timeout": 30}]
+                }],
+                "PreToolUse": [
+                    {"matcher": "Bash", "hooks": [{"type": "command", "command": "~/guard.sh"}]},
+                    {"matcher": "WebFetch", "hooks": [{"type": "command", "command": "~/net.sh"}]}
+                ],
+                "UserPromptSubmit": [{"hooks": [{"type": "command", "command": "~/prompt.sh"}]}]
+            }
+        });
+        let import = convert_claude_hooks(&settings);
+        assert_eq!(
+            import.hooks,
+            vec![
+                HookConfig {
+                    events: vec![HookEvent::AfterToolUse],
+                    matcher: Some("^(apply_patch)$".to_string()),
+                    command: stdin_adapter("npx prettier --check", HookEvent::AfterToolUse),
+                    timeout_ms: Some(30_000),
+                    ..Default::default()
+                },
+                HookConfig {
+                    events: vec![HookEvent::PreApproval],
+                    command: stdin_adapter("~/guard.sh", HookEvent::PreApproval),
+                    ..Default::default()
+                },
+            ]
+        );
+        assert_eq!(import.notes.len(), 2);
+        assert_eq!(
+            stdin_adapter("~/guard.sh", HookEvent::PreApproval)[2],
+            "CLAUDE_PROJECT_DIR=\"{cwd}\"; export CLAUDE_PROJECT_DIR\nprintf '%s' \"$1\" | {\n~/guard.sh\n}\n[ $? -eq 2 ] && echo deny\nexit 0"
+        );
+    }
+
+    #[test]
+    fn tool_matchers_are_translated() {
+        assert_eq!(codex_tool_matcher("*"), None);
+        assert_eq!(
+            codex_tool_matcher("Bash"),
+            Some("^(shell|shell_command|exec_command|local_shell)$".to_string())
+        );
+        assert_eq!(
+            codex_tool_matcher("Read|mcp__github__create_issue"),
+            Some("^(read_file|mcp__github__create_issue)$".to_string())
+        );
+        assert_eq!(
+            codex_tool_matcher("Notebook.*"),
+            Some("Notebook.*".to_string())
+        );
+    }
+}
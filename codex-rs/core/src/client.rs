@@ -1,11 +1,19 @@
+use std::borrow::Cow;
 use std::sync::Arc;
 
 use crate::api_bridge::auth_provider_from_auth;
 use crate::api_bridge::map_api_error;
+use async_channel::Sender;
 use codex_api::AggregateStreamExt;
+use codex_api::AnthropicClient as ApiAnthropicClient;
+use codex_api::AnthropicOptions as ApiAnthropicOptions;
+use codex_api::Cassette;
+use codex_api::CassetteTransport;
 use codex_api::ChatClient as ApiChatClient;
 use codex_api::CompactClient as ApiCompactClient;
 use codex_api::CompactionInput as ApiCompactionInput;
+use codex_api::GeminiClient as ApiGeminiClient;
+use codex_api::GeminiOptions as ApiGeminiOptions;
 use codex_api::Prompt as ApiPrompt;
 use codex_api::RequestTelemetry;
 use codex_api::ReqwestTransport;
@@ -32,6 +40,7 @@ use http::HeaderValue;
 use http::StatusCode as HttpStatusCode;
 use reqwest::StatusCode;
 use serde_json::Value;
+use std::sync::Mutex;
 use std::time::Duration;
 use tokio::sync::mpsc;
 use tracing::warn;
@@ -42,16 +51,24 @@ use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::client_common::ResponseStream;
 use crate::config::Config;
-use crate::default_client::build_reqwest_client;
+use crate::config::types::ModelRouteTarget;
+use crate::default_client::build_reqwest_client_for_provider;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::features::FEATURES;
 use crate::flags::CODEX_RS_SSE_FIXTURE;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::WireApi;
+use crate::model_routing;
+use crate::models_manager::manager::ModelsManager;
 use crate::models_manager::model_family::ModelFamily;
+use crate::protocol::Event as ProtocolEvent;
+use crate::protocol::EventMsg;
+use crate::protocol::RetryScheduledEvent;
+use crate::provider_auth;
 use crate::tools::spec::create_tools_json_for_chat_completions_api;
 use crate::tools::spec::create_tools_json_for_responses_api;
+use crate::truncate::approx_tokens_from_byte_count;
 
 #[derive(Debug, Clone)]
 pub struct ModelClient {
@@ -64,6 +81,21 @@ pub struct ModelClient {
     effort: Option<ReasoningEffortConfig>,
     summary: ReasoningSummaryConfig,
     session_source: SessionSource,
+    /// Model that served the latest `stream` call when `model_routing` sent it
+    /// somewhere other than the session model.
+    served_model: Arc<Mutex<Option<String>>>,
+    /// Where transport-level retry waits are reported, if anywhere.
+    retry_notifier: Option<RetryNotifier>,
+    /// Resolves the model family of a `model_routing` target.
+    models_manager: Option<Arc<ModelsManager>>,
+}
+
+/// Reports transport-level retry waits to the session as `RetryScheduled`
+/// events for the turn `sub_id`.
+#[derive(Debug, Clone)]
+struct RetryNotifier {
+    tx_event: Sender<ProtocolEvent>,
+    sub_id: String,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -89,9 +121,30 @@ impl ModelClient {
             effort,
             summary,
             session_source,
+            served_model: Arc::new(Mutex::new(None)),
+            retry_notifier: None,
+            models_manager: None,
         }
     }
 
+    /// Emits a `RetryScheduled` event on `tx_event` whenever a request is
+    /// about to be retried.
+    pub(crate) fn with_retry_notifier(
+        mut self,
+        tx_event: Sender<ProtocolEvent>,
+        sub_id: String,
+    ) -> Self {
+        self.retry_notifier = Some(RetryNotifier { tx_event, sub_id });
+        self
+    }
+
+    /// Resolves `model_routing` targets through `models_manager`, so a routed
+    /// request gets the same model family a session on that model would.
+    pub(crate) fn with_models_manager(mut self, models_manager: Arc<ModelsManager>) -> Self {
+        self.models_manager = Some(models_manager);
+        self
+    }
+
     pub fn get_model_context_window(&self) -> Option<i64> {
         let model_family = self.get_model_family();
         let effective_context_window_percent = model_family.effective_context_window_percent;
@@ -108,12 +161,77 @@ impl ModelClient {
         &self.provider
     }
 
-    /// Streams a single model turn using either the Responses or Chat
-    /// Completions wire API, depending on the configured provider.
+    /// Streams a single model turn, first applying the `model_routing` rules
+    /// and failing over to the configured fallbacks when the request is rate
+    /// limited or hits a server error. Only the initial request fails over;
+    /// errors later in the stream are left to the turn's retry loop.
+    pub async fn stream(&self, prompt: &Prompt) -> Result<ResponseStream> {
+        self.set_served_model(None);
+        let routing = &self.config.model_routing;
+        if routing.is_empty() {
+            return self.stream_with_provider(prompt).await;
+        }
+
+        let mut candidates =
+            model_routing::candidates(routing, prompt.purpose, estimate_input_tokens(prompt));
+        // `candidates` is never empty; the last entry gets no fallback.
+        let last = candidates.pop().flatten();
+        for target in candidates {
+            match self.stream_routed(prompt, target).await {
+                Ok(stream) => return Ok(stream),
+                Err((err, model)) if model_routing::should_fail_over(&err) => {
+                    warn!(
+                        "request to {model} failed, trying the next model_routing fallback: {err}"
+                    );
+                }
+                Err((err, _)) => return Err(err),
+            }
+        }
+        self.stream_routed(prompt, last)
+            .await
+            .map_err(|(err, _)| err)
+    }
+
+    /// Streams `prompt` to `target`, or to the session model for `None`. On
+    /// failure the error comes back with the model that was tried.
+    async fn stream_routed(
+        &self,
+        prompt: &Prompt,
+        target: Option<ModelRouteTarget>,
+    ) -> std::result::Result<ResponseStream, (CodexErr, String)> {
+        let client = match &target {
+            Some(target) => self
+                .routed_to(target)
+                .await
+                .map_err(|err| (err, target.model.clone()))?,
+            None => self.clone(),
+        };
+        // Encrypted reasoning can only be decrypted by the provider that
+        // produced it; another provider rejects the whole request.
+        let prompt = if client.provider == self.provider {
+            Cow::Borrowed(prompt)
+        } else {
+            Cow::Owned(Prompt {
+                input: model_routing::without_encrypted_reasoning(&prompt.input),
+                ..prompt.clone()
+            })
+        };
+        match client.stream_with_provider(&prompt).await {
+            Ok(stream) => {
+                self.set_served_model(target.map(|target| target.model));
+                Ok(stream)
+            }
+            Err(err) => Err((err, client.get_model())),
+        }
+    }
+
+    /// Streams a single model turn using the wire API of the configured
+    /// provider: Responses, Chat Completions, Anthropic Messages (directly or
+    /// on Bedrock) or Gemini.
     ///
     /// For Chat providers, the underlying stream is optionally aggregated
     /// based on the `show_raw_agent_reasoning` flag in the config.
-    pub async fn stream(&self, prompt: &Prompt) -> Result<ResponseStream> {
+    async fn stream_with_provider(&self, prompt: &Prompt) -> Result<ResponseStream> {
         match self.provider.wire_api {
             WireApi::Responses => self.stream_responses_api(prompt).await,
             WireApi::Chat => {
@@ -131,23 +249,28 @@ impl ModelClient {
                     ))
                 }
             }
+            WireApi::Anthropic | WireApi::Bedrock => {
+                let api_stream = self.stream_anthropic_messages(prompt).await?;
+                Ok(map_response_stream(api_stream, self.otel_manager.clone()))
+            }
+            WireApi::Gemini => {
+                let api_stream = self.stream_gemini(prompt).await?;
+                Ok(map_response_stream(api_stream, self.otel_manager.clone()))
+            }
         }
     }
 
     /// Streams a turn via the OpenAI Chat Completions API.
     ///
     /// This path is only used when the provider is configured with
-    /// `WireApi::Chat`; it does not support `output_schema` today.
+    /// `WireApi::Chat`. `output_schema` is sent as `response_format` and
+    /// also spelled out in the instructions, since not every compatible
+    /// server honors `response_format`. Like the Anthropic path,
+    /// `reasoning_effort` is only sent for an explicit effort.
     async fn stream_chat_completions(&self, prompt: &Prompt) -> Result<ApiResponseStream> {
-        if prompt.output_schema.is_some() {
-            return Err(CodexErr::UnsupportedOperation(
-                "output_schema is not supported for Chat Completions API".to_string(),
-            ));
-        }
-
         let auth_manager = self.auth_manager.clone();
         let model_family = self.get_model_family();
-        let instructions = prompt.get_full_instructions(&model_family).into_owned();
+        let instructions = prompt.get_instructions_with_output_schema(&model_family);
         let tools_json = create_tools_json_for_chat_completions_api(&prompt.tools)?;
         let api_prompt = build_api_prompt(prompt, instructions, tools_json);
         let conversation_id = self.conversation_id.to_string();
@@ -160,14 +283,18 @@ impl ModelClient {
                 .provider
                 .to_api_provider(auth.as_ref().map(|a| a.mode))?;
             let api_auth = auth_provider_from_auth(auth.clone(), &self.provider).await?;
-            let transport = ReqwestTransport::new(build_reqwest_client());
+            let transport = self.transport()?;
             let (request_telemetry, sse_telemetry) = self.build_streaming_telemetry();
+            let local_quirks = self.provider.local_quirks;
             let client = ApiChatClient::new(transport, api_provider, api_auth)
-                .with_telemetry(Some(request_telemetry), Some(sse_telemetry));
+                .with_telemetry(Some(request_telemetry), Some(sse_telemetry))
+                .with_local_quirks(local_quirks.is_some())
+                .with_tool_call_emulation(local_quirks.is_some_and(|q| q.emulate_tool_calls))
+                .with_reasoning_effort(self.effort);
 
             let stream_result = client
                 .stream_prompt(
-                    &self.get_model(),
+                    &self.request_model(),
                     &api_prompt,
                     Some(conversation_id.clone()),
                     Some(session_source.clone()),
@@ -179,7 +306,142 @@ impl ModelClient {
                 Err(ApiError::Transport(TransportError::Http { status, .. }))
                     if status == StatusCode::UNAUTHORIZED =>
                 {
-                    handle_unauthorized(status, &mut refreshed, &auth_manager, &auth).await?;
+                    handle_unauthorized(
+                        status,
+                        &mut refreshed,
+                        &auth_manager,
+                        &auth,
+                        &self.provider,
+                    )
+                    .await?;
+                    continue;
+                }
+                Err(err) => return Err(map_api_error(err)),
+            }
+        }
+    }
+
+    /// Streams a turn via the Anthropic Messages API.
+    ///
+    /// Extended thinking is only requested when a reasoning effort is set
+    /// explicitly. The API has no structured-output control, so
+    /// `output_schema` is only described in the instructions.
+    async fn stream_anthropic_messages(&self, prompt: &Prompt) -> Result<ApiResponseStream> {
+        let auth_manager = self.auth_manager.clone();
+        let model_family = self.get_model_family();
+        let instructions = prompt.get_instructions_with_output_schema(&model_family);
+        let tools_json = create_tools_json_for_chat_completions_api(&prompt.tools)?;
+        let api_prompt = build_api_prompt(prompt, instructions, tools_json);
+        let conversation_id = self.conversation_id.to_string();
+        let max_tokens = self
+            .config
+            .model_max_output_tokens
+            .and_then(|tokens| u64::try_from(tokens).ok());
+
+        let mut refreshed = false;
+        loop {
+            let auth = auth_manager.as_ref().and_then(|m| m.auth());
+            let api_provider = self
+                .provider
+                .to_api_provider(auth.as_ref().map(|a| a.mode))?;
+            let api_auth = auth_provider_from_auth(auth.clone(), &self.provider).await?;
+            let transport = self.transport()?;
+            let (request_telemetry, sse_telemetry) = self.build_streaming_telemetry();
+            let client = ApiAnthropicClient::new(transport, api_provider, api_auth)
+                .with_telemetry(Some(request_telemetry), Some(sse_telemetry));
+
+            let options = ApiAnthropicOptions {
+                max_tokens,
+                effort: self.effort,
+                conversation_id: Some(conversation_id.clone()),
+                cache_control: self.config.prompt_caching,
+            };
+
+            let stream_result = client
+                .stream_prompt(&self.request_model(), &api_prompt, options)
+                .await;
+
+            match stream_result {
+                Ok(stream) => return Ok(stream),
+                Err(ApiError::Transport(TransportError::Http { status, .. }))
+                    if status == StatusCode::UNAUTHORIZED =>
+                {
+                    handle_unauthorized(
+                        status,
+                        &mut refreshed,
+                        &auth_manager,
+                        &auth,
+                        &self.provider,
+                    )
+                    .await?;
+                    continue;
+                }
+                Err(err) => return Err(map_api_error(err)),
+            }
+        }
+    }
+
+    /// Streams a turn via the Gemini `streamGenerateContent` endpoint.
+    ///
+    /// Like the Anthropic path, thinking is only requested for an explicit
+    /// reasoning effort and `output_schema` is only described in the
+    /// instructions: Gemini's JSON mode can't be combined with tools.
+    async fn stream_gemini(&self, prompt: &Prompt) -> Result<ApiResponseStream> {
+        let auth_manager = self.auth_manager.clone();
+        let model_family = self.get_model_family();
+        let instructions = prompt.get_instructions_with_output_schema(&model_family);
+        let tools_json = create_tools_json_for_chat_completions_api(&prompt.tools)?;
+        let api_prompt = build_api_prompt(prompt, instructions, tools_json);
+        let conversation_id = self.conversation_id.to_string();
+        let max_output_tokens = self
+            .config
+            .model_max_output_tokens
+            .and_then(|tokens| u64::try_from(tokens).ok());
+        let mut safety_settings: Vec<(String, String)> = self
+            .provider
+            .safety_settings
+            .iter()
+            .flatten()
+            .map(|(category, threshold)| (category.clone(), threshold.clone()))
+            .collect();
+        safety_settings.sort();
+
+        let mut refreshed = false;
+        loop {
+            let auth = auth_manager.as_ref().and_then(|m| m.auth());
+            let api_provider = self
+                .provider
+                .to_api_provider(auth.as_ref().map(|a| a.mode))?;
+            let api_auth = auth_provider_from_auth(auth.clone(), &self.provider).await?;
+            let transport = self.transport()?;
+            let (request_telemetry, sse_telemetry) = self.build_streaming_telemetry();
+            let client = ApiGeminiClient::new(transport, api_provider, api_auth)
+                .with_telemetry(Some(request_telemetry), Some(sse_telemetry));
+
+            let options = ApiGeminiOptions {
+                max_output_tokens,
+                effort: self.effort,
+                safety_settings: safety_settings.clone(),
+                conversation_id: Some(conversation_id.clone()),
+            };
+
+            let stream_result = client
+                .stream_prompt(&self.request_model(), &api_prompt, options)
+                .await;
+
+            match stream_result {
+                Ok(stream) => return Ok(stream),
+                Err(ApiError::Transport(TransportError::Http { status, .. }))
+                    if status == StatusCode::UNAUTHORIZED =>
+                {
+                    handle_unauthorized(
+                        status,
+                        &mut refreshed,
+                        &auth_manager,
+                        &auth,
+                        &self.provider,
+                    )
+                    .await?;
                     continue;
                 }
                 Err(err) => return Err(map_api_error(err)),
@@ -249,7 +511,7 @@ impl ModelClient {
                 .provider
                 .to_api_provider(auth.as_ref().map(|a| a.mode))?;
             let api_auth = auth_provider_from_auth(auth.clone(), &self.provider).await?;
-            let transport = ReqwestTransport::new(build_reqwest_client());
+            let transport = self.transport()?;
             let (request_telemetry, sse_telemetry) = self.build_streaming_telemetry();
             let client = ApiResponsesClient::new(transport, api_provider, api_auth)
                 .with_telemetry(Some(request_telemetry), Some(sse_telemetry));
@@ -257,7 +519,7 @@ impl ModelClient {
             let options = ApiResponsesOptions {
                 reasoning: reasoning.clone(),
                 include: include.clone(),
-                prompt_cache_key: Some(conversation_id.clone()),
+                prompt_cache_key: self.config.prompt_caching.then(|| conversation_id.clone()),
                 text: text.clone(),
                 store_override: None,
                 conversation_id: Some(conversation_id.clone()),
@@ -266,7 +528,7 @@ impl ModelClient {
             };
 
             let stream_result = client
-                .stream_prompt(&self.get_model(), &api_prompt, options)
+                .stream_prompt(&self.request_model(), &api_prompt, options)
                 .await;
 
             match stream_result {
@@ -276,7 +538,14 @@ impl ModelClient {
                 Err(ApiError::Transport(TransportError::Http { status, .. }))
                     if status == StatusCode::UNAUTHORIZED =>
                 {
-                    handle_unauthorized(status, &mut refreshed, &auth_manager, &auth).await?;
+                    handle_unauthorized(
+                        status,
+                        &mut refreshed,
+                        &auth_manager,
+                        &auth,
+                        &self.provider,
+                    )
+                    .await?;
                     continue;
                 }
                 Err(err) => return Err(map_api_error(err)),
@@ -301,11 +570,67 @@ impl ModelClient {
         self.get_model_family().get_model_slug().to_string()
     }
 
+    /// The model name sent to the provider, which is the deployment name
+    /// for Azure providers that configure one.
+    fn request_model(&self) -> String {
+        self.provider.request_model(&self.get_model())
+    }
+
     /// Returns the currently configured model family.
     pub fn get_model_family(&self) -> ModelFamily {
         self.model_family.clone()
     }
 
+    /// Returns the model that served the latest `stream` call, which differs
+    /// from [`Self::get_model`] when `model_routing` picked another model.
+    pub fn served_model(&self) -> String {
+        self.served_model
+            .lock()
+            .ok()
+            .and_then(|served| served.clone())
+            .unwrap_or_else(|| self.get_model())
+    }
+
+    fn set_served_model(&self, model: Option<String>) {
+        if let Ok(mut served) = self.served_model.lock() {
+            *served = model;
+        }
+    }
+
+    /// Returns a copy of this client that sends requests to `target` instead
+    /// of the session provider and model.
+    async fn routed_to(&self, target: &ModelRouteTarget) -> Result<Self> {
+        let provider = match &target.provider {
+            Some(id) => self
+                .config
+                .model_providers
+                .get(id)
+                .cloned()
+                .ok_or_else(|| {
+                    CodexErr::UnsupportedOperation(format!("model provider `{id}` not found"))
+                })?,
+            None => self.provider.clone(),
+        };
+        let Some(models_manager) = &self.models_manager else {
+            return Err(CodexErr::UnsupportedOperation(format!(
+                "cannot route to model `{}`: no models manager",
+                target.model
+            )));
+        };
+        let model_family = models_manager
+            .construct_model_family(&target.model, &self.config)
+            .await;
+        let otel_manager = self
+            .otel_manager
+            .with_model(&target.model, model_family.get_model_slug());
+        Ok(Self {
+            model_family,
+            otel_manager,
+            provider,
+            ..self.clone()
+        })
+    }
+
     /// Returns the current reasoning effort setting.
     pub fn get_reasoning_effort(&self) -> Option<ReasoningEffortConfig> {
         self.effort
@@ -334,7 +659,7 @@ impl ModelClient {
             .provider
             .to_api_provider(auth.as_ref().map(|a| a.mode))?;
         let api_auth = auth_provider_from_auth(auth.clone(), &self.provider).await?;
-        let transport = ReqwestTransport::new(build_reqwest_client());
+        let transport = self.transport()?;
         let request_telemetry = self.build_request_telemetry();
         let client = ApiCompactClient::new(transport, api_provider, api_auth)
             .with_telemetry(Some(request_telemetry));
@@ -342,8 +667,9 @@ impl ModelClient {
         let instructions = prompt
             .get_full_instructions(&self.get_model_family())
             .into_owned();
+        let model = self.request_model();
         let payload = ApiCompactionInput {
-            model: &self.get_model(),
+            model: &model,
             input: &prompt.input,
             instructions: &instructions,
         };
@@ -371,9 +697,27 @@ impl ModelClient {
 }
 
 impl ModelClient {
+    /// HTTP transport for one request, wrapped in the configured cassette.
+    fn transport(&self) -> Result<CassetteTransport<ReqwestTransport>> {
+        let cassette = self
+            .config
+            .model_cassette
+            .as_ref()
+            .map(|cassette| Cassette::open(cassette.mode, &cassette.path))
+            .transpose()?;
+        Ok(CassetteTransport::new(
+            ReqwestTransport::new(build_reqwest_client_for_provider(&self.provider)),
+            cassette,
+        ))
+    }
+
     /// Builds request and SSE telemetry for streaming API calls (Chat/Responses).
     fn build_streaming_telemetry(&self) -> (Arc<dyn RequestTelemetry>, Arc<dyn SseTelemetry>) {
-        let telemetry = Arc::new(ApiTelemetry::new(self.otel_manager.clone()));
+        let telemetry = Arc::new(ApiTelemetry::new(
+            self.otel_manager.clone(),
+            self.retry_notifier.clone(),
+            self.provider.request_max_retries(),
+        ));
         let request_telemetry: Arc<dyn RequestTelemetry> = telemetry.clone();
         let sse_telemetry: Arc<dyn SseTelemetry> = telemetry;
         (request_telemetry, sse_telemetry)
@@ -381,7 +725,11 @@ impl ModelClient {
 
     /// Builds request telemetry for unary API calls (e.g., Compact endpoint).
     fn build_request_telemetry(&self) -> Arc<dyn RequestTelemetry> {
-        let telemetry = Arc::new(ApiTelemetry::new(self.otel_manager.clone()));
+        let telemetry = Arc::new(ApiTelemetry::new(
+            self.otel_manager.clone(),
+            self.retry_notifier.clone(),
+            self.provider.request_max_retries(),
+        ));
         let request_telemetry: Arc<dyn RequestTelemetry> = telemetry;
         request_telemetry
     }
@@ -398,6 +746,14 @@ fn build_api_prompt(prompt: &Prompt, instructions: String, tools_json: Vec<Value
     }
 }
 
+/// Rough size of the prompt input, used to match `min_input_tokens` rules.
+fn estimate_input_tokens(prompt: &Prompt) -> i64 {
+    let bytes = serde_json::to_string(&prompt.input)
+        .map(|input| input.len())
+        .unwrap_or_default();
+    i64::try_from(approx_tokens_from_byte_count(bytes)).unwrap_or(i64::MAX)
+}
+
 fn beta_feature_headers(config: &Config) -> ApiHeaderMap {
     let enabled = FEATURES
         .iter()
@@ -488,11 +844,23 @@ async fn handle_unauthorized(
     refreshed: &mut bool,
     auth_manager: &Option<Arc<AuthManager>>,
     auth: &Option<crate::auth::CodexAuth>,
+    provider: &ModelProviderInfo,
 ) -> Result<()> {
     if *refreshed {
         return Err(map_unauthorized_status(status));
     }
 
+    // An Entra ID token can be revoked before it expires; fetch a new one.
+    if let Some(command) = provider
+        .azure
+        .as_ref()
+        .and_then(|azure| azure.token_command.as_deref())
+    {
+        provider_auth::forget_azure_token(command);
+        *refreshed = true;
+        return Ok(());
+    }
+
     if let Some(manager) = auth_manager.as_ref()
         && let Some(auth) = auth.as_ref()
         && auth.mode == AuthMode::ChatGPT
@@ -520,11 +888,21 @@ fn map_unauthorized_status(status: StatusCode) -> CodexErr {
 
 struct ApiTelemetry {
     otel_manager: OtelManager,
+    retry_notifier: Option<RetryNotifier>,
+    max_retries: u64,
 }
 
 impl ApiTelemetry {
-    fn new(otel_manager: OtelManager) -> Self {
-        Self { otel_manager }
+    fn new(
+        otel_manager: OtelManager,
+        retry_notifier: Option<RetryNotifier>,
+        max_retries: u64,
+    ) -> Self {
+        Self {
+            otel_manager,
+            retry_notifier,
+            max_retries,
+        }
     }
 }
 
@@ -544,6 +922,44 @@ impl RequestTelemetry for ApiTelemetry {
             duration,
         );
     }
+
+    fn on_retry_wait(&self, attempt: u64, delay: Duration, error: &TransportError) {
+        let Some(notifier) = &self.retry_notifier else {
+            return;
+        };
+        let http_status_code = match error {
+            TransportError::Http { status, .. } => Some(status.as_u16()),
+            _ => None,
+        };
+        let reason = match http_status_code {
+            Some(429) => "rate limited".to_string(),
+            Some(code) => format!("server error ({code})"),
+            None => "connection failed".to_string(),
+        };
+        let event = ProtocolEvent {
+            id: notifier.sub_id.clone(),
+            msg: EventMsg::RetryScheduled(RetryScheduledEvent {
+                retry: attempt + 1,
+                max_retries: self.max_retries,
+                delay_ms: delay.as_millis() as u64,
+                http_status_code,
+                message: format!("{reason}, retrying in {}", format_retry_delay(delay)),
+            }),
+        };
+        if let Err(err) = notifier.tx_event.try_send(event) {
+            warn!("failed to send retry event: {err}");
+        }
+    }
+}
+
+/// Formats a retry delay for display: whole seconds (rounded up) from one
+/// second on, milliseconds below.
+fn format_retry_delay(delay: Duration) -> String {
+    if delay < Duration::from_secs(1) {
+        format!("{}ms", delay.as_millis())
+    } else {
+        format!("{}s", delay.as_secs_f64().ceil() as u64)
+    }
 }
 
 impl SseTelemetry for ApiTelemetry {
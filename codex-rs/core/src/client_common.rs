@@ -1,4 +1,5 @@
 use crate::client_common::tools::ToolSpec;
+use crate::config::types::RequestPurpose;
 use crate::error::Result;
 use crate::models_manager::model_family::ModelFamily;
 pub use codex_api::common::ResponseEvent;
@@ -23,6 +24,9 @@ pub const REVIEW_EXIT_SUCCESS_TMPL: &str = include_str!("../templates/review/exi
 pub const REVIEW_EXIT_INTERRUPTED_TMPL: &str =
     include_str!("../templates/review/exit_interrupted.xml");
 
+/// Precedes the schema in [`Prompt::get_instructions_with_output_schema`].
+const OUTPUT_SCHEMA_INSTRUCTIONS: &str = "When the task is done, your final message must be a single JSON value that validates against the JSON Schema below. Reply with the JSON only: no prose and no Markdown code fences.";
+
 /// API request payload for a single model turn
 #[derive(Default, Debug, Clone)]
 pub struct Prompt {
@@ -41,6 +45,9 @@ pub struct Prompt {
 
     /// Optional the output schema for the model's response.
     pub output_schema: Option<Value>,
+
+    /// Why the request is made; matched against `model_routing` rules.
+    pub(crate) purpose: RequestPurpose,
 }
 
 impl Prompt {
@@ -68,6 +75,20 @@ impl Prompt {
         }
     }
 
+    /// Full instructions plus, when an `output_schema` is set, a paragraph
+    /// asking for a final answer that validates against it. Used by wire
+    /// APIs that can't enforce the schema themselves.
+    pub(crate) fn get_instructions_with_output_schema(&self, model: &ModelFamily) -> String {
+        let instructions = self.get_full_instructions(model);
+        match &self.output_schema {
+            Some(schema) => format!(
+                "{instructions}\n\n{OUTPUT_SCHEMA_INSTRUCTIONS}\n{}",
+                serde_json::to_string_pretty(schema).unwrap_or_else(|_| schema.to_string())
+            ),
+            None => instructions.into_owned(),
+        }
+    }
+
     pub(crate) fn get_formatted_input(&self) -> Vec<ResponseItem> {
         let mut input = self.input.clone();
 
@@ -329,6 +350,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn output_schema_is_appended_to_the_instructions() {
+        let config = test_config();
+        let model_family = ModelsManager::construct_model_family_offline("gpt-5.1", &config);
+        let schema = serde_json::json!({"type": "object"});
+        let prompt = Prompt {
+            base_instructions_override: Some("Be brief.".to_string()),
+            output_schema: Some(schema.clone()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            prompt.get_instructions_with_output_schema(&model_family),
+            format!(
+                "Be brief.\n\n{OUTPUT_SCHEMA_INSTRUCTIONS}\n{}",
+                serde_json::to_string_pretty(&schema).unwrap()
+            )
+        );
+
+        let without_schema = Prompt {
+            output_schema: None,
+            ..prompt
+        };
+        assert_eq!(
+            without_schema.get_instructions_with_output_schema(&model_family),
+            "Be brief."
+        );
+    }
+
     #[test]
     fn serializes_text_verbosity_when_set() {
         let input: Vec<ResponseItem> = vec![];
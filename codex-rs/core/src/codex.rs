@@ -1,10 +1,16 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 use crate::AuthManager;
 use crate::SandboxState;
@@ -13,17 +19,25 @@ use crate::compact;
 use crate::compact::run_inline_auto_compact_task;
 use crate::compact::should_use_remote_compact_task;
 use crate::compact_remote::run_inline_remote_auto_compact_task;
+use crate::edit_checks;
 use crate::exec_policy::load_exec_policy_for_features;
+use crate::external_edits;
 use crate::features::Feature;
 use crate::features::Features;
+use crate::file_mentions::FileMentions;
+use crate::file_mentions::build_file_mentions;
 use crate::models_manager::manager::ModelsManager;
 use crate::models_manager::model_family::ModelFamily;
 use crate::parse_command::parse_command;
 use crate::parse_turn_item;
+use crate::permissions::PermissionStore;
 use crate::stream_events_utils::HandleOutputCtx;
 use crate::stream_events_utils::handle_non_tool_response_item;
 use crate::stream_events_utils::handle_output_item_done;
 use crate::terminal;
+use crate::tool_policy::ToolPolicy;
+use crate::tool_rules;
+use crate::tool_rules::ToolRules;
 use crate::truncate::TruncationPolicy;
 use crate::user_notification::UserNotifier;
 use crate::util::error_or_panic;
@@ -72,6 +86,11 @@ use tracing::warn;
 
 use crate::ModelProviderInfo;
 use crate::WireApi;
+use crate::after_tool_use_hook;
+use crate::after_tool_use_hook::AFTER_TOOL_USE_HOOK_TIMEOUT;
+use crate::after_tool_use_hook::AfterToolUse;
+use crate::budget;
+use crate::budget::BudgetUsage;
 use crate::client::ModelClient;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
@@ -80,37 +99,66 @@ use crate::config::Config;
 use crate::config::Constrained;
 use crate::config::ConstraintResult;
 use crate::config::GhostSnapshotConfig;
+use crate::config::edit::ConfigEditsBuilder;
+use crate::config::types::HookEvent;
+use crate::config::types::RequestPurpose;
 use crate::config::types::ShellEnvironmentPolicy;
+use crate::config::types::ToolRuleDecision;
 use crate::context_manager::ContextManager;
+use crate::context_pressure;
 use crate::environment_context::EnvironmentContext;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
 #[cfg(test)]
 use crate::exec::StreamOutput;
 use crate::exec_policy::ExecPolicyUpdateError;
+use crate::external_edits::ExternalEditTracker;
+use crate::git_info::resolve_root_git_project_for_trust;
+use crate::hooks;
+use crate::hooks::HookPlaceholders;
+use crate::hooks::HookSandbox;
+use crate::lsp::LspManager;
 use crate::mcp::auth::compute_auth_statuses;
 use crate::mcp_connection_manager::McpConnectionManager;
+use crate::memory_store;
 use crate::model_provider_info::CHAT_WIRE_API_DEPRECATION_SUMMARY;
+use crate::output_schema::check_final_answer;
+use crate::pinned_context::PinnedContext;
+use crate::plan_file;
+use crate::pre_approval_hook;
+use crate::pre_approval_hook::PRE_APPROVAL_HOOK_TIMEOUT;
+use crate::pre_approval_hook::PreApprovalRequest;
+use crate::project_doc::InstructionFiles;
+use crate::project_doc::cap_instructions;
 use crate::project_doc::get_user_instructions;
+use crate::project_tasks;
 use crate::protocol::AgentMessageContentDeltaEvent;
 use crate::protocol::AgentReasoningSectionBreakEvent;
 use crate::protocol::ApplyPatchApprovalRequestEvent;
 use crate::protocol::AskForApproval;
 use crate::protocol::BackgroundEventEvent;
+use crate::protocol::BudgetLimit;
+use crate::protocol::ContextPressureEvent;
 use crate::protocol::DeprecationNoticeEvent;
 use crate::protocol::ErrorEvent;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecApprovalRequestEvent;
 use crate::protocol::Op;
+use crate::protocol::PermissionScope;
+use crate::protocol::PinnedContextEntry;
 use crate::protocol::RateLimitSnapshot;
 use crate::protocol::ReasoningContentDeltaEvent;
 use crate::protocol::ReasoningRawContentDeltaEvent;
 use crate::protocol::ReviewDecision;
 use crate::protocol::SandboxPolicy;
+use crate::protocol::ScopedPermission;
+use crate::protocol::SecretsRedactedEvent;
 use crate::protocol::SessionConfiguredEvent;
+use crate::protocol::SessionDiffEvent;
 use crate::protocol::SkillErrorInfo;
 use crate::protocol::SkillMetadata as ProtocolSkillMetadata;
+use crate::protocol::SpendSummary;
 use crate::protocol::StreamErrorEvent;
 use crate::protocol::Submission;
 use crate::protocol::TokenCountEvent;
@@ -118,10 +166,23 @@ use crate::protocol::TokenUsage;
 use crate::protocol::TokenUsageInfo;
 use crate::protocol::TurnDiffEvent;
 use crate::protocol::WarningEvent;
+use crate::python_kernel::PythonKernelManager;
+use crate::redaction::RedactionCounts;
+use crate::redaction::Redactor;
+use crate::repo_map;
+use crate::resume_check;
 use crate::rollout::RolloutRecorder;
 use crate::rollout::RolloutRecorderParams;
 use crate::rollout::map_session_init_error;
+use crate::rollout::policy::is_reasoning_item;
+use crate::scratch_dir::ScratchDir;
+use crate::semantic_index;
+use crate::session_end_hook;
+use crate::session_end_hook::SessionEnd;
+use crate::session_status::SessionStatusFile;
+use crate::session_summary;
 use crate::shell;
+use crate::shell_session::ShellSessionManager;
 use crate::shell_snapshot::ShellSnapshot;
 use crate::skills::SkillError;
 use crate::skills::SkillInjections;
@@ -137,16 +198,26 @@ use crate::tasks::SessionTask;
 use crate::tasks::SessionTaskContext;
 use crate::tools::ToolRouter;
 use crate::tools::context::SharedTurnDiffTracker;
+use crate::tools::extensions::ToolEnv;
+use crate::tools::extensions::TurnExtensions;
+use crate::tools::loop_detection::ToolCallLoop;
 use crate::tools::parallel::ToolCallRuntime;
+use crate::tools::registry::MutatingToolCalls;
+use crate::tools::router::ToolCall;
 use crate::tools::sandboxing::ApprovalStore;
 use crate::tools::spec::ToolsConfig;
 use crate::tools::spec::ToolsConfigParams;
 use crate::turn_diff_tracker::TurnDiffTracker;
+use crate::unified_exec::BackgroundShellManager;
+use crate::unified_exec::TerminalManager;
 use crate::unified_exec::UnifiedExecSessionManager;
+use crate::usage_ledger;
+use crate::usage_ledger::UsageRecord;
 use crate::user_instructions::DeveloperInstructions;
 use crate::user_instructions::UserInstructions;
 use crate::user_notification::UserNotification;
 use crate::util::backoff;
+use crate::web::WebCache;
 use codex_async_utils::OrCancelExt;
 use codex_execpolicy::Policy as ExecPolicy;
 use codex_otel::otel_manager::OtelManager;
@@ -179,6 +250,12 @@ pub struct CodexSpawnOk {
 
 pub(crate) const INITIAL_SUBMIT_ID: &str = "";
 pub(crate) const SUBMISSION_CHANNEL_CAPACITY: usize = 64;
+/// How long shutdown waits for in-flight mutating tool calls by default.
+const DEFAULT_SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+/// Times a final answer that fails `final_output_json_schema` is sent back
+/// to the model before the task ends with an error.
+const MAX_OUTPUT_SCHEMA_RETRIES: usize = 2;
+const STEERED_RESPONSE_MARKER: &str = "[response interrupted by the user]";
 static CHAT_WIRE_API_DEPRECATION_EMITTED: AtomicBool = AtomicBool::new(false);
 
 fn maybe_push_chat_wire_api_deprecation(
@@ -205,6 +282,52 @@ fn maybe_push_chat_wire_api_deprecation(
     });
 }
 
+/// Assemble the user instructions for `config`: the AGENTS.md layers, the
+/// skills section, and saved memories when enabled, capped at
+/// `instructions_max_tokens`, followed by the repo map when enabled. Also
+/// returns a warning when the cap applied.
+async fn load_user_instructions(
+    config: &Config,
+    skills_manager: &SkillsManager,
+) -> (Option<String>, Option<String>) {
+    let loaded_skills = config
+        .features
+        .enabled(Feature::Skills)
+        .then(|| skills_manager.skills_for_cwd(&config.cwd));
+
+    if let Some(outcome) = &loaded_skills {
+        for err in &outcome.errors {
+            error!(
+                "failed to load skill {}: {}",
+                err.path.display(),
+                err.message
+            );
+        }
+    }
+
+    let user_instructions = get_user_instructions(
+        config,
+        loaded_skills
+            .as_ref()
+            .map(|outcome| outcome.skills.as_slice()),
+    )
+    .await;
+    let user_instructions = if config.features.enabled(Feature::Memory) {
+        memory_store::instructions_with_memories(user_instructions, config).await
+    } else {
+        user_instructions
+    };
+    let (user_instructions, warning) =
+        cap_instructions(user_instructions, config.instructions_max_tokens);
+    // The repo map has its own budget, so it is appended after the cap.
+    let user_instructions = if config.features.enabled(Feature::RepoMap) {
+        repo_map::instructions_with_repo_map(user_instructions, config).await
+    } else {
+        user_instructions
+    };
+    (user_instructions, warning)
+}
+
 impl Codex {
     /// Spawn a new [`Codex`] and initialize the session.
     pub async fn spawn(
@@ -218,34 +341,19 @@ impl Codex {
         let (tx_sub, rx_sub) = async_channel::bounded(SUBMISSION_CHANNEL_CAPACITY);
         let (tx_event, rx_event) = async_channel::unbounded();
 
-        let loaded_skills = config
-            .features
-            .enabled(Feature::Skills)
-            .then(|| skills_manager.skills_for_cwd(&config.cwd));
-
-        if let Some(outcome) = &loaded_skills {
-            for err in &outcome.errors {
-                error!(
-                    "failed to load skill {}: {}",
-                    err.path.display(),
-                    err.message
-                );
-            }
-        }
-
-        let user_instructions = get_user_instructions(
-            &config,
-            loaded_skills
-                .as_ref()
-                .map(|outcome| outcome.skills.as_slice()),
-        )
-        .await;
+        let (user_instructions, instructions_warning) =
+            load_user_instructions(&config, &skills_manager).await;
 
         let exec_policy = load_exec_policy_for_features(&config.features, &config.codex_home)
             .await
             .map_err(|err| CodexErr::Fatal(format!("failed to load execpolicy: {err}")))?;
         let exec_policy = Arc::new(RwLock::new(exec_policy));
 
+        // Front ends set these at startup; this covers embedders that do not.
+        // Only the first config in the process counts, so sessions with
+        // different `[http]` tables cannot switch each other's proxy.
+        crate::default_client::init_http_client_options(config.http.clone());
+
         let config = Arc::new(config);
         if config.features.enabled(Feature::RemoteModels)
             && let Err(err) = models_manager.refresh_available_models(&config).await
@@ -264,6 +372,7 @@ impl Codex {
             compact_prompt: config.compact_prompt.clone(),
             approval_policy: config.approval_policy.clone(),
             sandbox_policy: config.sandbox_policy.clone(),
+            read_only: config.read_only_mode,
             cwd: config.cwd.clone(),
             original_config_do_not_use: Arc::clone(&config),
             exec_policy,
@@ -289,6 +398,14 @@ impl Codex {
             map_session_init_error(&e, &config.codex_home)
         })?;
         let conversation_id = session.conversation_id;
+        if let Some(message) = instructions_warning {
+            session
+                .send_event_raw(Event {
+                    id: INITIAL_SUBMIT_ID.to_owned(),
+                    msg: EventMsg::Warning(WarningEvent { message }),
+                })
+                .await;
+        }
 
         // This task will run until Op::Shutdown is received.
         tokio::spawn(submission_loop(session, config, rx_sub));
@@ -373,6 +490,8 @@ pub(crate) struct TurnContext {
     pub(crate) tool_call_gate: Arc<ReadinessFlag>,
     pub(crate) exec_policy: Arc<RwLock<ExecPolicy>>,
     pub(crate) truncation_policy: TruncationPolicy,
+    /// Values hooks and tool handlers hand to each other during the turn.
+    pub(crate) extensions: TurnExtensions,
 }
 
 impl TurnContext {
@@ -417,6 +536,11 @@ pub(crate) struct SessionConfiguration {
     /// How to sandbox commands executed in the system
     sandbox_policy: Constrained<SandboxPolicy>,
 
+    /// Read-only "ask" mode. Overrides `approval_policy` and
+    /// `sandbox_policy` for each turn without replacing them, so turning it
+    /// off restores the session's own settings.
+    read_only: bool,
+
     /// Working directory that should be treated as the *root* of the
     /// session. All relative paths supplied by the model as well as the
     /// execution sandbox are resolved against this directory **instead**
@@ -456,6 +580,9 @@ impl SessionConfiguration {
         if let Some(cwd) = updates.cwd.clone() {
             next_configuration.cwd = cwd;
         }
+        if let Some(read_only) = updates.read_only {
+            next_configuration.read_only = read_only;
+        }
         Ok(next_configuration)
     }
 }
@@ -469,6 +596,7 @@ pub(crate) struct SessionSettingsUpdate {
     pub(crate) reasoning_effort: Option<Option<ReasoningEffortConfig>>,
     pub(crate) reasoning_summary: Option<ReasoningSummaryConfig>,
     pub(crate) final_output_json_schema: Option<Option<Value>>,
+    pub(crate) read_only: Option<bool>,
 }
 
 impl Session {
@@ -486,6 +614,7 @@ impl Session {
     #[allow(clippy::too_many_arguments)]
     fn make_turn_context(
         auth_manager: Option<Arc<AuthManager>>,
+        models_manager: Arc<ModelsManager>,
         otel_manager: &OtelManager,
         provider: ModelProviderInfo,
         session_configuration: &SessionConfiguration,
@@ -493,6 +622,7 @@ impl Session {
         model_family: ModelFamily,
         conversation_id: ConversationId,
         sub_id: String,
+        tx_event: Sender<Event>,
     ) -> TurnContext {
         let otel_manager = otel_manager.clone().with_model(
             session_configuration.model.as_str(),
@@ -510,12 +640,31 @@ impl Session {
             session_configuration.model_reasoning_summary,
             conversation_id,
             session_configuration.session_source.clone(),
-        );
+        )
+        .with_retry_notifier(tx_event, sub_id.clone())
+        .with_models_manager(models_manager);
 
-        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+        let mut tools_config = ToolsConfig::new(&ToolsConfigParams {
             model_family: &model_family,
             features: &per_turn_config.features,
         });
+        tools_config.allowed_tools = per_turn_config.allowed_tools.clone();
+        tools_config.web_search_provider = per_turn_config.web_search_provider.is_some();
+        tools_config.semantic_search &= per_turn_config.embeddings.is_some();
+        tools_config.tasks_only = per_turn_config.tasks_only;
+        tools_config.run_task |= per_turn_config.tasks_only;
+        if tools_config.run_task {
+            tools_config.project_tasks = project_tasks::discover(&session_configuration.cwd);
+        }
+        tools_config.read_only = session_configuration.read_only;
+        let (approval_policy, sandbox_policy) = if session_configuration.read_only {
+            (AskForApproval::Never, SandboxPolicy::ReadOnly)
+        } else {
+            (
+                session_configuration.approval_policy.value(),
+                session_configuration.sandbox_policy.get().clone(),
+            )
+        };
 
         TurnContext {
             sub_id,
@@ -525,14 +674,15 @@ impl Session {
             base_instructions: session_configuration.base_instructions.clone(),
             compact_prompt: session_configuration.compact_prompt.clone(),
             user_instructions: session_configuration.user_instructions.clone(),
-            approval_policy: session_configuration.approval_policy.value(),
-            sandbox_policy: session_configuration.sandbox_policy.get().clone(),
+            approval_policy,
+            sandbox_policy,
             shell_environment_policy: per_turn_config.shell_environment_policy.clone(),
             tools_config,
             ghost_snapshot: per_turn_config.ghost_snapshot.clone(),
             final_output_json_schema: None,
             codex_linux_sandbox_exe: per_turn_config.codex_linux_sandbox_exe.clone(),
             tool_call_gate: Arc::new(ReadinessFlag::new()),
+            extensions: TurnExtensions::default(),
             exec_policy: session_configuration.exec_policy.clone(),
             truncation_policy: TruncationPolicy::new(
                 per_turn_config.as_ref(),
@@ -656,21 +806,79 @@ impl Session {
                     .await
                     .map(Arc::new);
         }
-        let state = SessionState::new(session_configuration.clone());
+        let mut state = SessionState::new(session_configuration.clone());
+        state.instruction_files = InstructionFiles::snapshot(&config, &session_configuration.cwd);
+        state.global_instructions = config.user_instructions.clone();
+        state.spend.today_usd =
+            usage_ledger::today_spend_usd(&config.codex_home, &config.model_pricing).await;
+
+        let status_file = (!matches!(
+            session_configuration.session_source,
+            SessionSource::SubAgent(_)
+        ))
+        .then(|| {
+            SessionStatusFile::create(
+                &config.codex_home,
+                conversation_id.to_string(),
+                session_configuration.cwd.clone(),
+                session_configuration.model.clone(),
+            )
+        });
+
+        let scratch_dir = if config.features.enabled(Feature::ScratchDir) {
+            match ScratchDir::create(conversation_id) {
+                Ok(scratch_dir) => Some(scratch_dir),
+                Err(err) => {
+                    warn!("failed to create scratch directory: {err}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
 
         let services = SessionServices {
             mcp_connection_manager: Arc::new(RwLock::new(McpConnectionManager::default())),
             mcp_startup_cancellation_token: CancellationToken::new(),
             unified_exec_manager: UnifiedExecSessionManager::default(),
-            notifier: UserNotifier::new(config.notify.clone()),
+            background_shells: BackgroundShellManager::default(),
+            terminals: TerminalManager::default(),
+            shell_session: ShellSessionManager::default(),
+            python_kernel: PythonKernelManager::default(),
+            lsp: LspManager::default(),
+            last_test_run: Mutex::new(None),
+            scratch_dir,
+            web_cache: WebCache::default(),
+            notifier: UserNotifier::new(hooks::commands_for(
+                config.notify.as_ref(),
+                &config.hooks,
+                HookEvent::AgentTurnComplete,
+                &[],
+                config.sandbox_hooks,
+            )),
             rollout: Mutex::new(Some(rollout_recorder)),
             user_shell: Arc::new(default_shell),
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            persist_reasoning: config.model_provider.persists_reasoning(),
             auth_manager: Arc::clone(&auth_manager),
             otel_manager,
             models_manager: Arc::clone(&models_manager),
             tool_approvals: Mutex::new(ApprovalStore::default()),
             skills_manager,
+            session_diff_tracker: Arc::new(Mutex::new(TurnDiffTracker::new())),
+            external_edits: ExternalEditTracker::default(),
+            redactor: Redactor::from_config(&config.redaction).map_err(anyhow::Error::msg)?,
+            approved_command_prefixes: RwLock::new(config.approved_command_prefixes.clone()),
+            permissions: RwLock::new(PermissionStore::from_config(&config)),
+            tool_rules: ToolRules::new(&config.tool_rules).map_err(anyhow::Error::msg)?,
+            tool_policy: config
+                .tool_policy
+                .as_ref()
+                .map(|policy| ToolPolicy::new(policy, &config.codex_home))
+                .transpose()
+                .map_err(anyhow::Error::msg)?,
+            mutating_tool_calls: MutatingToolCalls::default(),
+            status_file,
         };
 
         let sess = Arc::new(Session {
@@ -751,6 +959,10 @@ impl Session {
         }
     }
 
+    pub(crate) fn conversation_id(&self) -> ConversationId {
+        self.conversation_id
+    }
+
     fn next_internal_sub_id(&self) -> String {
         let id = self
             .next_internal_sub_id
@@ -805,6 +1017,15 @@ impl Session {
                     }
                 }
 
+                // Restore pins from the most recent snapshot recorded in the rollout.
+                if let Some(pinned) = rollout_items.iter().rev().find_map(|it| match it {
+                    RolloutItem::EventMsg(EventMsg::PinnedContextUpdated(ev)) => Some(ev),
+                    _ => None,
+                }) {
+                    self.restore_pinned_context(&turn_context, pinned.entries.clone())
+                        .await;
+                }
+
                 // Always add response items to conversation history
                 let reconstructed_history =
                     self.reconstruct_history_from_rollout(&turn_context, &rollout_items);
@@ -817,6 +1038,18 @@ impl Session {
                 if persist && !rollout_items.is_empty() {
                     self.persist_rollout_items(&rollout_items).await;
                 }
+
+                if let InitialHistory::Resumed(resumed) = &conversation_history {
+                    self.revalidate_resumed_environment(
+                        &turn_context,
+                        &rollout_items,
+                        &resumed.rollout_path,
+                    )
+                    .await;
+                    if turn_context.client.config().sync_plan_file {
+                        self.restore_plan_file(&turn_context).await;
+                    }
+                }
                 // Flush after seeding history and any persisted rollout copy.
                 self.flush_rollout().await;
             }
@@ -841,6 +1074,12 @@ impl Session {
         }
     }
 
+    /// Whether read-only "ask" mode is on right now. Tool dispatch checks
+    /// this in addition to the turn's own setting.
+    pub(crate) async fn read_only_mode(&self) -> bool {
+        self.state.lock().await.session_configuration.read_only
+    }
+
     pub(crate) async fn new_turn_with_sub_id(
         &self,
         sub_id: String,
@@ -914,6 +1153,7 @@ impl Session {
             .await;
         let mut turn_context: TurnContext = Self::make_turn_context(
             Some(Arc::clone(&self.services.auth_manager)),
+            Arc::clone(&self.services.models_manager),
             &self.services.otel_manager,
             session_configuration.provider.clone(),
             &session_configuration,
@@ -921,10 +1161,14 @@ impl Session {
             model_family,
             self.conversation_id,
             sub_id,
+            self.get_tx_event(),
         );
         if let Some(final_schema) = final_output_json_schema {
             turn_context.final_output_json_schema = final_schema;
         }
+        if let Some(scratch_dir) = &self.services.scratch_dir {
+            scratch_dir.apply(&mut turn_context);
+        }
         Arc::new(turn_context)
     }
 
@@ -942,6 +1186,100 @@ impl Session {
             .await
     }
 
+    /// Reload the user instructions if any file feeding them was created,
+    /// edited, or deleted since they were last loaded, or a `cwd` change
+    /// brought different files into scope. New instructions are recorded in
+    /// the conversation, so they take precedence over the ones the model saw
+    /// earlier, and a turn context carrying them is returned. Otherwise
+    /// `turn_context` comes back as is.
+    async fn reload_instructions_if_changed(
+        &self,
+        turn_context: Arc<TurnContext>,
+    ) -> Arc<TurnContext> {
+        let mut config = {
+            let state = self.state.lock().await;
+            (*state.session_configuration.original_config_do_not_use).clone()
+        };
+        config.cwd = turn_context.cwd.clone();
+        let files = InstructionFiles::snapshot(&config, &turn_context.cwd);
+        {
+            let mut state = self.state.lock().await;
+            if state.instruction_files == files {
+                return turn_context;
+            }
+            if state.instruction_files.global_changed(&files) {
+                state.global_instructions = Config::load_instructions(Some(&config.codex_home));
+            }
+            state.instruction_files = files;
+            config.user_instructions = state.global_instructions.clone();
+        }
+
+        let (user_instructions, warning) =
+            load_user_instructions(&config, &self.services.skills_manager).await;
+        let session_configuration = {
+            let mut state = self.state.lock().await;
+            if state.session_configuration.user_instructions == user_instructions {
+                return turn_context;
+            }
+            state.session_configuration.user_instructions = user_instructions.clone();
+            state.session_configuration.clone()
+        };
+
+        let turn_context = self
+            .new_turn_from_configuration(
+                turn_context.sub_id.clone(),
+                session_configuration,
+                Some(turn_context.final_output_json_schema.clone()),
+                false,
+            )
+            .await;
+        if let Some(text) = user_instructions {
+            let item = ResponseItem::from(UserInstructions {
+                text,
+                directory: turn_context.cwd.to_string_lossy().into_owned(),
+            });
+            self.record_conversation_items(&turn_context, std::slice::from_ref(&item))
+                .await;
+        }
+        self.notify_background_event(
+            &turn_context,
+            "Reloaded instructions after AGENTS.md changed.",
+        )
+        .await;
+        if let Some(message) = warning {
+            self.send_event(&turn_context, EventMsg::Warning(WarningEvent { message }))
+                .await;
+        }
+        turn_context
+    }
+
+    /// Applies the effort set with `Op::OverrideNextTurnReasoning`, if any, to
+    /// `turn_context`. The session's own effort is left as it was.
+    async fn apply_next_turn_reasoning(&self, turn_context: Arc<TurnContext>) -> Arc<TurnContext> {
+        let (effort, mut session_configuration) = {
+            let mut state = self.state.lock().await;
+            let Some(effort) = state.next_turn_reasoning_effort.take() else {
+                return turn_context;
+            };
+            (effort, state.session_configuration.clone())
+        };
+        session_configuration.model_reasoning_effort = Some(effort);
+        let turn_context = self
+            .new_turn_from_configuration(
+                turn_context.sub_id.clone(),
+                session_configuration,
+                Some(turn_context.final_output_json_schema.clone()),
+                false,
+            )
+            .await;
+        self.notify_background_event(
+            &turn_context,
+            format!("Reasoning effort for this turn: {effort}."),
+        )
+        .await;
+        turn_context
+    }
+
     fn build_environment_update_item(
         &self,
         previous: Option<&Arc<TurnContext>>,
@@ -964,6 +1302,7 @@ impl Session {
 
     /// Persist the event to rollout and send it to clients.
     pub(crate) async fn send_event(&self, turn_context: &TurnContext, msg: EventMsg) {
+        self.update_status_file(turn_context, &msg);
         let legacy_source = msg.clone();
         let event = Event {
             id: turn_context.sub_id.clone(),
@@ -981,7 +1320,44 @@ impl Session {
         }
     }
 
+    /// Keeps the session's status file in step with task state, the model
+    /// in use and the context window left.
+    fn update_status_file(&self, turn_context: &TurnContext, msg: &EventMsg) {
+        let Some(status_file) = &self.services.status_file else {
+            return;
+        };
+        let busy = match msg {
+            EventMsg::TaskStarted(_) => Some(true),
+            EventMsg::TaskComplete(_) | EventMsg::TurnAborted(_) => Some(false),
+            EventMsg::TokenCount(_) => None,
+            _ => return,
+        };
+        let model = turn_context.client.get_model();
+        status_file.update(|status| {
+            if let Some(busy) = busy {
+                status.busy = busy;
+            }
+            if let EventMsg::TokenCount(TokenCountEvent {
+                info: Some(info), ..
+            }) = msg
+            {
+                status.tokens_used = info.total_token_usage.blended_total();
+                status.context_left_percent = info.model_context_window.map(|window| {
+                    info.last_token_usage
+                        .percent_of_context_window_remaining(window)
+                });
+            }
+            status.model = model;
+            true
+        });
+    }
+
     pub(crate) async fn send_event_raw(&self, event: Event) {
+        if let Some(scratch_dir) = &self.services.scratch_dir
+            && matches!(event.msg, EventMsg::Error(_))
+        {
+            scratch_dir.note_error();
+        }
         // Persist the event into rollout (recorder filters as needed)
         let rollout_items = vec![RolloutItem::EventMsg(event.msg.clone())];
         self.persist_rollout_items(&rollout_items).await;
@@ -1052,6 +1428,71 @@ impl Session {
         Ok(())
     }
 
+    /// Records `prefix` as approved for the project containing the session's
+    /// working directory, both in `config.toml` and for the rest of this
+    /// session.
+    pub(crate) async fn persist_project_command_prefix(
+        &self,
+        prefix: &[String],
+    ) -> anyhow::Result<()> {
+        let (codex_home, cwd) = {
+            let state = self.state.lock().await;
+            (
+                state
+                    .session_configuration
+                    .original_config_do_not_use
+                    .codex_home
+                    .clone(),
+                state.session_configuration.cwd.clone(),
+            )
+        };
+        let project = resolve_root_git_project_for_trust(&cwd).unwrap_or(cwd);
+        let rendered = shlex::try_join(prefix.iter().map(String::as_str))?;
+        ConfigEditsBuilder::new(&codex_home)
+            .add_project_command_prefix(project, &rendered)
+            .apply()
+            .await?;
+
+        let mut approved = self.services.approved_command_prefixes.write().await;
+        if !approved.iter().any(|existing| existing == prefix) {
+            approved.push(prefix.to_vec());
+        }
+        Ok(())
+    }
+
+    /// Grants `permissions` for the rest of this session and, for
+    /// [`PermissionScope::Project`], saves them for the project containing the
+    /// session's working directory.
+    pub(crate) async fn grant_permissions(
+        &self,
+        permissions: &[ScopedPermission],
+        scope: PermissionScope,
+    ) -> anyhow::Result<()> {
+        let project = {
+            let mut store = self.services.permissions.write().await;
+            for permission in permissions {
+                store.grant(permission);
+            }
+            store.project_root().to_path_buf()
+        };
+        if scope == PermissionScope::Project {
+            let codex_home = {
+                let state = self.state.lock().await;
+                state
+                    .session_configuration
+                    .original_config_do_not_use
+                    .codex_home
+                    .clone()
+            };
+            let mut edits = ConfigEditsBuilder::new(&codex_home);
+            for permission in permissions {
+                edits = edits.add_project_permission(project.clone(), permission);
+            }
+            edits.apply().await?;
+        }
+        Ok(())
+    }
+
     /// Emit an exec approval request event and await the user's decision.
     ///
     /// The request is keyed by `sub_id`/`call_id` so matching responses are delivered
@@ -1066,7 +1507,21 @@ impl Session {
         cwd: PathBuf,
         reason: Option<String>,
         proposed_execpolicy_amendment: Option<ExecPolicyAmendment>,
+        proposed_permissions: Vec<ScopedPermission>,
     ) -> ReviewDecision {
+        let request = PreApprovalRequest::ExecApproval {
+            thread_id: self.conversation_id.to_string(),
+            turn_id: &turn_context.sub_id,
+            call_id: &call_id,
+            cwd: &cwd,
+            command: &command,
+            reason: reason.as_deref(),
+            sandbox_policy: &turn_context.sandbox_policy,
+        };
+        if let Some(decision) = self.pre_approval_decision(turn_context, request).await {
+            return decision;
+        }
+
         let sub_id = turn_context.sub_id.clone();
         // Add the tx_approve callback to the map before sending the request.
         let (tx_approve, rx_approve) = oneshot::channel();
@@ -1087,16 +1542,35 @@ impl Session {
 
         let parsed_cmd = parse_command(&command);
         let event = EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
-            call_id,
+            call_id: call_id.clone(),
             turn_id: turn_context.sub_id.clone(),
             command,
             cwd,
             reason,
             proposed_execpolicy_amendment,
             parsed_cmd,
+            proposed_permissions,
         });
         self.send_event(turn_context, event).await;
-        rx_approve.await.unwrap_or_default()
+        self.await_approval(&call_id, async { rx_approve.await.unwrap_or_default() })
+            .await
+    }
+
+    /// Waits for `decision` without counting `call_id` as an in-flight
+    /// mutating tool call, so shutdown does not wait on an unanswered prompt.
+    /// A call approved after shutdown began is aborted rather than run.
+    pub(crate) async fn await_approval(
+        &self,
+        call_id: &str,
+        decision: impl Future<Output = ReviewDecision>,
+    ) -> ReviewDecision {
+        let tool_calls = &self.services.mutating_tool_calls;
+        let paused = tool_calls.pause(call_id);
+        let decision = decision.await;
+        if paused && !tool_calls.resume(call_id) {
+            return ReviewDecision::Abort;
+        }
+        decision
     }
 
     pub async fn request_patch_approval(
@@ -1106,10 +1580,26 @@ impl Session {
         changes: HashMap<PathBuf, FileChange>,
         reason: Option<String>,
         grant_root: Option<PathBuf>,
+        proposed_permissions: Vec<ScopedPermission>,
     ) -> oneshot::Receiver<ReviewDecision> {
+        let (tx_approve, rx_approve) = oneshot::channel();
+        let request = PreApprovalRequest::PatchApproval {
+            thread_id: self.conversation_id.to_string(),
+            turn_id: &turn_context.sub_id,
+            call_id: &call_id,
+            cwd: &turn_context.cwd,
+            files: changes.keys().map(PathBuf::as_path).collect(),
+            reason: reason.as_deref(),
+            grant_root: grant_root.as_deref(),
+            sandbox_policy: &turn_context.sandbox_policy,
+        };
+        if let Some(decision) = self.pre_approval_decision(turn_context, request).await {
+            tx_approve.send(decision).ok();
+            return rx_approve;
+        }
+
         let sub_id = turn_context.sub_id.clone();
         // Add the tx_approve callback to the map before sending the request.
-        let (tx_approve, rx_approve) = oneshot::channel();
         let event_id = sub_id.clone();
         let prev_entry = {
             let mut active = self.active_turn.lock().await;
@@ -1131,11 +1621,272 @@ impl Session {
             changes,
             reason,
             grant_root,
+            proposed_permissions,
         });
         self.send_event(turn_context, event).await;
         rx_approve
     }
 
+    /// Placeholder values shared by every hook run in `turn_context`.
+    fn hook_placeholders(&self, turn_context: &TurnContext) -> HookPlaceholders {
+        HookPlaceholders {
+            session_id: self.conversation_id.to_string(),
+            cwd: turn_context.cwd.display().to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Stops new tool calls from starting and, with `drain`, waits up to the
+    /// drain timeout for in-flight mutating ones. Returns how many were still
+    /// running.
+    async fn drain_tool_calls(&self, drain: bool) -> usize {
+        let tool_calls = &self.services.mutating_tool_calls;
+        tool_calls.close();
+        if !drain {
+            return tool_calls.len();
+        }
+        let timeout = {
+            let state = self.state.lock().await;
+            state
+                .session_configuration
+                .original_config_do_not_use
+                .shutdown_drain_timeout_ms
+                .map_or(DEFAULT_SHUTDOWN_DRAIN_TIMEOUT, Duration::from_millis)
+        };
+        if tokio::time::timeout(timeout, tool_calls.wait())
+            .await
+            .is_ok()
+        {
+            return 0;
+        }
+        let remaining = tool_calls.len();
+        warn!(
+            "{remaining} mutating tool call(s) still running after {} ms; killing them",
+            timeout.as_millis()
+        );
+        remaining
+    }
+
+    /// Runs the `SessionEnd` hooks one after another and waits for each.
+    async fn run_session_end_hooks(&self, killed_tool_calls: usize) {
+        let (commands, sandbox) = {
+            let state = self.state.lock().await;
+            let session_configuration = &state.session_configuration;
+            let config = &session_configuration.original_config_do_not_use;
+            let commands = hooks::commands_for(
+                None,
+                &config.hooks,
+                HookEvent::SessionEnd,
+                &[],
+                config.sandbox_hooks,
+            );
+            let sandbox = HookSandbox {
+                policy: session_configuration.sandbox_policy.get().clone(),
+                cwd: session_configuration.cwd.clone(),
+                codex_linux_sandbox_exe: config.codex_linux_sandbox_exe.clone(),
+            };
+            (commands, sandbox)
+        };
+        let event = SessionEnd {
+            thread_id: self.conversation_id.to_string(),
+            killed_tool_calls,
+        };
+        let placeholders = HookPlaceholders {
+            session_id: self.conversation_id.to_string(),
+            cwd: sandbox.cwd.display().to_string(),
+            ..Default::default()
+        };
+        for command in &commands {
+            session_end_hook::run(&placeholders.expand(command), &event, &sandbox).await;
+        }
+    }
+
+    /// Removes the `scratch_dir` directory once the `SessionEnd` hooks have
+    /// had a chance to look at it. A directory kept by `keep_on_error` is
+    /// reported to the client.
+    async fn cleanup_scratch_dir(&self, sub_id: &str) {
+        let Some(scratch_dir) = &self.services.scratch_dir else {
+            return;
+        };
+        let keep_on_error = {
+            let state = self.state.lock().await;
+            state
+                .session_configuration
+                .original_config_do_not_use
+                .scratch_dir
+                .keep_on_error
+        };
+        if let Some(path) = scratch_dir.cleanup(keep_on_error).await {
+            let event = Event {
+                id: sub_id.to_string(),
+                msg: EventMsg::BackgroundEvent(BackgroundEventEvent {
+                    message: format!(
+                        "Kept scratch directory {} because the session reported an error",
+                        path.display()
+                    ),
+                }),
+            };
+            self.send_event_raw(event).await;
+        }
+    }
+
+    /// Lets the `pre_approval_hook` answer an approval request before it is
+    /// shown to the user. Returns `None` when the user should be asked.
+    async fn pre_approval_decision(
+        &self,
+        turn_context: &TurnContext,
+        request: PreApprovalRequest<'_>,
+    ) -> Option<ReviewDecision> {
+        // A call an `ask` rule matched is always put to the user.
+        if tool_rules::verdict_for(turn_context, request.call_id())
+            .is_some_and(|verdict| verdict.decision == ToolRuleDecision::Ask)
+        {
+            return None;
+        }
+        let (commands, latency_budget) = {
+            let state = self.state.lock().await;
+            let config = &state.session_configuration.original_config_do_not_use;
+            let commands = hooks::commands_for(
+                config.pre_approval_hook.as_ref(),
+                &config.hooks,
+                HookEvent::PreApproval,
+                &request.subjects(),
+                config.sandbox_hooks,
+            );
+            (commands, config.hooks_max_total_latency_ms)
+        };
+        let sandbox = HookSandbox::for_turn(turn_context);
+        let placeholders = HookPlaceholders {
+            tool_command: request.tool_command(),
+            ..self.hook_placeholders(turn_context)
+        };
+        let started = Instant::now();
+        let mut decision = None;
+        for (index, command) in commands.iter().enumerate() {
+            let Some(timeout) = hooks::time_left(
+                latency_budget.map(Duration::from_millis),
+                started.elapsed(),
+                PRE_APPROVAL_HOOK_TIMEOUT,
+            ) else {
+                let message = format!(
+                    "Skipped {} pre-approval hook(s) for {}: hooks_max_total_latency_ms ({} ms) exceeded.",
+                    commands.len() - index,
+                    request.summary(),
+                    latency_budget.unwrap_or_default(),
+                );
+                self.send_event(turn_context, EventMsg::Warning(WarningEvent { message }))
+                    .await;
+                break;
+            };
+            let command = placeholders.expand(command);
+            let answer = pre_approval_hook::run(&command, &request, &sandbox, timeout).await;
+            if !answer.env.is_empty() {
+                turn_context
+                    .extensions
+                    .update::<ToolEnv>(|tool_env| tool_env.0.extend(answer.env));
+            }
+            decision = answer.decision;
+            if decision.is_some() {
+                break;
+            }
+        }
+        let decision = decision?;
+        let verb = if decision == ReviewDecision::Approved {
+            "approved"
+        } else {
+            "denied"
+        };
+        self.notify_background_event(
+            turn_context,
+            format!("Pre-approval hook {verb} {}", request.summary()),
+        )
+        .await;
+        Some(decision)
+    }
+
+    /// Runs the `AfterToolUse` hooks for a finished tool call and appends
+    /// the reasons of hooks that blocked it to `response`.
+    pub(crate) async fn apply_after_tool_use_hooks(
+        &self,
+        turn_context: &TurnContext,
+        tool_name: &str,
+        input: &str,
+        tool_command: Option<String>,
+        response: &mut ResponseInputItem,
+    ) {
+        let (commands, latency_budget) = {
+            let state = self.state.lock().await;
+            let config = &state.session_configuration.original_config_do_not_use;
+            let commands = hooks::commands_for(
+                None,
+                &config.hooks,
+                HookEvent::AfterToolUse,
+                &[tool_name.to_string()],
+                config.sandbox_hooks,
+            );
+            (commands, config.hooks_max_total_latency_ms)
+        };
+        if commands.is_empty() {
+            return;
+        }
+        let call_id = match &*response {
+            ResponseInputItem::FunctionCallOutput { call_id, .. }
+            | ResponseInputItem::McpToolCallOutput { call_id, .. }
+            | ResponseInputItem::CustomToolCallOutput { call_id, .. } => call_id.clone(),
+            ResponseInputItem::Message { .. } => return,
+        };
+        let (output, success) = after_tool_use_hook::result_text(response);
+        let call = AfterToolUse {
+            thread_id: self.conversation_id.to_string(),
+            turn_id: &turn_context.sub_id,
+            call_id: &call_id,
+            tool_name,
+            input,
+            output,
+            success,
+        };
+        let sandbox = HookSandbox::for_turn(turn_context);
+        let placeholders = HookPlaceholders {
+            tool_name: tool_name.to_string(),
+            tool_command: tool_command.unwrap_or_default(),
+            ..self.hook_placeholders(turn_context)
+        };
+        let started = Instant::now();
+        let mut feedback = Vec::new();
+        for (index, command) in commands.iter().enumerate() {
+            let Some(timeout) = hooks::time_left(
+                latency_budget.map(Duration::from_millis),
+                started.elapsed(),
+                AFTER_TOOL_USE_HOOK_TIMEOUT,
+            ) else {
+                let message = format!(
+                    "Skipped {} after-tool-use hook(s) for {tool_name}: hooks_max_total_latency_ms ({} ms) exceeded.",
+                    commands.len() - index,
+                    latency_budget.unwrap_or_default(),
+                );
+                self.send_event(turn_context, EventMsg::Warning(WarningEvent { message }))
+                    .await;
+                break;
+            };
+            let command = placeholders.expand(command);
+            if let Some(reason) = after_tool_use_hook::run(&command, &call, &sandbox, timeout).await
+            {
+                feedback.push(reason);
+            }
+        }
+        if !feedback.is_empty() {
+            self.notify_background_event(
+                turn_context,
+                format!(
+                    "After-tool-use hook flagged {tool_name}: {}",
+                    feedback.join("; ")
+                ),
+            )
+            .await;
+        }
+        after_tool_use_hook::append_feedback(response, &feedback);
+    }
+
     pub async fn notify_approval(&self, sub_id: &str, decision: ReviewDecision) {
         let entry = {
             let mut active = self.active_turn.lock().await;
@@ -1178,9 +1929,45 @@ impl Session {
         turn_context: &TurnContext,
         items: &[ResponseItem],
     ) {
-        self.record_into_history(items, turn_context).await;
-        self.persist_rollout_response_items(items).await;
-        self.send_raw_response_items(turn_context, items).await;
+        let items = self.redact_items(turn_context, items).await;
+        self.record_into_history(&items, turn_context).await;
+        self.persist_rollout_response_items(&items).await;
+        self.send_raw_response_items(turn_context, &items).await;
+    }
+
+    /// Masks secrets in user messages and tool output and tells clients how
+    /// many were found.
+    async fn redact_items<'a>(
+        &self,
+        turn_context: &TurnContext,
+        items: &'a [ResponseItem],
+    ) -> Cow<'a, [ResponseItem]> {
+        let Some(redactor) = self.services.redactor.as_ref() else {
+            return Cow::Borrowed(items);
+        };
+        let mut counts = RedactionCounts::new();
+        let redacted: Vec<Option<ResponseItem>> = items
+            .iter()
+            .map(|item| redactor.redact_item(item, &mut counts))
+            .collect();
+        if counts.is_empty() {
+            return Cow::Borrowed(items);
+        }
+        self.send_event(
+            turn_context,
+            EventMsg::SecretsRedacted(SecretsRedactedEvent {
+                count: counts.values().sum(),
+                detectors: counts.into_keys().collect(),
+            }),
+        )
+        .await;
+        Cow::Owned(
+            items
+                .iter()
+                .zip(redacted)
+                .map(|(item, redacted)| redacted.unwrap_or_else(|| item.clone()))
+                .collect(),
+        )
     }
 
     fn reconstruct_history_from_rollout(
@@ -1266,6 +2053,15 @@ impl Session {
         self.features.clone()
     }
 
+    /// How long a task may run before the watchdog cancels it.
+    pub(crate) async fn turn_timeout(&self) -> Option<Duration> {
+        let state = self.state.lock().await;
+        state
+            .session_configuration
+            .original_config_do_not_use
+            .turn_timeout
+    }
+
     async fn send_raw_response_items(&self, turn_context: &TurnContext, items: &[ResponseItem]) {
         for item in items {
             self.send_event(
@@ -1282,6 +2078,9 @@ impl Session {
         if let Some(developer_instructions) = turn_context.developer_instructions.as_deref() {
             items.push(DeveloperInstructions::new(developer_instructions.to_string()).into());
         }
+        if let Some(scratch_dir) = &self.services.scratch_dir {
+            items.push(DeveloperInstructions::new(scratch_dir.instructions()).into());
+        }
         if let Some(user_instructions) = turn_context.user_instructions.as_deref() {
             items.push(
                 UserInstructions {
@@ -1305,6 +2104,17 @@ impl Session {
             let guard = self.services.rollout.lock().await;
             guard.clone()
         };
+        let kept: Vec<RolloutItem>;
+        let items = if self.services.persist_reasoning {
+            items
+        } else {
+            kept = items
+                .iter()
+                .filter(|item| !is_reasoning_item(item))
+                .cloned()
+                .collect();
+            kept.as_slice()
+        };
         if let Some(rec) = recorder
             && let Err(e) = rec.record_items(items).await
         {
@@ -1312,6 +2122,25 @@ impl Session {
         }
     }
 
+    /// Writes the session's title and summary to the rollout when the
+    /// finished turn changed them.
+    pub(crate) async fn record_session_summary(&self) {
+        let summary = {
+            let mut state = self.state.lock().await;
+            let history = state.clone_history().get_history();
+            let Some(summary) = session_summary::summarize(&history) else {
+                return;
+            };
+            if state.session_summary.as_ref() == Some(&summary) {
+                return;
+            }
+            state.session_summary = Some(summary.clone());
+            summary
+        };
+        self.persist_rollout_items(&[RolloutItem::SessionSummary(summary)])
+            .await;
+    }
+
     pub(crate) async fn clone_history(&self) -> ContextManager {
         let state = self.state.lock().await;
         state.clone_history()
@@ -1331,7 +2160,183 @@ impl Session {
                 );
             }
         }
-        self.send_token_count_event(turn_context).await;
+        if let Some(token_usage) = token_usage {
+            self.record_usage(turn_context, token_usage).await;
+        }
+        self.send_token_count_event(turn_context).await;
+    }
+
+    /// Price `token_usage` for the turn's model, add it to the running spend
+    /// totals, and append it to the usage ledger in the background.
+    async fn record_usage(&self, turn_context: &TurnContext, token_usage: &TokenUsage) {
+        let model = turn_context.client.served_model();
+        let (codex_home, cost_usd) = {
+            let mut state = self.state.lock().await;
+            let config = &state.session_configuration.original_config_do_not_use;
+            let cost_usd = usage_ledger::pricing_for_model(&config.model_pricing, &model)
+                .map(|pricing| usage_ledger::cost_usd(pricing, token_usage));
+            let codex_home = config.codex_home.clone();
+            if let Some(cost) = cost_usd {
+                state.spend.session_usd += cost;
+                state.spend.today_usd += cost;
+            }
+            (codex_home, cost_usd)
+        };
+        let record = UsageRecord {
+            ts: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            session_id: self.conversation_id.to_string(),
+            turn_id: turn_context.sub_id.clone(),
+            model,
+            input_tokens: token_usage.input_tokens,
+            cached_input_tokens: token_usage.cached_input_tokens,
+            output_tokens: token_usage.output_tokens,
+            reasoning_output_tokens: token_usage.reasoning_output_tokens,
+            cost_usd,
+        };
+        tokio::spawn(async move {
+            if let Err(e) = usage_ledger::append_record(&codex_home, &record).await {
+                warn!("failed to append to usage ledger: {e}");
+            }
+        });
+    }
+
+    /// Check the configured budgets before issuing a model request. Returns
+    /// true (after emitting `BudgetExceeded`) when the request must not be
+    /// sent; otherwise counts the request against `budget.max_turns`.
+    async fn halt_if_over_budget(&self, turn_context: &TurnContext, task_started: Instant) -> bool {
+        let (exceeded, hook_commands) = {
+            let mut state = self.state.lock().await;
+            let budget = state
+                .session_configuration
+                .original_config_do_not_use
+                .budget
+                .clone();
+            if let Some(event) = state.budget_exceeded.clone() {
+                (Some(event), Vec::new())
+            } else {
+                let usage = BudgetUsage {
+                    total_tokens: state
+                        .token_info()
+                        .map(|info| info.total_token_usage.total_tokens)
+                        .unwrap_or_default(),
+                    cost_usd: state.spend.session_usd,
+                    turns: state.model_requests,
+                    task_elapsed: task_started.elapsed(),
+                };
+                match budget::check(&budget, &usage) {
+                    Some(event) => {
+                        if event.limit != BudgetLimit::TaskDuration {
+                            state.budget_exceeded = Some(event.clone());
+                        }
+                        let config = &state.session_configuration.original_config_do_not_use;
+                        let hook_commands = hooks::commands_for(
+                            budget.on_exceeded.as_ref(),
+                            &config.hooks,
+                            HookEvent::BudgetExceeded,
+                            &[],
+                            config.sandbox_hooks,
+                        );
+                        (Some(event), hook_commands)
+                    }
+                    None => {
+                        state.model_requests += 1;
+                        (None, Vec::new())
+                    }
+                }
+            }
+        };
+        let Some(event) = exceeded else {
+            return false;
+        };
+        warn!("{}", event.message);
+        let sandbox = HookSandbox::for_turn(turn_context);
+        let placeholders = self.hook_placeholders(turn_context);
+        for command in &hook_commands {
+            budget::run_on_exceeded_hook(&placeholders.expand(command), &event, &sandbox);
+        }
+        self.send_event(turn_context, EventMsg::BudgetExceeded(event))
+            .await;
+        true
+    }
+
+    /// Conversation history to send with the next prompt, preceded by any
+    /// pinned context items.
+    pub(crate) async fn history_for_prompt(&self) -> Vec<ResponseItem> {
+        let (mut history, mut input) = {
+            let state = self.state.lock().await;
+            (state.clone_history(), state.pinned_context.response_items())
+        };
+        input.extend(history.get_history_for_prompt());
+        input
+    }
+
+    /// Tells the user and the model what changed in the environment since the
+    /// resumed rollout was recorded.
+    async fn revalidate_resumed_environment(
+        &self,
+        turn_context: &TurnContext,
+        rollout_items: &[RolloutItem],
+        rollout_path: &Path,
+    ) {
+        let drift = {
+            let mcp_connection_manager = self.services.mcp_connection_manager.read().await;
+            resume_check::revalidate(
+                rollout_items,
+                rollout_path,
+                &turn_context.cwd,
+                &mcp_connection_manager,
+            )
+            .await
+        };
+        if drift.is_empty() {
+            return;
+        }
+        self.send_event(
+            turn_context,
+            EventMsg::Warning(WarningEvent {
+                message: drift.warning(),
+            }),
+        )
+        .await;
+        let note: ResponseItem = DeveloperInstructions::new(drift.context_note()).into();
+        self.record_conversation_items(turn_context, &[note]).await;
+    }
+
+    /// Shows the plan from `TODO.codex.md` and tells the model about it, so a
+    /// resumed session continues from the file, including any edits made
+    /// while it was closed.
+    async fn restore_plan_file(&self, turn_context: &TurnContext) {
+        let Some(plan) = plan_file::read(&turn_context.cwd).await else {
+            return;
+        };
+        let note = format!(
+            "The plan below was restored from {} in the working directory. People may have edited it since the session was saved; continue from it and keep it current with update_plan.\n\n{}",
+            plan_file::PLAN_FILE_NAME,
+            plan_file::render(&plan)
+        );
+        let note: ResponseItem = DeveloperInstructions::new(note).into();
+        self.record_conversation_items(turn_context, &[note]).await;
+        self.send_event(turn_context, EventMsg::PlanUpdate(plan))
+            .await;
+    }
+
+    async fn restore_pinned_context(
+        &self,
+        turn_context: &TurnContext,
+        entries: Vec<PinnedContextEntry>,
+    ) {
+        let (pinned, errors) = PinnedContext::restore(entries, &turn_context.cwd).await;
+        {
+            let mut state = self.state.lock().await;
+            state.pinned_context = pinned;
+        }
+        for message in errors {
+            self.send_event(turn_context, EventMsg::Warning(WarningEvent { message }))
+                .await;
+        }
     }
 
     pub(crate) async fn recompute_token_usage(&self, turn_context: &TurnContext) {
@@ -1342,6 +2347,8 @@ impl Session {
         else {
             return;
         };
+        let estimated_total_tokens = estimated_total_tokens
+            .saturating_add(self.state.lock().await.pinned_context.total_tokens());
         {
             let mut state = self.state.lock().await;
             let mut info = state.token_info().unwrap_or(TokenUsageInfo {
@@ -1380,12 +2387,74 @@ impl Session {
     }
 
     async fn send_token_count_event(&self, turn_context: &TurnContext) {
-        let (info, rate_limits) = {
+        let model = turn_context.client.get_model();
+        let (info, rate_limits, pinned_context_tokens, spend) = {
             let state = self.state.lock().await;
-            state.token_info_and_rate_limits()
+            let (info, rate_limits) = state.token_info_and_rate_limits();
+            let pinned_context_tokens =
+                (!state.pinned_context.is_empty()).then(|| state.pinned_context.total_tokens());
+            let pricing = &state
+                .session_configuration
+                .original_config_do_not_use
+                .model_pricing;
+            // Report $0 for a priced model rather than nothing, and keep
+            // reporting spend after switching to a model without pricing.
+            let priced = usage_ledger::pricing_for_model(pricing, &model).is_some();
+            let spend = (priced || state.spend != SpendSummary::default()).then_some(state.spend);
+            (info, rate_limits, pinned_context_tokens, spend)
         };
-        let event = EventMsg::TokenCount(TokenCountEvent { info, rate_limits });
+        let event = EventMsg::TokenCount(TokenCountEvent {
+            info,
+            rate_limits,
+            pinned_context_tokens,
+            spend,
+        });
         self.send_event(turn_context, event).await;
+        self.check_context_pressure(turn_context).await;
+    }
+
+    /// Emits `ContextPressure` when usage reaches a higher threshold than
+    /// the last one reported. Falling back below a threshold re-arms it.
+    async fn check_context_pressure(&self, turn_context: &TurnContext) {
+        let Some(context_window) = turn_context.client.get_model_context_window() else {
+            return;
+        };
+        let (threshold, used_tokens, history, pinned_tokens) = {
+            let mut state = self.state.lock().await;
+            let used_tokens = state.get_total_token_usage();
+            let crossed = context_pressure::crossed_threshold(
+                &state
+                    .session_configuration
+                    .original_config_do_not_use
+                    .context_pressure_thresholds,
+                used_tokens,
+                context_window,
+            );
+            let previous = std::mem::replace(&mut state.context_pressure_reported, crossed);
+            match crossed {
+                Some(threshold) if crossed > previous => (
+                    threshold,
+                    used_tokens,
+                    state.clone_history().get_history_for_prompt(),
+                    state.pinned_context.total_tokens(),
+                ),
+                _ => return,
+            }
+        };
+        let model_family = turn_context.client.get_model_family();
+        let event = ContextPressureEvent {
+            threshold_percent: threshold,
+            used_tokens,
+            context_window,
+            breakdown: context_pressure::breakdown(
+                &history,
+                &model_family.base_instructions,
+                pinned_tokens,
+            ),
+            largest_items: context_pressure::largest_items_for_event(&history),
+        };
+        self.send_event(turn_context, EventMsg::ContextPressure(event))
+            .await;
     }
 
     pub(crate) async fn set_total_tokens_full(&self, turn_context: &TurnContext) {
@@ -1483,6 +2552,58 @@ impl Session {
         }
     }
 
+    /// Queues `input` for the running task and cancels its in-flight model
+    /// request so the task restarts with it. Returns the input if there was
+    /// no task running to steer.
+    pub async fn steer_input(&self, input: Vec<UserInput>) -> Result<(), Vec<UserInput>> {
+        let mut active = self.active_turn.lock().await;
+        match active.as_mut() {
+            Some(at) => {
+                let mut ts = at.turn_state.lock().await;
+                if ts.steer(input.into()) {
+                    info!("steer received: restarting the in-flight request");
+                }
+                Ok(())
+            }
+            None => Err(input),
+        }
+    }
+
+    /// Registers the token that cancels the model request about to start.
+    async fn begin_request(&self, cancellation_token: CancellationToken) {
+        let mut active = self.active_turn.lock().await;
+        if let Some(at) = active.as_mut() {
+            at.turn_state.lock().await.begin_request(cancellation_token);
+        }
+    }
+
+    /// Whether the current request was cancelled by a steer rather than an
+    /// interrupt.
+    async fn was_steered(&self) -> bool {
+        let active = self.active_turn.lock().await;
+        match active.as_ref() {
+            Some(at) => at.turn_state.lock().await.steered(),
+            None => false,
+        }
+    }
+
+    /// Records a tool call of the running task and reports whether it
+    /// continues a loop of identical calls.
+    pub(crate) async fn observe_tool_call(&self, call: &ToolCall) -> Option<ToolCallLoop> {
+        let limit = self
+            .state
+            .lock()
+            .await
+            .session_configuration
+            .original_config_do_not_use
+            .tool_call_loop_limit;
+        let mut active = self.active_turn.lock().await;
+        let at = active.as_mut()?;
+        let mut ts = at.turn_state.lock().await;
+        ts.tool_calls_mut()
+            .observe(&call.tool_name, &call.payload, limit)
+    }
+
     pub async fn get_pending_input(&self) -> Vec<ResponseInputItem> {
         let mut active = self.active_turn.lock().await;
         match active.as_mut() {
@@ -1581,15 +2702,118 @@ impl Session {
     async fn cancel_mcp_startup(&self) {
         self.services.mcp_startup_cancellation_token.cancel();
     }
+
+    /// Records an `apply_patch_verify` result for the turn `turn_id` and
+    /// returns how many checks in a row have now failed within it.
+    pub(crate) async fn record_patch_verification(&self, turn_id: &str, passed: bool) -> u32 {
+        let mut state = self.state.lock().await;
+        let failures = match &state.patch_verify_failures {
+            Some((id, failures)) if id == turn_id => *failures,
+            _ => 0,
+        };
+        let failures = if passed {
+            0
+        } else {
+            failures.saturating_add(1)
+        };
+        state.patch_verify_failures = Some((turn_id.to_string(), failures));
+        failures
+    }
+
+    /// Restarts the idle clock used by `idle_suspend_after_ms`.
+    pub(crate) async fn mark_active(&self) {
+        self.state.lock().await.last_activity = Instant::now();
+    }
+
+    /// Once no task has run for `after`, stops the MCP servers and pauses
+    /// background shells and terminals, so idle sessions hold no server
+    /// processes and use no CPU.
+    async fn suspend_if_idle(&self, after: Duration) {
+        if self.active_turn.lock().await.is_some() {
+            return;
+        }
+        {
+            let mut state = self.state.lock().await;
+            if state.idle_suspended || state.last_activity.elapsed() < after {
+                return;
+            }
+            state.idle_suspended = true;
+        }
+        // Dropping the clients kills the server processes.
+        *self.services.mcp_connection_manager.write().await = McpConnectionManager::default();
+        self.services.background_shells.set_paused(true).await;
+        self.services.terminals.set_paused(true).await;
+        info!(
+            "session idle for {} ms; suspended MCP servers and background processes",
+            after.as_millis()
+        );
+    }
+
+    /// Records activity and, if the session was suspended while idle,
+    /// continues its background processes and restarts the MCP servers.
+    async fn resume_if_suspended(&self, config: &Config) {
+        let sandbox_state = {
+            let mut state = self.state.lock().await;
+            state.last_activity = Instant::now();
+            if !std::mem::take(&mut state.idle_suspended) {
+                return;
+            }
+            let session_configuration = &state.session_configuration;
+            SandboxState {
+                sandbox_policy: session_configuration.sandbox_policy.get().clone(),
+                codex_linux_sandbox_exe: config.codex_linux_sandbox_exe.clone(),
+                sandbox_cwd: session_configuration.cwd.clone(),
+            }
+        };
+        info!("resuming suspended session");
+        self.services.background_shells.set_paused(false).await;
+        self.services.terminals.set_paused(false).await;
+        let auth_statuses = compute_auth_statuses(
+            config.mcp_servers.iter(),
+            config.mcp_oauth_credentials_store_mode,
+        )
+        .await;
+        self.services
+            .mcp_connection_manager
+            .write()
+            .await
+            .initialize(
+                config.mcp_servers.clone(),
+                config.mcp_oauth_credentials_store_mode,
+                auth_statuses,
+                self.tx_event.clone(),
+                CancellationToken::new(),
+                sandbox_state,
+            )
+            .await;
+    }
 }
 
 async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiver<Submission>) {
     // Seed with context in case there is an OverrideTurnContext first.
     let mut previous_context: Option<Arc<TurnContext>> = Some(sess.new_default_turn().await);
 
+    let idle_suspend_after = config.idle_suspend_after_ms.map(Duration::from_millis);
+
     // To break out of this loop, send Op::Shutdown.
-    while let Ok(sub) = rx_sub.recv().await {
+    loop {
+        let sub = match idle_suspend_after {
+            Some(after) => tokio::select! {
+                sub = rx_sub.recv() => sub,
+                _ = tokio::time::sleep(after) => {
+                    sess.suspend_if_idle(after).await;
+                    continue;
+                }
+            },
+            None => rx_sub.recv().await,
+        };
+        let Ok(sub) = sub else {
+            break;
+        };
         debug!(?sub, "Submission");
+        if !matches!(sub.op, Op::Shutdown | Op::Quit) {
+            sess.resume_if_suspended(&config).await;
+        }
         match sub.op.clone() {
             Op::Interrupt => {
                 handlers::interrupt(&sess).await;
@@ -1621,6 +2845,15 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
                 handlers::user_input_or_turn(&sess, sub.id.clone(), sub.op, &mut previous_context)
                     .await;
             }
+            Op::OverrideNextTurnReasoning { effort } => {
+                handlers::override_next_turn_reasoning(&sess, effort).await;
+            }
+            Op::SetReadOnlyMode { enabled } => {
+                handlers::set_read_only_mode(&sess, sub.id.clone(), enabled).await;
+            }
+            Op::Steer { items } => {
+                handlers::steer(&sess, sub.id.clone(), items, &mut previous_context).await;
+            }
             Op::ExecApproval { id, decision } => {
                 handlers::exec_approval(&sess, id, decision).await;
             }
@@ -1665,14 +2898,24 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
             } => {
                 handlers::resolve_elicitation(&sess, server_name, request_id, decision).await;
             }
-            Op::Shutdown => {
-                if handlers::shutdown(&sess, sub.id.clone()).await {
+            Op::Shutdown | Op::Quit => {
+                let drain = matches!(sub.op, Op::Shutdown);
+                if handlers::shutdown(&sess, sub.id.clone(), drain).await {
                     break;
                 }
             }
             Op::Review { review_request } => {
                 handlers::review(&sess, &config, sub.id.clone(), review_request).await;
             }
+            Op::PinContext { item } => {
+                handlers::pin_context(&sess, sub.id.clone(), item).await;
+            }
+            Op::UnpinContext { id } => {
+                handlers::unpin_context(&sess, sub.id.clone(), id).await;
+            }
+            Op::DropContextItems { ids } => {
+                handlers::drop_context_items(&sess, sub.id.clone(), ids).await;
+            }
             _ => {} // Ignore unknown ops; enum is non_exhaustive to allow extensions.
         }
     }
@@ -1690,21 +2933,29 @@ mod handlers {
     use crate::features::Feature;
     use crate::mcp::auth::compute_auth_statuses;
     use crate::mcp::collect_mcp_snapshot_from_manager;
+    use crate::pinned_context::LoadedPin;
+    use crate::redaction::RedactionCounts;
     use crate::review_prompts::resolve_review_request;
     use crate::tasks::CompactTask;
     use crate::tasks::RegularTask;
     use crate::tasks::UndoTask;
     use crate::tasks::UserShellCommandTask;
     use codex_protocol::custom_prompts::CustomPrompt;
+    use codex_protocol::openai_models::ReasoningEffort as ReasoningEffortConfig;
     use codex_protocol::protocol::CodexErrorInfo;
+    use codex_protocol::protocol::CompactedItem;
     use codex_protocol::protocol::ErrorEvent;
     use codex_protocol::protocol::Event;
     use codex_protocol::protocol::EventMsg;
     use codex_protocol::protocol::ListCustomPromptsResponseEvent;
     use codex_protocol::protocol::ListSkillsResponseEvent;
     use codex_protocol::protocol::Op;
+    use codex_protocol::protocol::PinnedContextItem;
+    use codex_protocol::protocol::PinnedContextUpdatedEvent;
+    use codex_protocol::protocol::ReadOnlyModeChangedEvent;
     use codex_protocol::protocol::ReviewDecision;
     use codex_protocol::protocol::ReviewRequest;
+    use codex_protocol::protocol::RolloutItem;
     use codex_protocol::protocol::SkillsListEntry;
     use codex_protocol::protocol::TurnAbortReason;
     use codex_protocol::protocol::WarningEvent;
@@ -1765,6 +3016,7 @@ mod handlers {
                     reasoning_effort: Some(effort),
                     reasoning_summary: Some(summary),
                     final_output_json_schema: Some(final_output_json_schema),
+                    read_only: None,
                 },
             ),
             Op::UserInput { items } => (items, SessionSettingsUpdate::default()),
@@ -1782,6 +3034,8 @@ mod handlers {
 
         // Attempt to inject input into current task
         if let Err(items) = sess.inject_input(items).await {
+            let current_context = sess.reload_instructions_if_changed(current_context).await;
+            let current_context = sess.apply_next_turn_reasoning(current_context).await;
             if let Some(env_item) =
                 sess.build_environment_update_item(previous_context.as_ref(), &current_context)
             {
@@ -1795,6 +3049,44 @@ mod handlers {
         }
     }
 
+    pub async fn override_next_turn_reasoning(sess: &Session, effort: ReasoningEffortConfig) {
+        sess.state.lock().await.next_turn_reasoning_effort = Some(effort);
+    }
+
+    pub async fn set_read_only_mode(sess: &Session, sub_id: String, enabled: bool) {
+        let updates = SessionSettingsUpdate {
+            read_only: Some(enabled),
+            ..Default::default()
+        };
+        if sess.update_settings(updates).await.is_err() {
+            return;
+        }
+        let message = if enabled {
+            "Read-only mode on: Codex can read and explain code but will not edit files or run commands that change anything."
+        } else {
+            "Read-only mode off: edits and commands follow the session's approval and sandbox settings again."
+        };
+        sess.send_event_raw(Event {
+            id: sub_id,
+            msg: EventMsg::ReadOnlyModeChanged(ReadOnlyModeChangedEvent {
+                enabled,
+                message: message.to_string(),
+            }),
+        })
+        .await;
+    }
+
+    pub async fn steer(
+        sess: &Arc<Session>,
+        sub_id: String,
+        items: Vec<UserInput>,
+        previous_context: &mut Option<Arc<TurnContext>>,
+    ) {
+        if let Err(items) = sess.steer_input(items).await {
+            user_input_or_turn(sess, sub_id, Op::UserInput { items }, previous_context).await;
+        }
+    }
+
     pub async fn run_user_shell_command(
         sess: &Arc<Session>,
         sub_id: String,
@@ -1838,7 +3130,8 @@ mod handlers {
     }
 
     /// Propagate a user's exec approval decision to the session.
-    /// Also optionally applies an execpolicy amendment.
+    /// Also optionally applies an execpolicy amendment or saves an approved
+    /// command prefix for the project.
     pub async fn exec_approval(sess: &Arc<Session>, id: String, decision: ReviewDecision) {
         if let ReviewDecision::ApprovedExecpolicyAmendment {
             proposed_execpolicy_amendment,
@@ -1856,6 +3149,19 @@ mod handlers {
             })
             .await;
         }
+        if let ReviewDecision::ApprovedPrefixForProject { prefix } = &decision
+            && let Err(err) = sess.persist_project_command_prefix(prefix).await
+        {
+            let message = format!("Failed to save approved command prefix: {err}");
+            tracing::warn!("{message}");
+            let warning = EventMsg::Warning(WarningEvent { message });
+            sess.send_event_raw(Event {
+                id: id.clone(),
+                msg: warning,
+            })
+            .await;
+        }
+        grant_approved_permissions(sess, &id, &decision).await;
         match decision {
             ReviewDecision::Abort => {
                 sess.interrupt_task().await;
@@ -1865,6 +3171,7 @@ mod handlers {
     }
 
     pub async fn patch_approval(sess: &Arc<Session>, id: String, decision: ReviewDecision) {
+        grant_approved_permissions(sess, &id, &decision).await;
         match decision {
             ReviewDecision::Abort => {
                 sess.interrupt_task().await;
@@ -1873,7 +3180,30 @@ mod handlers {
         }
     }
 
+    /// Records the permissions granted by an `ApprovedPermissions` decision
+    /// before the waiting tool call resumes.
+    async fn grant_approved_permissions(sess: &Arc<Session>, id: &str, decision: &ReviewDecision) {
+        if let ReviewDecision::ApprovedPermissions { permissions, scope } = decision
+            && let Err(err) = sess.grant_permissions(permissions, *scope).await
+        {
+            let message = format!("Failed to save granted permissions: {err}");
+            tracing::warn!("{message}");
+            let warning = EventMsg::Warning(WarningEvent { message });
+            sess.send_event_raw(Event {
+                id: id.to_string(),
+                msg: warning,
+            })
+            .await;
+        }
+    }
+
     pub async fn add_to_history(sess: &Arc<Session>, config: &Arc<Config>, text: String) {
+        let text = match sess.services.redactor.as_ref() {
+            Some(redactor) => redactor
+                .redact(&text, &mut RedactionCounts::new())
+                .into_owned(),
+            None => text,
+        };
         let id = sess.conversation_id;
         let config = Arc::clone(config);
         tokio::spawn(async move {
@@ -1945,16 +3275,96 @@ mod handlers {
             } else {
                 Vec::new()
             };
+        let (codex_home, cwd) = {
+            let state = sess.state.lock().await;
+            (
+                state
+                    .session_configuration
+                    .original_config_do_not_use
+                    .codex_home
+                    .clone(),
+                state.session_configuration.cwd.clone(),
+            )
+        };
+        let custom_commands = crate::custom_prompts::discover_commands(&codex_home, &cwd).await;
 
         let event = Event {
             id: sub_id,
             msg: EventMsg::ListCustomPromptsResponse(ListCustomPromptsResponseEvent {
                 custom_prompts,
+                custom_commands,
             }),
         };
         sess.send_event_raw(event).await;
     }
 
+    pub async fn pin_context(sess: &Session, sub_id: String, item: PinnedContextItem) {
+        let cwd = {
+            let state = sess.state.lock().await;
+            state.session_configuration.cwd.clone()
+        };
+        // Read files before taking the lock for the pin itself.
+        let result = match LoadedPin::load(item, &cwd).await {
+            Ok(pin) => {
+                let mut state = sess.state.lock().await;
+                state.pinned_context.pin(pin);
+                Ok(state.pinned_context.to_event())
+            }
+            Err(err) => Err(err),
+        };
+        send_pinned_context_result(sess, sub_id, result).await;
+    }
+
+    pub async fn unpin_context(sess: &Session, sub_id: String, id: String) {
+        let result = {
+            let mut state = sess.state.lock().await;
+            if state.pinned_context.unpin(&id) {
+                Ok(state.pinned_context.to_event())
+            } else {
+                Err(format!("no pinned context item with id `{id}`"))
+            }
+        };
+        send_pinned_context_result(sess, sub_id, result).await;
+    }
+
+    pub async fn drop_context_items(sess: &Session, sub_id: String, ids: Vec<String>) {
+        let (event, history) = {
+            let mut state = sess.state.lock().await;
+            let event = state.history.drop_items(&ids);
+            (event, state.history.get_history())
+        };
+        if !event.dropped.is_empty() {
+            // Resuming replays this instead of the dropped items.
+            sess.persist_rollout_items(&[RolloutItem::Compacted(CompactedItem {
+                message: String::new(),
+                replacement_history: Some(history),
+            })])
+            .await;
+            let turn_context = sess.new_default_turn_with_sub_id(sub_id.clone()).await;
+            sess.recompute_token_usage(&turn_context).await;
+        }
+        sess.send_event_raw(Event {
+            id: sub_id,
+            msg: EventMsg::ContextItemsDropped(event),
+        })
+        .await;
+    }
+
+    async fn send_pinned_context_result(
+        sess: &Session,
+        sub_id: String,
+        result: Result<PinnedContextUpdatedEvent, String>,
+    ) {
+        let msg = match result {
+            Ok(event) => EventMsg::PinnedContextUpdated(event),
+            Err(message) => EventMsg::Error(ErrorEvent {
+                message,
+                codex_error_info: Some(CodexErrorInfo::BadRequest),
+            }),
+        };
+        sess.send_event_raw(Event { id: sub_id, msg }).await;
+    }
+
     pub async fn list_skills(
         sess: &Session,
         sub_id: String,
@@ -2016,12 +3426,25 @@ mod handlers {
         .await;
     }
 
-    pub async fn shutdown(sess: &Arc<Session>, sub_id: String) -> bool {
+    /// Shuts the session down. `drain` lets in-flight mutating tool calls
+    /// finish first; an interactive quit skips that.
+    pub async fn shutdown(sess: &Arc<Session>, sub_id: String, drain: bool) -> bool {
+        let killed_tool_calls = sess.drain_tool_calls(drain).await;
         sess.abort_all_tasks(TurnAbortReason::Interrupted).await;
         sess.services
             .unified_exec_manager
             .terminate_all_sessions()
             .await;
+        sess.services.background_shells.terminate_all().await;
+        sess.services.terminals.terminate_all().await;
+        sess.services.shell_session.terminate().await;
+        sess.services.python_kernel.terminate().await;
+        sess.services.lsp.terminate().await;
+        sess.run_session_end_hooks(killed_tool_calls).await;
+        sess.cleanup_scratch_dir(&sub_id).await;
+        if let Some(status_file) = &sess.services.status_file {
+            status_file.remove();
+        }
         info!("Shutting down Codex instance");
 
         // Gracefully flush and shutdown rollout recorder on session end so tests
@@ -2103,10 +3526,11 @@ async fn spawn_review_thread(
     review_features
         .disable(crate::features::Feature::WebSearchRequest)
         .disable(crate::features::Feature::ViewImageTool);
-    let tools_config = ToolsConfig::new(&ToolsConfigParams {
+    let mut tools_config = ToolsConfig::new(&ToolsConfigParams {
         model_family: &review_model_family,
         features: &review_features,
     });
+    tools_config.read_only = parent_turn_context.tools_config.read_only;
 
     let base_instructions = REVIEW_PROMPT.to_string();
     let review_prompt = resolved.prompt.clone();
@@ -2136,7 +3560,9 @@ async fn spawn_review_thread(
         per_turn_config.model_reasoning_summary,
         sess.conversation_id,
         parent_turn_context.client.get_session_source(),
-    );
+    )
+    .with_retry_notifier(sess.get_tx_event(), sub_id.clone())
+    .with_models_manager(Arc::clone(&sess.services.models_manager));
 
     let review_turn_context = TurnContext {
         sub_id: sub_id.to_string(),
@@ -2154,6 +3580,7 @@ async fn spawn_review_thread(
         final_output_json_schema: None,
         codex_linux_sandbox_exe: parent_turn_context.codex_linux_sandbox_exe.clone(),
         tool_call_gate: Arc::new(ReadinessFlag::new()),
+        extensions: TurnExtensions::default(),
         exec_policy: parent_turn_context.exec_policy.clone(),
         truncation_policy: TruncationPolicy::new(&per_turn_config, model_family.truncation_policy),
     };
@@ -2220,6 +3647,7 @@ pub(crate) async fn run_task(
     if input.is_empty() {
         return None;
     }
+    let task_started = Instant::now();
 
     let auto_compact_limit = turn_context
         .client
@@ -2251,6 +3679,19 @@ pub(crate) async fn run_task(
             .await;
     }
 
+    let FileMentions {
+        item: file_mentions,
+        warnings: file_mention_warnings,
+    } = build_file_mentions(&input, &turn_context.cwd).await;
+
+    for message in file_mention_warnings {
+        sess.send_event(&turn_context, EventMsg::Warning(WarningEvent { message }))
+            .await;
+    }
+
+    let retrieved_context =
+        retrieve_semantic_context(&sess, &turn_context, &input, &cancellation_token).await;
+
     let initial_input_for_turn: ResponseInputItem = ResponseInputItem::from(input);
     let response_item: ResponseItem = initial_input_for_turn.clone().into();
     sess.record_response_item_and_emit_turn_item(turn_context.as_ref(), response_item)
@@ -2261,14 +3702,46 @@ pub(crate) async fn run_task(
             .await;
     }
 
+    if let Some(item) = file_mentions {
+        sess.record_conversation_items(&turn_context, &[item]).await;
+    }
+
+    if let Some(item) = retrieved_context {
+        sess.record_conversation_items(&turn_context, &[item]).await;
+    }
+
+    if sess.enabled(Feature::ExternalEdits) {
+        let changes = sess.services.external_edits.take_changes().await;
+        if !changes.is_empty() {
+            let note: ResponseItem =
+                DeveloperInstructions::new(external_edits::notice(&changes, &turn_context.cwd))
+                    .into();
+            sess.record_conversation_items(&turn_context, &[note]).await;
+            sess.notify_background_event(
+                &turn_context,
+                format!(
+                    "{} file(s) changed outside the session; told the model.",
+                    changes.len()
+                ),
+            )
+            .await;
+        }
+    }
+
     sess.maybe_start_ghost_snapshot(Arc::clone(&turn_context), cancellation_token.child_token())
         .await;
     let mut last_agent_message: Option<String> = None;
+    let mut output_schema_retries = 0;
+    let mut edit_check_fix_turns = 0;
     // Although from the perspective of codex.rs, TurnDiffTracker has the lifecycle of a Task which contains
     // many turns, from the perspective of the user, it is a single turn.
     let turn_diff_tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
 
     loop {
+        if sess.halt_if_over_budget(&turn_context, task_started).await {
+            break;
+        }
+
         // Note that pending_input would be something like a message the user
         // submitted through the UI while the model was running. Though the UI
         // may support this, the model might not.
@@ -2283,7 +3756,7 @@ pub(crate) async fn run_task(
         let turn_input: Vec<ResponseItem> = {
             sess.record_conversation_items(&turn_context, &pending_input)
                 .await;
-            sess.clone_history().await.get_history_for_prompt()
+            sess.history_for_prompt().await
         };
 
         let turn_input_messages = turn_input
@@ -2294,19 +3767,22 @@ pub(crate) async fn run_task(
             })
             .map(|user_message| user_message.message())
             .collect::<Vec<String>>();
+        // A steer cancels only this request; the task keeps going.
+        let request_cancellation_token = cancellation_token.child_token();
+        sess.begin_request(request_cancellation_token.clone()).await;
         match run_turn(
             Arc::clone(&sess),
             Arc::clone(&turn_context),
             Arc::clone(&turn_diff_tracker),
             turn_input,
-            cancellation_token.child_token(),
+            request_cancellation_token,
         )
         .await
         {
             Ok(turn_output) => {
                 let TurnRunResult {
                     needs_follow_up,
-                    last_agent_message: turn_last_agent_message,
+                    last_agent_message: mut turn_last_agent_message,
                 } = turn_output;
                 let total_usage_tokens = sess.get_total_token_usage().await;
                 let token_limit_reached = total_usage_tokens >= auto_compact_limit;
@@ -2318,19 +3794,72 @@ pub(crate) async fn run_task(
                 }
 
                 if !needs_follow_up {
+                    if turn_context.client.config().edit_checks.is_enabled() {
+                        let task_diff = turn_diff_tracker.lock().await.get_unified_diff();
+                        if let Ok(Some(task_diff)) = task_diff
+                            && run_edit_checks(
+                                &sess,
+                                &turn_context,
+                                &task_diff,
+                                &mut edit_check_fix_turns,
+                            )
+                            .await
+                        {
+                            continue;
+                        }
+                    }
+                    if let Some(schema) = &turn_context.final_output_json_schema {
+                        match check_final_answer(schema, turn_last_agent_message.as_deref()) {
+                            Ok(json) => turn_last_agent_message = Some(json),
+                            Err(problem) if output_schema_retries < MAX_OUTPUT_SCHEMA_RETRIES => {
+                                output_schema_retries += 1;
+                                let retry: ResponseItem = DeveloperInstructions::new(format!(
+                                    "{problem}\nReply again with only a JSON value that validates against the output schema."
+                                ))
+                                .into();
+                                sess.record_conversation_items(&turn_context, &[retry])
+                                    .await;
+                                sess.notify_background_event(
+                                    &turn_context,
+                                    format!(
+                                        "Final answer did not match the output schema; asking again ({output_schema_retries}/{MAX_OUTPUT_SCHEMA_RETRIES})."
+                                    ),
+                                )
+                                .await;
+                                continue;
+                            }
+                            Err(problem) => {
+                                let event = EventMsg::Error(ErrorEvent {
+                                    message: format!(
+                                        "{problem}\nGave up after {MAX_OUTPUT_SCHEMA_RETRIES} retries."
+                                    ),
+                                    codex_error_info: None,
+                                });
+                                sess.send_event(&turn_context, event).await;
+                                break;
+                            }
+                        }
+                    }
                     last_agent_message = turn_last_agent_message;
-                    sess.notifier()
-                        .notify(&UserNotification::AgentTurnComplete {
+                    sess.notifier().notify(
+                        &UserNotification::AgentTurnComplete {
                             thread_id: sess.conversation_id.to_string(),
                             turn_id: turn_context.sub_id.clone(),
                             cwd: turn_context.cwd.display().to_string(),
                             input_messages: turn_input_messages,
                             last_assistant_message: last_agent_message.clone(),
-                        });
+                        },
+                        &HookSandbox::for_turn(&turn_context),
+                        &sess.hook_placeholders(&turn_context),
+                    );
                     break;
                 }
                 continue;
             }
+            Err(CodexErr::TurnAborted) if !cancellation_token.is_cancelled() => {
+                // Steered: restart with the guidance queued as pending input.
+                continue;
+            }
             Err(CodexErr::TurnAborted) => {
                 // Aborted turn is reported via a different event.
                 break;
@@ -2352,9 +3881,145 @@ pub(crate) async fn run_task(
         }
     }
 
+    // Only turns that changed files report the session diff.
+    let turn_changed_files = matches!(
+        turn_diff_tracker.lock().await.get_unified_diff(),
+        Ok(Some(_))
+    );
+    if turn_changed_files {
+        let session_diff = sess
+            .services
+            .session_diff_tracker
+            .lock()
+            .await
+            .get_unified_diff();
+        if let Ok(Some(unified_diff)) = session_diff {
+            sess.send_event(
+                &turn_context,
+                EventMsg::SessionDiff(SessionDiffEvent { unified_diff }),
+            )
+            .await;
+        }
+    }
+
     last_agent_message
 }
 
+/// Runs `[edit_checks]` on the files the task edited. Returns whether the
+/// failures were sent back to the model for a fix-up turn.
+async fn run_edit_checks(
+    sess: &Session,
+    turn_context: &TurnContext,
+    task_diff: &str,
+    fix_turns: &mut usize,
+) -> bool {
+    let config = turn_context.client.config();
+    let settings = &config.edit_checks;
+    let (refused, checks): (Vec<_>, Vec<_>) =
+        edit_checks::checks(settings, &turn_context.cwd, task_diff)
+            .into_iter()
+            .partition(|check| edit_checks::edited_by_task(check, task_diff));
+    if !refused.is_empty() {
+        let skipped = refused
+            .iter()
+            .map(|check| format!("`{}`", check.label()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let message = format!(
+            "Skipped {skipped}: the model edited the files these checks would run. Review the changes before running them yourself."
+        );
+        sess.send_event(turn_context, EventMsg::Warning(WarningEvent { message }))
+            .await;
+    }
+    if checks.is_empty() {
+        return false;
+    }
+    sess.notify_background_event(
+        turn_context,
+        format!("Running {} check(s) on the edited files…", checks.len()),
+    )
+    .await;
+    let failures = edit_checks::run(&checks, settings, &HookSandbox::for_turn(turn_context)).await;
+    if failures.is_empty() {
+        sess.notify_background_event(turn_context, "Checks passed.")
+            .await;
+        return false;
+    }
+
+    let failed = failures
+        .iter()
+        .map(|failure| format!("`{}`", failure.label))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let max_fix_turns = settings
+        .max_fix_turns
+        .unwrap_or(edit_checks::DEFAULT_MAX_FIX_TURNS);
+    if *fix_turns >= max_fix_turns {
+        let message =
+            format!("Checks still failing after {max_fix_turns} fix-up turn(s): {failed}");
+        sess.send_event(turn_context, EventMsg::Warning(WarningEvent { message }))
+            .await;
+        return false;
+    }
+    *fix_turns += 1;
+    let note: ResponseItem =
+        DeveloperInstructions::new(edit_checks::fix_up_instructions(&failures)).into();
+    sess.record_conversation_items(turn_context, &[note]).await;
+    sess.notify_background_event(
+        turn_context,
+        format!("{failed} failed; asking the model to fix it ({fix_turns}/{max_fix_turns})."),
+    )
+    .await;
+    true
+}
+
+/// Chunks of the workspace related to the user's message, when
+/// `semantic_search` is enabled with `embeddings.auto_retrieve`. Failures are
+/// reported as warnings and never block the turn; indexing that takes longer
+/// than a few seconds finishes in the background.
+async fn retrieve_semantic_context(
+    sess: &Session,
+    turn_context: &TurnContext,
+    input: &[UserInput],
+    cancellation_token: &CancellationToken,
+) -> Option<ResponseItem> {
+    if !sess.enabled(Feature::SemanticSearch) {
+        return None;
+    }
+    let config = turn_context.client.config();
+    let embeddings = config
+        .embeddings
+        .as_ref()
+        .filter(|embeddings| embeddings.auto_retrieve)?;
+    let query = input
+        .iter()
+        .filter_map(|item| match item {
+            UserInput::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if query.trim().is_empty() {
+        return None;
+    }
+    match semantic_index::retrieve_context(
+        &turn_context.cwd,
+        embeddings,
+        &query,
+        cancellation_token,
+    )
+    .await
+    {
+        Ok(item) => item,
+        Err(err) => {
+            let message = format!("Failed to retrieve workspace context: {err:#}");
+            sess.send_event(turn_context, EventMsg::Warning(WarningEvent { message }))
+                .await;
+            None
+        }
+    }
+}
+
 async fn run_auto_compact(sess: &Arc<Session>, turn_context: &Arc<TurnContext>) {
     if should_use_remote_compact_task(sess.as_ref(), &turn_context.client.get_provider()) {
         run_inline_remote_auto_compact_task(Arc::clone(sess), Arc::clone(turn_context)).await;
@@ -2407,6 +4072,7 @@ async fn run_turn(
         parallel_tool_calls: model_supports_parallel && sess.enabled(Feature::ParallelToolCalls),
         base_instructions_override: turn_context.base_instructions.clone(),
         output_schema: turn_context.final_output_json_schema.clone(),
+        purpose: RequestPurpose::Turn,
     };
 
     let mut retries = 0;
@@ -2535,6 +4201,14 @@ async fn try_run_turn(
         .instrument(trace_span!("stream_request"))
         .or_cancel(&cancellation_token)
         .await??;
+    let served_model = turn_context.client.served_model();
+    if served_model != turn_context.client.get_model() {
+        sess.notify_background_event(
+            &turn_context,
+            format!("model_routing: this turn is served by `{served_model}`."),
+        )
+        .await;
+    }
 
     let tool_runtime = ToolCallRuntime::new(
         Arc::clone(&router),
@@ -2547,6 +4221,9 @@ async fn try_run_turn(
     let mut needs_follow_up = false;
     let mut last_agent_message: Option<String> = None;
     let mut active_item: Option<TurnItem> = None;
+    // Text of the assistant message being streamed, kept in case a steer
+    // cuts it off before the item completes.
+    let mut partial_message = String::new();
     let mut should_emit_turn_diff = false;
     let receiving_span = trace_span!("receiving_stream");
     let outcome: CodexResult<TurnRunResult> = loop {
@@ -2565,7 +4242,16 @@ async fn try_run_turn(
             .await
         {
             Ok(event) => event,
-            Err(codex_async_utils::CancelErr::Cancelled) => break Err(CodexErr::TurnAborted),
+            Err(codex_async_utils::CancelErr::Cancelled) => {
+                if !partial_message.is_empty() && sess.was_steered().await {
+                    sess.record_conversation_items(
+                        &turn_context,
+                        &[truncated_agent_message(&partial_message)],
+                    )
+                    .await;
+                }
+                break Err(CodexErr::TurnAborted);
+            }
         };
 
         let event = match event {
@@ -2585,6 +4271,7 @@ async fn try_run_turn(
         match event {
             ResponseEvent::Created => {}
             ResponseEvent::OutputItemDone(item) => {
+                partial_message.clear();
                 let previously_active_item = active_item.take();
                 let mut ctx = HandleOutputCtx {
                     sess: sess.clone(),
@@ -2634,6 +4321,9 @@ async fn try_run_turn(
                 // In review child threads, suppress assistant text deltas; the
                 // UI will show a selection popup from the final ReviewOutput.
                 if let Some(active) = active_item.as_ref() {
+                    if matches!(active, TurnItem::AgentMessage(_)) {
+                        partial_message.push_str(&delta);
+                    }
                     let event = AgentMessageContentDeltaEvent {
                         thread_id: sess.conversation_id.to_string(),
                         turn_id: turn_context.sub_id.clone(),
@@ -2713,6 +4403,18 @@ async fn try_run_turn(
     outcome
 }
 
+/// The part of an assistant message streamed before a steer cancelled it,
+/// marked so the model knows it was cut off.
+fn truncated_agent_message(partial: &str) -> ResponseItem {
+    ResponseItem::Message {
+        id: None,
+        role: "assistant".to_string(),
+        content: vec![ContentItem::OutputText {
+            text: format!("{partial}\n\n{STEERED_RESPONSE_MARKER}"),
+        }],
+    }
+}
+
 pub(super) fn get_last_assistant_message_from_turn(responses: &[ResponseItem]) -> Option<String> {
     responses.iter().rev().find_map(|item| {
         if let ResponseItem::Message { role, content, .. } = item {
@@ -2840,6 +4542,7 @@ mod tests {
             compact_prompt: config.compact_prompt.clone(),
             approval_policy: config.approval_policy.clone(),
             sandbox_policy: config.sandbox_policy.clone(),
+            read_only: config.read_only_mode,
             cwd: config.cwd.clone(),
             original_config_do_not_use: Arc::clone(&config),
             exec_policy: Arc::new(RwLock::new(ExecPolicy::empty())),
@@ -2907,6 +4610,7 @@ mod tests {
             compact_prompt: config.compact_prompt.clone(),
             approval_policy: config.approval_policy.clone(),
             sandbox_policy: config.sandbox_policy.clone(),
+            read_only: config.read_only_mode,
             cwd: config.cwd.clone(),
             original_config_do_not_use: Arc::clone(&config),
             exec_policy: Arc::new(RwLock::new(ExecPolicy::empty())),
@@ -3114,6 +4818,7 @@ mod tests {
             compact_prompt: config.compact_prompt.clone(),
             approval_policy: config.approval_policy.clone(),
             sandbox_policy: config.sandbox_policy.clone(),
+            read_only: config.read_only_mode,
             cwd: config.cwd.clone(),
             original_config_do_not_use: Arc::clone(&config),
             exec_policy: Arc::new(RwLock::new(ExecPolicy::empty())),
@@ -3138,19 +4843,38 @@ mod tests {
             mcp_connection_manager: Arc::new(RwLock::new(McpConnectionManager::default())),
             mcp_startup_cancellation_token: CancellationToken::new(),
             unified_exec_manager: UnifiedExecSessionManager::default(),
-            notifier: UserNotifier::new(None),
+            background_shells: BackgroundShellManager::default(),
+            terminals: TerminalManager::default(),
+            shell_session: ShellSessionManager::default(),
+            python_kernel: PythonKernelManager::default(),
+            lsp: LspManager::default(),
+            last_test_run: Mutex::new(None),
+            scratch_dir: None,
+            web_cache: WebCache::default(),
+            notifier: UserNotifier::new(Vec::new()),
             rollout: Mutex::new(None),
             user_shell: Arc::new(default_user_shell()),
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            persist_reasoning: config.model_provider.persists_reasoning(),
             auth_manager: auth_manager.clone(),
             otel_manager: otel_manager.clone(),
             models_manager,
             tool_approvals: Mutex::new(ApprovalStore::default()),
             skills_manager,
+            session_diff_tracker: Arc::new(Mutex::new(TurnDiffTracker::new())),
+            external_edits: ExternalEditTracker::default(),
+            redactor: None,
+            approved_command_prefixes: RwLock::new(Vec::new()),
+            permissions: RwLock::new(PermissionStore::from_config(&config)),
+            tool_rules: ToolRules::default(),
+            tool_policy: None,
+            mutating_tool_calls: MutatingToolCalls::default(),
+            status_file: None,
         };
 
         let turn_context = Session::make_turn_context(
             Some(Arc::clone(&auth_manager)),
+            Arc::clone(&services.models_manager),
             &otel_manager,
             session_configuration.provider.clone(),
             &session_configuration,
@@ -3158,6 +4882,7 @@ mod tests {
             model_family,
             conversation_id,
             "turn_id".to_string(),
+            tx_event.clone(),
         );
 
         let session = Session {
@@ -3200,6 +4925,7 @@ mod tests {
             compact_prompt: config.compact_prompt.clone(),
             approval_policy: config.approval_policy.clone(),
             sandbox_policy: config.sandbox_policy.clone(),
+            read_only: config.read_only_mode,
             cwd: config.cwd.clone(),
             original_config_do_not_use: Arc::clone(&config),
             exec_policy: Arc::new(RwLock::new(ExecPolicy::empty())),
@@ -3224,19 +4950,38 @@ mod tests {
             mcp_connection_manager: Arc::new(RwLock::new(McpConnectionManager::default())),
             mcp_startup_cancellation_token: CancellationToken::new(),
             unified_exec_manager: UnifiedExecSessionManager::default(),
-            notifier: UserNotifier::new(None),
+            background_shells: BackgroundShellManager::default(),
+            terminals: TerminalManager::default(),
+            shell_session: ShellSessionManager::default(),
+            python_kernel: PythonKernelManager::default(),
+            lsp: LspManager::default(),
+            last_test_run: Mutex::new(None),
+            scratch_dir: None,
+            web_cache: WebCache::default(),
+            notifier: UserNotifier::new(Vec::new()),
             rollout: Mutex::new(None),
             user_shell: Arc::new(default_user_shell()),
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            persist_reasoning: config.model_provider.persists_reasoning(),
             auth_manager: Arc::clone(&auth_manager),
             otel_manager: otel_manager.clone(),
             models_manager,
             tool_approvals: Mutex::new(ApprovalStore::default()),
             skills_manager,
+            session_diff_tracker: Arc::new(Mutex::new(TurnDiffTracker::new())),
+            external_edits: ExternalEditTracker::default(),
+            redactor: None,
+            approved_command_prefixes: RwLock::new(Vec::new()),
+            permissions: RwLock::new(PermissionStore::from_config(&config)),
+            tool_rules: ToolRules::default(),
+            tool_policy: None,
+            mutating_tool_calls: MutatingToolCalls::default(),
+            status_file: None,
         };
 
         let turn_context = Arc::new(Session::make_turn_context(
             Some(Arc::clone(&auth_manager)),
+            Arc::clone(&services.models_manager),
             &otel_manager,
             session_configuration.provider.clone(),
             &session_configuration,
@@ -3244,6 +4989,7 @@ mod tests {
             model_family,
             conversation_id,
             "turn_id".to_string(),
+            tx_event.clone(),
         ));
 
         let session = Arc::new(Session {
@@ -1,3 +1,4 @@
+pub mod classifier;
 pub mod is_dangerous_command;
 pub mod is_safe_command;
 pub mod windows_safe_commands;
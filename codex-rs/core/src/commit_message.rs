@@ -0,0 +1,363 @@
+//! Commit messages for staged changes, shared by `codex commit` and the TUI's
+//! `/commit`.
+//!
+//! The staged diff is sent to the model in a single request, outside any
+//! conversation, with the `[commit] template` from config (or a Conventional
+//! Commits default) as the instructions.
+
+use std::io;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Arc;
+
+use codex_otel::otel_manager::OtelManager;
+use codex_protocol::ConversationId;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::SessionSource;
+use codex_utils_string::take_bytes_at_char_boundary;
+use futures::prelude::*;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::AuthManager;
+use crate::client::ModelClient;
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::compact::content_items_to_text;
+use crate::config::Config;
+use crate::error::CodexErr;
+use crate::error::Result as CodexResult;
+use crate::models_manager::manager::ModelsManager;
+use crate::terminal;
+
+/// Default instructions: a Conventional Commits message.
+pub const COMMIT_MESSAGE_PROMPT: &str = include_str!("../templates/commit/prompt.md");
+
+/// Diffs are cut to this size before they are sent; the file summary is
+/// always sent in full.
+const MAX_DIFF_BYTES: usize = 48 * 1024;
+
+const CONVENTIONAL_SUBJECT_MAX_CHARS: usize = 72;
+
+/// The changes in the index, as `git diff --cached` reports them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StagedChanges {
+    /// `git diff --cached --stat`.
+    pub stat: String,
+    pub diff: String,
+}
+
+/// Reads the staged changes of the repository containing `cwd`. Returns
+/// `None` when nothing is staged.
+pub async fn staged_changes(cwd: &Path) -> io::Result<Option<StagedChanges>> {
+    let stat = git(
+        cwd,
+        &["diff", "--cached", "--no-color", "--no-ext-diff", "--stat"],
+    )
+    .await?;
+    if stat.trim().is_empty() {
+        return Ok(None);
+    }
+    let diff = git(cwd, &["diff", "--cached", "--no-color", "--no-ext-diff"]).await?;
+    Ok(Some(StagedChanges { stat, diff }))
+}
+
+/// The user message sent along with the instructions.
+pub fn commit_message_input(changes: &StagedChanges) -> String {
+    let diff = if changes.diff.len() > MAX_DIFF_BYTES {
+        format!(
+            "{}\n[The diff was truncated; the file summary above lists every change.]",
+            take_bytes_at_char_boundary(&changes.diff, MAX_DIFF_BYTES).trim_end()
+        )
+    } else {
+        changes.diff.clone()
+    };
+    format!(
+        "Files changed:\n{}\n\nDiff:\n{}",
+        changes.stat.trim_end(),
+        diff.trim_end()
+    )
+}
+
+/// Asks the configured model for a commit message for `changes`. The reply is
+/// cleaned up with [`clean_commit_message`].
+pub async fn generate_commit_message(
+    config: &Config,
+    auth_manager: Arc<AuthManager>,
+    changes: &StagedChanges,
+) -> CodexResult<String> {
+    let models_manager = Arc::new(ModelsManager::new(Arc::clone(&auth_manager)));
+    let model = models_manager.get_model(&config.model, config).await;
+    let model_family = models_manager.construct_model_family(&model, config).await;
+    let conversation_id = ConversationId::new();
+    let auth = auth_manager.auth();
+    let otel_manager = OtelManager::new(
+        conversation_id,
+        model.as_str(),
+        model_family.get_model_slug(),
+        auth.as_ref().and_then(|auth| auth.get_account_id()),
+        auth.as_ref().and_then(|auth| auth.get_account_email()),
+        auth.as_ref().map(|auth| auth.mode),
+        config.otel.log_user_prompt,
+        terminal::user_agent(),
+        SessionSource::Cli,
+    );
+    let client = ModelClient::new(
+        Arc::new(config.clone()),
+        Some(auth_manager),
+        model_family,
+        otel_manager,
+        config.model_provider.clone(),
+        config.model_reasoning_effort,
+        config.model_reasoning_summary,
+        conversation_id,
+        SessionSource::Cli,
+    )
+    .with_models_manager(models_manager);
+
+    let prompt = Prompt {
+        input: vec![ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: commit_message_input(changes),
+            }],
+        }],
+        base_instructions_override: Some(
+            config
+                .commit
+                .template
+                .clone()
+                .unwrap_or_else(|| COMMIT_MESSAGE_PROMPT.to_string()),
+        ),
+        ..Default::default()
+    };
+
+    let mut stream = client.stream(&prompt).await?;
+    let mut reply = String::new();
+    loop {
+        match stream.next().await {
+            Some(Ok(ResponseEvent::OutputItemDone(ResponseItem::Message {
+                role,
+                content,
+                ..
+            }))) if role == "assistant" => {
+                if let Some(text) = content_items_to_text(&content) {
+                    reply.push_str(&text);
+                }
+            }
+            Some(Ok(ResponseEvent::Completed { .. })) => break,
+            Some(Ok(_)) => {}
+            Some(Err(err)) => return Err(err),
+            None => {
+                return Err(CodexErr::Stream(
+                    "stream closed before response.completed".into(),
+                    None,
+                ));
+            }
+        }
+    }
+
+    let message = clean_commit_message(&reply);
+    if message.is_empty() {
+        return Err(CodexErr::Stream(
+            "the model returned an empty commit message".into(),
+            None,
+        ));
+    }
+    Ok(message)
+}
+
+/// Trims the reply and drops a surrounding code fence.
+pub fn clean_commit_message(reply: &str) -> String {
+    let mut text = reply.trim();
+    if let Some(rest) = text.strip_prefix("```") {
+        // Skip the info string, e.g. ```text.
+        let rest = rest.split_once('\n').map_or("", |(_, rest)| rest);
+        text = rest.trim_end().strip_suffix("```").unwrap_or(rest).trim();
+    }
+    text.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns why `message` is not a Conventional Commits message, or `None`.
+pub fn conventional_commit_problem(message: &str) -> Option<String> {
+    let mut lines = message.lines();
+    let subject = lines.next().unwrap_or_default();
+    let Some((prefix, description)) = subject.split_once(": ") else {
+        return Some("the subject line does not start with `<type>: `".to_string());
+    };
+    let prefix = prefix.strip_suffix('!').unwrap_or(prefix);
+    let commit_type = match prefix.split_once('(') {
+        Some((commit_type, scope)) => {
+            let Some(scope) = scope.strip_suffix(')') else {
+                return Some(format!("the scope in `{prefix}` is not closed"));
+            };
+            if scope.is_empty() || scope.contains(char::is_whitespace) {
+                return Some(format!("`{prefix}` has an invalid scope"));
+            }
+            commit_type
+        }
+        None => prefix,
+    };
+    if commit_type.is_empty() || !commit_type.chars().all(|c| c.is_ascii_lowercase()) {
+        return Some(format!("`{commit_type}` is not a commit type"));
+    }
+    if description.trim().is_empty() {
+        return Some("the subject line has no description".to_string());
+    }
+    let length = subject.chars().count();
+    if length > CONVENTIONAL_SUBJECT_MAX_CHARS {
+        return Some(format!(
+            "the subject line is {length} characters long; keep it to {CONVENTIONAL_SUBJECT_MAX_CHARS}"
+        ));
+    }
+    if lines.next().is_some_and(|line| !line.trim().is_empty()) {
+        return Some("the subject line is not followed by a blank line".to_string());
+    }
+    None
+}
+
+/// Commits the staged changes with `message`, running the repository's
+/// hooks. Returns git's one-line summary of the new commit.
+pub async fn commit_staged(cwd: &Path, message: &str) -> io::Result<String> {
+    let mut child = Command::new("git")
+        .args(["commit", "--file", "-"])
+        .current_dir(cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(message.as_bytes()).await?;
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        return Err(io::Error::other(git_failure(&output)));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string())
+}
+
+async fn git(cwd: &Path, args: &[&str]) -> io::Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(io::Error::other(git_failure(&output)));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn git_failure(output: &std::process::Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let detail = if stderr.trim().is_empty() {
+        stdout.trim()
+    } else {
+        stderr.trim()
+    };
+    if detail.is_empty() {
+        format!("git exited with {}", output.status)
+    } else {
+        detail.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn replies_lose_code_fences_and_trailing_whitespace() {
+        assert_eq!(
+            clean_commit_message("```text\nfix(parser): handle empty input  \n\nDetails.\n```\n"),
+            "fix(parser): handle empty input\n\nDetails."
+        );
+        assert_eq!(
+            clean_commit_message("  feat: add --bundle\n"),
+            "feat: add --bundle"
+        );
+    }
+
+    #[test]
+    fn conventional_commit_subjects_are_checked() {
+        for ok in [
+            "feat: add bundles",
+            "fix(exec)!: exit with the signal's status\n\nBREAKING CHANGE: scripts see 130.",
+            "docs(config): document [commit]",
+        ] {
+            assert_eq!(conventional_commit_problem(ok), None, "{ok}");
+        }
+        for (message, problem) in [
+            (
+                "Add bundles",
+                "the subject line does not start with `<type>: `",
+            ),
+            ("Feat: add bundles", "`Feat` is not a commit type"),
+            ("feat(exec: add", "the scope in `feat(exec` is not closed"),
+            (
+                "feat: add\nbody without a blank line",
+                "the subject line is not followed by a blank line",
+            ),
+        ] {
+            assert_eq!(
+                conventional_commit_problem(message).as_deref(),
+                Some(problem),
+                "{message}"
+            );
+        }
+    }
+
+    #[test]
+    fn large_diffs_are_truncated_in_the_input() {
+        let changes = StagedChanges {
+            stat: " big.txt | 9000 ++++\n".to_string(),
+            diff: "+line\n".repeat(MAX_DIFF_BYTES),
+        };
+        let input = commit_message_input(&changes);
+        assert!(input.starts_with("Files changed:\n big.txt | 9000 ++++\n\nDiff:\n+line\n"));
+        assert!(input.len() < MAX_DIFF_BYTES + 1024);
+        assert!(input.ends_with("the file summary above lists every change.]"));
+    }
+
+    #[tokio::test]
+    async fn staged_changes_are_read_and_committed() {
+        let dir = TempDir::new().expect("tempdir");
+        let repo = dir.path();
+        for args in [
+            vec!["init", "--quiet"],
+            vec!["config", "user.email", "test@example.com"],
+            vec!["config", "user.name", "Test"],
+            vec!["config", "commit.gpgsign", "false"],
+        ] {
+            git(repo, &args).await.expect("git setup");
+        }
+        assert_eq!(staged_changes(repo).await.expect("staged changes"), None);
+
+        std::fs::write(repo.join("notes.txt"), "hello\n").expect("write file");
+        git(repo, &["add", "notes.txt"]).await.expect("git add");
+        let changes = staged_changes(repo)
+            .await
+            .expect("staged changes")
+            .expect("something is staged");
+        assert!(changes.stat.contains("notes.txt"), "{changes:?}");
+        assert!(changes.diff.contains("+hello"), "{changes:?}");
+
+        let summary = commit_staged(repo, "docs: add notes\n")
+            .await
+            .expect("commit");
+        assert!(summary.contains("docs: add notes"), "{summary}");
+        assert_eq!(staged_changes(repo).await.expect("staged changes"), None);
+    }
+}
@@ -1,7 +1,9 @@
 use crate::config::CONFIG_TOML_FILE;
+use crate::config::types::HookConfig;
 use crate::config::types::McpServerConfig;
 use crate::config::types::Notice;
 use anyhow::Context;
+use codex_protocol::approvals::ScopedPermission;
 use codex_protocol::config_types::TrustLevel;
 use codex_protocol::openai_models::ReasoningEffort;
 use std::collections::BTreeMap;
@@ -9,6 +11,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use tempfile::NamedTempFile;
 use tokio::task;
+use toml_edit::ArrayOfTables;
 use toml_edit::DocumentMut;
 use toml_edit::Item as TomlItem;
 use toml_edit::Table as TomlTable;
@@ -39,6 +42,14 @@ pub enum ConfigEdit {
     /// Set trust_level under `[projects."<path>"]`,
     /// migrating inline tables to explicit tables.
     SetProjectTrustLevel { path: PathBuf, level: TrustLevel },
+    /// Append to approved_command_prefixes under `[projects."<path>"]`.
+    AddProjectCommandPrefix { path: PathBuf, prefix: String },
+    /// Append to allowed_writes or allowed_network_hosts under
+    /// `[projects."<path>"]`.
+    AddProjectPermission {
+        path: PathBuf,
+        permission: ScopedPermission,
+    },
     /// Set the value stored at the exact dotted path.
     SetPath {
         segments: Vec<String>,
@@ -46,10 +57,13 @@ pub enum ConfigEdit {
     },
     /// Remove the value stored at the exact dotted path.
     ClearPath { segments: Vec<String> },
+    /// Append entries to the `[[hooks]]` array of tables.
+    AppendHooks(Vec<HookConfig>),
 }
 
 // TODO(jif) move to a dedicated file
 mod document_helpers {
+    use crate::config::types::HookConfig;
     use crate::config::types::McpServerConfig;
     use crate::config::types::McpServerTransportConfig;
     use toml_edit::Array as TomlArray;
@@ -172,6 +186,32 @@ mod document_helpers {
         serialize_mcp_server_table(config).into_inline_table()
     }
 
+    pub(super) fn serialize_hook(hook: &HookConfig) -> TomlTable {
+        let mut entry = TomlTable::new();
+        entry.set_implicit(false);
+        entry["events"] =
+            array_from_iter(hook.events.iter().map(|event| event.as_str().to_string()));
+        if let Some(matcher) = &hook.matcher {
+            entry["matcher"] = value(matcher.clone());
+        }
+        if !hook.command.is_empty() {
+            entry["command"] = array_from_iter(hook.command.iter().cloned());
+        }
+        if let Some(definition) = &hook.definition {
+            entry["definition"] = value(definition.clone());
+        }
+        if let Some(sandbox) = hook.sandbox {
+            entry["sandbox"] = value(sandbox);
+        }
+        if let Some(timeout_ms) = hook.timeout_ms {
+            entry["timeout_ms"] = value(i64::try_from(timeout_ms).unwrap_or(i64::MAX));
+        }
+        if !hook.env.is_empty() {
+            entry["env"] = table_from_pairs(hook.env.iter());
+        }
+        entry
+    }
+
     pub(super) fn merge_inline_table(existing: &mut InlineTable, replacement: InlineTable) {
         existing.retain(|key, _| replacement.get(key).is_some());
 
@@ -300,6 +340,7 @@ impl ConfigDocument {
             ConfigEdit::ReplaceMcpServers(servers) => Ok(self.replace_mcp_servers(servers)),
             ConfigEdit::SetPath { segments, value } => Ok(self.insert(segments, value.clone())),
             ConfigEdit::ClearPath { segments } => Ok(self.clear_owned(segments)),
+            ConfigEdit::AppendHooks(hooks) => self.append_hooks(hooks),
             ConfigEdit::SetProjectTrustLevel { path, level } => {
                 // Delegate to the existing, tested logic in config.rs to
                 // ensure tables are explicit and migration is preserved.
@@ -310,6 +351,20 @@ impl ConfigDocument {
                 )?;
                 Ok(true)
             }
+            ConfigEdit::AddProjectCommandPrefix { path, prefix } => {
+                crate::config::add_project_command_prefix_inner(
+                    &mut self.doc,
+                    path.as_path(),
+                    prefix,
+                )
+            }
+            ConfigEdit::AddProjectPermission { path, permission } => {
+                crate::config::add_project_permission_inner(
+                    &mut self.doc,
+                    path.as_path(),
+                    permission,
+                )
+            }
         }
     }
 
@@ -334,6 +389,23 @@ impl ConfigDocument {
         self.remove(segments)
     }
 
+    fn append_hooks(&mut self, hooks: &[HookConfig]) -> anyhow::Result<bool> {
+        if hooks.is_empty() {
+            return Ok(false);
+        }
+        let root = self.doc.as_table_mut();
+        let item = root
+            .entry("hooks")
+            .or_insert_with(|| TomlItem::ArrayOfTables(ArrayOfTables::new()));
+        let Some(entries) = item.as_array_of_tables_mut() else {
+            anyhow::bail!("`hooks` in config.toml must be written as [[hooks]] tables");
+        };
+        for hook in hooks {
+            entries.push(document_helpers::serialize_hook(hook));
+        }
+        Ok(true)
+    }
+
     fn replace_mcp_servers(&mut self, servers: &BTreeMap<String, McpServerConfig>) -> bool {
         if servers.is_empty() {
             return self.clear(Scope::Global, &["mcp_servers"]);
@@ -655,6 +727,35 @@ impl ConfigEditsBuilder {
         self
     }
 
+    pub fn add_project_command_prefix<P: Into<PathBuf>>(
+        mut self,
+        project_path: P,
+        prefix: &str,
+    ) -> Self {
+        self.edits.push(ConfigEdit::AddProjectCommandPrefix {
+            path: project_path.into(),
+            prefix: prefix.to_string(),
+        });
+        self
+    }
+
+    pub fn add_project_permission<P: Into<PathBuf>>(
+        mut self,
+        project_path: P,
+        permission: &ScopedPermission,
+    ) -> Self {
+        self.edits.push(ConfigEdit::AddProjectPermission {
+            path: project_path.into(),
+            permission: permission.clone(),
+        });
+        self
+    }
+
+    pub fn append_hooks(mut self, hooks: &[HookConfig]) -> Self {
+        self.edits.push(ConfigEdit::AppendHooks(hooks.to_vec()));
+        self
+    }
+
     /// Enable or disable a feature flag by key under the `[features]` table.
     pub fn set_feature_enabled(mut self, key: &str, enabled: bool) -> Self {
         self.edits.push(ConfigEdit::SetPath {
@@ -690,6 +791,7 @@ impl ConfigEditsBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::types::HookEvent;
     use crate::config::types::McpServerTransportConfig;
     use codex_protocol::openai_models::ReasoningEffort;
     use pretty_assertions::assert_eq;
@@ -737,6 +839,66 @@ model_reasoning_effort = "high"
         assert_eq!(contents, "enabled = true\n");
     }
 
+    #[test]
+    fn project_command_prefixes_are_appended_once() {
+        let tmp = tempdir().expect("tmpdir");
+        let codex_home = tmp.path();
+        std::fs::write(
+            codex_home.join(CONFIG_TOML_FILE),
+            r#"[projects."/repo"]
+trust_level = "trusted"
+"#,
+        )
+        .expect("seed config");
+
+        for prefix in ["npm test", "cargo test", "npm test"] {
+            ConfigEditsBuilder::new(codex_home)
+                .add_project_command_prefix("/repo", prefix)
+                .apply_blocking()
+                .expect("persist");
+        }
+
+        let contents =
+            std::fs::read_to_string(codex_home.join(CONFIG_TOML_FILE)).expect("read config");
+        let expected = r#"[projects."/repo"]
+trust_level = "trusted"
+approved_command_prefixes = ["npm test", "cargo test"]
+"#;
+        assert_eq!(contents, expected);
+    }
+
+    #[test]
+    fn project_permissions_are_appended_once() {
+        let tmp = tempdir().expect("tmpdir");
+        let codex_home = tmp.path();
+        let permissions = [
+            ScopedPermission::Write {
+                pattern: "src/**".to_string(),
+            },
+            ScopedPermission::Network {
+                host: "api.github.com".to_string(),
+            },
+            ScopedPermission::Write {
+                pattern: "src/**".to_string(),
+            },
+        ];
+
+        for permission in &permissions {
+            ConfigEditsBuilder::new(codex_home)
+                .add_project_permission("/repo", permission)
+                .apply_blocking()
+                .expect("persist");
+        }
+
+        let contents =
+            std::fs::read_to_string(codex_home.join(CONFIG_TOML_FILE)).expect("read config");
+        let expected = r#"[projects."/repo"]
+allowed_writes = ["src/**"]
+allowed_network_hosts = ["api.github.com"]
+"#;
+        assert_eq!(contents, expected);
+    }
+
     #[test]
     fn blocking_set_model_preserves_inline_table_contents() {
         let tmp = tempdir().expect("tmpdir");
@@ -1475,6 +1637,51 @@ model_reasoning_effort = "high"
         assert_eq!(notice, Some(true));
     }
 
+    #[test]
+    fn append_hooks_adds_entries_after_existing_ones() {
+        let tmp = tempdir().expect("tmpdir");
+        let codex_home = tmp.path();
+        std::fs::write(
+            codex_home.join(CONFIG_TOML_FILE),
+            r#"model = "gpt-5"
+
+[[hooks]]
+events = ["SessionEnd"]
+command = ["cleanup"]
+"#,
+        )
+        .expect("seed");
+
+        apply_blocking(
+            codex_home,
+            None,
+            &[ConfigEdit::AppendHooks(vec![HookConfig {
+                events: vec![HookEvent::AfterToolUse],
+                matcher: Some("^apply_patch$".to_string()),
+                command: vec!["sh".to_string(), "-c".to_string(), "fmt".to_string()],
+                timeout_ms: Some(5_000),
+                ..Default::default()
+            }])],
+        )
+        .expect("persist");
+
+        let contents =
+            std::fs::read_to_string(codex_home.join(CONFIG_TOML_FILE)).expect("read config");
+        let expected = r#"model = "gpt-5"
+
+[[hooks]]
+events = ["SessionEnd"]
+command = ["cleanup"]
+
+[[hooks]]
+events = ["AfterToolUse"]
+matcher = "^apply_patch$"
+command = ["sh", "-c", "fmt"]
+timeout_ms = 5000
+"#;
+        assert_eq!(contents, expected);
+    }
+
     #[test]
     fn replace_mcp_servers_blocking_clears_table_when_empty() {
         let tmp = tempdir().expect("tmpdir");
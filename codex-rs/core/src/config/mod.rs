@@ -1,25 +1,58 @@
 use crate::auth::AuthCredentialsStoreMode;
+use crate::config::types::AppServerMetricsConfig;
+use crate::config::types::AppServerStatusConfig;
+use crate::config::types::ApplyPatchVerify;
+use crate::config::types::Budget;
+use crate::config::types::CommandSafetyConfig;
+use crate::config::types::CommitConfig;
 use crate::config::types::DEFAULT_OTEL_ENVIRONMENT;
+use crate::config::types::EditChecksConfig;
+use crate::config::types::EmbeddingsConfig;
+use crate::config::types::FetchUrlConfig;
+use crate::config::types::GeneratedFilesConfig;
 use crate::config::types::History;
+use crate::config::types::HookConfig;
+use crate::config::types::HookDefinition;
+use crate::config::types::LspServerConfig;
 use crate::config::types::McpServerConfig;
+use crate::config::types::ModelCassette;
+use crate::config::types::ModelPricing;
+use crate::config::types::ModelRouting;
 use crate::config::types::Notice;
+use crate::config::types::NotificationBackend;
 use crate::config::types::Notifications;
 use crate::config::types::OtelConfig;
 use crate::config::types::OtelConfigToml;
 use crate::config::types::OtelExporterKind;
+use crate::config::types::PullRequestsConfig;
+use crate::config::types::RedactionConfig;
+use crate::config::types::ReviewEditsMode;
 use crate::config::types::SandboxWorkspaceWrite;
+use crate::config::types::ScratchDir;
 use crate::config::types::ScrollInputMode;
+use crate::config::types::SecretScanConfig;
+use crate::config::types::SessionRetentionConfig;
 use crate::config::types::ShellEnvironmentPolicy;
 use crate::config::types::ShellEnvironmentPolicyToml;
+use crate::config::types::ShellPty;
+use crate::config::types::StorageEncryptionConfig;
+use crate::config::types::ToolPolicyConfig;
+use crate::config::types::ToolRuleConfig;
 use crate::config::types::Tui;
+use crate::config::types::TuiTheme;
+use crate::config::types::TuiVoice;
 use crate::config::types::UriBasedFileOpener;
+use crate::config::types::WebSearchProviderConfig;
 use crate::config_loader::ConfigRequirements;
 use crate::config_loader::LoaderOverrides;
 use crate::config_loader::load_config_layers_state;
+use crate::context_pressure::DEFAULT_CONTEXT_PRESSURE_THRESHOLDS;
 use crate::features::Feature;
 use crate::features::FeatureOverrides;
 use crate::features::Features;
 use crate::features::FeaturesToml;
+use crate::flags::CODEX_RS_RECORD_CASSETTE;
+use crate::flags::CODEX_RS_REPLAY_CASSETTE;
 use crate::git_info::resolve_root_git_project_for_trust;
 use crate::model_provider_info::LMSTUDIO_OSS_PROVIDER_ID;
 use crate::model_provider_info::ModelProviderInfo;
@@ -29,8 +62,13 @@ use crate::project_doc::DEFAULT_PROJECT_DOC_FILENAME;
 use crate::project_doc::LOCAL_PROJECT_DOC_FILENAME;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
+use crate::tools::loop_detection::DEFAULT_TOOL_CALL_LOOP_LIMIT;
+use crate::usage_ledger::built_in_model_pricing;
+use codex_api::CassetteMode;
 use codex_app_server_protocol::Tools;
 use codex_app_server_protocol::UserSavedConfig;
+use codex_client::HttpClientOptions;
+use codex_protocol::approvals::ScopedPermission;
 use codex_protocol::config_types::ForcedLoginMethod;
 use codex_protocol::config_types::ReasoningSummary;
 use codex_protocol::config_types::SandboxMode;
@@ -49,6 +87,7 @@ use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 #[cfg(test)]
 use tempfile::tempdir;
 
@@ -76,6 +115,14 @@ pub use codex_git::GhostSnapshotConfig;
 /// files are *silently truncated* to this size so we do not take up too much of
 /// the context window.
 pub(crate) const PROJECT_DOC_MAX_BYTES: usize = 32 * 1024; // 32 KiB
+pub(crate) const INSTRUCTIONS_MAX_TOKENS: usize = 16 * 1024;
+
+/// Default size of the repository map injected under the `repo_map` feature.
+pub(crate) const REPO_MAP_MAX_TOKENS: usize = 2 * 1024;
+
+fn default_git_protected_branches() -> Vec<String> {
+    vec!["main".to_string(), "master".to_string()]
+}
 
 pub const CONFIG_TOML_FILE: &str = "config.toml";
 
@@ -105,6 +152,10 @@ pub struct Config {
     /// Token usage threshold triggering auto-compaction of conversation history.
     pub model_auto_compact_token_limit: Option<i64>,
 
+    /// Maximum number of tokens the model may generate per response. Only
+    /// sent to providers whose wire API requires it (Anthropic).
+    pub model_max_output_tokens: Option<i64>,
+
     /// Key into the model_providers map that specifies which provider to use.
     pub model_provider_id: String,
 
@@ -169,16 +220,66 @@ pub struct Config {
     /// If unset the feature is disabled.
     pub notify: Option<Vec<String>>,
 
+    /// Optional program consulted before an approval request is shown to the
+    /// user. Like `notify`, Codex appends one argument holding a JSON payload
+    /// describing the request; the program answers on stdout with `approve`
+    /// or `deny`, and any other outcome passes the request to the user.
+    pub pre_approval_hook: Option<Vec<String>>,
+
+    /// Hooks from `[[hooks]]`, each subscribed to one or more events. They run
+    /// after the single-purpose `notify`, `pre_approval_hook` and
+    /// `budget.on_exceeded` programs.
+    pub hooks: Vec<HookConfig>,
+
+    /// Run hook programs under the session's sandbox policy, like tool
+    /// calls. `[[hooks]]` entries can override this with `sandbox`.
+    pub sandbox_hooks: bool,
+
+    /// Total time the hooks for one tool call may take before the remaining
+    /// hooks are skipped. `None` leaves only the per-hook timeouts.
+    pub hooks_max_total_latency_ms: Option<u64>,
+
+    /// Rules from `[[tool_rules]]`, checked in-process before each tool
+    /// call and before any hook.
+    pub tool_rules: Vec<ToolRuleConfig>,
+
+    /// `[tool_policy]`: an external engine whose decisions combine with
+    /// `tool_rules`, the strictest winning.
+    pub tool_policy: Option<ToolPolicyConfig>,
+
+    /// How long shutdown waits for in-flight file-writing tool calls before
+    /// killing them. `None` uses the default of 10 seconds.
+    pub shutdown_drain_timeout_ms: Option<u64>,
+
+    /// Stop the session's MCP servers and pause its background processes
+    /// after this long without input; they resume on the next submission.
+    /// `None` never suspends.
+    pub idle_suspend_after_ms: Option<u64>,
+
+    /// Mirror the `update_plan` state into `TODO.codex.md` in the working
+    /// directory and read it back when a session resumes.
+    pub sync_plan_file: bool,
+
     /// TUI notifications preference. When set, the TUI will send OSC 9 notifications on approvals
     /// and turn completions when not focused.
     pub tui_notifications: Notifications,
 
+    /// How the TUI delivers desktop notifications.
+    pub tui_notification_backend: NotificationBackend,
+
+    /// Minimum duration of a turn before its completion triggers a TUI
+    /// notification.
+    pub tui_notification_min_turn_secs: u64,
+
     /// Enable ASCII animations and shimmer effects in the TUI.
     pub animations: bool,
 
     /// Show startup tooltips in the TUI welcome screen.
     pub show_tooltips: bool,
 
+    /// Collapse reasoning summaries to their header in the TUI.
+    pub collapse_reasoning: bool,
+
     /// Override the events-per-wheel-tick factor for TUI2 scroll normalization.
     ///
     /// This is the same `tui.scroll_events_per_tick` value from `config.toml`, plumbed through the
@@ -231,6 +332,18 @@ pub struct Config {
     /// consistently to both mouse wheels and trackpads.
     pub tui_scroll_invert: bool,
 
+    /// Color theme name from `tui.theme` (see [`Tui`]).
+    pub tui_theme: Option<String>,
+
+    /// Custom themes from `[tui.themes]`.
+    pub tui_themes: BTreeMap<String, TuiTheme>,
+
+    /// Remapped shortcuts from `[tui.keybindings]`.
+    pub tui_keybindings: BTreeMap<String, String>,
+
+    /// Voice input from `[tui.voice]`; `None` disables voice input.
+    pub tui_voice: Option<TuiVoice>,
+
     /// The directory that should be treated as the current working directory
     /// for the session. All relative paths inside the business-logic layer are
     /// resolved against this path.
@@ -257,9 +370,33 @@ pub struct Config {
     /// Combined provider map (defaults merged with user-defined overrides).
     pub model_providers: HashMap<String, ModelProviderInfo>,
 
+    /// Per-model pricing used for cost accounting (built-in defaults merged
+    /// with user-defined entries, which take precedence).
+    pub model_pricing: HashMap<String, ModelPricing>,
+
+    /// Routing rules and provider/model fallbacks for model requests.
+    pub model_routing: ModelRouting,
+
+    /// Whether to ask providers to cache the stable prompt prefix: a
+    /// `prompt_cache_key` for the Responses API and `cache_control`
+    /// breakpoints for Anthropic. Defaults to true.
+    pub prompt_caching: bool,
+
+    /// Cassette that model requests are recorded to or replayed from, for
+    /// offline tests and reproducible bug reports. Not read from config.toml.
+    pub model_cassette: Option<ModelCassette>,
+
     /// Maximum number of bytes to include from an AGENTS.md project doc file.
     pub project_doc_max_bytes: usize,
 
+    /// Cap on the combined user instructions (global and project AGENTS.md,
+    /// skills, memories), in approximate tokens.
+    pub instructions_max_tokens: usize,
+
+    /// Size of the repository map injected at session start under the
+    /// `repo_map` feature, in approximate tokens.
+    pub repo_map_max_tokens: usize,
+
     /// Additional filenames to try when looking for project-level docs.
     pub project_doc_fallback_filenames: Vec<String>,
 
@@ -274,6 +411,106 @@ pub struct Config {
     /// Settings that govern if and what will be written to `~/.codex/history.jsonl`.
     pub history: History,
 
+    /// Token, cost, turn, and wall-clock limits for the agent.
+    pub budget: Budget,
+
+    /// Cancels a task that runs longer than this, even in the middle of a
+    /// model request or tool call. `None` disables the watchdog.
+    pub turn_timeout: Option<Duration>,
+
+    /// Identical tool calls in a row (or round trips between two calls)
+    /// after which further repeats are answered with a loop warning instead
+    /// of being run. 0 disables the check.
+    pub tool_call_loop_limit: usize,
+
+    /// Percentages of the context window at which a `ContextPressure` event
+    /// is emitted, in ascending order. Empty disables the events.
+    pub context_pressure_thresholds: Vec<u8>,
+
+    /// Terminal size for shell commands run under the `shell_pty` feature.
+    pub shell_pty: ShellPty,
+
+    /// Check run after each successful `apply_patch`; its result is appended
+    /// to the tool output.
+    pub apply_patch_verify: Option<ApplyPatchVerify>,
+
+    /// Language servers asked for diagnostics after each `apply_patch`,
+    /// keyed by name.
+    pub lsp: BTreeMap<String, LspServerConfig>,
+
+    /// Limit the model to the project's own tasks: shell tools are removed
+    /// and `run_task` takes no extra arguments.
+    pub tasks_only: bool,
+
+    /// Start sessions in read-only "ask" mode: mutating tools are removed
+    /// and commands run under a read-only sandbox until the mode is turned
+    /// off with `Op::SetReadOnlyMode`.
+    pub read_only_mode: bool,
+
+    /// Cleanup of the per-session directory made by the `scratch_dir`
+    /// feature.
+    pub scratch_dir: ScratchDir,
+
+    /// Branches the git tools refuse to commit on or create.
+    pub git_protected_branches: Vec<String>,
+
+    /// Provider selection and credentials for the `pull_requests` tools.
+    pub pull_requests: PullRequestsConfig,
+
+    /// Search API for the `web_search` function tool.
+    pub web_search_provider: Option<WebSearchProviderConfig>,
+
+    /// Limits for the `fetch_url` tool.
+    pub fetch_url: FetchUrlConfig,
+
+    /// Embedding provider for the workspace index behind `semantic_search`.
+    pub embeddings: Option<EmbeddingsConfig>,
+
+    /// Prometheus `/metrics` endpoint for `codex app-server`.
+    pub app_server_metrics: AppServerMetricsConfig,
+
+    /// Status socket served by `codex app-server`.
+    pub app_server_status: AppServerStatusConfig,
+
+    /// Hold every `apply_patch` edit for approval so the user can accept or
+    /// reject it hunk by hunk. Resolved from `review_edits` and the project's
+    /// trust level.
+    pub review_edits: bool,
+
+    /// Secret masking applied to user messages and tool output before they
+    /// reach the history, the rollout or the model.
+    pub redaction: RedactionConfig,
+
+    /// Built-in check for secrets in file writes.
+    pub secret_scan: SecretScanConfig,
+
+    /// Built-in check for file writes to generated files.
+    pub generated_files: GeneratedFilesConfig,
+
+    /// Proxy and CA settings for outbound HTTP, applied process-wide when a
+    /// session starts.
+    pub http: HttpClientOptions,
+
+    /// Encryption at rest for `history.jsonl` and session rollouts.
+    pub storage_encryption: StorageEncryptionConfig,
+
+    /// Limits on the sessions kept under the Codex home.
+    pub session_retention: SessionRetentionConfig,
+
+    /// Checks run after the model edits files; see `[edit_checks]`.
+    pub edit_checks: EditChecksConfig,
+
+    /// Settings for `codex commit` and `/commit`.
+    pub commit: CommitConfig,
+
+    /// Built-in classification of risky shell commands, applied before
+    /// execpolicy rules.
+    pub command_safety: CommandSafetyConfig,
+
+    /// When set, only tools with these names are exposed to the model. Not
+    /// read from `config.toml`; used to restrict delegated sub-agents.
+    pub allowed_tools: Option<Vec<String>>,
+
     /// Optional URI-based file opener. If set, citations to files in the model
     /// output will be hyperlinked using the specified URI scheme.
     pub file_opener: UriBasedFileOpener,
@@ -312,6 +549,10 @@ pub struct Config {
     /// When set, restricts the login mechanism users may use.
     pub forced_login_method: Option<ForcedLoginMethod>,
 
+    /// Named login from the profile or `account`, when set; see
+    /// [`crate::auth::accounts`].
+    pub account: Option<String>,
+
     /// Include the `apply_patch` tool for models that benefit from invoking
     /// file edits as a structured tool call. When unset, this falls back to the
     /// model family's default preference.
@@ -335,6 +576,11 @@ pub struct Config {
     /// is (1) part of a git repo, (2) a git worktree, or (3) just using the cwd
     pub active_project: ProjectConfig,
 
+    /// Command prefixes, split into words, that run without an approval
+    /// prompt: the top-level `approved_command_prefixes` followed by the
+    /// active project's.
+    pub approved_command_prefixes: Vec<Vec<String>>,
+
     /// Tracks whether the Windows onboarding screen has been acknowledged.
     pub windows_wsl_setup_acknowledged: bool,
 
@@ -543,6 +789,68 @@ pub(crate) fn set_project_trust_level_inner(
     project_path: &Path,
     trust_level: TrustLevel,
 ) -> anyhow::Result<()> {
+    let proj_tbl = project_table_for_write(doc, project_path)?;
+    proj_tbl["trust_level"] = toml_edit::value(trust_level.to_string());
+    Ok(())
+}
+
+/// Append `prefix` to `approved_command_prefixes` under
+/// `[projects."<path>"]` unless it is already there.
+pub(crate) fn add_project_command_prefix_inner(
+    doc: &mut DocumentMut,
+    project_path: &Path,
+    prefix: &str,
+) -> anyhow::Result<bool> {
+    add_project_list_entry(doc, project_path, "approved_command_prefixes", prefix)
+}
+
+/// Append `permission` to `allowed_writes` or `allowed_network_hosts` under
+/// `[projects."<path>"]` unless it is already there.
+pub(crate) fn add_project_permission_inner(
+    doc: &mut DocumentMut,
+    project_path: &Path,
+    permission: &ScopedPermission,
+) -> anyhow::Result<bool> {
+    match permission {
+        ScopedPermission::Write { pattern } => {
+            add_project_list_entry(doc, project_path, "allowed_writes", pattern)
+        }
+        ScopedPermission::Network { host } => {
+            add_project_list_entry(doc, project_path, "allowed_network_hosts", host)
+        }
+    }
+}
+
+fn add_project_list_entry(
+    doc: &mut DocumentMut,
+    project_path: &Path,
+    key: &str,
+    entry: &str,
+) -> anyhow::Result<bool> {
+    let proj_tbl = project_table_for_write(doc, project_path)?;
+    let item = proj_tbl
+        .entry(key)
+        .or_insert_with(|| toml_edit::value(toml_edit::Array::new()));
+    let Some(entries) = item.as_array_mut() else {
+        return Err(anyhow::anyhow!(
+            "{key} for {} is not an array",
+            project_path.display()
+        ));
+    };
+    if entries
+        .iter()
+        .any(|existing| existing.as_str() == Some(entry))
+    {
+        return Ok(false);
+    }
+    entries.push(entry);
+    Ok(true)
+}
+
+fn project_table_for_write<'a>(
+    doc: &'a mut DocumentMut,
+    project_path: &Path,
+) -> anyhow::Result<&'a mut toml_edit::Table> {
     // Ensure we render a human-friendly structure:
     //
     // [projects]
@@ -603,8 +911,23 @@ pub(crate) fn set_project_trust_level_inner(
         return Err(anyhow::anyhow!("project table missing for {project_key}"));
     };
     proj_tbl.set_implicit(false);
-    proj_tbl["trust_level"] = toml_edit::value(trust_level.to_string());
-    Ok(())
+    Ok(proj_tbl)
+}
+
+/// Split approved command prefixes into words the way a shell would.
+fn parse_command_prefixes<'a>(
+    prefixes: impl IntoIterator<Item = &'a String>,
+) -> std::io::Result<Vec<Vec<String>>> {
+    prefixes
+        .into_iter()
+        .map(|prefix| match shlex::split(prefix) {
+            Some(words) if !words.is_empty() => Ok(words),
+            _ => Err(std::io::Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid approved command prefix `{prefix}`"),
+            )),
+        })
+        .collect()
 }
 
 /// Patch `CODEX_HOME/config.toml` project state to set trust level.
@@ -680,6 +1003,10 @@ pub struct ConfigToml {
     /// Token usage threshold triggering auto-compaction of conversation history.
     pub model_auto_compact_token_limit: Option<i64>,
 
+    /// Maximum number of tokens the model may generate per response. Only
+    /// sent to providers whose wire API requires it (Anthropic).
+    pub model_max_output_tokens: Option<i64>,
+
     /// Default approval policy for executing commands.
     pub approval_policy: Option<AskForApproval>,
 
@@ -696,6 +1023,42 @@ pub struct ConfigToml {
     #[serde(default)]
     pub notify: Option<Vec<String>>,
 
+    /// Optional program that can approve or deny approval requests before
+    /// they are shown to the user.
+    #[serde(default)]
+    pub pre_approval_hook: Option<Vec<String>>,
+
+    /// Hooks subscribed to one or more events.
+    #[serde(default)]
+    pub hooks: Vec<HookConfig>,
+
+    /// Named hooks that `[[hooks]]` entries can reference.
+    #[serde(default)]
+    pub hook_definitions: BTreeMap<String, HookDefinition>,
+
+    /// Run hook programs inside the session sandbox. Defaults to `false`.
+    pub sandbox_hooks: Option<bool>,
+
+    /// Latency budget, in milliseconds, shared by the hooks of one tool call.
+    pub hooks_max_total_latency_ms: Option<u64>,
+
+    /// Allow/ask/deny rules for tool calls, checked before hooks.
+    #[serde(default)]
+    pub tool_rules: Vec<ToolRuleConfig>,
+
+    /// External policy engine consulted for every tool call.
+    pub tool_policy: Option<ToolPolicyConfig>,
+
+    /// Time, in milliseconds, shutdown waits for in-flight mutating tools.
+    pub shutdown_drain_timeout_ms: Option<u64>,
+
+    /// Idle time, in milliseconds, after which MCP servers are stopped and
+    /// background processes paused.
+    pub idle_suspend_after_ms: Option<u64>,
+
+    /// Mirror the plan into `TODO.codex.md`. Defaults to `false`.
+    pub sync_plan_file: Option<bool>,
+
     /// System instructions.
     pub instructions: Option<String>,
 
@@ -721,6 +1084,11 @@ pub struct ConfigToml {
     #[serde(default)]
     pub cli_auth_credentials_store: Option<AuthCredentialsStoreMode>,
 
+    /// Named login to use, as created with `codex login --account`. Overrides
+    /// the account chosen with `codex auth use`.
+    #[serde(default)]
+    pub account: Option<String>,
+
     /// Definition for MCP servers that Codex can reach out to for tool calls.
     #[serde(default)]
     pub mcp_servers: HashMap<String, McpServerConfig>,
@@ -737,9 +1105,28 @@ pub struct ConfigToml {
     #[serde(default)]
     pub model_providers: HashMap<String, ModelProviderInfo>,
 
+    /// Per-model token prices (USD per million tokens) that extend/override
+    /// the built-in pricing table used for cost accounting.
+    #[serde(default)]
+    pub model_pricing: HashMap<String, ModelPricing>,
+
+    /// Rules that pick a model per request, and fallbacks tried when the
+    /// selected provider is rate limited or returns a server error.
+    #[serde(default)]
+    pub model_routing: Option<ModelRouting>,
+
+    /// Set to false to stop requesting provider prompt caching.
+    pub prompt_caching: Option<bool>,
+
     /// Maximum number of bytes to include from an AGENTS.md project doc file.
     pub project_doc_max_bytes: Option<usize>,
 
+    /// Cap on the combined user instructions, in approximate tokens.
+    pub instructions_max_tokens: Option<usize>,
+
+    /// Size of the injected repository map, in approximate tokens.
+    pub repo_map_max_tokens: Option<usize>,
+
     /// Ordered list of fallback filenames to look for when AGENTS.md is missing.
     pub project_doc_fallback_filenames: Option<Vec<String>>,
 
@@ -757,6 +1144,127 @@ pub struct ConfigToml {
     #[serde(default)]
     pub history: Option<History>,
 
+    /// Limits that halt the agent once exceeded.
+    #[serde(default)]
+    pub budget: Option<Budget>,
+
+    /// Wall-clock seconds a task may run before it is cancelled mid-flight,
+    /// including any model request or tool call in progress.
+    #[serde(default)]
+    pub turn_timeout_secs: Option<u64>,
+
+    /// Identical tool calls in a row after which the model is told it is
+    /// looping instead of the call being run. Defaults to 5; 0 disables.
+    #[serde(default)]
+    pub tool_call_loop_limit: Option<usize>,
+
+    /// Percentages of the context window at which front-ends are warned
+    /// that the conversation is filling up. Defaults to `[70, 90]`.
+    #[serde(default)]
+    pub context_pressure_thresholds: Option<Vec<u8>>,
+
+    /// Terminal size for shell commands run under the `shell_pty` feature.
+    #[serde(default)]
+    pub shell_pty: Option<ShellPty>,
+
+    /// Command to run after each successful `apply_patch`.
+    #[serde(default)]
+    pub apply_patch_verify: Option<ApplyPatchVerify>,
+
+    /// Language servers that report diagnostics for edited files.
+    #[serde(default)]
+    pub lsp: BTreeMap<String, LspServerConfig>,
+
+    /// Replace the shell tools with `run_task`. Defaults to `false`.
+    pub tasks_only: Option<bool>,
+
+    /// Start sessions in read-only "ask" mode. Defaults to `false`.
+    pub read_only_mode: Option<bool>,
+
+    /// Cleanup of the `scratch_dir` feature's per-session directory.
+    #[serde(default)]
+    pub scratch_dir: Option<ScratchDir>,
+
+    /// Branches the git tools refuse to commit on or create. Defaults to
+    /// `main` and `master`.
+    pub git_protected_branches: Option<Vec<String>>,
+
+    /// Provider selection and credentials for the `pull_requests` tools.
+    #[serde(default)]
+    pub pull_requests: Option<PullRequestsConfig>,
+
+    /// Search API for the `web_search` function tool under the `web_tools`
+    /// feature.
+    #[serde(default)]
+    pub web_search_provider: Option<WebSearchProviderConfig>,
+
+    /// Size, timeout and robots.txt settings for the `fetch_url` tool.
+    #[serde(default)]
+    pub fetch_url: Option<FetchUrlConfig>,
+
+    /// Embedding provider and retrieval limits for the workspace index used
+    /// by the `semantic_search` feature.
+    #[serde(default)]
+    pub embeddings: Option<EmbeddingsConfig>,
+
+    /// Serve Prometheus metrics from `codex app-server` at `/metrics`.
+    #[serde(default)]
+    pub app_server_metrics: Option<AppServerMetricsConfig>,
+
+    /// Serve the `codex status --json` report on a Unix socket from
+    /// `codex app-server`.
+    #[serde(default)]
+    pub app_server_status: Option<AppServerStatusConfig>,
+
+    /// Hold `apply_patch` edits for per-hunk review: `never` (default),
+    /// `untrusted` (projects not marked trusted) or `always`.
+    #[serde(default)]
+    pub review_edits: Option<ReviewEditsMode>,
+
+    /// Mask secrets (AWS keys, JWTs, private keys, configured patterns) in
+    /// user messages and tool output before they are recorded.
+    #[serde(default)]
+    pub redaction: Option<RedactionConfig>,
+
+    /// Scan `apply_patch`, `write_file` and `edit_file` writes for secrets
+    /// and warn or block before they are applied.
+    #[serde(default)]
+    pub secret_scan: Option<SecretScanConfig>,
+
+    /// Warn about or block `apply_patch`, `write_file` and `edit_file`
+    /// writes to lockfiles, build output and files ignored by git.
+    #[serde(default)]
+    pub generated_files: Option<GeneratedFilesConfig>,
+
+    /// Proxy and custom CA bundle for the model client, MCP servers and web
+    /// tools. Model providers can override them with their own `http` table.
+    #[serde(default)]
+    pub http: Option<HttpClientOptions>,
+
+    /// Encrypt `history.jsonl` and session rollouts with a key from the OS
+    /// keychain or a passphrase.
+    #[serde(default)]
+    pub storage_encryption: Option<StorageEncryptionConfig>,
+
+    /// How many sessions, how old and how much disk space to keep under
+    /// `$CODEX_HOME/sessions`, and whether repeated tool outputs are stored
+    /// once per session.
+    #[serde(default)]
+    pub session_retention: Option<SessionRetentionConfig>,
+
+    /// Checks run after the model edits files.
+    #[serde(default)]
+    pub edit_checks: Option<EditChecksConfig>,
+
+    /// Instructions for generated commit messages.
+    #[serde(default)]
+    pub commit: Option<CommitConfig>,
+
+    /// Prompt for or block commands such as `rm -rf /`, `curl | sh`, force
+    /// pushes and `chmod 777` before they run.
+    #[serde(default)]
+    pub command_safety: Option<CommandSafetyConfig>,
+
     /// Optional URI-based file opener. If set, citations to files in the model
     /// output will be hyperlinked using the specified URI scheme.
     pub file_opener: Option<UriBasedFileOpener>,
@@ -788,6 +1296,11 @@ pub struct ConfigToml {
 
     pub projects: Option<HashMap<String, ProjectConfig>>,
 
+    /// Command prefixes such as `cargo test` that run without an approval
+    /// prompt in every project.
+    #[serde(default)]
+    pub approved_command_prefixes: Option<Vec<String>>,
+
     /// Nested tools section for feature toggles
     pub tools: Option<ToolsToml>,
 
@@ -853,9 +1366,20 @@ impl From<ConfigToml> for UserSavedConfig {
     }
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Default)]
 pub struct ProjectConfig {
     pub trust_level: Option<TrustLevel>,
+    /// Command prefixes that run without an approval prompt in this project.
+    #[serde(default)]
+    pub approved_command_prefixes: Vec<String>,
+    /// Globs, relative to the project root or absolute, that tool calls may
+    /// write without an approval prompt.
+    #[serde(default)]
+    pub allowed_writes: Vec<String>,
+    /// Hosts tool calls may reach without an approval prompt;
+    /// `*.example.com` also matches subdomains.
+    #[serde(default)]
+    pub allowed_network_hosts: Vec<String>,
 }
 
 impl ProjectConfig {
@@ -1149,9 +1673,13 @@ impl Config {
             .into_iter()
             .map(|path| AbsolutePathBuf::resolve_path_against_base(path, &resolved_cwd))
             .collect::<Result<Vec<_>, _>>()?;
-        let active_project = cfg
-            .get_active_project(&resolved_cwd)
-            .unwrap_or(ProjectConfig { trust_level: None });
+        let active_project = cfg.get_active_project(&resolved_cwd).unwrap_or_default();
+        let approved_command_prefixes = parse_command_prefixes(
+            cfg.approved_command_prefixes
+                .iter()
+                .flatten()
+                .chain(&active_project.approved_command_prefixes),
+        )?;
 
         let SandboxPolicyResolution {
             policy: mut sandbox_policy,
@@ -1192,6 +1720,9 @@ impl Config {
             model_providers.entry(key).or_insert(provider);
         }
 
+        let mut model_pricing = built_in_model_pricing();
+        model_pricing.extend(cfg.model_pricing);
+
         let model_provider_id = model_provider
             .or(config_profile.model_provider)
             .or(cfg.model_provider)
@@ -1206,9 +1737,30 @@ impl Config {
             })?
             .clone();
 
+        let model_routing = cfg.model_routing.unwrap_or_default();
+        if let Some(unknown) = model_routing
+            .rules
+            .iter()
+            .map(|rule| &rule.target)
+            .chain(model_routing.fallbacks.iter())
+            .filter_map(|target| target.provider.as_ref())
+            .find(|id| !model_providers.contains_key(*id))
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Model provider `{unknown}` in model_routing not found"),
+            ));
+        }
+
         let shell_environment_policy = cfg.shell_environment_policy.into();
 
         let history = cfg.history.unwrap_or_default();
+        let budget = cfg.budget.unwrap_or_default();
+        let hooks = crate::hooks::resolve(cfg.hooks, &cfg.hook_definitions)
+            .and_then(|hooks| crate::hooks::validate(&hooks).map(|()| hooks))
+            .map_err(|message| std::io::Error::new(ErrorKind::InvalidData, message))?;
+        crate::tool_rules::ToolRules::new(&cfg.tool_rules)
+            .map_err(|message| std::io::Error::new(ErrorKind::InvalidData, message))?;
 
         let ghost_snapshot = {
             let mut config = GhostSnapshotConfig::default();
@@ -1251,6 +1803,32 @@ impl Config {
 
         let forced_login_method = cfg.forced_login_method;
 
+        let account = config_profile.account.clone().or(cfg.account.clone());
+        if let Some(account) = &account {
+            crate::auth::accounts::validate_account_name(account)
+                .map_err(|message| std::io::Error::new(std::io::ErrorKind::InvalidData, message))?;
+        }
+
+        let redaction = cfg.redaction.clone().unwrap_or_default();
+        crate::redaction::Redactor::from_config(&redaction)
+            .map_err(|message| std::io::Error::new(std::io::ErrorKind::InvalidData, message))?;
+        let secret_scan = cfg.secret_scan.clone().unwrap_or_default();
+        crate::secret_scan::SecretScanner::from_config(&secret_scan)
+            .map_err(|message| std::io::Error::new(std::io::ErrorKind::InvalidData, message))?;
+        let http = cfg.http.clone().unwrap_or_default();
+        http.validate()
+            .map_err(|message| std::io::Error::new(std::io::ErrorKind::InvalidData, message))?;
+        for (id, provider) in &model_providers {
+            if let Some(overrides) = &provider.http {
+                overrides.validate().map_err(|message| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("model_providers.{id}.http: {message}"),
+                    )
+                })?;
+            }
+        }
+
         let model = model.or(config_profile.model).or(cfg.model);
 
         let compact_prompt = compact_prompt.or(cfg.compact_prompt).and_then(|value| {
@@ -1312,6 +1890,7 @@ impl Config {
             review_model,
             model_context_window: cfg.model_context_window,
             model_auto_compact_token_limit: cfg.model_auto_compact_token_limit,
+            model_max_output_tokens: cfg.model_max_output_tokens,
             model_provider_id,
             model_provider,
             cwd: resolved_cwd,
@@ -1321,6 +1900,15 @@ impl Config {
             forced_auto_mode_downgraded_on_windows,
             shell_environment_policy,
             notify: cfg.notify,
+            pre_approval_hook: cfg.pre_approval_hook,
+            hooks,
+            sandbox_hooks: cfg.sandbox_hooks.unwrap_or(false),
+            hooks_max_total_latency_ms: cfg.hooks_max_total_latency_ms,
+            tool_rules: cfg.tool_rules,
+            tool_policy: cfg.tool_policy,
+            shutdown_drain_timeout_ms: cfg.shutdown_drain_timeout_ms,
+            idle_suspend_after_ms: cfg.idle_suspend_after_ms,
+            sync_plan_file: cfg.sync_plan_file.unwrap_or(false),
             user_instructions,
             base_instructions,
             developer_instructions,
@@ -1333,7 +1921,15 @@ impl Config {
             // is important in code to differentiate the mode from the store implementation.
             mcp_oauth_credentials_store_mode: cfg.mcp_oauth_credentials_store.unwrap_or_default(),
             model_providers,
+            model_pricing,
+            model_routing,
+            prompt_caching: cfg.prompt_caching.unwrap_or(true),
+            model_cassette: model_cassette_from_env(),
             project_doc_max_bytes: cfg.project_doc_max_bytes.unwrap_or(PROJECT_DOC_MAX_BYTES),
+            instructions_max_tokens: cfg
+                .instructions_max_tokens
+                .unwrap_or(INSTRUCTIONS_MAX_TOKENS),
+            repo_map_max_tokens: cfg.repo_map_max_tokens.unwrap_or(REPO_MAP_MAX_TOKENS),
             project_doc_fallback_filenames: cfg
                 .project_doc_fallback_filenames
                 .unwrap_or_default()
@@ -1350,6 +1946,53 @@ impl Config {
             tool_output_token_limit: cfg.tool_output_token_limit,
             codex_home,
             history,
+            budget,
+            turn_timeout: cfg
+                .turn_timeout_secs
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs),
+            tool_call_loop_limit: cfg
+                .tool_call_loop_limit
+                .unwrap_or(DEFAULT_TOOL_CALL_LOOP_LIMIT),
+            context_pressure_thresholds: {
+                let mut thresholds = cfg
+                    .context_pressure_thresholds
+                    .unwrap_or_else(|| DEFAULT_CONTEXT_PRESSURE_THRESHOLDS.to_vec());
+                thresholds.retain(|percent| (1..=100).contains(percent));
+                thresholds.sort_unstable();
+                thresholds.dedup();
+                thresholds
+            },
+            shell_pty: cfg.shell_pty.unwrap_or_default(),
+            apply_patch_verify: cfg.apply_patch_verify,
+            lsp: cfg.lsp,
+            tasks_only: cfg.tasks_only.unwrap_or(false),
+            read_only_mode: cfg.read_only_mode.unwrap_or(false),
+            scratch_dir: cfg.scratch_dir.unwrap_or_default(),
+            git_protected_branches: cfg
+                .git_protected_branches
+                .unwrap_or_else(default_git_protected_branches),
+            pull_requests: cfg.pull_requests.unwrap_or_default(),
+            web_search_provider: cfg.web_search_provider,
+            fetch_url: cfg.fetch_url.unwrap_or_default(),
+            embeddings: cfg.embeddings,
+            app_server_metrics: cfg.app_server_metrics.unwrap_or_default(),
+            app_server_status: cfg.app_server_status.unwrap_or_default(),
+            review_edits: match cfg.review_edits.unwrap_or_default() {
+                ReviewEditsMode::Never => false,
+                ReviewEditsMode::Untrusted => !active_project.is_trusted(),
+                ReviewEditsMode::Always => true,
+            },
+            redaction,
+            secret_scan,
+            generated_files: cfg.generated_files.unwrap_or_default(),
+            http,
+            storage_encryption: cfg.storage_encryption.unwrap_or_default(),
+            session_retention: cfg.session_retention.unwrap_or_default(),
+            edit_checks: cfg.edit_checks.unwrap_or_default(),
+            commit: cfg.commit.unwrap_or_default(),
+            command_safety: cfg.command_safety.unwrap_or_default(),
+            allowed_tools: None,
             file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
             codex_linux_sandbox_exe,
 
@@ -1374,6 +2017,7 @@ impl Config {
                 .unwrap_or("https://chatgpt.com/backend-api/".to_string()),
             forced_chatgpt_workspace_id,
             forced_login_method,
+            account,
             include_apply_patch_tool: include_apply_patch_tool_flag,
             tools_web_search_request,
             use_experimental_unified_exec_tool,
@@ -1381,6 +2025,7 @@ impl Config {
             features,
             active_profile: active_profile_name,
             active_project,
+            approved_command_prefixes,
             windows_wsl_setup_acknowledged: cfg.windows_wsl_setup_acknowledged.unwrap_or(false),
             notices: cfg.notice.unwrap_or_default(),
             check_for_update_on_startup,
@@ -1390,8 +2035,19 @@ impl Config {
                 .as_ref()
                 .map(|t| t.notifications.clone())
                 .unwrap_or_default(),
+            tui_notification_backend: cfg
+                .tui
+                .as_ref()
+                .map(|t| t.notification_backend)
+                .unwrap_or_default(),
+            tui_notification_min_turn_secs: cfg
+                .tui
+                .as_ref()
+                .and_then(|t| t.notification_min_turn_secs)
+                .unwrap_or(0),
             animations: cfg.tui.as_ref().map(|t| t.animations).unwrap_or(true),
             show_tooltips: cfg.tui.as_ref().map(|t| t.show_tooltips).unwrap_or(true),
+            collapse_reasoning: cfg.tui.as_ref().is_some_and(|t| t.collapse_reasoning),
             tui_scroll_events_per_tick: cfg.tui.as_ref().and_then(|t| t.scroll_events_per_tick),
             tui_scroll_wheel_lines: cfg.tui.as_ref().and_then(|t| t.scroll_wheel_lines),
             tui_scroll_trackpad_lines: cfg.tui.as_ref().and_then(|t| t.scroll_trackpad_lines),
@@ -1413,6 +2069,18 @@ impl Config {
                 .as_ref()
                 .and_then(|t| t.scroll_wheel_like_max_duration_ms),
             tui_scroll_invert: cfg.tui.as_ref().map(|t| t.scroll_invert).unwrap_or(false),
+            tui_theme: cfg.tui.as_ref().and_then(|t| t.theme.clone()),
+            tui_themes: cfg
+                .tui
+                .as_ref()
+                .map(|t| t.themes.clone())
+                .unwrap_or_default(),
+            tui_keybindings: cfg
+                .tui
+                .as_ref()
+                .map(|t| t.keybindings.clone())
+                .unwrap_or_default(),
+            tui_voice: cfg.tui.as_ref().and_then(|t| t.voice.clone()),
             otel: {
                 let t: OtelConfigToml = cfg.otel.unwrap_or_default();
                 let log_user_prompt = t.log_user_prompt.unwrap_or(false);
@@ -1421,18 +2089,22 @@ impl Config {
                     .unwrap_or(DEFAULT_OTEL_ENVIRONMENT.to_string());
                 let exporter = t.exporter.unwrap_or(OtelExporterKind::None);
                 let trace_exporter = t.trace_exporter.unwrap_or_else(|| exporter.clone());
+                let metrics_exporter = t.metrics_exporter.unwrap_or(OtelExporterKind::None);
                 OtelConfig {
                     log_user_prompt,
                     environment,
                     exporter,
                     trace_exporter,
+                    metrics_exporter,
+                    service_name: t.service_name,
+                    tool_payloads: t.tool_payloads.unwrap_or_default(),
                 }
             },
         };
         Ok(config)
     }
 
-    fn load_instructions(codex_dir: Option<&Path>) -> Option<String> {
+    pub(crate) fn load_instructions(codex_dir: Option<&Path>) -> Option<String> {
         let base = codex_dir?;
         for candidate in [LOCAL_PROJECT_DOC_FILENAME, DEFAULT_PROJECT_DOC_FILENAME] {
             let mut path = base.to_path_buf();
@@ -1491,6 +2163,20 @@ fn default_review_model() -> String {
     OPENAI_DEFAULT_REVIEW_MODEL.to_string()
 }
 
+/// Replay takes precedence when both cassette variables are set.
+fn model_cassette_from_env() -> Option<ModelCassette> {
+    if let Some(path) = *CODEX_RS_REPLAY_CASSETTE {
+        return Some(ModelCassette {
+            mode: CassetteMode::Replay,
+            path: PathBuf::from(path),
+        });
+    }
+    CODEX_RS_RECORD_CASSETTE.map(|path| ModelCassette {
+        mode: CassetteMode::Record,
+        path: PathBuf::from(path),
+    })
+}
+
 /// Returns the path to the Codex configuration directory, which can be
 /// specified by the `CODEX_HOME` environment variable. If not set, defaults to
 /// `~/.codex`.
@@ -1532,6 +2218,7 @@ mod tests {
     use crate::config::edit::ConfigEditsBuilder;
     use crate::config::edit::apply_blocking;
     use crate::config::types::HistoryPersistence;
+    use crate::config::types::HookEvent;
     use crate::config::types::McpServerTransportConfig;
     use crate::config::types::Notifications;
     use crate::features::Feature;
@@ -1589,8 +2276,11 @@ persistence = "none"
             tui,
             Tui {
                 notifications: Notifications::Enabled(true),
+                notification_backend: NotificationBackend::Auto,
+                notification_min_turn_secs: None,
                 animations: true,
                 show_tooltips: true,
+                collapse_reasoning: false,
                 scroll_events_per_tick: None,
                 scroll_wheel_lines: None,
                 scroll_trackpad_lines: None,
@@ -1600,10 +2290,167 @@ persistence = "none"
                 scroll_wheel_tick_detect_max_ms: None,
                 scroll_wheel_like_max_duration_ms: None,
                 scroll_invert: false,
+                theme: None,
+                themes: BTreeMap::new(),
+                keybindings: BTreeMap::new(),
+                voice: None,
             }
         );
     }
 
+    #[test]
+    fn tui_config_parses_themes_and_keybindings() {
+        let cfg = r##"
+[tui]
+theme = "solarized"
+
+[tui.themes.solarized]
+base = "high-contrast"
+colors = { cyan = "#2aa198" }
+
+[tui.keybindings]
+transcript = "ctrl+y"
+"##;
+
+        let parsed = toml::from_str::<ConfigToml>(cfg).expect("TUI theme config should parse");
+        let tui = parsed.tui.expect("config should include tui section");
+
+        assert_eq!(tui.theme.as_deref(), Some("solarized"));
+        assert_eq!(
+            tui.themes.get("solarized"),
+            Some(&TuiTheme {
+                base: Some("high-contrast".to_string()),
+                colors: BTreeMap::from([("cyan".to_string(), "#2aa198".to_string())]),
+            })
+        );
+        assert_eq!(
+            tui.keybindings,
+            BTreeMap::from([("transcript".to_string(), "ctrl+y".to_string())])
+        );
+    }
+
+    #[test]
+    fn tui_config_parses_voice_table() {
+        let cfg = r#"
+[tui.voice]
+transcribe_command = ["whisper-cli", "-m", "ggml-base.en.bin", "-nt", "-f", "{file}"]
+"#;
+
+        let parsed = toml::from_str::<ConfigToml>(cfg).expect("TUI voice config should parse");
+        let voice = parsed.tui.and_then(|tui| tui.voice);
+
+        assert_eq!(
+            voice,
+            Some(TuiVoice {
+                transcribe_command: Some(
+                    [
+                        "whisper-cli",
+                        "-m",
+                        "ggml-base.en.bin",
+                        "-nt",
+                        "-f",
+                        "{file}"
+                    ]
+                    .map(String::from)
+                    .to_vec()
+                ),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn tui_config_parses_notification_delivery() {
+        let cfg = r#"
+[tui]
+notifications = ["approval-requested", "agent-turn-complete"]
+notification_backend = "native"
+notification_min_turn_secs = 30
+"#;
+
+        let parsed =
+            toml::from_str::<ConfigToml>(cfg).expect("TUI notification config should parse");
+        let tui = parsed.tui.expect("config should include tui section");
+
+        assert_eq!(tui.notification_backend, NotificationBackend::Native);
+        assert_eq!(tui.notification_min_turn_secs, Some(30));
+    }
+
+    #[test]
+    fn hooks_subscribe_to_several_events() {
+        let cfg = r#"
+[[hooks]]
+events = ["PreApproval", "BudgetExceeded"]
+matcher = "^git "
+command = ["/usr/local/bin/audit"]
+"#;
+
+        let parsed = toml::from_str::<ConfigToml>(cfg).expect("hooks config should parse");
+
+        assert_eq!(
+            parsed.hooks,
+            vec![HookConfig {
+                events: vec![HookEvent::PreApproval, HookEvent::BudgetExceeded],
+                matcher: Some("^git ".to_string()),
+                command: vec!["/usr/local/bin/audit".to_string()],
+                ..Default::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn tool_rules_parse() {
+        let cfg = r#"
+[[tool_rules]]
+name = "protect-env"
+paths = ["**/.env"]
+decision = "deny"
+reason = "keep credentials out of reach"
+"#;
+
+        let parsed = toml::from_str::<ConfigToml>(cfg).expect("tool rules should parse");
+
+        assert_eq!(
+            parsed.tool_rules,
+            vec![ToolRuleConfig {
+                name: Some("protect-env".to_string()),
+                tools: Vec::new(),
+                command: None,
+                arguments: None,
+                paths: vec!["**/.env".to_string()],
+                sandbox: Vec::new(),
+                decision: crate::config::types::ToolRuleDecision::Deny,
+                reason: Some("keep credentials out of reach".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn tool_policy_parses() {
+        let cfg = r#"
+[tool_policy]
+backend = "opa"
+url = "http://localhost:8181/v1/data/codex/tool_call"
+cache_ttl_secs = 0
+skip_tools = ["read_file"]
+"#;
+
+        let parsed = toml::from_str::<ConfigToml>(cfg).expect("tool policy should parse");
+
+        assert_eq!(
+            parsed.tool_policy,
+            Some(ToolPolicyConfig {
+                backend: crate::config::types::ToolPolicyBackend::Opa,
+                url: "http://localhost:8181/v1/data/codex/tool_call".to_string(),
+                token_env: None,
+                timeout_ms: None,
+                cache_ttl_secs: Some(0),
+                on_error: crate::config::types::ToolRuleDecision::Ask,
+                skip_tools: vec!["read_file".to_string()],
+            })
+        );
+    }
+
     #[test]
     fn test_sandbox_config_parsing() {
         let sandbox_full_access = r#"
@@ -3091,7 +3938,15 @@ model_verbosity = "high"
             request_max_retries: Some(4),
             stream_max_retries: Some(10),
             stream_idle_timeout_ms: Some(300_000),
+            retry_rate_limited: None,
+            request_max_retry_time_ms: None,
             requires_openai_auth: false,
+            safety_settings: None,
+            local_quirks: None,
+            http: None,
+            azure: None,
+            bedrock: None,
+            persist_reasoning: None,
         };
         let model_provider_map = {
             let mut model_provider_map = built_in_model_providers();
@@ -3149,6 +4004,7 @@ model_verbosity = "high"
                 review_model: OPENAI_DEFAULT_REVIEW_MODEL.to_string(),
                 model_context_window: None,
                 model_auto_compact_token_limit: None,
+                model_max_output_tokens: None,
                 model_provider_id: "openai".to_string(),
                 model_provider: fixture.openai_provider.clone(),
                 approval_policy: Constrained::allow_any(AskForApproval::Never),
@@ -3158,16 +4014,59 @@ model_verbosity = "high"
                 shell_environment_policy: ShellEnvironmentPolicy::default(),
                 user_instructions: None,
                 notify: None,
+                pre_approval_hook: None,
+                hooks: Vec::new(),
+                sandbox_hooks: false,
+                hooks_max_total_latency_ms: None,
+                tool_rules: Vec::new(),
+                tool_policy: None,
+                shutdown_drain_timeout_ms: None,
+                idle_suspend_after_ms: None,
+                sync_plan_file: false,
                 cwd: fixture.cwd(),
                 cli_auth_credentials_store_mode: Default::default(),
                 mcp_servers: HashMap::new(),
                 mcp_oauth_credentials_store_mode: Default::default(),
                 model_providers: fixture.model_provider_map.clone(),
+                model_pricing: built_in_model_pricing(),
+                model_routing: ModelRouting::default(),
+                prompt_caching: true,
+                model_cassette: None,
                 project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+                instructions_max_tokens: INSTRUCTIONS_MAX_TOKENS,
+                repo_map_max_tokens: REPO_MAP_MAX_TOKENS,
                 project_doc_fallback_filenames: Vec::new(),
                 tool_output_token_limit: None,
                 codex_home: fixture.codex_home(),
                 history: History::default(),
+                budget: Budget::default(),
+                turn_timeout: None,
+                tool_call_loop_limit: DEFAULT_TOOL_CALL_LOOP_LIMIT,
+                context_pressure_thresholds: DEFAULT_CONTEXT_PRESSURE_THRESHOLDS.to_vec(),
+                shell_pty: ShellPty::default(),
+                apply_patch_verify: None,
+                lsp: BTreeMap::new(),
+                tasks_only: false,
+                read_only_mode: false,
+                scratch_dir: ScratchDir::default(),
+                git_protected_branches: default_git_protected_branches(),
+                pull_requests: PullRequestsConfig::default(),
+                web_search_provider: None,
+                fetch_url: FetchUrlConfig::default(),
+                app_server_metrics: AppServerMetricsConfig::default(),
+                app_server_status: AppServerStatusConfig::default(),
+                review_edits: false,
+                redaction: RedactionConfig::default(),
+                secret_scan: SecretScanConfig::default(),
+                generated_files: GeneratedFilesConfig::default(),
+                http: HttpClientOptions::default(),
+                storage_encryption: StorageEncryptionConfig::default(),
+                session_retention: SessionRetentionConfig::default(),
+                edit_checks: EditChecksConfig::default(),
+                commit: CommitConfig::default(),
+                command_safety: CommandSafetyConfig::default(),
+                embeddings: None,
+                allowed_tools: None,
                 file_opener: UriBasedFileOpener::VsCode,
                 codex_linux_sandbox_exe: None,
                 hide_agent_reasoning: false,
@@ -3183,20 +4082,25 @@ model_verbosity = "high"
                 compact_prompt: None,
                 forced_chatgpt_workspace_id: None,
                 forced_login_method: None,
+                account: None,
                 include_apply_patch_tool: false,
                 tools_web_search_request: false,
                 use_experimental_unified_exec_tool: false,
                 ghost_snapshot: GhostSnapshotConfig::default(),
                 features: Features::with_defaults(),
                 active_profile: Some("o3".to_string()),
-                active_project: ProjectConfig { trust_level: None },
+                active_project: ProjectConfig::default(),
+                approved_command_prefixes: Vec::new(),
                 windows_wsl_setup_acknowledged: false,
                 notices: Default::default(),
                 check_for_update_on_startup: true,
                 disable_paste_burst: false,
                 tui_notifications: Default::default(),
+                tui_notification_backend: NotificationBackend::Auto,
+                tui_notification_min_turn_secs: 0,
                 animations: true,
                 show_tooltips: true,
+                collapse_reasoning: false,
                 tui_scroll_events_per_tick: None,
                 tui_scroll_wheel_lines: None,
                 tui_scroll_trackpad_lines: None,
@@ -3206,6 +4110,10 @@ model_verbosity = "high"
                 tui_scroll_wheel_tick_detect_max_ms: None,
                 tui_scroll_wheel_like_max_duration_ms: None,
                 tui_scroll_invert: false,
+                tui_theme: None,
+                tui_themes: BTreeMap::new(),
+                tui_keybindings: BTreeMap::new(),
+                tui_voice: None,
                 otel: OtelConfig::default(),
             },
             o3_profile_config
@@ -3232,6 +4140,7 @@ model_verbosity = "high"
             review_model: OPENAI_DEFAULT_REVIEW_MODEL.to_string(),
             model_context_window: None,
             model_auto_compact_token_limit: None,
+            model_max_output_tokens: None,
             model_provider_id: "openai-chat-completions".to_string(),
             model_provider: fixture.openai_chat_completions_provider.clone(),
             approval_policy: Constrained::allow_any(AskForApproval::UnlessTrusted),
@@ -3241,16 +4150,59 @@ model_verbosity = "high"
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
+            pre_approval_hook: None,
+            hooks: Vec::new(),
+            sandbox_hooks: false,
+            hooks_max_total_latency_ms: None,
+            tool_rules: Vec::new(),
+            tool_policy: None,
+            shutdown_drain_timeout_ms: None,
+            idle_suspend_after_ms: None,
+            sync_plan_file: false,
             cwd: fixture.cwd(),
             cli_auth_credentials_store_mode: Default::default(),
             mcp_servers: HashMap::new(),
             mcp_oauth_credentials_store_mode: Default::default(),
             model_providers: fixture.model_provider_map.clone(),
+            model_pricing: built_in_model_pricing(),
+            model_routing: ModelRouting::default(),
+            prompt_caching: true,
+            model_cassette: None,
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+            instructions_max_tokens: INSTRUCTIONS_MAX_TOKENS,
+            repo_map_max_tokens: REPO_MAP_MAX_TOKENS,
             project_doc_fallback_filenames: Vec::new(),
             tool_output_token_limit: None,
             codex_home: fixture.codex_home(),
             history: History::default(),
+            budget: Budget::default(),
+            turn_timeout: None,
+            tool_call_loop_limit: DEFAULT_TOOL_CALL_LOOP_LIMIT,
+            context_pressure_thresholds: DEFAULT_CONTEXT_PRESSURE_THRESHOLDS.to_vec(),
+            shell_pty: ShellPty::default(),
+            apply_patch_verify: None,
+            lsp: BTreeMap::new(),
+            tasks_only: false,
+            read_only_mode: false,
+            scratch_dir: ScratchDir::default(),
+            git_protected_branches: default_git_protected_branches(),
+            pull_requests: PullRequestsConfig::default(),
+            web_search_provider: None,
+            fetch_url: FetchUrlConfig::default(),
+            app_server_metrics: AppServerMetricsConfig::default(),
+            app_server_status: AppServerStatusConfig::default(),
+            review_edits: false,
+            redaction: RedactionConfig::default(),
+            secret_scan: SecretScanConfig::default(),
+            generated_files: GeneratedFilesConfig::default(),
+            http: HttpClientOptions::default(),
+            storage_encryption: StorageEncryptionConfig::default(),
+            session_retention: SessionRetentionConfig::default(),
+            edit_checks: EditChecksConfig::default(),
+            commit: CommitConfig::default(),
+            command_safety: CommandSafetyConfig::default(),
+            embeddings: None,
+            allowed_tools: None,
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
@@ -3266,20 +4218,25 @@ model_verbosity = "high"
             compact_prompt: None,
             forced_chatgpt_workspace_id: None,
             forced_login_method: None,
+            account: None,
             include_apply_patch_tool: false,
             tools_web_search_request: false,
             use_experimental_unified_exec_tool: false,
             ghost_snapshot: GhostSnapshotConfig::default(),
             features: Features::with_defaults(),
             active_profile: Some("gpt3".to_string()),
-            active_project: ProjectConfig { trust_level: None },
+            active_project: ProjectConfig::default(),
+            approved_command_prefixes: Vec::new(),
             windows_wsl_setup_acknowledged: false,
             notices: Default::default(),
             check_for_update_on_startup: true,
             disable_paste_burst: false,
             tui_notifications: Default::default(),
+            tui_notification_backend: NotificationBackend::Auto,
+            tui_notification_min_turn_secs: 0,
             animations: true,
             show_tooltips: true,
+            collapse_reasoning: false,
             tui_scroll_events_per_tick: None,
             tui_scroll_wheel_lines: None,
             tui_scroll_trackpad_lines: None,
@@ -3289,6 +4246,10 @@ model_verbosity = "high"
             tui_scroll_wheel_tick_detect_max_ms: None,
             tui_scroll_wheel_like_max_duration_ms: None,
             tui_scroll_invert: false,
+            tui_theme: None,
+            tui_themes: BTreeMap::new(),
+            tui_keybindings: BTreeMap::new(),
+            tui_voice: None,
             otel: OtelConfig::default(),
         };
 
@@ -3330,6 +4291,7 @@ model_verbosity = "high"
             review_model: OPENAI_DEFAULT_REVIEW_MODEL.to_string(),
             model_context_window: None,
             model_auto_compact_token_limit: None,
+            model_max_output_tokens: None,
             model_provider_id: "openai".to_string(),
             model_provider: fixture.openai_provider.clone(),
             approval_policy: Constrained::allow_any(AskForApproval::OnFailure),
@@ -3339,16 +4301,59 @@ model_verbosity = "high"
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
+            pre_approval_hook: None,
+            hooks: Vec::new(),
+            sandbox_hooks: false,
+            hooks_max_total_latency_ms: None,
+            tool_rules: Vec::new(),
+            tool_policy: None,
+            shutdown_drain_timeout_ms: None,
+            idle_suspend_after_ms: None,
+            sync_plan_file: false,
             cwd: fixture.cwd(),
             cli_auth_credentials_store_mode: Default::default(),
             mcp_servers: HashMap::new(),
             mcp_oauth_credentials_store_mode: Default::default(),
             model_providers: fixture.model_provider_map.clone(),
+            model_pricing: built_in_model_pricing(),
+            model_routing: ModelRouting::default(),
+            prompt_caching: true,
+            model_cassette: None,
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+            instructions_max_tokens: INSTRUCTIONS_MAX_TOKENS,
+            repo_map_max_tokens: REPO_MAP_MAX_TOKENS,
             project_doc_fallback_filenames: Vec::new(),
             tool_output_token_limit: None,
             codex_home: fixture.codex_home(),
             history: History::default(),
+            budget: Budget::default(),
+            turn_timeout: None,
+            tool_call_loop_limit: DEFAULT_TOOL_CALL_LOOP_LIMIT,
+            context_pressure_thresholds: DEFAULT_CONTEXT_PRESSURE_THRESHOLDS.to_vec(),
+            shell_pty: ShellPty::default(),
+            apply_patch_verify: None,
+            lsp: BTreeMap::new(),
+            tasks_only: false,
+            read_only_mode: false,
+            scratch_dir: ScratchDir::default(),
+            git_protected_branches: default_git_protected_branches(),
+            pull_requests: PullRequestsConfig::default(),
+            web_search_provider: None,
+            fetch_url: FetchUrlConfig::default(),
+            app_server_metrics: AppServerMetricsConfig::default(),
+            app_server_status: AppServerStatusConfig::default(),
+            review_edits: false,
+            redaction: RedactionConfig::default(),
+            secret_scan: SecretScanConfig::default(),
+            generated_files: GeneratedFilesConfig::default(),
+            http: HttpClientOptions::default(),
+            storage_encryption: StorageEncryptionConfig::default(),
+            session_retention: SessionRetentionConfig::default(),
+            edit_checks: EditChecksConfig::default(),
+            commit: CommitConfig::default(),
+            command_safety: CommandSafetyConfig::default(),
+            embeddings: None,
+            allowed_tools: None,
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
@@ -3364,20 +4369,25 @@ model_verbosity = "high"
             compact_prompt: None,
             forced_chatgpt_workspace_id: None,
             forced_login_method: None,
+            account: None,
             include_apply_patch_tool: false,
             tools_web_search_request: false,
             use_experimental_unified_exec_tool: false,
             ghost_snapshot: GhostSnapshotConfig::default(),
             features: Features::with_defaults(),
             active_profile: Some("zdr".to_string()),
-            active_project: ProjectConfig { trust_level: None },
+            active_project: ProjectConfig::default(),
+            approved_command_prefixes: Vec::new(),
             windows_wsl_setup_acknowledged: false,
             notices: Default::default(),
             check_for_update_on_startup: true,
             disable_paste_burst: false,
             tui_notifications: Default::default(),
+            tui_notification_backend: NotificationBackend::Auto,
+            tui_notification_min_turn_secs: 0,
             animations: true,
             show_tooltips: true,
+            collapse_reasoning: false,
             tui_scroll_events_per_tick: None,
             tui_scroll_wheel_lines: None,
             tui_scroll_trackpad_lines: None,
@@ -3387,6 +4397,10 @@ model_verbosity = "high"
             tui_scroll_wheel_tick_detect_max_ms: None,
             tui_scroll_wheel_like_max_duration_ms: None,
             tui_scroll_invert: false,
+            tui_theme: None,
+            tui_themes: BTreeMap::new(),
+            tui_keybindings: BTreeMap::new(),
+            tui_voice: None,
             otel: OtelConfig::default(),
         };
 
@@ -3414,6 +4428,7 @@ model_verbosity = "high"
             review_model: OPENAI_DEFAULT_REVIEW_MODEL.to_string(),
             model_context_window: None,
             model_auto_compact_token_limit: None,
+            model_max_output_tokens: None,
             model_provider_id: "openai".to_string(),
             model_provider: fixture.openai_provider.clone(),
             approval_policy: Constrained::allow_any(AskForApproval::OnFailure),
@@ -3423,16 +4438,59 @@ model_verbosity = "high"
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
+            pre_approval_hook: None,
+            hooks: Vec::new(),
+            sandbox_hooks: false,
+            hooks_max_total_latency_ms: None,
+            tool_rules: Vec::new(),
+            tool_policy: None,
+            shutdown_drain_timeout_ms: None,
+            idle_suspend_after_ms: None,
+            sync_plan_file: false,
             cwd: fixture.cwd(),
             cli_auth_credentials_store_mode: Default::default(),
             mcp_servers: HashMap::new(),
             mcp_oauth_credentials_store_mode: Default::default(),
             model_providers: fixture.model_provider_map.clone(),
+            model_pricing: built_in_model_pricing(),
+            model_routing: ModelRouting::default(),
+            prompt_caching: true,
+            model_cassette: None,
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+            instructions_max_tokens: INSTRUCTIONS_MAX_TOKENS,
+            repo_map_max_tokens: REPO_MAP_MAX_TOKENS,
             project_doc_fallback_filenames: Vec::new(),
             tool_output_token_limit: None,
             codex_home: fixture.codex_home(),
             history: History::default(),
+            budget: Budget::default(),
+            turn_timeout: None,
+            tool_call_loop_limit: DEFAULT_TOOL_CALL_LOOP_LIMIT,
+            context_pressure_thresholds: DEFAULT_CONTEXT_PRESSURE_THRESHOLDS.to_vec(),
+            shell_pty: ShellPty::default(),
+            apply_patch_verify: None,
+            lsp: BTreeMap::new(),
+            tasks_only: false,
+            read_only_mode: false,
+            scratch_dir: ScratchDir::default(),
+            git_protected_branches: default_git_protected_branches(),
+            pull_requests: PullRequestsConfig::default(),
+            web_search_provider: None,
+            fetch_url: FetchUrlConfig::default(),
+            app_server_metrics: AppServerMetricsConfig::default(),
+            app_server_status: AppServerStatusConfig::default(),
+            review_edits: false,
+            redaction: RedactionConfig::default(),
+            secret_scan: SecretScanConfig::default(),
+            generated_files: GeneratedFilesConfig::default(),
+            http: HttpClientOptions::default(),
+            storage_encryption: StorageEncryptionConfig::default(),
+            session_retention: SessionRetentionConfig::default(),
+            edit_checks: EditChecksConfig::default(),
+            commit: CommitConfig::default(),
+            command_safety: CommandSafetyConfig::default(),
+            embeddings: None,
+            allowed_tools: None,
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
@@ -3448,20 +4506,25 @@ model_verbosity = "high"
             compact_prompt: None,
             forced_chatgpt_workspace_id: None,
             forced_login_method: None,
+            account: None,
             include_apply_patch_tool: false,
             tools_web_search_request: false,
             use_experimental_unified_exec_tool: false,
             ghost_snapshot: GhostSnapshotConfig::default(),
             features: Features::with_defaults(),
             active_profile: Some("gpt5".to_string()),
-            active_project: ProjectConfig { trust_level: None },
+            active_project: ProjectConfig::default(),
+            approved_command_prefixes: Vec::new(),
             windows_wsl_setup_acknowledged: false,
             notices: Default::default(),
             check_for_update_on_startup: true,
             disable_paste_burst: false,
             tui_notifications: Default::default(),
+            tui_notification_backend: NotificationBackend::Auto,
+            tui_notification_min_turn_secs: 0,
             animations: true,
             show_tooltips: true,
+            collapse_reasoning: false,
             tui_scroll_events_per_tick: None,
             tui_scroll_wheel_lines: None,
             tui_scroll_trackpad_lines: None,
@@ -3471,6 +4534,10 @@ model_verbosity = "high"
             tui_scroll_wheel_tick_detect_max_ms: None,
             tui_scroll_wheel_like_max_duration_ms: None,
             tui_scroll_invert: false,
+            tui_theme: None,
+            tui_themes: BTreeMap::new(),
+            tui_keybindings: BTreeMap::new(),
+            tui_voice: None,
             otel: OtelConfig::default(),
         };
 
@@ -3749,6 +4816,7 @@ trust_level = "untrusted"
                     test_path.to_string_lossy().to_string(),
                     ProjectConfig {
                         trust_level: Some(TrustLevel::Untrusted),
+                        ..Default::default()
                     },
                 )])),
                 ..Default::default()
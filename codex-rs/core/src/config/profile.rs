@@ -32,6 +32,8 @@ pub struct ConfigProfile {
     #[serde(default)]
     pub features: Option<crate::features::FeaturesToml>,
     pub oss_provider: Option<String>,
+    /// Named login to use with this profile.
+    pub account: Option<String>,
 }
 
 impl From<ConfigProfile> for codex_app_server_protocol::Profile {
@@ -3,6 +3,7 @@
 // Note this file should generally be restricted to simple struct/enum
 // definitions that do not contain business logic.
 
+use codex_api::CassetteMode;
 use codex_utils_absolute_path::AbsolutePathBuf;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
@@ -273,6 +274,755 @@ pub enum HistoryPersistence {
     None,
 }
 
+/// Token prices for a model, in USD per million tokens. Used to estimate
+/// spend in the usage ledger (`~/.codex/usage.jsonl`).
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct ModelPricing {
+    /// Price of uncached input tokens.
+    pub input: f64,
+
+    /// Price of cached input tokens. Defaults to the uncached input price.
+    #[serde(default)]
+    pub cached_input: Option<f64>,
+
+    /// Price of output tokens (including reasoning tokens).
+    pub output: f64,
+}
+
+/// Hard limits that stop the agent from issuing further model requests once
+/// reached. All limits are unset (unlimited) by default.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Budget {
+    /// Maximum total tokens (input + output) a session may consume.
+    pub max_tokens: Option<i64>,
+
+    /// Maximum estimated session spend in USD, priced with `model_pricing`.
+    pub max_cost_usd: Option<f64>,
+
+    /// Maximum number of model requests a session may issue.
+    pub max_turns: Option<u32>,
+
+    /// Maximum wall-clock seconds a single task may run.
+    pub max_task_duration_secs: Option<u64>,
+
+    /// Optional program to run when a budget is exceeded. The
+    /// `BudgetExceeded` event is passed to it as a JSON argument.
+    pub on_exceeded: Option<Vec<String>>,
+}
+
+/// Events a `[[hooks]]` entry can subscribe to.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    /// Before an approval request is shown; the hook may answer it, like
+    /// `pre_approval_hook`.
+    PreApproval,
+    /// After the agent finishes a turn, like `notify`.
+    AgentTurnComplete,
+    /// When a budget is exceeded, like `budget.on_exceeded`.
+    BudgetExceeded,
+    /// After a tool call finishes; the hook may send feedback to the model
+    /// with the result.
+    AfterToolUse,
+    /// While the session shuts down; shutdown waits for the hook to exit.
+    SessionEnd,
+}
+
+impl HookEvent {
+    /// The name used for this event in `config.toml`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PreApproval => "PreApproval",
+            Self::AgentTurnComplete => "AgentTurnComplete",
+            Self::BudgetExceeded => "BudgetExceeded",
+            Self::AfterToolUse => "AfterToolUse",
+            Self::SessionEnd => "SessionEnd",
+        }
+    }
+}
+
+/// One `[[hooks]]` entry: a command run for every event it subscribes to.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct HookConfig {
+    pub events: Vec<HookEvent>,
+
+    /// Regex the event's subject must match for the hook to run: the command
+    /// line of an exec approval or any changed path of a patch approval.
+    /// Ignored for events without a subject.
+    pub matcher: Option<String>,
+
+    /// Program and arguments; the event is appended as a JSON argument.
+    /// Empty when the entry names a `definition` instead.
+    #[serde(default)]
+    pub command: Vec<String>,
+
+    /// Name of a `[hook_definitions.<name>]` table supplying the command and
+    /// its settings. Settings on the entry take precedence.
+    pub definition: Option<String>,
+
+    /// Run this hook inside the session sandbox. Defaults to `sandbox_hooks`.
+    pub sandbox: Option<bool>,
+
+    /// Longest the hook may run, in milliseconds. Only shortens the timeout
+    /// Codex already applies to hooks it waits for.
+    pub timeout_ms: Option<u64>,
+
+    /// Extra environment variables for the hook process.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+/// A hook defined once under `[hook_definitions.<name>]` and referenced by
+/// `[[hooks]]` entries through `definition`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct HookDefinition {
+    pub command: Vec<String>,
+    pub sandbox: Option<bool>,
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+/// One `[[tool_rules]]` entry: a decision for the tool calls it matches.
+/// A call matches when every condition the entry sets holds.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolRuleConfig {
+    /// Shown in messages about the rule. Defaults to its position, e.g. `#2`.
+    pub name: Option<String>,
+
+    /// Tool names the rule applies to; `"shell"` covers every shell tool.
+    /// Empty matches any tool.
+    #[serde(default)]
+    pub tools: Vec<String>,
+
+    /// Regex searched for in the command line of shell tool calls. Calls
+    /// without a command line do not match.
+    pub command: Option<String>,
+
+    /// Regex searched for in the call's raw arguments (or patch input).
+    pub arguments: Option<String>,
+
+    /// Globs matched against the paths the call writes, both relative to
+    /// the working directory and absolute. Matches when any path does.
+    #[serde(default)]
+    pub paths: Vec<String>,
+
+    /// Sandbox modes the rule applies under (`read-only`,
+    /// `workspace-write`, `danger-full-access`, `external-sandbox`). Empty
+    /// matches any.
+    #[serde(default)]
+    pub sandbox: Vec<String>,
+
+    pub decision: ToolRuleDecision,
+
+    /// Told to the model when the rule denies a call, and shown with the
+    /// approval prompt when it asks.
+    pub reason: Option<String>,
+}
+
+/// What a `[[tool_rules]]` entry decides, from least to most strict.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolRuleDecision {
+    /// Run without the approval prompt the approval policy would show.
+    Allow,
+    /// Always show an approval prompt, without consulting hooks.
+    Ask,
+    /// Refuse the call.
+    Deny,
+}
+
+/// `[tool_policy]`: an external policy engine asked to decide every tool
+/// call, alongside `[[tool_rules]]`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolPolicyConfig {
+    pub backend: ToolPolicyBackend,
+
+    /// URL of the decision document, e.g.
+    /// `http://localhost:8181/v1/data/codex/tool_call` for OPA.
+    pub url: String,
+
+    /// Environment variable holding a bearer token for the engine.
+    pub token_env: Option<String>,
+
+    /// How long a call waits for a decision. Defaults to 5 seconds.
+    pub timeout_ms: Option<u64>,
+
+    /// How long a decision is reused for identical calls. Defaults to 60
+    /// seconds; `0` asks the engine every time.
+    pub cache_ttl_secs: Option<u64>,
+
+    /// Decision applied when the engine cannot be reached or answers with
+    /// something other than a decision.
+    #[serde(default = "default_tool_policy_on_error")]
+    pub on_error: ToolRuleDecision,
+
+    /// Tools whose calls are not sent to the engine, such as `read_file`,
+    /// so they do not wait for it. `[[tool_rules]]` still apply to them.
+    #[serde(default)]
+    pub skip_tools: Vec<String>,
+}
+
+fn default_tool_policy_on_error() -> ToolRuleDecision {
+    ToolRuleDecision::Ask
+}
+
+/// Policy engines `[tool_policy]` can delegate to.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolPolicyBackend {
+    /// An Open Policy Agent server, queried through its Data API.
+    Opa,
+}
+
+/// Cassette that model requests are recorded to or replayed from. Set from
+/// `CODEX_RS_RECORD_CASSETTE` / `CODEX_RS_REPLAY_CASSETTE`, not config.toml.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelCassette {
+    pub mode: CassetteMode,
+    pub path: PathBuf,
+}
+
+/// Model selection and failover for model requests (`[model_routing]`).
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ModelRouting {
+    /// Evaluated in order; the first matching rule replaces the session model
+    /// for that request.
+    #[serde(default)]
+    pub rules: Vec<ModelRoutingRule>,
+
+    /// Tried in order when a request is rate limited or fails with a server
+    /// error, after the provider's own `request_max_retries` are used up.
+    #[serde(default)]
+    pub fallbacks: Vec<ModelRouteTarget>,
+}
+
+impl ModelRouting {
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty() && self.fallbacks.is_empty()
+    }
+}
+
+/// A model, optionally on a different entry of `model_providers`. Without a
+/// `provider` the session provider is used.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ModelRouteTarget {
+    pub model: String,
+    #[serde(default)]
+    pub provider: Option<String>,
+}
+
+/// Sends matching requests to `model`. All set conditions must hold.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ModelRoutingRule {
+    /// Match requests whose estimated input is at least this many tokens.
+    #[serde(default)]
+    pub min_input_tokens: Option<i64>,
+
+    /// Match only requests made for this purpose.
+    #[serde(default)]
+    pub purpose: Option<RequestPurpose>,
+
+    #[serde(flatten)]
+    pub target: ModelRouteTarget,
+}
+
+/// Why a model request is made.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestPurpose {
+    /// A regular agent turn.
+    #[default]
+    Turn,
+    /// Summarizing the conversation during compaction.
+    Compact,
+}
+
+/// Terminal used when the `shell_pty` feature runs shell tool commands in a
+/// pseudo-terminal.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct ShellPty {
+    pub rows: u16,
+    pub columns: u16,
+}
+
+impl Default for ShellPty {
+    fn default() -> Self {
+        Self {
+            rows: 24,
+            columns: 80,
+        }
+    }
+}
+
+/// Cleanup of the per-session directory created by the `scratch_dir`
+/// feature.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(default)]
+pub struct ScratchDir {
+    /// Leave the directory in place for inspection when the session reported
+    /// an error.
+    pub keep_on_error: bool,
+}
+
+/// Command run after every successful `apply_patch` to check that the edit
+/// still builds, e.g. `cargo check` or `tsc --noEmit`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ApplyPatchVerify {
+    /// Run through the user's shell from the session's working directory.
+    pub command: String,
+    #[serde(default = "default_apply_patch_verify_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Failed checks in a row, within one task, that the model is asked to
+    /// fix with a corrected patch before it is told to stop and report.
+    #[serde(default = "default_apply_patch_verify_max_retries")]
+    pub max_retries: u32,
+}
+
+fn default_apply_patch_verify_timeout_ms() -> u64 {
+    120_000
+}
+
+fn default_apply_patch_verify_max_retries() -> u32 {
+    3
+}
+
+/// A language server asked for diagnostics after each `apply_patch`, keyed
+/// by name under `[lsp]`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct LspServerConfig {
+    /// Program and arguments that start the server speaking LSP over stdio,
+    /// e.g. `["rust-analyzer"]`.
+    pub command: Vec<String>,
+    /// File extensions, without the dot, whose edits go to this server.
+    pub extensions: Vec<String>,
+    /// `languageId` sent when opening a file. Defaults to the extension.
+    #[serde(default)]
+    pub language_id: Option<String>,
+    /// How long to wait for diagnostics after an edit.
+    #[serde(default = "default_lsp_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_lsp_timeout_ms() -> u64 {
+    5_000
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PullRequestProvider {
+    Github,
+    Gitlab,
+}
+
+/// Settings for the `pr_*` tools under the `pull_requests` feature. Without
+/// tokens, `GH_TOKEN`/`GITHUB_TOKEN`/`GITLAB_TOKEN` and then the `gh` and
+/// `glab` logins are used. Configured and environment tokens are only sent to
+/// github.com, gitlab.com or a configured API URL.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PullRequestsConfig {
+    /// Required unless the `origin` remote is on github.com or gitlab.com.
+    #[serde(default)]
+    pub provider: Option<PullRequestProvider>,
+    #[serde(default)]
+    pub github_token: Option<String>,
+    /// Defaults to `https://api.github.com`, or `https://<host>/api/v3` for
+    /// GitHub Enterprise hosts.
+    #[serde(default)]
+    pub github_api_url: Option<String>,
+    #[serde(default)]
+    pub gitlab_token: Option<String>,
+    /// Defaults to `https://<host>/api/v4`.
+    #[serde(default)]
+    pub gitlab_api_url: Option<String>,
+}
+
+/// Search API behind the `web_search` function tool under the `web_tools`
+/// feature. Without one, the model keeps the hosted search tool.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct WebSearchProviderConfig {
+    #[serde(flatten)]
+    pub provider: WebSearchProvider,
+    /// Results returned when the model does not ask for a count.
+    #[serde(default = "default_web_search_max_results")]
+    pub max_results: usize,
+}
+
+fn default_web_search_max_results() -> usize {
+    5
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum WebSearchProvider {
+    /// Reads its key from `api_key_env`, `BRAVE_API_KEY` by default.
+    Brave {
+        #[serde(default)]
+        api_key_env: Option<String>,
+        #[serde(default)]
+        base_url: Option<String>,
+    },
+    /// Reads its key from `api_key_env`, `TAVILY_API_KEY` by default.
+    Tavily {
+        #[serde(default)]
+        api_key_env: Option<String>,
+        #[serde(default)]
+        base_url: Option<String>,
+    },
+    /// A SearXNG instance with the JSON output format enabled.
+    Searxng { base_url: String },
+}
+
+/// Limits for the `fetch_url` tool.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct FetchUrlConfig {
+    /// Bytes of the response body read before the rest is dropped.
+    #[serde(default = "default_fetch_url_max_bytes")]
+    pub max_bytes: usize,
+    #[serde(default = "default_true")]
+    pub respect_robots_txt: bool,
+    #[serde(default = "default_fetch_url_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl Default for FetchUrlConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: default_fetch_url_max_bytes(),
+            respect_robots_txt: true,
+            timeout_ms: default_fetch_url_timeout_ms(),
+        }
+    }
+}
+
+fn default_fetch_url_max_bytes() -> usize {
+    2 * 1024 * 1024
+}
+
+fn default_fetch_url_timeout_ms() -> u64 {
+    30_000
+}
+
+/// Embedding provider and retrieval limits for the workspace index behind
+/// the `semantic_search` feature.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct EmbeddingsConfig {
+    #[serde(flatten)]
+    pub provider: EmbeddingProvider,
+    /// Add the chunks most relevant to each user message to the context.
+    #[serde(default = "default_true")]
+    pub auto_retrieve: bool,
+    /// Chunks returned when the model does not ask for a count, and the
+    /// number retrieved automatically.
+    #[serde(default = "default_embeddings_max_results")]
+    pub max_results: usize,
+    /// Token budget for automatically retrieved chunks.
+    #[serde(default = "default_embeddings_retrieval_max_tokens")]
+    pub retrieval_max_tokens: usize,
+}
+
+fn default_embeddings_max_results() -> usize {
+    5
+}
+
+fn default_embeddings_retrieval_max_tokens() -> usize {
+    2_000
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum EmbeddingProvider {
+    /// The OpenAI embeddings API or a compatible server. Reads its key from
+    /// `api_key_env`, `OPENAI_API_KEY` by default.
+    Openai {
+        /// Defaults to `text-embedding-3-small`.
+        #[serde(default)]
+        model: Option<String>,
+        #[serde(default)]
+        api_key_env: Option<String>,
+        #[serde(default)]
+        base_url: Option<String>,
+    },
+    /// A local Ollama server.
+    Ollama {
+        model: String,
+        #[serde(default)]
+        base_url: Option<String>,
+    },
+}
+
+/// Prometheus endpoint served by `codex app-server`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct AppServerMetricsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Address the `/metrics` HTTP listener binds to.
+    #[serde(default = "default_app_server_metrics_bind_address")]
+    pub bind_address: String,
+}
+
+impl Default for AppServerMetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: default_app_server_metrics_bind_address(),
+        }
+    }
+}
+
+fn default_app_server_metrics_bind_address() -> String {
+    "127.0.0.1:9464".to_string()
+}
+
+/// Unix socket on which `codex app-server` serves the `codex status --json`
+/// report.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct AppServerStatusConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Socket path; defaults to `$CODEX_HOME/app-server-status.sock`.
+    #[serde(default)]
+    pub socket: Option<PathBuf>,
+}
+
+/// Masking of secrets in user messages and tool output before they are
+/// recorded or sent to the model.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RedactionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Also mask long tokens whose character entropy suggests a random
+    /// secret.
+    #[serde(default)]
+    pub entropy: bool,
+    /// Extra regexes, keyed by the detector name reported in redaction
+    /// notices. A pattern with a capture group masks only the first group.
+    #[serde(default)]
+    pub patterns: BTreeMap<String, String>,
+}
+
+/// What the built-in secret scan does when a file write adds a secret.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretScanMode {
+    #[default]
+    Off,
+    /// Show a warning and let the write go ahead.
+    Warn,
+    /// Reject the write and tell the model why.
+    Block,
+}
+
+/// Scanning of `apply_patch`, `write_file` and `edit_file` writes for
+/// secrets before they are applied.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SecretScanConfig {
+    #[serde(default)]
+    pub mode: SecretScanMode,
+    /// Extra regexes, keyed by the detector name reported in findings. The
+    /// built-in redaction patterns always apply.
+    #[serde(default)]
+    pub patterns: BTreeMap<String, String>,
+}
+
+/// What the built-in check does when a file write targets a generated file.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GeneratedFilesMode {
+    #[default]
+    Off,
+    /// Let the write go ahead and tell the model to edit the sources next
+    /// time.
+    Warn,
+    /// Reject the write and tell the model to edit the sources instead.
+    Block,
+}
+
+/// Checking of `apply_patch`, `write_file` and `edit_file` writes for
+/// lockfiles, build output and files ignored by git.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct GeneratedFilesConfig {
+    #[serde(default)]
+    pub mode: GeneratedFilesMode,
+    /// Extra globs, relative to the working directory, for files that are
+    /// generated.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Globs for files that are never treated as generated.
+    #[serde(default)]
+    pub allow: Vec<String>,
+}
+
+/// Encryption at rest for `history.jsonl` and session rollouts.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct StorageEncryptionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Where the key comes from. Only consulted when the key is first
+    /// created; afterwards `storage-key.json` in the Codex home decides.
+    #[serde(default)]
+    pub key_source: StorageKeySource,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageKeySource {
+    /// A random key kept in the OS keychain.
+    #[default]
+    Keyring,
+    /// A key derived from the `CODEX_STORAGE_PASSPHRASE` environment variable.
+    Passphrase,
+}
+
+/// Settings for `codex commit` and `/commit`.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitConfig {
+    /// Instructions for writing the message, replacing the built-in
+    /// Conventional Commits prompt. The staged changes are sent after them.
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+/// Checks run when the model finishes a task that edited files. Failures are
+/// sent back to the model for a fix-up turn before control returns.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EditChecksConfig {
+    /// Run the repository's pre-commit hooks on the edited files.
+    #[serde(default)]
+    pub pre_commit: bool,
+    /// Further commands, each an argv run in the session's working directory.
+    #[serde(default)]
+    pub commands: Vec<Vec<String>>,
+    /// Fix-up turns allowed before failures are handed to the user
+    /// (default: 2).
+    #[serde(default)]
+    pub max_fix_turns: Option<usize>,
+    /// Time limit for each check (default: 5 minutes).
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Run the checks under the session's sandbox (default: true).
+    #[serde(default = "default_true")]
+    pub sandbox: bool,
+}
+
+impl Default for EditChecksConfig {
+    fn default() -> Self {
+        Self {
+            pre_commit: false,
+            commands: Vec::new(),
+            max_fix_turns: None,
+            timeout_ms: None,
+            sandbox: true,
+        }
+    }
+}
+
+impl EditChecksConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.pre_commit || self.commands.iter().any(|command| !command.is_empty())
+    }
+}
+
+/// Limits on the session rollouts kept under the Codex home. They are applied
+/// in the background at startup and by `codex sessions gc`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SessionRetentionConfig {
+    /// Keep at most this many sessions, newest first.
+    #[serde(default)]
+    pub max_sessions: Option<usize>,
+    /// Remove sessions last updated more than this many days ago.
+    #[serde(default)]
+    pub max_age_days: Option<u64>,
+    /// Remove the oldest sessions once all of them together take more space.
+    #[serde(default)]
+    pub max_disk_bytes: Option<u64>,
+    /// Write a tool output that repeats an earlier one in the same session as
+    /// a reference to it.
+    #[serde(default = "default_true")]
+    pub dedupe_tool_outputs: bool,
+}
+
+impl Default for SessionRetentionConfig {
+    fn default() -> Self {
+        Self {
+            max_sessions: None,
+            max_age_days: None,
+            max_disk_bytes: None,
+            dedupe_tool_outputs: true,
+        }
+    }
+}
+
+impl SessionRetentionConfig {
+    /// Whether any limit is set, i.e. whether sessions are ever removed.
+    pub fn has_limits(&self) -> bool {
+        self.max_sessions.is_some() || self.max_age_days.is_some() || self.max_disk_bytes.is_some()
+    }
+}
+
+/// What the built-in shell safety classifier does with each kind of risky
+/// command it recognises.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CommandSafetyConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Recursive `rm` of `/`, the home directory or everything under them.
+    #[serde(default = "default_block")]
+    pub recursive_delete: SafetyAction,
+    /// A download from `curl` or `wget` executed by a shell or interpreter.
+    #[serde(default = "default_prompt")]
+    pub pipe_to_shell: SafetyAction,
+    /// `git push --force` and `+refspec` pushes.
+    #[serde(default = "default_prompt")]
+    pub force_push: SafetyAction,
+    /// `chmod` that makes files writable by everyone, such as `chmod 777`.
+    #[serde(default = "default_prompt")]
+    pub world_writable: SafetyAction,
+}
+
+impl Default for CommandSafetyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            recursive_delete: SafetyAction::Block,
+            pipe_to_shell: SafetyAction::Prompt,
+            force_push: SafetyAction::Prompt,
+            world_writable: SafetyAction::Prompt,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SafetyAction {
+    /// Leave the decision to the approval policy and execpolicy rules.
+    Allow,
+    /// Ask the user before running, even when the approval policy would not.
+    Prompt,
+    /// Refuse to run.
+    Block,
+}
+
+const fn default_block() -> SafetyAction {
+    SafetyAction::Block
+}
+
+const fn default_prompt() -> SafetyAction {
+    SafetyAction::Prompt
+}
+
+/// Which projects hold `apply_patch` edits for per-hunk review before they
+/// are written.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewEditsMode {
+    #[default]
+    Never,
+    /// Projects not marked `trust_level = "trusted"`.
+    Untrusted,
+    Always,
+}
+
 // ===== OTEL configuration =====
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
@@ -328,6 +1078,31 @@ pub struct OtelConfigToml {
 
     /// Optional trace exporter
     pub trace_exporter: Option<OtelExporterKind>,
+
+    /// Optional metrics exporter. Unlike traces, metrics are not sent to the
+    /// log exporter's endpoint by default.
+    pub metrics_exporter: Option<OtelExporterKind>,
+
+    /// `service.name` resource attribute. Defaults to the originator.
+    pub service_name: Option<String>,
+
+    /// How tool call arguments are written to OTEL events and logs.
+    pub tool_payloads: Option<OtelToolPayloads>,
+}
+
+/// Rewrites tool call arguments before they reach OTEL events and logs.
+/// Tools are named as the model calls them, e.g. `shell` or `apply_patch`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct OtelToolPayloads {
+    /// Tools whose arguments are replaced by their size.
+    #[serde(default)]
+    pub omit: Vec<String>,
+    /// Tools whose arguments are replaced by a SHA-256 digest and their size,
+    /// so identical calls can still be correlated.
+    #[serde(default)]
+    pub hash: Vec<String>,
+    /// Longest argument string, in bytes, logged for any other tool.
+    pub max_bytes: Option<usize>,
 }
 
 /// Effective OTEL settings after defaults are applied.
@@ -337,6 +1112,9 @@ pub struct OtelConfig {
     pub environment: String,
     pub exporter: OtelExporterKind,
     pub trace_exporter: OtelExporterKind,
+    pub metrics_exporter: OtelExporterKind,
+    pub service_name: Option<String>,
+    pub tool_payloads: OtelToolPayloads,
 }
 
 impl Default for OtelConfig {
@@ -346,6 +1124,9 @@ impl Default for OtelConfig {
             environment: DEFAULT_OTEL_ENVIRONMENT.to_owned(),
             exporter: OtelExporterKind::None,
             trace_exporter: OtelExporterKind::None,
+            metrics_exporter: OtelExporterKind::None,
+            service_name: None,
+            tool_payloads: OtelToolPayloads::default(),
         }
     }
 }
@@ -363,6 +1144,20 @@ impl Default for Notifications {
     }
 }
 
+/// How the TUI delivers desktop notifications.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationBackend {
+    /// Use OSC 9 escape codes unless the terminal is known to ignore them.
+    #[default]
+    Auto,
+    /// Always use OSC 9 escape codes.
+    Osc9,
+    /// Use the operating system's notifier: `osascript` on macOS,
+    /// `notify-send` on Linux and toasts on Windows and WSL.
+    Native,
+}
+
 /// How TUI2 should interpret mouse scroll events.
 ///
 /// Terminals generally encode both mouse wheels and trackpads as the same "scroll up/down" mouse
@@ -393,6 +1188,14 @@ pub struct Tui {
     #[serde(default)]
     pub notifications: Notifications,
 
+    /// How desktop notifications are delivered. Defaults to `auto`.
+    #[serde(default)]
+    pub notification_backend: NotificationBackend,
+
+    /// Only notify about a finished turn when it ran at least this many
+    /// seconds. Approval requests always notify. Defaults to 0.
+    pub notification_min_turn_secs: Option<u64>,
+
     /// Enable animations (welcome screen, shimmer effects, spinners).
     /// Defaults to `true`.
     #[serde(default = "default_true")]
@@ -403,6 +1206,11 @@ pub struct Tui {
     #[serde(default = "default_true")]
     pub show_tooltips: bool,
 
+    /// Show only the header of each reasoning summary; the transcript
+    /// overlay keeps the full text. Defaults to `false`.
+    #[serde(default)]
+    pub collapse_reasoning: bool,
+
     /// Override the *wheel* event density used to normalize TUI2 scrolling.
     ///
     /// Terminals generally deliver both mouse wheels and trackpads as discrete `scroll up/down`
@@ -505,6 +1313,61 @@ pub struct Tui {
     /// wheel and trackpad input.
     #[serde(default)]
     pub scroll_invert: bool,
+
+    /// Color theme: `default`, `no-color`, `high-contrast`, or the name of a
+    /// theme defined under `[tui.themes]`. Defaults to `no-color` when the
+    /// `NO_COLOR` environment variable is set and `default` otherwise.
+    pub theme: Option<String>,
+
+    /// Custom color themes, keyed by the name used in `theme`.
+    #[serde(default)]
+    pub themes: BTreeMap<String, TuiTheme>,
+
+    /// Remapped shortcuts, keyed by action (e.g. `transcript = "ctrl+y"`).
+    #[serde(default)]
+    pub keybindings: BTreeMap<String, String>,
+
+    /// Voice input for the composer. Disabled unless this table is present.
+    pub voice: Option<TuiVoice>,
+}
+
+/// A color theme defined under `[tui.themes.<name>]`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct TuiTheme {
+    /// Built-in theme this one starts from. Defaults to `default`.
+    pub base: Option<String>,
+
+    /// Replacements for the colors the TUI draws with, e.g. `cyan = "#5fafff"`.
+    /// Keys and values accept color names, `#rrggbb` and 256-color indexes.
+    #[serde(default)]
+    pub colors: BTreeMap<String, String>,
+}
+
+/// Voice input settings under `[tui.voice]`.
+///
+/// Command arguments may contain `{file}`, which is replaced with the path of
+/// the recorded WAV file.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct TuiVoice {
+    /// Command that records 16 kHz mono WAV audio to `{file}` until it is
+    /// interrupted. Defaults to SoX:
+    /// `["sox", "-q", "-d", "-c", "1", "-r", "16000", "-b", "16", "{file}"]`.
+    pub record_command: Option<Vec<String>>,
+
+    /// Local transcription command that prints the transcript of `{file}` to
+    /// stdout, e.g. whisper.cpp's `whisper-cli`. When unset, audio is sent to
+    /// the transcription API instead.
+    pub transcribe_command: Option<Vec<String>>,
+
+    /// Base URL of an OpenAI-compatible API with an `/audio/transcriptions`
+    /// endpoint. Defaults to `https://api.openai.com/v1`.
+    pub api_base_url: Option<String>,
+
+    /// Transcription model requested from the API. Defaults to `whisper-1`.
+    pub api_model: Option<String>,
+
+    /// Environment variable holding the API key. Defaults to `OPENAI_API_KEY`.
+    pub api_key_env: Option<String>,
 }
 
 const fn default_true() -> bool {
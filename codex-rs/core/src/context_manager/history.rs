@@ -1,5 +1,6 @@
 use crate::codex::TurnContext;
 use crate::context_manager::normalize;
+use crate::context_pressure::context_item_id;
 use crate::truncate::TruncationPolicy;
 use crate::truncate::approx_token_count;
 use crate::truncate::approx_tokens_from_byte_count;
@@ -9,6 +10,7 @@ use codex_protocol::models::ContentItem;
 use codex_protocol::models::FunctionCallOutputContentItem;
 use codex_protocol::models::FunctionCallOutputPayload;
 use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::ContextItemsDroppedEvent;
 use codex_protocol::protocol::TokenUsage;
 use codex_protocol::protocol::TokenUsageInfo;
 use std::ops::Deref;
@@ -86,25 +88,34 @@ impl ContextManager {
                 .unwrap_or(i64::MAX);
 
         let items_tokens = self.items.iter().fold(0i64, |acc, item| {
-            acc + match item {
-                ResponseItem::GhostSnapshot { .. } => 0,
-                ResponseItem::Reasoning {
-                    encrypted_content: Some(content),
-                    ..
-                }
-                | ResponseItem::Compaction {
-                    encrypted_content: content,
-                } => estimate_reasoning_length(content.len()) as i64,
-                item => {
-                    let serialized = serde_json::to_string(item).unwrap_or_default();
-                    i64::try_from(approx_token_count(&serialized)).unwrap_or(i64::MAX)
-                }
-            }
+            acc.saturating_add(estimate_item_tokens(item))
         });
 
         Some(base_tokens.saturating_add(items_tokens))
     }
 
+    /// Removes the items whose [`context_item_id`] is in `ids`, along with
+    /// the outputs of any dropped tool calls.
+    pub(crate) fn drop_items(&mut self, ids: &[String]) -> ContextItemsDroppedEvent {
+        let mut dropped = Vec::new();
+        self.items.retain(|item| match context_item_id(item) {
+            Some(id) if ids.contains(&id) => {
+                if !dropped.contains(&id) {
+                    dropped.push(id);
+                }
+                false
+            }
+            _ => true,
+        });
+        let not_found = ids
+            .iter()
+            .filter(|id| !dropped.contains(id))
+            .cloned()
+            .collect();
+        self.normalize_history();
+        ContextItemsDroppedEvent { dropped, not_found }
+    }
+
     pub(crate) fn remove_first_item(&mut self) {
         if !self.items.is_empty() {
             // Remove the oldest item (front of the list). Items are ordered from
@@ -283,6 +294,25 @@ fn is_api_message(message: &ResponseItem) -> bool {
     }
 }
 
+/// Coarse token estimate for one history item, as used by
+/// [`ContextManager::estimate_token_count`].
+pub(crate) fn estimate_item_tokens(item: &ResponseItem) -> i64 {
+    match item {
+        ResponseItem::GhostSnapshot { .. } => 0,
+        ResponseItem::Reasoning {
+            encrypted_content: Some(content),
+            ..
+        }
+        | ResponseItem::Compaction {
+            encrypted_content: content,
+        } => estimate_reasoning_length(content.len()) as i64,
+        item => {
+            let serialized = serde_json::to_string(item).unwrap_or_default();
+            i64::try_from(approx_token_count(&serialized)).unwrap_or(i64::MAX)
+        }
+    }
+}
+
 fn estimate_reasoning_length(encoded_len: usize) -> usize {
     encoded_len
         .saturating_mul(3)
@@ -177,6 +177,40 @@ fn remove_first_item_removes_matching_output_for_function_call() {
     assert_eq!(h.contents(), vec![]);
 }
 
+#[test]
+fn drop_items_removes_tool_calls_with_their_outputs() {
+    let call = ResponseItem::FunctionCall {
+        id: None,
+        name: "shell".to_string(),
+        arguments: "{}".to_string(),
+        call_id: "call-1".to_string(),
+    };
+    let output = ResponseItem::FunctionCallOutput {
+        call_id: "call-1".to_string(),
+        output: FunctionCallOutputPayload {
+            content: "a lot of output".to_string(),
+            ..Default::default()
+        },
+    };
+    let question = user_msg("why does the build fail?");
+    let answer = assistant_msg("A missing import.");
+    let mut h = create_history_with_items(vec![question.clone(), call, output, answer.clone()]);
+
+    let question_id = context_item_id(&question).unwrap_or_default();
+    let dropped = h.drop_items(&["call-1".to_string(), "call-2".to_string()]);
+    assert_eq!(
+        dropped,
+        ContextItemsDroppedEvent {
+            dropped: vec!["call-1".to_string()],
+            not_found: vec!["call-2".to_string()],
+        }
+    );
+    assert_eq!(h.contents(), vec![question, answer.clone()]);
+
+    h.drop_items(&[question_id]);
+    assert_eq!(h.contents(), vec![answer]);
+}
+
 #[test]
 fn remove_first_item_removes_matching_call_for_output() {
     let items = vec![
@@ -0,0 +1,288 @@
+//! Context window pressure: which of the configured thresholds the
+//! conversation has reached, where its tokens go, and which history items a
+//! front-end can offer to drop with `Op::DropContextItems`.
+
+use std::collections::HashMap;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::ContextItemKind;
+use codex_protocol::protocol::ContextItemSummary;
+use codex_protocol::protocol::ContextUsageBreakdown;
+use codex_protocol::protocol::ENVIRONMENT_CONTEXT_OPEN_TAG;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::context_manager::estimate_item_tokens;
+use crate::truncate::approx_token_count;
+use crate::user_instructions::SkillInstructions;
+use crate::user_instructions::UserInstructions;
+
+/// Percentages of the context window that trigger `ContextPressure` when
+/// `context_pressure_thresholds` is unset.
+pub(crate) const DEFAULT_CONTEXT_PRESSURE_THRESHOLDS: [u8; 2] = [70, 90];
+/// Items listed in a `ContextPressure` event.
+const LARGEST_ITEMS: usize = 10;
+const PREVIEW_CHARS: usize = 80;
+
+/// The highest of `thresholds` that `used_tokens` has reached.
+pub(crate) fn crossed_threshold(
+    thresholds: &[u8],
+    used_tokens: i64,
+    context_window: i64,
+) -> Option<u8> {
+    if context_window <= 0 {
+        return None;
+    }
+    thresholds
+        .iter()
+        .copied()
+        .filter(|threshold| {
+            used_tokens.saturating_mul(100) >= i64::from(*threshold) * context_window
+        })
+        .max()
+}
+
+/// Estimated tokens of each part of the prompt.
+pub(crate) fn breakdown(
+    items: &[ResponseItem],
+    base_instructions: &str,
+    pinned_tokens: i64,
+) -> ContextUsageBreakdown {
+    let mut breakdown = ContextUsageBreakdown {
+        instructions: i64::try_from(approx_token_count(base_instructions)).unwrap_or(i64::MAX),
+        pinned: pinned_tokens,
+        ..Default::default()
+    };
+    for item in items {
+        let tokens = estimate_item_tokens(item);
+        let bucket = if is_instructions(item) {
+            &mut breakdown.instructions
+        } else if is_tool_output(item) {
+            &mut breakdown.tool_outputs
+        } else {
+            &mut breakdown.history
+        };
+        *bucket = bucket.saturating_add(tokens);
+    }
+    breakdown
+}
+
+/// The `limit` largest droppable items, biggest first. A tool call and its
+/// output are listed together under the call id.
+pub(crate) fn largest_items(items: &[ResponseItem], limit: usize) -> Vec<ContextItemSummary> {
+    let mut summaries: Vec<ContextItemSummary> = Vec::new();
+    let mut index_by_id: HashMap<String, usize> = HashMap::new();
+    for item in items {
+        let Some(id) = context_item_id(item) else {
+            continue;
+        };
+        let tokens = estimate_item_tokens(item);
+        if let Some(&index) = index_by_id.get(&id) {
+            let summary = &mut summaries[index];
+            summary.tokens = summary.tokens.saturating_add(tokens);
+            if summary.preview.is_empty() {
+                summary.preview = preview(item);
+            }
+            continue;
+        }
+        index_by_id.insert(id.clone(), summaries.len());
+        summaries.push(ContextItemSummary {
+            id,
+            kind: kind(item),
+            tokens,
+            preview: preview(item),
+        });
+    }
+    summaries.sort_by(|a, b| b.tokens.cmp(&a.tokens));
+    summaries.truncate(limit);
+    summaries
+}
+
+/// [`largest_items`] with the number of items an event lists.
+pub(crate) fn largest_items_for_event(items: &[ResponseItem]) -> Vec<ContextItemSummary> {
+    largest_items(items, LARGEST_ITEMS)
+}
+
+/// The id `Op::DropContextItems` knows `item` by: the call id for tool calls
+/// and their outputs, else the item's own id, else a digest of its contents.
+/// Instructions and snapshots have none, since they can't be dropped.
+pub(crate) fn context_item_id(item: &ResponseItem) -> Option<String> {
+    if is_instructions(item) {
+        return None;
+    }
+    match item {
+        ResponseItem::FunctionCall { call_id, .. }
+        | ResponseItem::FunctionCallOutput { call_id, .. }
+        | ResponseItem::CustomToolCall { call_id, .. }
+        | ResponseItem::CustomToolCallOutput { call_id, .. } => Some(call_id.clone()),
+        ResponseItem::LocalShellCall { call_id, id, .. } => call_id.clone().or_else(|| id.clone()),
+        ResponseItem::Reasoning { id, .. } if !id.is_empty() => Some(id.clone()),
+        ResponseItem::Message { id: Some(id), .. }
+        | ResponseItem::WebSearchCall { id: Some(id), .. } => Some(id.clone()),
+        ResponseItem::Message { .. }
+        | ResponseItem::Reasoning { .. }
+        | ResponseItem::WebSearchCall { .. } => {
+            let serialized = serde_json::to_string(item).ok()?;
+            let digest = Sha256::digest(serialized.as_bytes());
+            Some(format!("item_{}", &format!("{digest:x}")[..12]))
+        }
+        ResponseItem::GhostSnapshot { .. }
+        | ResponseItem::Compaction { .. }
+        | ResponseItem::Other => None,
+    }
+}
+
+/// Developer messages and the user-role messages Codex injects at the start
+/// of a session: AGENTS.md, skills and the environment context.
+fn is_instructions(item: &ResponseItem) -> bool {
+    let ResponseItem::Message { role, content, .. } = item else {
+        return false;
+    };
+    match role.as_str() {
+        "developer" | "system" => true,
+        "user" => {
+            UserInstructions::is_user_instructions(content)
+                || SkillInstructions::is_skill_instructions(content)
+                || content.iter().any(|content| {
+                    matches!(content, ContentItem::InputText { text }
+                        if text.trim_start().starts_with(ENVIRONMENT_CONTEXT_OPEN_TAG))
+                })
+        }
+        _ => false,
+    }
+}
+
+fn is_tool_output(item: &ResponseItem) -> bool {
+    matches!(
+        item,
+        ResponseItem::FunctionCallOutput { .. } | ResponseItem::CustomToolCallOutput { .. }
+    )
+}
+
+fn kind(item: &ResponseItem) -> ContextItemKind {
+    match item {
+        ResponseItem::Message { role, .. } if role == "user" => ContextItemKind::UserMessage,
+        ResponseItem::Message { role, .. } if role == "assistant" => ContextItemKind::AgentMessage,
+        ResponseItem::Reasoning { .. } => ContextItemKind::Reasoning,
+        ResponseItem::FunctionCall { .. }
+        | ResponseItem::FunctionCallOutput { .. }
+        | ResponseItem::CustomToolCall { .. }
+        | ResponseItem::CustomToolCallOutput { .. }
+        | ResponseItem::LocalShellCall { .. } => ContextItemKind::ToolCall,
+        _ => ContextItemKind::Other,
+    }
+}
+
+fn preview(item: &ResponseItem) -> String {
+    let text = match item {
+        ResponseItem::Message { content, .. } => content
+            .iter()
+            .find_map(|content| match content {
+                ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                    Some(text.clone())
+                }
+                ContentItem::InputImage { .. } => None,
+            })
+            .unwrap_or_else(|| "[image]".to_string()),
+        ResponseItem::FunctionCall { name, .. } | ResponseItem::CustomToolCall { name, .. } => {
+            name.clone()
+        }
+        ResponseItem::LocalShellCall { .. } => "local_shell".to_string(),
+        _ => String::new(),
+    };
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    fn message(role: &str, text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    fn tool_call(call_id: &str, output: &str) -> [ResponseItem; 2] {
+        [
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: "{}".to_string(),
+                call_id: call_id.to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: call_id.to_string(),
+                output: FunctionCallOutputPayload {
+                    content: output.to_string(),
+                    ..Default::default()
+                },
+            },
+        ]
+    }
+
+    #[test]
+    fn thresholds_report_the_highest_one_reached() {
+        assert_eq!(crossed_threshold(&[70, 90], 600, 1000), None);
+        assert_eq!(crossed_threshold(&[70, 90], 700, 1000), Some(70));
+        assert_eq!(crossed_threshold(&[70, 90], 950, 1000), Some(90));
+        assert_eq!(crossed_threshold(&[], 950, 1000), None);
+        assert_eq!(crossed_threshold(&[70], 950, 0), None);
+    }
+
+    #[test]
+    fn breakdown_separates_instructions_and_tool_outputs() {
+        let [call, output] = tool_call("call-1", &"x".repeat(4000));
+        let items = vec![
+            message(
+                "user",
+                "<environment_context>\n  <cwd>/repo</cwd>\n</environment_context>",
+            ),
+            message("user", "fix the build"),
+            call,
+            output.clone(),
+        ];
+        let breakdown = breakdown(&items, "base", 7);
+        assert_eq!(breakdown.pinned, 7);
+        assert_eq!(breakdown.tool_outputs, estimate_item_tokens(&output));
+        assert!(breakdown.instructions > 1);
+        assert!(breakdown.history < breakdown.tool_outputs);
+    }
+
+    #[test]
+    fn largest_items_pair_calls_with_outputs_and_skip_instructions() {
+        let [call, output] = tool_call("call-1", &"x".repeat(4000));
+        let items = vec![
+            message("developer", &"y".repeat(8000)),
+            message("user", "fix the build"),
+            call.clone(),
+            output.clone(),
+        ];
+        let largest = largest_items(&items, 1);
+        assert_eq!(
+            largest,
+            vec![ContextItemSummary {
+                id: "call-1".to_string(),
+                kind: ContextItemKind::ToolCall,
+                tokens: estimate_item_tokens(&call) + estimate_item_tokens(&output),
+                preview: "shell".to_string(),
+            }]
+        );
+        assert_eq!(context_item_id(&items[0]), None);
+        assert_eq!(
+            context_item_id(&items[1]).map(|id| id.len()),
+            Some("item_".len() + 12)
+        );
+    }
+}
@@ -185,7 +185,7 @@ impl ConversationManager {
         rollout_path: PathBuf,
         auth_manager: Arc<AuthManager>,
     ) -> CodexResult<NewConversation> {
-        let initial_history = RolloutRecorder::get_rollout_history(&rollout_path).await?;
+        let initial_history = RolloutRecorder::get_resume_history(&rollout_path).await?;
         self.resume_conversation_with_history(config, initial_history, auth_manager)
             .await
     }
@@ -0,0 +1,407 @@
+//! Secrets Codex keeps on the user's behalf, managed with `codex auth`.
+//!
+//! A model provider's API key is stored under the name of its `env_key`
+//! variable and used whenever that variable is unset, so keys no longer need
+//! to live in shell profiles. Each secret goes to the OS keychain (macOS
+//! Keychain, Windows Credential Manager or the Secret Service) when
+//! `cli_auth_credentials_store` allows it. Otherwise it is sealed with the
+//! storage encryption key and written to `$CODEX_HOME/credentials.json`,
+//! which also lists the keychain entries, since keychains cannot be
+//! enumerated.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+
+use codex_keyring_store::DefaultKeyringStore;
+use codex_keyring_store::KeyringStore;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use tracing::warn;
+
+use crate::auth::AuthCredentialsStoreMode;
+use crate::config::find_codex_home;
+use crate::storage_encryption;
+use crate::storage_encryption::PASSPHRASE_ENV_VAR;
+
+const INDEX_FILE: &str = "credentials.json";
+const KEYRING_SERVICE: &str = "Codex Credentials";
+
+/// Secrets this process already loaded, so provider requests don't go back
+/// to the keychain every time.
+static LOADED: LazyLock<Mutex<HashMap<String, String>>> = LazyLock::new(Mutex::default);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialLocation {
+    Keyring,
+    EncryptedFile,
+}
+
+impl std::fmt::Display for CredentialLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Keyring => f.write_str("OS keychain"),
+            Self::EncryptedFile => f.write_str("encrypted file"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StoredCredential {
+    pub name: String,
+    pub location: CredentialLocation,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexFile {
+    #[serde(default)]
+    credentials: BTreeMap<String, IndexEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    location: CredentialLocation,
+    /// The sealed secret, for entries kept in this file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CredentialStore {
+    codex_home: PathBuf,
+    mode: AuthCredentialsStoreMode,
+    keyring: Arc<dyn KeyringStore>,
+    passphrase: Option<String>,
+}
+
+impl CredentialStore {
+    pub fn new(codex_home: PathBuf, mode: AuthCredentialsStoreMode) -> Self {
+        Self::with_keyring(
+            codex_home,
+            mode,
+            Arc::new(DefaultKeyringStore),
+            std::env::var(PASSPHRASE_ENV_VAR).ok(),
+        )
+    }
+
+    fn with_keyring(
+        codex_home: PathBuf,
+        mode: AuthCredentialsStoreMode,
+        keyring: Arc<dyn KeyringStore>,
+        passphrase: Option<String>,
+    ) -> Self {
+        Self {
+            codex_home,
+            mode,
+            keyring,
+            passphrase,
+        }
+    }
+
+    /// Every stored secret, sorted by name.
+    pub fn list(&self) -> io::Result<Vec<StoredCredential>> {
+        Ok(self
+            .read_index()?
+            .credentials
+            .into_iter()
+            .map(|(name, entry)| StoredCredential {
+                name,
+                location: entry.location,
+            })
+            .collect())
+    }
+
+    pub fn get(&self, name: &str) -> io::Result<Option<String>> {
+        let index = self.read_index()?;
+        let Some(entry) = index.credentials.get(name) else {
+            return Ok(None);
+        };
+        match entry.location {
+            CredentialLocation::Keyring => self
+                .keyring
+                .load(KEYRING_SERVICE, &self.account(name))
+                .map_err(|err| {
+                    io::Error::other(format!(
+                        "failed to load {name} from the OS keychain: {}",
+                        err.message()
+                    ))
+                }),
+            CredentialLocation::EncryptedFile => {
+                let sealed = entry.value.as_deref().ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{INDEX_FILE} has no value for {name}"),
+                    )
+                })?;
+                self.cipher()?.decrypt_line(sealed).map(Some)
+            }
+        }
+    }
+
+    /// Stores `secret` under `name`, replacing any previous value, and
+    /// returns where it ended up.
+    pub fn set(&self, name: &str, secret: &str) -> io::Result<CredentialLocation> {
+        let mut index = self.read_index()?;
+        let entry = match self.mode {
+            AuthCredentialsStoreMode::Keyring => self.save_to_keyring(name, secret)?,
+            AuthCredentialsStoreMode::File => self.seal(secret)?,
+            AuthCredentialsStoreMode::Auto => match self.save_to_keyring(name, secret) {
+                Ok(entry) => entry,
+                Err(err) => {
+                    warn!("{err}; falling back to an encrypted file");
+                    self.seal(secret)?
+                }
+            },
+        };
+
+        // Don't leave an old copy behind in the keychain.
+        let previous = index.credentials.insert(name.to_string(), entry.clone());
+        if previous.is_some_and(|previous| previous.location == CredentialLocation::Keyring)
+            && entry.location != CredentialLocation::Keyring
+            && let Err(err) = self.keyring.delete(KEYRING_SERVICE, &self.account(name))
+        {
+            warn!(
+                "failed to remove the old {name} from the OS keychain: {}",
+                err.message()
+            );
+        }
+        self.write_index(&index)?;
+        Ok(entry.location)
+    }
+
+    /// Removes `name`, returning whether it was stored.
+    pub fn remove(&self, name: &str) -> io::Result<bool> {
+        let mut index = self.read_index()?;
+        let Some(entry) = index.credentials.remove(name) else {
+            return Ok(false);
+        };
+        if entry.location == CredentialLocation::Keyring {
+            self.keyring
+                .delete(KEYRING_SERVICE, &self.account(name))
+                .map_err(|err| {
+                    io::Error::other(format!(
+                        "failed to remove {name} from the OS keychain: {}",
+                        err.message()
+                    ))
+                })?;
+        }
+        self.write_index(&index)?;
+        Ok(true)
+    }
+
+    fn save_to_keyring(&self, name: &str, secret: &str) -> io::Result<IndexEntry> {
+        self.keyring
+            .save(KEYRING_SERVICE, &self.account(name), secret)
+            .map_err(|err| {
+                io::Error::other(format!(
+                    "failed to save {name} to the OS keychain: {}",
+                    err.message()
+                ))
+            })?;
+        Ok(IndexEntry {
+            location: CredentialLocation::Keyring,
+            value: None,
+        })
+    }
+
+    fn seal(&self, secret: &str) -> io::Result<IndexEntry> {
+        Ok(IndexEntry {
+            location: CredentialLocation::EncryptedFile,
+            value: Some(self.cipher()?.encrypt_line(secret)?),
+        })
+    }
+
+    fn cipher(&self) -> io::Result<Arc<storage_encryption::StorageCipher>> {
+        storage_encryption::secrets_cipher(
+            &self.codex_home,
+            self.keyring.as_ref(),
+            self.passphrase.as_deref(),
+        )
+    }
+
+    /// Keychain account for `name`, scoped to this Codex home so separate
+    /// homes keep separate secrets.
+    fn account(&self, name: &str) -> String {
+        let canonical = self
+            .codex_home
+            .canonicalize()
+            .unwrap_or_else(|_| self.codex_home.clone());
+        let digest = Sha256::digest(canonical.to_string_lossy().as_bytes());
+        let hex = format!("{digest:x}");
+        format!("{}|{name}", hex.get(..16).unwrap_or(&hex))
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.codex_home.join(INDEX_FILE)
+    }
+
+    fn read_index(&self) -> io::Result<IndexFile> {
+        read_index(&self.index_path())
+    }
+
+    /// Replaces the index atomically, so a crash mid-write cannot leave a
+    /// truncated file that would hide every stored credential. The temporary
+    /// file is created with mode 0600 on Unix.
+    fn write_index(&self, index: &IndexFile) -> io::Result<()> {
+        std::fs::create_dir_all(&self.codex_home)?;
+        let contents = serde_json::to_string_pretty(index)?;
+        let mut tmp = tempfile::NamedTempFile::new_in(&self.codex_home)?;
+        tmp.write_all(contents.as_bytes())?;
+        tmp.as_file().sync_all()?;
+        tmp.persist(self.index_path()).map_err(|err| err.error)?;
+        Ok(())
+    }
+}
+
+fn read_index(path: &Path) -> io::Result<IndexFile> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid {}: {err}", path.display()),
+            )
+        }),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(IndexFile::default()),
+        Err(err) => Err(err),
+    }
+}
+
+/// The secret stored under `name` in the Codex home, if any. Failures are
+/// logged rather than returned, since callers fall back to asking the user
+/// to set the environment variable.
+pub(crate) fn stored_secret(name: &str) -> Option<String> {
+    if let Some(secret) = LOADED
+        .lock()
+        .ok()
+        .and_then(|loaded| loaded.get(name).cloned())
+    {
+        return Some(secret);
+    }
+    let codex_home = find_codex_home().ok()?;
+    // Reads go wherever the entry says it is, so the mode doesn't matter.
+    let store = CredentialStore::new(codex_home, AuthCredentialsStoreMode::Auto);
+    match store.get(name) {
+        Ok(Some(secret)) => {
+            if let Ok(mut loaded) = LOADED.lock() {
+                loaded.insert(name.to_string(), secret.clone());
+            }
+            Some(secret)
+        }
+        Ok(None) => None,
+        Err(err) => {
+            warn!("failed to read stored credential {name}: {err}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_keyring_store::tests::MockKeyringStore;
+    use keyring::Error as KeyringError;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    #[test]
+    fn keyring_entries_are_listed_and_removed() -> anyhow::Result<()> {
+        let home = tempdir()?;
+        let keyring = Arc::new(MockKeyringStore::default());
+        let store = CredentialStore::with_keyring(
+            home.path().to_path_buf(),
+            AuthCredentialsStoreMode::Keyring,
+            keyring.clone(),
+            None,
+        );
+
+        assert_eq!(
+            store.set("OPENROUTER_API_KEY", "sk-or-1")?,
+            CredentialLocation::Keyring
+        );
+        assert_eq!(
+            store.get("OPENROUTER_API_KEY")?,
+            Some("sk-or-1".to_string())
+        );
+        let index = std::fs::read_to_string(home.path().join(INDEX_FILE))?;
+        assert!(!index.contains("sk-or-1"), "{index}");
+        assert_eq!(
+            store.list()?,
+            vec![StoredCredential {
+                name: "OPENROUTER_API_KEY".to_string(),
+                location: CredentialLocation::Keyring,
+            }]
+        );
+
+        assert!(store.remove("OPENROUTER_API_KEY")?);
+        assert_eq!(
+            keyring.saved_value(&store.account("OPENROUTER_API_KEY")),
+            None
+        );
+        assert_eq!(store.get("OPENROUTER_API_KEY")?, None);
+        assert!(!store.remove("OPENROUTER_API_KEY")?);
+        Ok(())
+    }
+
+    #[test]
+    fn auto_falls_back_to_an_encrypted_file() -> anyhow::Result<()> {
+        let home = tempdir()?;
+        let keyring = Arc::new(MockKeyringStore::default());
+        let store = CredentialStore::with_keyring(
+            home.path().to_path_buf(),
+            AuthCredentialsStoreMode::Auto,
+            keyring.clone(),
+            Some("correct horse".to_string()),
+        );
+        keyring.set_error(
+            &store.account("GROQ_API_KEY"),
+            KeyringError::Invalid("error".into(), "save".into()),
+        );
+
+        assert_eq!(
+            store.set("GROQ_API_KEY", "gsk-1")?,
+            CredentialLocation::EncryptedFile
+        );
+        let index = std::fs::read_to_string(home.path().join(INDEX_FILE))?;
+        assert!(!index.contains("gsk-1"), "{index}");
+        assert_eq!(store.get("GROQ_API_KEY")?, Some("gsk-1".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn index_is_replaced_without_leftovers() -> anyhow::Result<()> {
+        let home = tempdir()?;
+        let store = CredentialStore::with_keyring(
+            home.path().to_path_buf(),
+            AuthCredentialsStoreMode::Keyring,
+            Arc::new(MockKeyringStore::default()),
+            None,
+        );
+
+        store.set("OPENROUTER_API_KEY", "sk-or-1")?;
+        store.set("GROQ_API_KEY", "gsk-1")?;
+
+        let entries: Vec<_> = std::fs::read_dir(home.path())?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<io::Result<_>>()?;
+        assert_eq!(entries, vec![std::ffi::OsString::from(INDEX_FILE)]);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(home.path().join(INDEX_FILE))?
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        assert_eq!(store.list()?.len(), 2);
+        Ok(())
+    }
+}
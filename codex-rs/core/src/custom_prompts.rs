@@ -1,3 +1,5 @@
+use crate::git_info::resolve_root_git_project_for_trust;
+use codex_protocol::custom_prompts::COMMAND_ARGUMENTS_PLACEHOLDER;
 use codex_protocol::custom_prompts::CustomPrompt;
 use std::collections::HashSet;
 use std::path::Path;
@@ -12,6 +14,54 @@ pub fn default_prompts_dir() -> Option<PathBuf> {
         .map(|home| home.join("prompts"))
 }
 
+/// Directory holding custom slash commands, both under `$CODEX_HOME` and
+/// under a project's `.codex` directory.
+const COMMANDS_DIR_NAME: &str = "commands";
+
+/// Discover custom slash commands in `$CODEX_HOME/commands` and in the
+/// nearest `.codex/commands` between `cwd` and its repository root. A project
+/// command replaces a user command with the same name. Sorted by name.
+pub async fn discover_commands(codex_home: &Path, cwd: &Path) -> Vec<CustomPrompt> {
+    let mut commands = discover_prompts_in(&codex_home.join(COMMANDS_DIR_NAME)).await;
+    if let Some(dir) = project_commands_dir(cwd) {
+        let project = discover_prompts_in(&dir).await;
+        commands.retain(|command| !project.iter().any(|p| p.name == command.name));
+        commands.extend(project);
+        commands.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    commands
+}
+
+fn project_commands_dir(cwd: &Path) -> Option<PathBuf> {
+    let repo_root = resolve_root_git_project_for_trust(cwd);
+    for dir in cwd.ancestors() {
+        let candidate = dir.join(".codex").join(COMMANDS_DIR_NAME);
+        if candidate.is_dir() {
+            return Some(candidate);
+        }
+        if repo_root.as_deref().is_none_or(|root| root == dir) {
+            break;
+        }
+    }
+    None
+}
+
+/// Expand a custom command invoked as `/name arguments`. Each
+/// `$ARGUMENTS` in the body is replaced by `arguments`; a body without the
+/// placeholder gets the arguments appended so they are never dropped.
+pub fn expand_command(command: &CustomPrompt, arguments: &str) -> String {
+    let arguments = arguments.trim();
+    if command.content.contains(COMMAND_ARGUMENTS_PLACEHOLDER) {
+        command
+            .content
+            .replace(COMMAND_ARGUMENTS_PLACEHOLDER, arguments)
+    } else if arguments.is_empty() {
+        command.content.clone()
+    } else {
+        format!("{}\n\n{arguments}", command.content.trim_end())
+    }
+}
+
 /// Discover prompt files in the given directory, returning entries sorted by name.
 /// Non-files are ignored. If the directory does not exist or cannot be read, returns empty.
 pub async fn discover_prompts_in(dir: &Path) -> Vec<CustomPrompt> {
@@ -233,6 +283,51 @@ mod tests {
         assert_eq!(p.content, "Actual body with $1 and $ARGUMENTS");
     }
 
+    #[tokio::test]
+    async fn project_commands_override_user_commands() {
+        let home = tempdir().expect("create TempDir");
+        let project = tempdir().expect("create TempDir");
+        let user_dir = home.path().join("commands");
+        let project_dir = project.path().join(".codex").join("commands");
+        fs::create_dir_all(&user_dir).unwrap();
+        fs::create_dir_all(&project_dir).unwrap();
+        fs::write(user_dir.join("deploy.md"), b"user deploy").unwrap();
+        fs::write(user_dir.join("triage.md"), b"user triage").unwrap();
+        fs::write(project_dir.join("deploy.md"), b"project deploy").unwrap();
+
+        let commands = discover_commands(home.path(), project.path()).await;
+
+        let found: Vec<(&str, &str)> = commands
+            .iter()
+            .map(|c| (c.name.as_str(), c.content.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![("deploy", "project deploy"), ("triage", "user triage")]
+        );
+    }
+
+    #[test]
+    fn expand_command_substitutes_or_appends_arguments() {
+        let command = |content: &str| CustomPrompt {
+            name: "fix".to_string(),
+            path: "/tmp/fix.md".into(),
+            content: content.to_string(),
+            description: None,
+            argument_hint: None,
+        };
+
+        assert_eq!(
+            expand_command(&command("Fix issue $ARGUMENTS now"), " #42 "),
+            "Fix issue #42 now"
+        );
+        assert_eq!(
+            expand_command(&command("Fix the build.\n"), "quickly"),
+            "Fix the build.\n\nquickly"
+        );
+        assert_eq!(expand_command(&command("Fix it"), ""), "Fix it");
+    }
+
     #[test]
     fn parse_frontmatter_preserves_body_newlines() {
         let content = "---\r\ndescription: \"Line endings\"\r\nargument_hint: \"[arg]\"\r\n---\r\nFirst line\r\nSecond line\r\n";
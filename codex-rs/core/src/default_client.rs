@@ -1,6 +1,10 @@
+use crate::model_provider_info::ModelProviderInfo;
 use crate::spawn::CODEX_SANDBOX_ENV_VAR;
 use codex_client::CodexHttpClient;
 pub use codex_client::CodexRequestBuilder;
+use codex_client::HttpClientOptions;
+use codex_client::http_client_options;
+pub use codex_client::init_http_client_options;
 use reqwest::header::HeaderValue;
 use std::sync::LazyLock;
 use std::sync::Mutex;
@@ -133,7 +137,38 @@ pub fn create_client() -> CodexHttpClient {
     CodexHttpClient::new(inner)
 }
 
+/// Builds a client honoring the process-wide `[http]` proxy and CA
+/// settings.
 pub fn build_reqwest_client() -> reqwest::Client {
+    build_reqwest_client_with(http_client_options())
+}
+
+/// Like [`build_reqwest_client`], with the model provider's own `http`
+/// settings taking precedence over `[http]`.
+pub fn build_reqwest_client_for_provider(provider: &ModelProviderInfo) -> reqwest::Client {
+    let options = match &provider.http {
+        Some(overrides) => http_client_options().with_overrides(overrides),
+        None => http_client_options(),
+    };
+    build_reqwest_client_with(options)
+}
+
+/// Like [`build_reqwest_client`], but redirects are handed back to the
+/// caller instead of followed, for callers that vet every hop.
+pub fn build_reqwest_client_without_redirects() -> reqwest::Client {
+    client_builder(http_client_options())
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+}
+
+fn build_reqwest_client_with(options: HttpClientOptions) -> reqwest::Client {
+    client_builder(options)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+}
+
+fn client_builder(options: HttpClientOptions) -> reqwest::ClientBuilder {
     use reqwest::header::HeaderMap;
 
     let mut headers = HeaderMap::new();
@@ -144,11 +179,11 @@ pub fn build_reqwest_client() -> reqwest::Client {
         // Set UA via dedicated helper to avoid header validation pitfalls
         .user_agent(ua)
         .default_headers(headers);
+    builder = options.apply(builder);
     if is_sandboxed() {
         builder = builder.no_proxy();
     }
-
-    builder.build().unwrap_or_else(|_| reqwest::Client::new())
+    builder
 }
 
 fn is_sandboxed() -> bool {
@@ -0,0 +1,384 @@
+//! Environment and configuration checks behind `codex doctor`.
+//!
+//! Each check exercises the real code path where it can: the sandbox check
+//! runs a trivial command under the platform sandbox, the MCP check starts
+//! every configured server, and the provider check sends an authenticated
+//! request to the provider's `/models` endpoint.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use codex_api::AuthProvider as _;
+use codex_app_server_protocol::AuthMode;
+use http::Method;
+use serde::Serialize;
+
+use crate::AuthManager;
+use crate::api_bridge::auth_provider_from_auth;
+use crate::config::Config;
+use crate::default_client::build_reqwest_client;
+use crate::mcp::check_mcp_startup;
+use crate::model_provider_info::WireApi;
+use crate::protocol::SandboxPolicy;
+
+/// How long the sandbox probe and the provider request may take.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DoctorCheck {
+    /// Area of the check, such as `sandbox` or `mcp:docs`.
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// What to do about a warning or failure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl DoctorCheck {
+    fn ok(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: CheckStatus::Ok,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: impl Into<String>, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: impl Into<String>, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Runs every check against a loaded `config`. Loading the config is the
+/// caller's job, so a config error is reported before any of these run.
+pub async fn run_checks(config: &Config) -> Vec<DoctorCheck> {
+    let mut checks = vec![check_sandbox(config).await];
+    checks.extend(check_provider(config).await);
+    checks.extend(check_mcp_servers(config).await);
+    checks.extend(check_hooks(config));
+    checks
+}
+
+async fn check_sandbox(config: &Config) -> DoctorCheck {
+    let check = probe_sandbox(config).await;
+    if check.status == CheckStatus::Ok
+        && matches!(config.sandbox_policy.get(), SandboxPolicy::DangerFullAccess)
+    {
+        return DoctorCheck::warn(
+            "sandbox",
+            format!(
+                "{}, but sandbox_mode is danger-full-access so commands run unsandboxed",
+                check.detail
+            ),
+            "Use `sandbox_mode = \"workspace-write\"` unless Codex already runs in an isolated environment.",
+        );
+    }
+    check
+}
+
+#[cfg(target_os = "linux")]
+async fn probe_sandbox(config: &Config) -> DoctorCheck {
+    let Some(exe) = config.codex_linux_sandbox_exe.as_ref() else {
+        return DoctorCheck::fail(
+            "sandbox",
+            "codex-linux-sandbox helper not found",
+            "Run doctor through the `codex` binary so it can locate its sandbox helper.",
+        );
+    };
+    let child = crate::landlock::spawn_command_under_linux_sandbox(
+        exe,
+        vec!["true".to_string()],
+        config.cwd.clone(),
+        &SandboxPolicy::ReadOnly,
+        &config.cwd,
+        crate::spawn::StdioPolicy::RedirectForShellTool,
+        std::collections::HashMap::new(),
+    )
+    .await;
+    match run_probe(child).await {
+        Ok(()) => DoctorCheck::ok("sandbox", "Landlock and seccomp are available"),
+        Err(err) => DoctorCheck::fail(
+            "sandbox",
+            format!("Landlock/seccomp sandbox failed: {err}"),
+            "Check that the kernel supports Landlock (5.13+) and that it is not disabled by the container runtime.",
+        ),
+    }
+}
+
+#[cfg(target_os = "macos")]
+async fn probe_sandbox(config: &Config) -> DoctorCheck {
+    let child = crate::seatbelt::spawn_command_under_seatbelt(
+        vec!["/usr/bin/true".to_string()],
+        config.cwd.clone(),
+        &SandboxPolicy::ReadOnly,
+        &config.cwd,
+        crate::spawn::StdioPolicy::RedirectForShellTool,
+        std::collections::HashMap::new(),
+    )
+    .await;
+    match run_probe(child).await {
+        Ok(()) => DoctorCheck::ok("sandbox", "Seatbelt (sandbox-exec) is available"),
+        Err(err) => DoctorCheck::fail(
+            "sandbox",
+            format!("Seatbelt sandbox failed: {err}"),
+            "Codex cannot nest Seatbelt; if it runs inside another sandbox, use `sandbox_mode = \"danger-full-access\"` there.",
+        ),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+async fn probe_sandbox(config: &Config) -> DoctorCheck {
+    match crate::get_platform_sandbox() {
+        Some(_)
+            if config
+                .features
+                .enabled(crate::features::Feature::WindowsSandboxElevated) =>
+        {
+            DoctorCheck::ok("sandbox", "Windows sandbox enabled (elevated)")
+        }
+        Some(_) => DoctorCheck::ok("sandbox", "Windows restricted-token sandbox enabled"),
+        None => DoctorCheck::warn(
+            "sandbox",
+            "no sandbox is enabled on this platform; commands run unsandboxed",
+            "Enable `experimental_windows_sandbox` under `[features]` to sandbox commands.",
+        ),
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+async fn run_probe(child: std::io::Result<tokio::process::Child>) -> Result<(), String> {
+    let child = child.map_err(|err| err.to_string())?;
+    let output = tokio::time::timeout(PROBE_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| "timed out".to_string())?
+        .map_err(|err| err.to_string())?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(match stderr.trim() {
+        "" => format!("probe exited with {}", output.status),
+        stderr => stderr.to_string(),
+    })
+}
+
+async fn check_provider(config: &Config) -> Vec<DoctorCheck> {
+    let provider = &config.model_provider;
+    let name = format!("provider:{}", config.model_provider_id);
+
+    let auth_manager = AuthManager::from_config(config, true);
+    let auth = auth_manager.auth();
+    let auth_check = if !provider.requires_openai_auth {
+        match provider.api_key() {
+            Ok(Some(_)) => DoctorCheck::ok("auth", "API key found in the provider's `env_key`"),
+            Ok(None) => DoctorCheck::ok("auth", "provider needs no credentials"),
+            Err(err) => DoctorCheck::fail(
+                "auth",
+                err.to_string(),
+                "Export the variable named by the provider's `env_key`.",
+            ),
+        }
+    } else {
+        match auth.as_ref().map(|auth| auth.mode) {
+            Some(AuthMode::ChatGPT) => DoctorCheck::ok("auth", "signed in with ChatGPT"),
+            Some(AuthMode::ApiKey) => DoctorCheck::ok("auth", "using an API key"),
+            None => DoctorCheck::fail(
+                "auth",
+                "not signed in",
+                "Run `codex login`, or set OPENAI_API_KEY.",
+            ),
+        }
+    };
+    if auth_check.status == CheckStatus::Fail {
+        return vec![auth_check];
+    }
+
+    let mut checks = vec![auth_check];
+    let connectivity = async {
+        let auth_mode = auth.as_ref().map(|auth| auth.mode);
+        let api_provider = provider
+            .to_api_provider(auth_mode)
+            .map_err(|err| err.to_string())?;
+        // Only OpenAI-backed providers get Codex credentials.
+        let auth = auth.clone().filter(|_| provider.requires_openai_auth);
+        let api_auth = auth_provider_from_auth(auth, provider)
+            .await
+            .map_err(|err| err.to_string())?;
+        let request = api_provider.build_request(Method::GET, "models");
+        let mut builder = build_reqwest_client()
+            .get(&request.url)
+            .headers(request.headers)
+            .timeout(PROBE_TIMEOUT);
+        if let Some(token) = api_auth.bearer_token() {
+            builder = builder.bearer_auth(token);
+        }
+        if let Some(account_id) = api_auth.account_id() {
+            builder = builder.header("ChatGPT-Account-ID", account_id);
+        }
+        let response = builder.send().await.map_err(|err| err.to_string())?;
+        Ok::<_, String>((api_provider.base_url, response.status()))
+    };
+    checks.push(match connectivity.await {
+        Ok((base_url, status)) if status.is_success() => {
+            DoctorCheck::ok(name, format!("{base_url} is reachable"))
+        }
+        Ok((base_url, status))
+            if matches!(status.as_u16(), 401 | 403)
+                && matches!(provider.wire_api, WireApi::Responses | WireApi::Chat) =>
+        {
+            DoctorCheck::fail(
+                name,
+                format!("{base_url} rejected the credentials ({status})"),
+                "Sign in again with `codex login`, or check the provider's API key.",
+            )
+        }
+        Ok((base_url, status)) => DoctorCheck::ok(
+            name,
+            format!("{base_url} is reachable (GET /models returned {status})"),
+        ),
+        Err(err) => DoctorCheck::fail(
+            name,
+            format!("request failed: {err}"),
+            "Check network access, proxy settings and the provider's `base_url`.",
+        ),
+    });
+    checks
+}
+
+async fn check_mcp_servers(config: &Config) -> Vec<DoctorCheck> {
+    let summary = check_mcp_startup(config).await;
+    let mut checks: Vec<DoctorCheck> = summary
+        .ready
+        .into_iter()
+        .map(|server| DoctorCheck::ok(format!("mcp:{server}"), "started"))
+        .collect();
+    checks.extend(summary.failed.into_iter().map(|failure| {
+        DoctorCheck::fail(
+            format!("mcp:{}", failure.server),
+            failure.error,
+            format!(
+                "Inspect the server with `codex mcp get {}` or disable it with `enabled = false`.",
+                failure.server
+            ),
+        )
+    }));
+    checks.extend(summary.cancelled.into_iter().map(|server| {
+        DoctorCheck::warn(
+            format!("mcp:{server}"),
+            "startup was cancelled",
+            "Run doctor again.",
+        )
+    }));
+    checks.sort_by(|a, b| a.name.cmp(&b.name));
+    checks
+}
+
+fn check_hooks(config: &Config) -> Vec<DoctorCheck> {
+    let configured = config
+        .hooks
+        .iter()
+        .enumerate()
+        .map(|(idx, hook)| (format!("hook:hooks[{idx}]"), Some(&hook.command)));
+    [
+        ("hook:notify", config.notify.as_ref()),
+        ("hook:pre_approval_hook", config.pre_approval_hook.as_ref()),
+        (
+            "hook:budget.on_exceeded",
+            config.budget.on_exceeded.as_ref(),
+        ),
+    ]
+    .into_iter()
+    .map(|(name, command)| (name.to_string(), command))
+    .chain(configured)
+    .filter_map(|(name, command)| Some((name, command?.first()?)))
+    .map(
+        |(name, program)| match resolve_program(program, &config.cwd) {
+            Ok(path) => DoctorCheck::ok(name, format!("{} is executable", path.display())),
+            Err(err) => DoctorCheck::fail(
+                name,
+                format!("`{program}` {err}"),
+                "Fix the path or mark the script executable with `chmod +x`.",
+            ),
+        },
+    )
+    .collect()
+}
+
+/// Resolves `program` the way spawning it would: paths as given (relative to
+/// `cwd`), bare names through `PATH`.
+fn resolve_program(program: &str, cwd: &Path) -> Result<PathBuf, String> {
+    if !program.contains(std::path::MAIN_SEPARATOR) && !program.contains('/') {
+        return which::which(program).map_err(|_| "was not found on PATH".to_string());
+    }
+    let path = cwd.join(program);
+    let metadata = std::fs::metadata(&path).map_err(|_| "does not exist".to_string())?;
+    if !metadata.is_file() {
+        return Err("is not a file".to_string());
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return Err("is not executable".to_string());
+        }
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[cfg(unix)]
+    #[test]
+    fn resolves_hook_programs() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let script = dir.path().join("notify.sh");
+        std::fs::write(&script, "#!/bin/sh\n").expect("write script");
+        assert_eq!(
+            resolve_program("./notify.sh", dir.path()),
+            Err("is not executable".to_string())
+        );
+
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+        assert_eq!(
+            resolve_program("./notify.sh", dir.path()),
+            Ok(dir.path().join("./notify.sh"))
+        );
+        assert_eq!(
+            resolve_program("./missing.sh", dir.path()),
+            Err("does not exist".to_string())
+        );
+        assert_eq!(
+            resolve_program("definitely-not-a-codex-hook", dir.path()),
+            Err("was not found on PATH".to_string())
+        );
+    }
+}
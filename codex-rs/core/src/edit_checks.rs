@@ -0,0 +1,352 @@
+//! `[edit_checks]`: the repository's pre-commit hooks and configured commands,
+//! run when the model finishes a task that edited files. Failures are handed
+//! back to the model for a fix-up turn.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use codex_protocol::models::SandboxPermissions;
+use codex_utils_absolute_path::AbsolutePathBuf;
+use codex_utils_string::take_last_bytes_at_char_boundary;
+
+use crate::config::types::EditChecksConfig;
+use crate::error::CodexErr;
+use crate::error::SandboxErr;
+use crate::exec::ExecExpiration;
+use crate::exec::ExecParams;
+use crate::exec::process_exec_tool_call;
+use crate::git_info::get_git_repo_root;
+use crate::hooks::HookSandbox;
+use crate::protocol::SandboxPolicy;
+use crate::resume_check::edited_files;
+
+pub(crate) const DEFAULT_MAX_FIX_TURNS: usize = 2;
+const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const PRE_COMMIT_CONFIG: &str = ".pre-commit-config.yaml";
+
+/// Only the end of a failing check's output is sent; that is where the
+/// errors usually are.
+const MAX_FAILURE_OUTPUT_BYTES: usize = 8 * 1024;
+
+/// A command to run and where to run it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EditCheck {
+    pub(crate) command: Vec<String>,
+    pub(crate) cwd: PathBuf,
+}
+
+impl EditCheck {
+    pub(crate) fn label(&self) -> String {
+        match self.command.first().map(String::as_str) {
+            Some("pre-commit") => "pre-commit".to_string(),
+            _ => self.command.join(" "),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CheckFailure {
+    pub(crate) label: String,
+    /// How the check failed, e.g. "exited with code 1".
+    pub(crate) reason: String,
+    pub(crate) output: String,
+}
+
+/// The checks to run for a task whose cumulative diff is `unified_diff`.
+/// pre-commit is skipped outside a git repository, when the repository has
+/// no `.pre-commit-config.yaml`, or when every edited file was deleted.
+pub(crate) fn checks(config: &EditChecksConfig, cwd: &Path, unified_diff: &str) -> Vec<EditCheck> {
+    let mut checks = Vec::new();
+    if config.pre_commit
+        && let Some(root) = get_git_repo_root(cwd)
+        && root.join(PRE_COMMIT_CONFIG).is_file()
+    {
+        let files: Vec<String> = edited_files(unified_diff)
+            .into_iter()
+            .filter_map(|(path, deleted)| (!deleted).then_some(path))
+            .collect();
+        if !files.is_empty() {
+            let mut command = vec![
+                "pre-commit".to_string(),
+                "run".to_string(),
+                "--files".to_string(),
+            ];
+            command.extend(files);
+            checks.push(EditCheck { command, cwd: root });
+        }
+    }
+    checks.extend(
+        config
+            .commands
+            .iter()
+            .filter(|command| !command.is_empty())
+            .map(|command| EditCheck {
+                command: command.clone(),
+                cwd: cwd.to_path_buf(),
+            }),
+    );
+    checks
+}
+
+/// Whether the task edited what `check` would run: `.pre-commit-config.yaml`
+/// for pre-commit, or a file a command names, such as its script. Running
+/// such a check would run whatever the model wrote, so it is skipped.
+pub(crate) fn edited_by_task(check: &EditCheck, unified_diff: &str) -> bool {
+    let root = get_git_repo_root(&check.cwd).unwrap_or_else(|| check.cwd.clone());
+    let edited: Vec<PathBuf> = edited_files(unified_diff)
+        .into_iter()
+        .filter_map(|(path, _)| resolve(&root, &path))
+        .collect();
+    let names: Vec<PathBuf> = if check.command.first().map(String::as_str) == Some("pre-commit") {
+        resolve(&check.cwd, PRE_COMMIT_CONFIG).into_iter().collect()
+    } else {
+        check
+            .command
+            .iter()
+            .map(|word| {
+                word.rsplit_once('=')
+                    .map_or(word.as_str(), |(_, value)| value)
+            })
+            .filter_map(|word| resolve(&check.cwd, word))
+            .collect()
+    };
+    names.iter().any(|name| edited.contains(name))
+}
+
+fn resolve(base: &Path, path: &str) -> Option<PathBuf> {
+    AbsolutePathBuf::resolve_path_against_base(path, base)
+        .ok()
+        .map(AbsolutePathBuf::into_path_buf)
+}
+
+/// Runs every check, under the session's sandbox unless `config.sandbox` is
+/// turned off, and returns the ones that failed.
+pub(crate) async fn run(
+    checks: &[EditCheck],
+    config: &EditChecksConfig,
+    sandbox: &HookSandbox,
+) -> Vec<CheckFailure> {
+    let timeout = config
+        .timeout_ms
+        .map_or(DEFAULT_CHECK_TIMEOUT, Duration::from_millis);
+    let policy = if config.sandbox {
+        sandbox.policy.clone()
+    } else {
+        SandboxPolicy::DangerFullAccess
+    };
+    let mut failures = Vec::new();
+    for check in checks {
+        if let Some(failure) = run_one(check, timeout, &policy, sandbox).await {
+            failures.push(failure);
+        }
+    }
+    failures
+}
+
+async fn run_one(
+    check: &EditCheck,
+    timeout: Duration,
+    policy: &SandboxPolicy,
+    sandbox: &HookSandbox,
+) -> Option<CheckFailure> {
+    let params = ExecParams {
+        command: check.command.clone(),
+        cwd: check.cwd.clone(),
+        expiration: ExecExpiration::Timeout(timeout),
+        env: std::env::vars().collect::<HashMap<_, _>>(),
+        sandbox_permissions: SandboxPermissions::UseDefault,
+        justification: None,
+        arg0: None,
+    };
+    let (reason, output) = match process_exec_tool_call(
+        params,
+        policy,
+        &sandbox.cwd,
+        &sandbox.codex_linux_sandbox_exe,
+        None,
+    )
+    .await
+    {
+        Ok(output) if output.exit_code == 0 => return None,
+        Ok(output) => (
+            format!("exited with code {}", output.exit_code),
+            output.aggregated_output.text,
+        ),
+        Err(CodexErr::Sandbox(SandboxErr::Timeout { output })) => (
+            format!("timed out after {}s", timeout.as_secs()),
+            output.aggregated_output.text,
+        ),
+        Err(CodexErr::Sandbox(SandboxErr::Denied { output })) => (
+            "was blocked by the sandbox".to_string(),
+            output.aggregated_output.text,
+        ),
+        Err(err) => (format!("could not run: {err}"), String::new()),
+    };
+    Some(CheckFailure {
+        label: check.label(),
+        reason,
+        output: output_tail(&output),
+    })
+}
+
+/// The developer message that asks the model to fix `failures`.
+pub(crate) fn fix_up_instructions(failures: &[CheckFailure]) -> String {
+    let mut text = String::from(
+        "The checks configured for this repository failed after your edits. \
+         Fix the problems below, then finish your reply as before.",
+    );
+    for failure in failures {
+        text.push_str(&format!("\n\n`{}` {}", failure.label, failure.reason));
+        if failure.output.trim().is_empty() {
+            text.push('.');
+        } else {
+            text.push_str(&format!(":\n```\n{}\n```", failure.output.trim_end()));
+        }
+    }
+    text
+}
+
+fn output_tail(output: &str) -> String {
+    if output.len() <= MAX_FAILURE_OUTPUT_BYTES {
+        return output.to_string();
+    }
+    format!(
+        "[earlier output omitted]\n{}",
+        take_last_bytes_at_char_boundary(output, MAX_FAILURE_OUTPUT_BYTES)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    const DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs\n\
+        index 1111111..2222222 100644\n\
+        --- a/src/lib.rs\n\
+        +++ b/src/lib.rs\n\
+        @@ -1 +1 @@\n\
+        -old\n\
+        +new\n\
+        diff --git a/old.txt b/old.txt\n\
+        deleted file mode 100644\n\
+        index 3333333..0000000\n";
+
+    #[test]
+    fn pre_commit_runs_on_edited_files_when_the_repo_uses_it() {
+        let repo = TempDir::new().expect("tempdir");
+        std::fs::create_dir(repo.path().join(".git")).expect("create .git");
+        let config = EditChecksConfig {
+            pre_commit: true,
+            commands: vec![vec!["cargo".to_string(), "check".to_string()], vec![]],
+            ..Default::default()
+        };
+
+        let cargo_check = EditCheck {
+            command: vec!["cargo".to_string(), "check".to_string()],
+            cwd: repo.path().to_path_buf(),
+        };
+        assert_eq!(
+            checks(&config, repo.path(), DIFF),
+            vec![cargo_check.clone()]
+        );
+
+        std::fs::write(repo.path().join(PRE_COMMIT_CONFIG), "repos: []\n")
+            .expect("write pre-commit config");
+        let pre_commit = EditCheck {
+            command: ["pre-commit", "run", "--files", "src/lib.rs"]
+                .map(String::from)
+                .to_vec(),
+            cwd: repo.path().to_path_buf(),
+        };
+        assert_eq!(
+            checks(&config, repo.path(), DIFF),
+            vec![pre_commit.clone(), cargo_check]
+        );
+        assert_eq!(pre_commit.label(), "pre-commit");
+    }
+
+    #[test]
+    fn checks_the_task_edited_are_detected() {
+        let repo = TempDir::new().expect("tempdir");
+        std::fs::create_dir(repo.path().join(".git")).expect("create .git");
+        let root = repo.path().to_path_buf();
+        let diff = |path: &str| format!("diff --git a/{path} b/{path}\n");
+        let pre_commit = EditCheck {
+            command: ["pre-commit", "run", "--files", "src/lib.rs"]
+                .map(String::from)
+                .to_vec(),
+            cwd: root.clone(),
+        };
+        let script = EditCheck {
+            command: ["sh", "./scripts/../scripts/lint.sh"]
+                .map(String::from)
+                .to_vec(),
+            cwd: root.clone(),
+        };
+
+        assert!(edited_by_task(&pre_commit, &diff(PRE_COMMIT_CONFIG)));
+        assert!(!edited_by_task(&pre_commit, &diff("src/lib.rs")));
+        assert!(edited_by_task(&script, &diff("scripts/lint.sh")));
+        assert!(!edited_by_task(&script, &diff("src/lib.rs")));
+    }
+
+    #[test]
+    fn failures_are_described_for_the_model() {
+        let text = fix_up_instructions(&[
+            CheckFailure {
+                label: "cargo clippy".to_string(),
+                reason: "exited with code 101".to_string(),
+                output: "error: unused variable `x`\n".to_string(),
+            },
+            CheckFailure {
+                label: "lint".to_string(),
+                reason: "could not run: No such file".to_string(),
+                output: String::new(),
+            },
+        ]);
+        assert!(text.ends_with(
+            "`cargo clippy` exited with code 101:\n```\nerror: unused variable `x`\n```\n\n\
+             `lint` could not run: No such file."
+        ));
+        assert!(
+            output_tail(&"x".repeat(MAX_FAILURE_OUTPUT_BYTES + 1))
+                .starts_with("[earlier output omitted]\n")
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn failing_commands_are_reported_with_their_output() {
+        let dir = TempDir::new().expect("tempdir");
+        let sandbox = HookSandbox {
+            policy: SandboxPolicy::new_read_only_policy(),
+            cwd: dir.path().to_path_buf(),
+            codex_linux_sandbox_exe: None,
+        };
+        let check = |script: &str| EditCheck {
+            command: ["sh", "-c", script].map(String::from).to_vec(),
+            cwd: dir.path().to_path_buf(),
+        };
+        let failures = run(
+            &[check("true"), check("echo 'lint: bad'; exit 3")],
+            &EditChecksConfig {
+                sandbox: false,
+                ..Default::default()
+            },
+            &sandbox,
+        )
+        .await;
+        assert_eq!(
+            failures,
+            vec![CheckFailure {
+                label: "sh -c echo 'lint: bad'; exit 3".to_string(),
+                reason: "exited with code 3".to_string(),
+                output: "lint: bad\n".to_string(),
+            }]
+        );
+    }
+}
@@ -13,6 +13,9 @@ use codex_protocol::user_input::UserInput;
 use tracing::warn;
 use uuid::Uuid;
 
+use crate::file_mentions::is_file_mentions;
+use crate::pinned_context::is_pinned_context;
+use crate::semantic_index::is_retrieved_context;
 use crate::user_instructions::SkillInstructions;
 use crate::user_instructions::UserInstructions;
 use crate::user_shell_command::is_user_shell_command_text;
@@ -26,6 +29,9 @@ fn is_session_prefix(text: &str) -> bool {
 fn parse_user_message(message: &[ContentItem]) -> Option<UserMessageItem> {
     if UserInstructions::is_user_instructions(message)
         || SkillInstructions::is_skill_instructions(message)
+        || is_pinned_context(message)
+        || is_retrieved_context(message)
+        || is_file_mentions(message)
     {
         return None;
     }
@@ -216,6 +222,14 @@ mod tests {
                     text: "<user_shell_command>echo 42</user_shell_command>".to_string(),
                 }],
             },
+            ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "<retrieved_context>\nsrc/lib.rs:1-1\nfn main() {}\n</retrieved_context>"
+                        .to_string(),
+                }],
+            },
         ];
 
         for item in items {
@@ -32,6 +32,9 @@ use crate::sandboxing::SandboxPermissions;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
 use crate::text_encoding::bytes_to_string_smart;
+use codex_utils_pty::TerminalSize;
+
+mod pty;
 
 pub const DEFAULT_EXEC_COMMAND_TIMEOUT_MS: u64 = 10_000;
 
@@ -216,6 +219,46 @@ pub(crate) async fn execute_exec_env(
     finalize_exec_result(raw_output_result, sandbox, duration)
 }
 
+/// Like [`execute_exec_env`], but runs the command attached to a
+/// pseudo-terminal of the given size. Falls back to pipes where a PTY cannot
+/// be used.
+pub(crate) async fn execute_exec_env_in_pty(
+    env: ExecEnv,
+    sandbox_policy: &SandboxPolicy,
+    size: TerminalSize,
+    stdout_stream: Option<StdoutStream>,
+) -> Result<ExecToolCallOutput> {
+    if env.sandbox == SandboxType::WindowsRestrictedToken || !codex_utils_pty::conpty_supported() {
+        return execute_exec_env(env, sandbox_policy, stdout_stream).await;
+    }
+
+    let ExecEnv {
+        command,
+        cwd,
+        env,
+        expiration,
+        sandbox,
+        sandbox_permissions,
+        justification,
+        arg0,
+    } = env;
+
+    let params = ExecParams {
+        command,
+        cwd,
+        expiration,
+        env,
+        sandbox_permissions,
+        justification,
+        arg0,
+    };
+
+    let start = Instant::now();
+    let raw_output_result = pty::exec_in_pty(params, size, stdout_stream).await;
+    let duration = start.elapsed();
+    finalize_exec_result(raw_output_result, sandbox, duration)
+}
+
 #[cfg(target_os = "windows")]
 async fn exec_windows_sandbox(
     params: ExecParams,
@@ -751,7 +794,7 @@ fn synthetic_exit_status(code: i32) -> ExitStatus {
 }
 
 #[cfg(unix)]
-fn kill_child_process_group(child: &mut Child) -> io::Result<()> {
+pub(crate) fn kill_child_process_group(child: &mut Child) -> io::Result<()> {
     use std::io::ErrorKind;
 
     if let Some(pid) = child.id() {
@@ -778,7 +821,7 @@ fn kill_child_process_group(child: &mut Child) -> io::Result<()> {
 }
 
 #[cfg(not(unix))]
-fn kill_child_process_group(_: &mut Child) -> io::Result<()> {
+pub(crate) fn kill_child_process_group(_: &mut Child) -> io::Result<()> {
     Ok(())
 }
 
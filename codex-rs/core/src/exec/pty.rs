@@ -0,0 +1,279 @@
+//! Runs shell tool commands attached to a pseudo-terminal (the `shell_pty`
+//! feature) so programs that check `isatty` behave as they would for a user,
+//! then strips the terminal control sequences from what the model sees.
+
+use std::io;
+use std::process::ExitStatus;
+use std::time::Duration;
+
+use codex_utils_pty::SpawnedPty;
+use codex_utils_pty::TerminalSize;
+use tokio::sync::broadcast::error::RecvError;
+
+use super::EXIT_CODE_SIGNAL_BASE;
+use super::ExecParams;
+use super::MAX_EXEC_OUTPUT_DELTAS_PER_CALL;
+use super::RawExecToolCallOutput;
+use super::StdoutStream;
+use super::StreamOutput;
+use super::TIMEOUT_CODE;
+use super::synthetic_exit_status;
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::ExecCommandOutputDeltaEvent;
+use crate::protocol::ExecOutputStream;
+
+/// Output printed after the process exits can still be in flight from the
+/// PTY reader thread.
+const POST_EXIT_OUTPUT_GRACE: Duration = Duration::from_millis(100);
+
+/// Sent right after spawn so programs reading stdin see end-of-file, like
+/// the `/dev/null` stdin of the pipe-based path.
+const EOT: u8 = 0x04;
+
+const PTY_ENV: [(&str, &str); 2] = [("PAGER", "cat"), ("GIT_PAGER", "cat")];
+const DEFAULT_TERM: &str = "xterm-256color";
+
+pub(super) async fn exec_in_pty(
+    params: ExecParams,
+    size: TerminalSize,
+    stdout_stream: Option<StdoutStream>,
+) -> Result<RawExecToolCallOutput> {
+    let ExecParams {
+        command,
+        cwd,
+        mut env,
+        arg0,
+        expiration,
+        ..
+    } = params;
+
+    let (program, args) = command.split_first().ok_or_else(|| {
+        CodexErr::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "command args are empty",
+        ))
+    })?;
+    for (key, value) in PTY_ENV {
+        env.insert(key.to_string(), value.to_string());
+    }
+    env.entry("TERM".to_string())
+        .or_insert_with(|| DEFAULT_TERM.to_string());
+
+    let SpawnedPty {
+        session,
+        mut output_rx,
+        mut exit_rx,
+    } = codex_utils_pty::spawn_pty_process_with_size(program, args, &cwd, &env, &arg0, size)
+        .await
+        .map_err(|err| CodexErr::Io(io::Error::other(err)))?;
+    let _ = session.writer_sender().send(vec![EOT]).await;
+
+    let mut collected = Vec::new();
+    let mut emitted_deltas = 0;
+    let expiration = expiration.wait();
+    tokio::pin!(expiration);
+    let (exit_status, timed_out) = loop {
+        tokio::select! {
+            chunk = output_rx.recv() => match chunk {
+                Ok(chunk) => {
+                    emit_delta(stdout_stream.as_ref(), &chunk, &mut emitted_deltas).await;
+                    collected.extend_from_slice(&chunk);
+                }
+                Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => {
+                    let code = (&mut exit_rx).await.unwrap_or(-1);
+                    break (exit_status_from_code(code), false);
+                }
+            },
+            code = &mut exit_rx => break (exit_status_from_code(code.unwrap_or(-1)), false),
+            _ = &mut expiration => {
+                session.terminate();
+                break (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + TIMEOUT_CODE), true);
+            }
+        }
+    };
+
+    while let Ok(Ok(chunk)) = tokio::time::timeout(POST_EXIT_OUTPUT_GRACE, output_rx.recv()).await {
+        emit_delta(stdout_stream.as_ref(), &chunk, &mut emitted_deltas).await;
+        collected.extend_from_slice(&chunk);
+    }
+    session.terminate();
+
+    let text = sanitize_terminal_output(&collected);
+    Ok(RawExecToolCallOutput {
+        exit_status,
+        stdout: StreamOutput {
+            text: text.clone(),
+            truncated_after_lines: None,
+        },
+        // A terminal merges stderr into the same stream as stdout.
+        stderr: StreamOutput {
+            text: Vec::new(),
+            truncated_after_lines: None,
+        },
+        aggregated_output: StreamOutput {
+            text,
+            truncated_after_lines: None,
+        },
+        timed_out,
+    })
+}
+
+async fn emit_delta(stream: Option<&StdoutStream>, chunk: &[u8], emitted_deltas: &mut usize) {
+    let Some(stream) = stream else {
+        return;
+    };
+    if *emitted_deltas >= MAX_EXEC_OUTPUT_DELTAS_PER_CALL {
+        return;
+    }
+    let event = Event {
+        id: stream.sub_id.clone(),
+        msg: EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
+            call_id: stream.call_id.clone(),
+            stream: ExecOutputStream::Stdout,
+            chunk: chunk.to_vec(),
+        }),
+    };
+    let _ = stream.tx_event.send(event).await;
+    *emitted_deltas += 1;
+}
+
+#[cfg(unix)]
+fn exit_status_from_code(code: i32) -> ExitStatus {
+    // Wait statuses keep the exit code in the second byte.
+    synthetic_exit_status((code & 0xff) << 8)
+}
+
+#[cfg(windows)]
+fn exit_status_from_code(code: i32) -> ExitStatus {
+    synthetic_exit_status(code)
+}
+
+/// Reduces raw terminal output to the text a user would have been left
+/// looking at: escape sequences are dropped, `\r\n` becomes `\n`, a bare `\r`
+/// discards the line it returns over (progress bars) and backspace erases the
+/// previous character.
+fn sanitize_terminal_output(raw: &[u8]) -> Vec<u8> {
+    const ESC: u8 = 0x1b;
+    const BEL: u8 = 0x07;
+    const BACKSPACE: u8 = 0x08;
+
+    let mut out = Vec::with_capacity(raw.len());
+    let mut line: Vec<u8> = Vec::new();
+    let mut i = 0;
+    while i < raw.len() {
+        let byte = raw[i];
+        i += 1;
+        match byte {
+            b'\n' => {
+                out.append(&mut line);
+                out.push(b'\n');
+            }
+            b'\r' => {
+                if raw.get(i) != Some(&b'\n') {
+                    line.clear();
+                }
+            }
+            BACKSPACE => {
+                // Drop a whole UTF-8 character, not just its last byte.
+                while line.pop().is_some_and(|b| b & 0xc0 == 0x80) {}
+            }
+            ESC => match raw.get(i) {
+                Some(b'[') => {
+                    i += 1;
+                    while i < raw.len() && !(0x40..=0x7e).contains(&raw[i]) {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                Some(b']' | b'P' | b'X' | b'^' | b'_') => {
+                    i += 1;
+                    while i < raw.len() {
+                        if raw[i] == BEL {
+                            i += 1;
+                            break;
+                        }
+                        if raw[i] == ESC && raw.get(i + 1) == Some(&b'\\') {
+                            i += 2;
+                            break;
+                        }
+                        i += 1;
+                    }
+                }
+                Some(b'(' | b')' | b'*' | b'+') => i += 2,
+                Some(_) => i += 1,
+                None => {}
+            },
+            b'\t' => line.push(byte),
+            _ if byte < 0x20 || byte == 0x7f => {}
+            _ => line.push(byte),
+        }
+    }
+    out.append(&mut line);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn sanitize(raw: &str) -> String {
+        String::from_utf8(sanitize_terminal_output(raw.as_bytes())).unwrap_or_default()
+    }
+
+    #[test]
+    fn strips_colors_and_terminal_titles() {
+        assert_eq!(
+            sanitize("\x1b]0;build\x07\x1b[1;32mok\x1b[0m test passed\r\n"),
+            "ok test passed\n"
+        );
+    }
+
+    #[test]
+    fn keeps_only_the_final_state_of_redrawn_lines() {
+        assert_eq!(
+            sanitize("downloading  10%\rdownloading  90%\r\x1b[Kdone\r\nnext\n"),
+            "done\nnext\n"
+        );
+        assert_eq!(sanitize("héé\x08\x08llo"), "hllo");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn runs_commands_attached_to_a_terminal() -> anyhow::Result<()> {
+        let params = ExecParams {
+            command: vec![
+                "bash".to_string(),
+                "-c".to_string(),
+                "[ -t 1 ] && echo tty; tput cols; read line || echo eof; exit 3".to_string(),
+            ],
+            cwd: std::env::current_dir()?,
+            expiration: 10_000.into(),
+            env: std::env::vars().collect(),
+            sandbox_permissions: crate::sandboxing::SandboxPermissions::UseDefault,
+            justification: None,
+            arg0: None,
+        };
+        let output = exec_in_pty(
+            params,
+            TerminalSize {
+                rows: 30,
+                cols: 132,
+            },
+            None,
+        )
+        .await?;
+
+        assert_eq!(
+            String::from_utf8_lossy(&output.aggregated_output.text),
+            "tty\n132\neof\n"
+        );
+        assert_eq!(output.exit_status.code(), Some(3));
+        assert!(!output.timed_out);
+        Ok(())
+    }
+}
@@ -3,6 +3,8 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use crate::command_safety::classifier::CommandClassification;
+use crate::command_safety::classifier::classify_command;
 use crate::command_safety::is_dangerous_command::requires_initial_appoval;
 use codex_execpolicy::AmendError;
 use codex_execpolicy::Decision;
@@ -21,6 +23,7 @@ use tokio::sync::RwLock;
 use tokio::task::spawn_blocking;
 
 use crate::bash::parse_shell_lc_plain_commands;
+use crate::config::types::CommandSafetyConfig;
 use crate::features::Feature;
 use crate::features::Features;
 use crate::sandboxing::SandboxPermissions;
@@ -217,17 +220,36 @@ fn derive_prompt_reason(evaluation: &Evaluation) -> Option<String> {
     })
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn create_exec_approval_requirement_for_command(
     exec_policy: &Arc<RwLock<Policy>>,
     features: &Features,
+    command_safety: &CommandSafetyConfig,
+    approved_prefixes: &[Vec<String>],
     command: &[String],
     approval_policy: AskForApproval,
     sandbox_policy: &SandboxPolicy,
     sandbox_permissions: SandboxPermissions,
 ) -> ExecApprovalRequirement {
+    let classification = classify_command(command, command_safety);
+    if let CommandClassification::Blocked(rule) = classification {
+        return ExecApprovalRequirement::Forbidden {
+            reason: format!("command safety blocks this command: {}", rule.description()),
+        };
+    }
+
     let commands = parse_shell_lc_plain_commands(command).unwrap_or_else(|| vec![command.to_vec()]);
     let heuristics_fallback = |cmd: &[String]| {
-        if requires_initial_appoval(approval_policy, sandbox_policy, cmd, sandbox_permissions) {
+        // Approved prefixes only skip the prompt; unlike execpolicy allow
+        // rules they keep the sandbox, so they cannot satisfy a request to
+        // escalate.
+        let approved = !sandbox_permissions.requires_escalated_permissions()
+            && approved_prefixes
+                .iter()
+                .any(|prefix| cmd.starts_with(prefix));
+        if !approved
+            && requires_initial_appoval(approval_policy, sandbox_policy, cmd, sandbox_permissions)
+        {
             Decision::Prompt
         } else {
             Decision::Allow
@@ -236,6 +258,27 @@ pub(crate) async fn create_exec_approval_requirement_for_command(
     let policy = exec_policy.read().await;
     let evaluation = policy.check_multiple(commands.iter(), &heuristics_fallback);
 
+    // The classifier can only tighten what execpolicy decided: an execpolicy
+    // `forbidden` still wins, but an allow rule does not skip the prompt.
+    if let CommandClassification::NeedsApproval(rule) = classification
+        && evaluation.decision != Decision::Forbidden
+    {
+        let reason = format!(
+            "command safety requires approval for this command: {}",
+            rule.description()
+        );
+        return if matches!(approval_policy, AskForApproval::Never) {
+            ExecApprovalRequirement::Forbidden {
+                reason: format!("{reason}, but AskForApproval is set to Never"),
+            }
+        } else {
+            ExecApprovalRequirement::NeedsApproval {
+                reason: Some(reason),
+                proposed_execpolicy_amendment: None,
+            }
+        };
+    }
+
     match evaluation.decision {
         Decision::Forbidden => ExecApprovalRequirement::Forbidden {
             reason: FORBIDDEN_REASON.to_string(),
@@ -270,6 +313,45 @@ pub(crate) async fn create_exec_approval_requirement_for_command(
     }
 }
 
+/// Whether `requirement` prompts only because the call asked to run outside
+/// the sandbox: kept in the sandbox, the same command would run unprompted.
+/// Granted permissions may stand in for such a prompt, and for no other.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn prompts_only_for_escalation(
+    exec_policy: &Arc<RwLock<Policy>>,
+    features: &Features,
+    command_safety: &CommandSafetyConfig,
+    approved_prefixes: &[Vec<String>],
+    command: &[String],
+    approval_policy: AskForApproval,
+    sandbox_policy: &SandboxPolicy,
+    sandbox_permissions: SandboxPermissions,
+    requirement: &ExecApprovalRequirement,
+) -> bool {
+    if !sandbox_permissions.requires_escalated_permissions()
+        || !matches!(requirement, ExecApprovalRequirement::NeedsApproval { .. })
+    {
+        return false;
+    }
+    matches!(
+        create_exec_approval_requirement_for_command(
+            exec_policy,
+            features,
+            command_safety,
+            approved_prefixes,
+            command,
+            approval_policy,
+            sandbox_policy,
+            SandboxPermissions::UseDefault,
+        )
+        .await,
+        ExecApprovalRequirement::Skip {
+            bypass_sandbox: false,
+            ..
+        }
+    )
+}
+
 async fn collect_policy_files(dir: &Path) -> Result<Vec<PathBuf>, ExecPolicyError> {
     let mut read_dir = match fs::read_dir(dir).await {
         Ok(read_dir) => read_dir,
@@ -436,6 +518,8 @@ prefix_rule(pattern=["rm"], decision="forbidden")
         let requirement = create_exec_approval_requirement_for_command(
             &policy,
             &Features::with_defaults(),
+            &CommandSafetyConfig::default(),
+            &[],
             &forbidden_script,
             AskForApproval::OnRequest,
             &SandboxPolicy::DangerFullAccess,
@@ -451,6 +535,112 @@ prefix_rule(pattern=["rm"], decision="forbidden")
         );
     }
 
+    #[tokio::test]
+    async fn command_safety_blocks_and_overrides_allow_rules() {
+        let policy_src = r#"prefix_rule(pattern=["git", "push"], decision="allow")"#;
+        let mut parser = PolicyParser::new();
+        parser
+            .parse("test.rules", policy_src)
+            .expect("parse policy");
+        let policy = Arc::new(RwLock::new(parser.build()));
+        let force_push = vec![
+            "bash".to_string(),
+            "-lc".to_string(),
+            "git push --force origin main".to_string(),
+        ];
+
+        let requirement = create_exec_approval_requirement_for_command(
+            &policy,
+            &Features::with_defaults(),
+            &CommandSafetyConfig::default(),
+            &[],
+            &force_push,
+            AskForApproval::OnRequest,
+            &SandboxPolicy::DangerFullAccess,
+            SandboxPermissions::UseDefault,
+        )
+        .await;
+        assert_eq!(
+            requirement,
+            ExecApprovalRequirement::NeedsApproval {
+                reason: Some(
+                    "command safety requires approval for this command: git force push".to_string()
+                ),
+                proposed_execpolicy_amendment: None,
+            }
+        );
+
+        let requirement = create_exec_approval_requirement_for_command(
+            &policy,
+            &Features::with_defaults(),
+            &CommandSafetyConfig::default(),
+            &[],
+            &["rm".to_string(), "-rf".to_string(), "/".to_string()],
+            AskForApproval::Never,
+            &SandboxPolicy::DangerFullAccess,
+            SandboxPermissions::UseDefault,
+        )
+        .await;
+        assert_eq!(
+            requirement,
+            ExecApprovalRequirement::Forbidden {
+                reason: "command safety blocks this command: recursive delete of / or the home \
+                         directory"
+                    .to_string()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn approved_prefixes_skip_the_prompt_but_keep_the_sandbox() {
+        let approved = vec![vec!["npm".to_string(), "test".to_string()]];
+        let command = vec![
+            "bash".to_string(),
+            "-lc".to_string(),
+            "npm test -- --watch=false".to_string(),
+        ];
+
+        let requirement = create_exec_approval_requirement_for_command(
+            &Arc::new(RwLock::new(Policy::empty())),
+            &Features::with_defaults(),
+            &CommandSafetyConfig::default(),
+            &approved,
+            &command,
+            AskForApproval::UnlessTrusted,
+            &SandboxPolicy::ReadOnly,
+            SandboxPermissions::UseDefault,
+        )
+        .await;
+        assert_eq!(
+            requirement,
+            ExecApprovalRequirement::Skip {
+                bypass_sandbox: false,
+                proposed_execpolicy_amendment: Some(ExecPolicyAmendment::new(vec![
+                    "npm".to_string(),
+                    "test".to_string(),
+                    "--".to_string(),
+                    "--watch=false".to_string(),
+                ])),
+            }
+        );
+
+        let requirement = create_exec_approval_requirement_for_command(
+            &Arc::new(RwLock::new(Policy::empty())),
+            &Features::with_defaults(),
+            &CommandSafetyConfig::default(),
+            &approved,
+            &["npm".to_string(), "install".to_string()],
+            AskForApproval::UnlessTrusted,
+            &SandboxPolicy::ReadOnly,
+            SandboxPermissions::UseDefault,
+        )
+        .await;
+        assert!(matches!(
+            requirement,
+            ExecApprovalRequirement::NeedsApproval { .. }
+        ));
+    }
+
     #[tokio::test]
     async fn exec_approval_requirement_prefers_execpolicy_match() {
         let policy_src = r#"prefix_rule(pattern=["rm"], decision="prompt")"#;
@@ -464,6 +654,8 @@ prefix_rule(pattern=["rm"], decision="forbidden")
         let requirement = create_exec_approval_requirement_for_command(
             &policy,
             &Features::with_defaults(),
+            &CommandSafetyConfig::default(),
+            &[],
             &command,
             AskForApproval::OnRequest,
             &SandboxPolicy::DangerFullAccess,
@@ -493,6 +685,8 @@ prefix_rule(pattern=["rm"], decision="forbidden")
         let requirement = create_exec_approval_requirement_for_command(
             &policy,
             &Features::with_defaults(),
+            &CommandSafetyConfig::default(),
+            &[],
             &command,
             AskForApproval::Never,
             &SandboxPolicy::DangerFullAccess,
@@ -508,6 +702,48 @@ prefix_rule(pattern=["rm"], decision="forbidden")
         );
     }
 
+    #[tokio::test]
+    async fn only_escalation_prompts_can_be_replaced_by_grants() {
+        let policy = Arc::new(RwLock::new(Policy::empty()));
+        let workspace_write = SandboxPolicy::new_workspace_write_policy();
+        let check = |command: &'static [&'static str], approval_policy| {
+            let policy = Arc::clone(&policy);
+            let workspace_write = workspace_write.clone();
+            async move {
+                let command: Vec<String> = command.iter().map(ToString::to_string).collect();
+                let features = Features::with_defaults();
+                let safety = CommandSafetyConfig::default();
+                let requirement = create_exec_approval_requirement_for_command(
+                    &policy,
+                    &features,
+                    &safety,
+                    &[],
+                    &command,
+                    approval_policy,
+                    &workspace_write,
+                    SandboxPermissions::RequireEscalated,
+                )
+                .await;
+                prompts_only_for_escalation(
+                    &policy,
+                    &features,
+                    &safety,
+                    &[],
+                    &command,
+                    approval_policy,
+                    &workspace_write,
+                    SandboxPermissions::RequireEscalated,
+                    &requirement,
+                )
+                .await
+            }
+        };
+
+        assert!(check(&["cargo", "fetch"], AskForApproval::OnRequest).await);
+        // Untrusted commands prompt whatever the sandbox allows.
+        assert!(!check(&["cargo", "fetch"], AskForApproval::UnlessTrusted).await);
+    }
+
     #[tokio::test]
     async fn exec_approval_requirement_falls_back_to_heuristics() {
         let command = vec!["cargo".to_string(), "build".to_string()];
@@ -516,6 +752,8 @@ prefix_rule(pattern=["rm"], decision="forbidden")
         let requirement = create_exec_approval_requirement_for_command(
             &empty_policy,
             &Features::with_defaults(),
+            &CommandSafetyConfig::default(),
+            &[],
             &command,
             AskForApproval::UnlessTrusted,
             &SandboxPolicy::ReadOnly,
@@ -550,6 +788,8 @@ prefix_rule(pattern=["rm"], decision="forbidden")
             create_exec_approval_requirement_for_command(
                 &policy,
                 &Features::with_defaults(),
+                &CommandSafetyConfig::default(),
+                &[],
                 &command,
                 AskForApproval::UnlessTrusted,
                 &SandboxPolicy::DangerFullAccess,
@@ -621,6 +861,8 @@ prefix_rule(pattern=["rm"], decision="forbidden")
         let requirement = create_exec_approval_requirement_for_command(
             &empty_policy,
             &Features::with_defaults(),
+            &CommandSafetyConfig::default(),
+            &[],
             &command,
             AskForApproval::UnlessTrusted,
             &SandboxPolicy::ReadOnly,
@@ -647,6 +889,8 @@ prefix_rule(pattern=["rm"], decision="forbidden")
         let requirement = create_exec_approval_requirement_for_command(
             &Arc::new(RwLock::new(Policy::empty())),
             &features,
+            &CommandSafetyConfig::default(),
+            &[],
             &command,
             AskForApproval::UnlessTrusted,
             &SandboxPolicy::ReadOnly,
@@ -676,6 +920,8 @@ prefix_rule(pattern=["rm"], decision="forbidden")
         let requirement = create_exec_approval_requirement_for_command(
             &policy,
             &Features::with_defaults(),
+            &CommandSafetyConfig::default(),
+            &[],
             &command,
             AskForApproval::OnRequest,
             &SandboxPolicy::DangerFullAccess,
@@ -702,6 +948,8 @@ prefix_rule(pattern=["rm"], decision="forbidden")
         let requirement = create_exec_approval_requirement_for_command(
             &Arc::new(RwLock::new(Policy::empty())),
             &Features::with_defaults(),
+            &CommandSafetyConfig::default(),
+            &[],
             &command,
             AskForApproval::UnlessTrusted,
             &SandboxPolicy::ReadOnly,
@@ -740,6 +988,8 @@ prefix_rule(pattern=["rm"], decision="forbidden")
             create_exec_approval_requirement_for_command(
                 &policy,
                 &Features::with_defaults(),
+                &CommandSafetyConfig::default(),
+                &[],
                 &command,
                 AskForApproval::UnlessTrusted,
                 &SandboxPolicy::ReadOnly,
@@ -762,6 +1012,8 @@ prefix_rule(pattern=["rm"], decision="forbidden")
         let requirement = create_exec_approval_requirement_for_command(
             &Arc::new(RwLock::new(Policy::empty())),
             &Features::with_defaults(),
+            &CommandSafetyConfig::default(),
+            &[],
             &command,
             AskForApproval::OnRequest,
             &SandboxPolicy::ReadOnly,
@@ -791,6 +1043,8 @@ prefix_rule(pattern=["rm"], decision="forbidden")
         let requirement = create_exec_approval_requirement_for_command(
             &policy,
             &Features::with_defaults(),
+            &CommandSafetyConfig::default(),
+            &[],
             &command,
             AskForApproval::OnRequest,
             &SandboxPolicy::ReadOnly,
@@ -0,0 +1,150 @@
+//! Notices about files that changed on disk behind the model's back,
+//! enabled with the `external_edits` feature.
+//!
+//! Every file the model reads with `read_file` or edits through
+//! `apply_patch` is remembered with the contents it had at that point. When
+//! the next task starts, those files are compared with what is on disk and
+//! the differences are written into the conversation, so the model re-reads
+//! them instead of overwriting the user's edits with a stale copy.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::truncate::TruncationPolicy;
+use crate::truncate::formatted_truncate_text;
+
+/// Files larger than this are not remembered.
+const MAX_TRACKED_BYTES: usize = 256 * 1024;
+
+/// Token budget for the diffs in one notice.
+const MAX_NOTICE_TOKENS: usize = 2_000;
+
+/// A remembered file whose contents on disk no longer match.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ExternalEdit {
+    pub path: PathBuf,
+    pub old: String,
+    /// `None` when the file was deleted.
+    pub new: Option<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct ExternalEditTracker {
+    known: Mutex<HashMap<PathBuf, String>>,
+}
+
+impl ExternalEditTracker {
+    /// Remembers the current contents of `path`. Missing, binary and very
+    /// large files are forgotten instead.
+    pub(crate) async fn observe(&self, path: &Path) {
+        match tokio::fs::read_to_string(path).await {
+            Ok(contents) => self.remember(path, contents),
+            Err(_) => self.forget(path),
+        }
+    }
+
+    fn remember(&self, path: &Path, contents: String) {
+        if contents.len() > MAX_TRACKED_BYTES {
+            self.forget(path);
+        } else if let Ok(mut known) = self.known.lock() {
+            known.insert(path.to_path_buf(), contents);
+        }
+    }
+
+    pub(crate) fn forget(&self, path: &Path) {
+        if let Ok(mut known) = self.known.lock() {
+            known.remove(path);
+        }
+    }
+
+    /// Remembered files that changed on disk since they were last observed,
+    /// sorted by path. Each is remembered with its new contents, so a change
+    /// is reported once.
+    pub(crate) async fn take_changes(&self) -> Vec<ExternalEdit> {
+        let known: Vec<(PathBuf, String)> = match self.known.lock() {
+            Ok(known) => known
+                .iter()
+                .map(|(path, contents)| (path.clone(), contents.clone()))
+                .collect(),
+            Err(_) => return Vec::new(),
+        };
+
+        let mut changes = Vec::new();
+        for (path, old) in known {
+            let new = match tokio::fs::read_to_string(&path).await {
+                Ok(new) if new == old => continue,
+                Ok(new) => {
+                    self.remember(&path, new.clone());
+                    Some(new)
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                    self.forget(&path);
+                    None
+                }
+                Err(_) => {
+                    self.forget(&path);
+                    continue;
+                }
+            };
+            changes.push(ExternalEdit { path, old, new });
+        }
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        changes
+    }
+}
+
+/// The conversation note for `changes`, with paths relative to `cwd` where
+/// possible and diffs truncated to the notice's token budget.
+pub(crate) fn notice(changes: &[ExternalEdit], cwd: &Path) -> String {
+    let mut diffs = String::new();
+    for change in changes {
+        let path = change.path.strip_prefix(cwd).unwrap_or(&change.path);
+        let path = path.display().to_string();
+        match &change.new {
+            Some(new) => diffs.push_str(
+                &similar::TextDiff::from_lines(&change.old, new)
+                    .unified_diff()
+                    .context_radius(2)
+                    .header(&format!("a/{path}"), &format!("b/{path}"))
+                    .to_string(),
+            ),
+            None => diffs.push_str(&format!("{path} was deleted\n")),
+        }
+    }
+    format!(
+        "These files changed outside this session since you last read or edited them, either by the user or by a command. Keep these changes: re-read a file before editing it again.\n\n{}",
+        formatted_truncate_text(&diffs, TruncationPolicy::Tokens(MAX_NOTICE_TOKENS))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn reports_each_external_change_once() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let edited = dir.path().join("app.py");
+        let deleted = dir.path().join("notes.txt");
+        std::fs::write(&edited, "a = 1\nb = 2\n")?;
+        std::fs::write(&deleted, "todo\n")?;
+        let tracker = ExternalEditTracker::default();
+        tracker.observe(&edited).await;
+        tracker.observe(&deleted).await;
+        assert_eq!(tracker.take_changes().await, Vec::new());
+
+        std::fs::write(&edited, "a = 1\nb = 3\n")?;
+        std::fs::remove_file(&deleted)?;
+        let changes = tracker.take_changes().await;
+
+        assert_eq!(
+            notice(&changes, dir.path()),
+            "These files changed outside this session since you last read or edited them, either by the user or by a command. Keep these changes: re-read a file before editing it again.\n\n--- a/app.py\n+++ b/app.py\n@@ -1,2 +1,2 @@\n a = 1\n-b = 2\n+b = 3\nnotes.txt was deleted\n"
+        );
+        assert_eq!(tracker.take_changes().await, Vec::new());
+        Ok(())
+    }
+}
@@ -91,6 +91,43 @@ pub enum Feature {
     Tui2,
     /// Enable discovery and injection of skills.
     Skills,
+    /// Allow the model to delegate scoped tasks to child agents via `spawn_agent`.
+    SubAgents,
+    /// Persist project notes across sessions via `memory_write`/`memory_search`.
+    Memory,
+    /// Long-running processes via `shell_bg_start`/`shell_bg_tail`/`shell_bg_kill`.
+    BackgroundShell,
+    /// Run shell tool commands in a pseudo-terminal instead of pipes.
+    ShellPty,
+    /// Keep one long-lived shell per session for the `shell_session` tool.
+    ShellSession,
+    /// Expose `read_file`, `write_file` and `edit_file` next to `apply_patch`.
+    FileEditTools,
+    /// Expose the `code_search` tool.
+    CodeSearch,
+    /// Expose `repo_map`; later sessions start with its cached map.
+    RepoMap,
+    /// Expose `git_status`, `git_diff`, `git_commit` and `git_create_branch`.
+    GitTools,
+    /// Expose `pr_create`, `pr_comments` and `pr_reply` for GitHub and GitLab.
+    PullRequests,
+    /// Expose `fetch_url`, and `web_search` backed by `[web_search_provider]`.
+    WebTools,
+    /// Index the workspace with `[embeddings]`, expose `semantic_search` and
+    /// retrieve relevant chunks for each user message.
+    SemanticSearch,
+    /// Drive interactive programs via `terminal_start`/`terminal_send_keys`.
+    Terminal,
+    /// Expose a `python` tool backed by a persistent interpreter.
+    PythonKernel,
+    /// Tell the model about external edits to files it read or edited.
+    ExternalEdits,
+    /// Expose `run_tests`, which parses test runner output into results.
+    RunTests,
+    /// Expose `run_task` for the project's make, just, npm and cargo tasks.
+    RunTask,
+    /// Give each session a temp directory for throwaway files.
+    ScratchDir,
 }
 
 impl Feature {
@@ -386,6 +423,114 @@ pub const FEATURES: &[FeatureSpec] = &[
         stage: Stage::Experimental,
         default_enabled: true,
     },
+    FeatureSpec {
+        id: Feature::SubAgents,
+        key: "sub_agents",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::Memory,
+        key: "memory",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::BackgroundShell,
+        key: "background_shell",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::ShellPty,
+        key: "shell_pty",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::ShellSession,
+        key: "shell_session",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::FileEditTools,
+        key: "file_edit_tools",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::CodeSearch,
+        key: "code_search",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::RepoMap,
+        key: "repo_map",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::GitTools,
+        key: "git_tools",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::PullRequests,
+        key: "pull_requests",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::WebTools,
+        key: "web_tools",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::SemanticSearch,
+        key: "semantic_search",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::Terminal,
+        key: "terminal",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::PythonKernel,
+        key: "python_kernel",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::ExternalEdits,
+        key: "external_edits",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::RunTests,
+        key: "run_tests",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::RunTask,
+        key: "run_task",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::ScratchDir,
+        key: "scratch_dir",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
     FeatureSpec {
         id: Feature::Tui2,
         key: "tui2",
@@ -0,0 +1,173 @@
+//! Contents of files the user mentioned with `@path`.
+//!
+//! Clients send the paths as `UserInput::FileMention`. The files are read
+//! when the turn starts and recorded as one tagged user message right after
+//! the user's own message. All files of a message share one token budget, so
+//! mentioning a large file truncates it instead of flooding the context.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::user_input::UserInput;
+use tokio::fs;
+
+use crate::truncate::TruncationPolicy;
+use crate::truncate::approx_token_count;
+use crate::truncate::truncate_text;
+
+pub(crate) const FILE_MENTIONS_OPEN_TAG: &str = "<mentioned_files>";
+const FILE_MENTIONS_CLOSE_TAG: &str = "</mentioned_files>";
+
+/// Token budget shared by every file mentioned in one message.
+const FILE_MENTIONS_TOKEN_BUDGET: usize = 8_000;
+
+#[derive(Debug, Default)]
+pub(crate) struct FileMentions {
+    pub(crate) item: Option<ResponseItem>,
+    pub(crate) warnings: Vec<String>,
+}
+
+pub(crate) async fn build_file_mentions(inputs: &[UserInput], cwd: &Path) -> FileMentions {
+    let mut seen: HashSet<&PathBuf> = HashSet::new();
+    let paths: Vec<&PathBuf> = inputs
+        .iter()
+        .filter_map(|input| match input {
+            UserInput::FileMention { path } => Some(path),
+            _ => None,
+        })
+        .filter(|path| seen.insert(*path))
+        .collect();
+    if paths.is_empty() {
+        return FileMentions::default();
+    }
+
+    let mut result = FileMentions::default();
+    let mut remaining = FILE_MENTIONS_TOKEN_BUDGET;
+    let mut rendered = Vec::new();
+    for path in paths {
+        let contents = match fs::read(cwd.join(path)).await {
+            Ok(bytes) if bytes.contains(&0) => None,
+            Ok(bytes) => String::from_utf8(bytes).ok(),
+            Err(err) => {
+                result
+                    .warnings
+                    .push(format!("Failed to attach {}: {err}", path.display()));
+                continue;
+            }
+        };
+        let Some(contents) = contents else {
+            result.warnings.push(format!(
+                "Did not attach {}: it is not a text file",
+                path.display()
+            ));
+            continue;
+        };
+        if remaining == 0 {
+            result.warnings.push(format!(
+                "Did not attach {}: the mentioned files exceed {FILE_MENTIONS_TOKEN_BUDGET} tokens",
+                path.display()
+            ));
+            continue;
+        }
+        let contents = truncate_text(&contents, TruncationPolicy::Tokens(remaining));
+        remaining = remaining.saturating_sub(approx_token_count(&contents));
+        rendered.push(format!(
+            "<file path=\"{}\">\n{contents}\n</file>",
+            path.display()
+        ));
+    }
+
+    if !rendered.is_empty() {
+        result.item = Some(ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: format!(
+                    "{FILE_MENTIONS_OPEN_TAG}\n{}\n{FILE_MENTIONS_CLOSE_TAG}",
+                    rendered.join("\n")
+                ),
+            }],
+        });
+    }
+    result
+}
+
+/// Returns true when `message` holds the contents of mentioned files.
+pub(crate) fn is_file_mentions(message: &[ContentItem]) -> bool {
+    if let [ContentItem::InputText { text }] = message {
+        text.starts_with(FILE_MENTIONS_OPEN_TAG)
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    fn mention(path: &str) -> UserInput {
+        UserInput::FileMention { path: path.into() }
+    }
+
+    #[tokio::test]
+    async fn attaches_mentioned_files_once() {
+        let dir = tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("notes.md"), "remember this").expect("write");
+
+        let mentions = build_file_mentions(
+            &[
+                UserInput::Text {
+                    text: "see notes.md".to_string(),
+                },
+                mention("notes.md"),
+                mention("notes.md"),
+            ],
+            dir.path(),
+        )
+        .await;
+
+        assert_eq!(mentions.warnings, Vec::<String>::new());
+        assert_eq!(
+            mentions.item,
+            Some(ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "<mentioned_files>\n<file path=\"notes.md\">\nremember this\n</file>\n</mentioned_files>"
+                        .to_string(),
+                }],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn large_files_are_truncated_and_binaries_skipped() {
+        let dir = tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("big.txt"), "word ".repeat(20_000)).expect("write");
+        std::fs::write(dir.path().join("blob.bin"), [0u8, 1, 2]).expect("write");
+
+        let mentions = build_file_mentions(
+            &[mention("big.txt"), mention("blob.bin"), mention("gone.txt")],
+            dir.path(),
+        )
+        .await;
+
+        assert_eq!(mentions.warnings.len(), 2);
+        assert!(mentions.warnings[0].starts_with("Did not attach blob.bin"));
+        assert!(mentions.warnings[1].starts_with("Failed to attach gone.txt"));
+        let Some(ResponseItem::Message { content, .. }) = mentions.item else {
+            panic!("expected an attachment");
+        };
+        assert!(is_file_mentions(&content));
+        let [ContentItem::InputText { text }] = content.as_slice() else {
+            panic!("expected a single text item");
+        };
+        assert!(text.contains("truncated"));
+        assert!(approx_token_count(text) <= FILE_MENTIONS_TOKEN_BUDGET + 100);
+    }
+}
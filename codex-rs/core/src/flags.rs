@@ -3,4 +3,10 @@ use env_flags::env_flags;
 env_flags! {
     /// Fixture path for offline tests (see client.rs).
     pub CODEX_RS_SSE_FIXTURE: Option<&str> = None;
+
+    /// Cassette file that model requests are recorded to.
+    pub CODEX_RS_RECORD_CASSETTE: Option<&str> = None;
+
+    /// Cassette file that model requests are replayed from, without network.
+    pub CODEX_RS_REPLAY_CASSETTE: Option<&str> = None;
 }
@@ -1,5 +1,9 @@
+use codex_otel::otel_manager::ToolErrorKind;
 use thiserror::Error;
 
+/// Why a tool call failed. Every variant but `Fatal` is reported back to the
+/// model; [`FunctionCallError::kind`] names the category for the model and
+/// for telemetry.
 #[derive(Debug, Error, PartialEq)]
 pub enum FunctionCallError {
     #[error("{0}")]
@@ -7,8 +11,86 @@ pub enum FunctionCallError {
     #[error("{0}")]
     #[allow(dead_code)] // TODO(jif) fix in a follow-up PR
     Denied(String),
+    /// The arguments did not parse or failed validation.
+    #[error("{0}")]
+    InvalidArguments(String),
+    /// The sandbox refused the command; holds the formatted exec output.
+    #[error("{0}")]
+    SandboxDenied(String),
+    /// The command ran past its timeout; holds the formatted exec output.
+    #[error("{0}")]
+    Timeout(String),
+    /// A hook, including the built-in secret scan, rejected the call.
+    #[error("{0}")]
+    HookBlocked(String),
+    /// The tool handler panicked.
+    #[error("{0}")]
+    HandlerPanic(String),
+    /// Talking to an MCP server failed.
+    #[error("{0}")]
+    McpTransport(String),
     #[error("LocalShellCall without call_id or id")]
     MissingLocalShellCallId,
     #[error("Fatal error: {0}")]
     Fatal(String),
 }
+
+impl FunctionCallError {
+    /// Stable, machine-readable failure category.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::RespondToModel(_) => "tool_error",
+            Self::Denied(_) => "denied",
+            Self::InvalidArguments(_) | Self::MissingLocalShellCallId => "invalid_arguments",
+            Self::SandboxDenied(_) => "sandbox_denied",
+            Self::Timeout(_) => "timeout",
+            Self::HookBlocked(_) => "hook_blocked",
+            Self::HandlerPanic(_) => "handler_panic",
+            Self::McpTransport(_) => "mcp_transport",
+            Self::Fatal(_) => "fatal",
+        }
+    }
+
+    /// The text returned to the model. Categorized failures start with
+    /// `[<kind>]`, except sandbox denials and timeouts, whose exec output
+    /// already reports the exit code and must stay parseable.
+    pub fn model_message(&self) -> String {
+        match self {
+            Self::RespondToModel(_)
+            | Self::Denied(_)
+            | Self::SandboxDenied(_)
+            | Self::Timeout(_)
+            | Self::Fatal(_) => self.to_string(),
+            _ => format!("[{}] {self}", self.kind()),
+        }
+    }
+}
+
+impl ToolErrorKind for FunctionCallError {
+    fn error_kind(&self) -> &'static str {
+        self.kind()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn categorized_failures_are_tagged_for_the_model() {
+        assert_eq!(
+            FunctionCallError::InvalidArguments("missing field `path`".to_string()).model_message(),
+            "[invalid_arguments] missing field `path`"
+        );
+        assert_eq!(
+            FunctionCallError::Timeout("Exit code: 124".to_string()).model_message(),
+            "Exit code: 124"
+        );
+        assert_eq!(
+            FunctionCallError::RespondToModel("no such file".to_string()).model_message(),
+            "no such file"
+        );
+        assert_eq!(FunctionCallError::Timeout(String::new()).kind(), "timeout");
+    }
+}
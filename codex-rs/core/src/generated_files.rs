@@ -0,0 +1,274 @@
+//! Built-in check for edits to generated files, enabled with
+//! `[generated_files]`.
+//!
+//! Runs before a patch from `apply_patch`, `write_file` or `edit_file` is
+//! approved. A file counts as generated when it is a lockfile, sits in a
+//! build output directory such as `target/` or `node_modules/`, is ignored
+//! by git, or matches one of the configured `patterns`. Deleting such files
+//! is not flagged.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_apply_patch::ApplyPatchAction;
+use codex_apply_patch::ApplyPatchFileChange;
+use ignore::Match;
+use ignore::gitignore::Gitignore;
+use ignore::gitignore::GitignoreBuilder;
+use wildmatch::WildMatch;
+
+use crate::config::types::GeneratedFilesConfig;
+use crate::git_info::get_git_repo_root;
+
+/// What the model is told to do instead.
+pub(crate) const GUIDANCE: &str = "Edit the sources these files are generated from and rerun the build or generator instead; update lockfiles through the package manager.";
+
+/// Written by package managers, never by hand.
+const LOCKFILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "poetry.lock",
+    "Pipfile.lock",
+    "uv.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+    "flake.lock",
+    "mix.lock",
+    "pubspec.lock",
+    "Podfile.lock",
+];
+
+/// Directories, relative to the working directory, that hold build output
+/// or installed dependencies.
+const BUILD_OUTPUT_DIRS: &[&str] = &[
+    "target",
+    "node_modules",
+    "dist",
+    "build",
+    "out",
+    "__pycache__",
+    ".next",
+    ".nuxt",
+    ".gradle",
+    ".venv",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum GeneratedReason {
+    Lockfile,
+    Pattern(String),
+    BuildOutput(String),
+    Gitignored,
+}
+
+/// A file written by `action` that looks generated.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct GeneratedFile {
+    pub(crate) path: PathBuf,
+    pub(crate) reason: GeneratedReason,
+}
+
+pub(crate) struct GeneratedFileGuard {
+    patterns: Vec<(String, WildMatch)>,
+    allow: Vec<WildMatch>,
+}
+
+impl GeneratedFileGuard {
+    pub(crate) fn from_config(config: &GeneratedFilesConfig) -> Self {
+        Self {
+            patterns: config
+                .patterns
+                .iter()
+                .map(|pattern| (pattern.clone(), WildMatch::new(pattern)))
+                .collect(),
+            allow: config
+                .allow
+                .iter()
+                .map(|pattern| WildMatch::new(pattern))
+                .collect(),
+        }
+    }
+
+    /// Every file `action` adds or updates that looks generated, sorted by
+    /// path.
+    pub(crate) fn check(&self, action: &ApplyPatchAction) -> Vec<GeneratedFile> {
+        let mut files = Vec::new();
+        for (path, change) in action.changes() {
+            let written = match change {
+                ApplyPatchFileChange::Add { .. } => path,
+                ApplyPatchFileChange::Update { move_path, .. } => {
+                    move_path.as_ref().unwrap_or(path)
+                }
+                ApplyPatchFileChange::Delete { .. } => continue,
+            };
+            if let Some(reason) = self.reason(written, &action.cwd) {
+                files.push(GeneratedFile {
+                    path: written.clone(),
+                    reason,
+                });
+            }
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        files
+    }
+
+    fn reason(&self, path: &Path, cwd: &Path) -> Option<GeneratedReason> {
+        let relative = path.strip_prefix(cwd).ok();
+        let shown = relative
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        if self.allow.iter().any(|pattern| pattern.matches(&shown)) {
+            return None;
+        }
+        let file_name = path.file_name().and_then(|name| name.to_str());
+        if file_name.is_some_and(|name| LOCKFILES.contains(&name)) {
+            return Some(GeneratedReason::Lockfile);
+        }
+        if let Some((pattern, _)) = self
+            .patterns
+            .iter()
+            .find(|(_, pattern)| pattern.matches(&shown))
+        {
+            return Some(GeneratedReason::Pattern(pattern.clone()));
+        }
+        let build_dir = relative.and_then(|relative| {
+            relative.parent()?.components().find_map(|component| {
+                let name = component.as_os_str().to_str()?;
+                BUILD_OUTPUT_DIRS.contains(&name).then(|| name.to_string())
+            })
+        });
+        if let Some(dir) = build_dir {
+            return Some(GeneratedReason::BuildOutput(dir));
+        }
+        is_gitignored(path).then_some(GeneratedReason::Gitignored)
+    }
+}
+
+/// Whether git would ignore `path`, going by `.git/info/exclude` and every
+/// `.gitignore` from the repository root down to the file's directory.
+fn is_gitignored(path: &Path) -> bool {
+    let Some(parent) = path.parent() else {
+        return false;
+    };
+    let Some(root) = get_git_repo_root(parent) else {
+        return false;
+    };
+    let mut matchers = Vec::new();
+    matchers.extend(load_ignore_file(&root, &root.join(".git/info/exclude")));
+    let mut dirs: Vec<&Path> = parent
+        .ancestors()
+        .take_while(|dir| dir.starts_with(&root))
+        .collect();
+    dirs.reverse();
+    for dir in dirs {
+        matchers.extend(load_ignore_file(dir, &dir.join(".gitignore")));
+    }
+
+    // Deeper files override shallower ones, as in git.
+    let mut ignored = false;
+    for matcher in &matchers {
+        match matcher.matched_path_or_any_parents(path, false) {
+            Match::Ignore(_) => ignored = true,
+            Match::Whitelist(_) => ignored = false,
+            Match::None => {}
+        }
+    }
+    ignored
+}
+
+fn load_ignore_file(root: &Path, file: &Path) -> Option<Gitignore> {
+    if !file.is_file() {
+        return None;
+    }
+    let mut builder = GitignoreBuilder::new(root);
+    if builder.add(file).is_some() {
+        return None;
+    }
+    builder.build().ok()
+}
+
+/// One line per file, with paths relative to `cwd` where possible.
+pub(crate) fn describe(files: &[GeneratedFile], cwd: &Path) -> String {
+    files
+        .iter()
+        .map(|file| {
+            let path = file.path.strip_prefix(cwd).unwrap_or(&file.path);
+            let reason = match &file.reason {
+                GeneratedReason::Lockfile => "lockfile".to_string(),
+                GeneratedReason::Pattern(pattern) => format!("matches `{pattern}`"),
+                GeneratedReason::BuildOutput(dir) => format!("build output in `{dir}/`"),
+                GeneratedReason::Gitignored => "ignored by git".to_string(),
+            };
+            format!("{}: {reason}", path.display())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    #[test]
+    fn flags_lockfiles_patterns_build_output_and_ignored_files() -> anyhow::Result<()> {
+        let tmp = tempdir()?;
+        let cwd = tmp.path();
+        std::fs::create_dir(cwd.join(".git"))?;
+        std::fs::write(cwd.join(".gitignore"), "*.log\ngen/\n")?;
+        std::fs::create_dir(cwd.join("logs"))?;
+        std::fs::write(cwd.join("logs/.gitignore"), "!keep.log\n")?;
+        let guard = GeneratedFileGuard::from_config(&GeneratedFilesConfig {
+            patterns: vec!["proto/*.pb.go".to_string()],
+            allow: vec!["build/*.sh".to_string()],
+            ..Default::default()
+        });
+
+        let files: Vec<GeneratedFile> = [
+            "Cargo.lock",
+            "src/main.rs",
+            "target/debug/out.txt",
+            "build/setup.sh",
+            "proto/api.pb.go",
+            "logs/run.log",
+            "logs/keep.log",
+            "gen/mod.rs",
+        ]
+        .iter()
+        .filter_map(|path| {
+            let path = cwd.join(path);
+            let reason = guard.reason(&path, cwd)?;
+            Some(GeneratedFile { path, reason })
+        })
+        .collect();
+
+        assert_eq!(
+            describe(&files, cwd),
+            "Cargo.lock: lockfile\ntarget/debug/out.txt: build output in `target/`\nproto/api.pb.go: matches `proto/*.pb.go`\nlogs/run.log: ignored by git\ngen/mod.rs: ignored by git"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn check_reports_written_files() {
+        let tmp = tempdir().expect("tmp");
+        let guard = GeneratedFileGuard::from_config(&GeneratedFilesConfig::default());
+        let action =
+            ApplyPatchAction::new_add_for_test(&tmp.path().join("yarn.lock"), String::new());
+
+        assert_eq!(
+            guard.check(&action),
+            vec![GeneratedFile {
+                path: tmp.path().join("yarn.lock"),
+                reason: GeneratedReason::Lockfile,
+            }]
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! Disposable git worktrees used to run delegated agents in isolation.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use tokio::process::Command;
+
+/// A detached worktree checked out at the repository's `HEAD`. Uncommitted
+/// changes in the main checkout are not carried over.
+///
+/// Dropping a worktree that was not [`remove`](Self::remove)d, for example
+/// when the agent using it is cancelled, removes it in the background.
+#[derive(Debug)]
+pub(crate) struct AgentWorktree {
+    repo_root: PathBuf,
+    path: PathBuf,
+    removed: bool,
+}
+
+impl AgentWorktree {
+    /// Create a detached worktree of `repo_root` at `path`.
+    pub(crate) async fn create(repo_root: &Path, path: &Path) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let path_arg = path.to_string_lossy();
+        run_git(
+            repo_root,
+            &["worktree", "add", "--detach", &path_arg, "HEAD"],
+        )
+        .await?;
+        Ok(Self {
+            repo_root: repo_root.to_path_buf(),
+            path: path.to_path_buf(),
+            removed: false,
+        })
+    }
+
+    /// Map `cwd` (inside the main checkout) to the same location in this worktree.
+    pub(crate) fn map_cwd(&self, cwd: &Path) -> PathBuf {
+        match cwd.strip_prefix(&self.repo_root) {
+            Ok(relative) => self.path.join(relative),
+            Err(_) => self.path.clone(),
+        }
+    }
+
+    /// Everything changed in the worktree relative to `HEAD`, including new
+    /// files, as a patch that `git apply` accepts.
+    pub(crate) async fn diff(&self) -> io::Result<String> {
+        run_git(&self.path, &["add", "--all"]).await?;
+        run_git(&self.path, &["diff", "--cached", "--binary", "HEAD"]).await
+    }
+
+    /// Remove the worktree and its administrative files.
+    pub(crate) async fn remove(mut self) -> io::Result<()> {
+        // The caller handles a failure; do not retry it on drop.
+        self.removed = true;
+        remove_worktree(&self.repo_root, &self.path).await
+    }
+}
+
+impl Drop for AgentWorktree {
+    fn drop(&mut self) {
+        if self.removed {
+            return;
+        }
+        let repo_root = self.repo_root.clone();
+        let path = self.path.clone();
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move {
+                    if let Err(err) = remove_worktree(&repo_root, &path).await {
+                        tracing::warn!(
+                            "failed to remove abandoned worktree {}: {err}",
+                            path.display()
+                        );
+                    }
+                });
+            }
+            Err(_) => {
+                let _ = std::process::Command::new("git")
+                    .args(["worktree", "remove", "--force"])
+                    .arg(&path)
+                    .current_dir(&repo_root)
+                    .status();
+            }
+        }
+    }
+}
+
+async fn remove_worktree(repo_root: &Path, path: &Path) -> io::Result<()> {
+    let path_arg = path.to_string_lossy();
+    run_git(repo_root, &["worktree", "remove", "--force", &path_arg]).await?;
+    Ok(())
+}
+
+async fn run_git(cwd: &Path, args: &[&str]) -> io::Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(cwd)
+        .kill_on_drop(true)
+        .output()
+        .await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            stderr.trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::process::Command as StdCommand;
+    use tempfile::TempDir;
+
+    fn git(cwd: &Path, args: &[&str]) {
+        let status = StdCommand::new("git")
+            .args([
+                "-c",
+                "user.name=Codex",
+                "-c",
+                "user.email=codex@example.com",
+            ])
+            .args(args)
+            .current_dir(cwd)
+            .status()
+            .expect("run git");
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    #[tokio::test]
+    async fn worktree_captures_changes_and_is_removed() {
+        let repo = TempDir::new().expect("repo dir");
+        git(repo.path(), &["init", "-q"]);
+        std::fs::create_dir(repo.path().join("src")).expect("mkdir");
+        std::fs::write(repo.path().join("src/lib.rs"), "fn a() {}\n").expect("write");
+        git(repo.path(), &["add", "."]);
+        git(repo.path(), &["commit", "-q", "-m", "init"]);
+
+        let worktrees = TempDir::new().expect("worktrees dir");
+        let path = worktrees.path().join("agent-1");
+        let worktree = AgentWorktree::create(repo.path(), &path)
+            .await
+            .expect("create worktree");
+        assert_eq!(worktree.map_cwd(&repo.path().join("src")), path.join("src"));
+
+        std::fs::write(path.join("src/new.rs"), "fn b() {}\n").expect("write");
+        let diff = worktree.diff().await.expect("diff");
+        assert!(diff.contains("+++ b/src/new.rs"), "unexpected diff: {diff}");
+        // The main checkout is untouched.
+        assert!(!repo.path().join("src/new.rs").exists());
+
+        worktree.remove().await.expect("remove");
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn dropped_worktree_is_removed() {
+        let repo = TempDir::new().expect("repo dir");
+        git(repo.path(), &["init", "-q"]);
+        std::fs::write(repo.path().join("README.md"), "hi\n").expect("write");
+        git(repo.path(), &["add", "."]);
+        git(repo.path(), &["commit", "-q", "-m", "init"]);
+
+        let worktrees = TempDir::new().expect("worktrees dir");
+        let path = worktrees.path().join("agent-1");
+        let worktree = AgentWorktree::create(repo.path(), &path)
+            .await
+            .expect("create worktree");
+        drop(worktree);
+
+        let is_listed = || {
+            let listed = StdCommand::new("git")
+                .args(["worktree", "list"])
+                .current_dir(repo.path())
+                .output()
+                .expect("git worktree list");
+            String::from_utf8_lossy(&listed.stdout).contains("agent-1")
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(10), async {
+            while path.exists() || is_listed() {
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("abandoned worktree should be removed");
+    }
+}
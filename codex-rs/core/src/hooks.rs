@@ -0,0 +1,718 @@
+//! Resolves which hook commands run for an event, and runs them.
+//!
+//! Most events have a single-purpose key (`notify`, `pre_approval_hook`,
+//! `budget.on_exceeded`), and every event has any number of `[[hooks]]`
+//! entries subscribed to it. The single-purpose command runs first, then
+//! matching entries in the order they appear in config.
+//!
+//! Command arguments may contain `{session_id}`, `{cwd}`, `{tool_name}` and
+//! `{tool_input.command}`, replaced when the hook runs. Placeholders that do
+//! not apply to an event expand to nothing; other braces are left alone.
+//!
+//! Every payload carries `hook-protocol-version`. Hooks that print JSON may
+//! state the protocol version they were written for and the oldest Codex
+//! they support; output from a hook that needs something newer is ignored.
+//!
+//! Hooks run unsandboxed unless `sandbox_hooks` or the entry's `sandbox` says
+//! otherwise. Sandboxed hooks run through the same sandbox as tool calls,
+//! under the sandbox policy of the current turn.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use codex_protocol::models::SandboxPermissions;
+use regex_lite::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::process::Command;
+use tracing::warn;
+
+use crate::codex::TurnContext;
+use crate::config::types::HookConfig;
+use crate::config::types::HookDefinition;
+use crate::config::types::HookEvent;
+use crate::exec::ExecExpiration;
+use crate::exec::ExecParams;
+use crate::exec::process_exec_tool_call;
+use crate::protocol::SandboxPolicy;
+
+/// Version of the JSON hooks receive and print. Bump it when a payload or
+/// the output format changes in a way existing hooks could misread.
+pub(crate) const HOOK_PROTOCOL_VERSION: u32 = 1;
+
+/// How long a sandboxed hook that nobody waits for may run.
+const BACKGROUND_HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A hook program, whether it runs inside the session sandbox, and the
+/// settings of its `[[hooks]]` entry.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct HookCommand {
+    pub(crate) command: Vec<String>,
+    pub(crate) sandboxed: bool,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) env: BTreeMap<String, String>,
+}
+
+/// The sandbox that sandboxed hooks run in.
+#[derive(Debug, Clone)]
+pub(crate) struct HookSandbox {
+    pub(crate) policy: SandboxPolicy,
+    pub(crate) cwd: PathBuf,
+    pub(crate) codex_linux_sandbox_exe: Option<PathBuf>,
+}
+
+impl HookSandbox {
+    /// The sandbox tool calls use in `turn_context`.
+    pub(crate) fn for_turn(turn_context: &TurnContext) -> Self {
+        Self {
+            policy: turn_context.sandbox_policy.clone(),
+            cwd: turn_context.cwd.clone(),
+            codex_linux_sandbox_exe: turn_context.codex_linux_sandbox_exe.clone(),
+        }
+    }
+}
+
+/// Serializes `event` for a hook, stamped with `hook-protocol-version`.
+pub(crate) fn payload<T: Serialize>(event: &T) -> Option<String> {
+    let mut value = serde_json::to_value(event).ok()?;
+    if let Some(object) = value.as_object_mut() {
+        object.insert(
+            "hook-protocol-version".to_string(),
+            HOOK_PROTOCOL_VERSION.into(),
+        );
+    }
+    serde_json::to_string(&value).ok()
+}
+
+/// JSON a hook may print on stdout.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct HookOutput {
+    pub(crate) decision: Option<String>,
+    pub(crate) reason: Option<String>,
+    /// Variables to export to later shell commands in the turn.
+    #[serde(default)]
+    pub(crate) env: BTreeMap<String, String>,
+    /// Protocol version the hook was written for.
+    #[serde(alias = "hookProtocolVersion")]
+    pub(crate) hook_protocol_version: Option<u32>,
+    /// Oldest Codex release the hook works with.
+    #[serde(alias = "minimumCodexVersion")]
+    pub(crate) minimum_codex_version: Option<String>,
+}
+
+impl HookOutput {
+    /// Parses hook output that is a JSON object. Returns `Ok(None)` for any
+    /// other output, and an error when the hook needs a newer protocol or a
+    /// newer Codex than this one.
+    pub(crate) fn parse(stdout: &str) -> Result<Option<Self>, String> {
+        let stdout = stdout.trim();
+        if !stdout.starts_with('{') {
+            return Ok(None);
+        }
+        let Ok(output) = serde_json::from_str::<Self>(stdout) else {
+            return Ok(None);
+        };
+        output.check_compatible(env!("CARGO_PKG_VERSION"))?;
+        Ok(Some(output))
+    }
+
+    fn check_compatible(&self, codex_version: &str) -> Result<(), String> {
+        if let Some(version) = self.hook_protocol_version
+            && version > HOOK_PROTOCOL_VERSION
+        {
+            return Err(format!(
+                "speaks hook protocol version {version}; this Codex speaks version {HOOK_PROTOCOL_VERSION}"
+            ));
+        }
+        let Some(minimum) = self.minimum_codex_version.as_deref() else {
+            return Ok(());
+        };
+        let Some(minimum_parsed) = parse_version(minimum) else {
+            return Err(format!("has an invalid minimum-codex-version `{minimum}`"));
+        };
+        // Development builds are versioned 0.0.0 and satisfy any minimum.
+        match parse_version(codex_version) {
+            Some((0, 0, 0)) | None => Ok(()),
+            Some(current) if current >= minimum_parsed => Ok(()),
+            Some(_) => Err(format!(
+                "needs Codex {minimum} or newer; this is {codex_version}"
+            )),
+        }
+    }
+}
+
+pub(crate) fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = version.trim().trim_start_matches('v').split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().unwrap_or("0").parse().ok()?;
+    let patch = parts.next().unwrap_or("0");
+    // Ignore pre-release and build suffixes such as `-alpha.1`.
+    let patch = patch.split(['-', '+']).next()?.parse().ok()?;
+    Some((major, minor, patch))
+}
+
+/// Values for the placeholders in hook command arguments.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HookPlaceholders {
+    pub(crate) session_id: String,
+    pub(crate) cwd: String,
+    pub(crate) tool_name: String,
+    /// The command line of the shell command the event is about.
+    pub(crate) tool_command: String,
+}
+
+impl HookPlaceholders {
+    /// `hook` with its placeholders replaced.
+    pub(crate) fn expand(&self, hook: &HookCommand) -> HookCommand {
+        HookCommand {
+            command: hook
+                .command
+                .iter()
+                .map(|arg| self.expand_arg(arg))
+                .collect(),
+            ..hook.clone()
+        }
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        match name {
+            "session_id" => Some(&self.session_id),
+            "cwd" => Some(&self.cwd),
+            "tool_name" => Some(&self.tool_name),
+            "tool_input.command" => Some(&self.tool_command),
+            _ => None,
+        }
+    }
+
+    fn expand_arg(&self, arg: &str) -> String {
+        let mut expanded = String::with_capacity(arg.len());
+        let mut rest = arg;
+        while let Some(open) = rest.find('{') {
+            expanded.push_str(&rest[..open]);
+            let after = &rest[open + 1..];
+            match after
+                .find('}')
+                .and_then(|close| Some((close, self.value(&after[..close])?)))
+            {
+                Some((close, value)) => {
+                    expanded.push_str(value);
+                    rest = &after[close + 1..];
+                }
+                None => {
+                    expanded.push('{');
+                    rest = after;
+                }
+            }
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+}
+
+/// Commands to run for `event`. `subjects` are what an entry's `matcher` is
+/// tested against; an entry matches when any subject does. Events without
+/// subjects ignore matchers. `sandbox_default` is the `sandbox_hooks`
+/// setting, which entries can override.
+pub(crate) fn commands_for(
+    legacy: Option<&Vec<String>>,
+    hooks: &[HookConfig],
+    event: HookEvent,
+    subjects: &[String],
+    sandbox_default: bool,
+) -> Vec<HookCommand> {
+    let legacy = legacy.map(|command| HookCommand {
+        command: command.clone(),
+        sandboxed: sandbox_default,
+        ..Default::default()
+    });
+    let configured = hooks
+        .iter()
+        .filter(|hook| hook.events.contains(&event))
+        .filter(|hook| matches(hook.matcher.as_deref(), subjects))
+        .map(|hook| HookCommand {
+            command: hook.command.clone(),
+            sandboxed: hook.sandbox.unwrap_or(sandbox_default),
+            timeout: hook.timeout_ms.map(Duration::from_millis),
+            env: hook.env.clone(),
+        });
+    legacy
+        .into_iter()
+        .chain(configured)
+        .filter(|hook| !hook.command.is_empty())
+        .collect()
+}
+
+/// Starts `hook` with `payload` as its final argument without waiting for it.
+pub(crate) fn spawn(hook: &HookCommand, payload: String, sandbox: &HookSandbox) {
+    let Some((program, args)) = hook.command.split_first() else {
+        return;
+    };
+    if hook.sandboxed || hook.timeout.is_some() {
+        let hook = hook.clone();
+        let sandbox = sandbox.clone();
+        tokio::spawn(async move {
+            if let Err(err) = output(&hook, payload, &sandbox, BACKGROUND_HOOK_TIMEOUT).await {
+                warn!("hook failed: {err}");
+            }
+        });
+        return;
+    }
+    // Fire-and-forget – we do not wait for completion.
+    if let Err(e) = std::process::Command::new(program)
+        .args(args)
+        .arg(payload)
+        .envs(&hook.env)
+        .spawn()
+    {
+        warn!("failed to spawn hook '{program}': {e}");
+    }
+}
+
+/// Runs `hook` with `payload` as its final argument and returns its stdout.
+/// A non-zero exit, a sandbox denial or running past `timeout`, or the hook's
+/// own shorter timeout, is an error.
+pub(crate) async fn output(
+    hook: &HookCommand,
+    payload: String,
+    sandbox: &HookSandbox,
+    timeout: Duration,
+) -> Result<String, String> {
+    let Some((program, args)) = hook.command.split_first() else {
+        return Err("hook command is empty".to_string());
+    };
+    let timeout = hook.timeout.map_or(timeout, |own| own.min(timeout));
+    if hook.sandboxed {
+        return run_sandboxed(hook, payload, sandbox, timeout).await;
+    }
+    let output = Command::new(program)
+        .args(args)
+        .arg(payload)
+        .envs(&hook.env)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    match tokio::time::timeout(timeout, output).await {
+        Ok(Ok(output)) if output.status.success() => {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        }
+        Ok(Ok(output)) => Err(format!("'{program}' exited with {}", output.status)),
+        Ok(Err(e)) => Err(format!("failed to run '{program}': {e}")),
+        Err(_) => Err(format!("'{program}' timed out")),
+    }
+}
+
+async fn run_sandboxed(
+    hook: &HookCommand,
+    payload: String,
+    sandbox: &HookSandbox,
+    timeout: Duration,
+) -> Result<String, String> {
+    let program = hook.command.first().cloned().unwrap_or_default();
+    let mut command = hook.command.clone();
+    command.push(payload);
+    let mut env: HashMap<String, String> = std::env::vars().collect();
+    env.extend(hook.env.clone());
+    let params = ExecParams {
+        command,
+        cwd: sandbox.cwd.clone(),
+        expiration: ExecExpiration::Timeout(timeout),
+        env,
+        sandbox_permissions: SandboxPermissions::UseDefault,
+        justification: None,
+        arg0: None,
+    };
+    let output = process_exec_tool_call(
+        params,
+        &sandbox.policy,
+        &sandbox.cwd,
+        &sandbox.codex_linux_sandbox_exe,
+        None,
+    )
+    .await
+    .map_err(|err| format!("'{program}' failed in the sandbox: {err}"))?;
+    if output.exit_code != 0 {
+        return Err(format!("'{program}' exited with code {}", output.exit_code));
+    }
+    Ok(output.stdout.text)
+}
+
+/// How long the next hook of a chain may run: its own `timeout`, cut short by
+/// what is left of `budget` after `elapsed`. `None` once the budget is spent.
+pub(crate) fn time_left(
+    budget: Option<Duration>,
+    elapsed: Duration,
+    timeout: Duration,
+) -> Option<Duration> {
+    let Some(budget) = budget else {
+        return Some(timeout);
+    };
+    let remaining = budget.saturating_sub(elapsed);
+    (!remaining.is_zero()).then(|| remaining.min(timeout))
+}
+
+fn matches(matcher: Option<&str>, subjects: &[String]) -> bool {
+    let Some(matcher) = matcher else {
+        return true;
+    };
+    if subjects.is_empty() {
+        return true;
+    }
+    Regex::new(matcher).is_ok_and(|re| subjects.iter().any(|subject| re.is_match(subject)))
+}
+
+/// Fills in `[[hooks]]` entries that name a `definition` from
+/// `[hook_definitions]`. Settings on the entry win over the definition's;
+/// `env` tables are merged.
+pub(crate) fn resolve(
+    hooks: Vec<HookConfig>,
+    definitions: &BTreeMap<String, HookDefinition>,
+) -> Result<Vec<HookConfig>, String> {
+    hooks
+        .into_iter()
+        .map(|hook| {
+            let Some(name) = hook.definition.as_deref() else {
+                return Ok(hook);
+            };
+            let Some(definition) = definitions.get(name) else {
+                return Err(format!(
+                    "`[[hooks]]` entry refers to unknown definition `{name}`"
+                ));
+            };
+            if !hook.command.is_empty() {
+                return Err(format!(
+                    "`[[hooks]]` entry sets both `command` and `definition = \"{name}\"`"
+                ));
+            }
+            let mut env = definition.env.clone();
+            env.extend(hook.env.clone());
+            Ok(HookConfig {
+                command: definition.command.clone(),
+                sandbox: hook.sandbox.or(definition.sandbox),
+                timeout_ms: hook.timeout_ms.or(definition.timeout_ms),
+                env,
+                ..hook
+            })
+        })
+        .collect()
+}
+
+/// Rejects `[[hooks]]` entries that could never run.
+pub(crate) fn validate(hooks: &[HookConfig]) -> Result<(), String> {
+    for hook in hooks {
+        if hook.command.is_empty() {
+            return Err("`[[hooks]]` entries need a non-empty `command`".to_string());
+        }
+        if hook.events.is_empty() {
+            return Err(format!(
+                "`[[hooks]]` entry `{}` subscribes to no `events`",
+                hook.command[0]
+            ));
+        }
+        if let Some(matcher) = &hook.matcher
+            && let Err(err) = Regex::new(matcher)
+        {
+            return Err(format!("invalid `[[hooks]]` matcher `{matcher}`: {err}"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn hook(events: &[HookEvent], matcher: Option<&str>, program: &str) -> HookConfig {
+        HookConfig {
+            events: events.to_vec(),
+            matcher: matcher.map(str::to_string),
+            command: vec![program.to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn programs(hooks: Vec<HookCommand>) -> Vec<String> {
+        hooks
+            .into_iter()
+            .map(|hook| hook.command[0].clone())
+            .collect()
+    }
+
+    #[test]
+    fn one_entry_serves_several_events_after_the_legacy_key() {
+        let hooks = [
+            hook(
+                &[HookEvent::PreApproval, HookEvent::BudgetExceeded],
+                None,
+                "audit",
+            ),
+            hook(&[HookEvent::AgentTurnComplete], None, "notify-send"),
+        ];
+        let legacy = vec!["policy".to_string()];
+
+        assert_eq!(
+            programs(commands_for(
+                Some(&legacy),
+                &hooks,
+                HookEvent::PreApproval,
+                &[],
+                false
+            )),
+            vec!["policy", "audit"]
+        );
+        assert_eq!(
+            programs(commands_for(
+                None,
+                &hooks,
+                HookEvent::BudgetExceeded,
+                &[],
+                false
+            )),
+            vec!["audit"]
+        );
+    }
+
+    #[test]
+    fn entries_can_override_sandbox_hooks() {
+        let mut unsandboxed = hook(&[HookEvent::PreApproval], None, "trusted");
+        unsandboxed.sandbox = Some(false);
+        let hooks = [unsandboxed, hook(&[HookEvent::PreApproval], None, "audit")];
+        let legacy = vec!["policy".to_string()];
+
+        let sandboxed: Vec<bool> =
+            commands_for(Some(&legacy), &hooks, HookEvent::PreApproval, &[], true)
+                .iter()
+                .map(|hook| hook.sandboxed)
+                .collect();
+        assert_eq!(sandboxed, vec![true, false, true]);
+    }
+
+    #[test]
+    fn matcher_filters_by_subject() {
+        let hooks = [hook(&[HookEvent::PreApproval], Some("^git "), "git-policy")];
+        let subjects = |s: &str| vec![s.to_string()];
+
+        assert_eq!(
+            commands_for(
+                None,
+                &hooks,
+                HookEvent::PreApproval,
+                &subjects("git push"),
+                false
+            )
+            .len(),
+            1
+        );
+        assert!(
+            commands_for(
+                None,
+                &hooks,
+                HookEvent::PreApproval,
+                &subjects("rm -rf build"),
+                false
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn validate_rejects_unusable_entries() {
+        assert!(validate(&[hook(&[HookEvent::PreApproval], Some("("), "x")]).is_err());
+        assert!(validate(&[hook(&[], None, "x")]).is_err());
+        assert!(validate(&[hook(&[HookEvent::PreApproval], Some("^git"), "x")]).is_ok());
+    }
+
+    #[test]
+    fn entries_take_command_and_settings_from_definitions() {
+        let definitions = BTreeMap::from([(
+            "audit".to_string(),
+            HookDefinition {
+                command: vec!["audit".to_string()],
+                sandbox: Some(true),
+                timeout_ms: Some(500),
+                env: BTreeMap::from([
+                    ("AUDIT_LEVEL".to_string(), "full".to_string()),
+                    ("AUDIT_LOG".to_string(), "/var/log/audit".to_string()),
+                ]),
+            },
+        )]);
+        let entry = HookConfig {
+            events: vec![HookEvent::PreApproval],
+            definition: Some("audit".to_string()),
+            sandbox: Some(false),
+            env: BTreeMap::from([("AUDIT_LEVEL".to_string(), "brief".to_string())]),
+            ..Default::default()
+        };
+
+        let resolved = resolve(vec![entry.clone()], &definitions).expect("resolve");
+
+        assert_eq!(
+            resolved,
+            vec![HookConfig {
+                command: vec!["audit".to_string()],
+                timeout_ms: Some(500),
+                env: BTreeMap::from([
+                    ("AUDIT_LEVEL".to_string(), "brief".to_string()),
+                    ("AUDIT_LOG".to_string(), "/var/log/audit".to_string()),
+                ]),
+                ..entry.clone()
+            }]
+        );
+        assert_eq!(
+            commands_for(None, &resolved, HookEvent::PreApproval, &[], true)[0].timeout,
+            Some(Duration::from_millis(500))
+        );
+        assert!(
+            resolve(
+                vec![HookConfig {
+                    definition: Some("missing".to_string()),
+                    ..entry.clone()
+                }],
+                &definitions
+            )
+            .is_err()
+        );
+        assert!(
+            resolve(
+                vec![HookConfig {
+                    command: vec!["other".to_string()],
+                    ..entry
+                }],
+                &definitions
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn payloads_carry_the_protocol_version() {
+        #[derive(Serialize)]
+        struct Event {
+            r#type: &'static str,
+        }
+        let json = payload(&Event { r#type: "test" }).expect("payload");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).expect("json"),
+            serde_json::json!({ "type": "test", "hook-protocol-version": 1 })
+        );
+    }
+
+    #[test]
+    fn output_from_newer_hooks_is_rejected() {
+        let output = |json: &str| serde_json::from_str::<HookOutput>(json).expect("output");
+
+        assert_eq!(
+            HookOutput::parse(r#"{"decision": "block", "hook-protocol-version": 1}"#),
+            Ok(Some(HookOutput {
+                decision: Some("block".to_string()),
+                hook_protocol_version: Some(1),
+                ..Default::default()
+            }))
+        );
+        assert_eq!(HookOutput::parse("approve"), Ok(None));
+        assert!(
+            output(r#"{"hookProtocolVersion": 2}"#)
+                .check_compatible("1.4.0")
+                .is_err()
+        );
+        assert!(
+            output(r#"{"minimumCodexVersion": "1.5.0"}"#)
+                .check_compatible("1.4.2")
+                .is_err()
+        );
+        assert!(
+            output(r#"{"minimum-codex-version": "1.4"}"#)
+                .check_compatible("1.4.2")
+                .is_ok()
+        );
+        assert!(
+            output(r#"{"minimum-codex-version": "9.0.0"}"#)
+                .check_compatible("0.0.0")
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn latency_budget_shortens_and_then_skips_hooks() {
+        let timeout = Duration::from_secs(30);
+        assert_eq!(
+            time_left(None, Duration::from_secs(90), timeout),
+            Some(timeout)
+        );
+        assert_eq!(
+            time_left(
+                Some(Duration::from_millis(500)),
+                Duration::from_millis(200),
+                timeout
+            ),
+            Some(Duration::from_millis(300))
+        );
+        assert_eq!(
+            time_left(
+                Some(Duration::from_secs(60)),
+                Duration::from_secs(1),
+                timeout
+            ),
+            Some(timeout)
+        );
+        assert_eq!(
+            time_left(
+                Some(Duration::from_millis(500)),
+                Duration::from_millis(500),
+                timeout
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn placeholders_expand_in_arguments() {
+        let placeholders = HookPlaceholders {
+            session_id: "thread-1".to_string(),
+            cwd: "/repo".to_string(),
+            tool_name: "shell".to_string(),
+            tool_command: "cargo test".to_string(),
+        };
+        let hook = HookCommand {
+            command: [
+                "logger",
+                "-t",
+                "codex-{session_id}",
+                "{tool_name} in {cwd}: {tool_input.command}",
+                "{\"json\": true}",
+                "{unknown}",
+            ]
+            .map(String::from)
+            .to_vec(),
+            sandboxed: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            placeholders.expand(&hook),
+            HookCommand {
+                command: [
+                    "logger",
+                    "-t",
+                    "codex-thread-1",
+                    "shell in /repo: cargo test",
+                    "{\"json\": true}",
+                    "{unknown}",
+                ]
+                .map(String::from)
+                .to_vec(),
+                sandboxed: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            HookPlaceholders::default().expand_arg("[{tool_name}]"),
+            "[]"
+        );
+    }
+}
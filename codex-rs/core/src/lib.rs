@@ -5,14 +5,18 @@
 // the TUI or the tracing stack).
 #![deny(clippy::print_stdout, clippy::print_stderr)]
 
+mod after_tool_use_hook;
 pub mod api_bridge;
 mod apply_patch;
 pub mod auth;
 pub mod bash;
+mod budget;
+pub mod claude_import;
 mod client;
 mod client_common;
 pub mod codex;
 mod codex_conversation;
+pub mod commit_message;
 mod compact_remote;
 pub use codex_conversation::CodexConversation;
 mod codex_delegate;
@@ -20,17 +24,26 @@ mod command_safety;
 pub mod config;
 pub mod config_loader;
 mod context_manager;
+mod context_pressure;
+pub mod credential_store;
 pub mod custom_prompts;
+mod edit_checks;
 pub mod env;
 mod environment_context;
 pub mod error;
 pub mod exec;
 pub mod exec_env;
 mod exec_policy;
+mod external_edits;
 pub mod features;
+mod file_mentions;
 mod flags;
+mod generated_files;
 pub mod git_info;
+mod git_worktree;
+mod hooks;
 pub mod landlock;
+mod lsp;
 pub mod mcp;
 mod mcp_connection_manager;
 pub mod models_manager;
@@ -38,22 +51,49 @@ pub use mcp_connection_manager::MCP_SANDBOX_STATE_CAPABILITY;
 pub use mcp_connection_manager::MCP_SANDBOX_STATE_METHOD;
 pub use mcp_connection_manager::SandboxState;
 mod mcp_tool_call;
+pub mod memory_store;
 mod message_history;
 mod model_provider_info;
+mod model_routing;
+mod output_schema;
 pub mod parse_command;
+pub mod patch_review;
 pub mod path_utils;
+mod permissions;
+mod pinned_context;
+mod plan_file;
 pub mod powershell;
+mod pre_approval_hook;
+mod provider_auth;
+pub mod pull_requests;
+mod python_kernel;
+mod redaction;
+pub mod repo_map;
+mod resume_check;
 pub mod sandboxing;
+mod scratch_dir;
+mod secret_scan;
+pub mod semantic_index;
+mod session_end_hook;
+pub mod session_status;
+mod session_summary;
 mod stream_events_utils;
+mod test_runner;
 mod text_encoding;
+mod tool_policy;
+mod tool_rules;
 pub mod token_data;
 mod truncate;
 mod unified_exec;
+pub mod usage_ledger;
 mod user_instructions;
+pub use model_provider_info::AzureProviderOptions;
+pub use model_provider_info::BedrockProviderOptions;
 pub use model_provider_info::CHAT_WIRE_API_DEPRECATION_SUMMARY;
 pub use model_provider_info::DEFAULT_LMSTUDIO_PORT;
 pub use model_provider_info::DEFAULT_OLLAMA_PORT;
 pub use model_provider_info::LMSTUDIO_OSS_PROVIDER_ID;
+pub use model_provider_info::LocalModelQuirks;
 pub use model_provider_info::ModelProviderInfo;
 pub use model_provider_info::OLLAMA_OSS_PROVIDER_ID;
 pub use model_provider_info::WireApi;
@@ -63,6 +103,7 @@ mod conversation_manager;
 mod event_mapping;
 pub mod review_format;
 pub mod review_prompts;
+pub mod review_sarif;
 pub use codex_protocol::protocol::InitialHistory;
 pub use conversation_manager::ConversationManager;
 pub use conversation_manager::NewConversation;
@@ -70,14 +111,19 @@ pub use conversation_manager::NewConversation;
 pub use auth::AuthManager;
 pub use auth::CodexAuth;
 pub mod default_client;
+pub mod doctor;
 pub mod project_doc;
+mod project_tasks;
 mod rollout;
 pub(crate) mod safety;
 pub mod seatbelt;
 pub mod shell;
+mod shell_session;
 pub mod shell_snapshot;
 pub mod skills;
 pub mod spawn;
+pub mod storage_encryption;
+pub mod task_bundle;
 pub mod terminal;
 mod tools;
 pub mod turn_diff_tracker;
@@ -87,17 +133,25 @@ pub use rollout::RolloutRecorder;
 pub use rollout::SESSIONS_SUBDIR;
 pub use rollout::SessionMeta;
 pub use rollout::find_conversation_path_by_id_str;
+pub use rollout::index::read_last_items;
+pub use rollout::index::rollout_index_path;
 pub use rollout::list::ConversationItem;
 pub use rollout::list::ConversationsPage;
 pub use rollout::list::Cursor;
 pub use rollout::list::parse_cursor;
 pub use rollout::list::read_head_for_summary;
+pub use rollout::list::read_session_summary;
+pub use rollout::list::read_token_usage;
+pub use rollout::retention::SessionGcReport;
+pub use rollout::retention::gc_sessions;
+pub use rollout::retention::spawn_session_vacuum;
 mod function_tool;
 mod state;
 mod tasks;
 mod user_notification;
 mod user_shell_command;
 pub mod util;
+mod web;
 
 pub use apply_patch::CODEX_APPLY_PATCH_ARG1;
 pub use command_safety::is_dangerous_command;
@@ -0,0 +1,780 @@
+//! Diagnostics from the language servers configured under `[lsp]`.
+//!
+//! After `apply_patch` lands, every edited file whose extension a server
+//! handles is opened in (or changed and saved to) that server, and the
+//! errors and warnings it publishes for the file are appended to the tool
+//! output. Servers start on the first matching edit, with the session's
+//! working directory as their workspace root, and run until the session
+//! ends. Like MCP servers, they run outside the sandbox.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::Mutex as StdMutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use serde_json::Value;
+use serde_json::json;
+use tokio::io::AsyncBufRead;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::process::Child;
+use tokio::process::ChildStdin;
+use tokio::process::Command;
+use tokio::sync::Mutex;
+use tokio::sync::oneshot;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use url::Url;
+
+use crate::config::types::LspServerConfig;
+
+/// How long a server may take to answer `initialize`.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Servers often publish an empty report before the real one, so after the
+/// first report for a file we keep listening until they go quiet this long.
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+/// Diagnostics listed per file; the rest are counted.
+const MAX_DIAGNOSTICS_PER_FILE: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Severity {
+    Error,
+    Warning,
+}
+
+/// One error or warning, with a one-based position.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Diagnostic {
+    pub line: u64,
+    pub column: u64,
+    pub severity: Severity,
+    pub message: String,
+    pub source: Option<String>,
+}
+
+/// What the servers said about one edited file. `diagnostics` is `None`
+/// when the server did not report within its timeout.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FileDiagnostics {
+    pub path: PathBuf,
+    pub diagnostics: Option<Vec<Diagnostic>>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct LspReport {
+    pub files: Vec<FileDiagnostics>,
+    /// Servers that failed to start. Each is reported once and then left
+    /// alone for the rest of the session.
+    pub failures: Vec<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct LspManager {
+    /// `None` marks a server that failed to start.
+    clients: Mutex<HashMap<String, Option<Arc<LspClient>>>>,
+}
+
+impl LspManager {
+    /// Syncs `edited` and `removed` files with the servers that handle them
+    /// and collects the diagnostics published for the edited ones.
+    pub(crate) async fn diagnostics(
+        &self,
+        servers: &BTreeMap<String, LspServerConfig>,
+        root: &Path,
+        edited: &[PathBuf],
+        removed: &[PathBuf],
+    ) -> LspReport {
+        let mut report = LspReport::default();
+        let mut by_server: BTreeMap<&str, (Vec<&Path>, Vec<&Path>)> = BTreeMap::new();
+        for path in edited {
+            if let Some(name) = server_for(servers, path) {
+                by_server.entry(name).or_default().0.push(path);
+            }
+        }
+        for path in removed {
+            if let Some(name) = server_for(servers, path) {
+                by_server.entry(name).or_default().1.push(path);
+            }
+        }
+
+        for (name, (edited, removed)) in by_server {
+            let Some(config) = servers.get(name) else {
+                continue;
+            };
+            let client = match self.client(name, config, root).await {
+                Ok(Some(client)) => client,
+                Ok(None) => continue,
+                Err(err) => {
+                    report
+                        .failures
+                        .push(format!("language server `{name}` failed to start: {err}"));
+                    continue;
+                }
+            };
+            match client
+                .sync(
+                    config,
+                    &edited,
+                    &removed,
+                    Duration::from_millis(config.timeout_ms),
+                )
+                .await
+            {
+                Ok(files) => report.files.extend(files),
+                Err(err) => {
+                    client.kill().await;
+                    report.failures.push(format!(
+                        "language server `{name}` stopped responding: {err}"
+                    ));
+                }
+            }
+        }
+        report.files.sort_by(|a, b| a.path.cmp(&b.path));
+        report
+    }
+
+    /// The running client for `name`, starting it first when it is not
+    /// running. `Ok(None)` means it failed to start earlier.
+    async fn client(
+        &self,
+        name: &str,
+        config: &LspServerConfig,
+        root: &Path,
+    ) -> io::Result<Option<Arc<LspClient>>> {
+        let mut clients = self.clients.lock().await;
+        match clients.get(name) {
+            Some(Some(client)) if !client.shared.closed.load(Ordering::Acquire) => {
+                return Ok(Some(client.clone()));
+            }
+            Some(None) => return Ok(None),
+            _ => {}
+        }
+        match LspClient::start(config, root).await {
+            Ok(client) => {
+                let client = Arc::new(client);
+                clients.insert(name.to_string(), Some(client.clone()));
+                Ok(Some(client))
+            }
+            Err(err) => {
+                clients.insert(name.to_string(), None);
+                Err(err)
+            }
+        }
+    }
+
+    pub(crate) async fn terminate(&self) {
+        let clients: Vec<Arc<LspClient>> = self
+            .clients
+            .lock()
+            .await
+            .drain()
+            .filter_map(|(_, client)| client)
+            .collect();
+        for client in clients {
+            client.shutdown().await;
+        }
+    }
+}
+
+/// The first configured server, in name order, that handles `path`'s
+/// extension.
+fn server_for<'a>(servers: &'a BTreeMap<String, LspServerConfig>, path: &Path) -> Option<&'a str> {
+    let extension = path.extension()?.to_str()?;
+    servers
+        .iter()
+        .find(|(_, config)| {
+            config
+                .extensions
+                .iter()
+                .any(|ext| ext.trim_start_matches('.') == extension)
+        })
+        .map(|(name, _)| name.as_str())
+}
+
+/// `languageId` for `path` when the server config does not set one.
+fn default_language_id(path: &Path) -> String {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    match extension {
+        "rs" => "rust",
+        "py" | "pyi" => "python",
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "typescriptreact",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "javascriptreact",
+        "h" => "c",
+        "cc" | "cxx" | "hpp" | "hh" => "cpp",
+        "cs" => "csharp",
+        "rb" => "ruby",
+        "kt" | "kts" => "kotlin",
+        "sh" | "bash" => "shellscript",
+        "yml" => "yaml",
+        "md" => "markdown",
+        other => other,
+    }
+    .to_string()
+}
+
+/// State shared with the task reading the server's stdout.
+struct Shared {
+    stdin: Mutex<ChildStdin>,
+    pending: StdMutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>,
+    /// Latest `publishDiagnostics` per document URI.
+    published: StdMutex<HashMap<String, Published>>,
+    /// Bumped on every `publishDiagnostics`.
+    publish_count: watch::Sender<u64>,
+    closed: AtomicBool,
+}
+
+struct Published {
+    seq: u64,
+    version: Option<i64>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+struct LspClient {
+    child: Mutex<Child>,
+    shared: Arc<Shared>,
+    next_id: AtomicI64,
+    /// Versions of the documents open in the server, by URI. Held for the
+    /// whole of [`LspClient::sync`] so concurrent edits do not interleave.
+    open: Mutex<HashMap<String, i64>>,
+}
+
+impl LspClient {
+    async fn start(config: &LspServerConfig, root: &Path) -> io::Result<Self> {
+        let (program, args) = config
+            .command
+            .split_first()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "command is empty"))?;
+        let mut child = Command::new(program)
+            .args(args)
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| io::Error::other("stdin pipe was unexpectedly not available"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| io::Error::other("stdout pipe was unexpectedly not available"))?;
+
+        let (publish_count, _) = watch::channel(0);
+        let shared = Arc::new(Shared {
+            stdin: Mutex::new(stdin),
+            pending: StdMutex::new(HashMap::new()),
+            published: StdMutex::new(HashMap::new()),
+            publish_count,
+            closed: AtomicBool::new(false),
+        });
+        tokio::spawn(read_loop(BufReader::new(stdout), shared.clone()));
+
+        let client = Self {
+            child: Mutex::new(child),
+            shared,
+            next_id: AtomicI64::new(1),
+            open: Mutex::new(HashMap::new()),
+        };
+        let root_uri = file_uri(root)?;
+        let root_name = root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| root.display().to_string());
+        let initialized = client
+            .request(
+                "initialize",
+                json!({
+                    "processId": std::process::id(),
+                    "clientInfo": { "name": "codex" },
+                    "rootUri": root_uri,
+                    "workspaceFolders": [{ "uri": root_uri, "name": root_name }],
+                    "capabilities": {
+                        "textDocument": {
+                            "synchronization": { "didSave": true },
+                            "publishDiagnostics": { "versionSupport": true },
+                        },
+                        "workspace": { "configuration": true, "workspaceFolders": true },
+                    },
+                }),
+                STARTUP_TIMEOUT,
+            )
+            .await;
+        if let Err(err) = initialized {
+            client.kill().await;
+            return Err(err);
+        }
+        client.notify("initialized", json!({})).await?;
+        Ok(client)
+    }
+
+    /// Sends the current contents of `edited` and closes `removed`, then
+    /// waits until `timeout` for diagnostics on each edited file.
+    async fn sync(
+        &self,
+        config: &LspServerConfig,
+        edited: &[&Path],
+        removed: &[&Path],
+        timeout: Duration,
+    ) -> io::Result<Vec<FileDiagnostics>> {
+        let mut open = self.open.lock().await;
+        for path in removed {
+            let uri = file_uri(path)?;
+            if open.remove(&uri).is_some() {
+                self.notify(
+                    "textDocument/didClose",
+                    json!({ "textDocument": { "uri": uri } }),
+                )
+                .await?;
+            }
+        }
+
+        let mut waiting = Vec::new();
+        for path in edited {
+            let Ok(text) = tokio::fs::read_to_string(path).await else {
+                continue;
+            };
+            let uri = file_uri(path)?;
+            if let Ok(mut published) = self.shared.published.lock() {
+                published.remove(&uri);
+            }
+            let version = match open.get_mut(&uri) {
+                Some(version) => {
+                    *version += 1;
+                    self.notify(
+                        "textDocument/didChange",
+                        json!({
+                            "textDocument": { "uri": uri, "version": *version },
+                            "contentChanges": [{ "text": text }],
+                        }),
+                    )
+                    .await?;
+                    *version
+                }
+                None => {
+                    let language_id = config
+                        .language_id
+                        .clone()
+                        .unwrap_or_else(|| default_language_id(path));
+                    self.notify(
+                        "textDocument/didOpen",
+                        json!({
+                            "textDocument": {
+                                "uri": uri,
+                                "languageId": language_id,
+                                "version": 1,
+                                "text": text,
+                            },
+                        }),
+                    )
+                    .await?;
+                    open.insert(uri.clone(), 1);
+                    1
+                }
+            };
+            self.notify(
+                "textDocument/didSave",
+                json!({ "textDocument": { "uri": uri }, "text": text }),
+            )
+            .await?;
+            waiting.push((path.to_path_buf(), uri, version));
+        }
+
+        let deadline = Instant::now() + timeout;
+        let mut files = Vec::with_capacity(waiting.len());
+        for (path, uri, version) in waiting {
+            files.push(FileDiagnostics {
+                path,
+                diagnostics: self.wait_for_diagnostics(&uri, version, deadline).await,
+            });
+        }
+        Ok(files)
+    }
+
+    /// The diagnostics published for `uri` at `version` or later, once the
+    /// server has gone quiet about it or `deadline` passes.
+    async fn wait_for_diagnostics(
+        &self,
+        uri: &str,
+        version: i64,
+        deadline: Instant,
+    ) -> Option<Vec<Diagnostic>> {
+        let mut publish_count = self.shared.publish_count.subscribe();
+        let mut seen: Option<u64> = None;
+        let mut settle_until = deadline;
+        loop {
+            if let Some(seq) = self.shared.published_seq(uri, version)
+                && seen != Some(seq)
+            {
+                seen = Some(seq);
+                settle_until = (Instant::now() + SETTLE_TIME).min(deadline);
+            }
+            if self.shared.closed.load(Ordering::Acquire) {
+                break;
+            }
+            let until = if seen.is_some() {
+                settle_until
+            } else {
+                deadline
+            };
+            match tokio::time::timeout_at(until, publish_count.changed()).await {
+                Ok(Ok(())) => {}
+                Ok(Err(_)) | Err(_) => break,
+            }
+        }
+        seen?;
+        let published = self.shared.published.lock().ok()?;
+        published
+            .get(uri)
+            .map(|published| published.diagnostics.clone())
+    }
+
+    async fn request(&self, method: &str, params: Value, timeout: Duration) -> io::Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        if let Ok(mut pending) = self.shared.pending.lock() {
+            pending.insert(id, tx);
+        }
+        self.shared
+            .send(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+            .await?;
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(message))) => Err(io::Error::other(format!("{method}: {message}"))),
+            Ok(Err(_)) => Err(io::Error::other(format!(
+                "the server exited before answering {method}"
+            ))),
+            Err(_) => {
+                if let Ok(mut pending) = self.shared.pending.lock() {
+                    pending.remove(&id);
+                }
+                Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("no answer to {method} within {} s", timeout.as_secs()),
+                ))
+            }
+        }
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> io::Result<()> {
+        self.shared
+            .send(&json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+            .await
+    }
+
+    /// Asks the server to exit, then kills it if it is still running.
+    async fn shutdown(&self) {
+        if !self.shared.closed.load(Ordering::Acquire)
+            && self
+                .request("shutdown", Value::Null, Duration::from_secs(1))
+                .await
+                .is_ok()
+        {
+            let _ = self.notify("exit", Value::Null).await;
+        }
+        self.kill().await;
+    }
+
+    async fn kill(&self) {
+        self.shared.closed.store(true, Ordering::Release);
+        let _ = self.child.lock().await.start_kill();
+    }
+}
+
+impl Shared {
+    async fn send(&self, message: &Value) -> io::Result<()> {
+        write_message(&mut *self.stdin.lock().await, message).await
+    }
+
+    /// Sequence number of the latest report for `uri`, unless the server
+    /// said it was for a version older than `version`.
+    fn published_seq(&self, uri: &str, version: i64) -> Option<u64> {
+        let published = self.published.lock().ok()?;
+        let entry = published.get(uri)?;
+        match entry.version {
+            Some(published_version) if published_version < version => None,
+            _ => Some(entry.seq),
+        }
+    }
+}
+
+/// Dispatches everything the server writes: responses go to their waiting
+/// request, diagnostics are stored, and requests from the server get a
+/// neutral answer so it does not stall.
+async fn read_loop<R: AsyncBufRead + Unpin>(mut reader: R, shared: Arc<Shared>) {
+    while let Ok(Some(message)) = read_message(&mut reader).await {
+        let method = message.get("method").and_then(Value::as_str);
+        let id = message.get("id").cloned();
+        match (method, id) {
+            (Some(method), Some(id)) => {
+                let result = match method {
+                    "workspace/configuration" => {
+                        let items = message["params"]["items"]
+                            .as_array()
+                            .map_or(0, |items| items.len());
+                        Value::Array(vec![Value::Null; items])
+                    }
+                    _ => Value::Null,
+                };
+                let reply = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+                if shared.send(&reply).await.is_err() {
+                    break;
+                }
+            }
+            (Some("textDocument/publishDiagnostics"), None) => {
+                let params = &message["params"];
+                let Some(uri) = params["uri"].as_str() else {
+                    continue;
+                };
+                let diagnostics = params["diagnostics"]
+                    .as_array()
+                    .map(|items| items.iter().filter_map(parse_diagnostic).collect())
+                    .unwrap_or_default();
+                let seq = *shared.publish_count.borrow() + 1;
+                if let Ok(mut published) = shared.published.lock() {
+                    published.insert(
+                        uri.to_string(),
+                        Published {
+                            seq,
+                            version: params["version"].as_i64(),
+                            diagnostics,
+                        },
+                    );
+                }
+                shared.publish_count.send_replace(seq);
+            }
+            (None, Some(id)) => {
+                let Some(id) = id.as_i64() else {
+                    continue;
+                };
+                let sender = shared
+                    .pending
+                    .lock()
+                    .ok()
+                    .and_then(|mut pending| pending.remove(&id));
+                if let Some(sender) = sender {
+                    let result = match message.get("error") {
+                        Some(error) => Err(error["message"]
+                            .as_str()
+                            .unwrap_or("unknown error")
+                            .to_string()),
+                        None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                    };
+                    let _ = sender.send(result);
+                }
+            }
+            _ => {}
+        }
+    }
+    shared.closed.store(true, Ordering::Release);
+    if let Ok(mut pending) = shared.pending.lock() {
+        pending.clear();
+    }
+    // Wakes anyone waiting for diagnostics.
+    shared.publish_count.send_modify(|_| {});
+}
+
+/// Errors and warnings only; information and hints are dropped. LSP leaves
+/// a missing severity to the client, and we treat it as an error.
+fn parse_diagnostic(value: &Value) -> Option<Diagnostic> {
+    let severity = match value["severity"].as_u64() {
+        None | Some(1) => Severity::Error,
+        Some(2) => Severity::Warning,
+        Some(_) => return None,
+    };
+    let start = &value["range"]["start"];
+    Some(Diagnostic {
+        line: start["line"].as_u64()? + 1,
+        column: start["character"].as_u64()? + 1,
+        severity,
+        message: value["message"].as_str()?.to_string(),
+        source: value["source"].as_str().map(str::to_string),
+    })
+}
+
+fn file_uri(path: &Path) -> io::Result<String> {
+    Url::from_file_path(path)
+        .map(String::from)
+        .map_err(|()| io::Error::other(format!("{} is not an absolute path", path.display())))
+}
+
+async fn write_message<W: AsyncWrite + Unpin>(writer: &mut W, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    writer
+        .write_all(format!("Content-Length: {}\r\n\r\n{body}", body.len()).as_bytes())
+        .await?;
+    writer.flush().await
+}
+
+/// Reads one `Content-Length`-framed message. `Ok(None)` at end of stream.
+async fn read_message<R: AsyncBufRead + Unpin>(reader: &mut R) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let mut body = vec![0; content_length.unwrap_or_default()];
+    reader.read_exact(&mut body).await?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// The section appended to the `apply_patch` output, or `None` when no
+/// configured server handles the edited files.
+pub(crate) fn format_report(report: &LspReport, cwd: &Path) -> Option<String> {
+    if report.files.is_empty() && report.failures.is_empty() {
+        return None;
+    }
+    let mut lines = Vec::new();
+    for failure in &report.failures {
+        lines.push(format!("Could not get diagnostics: {failure}"));
+    }
+    let mut clean = 0;
+    for file in &report.files {
+        let path = file.path.strip_prefix(cwd).unwrap_or(&file.path).display();
+        match &file.diagnostics {
+            None => lines.push(format!("{path}: no diagnostics reported in time")),
+            Some(diagnostics) if diagnostics.is_empty() => clean += 1,
+            Some(diagnostics) => {
+                lines.push(format!("{path}:"));
+                for diagnostic in diagnostics.iter().take(MAX_DIAGNOSTICS_PER_FILE) {
+                    let severity = match diagnostic.severity {
+                        Severity::Error => "error",
+                        Severity::Warning => "warning",
+                    };
+                    let source = diagnostic
+                        .source
+                        .as_ref()
+                        .map(|source| format!(" [{source}]"))
+                        .unwrap_or_default();
+                    lines.push(format!(
+                        "  {}:{} {severity}: {}{source}",
+                        diagnostic.line, diagnostic.column, diagnostic.message
+                    ));
+                }
+                if diagnostics.len() > MAX_DIAGNOSTICS_PER_FILE {
+                    lines.push(format!(
+                        "  ... {} more",
+                        diagnostics.len() - MAX_DIAGNOSTICS_PER_FILE
+                    ));
+                }
+            }
+        }
+    }
+    if clean > 0 && clean == report.files.len() {
+        lines.push("No errors or warnings in the edited files.".to_string());
+    } else if clean > 0 {
+        lines.push(format!(
+            "No errors or warnings in the other {clean} file(s)."
+        ));
+    }
+    Some(format!(
+        "Language server diagnostics:\n{}",
+        lines.join("\n")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn messages_round_trip_through_framing() -> anyhow::Result<()> {
+        let first = json!({ "jsonrpc": "2.0", "id": 1, "result": { "capabilities": {} } });
+        let second = json!({ "jsonrpc": "2.0", "method": "window/logMessage", "params": { "message": "ünïcode" } });
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &first).await?;
+        write_message(&mut buffer, &second).await?;
+
+        let mut reader = BufReader::new(buffer.as_slice());
+        assert_eq!(read_message(&mut reader).await?, Some(first));
+        assert_eq!(read_message(&mut reader).await?, Some(second));
+        assert_eq!(read_message(&mut reader).await?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn report_lists_errors_and_warnings_per_file() {
+        let cwd = Path::new("/repo");
+        let diagnostics = json!([
+            {
+                "range": { "start": { "line": 11, "character": 4 }, "end": { "line": 11, "character": 9 } },
+                "severity": 1,
+                "source": "rustc",
+                "message": "mismatched types",
+            },
+            {
+                "range": { "start": { "line": 2, "character": 0 }, "end": { "line": 2, "character": 1 } },
+                "severity": 3,
+                "message": "consider importing",
+            },
+            {
+                "range": { "start": { "line": 29, "character": 8 }, "end": { "line": 29, "character": 9 } },
+                "severity": 2,
+                "message": "unused variable: `x`",
+            },
+        ]);
+        let report = LspReport {
+            files: vec![
+                FileDiagnostics {
+                    path: cwd.join("src/lib.rs"),
+                    diagnostics: diagnostics
+                        .as_array()
+                        .map(|items| items.iter().filter_map(parse_diagnostic).collect()),
+                },
+                FileDiagnostics {
+                    path: cwd.join("src/main.rs"),
+                    diagnostics: Some(Vec::new()),
+                },
+                FileDiagnostics {
+                    path: cwd.join("src/slow.rs"),
+                    diagnostics: None,
+                },
+            ],
+            failures: Vec::new(),
+        };
+
+        assert_eq!(
+            format_report(&report, cwd).as_deref(),
+            Some(
+                "Language server diagnostics:\nsrc/lib.rs:\n  12:5 error: mismatched types [rustc]\n  30:9 warning: unused variable: `x`\nsrc/slow.rs: no diagnostics reported in time\nNo errors or warnings in the other 1 file(s)."
+            )
+        );
+        assert_eq!(format_report(&LspReport::default(), cwd), None);
+    }
+}
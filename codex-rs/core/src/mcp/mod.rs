@@ -3,13 +3,18 @@ use std::collections::HashMap;
 use std::env;
 use std::path::PathBuf;
 
+use async_channel::Receiver;
 use async_channel::unbounded;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
 use codex_protocol::protocol::McpListToolsResponseEvent;
+use codex_protocol::protocol::McpStartupCompleteEvent;
 use codex_protocol::protocol::SandboxPolicy;
 use mcp_types::Tool as McpTool;
 use tokio_util::sync::CancellationToken;
 
 use crate::config::Config;
+use crate::mcp::auth::McpAuthStatusEntry;
 use crate::mcp::auth::compute_auth_statuses;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::mcp_connection_manager::SandboxState;
@@ -27,6 +32,48 @@ pub async fn collect_mcp_snapshot(config: &Config) -> McpListToolsResponseEvent
         };
     }
 
+    let (mcp_connection_manager, auth_status_entries, _rx_event, cancel_token) =
+        start_mcp_servers(config).await;
+
+    let snapshot =
+        collect_mcp_snapshot_from_manager(&mcp_connection_manager, auth_status_entries).await;
+
+    cancel_token.cancel();
+
+    snapshot
+}
+
+/// Starts every enabled MCP server the way a session would and reports which
+/// came up and why the others failed. The servers are shut down afterwards.
+pub async fn check_mcp_startup(config: &Config) -> McpStartupCompleteEvent {
+    if config.mcp_servers.is_empty() {
+        return McpStartupCompleteEvent::default();
+    }
+
+    let (_mcp_connection_manager, _auth_status_entries, rx_event, cancel_token) =
+        start_mcp_servers(config).await;
+
+    let mut summary = McpStartupCompleteEvent::default();
+    while let Ok(event) = rx_event.recv().await {
+        if let EventMsg::McpStartupComplete(complete) = event.msg {
+            summary = complete;
+            break;
+        }
+    }
+
+    cancel_token.cancel();
+
+    summary
+}
+
+async fn start_mcp_servers(
+    config: &Config,
+) -> (
+    McpConnectionManager,
+    HashMap<String, McpAuthStatusEntry>,
+    Receiver<Event>,
+    CancellationToken,
+) {
     let auth_status_entries = compute_auth_statuses(
         config.mcp_servers.iter(),
         config.mcp_oauth_credentials_store_mode,
@@ -35,7 +82,6 @@ pub async fn collect_mcp_snapshot(config: &Config) -> McpListToolsResponseEvent
 
     let mut mcp_connection_manager = McpConnectionManager::default();
     let (tx_event, rx_event) = unbounded();
-    drop(rx_event);
     let cancel_token = CancellationToken::new();
 
     // Use ReadOnly sandbox policy for MCP snapshot collection (safest default)
@@ -56,12 +102,12 @@ pub async fn collect_mcp_snapshot(config: &Config) -> McpListToolsResponseEvent
         )
         .await;
 
-    let snapshot =
-        collect_mcp_snapshot_from_manager(&mcp_connection_manager, auth_status_entries).await;
-
-    cancel_token.cancel();
-
-    snapshot
+    (
+        mcp_connection_manager,
+        auth_status_entries,
+        rx_event,
+        cancel_token,
+    )
 }
 
 pub fn split_qualified_tool_name(qualified_name: &str) -> Option<(String, String)> {
@@ -95,7 +141,7 @@ pub fn group_tools_by_server(
 
 pub(crate) async fn collect_mcp_snapshot_from_manager(
     mcp_connection_manager: &McpConnectionManager,
-    auth_status_entries: HashMap<String, crate::mcp::auth::McpAuthStatusEntry>,
+    auth_status_entries: HashMap<String, McpAuthStatusEntry>,
 ) -> McpListToolsResponseEvent {
     let (tools, resources, resource_templates) = tokio::join!(
         mcp_connection_manager.list_all_tools(),
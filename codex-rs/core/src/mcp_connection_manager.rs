@@ -79,6 +79,11 @@ pub const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
 /// Default timeout for individual tool calls.
 const DEFAULT_TOOL_TIMEOUT: Duration = Duration::from_secs(60);
 
+/// Prefix shared by the fully qualified names of every MCP tool.
+pub(crate) fn qualified_tool_prefix() -> String {
+    format!("mcp{MCP_TOOL_NAME_DELIMITER}")
+}
+
 fn qualify_tools<I>(tools: I) -> HashMap<String, ToolInfo>
 where
     I: IntoIterator<Item = ToolInfo>,
@@ -415,6 +420,26 @@ impl McpConnectionManager {
         tools
     }
 
+    /// Whether `server` annotates `tool` with `readOnlyHint`. Unknown and
+    /// unannotated tools are assumed to make changes.
+    pub(crate) async fn is_read_only_tool(&self, server: &str, tool: &str) -> bool {
+        let Some(managed_client) = self.clients.get(server) else {
+            return false;
+        };
+        let Ok(client) = managed_client.client().await else {
+            return false;
+        };
+        client.tools.iter().any(|info| {
+            info.tool_name == tool
+                && info
+                    .tool
+                    .annotations
+                    .as_ref()
+                    .and_then(|annotations| annotations.read_only_hint)
+                    .unwrap_or(false)
+        })
+    }
+
     /// Returns a single map that contains all resources. Each key is the
     /// server name and the value is a vector of resources.
     pub async fn list_all_resources(&self) -> HashMap<String, Vec<Resource>> {
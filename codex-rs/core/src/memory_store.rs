@@ -0,0 +1,391 @@
+//! Project-scoped notes the agent keeps across sessions.
+//!
+//! Memories are stored in `~/.codex/memories.jsonl`, one JSON object per line:
+//!
+//! ```text
+//! {"id":"3f9c2a1b","project":"/home/me/src/app","created_at":1767225600,"text":"Integration tests need `just db-up` first.","tags":["testing"]}
+//! ```
+//!
+//! Each memory belongs to the project it was written in (the root of the main
+//! git checkout, or the working directory outside git). New entries are
+//! appended; deleting or expiring entries rewrites the file. Both hold an
+//! advisory lock on `memories.jsonl.lock`, so a rewrite in one session cannot
+//! drop an entry another session appends meanwhile.
+
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Result;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::git_info::resolve_root_git_project_for_trust;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+/// Filename of the memory store inside `~/.codex`.
+pub const MEMORY_STORE_FILENAME: &str = "memories.jsonl";
+const MEMORY_LOCK_FILENAME: &str = "memories.jsonl.lock";
+
+/// Attempts to take the store lock before giving up.
+const LOCK_RETRIES: usize = 10;
+const LOCK_RETRY_SLEEP: Duration = Duration::from_millis(100);
+
+/// Most memories injected into the instructions at session start.
+const INJECTED_MEMORIES_LIMIT: usize = 20;
+/// Upper bound on the size of the injected memories section.
+const INJECTED_MEMORIES_MAX_BYTES: usize = 4 * 1024;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MemoryEntry {
+    pub id: String,
+    pub project: String,
+    pub created_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl MemoryEntry {
+    pub fn new(
+        project: String,
+        text: String,
+        tags: Vec<String>,
+        ttl_days: Option<u64>,
+        now: u64,
+    ) -> Self {
+        let mut id = uuid::Uuid::new_v4().simple().to_string();
+        id.truncate(8);
+        Self {
+            id,
+            project,
+            created_at: now,
+            expires_at: ttl_days
+                .map(|days| now.saturating_add(days.saturating_mul(SECONDS_PER_DAY))),
+            text,
+            tags,
+        }
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// `created_at` as a UTC `YYYY-MM-DD` date.
+    pub fn created_date(&self) -> String {
+        chrono::DateTime::from_timestamp(self.created_at as i64, 0)
+            .map(|ts| ts.format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+    }
+}
+
+pub fn store_path(codex_home: &Path) -> PathBuf {
+    codex_home.join(MEMORY_STORE_FILENAME)
+}
+
+/// The project a memory written from `cwd` belongs to. Worktrees resolve to
+/// their main checkout so every agent working on a repo shares its memories.
+pub fn project_key(cwd: &Path) -> String {
+    resolve_root_git_project_for_trust(cwd)
+        .unwrap_or_else(|| cwd.to_path_buf())
+        .display()
+        .to_string()
+}
+
+pub fn now_unix_seconds() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+/// Take the store's exclusive lock, retrying briefly while another session
+/// holds it. The lock is released when the returned file is dropped.
+fn lock_store(codex_home: &Path) -> Result<File> {
+    std::fs::create_dir_all(codex_home)?;
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(false);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let file = options.open(codex_home.join(MEMORY_LOCK_FILENAME))?;
+    for _ in 0..LOCK_RETRIES {
+        match file.try_lock() {
+            Ok(()) => return Ok(file),
+            Err(std::fs::TryLockError::WouldBlock) => std::thread::sleep(LOCK_RETRY_SLEEP),
+            Err(std::fs::TryLockError::Error(err)) => return Err(err),
+        }
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::WouldBlock,
+        "could not acquire the memory store lock after multiple attempts",
+    ))
+}
+
+/// Append `entry` to the store using a single `O_APPEND` write.
+pub(crate) async fn append_entry(codex_home: &Path, entry: &MemoryEntry) -> Result<()> {
+    let codex_home = codex_home.to_path_buf();
+    let path = store_path(&codex_home);
+    let mut line = serde_json::to_string(entry)
+        .map_err(|e| std::io::Error::other(format!("failed to serialise memory: {e}")))?;
+    line.push('\n');
+
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let _lock = lock_store(&codex_home)?;
+        let mut options = OpenOptions::new();
+        options.append(true).create(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options.open(&path)?;
+        file.write_all(line.as_bytes())?;
+        file.flush()
+    })
+    .await?
+}
+
+/// Read every well-formed entry. A missing store yields an empty list;
+/// malformed lines are skipped.
+pub fn read_entries(codex_home: &Path) -> Result<Vec<MemoryEntry>> {
+    let contents = match std::fs::read_to_string(store_path(codex_home)) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<MemoryEntry>(line).ok())
+        .collect())
+}
+
+/// Drop every entry matching `remove` and return how many were removed.
+/// Blocks while another session holds the store lock.
+pub fn remove_entries(
+    codex_home: &Path,
+    mut remove: impl FnMut(&MemoryEntry) -> bool,
+) -> Result<usize> {
+    let _lock = lock_store(codex_home)?;
+    let entries = read_entries(codex_home)?;
+    let before = entries.len();
+    let kept: Vec<MemoryEntry> = entries.into_iter().filter(|e| !remove(e)).collect();
+    let removed = before - kept.len();
+    if removed == 0 {
+        return Ok(0);
+    }
+
+    let mut contents = String::new();
+    for entry in &kept {
+        let line = serde_json::to_string(entry)
+            .map_err(|e| std::io::Error::other(format!("failed to serialise memory: {e}")))?;
+        contents.push_str(&line);
+        contents.push('\n');
+    }
+    let path = store_path(codex_home);
+    let tmp = path.with_extension("jsonl.tmp");
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(&tmp)?;
+    file.write_all(contents.as_bytes())?;
+    file.flush()?;
+    std::fs::rename(&tmp, &path)?;
+    Ok(removed)
+}
+
+/// Live memories for `project`, best match first. Every whitespace-separated
+/// query term found in an entry's text or tags adds to its score; ties go to
+/// the newer entry. An empty query returns the newest entries.
+pub fn search<'a>(
+    entries: &'a [MemoryEntry],
+    project: &str,
+    query: &str,
+    limit: usize,
+    now: u64,
+) -> Vec<&'a MemoryEntry> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let mut scored: Vec<(usize, &MemoryEntry)> = entries
+        .iter()
+        .filter(|entry| entry.project == project && !entry.is_expired(now))
+        .filter_map(|entry| {
+            if terms.is_empty() {
+                return Some((0, entry));
+            }
+            let haystack = format!("{} {}", entry.text, entry.tags.join(" ")).to_lowercase();
+            let score = terms
+                .iter()
+                .filter(|term| haystack.contains(term.as_str()))
+                .count();
+            (score > 0).then_some((score, entry))
+        })
+        .collect();
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .cmp(a_score)
+            .then_with(|| b.created_at.cmp(&a.created_at))
+    });
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, entry)| entry)
+        .collect()
+}
+
+/// Render memories as an instructions section, stopping before it grows past
+/// [`INJECTED_MEMORIES_MAX_BYTES`].
+pub(crate) fn render_memories_section(memories: &[&MemoryEntry]) -> Option<String> {
+    if memories.is_empty() {
+        return None;
+    }
+    let mut out = String::from(
+        "## Memories\nNotes saved in earlier sessions on this project, newest first. \
+         Use `memory_search` to look up more and `memory_write` to record durable facts.\n",
+    );
+    for memory in memories {
+        let line = format!("- [{}] {}\n", memory.id, memory.text.replace('\n', " "));
+        if out.len() + line.len() > INJECTED_MEMORIES_MAX_BYTES {
+            break;
+        }
+        out.push_str(&line);
+    }
+    Some(out)
+}
+
+/// Append the project's most recent memories to `user_instructions`.
+pub(crate) async fn instructions_with_memories(
+    user_instructions: Option<String>,
+    config: &Config,
+) -> Option<String> {
+    let codex_home = config.codex_home.clone();
+    let entries = match tokio::task::spawn_blocking(move || read_entries(&codex_home)).await {
+        Ok(Ok(entries)) => entries,
+        Ok(Err(err)) => {
+            tracing::warn!("failed to read memories: {err}");
+            return user_instructions;
+        }
+        Err(_) => return user_instructions,
+    };
+    let project = project_key(&config.cwd);
+    let recent = search(
+        &entries,
+        &project,
+        "",
+        INJECTED_MEMORIES_LIMIT,
+        now_unix_seconds(),
+    );
+    match (user_instructions, render_memories_section(&recent)) {
+        (Some(instructions), Some(section)) => Some(format!("{instructions}\n\n{section}")),
+        (instructions, None) => instructions,
+        (None, section) => section,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn entry(id: &str, project: &str, created_at: u64, text: &str) -> MemoryEntry {
+        MemoryEntry {
+            id: id.to_string(),
+            project: project.to_string(),
+            created_at,
+            expires_at: None,
+            text: text.to_string(),
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn search_ranks_by_matching_terms_then_recency() {
+        let mut tagged = entry("c", "/repo", 3, "Run migrations first");
+        tagged.tags = vec!["database".to_string()];
+        let mut expired = entry("d", "/repo", 4, "database url is localhost");
+        expired.expires_at = Some(10);
+        let entries = vec![
+            entry("a", "/repo", 1, "The database tests are slow"),
+            entry("b", "/repo", 2, "Database tests need docker running"),
+            tagged,
+            expired,
+            entry("e", "/other", 5, "database tests elsewhere"),
+        ];
+
+        let ids = |found: Vec<&MemoryEntry>| {
+            found
+                .into_iter()
+                .map(|entry| entry.id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(search(&entries, "/repo", "database tests", 10, 20)),
+            vec!["b", "a", "c"]
+        );
+        assert_eq!(ids(search(&entries, "/repo", "", 2, 20)), vec!["c", "b"]);
+    }
+
+    #[tokio::test]
+    async fn append_read_and_remove_round_trip() {
+        let home = TempDir::new().expect("tempdir");
+        let first = entry("a", "/repo", 1, "first");
+        let second = entry("b", "/repo", 2, "second");
+        append_entry(home.path(), &first).await.expect("append");
+        append_entry(home.path(), &second).await.expect("append");
+        assert_eq!(
+            read_entries(home.path()).expect("read"),
+            vec![first, second.clone()]
+        );
+
+        let removed = remove_entries(home.path(), |entry| entry.id == "a").expect("remove");
+        assert_eq!(removed, 1);
+        assert_eq!(read_entries(home.path()).expect("read"), vec![second]);
+    }
+
+    #[test]
+    fn huge_ttl_saturates() {
+        let entry = MemoryEntry::new(
+            "/repo".to_string(),
+            "note".to_string(),
+            Vec::new(),
+            Some(u64::MAX),
+            100,
+        );
+        assert_eq!(entry.expires_at, Some(u64::MAX));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn appends_during_a_rewrite_are_kept() {
+        let home = TempDir::new().expect("tempdir");
+        append_entry(home.path(), &entry("a", "/repo", 1, "old"))
+            .await
+            .expect("append");
+
+        // Hold the lock the way a rewrite in another session would, then
+        // append: the append must wait rather than land in the replaced file.
+        let lock = lock_store(home.path()).expect("lock");
+        let writer = tokio::spawn({
+            let home = home.path().to_path_buf();
+            async move { append_entry(&home, &entry("b", "/repo", 2, "new")).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!writer.is_finished());
+        drop(lock);
+        writer.await.expect("join").expect("append");
+
+        let removed = remove_entries(home.path(), |entry| entry.id == "a").expect("remove");
+        assert_eq!(removed, 1);
+        let ids: Vec<String> = read_entries(home.path())
+            .expect("read")
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect();
+        assert_eq!(ids, vec!["b".to_string()]);
+    }
+}
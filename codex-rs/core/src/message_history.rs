@@ -35,6 +35,8 @@ use tokio::io::AsyncReadExt;
 
 use crate::config::Config;
 use crate::config::types::HistoryPersistence;
+use crate::storage_encryption::decode_line;
+use crate::storage_encryption::writer_cipher;
 
 use codex_protocol::ConversationId;
 #[cfg(unix)]
@@ -82,7 +84,7 @@ pub(crate) async fn append_entry(
         }
     }
 
-    // TODO: check `text` for sensitive patterns
+    // Callers mask secrets first when `[redaction]` is enabled.
 
     // Resolve `~/.codex/history.jsonl` and ensure the parent directory exists.
     let path = history_filepath(config);
@@ -104,6 +106,9 @@ pub(crate) async fn append_entry(
     };
     let mut line = serde_json::to_string(&entry)
         .map_err(|e| std::io::Error::other(format!("failed to serialise history entry: {e}")))?;
+    if let Some(cipher) = writer_cipher(config)? {
+        line = cipher.encrypt_line(&line)?;
+    }
     line.push('\n');
 
     // Open the history file for read/write access (append-only on Unix).
@@ -356,6 +361,13 @@ fn lookup_history_entry(path: &Path, log_id: u64, offset: usize) -> Option<Histo
                     };
 
                     if idx == offset {
+                        let line = match decode_line(&line) {
+                            Ok(line) => line,
+                            Err(e) => {
+                                tracing::warn!(error = %e, "failed to decrypt history entry");
+                                return None;
+                            }
+                        };
                         match serde_json::from_str::<HistoryEntry>(&line) {
                             Ok(entry) => return Some(entry),
                             Err(e) => {
@@ -9,19 +9,22 @@ use codex_api::Provider as ApiProvider;
 use codex_api::WireApi as ApiWireApi;
 use codex_api::provider::RetryConfig as ApiRetryConfig;
 use codex_app_server_protocol::AuthMode;
+use codex_client::HttpClientOptions;
 use http::HeaderMap;
 use http::header::HeaderName;
 use http::header::HeaderValue;
 use serde::Deserialize;
 use serde::Serialize;
 use std::collections::HashMap;
-use std::env::VarError;
 use std::time::Duration;
 
+use crate::credential_store;
 use crate::error::EnvVarError;
+use crate::provider_auth;
 const DEFAULT_STREAM_IDLE_TIMEOUT_MS: u64 = 300_000;
 const DEFAULT_STREAM_MAX_RETRIES: u64 = 5;
 const DEFAULT_REQUEST_MAX_RETRIES: u64 = 4;
+const DEFAULT_REQUEST_MAX_RETRY_TIME_MS: u64 = 120_000;
 /// Hard cap for user-configured `stream_max_retries`.
 const MAX_STREAM_MAX_RETRIES: u64 = 100;
 /// Hard cap for user-configured `request_max_retries`.
@@ -45,6 +48,15 @@ pub enum WireApi {
     /// Regular Chat Completions compatible with `/v1/chat/completions`.
     #[default]
     Chat,
+
+    /// The Anthropic Messages API at `/v1/messages`.
+    Anthropic,
+
+    /// The Gemini API's `streamGenerateContent` endpoint.
+    Gemini,
+
+    /// Anthropic models on Amazon Bedrock, signed with AWS SigV4.
+    Bedrock,
 }
 
 /// Serializable representation of a provider definition.
@@ -93,12 +105,102 @@ pub struct ModelProviderInfo {
     /// the connection as lost.
     pub stream_idle_timeout_ms: Option<u64>,
 
+    /// Retry requests rejected with `429 Too Many Requests`, waiting as long as
+    /// the provider's `Retry-After` or rate-limit reset headers ask. Defaults
+    /// to true; exhausted quotas and plan limits are never retried.
+    pub retry_rate_limited: Option<bool>,
+
+    /// Maximum total time (in milliseconds) to spend waiting between retries
+    /// of a single request before giving up.
+    pub request_max_retry_time_ms: Option<u64>,
+
     /// Does this provider require an OpenAI API Key or ChatGPT login token? If true,
     /// user is presented with login screen on first run, and login preference and token/key
     /// are stored in auth.json. If false (which is the default), login screen is skipped,
     /// and API key (if needed) comes from the "env_key" environment variable.
     #[serde(default)]
     pub requires_openai_auth: bool,
+
+    /// Gemini `safetySettings`, as harm category to block threshold (for
+    /// example `HARM_CATEGORY_DANGEROUS_CONTENT = "BLOCK_ONLY_HIGH"`). Only
+    /// sent when `wire_api` is `gemini`.
+    pub safety_settings: Option<HashMap<String, String>>,
+
+    /// Compatibility mode for local OpenAI-compatible servers such as Ollama,
+    /// llama.cpp or LM Studio. Set (even to an empty table) to enable it.
+    pub local_quirks: Option<LocalModelQuirks>,
+
+    /// Proxy and CA settings for this provider's requests, overriding the
+    /// ones from `[http]`.
+    pub http: Option<HttpClientOptions>,
+
+    /// Azure OpenAI deployment, API version and Entra ID token settings.
+    pub azure: Option<AzureProviderOptions>,
+
+    /// Region and credentials profile for `wire_api = "bedrock"`.
+    pub bedrock: Option<BedrockProviderOptions>,
+
+    /// Set to `false` for providers whose terms forbid storing reasoning
+    /// traces. Reasoning still reaches the model during the session but is
+    /// left out of the session files, so resumed sessions start without it.
+    pub persist_reasoning: Option<bool>,
+}
+
+/// Describes an Azure OpenAI deployment without spelling out its URL layout.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AzureProviderOptions {
+    /// Deployment to send requests to. It is sent as the model name, and with
+    /// `wire_api = "chat"` also selects the `deployments/<name>` path.
+    pub deployment: Option<String>,
+
+    /// Sent as the `api-version` query parameter.
+    pub api_version: Option<String>,
+
+    /// Command that prints a Microsoft Entra ID access token, used as the
+    /// bearer token instead of an API key. For example
+    /// `["az", "account", "get-access-token", "--resource",
+    /// "https://cognitiveservices.azure.com", "--query", "accessToken", "-o", "tsv"]`.
+    pub token_command: Option<Vec<String>>,
+}
+
+/// Where Bedrock requests go and which AWS keys sign them.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct BedrockProviderOptions {
+    /// AWS region; defaults to `AWS_REGION`, then `AWS_DEFAULT_REGION`.
+    pub region: Option<String>,
+
+    /// Profile in the shared credentials file, used when the
+    /// `AWS_ACCESS_KEY_ID` variables are unset. Defaults to `AWS_PROFILE`,
+    /// then `default`.
+    pub profile: Option<String>,
+}
+
+/// Settings for talking to a model served locally rather than by a hosted API.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub struct LocalModelQuirks {
+    /// Describe tools in the prompt and read tool calls back out of
+    /// structured JSON replies, for models without native function calling.
+    /// Only applies to `wire_api = "chat"`.
+    #[serde(default)]
+    pub emulate_tool_calls: bool,
+
+    /// Ask the server for the loaded model's context length when
+    /// `model_context_window` is not configured.
+    #[serde(default = "default_detect_context_window")]
+    pub detect_context_window: bool,
+}
+
+impl Default for LocalModelQuirks {
+    fn default() -> Self {
+        Self {
+            emulate_tool_calls: false,
+            detect_context_window: default_detect_context_window(),
+        }
+    }
+}
+
+const fn default_detect_context_window() -> bool {
+    true
 }
 
 impl ModelProviderInfo {
@@ -131,32 +233,60 @@ impl ModelProviderInfo {
         &self,
         auth_mode: Option<AuthMode>,
     ) -> crate::error::Result<ApiProvider> {
-        let default_base_url = if matches!(auth_mode, Some(AuthMode::ChatGPT)) {
-            "https://chatgpt.com/backend-api/codex"
+        let default_base_url = if self.wire_api == WireApi::Anthropic {
+            "https://api.anthropic.com/v1".to_string()
+        } else if self.wire_api == WireApi::Gemini {
+            "https://generativelanguage.googleapis.com/v1beta".to_string()
+        } else if self.wire_api == WireApi::Bedrock && self.base_url.is_none() {
+            let region = provider_auth::aws_region(
+                self.bedrock
+                    .as_ref()
+                    .and_then(|bedrock| bedrock.region.as_deref()),
+            )?;
+            format!("https://bedrock-runtime.{region}.amazonaws.com")
+        } else if matches!(auth_mode, Some(AuthMode::ChatGPT)) {
+            "https://chatgpt.com/backend-api/codex".to_string()
         } else {
-            "https://api.openai.com/v1"
+            "https://api.openai.com/v1".to_string()
         };
-        let base_url = self
-            .base_url
-            .clone()
-            .unwrap_or_else(|| default_base_url.to_string());
+        let mut base_url = self.base_url.clone().unwrap_or(default_base_url);
+        let mut query_params = self.query_params.clone();
+        if let Some(azure) = &self.azure {
+            if let Some(deployment) = &azure.deployment
+                && self.wire_api == WireApi::Chat
+            {
+                base_url = format!(
+                    "{}/deployments/{deployment}",
+                    base_url.trim_end_matches('/')
+                );
+            }
+            if let Some(api_version) = &azure.api_version {
+                query_params
+                    .get_or_insert_with(HashMap::new)
+                    .insert("api-version".to_string(), api_version.clone());
+            }
+        }
 
         let headers = self.build_header_map()?;
         let retry = ApiRetryConfig {
             max_attempts: self.request_max_retries(),
             base_delay: Duration::from_millis(200),
-            retry_429: false,
+            retry_429: self.retry_rate_limited.unwrap_or(true),
             retry_5xx: true,
             retry_transport: true,
+            max_retry_time: Some(self.request_max_retry_time()),
         };
 
         Ok(ApiProvider {
             name: self.name.clone(),
             base_url,
-            query_params: self.query_params.clone(),
+            query_params,
             wire: match self.wire_api {
                 WireApi::Responses => ApiWireApi::Responses,
                 WireApi::Chat => ApiWireApi::Chat,
+                WireApi::Anthropic => ApiWireApi::Anthropic,
+                WireApi::Gemini => ApiWireApi::Gemini,
+                WireApi::Bedrock => ApiWireApi::Bedrock,
             },
             headers,
             retry,
@@ -165,31 +295,40 @@ impl ModelProviderInfo {
     }
 
     /// If `env_key` is Some, returns the API key for this provider if present
-    /// (and non-empty) in the environment. If `env_key` is required but
-    /// cannot be found, returns an error.
+    /// (and non-empty) in the environment, or else stored under that name
+    /// with `codex auth set`. If `env_key` is required but cannot be found,
+    /// returns an error.
     pub fn api_key(&self) -> crate::error::Result<Option<String>> {
         match &self.env_key {
-            Some(env_key) => {
-                let env_value = std::env::var(env_key);
-                env_value
-                    .and_then(|v| {
-                        if v.trim().is_empty() {
-                            Err(VarError::NotPresent)
-                        } else {
-                            Ok(Some(v))
-                        }
-                    })
-                    .map_err(|_| {
-                        crate::error::CodexErr::EnvVar(EnvVarError {
-                            var: env_key.clone(),
-                            instructions: self.env_key_instructions.clone(),
-                        })
+            Some(env_key) => std::env::var(env_key)
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .or_else(|| credential_store::stored_secret(env_key))
+                .map(Some)
+                .ok_or_else(|| {
+                    crate::error::CodexErr::EnvVar(EnvVarError {
+                        var: env_key.clone(),
+                        instructions: self.env_key_instructions.clone(),
                     })
-            }
+                }),
             None => Ok(None),
         }
     }
 
+    /// Model name to put in requests: the Azure deployment when one is
+    /// configured, else `model`.
+    pub fn request_model(&self, model: &str) -> String {
+        self.azure
+            .as_ref()
+            .and_then(|azure| azure.deployment.clone())
+            .unwrap_or_else(|| model.to_string())
+    }
+
+    /// Whether reasoning may be written to session files.
+    pub fn persists_reasoning(&self) -> bool {
+        self.persist_reasoning.unwrap_or(true)
+    }
+
     /// Effective maximum number of request retries for this provider.
     pub fn request_max_retries(&self) -> u64 {
         self.request_max_retries
@@ -197,6 +336,14 @@ impl ModelProviderInfo {
             .min(MAX_REQUEST_MAX_RETRIES)
     }
 
+    /// Effective cap on the time spent waiting between request retries.
+    pub fn request_max_retry_time(&self) -> Duration {
+        Duration::from_millis(
+            self.request_max_retry_time_ms
+                .unwrap_or(DEFAULT_REQUEST_MAX_RETRY_TIME_MS),
+        )
+    }
+
     /// Effective maximum number of stream reconnection attempts for this provider.
     pub fn stream_max_retries(&self) -> u64 {
         self.stream_max_retries
@@ -246,7 +393,15 @@ impl ModelProviderInfo {
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            retry_rate_limited: None,
+            request_max_retry_time_ms: None,
             requires_openai_auth: true,
+            safety_settings: None,
+            local_quirks: None,
+            http: None,
+            azure: None,
+            bedrock: None,
+            persist_reasoning: None,
         }
     }
 
@@ -319,7 +474,15 @@ pub fn create_oss_provider_with_base_url(base_url: &str, wire_api: WireApi) -> M
         request_max_retries: None,
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
+        retry_rate_limited: None,
+        request_max_retry_time_ms: None,
         requires_openai_auth: false,
+        safety_settings: None,
+        local_quirks: Some(LocalModelQuirks::default()),
+        http: None,
+        azure: None,
+        bedrock: None,
+        persist_reasoning: None,
     }
 }
 
@@ -347,7 +510,15 @@ base_url = "http://localhost:11434/v1"
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            retry_rate_limited: None,
+            request_max_retry_time_ms: None,
             requires_openai_auth: false,
+            safety_settings: None,
+            local_quirks: None,
+            http: None,
+            azure: None,
+            bedrock: None,
+            persist_reasoning: None,
         };
 
         let provider: ModelProviderInfo = toml::from_str(azure_provider_toml).unwrap();
@@ -377,7 +548,15 @@ query_params = { api-version = "2025-04-01-preview" }
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            retry_rate_limited: None,
+            request_max_retry_time_ms: None,
             requires_openai_auth: false,
+            safety_settings: None,
+            local_quirks: None,
+            http: None,
+            azure: None,
+            bedrock: None,
+            persist_reasoning: None,
         };
 
         let provider: ModelProviderInfo = toml::from_str(azure_provider_toml).unwrap();
@@ -410,13 +589,74 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            retry_rate_limited: None,
+            request_max_retry_time_ms: None,
             requires_openai_auth: false,
+            safety_settings: None,
+            local_quirks: None,
+            http: None,
+            azure: None,
+            bedrock: None,
+            persist_reasoning: None,
         };
 
         let provider: ModelProviderInfo = toml::from_str(azure_provider_toml).unwrap();
         assert_eq!(expected_provider, provider);
     }
 
+    #[test]
+    fn azure_and_bedrock_tables_shape_the_api_provider() {
+        let azure: ModelProviderInfo = toml::from_str(
+            r#"
+name = "Azure"
+base_url = "https://example.openai.azure.com/openai/"
+azure = { deployment = "codex-prod", api_version = "2024-10-21" }
+"#,
+        )
+        .unwrap();
+        let api = azure.to_api_provider(None).expect("api provider");
+        assert_eq!(
+            api.url_for_path("chat/completions"),
+            "https://example.openai.azure.com/openai/deployments/codex-prod/chat/completions?api-version=2024-10-21"
+        );
+        assert_eq!(azure.request_model("gpt-5.1"), "codex-prod");
+
+        let bedrock: ModelProviderInfo = toml::from_str(
+            r#"
+name = "Bedrock"
+wire_api = "bedrock"
+bedrock = { region = "eu-central-1", profile = "work" }
+"#,
+        )
+        .unwrap();
+        let api = bedrock.to_api_provider(None).expect("api provider");
+        assert_eq!(
+            api.base_url,
+            "https://bedrock-runtime.eu-central-1.amazonaws.com"
+        );
+        assert_eq!(api.wire, ApiWireApi::Bedrock);
+        assert_eq!(
+            bedrock.request_model("anthropic.claude"),
+            "anthropic.claude"
+        );
+    }
+
+    #[test]
+    fn reasoning_is_persisted_unless_the_provider_opts_out() {
+        let provider: ModelProviderInfo = toml::from_str(r#"name = "Default""#).unwrap();
+        assert!(provider.persists_reasoning());
+
+        let provider: ModelProviderInfo = toml::from_str(
+            r#"
+name = "No reasoning storage"
+wire_api = "anthropic"
+persist_reasoning = false
+"#,
+        )
+        .unwrap();
+        assert!(!provider.persists_reasoning());
+    }
+
     #[test]
     fn detects_azure_responses_base_urls() {
         let positive_cases = [
@@ -441,7 +681,15 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
                 request_max_retries: None,
                 stream_max_retries: None,
                 stream_idle_timeout_ms: None,
+                retry_rate_limited: None,
+                request_max_retry_time_ms: None,
                 requires_openai_auth: false,
+                safety_settings: None,
+                local_quirks: None,
+                http: None,
+                azure: None,
+                bedrock: None,
+                persist_reasoning: None,
             };
             let api = provider.to_api_provider(None).expect("api provider");
             assert!(
@@ -463,7 +711,15 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
             request_max_retries: None,
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
+            retry_rate_limited: None,
+            request_max_retry_time_ms: None,
             requires_openai_auth: false,
+            safety_settings: None,
+            local_quirks: None,
+            http: None,
+            azure: None,
+            bedrock: None,
+            persist_reasoning: None,
         };
         let named_api = named_provider.to_api_provider(None).expect("api provider");
         assert!(named_api.is_azure_responses_endpoint());
@@ -487,7 +743,15 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
                 request_max_retries: None,
                 stream_max_retries: None,
                 stream_idle_timeout_ms: None,
+                retry_rate_limited: None,
+                request_max_retry_time_ms: None,
                 requires_openai_auth: false,
+                safety_settings: None,
+                local_quirks: None,
+                http: None,
+                azure: None,
+                bedrock: None,
+                persist_reasoning: None,
             };
             let api = provider.to_api_provider(None).expect("api provider");
             assert!(
@@ -0,0 +1,169 @@
+//! Selection of the provider and model that serve a model request, from the
+//! `[model_routing]` section of `config.toml`.
+//!
+//! The first matching rule picks the model for a request; without a match the
+//! session model is used. The configured fallbacks follow, and the client moves
+//! down the list whenever a request fails with an error that another provider
+//! or model might not hit (rate limits and server errors).
+
+use codex_protocol::models::ResponseItem;
+use http::StatusCode;
+
+use crate::config::types::ModelRouteTarget;
+use crate::config::types::ModelRouting;
+use crate::config::types::RequestPurpose;
+use crate::error::CodexErr;
+
+/// Returns the targets to try in order for a request. `None` stands for the
+/// session provider and model; repeated targets are dropped.
+pub(crate) fn candidates(
+    routing: &ModelRouting,
+    purpose: RequestPurpose,
+    input_tokens: i64,
+) -> Vec<Option<ModelRouteTarget>> {
+    let primary = routing
+        .rules
+        .iter()
+        .find(|rule| {
+            rule.purpose.is_none_or(|p| p == purpose)
+                && rule.min_input_tokens.is_none_or(|min| input_tokens >= min)
+        })
+        .map(|rule| rule.target.clone());
+
+    let mut candidates = vec![primary];
+    for fallback in &routing.fallbacks {
+        let fallback = Some(fallback.clone());
+        if !candidates.contains(&fallback) {
+            candidates.push(fallback);
+        }
+    }
+    candidates
+}
+
+/// Whether `err` should move the request on to the next candidate.
+pub(crate) fn should_fail_over(err: &CodexErr) -> bool {
+    match err {
+        CodexErr::RetryLimit(_)
+        | CodexErr::InternalServerError
+        | CodexErr::UsageLimitReached(_)
+        | CodexErr::QuotaExceeded => true,
+        CodexErr::UnexpectedStatus(err) => {
+            err.status == StatusCode::TOO_MANY_REQUESTS || err.status.is_server_error()
+        }
+        _ => false,
+    }
+}
+
+/// `input` with the encrypted content of reasoning items removed, for a
+/// request that goes to a different provider than the one that produced it.
+pub(crate) fn without_encrypted_reasoning(input: &[ResponseItem]) -> Vec<ResponseItem> {
+    input
+        .iter()
+        .cloned()
+        .map(|item| match item {
+            ResponseItem::Reasoning {
+                id,
+                summary,
+                content,
+                encrypted_content: Some(_),
+            } => ResponseItem::Reasoning {
+                id,
+                summary,
+                content,
+                encrypted_content: None,
+            },
+            item => item,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::types::ModelRoutingRule;
+    use crate::error::UnexpectedResponseError;
+    use pretty_assertions::assert_eq;
+
+    fn target(model: &str, provider: Option<&str>) -> ModelRouteTarget {
+        ModelRouteTarget {
+            model: model.to_string(),
+            provider: provider.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn first_matching_rule_is_followed_by_unique_fallbacks() {
+        let routing = ModelRouting {
+            rules: vec![
+                ModelRoutingRule {
+                    min_input_tokens: None,
+                    purpose: Some(RequestPurpose::Compact),
+                    target: target("mini", None),
+                },
+                ModelRoutingRule {
+                    min_input_tokens: Some(100_000),
+                    purpose: None,
+                    target: target("long", Some("openrouter")),
+                },
+            ],
+            fallbacks: vec![target("long", Some("openrouter")), target("backup", None)],
+        };
+
+        assert_eq!(
+            candidates(&routing, RequestPurpose::Turn, 10),
+            vec![
+                None,
+                Some(target("long", Some("openrouter"))),
+                Some(target("backup", None)),
+            ]
+        );
+        assert_eq!(
+            candidates(&routing, RequestPurpose::Turn, 150_000),
+            vec![
+                Some(target("long", Some("openrouter"))),
+                Some(target("backup", None)),
+            ]
+        );
+        assert_eq!(
+            candidates(&routing, RequestPurpose::Compact, 150_000)[0],
+            Some(target("mini", None))
+        );
+    }
+
+    #[test]
+    fn fails_over_on_rate_limits_and_server_errors_only() {
+        let status = |status| {
+            CodexErr::UnexpectedStatus(UnexpectedResponseError {
+                status,
+                body: String::new(),
+                request_id: None,
+            })
+        };
+        assert!(should_fail_over(&status(StatusCode::SERVICE_UNAVAILABLE)));
+        assert!(should_fail_over(&status(StatusCode::TOO_MANY_REQUESTS)));
+        assert!(should_fail_over(&CodexErr::InternalServerError));
+        assert!(!should_fail_over(&status(StatusCode::NOT_FOUND)));
+        assert!(!should_fail_over(&CodexErr::ContextWindowExceeded));
+        assert!(!should_fail_over(&CodexErr::InvalidRequest(String::new())));
+    }
+
+    #[test]
+    fn encrypted_reasoning_is_dropped_and_other_items_kept() {
+        let reasoning = |encrypted_content: Option<&str>| ResponseItem::Reasoning {
+            id: "rs_1".to_string(),
+            summary: Vec::new(),
+            content: None,
+            encrypted_content: encrypted_content.map(str::to_string),
+        };
+        let message = ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: Vec::new(),
+        };
+
+        assert_eq!(
+            without_encrypted_reasoning(&[message.clone(), reasoning(Some("gAAAA"))]),
+            vec![message, reasoning(None)]
+        );
+    }
+}
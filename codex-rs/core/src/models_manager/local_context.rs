@@ -0,0 +1,141 @@
+//! Context-length discovery for models served by local OpenAI-compatible
+//! servers. Ollama, llama.cpp and LM Studio each report the loaded model's
+//! context size from a different endpoint, so they are probed in turn and the
+//! first answer wins.
+
+use std::time::Duration;
+
+use reqwest::Client;
+use serde_json::Value;
+use serde_json::json;
+use tracing::debug;
+
+use crate::default_client::build_reqwest_client;
+
+const DETECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Returns the context length the local server at `base_url` runs `model`
+/// with, or `None` when no known endpoint reports it.
+pub(crate) async fn detect_context_window(base_url: &str, model: &str) -> Option<i64> {
+    let client = build_reqwest_client();
+    let root = host_root(base_url);
+    let detected = match ollama_context_window(&client, &root, model).await {
+        Some(window) => Some(window),
+        None => match llama_cpp_context_window(&client, &root).await {
+            Some(window) => Some(window),
+            None => lmstudio_context_window(&client, &root, model).await,
+        },
+    };
+    debug!("detected context window for {model} at {root}: {detected:?}");
+    detected
+}
+
+fn host_root(base_url: &str) -> String {
+    let trimmed = base_url.trim_end_matches('/');
+    trimmed.strip_suffix("/v1").unwrap_or(trimmed).to_string()
+}
+
+async fn get_json(request: reqwest::RequestBuilder) -> Option<Value> {
+    let response = request.timeout(DETECT_TIMEOUT).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    response.json().await.ok()
+}
+
+/// Ollama: the runtime context of a loaded model (`/api/ps`), then an explicit
+/// `num_ctx` parameter, then the model's trained context length (`/api/show`).
+async fn ollama_context_window(client: &Client, root: &str, model: &str) -> Option<i64> {
+    if let Some(ps) = get_json(client.get(format!("{root}/api/ps"))).await
+        && let Some(window) = ps["models"].as_array().and_then(|models| {
+            models
+                .iter()
+                .find(|m| m["name"] == model || m["model"] == model)
+                .and_then(|m| m["context_length"].as_i64())
+        })
+    {
+        return Some(window);
+    }
+    let show = get_json(
+        client
+            .post(format!("{root}/api/show"))
+            .json(&json!({"model": model})),
+    )
+    .await?;
+    ollama_show_context_window(&show)
+}
+
+fn ollama_show_context_window(show: &Value) -> Option<i64> {
+    let num_ctx = show["parameters"].as_str().and_then(|parameters| {
+        parameters.lines().find_map(|line| {
+            let mut parts = line.split_whitespace();
+            (parts.next() == Some("num_ctx"))
+                .then(|| parts.next())
+                .flatten()
+                .and_then(|value| value.parse().ok())
+        })
+    });
+    num_ctx.or_else(|| {
+        show["model_info"].as_object().and_then(|info| {
+            info.iter()
+                .find(|(key, _)| key.ends_with(".context_length"))
+                .and_then(|(_, value)| value.as_i64())
+        })
+    })
+}
+
+/// llama.cpp `llama-server` reports the slot context size in `/props`.
+async fn llama_cpp_context_window(client: &Client, root: &str) -> Option<i64> {
+    let props = get_json(client.get(format!("{root}/props"))).await?;
+    props["default_generation_settings"]["n_ctx"]
+        .as_i64()
+        .or_else(|| props["n_ctx"].as_i64())
+}
+
+/// LM Studio's REST API reports both the loaded and the maximum context.
+async fn lmstudio_context_window(client: &Client, root: &str, model: &str) -> Option<i64> {
+    let info = get_json(client.get(format!("{root}/api/v0/models/{model}"))).await?;
+    info["loaded_context_length"]
+        .as_i64()
+        .or_else(|| info["max_context_length"].as_i64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+
+    #[test]
+    fn ollama_show_prefers_num_ctx_over_trained_length() {
+        let show = json!({
+            "parameters": "stop \"<|im_end|>\"\nnum_ctx 16384",
+            "model_info": {"qwen2.context_length": 32768},
+        });
+        assert_eq!(ollama_show_context_window(&show), Some(16_384));
+
+        let show =
+            json!({"model_info": {"general.architecture": "qwen2", "qwen2.context_length": 32768}});
+        assert_eq!(ollama_show_context_window(&show), Some(32_768));
+    }
+
+    #[tokio::test]
+    async fn detects_llama_cpp_context_from_props() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/props"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"default_generation_settings": {"n_ctx": 8192}})),
+            )
+            .mount(&server)
+            .await;
+
+        let detected = detect_context_window(&format!("{}/v1", server.uri()), "local").await;
+        assert_eq!(detected, Some(8_192));
+    }
+}
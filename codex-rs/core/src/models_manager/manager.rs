@@ -6,6 +6,7 @@ use codex_protocol::openai_models::ModelInfo;
 use codex_protocol::openai_models::ModelPreset;
 use codex_protocol::openai_models::ModelsResponse;
 use http::HeaderMap;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -16,11 +17,12 @@ use tracing::error;
 
 use super::cache;
 use super::cache::ModelsCache;
+use super::local_context;
 use crate::api_bridge::auth_provider_from_auth;
 use crate::api_bridge::map_api_error;
 use crate::auth::AuthManager;
 use crate::config::Config;
-use crate::default_client::build_reqwest_client;
+use crate::default_client::build_reqwest_client_for_provider;
 use crate::error::Result as CoreResult;
 use crate::features::Feature;
 use crate::model_provider_info::ModelProviderInfo;
@@ -44,6 +46,8 @@ pub struct ModelsManager {
     codex_home: PathBuf,
     cache_ttl: Duration,
     provider: ModelProviderInfo,
+    /// Context windows reported by local model servers, keyed by base URL and model.
+    local_context_windows: RwLock<HashMap<String, Option<i64>>>,
 }
 
 impl ModelsManager {
@@ -58,6 +62,7 @@ impl ModelsManager {
             codex_home,
             cache_ttl: DEFAULT_MODEL_CACHE_TTL,
             provider: ModelProviderInfo::create_openai_provider(),
+            local_context_windows: RwLock::new(HashMap::new()),
         }
     }
 
@@ -73,6 +78,7 @@ impl ModelsManager {
             codex_home,
             cache_ttl: DEFAULT_MODEL_CACHE_TTL,
             provider,
+            local_context_windows: RwLock::new(HashMap::new()),
         }
     }
 
@@ -90,7 +96,7 @@ impl ModelsManager {
         let auth = self.auth_manager.auth();
         let api_provider = self.provider.to_api_provider(Some(AuthMode::ChatGPT))?;
         let api_auth = auth_provider_from_auth(auth.clone(), &self.provider).await?;
-        let transport = ReqwestTransport::new(build_reqwest_client());
+        let transport = ReqwestTransport::new(build_reqwest_client_for_provider(&self.provider));
         let client = ModelsClient::new(transport, api_provider, api_auth);
 
         let client_version = format_client_version_to_whole();
@@ -126,9 +132,36 @@ impl ModelsManager {
 
     /// Look up the requested model family while applying remote metadata overrides.
     pub async fn construct_model_family(&self, model: &str, config: &Config) -> ModelFamily {
-        Self::find_family_for_model(model)
+        let mut family = Self::find_family_for_model(model)
             .with_remote_overrides(self.remote_models(config).await)
-            .with_config_overrides(config)
+            .with_config_overrides(config);
+        if config.model_context_window.is_none()
+            && let Some(window) = self.local_context_window(model, config).await
+        {
+            family.context_window = Some(window);
+        }
+        family
+    }
+
+    /// Context window reported by a local model server, when the provider
+    /// opted into `local_quirks.detect_context_window`. Looked up once per
+    /// server and model.
+    async fn local_context_window(&self, model: &str, config: &Config) -> Option<i64> {
+        let provider = &config.model_provider;
+        if !provider
+            .local_quirks
+            .is_some_and(|quirks| quirks.detect_context_window)
+        {
+            return None;
+        }
+        let base_url = provider.base_url.as_deref()?;
+        let key = format!("{base_url}#{model}");
+        if let Some(window) = self.local_context_windows.read().await.get(&key) {
+            return *window;
+        }
+        let window = local_context::detect_context_window(base_url, model).await;
+        self.local_context_windows.write().await.insert(key, window);
+        window
     }
 
     pub async fn get_model(&self, model: &Option<String>, config: &Config) -> String {
@@ -374,7 +407,15 @@ mod tests {
             request_max_retries: Some(0),
             stream_max_retries: Some(0),
             stream_idle_timeout_ms: Some(5_000),
+            retry_rate_limited: None,
+            request_max_retry_time_ms: None,
             requires_openai_auth: false,
+            safety_settings: None,
+            local_quirks: None,
+            http: None,
+            azure: None,
+            bedrock: None,
+            persist_reasoning: None,
         }
     }
 
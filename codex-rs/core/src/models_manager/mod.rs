@@ -1,4 +1,5 @@
 pub mod cache;
+mod local_context;
 pub mod manager;
 pub mod model_family;
 pub mod model_presets;
@@ -63,14 +63,20 @@ pub fn build_provider(
 
     let exporter = to_otel_exporter(&config.otel.exporter);
     let trace_exporter = to_otel_exporter(&config.otel.trace_exporter);
+    let metrics_exporter = to_otel_exporter(&config.otel.metrics_exporter);
 
     OtelProvider::from(&OtelSettings {
-        service_name: originator().value.to_owned(),
+        service_name: config
+            .otel
+            .service_name
+            .clone()
+            .unwrap_or_else(|| originator().value.to_owned()),
         service_version: service_version.to_string(),
         codex_home: config.codex_home.clone(),
         environment: config.otel.environment.to_string(),
         exporter,
         trace_exporter,
+        metrics_exporter,
     })
 }
 
@@ -0,0 +1,391 @@
+//! Checks final answers against the turn's `final_output_json_schema`.
+//!
+//! Only the keywords structured-output APIs accept are checked: `type`,
+//! `properties`/`required`/`additionalProperties`, `items`, `enum`/`const`,
+//! `allOf`/`anyOf`/`oneOf`, local `$ref`s and the simple length and range
+//! bounds. Other keywords are ignored, so an answer is never rejected for a
+//! rule this module doesn't understand.
+
+use regex_lite::Regex;
+use serde_json::Map;
+use serde_json::Value;
+
+/// Problems listed in the message sent back to the model.
+const MAX_REPORTED_ERRORS: usize = 5;
+/// Guards against schemas that `$ref` themselves without consuming input.
+const MAX_DEPTH: usize = 64;
+
+/// The JSON text of `message` if it validates against `schema`, else a
+/// description of what is wrong that can be sent back to the model.
+pub(crate) fn check_final_answer(schema: &Value, message: Option<&str>) -> Result<String, String> {
+    let Some(message) = message.map(str::trim).filter(|message| !message.is_empty()) else {
+        return Err("The final message was empty.".to_string());
+    };
+    let Some((json, value)) = extract_json(message) else {
+        return Err("The final message is not valid JSON.".to_string());
+    };
+
+    let mut errors = Vec::new();
+    validate(schema, schema, &value, "", 0, &mut errors);
+    if errors.is_empty() {
+        return Ok(json.to_string());
+    }
+    let mut report = errors
+        .iter()
+        .take(MAX_REPORTED_ERRORS)
+        .map(|error| format!("- {error}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if errors.len() > MAX_REPORTED_ERRORS {
+        report.push_str(&format!(
+            "\n- and {} more",
+            errors.len() - MAX_REPORTED_ERRORS
+        ));
+    }
+    Err(format!(
+        "The final message does not match the output schema:\n{report}"
+    ))
+}
+
+/// The JSON in `message`: all of it, or the body of a Markdown code fence
+/// wrapped around it.
+fn extract_json(message: &str) -> Option<(&str, Value)> {
+    [Some(message), strip_code_fence(message)]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .find_map(|text| serde_json::from_str(text).ok().map(|value| (text, value)))
+}
+
+fn strip_code_fence(message: &str) -> Option<&str> {
+    let body = message.strip_prefix("```")?.strip_suffix("```")?;
+    // The first line is the info string, e.g. `json`.
+    body.split_once('\n').map(|(_, body)| body)
+}
+
+fn validate(
+    root: &Value,
+    schema: &Value,
+    value: &Value,
+    path: &str,
+    depth: usize,
+    errors: &mut Vec<String>,
+) {
+    let schema = match schema {
+        Value::Bool(false) => {
+            errors.push(format!("{}: no value is allowed here", at(path)));
+            return;
+        }
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+    if depth > MAX_DEPTH {
+        errors.push(format!("{}: the schema nests too deeply", at(path)));
+        return;
+    }
+    let depth = depth + 1;
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str)
+        && let Some(target) = reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+    {
+        validate(root, target, value, path, depth, errors);
+    }
+
+    if let Some(types) = schema.get("type") {
+        let allowed: Vec<&str> = match types {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|name| has_type(value, name)) {
+            errors.push(format!(
+                "{}: expected {}, found {}",
+                at(path),
+                allowed.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+    if let Some(options) = schema.get("enum").and_then(Value::as_array)
+        && !options.contains(value)
+    {
+        errors.push(format!(
+            "{}: {value} is not one of the allowed values",
+            at(path)
+        ));
+    }
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        errors.push(format!("{}: expected {expected}", at(path)));
+    }
+
+    if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+        for subschema in all {
+            validate(root, subschema, value, path, depth, errors);
+        }
+    }
+    if let Some(any) = schema.get("anyOf").and_then(Value::as_array)
+        && !any
+            .iter()
+            .any(|subschema| is_valid(root, subschema, value, depth))
+    {
+        errors.push(format!("{}: matches none of the `anyOf` schemas", at(path)));
+    }
+    if let Some(one) = schema.get("oneOf").and_then(Value::as_array) {
+        let matching = one
+            .iter()
+            .filter(|subschema| is_valid(root, subschema, value, depth))
+            .count();
+        if matching != 1 {
+            errors.push(format!(
+                "{}: matches {matching} of the `oneOf` schemas instead of exactly one",
+                at(path)
+            ));
+        }
+    }
+
+    match value {
+        Value::Object(object) => validate_object(root, schema, object, path, depth, errors),
+        Value::Array(items) => validate_array(root, schema, items, path, depth, errors),
+        Value::String(text) => validate_string(schema, text, path, errors),
+        Value::Number(number) => {
+            if let Some(number) = number.as_f64() {
+                validate_number(schema, number, path, errors);
+            }
+        }
+        Value::Bool(_) | Value::Null => {}
+    }
+}
+
+fn validate_object(
+    root: &Value,
+    schema: &Map<String, Value>,
+    object: &Map<String, Value>,
+    path: &str,
+    depth: usize,
+    errors: &mut Vec<String>,
+) {
+    let properties = schema.get("properties").and_then(Value::as_object);
+    if let Some(required) = schema.get("required").and_then(Value::as_array) {
+        for name in required.iter().filter_map(Value::as_str) {
+            if !object.contains_key(name) {
+                errors.push(format!("{}: missing required property `{name}`", at(path)));
+            }
+        }
+    }
+    for (name, value) in object {
+        let child = format!("{path}/{}", name.replace('~', "~0").replace('/', "~1"));
+        match properties.and_then(|properties| properties.get(name)) {
+            Some(subschema) => validate(root, subschema, value, &child, depth, errors),
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    errors.push(format!("{}: unexpected property `{name}`", at(path)));
+                }
+                Some(subschema) => validate(root, subschema, value, &child, depth, errors),
+                None => {}
+            },
+        }
+    }
+}
+
+fn validate_array(
+    root: &Value,
+    schema: &Map<String, Value>,
+    items: &[Value],
+    path: &str,
+    depth: usize,
+    errors: &mut Vec<String>,
+) {
+    if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+        && (items.len() as u64) < min
+    {
+        errors.push(format!("{}: expected at least {min} items", at(path)));
+    }
+    if let Some(max) = schema.get("maxItems").and_then(Value::as_u64)
+        && (items.len() as u64) > max
+    {
+        errors.push(format!("{}: expected at most {max} items", at(path)));
+    }
+    if let Some(subschema) = schema.get("items") {
+        for (index, item) in items.iter().enumerate() {
+            validate(
+                root,
+                subschema,
+                item,
+                &format!("{path}/{index}"),
+                depth,
+                errors,
+            );
+        }
+    }
+}
+
+fn validate_string(schema: &Map<String, Value>, text: &str, path: &str, errors: &mut Vec<String>) {
+    let length = text.chars().count() as u64;
+    if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+        && length < min
+    {
+        errors.push(format!("{}: expected at least {min} characters", at(path)));
+    }
+    if let Some(max) = schema.get("maxLength").and_then(Value::as_u64)
+        && length > max
+    {
+        errors.push(format!("{}: expected at most {max} characters", at(path)));
+    }
+    if let Some(pattern) = schema.get("pattern").and_then(Value::as_str)
+        && let Ok(regex) = Regex::new(pattern)
+        && !regex.is_match(text)
+    {
+        errors.push(format!("{}: does not match `{pattern}`", at(path)));
+    }
+}
+
+fn validate_number(schema: &Map<String, Value>, number: f64, path: &str, errors: &mut Vec<String>) {
+    let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+    if let Some(min) = bound("minimum")
+        && number < min
+    {
+        errors.push(format!("{}: expected at least {min}", at(path)));
+    }
+    if let Some(max) = bound("maximum")
+        && number > max
+    {
+        errors.push(format!("{}: expected at most {max}", at(path)));
+    }
+    if let Some(min) = bound("exclusiveMinimum")
+        && number <= min
+    {
+        errors.push(format!("{}: expected more than {min}", at(path)));
+    }
+    if let Some(max) = bound("exclusiveMaximum")
+        && number >= max
+    {
+        errors.push(format!("{}: expected less than {max}", at(path)));
+    }
+}
+
+fn is_valid(root: &Value, schema: &Value, value: &Value, depth: usize) -> bool {
+    let mut errors = Vec::new();
+    validate(root, schema, value, "", depth, &mut errors);
+    errors.is_empty()
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64()
+                || value.is_u64()
+                || value.as_f64().is_some_and(|number| number.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+        Value::Number(_) => "number",
+    }
+}
+
+/// A JSON pointer to the offending value, for error messages.
+fn at(path: &str) -> String {
+    if path.is_empty() {
+        "(root)".to_string()
+    } else {
+        format!("`{path}`")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "status": {"enum": ["ok", "failed"]},
+                "files": {"type": "array", "items": {"$ref": "#/$defs/file"}},
+            },
+            "required": ["status", "files"],
+            "additionalProperties": false,
+            "$defs": {
+                "file": {
+                    "type": "object",
+                    "properties": {"path": {"type": "string", "minLength": 1}},
+                    "required": ["path"],
+                },
+            },
+        })
+    }
+
+    #[test]
+    fn accepts_matching_answers_with_or_without_a_code_fence() {
+        let answer = r#"{"status": "ok", "files": [{"path": "src/lib.rs"}]}"#;
+        assert_eq!(
+            check_final_answer(&schema(), Some(answer)),
+            Ok(answer.to_string())
+        );
+        assert_eq!(
+            check_final_answer(&schema(), Some(&format!("```json\n{answer}\n```\n"))),
+            Ok(answer.to_string())
+        );
+    }
+
+    #[test]
+    fn reports_every_mismatch_with_its_location() {
+        // Keys in sorted order, so the report order doesn't depend on
+        // whether serde_json preserves insertion order.
+        let answer = r#"{"extra": 1, "files": [{"path": ""}, {}], "status": "done"}"#;
+        assert_eq!(
+            check_final_answer(&schema(), Some(answer)),
+            Err("The final message does not match the output schema:\n\
+                 - (root): unexpected property `extra`\n\
+                 - `/files/0/path`: expected at least 1 characters\n\
+                 - `/files/1`: missing required property `path`\n\
+                 - `/status`: \"done\" is not one of the allowed values"
+                .to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_prose_and_empty_answers() {
+        assert_eq!(
+            check_final_answer(&schema(), Some("Done! Everything passed.")),
+            Err("The final message is not valid JSON.".to_string())
+        );
+        assert_eq!(
+            check_final_answer(&schema(), None),
+            Err("The final message was empty.".to_string())
+        );
+    }
+
+    #[test]
+    fn checks_types_and_alternatives() {
+        let schema = json!({"anyOf": [{"type": "integer"}, {"type": "null"}]});
+        assert_eq!(check_final_answer(&schema, Some("3")), Ok("3".to_string()));
+        assert_eq!(
+            check_final_answer(&schema, Some("3.5")),
+            Err("The final message does not match the output schema:\n\
+                 - (root): matches none of the `anyOf` schemas"
+                .to_string())
+        );
+    }
+}
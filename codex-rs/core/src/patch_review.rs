@@ -0,0 +1,297 @@
+//! Per-hunk review of `apply_patch` edits.
+//!
+//! Clients split each [`FileChange`] with [`hunks`] and answer a patch
+//! approval with `ReviewDecision::PartiallyApproved`, naming the hunks to
+//! drop by their index in that list. [`retain_hunks`] then rebuilds the patch
+//! from what is left, so rejected edits never reach the working tree.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_apply_patch::ApplyPatchAction;
+use codex_apply_patch::ApplyPatchFileChange;
+use codex_apply_patch::MaybeApplyPatchVerified;
+use codex_apply_patch::maybe_parse_apply_patch_verified;
+
+use crate::protocol::FileChange;
+
+/// One reviewable piece of a file change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchHunk {
+    /// The `@@ -a,b +c,d @@` line of the hunk.
+    pub header: String,
+    /// Diff lines, each starting with ` `, `-` or `+`.
+    pub lines: Vec<String>,
+}
+
+/// Splits `change` into the hunks a reviewer accepts or rejects. Added and
+/// deleted files are a single hunk, as is a rename without edits.
+pub fn hunks(change: &FileChange) -> Vec<PatchHunk> {
+    match change {
+        FileChange::Add { content } => {
+            let lines: Vec<String> = content.lines().map(|line| format!("+{line}")).collect();
+            vec![PatchHunk {
+                header: format!("@@ -0,0 +1,{} @@", lines.len()),
+                lines,
+            }]
+        }
+        FileChange::Delete { content } => {
+            let lines: Vec<String> = content.lines().map(|line| format!("-{line}")).collect();
+            vec![PatchHunk {
+                header: format!("@@ -1,{} +0,0 @@", lines.len()),
+                lines,
+            }]
+        }
+        FileChange::Update { unified_diff, .. } => {
+            let hunks = parse_hunks(unified_diff);
+            if hunks.is_empty() {
+                vec![PatchHunk {
+                    header: "@@ rename @@".to_string(),
+                    lines: Vec::new(),
+                }]
+            } else {
+                hunks
+            }
+        }
+    }
+}
+
+/// Rebuilds `action` without the hunks in `rejected`, keyed by the same
+/// absolute paths as the approval request. Returns `Ok(None)` when nothing
+/// is left to apply.
+pub(crate) fn retain_hunks(
+    action: &ApplyPatchAction,
+    rejected: &HashMap<PathBuf, Vec<usize>>,
+) -> Result<Option<ApplyPatchAction>, String> {
+    let mut changes: Vec<(&PathBuf, &ApplyPatchFileChange)> = action.changes().iter().collect();
+    changes.sort_by_key(|(path, _)| *path);
+
+    let mut patch = String::from("*** Begin Patch\n");
+    let mut kept_any = false;
+    for (path, change) in changes {
+        let rejected = rejected.get(path).map(Vec::as_slice).unwrap_or_default();
+        let keep = |index: usize| !rejected.contains(&index);
+        match change {
+            ApplyPatchFileChange::Add { content } => {
+                if keep(0) {
+                    patch.push_str(&format!("*** Add File: {}\n", path.display()));
+                    for line in content.lines() {
+                        patch.push_str(&format!("+{line}\n"));
+                    }
+                    kept_any = true;
+                }
+            }
+            ApplyPatchFileChange::Delete { .. } => {
+                if keep(0) {
+                    patch.push_str(&format!("*** Delete File: {}\n", path.display()));
+                    kept_any = true;
+                }
+            }
+            ApplyPatchFileChange::Update {
+                unified_diff,
+                move_path,
+                ..
+            } => {
+                let hunks = parse_hunks(unified_diff);
+                let kept = if hunks.is_empty() {
+                    keep(0)
+                } else {
+                    (0..hunks.len()).any(keep)
+                };
+                if !kept {
+                    continue;
+                }
+                patch.push_str(&format!("*** Update File: {}\n", path.display()));
+                if let Some(move_path) = move_path {
+                    patch.push_str(&format!("*** Move to: {}\n", move_path.display()));
+                }
+                if !hunks.is_empty() {
+                    let original = std::fs::read_to_string(path)
+                        .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+                    let updated = apply_hunks(&original, &hunks, keep)
+                        .map_err(|err| format!("{}: {err}", path.display()))?;
+                    // Replace the whole file so the chunk cannot match anywhere
+                    // but the top; apply_patch recomputes the minimal diff.
+                    patch.push_str("@@\n");
+                    for line in original.lines() {
+                        patch.push_str(&format!("-{line}\n"));
+                    }
+                    for line in updated.lines() {
+                        patch.push_str(&format!("+{line}\n"));
+                    }
+                }
+                kept_any = true;
+            }
+        }
+    }
+    if !kept_any {
+        return Ok(None);
+    }
+    patch.push_str("*** End Patch");
+
+    let command = vec!["apply_patch".to_string(), patch];
+    match maybe_parse_apply_patch_verified(&command, &action.cwd) {
+        MaybeApplyPatchVerified::Body(action) => Ok(Some(action)),
+        MaybeApplyPatchVerified::CorrectnessError(err) => Err(err.to_string()),
+        MaybeApplyPatchVerified::ShellParseError(err) => Err(format!("{err:?}")),
+        MaybeApplyPatchVerified::NotApplyPatch => {
+            Err("rebuilt patch was not recognized".to_string())
+        }
+    }
+}
+
+/// Tells the model which files kept only part of their edits.
+pub(crate) fn rejection_note(rejected: &HashMap<PathBuf, Vec<usize>>, cwd: &Path) -> String {
+    let mut paths: Vec<String> = rejected
+        .iter()
+        .filter(|(_, hunks)| !hunks.is_empty())
+        .map(|(path, _)| path.strip_prefix(cwd).unwrap_or(path).display().to_string())
+        .collect();
+    paths.sort();
+    format!(
+        "The user rejected some hunks of this patch; those edits were not applied to: {}. Re-read these files before editing them again.",
+        paths.join(", ")
+    )
+}
+
+fn parse_hunks(unified_diff: &str) -> Vec<PatchHunk> {
+    let mut hunks: Vec<PatchHunk> = Vec::new();
+    for line in unified_diff.lines() {
+        if line.starts_with("@@") {
+            hunks.push(PatchHunk {
+                header: line.to_string(),
+                lines: Vec::new(),
+            });
+        } else if let Some(hunk) = hunks.last_mut()
+            && !line.starts_with('\\')
+        {
+            hunk.lines.push(line.to_string());
+        }
+    }
+    hunks
+}
+
+/// Start line and length of the old side of a `@@ -a,b +c,d @@` header.
+fn old_range(header: &str) -> Option<(usize, usize)> {
+    let old = header.strip_prefix("@@ -")?.split(' ').next()?;
+    match old.split_once(',') {
+        Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+        None => Some((old.parse().ok()?, 1)),
+    }
+}
+
+/// Applies the hunks for which `keep` is true to `original`, leaving the
+/// lines under the others untouched.
+fn apply_hunks(
+    original: &str,
+    hunks: &[PatchHunk],
+    keep: impl Fn(usize) -> bool,
+) -> Result<String, String> {
+    let original_lines: Vec<&str> = original.lines().collect();
+    let mut updated: Vec<&str> = Vec::with_capacity(original_lines.len());
+    let mut next = 0;
+    for (index, hunk) in hunks.iter().enumerate() {
+        let (start, len) =
+            old_range(&hunk.header).ok_or_else(|| format!("malformed hunk `{}`", hunk.header))?;
+        // A zero-length old range names the line the hunk is inserted after.
+        let start = if len == 0 {
+            start
+        } else {
+            start.saturating_sub(1)
+        };
+        let unchanged = original_lines
+            .get(next..start)
+            .ok_or_else(|| "the file changed since the patch was proposed".to_string())?;
+        updated.extend_from_slice(unchanged);
+        let keep = keep(index);
+        for line in &hunk.lines {
+            match line.split_at_checked(1) {
+                Some((" ", text)) => updated.push(text),
+                Some(("-", text)) if !keep => updated.push(text),
+                Some(("+", text)) if keep => updated.push(text),
+                _ => {}
+            }
+        }
+        next = start + len;
+    }
+    let rest = original_lines
+        .get(next..)
+        .ok_or_else(|| "the file changed since the patch was proposed".to_string())?;
+    updated.extend_from_slice(rest);
+
+    let mut updated = updated.join("\n");
+    if !updated.is_empty() {
+        updated.push('\n');
+    }
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const ORIGINAL: &str = "a\nb\nc\nd\ne\nf\ng\n";
+    const DIFF: &str = "@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n@@ -5,3 +5,4 @@\n e\n-f\n+F\n+F2\n g\n";
+
+    #[test]
+    fn splits_update_into_hunks() {
+        let change = FileChange::Update {
+            unified_diff: DIFF.to_string(),
+            move_path: None,
+        };
+        let hunks = hunks(&change);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[1].header, "@@ -5,3 +5,4 @@");
+        assert_eq!(hunks[1].lines, vec![" e", "-f", "+F", "+F2", " g"]);
+    }
+
+    #[test]
+    fn applies_only_kept_hunks() {
+        let hunks = parse_hunks(DIFF);
+        assert_eq!(
+            apply_hunks(ORIGINAL, &hunks, |index| index != 0),
+            Ok("a\nb\nc\nd\ne\nF\nF2\ng\n".to_string())
+        );
+        assert_eq!(
+            apply_hunks(ORIGINAL, &hunks, |index| index == 0),
+            Ok("a\nB\nc\nd\ne\nf\ng\n".to_string())
+        );
+        assert_eq!(
+            apply_hunks(ORIGINAL, &hunks, |_| true),
+            Ok("a\nB\nc\nd\ne\nF\nF2\ng\n".to_string())
+        );
+    }
+
+    #[test]
+    fn retains_unrejected_hunks_of_a_patch() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("file.txt");
+        std::fs::write(&path, ORIGINAL).expect("write file");
+        let patch = "*** Begin Patch\n*** Update File: file.txt\n@@\n a\n-b\n+B\n c\n@@\n e\n-f\n+F\n g\n*** Add File: new.txt\n+new\n*** End Patch";
+        let command = vec!["apply_patch".to_string(), patch.to_string()];
+        let MaybeApplyPatchVerified::Body(action) =
+            maybe_parse_apply_patch_verified(&command, dir.path())
+        else {
+            panic!("expected a verified patch");
+        };
+
+        let rejected = HashMap::from([
+            (path.clone(), vec![1]),
+            (dir.path().join("new.txt"), vec![0]),
+        ]);
+        let retained = retain_hunks(&action, &rejected)
+            .expect("rebuild patch")
+            .expect("some hunks kept");
+        let changes = retained.changes();
+        assert_eq!(changes.len(), 1);
+        let Some(ApplyPatchFileChange::Update { new_content, .. }) = changes.get(&path) else {
+            panic!("expected an update to file.txt");
+        };
+        assert_eq!(new_content, "a\nB\nc\nd\ne\nf\ng\n");
+
+        let everything = HashMap::from([(path, vec![0, 1]), (dir.path().join("new.txt"), vec![0])]);
+        assert_eq!(retain_hunks(&action, &everything), Ok(None));
+    }
+}
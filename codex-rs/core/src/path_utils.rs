@@ -1,3 +1,4 @@
+use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -8,6 +9,37 @@ pub fn normalize_for_path_comparison(path: impl AsRef<Path>) -> std::io::Result<
     Ok(normalize_for_wsl(canonical))
 }
 
+/// `path` resolved against `base`, with `.` and `..` folded away lexically.
+/// Symlinks are not followed and `..` never climbs above the root.
+pub fn normalize_lexically(path: &Path, base: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in base.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if normalized.parent().is_some() {
+                    normalized.pop();
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// The strings path globs are matched against: the normalized absolute path,
+/// and the path relative to `base` when it lies inside it, both with `/`
+/// separators. Normalizing first keeps `docs/../../.bashrc` from matching
+/// `docs/*`.
+pub fn glob_subjects(path: &Path, base: &Path) -> (String, Option<String>) {
+    let normalized = normalize_lexically(path, base);
+    let relative = normalized
+        .strip_prefix(normalize_lexically(Path::new(""), base))
+        .ok()
+        .map(|relative| relative.to_string_lossy().replace('\\', "/"));
+    (normalized.to_string_lossy().replace('\\', "/"), relative)
+}
+
 fn normalize_for_wsl(path: PathBuf) -> PathBuf {
     normalize_for_wsl_with_flag(path, env::is_wsl())
 }
@@ -84,6 +116,37 @@ fn lower_ascii_path(path: PathBuf) -> PathBuf {
 
 #[cfg(test)]
 mod tests {
+    use super::glob_subjects;
+    use pretty_assertions::assert_eq;
+    use std::path::Path;
+
+    #[test]
+    fn glob_subjects_fold_dot_segments() {
+        let base = Path::new("/repo");
+        assert_eq!(
+            glob_subjects(Path::new("docs/./guide.md"), base),
+            (
+                "/repo/docs/guide.md".to_string(),
+                Some("docs/guide.md".to_string())
+            )
+        );
+        assert_eq!(
+            glob_subjects(Path::new("docs/../../home/u/.bashrc"), base),
+            ("/home/u/.bashrc".to_string(), None)
+        );
+        assert_eq!(
+            glob_subjects(Path::new("/repo/x/../secrets/token"), base),
+            (
+                "/repo/secrets/token".to_string(),
+                Some("secrets/token".to_string())
+            )
+        );
+        assert_eq!(
+            glob_subjects(Path::new("../../../../etc/passwd"), base),
+            ("/etc/passwd".to_string(), None)
+        );
+    }
+
     #[cfg(target_os = "linux")]
     mod wsl {
         use super::super::normalize_for_wsl_with_flag;
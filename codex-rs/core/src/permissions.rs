@@ -0,0 +1,440 @@
+//! Scoped permissions granted from approval prompts.
+//!
+//! Instead of approving one call or dropping the sandbox altogether, the user
+//! can grant write access to a glob such as `src/**` or network access to a
+//! host such as `api.github.com`, for the session or for the project. Project
+//! grants are saved as `allowed_writes` and `allowed_network_hosts` under
+//! `[projects."<path>"]`. The shell, patch and fetch tools consult the
+//! [`PermissionStore`] before asking, and run granted work in a sandbox
+//! widened just enough to allow it.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::approvals::ScopedPermission;
+use codex_protocol::protocol::NetworkAccess;
+use codex_protocol::protocol::SandboxPolicy;
+use codex_utils_absolute_path::AbsolutePathBuf;
+use url::Url;
+use wildmatch::WildMatch;
+
+use crate::config::Config;
+use crate::git_info::resolve_root_git_project_for_trust;
+use crate::parse_command::extract_shell_command;
+use crate::path_utils::glob_subjects;
+
+/// URL schemes whose host a command is taken to connect to.
+const URL_SCHEMES: &[&str] = &["https://", "http://", "ssh://", "git://"];
+
+/// Permissions granted for the current project, from config and from
+/// prompts answered during the session.
+#[derive(Debug, Clone)]
+pub(crate) struct PermissionStore {
+    project_root: PathBuf,
+    writes: Vec<(String, WildMatch)>,
+    hosts: Vec<String>,
+}
+
+impl PermissionStore {
+    pub(crate) fn new(project_root: PathBuf) -> Self {
+        Self {
+            project_root,
+            writes: Vec::new(),
+            hosts: Vec::new(),
+        }
+    }
+
+    /// The store for the project containing `config.cwd`, seeded with the
+    /// project's `allowed_writes` and `allowed_network_hosts`.
+    pub(crate) fn from_config(config: &Config) -> Self {
+        let mut store = Self::new(project_root(&config.cwd));
+        for pattern in &config.active_project.allowed_writes {
+            store.grant(&ScopedPermission::Write {
+                pattern: pattern.clone(),
+            });
+        }
+        for host in &config.active_project.allowed_network_hosts {
+            store.grant(&ScopedPermission::Network { host: host.clone() });
+        }
+        store
+    }
+
+    pub(crate) fn project_root(&self) -> &Path {
+        &self.project_root
+    }
+
+    pub(crate) fn grant(&mut self, permission: &ScopedPermission) {
+        match permission {
+            ScopedPermission::Write { pattern } => {
+                if !self.writes.iter().any(|(existing, _)| existing == pattern) {
+                    self.writes
+                        .push((pattern.clone(), WildMatch::new(pattern.as_str())));
+                }
+            }
+            ScopedPermission::Network { host } => {
+                let host = host.to_ascii_lowercase();
+                if !self.hosts.contains(&host) {
+                    self.hosts.push(host);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn allows(&self, permission: &ScopedPermission) -> bool {
+        match permission {
+            ScopedPermission::Write { pattern } => {
+                self.writes.iter().any(|(existing, _)| existing == pattern)
+                    || self.allows_write(&self.project_root.join(pattern))
+            }
+            ScopedPermission::Network { host } => self.allows_host(host),
+        }
+    }
+
+    pub(crate) fn allows_all(&self, permissions: &[ScopedPermission]) -> bool {
+        permissions.iter().all(|permission| self.allows(permission))
+    }
+
+    /// Whether a granted pattern matches `path`, relative to the project root
+    /// when it lies inside it, or as an absolute path.
+    pub(crate) fn allows_write(&self, path: &Path) -> bool {
+        let (absolute, relative) = glob_subjects(path, &self.project_root);
+        self.writes.iter().any(|(_, glob)| {
+            glob.matches(&absolute) || relative.as_deref().is_some_and(|rel| glob.matches(rel))
+        })
+    }
+
+    /// Exact matches, and subdomains of granted `*.example.com` entries.
+    pub(crate) fn allows_host(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        self.hosts
+            .iter()
+            .any(|granted| match granted.strip_prefix("*.") {
+                Some(domain) => host == domain || host.ends_with(&format!(".{domain}")),
+                None => *granted == host,
+            })
+    }
+
+    /// `policy` with the directories of granted `dir/**` patterns added as
+    /// writable roots and, when `network` is set, network access turned on.
+    /// Only workspace-write and external sandboxes can be widened; other
+    /// policies are returned unchanged.
+    pub(crate) fn widen(&self, policy: &SandboxPolicy, network: bool) -> SandboxPolicy {
+        match policy {
+            SandboxPolicy::WorkspaceWrite {
+                writable_roots,
+                network_access,
+                exclude_tmpdir_env_var,
+                exclude_slash_tmp,
+            } => {
+                let mut writable_roots = writable_roots.clone();
+                for dir in self.write_dirs() {
+                    if let Ok(dir) = AbsolutePathBuf::from_absolute_path(&dir)
+                        && !writable_roots
+                            .iter()
+                            .any(|root| root.as_path() == dir.as_path())
+                    {
+                        writable_roots.push(dir);
+                    }
+                }
+                SandboxPolicy::WorkspaceWrite {
+                    writable_roots,
+                    network_access: *network_access || network,
+                    exclude_tmpdir_env_var: *exclude_tmpdir_env_var,
+                    exclude_slash_tmp: *exclude_slash_tmp,
+                }
+            }
+            SandboxPolicy::ExternalSandbox { .. } if network => SandboxPolicy::ExternalSandbox {
+                network_access: NetworkAccess::Enabled,
+            },
+            other => other.clone(),
+        }
+    }
+
+    /// Directories covered entirely by a granted `dir/**` pattern.
+    fn write_dirs(&self) -> Vec<PathBuf> {
+        self.writes
+            .iter()
+            .filter_map(|(pattern, _)| pattern.strip_suffix("/**"))
+            .filter(|dir| !dir.contains(['*', '?', '[']))
+            .map(|dir| self.project_root.join(dir))
+            .collect()
+    }
+}
+
+/// The root git project containing `cwd`, or `cwd` itself.
+pub(crate) fn project_root(cwd: &Path) -> PathBuf {
+    resolve_root_git_project_for_trust(cwd).unwrap_or_else(|| cwd.to_path_buf())
+}
+
+/// Write permissions that would cover `paths`: one `dir/**` per parent
+/// directory, relative to `project_root` when inside it. Files directly in
+/// the project root are proposed on their own.
+pub(crate) fn write_permissions_for<'a>(
+    project_root: &Path,
+    paths: impl IntoIterator<Item = &'a Path>,
+) -> Vec<ScopedPermission> {
+    let mut permissions = Vec::new();
+    for path in paths {
+        let pattern = match path.parent() {
+            Some(parent) if parent == project_root => path
+                .strip_prefix(project_root)
+                .map(|relative| relative.to_string_lossy().into_owned())
+                .unwrap_or_else(|_| path.to_string_lossy().into_owned()),
+            Some(parent) => {
+                let dir = parent.strip_prefix(project_root).unwrap_or(parent);
+                format!("{}/**", dir.to_string_lossy())
+            }
+            None => path.to_string_lossy().into_owned(),
+        };
+        let permission = ScopedPermission::Write {
+            pattern: pattern.replace('\\', "/"),
+        };
+        if !permissions.contains(&permission) {
+            permissions.push(permission);
+        }
+    }
+    permissions
+}
+
+/// Network permissions for the hosts `command` names in URLs or scp-style
+/// git remotes. Empty when `policy` already allows network access or cannot
+/// be widened to allow it, and when the command's destinations cannot all be
+/// told apart: a script that does not parse, a word built from a variable or
+/// substitution, or a host named without a scheme. Such commands are never
+/// run on the strength of a grant.
+pub(crate) fn network_permissions_for_command(
+    command: &[String],
+    policy: &SandboxPolicy,
+) -> Vec<ScopedPermission> {
+    if policy.has_full_network_access()
+        || !matches!(
+            policy,
+            SandboxPolicy::WorkspaceWrite { .. } | SandboxPolicy::ExternalSandbox { .. }
+        )
+    {
+        return Vec::new();
+    }
+    let words: Vec<String> = match extract_shell_command(command) {
+        Some((_, script)) => match shlex::split(script) {
+            Some(words) => words,
+            None => return Vec::new(),
+        },
+        None => command.to_vec(),
+    };
+    let mut permissions = Vec::new();
+    for word in &words {
+        let host = match destination_in_word(word) {
+            Destination::Host(host) => host,
+            Destination::Unknown => return Vec::new(),
+            Destination::None => continue,
+        };
+        let permission = ScopedPermission::Network { host };
+        if !permissions.contains(&permission) {
+            permissions.push(permission);
+        }
+    }
+    permissions
+}
+
+/// The host of `url`, for tools that take a URL directly.
+pub(crate) fn network_permission_for_url(url: &str) -> Option<ScopedPermission> {
+    let host = Url::parse(url).ok()?.host_str()?.to_ascii_lowercase();
+    Some(ScopedPermission::Network { host })
+}
+
+enum Destination {
+    None,
+    Host(String),
+    /// The word may name a host that cannot be told for sure.
+    Unknown,
+}
+
+fn destination_in_word(word: &str) -> Destination {
+    if word.contains(['$', '`']) {
+        return Destination::Unknown;
+    }
+    for scheme in URL_SCHEMES {
+        if let Some(start) = word.find(scheme) {
+            return match Url::parse(&word[start..])
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+            {
+                Some(host) => Destination::Host(host),
+                None => Destination::Unknown,
+            };
+        }
+    }
+    if let Some(host) = scp_host(word) {
+        return Destination::Host(host);
+    }
+    if word.contains("://") || names_bare_host(word) {
+        return Destination::Unknown;
+    }
+    Destination::None
+}
+
+/// The host of an scp-style remote such as `git@github.com:org/repo.git`.
+fn scp_host(word: &str) -> Option<String> {
+    let (user, rest) = word.split_once('@')?;
+    let (host, _) = rest.split_once(':')?;
+    (is_name(user) && is_name(host) && host.contains('.')).then(|| host.to_ascii_lowercase())
+}
+
+/// Whether `word` could be a host given without a scheme, such as
+/// `example.com`, `user@example.com`, `--url=example.com/x` or `10.0.0.1`.
+/// File names like `notes.md` count too; telling them apart is guesswork.
+fn names_bare_host(word: &str) -> bool {
+    let word = word.rsplit_once('=').map_or(word, |(_, value)| value);
+    let word = word.rsplit_once('@').map_or(word, |(_, host)| host);
+    let host = word.split(['/', ':']).next().unwrap_or_default();
+    let labels: Vec<&str> = host.split('.').collect();
+    if labels.len() < 2
+        || !labels.iter().all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+    {
+        return false;
+    }
+    let numeric = |label: &&str| label.chars().all(|c| c.is_ascii_digit());
+    labels
+        .last()
+        .is_some_and(|tld| tld.chars().all(|c| c.is_ascii_alphabetic()))
+        || (labels.len() == 4 && labels.iter().all(numeric))
+}
+
+fn is_name(part: &str) -> bool {
+    !part.is_empty()
+        && part
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn write(pattern: &str) -> ScopedPermission {
+        ScopedPermission::Write {
+            pattern: pattern.to_string(),
+        }
+    }
+
+    fn network(host: &str) -> ScopedPermission {
+        ScopedPermission::Network {
+            host: host.to_string(),
+        }
+    }
+
+    fn workspace_write(root: &str) -> SandboxPolicy {
+        SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![AbsolutePathBuf::from_absolute_path(root).expect("absolute root")],
+            network_access: false,
+            exclude_tmpdir_env_var: false,
+            exclude_slash_tmp: false,
+        }
+    }
+
+    #[test]
+    fn granted_patterns_match_project_relative_and_absolute_paths() {
+        let mut store = PermissionStore::new(PathBuf::from("/repo"));
+        store.grant(&write("src/**"));
+        store.grant(&write("/tmp/out/**"));
+        store.grant(&network("*.github.com"));
+
+        assert!(store.allows_write(Path::new("/repo/src/lib.rs")));
+        assert!(store.allows_write(Path::new("/repo/src/nested/mod.rs")));
+        assert!(store.allows_write(Path::new("/tmp/out/report.txt")));
+        assert!(!store.allows_write(Path::new("/repo/Cargo.toml")));
+        assert!(!store.allows_write(Path::new("/repo/src/../Cargo.toml")));
+        assert!(!store.allows_write(Path::new("/tmp/out/../../etc/passwd")));
+        assert!(store.allows(&write("src/**")));
+        assert!(store.allows(&write("src/nested/**")));
+        assert!(!store.allows(&write("tests/**")));
+
+        assert!(store.allows_host("api.github.com"));
+        assert!(store.allows_host("GitHub.com"));
+        assert!(!store.allows_host("github.com.evil.dev"));
+    }
+
+    #[test]
+    fn widening_adds_granted_dirs_and_network() {
+        let mut store = PermissionStore::new(PathBuf::from("/repo"));
+        store.grant(&write("../shared/**"));
+        store.grant(&write("*.lock"));
+
+        let widened = store.widen(&workspace_write("/repo"), true);
+        let SandboxPolicy::WorkspaceWrite {
+            writable_roots,
+            network_access,
+            ..
+        } = widened
+        else {
+            panic!("expected workspace-write policy");
+        };
+        assert!(network_access);
+        assert_eq!(
+            writable_roots
+                .iter()
+                .map(|root| root.as_path().to_path_buf())
+                .collect::<Vec<_>>(),
+            vec![PathBuf::from("/repo"), PathBuf::from("/shared")]
+        );
+        assert_eq!(
+            store.widen(&SandboxPolicy::ReadOnly, true),
+            SandboxPolicy::ReadOnly
+        );
+    }
+
+    #[test]
+    fn proposals_cover_written_dirs_and_named_hosts() {
+        let root = Path::new("/repo");
+        assert_eq!(
+            write_permissions_for(
+                root,
+                [
+                    Path::new("/repo/src/a.rs"),
+                    Path::new("/repo/src/b.rs"),
+                    Path::new("/repo/README.md"),
+                    Path::new("/etc/hosts"),
+                ]
+            ),
+            vec![write("src/**"), write("README.md"), write("/etc/**")]
+        );
+
+        let command = [
+            "bash",
+            "-lc",
+            "curl -s https://api.github.com/repos && git clone git@gitlab.com:org/repo.git",
+        ]
+        .map(String::from);
+        assert_eq!(
+            network_permissions_for_command(&command, &workspace_write("/repo")),
+            vec![network("api.github.com"), network("gitlab.com")]
+        );
+        assert_eq!(
+            network_permissions_for_command(&command, &SandboxPolicy::ReadOnly),
+            Vec::new()
+        );
+
+        // Destinations that cannot all be told never lead to a grant.
+        for script in [
+            "curl https://api.github.com; curl evil.example",
+            "curl https://api.github.com && ssh deploy@evil.example",
+            "curl https://api.github.com $URL",
+            "curl 'https://api.github.com",
+            "curl https://api.github.com -o 10.0.0.1",
+        ] {
+            let command = ["bash", "-lc", script].map(String::from);
+            assert_eq!(
+                network_permissions_for_command(&command, &workspace_write("/repo")),
+                Vec::new(),
+                "{script}"
+            );
+        }
+        assert_eq!(
+            network_permission_for_url("https://Docs.rs/serde"),
+            Some(network("docs.rs"))
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! Context items the user pinned for the lifetime of a session.
+//!
+//! Pinned items live outside of the conversation history so that compaction
+//! and history truncation can never drop them. They are rendered as tagged
+//! user messages and prepended to every prompt.
+
+use std::path::Path;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::PinnedContextEntry;
+use codex_protocol::protocol::PinnedContextItem;
+use codex_protocol::protocol::PinnedContextUpdatedEvent;
+
+use crate::truncate::approx_token_count;
+
+pub(crate) const PINNED_CONTEXT_OPEN_TAG: &str = "<pinned_context";
+const PINNED_CONTEXT_CLOSE_TAG: &str = "</pinned_context>";
+
+/// Files larger than this are rejected rather than pinned, since they would
+/// be resent with every prompt.
+const MAX_PINNED_FILE_BYTES: u64 = 64 * 1024;
+
+#[derive(Debug, Clone)]
+struct PinnedEntry {
+    id: String,
+    item: PinnedContextItem,
+    rendered: ResponseItem,
+    tokens: i64,
+}
+
+/// An item whose contents have been read and are ready to pin. Loading does
+/// the file I/O so that pinning can happen under the session state lock.
+#[derive(Debug)]
+pub(crate) struct LoadedPin {
+    item: PinnedContextItem,
+    attrs: String,
+    body: String,
+}
+
+impl LoadedPin {
+    /// File items are read relative to `cwd` and their contents captured now.
+    pub(crate) async fn load(item: PinnedContextItem, cwd: &Path) -> Result<Self, String> {
+        let (attrs, body) = match &item {
+            PinnedContextItem::Message { text } => (r#"kind="message""#.to_string(), text.clone()),
+            PinnedContextItem::Instruction { text } => {
+                (r#"kind="instruction""#.to_string(), text.clone())
+            }
+            PinnedContextItem::File { path } => {
+                let resolved = cwd.join(path);
+                let metadata = tokio::fs::metadata(&resolved)
+                    .await
+                    .map_err(|err| format!("failed to pin {}: {err}", path.display()))?;
+                if metadata.len() > MAX_PINNED_FILE_BYTES {
+                    return Err(format!(
+                        "failed to pin {}: file is larger than {MAX_PINNED_FILE_BYTES} bytes",
+                        path.display()
+                    ));
+                }
+                let contents = tokio::fs::read_to_string(&resolved)
+                    .await
+                    .map_err(|err| format!("failed to pin {}: {err}", path.display()))?;
+                (
+                    format!(r#"kind="file" path="{}""#, path.display()),
+                    contents,
+                )
+            }
+        };
+        Ok(Self { item, attrs, body })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PinnedContext {
+    entries: Vec<PinnedEntry>,
+    next_id: u64,
+}
+
+impl PinnedContext {
+    /// Pin a loaded item, returning the id assigned to it. Items that fail
+    /// to load never reach this point, so they do not use up an id.
+    pub(crate) fn pin(&mut self, pin: LoadedPin) -> String {
+        self.next_id += 1;
+        let id = format!("pin-{}", self.next_id);
+        self.insert(id.clone(), pin);
+        id
+    }
+
+    /// Remove the entry with `id`. Returns false when no such entry exists.
+    pub(crate) fn unpin(&mut self, id: &str) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.id != id);
+        self.entries.len() != before
+    }
+
+    /// Rebuild pins from a previously recorded snapshot (e.g. on resume).
+    /// Files are re-read; entries that can no longer be loaded are skipped and
+    /// reported back to the caller.
+    pub(crate) async fn restore(
+        entries: Vec<PinnedContextEntry>,
+        cwd: &Path,
+    ) -> (Self, Vec<String>) {
+        let mut pinned = Self::default();
+        let mut errors = Vec::new();
+        for entry in entries {
+            if let Some(n) = entry
+                .id
+                .strip_prefix("pin-")
+                .and_then(|n| n.parse::<u64>().ok())
+            {
+                pinned.next_id = pinned.next_id.max(n);
+            }
+            match LoadedPin::load(entry.item, cwd).await {
+                Ok(pin) => pinned.insert(entry.id, pin),
+                Err(err) => errors.push(err),
+            }
+        }
+        (pinned, errors)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Messages to prepend to the model input, in pin order.
+    pub(crate) fn response_items(&self) -> Vec<ResponseItem> {
+        self.entries
+            .iter()
+            .map(|entry| entry.rendered.clone())
+            .collect()
+    }
+
+    pub(crate) fn total_tokens(&self) -> i64 {
+        self.entries
+            .iter()
+            .fold(0i64, |acc, entry| acc.saturating_add(entry.tokens))
+    }
+
+    pub(crate) fn to_event(&self) -> PinnedContextUpdatedEvent {
+        PinnedContextUpdatedEvent {
+            entries: self
+                .entries
+                .iter()
+                .map(|entry| PinnedContextEntry {
+                    id: entry.id.clone(),
+                    item: entry.item.clone(),
+                    tokens: entry.tokens,
+                })
+                .collect(),
+            total_tokens: self.total_tokens(),
+        }
+    }
+
+    fn insert(&mut self, id: String, pin: LoadedPin) {
+        let LoadedPin { item, attrs, body } = pin;
+        let text = format!(
+            "{PINNED_CONTEXT_OPEN_TAG} id=\"{id}\" {attrs}>\n{body}\n{PINNED_CONTEXT_CLOSE_TAG}"
+        );
+        let tokens = i64::try_from(approx_token_count(&text)).unwrap_or(i64::MAX);
+        self.entries.push(PinnedEntry {
+            id,
+            item,
+            rendered: ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText { text }],
+            },
+            tokens,
+        });
+    }
+}
+
+/// Returns true when `message` is a rendered pinned context item.
+pub(crate) fn is_pinned_context(message: &[ContentItem]) -> bool {
+    if let [ContentItem::InputText { text }] = message {
+        text.starts_with(PINNED_CONTEXT_OPEN_TAG)
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    async fn pin(
+        pinned: &mut PinnedContext,
+        item: PinnedContextItem,
+        cwd: &Path,
+    ) -> Result<String, String> {
+        LoadedPin::load(item, cwd)
+            .await
+            .map(|loaded| pinned.pin(loaded))
+    }
+
+    #[tokio::test]
+    async fn pins_render_as_tagged_user_messages() {
+        let dir = tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("notes.md"), "remember this").expect("write");
+
+        let mut pinned = PinnedContext::default();
+        let first = pin(
+            &mut pinned,
+            PinnedContextItem::Instruction {
+                text: "use tabs".to_string(),
+            },
+            dir.path(),
+        )
+        .await
+        .expect("pin instruction");
+        let second = pin(
+            &mut pinned,
+            PinnedContextItem::File {
+                path: "notes.md".into(),
+            },
+            dir.path(),
+        )
+        .await
+        .expect("pin file");
+
+        assert_eq!((first.as_str(), second.as_str()), ("pin-1", "pin-2"));
+        assert_eq!(
+            pinned.response_items(),
+            vec![
+                ResponseItem::Message {
+                    id: None,
+                    role: "user".to_string(),
+                    content: vec![ContentItem::InputText {
+                        text: "<pinned_context id=\"pin-1\" kind=\"instruction\">\nuse tabs\n</pinned_context>"
+                            .to_string(),
+                    }],
+                },
+                ResponseItem::Message {
+                    id: None,
+                    role: "user".to_string(),
+                    content: vec![ContentItem::InputText {
+                        text: "<pinned_context id=\"pin-2\" kind=\"file\" path=\"notes.md\">\nremember this\n</pinned_context>"
+                            .to_string(),
+                    }],
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn unpin_and_restore_keep_ids_stable() {
+        let dir = tempdir().expect("tempdir");
+        let mut pinned = PinnedContext::default();
+        for text in ["a", "b", "c"] {
+            pin(
+                &mut pinned,
+                PinnedContextItem::Message {
+                    text: text.to_string(),
+                },
+                dir.path(),
+            )
+            .await
+            .expect("pin");
+        }
+        assert!(pinned.unpin("pin-2"));
+        assert!(!pinned.unpin("pin-2"));
+
+        let snapshot = pinned.to_event();
+        let (mut restored, errors) =
+            PinnedContext::restore(snapshot.entries.clone(), dir.path()).await;
+        assert_eq!(errors, Vec::<String>::new());
+        assert_eq!(restored.to_event(), snapshot);
+
+        let next = pin(
+            &mut restored,
+            PinnedContextItem::Message {
+                text: "d".to_string(),
+            },
+            dir.path(),
+        )
+        .await
+        .expect("pin");
+        assert_eq!(next, "pin-4");
+    }
+
+    #[tokio::test]
+    async fn missing_file_is_rejected() {
+        let dir = tempdir().expect("tempdir");
+        let mut pinned = PinnedContext::default();
+        let result = pin(
+            &mut pinned,
+            PinnedContextItem::File {
+                path: "missing.txt".into(),
+            },
+            dir.path(),
+        )
+        .await;
+        assert!(result.is_err());
+        assert!(pinned.is_empty());
+
+        let id = pin(
+            &mut pinned,
+            PinnedContextItem::Instruction {
+                text: "use tabs".to_string(),
+            },
+            dir.path(),
+        )
+        .await
+        .expect("pin instruction");
+        assert_eq!(id, "pin-1");
+    }
+}
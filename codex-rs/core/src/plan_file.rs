@@ -0,0 +1,166 @@
+//! Mirror of the `update_plan` state in `TODO.codex.md`, enabled with
+//! `sync_plan_file`.
+//!
+//! Every plan update rewrites the file as a Markdown checklist in the
+//! session's working directory. A resumed session reads it back, so the plan
+//! survives restarts and picks up edits people made in the meantime.
+
+use std::path::Path;
+
+use codex_protocol::plan_tool::PlanItemArg;
+use codex_protocol::plan_tool::StepStatus;
+use codex_protocol::plan_tool::UpdatePlanArgs;
+use tracing::warn;
+
+pub(crate) const PLAN_FILE_NAME: &str = "TODO.codex.md";
+
+const HEADER: &str = "# Plan";
+const NOTICE: &str =
+    "<!-- Kept in sync with the Codex plan. Edits are picked up when the session resumes. -->";
+const IN_PROGRESS_SUFFIX: &str = " (in progress)";
+
+/// Writes `plan` to `TODO.codex.md` in `cwd`. Failures are logged, not
+/// reported to the model: the plan itself was still updated.
+pub(crate) async fn write(cwd: &Path, plan: &UpdatePlanArgs) {
+    let path = cwd.join(PLAN_FILE_NAME);
+    if let Err(err) = tokio::fs::write(&path, render(plan)).await {
+        warn!("failed to write {}: {err}", path.display());
+    }
+}
+
+/// Reads the plan from `TODO.codex.md` in `cwd`, if there is one with at
+/// least one step.
+pub(crate) async fn read(cwd: &Path) -> Option<UpdatePlanArgs> {
+    let contents = tokio::fs::read_to_string(cwd.join(PLAN_FILE_NAME))
+        .await
+        .ok()?;
+    parse(&contents)
+}
+
+pub(crate) fn render(plan: &UpdatePlanArgs) -> String {
+    let mut out = format!("{NOTICE}\n{HEADER}\n\n");
+    if let Some(explanation) = plan
+        .explanation
+        .as_deref()
+        .map(str::trim)
+        .filter(|explanation| !explanation.is_empty())
+    {
+        out.push_str(explanation);
+        out.push_str("\n\n");
+    }
+    for item in &plan.plan {
+        let step = item.step.trim();
+        let line = match item.status {
+            StepStatus::Completed => format!("- [x] {step}"),
+            StepStatus::InProgress => format!("- [ ] {step}{IN_PROGRESS_SUFFIX}"),
+            StepStatus::Pending => format!("- [ ] {step}"),
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Parses a checklist written by [`render`] or edited by hand. Text before
+/// the first step, other than the header and comments, is the explanation.
+fn parse(contents: &str) -> Option<UpdatePlanArgs> {
+    let mut explanation = Vec::new();
+    let mut plan = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        if let Some(item) = parse_item(line) {
+            plan.push(item);
+        } else if plan.is_empty()
+            && !line.is_empty()
+            && !line.starts_with('#')
+            && !line.starts_with("<!--")
+        {
+            explanation.push(line);
+        }
+    }
+    if plan.is_empty() {
+        return None;
+    }
+    Some(UpdatePlanArgs {
+        explanation: (!explanation.is_empty()).then(|| explanation.join("\n")),
+        plan,
+    })
+}
+
+fn parse_item(line: &str) -> Option<PlanItemArg> {
+    let rest = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))?;
+    let (done, step) = if let Some(step) = rest.strip_prefix("[ ]") {
+        (false, step)
+    } else if let Some(step) = rest
+        .strip_prefix("[x]")
+        .or_else(|| rest.strip_prefix("[X]"))
+    {
+        (true, step)
+    } else {
+        return None;
+    };
+    let step = step.trim();
+    let (step, status) = if done {
+        (step, StepStatus::Completed)
+    } else if let Some(step) = step.strip_suffix(IN_PROGRESS_SUFFIX) {
+        (step.trim_end(), StepStatus::InProgress)
+    } else {
+        (step, StepStatus::Pending)
+    };
+    if step.is_empty() {
+        return None;
+    }
+    Some(PlanItemArg {
+        step: step.to_string(),
+        status,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn item(step: &str, status: StepStatus) -> PlanItemArg {
+        PlanItemArg {
+            step: step.to_string(),
+            status,
+        }
+    }
+
+    #[test]
+    fn renders_a_checklist_that_parses_back() {
+        let plan = UpdatePlanArgs {
+            explanation: Some("Fix the flaky parser test.".to_string()),
+            plan: vec![
+                item("Reproduce the failure", StepStatus::Completed),
+                item("Patch the tokenizer", StepStatus::InProgress),
+                item("Run the suite", StepStatus::Pending),
+            ],
+        };
+
+        let rendered = render(&plan);
+
+        assert_eq!(
+            rendered,
+            format!(
+                "{NOTICE}\n# Plan\n\nFix the flaky parser test.\n\n- [x] Reproduce the failure\n- [ ] Patch the tokenizer (in progress)\n- [ ] Run the suite\n"
+            )
+        );
+        assert_eq!(render(&parse(&rendered).expect("plan")), rendered);
+    }
+
+    #[test]
+    fn reads_hand_edited_checklists() {
+        let plan =
+            parse("# Plan\n\n* [X] Reproduce\n- [ ] Add a regression test\nnotes\n").expect("plan");
+
+        assert_eq!(plan.explanation, None);
+        assert_eq!(
+            render(&plan),
+            format!("{NOTICE}\n# Plan\n\n- [x] Reproduce\n- [ ] Add a regression test\n")
+        );
+        assert!(parse("# Plan\n\nNothing yet.\n").is_none());
+    }
+}
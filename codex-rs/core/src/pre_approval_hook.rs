@@ -0,0 +1,252 @@
+//! The `pre_approval_hook` program, consulted before an approval prompt.
+//!
+//! Codex runs the program with the pending request serialized as JSON in its
+//! final argument and reads the answer from stdout: `approve` or `deny`.
+//! Anything else, including no output, a non-zero exit or a timeout, passes
+//! the request through to the user as usual. A JSON answer may also carry an
+//! `env` object, exported to the shell commands that follow in the turn.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use codex_protocol::protocol::ReviewDecision;
+use codex_protocol::protocol::SandboxPolicy;
+use serde::Serialize;
+use tracing::error;
+use tracing::warn;
+
+use crate::hooks;
+use crate::hooks::HookCommand;
+use crate::hooks::HookOutput;
+use crate::hooks::HookSandbox;
+
+/// How long an approval waits for the hook before asking the user.
+pub(crate) const PRE_APPROVAL_HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The approval request handed to the hook.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub(crate) enum PreApprovalRequest<'a> {
+    #[serde(rename_all = "kebab-case")]
+    ExecApproval {
+        thread_id: String,
+        turn_id: &'a str,
+        call_id: &'a str,
+        cwd: &'a Path,
+        command: &'a [String],
+        reason: Option<&'a str>,
+        sandbox_policy: &'a SandboxPolicy,
+    },
+    #[serde(rename_all = "kebab-case")]
+    PatchApproval {
+        thread_id: String,
+        turn_id: &'a str,
+        call_id: &'a str,
+        cwd: &'a Path,
+        files: Vec<&'a Path>,
+        reason: Option<&'a str>,
+        grant_root: Option<&'a Path>,
+        sandbox_policy: &'a SandboxPolicy,
+    },
+}
+
+impl PreApprovalRequest<'_> {
+    /// What `[[hooks]]` matchers are tested against: the command line or the
+    /// changed paths.
+    pub(crate) fn subjects(&self) -> Vec<String> {
+        match self {
+            Self::ExecApproval { command, .. } => vec![command.join(" ")],
+            Self::PatchApproval { files, .. } => files
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect(),
+        }
+    }
+
+    pub(crate) fn call_id(&self) -> &str {
+        match self {
+            Self::ExecApproval { call_id, .. } | Self::PatchApproval { call_id, .. } => call_id,
+        }
+    }
+
+    /// The command line for `{tool_input.command}`; empty for patches.
+    pub(crate) fn tool_command(&self) -> String {
+        match self {
+            Self::ExecApproval { command, .. } => command.join(" "),
+            Self::PatchApproval { .. } => String::new(),
+        }
+    }
+
+    /// What the hook decided on, for the event shown to the user.
+    pub(crate) fn summary(&self) -> String {
+        match self {
+            Self::ExecApproval { command, .. } => format!("`{}`", command.join(" ")),
+            Self::PatchApproval { files, .. } if files.len() == 1 => {
+                format!("changes to {}", files[0].display())
+            }
+            Self::PatchApproval { files, .. } => format!("changes to {} files", files.len()),
+        }
+    }
+}
+
+/// What a hook answered.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct PreApprovalAnswer {
+    /// `None` asks the user.
+    pub(crate) decision: Option<ReviewDecision>,
+    pub(crate) env: BTreeMap<String, String>,
+}
+
+/// Runs the hook for at most `timeout` and returns its answer.
+pub(crate) async fn run(
+    hook: &HookCommand,
+    request: &PreApprovalRequest<'_>,
+    sandbox: &HookSandbox,
+    timeout: Duration,
+) -> PreApprovalAnswer {
+    let Some(json) = hooks::payload(request) else {
+        error!("failed to serialise pre-approval request");
+        return PreApprovalAnswer::default();
+    };
+    match hooks::output(hook, json, sandbox, timeout).await {
+        Ok(stdout) => parse_answer(&stdout),
+        Err(err) => {
+            warn!("pre-approval hook {err}; asking the user");
+            PreApprovalAnswer::default()
+        }
+    }
+}
+
+fn parse_answer(stdout: &str) -> PreApprovalAnswer {
+    let (decision, env) = match HookOutput::parse(stdout) {
+        Ok(Some(output)) => (output.decision, output.env),
+        Ok(None) => (Some(stdout.trim().to_string()), BTreeMap::new()),
+        Err(err) => {
+            warn!("pre-approval hook {err}; asking the user");
+            return PreApprovalAnswer::default();
+        }
+    };
+    let decision = decision.and_then(|decision| match decision.to_ascii_lowercase().as_str() {
+        "approve" => Some(ReviewDecision::Approved),
+        "deny" => Some(ReviewDecision::Denied),
+        _ => None,
+    });
+    PreApprovalAnswer { decision, env }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn exec_request<'a>(
+        command: &'a [String],
+        policy: &'a SandboxPolicy,
+    ) -> PreApprovalRequest<'a> {
+        PreApprovalRequest::ExecApproval {
+            thread_id: "thread-1".to_string(),
+            turn_id: "turn-1",
+            call_id: "call-1",
+            cwd: Path::new("/repo"),
+            command,
+            reason: Some("needs network"),
+            sandbox_policy: policy,
+        }
+    }
+
+    #[test]
+    fn parses_decisions() {
+        assert_eq!(
+            parse_answer("approve\n").decision,
+            Some(ReviewDecision::Approved)
+        );
+        assert_eq!(
+            parse_answer(" DENY ").decision,
+            Some(ReviewDecision::Denied)
+        );
+        assert_eq!(
+            parse_answer(r#"{"decision": "approve", "hook-protocol-version": 1}"#).decision,
+            Some(ReviewDecision::Approved)
+        );
+        assert_eq!(
+            parse_answer(r#"{"decision": "approve", "hook-protocol-version": 99}"#).decision,
+            None
+        );
+        assert_eq!(parse_answer("pass").decision, None);
+        assert_eq!(parse_answer("").decision, None);
+    }
+
+    #[test]
+    fn json_answers_can_export_env() {
+        assert_eq!(
+            parse_answer(r#"{"env": {"TICKET": "OPS-42"}}"#),
+            PreApprovalAnswer {
+                decision: None,
+                env: BTreeMap::from([("TICKET".to_string(), "OPS-42".to_string())]),
+            }
+        );
+    }
+
+    #[test]
+    fn serializes_exec_request() {
+        let command = ["cargo", "test"].map(String::from);
+        let policy = SandboxPolicy::ReadOnly;
+        let json = serde_json::to_value(exec_request(&command, &policy)).expect("serialize");
+        assert_eq!(json["type"], "exec-approval");
+        assert_eq!(json["call-id"], "call-1");
+        assert_eq!(json["command"], serde_json::json!(["cargo", "test"]));
+        assert_eq!(json["reason"], "needs network");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn hook_output_decides_or_passes() {
+        let command = ["cargo", "test"].map(String::from);
+        let policy = SandboxPolicy::ReadOnly;
+        let request = exec_request(&command, &policy);
+        let sandbox = HookSandbox {
+            policy: SandboxPolicy::DangerFullAccess,
+            cwd: std::env::temp_dir(),
+            codex_linux_sandbox_exe: None,
+        };
+        let hook = |script: &str| HookCommand {
+            command: ["sh", "-c", script, "hook"].map(String::from).to_vec(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            run(
+                &hook("echo approve"),
+                &request,
+                &sandbox,
+                PRE_APPROVAL_HOOK_TIMEOUT
+            )
+            .await
+            .decision,
+            Some(ReviewDecision::Approved)
+        );
+        assert_eq!(
+            run(
+                &hook(r#"case "$1" in *rm*) echo deny;; esac"#),
+                &request,
+                &sandbox,
+                PRE_APPROVAL_HOOK_TIMEOUT
+            )
+            .await
+            .decision,
+            None
+        );
+        assert_eq!(
+            run(
+                &hook("echo deny; exit 1"),
+                &request,
+                &sandbox,
+                PRE_APPROVAL_HOOK_TIMEOUT
+            )
+            .await
+            .decision,
+            None
+        );
+    }
+}
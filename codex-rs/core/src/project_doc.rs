@@ -2,22 +2,32 @@
 //!
 //! Project-level documentation is primarily stored in files named `AGENTS.md`.
 //! Additional fallback filenames can be configured via `project_doc_fallback_filenames`.
-//! We include the concatenation of all files found along the path from the
-//! repository root to the current working directory as follows:
+//! Instructions are layered in a fixed order, most general first:
 //!
-//! 1.  Determine the Git repository root by walking upwards from the current
-//!     working directory until a `.git` directory or file is found. If no Git
-//!     root is found, only the current working directory is considered.
-//! 2.  Collect every `AGENTS.md` found from the repository root down to the
-//!     current working directory (inclusive) and concatenate their contents in
-//!     that order.
-//! 3.  We do **not** walk past the Git root.
+//! 1.  The global `$CODEX_HOME/AGENTS.md` (or `AGENTS.override.md`), loaded
+//!     into `Config::user_instructions`.
+//! 2.  Every `AGENTS.md` found from the repository root down to the current
+//!     working directory (inclusive), concatenated in that order. The Git
+//!     root is found by walking upwards from the working directory until a
+//!     `.git` directory or file is found; we do **not** walk past it. If no
+//!     Git root is found, only the current working directory is considered.
+//! 3.  The skills section, when skills are enabled.
+//!
+//! Later layers are more specific, so they come last and take precedence
+//! when instructions conflict. [`InstructionFiles`] records the state of
+//! every file that feeds these layers so a running session can notice edits
+//! and reload its instructions.
 
 use crate::config::Config;
 use crate::skills::SkillMetadata;
 use crate::skills::render_skills_section;
+use crate::truncate::TruncationPolicy;
+use crate::truncate::approx_token_count;
+use crate::truncate::truncate_text;
 use dunce::canonicalize as normalize_path;
+use std::path::Path;
 use std::path::PathBuf;
+use std::time::SystemTime;
 use tokio::io::AsyncReadExt;
 use tracing::error;
 
@@ -133,7 +143,35 @@ pub async fn read_project_docs(config: &Config) -> std::io::Result<Option<String
 /// directory (inclusive). Symlinks are allowed. When `project_doc_max_bytes`
 /// is zero, returns an empty list.
 pub fn discover_project_doc_paths(config: &Config) -> std::io::Result<Vec<PathBuf>> {
-    let mut dir = config.cwd.clone();
+    let search_dirs = project_doc_search_dirs(&config.cwd)?;
+
+    let mut found: Vec<PathBuf> = Vec::new();
+    let candidate_filenames = candidate_filenames(config);
+    for d in search_dirs {
+        for name in &candidate_filenames {
+            let candidate = d.join(name);
+            match std::fs::symlink_metadata(&candidate) {
+                Ok(md) => {
+                    let ft = md.file_type();
+                    // Allow regular files and symlinks; opening will later fail for dangling links.
+                    if ft.is_file() || ft.is_symlink() {
+                        found.push(candidate);
+                        break;
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    Ok(found)
+}
+
+/// Directories searched for project docs, from the repository root down to
+/// `cwd`, or just `cwd` outside a repository.
+fn project_doc_search_dirs(cwd: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut dir = cwd.to_path_buf();
     if let Ok(canon) = normalize_path(&dir) {
         dir = canon;
     }
@@ -159,45 +197,22 @@ pub fn discover_project_doc_paths(config: &Config) -> std::io::Result<Vec<PathBu
         cursor = parent.to_path_buf();
     }
 
-    let search_dirs: Vec<PathBuf> = if let Some(root) = git_root {
-        let mut dirs: Vec<PathBuf> = Vec::new();
-        let mut saw_root = false;
-        for p in chain.iter().rev() {
-            if !saw_root {
-                if p == &root {
-                    saw_root = true;
-                } else {
-                    continue;
-                }
-            }
-            dirs.push(p.clone());
-        }
-        dirs
-    } else {
-        vec![config.cwd.clone()]
+    let Some(root) = git_root else {
+        return Ok(vec![cwd.to_path_buf()]);
     };
-
-    let mut found: Vec<PathBuf> = Vec::new();
-    let candidate_filenames = candidate_filenames(config);
-    for d in search_dirs {
-        for name in &candidate_filenames {
-            let candidate = d.join(name);
-            match std::fs::symlink_metadata(&candidate) {
-                Ok(md) => {
-                    let ft = md.file_type();
-                    // Allow regular files and symlinks; opening will later fail for dangling links.
-                    if ft.is_file() || ft.is_symlink() {
-                        found.push(candidate);
-                        break;
-                    }
-                }
-                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
-                Err(e) => return Err(e),
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    let mut saw_root = false;
+    for p in chain.iter().rev() {
+        if !saw_root {
+            if p == &root {
+                saw_root = true;
+            } else {
+                continue;
             }
         }
+        dirs.push(p.clone());
     }
-
-    Ok(found)
+    Ok(dirs)
 }
 
 fn candidate_filenames<'a>(config: &'a Config) -> Vec<&'a str> {
@@ -217,6 +232,67 @@ fn candidate_filenames<'a>(config: &'a Config) -> Vec<&'a str> {
     names
 }
 
+/// Modification time and size of every instruction file that currently
+/// exists, so creating, editing, or deleting one changes the snapshot.
+/// Switching to a directory with the same set of files does not.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct InstructionFiles {
+    global: Vec<(PathBuf, SystemTime, u64)>,
+    project: Vec<(PathBuf, SystemTime, u64)>,
+}
+
+impl InstructionFiles {
+    pub(crate) fn snapshot(config: &Config, cwd: &Path) -> Self {
+        let global = [LOCAL_PROJECT_DOC_FILENAME, DEFAULT_PROJECT_DOC_FILENAME]
+            .into_iter()
+            .filter_map(|name| stamp(config.codex_home.join(name)))
+            .collect();
+        let project = if config.project_doc_max_bytes == 0 {
+            Vec::new()
+        } else {
+            let names = candidate_filenames(config);
+            project_doc_search_dirs(cwd)
+                .unwrap_or_default()
+                .iter()
+                .flat_map(|dir| names.iter().filter_map(|name| stamp(dir.join(name))))
+                .collect()
+        };
+        Self { global, project }
+    }
+
+    /// Whether `$CODEX_HOME/AGENTS.md` changed between the two snapshots.
+    pub(crate) fn global_changed(&self, other: &Self) -> bool {
+        self.global != other.global
+    }
+}
+
+fn stamp(path: PathBuf) -> Option<(PathBuf, SystemTime, u64)> {
+    let meta = std::fs::metadata(&path).ok()?;
+    Some((path, meta.modified().ok()?, meta.len()))
+}
+
+/// Truncate `instructions` to roughly `max_tokens`. Returns a warning for the
+/// user when anything was cut.
+pub(crate) fn cap_instructions(
+    instructions: Option<String>,
+    max_tokens: usize,
+) -> (Option<String>, Option<String>) {
+    let Some(text) = instructions else {
+        return (None, None);
+    };
+    let tokens = approx_token_count(&text);
+    if tokens <= max_tokens {
+        return (Some(text), None);
+    }
+    let warning = format!(
+        "Instructions from AGENTS.md files are about {tokens} tokens, over the {max_tokens}-token limit, and were truncated. Shorten them or raise `instructions_max_tokens`."
+    );
+    (
+        Some(truncate_text(&text, TruncationPolicy::Tokens(max_tokens))),
+        Some(warning),
+    )
+}
+
 fn merge_project_docs_with_skills(
     project_doc: Option<String>,
     skills_section: Option<String>,
@@ -544,6 +620,61 @@ mod tests {
         assert_eq!(res, expected);
     }
 
+    /// Creating, editing, or removing any layer's file changes the snapshot;
+    /// only the global file counts as a global change.
+    #[tokio::test]
+    async fn instruction_files_snapshot_tracks_every_layer() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        fs::create_dir(repo.path().join(".git")).unwrap();
+        let nested = repo.path().join("crate_a");
+        fs::create_dir(&nested).unwrap();
+        let mut cfg = make_config(&repo, 4096, None).await;
+        let codex_home = tempfile::tempdir().expect("codex home");
+        cfg.codex_home = codex_home.path().to_path_buf();
+
+        let initial = InstructionFiles::snapshot(&cfg, &nested);
+        assert_eq!(initial, InstructionFiles::snapshot(&cfg, &nested));
+
+        fs::write(nested.join("AGENTS.md"), "crate doc").unwrap();
+        let created = InstructionFiles::snapshot(&cfg, &nested);
+        assert_ne!(initial, created);
+        assert!(!initial.global_changed(&created));
+
+        fs::write(nested.join("AGENTS.md"), "crate doc, edited").unwrap();
+        let edited = InstructionFiles::snapshot(&cfg, &nested);
+        assert_ne!(created, edited);
+
+        fs::write(codex_home.path().join("AGENTS.md"), "global").unwrap();
+        let global = InstructionFiles::snapshot(&cfg, &nested);
+        assert!(edited.global_changed(&global));
+
+        // Moving to the repo root drops the nested doc from the search.
+        assert_ne!(global, InstructionFiles::snapshot(&cfg, repo.path()));
+        fs::remove_file(nested.join("AGENTS.md")).unwrap();
+        assert_eq!(
+            InstructionFiles::snapshot(&cfg, &nested),
+            InstructionFiles::snapshot(&cfg, repo.path())
+        );
+    }
+
+    #[test]
+    fn cap_instructions_truncates_and_warns() {
+        let short = Some("short".to_string());
+        assert_eq!(cap_instructions(short.clone(), 100), (short, None));
+
+        let long = format!("{}{}", "a".repeat(400), "b".repeat(400));
+        let (capped, warning) = cap_instructions(Some(long), 50);
+        let capped = capped.expect("instructions kept");
+        assert!(capped.starts_with('a') && capped.ends_with('b'));
+        assert!(capped.len() < 400, "not truncated: {capped}");
+        assert_eq!(
+            warning.as_deref(),
+            Some(
+                "Instructions from AGENTS.md files are about 200 tokens, over the 50-token limit, and were truncated. Shorten them or raise `instructions_max_tokens`."
+            )
+        );
+    }
+
     fn create_skill(codex_home: PathBuf, name: &str, description: &str) {
         let skill_dir = codex_home.join(format!("skills/{name}"));
         fs::create_dir_all(&skill_dir).unwrap();
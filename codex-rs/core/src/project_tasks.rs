@@ -0,0 +1,382 @@
+//! Tasks the project defines for itself, run with the `run_task` tool.
+//!
+//! Targets are read from the working directory's `Makefile`, `justfile`,
+//! `package.json` scripts and `.cargo/config.toml` aliases. Each is known to
+//! the model by an id such as `make:build` or `npm:lint`, listed in the
+//! tool's description.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use serde_json::Value;
+
+/// Tasks listed in the tool description; the rest can still be run by id.
+pub(crate) const MAX_LISTED_TASKS: usize = 100;
+
+/// Longest description kept for a task, in characters.
+const MAX_DESCRIPTION_CHARS: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TaskSource {
+    Make,
+    Just,
+    Npm,
+    Cargo,
+}
+
+impl TaskSource {
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::Make => "make",
+            Self::Just => "just",
+            Self::Npm => "npm",
+            Self::Cargo => "cargo",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ProjectTask {
+    pub source: TaskSource,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+impl ProjectTask {
+    /// How the model refers to the task, e.g. `make:build`.
+    pub(crate) fn id(&self) -> String {
+        format!("{}:{}", self.source.prefix(), self.name)
+    }
+
+    /// The command that runs the task from `cwd` with `args` appended.
+    pub(crate) fn command(&self, cwd: &Path, args: &[String]) -> Vec<String> {
+        let mut command = match self.source {
+            TaskSource::Make => vec!["make".to_string(), self.name.clone()],
+            TaskSource::Just => vec!["just".to_string(), self.name.clone()],
+            TaskSource::Npm => vec![
+                package_manager(cwd).to_string(),
+                "run".to_string(),
+                self.name.clone(),
+            ],
+            TaskSource::Cargo => vec!["cargo".to_string(), self.name.clone()],
+        };
+        if !args.is_empty() {
+            if self.source == TaskSource::Npm {
+                command.push("--".to_string());
+            }
+            command.extend(args.iter().cloned());
+        }
+        command
+    }
+}
+
+const MAKEFILES: &[&str] = &["GNUmakefile", "makefile", "Makefile"];
+const JUSTFILES: &[&str] = &["justfile", "Justfile", ".justfile"];
+const PACKAGE_JSON: &[&str] = &["package.json"];
+const CARGO_CONFIGS: &[&str] = &[".cargo/config.toml", ".cargo/config"];
+
+/// Every task defined in `cwd`, grouped by source and in file order.
+pub(crate) fn discover(cwd: &Path) -> Vec<ProjectTask> {
+    let mut tasks = Vec::new();
+    if let Some(contents) = read_first(cwd, MAKEFILES) {
+        tasks.extend(tasks_from(TaskSource::Make, parse_makefile(&contents)));
+    }
+    if let Some(contents) = read_first(cwd, JUSTFILES) {
+        tasks.extend(tasks_from(TaskSource::Just, parse_justfile(&contents)));
+    }
+    if let Some(contents) = read_first(cwd, PACKAGE_JSON) {
+        tasks.extend(tasks_from(
+            TaskSource::Npm,
+            parse_package_scripts(&contents),
+        ));
+    }
+    if let Some(contents) = read_first(cwd, CARGO_CONFIGS) {
+        tasks.extend(tasks_from(
+            TaskSource::Cargo,
+            parse_cargo_aliases(&contents),
+        ));
+    }
+    tasks
+}
+
+/// Whether `path` is one of the files [`discover`] reads tasks from in `cwd`.
+/// Names are compared ignoring ASCII case, as on case-insensitive file
+/// systems `MAKEFILE` is the same file.
+pub(crate) fn is_task_file(path: &Path, cwd: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(cwd) else {
+        return false;
+    };
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    [MAKEFILES, JUSTFILES, PACKAGE_JSON, CARGO_CONFIGS]
+        .concat()
+        .iter()
+        .any(|name| relative.eq_ignore_ascii_case(name))
+}
+
+/// Finds a task by id, or by bare name when only one source defines it.
+pub(crate) fn find<'a>(tasks: &'a [ProjectTask], task: &str) -> Option<&'a ProjectTask> {
+    if let Some(found) = tasks.iter().find(|candidate| candidate.id() == task) {
+        return Some(found);
+    }
+    let mut by_name = tasks.iter().filter(|candidate| candidate.name == task);
+    match (by_name.next(), by_name.next()) {
+        (Some(found), None) => Some(found),
+        _ => None,
+    }
+}
+
+/// One line per task for the tool description.
+pub(crate) fn describe(tasks: &[ProjectTask]) -> String {
+    let mut lines: Vec<String> = tasks
+        .iter()
+        .take(MAX_LISTED_TASKS)
+        .map(|task| match &task.description {
+            Some(description) => format!("- {}: {description}", task.id()),
+            None => format!("- {}", task.id()),
+        })
+        .collect();
+    if tasks.len() > MAX_LISTED_TASKS {
+        lines.push(format!("- ... and {} more", tasks.len() - MAX_LISTED_TASKS));
+    }
+    lines.join("\n")
+}
+
+fn read_first(cwd: &Path, names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .find_map(|name| std::fs::read_to_string(cwd.join(name)).ok())
+}
+
+fn tasks_from(
+    source: TaskSource,
+    entries: Vec<(String, Option<String>)>,
+) -> impl Iterator<Item = ProjectTask> {
+    let mut seen = BTreeSet::new();
+    entries
+        .into_iter()
+        .filter(move |(name, _)| seen.insert(name.clone()))
+        .map(move |(name, description)| ProjectTask {
+            source,
+            name,
+            description: description
+                .map(|description| shorten(description.trim()))
+                .filter(|description| !description.is_empty()),
+        })
+}
+
+fn shorten(text: &str) -> String {
+    if text.chars().count() <= MAX_DESCRIPTION_CHARS {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(MAX_DESCRIPTION_CHARS - 3).collect();
+    format!("{cut}...")
+}
+
+/// `pnpm` or `yarn` when their lockfile is present, `npm` otherwise.
+fn package_manager(cwd: &Path) -> &'static str {
+    if cwd.join("pnpm-lock.yaml").is_file() {
+        "pnpm"
+    } else if cwd.join("yarn.lock").is_file() {
+        "yarn"
+    } else {
+        "npm"
+    }
+}
+
+/// Explicit targets, described by a trailing `## comment` or the comment
+/// line right above them. Pattern rules, special targets like `.PHONY` and
+/// variable assignments are skipped.
+fn parse_makefile(contents: &str) -> Vec<(String, Option<String>)> {
+    let mut targets = Vec::new();
+    let mut comment: Option<String> = None;
+    for line in contents.lines() {
+        if line.starts_with('\t') || line.starts_with(' ') || line.trim().is_empty() {
+            comment = None;
+            continue;
+        }
+        if let Some(text) = line.strip_prefix('#') {
+            comment = Some(text.trim_start_matches('#').trim().to_string());
+            continue;
+        }
+        let above = comment.take();
+        let Some((names, rest)) = line.split_once(':') else {
+            continue;
+        };
+        if names.contains('=') || rest.starts_with('=') || rest.starts_with(":=") {
+            continue;
+        }
+        let description = rest
+            .split_once("##")
+            .map(|(_, text)| text.to_string())
+            .or(above);
+        for name in names.split_whitespace() {
+            let valid = !name.starts_with('.')
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "_-./".contains(c));
+            if valid {
+                targets.push((name.to_string(), description.clone()));
+            }
+        }
+    }
+    targets
+}
+
+/// Public recipes, described by the comment line above them. Recipes whose
+/// name starts with `_` or that carry a `[private]` attribute are skipped.
+fn parse_justfile(contents: &str) -> Vec<(String, Option<String>)> {
+    let mut recipes = Vec::new();
+    let mut comment: Option<String> = None;
+    let mut private = false;
+    for line in contents.lines() {
+        if line.starts_with(char::is_whitespace) || line.trim().is_empty() {
+            comment = None;
+            private = false;
+            continue;
+        }
+        if let Some(text) = line.strip_prefix('#') {
+            comment = Some(text.trim().to_string());
+            continue;
+        }
+        if line.starts_with('[') {
+            private |= line.contains("private");
+            continue;
+        }
+        let (above, is_private) = (comment.take(), std::mem::take(&mut private));
+        let keyword = line.split_whitespace().next().unwrap_or_default();
+        if matches!(keyword, "set" | "alias" | "export" | "import" | "mod") {
+            continue;
+        }
+        let header = line.trim_start_matches('@');
+        let name_len = header
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(header.len());
+        let (name, rest) = header.split_at(name_len);
+        let rest = rest.trim_start();
+        if name.is_empty() || rest.starts_with(":=") || !rest.contains(':') {
+            continue;
+        }
+        if is_private || name.starts_with('_') {
+            continue;
+        }
+        recipes.push((name.to_string(), above));
+    }
+    recipes
+}
+
+/// `scripts` entries, described by their command. `pre`/`post` hooks of
+/// another script are skipped since npm runs them along with it.
+fn parse_package_scripts(contents: &str) -> Vec<(String, Option<String>)> {
+    let Ok(package) = serde_json::from_str::<Value>(contents) else {
+        return Vec::new();
+    };
+    let Some(scripts) = package["scripts"].as_object() else {
+        return Vec::new();
+    };
+    scripts
+        .iter()
+        .filter(|(name, _)| {
+            let hook_of = name
+                .strip_prefix("pre")
+                .or_else(|| name.strip_prefix("post"));
+            !hook_of.is_some_and(|script| scripts.contains_key(script))
+        })
+        .map(|(name, command)| (name.clone(), command.as_str().map(str::to_string)))
+        .collect()
+}
+
+/// The `[alias]` table, described by what each alias expands to.
+fn parse_cargo_aliases(contents: &str) -> Vec<(String, Option<String>)> {
+    let Ok(config) = contents.parse::<toml::Table>() else {
+        return Vec::new();
+    };
+    let Some(aliases) = config.get("alias").and_then(toml::Value::as_table) else {
+        return Vec::new();
+    };
+    aliases
+        .iter()
+        .map(|(name, value)| {
+            let expansion = match value {
+                toml::Value::String(command) => Some(command.clone()),
+                toml::Value::Array(parts) => Some(
+                    parts
+                        .iter()
+                        .filter_map(toml::Value::as_str)
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                ),
+                _ => None,
+            };
+            (
+                name.clone(),
+                expansion.map(|command| format!("cargo {command}")),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn task_files_are_recognized_at_the_project_root() {
+        let cwd = Path::new("/repo");
+        for path in [
+            "/repo/Makefile",
+            "/repo/MAKEFILE",
+            "/repo/justfile",
+            "/repo/package.json",
+            "/repo/.cargo/config.toml",
+        ] {
+            assert!(is_task_file(Path::new(path), cwd), "{path}");
+        }
+        for path in [
+            "/repo/src/Makefile",
+            "/repo/package-lock.json",
+            "/other/Makefile",
+        ] {
+            assert!(!is_task_file(Path::new(path), cwd), "{path}");
+        }
+    }
+
+    #[test]
+    fn discovers_tasks_from_each_source() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(
+            dir.path().join("Makefile"),
+            "CC := gcc\n.PHONY: build test\n\n# Compile everything\nbuild: deps\n\t$(CC) main.c\n\ntest: build ## Run the tests\n\t./test\n%.o: %.c\n\t$(CC) -c $<\n",
+        )?;
+        std::fs::write(
+            dir.path().join("justfile"),
+            "set shell := [\"bash\", \"-c\"]\nversion := \"1\"\n\n# Format the code\nfmt:\n    cargo fmt\n\n[private]\nhelper:\n    true\n\n_hidden:\n    true\n\nrelease tag:\n    git tag {{tag}}\n",
+        )?;
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts": {"lint": "eslint .", "prelint": "echo", "dev": "vite"}}"#,
+        )?;
+        std::fs::create_dir(dir.path().join(".cargo"))?;
+        std::fs::write(
+            dir.path().join(".cargo/config.toml"),
+            "[alias]\nxtask = \"run --package xtask --\"\nci = [\"test\", \"--workspace\"]\n",
+        )?;
+
+        let tasks = discover(dir.path());
+        assert_eq!(
+            describe(&tasks),
+            "- make:build: Compile everything\n- make:test: Run the tests\n- just:fmt: Format the code\n- just:release\n- npm:lint: eslint .\n- npm:dev: vite\n- cargo:xtask: cargo run --package xtask --\n- cargo:ci: cargo test --workspace"
+        );
+
+        let lint = find(&tasks, "lint").expect("lint task");
+        assert_eq!(
+            lint.command(dir.path(), &["--fix".to_string()]),
+            vec!["npm", "run", "lint", "--", "--fix"]
+        );
+        assert_eq!(
+            find(&tasks, "make:test").map(ProjectTask::id),
+            Some("make:test".to_string())
+        );
+        Ok(())
+    }
+}
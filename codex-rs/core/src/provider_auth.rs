@@ -0,0 +1,212 @@
+//! Credentials for providers that don't authenticate with a plain API key:
+//! Microsoft Entra ID tokens for Azure OpenAI and AWS keys for Bedrock.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_api::AwsCredentials;
+use tokio::process::Command;
+
+use crate::error::CodexErr;
+use crate::error::EnvVarError;
+use crate::error::RefreshTokenFailedError;
+use crate::error::RefreshTokenFailedReason;
+use crate::error::Result;
+
+/// Entra ID access tokens live for at least an hour; re-running the command
+/// well before that keeps requests from racing the expiry.
+const AZURE_TOKEN_TTL: Duration = Duration::from_secs(10 * 60);
+const AZURE_TOKEN_COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_AWS_PROFILE: &str = "default";
+
+/// Tokens printed by each `token_command`, with when they were fetched.
+static AZURE_TOKENS: LazyLock<Mutex<HashMap<Vec<String>, (String, Instant)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Runs `command` and returns the token it prints, reusing a recent one.
+pub(crate) async fn azure_token(command: &[String]) -> Result<String> {
+    if let Ok(tokens) = AZURE_TOKENS.lock()
+        && let Some((token, fetched_at)) = tokens.get(command)
+        && fetched_at.elapsed() < AZURE_TOKEN_TTL
+    {
+        return Ok(token.clone());
+    }
+
+    let failed = |message: String| {
+        CodexErr::RefreshTokenFailed(RefreshTokenFailedError::new(
+            RefreshTokenFailedReason::Other,
+            format!("Azure token_command failed: {message}"),
+        ))
+    };
+    let Some((program, args)) = command.split_first() else {
+        return Err(failed("the command is empty".to_string()));
+    };
+    let output = tokio::time::timeout(
+        AZURE_TOKEN_COMMAND_TIMEOUT,
+        Command::new(program).args(args).kill_on_drop(true).output(),
+    )
+    .await
+    .map_err(|_| failed(format!("`{program}` timed out")))?
+    .map_err(|err| failed(format!("could not run `{program}`: {err}")))?;
+    if !output.status.success() {
+        return Err(failed(format!(
+            "`{program}` exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if token.is_empty() {
+        return Err(failed(format!("`{program}` printed no token")));
+    }
+
+    if let Ok(mut tokens) = AZURE_TOKENS.lock() {
+        tokens.insert(command.to_vec(), (token.clone(), Instant::now()));
+    }
+    Ok(token)
+}
+
+/// Drops the cached token so the next request runs `command` again.
+pub(crate) fn forget_azure_token(command: &[String]) {
+    if let Ok(mut tokens) = AZURE_TOKENS.lock() {
+        tokens.remove(command);
+    }
+}
+
+/// AWS keys from the `AWS_ACCESS_KEY_ID` family of variables, else from
+/// `profile` (or `AWS_PROFILE`, or `default`) in the shared credentials file.
+pub(crate) fn aws_credentials(profile: Option<&str>) -> Result<AwsCredentials> {
+    if let Some(access_key_id) = non_empty_env("AWS_ACCESS_KEY_ID")
+        && let Some(secret_access_key) = non_empty_env("AWS_SECRET_ACCESS_KEY")
+    {
+        return Ok(AwsCredentials {
+            access_key_id,
+            secret_access_key,
+            session_token: non_empty_env("AWS_SESSION_TOKEN"),
+        });
+    }
+
+    let profile = profile
+        .map(str::to_string)
+        .or_else(|| non_empty_env("AWS_PROFILE"))
+        .unwrap_or_else(|| DEFAULT_AWS_PROFILE.to_string());
+    if let Some(path) = shared_credentials_path()
+        && let Ok(contents) = std::fs::read_to_string(&path)
+        && let Some(credentials) = parse_shared_credentials(&contents, &profile)
+    {
+        return Ok(credentials);
+    }
+
+    Err(CodexErr::EnvVar(EnvVarError {
+        var: "AWS_ACCESS_KEY_ID".to_string(),
+        instructions: Some(format!(
+            "Export AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY, or add a `{profile}` profile to ~/.aws/credentials (for SSO logins, `aws configure export-credentials --format env` prints the variables)."
+        )),
+    }))
+}
+
+/// The Bedrock region: `configured`, else `AWS_REGION`, else
+/// `AWS_DEFAULT_REGION`.
+pub(crate) fn aws_region(configured: Option<&str>) -> Result<String> {
+    configured
+        .map(str::to_string)
+        .or_else(|| non_empty_env("AWS_REGION"))
+        .or_else(|| non_empty_env("AWS_DEFAULT_REGION"))
+        .ok_or_else(|| {
+            CodexErr::EnvVar(EnvVarError {
+                var: "AWS_REGION".to_string(),
+                instructions: Some(
+                    "Set `bedrock.region` on the provider or export AWS_REGION.".to_string(),
+                ),
+            })
+        })
+}
+
+fn non_empty_env(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+}
+
+fn shared_credentials_path() -> Option<PathBuf> {
+    non_empty_env("AWS_SHARED_CREDENTIALS_FILE")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".aws").join("credentials")))
+}
+
+fn parse_shared_credentials(contents: &str, profile: &str) -> Option<AwsCredentials> {
+    let mut in_profile = false;
+    let mut values: HashMap<&str, &str> = HashMap::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_profile = section.trim() == profile;
+            continue;
+        }
+        if in_profile && let Some((key, value)) = line.split_once('=') {
+            values.insert(key.trim(), value.trim());
+        }
+    }
+    Some(AwsCredentials {
+        access_key_id: values.get("aws_access_key_id")?.to_string(),
+        secret_access_key: values.get("aws_secret_access_key")?.to_string(),
+        session_token: values
+            .get("aws_session_token")
+            .map(|token| (*token).to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn reads_the_named_profile_from_shared_credentials() {
+        let contents = "\
+[default]
+aws_access_key_id = AKIDDEFAULT
+aws_secret_access_key = default-secret
+
+# temporary keys
+[work]
+aws_access_key_id=AKIDWORK
+aws_secret_access_key=work-secret
+aws_session_token=work-token
+";
+        assert_eq!(
+            parse_shared_credentials(contents, "work"),
+            Some(AwsCredentials {
+                access_key_id: "AKIDWORK".to_string(),
+                secret_access_key: "work-secret".to_string(),
+                session_token: Some("work-token".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_shared_credentials(contents, "default").map(|c| c.session_token),
+            Some(None)
+        );
+        assert_eq!(parse_shared_credentials(contents, "missing"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn azure_token_comes_from_the_command_output() {
+        let command = vec!["echo".to_string(), "  entra-token  ".to_string()];
+        assert_eq!(
+            azure_token(&command).await.ok(),
+            Some("entra-token".to_string())
+        );
+        forget_azure_token(&command);
+
+        let failing = vec!["false".to_string()];
+        assert!(azure_token(&failing).await.is_err());
+    }
+}
@@ -0,0 +1,287 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::Comment;
+use super::Conversation;
+use super::NewPullRequest;
+use super::PAGE_SIZE;
+use super::PullRequest;
+use super::PullRequestClient;
+use super::ReviewThread;
+
+#[derive(Deserialize)]
+struct Repository {
+    default_branch: String,
+}
+
+#[derive(Deserialize)]
+struct Pull {
+    number: u64,
+    title: String,
+    html_url: String,
+    state: String,
+    #[serde(default)]
+    draft: bool,
+    head: BranchRef,
+    base: BranchRef,
+}
+
+#[derive(Deserialize)]
+struct BranchRef {
+    #[serde(rename = "ref")]
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct User {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct IssueComment {
+    id: u64,
+    user: Option<User>,
+    #[serde(default)]
+    body: Option<String>,
+    created_at: String,
+}
+
+#[derive(Deserialize)]
+struct Review {
+    id: u64,
+    user: Option<User>,
+    #[serde(default)]
+    body: Option<String>,
+    state: String,
+    #[serde(default)]
+    submitted_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ReviewComment {
+    id: u64,
+    #[serde(default)]
+    in_reply_to_id: Option<u64>,
+    path: String,
+    #[serde(default)]
+    line: Option<u64>,
+    #[serde(default)]
+    original_line: Option<u64>,
+    user: Option<User>,
+    body: String,
+    created_at: String,
+}
+
+impl From<Pull> for PullRequest {
+    fn from(pull: Pull) -> Self {
+        let state = if pull.draft && pull.state == "open" {
+            "draft".to_string()
+        } else {
+            pull.state
+        };
+        Self {
+            number: pull.number,
+            title: pull.title,
+            url: pull.html_url,
+            state,
+            source_branch: pull.head.name,
+            target_branch: pull.base.name,
+        }
+    }
+}
+
+fn author(user: Option<User>) -> String {
+    user.map(|user| user.login)
+        .unwrap_or_else(|| "ghost".to_string())
+}
+
+pub(super) async fn create(
+    client: &PullRequestClient,
+    request: NewPullRequest<'_>,
+) -> Result<PullRequest> {
+    let project = &client.project;
+    let target_branch = match request.target_branch {
+        Some(branch) => branch.to_string(),
+        None => {
+            client
+                .get::<Repository>(&format!("/repos/{project}"))
+                .await?
+                .default_branch
+        }
+    };
+    let pull: Pull = client
+        .post(
+            &format!("/repos/{project}/pulls"),
+            &json!({
+                "title": request.title,
+                "body": request.body,
+                "head": request.source_branch,
+                "base": target_branch,
+                "draft": request.draft,
+            }),
+        )
+        .await?;
+    Ok(pull.into())
+}
+
+pub(super) async fn find_for_branch(
+    client: &PullRequestClient,
+    branch: &str,
+) -> Result<Option<PullRequest>> {
+    let project = &client.project;
+    let owner = project.split('/').next().unwrap_or_default();
+    let head: String =
+        url::form_urlencoded::byte_serialize(format!("{owner}:{branch}").as_bytes()).collect();
+    let pulls: Vec<Pull> = client
+        .get(&format!(
+            "/repos/{project}/pulls?head={head}&state=open&per_page=1"
+        ))
+        .await?;
+    Ok(pulls.into_iter().next().map(PullRequest::from))
+}
+
+pub(super) async fn conversation(client: &PullRequestClient, number: u64) -> Result<Conversation> {
+    let project = &client.project;
+    let pull: Pull = client
+        .get(&format!("/repos/{project}/pulls/{number}"))
+        .await?;
+    let issue_comments: Vec<IssueComment> = client
+        .get(&format!(
+            "/repos/{project}/issues/{number}/comments?per_page={PAGE_SIZE}"
+        ))
+        .await?;
+    let reviews: Vec<Review> = client
+        .get(&format!(
+            "/repos/{project}/pulls/{number}/reviews?per_page={PAGE_SIZE}"
+        ))
+        .await?;
+    let review_comments: Vec<ReviewComment> = client
+        .get(&format!(
+            "/repos/{project}/pulls/{number}/comments?per_page={PAGE_SIZE}"
+        ))
+        .await?;
+
+    let mut comments: Vec<Comment> = issue_comments
+        .into_iter()
+        .map(|comment| Comment {
+            id: comment.id.to_string(),
+            author: author(comment.user),
+            body: comment.body.unwrap_or_default(),
+            created_at: comment.created_at,
+        })
+        .collect();
+    comments.extend(reviews.into_iter().filter_map(|review| {
+        let body = review.body.filter(|body| !body.trim().is_empty())?;
+        Some(Comment {
+            id: review.id.to_string(),
+            author: author(review.user),
+            body: format!(
+                "[review: {}] {body}",
+                review.state.to_lowercase().replace('_', " ")
+            ),
+            created_at: review.submitted_at.unwrap_or_default(),
+        })
+    }));
+    comments.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+
+    Ok(Conversation {
+        pull_request: pull.into(),
+        comments,
+        threads: group_threads(review_comments),
+    })
+}
+
+/// GitHub returns review comments flat; replies point at the first comment
+/// of their thread through `in_reply_to_id`.
+fn group_threads(review_comments: Vec<ReviewComment>) -> Vec<ReviewThread> {
+    let mut threads: Vec<ReviewThread> = Vec::new();
+    for comment in review_comments {
+        let entry = Comment {
+            id: comment.id.to_string(),
+            author: author(comment.user),
+            body: comment.body,
+            created_at: comment.created_at,
+        };
+        let root = comment.in_reply_to_id.map(|id| id.to_string());
+        match root.and_then(|root| threads.iter_mut().find(|thread| thread.id == root)) {
+            Some(thread) => thread.comments.push(entry),
+            None => threads.push(ReviewThread {
+                id: entry.id.clone(),
+                path: Some(comment.path),
+                line: comment.line.or(comment.original_line),
+                resolved: None,
+                comments: vec![entry],
+            }),
+        }
+    }
+    threads
+}
+
+pub(super) async fn reply(
+    client: &PullRequestClient,
+    number: u64,
+    thread_id: Option<&str>,
+    body: &str,
+) -> Result<Comment> {
+    let project = &client.project;
+    let body = json!({ "body": body });
+    let path = match thread_id {
+        Some(thread_id) => format!("/repos/{project}/pulls/{number}/comments/{thread_id}/replies"),
+        None => format!("/repos/{project}/issues/{number}/comments"),
+    };
+    let comment: IssueComment = client.post(&path, &body).await?;
+    Ok(Comment {
+        id: comment.id.to_string(),
+        author: author(comment.user),
+        body: comment.body.unwrap_or_default(),
+        created_at: comment.created_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn groups_replies_under_their_root_comment() {
+        let comments: Vec<ReviewComment> = serde_json::from_value(json!([
+            {"id": 1, "path": "src/lib.rs", "line": 10, "user": {"login": "alice"}, "body": "rename this", "created_at": "2024-01-01T00:00:00Z"},
+            {"id": 2, "path": "src/main.rs", "line": null, "original_line": 4, "user": null, "body": "outdated", "created_at": "2024-01-01T00:01:00Z"},
+            {"id": 3, "in_reply_to_id": 1, "path": "src/lib.rs", "line": 10, "user": {"login": "bob"}, "body": "done", "created_at": "2024-01-01T00:02:00Z"}
+        ]))
+        .expect("valid comments");
+
+        let threads = group_threads(comments);
+        let summary: Vec<(String, Option<u64>, Vec<String>)> = threads
+            .into_iter()
+            .map(|thread| {
+                (
+                    thread.id,
+                    thread.line,
+                    thread
+                        .comments
+                        .into_iter()
+                        .map(|comment| format!("{}: {}", comment.author, comment.body))
+                        .collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "1".to_string(),
+                    Some(10),
+                    vec!["alice: rename this".to_string(), "bob: done".to_string()]
+                ),
+                (
+                    "2".to_string(),
+                    Some(4),
+                    vec!["ghost: outdated".to_string()]
+                ),
+            ]
+        );
+    }
+}
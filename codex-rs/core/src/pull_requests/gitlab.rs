@@ -0,0 +1,278 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::Comment;
+use super::Conversation;
+use super::NewPullRequest;
+use super::PAGE_SIZE;
+use super::PullRequest;
+use super::PullRequestClient;
+use super::ReviewThread;
+
+#[derive(Deserialize)]
+struct Project {
+    default_branch: String,
+}
+
+#[derive(Deserialize)]
+struct MergeRequest {
+    iid: u64,
+    title: String,
+    web_url: String,
+    state: String,
+    #[serde(default)]
+    draft: bool,
+    source_branch: String,
+    target_branch: String,
+}
+
+#[derive(Deserialize)]
+struct Discussion {
+    id: String,
+    #[serde(default)]
+    individual_note: bool,
+    notes: Vec<Note>,
+}
+
+#[derive(Deserialize)]
+struct Note {
+    id: u64,
+    author: Author,
+    body: String,
+    created_at: String,
+    #[serde(default)]
+    system: bool,
+    #[serde(default)]
+    position: Option<Position>,
+    #[serde(default)]
+    resolvable: bool,
+    #[serde(default)]
+    resolved: bool,
+}
+
+#[derive(Deserialize)]
+struct Author {
+    username: String,
+}
+
+#[derive(Deserialize)]
+struct Position {
+    #[serde(default)]
+    new_path: Option<String>,
+    #[serde(default)]
+    old_path: Option<String>,
+    #[serde(default)]
+    new_line: Option<u64>,
+    #[serde(default)]
+    old_line: Option<u64>,
+}
+
+impl From<MergeRequest> for PullRequest {
+    fn from(merge_request: MergeRequest) -> Self {
+        let state = if merge_request.draft && merge_request.state == "opened" {
+            "draft".to_string()
+        } else {
+            merge_request.state
+        };
+        Self {
+            number: merge_request.iid,
+            title: merge_request.title,
+            url: merge_request.web_url,
+            state,
+            source_branch: merge_request.source_branch,
+            target_branch: merge_request.target_branch,
+        }
+    }
+}
+
+impl From<Note> for Comment {
+    fn from(note: Note) -> Self {
+        Self {
+            id: note.id.to_string(),
+            author: note.author.username,
+            body: note.body,
+            created_at: note.created_at,
+        }
+    }
+}
+
+/// GitLab takes the URL-encoded project path wherever it wants a project id.
+fn project_id(client: &PullRequestClient) -> String {
+    url::form_urlencoded::byte_serialize(client.project.as_bytes()).collect()
+}
+
+pub(super) async fn create(
+    client: &PullRequestClient,
+    request: NewPullRequest<'_>,
+) -> Result<PullRequest> {
+    let project = project_id(client);
+    let target_branch = match request.target_branch {
+        Some(branch) => branch.to_string(),
+        None => {
+            client
+                .get::<Project>(&format!("/projects/{project}"))
+                .await?
+                .default_branch
+        }
+    };
+    let title = if request.draft {
+        format!("Draft: {}", request.title)
+    } else {
+        request.title.to_string()
+    };
+    let merge_request: MergeRequest = client
+        .post(
+            &format!("/projects/{project}/merge_requests"),
+            &json!({
+                "source_branch": request.source_branch,
+                "target_branch": target_branch,
+                "title": title,
+                "description": request.body,
+            }),
+        )
+        .await?;
+    Ok(merge_request.into())
+}
+
+pub(super) async fn find_for_branch(
+    client: &PullRequestClient,
+    branch: &str,
+) -> Result<Option<PullRequest>> {
+    let project = project_id(client);
+    let branch: String = url::form_urlencoded::byte_serialize(branch.as_bytes()).collect();
+    let merge_requests: Vec<MergeRequest> = client
+        .get(&format!(
+            "/projects/{project}/merge_requests?source_branch={branch}&state=opened&per_page=1"
+        ))
+        .await?;
+    Ok(merge_requests.into_iter().next().map(PullRequest::from))
+}
+
+pub(super) async fn conversation(client: &PullRequestClient, number: u64) -> Result<Conversation> {
+    let project = project_id(client);
+    let merge_request: MergeRequest = client
+        .get(&format!("/projects/{project}/merge_requests/{number}"))
+        .await?;
+    let discussions: Vec<Discussion> = client
+        .get(&format!(
+            "/projects/{project}/merge_requests/{number}/discussions?per_page={PAGE_SIZE}"
+        ))
+        .await?;
+    let (comments, threads) = split_discussions(discussions);
+    Ok(Conversation {
+        pull_request: merge_request.into(),
+        comments,
+        threads,
+    })
+}
+
+/// Standalone notes become comments; everything else, including replies to
+/// a standalone note, is a thread. System notes ("added 2 commits") are
+/// dropped.
+fn split_discussions(discussions: Vec<Discussion>) -> (Vec<Comment>, Vec<ReviewThread>) {
+    let mut comments = Vec::new();
+    let mut threads = Vec::new();
+    for discussion in discussions {
+        let mut notes: Vec<Note> = discussion
+            .notes
+            .into_iter()
+            .filter(|note| !note.system)
+            .collect();
+        let Some(first) = notes.first() else {
+            continue;
+        };
+        if discussion.individual_note && first.position.is_none() {
+            comments.extend(notes.drain(..).map(Comment::from));
+            continue;
+        }
+        let (path, line) = first
+            .position
+            .as_ref()
+            .map(|position| {
+                (
+                    position
+                        .new_path
+                        .clone()
+                        .or_else(|| position.old_path.clone()),
+                    position.new_line.or(position.old_line),
+                )
+            })
+            .unwrap_or_default();
+        let resolved = first.resolvable.then_some(first.resolved);
+        threads.push(ReviewThread {
+            id: discussion.id,
+            path,
+            line,
+            resolved,
+            comments: notes.into_iter().map(Comment::from).collect(),
+        });
+    }
+    (comments, threads)
+}
+
+pub(super) async fn reply(
+    client: &PullRequestClient,
+    number: u64,
+    thread_id: Option<&str>,
+    body: &str,
+) -> Result<Comment> {
+    let project = project_id(client);
+    let body = json!({ "body": body });
+    let path = match thread_id {
+        Some(thread_id) => {
+            format!("/projects/{project}/merge_requests/{number}/discussions/{thread_id}/notes")
+        }
+        None => format!("/projects/{project}/merge_requests/{number}/notes"),
+    };
+    let note: Note = client.post(&path, &body).await?;
+    Ok(note.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn splits_standalone_notes_from_threads() {
+        let discussions: Vec<Discussion> = serde_json::from_value(json!([
+            {"id": "a1", "individual_note": true, "notes": [
+                {"id": 1, "author": {"username": "alice"}, "body": "LGTM overall", "created_at": "t1"}
+            ]},
+            {"id": "b2", "individual_note": true, "notes": [
+                {"id": 2, "author": {"username": "gitlab"}, "body": "added 1 commit", "created_at": "t2", "system": true}
+            ]},
+            {"id": "c3", "individual_note": false, "notes": [
+                {"id": 3, "author": {"username": "bob"}, "body": "off by one", "created_at": "t3",
+                 "position": {"new_path": "src/lib.rs", "new_line": 7}, "resolvable": true, "resolved": false},
+                {"id": 4, "author": {"username": "alice"}, "body": "fixed", "created_at": "t4",
+                 "position": {"new_path": "src/lib.rs", "new_line": 7}, "resolvable": true, "resolved": false}
+            ]}
+        ]))
+        .expect("valid discussions");
+
+        let (comments, threads) = split_discussions(discussions);
+        assert_eq!(
+            comments,
+            vec![Comment {
+                id: "1".to_string(),
+                author: "alice".to_string(),
+                body: "LGTM overall".to_string(),
+                created_at: "t1".to_string(),
+            }]
+        );
+        assert_eq!(threads.len(), 1);
+        let thread = &threads[0];
+        assert_eq!(
+            (
+                thread.id.as_str(),
+                thread.path.as_deref(),
+                thread.line,
+                thread.resolved
+            ),
+            ("c3", Some("src/lib.rs"), Some(7), Some(false))
+        );
+        assert_eq!(thread.comments.len(), 2);
+    }
+}
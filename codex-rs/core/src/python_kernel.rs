@@ -0,0 +1,360 @@
+//! The persistent interpreter behind the `python` tool.
+//!
+//! One Python process per session runs `templates/python/kernel.py`, which
+//! keeps a single namespace across calls, so variables, imports and loaded
+//! data frames carry over. Each call is a JSON line on stdin; the reply is a
+//! JSON line after a marker on stdout, carrying what the code printed, the
+//! value of its last expression, any traceback, and matplotlib figures as
+//! PNGs.
+
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rand::Rng;
+use serde::Deserialize;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Child;
+use tokio::process::ChildStdin;
+use tokio::sync::Mutex;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+use crate::exec::SandboxType;
+use crate::exec::kill_child_process_group;
+use crate::protocol::SandboxPolicy;
+use crate::sandboxing::ExecEnv;
+use crate::shell_session::forward_output;
+use crate::spawn::StdioPolicy;
+use crate::spawn::spawn_child_async;
+
+pub(crate) const KERNEL_DRIVER: &str = include_str!("../templates/python/kernel.py");
+
+/// How long the interpreter may take to start before the first call.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The interpreter started for the kernel.
+pub(crate) fn python_program() -> &'static str {
+    if cfg!(windows) { "python" } else { "python3" }
+}
+
+/// Result of running one cell in the kernel.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub(crate) struct PythonOutcome {
+    /// What the code printed to stdout and stderr.
+    pub output: String,
+    /// `repr` of the last expression, or a Markdown table for data frames.
+    pub result: Option<String>,
+    pub error: Option<String>,
+    /// Base64-encoded PNGs of the matplotlib figures the code left open.
+    pub images: Vec<String>,
+    #[serde(skip)]
+    pub timed_out: bool,
+    /// Tells the model when kernel state was lost.
+    #[serde(skip)]
+    pub note: Option<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct PythonKernelManager {
+    kernel: Mutex<Option<Kernel>>,
+}
+
+impl PythonKernelManager {
+    /// Runs `code` in the kernel, starting one from `spawn_env` first when
+    /// none is running, `reset` is set, or the sandbox it was started under
+    /// no longer matches.
+    pub(crate) async fn run(
+        &self,
+        code: &str,
+        reset: bool,
+        spawn_env: ExecEnv,
+        sandbox_policy: &SandboxPolicy,
+        timeout: Duration,
+    ) -> io::Result<PythonOutcome> {
+        let mut guard = self.kernel.lock().await;
+        let mut notes = Vec::new();
+        if let Some(kernel) = guard.as_ref()
+            && (reset
+                || kernel.sandbox != spawn_env.sandbox
+                || &kernel.sandbox_policy != sandbox_policy)
+        {
+            if !reset {
+                notes.push("The sandbox changed, so a new kernel was started and earlier variables were lost.".to_string());
+            }
+            if let Some(mut kernel) = guard.take() {
+                kernel.kill();
+            }
+        }
+        let kernel = match guard.as_mut() {
+            Some(kernel) => kernel,
+            None => guard.insert(Kernel::spawn(spawn_env, sandbox_policy).await?),
+        };
+
+        let outcome = match kernel.run(code, timeout).await? {
+            RunResult::Finished(outcome) => outcome,
+            RunResult::TimedOut { output } => {
+                kernel.kill();
+                *guard = None;
+                notes.push("The code timed out, so the kernel was killed; the next call starts a new kernel.".to_string());
+                PythonOutcome {
+                    output,
+                    timed_out: true,
+                    ..Default::default()
+                }
+            }
+            RunResult::Exited { output } => {
+                kernel.kill();
+                *guard = None;
+                notes.push("The kernel exited; the next call starts a new kernel.".to_string());
+                PythonOutcome {
+                    output,
+                    ..Default::default()
+                }
+            }
+        };
+
+        Ok(PythonOutcome {
+            note: (!notes.is_empty()).then(|| notes.join(" ")),
+            ..outcome
+        })
+    }
+
+    pub(crate) async fn terminate(&self) {
+        if let Some(mut kernel) = self.kernel.lock().await.take() {
+            kernel.kill();
+        }
+    }
+}
+
+struct Kernel {
+    child: Child,
+    stdin: ChildStdin,
+    output_rx: mpsc::UnboundedReceiver<Vec<u8>>,
+    marker: String,
+    sandbox: SandboxType,
+    sandbox_policy: SandboxPolicy,
+}
+
+#[derive(Debug, PartialEq)]
+enum RunResult {
+    Finished(PythonOutcome),
+    TimedOut { output: String },
+    Exited { output: String },
+}
+
+impl Kernel {
+    /// `env.command` is the interpreter invocation; the marker is appended
+    /// as the driver's only argument.
+    async fn spawn(env: ExecEnv, sandbox_policy: &SandboxPolicy) -> io::Result<Self> {
+        let ExecEnv {
+            command,
+            cwd,
+            env,
+            sandbox,
+            arg0,
+            ..
+        } = env;
+        let nonce: String = {
+            let mut rng = rand::rng();
+            (0..16)
+                .map(|_| format!("{:x}", rng.random_range(0..16)))
+                .collect()
+        };
+        let marker = format!("__codex_python_kernel_{nonce}__");
+        let (program, args) = command
+            .split_first()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "command args are empty"))?;
+        let mut args = args.to_vec();
+        args.push(marker.clone());
+        let mut child = spawn_child_async(
+            PathBuf::from(program),
+            args,
+            arg0.as_deref(),
+            cwd,
+            sandbox_policy,
+            StdioPolicy::PersistentShell,
+            env,
+        )
+        .await?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| io::Error::other("stdin pipe was unexpectedly not available"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| io::Error::other("stdout pipe was unexpectedly not available"))?;
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| io::Error::other("stderr pipe was unexpectedly not available"))?;
+        let (output_tx, output_rx) = mpsc::unbounded_channel();
+        forward_output(stdout, output_tx.clone());
+        forward_output(stderr, output_tx);
+
+        let mut kernel = Self {
+            child,
+            stdin,
+            output_rx,
+            marker,
+            sandbox,
+            sandbox_policy: sandbox_policy.clone(),
+        };
+        match kernel.run("pass", STARTUP_TIMEOUT).await? {
+            RunResult::Finished(_) => Ok(kernel),
+            RunResult::TimedOut { output } | RunResult::Exited { output } => {
+                kernel.kill();
+                Err(io::Error::other(format!(
+                    "python kernel failed to start: {}",
+                    output.trim()
+                )))
+            }
+        }
+    }
+
+    async fn run(&mut self, code: &str, timeout: Duration) -> io::Result<RunResult> {
+        let mut request = serde_json::json!({ "code": code }).to_string();
+        request.push('\n');
+        let written = async {
+            self.stdin.write_all(request.as_bytes()).await?;
+            self.stdin.flush().await
+        }
+        .await;
+        if let Err(err) = written {
+            if err.kind() == io::ErrorKind::BrokenPipe {
+                return Ok(RunResult::Exited {
+                    output: String::new(),
+                });
+            }
+            return Err(err);
+        }
+
+        let deadline = Instant::now() + timeout;
+        let mut collected: Vec<u8> = Vec::new();
+        loop {
+            if let Some(outcome) = parse_response(&collected, &self.marker) {
+                return Ok(RunResult::Finished(outcome));
+            }
+            match tokio::time::timeout_at(deadline, self.output_rx.recv()).await {
+                Ok(Some(chunk)) => collected.extend_from_slice(&chunk),
+                Ok(None) => {
+                    return Ok(RunResult::Exited {
+                        output: String::from_utf8_lossy(&collected).into_owned(),
+                    });
+                }
+                Err(_) => {
+                    return Ok(RunResult::TimedOut {
+                        output: String::from_utf8_lossy(&collected).into_owned(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn kill(&mut self) {
+        let _ = kill_child_process_group(&mut self.child);
+        let _ = self.child.start_kill();
+    }
+}
+
+/// Parses the reply once its whole line has arrived. Anything written
+/// straight to the process's stdout or stderr, e.g. by a subprocess, comes
+/// before it and is prepended to the output.
+fn parse_response(collected: &[u8], marker: &str) -> Option<PythonOutcome> {
+    let needle = format!("\n{marker} ");
+    let start = collected
+        .windows(needle.len())
+        .position(|window| window == needle.as_bytes())?;
+    let rest = &collected[start + needle.len()..];
+    let line_end = rest.iter().position(|&byte| byte == b'\n')?;
+    let mut outcome: PythonOutcome = match serde_json::from_slice(&rest[..line_end]) {
+        Ok(outcome) => outcome,
+        Err(err) => PythonOutcome {
+            error: Some(format!("failed to read the kernel's reply: {err}")),
+            ..Default::default()
+        },
+    };
+    let stray = String::from_utf8_lossy(&collected[..start]);
+    let stray = stray.trim_end_matches('\n');
+    if !stray.is_empty() {
+        outcome.output = format!("{stray}\n{}", outcome.output);
+    }
+    Some(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_response_waits_for_the_whole_line() {
+        let marker = "__codex_python_kernel_test__";
+        assert_eq!(
+            parse_response(b"\n__codex_python_kernel_test__ {\"output\": \"\"", marker),
+            None
+        );
+        assert_eq!(
+            parse_response(
+                b"stray\n__codex_python_kernel_test__ {\"output\": \"hi\\n\", \"result\": \"42\", \"error\": null, \"images\": []}\n",
+                marker
+            ),
+            Some(PythonOutcome {
+                output: "stray\nhi\n".to_string(),
+                result: Some("42".to_string()),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn variables_persist_between_calls() -> anyhow::Result<()> {
+        if which::which(python_program()).is_err() {
+            return Ok(());
+        }
+        let dir = tempfile::tempdir()?;
+        let manager = PythonKernelManager::default();
+        let policy = SandboxPolicy::DangerFullAccess;
+        let spawn_env = || ExecEnv {
+            command: vec![
+                python_program().to_string(),
+                "-u".to_string(),
+                "-c".to_string(),
+                KERNEL_DRIVER.to_string(),
+            ],
+            cwd: dir.path().to_path_buf(),
+            env: std::env::vars().collect(),
+            expiration: crate::exec::ExecExpiration::DefaultTimeout,
+            sandbox: SandboxType::None,
+            sandbox_permissions: crate::sandboxing::SandboxPermissions::UseDefault,
+            justification: None,
+            arg0: None,
+        };
+        let timeout = Duration::from_secs(30);
+
+        manager
+            .run("x = 20", false, spawn_env(), &policy, timeout)
+            .await?;
+        let outcome = manager
+            .run("print('hi')\nx + 22", false, spawn_env(), &policy, timeout)
+            .await?;
+        assert_eq!(
+            outcome,
+            PythonOutcome {
+                output: "hi\n".to_string(),
+                result: Some("42".to_string()),
+                ..Default::default()
+            }
+        );
+
+        let reset = manager
+            .run("x", true, spawn_env(), &policy, timeout)
+            .await?;
+        assert!(reset.error.is_some_and(|error| error.contains("NameError")));
+        manager.terminate().await;
+        Ok(())
+    }
+}
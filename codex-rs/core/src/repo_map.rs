@@ -0,0 +1,569 @@
+//! A compact map of the repository (files, sizes and top-level symbols) so
+//! the model can orient itself without listing directories one by one.
+//!
+//! Symbols come from per-language declaration patterns matched against
+//! unindented lines rather than from full parses, which keeps a cold build
+//! of a large checkout to a few seconds. Results are cached per project in
+//! `$CODEX_HOME/repo_maps/` and reused while a file's size and modification
+//! time are unchanged. Each session refreshes the map at start, within
+//! [`STARTUP_BUILD_BUDGET`], and adds it to the instructions; a build that
+//! takes longer finishes in the background for the `repo_map` tool and the
+//! next session.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::time::Duration;
+use std::time::UNIX_EPOCH;
+
+use ignore::WalkBuilder;
+use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::config::Config;
+use crate::git_info::resolve_root_git_project_for_trust;
+use crate::truncate::approx_token_count;
+
+/// Directory inside `CODEX_HOME` holding one cached map per project.
+pub const REPO_MAP_CACHE_DIR: &str = "repo_maps";
+
+/// Bumped whenever the cache layout or symbol extraction changes.
+const CACHE_VERSION: u32 = 1;
+/// Stop walking after this many files; huge monorepos get a partial map.
+const MAX_FILES: usize = 10_000;
+/// Larger files are listed without symbols.
+const MAX_SCAN_BYTES: u64 = 512 * 1024;
+/// Symbols kept per file.
+const MAX_SYMBOLS_PER_FILE: usize = 40;
+/// Symbols shown per file when the whole map fits the budget.
+const RENDERED_SYMBOLS_PER_FILE: usize = 12;
+/// How long session start waits for the map. Refreshing a cached map only
+/// stats the files, so this mostly bounds the first build of a large checkout.
+const STARTUP_BUILD_BUDGET: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    /// Declaration keyword, e.g. `fn`, `class` or `interface`.
+    pub kind: String,
+    pub name: String,
+    /// 1-indexed.
+    pub line: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RepoMapFile {
+    /// Relative to the project root, with `/` separators.
+    pub path: String,
+    pub size: u64,
+    /// Seconds since the epoch; with `size`, decides whether the cached
+    /// symbols are still valid.
+    pub modified: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub symbols: Vec<Symbol>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RepoMap {
+    version: u32,
+    pub files: Vec<RepoMapFile>,
+    /// Set when the walk stopped at `MAX_FILES`.
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// Project root the map describes: the git checkout containing `cwd`, or
+/// `cwd` itself.
+pub fn project_root(cwd: &Path) -> PathBuf {
+    resolve_root_git_project_for_trust(cwd).unwrap_or_else(|| cwd.to_path_buf())
+}
+
+/// Where the map of `root` is cached: named after a hash of the root so the
+/// user's checkout is never written to.
+pub fn cache_path(codex_home: &Path, root: &Path) -> PathBuf {
+    let digest = Sha256::digest(root.to_string_lossy().as_bytes());
+    let hex = format!("{digest:x}");
+    codex_home
+        .join(REPO_MAP_CACHE_DIR)
+        .join(format!("{}.json", hex.get(..16).unwrap_or(&hex)))
+}
+
+/// The map of `root` from the last scan, without looking at the files.
+pub fn cached(codex_home: &Path, root: &Path) -> Option<RepoMap> {
+    std::fs::read_to_string(cache_path(codex_home, root))
+        .ok()
+        .and_then(|text| serde_json::from_str::<RepoMap>(&text).ok())
+        .filter(|map| map.version == CACHE_VERSION)
+}
+
+/// Scans `root`, reusing cached symbols for unchanged files, and rewrites the
+/// cache. A cache that cannot be read or written only costs a full rescan.
+pub fn build(codex_home: &Path, root: &Path) -> io::Result<RepoMap> {
+    let cached: BTreeMap<String, RepoMapFile> = cached(codex_home, root)
+        .map(|map| {
+            map.files
+                .into_iter()
+                .map(|file| (file.path.clone(), file))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut files = Vec::new();
+    let mut truncated = false;
+    let walker = WalkBuilder::new(root)
+        .require_git(false)
+        .sort_by_file_name(Ord::cmp)
+        .build();
+    for entry in walker {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+            continue;
+        }
+        let Ok(relative) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        let path = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if files.len() == MAX_FILES {
+            truncated = true;
+            break;
+        }
+        let size = metadata.len();
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        if let Some(hit) = cached.get(&path)
+            && hit.size == size
+            && hit.modified == modified
+        {
+            files.push(hit.clone());
+            continue;
+        }
+        let symbols = if size <= MAX_SCAN_BYTES {
+            std::fs::read(entry.path())
+                .map(|bytes| extract_symbols(&path, &String::from_utf8_lossy(&bytes)))
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        files.push(RepoMapFile {
+            path,
+            size,
+            modified,
+            symbols,
+        });
+    }
+
+    let map = RepoMap {
+        version: CACHE_VERSION,
+        files,
+        truncated,
+    };
+    if let Err(err) = write_cache(&cache_path(codex_home, root), &map) {
+        tracing::debug!("failed to write repo map cache: {err}");
+    }
+    Ok(map)
+}
+
+fn write_cache(cache_path: &Path, map: &RepoMap) -> io::Result<()> {
+    if let Some(parent) = cache_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = cache_path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec(map)?)?;
+    std::fs::rename(tmp, cache_path)
+}
+
+struct LanguagePatterns {
+    extensions: &'static [&'static str],
+    /// Capture group `kind` is the declaration keyword and `name` the symbol.
+    declaration: Regex,
+}
+
+static LANGUAGES: LazyLock<Vec<LanguagePatterns>> = LazyLock::new(|| {
+    let language = |extensions, pattern: &str| {
+        #[expect(clippy::expect_used)]
+        LanguagePatterns {
+            extensions,
+            declaration: Regex::new(pattern).expect("valid declaration pattern"),
+        }
+    };
+    vec![
+        language(
+            &["rs"],
+            r"^(?:pub(?:\([^)]*\))?\s+)?(?:(?:async|const|unsafe)\s+)*(?P<kind>fn|struct|enum|trait|type|mod|macro_rules!)\s*(?P<name>[A-Za-z_][A-Za-z0-9_]*)",
+        ),
+        language(
+            &["py", "pyi"],
+            r"^(?:async\s+)?(?P<kind>def|class)\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)",
+        ),
+        language(
+            &["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts"],
+            r"^(?:export\s+)?(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?(?:async\s+)?(?P<kind>function\*?|class|interface|type|enum)\s+(?P<name>[A-Za-z_$][A-Za-z0-9_$]*)",
+        ),
+        language(
+            &["go"],
+            r"^(?P<kind>func|type)\s+(?:\([^)]*\)\s*)?(?P<name>[A-Za-z_][A-Za-z0-9_]*)",
+        ),
+        language(
+            &["java", "kt", "kts", "cs", "scala", "swift"],
+            r"^(?:(?:public|private|protected|internal|open|abstract|final|sealed|static|data|partial)\s+)*(?P<kind>class|interface|enum|record|object|struct|protocol|trait)\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)",
+        ),
+        language(
+            &["c", "h", "cc", "cpp", "cxx", "hpp", "hh"],
+            r"^(?:typedef\s+)?(?P<kind>struct|class|enum|union|namespace)\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)",
+        ),
+        language(
+            &["rb"],
+            r"^(?P<kind>class|module|def)\s+(?P<name>[A-Za-z_][A-Za-z0-9_:.?!]*)",
+        ),
+    ]
+});
+
+/// Top-level declarations in `text`, chosen by the extension of `path`.
+fn extract_symbols(path: &str, text: &str) -> Vec<Symbol> {
+    let Some(extension) = path.rsplit_once('.').map(|(_, extension)| extension) else {
+        return Vec::new();
+    };
+    let Some(language) = LANGUAGES
+        .iter()
+        .find(|language| language.extensions.contains(&extension))
+    else {
+        return Vec::new();
+    };
+    text.lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let captures = language.declaration.captures(line)?;
+            Some(Symbol {
+                kind: captures["kind"].to_string(),
+                name: captures["name"].to_string(),
+                line: index + 1,
+            })
+        })
+        .take(MAX_SYMBOLS_PER_FILE)
+        .collect()
+}
+
+/// Renders the files under `prefix` (all files when empty) within
+/// `max_tokens`. When everything does not fit, symbols are dropped from the
+/// deepest files first, then the deepest files themselves.
+pub fn render(map: &RepoMap, prefix: &str, max_tokens: usize) -> String {
+    let prefix = prefix.trim_matches('/');
+    let files: Vec<&RepoMapFile> = map
+        .files
+        .iter()
+        .filter(|file| {
+            prefix.is_empty()
+                || file.path == prefix
+                || file
+                    .path
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+        .collect();
+
+    let lines: Vec<RenderedFile> = files.iter().map(|file| RenderedFile::new(file)).collect();
+
+    // Find the shallowest depth whose symbols must go for the map to fit,
+    // from per-depth token totals so each step is cheap.
+    let max_depth = lines.iter().map(|line| line.depth).max().unwrap_or(0);
+    let mut bare_tokens_by_depth = vec![0; max_depth + 1];
+    let mut full_tokens_by_depth = vec![0; max_depth + 1];
+    for line in &lines {
+        bare_tokens_by_depth[line.depth] += line.bare_tokens;
+        full_tokens_by_depth[line.depth] += line.full_tokens;
+    }
+    let mut symbol_depth = max_depth + 1;
+    let mut total: usize = full_tokens_by_depth.iter().sum();
+    while symbol_depth > 0 && total > max_tokens {
+        symbol_depth -= 1;
+        total -= full_tokens_by_depth[symbol_depth];
+        total += bare_tokens_by_depth[symbol_depth];
+    }
+
+    // Then leave out the deepest files, last listed first.
+    let mut removal_order: Vec<usize> = (0..lines.len()).collect();
+    removal_order.sort_unstable_by_key(|&index| std::cmp::Reverse((lines[index].depth, index)));
+    let mut shown = vec![true; lines.len()];
+    let mut omitted = 0;
+    for index in removal_order {
+        if lines.len() - omitted <= 1 || total <= max_tokens {
+            break;
+        }
+        total -= lines[index].tokens(symbol_depth);
+        shown[index] = false;
+        omitted += 1;
+    }
+
+    let mut out = String::from("Repository map (path, size, top-level symbols):\n");
+    for (line, shown) in lines.iter().zip(shown) {
+        if shown {
+            out.push_str(line.text(symbol_depth));
+            out.push('\n');
+        }
+    }
+    if omitted > 0 {
+        out.push_str(&format!(
+            "[{omitted} of {} files not shown; call repo_map with a path prefix to see more]\n",
+            files.len()
+        ));
+    }
+    if map.truncated {
+        out.push_str(&format!(
+            "[the scan stopped after {MAX_FILES} files; call repo_map with a path prefix to see more]\n"
+        ));
+    }
+    out.trim_end().to_string()
+}
+
+/// One file's line in the map, with and without its symbols.
+struct RenderedFile {
+    depth: usize,
+    bare: String,
+    full: String,
+    /// Token costs including the newline.
+    bare_tokens: usize,
+    full_tokens: usize,
+}
+
+impl RenderedFile {
+    fn new(file: &RepoMapFile) -> Self {
+        let bare = render_file(file, 0);
+        let full = render_file(file, RENDERED_SYMBOLS_PER_FILE);
+        Self {
+            depth: file.path.matches('/').count(),
+            bare_tokens: approx_token_count(&bare) + 1,
+            full_tokens: approx_token_count(&full) + 1,
+            bare,
+            full,
+        }
+    }
+
+    /// Files shallower than `symbol_depth` keep their symbols.
+    fn text(&self, symbol_depth: usize) -> &str {
+        if self.depth < symbol_depth {
+            &self.full
+        } else {
+            &self.bare
+        }
+    }
+
+    fn tokens(&self, symbol_depth: usize) -> usize {
+        if self.depth < symbol_depth {
+            self.full_tokens
+        } else {
+            self.bare_tokens
+        }
+    }
+}
+
+fn render_file(file: &RepoMapFile, symbol_limit: usize) -> String {
+    let size = if file.size >= 1024 {
+        format!("{}K", file.size.div_ceil(1024))
+    } else {
+        format!("{}B", file.size)
+    };
+    let mut line = format!("{} ({size})", file.path);
+    if symbol_limit > 0 && !file.symbols.is_empty() {
+        let names: Vec<String> = file
+            .symbols
+            .iter()
+            .take(symbol_limit)
+            .map(|symbol| format!("{} {}", symbol.kind, symbol.name))
+            .collect();
+        line.push_str(": ");
+        line.push_str(&names.join(", "));
+        if file.symbols.len() > symbol_limit {
+            line.push_str(&format!(", +{}", file.symbols.len() - symbol_limit));
+        }
+    }
+    line
+}
+
+/// Append the repository map for `config.cwd` to `user_instructions`. The
+/// map is rebuilt first, so files changed since the cache was written are
+/// rescanned; when that takes longer than [`STARTUP_BUILD_BUDGET`] the
+/// session starts without a map and the build finishes in the background.
+pub(crate) async fn instructions_with_repo_map(
+    user_instructions: Option<String>,
+    config: &Config,
+) -> Option<String> {
+    let root = project_root(&config.cwd);
+    let codex_home = config.codex_home.clone();
+    let task = tokio::task::spawn_blocking(move || build(&codex_home, &root));
+    let map = match tokio::time::timeout(STARTUP_BUILD_BUDGET, task).await {
+        Ok(Ok(Ok(map))) if !map.files.is_empty() => map,
+        Ok(Ok(Ok(_))) => return user_instructions,
+        Ok(Ok(Err(err))) => {
+            tracing::debug!("failed to build the repo map: {err}");
+            return user_instructions;
+        }
+        Ok(Err(err)) => {
+            tracing::debug!("repo map build panicked: {err}");
+            return user_instructions;
+        }
+        Err(_) => {
+            tracing::debug!("repo map not ready at session start; building in the background");
+            return user_instructions;
+        }
+    };
+    let section = render(&map, "", config.repo_map_max_tokens);
+    match user_instructions {
+        Some(instructions) => Some(format!("{instructions}\n\n{section}")),
+        None => Some(section),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn extracts_top_level_declarations() {
+        let rust = "use std::io;\n\npub struct Config {\n    fn nested() {}\n}\n\npub(crate) async fn load() {}\nimpl Config {}\n";
+        assert_eq!(
+            extract_symbols("src/config.rs", rust),
+            vec![
+                Symbol {
+                    kind: "struct".to_string(),
+                    name: "Config".to_string(),
+                    line: 3,
+                },
+                Symbol {
+                    kind: "fn".to_string(),
+                    name: "load".to_string(),
+                    line: 7,
+                },
+            ]
+        );
+
+        let ts = "export default class App {}\nexport async function main() {}\nconst x = 1;\n";
+        let names: Vec<String> = extract_symbols("web/app.tsx", ts)
+            .into_iter()
+            .map(|symbol| format!("{} {}", symbol.kind, symbol.name))
+            .collect();
+        assert_eq!(names, vec!["class App", "function main"]);
+        assert_eq!(extract_symbols("notes.txt", "class Foo"), Vec::new());
+    }
+
+    #[test]
+    fn build_caches_in_codex_home_and_respects_gitignore() -> anyhow::Result<()> {
+        let codex_home = tempfile::tempdir()?;
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir_all(dir.path().join("src"))?;
+        std::fs::write(dir.path().join(".gitignore"), "target/\n")?;
+        std::fs::create_dir_all(dir.path().join("target"))?;
+        std::fs::write(dir.path().join("target/out.rs"), "fn ignored() {}\n")?;
+        std::fs::write(dir.path().join("src/lib.rs"), "pub fn run() {}\n")?;
+
+        assert_eq!(cached(codex_home.path(), dir.path()), None);
+        let map = build(codex_home.path(), dir.path())?;
+        let paths: Vec<&str> = map.files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/lib.rs"]);
+        assert!(cache_path(codex_home.path(), dir.path()).exists());
+        assert!(!dir.path().join(".codex").exists());
+
+        assert_eq!(cached(codex_home.path(), dir.path()), Some(map.clone()));
+        assert_eq!(build(codex_home.path(), dir.path())?, map);
+        Ok(())
+    }
+
+    #[test]
+    fn render_drops_deep_symbols_then_deep_files_to_fit() {
+        let file = |path: &str, symbols: &[&str]| RepoMapFile {
+            path: path.to_string(),
+            size: 2048,
+            modified: 0,
+            symbols: symbols
+                .iter()
+                .enumerate()
+                .map(|(index, name)| Symbol {
+                    kind: "fn".to_string(),
+                    name: name.to_string(),
+                    line: index + 1,
+                })
+                .collect(),
+        };
+        let map = RepoMap {
+            version: CACHE_VERSION,
+            files: vec![
+                file("README.md", &[]),
+                file("src/lib.rs", &["alpha", "beta"]),
+                file(
+                    "src/deep/nested/mod.rs",
+                    &["gamma_function_with_a_long_name"],
+                ),
+            ],
+            truncated: false,
+        };
+
+        assert_eq!(
+            render(&map, "", 1_000),
+            "Repository map (path, size, top-level symbols):
+README.md (2K)
+src/lib.rs (2K): fn alpha, fn beta
+src/deep/nested/mod.rs (2K): fn gamma_function_with_a_long_name"
+        );
+        assert_eq!(
+            render(&map, "", 23),
+            "Repository map (path, size, top-level symbols):
+README.md (2K)
+src/lib.rs (2K): fn alpha, fn beta
+src/deep/nested/mod.rs (2K)"
+        );
+        assert_eq!(
+            render(&map, "", 10),
+            "Repository map (path, size, top-level symbols):
+README.md (2K)
+src/lib.rs (2K)
+[1 of 3 files not shown; call repo_map with a path prefix to see more]"
+        );
+        assert_eq!(
+            render(&map, "src/deep", 1_000),
+            "Repository map (path, size, top-level symbols):
+src/deep/nested/mod.rs (2K): fn gamma_function_with_a_long_name"
+        );
+    }
+
+    #[test]
+    fn render_keeps_the_shallowest_files_of_a_large_map() {
+        let files: Vec<RepoMapFile> = (0..MAX_FILES)
+            .map(|index| RepoMapFile {
+                path: format!("{}file{index}.rs", "d/".repeat(index % 8)),
+                size: 10,
+                modified: 0,
+                symbols: Vec::new(),
+            })
+            .collect();
+        let map = RepoMap {
+            version: CACHE_VERSION,
+            files,
+            truncated: false,
+        };
+
+        let rendered = render(&map, "", 100);
+        assert!(rendered.contains("file0.rs (10B)"));
+        assert!(!rendered.contains("file7.rs"));
+        assert!(
+            rendered.ends_with("files not shown; call repo_map with a path prefix to see more]")
+        );
+    }
+}
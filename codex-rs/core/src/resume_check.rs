@@ -0,0 +1,331 @@
+//! Revalidates the environment when a session is resumed from a rollout.
+//!
+//! A rollout records where the session ran (working directory, git branch and
+//! commit), which files it edited and which MCP tools it called. The world
+//! keeps moving while the rollout sits on disk, so on resume [`revalidate`]
+//! compares that snapshot with the current state. The differences are shown
+//! to the user and written into the conversation so the model re-checks its
+//! assumptions instead of continuing from stale observations.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::GitInfo;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::SessionDiffEvent;
+
+use crate::git_info::collect_git_info;
+use crate::git_info::get_git_repo_root;
+use crate::mcp_connection_manager::McpConnectionManager;
+use crate::mcp_connection_manager::qualified_tool_prefix;
+
+/// What a rollout says about the environment it was recorded in.
+#[derive(Debug, Default)]
+struct ResumeSnapshot {
+    cwd: Option<PathBuf>,
+    git: Option<GitInfo>,
+    /// Files edited by the session, as named in its cumulative diff, and
+    /// whether the session deleted them.
+    edited_files: Vec<(String, bool)>,
+    /// Fully qualified names of the MCP tools the session called.
+    mcp_tools: Vec<String>,
+}
+
+impl ResumeSnapshot {
+    fn from_rollout(items: &[RolloutItem]) -> Self {
+        let mut snapshot = Self::default();
+        let mut session_diff = None;
+        for item in items {
+            match item {
+                RolloutItem::SessionMeta(meta) => {
+                    snapshot.cwd = Some(meta.meta.cwd.clone());
+                    snapshot.git = meta.git.clone();
+                }
+                RolloutItem::TurnContext(ctx) => snapshot.cwd = Some(ctx.cwd.clone()),
+                RolloutItem::ResponseItem(ResponseItem::FunctionCall { name, .. })
+                    if name.starts_with(&qualified_tool_prefix())
+                        && !snapshot.mcp_tools.contains(name) =>
+                {
+                    snapshot.mcp_tools.push(name.clone());
+                }
+                RolloutItem::EventMsg(EventMsg::SessionDiff(SessionDiffEvent { unified_diff })) => {
+                    session_diff = Some(unified_diff.as_str());
+                }
+                _ => {}
+            }
+        }
+        snapshot.edited_files = session_diff.map(edited_files).unwrap_or_default();
+        snapshot
+    }
+}
+
+/// Differences between a resumed rollout and the current environment.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct ResumeDrift {
+    /// Recorded and current working directory.
+    pub cwd: Option<(PathBuf, PathBuf)>,
+    /// Recorded and current branch.
+    pub branch: Option<(String, String)>,
+    /// Recorded and current `HEAD` commit.
+    pub head: Option<(String, String)>,
+    /// Files the session edited that changed afterwards, with what happened.
+    pub changed_files: Vec<(String, &'static str)>,
+    /// MCP tools the session called that no server provides any more.
+    pub missing_tools: Vec<String>,
+}
+
+impl ResumeDrift {
+    pub(crate) fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some((recorded, current)) = &self.cwd {
+            lines.push(format!(
+                "working directory changed from {} to {}",
+                recorded.display(),
+                current.display()
+            ));
+        }
+        if let Some((recorded, current)) = &self.branch {
+            lines.push(format!(
+                "git branch changed from `{recorded}` to `{current}`"
+            ));
+        }
+        if let Some((recorded, current)) = &self.head {
+            lines.push(format!(
+                "HEAD moved from {} to {}",
+                short_sha(recorded),
+                short_sha(current)
+            ));
+        }
+        for (path, change) in &self.changed_files {
+            lines.push(format!("{path} was {change} outside this session"));
+        }
+        for tool in &self.missing_tools {
+            lines.push(format!("MCP tool `{tool}` is no longer available"));
+        }
+        lines
+    }
+
+    /// Shown to the user when the session resumes.
+    pub(crate) fn warning(&self) -> String {
+        let mut message = "The environment changed since this session was last active:".to_string();
+        for line in self.lines() {
+            message.push_str("\n- ");
+            message.push_str(&line);
+        }
+        message
+    }
+
+    /// Recorded into the conversation ahead of the next turn.
+    pub(crate) fn context_note(&self) -> String {
+        let mut note =
+            "State changed since the last session. Earlier observations may be stale:".to_string();
+        for line in self.lines() {
+            note.push_str("\n- ");
+            note.push_str(&line);
+        }
+        note.push_str(
+            "\nRe-read affected files and re-check the repository state before relying on them.",
+        );
+        note
+    }
+}
+
+/// Compares the environment recorded in `items` with the one the session is
+/// resuming into. `rollout_path` is the resumed rollout, whose modification
+/// time marks when the session was last active.
+pub(crate) async fn revalidate(
+    items: &[RolloutItem],
+    rollout_path: &Path,
+    cwd: &Path,
+    mcp_connection_manager: &McpConnectionManager,
+) -> ResumeDrift {
+    let snapshot = ResumeSnapshot::from_rollout(items);
+    let mut drift = ResumeDrift::default();
+
+    if let Some(recorded) = snapshot.cwd
+        && recorded != cwd
+    {
+        drift.cwd = Some((recorded, cwd.to_path_buf()));
+    }
+
+    if let Some(recorded) = snapshot.git
+        && let Some(current) = collect_git_info(cwd).await
+    {
+        if let (Some(recorded), Some(current)) = (recorded.branch, current.branch)
+            && recorded != current
+        {
+            drift.branch = Some((recorded, current));
+        }
+        if let (Some(recorded), Some(current)) = (recorded.commit_hash, current.commit_hash)
+            && recorded != current
+        {
+            drift.head = Some((recorded, current));
+        }
+    }
+
+    let last_active = std::fs::metadata(rollout_path)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    let root = get_git_repo_root(cwd).unwrap_or_else(|| cwd.to_path_buf());
+    for (path, deleted) in snapshot.edited_files {
+        if let Some(change) = file_change(&root.join(&path), deleted, last_active) {
+            drift.changed_files.push((path, change));
+        }
+    }
+
+    if !snapshot.mcp_tools.is_empty() {
+        // Waits for every configured server to finish its handshake.
+        let available = mcp_connection_manager.list_all_tools().await;
+        drift.missing_tools = snapshot
+            .mcp_tools
+            .into_iter()
+            .filter(|tool| !available.contains_key(tool))
+            .collect();
+    }
+
+    drift
+}
+
+/// How the file at `path` differs from what the session left behind.
+fn file_change(
+    path: &Path,
+    deleted: bool,
+    last_active: Option<SystemTime>,
+) -> Option<&'static str> {
+    match std::fs::metadata(path) {
+        Ok(_) if deleted => Some("recreated"),
+        Ok(metadata) => {
+            let modified = metadata.modified().ok()?;
+            (modified > last_active?).then_some("modified")
+        }
+        Err(_) if !deleted => Some("deleted"),
+        Err(_) => None,
+    }
+}
+
+/// Paths in a cumulative session diff, relative to the repository root, and
+/// whether the session deleted each one.
+pub(crate) fn edited_files(unified_diff: &str) -> Vec<(String, bool)> {
+    let mut files: Vec<(String, bool)> = Vec::new();
+    for line in unified_diff.lines() {
+        if let Some(paths) = line.strip_prefix("diff --git a/")
+            && let Some((_, path)) = paths.rsplit_once(" b/")
+        {
+            files.push((path.to_string(), false));
+        } else if line.starts_with("deleted file mode")
+            && let Some((_, deleted)) = files.last_mut()
+        {
+            *deleted = true;
+        }
+    }
+    files
+}
+
+fn short_sha(sha: &str) -> &str {
+    sha.get(..7).unwrap_or(sha)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::SessionMeta;
+    use codex_protocol::protocol::SessionMetaLine;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn snapshot_reads_cwd_git_edits_and_mcp_tools() {
+        let items = vec![
+            RolloutItem::SessionMeta(SessionMetaLine {
+                meta: SessionMeta {
+                    cwd: PathBuf::from("/repo"),
+                    ..Default::default()
+                },
+                git: Some(GitInfo {
+                    commit_hash: Some("abc".to_string()),
+                    branch: Some("main".to_string()),
+                    repository_url: None,
+                }),
+            }),
+            RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                id: None,
+                name: "mcp__docs__search".to_string(),
+                arguments: "{}".to_string(),
+                call_id: "call-1".to_string(),
+            }),
+            RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: "{}".to_string(),
+                call_id: "call-2".to_string(),
+            }),
+            RolloutItem::EventMsg(EventMsg::SessionDiff(SessionDiffEvent {
+                unified_diff: "diff --git a/src/lib.rs b/src/lib.rs\nindex 1..2 100644\n\
+                               diff --git a/old.txt b/old.txt\ndeleted file mode 100644\n"
+                    .to_string(),
+            })),
+        ];
+
+        let snapshot = ResumeSnapshot::from_rollout(&items);
+        assert_eq!(snapshot.cwd, Some(PathBuf::from("/repo")));
+        assert_eq!(
+            snapshot.git.and_then(|git| git.branch),
+            Some("main".to_string())
+        );
+        assert_eq!(snapshot.mcp_tools, vec!["mcp__docs__search".to_string()]);
+        assert_eq!(
+            snapshot.edited_files,
+            vec![
+                ("src/lib.rs".to_string(), false),
+                ("old.txt".to_string(), true)
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_files_changed_after_the_session() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("file.txt");
+        std::fs::write(&path, "x").expect("write file");
+        let modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .expect("mtime");
+        let earlier = modified - std::time::Duration::from_secs(60);
+
+        assert_eq!(file_change(&path, false, Some(earlier)), Some("modified"));
+        assert_eq!(file_change(&path, false, Some(modified)), None);
+        assert_eq!(file_change(&path, true, Some(modified)), Some("recreated"));
+        let missing = dir.path().join("missing.txt");
+        assert_eq!(
+            file_change(&missing, false, Some(modified)),
+            Some("deleted")
+        );
+        assert_eq!(file_change(&missing, true, Some(modified)), None);
+    }
+
+    #[test]
+    fn note_lists_every_change() {
+        let drift = ResumeDrift {
+            branch: Some(("main".to_string(), "feature".to_string())),
+            head: Some(("0123456789".to_string(), "abcdef0123".to_string())),
+            changed_files: vec![("src/lib.rs".to_string(), "modified")],
+            missing_tools: vec!["mcp__docs__search".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            drift.warning(),
+            "The environment changed since this session was last active:\n\
+             - git branch changed from `main` to `feature`\n\
+             - HEAD moved from 0123456 to abcdef0\n\
+             - src/lib.rs was modified outside this session\n\
+             - MCP tool `mcp__docs__search` is no longer available"
+        );
+        assert!(ResumeDrift::default().is_empty());
+    }
+}
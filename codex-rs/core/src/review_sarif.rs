@@ -0,0 +1,236 @@
+//! Render review findings as SARIF 2.1.0, the format GitHub code scanning and
+//! most review tooling ingest.
+
+use std::path::Path;
+
+use serde_json::Value;
+use serde_json::json;
+use url::Url;
+
+use crate::protocol::ReviewFinding;
+use crate::protocol::ReviewOutputEvent;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+const SRCROOT: &str = "%SRCROOT%";
+
+/// One rule per review priority; findings carry no finer classification.
+const RULES: [(&str, &str, &str); 4] = [
+    ("codex-review/P0", "P0: drop everything to fix", "error"),
+    (
+        "codex-review/P1",
+        "P1: urgent, fix in the next cycle",
+        "error",
+    ),
+    ("codex-review/P2", "P2: normal, fix eventually", "warning"),
+    ("codex-review/P3", "P3: low, nice to have", "note"),
+];
+
+/// Builds a SARIF log with one run for `output`. Paths under `root` are made
+/// relative to it, so results line up with the repository when uploaded.
+/// `None` means the review produced no output; the run is then marked as not
+/// having completed successfully.
+pub fn review_output_to_sarif(
+    output: Option<&ReviewOutputEvent>,
+    root: &Path,
+    tool_version: &str,
+) -> Value {
+    let rules: Vec<Value> = RULES
+        .iter()
+        .map(|(id, description, level)| {
+            json!({
+                "id": id,
+                "shortDescription": { "text": description },
+                "defaultConfiguration": { "level": level },
+            })
+        })
+        .collect();
+    let results: Vec<Value> = output
+        .map(|output| {
+            output
+                .findings
+                .iter()
+                .map(|finding| finding_to_result(finding, root))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut run = json!({
+        "tool": {
+            "driver": {
+                "name": "codex",
+                "version": tool_version,
+                "informationUri": "https://github.com/openai/codex",
+                "rules": rules,
+            }
+        },
+        "invocations": [{ "executionSuccessful": output.is_some() }],
+        "results": results,
+    });
+    if let Ok(root_uri) = Url::from_directory_path(root) {
+        run["originalUriBaseIds"] = json!({ SRCROOT: { "uri": root_uri.as_str() } });
+    }
+    if let Some(output) = output {
+        run["properties"] = json!({
+            "overallCorrectness": output.overall_correctness,
+            "overallExplanation": output.overall_explanation,
+            "overallConfidenceScore": output.overall_confidence_score,
+        });
+    }
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [run],
+    })
+}
+
+fn finding_to_result(finding: &ReviewFinding, root: &Path) -> Value {
+    let (rule_index, level) = match usize::try_from(finding.priority)
+        .ok()
+        .and_then(|index| RULES.get(index).map(|rule| (index, rule.2)))
+    {
+        Some(rule) => rule,
+        // Unknown priorities are reported as P2.
+        None => (2, RULES[2].2),
+    };
+    let title = strip_priority_tag(&finding.title);
+    let text = if finding.body.trim().is_empty() {
+        title.to_string()
+    } else {
+        format!("{title}\n\n{}", finding.body.trim())
+    };
+
+    let location = &finding.code_location;
+    let artifact = match location.absolute_file_path.strip_prefix(root) {
+        Ok(relative) => json!({
+            "uri": relative_uri(relative),
+            "uriBaseId": SRCROOT,
+        }),
+        Err(_) => match Url::from_file_path(&location.absolute_file_path) {
+            Ok(uri) => json!({ "uri": uri.as_str() }),
+            Err(()) => json!({ "uri": location.absolute_file_path.to_string_lossy() }),
+        },
+    };
+    // SARIF lines are 1-based; the reviewer occasionally reports 0.
+    let start_line = location.line_range.start.max(1);
+    let end_line = location.line_range.end.max(start_line);
+
+    json!({
+        "ruleId": RULES[rule_index].0,
+        "ruleIndex": rule_index,
+        "level": level,
+        "message": { "text": text },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": artifact,
+                "region": { "startLine": start_line, "endLine": end_line },
+            }
+        }],
+        "properties": {
+            "confidence": finding.confidence_score,
+            "priority": finding.priority,
+        },
+    })
+}
+
+/// Titles are written as `[P1] Short description`; the tag is already
+/// conveyed by the rule.
+fn strip_priority_tag(title: &str) -> &str {
+    let trimmed = title.trim();
+    if let Some(rest) = trimmed.strip_prefix("[P")
+        && let Some((tag, rest)) = rest.split_once(']')
+        && tag.chars().all(|c| c.is_ascii_digit())
+    {
+        return rest.trim_start();
+    }
+    trimmed
+}
+
+fn relative_uri(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::ReviewCodeLocation;
+    use crate::protocol::ReviewLineRange;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn finding(title: &str, priority: i32, path: &str, start: u32, end: u32) -> ReviewFinding {
+        ReviewFinding {
+            title: title.to_string(),
+            body: "The lock is released before the write completes.".to_string(),
+            confidence_score: 0.75,
+            priority,
+            code_location: ReviewCodeLocation {
+                absolute_file_path: PathBuf::from(path),
+                line_range: ReviewLineRange { start, end },
+            },
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn findings_become_results_with_relative_locations() {
+        let output = ReviewOutputEvent {
+            findings: vec![
+                finding("[P1] Race on shutdown", 1, "/repo/src/lib.rs", 10, 12),
+                finding("Unused import", 3, "/elsewhere/mod.rs", 0, 0),
+            ],
+            overall_correctness: "patch is incorrect".to_string(),
+            overall_explanation: "One race.".to_string(),
+            overall_confidence_score: 0.5,
+        };
+        let sarif = review_output_to_sarif(Some(&output), Path::new("/repo"), "1.2.3");
+
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["version"], "1.2.3");
+        assert_eq!(run["originalUriBaseIds"][SRCROOT]["uri"], "file:///repo/");
+        assert_eq!(run["invocations"][0]["executionSuccessful"], true);
+        assert_eq!(
+            run["results"][0],
+            json!({
+                "ruleId": "codex-review/P1",
+                "ruleIndex": 1,
+                "level": "error",
+                "message": {
+                    "text": "Race on shutdown\n\nThe lock is released before the write completes."
+                },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "src/lib.rs", "uriBaseId": SRCROOT },
+                        "region": { "startLine": 10, "endLine": 12 },
+                    }
+                }],
+                "properties": { "confidence": 0.75, "priority": 1 },
+            })
+        );
+        let second = &run["results"][1];
+        assert_eq!(second["level"], "note");
+        assert_eq!(
+            second["locations"][0]["physicalLocation"]["artifactLocation"],
+            json!({ "uri": "file:///elsewhere/mod.rs" })
+        );
+        assert_eq!(
+            second["locations"][0]["physicalLocation"]["region"],
+            json!({ "startLine": 1, "endLine": 1 })
+        );
+    }
+
+    #[test]
+    fn a_failed_review_is_an_unsuccessful_run_without_results() {
+        let sarif = review_output_to_sarif(None, Path::new("/repo"), "1.2.3");
+        assert_eq!(sarif["version"], SARIF_VERSION);
+        assert_eq!(
+            sarif["runs"][0]["invocations"][0]["executionSuccessful"],
+            false
+        );
+        assert_eq!(sarif["runs"][0]["results"], json!([]));
+    }
+}
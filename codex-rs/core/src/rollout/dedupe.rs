@@ -0,0 +1,259 @@
+//! Deduplication of tool outputs in rollout files. Long sessions often run the
+//! same command or read the same file many times; an output identical to an
+//! earlier one in the same file is written as a [`ToolOutputRefItem`] and
+//! expanded back into the full output when the rollout is loaded.
+//!
+//! References never cross a compaction checkpoint, so a session resumed from
+//! its last checkpoint can expand them without reading what came before.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::ToolOutputRefItem;
+use sha2::Digest;
+use sha2::Sha256;
+use tracing::warn;
+
+use super::index::EntryKind;
+
+/// Outputs shorter than this are always written in full; a reference would
+/// save next to nothing.
+const MIN_DEDUPE_BYTES: usize = 1024;
+
+/// Replaces repeated tool outputs with references to their first occurrence.
+#[derive(Debug, Default)]
+pub(crate) struct ToolOutputDeduper {
+    first_call_by_digest: HashMap<[u8; 32], String>,
+    /// Calls whose output was replaced by a reference, and the call it now
+    /// points to. Only needed when rewriting a file that already contains
+    /// references to those calls.
+    aliases: HashMap<String, String>,
+}
+
+impl ToolOutputDeduper {
+    /// `item`, or a reference to an earlier identical tool output.
+    pub(crate) fn dedupe(&mut self, item: RolloutItem) -> RolloutItem {
+        if EntryKind::of(&item) == EntryKind::Checkpoint {
+            self.first_call_by_digest.clear();
+        }
+        let response = match item {
+            RolloutItem::ResponseItem(response) => response,
+            RolloutItem::ToolOutputRef(mut reference) => {
+                if let Some(target) = self.aliases.get(&reference.same_as) {
+                    reference.same_as = target.clone();
+                }
+                return RolloutItem::ToolOutputRef(reference);
+            }
+            other => return other,
+        };
+        let Some((call_id, digest)) = output_digest(&response) else {
+            return RolloutItem::ResponseItem(response);
+        };
+        match self.first_call_by_digest.entry(digest) {
+            Entry::Occupied(first) => {
+                let same_as = first.get().clone();
+                self.aliases.insert(call_id.clone(), same_as.clone());
+                RolloutItem::ToolOutputRef(ToolOutputRefItem { call_id, same_as })
+            }
+            Entry::Vacant(slot) => {
+                slot.insert(call_id);
+                RolloutItem::ResponseItem(response)
+            }
+        }
+    }
+}
+
+/// Turns [`ToolOutputRefItem`]s back into the outputs they stand for.
+#[derive(Debug, Default)]
+pub(crate) struct ToolOutputExpander {
+    outputs: HashMap<String, ResponseItem>,
+}
+
+impl ToolOutputExpander {
+    /// `item` with references expanded; `None` for a reference whose target
+    /// is missing from the file.
+    pub(crate) fn expand(&mut self, item: RolloutItem) -> Option<RolloutItem> {
+        match item {
+            RolloutItem::ToolOutputRef(reference) => {
+                let Some(output) = self.outputs.get(&reference.same_as) else {
+                    warn!(
+                        "rollout references the output of unknown call {}",
+                        reference.same_as
+                    );
+                    return None;
+                };
+                Some(RolloutItem::ResponseItem(with_call_id(
+                    output,
+                    reference.call_id,
+                )))
+            }
+            RolloutItem::ResponseItem(response) => {
+                if let Some((call_id, _)) = output_digest(&response) {
+                    self.outputs.insert(call_id, response.clone());
+                }
+                Some(RolloutItem::ResponseItem(response))
+            }
+            other => Some(other),
+        }
+    }
+}
+
+/// The call id and a digest of the output, for tool outputs large enough to
+/// be worth deduplicating.
+fn output_digest(item: &ResponseItem) -> Option<(String, [u8; 32])> {
+    let mut hasher = Sha256::new();
+    let call_id = match item {
+        ResponseItem::FunctionCallOutput { call_id, output }
+            if output.content.len() >= MIN_DEDUPE_BYTES =>
+        {
+            hasher.update(b"function_call_output\0");
+            hasher.update(serde_json::to_vec(output).ok()?);
+            call_id
+        }
+        ResponseItem::CustomToolCallOutput { call_id, output }
+            if output.len() >= MIN_DEDUPE_BYTES =>
+        {
+            hasher.update(b"custom_tool_call_output\0");
+            hasher.update(output.as_bytes());
+            call_id
+        }
+        _ => return None,
+    };
+    Some((call_id.clone(), hasher.finalize().into()))
+}
+
+fn with_call_id(output: &ResponseItem, new_call_id: String) -> ResponseItem {
+    let mut output = output.clone();
+    if let ResponseItem::FunctionCallOutput { call_id, .. }
+    | ResponseItem::CustomToolCallOutput { call_id, .. } = &mut output
+    {
+        *call_id = new_call_id;
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use codex_protocol::protocol::CompactedItem;
+    use pretty_assertions::assert_eq;
+
+    fn output(call_id: &str, content: &str) -> RolloutItem {
+        RolloutItem::ResponseItem(ResponseItem::FunctionCallOutput {
+            call_id: call_id.to_string(),
+            output: FunctionCallOutputPayload {
+                content: content.to_string(),
+                ..Default::default()
+            },
+        })
+    }
+
+    fn reference(call_id: &str, same_as: &str) -> RolloutItem {
+        RolloutItem::ToolOutputRef(ToolOutputRefItem {
+            call_id: call_id.to_string(),
+            same_as: same_as.to_string(),
+        })
+    }
+
+    fn items_json(items: &[RolloutItem]) -> Vec<serde_json::Value> {
+        items
+            .iter()
+            .map(|item| serde_json::to_value(item).expect("serialize"))
+            .collect()
+    }
+
+    #[test]
+    fn repeated_large_outputs_round_trip_through_references() {
+        let large = "x".repeat(MIN_DEDUPE_BYTES);
+        let original = vec![
+            output("call-1", &large),
+            output("call-2", "small"),
+            output("call-3", &large),
+            output("call-4", "small"),
+        ];
+
+        let mut deduper = ToolOutputDeduper::default();
+        let written: Vec<RolloutItem> = original
+            .iter()
+            .cloned()
+            .map(|item| deduper.dedupe(item))
+            .collect();
+        assert_eq!(
+            items_json(&written),
+            items_json(&[
+                output("call-1", &large),
+                output("call-2", "small"),
+                reference("call-3", "call-1"),
+                output("call-4", "small"),
+            ])
+        );
+
+        let mut expander = ToolOutputExpander::default();
+        let loaded: Vec<RolloutItem> = written
+            .into_iter()
+            .filter_map(|item| expander.expand(item))
+            .collect();
+        assert_eq!(items_json(&loaded), items_json(&original));
+    }
+
+    #[test]
+    fn rewriting_redirects_references_to_outputs_that_became_references() {
+        // A resumed session starts with an empty deduper, so the file can
+        // hold the same output twice with references to the second copy.
+        let large = "y".repeat(MIN_DEDUPE_BYTES);
+        let mut deduper = ToolOutputDeduper::default();
+        let rewritten: Vec<RolloutItem> = [
+            output("call-1", &large),
+            output("call-2", &large),
+            reference("call-3", "call-2"),
+        ]
+        .into_iter()
+        .map(|item| deduper.dedupe(item))
+        .collect();
+        assert_eq!(
+            items_json(&rewritten),
+            items_json(&[
+                output("call-1", &large),
+                reference("call-2", "call-1"),
+                reference("call-3", "call-1"),
+            ])
+        );
+    }
+
+    #[test]
+    fn references_do_not_cross_checkpoints() {
+        let large = "z".repeat(MIN_DEDUPE_BYTES);
+        let checkpoint = RolloutItem::Compacted(CompactedItem {
+            message: String::new(),
+            replacement_history: Some(Vec::new()),
+        });
+        let mut deduper = ToolOutputDeduper::default();
+        let written: Vec<RolloutItem> = [
+            output("call-1", &large),
+            checkpoint.clone(),
+            output("call-2", &large),
+            output("call-3", &large),
+        ]
+        .into_iter()
+        .map(|item| deduper.dedupe(item))
+        .collect();
+        assert_eq!(
+            items_json(&written),
+            items_json(&[
+                output("call-1", &large),
+                checkpoint,
+                output("call-2", &large),
+                reference("call-3", "call-2"),
+            ])
+        );
+    }
+
+    #[test]
+    fn dangling_references_are_dropped() {
+        let mut expander = ToolOutputExpander::default();
+        assert!(expander.expand(reference("call-2", "call-1")).is_none());
+    }
+}
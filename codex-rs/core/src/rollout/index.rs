@@ -0,0 +1,599 @@
+//! Rollout format v2: a sidecar index next to each rollout file.
+//!
+//! Rollouts stay JSONL, one (possibly encrypted) item per line, so `jq` and
+//! older Codex versions keep working. Next to `rollout-*.jsonl` the writer
+//! keeps `rollout-*.jsonl.idx`: an 8-byte header followed by one fixed-size
+//! entry per line with the line's offset, length, checksum and kind. The
+//! index lets readers
+//!
+//! - fetch the last N items without parsing the rest of the file,
+//! - resume a large session from its last compaction checkpoint, and
+//! - notice lines that were torn or altered after a crash.
+//!
+//! Only the writer changes the index. Readers that find it missing or behind
+//! the rollout (e.g. after a crash between the two writes) scan the uncovered
+//! part of the rollout instead; the writer extends the index the next time
+//! the session is resumed.
+
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use sha2::Digest;
+use sha2::Sha256;
+use tracing::warn;
+
+use crate::storage_encryption::decode_line;
+
+const INDEX_SUFFIX: &str = ".idx";
+const MAGIC: &[u8; 8] = b"CXRIDX02";
+const ENTRY_BYTES: usize = 24;
+/// How much [`repair_torn_tail`] reads at a time while looking back for the
+/// end of the last complete line.
+const REPAIR_CHUNK_BYTES: u64 = 64 * 1024;
+
+/// What a rollout line holds, as far as partial reads care.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EntryKind {
+    Other = 0,
+    SessionMeta = 1,
+    /// A compaction that replaced the whole history; nothing before it is
+    /// needed to rebuild what the model sees.
+    Checkpoint = 2,
+    TurnContext = 3,
+    SessionSummary = 4,
+    PinnedContext = 5,
+    SessionDiff = 6,
+    TokenCount = 7,
+    PlanUpdate = 8,
+}
+
+impl EntryKind {
+    pub(crate) fn of(item: &RolloutItem) -> Self {
+        match item {
+            RolloutItem::SessionMeta(_) => Self::SessionMeta,
+            RolloutItem::Compacted(compacted) if compacted.replacement_history.is_some() => {
+                Self::Checkpoint
+            }
+            RolloutItem::TurnContext(_) => Self::TurnContext,
+            RolloutItem::SessionSummary(_) => Self::SessionSummary,
+            RolloutItem::EventMsg(EventMsg::PinnedContextUpdated(_)) => Self::PinnedContext,
+            RolloutItem::EventMsg(EventMsg::SessionDiff(_)) => Self::SessionDiff,
+            RolloutItem::EventMsg(EventMsg::TokenCount(_)) => Self::TokenCount,
+            RolloutItem::EventMsg(EventMsg::PlanUpdate(_)) => Self::PlanUpdate,
+            _ => Self::Other,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Self {
+        match byte {
+            1 => Self::SessionMeta,
+            2 => Self::Checkpoint,
+            3 => Self::TurnContext,
+            4 => Self::SessionSummary,
+            5 => Self::PinnedContext,
+            6 => Self::SessionDiff,
+            7 => Self::TokenCount,
+            8 => Self::PlanUpdate,
+            _ => Self::Other,
+        }
+    }
+
+    /// Kinds whose latest entry describes the session's state, so resuming
+    /// from a checkpoint still loads the last one recorded before it.
+    fn is_snapshot(self) -> bool {
+        matches!(
+            self,
+            Self::TurnContext
+                | Self::SessionSummary
+                | Self::PinnedContext
+                | Self::SessionDiff
+                | Self::TokenCount
+                | Self::PlanUpdate
+        )
+    }
+}
+
+/// Where one rollout line lives, without its trailing newline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct IndexEntry {
+    pub offset: u64,
+    pub len: u32,
+    pub checksum: u32,
+    pub kind: EntryKind,
+}
+
+impl IndexEntry {
+    pub(crate) fn new(offset: u64, line: &[u8], kind: EntryKind) -> Self {
+        Self {
+            offset,
+            len: u32::try_from(line.len()).unwrap_or(u32::MAX),
+            checksum: checksum(line),
+            kind,
+        }
+    }
+
+    /// Offset just past the line's newline.
+    pub(crate) fn end(&self) -> u64 {
+        self.offset + u64::from(self.len) + 1
+    }
+
+    pub(crate) fn encode(&self) -> [u8; ENTRY_BYTES] {
+        let mut bytes = [0u8; ENTRY_BYTES];
+        bytes[..8].copy_from_slice(&self.offset.to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.len.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.checksum.to_le_bytes());
+        bytes[16] = self.kind as u8;
+        bytes
+    }
+
+    fn decode(bytes: &[u8; ENTRY_BYTES]) -> Self {
+        let mut offset = [0u8; 8];
+        offset.copy_from_slice(&bytes[..8]);
+        let mut len = [0u8; 4];
+        len.copy_from_slice(&bytes[8..12]);
+        let mut checksum = [0u8; 4];
+        checksum.copy_from_slice(&bytes[12..16]);
+        Self {
+            offset: u64::from_le_bytes(offset),
+            len: u32::from_le_bytes(len),
+            checksum: u32::from_le_bytes(checksum),
+            kind: EntryKind::from_byte(bytes[16]),
+        }
+    }
+}
+
+/// The index file that belongs to `rollout_path`. Anything that deletes or
+/// moves a rollout should take its index along.
+pub fn rollout_index_path(rollout_path: &Path) -> PathBuf {
+    let mut name = rollout_path.as_os_str().to_os_string();
+    name.push(INDEX_SUFFIX);
+    PathBuf::from(name)
+}
+
+fn checksum(line: &[u8]) -> u32 {
+    let digest = Sha256::digest(line);
+    u32::from_le_bytes([digest[0], digest[1], digest[2], digest[3]])
+}
+
+/// Appends entries to the index of a rollout the caller is writing.
+pub(crate) struct IndexWriter {
+    file: File,
+}
+
+impl IndexWriter {
+    /// Starts the index of a new, empty rollout.
+    pub(crate) fn create(rollout_path: &Path) -> io::Result<Self> {
+        let mut file = File::create(rollout_index_path(rollout_path))?;
+        file.write_all(MAGIC)?;
+        Ok(Self { file })
+    }
+
+    /// Prepares an existing rollout for appending: drops a line torn by a
+    /// crash, brings the index up to date with the rollout and returns the
+    /// writer together with the offset the next line will be written at.
+    pub(crate) fn open_for_append(rollout_path: &Path) -> io::Result<(Self, u64)> {
+        let len = repair_torn_tail(rollout_path)?;
+        let indexed = indexed_entries(rollout_path, len);
+        let covered = indexed.last().map_or(0, IndexEntry::end);
+        let missing = scan_lines(rollout_path, covered, len)?;
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(rollout_index_path(rollout_path))?;
+        if indexed.is_empty() {
+            file.set_len(0)?;
+            file.write_all(MAGIC)?;
+        } else {
+            file.set_len((MAGIC.len() + indexed.len() * ENTRY_BYTES) as u64)?;
+            file.seek(SeekFrom::End(0))?;
+        }
+        let mut writer = Self { file };
+        for entry in missing {
+            writer.append(&entry)?;
+        }
+        Ok((writer, len))
+    }
+
+    pub(crate) fn append(&mut self, entry: &IndexEntry) -> io::Result<()> {
+        self.file.write_all(&entry.encode())
+    }
+
+    /// Hands the index file over to a writer that appends
+    /// [`IndexEntry::encode`]d entries itself.
+    pub(crate) fn into_file(self) -> File {
+        self.file
+    }
+}
+
+/// Cuts a partially written last line off the rollout at `path` and returns
+/// the resulting length. Appending after such a line would glue the next
+/// item onto it and lose both.
+pub(crate) fn repair_torn_tail(path: &Path) -> io::Result<u64> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let len = file.metadata()?.len();
+    if len == 0 {
+        return Ok(0);
+    }
+    let mut last = [0u8; 1];
+    file.seek(SeekFrom::Start(len - 1))?;
+    file.read_exact(&mut last)?;
+    if last[0] == b'\n' {
+        return Ok(len);
+    }
+
+    let mut end = len;
+    let mut keep = 0;
+    while end > 0 {
+        let start = end.saturating_sub(REPAIR_CHUNK_BYTES);
+        let mut chunk = vec![0u8; (end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        if let Some(newline) = chunk.iter().rposition(|byte| *byte == b'\n') {
+            keep = start + newline as u64 + 1;
+            break;
+        }
+        end = start;
+    }
+    warn!(
+        "dropping {} bytes of a partially written line at the end of {}",
+        len - keep,
+        path.display()
+    );
+    file.set_len(keep)?;
+    Ok(keep)
+}
+
+/// Entries for every complete line of the rollout at `rollout_path`, from the
+/// index where it is current and from the rollout itself beyond that.
+pub(crate) fn load_entries(rollout_path: &Path) -> io::Result<Vec<IndexEntry>> {
+    let len = std::fs::metadata(rollout_path)?.len();
+    let mut entries = indexed_entries(rollout_path, len);
+    let covered = entries.last().map_or(0, IndexEntry::end);
+    entries.extend(scan_lines(rollout_path, covered, len)?);
+    Ok(entries)
+}
+
+/// The index entries that still describe the first `len` bytes of the
+/// rollout; none when the index is missing or its last entry no longer
+/// matches the rollout (e.g. the file was rewritten).
+fn indexed_entries(rollout_path: &Path, len: u64) -> Vec<IndexEntry> {
+    let Ok(mut entries) = read_index(&rollout_index_path(rollout_path)) else {
+        return Vec::new();
+    };
+    let valid = entries
+        .iter()
+        .take_while(|entry| entry.end() <= len)
+        .count();
+    entries.truncate(valid);
+    if let Some(last) = entries.last()
+        && !line_matches(rollout_path, last)
+    {
+        entries.clear();
+    }
+    entries
+}
+
+fn line_matches(path: &Path, entry: &IndexEntry) -> bool {
+    let read_line = || -> io::Result<Vec<u8>> {
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(entry.offset))?;
+        let mut line = vec![0u8; entry.len as usize + 1];
+        file.read_exact(&mut line)?;
+        Ok(line)
+    };
+    match read_line() {
+        Ok(mut line) => line.pop() == Some(b'\n') && checksum(&line) == entry.checksum,
+        Err(_) => false,
+    }
+}
+
+fn read_index(path: &Path) -> io::Result<Vec<IndexEntry>> {
+    let bytes = std::fs::read(path)?;
+    let Some(body) = bytes.strip_prefix(MAGIC.as_slice()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a rollout index",
+        ));
+    };
+    Ok(body
+        .chunks_exact(ENTRY_BYTES)
+        .filter_map(|chunk| chunk.try_into().ok())
+        .map(IndexEntry::decode)
+        .collect())
+}
+
+/// Index entries for the complete lines in `start..end` of the rollout.
+fn scan_lines(path: &Path, start: u64, end: u64) -> io::Result<Vec<IndexEntry>> {
+    if start >= end {
+        return Ok(Vec::new());
+    }
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::new();
+    file.take(end - start).read_to_end(&mut bytes)?;
+
+    let mut entries = Vec::new();
+    let mut offset = start;
+    let mut rest = bytes.as_slice();
+    while let Some(newline) = rest.iter().position(|byte| *byte == b'\n') {
+        let line = &rest[..newline];
+        entries.push(IndexEntry::new(offset, line, kind_of_line(line)));
+        offset += newline as u64 + 1;
+        rest = &rest[newline + 1..];
+    }
+    Ok(entries)
+}
+
+fn kind_of_line(line: &[u8]) -> EntryKind {
+    std::str::from_utf8(line)
+        .ok()
+        .and_then(|line| decode_line(line.trim()).ok())
+        .and_then(|line| serde_json::from_str::<RolloutLine>(&line).ok())
+        .map_or(EntryKind::Other, |line| EntryKind::of(&line.item))
+}
+
+/// The last `n` items of the rollout at `path`. Tool outputs recorded as
+/// references to earlier outputs are returned as references.
+pub fn read_last_items(path: &Path, n: usize) -> io::Result<Vec<RolloutItem>> {
+    let entries = load_entries(path)?;
+    let start = entries.len().saturating_sub(n);
+    read_items(path, &entries[start..])
+}
+
+/// The items needed to resume the rollout at `path` from its last
+/// checkpoint: the session metadata, the latest state snapshots recorded
+/// before the checkpoint, and everything from the checkpoint on. `None` when
+/// the rollout has no checkpoint.
+pub(crate) fn read_from_checkpoint(path: &Path) -> io::Result<Option<Vec<RolloutItem>>> {
+    let entries = load_entries(path)?;
+    let Some(checkpoint) = entries
+        .iter()
+        .rposition(|entry| entry.kind == EntryKind::Checkpoint)
+    else {
+        return Ok(None);
+    };
+
+    let (before, after) = entries.split_at(checkpoint);
+    let mut selected: Vec<IndexEntry> = before
+        .iter()
+        .filter(|entry| entry.kind == EntryKind::SessionMeta)
+        .copied()
+        .collect();
+    let mut snapshots: Vec<IndexEntry> = Vec::new();
+    for entry in before.iter().rev().filter(|entry| entry.kind.is_snapshot()) {
+        if !snapshots.iter().any(|seen| seen.kind == entry.kind) {
+            snapshots.push(*entry);
+        }
+    }
+    selected.extend(snapshots);
+    selected.sort_by_key(|entry| entry.offset);
+    selected.extend_from_slice(after);
+    read_items(path, &selected).map(Some)
+}
+
+/// Reads the lines behind `entries`, skipping any whose checksum no longer
+/// matches or that don't parse.
+fn read_items(path: &Path, entries: &[IndexEntry]) -> io::Result<Vec<RolloutItem>> {
+    let mut file = File::open(path)?;
+    let mut items = Vec::with_capacity(entries.len());
+    let mut line = Vec::new();
+    let mut position = None;
+    for entry in entries {
+        if position != Some(entry.offset) {
+            file.seek(SeekFrom::Start(entry.offset))?;
+        }
+        line.resize(entry.len as usize, 0);
+        file.read_exact(&mut line)?;
+        // Step over the newline so contiguous entries need no seek.
+        let mut newline = [0u8; 1];
+        file.read_exact(&mut newline)?;
+        position = Some(entry.end());
+
+        if checksum(&line) != entry.checksum {
+            warn!(
+                "skipping corrupted line at byte {} of {}",
+                entry.offset,
+                path.display()
+            );
+            continue;
+        }
+        let Ok(text) = std::str::from_utf8(&line) else {
+            continue;
+        };
+        let decoded = match decode_line(text.trim()) {
+            Ok(decoded) => decoded,
+            Err(err) => {
+                warn!("failed to decrypt rollout line: {err}");
+                continue;
+            }
+        };
+        match serde_json::from_str::<RolloutLine>(&decoded) {
+            Ok(rollout_line) => items.push(rollout_line.item),
+            Err(err) => warn!("failed to parse rollout line: {err}"),
+        }
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::ConversationId;
+    use codex_protocol::models::ContentItem;
+    use codex_protocol::models::ResponseItem;
+    use codex_protocol::protocol::CompactedItem;
+    use codex_protocol::protocol::SessionMeta;
+    use codex_protocol::protocol::SessionMetaLine;
+    use codex_protocol::protocol::SessionSummaryItem;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn message(text: &str) -> RolloutItem {
+        RolloutItem::ResponseItem(ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        })
+    }
+
+    fn summary(title: &str) -> RolloutItem {
+        RolloutItem::SessionSummary(SessionSummaryItem {
+            title: title.to_string(),
+            summary: String::new(),
+        })
+    }
+
+    fn checkpoint(text: &str) -> RolloutItem {
+        RolloutItem::Compacted(CompactedItem {
+            message: String::new(),
+            replacement_history: Some(vec![ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: text.to_string(),
+                }],
+            }]),
+        })
+    }
+
+    /// Writes `items` the way the recorder does, index included.
+    fn write_rollout(path: &Path, items: &[RolloutItem]) {
+        let mut file = File::create(path).expect("create rollout");
+        let mut index = IndexWriter::create(path).expect("create index");
+        let mut offset = 0;
+        for item in items {
+            let line = serde_json::to_string(&RolloutLine {
+                timestamp: "2025-01-01T00:00:00.000Z".to_string(),
+                item: item.clone(),
+            })
+            .expect("serialize");
+            let entry = IndexEntry::new(offset, line.as_bytes(), EntryKind::of(item));
+            writeln!(file, "{line}").expect("write line");
+            index.append(&entry).expect("append entry");
+            offset = entry.end();
+        }
+    }
+
+    fn as_json(items: &[RolloutItem]) -> Vec<serde_json::Value> {
+        items
+            .iter()
+            .map(|item| serde_json::to_value(item).expect("serialize"))
+            .collect()
+    }
+
+    fn meta() -> RolloutItem {
+        RolloutItem::SessionMeta(SessionMetaLine {
+            meta: SessionMeta {
+                id: ConversationId::from_string("67e55044-10b1-426f-9247-bb680e5fe0c8")
+                    .expect("conversation id"),
+                ..SessionMeta::default()
+            },
+            git: None,
+        })
+    }
+
+    #[test]
+    fn reads_the_last_items_with_or_without_a_current_index() {
+        let dir = TempDir::new().expect("tempdir");
+        let path = dir.path().join("rollout-a.jsonl");
+        let items = vec![meta(), message("one"), message("two"), message("three")];
+        write_rollout(&path, &items);
+        assert_eq!(
+            as_json(&read_last_items(&path, 2).expect("read")),
+            as_json(&items[2..])
+        );
+
+        // A line the index doesn't know about yet is found by scanning.
+        let line = serde_json::to_string(&RolloutLine {
+            timestamp: "2025-01-01T00:00:01.000Z".to_string(),
+            item: message("four"),
+        })
+        .expect("serialize");
+        let mut file = OpenOptions::new().append(true).open(&path).expect("open");
+        writeln!(file, "{line}").expect("append");
+        assert_eq!(
+            as_json(&read_last_items(&path, 1).expect("read")),
+            as_json(&[message("four")])
+        );
+
+        std::fs::remove_file(rollout_index_path(&path)).expect("remove index");
+        assert_eq!(read_last_items(&path, 10).expect("read").len(), 5);
+    }
+
+    #[test]
+    fn resuming_from_a_checkpoint_keeps_metadata_and_latest_snapshots() {
+        let dir = TempDir::new().expect("tempdir");
+        let path = dir.path().join("rollout-b.jsonl");
+        let items = vec![
+            meta(),
+            message("old request"),
+            summary("first title"),
+            message("another old request"),
+            summary("second title"),
+            checkpoint("summary of the old requests"),
+            message("new request"),
+        ];
+        write_rollout(&path, &items);
+
+        let resumed = read_from_checkpoint(&path)
+            .expect("read")
+            .expect("checkpoint");
+        assert_eq!(
+            as_json(&resumed),
+            as_json(&[
+                meta(),
+                summary("second title"),
+                checkpoint("summary of the old requests"),
+                message("new request"),
+            ])
+        );
+
+        let plain = dir.path().join("rollout-c.jsonl");
+        write_rollout(&plain, &[meta(), message("hi")]);
+        assert!(read_from_checkpoint(&plain).expect("read").is_none());
+    }
+
+    #[test]
+    fn a_torn_last_line_is_dropped_before_appending() {
+        let dir = TempDir::new().expect("tempdir");
+        let path = dir.path().join("rollout-d.jsonl");
+        write_rollout(&path, &[meta(), message("kept")]);
+        let complete = std::fs::metadata(&path).expect("metadata").len();
+        let mut file = OpenOptions::new().append(true).open(&path).expect("open");
+        file.write_all(br#"{"timestamp":"2025-01-01T00:00:02.000Z","type":"resp"#)
+            .expect("append torn line");
+        drop(file);
+
+        let (_writer, offset) = IndexWriter::open_for_append(&path).expect("open for append");
+        assert_eq!(offset, complete);
+        assert_eq!(std::fs::metadata(&path).expect("metadata").len(), complete);
+        assert_eq!(load_entries(&path).expect("entries").len(), 2);
+    }
+
+    #[test]
+    fn altered_lines_are_skipped() {
+        let dir = TempDir::new().expect("tempdir");
+        let path = dir.path().join("rollout-e.jsonl");
+        write_rollout(&path, &[meta(), message("abc")]);
+        let text = std::fs::read_to_string(&path).expect("read");
+        std::fs::write(&path, text.replace("\"abc\"", "\"xyz\"")).expect("write");
+
+        let items = read_last_items(&path, 10).expect("read");
+        assert_eq!(as_json(&items), as_json(&[meta()]));
+    }
+}
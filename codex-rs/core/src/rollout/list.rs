@@ -15,10 +15,13 @@ use uuid::Uuid;
 
 use super::SESSIONS_SUBDIR;
 use crate::protocol::EventMsg;
+use crate::protocol::TokenUsage;
+use crate::storage_encryption::decode_line;
 use codex_file_search as file_search;
 use codex_protocol::protocol::RolloutItem;
 use codex_protocol::protocol::RolloutLine;
 use codex_protocol::protocol::SessionSource;
+use codex_protocol::protocol::SessionSummaryItem;
 
 /// Returned page of conversation summaries.
 #[derive(Debug, Default, PartialEq)]
@@ -60,6 +63,9 @@ struct HeadTailSummary {
 /// Hard cap to bound worst‑case work per request.
 const MAX_SCAN_FILES: usize = 10000;
 const HEAD_RECORD_LIMIT: usize = 10;
+/// How much of the end of a rollout file is searched for token usage and
+/// the session summary.
+const TAIL_BYTES: u64 = 256 * 1024;
 
 /// Pagination cursor identifying a file by timestamp and UUID.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -392,7 +398,10 @@ async fn read_head_summary(path: &Path, head_limit: usize) -> io::Result<HeadTai
             continue;
         }
 
-        let parsed: Result<RolloutLine, _> = serde_json::from_str(trimmed);
+        let Ok(decoded) = decode_line(trimmed) else {
+            continue;
+        };
+        let parsed: Result<RolloutLine, _> = serde_json::from_str(&decoded);
         let Ok(rollout_line) = parsed else { continue };
 
         match rollout_line.item {
@@ -420,7 +429,9 @@ async fn read_head_summary(path: &Path, head_limit: usize) -> io::Result<HeadTai
             RolloutItem::TurnContext(_) => {
                 // Not included in `head`; skip.
             }
-            RolloutItem::Compacted(_) => {
+            RolloutItem::Compacted(_)
+            | RolloutItem::SessionSummary(_)
+            | RolloutItem::ToolOutputRef(_) => {
                 // Not included in `head`; skip.
             }
             RolloutItem::EventMsg(ev) => {
@@ -445,6 +456,58 @@ pub async fn read_head_for_summary(path: &Path) -> io::Result<Vec<serde_json::Va
     Ok(summary.head)
 }
 
+/// Return the session's cumulative token usage from the last token count
+/// recorded in the rollout file at `path`. Only the tail of the file is read,
+/// so sessions whose last token count is older than that report `None`.
+pub async fn read_token_usage(path: &Path) -> io::Result<Option<TokenUsage>> {
+    find_last_in_tail(path, |item| match item {
+        RolloutItem::EventMsg(EventMsg::TokenCount(event)) => {
+            event.info.map(|info| info.total_token_usage)
+        }
+        _ => None,
+    })
+    .await
+}
+
+/// Return the title and summary last recorded in the rollout file at `path`.
+/// Like [`read_token_usage`], only the tail of the file is searched.
+pub async fn read_session_summary(path: &Path) -> io::Result<Option<SessionSummaryItem>> {
+    find_last_in_tail(path, |item| match item {
+        RolloutItem::SessionSummary(summary) => Some(summary),
+        _ => None,
+    })
+    .await
+}
+
+/// The last item near the end of the rollout file at `path` that `extract`
+/// maps to a value.
+async fn find_last_in_tail<T>(
+    path: &Path,
+    extract: impl Fn(RolloutItem) -> Option<T>,
+) -> io::Result<Option<T>> {
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncSeekExt;
+
+    let mut file = tokio::fs::File::open(path).await?;
+    let len = file.metadata().await?.len();
+    let start = len.saturating_sub(TAIL_BYTES);
+    file.seek(io::SeekFrom::Start(start)).await?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).await?;
+    let tail = String::from_utf8_lossy(&tail);
+
+    let mut lines = tail.lines();
+    if start > 0 {
+        // The first line is most likely cut off by the seek.
+        lines.next();
+    }
+    Ok(lines.rev().find_map(|line| {
+        let decoded = decode_line(line.trim()).ok()?;
+        let rollout_line: RolloutLine = serde_json::from_str(&decoded).ok()?;
+        extract(rollout_line.item)
+    }))
+}
+
 async fn file_modified_rfc3339(path: &Path) -> io::Result<Option<String>> {
     let meta = tokio::fs::metadata(path).await?;
     let modified = meta.modified().ok();
@@ -7,10 +7,13 @@ pub const ARCHIVED_SESSIONS_SUBDIR: &str = "archived_sessions";
 pub const INTERACTIVE_SESSION_SOURCES: &[SessionSource] =
     &[SessionSource::Cli, SessionSource::VSCode];
 
+mod dedupe;
 pub(crate) mod error;
+pub(crate) mod index;
 pub mod list;
 pub(crate) mod policy;
 pub mod recorder;
+pub mod retention;
 
 pub use codex_protocol::protocol::SessionMeta;
 pub(crate) use error::map_session_init_error;
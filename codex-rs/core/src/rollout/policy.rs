@@ -9,12 +9,26 @@ pub(crate) fn is_persisted_response_item(item: &RolloutItem) -> bool {
         RolloutItem::ResponseItem(item) => should_persist_response_item(item),
         RolloutItem::EventMsg(ev) => should_persist_event_msg(ev),
         // Persist Codex executive markers so we can analyze flows (e.g., compaction, API turns).
-        RolloutItem::Compacted(_) | RolloutItem::TurnContext(_) | RolloutItem::SessionMeta(_) => {
-            true
-        }
+        RolloutItem::Compacted(_)
+        | RolloutItem::TurnContext(_)
+        | RolloutItem::SessionMeta(_)
+        | RolloutItem::SessionSummary(_)
+        | RolloutItem::ToolOutputRef(_) => true,
     }
 }
 
+/// Whether `item` carries model reasoning, which providers with
+/// `persist_reasoning = false` keep out of rollout files.
+pub(crate) fn is_reasoning_item(item: &RolloutItem) -> bool {
+    matches!(
+        item,
+        RolloutItem::ResponseItem(ResponseItem::Reasoning { .. })
+            | RolloutItem::EventMsg(
+                EventMsg::AgentReasoning(_) | EventMsg::AgentReasoningRawContent(_)
+            )
+    )
+}
+
 /// Whether a `ResponseItem` should be persisted in rollout files.
 #[inline]
 pub(crate) fn should_persist_response_item(item: &ResponseItem) -> bool {
@@ -46,8 +60,15 @@ pub(crate) fn should_persist_event_msg(ev: &EventMsg) -> bool {
         | EventMsg::EnteredReviewMode(_)
         | EventMsg::ExitedReviewMode(_)
         | EventMsg::UndoCompleted(_)
-        | EventMsg::TurnAborted(_) => true,
+        | EventMsg::TurnAborted(_)
+        | EventMsg::PinnedContextUpdated(_)
+        | EventMsg::SessionDiff(_)
+        | EventMsg::PlanUpdate(_)
+        | EventMsg::BudgetExceeded(_)
+        | EventMsg::TurnTimedOut(_)
+        | EventMsg::ToolCallLoopDetected(_) => true,
         EventMsg::Error(_)
+        | EventMsg::SubAgent(_)
         | EventMsg::Warning(_)
         | EventMsg::TaskStarted(_)
         | EventMsg::TaskComplete(_)
@@ -70,6 +91,7 @@ pub(crate) fn should_persist_event_msg(ev: &EventMsg) -> bool {
         | EventMsg::ApplyPatchApprovalRequest(_)
         | EventMsg::BackgroundEvent(_)
         | EventMsg::StreamError(_)
+        | EventMsg::RetryScheduled(_)
         | EventMsg::PatchApplyBegin(_)
         | EventMsg::PatchApplyEnd(_)
         | EventMsg::TurnDiff(_)
@@ -80,7 +102,6 @@ pub(crate) fn should_persist_event_msg(ev: &EventMsg) -> bool {
         | EventMsg::McpStartupComplete(_)
         | EventMsg::ListCustomPromptsResponse(_)
         | EventMsg::ListSkillsResponse(_)
-        | EventMsg::PlanUpdate(_)
         | EventMsg::ShutdownComplete
         | EventMsg::ViewImageToolCall(_)
         | EventMsg::DeprecationNotice(_)
@@ -89,6 +110,10 @@ pub(crate) fn should_persist_event_msg(ev: &EventMsg) -> bool {
         | EventMsg::AgentMessageContentDelta(_)
         | EventMsg::ReasoningContentDelta(_)
         | EventMsg::ReasoningRawContentDelta(_)
+        | EventMsg::SecretsRedacted(_)
+        | EventMsg::ContextPressure(_)
+        | EventMsg::ContextItemsDropped(_)
+        | EventMsg::ReadOnlyModeChanged(_)
         | EventMsg::SkillsUpdateAvailable => false,
     }
 }
@@ -5,6 +5,7 @@ use std::fs::{self};
 use std::io::Error as IoError;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use codex_protocol::ConversationId;
 use serde_json::Value;
@@ -19,6 +20,12 @@ use tracing::info;
 use tracing::warn;
 
 use super::SESSIONS_SUBDIR;
+use super::dedupe::ToolOutputDeduper;
+use super::dedupe::ToolOutputExpander;
+use super::index::EntryKind;
+use super::index::IndexEntry;
+use super::index::IndexWriter;
+use super::index::read_from_checkpoint;
 use super::list::ConversationsPage;
 use super::list::Cursor;
 use super::list::get_conversations;
@@ -26,6 +33,8 @@ use super::policy::is_persisted_response_item;
 use crate::config::Config;
 use crate::default_client::originator;
 use crate::git_info::collect_git_info;
+use crate::storage_encryption::StorageCipher;
+use crate::storage_encryption::decode_line;
 use codex_protocol::protocol::InitialHistory;
 use codex_protocol::protocol::ResumedHistory;
 use codex_protocol::protocol::RolloutItem;
@@ -34,6 +43,10 @@ use codex_protocol::protocol::SessionMeta;
 use codex_protocol::protocol::SessionMetaLine;
 use codex_protocol::protocol::SessionSource;
 
+/// Rollouts at least this large are resumed from their last compaction
+/// checkpoint instead of being parsed in full.
+const CHECKPOINT_RESUME_MIN_BYTES: u64 = 16 * 1024 * 1024;
+
 /// Records all [`ResponseItem`]s for a session and flushes them to disk after
 /// every update.
 ///
@@ -43,6 +56,9 @@ use codex_protocol::protocol::SessionSource;
 /// $ jq -C . ~/.codex/sessions/rollout-2025-05-07T17-24-21-5973b6c0-94b8-487b-a530-2aeb6098ae0e.jsonl
 /// $ fx ~/.codex/sessions/rollout-2025-05-07T17-24-21-5973b6c0-94b8-487b-a530-2aeb6098ae0e.jsonl
 /// ```
+///
+/// Each rollout has a sidecar index (see [`super::index`]) that the recorder
+/// keeps up to date for partial reads.
 #[derive(Clone)]
 pub struct RolloutRecorder {
     tx: Sender<RolloutCmd>,
@@ -115,7 +131,7 @@ impl RolloutRecorder {
     /// cannot be created or the rollout file cannot be opened we return the
     /// error so the caller can decide whether to disable persistence.
     pub async fn new(config: &Config, params: RolloutRecorderParams) -> std::io::Result<Self> {
-        let (file, rollout_path, meta) = match params {
+        let (file, rollout_path, meta, index, offset) = match params {
             RolloutRecorderParams::Create {
                 conversation_id,
                 instructions,
@@ -136,6 +152,10 @@ impl RolloutRecorder {
                     .format(timestamp_format)
                     .map_err(|e| IoError::other(format!("failed to format timestamp: {e}")))?;
 
+                let offset = file.metadata()?.len();
+                let index = IndexWriter::create(&path)
+                    .inspect_err(|e| warn!("failed to create rollout index: {e}"))
+                    .ok();
                 (
                     tokio::fs::File::from_std(file),
                     path,
@@ -149,20 +169,36 @@ impl RolloutRecorder {
                         source,
                         model_provider: Some(config.model_provider_id.clone()),
                     }),
+                    index,
+                    offset,
+                )
+            }
+            RolloutRecorderParams::Resume { path } => {
+                // Drop a line torn by a crash and catch the index up with the
+                // rollout before appending to either.
+                let (index, offset) = match IndexWriter::open_for_append(&path) {
+                    Ok((index, offset)) => (Some(index), offset),
+                    Err(e) => {
+                        warn!("failed to open rollout index: {e}");
+                        (None, tokio::fs::metadata(&path).await?.len())
+                    }
+                };
+                (
+                    tokio::fs::OpenOptions::new()
+                        .append(true)
+                        .open(&path)
+                        .await?,
+                    path,
+                    None,
+                    index,
+                    offset,
                 )
             }
-            RolloutRecorderParams::Resume { path } => (
-                tokio::fs::OpenOptions::new()
-                    .append(true)
-                    .open(&path)
-                    .await?,
-                path,
-                None,
-            ),
         };
 
         // Clone the cwd for the spawned task to collect git info asynchronously
         let cwd = config.cwd.clone();
+        let writer = JsonlWriter::new(config, file, index, offset)?;
 
         // A reasonably-sized bounded channel. If the buffer fills up the send
         // future will yield, which is fine – we only need to ensure we do not
@@ -172,7 +208,7 @@ impl RolloutRecorder {
         // Spawn a Tokio task that owns the file handle and performs async
         // writes. Using `tokio::fs::File` keeps everything on the async I/O
         // driver instead of blocking the runtime.
-        tokio::task::spawn(rollout_writer(file, rx, meta, cwd));
+        tokio::task::spawn(rollout_writer(writer, rx, meta, cwd));
 
         Ok(Self { tx, rollout_path })
     }
@@ -215,12 +251,19 @@ impl RolloutRecorder {
         }
 
         let mut items: Vec<RolloutItem> = Vec::new();
-        let mut conversation_id: Option<ConversationId> = None;
+        let mut expander = ToolOutputExpander::default();
         for line in text.lines() {
             if line.trim().is_empty() {
                 continue;
             }
-            let v: Value = match serde_json::from_str(line) {
+            let line = match decode_line(line) {
+                Ok(line) => line,
+                Err(e) => {
+                    warn!("failed to decrypt rollout line: {e}");
+                    continue;
+                }
+            };
+            let v: Value = match serde_json::from_str(&line) {
                 Ok(v) => v,
                 Err(e) => {
                     warn!("failed to parse line as JSON: {line:?}, error: {e}");
@@ -230,27 +273,28 @@ impl RolloutRecorder {
 
             // Parse the rollout line structure
             match serde_json::from_value::<RolloutLine>(v.clone()) {
-                Ok(rollout_line) => match rollout_line.item {
-                    RolloutItem::SessionMeta(session_meta_line) => {
-                        // Use the FIRST SessionMeta encountered in the file as the canonical
-                        // conversation id and main session information. Keep all items intact.
-                        if conversation_id.is_none() {
-                            conversation_id = Some(session_meta_line.meta.id);
-                        }
+                Ok(rollout_line) => match expander.expand(rollout_line.item) {
+                    Some(RolloutItem::SessionMeta(session_meta_line)) => {
                         items.push(RolloutItem::SessionMeta(session_meta_line));
                     }
-                    RolloutItem::ResponseItem(item) => {
+                    Some(RolloutItem::ResponseItem(item)) => {
                         items.push(RolloutItem::ResponseItem(item));
                     }
-                    RolloutItem::Compacted(item) => {
+                    Some(RolloutItem::Compacted(item)) => {
                         items.push(RolloutItem::Compacted(item));
                     }
-                    RolloutItem::TurnContext(item) => {
+                    Some(RolloutItem::TurnContext(item)) => {
                         items.push(RolloutItem::TurnContext(item));
                     }
-                    RolloutItem::EventMsg(_ev) => {
+                    Some(RolloutItem::EventMsg(_ev)) => {
                         items.push(RolloutItem::EventMsg(_ev));
                     }
+                    Some(RolloutItem::SessionSummary(item)) => {
+                        items.push(RolloutItem::SessionSummary(item));
+                    }
+                    // References are expanded into the output they stand for;
+                    // `None` is a reference to an output missing from the file.
+                    Some(RolloutItem::ToolOutputRef(_)) | None => {}
                 },
                 Err(e) => {
                     warn!("failed to parse rollout line: {v:?}, error: {e}");
@@ -258,24 +302,40 @@ impl RolloutRecorder {
             }
         }
 
-        info!(
-            "Resumed rollout with {} items, conversation ID: {:?}",
-            items.len(),
-            conversation_id
-        );
-        let conversation_id = conversation_id
-            .ok_or_else(|| IoError::other("failed to parse conversation ID from rollout file"))?;
+        resumed_history(path, items)
+    }
 
-        if items.is_empty() {
-            return Ok(InitialHistory::New);
+    /// Like [`Self::get_rollout_history`], but a large rollout that has been
+    /// compacted is loaded from its last compaction checkpoint on, which is
+    /// all a resumed session needs to rebuild its context. Forks and other
+    /// callers that need every turn should keep using the full load.
+    pub async fn get_resume_history(path: &Path) -> std::io::Result<InitialHistory> {
+        if tokio::fs::metadata(path).await?.len() >= CHECKPOINT_RESUME_MIN_BYTES {
+            let checkpoint_path = path.to_path_buf();
+            let loaded =
+                tokio::task::spawn_blocking(move || read_from_checkpoint(&checkpoint_path))
+                    .await
+                    .map_err(|e| IoError::other(format!("failed to read rollout: {e}")))
+                    .and_then(|loaded| loaded);
+            match loaded {
+                Ok(Some(items)) => {
+                    let mut expander = ToolOutputExpander::default();
+                    // A reference to an output written before the checkpoint
+                    // can only be resolved by reading the whole file.
+                    let expanded: Option<Vec<RolloutItem>> = items
+                        .into_iter()
+                        .map(|item| expander.expand(item))
+                        .collect();
+                    if let Some(items) = expanded {
+                        info!("Resuming rollout from its last checkpoint: {path:?}");
+                        return resumed_history(path, items);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => warn!("failed to resume {path:?} from its index: {e}"),
+            }
         }
-
-        info!("Resumed rollout successfully from {path:?}");
-        Ok(InitialHistory::Resumed(ResumedHistory {
-            conversation_id,
-            history: items,
-            rollout_path: path.to_path_buf(),
-        }))
+        Self::get_rollout_history(path).await
     }
 
     pub async fn shutdown(&self) -> std::io::Result<()> {
@@ -294,6 +354,57 @@ impl RolloutRecorder {
     }
 }
 
+/// Writes a finished rollout for a session recorded elsewhere, such as an
+/// imported one, and returns its path. `lines` should start with the
+/// session's `SessionMeta`; `started_at` places the file in the sessions
+/// tree. Lines get the same encryption, deduplication and index as a live
+/// session's.
+pub(crate) async fn write_rollout(
+    config: &Config,
+    conversation_id: ConversationId,
+    started_at: OffsetDateTime,
+    lines: Vec<RolloutLine>,
+) -> std::io::Result<PathBuf> {
+    let local_offset = time::UtcOffset::current_local_offset().unwrap_or(time::UtcOffset::UTC);
+    let LogFileInfo { file, path, .. } =
+        create_log_file_at(config, conversation_id, started_at.to_offset(local_offset))?;
+    let index = IndexWriter::create(&path)
+        .inspect_err(|e| warn!("failed to create rollout index: {e}"))
+        .ok();
+    let mut writer = JsonlWriter::new(config, tokio::fs::File::from_std(file), index, 0)?;
+    for line in lines {
+        writer.write_rollout_line(line).await?;
+    }
+    Ok(path)
+}
+
+/// Wraps the items loaded from the rollout at `path` for resuming. The first
+/// `SessionMeta` in the file holds the canonical conversation id.
+fn resumed_history(path: &Path, items: Vec<RolloutItem>) -> std::io::Result<InitialHistory> {
+    let conversation_id = items.iter().find_map(|item| match item {
+        RolloutItem::SessionMeta(session_meta_line) => Some(session_meta_line.meta.id),
+        _ => None,
+    });
+    info!(
+        "Resumed rollout with {} items, conversation ID: {:?}",
+        items.len(),
+        conversation_id
+    );
+    let conversation_id = conversation_id
+        .ok_or_else(|| IoError::other("failed to parse conversation ID from rollout file"))?;
+
+    if items.is_empty() {
+        return Ok(InitialHistory::New);
+    }
+
+    info!("Resumed rollout successfully from {path:?}");
+    Ok(InitialHistory::Resumed(ResumedHistory {
+        conversation_id,
+        history: items,
+        rollout_path: path.to_path_buf(),
+    }))
+}
+
 struct LogFileInfo {
     /// Opened file handle to the rollout file.
     file: File,
@@ -312,9 +423,17 @@ fn create_log_file(
     config: &Config,
     conversation_id: ConversationId,
 ) -> std::io::Result<LogFileInfo> {
-    // Resolve ~/.codex/sessions/YYYY/MM/DD and create it if missing.
     let timestamp = OffsetDateTime::now_local()
         .map_err(|e| IoError::other(format!("failed to get local time: {e}")))?;
+    create_log_file_at(config, conversation_id, timestamp)
+}
+
+fn create_log_file_at(
+    config: &Config,
+    conversation_id: ConversationId,
+    timestamp: OffsetDateTime,
+) -> std::io::Result<LogFileInfo> {
+    // Resolve ~/.codex/sessions/YYYY/MM/DD and create it if missing.
     let mut dir = config.codex_home.clone();
     dir.push(SESSIONS_SUBDIR);
     dir.push(timestamp.year().to_string());
@@ -347,13 +466,11 @@ fn create_log_file(
 }
 
 async fn rollout_writer(
-    file: tokio::fs::File,
+    mut writer: JsonlWriter,
     mut rx: mpsc::Receiver<RolloutCmd>,
     mut meta: Option<SessionMeta>,
     cwd: std::path::PathBuf,
 ) -> std::io::Result<()> {
-    let mut writer = JsonlWriter { file };
-
     // If we have a meta, collect git info asynchronously and write meta first
     if let Some(session_meta) = meta.take() {
         let git_info = collect_git_info(&cwd).await;
@@ -397,9 +514,36 @@ async fn rollout_writer(
 
 struct JsonlWriter {
     file: tokio::fs::File,
+    /// The rollout's index; dropped after a failed write, in which case
+    /// readers scan the part it doesn't cover.
+    index: Option<tokio::fs::File>,
+    /// Where the next line starts in `file`.
+    offset: u64,
+    /// Seals each line when `[storage_encryption]` is enabled.
+    cipher: Option<Arc<StorageCipher>>,
+    /// Set when `session_retention.dedupe_tool_outputs` is enabled.
+    deduper: Option<ToolOutputDeduper>,
 }
 
 impl JsonlWriter {
+    fn new(
+        config: &Config,
+        file: tokio::fs::File,
+        index: Option<IndexWriter>,
+        offset: u64,
+    ) -> std::io::Result<Self> {
+        Ok(Self {
+            file,
+            index: index.map(|index| tokio::fs::File::from_std(index.into_file())),
+            offset,
+            cipher: crate::storage_encryption::writer_cipher(config)?,
+            deduper: config
+                .session_retention
+                .dedupe_tool_outputs
+                .then(ToolOutputDeduper::default),
+        })
+    }
+
     async fn write_rollout_item(&mut self, rollout_item: RolloutItem) -> std::io::Result<()> {
         let timestamp_format: &[FormatItem] = format_description!(
             "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z"
@@ -407,18 +551,54 @@ impl JsonlWriter {
         let timestamp = OffsetDateTime::now_utc()
             .format(timestamp_format)
             .map_err(|e| IoError::other(format!("failed to format timestamp: {e}")))?;
-
-        let line = RolloutLine {
+        self.write_rollout_line(RolloutLine {
             timestamp,
             item: rollout_item,
+        })
+        .await
+    }
+
+    async fn write_rollout_line(&mut self, line: RolloutLine) -> std::io::Result<()> {
+        let RolloutLine { timestamp, item } = line;
+        let kind = EntryKind::of(&item);
+        let item = match &mut self.deduper {
+            Some(deduper) => deduper.dedupe(item),
+            None => item,
         };
-        self.write_line(&line).await
+        let line = RolloutLine { timestamp, item };
+        self.write_line(&line, kind).await
     }
-    async fn write_line(&mut self, item: &impl serde::Serialize) -> std::io::Result<()> {
+    async fn write_line(
+        &mut self,
+        item: &impl serde::Serialize,
+        kind: EntryKind,
+    ) -> std::io::Result<()> {
         let mut json = serde_json::to_string(item)?;
+        if let Some(cipher) = &self.cipher {
+            json = cipher.encrypt_line(&json)?;
+        }
+        let entry = IndexEntry::new(self.offset, json.as_bytes(), kind);
         json.push('\n');
         self.file.write_all(json.as_bytes()).await?;
         self.file.flush().await?;
+        self.offset = entry.end();
+        self.write_index_entry(&entry).await;
         Ok(())
     }
+
+    /// The index only speeds up reads, so failing to update it must not stop
+    /// the session from being recorded.
+    async fn write_index_entry(&mut self, entry: &IndexEntry) {
+        let Some(index) = &mut self.index else {
+            return;
+        };
+        let written = match index.write_all(&entry.encode()).await {
+            Ok(()) => index.flush().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = written {
+            warn!("failed to update rollout index, disabling it: {e}");
+            self.index = None;
+        }
+    }
 }
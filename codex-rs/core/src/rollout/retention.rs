@@ -0,0 +1,410 @@
+//! Enforces `[session_retention]`: removes the rollout files that fall
+//! outside the configured limits and, for `codex sessions gc`, rewrites the
+//! remaining ones with repeated tool outputs replaced by references.
+
+use std::borrow::Cow;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use tracing::info;
+use tracing::warn;
+
+use super::ARCHIVED_SESSIONS_SUBDIR;
+use super::SESSIONS_SUBDIR;
+use super::dedupe::ToolOutputDeduper;
+use super::index::rollout_index_path;
+use crate::config::Config;
+use crate::config::types::SessionRetentionConfig;
+use crate::storage_encryption::StorageCipher;
+use crate::storage_encryption::decode_line;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+/// Sessions updated more recently than this are never touched, since another
+/// Codex process may still be writing them.
+const RECENT_SESSION_GRACE: Duration = Duration::from_secs(60 * 60);
+
+/// What a garbage collection removed or would remove.
+#[derive(Debug, Default, PartialEq)]
+pub struct SessionGcReport {
+    /// Rollout files removed for being outside the limits.
+    pub removed: Vec<PathBuf>,
+    /// Bytes those files took up.
+    pub removed_bytes: u64,
+    /// Rollout files left in place.
+    pub kept: usize,
+    /// Kept files in which repeated tool outputs were replaced.
+    pub deduplicated_files: usize,
+    /// Bytes saved by those replacements.
+    pub deduplicated_bytes: u64,
+}
+
+#[derive(Debug)]
+struct RolloutFile {
+    path: PathBuf,
+    bytes: u64,
+    modified: SystemTime,
+}
+
+/// Removes the rollout files under `codex_home` that fall outside
+/// `retention`, newest sessions being kept first. With `dedupe`, the kept
+/// files are also rewritten with repeated tool outputs replaced by
+/// references; `cipher` seals rewritten lines of encrypted files, which are
+/// skipped without one. With `dry_run` nothing is changed on disk.
+pub async fn gc_sessions(
+    codex_home: &Path,
+    retention: &SessionRetentionConfig,
+    dedupe: bool,
+    cipher: Option<Arc<StorageCipher>>,
+    dry_run: bool,
+) -> io::Result<SessionGcReport> {
+    let codex_home = codex_home.to_path_buf();
+    let retention = retention.clone();
+    tokio::task::spawn_blocking(move || {
+        collect_garbage_blocking(
+            &codex_home,
+            &retention,
+            dedupe,
+            cipher.as_deref(),
+            dry_run,
+            SystemTime::now(),
+        )
+    })
+    .await
+    .map_err(io::Error::other)?
+}
+
+/// Applies the configured limits in the background; used at startup.
+/// Deduplicating existing files is left to `codex sessions gc`.
+pub fn spawn_session_vacuum(config: &Config) {
+    if !config.session_retention.has_limits() {
+        return;
+    }
+    let codex_home = config.codex_home.clone();
+    let retention = config.session_retention.clone();
+    tokio::spawn(async move {
+        match gc_sessions(&codex_home, &retention, false, None, false).await {
+            Ok(report) if !report.removed.is_empty() => info!(
+                "removed {} old sessions ({} bytes) per session_retention",
+                report.removed.len(),
+                report.removed_bytes
+            ),
+            Ok(_) => {}
+            Err(err) => warn!("failed to apply session_retention: {err}"),
+        }
+    });
+}
+
+fn collect_garbage_blocking(
+    codex_home: &Path,
+    retention: &SessionRetentionConfig,
+    dedupe: bool,
+    cipher: Option<&StorageCipher>,
+    dry_run: bool,
+    now: SystemTime,
+) -> io::Result<SessionGcReport> {
+    let mut files = Vec::new();
+    for subdir in [SESSIONS_SUBDIR, ARCHIVED_SESSIONS_SUBDIR] {
+        collect_rollout_files(&codex_home.join(subdir), &mut files)?;
+    }
+    files.sort_by(|a, b| b.modified.cmp(&a.modified));
+
+    let max_age = retention
+        .max_age_days
+        .map(|days| Duration::from_secs(days.saturating_mul(SECONDS_PER_DAY)));
+    let mut report = SessionGcReport::default();
+    let mut kept_bytes: u64 = 0;
+    for file in files {
+        let age = now.duration_since(file.modified).unwrap_or_default();
+        let over_limit = retention.max_sessions.is_some_and(|max| report.kept >= max)
+            || max_age.is_some_and(|max| age > max)
+            || retention
+                .max_disk_bytes
+                .is_some_and(|max| kept_bytes.saturating_add(file.bytes) > max);
+        if over_limit && age >= RECENT_SESSION_GRACE {
+            if !dry_run {
+                std::fs::remove_file(&file.path)?;
+                remove_index(&file.path);
+            }
+            report.removed_bytes = report.removed_bytes.saturating_add(file.bytes);
+            report.removed.push(file.path);
+            continue;
+        }
+
+        report.kept += 1;
+        kept_bytes = kept_bytes.saturating_add(file.bytes);
+        if dedupe && age >= RECENT_SESSION_GRACE {
+            match dedupe_rollout_file(&file.path, cipher, dry_run) {
+                Ok(0) => {}
+                Ok(saved) => {
+                    report.deduplicated_files += 1;
+                    report.deduplicated_bytes = report.deduplicated_bytes.saturating_add(saved);
+                }
+                Err(err) => warn!("failed to deduplicate {}: {err}", file.path.display()),
+            }
+        }
+    }
+
+    if !dry_run {
+        for subdir in [SESSIONS_SUBDIR, ARCHIVED_SESSIONS_SUBDIR] {
+            remove_empty_dirs(&codex_home.join(subdir));
+        }
+    }
+    Ok(report)
+}
+
+fn collect_rollout_files(dir: &Path, files: &mut Vec<RolloutFile>) -> io::Result<()> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let path = entry.path();
+        if metadata.is_dir() {
+            collect_rollout_files(&path, files)?;
+        } else if is_rollout_file(&path) {
+            files.push(RolloutFile {
+                path,
+                bytes: metadata.len(),
+                modified: metadata.modified()?,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn is_rollout_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("rollout-") && name.ends_with(".jsonl"))
+}
+
+/// Removes the directories left empty under `dir`, but not `dir` itself.
+fn remove_empty_dirs(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            remove_empty_dirs(&path);
+            // Fails, as intended, when the directory still has entries.
+            let _ = std::fs::remove_dir(&path);
+        }
+    }
+}
+
+/// Rewrites the rollout file at `path` with repeated tool outputs replaced by
+/// references, and returns the bytes saved. Encrypted files are left alone
+/// unless `cipher` can seal the rewritten lines.
+fn dedupe_rollout_file(
+    path: &Path,
+    cipher: Option<&StorageCipher>,
+    dry_run: bool,
+) -> io::Result<u64> {
+    let text = std::fs::read_to_string(path)?;
+    let mut deduper = ToolOutputDeduper::default();
+    let mut rewritten = String::with_capacity(text.len());
+    let mut changed = false;
+    for line in text.lines() {
+        let new_line = dedupe_line(line, &mut deduper, cipher)?;
+        changed |= new_line.is_some();
+        rewritten.push_str(new_line.as_deref().unwrap_or(line));
+        rewritten.push('\n');
+    }
+    let saved = (text.len() as u64).saturating_sub(rewritten.len() as u64);
+    if !changed || saved == 0 {
+        return Ok(0);
+    }
+    if !dry_run {
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        let mut tmp = tempfile::NamedTempFile::new_in(dir)?;
+        tmp.write_all(rewritten.as_bytes())?;
+        tmp.persist(path).map_err(|err| err.error)?;
+        // Every offset changed; the recorder rebuilds the index on resume.
+        remove_index(path);
+    }
+    Ok(saved)
+}
+
+fn remove_index(rollout_path: &Path) {
+    let index = rollout_index_path(rollout_path);
+    if let Err(err) = std::fs::remove_file(&index)
+        && err.kind() != io::ErrorKind::NotFound
+    {
+        warn!("failed to remove {}: {err}", index.display());
+    }
+}
+
+/// The replacement for `line`, or `None` to keep it as it is.
+fn dedupe_line(
+    line: &str,
+    deduper: &mut ToolOutputDeduper,
+    cipher: Option<&StorageCipher>,
+) -> io::Result<Option<String>> {
+    let decoded = decode_line(line.trim())?;
+    let encrypted = matches!(decoded, Cow::Owned(_));
+    if encrypted && cipher.is_none() {
+        return Err(io::Error::other(
+            "the file is encrypted and storage_encryption is disabled",
+        ));
+    }
+    let Ok(rollout_line) = serde_json::from_str::<RolloutLine>(&decoded) else {
+        return Ok(None);
+    };
+    if !matches!(
+        rollout_line.item,
+        RolloutItem::ResponseItem(_) | RolloutItem::ToolOutputRef(_)
+    ) {
+        return Ok(None);
+    }
+    let before = serde_json::to_value(&rollout_line.item)?;
+    let item = deduper.dedupe(rollout_line.item);
+    if serde_json::to_value(&item)? == before {
+        return Ok(None);
+    }
+    let json = serde_json::to_string(&RolloutLine {
+        timestamp: rollout_line.timestamp,
+        item,
+    })?;
+    match cipher.filter(|_| encrypted) {
+        Some(cipher) => cipher.encrypt_line(&json).map(Some),
+        None => Ok(Some(json)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use codex_protocol::models::ResponseItem;
+    use pretty_assertions::assert_eq;
+    use serde_json::Value;
+    use tempfile::TempDir;
+
+    const DAY: Duration = Duration::from_secs(SECONDS_PER_DAY);
+
+    fn output_line(call_id: &str, content: &str) -> String {
+        let line = RolloutLine {
+            timestamp: "2025-01-01T00:00:00.000Z".to_string(),
+            item: RolloutItem::ResponseItem(ResponseItem::FunctionCallOutput {
+                call_id: call_id.to_string(),
+                output: FunctionCallOutputPayload {
+                    content: content.to_string(),
+                    ..Default::default()
+                },
+            }),
+        };
+        serde_json::to_string(&line).expect("serialize")
+    }
+
+    /// Writes a rollout file last modified `age` before `now`.
+    fn write_rollout(home: &Path, name: &str, contents: &str, now: SystemTime, age: Duration) {
+        let dir = home.join(SESSIONS_SUBDIR).join("2025/01/01");
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let path = dir.join(format!("rollout-{name}.jsonl"));
+        std::fs::write(&path, contents).expect("write rollout");
+        let file = std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .expect("open rollout");
+        file.set_modified(now - age).expect("set mtime");
+    }
+
+    fn names(paths: &[PathBuf]) -> Vec<String> {
+        paths
+            .iter()
+            .filter_map(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn removes_the_oldest_sessions_beyond_each_limit() {
+        let home = TempDir::new().expect("tempdir");
+        let now = SystemTime::now();
+        write_rollout(home.path(), "new", "new\n", now, Duration::ZERO);
+        write_rollout(home.path(), "week", "week\n", now, 7 * DAY);
+        write_rollout(home.path(), "month", "month\n", now, 30 * DAY);
+        write_rollout(home.path(), "year", "year\n", now, 365 * DAY);
+
+        let by_count = SessionRetentionConfig {
+            max_sessions: Some(3),
+            ..Default::default()
+        };
+        let report =
+            collect_garbage_blocking(home.path(), &by_count, false, None, true, now).expect("gc");
+        assert_eq!(names(&report.removed), vec!["rollout-year.jsonl"]);
+        assert_eq!(report.kept, 3);
+
+        let by_age = SessionRetentionConfig {
+            max_age_days: Some(10),
+            ..Default::default()
+        };
+        let report =
+            collect_garbage_blocking(home.path(), &by_age, false, None, true, now).expect("gc");
+        assert_eq!(
+            names(&report.removed),
+            vec!["rollout-month.jsonl", "rollout-year.jsonl"]
+        );
+
+        // "new\n" and "week\n" fit in 10 bytes.
+        let by_size = SessionRetentionConfig {
+            max_disk_bytes: Some(10),
+            ..Default::default()
+        };
+        let report =
+            collect_garbage_blocking(home.path(), &by_size, false, None, false, now).expect("gc");
+        assert_eq!(
+            names(&report.removed),
+            vec!["rollout-month.jsonl", "rollout-year.jsonl"]
+        );
+        assert_eq!(report.removed_bytes, "month\n".len() as u64 + 5);
+        assert!(report.removed.iter().all(|path| !path.exists()));
+    }
+
+    #[test]
+    fn gc_replaces_repeated_outputs_in_old_sessions() {
+        let home = TempDir::new().expect("tempdir");
+        let now = SystemTime::now();
+        let large = "z".repeat(4096);
+        let contents = format!(
+            "{}\n{}\n{}\n",
+            output_line("call-1", &large),
+            output_line("call-2", "small"),
+            output_line("call-3", &large),
+        );
+        write_rollout(home.path(), "old", &contents, now, 2 * DAY);
+        write_rollout(home.path(), "active", &contents, now, Duration::ZERO);
+
+        let retention = SessionRetentionConfig::default();
+        let report =
+            collect_garbage_blocking(home.path(), &retention, true, None, false, now).expect("gc");
+        assert_eq!(report.removed, Vec::<PathBuf>::new());
+        assert_eq!(report.deduplicated_files, 1);
+        assert!(report.deduplicated_bytes > 4000);
+
+        let dir = home.path().join(SESSIONS_SUBDIR).join("2025/01/01");
+        let old = std::fs::read_to_string(dir.join("rollout-old.jsonl")).expect("read");
+        let lines: Vec<Value> = old
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("json"))
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2]["type"], "tool_output_ref");
+        assert_eq!(lines[2]["payload"]["same_as"], "call-1");
+        assert_eq!(
+            std::fs::read_to_string(dir.join("rollout-active.jsonl")).expect("read"),
+            contents
+        );
+    }
+}
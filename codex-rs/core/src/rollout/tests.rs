@@ -17,6 +17,8 @@ use crate::rollout::list::ConversationItem;
 use crate::rollout::list::ConversationsPage;
 use crate::rollout::list::Cursor;
 use crate::rollout::list::get_conversations;
+use crate::rollout::list::read_session_summary;
+use crate::rollout::list::read_token_usage;
 use anyhow::Result;
 use codex_protocol::ConversationId;
 use codex_protocol::models::ContentItem;
@@ -27,6 +29,10 @@ use codex_protocol::protocol::RolloutLine;
 use codex_protocol::protocol::SessionMeta;
 use codex_protocol::protocol::SessionMetaLine;
 use codex_protocol::protocol::SessionSource;
+use codex_protocol::protocol::SessionSummaryItem;
+use codex_protocol::protocol::TokenCountEvent;
+use codex_protocol::protocol::TokenUsage;
+use codex_protocol::protocol::TokenUsageInfo;
 use codex_protocol::protocol::UserMessageEvent;
 
 const NO_SOURCE_FILTER: &[SessionSource] = &[];
@@ -648,6 +654,81 @@ async fn test_updated_at_uses_file_mtime() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_read_token_usage_uses_last_token_count() -> Result<()> {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    write_session_file(home, "2025-06-02T09-00-00", Uuid::from_u128(7), 0, None)?;
+    let path = fs::read_dir(home.join("sessions/2025/06/02"))?
+        .next()
+        .expect("rollout file")?
+        .path();
+    assert!(read_token_usage(&path).await?.is_none());
+
+    let mut file = fs::OpenOptions::new().append(true).open(&path)?;
+    for total_tokens in [100, 250] {
+        let usage = TokenUsage {
+            total_tokens,
+            ..Default::default()
+        };
+        let line = RolloutLine {
+            timestamp: "2025-06-02T09-00-01".to_string(),
+            item: RolloutItem::EventMsg(EventMsg::TokenCount(TokenCountEvent {
+                info: Some(TokenUsageInfo {
+                    total_token_usage: usage.clone(),
+                    last_token_usage: usage,
+                    model_context_window: None,
+                }),
+                rate_limits: None,
+                pinned_context_tokens: None,
+                spend: None,
+            })),
+        };
+        writeln!(file, "{}", serde_json::to_string(&line)?)?;
+    }
+    drop(file);
+
+    let usage = read_token_usage(&path).await?.expect("token usage");
+    assert_eq!(usage.total_tokens, 250);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_session_summary_uses_last_summary() -> Result<()> {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    write_session_file(home, "2025-06-03T09-00-00", Uuid::from_u128(8), 1, None)?;
+    let path = fs::read_dir(home.join("sessions/2025/06/03"))?
+        .next()
+        .expect("rollout file")?
+        .path();
+    assert_eq!(read_session_summary(&path).await?, None);
+
+    let mut file = fs::OpenOptions::new().append(true).open(&path)?;
+    for title in ["Fix the build", "Fix the build and the docs"] {
+        let line = RolloutLine {
+            timestamp: "2025-06-03T09-00-01".to_string(),
+            item: RolloutItem::SessionSummary(SessionSummaryItem {
+                title: title.to_string(),
+                summary: format!("{title} → Done."),
+            }),
+        };
+        writeln!(file, "{}", serde_json::to_string(&line)?)?;
+    }
+    drop(file);
+
+    assert_eq!(
+        read_session_summary(&path).await?,
+        Some(SessionSummaryItem {
+            title: "Fix the build and the docs".to_string(),
+            summary: "Fix the build and the docs → Done.".to_string(),
+        })
+    );
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_stable_ordering_same_second_pagination() {
     let temp = TempDir::new().unwrap();
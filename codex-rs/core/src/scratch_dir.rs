@@ -0,0 +1,127 @@
+//! A per-session directory for throwaway files, enabled with the
+//! `scratch_dir` feature.
+//!
+//! The directory is created under the system temp dir when the session
+//! starts, added to the sandbox's writable roots, exported to commands as
+//! `CODEX_SCRATCH_DIR` and described to the model, so one-off scripts and
+//! logs stop landing in the repository. It is removed when the session ends.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use codex_protocol::ConversationId;
+use codex_utils_absolute_path::AbsolutePathBuf;
+
+use crate::codex::TurnContext;
+use crate::protocol::SandboxPolicy;
+
+/// Environment variable holding the directory's path in every command.
+pub(crate) const SCRATCH_DIR_ENV_VAR: &str = "CODEX_SCRATCH_DIR";
+
+pub(crate) struct ScratchDir {
+    path: AbsolutePathBuf,
+    had_error: AtomicBool,
+}
+
+impl ScratchDir {
+    /// Creates `codex-scratch-<conversation id>-<random>` under the temp
+    /// dir. The directory is always new and, on Unix, readable only by the
+    /// user, so nobody else sharing the temp dir can plant files in it first.
+    pub(crate) fn create(conversation_id: ConversationId) -> std::io::Result<Self> {
+        let prefix = format!("codex-scratch-{conversation_id}-");
+        let mut builder = tempfile::Builder::new();
+        builder.prefix(&prefix);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            builder.permissions(std::fs::Permissions::from_mode(0o700));
+        }
+        // Removed by `cleanup` rather than on drop, so it can be kept.
+        let path = builder.tempdir()?.keep();
+        Ok(Self {
+            path: AbsolutePathBuf::from_absolute_path(path)?,
+            had_error: AtomicBool::new(false),
+        })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    /// Lets commands run during the turn write to the directory and find it.
+    pub(crate) fn apply(&self, turn_context: &mut TurnContext) {
+        if let SandboxPolicy::WorkspaceWrite { writable_roots, .. } =
+            &mut turn_context.sandbox_policy
+            && !writable_roots.contains(&self.path)
+        {
+            writable_roots.push(self.path.clone());
+        }
+        turn_context.shell_environment_policy.r#set.insert(
+            SCRATCH_DIR_ENV_VAR.to_string(),
+            self.path.to_string_lossy().into_owned(),
+        );
+    }
+
+    /// Developer message telling the model what the directory is for.
+    pub(crate) fn instructions(&self) -> String {
+        format!(
+            "A scratch directory for this session is at {} (also in ${SCRATCH_DIR_ENV_VAR}). Put throwaway scripts, logs and other temporary files there instead of in the workspace. It is deleted when the session ends.",
+            self.path.display()
+        )
+    }
+
+    /// Records that the session reported an error, for `keep_on_error`.
+    pub(crate) fn note_error(&self) {
+        self.had_error.store(true, Ordering::Relaxed);
+    }
+
+    /// Removes the directory, unless `keep_on_error` is set and the session
+    /// reported an error. Returns the path when the directory was kept.
+    pub(crate) async fn cleanup(&self, keep_on_error: bool) -> Option<PathBuf> {
+        if keep_on_error && self.had_error.load(Ordering::Relaxed) {
+            return Some(self.path.to_path_buf());
+        }
+        if let Err(err) = tokio::fs::remove_dir_all(&self.path).await
+            && err.kind() != std::io::ErrorKind::NotFound
+        {
+            tracing::warn!(
+                "failed to remove scratch directory {}: {err}",
+                self.path.display()
+            );
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn cleanup_keeps_the_directory_only_after_an_error() -> anyhow::Result<()> {
+        let scratch = ScratchDir::create(ConversationId::new())?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(scratch.path())?.permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+        std::fs::write(scratch.path().join("notes.txt"), "hi")?;
+        assert_eq!(scratch.cleanup(true).await, None);
+        assert!(!scratch.path().exists());
+
+        let scratch = ScratchDir::create(ConversationId::new())?;
+        scratch.note_error();
+        assert_eq!(
+            scratch.cleanup(true).await,
+            Some(scratch.path().to_path_buf())
+        );
+        assert!(scratch.path().exists());
+        assert_eq!(scratch.cleanup(false).await, None);
+        assert!(!scratch.path().exists());
+        Ok(())
+    }
+}
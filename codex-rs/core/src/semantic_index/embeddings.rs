@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use crate::config::types::EmbeddingProvider;
+use crate::default_client::build_reqwest_client;
+
+const OPENAI_API_URL: &str = "https://api.openai.com/v1";
+const OLLAMA_API_URL: &str = "http://localhost:11434";
+const DEFAULT_OPENAI_MODEL: &str = "text-embedding-3-small";
+const DEFAULT_OPENAI_API_KEY_ENV: &str = "OPENAI_API_KEY";
+/// Inputs sent per request; both APIs accept far more, but smaller batches
+/// keep a single slow request from holding up the whole index.
+const BATCH_SIZE: usize = 64;
+const EMBED_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Deserialize)]
+struct OpenAiResponse {
+    data: Vec<OpenAiEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[derive(Deserialize)]
+struct OllamaResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+/// Identifies the vectors `provider` produces; an index built with another
+/// model is discarded rather than compared against.
+pub(crate) fn model_id(provider: &EmbeddingProvider) -> String {
+    match provider {
+        EmbeddingProvider::Openai { model, .. } => {
+            format!(
+                "openai/{}",
+                model.as_deref().unwrap_or(DEFAULT_OPENAI_MODEL)
+            )
+        }
+        EmbeddingProvider::Ollama { model, .. } => format!("ollama/{model}"),
+    }
+}
+
+/// Embeds `inputs` in order, batching requests to the provider.
+pub(crate) async fn embed(
+    provider: &EmbeddingProvider,
+    inputs: &[String],
+) -> Result<Vec<Vec<f32>>> {
+    let client = build_reqwest_client();
+    let mut vectors = Vec::with_capacity(inputs.len());
+    for batch in inputs.chunks(BATCH_SIZE) {
+        let mut embedded = match provider {
+            EmbeddingProvider::Openai {
+                model,
+                api_key_env,
+                base_url,
+            } => {
+                let env = api_key_env.as_deref().unwrap_or(DEFAULT_OPENAI_API_KEY_ENV);
+                let api_key = std::env::var(env)
+                    .ok()
+                    .filter(|key| !key.trim().is_empty())
+                    .with_context(|| format!("set `{env}` to the embedding provider's API key"))?;
+                let base_url = base_url.as_deref().unwrap_or(OPENAI_API_URL);
+                let request = client
+                    .post(format!("{}/embeddings", base_url.trim_end_matches('/')))
+                    .bearer_auth(api_key)
+                    .json(&json!({
+                        "model": model.as_deref().unwrap_or(DEFAULT_OPENAI_MODEL),
+                        "input": batch,
+                    }));
+                let mut response: OpenAiResponse = send(request).await?;
+                response.data.sort_by_key(|embedding| embedding.index);
+                response
+                    .data
+                    .into_iter()
+                    .map(|embedding| embedding.embedding)
+                    .collect::<Vec<_>>()
+            }
+            EmbeddingProvider::Ollama { model, base_url } => {
+                let base_url = base_url.as_deref().unwrap_or(OLLAMA_API_URL);
+                let request = client
+                    .post(format!("{}/api/embed", base_url.trim_end_matches('/')))
+                    .json(&json!({
+                        "model": model,
+                        "input": batch,
+                    }));
+                let response: OllamaResponse = send(request).await?;
+                response.embeddings
+            }
+        };
+        if embedded.len() != batch.len() {
+            bail!(
+                "embedding provider returned {} vectors for {} inputs",
+                embedded.len(),
+                batch.len()
+            );
+        }
+        vectors.append(&mut embedded);
+    }
+    Ok(vectors)
+}
+
+async fn send<T: DeserializeOwned>(request: reqwest::RequestBuilder) -> Result<T> {
+    let response = request.timeout(EMBED_TIMEOUT).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        bail!("embedding provider returned {status}: {}", body.trim());
+    }
+    Ok(response.json().await?)
+}
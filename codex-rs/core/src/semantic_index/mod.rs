@@ -0,0 +1,531 @@
+//! An embeddings index of the workspace behind the `semantic_search` feature.
+//!
+//! Files are split into overlapping windows of lines, embedded with the
+//! `[embeddings]` provider and stored in `.codex/index/chunks.json` under the
+//! project root. Every search first re-embeds the files whose size or
+//! modification time changed and drops deleted ones, so the index follows
+//! edits without a file watcher and an untouched checkout costs one walk.
+//! Automatic retrieval waits at most [`AUTO_RETRIEVE_BUDGET`] for that update
+//! and leaves it running in the background otherwise.
+
+mod embeddings;
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::LazyLock;
+use std::time::Duration;
+use std::time::UNIX_EPOCH;
+
+use anyhow::Context;
+use anyhow::Result;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use ignore::WalkBuilder;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::types::EmbeddingProvider;
+use crate::config::types::EmbeddingsConfig;
+use crate::repo_map::project_root;
+use crate::truncate::approx_token_count;
+
+/// Index location relative to the project root.
+pub const SEMANTIC_INDEX_PATH: &str = ".codex/index/chunks.json";
+
+pub(crate) const RETRIEVED_CONTEXT_OPEN_TAG: &str = "<retrieved_context>";
+const RETRIEVED_CONTEXT_CLOSE_TAG: &str = "</retrieved_context>";
+
+/// Bumped whenever the index layout or chunking changes.
+const INDEX_VERSION: u32 = 1;
+/// Stop walking after this many files; huge monorepos get a partial index.
+const MAX_FILES: usize = 5_000;
+/// Larger files are usually generated or vendored and are not indexed.
+const MAX_FILE_BYTES: u64 = 256 * 1024;
+const CHUNK_LINES: usize = 60;
+/// Lines shared by consecutive chunks, so a definition split across a
+/// boundary still appears whole in one of them.
+const CHUNK_OVERLAP: usize = 10;
+/// Chunks are cut off here so one minified line cannot exceed the embedding
+/// model's input limit.
+const MAX_CHUNK_BYTES: usize = 8 * 1024;
+
+/// Longest a turn waits for automatic retrieval before it starts without
+/// the retrieved context.
+const AUTO_RETRIEVE_BUDGET: Duration = Duration::from_secs(3);
+
+/// Serializes index updates, which otherwise race to embed the same files
+/// when the tool and automatic retrieval run together.
+static INDEX_LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct IndexedChunk {
+    /// 1-indexed, inclusive.
+    start_line: usize,
+    end_line: usize,
+    vector: Vec<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct IndexedFile {
+    size: u64,
+    /// Seconds since the epoch; with `size`, decides whether the stored
+    /// vectors are still valid.
+    modified: u64,
+    chunks: Vec<IndexedChunk>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct SemanticIndex {
+    version: u32,
+    /// Vectors from different models are not comparable, so a model change
+    /// rebuilds the index.
+    model: String,
+    /// Keyed by path relative to the project root, with `/` separators.
+    files: BTreeMap<String, IndexedFile>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SearchHit {
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Cosine similarity to the query.
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TextChunk {
+    start_line: usize,
+    end_line: usize,
+    text: String,
+}
+
+enum ScannedFile {
+    Unchanged(IndexedFile),
+    Changed {
+        size: u64,
+        modified: u64,
+        chunks: Vec<TextChunk>,
+    },
+}
+
+/// Returns the chunks of the workspace containing `cwd` most similar to
+/// `query`, bringing the index up to date first.
+pub(crate) async fn search(
+    cwd: &Path,
+    config: &EmbeddingsConfig,
+    query: &str,
+    max_results: usize,
+) -> Result<Vec<SearchHit>> {
+    let root = project_root(cwd);
+    let index = update(&root, &config.provider).await?;
+    let query_vector = embeddings::embed(&config.provider, &[query.to_string()])
+        .await?
+        .pop()
+        .context("embedding provider returned no vector for the query")?;
+    Ok(rank(&index, &query_vector, max_results))
+}
+
+/// Renders `hits` as `path:start-end` headers followed by the chunk's lines,
+/// read from the workspace containing `cwd`, stopping at `max_tokens`. Hits
+/// whose lines no longer exist are skipped.
+pub(crate) fn render_hits(cwd: &Path, hits: &[SearchHit], max_tokens: usize) -> String {
+    let root = project_root(cwd);
+    let mut files: HashMap<&str, Option<Vec<String>>> = HashMap::new();
+    let mut sections = Vec::new();
+    let mut used_tokens = 0;
+    for hit in hits {
+        let lines = files.entry(hit.path.as_str()).or_insert_with(|| {
+            std::fs::read(root.join(&hit.path)).ok().map(|bytes| {
+                String::from_utf8_lossy(&bytes)
+                    .lines()
+                    .map(str::to_string)
+                    .collect()
+            })
+        });
+        let Some(lines) = lines.as_ref() else {
+            continue;
+        };
+        let Some(body) = lines.get(hit.start_line.saturating_sub(1)..hit.end_line.min(lines.len()))
+        else {
+            continue;
+        };
+        let section = format!(
+            "{}:{}-{}\n{}",
+            hit.path,
+            hit.start_line,
+            hit.end_line.min(lines.len()),
+            body.join("\n")
+        );
+        let tokens = approx_token_count(&section);
+        if used_tokens + tokens > max_tokens {
+            break;
+        }
+        used_tokens += tokens;
+        sections.push(section);
+    }
+    sections.join("\n\n")
+}
+
+/// Builds the message that adds the chunks most relevant to `text` to the
+/// context, or `None` when nothing in the index relates to it, the index is
+/// not ready within [`AUTO_RETRIEVE_BUDGET`], or `cancel` fires. An update
+/// that outlasts the budget keeps running for later turns; cancelling stops
+/// it.
+pub(crate) async fn retrieve_context(
+    cwd: &Path,
+    config: &EmbeddingsConfig,
+    text: &str,
+    cancel: &CancellationToken,
+) -> Result<Option<ResponseItem>> {
+    let root = project_root(cwd);
+    let provider = config.provider.clone();
+    let updating = tokio::spawn(async move { update(&root, &provider).await });
+    let stop_updating = updating.abort_handle();
+    let retrieval = async move {
+        let index = updating.await??;
+        let query_vector = embeddings::embed(&config.provider, &[text.to_string()])
+            .await?
+            .pop()
+            .context("embedding provider returned no vector for the query")?;
+        anyhow::Ok(rank(&index, &query_vector, config.max_results))
+    };
+    let hits = tokio::select! {
+        hits = retrieval => hits?,
+        _ = cancel.cancelled() => {
+            stop_updating.abort();
+            return Ok(None);
+        }
+        _ = tokio::time::sleep(AUTO_RETRIEVE_BUDGET) => {
+            tracing::info!("workspace index not ready; continuing without retrieved context");
+            return Ok(None);
+        }
+    };
+    let hits: Vec<SearchHit> = hits.into_iter().filter(|hit| hit.score > 0.0).collect();
+    let rendered = render_hits(cwd, &hits, config.retrieval_max_tokens);
+    if rendered.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: format!(
+                "{RETRIEVED_CONTEXT_OPEN_TAG}\nExcerpts from the workspace that may be relevant to the request, most similar first.\n\n{rendered}\n{RETRIEVED_CONTEXT_CLOSE_TAG}"
+            ),
+        }],
+    }))
+}
+
+/// Returns true when `message` is automatically retrieved context.
+pub(crate) fn is_retrieved_context(message: &[ContentItem]) -> bool {
+    if let [ContentItem::InputText { text }] = message {
+        text.starts_with(RETRIEVED_CONTEXT_OPEN_TAG)
+    } else {
+        false
+    }
+}
+
+/// Re-embeds changed files under `root`, drops deleted ones and rewrites the
+/// index when anything changed.
+async fn update(root: &Path, provider: &EmbeddingProvider) -> Result<SemanticIndex> {
+    let _guard = INDEX_LOCK.lock().await;
+    let model = embeddings::model_id(provider);
+    let scan_root = root.to_path_buf();
+    let scan_model = model.clone();
+    let (scanned, stale) =
+        tokio::task::spawn_blocking(move || scan(&scan_root, &scan_model)).await?;
+
+    let inputs: Vec<String> = scanned
+        .iter()
+        .flat_map(|(path, file)| match file {
+            ScannedFile::Changed { chunks, .. } => chunks
+                .iter()
+                .map(|chunk| format!("{path}\n{}", chunk.text))
+                .collect::<Vec<_>>(),
+            ScannedFile::Unchanged(_) => Vec::new(),
+        })
+        .collect();
+    let mut vectors = if inputs.is_empty() {
+        Vec::new()
+    } else {
+        embeddings::embed(provider, &inputs).await?
+    }
+    .into_iter();
+
+    let mut files = BTreeMap::new();
+    for (path, file) in scanned {
+        let file = match file {
+            ScannedFile::Unchanged(file) => file,
+            ScannedFile::Changed {
+                size,
+                modified,
+                chunks,
+            } => IndexedFile {
+                size,
+                modified,
+                chunks: chunks
+                    .into_iter()
+                    .zip(vectors.by_ref())
+                    .map(|(chunk, vector)| IndexedChunk {
+                        start_line: chunk.start_line,
+                        end_line: chunk.end_line,
+                        vector,
+                    })
+                    .collect(),
+            },
+        };
+        files.insert(path, file);
+    }
+
+    let index = SemanticIndex {
+        version: INDEX_VERSION,
+        model,
+        files,
+    };
+    if stale {
+        let index_path = root.join(SEMANTIC_INDEX_PATH);
+        let serialized = serde_json::to_vec(&index)?;
+        tokio::task::spawn_blocking(move || write_index(&index_path, &serialized)).await??;
+    }
+    Ok(index)
+}
+
+/// Walks `root`, reusing stored vectors for unchanged files and chunking the
+/// rest. The flag is set when the stored index no longer matches the
+/// workspace.
+fn scan(root: &Path, model: &str) -> (Vec<(String, ScannedFile)>, bool) {
+    let mut stored: BTreeMap<String, IndexedFile> =
+        std::fs::read_to_string(root.join(SEMANTIC_INDEX_PATH))
+            .ok()
+            .and_then(|text| serde_json::from_str::<SemanticIndex>(&text).ok())
+            .filter(|index| index.version == INDEX_VERSION && index.model == model)
+            .map(|index| index.files)
+            .unwrap_or_default();
+
+    let mut scanned = Vec::new();
+    let mut stale = false;
+    let walker = WalkBuilder::new(root)
+        .require_git(false)
+        .sort_by_file_name(Ord::cmp)
+        .build();
+    for entry in walker {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+            continue;
+        }
+        let Ok(relative) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let size = metadata.len();
+        if size > MAX_FILE_BYTES {
+            continue;
+        }
+        if scanned.len() == MAX_FILES {
+            break;
+        }
+        let path = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        if let Some(hit) = stored.remove(&path)
+            && hit.size == size
+            && hit.modified == modified
+        {
+            scanned.push((path, ScannedFile::Unchanged(hit)));
+            continue;
+        }
+        stale = true;
+        let chunks = match std::fs::read(entry.path()) {
+            // Binary files carry no text worth embedding.
+            Ok(bytes) if !bytes.contains(&0) => chunk_text(&String::from_utf8_lossy(&bytes)),
+            _ => Vec::new(),
+        };
+        scanned.push((
+            path,
+            ScannedFile::Changed {
+                size,
+                modified,
+                chunks,
+            },
+        ));
+    }
+    // Whatever is left was deleted, ignored or pushed past `MAX_FILES`.
+    stale |= !stored.is_empty();
+    (scanned, stale)
+}
+
+fn chunk_text(text: &str) -> Vec<TextChunk> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < lines.len() {
+        let end = (start + CHUNK_LINES).min(lines.len());
+        let mut text = lines[start..end].join("\n");
+        if text.len() > MAX_CHUNK_BYTES {
+            let mut cut = MAX_CHUNK_BYTES;
+            while !text.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            text.truncate(cut);
+        }
+        if !text.trim().is_empty() {
+            chunks.push(TextChunk {
+                start_line: start + 1,
+                end_line: end,
+                text,
+            });
+        }
+        if end == lines.len() {
+            break;
+        }
+        start += CHUNK_LINES - CHUNK_OVERLAP;
+    }
+    chunks
+}
+
+fn rank(index: &SemanticIndex, query: &[f32], max_results: usize) -> Vec<SearchHit> {
+    let mut hits: Vec<SearchHit> = index
+        .files
+        .iter()
+        .flat_map(|(path, file)| {
+            file.chunks.iter().map(move |chunk| SearchHit {
+                path: path.clone(),
+                start_line: chunk.start_line,
+                end_line: chunk.end_line,
+                score: cosine_similarity(query, &chunk.vector),
+            })
+        })
+        .collect();
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(max_results);
+    hits
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+fn write_index(index_path: &Path, serialized: &[u8]) -> io::Result<()> {
+    if let Some(parent) = index_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = index_path.with_extension("json.tmp");
+    std::fs::write(&tmp, serialized)?;
+    std::fs::rename(tmp, index_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    #[test]
+    fn chunks_overlap_and_skip_blank_windows() {
+        let text: String = (1..=130)
+            .map(|n| {
+                if n > 120 {
+                    "\n".to_string()
+                } else {
+                    format!("line {n}\n")
+                }
+            })
+            .collect();
+
+        let ranges: Vec<(usize, usize)> = chunk_text(&text)
+            .iter()
+            .map(|chunk| (chunk.start_line, chunk.end_line))
+            .collect();
+
+        assert_eq!(ranges, vec![(1, 60), (51, 110), (101, 130)]);
+        assert_eq!(chunk_text("\n\n  \n"), Vec::new());
+    }
+
+    #[test]
+    fn ranks_by_cosine_similarity_and_renders_within_budget() {
+        let dir = tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("a.rs"), "fn alpha() {}\nfn beta() {}\n").expect("write");
+        std::fs::write(dir.path().join("b.rs"), "fn gamma() {}\n").expect("write");
+
+        let chunk = |start_line, end_line, vector: Vec<f32>| IndexedChunk {
+            start_line,
+            end_line,
+            vector,
+        };
+        let file = |chunks| IndexedFile {
+            size: 0,
+            modified: 0,
+            chunks,
+        };
+        let index = SemanticIndex {
+            version: INDEX_VERSION,
+            model: "test".to_string(),
+            files: BTreeMap::from([
+                (
+                    "a.rs".to_string(),
+                    file(vec![
+                        chunk(1, 1, vec![1.0, 0.0]),
+                        chunk(2, 2, vec![0.6, 0.8]),
+                    ]),
+                ),
+                ("b.rs".to_string(), file(vec![chunk(1, 1, vec![0.0, 1.0])])),
+            ]),
+        };
+
+        let hits = rank(&index, &[0.0, 2.0], 2);
+        assert_eq!(
+            hits.iter()
+                .map(|hit| (hit.path.as_str(), hit.start_line))
+                .collect::<Vec<_>>(),
+            vec![("b.rs", 1), ("a.rs", 2)]
+        );
+
+        assert_eq!(
+            render_hits(dir.path(), &hits, 1_000),
+            "b.rs:1-1\nfn gamma() {}\n\na.rs:2-2\nfn beta() {}"
+        );
+        assert_eq!(render_hits(dir.path(), &hits, 6), "b.rs:1-1\nfn gamma() {}");
+    }
+
+    #[test]
+    fn retrieved_context_is_recognized_by_its_tag() {
+        let message = |text: &str| {
+            vec![ContentItem::InputText {
+                text: text.to_string(),
+            }]
+        };
+        assert!(is_retrieved_context(&message(
+            "<retrieved_context>\nsrc/lib.rs:1-2\n</retrieved_context>"
+        )));
+        assert!(!is_retrieved_context(&message(
+            "what does retrieved_context do?"
+        )));
+    }
+}
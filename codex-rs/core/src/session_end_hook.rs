@@ -0,0 +1,61 @@
+//! `SessionEnd` hooks, run while the session shuts down.
+//!
+//! Unlike `notify`, these hooks are awaited: shutdown does not complete until
+//! each has exited or timed out, so a hook can upload logs or release a lock
+//! before the process goes away. Their output is ignored.
+
+use std::time::Duration;
+
+use serde::Serialize;
+use tracing::error;
+use tracing::warn;
+
+use crate::hooks;
+use crate::hooks::HookCommand;
+use crate::hooks::HookSandbox;
+
+/// How long shutdown waits for each hook.
+pub(crate) const SESSION_END_HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The ending session handed to the hook.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename = "session-end", rename_all = "kebab-case")]
+pub(crate) struct SessionEnd {
+    pub(crate) thread_id: String,
+    /// Mutating tool calls killed because they outlived the drain timeout.
+    pub(crate) killed_tool_calls: usize,
+}
+
+pub(crate) async fn run(hook: &HookCommand, event: &SessionEnd, sandbox: &HookSandbox) {
+    let Some(json) = hooks::payload(event) else {
+        error!("failed to serialise session-end event");
+        return;
+    };
+    if let Err(err) = hooks::output(hook, json, sandbox, SESSION_END_HOOK_TIMEOUT).await {
+        warn!("session-end hook {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn serializes_session_end() {
+        let event = SessionEnd {
+            thread_id: "thread-1".to_string(),
+            killed_tool_calls: 1,
+        };
+        let json = hooks::payload(&event).expect("payload");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).expect("json"),
+            serde_json::json!({
+                "type": "session-end",
+                "thread-id": "thread-1",
+                "killed-tool-calls": 1,
+                "hook-protocol-version": 1,
+            })
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! Live status of running sessions, for shell prompts and editor statuslines.
+//!
+//! Every interactive or headless session keeps a small JSON file under
+//! `$CODEX_HOME/status` up to date with its model, whether a task is running
+//! and how much of the context window is left, and removes it when it shuts
+//! down. [`status_report`] combines those files with today's spend from the
+//! usage ledger; `codex status` prints it and the app server can serve it on
+//! a Unix socket.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use chrono::Local;
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::config::Config;
+use crate::usage_ledger::read_records;
+use crate::usage_ledger::start_of_local_day;
+use crate::usage_ledger::summarize_by_day;
+
+/// Directory inside `~/.codex` holding one status file per live session.
+pub const SESSION_STATUS_DIRNAME: &str = "status";
+
+/// What a live session reports about itself.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SessionStatus {
+    pub session_id: String,
+    pub pid: u32,
+    pub cwd: PathBuf,
+    pub model: String,
+    /// Whether a task is running.
+    pub busy: bool,
+    /// Percentage of the context window left; `None` until the model first
+    /// reports usage.
+    #[serde(default)]
+    pub context_left_percent: Option<i64>,
+    /// Tokens used by the session so far.
+    #[serde(default)]
+    pub tokens_used: i64,
+    /// Unix seconds.
+    pub started_at: u64,
+    /// Unix seconds of the last change.
+    pub updated_at: u64,
+}
+
+/// The output of `codex status --json`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StatusReport {
+    /// Live sessions, most recently updated first.
+    pub active_sessions: Vec<SessionStatus>,
+    /// Model of the most recently updated session, or the configured model
+    /// when no session is running. `None` when neither is known.
+    pub model: Option<String>,
+    /// Estimated spend since local midnight, across all sessions.
+    pub cost_today_usd: f64,
+    /// Tokens used since local midnight, across all sessions.
+    pub tokens_today: i64,
+}
+
+/// The status file of this process's session. Dropping it removes the file.
+pub(crate) struct SessionStatusFile {
+    path: PathBuf,
+    status: Mutex<SessionStatus>,
+}
+
+impl SessionStatusFile {
+    pub(crate) fn create(
+        codex_home: &Path,
+        session_id: String,
+        cwd: PathBuf,
+        model: String,
+    ) -> Self {
+        let now = unix_now();
+        let file = Self {
+            path: status_dir(codex_home).join(format!("{session_id}.json")),
+            status: Mutex::new(SessionStatus {
+                session_id,
+                pid: std::process::id(),
+                cwd,
+                model,
+                busy: false,
+                context_left_percent: None,
+                tokens_used: 0,
+                started_at: now,
+                updated_at: now,
+            }),
+        };
+        if let Some(dir) = file.path.parent()
+            && let Err(err) = std::fs::create_dir_all(dir)
+        {
+            warn!("failed to create {}: {err}", dir.display());
+        }
+        file.update(|_| true);
+        file
+    }
+
+    /// Applies `change` and rewrites the file when it reports a change.
+    pub(crate) fn update(&self, change: impl FnOnce(&mut SessionStatus) -> bool) {
+        let Ok(mut status) = self.status.lock() else {
+            return;
+        };
+        if !change(&mut status) {
+            return;
+        }
+        status.updated_at = unix_now();
+        if let Err(err) = write_atomically(&self.path, &status) {
+            warn!("failed to write {}: {err}", self.path.display());
+        }
+    }
+
+    pub(crate) fn remove(&self) {
+        match std::fs::remove_file(&self.path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => warn!("failed to remove {}: {err}", self.path.display()),
+        }
+    }
+}
+
+impl Drop for SessionStatusFile {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+pub fn status_dir(codex_home: &Path) -> PathBuf {
+    codex_home.join(SESSION_STATUS_DIRNAME)
+}
+
+/// Status files of sessions whose process is still running, most recently
+/// updated first. Files left behind by processes that died are removed.
+pub fn active_sessions(codex_home: &Path) -> Vec<SessionStatus> {
+    let Ok(entries) = std::fs::read_dir(status_dir(codex_home)) else {
+        return Vec::new();
+    };
+    let mut sessions = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let Some(status) = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str::<SessionStatus>(&text).ok())
+        else {
+            continue;
+        };
+        if process_is_running(status.pid) {
+            sessions.push(status);
+        } else {
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+    sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    sessions
+}
+
+/// Live sessions, the current model and today's usage.
+pub fn status_report(config: &Config) -> StatusReport {
+    let active_sessions = active_sessions(&config.codex_home);
+    let model = active_sessions
+        .first()
+        .map(|session| session.model.clone())
+        .or_else(|| config.model.clone());
+    let records = read_records(&config.codex_home).unwrap_or_default();
+    let today = summarize_by_day(
+        &records,
+        start_of_local_day(Local::now()),
+        &config.model_pricing,
+    )
+    .pop()
+    .map(|day| day.totals)
+    .unwrap_or_default();
+    StatusReport {
+        active_sessions,
+        model,
+        cost_today_usd: today.cost_usd,
+        tokens_today: today.input_tokens + today.output_tokens,
+    }
+}
+
+fn write_atomically(path: &Path, status: &SessionStatus) -> std::io::Result<()> {
+    let json = serde_json::to_vec(status).map_err(std::io::Error::other)?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(&tmp, path)
+}
+
+#[cfg(unix)]
+fn process_is_running(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 only checks that the process exists and may be signalled.
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return true;
+    }
+    std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Without a cheap liveness check, trust the file; sessions remove their own
+/// file when they shut down.
+#[cfg(not(unix))]
+fn process_is_running(_pid: u32) -> bool {
+    true
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn status_files_follow_the_session() {
+        let home = TempDir::new().expect("tempdir");
+        let file = SessionStatusFile::create(
+            home.path(),
+            "abc".to_string(),
+            PathBuf::from("/repo"),
+            "gpt-5.1-codex".to_string(),
+        );
+        file.update(|status| {
+            status.busy = true;
+            status.context_left_percent = Some(63);
+            true
+        });
+
+        let sessions = active_sessions(home.path());
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].session_id, "abc");
+        assert_eq!(sessions[0].pid, std::process::id());
+        assert_eq!(sessions[0].model, "gpt-5.1-codex");
+        assert!(sessions[0].busy);
+        assert_eq!(sessions[0].context_left_percent, Some(63));
+
+        drop(file);
+        assert_eq!(active_sessions(home.path()), Vec::new());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn files_of_dead_processes_are_removed() {
+        let home = TempDir::new().expect("tempdir");
+        let dir = status_dir(home.path());
+        std::fs::create_dir_all(&dir).expect("create status dir");
+        let mut child = std::process::Command::new("true")
+            .spawn()
+            .expect("spawn true");
+        child.wait().expect("wait for true");
+        let stale = SessionStatus {
+            session_id: "gone".to_string(),
+            pid: child.id(),
+            cwd: PathBuf::from("/repo"),
+            model: "gpt-5.1".to_string(),
+            busy: true,
+            context_left_percent: None,
+            tokens_used: 0,
+            started_at: 1,
+            updated_at: 1,
+        };
+        let path = dir.join("gone.json");
+        write_atomically(&path, &stale).expect("write status");
+
+        assert_eq!(active_sessions(home.path()), Vec::new());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,142 @@
+//! The title and summary recorded in rollout files so session lists can show
+//! what a session was about. Both are derived from the transcript, without a
+//! model request: the title from the first request, the summary from the
+//! first request, how many followed it and the latest reply.
+
+use codex_protocol::items::AgentMessageContent;
+use codex_protocol::items::TurnItem;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::SessionSummaryItem;
+
+use crate::event_mapping::parse_turn_item;
+
+const TITLE_CHARS: usize = 60;
+const REQUEST_CHARS: usize = 160;
+const REPLY_CHARS: usize = 200;
+
+/// The summary of `items`, or `None` before the user has asked anything.
+pub(crate) fn summarize(items: &[ResponseItem]) -> Option<SessionSummaryItem> {
+    let mut requests = Vec::new();
+    let mut last_reply = None;
+    for item in items {
+        match parse_turn_item(item) {
+            Some(TurnItem::UserMessage(user)) => {
+                let message = collapse_whitespace(&user.message());
+                if !message.is_empty() {
+                    requests.push(message);
+                }
+            }
+            Some(TurnItem::AgentMessage(agent)) => {
+                let reply = agent
+                    .content
+                    .iter()
+                    .map(|content| match content {
+                        AgentMessageContent::Text { text } => text.as_str(),
+                    })
+                    .collect::<String>();
+                let reply = collapse_whitespace(&reply);
+                if !reply.is_empty() {
+                    last_reply = Some(reply);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let first = requests.first()?;
+    let mut summary = truncate_at_word(first, REQUEST_CHARS);
+    match requests.len() - 1 {
+        0 => {}
+        1 => summary.push_str(" (1 follow-up)"),
+        followups => summary.push_str(&format!(" ({followups} follow-ups)")),
+    }
+    if let Some(reply) = last_reply {
+        summary.push_str(" → ");
+        summary.push_str(&truncate_at_word(&reply, REPLY_CHARS));
+    }
+    Some(SessionSummaryItem {
+        title: truncate_at_word(first, TITLE_CHARS),
+        summary,
+    })
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `text` cut to at most `max_chars`, at the last word boundary that fits.
+fn truncate_at_word(text: &str, max_chars: usize) -> String {
+    let Some((end, _)) = text.char_indices().nth(max_chars) else {
+        return text.to_string();
+    };
+    let cut = &text[..end];
+    let cut = match cut.rfind(' ') {
+        Some(space) if space > 0 => &cut[..space],
+        _ => cut,
+    };
+    format!("{}…", cut.trim_end_matches([',', '.', ';', ':']))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::ContentItem;
+    use pretty_assertions::assert_eq;
+
+    fn message(role: &str, text: &str) -> ResponseItem {
+        let content = if role == "assistant" {
+            ContentItem::OutputText {
+                text: text.to_string(),
+            }
+        } else {
+            ContentItem::InputText {
+                text: text.to_string(),
+            }
+        };
+        ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![content],
+        }
+    }
+
+    #[test]
+    fn summarizes_the_first_request_and_the_latest_reply() {
+        let items = vec![
+            message(
+                "user",
+                "<environment_context>\n  <cwd>/repo</cwd>\n</environment_context>",
+            ),
+            message("user", "Fix the flaky\n  resume picker test"),
+            message("assistant", "It raced the page loader; I added a wait."),
+            message("user", "also run clippy"),
+            message("assistant", "Clippy is clean."),
+        ];
+        assert_eq!(
+            summarize(&items),
+            Some(SessionSummaryItem {
+                title: "Fix the flaky resume picker test".to_string(),
+                summary: "Fix the flaky resume picker test (1 follow-up) → Clippy is clean."
+                    .to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn long_requests_are_cut_at_a_word_boundary() {
+        let request = "Refactor the rollout listing so that it reads the head and the tail of each file in one pass";
+        let items = vec![message("user", request)];
+        let summary = summarize(&items).expect("summary");
+        assert_eq!(
+            summary.title,
+            "Refactor the rollout listing so that it reads the head and…"
+        );
+        assert_eq!(summary.summary, request);
+    }
+
+    #[test]
+    fn sessions_without_a_request_have_no_summary() {
+        assert_eq!(summarize(&[message("assistant", "Hello!")]), None);
+        assert_eq!(summarize(&[]), None);
+    }
+}
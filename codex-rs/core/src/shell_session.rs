@@ -0,0 +1,407 @@
+//! The long-lived shell behind the `shell_session` tool.
+//!
+//! Commands are written to a single POSIX shell over stdin, so `cd`,
+//! exported variables, activated virtualenvs and shell functions carry over
+//! between calls. After each command the shell prints a marker line holding
+//! the exit status and working directory; that line is how we know the
+//! command finished.
+
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rand::Rng;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Child;
+use tokio::process::ChildStdin;
+use tokio::sync::Mutex;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+use crate::exec::SandboxType;
+use crate::exec::kill_child_process_group;
+use crate::protocol::SandboxPolicy;
+use crate::sandboxing::ExecEnv;
+use crate::spawn::StdioPolicy;
+use crate::spawn::spawn_child_async;
+
+/// How long the shell may take to load its profile before the first command.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Result of running one command in the session shell.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ShellSessionOutcome {
+    pub output: String,
+    /// `None` when the command did not finish: it timed out or the shell
+    /// exited.
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    /// Tells the model when shell state was lost.
+    pub note: Option<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct ShellSessionManager {
+    shell: Mutex<Option<PersistentShell>>,
+}
+
+impl ShellSessionManager {
+    /// Working directory the shell was left in, if one is running.
+    pub(crate) async fn cwd(&self) -> Option<PathBuf> {
+        self.shell
+            .lock()
+            .await
+            .as_ref()
+            .map(|shell| shell.cwd.clone())
+    }
+
+    /// Runs `command` in the session shell, starting one from `spawn_env`
+    /// first when none is running or the sandbox it was started under no
+    /// longer matches.
+    pub(crate) async fn run(
+        &self,
+        command: &str,
+        spawn_env: ExecEnv,
+        sandbox_policy: &SandboxPolicy,
+        timeout: Duration,
+    ) -> io::Result<ShellSessionOutcome> {
+        let mut guard = self.shell.lock().await;
+        let mut notes = Vec::new();
+        if let Some(shell) = guard.as_ref()
+            && (shell.sandbox != spawn_env.sandbox || &shell.sandbox_policy != sandbox_policy)
+        {
+            if let Some(mut shell) = guard.take() {
+                shell.kill();
+            }
+            notes.push("The sandbox changed, so a new shell was started and earlier state (cwd, variables) was lost.".to_string());
+        }
+        let shell = match guard.as_mut() {
+            Some(shell) => shell,
+            None => guard.insert(PersistentShell::spawn(spawn_env, sandbox_policy).await?),
+        };
+
+        let outcome = match shell.run(command, timeout).await? {
+            RunResult::Finished {
+                output,
+                exit_code,
+                cwd,
+            } => {
+                shell.cwd = cwd;
+                ShellSessionOutcome {
+                    output,
+                    exit_code: Some(exit_code),
+                    timed_out: false,
+                    note: None,
+                }
+            }
+            RunResult::TimedOut { output } => {
+                shell.kill();
+                *guard = None;
+                notes.push("The command timed out, so the shell was killed; the next command starts a new shell.".to_string());
+                ShellSessionOutcome {
+                    output,
+                    exit_code: None,
+                    timed_out: true,
+                    note: None,
+                }
+            }
+            RunResult::Exited { output } => {
+                shell.kill();
+                *guard = None;
+                notes.push("The shell exited; the next command starts a new shell.".to_string());
+                ShellSessionOutcome {
+                    output,
+                    exit_code: None,
+                    timed_out: false,
+                    note: None,
+                }
+            }
+        };
+
+        Ok(ShellSessionOutcome {
+            note: (!notes.is_empty()).then(|| notes.join(" ")),
+            ..outcome
+        })
+    }
+
+    pub(crate) async fn terminate(&self) {
+        if let Some(mut shell) = self.shell.lock().await.take() {
+            shell.kill();
+        }
+    }
+}
+
+struct PersistentShell {
+    child: Child,
+    stdin: ChildStdin,
+    output_rx: mpsc::UnboundedReceiver<Vec<u8>>,
+    marker: String,
+    sandbox: SandboxType,
+    sandbox_policy: SandboxPolicy,
+    cwd: PathBuf,
+}
+
+#[derive(Debug, PartialEq)]
+enum RunResult {
+    Finished {
+        output: String,
+        exit_code: i32,
+        cwd: PathBuf,
+    },
+    TimedOut {
+        output: String,
+    },
+    Exited {
+        output: String,
+    },
+}
+
+impl PersistentShell {
+    async fn spawn(env: ExecEnv, sandbox_policy: &SandboxPolicy) -> io::Result<Self> {
+        let ExecEnv {
+            command,
+            cwd,
+            env,
+            sandbox,
+            arg0,
+            ..
+        } = env;
+        let (program, args) = command
+            .split_first()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "command args are empty"))?;
+        let mut child = spawn_child_async(
+            PathBuf::from(program),
+            args.to_vec(),
+            arg0.as_deref(),
+            cwd.clone(),
+            sandbox_policy,
+            StdioPolicy::PersistentShell,
+            env,
+        )
+        .await?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| io::Error::other("stdin pipe was unexpectedly not available"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| io::Error::other("stdout pipe was unexpectedly not available"))?;
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| io::Error::other("stderr pipe was unexpectedly not available"))?;
+        let (output_tx, output_rx) = mpsc::unbounded_channel();
+        forward_output(stdout, output_tx.clone());
+        forward_output(stderr, output_tx);
+
+        let nonce: String = {
+            let mut rng = rand::rng();
+            (0..16)
+                .map(|_| format!("{:x}", rng.random_range(0..16)))
+                .collect()
+        };
+        let mut shell = Self {
+            child,
+            stdin,
+            output_rx,
+            marker: format!("__codex_shell_session_{nonce}__"),
+            sandbox,
+            sandbox_policy: sandbox_policy.clone(),
+            cwd,
+        };
+
+        // Swallow whatever the profile prints so it does not end up in the
+        // first command's output.
+        match shell.run(":", STARTUP_TIMEOUT).await? {
+            RunResult::Finished { cwd, .. } => {
+                shell.cwd = cwd;
+                Ok(shell)
+            }
+            RunResult::TimedOut { output } | RunResult::Exited { output } => {
+                shell.kill();
+                Err(io::Error::other(format!(
+                    "shell failed to start: {}",
+                    output.trim()
+                )))
+            }
+        }
+    }
+
+    async fn run(&mut self, command: &str, timeout: Duration) -> io::Result<RunResult> {
+        let script = wrap_command(command, &self.marker);
+        let written = async {
+            self.stdin.write_all(script.as_bytes()).await?;
+            self.stdin.flush().await
+        }
+        .await;
+        if let Err(err) = written {
+            if err.kind() == io::ErrorKind::BrokenPipe {
+                return Ok(RunResult::Exited {
+                    output: String::new(),
+                });
+            }
+            return Err(err);
+        }
+
+        let deadline = Instant::now() + timeout;
+        let mut collected: Vec<u8> = Vec::new();
+        loop {
+            if let Some((output, exit_code, cwd)) = parse_marker(&collected, &self.marker) {
+                return Ok(RunResult::Finished {
+                    output,
+                    exit_code,
+                    cwd,
+                });
+            }
+            match tokio::time::timeout_at(deadline, self.output_rx.recv()).await {
+                Ok(Some(chunk)) => collected.extend_from_slice(&chunk),
+                Ok(None) => {
+                    return Ok(RunResult::Exited {
+                        output: String::from_utf8_lossy(&collected).into_owned(),
+                    });
+                }
+                Err(_) => {
+                    return Ok(RunResult::TimedOut {
+                        output: String::from_utf8_lossy(&collected).into_owned(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn kill(&mut self) {
+        let _ = kill_child_process_group(&mut self.child);
+        let _ = self.child.start_kill();
+    }
+}
+
+pub(crate) fn forward_output<R>(mut reader: R, tx: mpsc::UnboundedSender<Vec<u8>>)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut buf = [0u8; 8192];
+        loop {
+            match reader.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    if tx.send(buf[..n].to_vec()).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Runs the command through `eval` so a syntax error fails the command
+/// rather than the shell, and detaches its stdin so it cannot swallow the
+/// commands that follow.
+fn wrap_command(command: &str, marker: &str) -> String {
+    let quoted = format!("'{}'", command.replace('\'', r"'\''"));
+    format!("eval {quoted} </dev/null 2>&1\nprintf '\\n%s %s %s\\n' {marker} \"$?\" \"$PWD\"\n")
+}
+
+/// Splits collected output at the marker line, returning the command output
+/// along with its exit status and the shell's working directory.
+fn parse_marker(collected: &[u8], marker: &str) -> Option<(String, i32, PathBuf)> {
+    let needle = format!("\n{marker} ");
+    let start = collected
+        .windows(needle.len())
+        .position(|window| window == needle.as_bytes())?;
+    let rest = &collected[start + needle.len()..];
+    let line_end = rest.iter().position(|&byte| byte == b'\n')?;
+    let line = String::from_utf8_lossy(&rest[..line_end]);
+    let (status, cwd) = line.split_once(' ')?;
+    let exit_code = status.parse().ok()?;
+    Some((
+        String::from_utf8_lossy(&collected[..start]).into_owned(),
+        exit_code,
+        PathBuf::from(cwd),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_marker_waits_for_the_whole_marker_line() {
+        let marker = "__codex_shell_session_test__";
+        assert_eq!(
+            parse_marker(b"hello\n\n__codex_shell_session_test__ 0 /tm", marker),
+            None
+        );
+        assert_eq!(
+            parse_marker(
+                b"hello\n\n__codex_shell_session_test__ 2 /tmp/with space\n",
+                marker
+            ),
+            Some(("hello\n".to_string(), 2, PathBuf::from("/tmp/with space")))
+        );
+    }
+
+    #[test]
+    fn wrap_command_quotes_single_quotes() {
+        assert_eq!(
+            wrap_command("echo 'a b'", "M"),
+            "eval 'echo '\\''a b'\\''' </dev/null 2>&1\nprintf '\\n%s %s %s\\n' M \"$?\" \"$PWD\"\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn state_persists_between_commands() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let manager = ShellSessionManager::default();
+        let policy = SandboxPolicy::DangerFullAccess;
+        let spawn_env = || ExecEnv {
+            command: vec!["/bin/sh".to_string()],
+            cwd: dir.path().to_path_buf(),
+            env: std::env::vars().collect(),
+            expiration: crate::exec::ExecExpiration::DefaultTimeout,
+            sandbox: SandboxType::None,
+            sandbox_permissions: crate::sandboxing::SandboxPermissions::UseDefault,
+            justification: None,
+            arg0: None,
+        };
+        let timeout = Duration::from_secs(10);
+
+        manager
+            .run(
+                "mkdir sub && cd sub && export GREETING=hi && greet() { echo \"$GREETING from $(basename \"$PWD\")\"; }",
+                spawn_env(),
+                &policy,
+                timeout,
+            )
+            .await?;
+        let outcome = manager
+            .run(
+                "greet; exit_code() { return 3; }; exit_code",
+                spawn_env(),
+                &policy,
+                timeout,
+            )
+            .await?;
+        assert_eq!(
+            outcome,
+            ShellSessionOutcome {
+                output: "hi from sub\n".to_string(),
+                exit_code: Some(3),
+                timed_out: false,
+                note: None,
+            }
+        );
+        assert!(manager.cwd().await.is_some_and(|cwd| cwd.ends_with("sub")));
+
+        let exited = manager.run("exit 0", spawn_env(), &policy, timeout).await?;
+        assert_eq!(exited.exit_code, None);
+        assert_eq!(manager.cwd().await, None);
+        Ok(())
+    }
+}
@@ -2,12 +2,28 @@ use std::sync::Arc;
 
 use crate::AuthManager;
 use crate::RolloutRecorder;
+use crate::external_edits::ExternalEditTracker;
+use crate::lsp::LspManager;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::models_manager::manager::ModelsManager;
+use crate::permissions::PermissionStore;
+use crate::python_kernel::PythonKernelManager;
+use crate::redaction::Redactor;
+use crate::scratch_dir::ScratchDir;
+use crate::session_status::SessionStatusFile;
+use crate::shell_session::ShellSessionManager;
 use crate::skills::SkillsManager;
+use crate::test_runner::LastTestRun;
+use crate::tool_policy::ToolPolicy;
+use crate::tool_rules::ToolRules;
+use crate::tools::context::SharedTurnDiffTracker;
+use crate::tools::registry::MutatingToolCalls;
 use crate::tools::sandboxing::ApprovalStore;
+use crate::unified_exec::BackgroundShellManager;
+use crate::unified_exec::TerminalManager;
 use crate::unified_exec::UnifiedExecSessionManager;
 use crate::user_notification::UserNotifier;
+use crate::web::WebCache;
 use codex_otel::otel_manager::OtelManager;
 use tokio::sync::Mutex;
 use tokio::sync::RwLock;
@@ -17,13 +33,51 @@ pub(crate) struct SessionServices {
     pub(crate) mcp_connection_manager: Arc<RwLock<McpConnectionManager>>,
     pub(crate) mcp_startup_cancellation_token: CancellationToken,
     pub(crate) unified_exec_manager: UnifiedExecSessionManager,
+    pub(crate) background_shells: BackgroundShellManager,
+    pub(crate) terminals: TerminalManager,
+    pub(crate) shell_session: ShellSessionManager,
+    pub(crate) python_kernel: PythonKernelManager,
+    /// Language servers from `[lsp]`, started on the first matching edit.
+    pub(crate) lsp: LspManager,
+    /// Failures of the last `run_tests` call, for `rerun_failed`.
+    pub(crate) last_test_run: Mutex<Option<LastTestRun>>,
+    /// Directory for throwaway files; `None` unless `scratch_dir` is enabled.
+    pub(crate) scratch_dir: Option<ScratchDir>,
+    /// Pages and robots.txt rules fetched by `fetch_url` this session.
+    pub(crate) web_cache: WebCache,
     pub(crate) notifier: UserNotifier,
     pub(crate) rollout: Mutex<Option<RolloutRecorder>>,
     pub(crate) user_shell: Arc<crate::shell::Shell>,
     pub(crate) show_raw_agent_reasoning: bool,
+    /// `false` when the provider forbids storing reasoning in session files.
+    pub(crate) persist_reasoning: bool,
     pub(crate) auth_manager: Arc<AuthManager>,
     pub(crate) models_manager: Arc<ModelsManager>,
     pub(crate) otel_manager: OtelManager,
     pub(crate) tool_approvals: Mutex<ApprovalStore>,
     pub(crate) skills_manager: Arc<SkillsManager>,
+    /// Baselines every file a patch touched since the session started, so the
+    /// end of each turn can report the cumulative diff.
+    pub(crate) session_diff_tracker: SharedTurnDiffTracker,
+    /// Files the model read or edited, for `external_edits` notices.
+    pub(crate) external_edits: ExternalEditTracker,
+    /// Masks secrets in recorded items; `None` when `[redaction]` is off.
+    pub(crate) redactor: Option<Redactor>,
+    /// Command prefixes that skip the approval prompt: those from the config
+    /// plus any approved for the project during this session.
+    pub(crate) approved_command_prefixes: RwLock<Vec<Vec<String>>>,
+    /// Write globs and network hosts granted for the project, from config and
+    /// from approval prompts.
+    pub(crate) permissions: RwLock<PermissionStore>,
+    /// Compiled `[[tool_rules]]`, checked by the router before each call.
+    pub(crate) tool_rules: ToolRules,
+    /// The `[tool_policy]` engine, asked alongside the rules; `None` when
+    /// none is configured.
+    pub(crate) tool_policy: Option<ToolPolicy>,
+    /// Mutating tool calls in flight. Closed at shutdown, after which no new
+    /// tool calls start.
+    pub(crate) mutating_tool_calls: MutatingToolCalls,
+    /// `$CODEX_HOME/status/<id>.json`, read by `codex status`; `None` for
+    /// sub-agents.
+    pub(crate) status_file: Option<SessionStatusFile>,
 }
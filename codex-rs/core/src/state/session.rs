@@ -1,10 +1,18 @@
 //! Session-wide mutable state.
 
+use std::time::Instant;
+
 use codex_protocol::models::ResponseItem;
+use codex_protocol::openai_models::ReasoningEffort as ReasoningEffortConfig;
 
 use crate::codex::SessionConfiguration;
 use crate::context_manager::ContextManager;
+use crate::pinned_context::PinnedContext;
+use crate::project_doc::InstructionFiles;
+use crate::protocol::BudgetExceededEvent;
 use crate::protocol::RateLimitSnapshot;
+use crate::protocol::SessionSummaryItem;
+use crate::protocol::SpendSummary;
 use crate::protocol::TokenUsage;
 use crate::protocol::TokenUsageInfo;
 use crate::truncate::TruncationPolicy;
@@ -14,6 +22,29 @@ pub(crate) struct SessionState {
     pub(crate) session_configuration: SessionConfiguration,
     pub(crate) history: ContextManager,
     pub(crate) latest_rate_limits: Option<RateLimitSnapshot>,
+    pub(crate) pinned_context: PinnedContext,
+    pub(crate) spend: SpendSummary,
+    /// Model requests issued so far, counted against `budget.max_turns`.
+    pub(crate) model_requests: u32,
+    /// Set once a session-wide budget is exhausted; further requests are refused.
+    pub(crate) budget_exceeded: Option<BudgetExceededEvent>,
+    /// Instruction files as of the last time the user instructions were built.
+    pub(crate) instruction_files: InstructionFiles,
+    /// Contents of `$CODEX_HOME/AGENTS.md` behind the current instructions.
+    pub(crate) global_instructions: Option<String>,
+    /// When the last submission arrived or the last task finished.
+    pub(crate) last_activity: Instant,
+    /// Set while MCP servers are stopped because the session went idle.
+    pub(crate) idle_suspended: bool,
+    /// Effort for the next task only, from `Op::OverrideNextTurnReasoning`.
+    pub(crate) next_turn_reasoning_effort: Option<ReasoningEffortConfig>,
+    /// The highest `context_pressure_thresholds` entry reported so far; it
+    /// drops again when compaction or trimming frees up the context.
+    pub(crate) context_pressure_reported: Option<u8>,
+    /// The title and summary last written to the rollout.
+    pub(crate) session_summary: Option<SessionSummaryItem>,
+    /// Consecutive `apply_patch_verify` failures, with the turn they belong to.
+    pub(crate) patch_verify_failures: Option<(String, u32)>,
 }
 
 impl SessionState {
@@ -24,6 +55,18 @@ impl SessionState {
             session_configuration,
             history,
             latest_rate_limits: None,
+            pinned_context: PinnedContext::default(),
+            spend: SpendSummary::default(),
+            model_requests: 0,
+            budget_exceeded: None,
+            instruction_files: InstructionFiles::default(),
+            global_instructions: None,
+            last_activity: Instant::now(),
+            idle_suspended: false,
+            next_turn_reasoning_effort: None,
+            context_pressure_reported: None,
+            session_summary: None,
+            patch_verify_failures: None,
         }
     }
 
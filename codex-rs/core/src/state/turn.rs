@@ -14,6 +14,7 @@ use tokio::sync::oneshot;
 use crate::codex::TurnContext;
 use crate::protocol::ReviewDecision;
 use crate::tasks::SessionTask;
+use crate::tools::loop_detection::ToolCallLoopDetector;
 
 /// Metadata about the currently running turn.
 pub(crate) struct ActiveTurn {
@@ -68,6 +69,12 @@ impl ActiveTurn {
 pub(crate) struct TurnState {
     pending_approvals: HashMap<String, oneshot::Sender<ReviewDecision>>,
     pending_input: Vec<ResponseInputItem>,
+    /// Cancels the model request currently in flight, without ending the
+    /// task, so a steer can restart it.
+    request_cancellation: Option<CancellationToken>,
+    steered: bool,
+    /// Recent tool calls of the task, for loop detection.
+    tool_calls: ToolCallLoopDetector,
 }
 
 impl TurnState {
@@ -104,6 +111,33 @@ impl TurnState {
             ret
         }
     }
+
+    pub(crate) fn begin_request(&mut self, cancellation_token: CancellationToken) {
+        self.request_cancellation = Some(cancellation_token);
+        self.steered = false;
+    }
+
+    /// Queues `input` and cancels the in-flight request. Returns whether a
+    /// request was running to cancel.
+    pub(crate) fn steer(&mut self, input: ResponseInputItem) -> bool {
+        self.pending_input.push(input);
+        match self.request_cancellation.take() {
+            Some(cancellation_token) => {
+                self.steered = true;
+                cancellation_token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn steered(&self) -> bool {
+        self.steered
+    }
+
+    pub(crate) fn tool_calls_mut(&mut self) -> &mut ToolCallLoopDetector {
+        &mut self.tool_calls
+    }
 }
 
 impl ActiveTurn {
@@ -0,0 +1,541 @@
+//! Encryption at rest for `history.jsonl` and session rollouts.
+//!
+//! Both files are JSON Lines. With `[storage_encryption]` enabled each line is
+//! written as `enc1:<key id>:<base64 nonce and ciphertext>`, sealed with
+//! ChaCha20-Poly1305, so appends and history pruning keep working line by
+//! line. [`decode_line`] decrypts such lines and passes plaintext ones
+//! through, which keeps files written before encryption was enabled readable.
+//!
+//! The key is either a random key stored in the OS keychain or derived from
+//! the `CODEX_STORAGE_PASSPHRASE` environment variable. Which one, together
+//! with the key id and passphrase salt, is recorded in
+//! `$CODEX_HOME/storage-key.json` when the key is first created; the key
+//! itself is never written to disk.
+
+use std::borrow::Cow;
+use std::io;
+use std::io::Write;
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use codex_keyring_store::DefaultKeyringStore;
+use codex_keyring_store::KeyringStore;
+use ring::aead::Aad;
+use ring::aead::CHACHA20_POLY1305;
+use ring::aead::LessSafeKey;
+use ring::aead::NONCE_LEN;
+use ring::aead::Nonce;
+use ring::aead::UnboundKey;
+use ring::pbkdf2;
+use ring::rand::SecureRandom;
+use ring::rand::SystemRandom;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::config::Config;
+use crate::config::find_codex_home;
+use crate::config::types::StorageKeySource;
+
+/// Environment variable holding the passphrase for `key_source = "passphrase"`.
+pub const PASSPHRASE_ENV_VAR: &str = "CODEX_STORAGE_PASSPHRASE";
+
+const LINE_PREFIX: &str = "enc1:";
+const KEY_FILE: &str = "storage-key.json";
+const KEYRING_SERVICE: &str = "Codex Storage";
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const PBKDF2_ITERATIONS: u32 = 600_000;
+/// How long to wait for the keychain entry of a key another process has just
+/// recorded, which it saves right after the key file.
+const KEYCHAIN_RETRIES: usize = 20;
+const KEYCHAIN_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Ciphers loaded by this process, so readers can find the key for a line by
+/// its id without going back to the keychain.
+static LOADED: LazyLock<Mutex<Vec<Arc<StorageCipher>>>> = LazyLock::new(Mutex::default);
+
+/// What `storage-key.json` records about the key.
+#[derive(Debug, Serialize, Deserialize)]
+struct KeyFile {
+    source: StorageKeySource,
+    key_id: String,
+    /// Base64 PBKDF2 salt, for passphrase-derived keys.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    salt: Option<String>,
+}
+
+pub struct StorageCipher {
+    key_id: String,
+    key: LessSafeKey,
+}
+
+impl std::fmt::Debug for StorageCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StorageCipher")
+            .field("key_id", &self.key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StorageCipher {
+    fn new(key: &[u8; KEY_LEN]) -> io::Result<Self> {
+        let unbound = UnboundKey::new(&CHACHA20_POLY1305, key)
+            .map_err(|_| io::Error::other("invalid storage encryption key"))?;
+        Ok(Self {
+            key_id: key_id(key),
+            key: LessSafeKey::new(unbound),
+        })
+    }
+
+    /// Seals one line; `line` must not contain the trailing newline.
+    pub fn encrypt_line(&self, line: &str) -> io::Result<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| io::Error::other("failed to generate a nonce"))?;
+        let mut sealed = line.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::empty(),
+                &mut sealed,
+            )
+            .map_err(|_| io::Error::other("failed to encrypt line"))?;
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&sealed);
+        Ok(format!(
+            "{LINE_PREFIX}{}:{}",
+            self.key_id,
+            BASE64.encode(payload)
+        ))
+    }
+
+    /// Opens a line sealed by [`Self::encrypt_line`] with this key.
+    pub(crate) fn decrypt_line(&self, line: &str) -> io::Result<String> {
+        let (id, payload) = line
+            .strip_prefix(LINE_PREFIX)
+            .and_then(|encrypted| encrypted.split_once(':'))
+            .ok_or_else(|| invalid_data("value is not an encrypted line"))?;
+        if id != self.key_id {
+            return Err(invalid_data(format!(
+                "value was encrypted with key {id}, but the configured key is {}",
+                self.key_id
+            )));
+        }
+        self.decrypt(payload)
+    }
+
+    fn decrypt(&self, payload: &str) -> io::Result<String> {
+        let payload = BASE64
+            .decode(payload)
+            .map_err(|e| invalid_data(format!("malformed encrypted line: {e}")))?;
+        if payload.len() < NONCE_LEN {
+            return Err(invalid_data("malformed encrypted line: too short"));
+        }
+        let (nonce, sealed) = payload.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| invalid_data("malformed encrypted line: bad nonce"))?;
+        let mut sealed = sealed.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut sealed)
+            .map_err(|_| invalid_data("encrypted line failed authentication"))?;
+        String::from_utf8(plaintext.to_vec()).map_err(|e| invalid_data(e.to_string()))
+    }
+}
+
+/// Cipher for new history and rollout lines, or `None` when encryption is
+/// disabled. Creates the key the first time it is needed.
+pub fn writer_cipher(config: &Config) -> io::Result<Option<Arc<StorageCipher>>> {
+    if !config.storage_encryption.enabled {
+        return Ok(None);
+    }
+    let passphrase = std::env::var(PASSPHRASE_ENV_VAR).ok();
+    load_or_create(
+        &config.codex_home,
+        config.storage_encryption.key_source,
+        &DefaultKeyringStore,
+        passphrase.as_deref(),
+    )
+    .map(Some)
+}
+
+/// Cipher for secrets stored outside the OS keychain, such as `codex auth`
+/// entries that fall back to a file. Reuses the storage key when one exists;
+/// otherwise the key is derived from `passphrase`, since these secrets only
+/// end up in a file when the keychain is unavailable or not wanted.
+pub(crate) fn secrets_cipher(
+    codex_home: &Path,
+    keyring: &dyn KeyringStore,
+    passphrase: Option<&str>,
+) -> io::Result<Arc<StorageCipher>> {
+    load_or_create(
+        codex_home,
+        StorageKeySource::Passphrase,
+        keyring,
+        passphrase,
+    )
+}
+
+/// Returns `line` decrypted when it was written encrypted, or unchanged
+/// otherwise. The key is looked up among those this process already loaded,
+/// then in the Codex home.
+pub fn decode_line(line: &str) -> io::Result<Cow<'_, str>> {
+    let Some(encrypted) = line.strip_prefix(LINE_PREFIX) else {
+        return Ok(Cow::Borrowed(line));
+    };
+    let (id, payload) = encrypted
+        .split_once(':')
+        .ok_or_else(|| invalid_data("malformed encrypted line: missing key id"))?;
+    let cipher = match loaded(id) {
+        Some(cipher) => cipher,
+        None => {
+            let passphrase = std::env::var(PASSPHRASE_ENV_VAR).ok();
+            let cipher = load_existing(
+                &find_codex_home()?,
+                &DefaultKeyringStore,
+                passphrase.as_deref(),
+            )?;
+            if cipher.key_id != id {
+                return Err(invalid_data(format!(
+                    "line was encrypted with key {id}, but the configured key is {}",
+                    cipher.key_id
+                )));
+            }
+            cipher
+        }
+    };
+    cipher.decrypt(payload).map(Cow::Owned)
+}
+
+fn load_or_create(
+    codex_home: &Path,
+    source: StorageKeySource,
+    keyring: &dyn KeyringStore,
+    passphrase: Option<&str>,
+) -> io::Result<Arc<StorageCipher>> {
+    if codex_home.join(KEY_FILE).exists() {
+        return load_existing(codex_home, keyring, passphrase);
+    }
+
+    let rng = SystemRandom::new();
+    let (key, salt) = match source {
+        StorageKeySource::Keyring => {
+            let mut key = [0u8; KEY_LEN];
+            rng.fill(&mut key)
+                .map_err(|_| io::Error::other("failed to generate a storage key"))?;
+            (key, None)
+        }
+        StorageKeySource::Passphrase => {
+            let passphrase = require_passphrase(passphrase)?;
+            let mut salt = [0u8; SALT_LEN];
+            rng.fill(&mut salt)
+                .map_err(|_| io::Error::other("failed to generate a salt"))?;
+            (derive_key(passphrase, &salt), Some(BASE64.encode(salt)))
+        }
+    };
+
+    let cipher = StorageCipher::new(&key)?;
+    let key_file = KeyFile {
+        source,
+        key_id: cipher.key_id.clone(),
+        salt,
+    };
+    let contents = serde_json::to_string_pretty(&key_file)?;
+    std::fs::create_dir_all(codex_home)?;
+    // The key file decides which key wins when several processes start at
+    // once, so the keychain is only written by the process that created it.
+    match write_key_file(codex_home, &contents) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+            return load_key_created_elsewhere(codex_home, keyring, passphrase);
+        }
+        Err(err) => return Err(err),
+    }
+    if source == StorageKeySource::Keyring
+        && let Err(err) = keyring.save(
+            KEYRING_SERVICE,
+            &keyring_account(codex_home),
+            &BASE64.encode(key),
+        )
+    {
+        // Without its key the file would make every later start fail.
+        let _ = std::fs::remove_file(codex_home.join(KEY_FILE));
+        return Err(io::Error::other(format!(
+            "failed to save storage key: {err}"
+        )));
+    }
+    Ok(remember(cipher))
+}
+
+/// Creates `storage-key.json` with owner-only permissions, failing with
+/// `AlreadyExists` instead of replacing a file another process created.
+fn write_key_file(codex_home: &Path, contents: &str) -> io::Result<()> {
+    let mut tmp = tempfile::NamedTempFile::new_in(codex_home)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        tmp.as_file()
+            .set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    tmp.write_all(contents.as_bytes())?;
+    tmp.as_file().sync_all()?;
+    tmp.persist_noclobber(codex_home.join(KEY_FILE))
+        .map_err(|err| err.error)?;
+    Ok(())
+}
+
+/// Loads the key another process recorded while this one was creating its
+/// own, waiting briefly for that process to save it to the keychain.
+fn load_key_created_elsewhere(
+    codex_home: &Path,
+    keyring: &dyn KeyringStore,
+    passphrase: Option<&str>,
+) -> io::Result<Arc<StorageCipher>> {
+    for _ in 0..KEYCHAIN_RETRIES {
+        match load_existing(codex_home, keyring, passphrase) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                std::thread::sleep(KEYCHAIN_RETRY_DELAY);
+            }
+            result => return result,
+        }
+    }
+    load_existing(codex_home, keyring, passphrase)
+}
+
+fn load_existing(
+    codex_home: &Path,
+    keyring: &dyn KeyringStore,
+    passphrase: Option<&str>,
+) -> io::Result<Arc<StorageCipher>> {
+    let path = codex_home.join(KEY_FILE);
+    let contents = std::fs::read_to_string(&path).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("failed to read storage key file {}: {e}", path.display()),
+        )
+    })?;
+    let key_file: KeyFile = serde_json::from_str(&contents)
+        .map_err(|e| invalid_data(format!("invalid {}: {e}", path.display())))?;
+    if let Some(cipher) = loaded(&key_file.key_id) {
+        return Ok(cipher);
+    }
+
+    let key = match key_file.source {
+        StorageKeySource::Keyring => {
+            let encoded = keyring
+                .load(KEYRING_SERVICE, &keyring_account(codex_home))
+                .map_err(|e| io::Error::other(format!("failed to load storage key: {e}")))?
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        "storage key is missing from the OS keychain",
+                    )
+                })?;
+            BASE64
+                .decode(encoded)
+                .ok()
+                .and_then(|key| <[u8; KEY_LEN]>::try_from(key).ok())
+                .ok_or_else(|| invalid_data("storage key in the OS keychain is malformed"))?
+        }
+        StorageKeySource::Passphrase => {
+            let passphrase = require_passphrase(passphrase)?;
+            let salt = key_file
+                .salt
+                .as_deref()
+                .and_then(|salt| BASE64.decode(salt).ok())
+                .ok_or_else(|| invalid_data(format!("{} has no valid salt", path.display())))?;
+            derive_key(passphrase, &salt)
+        }
+    };
+
+    let cipher = StorageCipher::new(&key)?;
+    if cipher.key_id != key_file.key_id {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            match key_file.source {
+                StorageKeySource::Keyring => {
+                    format!("the OS keychain key does not match the one recorded in {KEY_FILE}")
+                }
+                StorageKeySource::Passphrase => {
+                    format!("{PASSPHRASE_ENV_VAR} does not match the key recorded in {KEY_FILE}")
+                }
+            },
+        ));
+    }
+    Ok(remember(cipher))
+}
+
+fn require_passphrase(passphrase: Option<&str>) -> io::Result<&str> {
+    passphrase.filter(|p| !p.is_empty()).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("set {PASSPHRASE_ENV_VAR} to read or write encrypted Codex files"),
+        )
+    })
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> [u8; KEY_LEN] {
+    let mut key = [0u8; KEY_LEN];
+    let iterations = NonZeroU32::new(PBKDF2_ITERATIONS).unwrap_or(NonZeroU32::MIN);
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    key
+}
+
+fn key_id(key: &[u8]) -> String {
+    let digest = Sha256::digest(key);
+    let hex = format!("{digest:x}");
+    hex.get(..16).unwrap_or(&hex).to_string()
+}
+
+fn keyring_account(codex_home: &Path) -> String {
+    let canonical = codex_home
+        .canonicalize()
+        .unwrap_or_else(|_| codex_home.to_path_buf());
+    let digest = Sha256::digest(canonical.to_string_lossy().as_bytes());
+    let hex = format!("{digest:x}");
+    format!("storage|{}", hex.get(..16).unwrap_or(&hex))
+}
+
+fn loaded(id: &str) -> Option<Arc<StorageCipher>> {
+    LOADED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .find(|cipher| cipher.key_id == id)
+        .cloned()
+}
+
+fn remember(cipher: StorageCipher) -> Arc<StorageCipher> {
+    let mut loaded = LOADED.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(existing) = loaded.iter().find(|c| c.key_id == cipher.key_id) {
+        return Arc::clone(existing);
+    }
+    let cipher = Arc::new(cipher);
+    loaded.push(Arc::clone(&cipher));
+    cipher
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_keyring_store::tests::MockKeyringStore;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn keyring_key_round_trips_lines() {
+        let home = TempDir::new().expect("tempdir");
+        let keyring = MockKeyringStore::default();
+        let cipher = load_or_create(home.path(), StorageKeySource::Keyring, &keyring, None)
+            .expect("create key");
+        assert!(keyring.contains(&keyring_account(home.path())));
+
+        let line = r#"{"session_id":"s","ts":1,"text":"secret plans"}"#;
+        let encrypted = cipher.encrypt_line(line).expect("encrypt");
+        assert!(encrypted.starts_with(&format!("enc1:{}:", cipher.key_id)));
+        assert!(!encrypted.contains("secret plans"));
+        assert_eq!(decode_line(&encrypted).expect("decrypt"), line);
+        assert_eq!(decode_line(line).expect("plaintext"), line);
+
+        let reloaded = load_existing(home.path(), &keyring, None).expect("reload key");
+        assert_eq!(reloaded.key_id, cipher.key_id);
+    }
+
+    #[test]
+    fn passphrase_must_match_the_recorded_key() {
+        let home = TempDir::new().expect("tempdir");
+        let keyring = MockKeyringStore::default();
+        let err = load_or_create(home.path(), StorageKeySource::Passphrase, &keyring, None)
+            .expect_err("passphrase required");
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        // Record a passphrase key without loading it, as a previous run would.
+        let salt = [3u8; SALT_LEN];
+        let cipher = StorageCipher::new(&derive_key("correct horse", &salt)).expect("cipher");
+        let key_file = KeyFile {
+            source: StorageKeySource::Passphrase,
+            key_id: cipher.key_id.clone(),
+            salt: Some(BASE64.encode(salt)),
+        };
+        std::fs::write(
+            home.path().join(KEY_FILE),
+            serde_json::to_string(&key_file).expect("serialize key file"),
+        )
+        .expect("write key file");
+
+        let err = load_existing(home.path(), &keyring, Some("wrong horse"))
+            .expect_err("wrong passphrase");
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+        let loaded = load_existing(home.path(), &keyring, Some("correct horse"))
+            .expect("correct passphrase");
+        assert_eq!(loaded.key_id, cipher.key_id);
+    }
+
+    #[test]
+    fn key_file_is_private_and_never_replaced() {
+        let home = TempDir::new().expect("tempdir");
+        write_key_file(home.path(), "first").expect("create key file");
+        let err = write_key_file(home.path(), "second").expect_err("file exists");
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        let path = home.path().join(KEY_FILE);
+        assert_eq!(std::fs::read_to_string(&path).expect("read"), "first");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path)
+                .expect("metadata")
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn losing_the_creation_race_loads_the_recorded_key() {
+        let home = TempDir::new().expect("tempdir");
+        let keyring = MockKeyringStore::default();
+        let winner = load_or_create(home.path(), StorageKeySource::Keyring, &keyring, None)
+            .expect("create key");
+        let saved = keyring.saved_value(&keyring_account(home.path()));
+
+        let loser = load_key_created_elsewhere(home.path(), &keyring, None).expect("load key");
+        assert_eq!(loser.key_id, winner.key_id);
+        assert_eq!(keyring.saved_value(&keyring_account(home.path())), saved);
+    }
+
+    #[test]
+    fn tampered_lines_fail_authentication() {
+        let key = [7u8; KEY_LEN];
+        let cipher = StorageCipher::new(&key).expect("cipher");
+        let encrypted = cipher.encrypt_line("hello").expect("encrypt");
+        let (_, payload) = encrypted.rsplit_once(':').expect("payload");
+        let mut bytes = BASE64.decode(payload).expect("base64");
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        let err = cipher
+            .decrypt(&BASE64.encode(bytes))
+            .expect_err("tampered line");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
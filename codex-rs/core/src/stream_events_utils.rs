@@ -10,6 +10,8 @@ use crate::error::CodexErr;
 use crate::error::Result;
 use crate::function_tool::FunctionCallError;
 use crate::parse_turn_item;
+use crate::protocol::EventMsg;
+use crate::tools::context::ToolPayload;
 use crate::tools::parallel::ToolCallRuntime;
 use crate::tools::router::ToolRouter;
 use codex_protocol::models::FunctionCallOutputPayload;
@@ -50,19 +52,47 @@ pub(crate) async fn handle_output_item_done(
     match ToolRouter::build_tool_call(ctx.sess.as_ref(), item.clone()).await {
         // The model emitted a tool call; log it, persist the item immediately, and queue the tool execution.
         Ok(Some(call)) => {
-            let payload_preview = call.payload.log_payload().into_owned();
+            let payload_preview = call
+                .payload
+                .telemetry_payload(
+                    &call.tool_name,
+                    &ctx.turn_context.client.config().otel.tool_payloads,
+                )
+                .into_owned();
             tracing::info!("ToolCall: {} {}", call.tool_name, payload_preview);
 
             ctx.sess
                 .record_conversation_items(&ctx.turn_context, std::slice::from_ref(&item))
                 .await;
 
-            let cancellation_token = ctx.cancellation_token.child_token();
-            let tool_future: InFlightFuture<'static> = Box::pin(
-                ctx.tool_runtime
-                    .clone()
-                    .handle_tool_call(call, cancellation_token),
-            );
+            // A call that continues a loop is answered without running it.
+            let tool_future: InFlightFuture<'static> = match ctx.sess.observe_tool_call(&call).await
+            {
+                Some(found) => {
+                    if found.newly_detected {
+                        ctx.sess
+                            .send_event(
+                                &ctx.turn_context,
+                                EventMsg::ToolCallLoopDetected(found.event()),
+                            )
+                            .await;
+                    }
+                    let response = ToolRouter::failure_response(
+                        call.call_id,
+                        matches!(call.payload, ToolPayload::Custom { .. }),
+                        FunctionCallError::RespondToModel(found.model_message()),
+                    );
+                    Box::pin(async move { Ok(response) })
+                }
+                None => {
+                    let cancellation_token = ctx.cancellation_token.child_token();
+                    Box::pin(
+                        ctx.tool_runtime
+                            .clone()
+                            .handle_tool_call(call, cancellation_token),
+                    )
+                }
+            };
 
             output.needs_follow_up = true;
             output.tool_future = Some(tool_future);
@@ -91,10 +121,11 @@ pub(crate) async fn handle_output_item_done(
         // Guardrail: the model issued a LocalShellCall without an id; surface the error back into history.
         Err(FunctionCallError::MissingLocalShellCallId) => {
             let msg = "LocalShellCall without call_id or id";
-            ctx.turn_context
-                .client
-                .get_otel_manager()
-                .log_tool_failed("local_shell", msg);
+            ctx.turn_context.client.get_otel_manager().log_tool_failed(
+                "local_shell",
+                FunctionCallError::MissingLocalShellCallId.kind(),
+                msg,
+            );
             tracing::error!(msg);
 
             let response = ResponseInputItem::FunctionCallOutput {
@@ -118,13 +149,16 @@ pub(crate) async fn handle_output_item_done(
 
             output.needs_follow_up = true;
         }
+        // A fatal error occurred; surface it back into history.
+        Err(FunctionCallError::Fatal(message)) => {
+            return Err(CodexErr::Fatal(message));
+        }
         // The tool request should be answered directly (or was denied); push that response into the transcript.
-        Err(FunctionCallError::RespondToModel(message))
-        | Err(FunctionCallError::Denied(message)) => {
+        Err(err) => {
             let response = ResponseInputItem::FunctionCallOutput {
                 call_id: String::new(),
                 output: FunctionCallOutputPayload {
-                    content: message,
+                    content: err.model_message(),
                     ..Default::default()
                 },
             };
@@ -142,10 +176,6 @@ pub(crate) async fn handle_output_item_done(
 
             output.needs_follow_up = true;
         }
-        // A fatal error occurred; surface it back into history.
-        Err(FunctionCallError::Fatal(message)) => {
-            return Err(CodexErr::Fatal(message));
-        }
     }
 
     Ok(output)
@@ -0,0 +1,488 @@
+//! Task bundles: a prompt plus the setup it was written for, in one TOML
+//! file that a teammate or CI job can run with `codex run --bundle`.
+//!
+//! A bundle records the model settings to use, context to pin before the
+//! first turn, and what the machine needs to provide: commands on `PATH`,
+//! environment variables and, optionally, the git commit the task was
+//! written against. Pinned files are referenced by path and checksum rather
+//! than copied, so a bundle stays small and notices when the file drifted.
+
+use std::io;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::config_types::ReasoningSummary;
+use codex_protocol::config_types::SandboxMode;
+use codex_protocol::config_types::Verbosity;
+use codex_protocol::openai_models::ReasoningEffort;
+use codex_protocol::protocol::PinnedContextItem;
+use codex_protocol::protocol::SandboxPolicy;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::config::Config;
+use crate::git_info::collect_git_info;
+use crate::hooks::parse_version;
+
+/// Version of the bundle format written by this Codex. Bump it when a
+/// bundle written now would be misread by an older Codex.
+pub const TASK_BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Extension `codex bundle create` suggests for bundle files.
+pub const TASK_BUNDLE_EXTENSION: &str = "codexbundle";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskBundle {
+    pub format_version: u32,
+    /// Codex version that created the bundle.
+    pub codex_version: String,
+    /// The first message of the session.
+    pub prompt: String,
+    #[serde(default)]
+    pub profile: BundleProfile,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned: Vec<BundlePin>,
+    #[serde(default)]
+    pub requirements: BundleRequirements,
+}
+
+/// Settings applied as `-c` overrides; anything passed on the command line
+/// still wins. `sandbox_mode` is applied separately, see
+/// [`TaskBundle::sandbox_mode`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BundleProfile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_provider: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_reasoning_effort: Option<ReasoningEffort>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_reasoning_summary: Option<ReasoningSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_verbosity: Option<Verbosity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sandbox_mode: Option<SandboxMode>,
+}
+
+impl BundleProfile {
+    /// The settings `config` resolved to, including its profile and overrides.
+    pub fn from_config(config: &Config) -> Self {
+        let sandbox_mode = sandbox_mode_of(config.sandbox_policy.get());
+        Self {
+            model: config.model.clone(),
+            model_provider: Some(config.model_provider_id.clone()),
+            model_reasoning_effort: config.model_reasoning_effort,
+            model_reasoning_summary: Some(config.model_reasoning_summary),
+            model_verbosity: config.model_verbosity,
+            sandbox_mode,
+        }
+    }
+}
+
+/// Context pinned before the first turn, like `/pin`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BundlePin {
+    /// A file relative to the working directory, with the SHA-256 of its
+    /// contents when the bundle was created.
+    File {
+        path: PathBuf,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        sha256: Option<String>,
+    },
+    Instruction {
+        text: String,
+    },
+    Message {
+        text: String,
+    },
+}
+
+impl BundlePin {
+    /// Pins `path`, resolved against `cwd`, with the checksum of its current
+    /// contents.
+    pub fn file(cwd: &Path, path: &Path) -> io::Result<Self> {
+        if leaves_cwd(path) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} must be relative to the working directory and stay inside it",
+                    path.display()
+                ),
+            ));
+        }
+        let contents = std::fs::read(cwd.join(path))?;
+        Ok(Self::File {
+            path: path.to_path_buf(),
+            sha256: Some(sha256_hex(&contents)),
+        })
+    }
+
+    fn into_pinned_item(self) -> PinnedContextItem {
+        match self {
+            Self::File { path, .. } => PinnedContextItem::File { path },
+            Self::Instruction { text } => PinnedContextItem::Instruction { text },
+            Self::Message { text } => PinnedContextItem::Message { text },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BundleRequirements {
+    /// Programs that must be on `PATH`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<String>,
+    /// Environment variables that must be set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<String>,
+    /// Commit the task was written against. Running elsewhere only warns.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_commit: Option<String>,
+}
+
+/// What [`TaskBundle::check`] found.
+#[derive(Debug, Default, PartialEq)]
+pub struct BundleCheck {
+    /// Unmet requirements; the bundle should not run.
+    pub problems: Vec<String>,
+    /// Differences that may change the outcome.
+    pub warnings: Vec<String>,
+}
+
+impl TaskBundle {
+    pub fn new(prompt: String, profile: BundleProfile) -> Self {
+        Self {
+            format_version: TASK_BUNDLE_FORMAT_VERSION,
+            codex_version: env!("CARGO_PKG_VERSION").to_string(),
+            prompt,
+            profile,
+            pinned: Vec::new(),
+            requirements: BundleRequirements::default(),
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        toml::from_str(&text).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is not a valid task bundle: {err}", path.display()),
+            )
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let text = toml::to_string_pretty(self).map_err(io::Error::other)?;
+        std::fs::write(path, text)
+    }
+
+    /// `-c` style overrides for the bundle's profile, except `sandbox_mode`.
+    pub fn config_overrides(&self) -> Vec<(String, toml::Value)> {
+        match toml::Value::try_from(&self.profile) {
+            Ok(toml::Value::Table(table)) => table
+                .into_iter()
+                .filter(|(key, _)| key != "sandbox_mode")
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The sandbox mode to run the bundle with, given `local`, the mode the
+    /// local config resolves to (`None` for an external sandbox). A bundle
+    /// may tighten the sandbox, but a bundle from someone else must not be
+    /// able to loosen it, so a looser mode is refused unless `allow_looser`
+    /// is set. `Ok(None)` leaves the local mode in place.
+    pub fn sandbox_mode(
+        &self,
+        local: Option<SandboxMode>,
+        allow_looser: bool,
+    ) -> Result<Option<SandboxMode>, String> {
+        let Some(wanted) = self.profile.sandbox_mode else {
+            return Ok(None);
+        };
+        match local {
+            Some(local) if local == wanted => Ok(None),
+            Some(local) if strictness(wanted) > strictness(local) => Ok(Some(wanted)),
+            _ if allow_looser => Ok(Some(wanted)),
+            Some(local) => Err(format!(
+                "the bundle asks for sandbox_mode {wanted}, which is looser than your {local}; pass --allow-bundle-sandbox to allow it"
+            )),
+            None => Err(format!(
+                "the bundle asks for sandbox_mode {wanted}, but Codex runs in an external sandbox; pass --allow-bundle-sandbox to allow it"
+            )),
+        }
+    }
+
+    /// The items to pin before the first turn.
+    pub fn pinned_items(&self) -> Vec<PinnedContextItem> {
+        self.pinned
+            .iter()
+            .cloned()
+            .map(BundlePin::into_pinned_item)
+            .collect()
+    }
+
+    /// Checks the bundle against this machine, with `cwd` as the working
+    /// directory the task will run in.
+    pub async fn check(&self, cwd: &Path) -> BundleCheck {
+        let mut check = BundleCheck::default();
+        if self.format_version > TASK_BUNDLE_FORMAT_VERSION {
+            check.problems.push(format!(
+                "the bundle uses format version {}; this Codex reads version {TASK_BUNDLE_FORMAT_VERSION}",
+                self.format_version
+            ));
+        }
+        let current = env!("CARGO_PKG_VERSION");
+        if let (Some(created), Some(running)) =
+            (parse_version(&self.codex_version), parse_version(current))
+            && running != (0, 0, 0)
+            && running < created
+        {
+            check.warnings.push(format!(
+                "the bundle was created with Codex {}; this is {current}",
+                self.codex_version
+            ));
+        }
+
+        for command in &self.requirements.commands {
+            if which::which(command).is_err() {
+                check
+                    .problems
+                    .push(format!("`{command}` is not installed or not on PATH"));
+            }
+        }
+        for var in &self.requirements.env {
+            if std::env::var_os(var).is_none() {
+                check
+                    .problems
+                    .push(format!("environment variable {var} is not set"));
+            }
+        }
+        if let Some(expected) = &self.requirements.git_commit {
+            let head = collect_git_info(cwd)
+                .await
+                .and_then(|info| info.commit_hash);
+            match head {
+                Some(head) if head.starts_with(expected.as_str()) => {}
+                Some(head) => check.warnings.push(format!(
+                    "the bundle was written against commit {expected}; HEAD is {head}"
+                )),
+                None => check.warnings.push(format!(
+                    "the bundle was written against commit {expected}, but {} is not a git checkout",
+                    cwd.display()
+                )),
+            }
+        }
+
+        for pin in &self.pinned {
+            let BundlePin::File { path, sha256 } = pin else {
+                continue;
+            };
+            if leaves_cwd(path) {
+                check.problems.push(format!(
+                    "pinned file {} is outside the working directory",
+                    path.display()
+                ));
+                continue;
+            }
+            match std::fs::read(cwd.join(path)) {
+                Ok(contents) => {
+                    if let Some(expected) = sha256
+                        && *expected != sha256_hex(&contents)
+                    {
+                        check.warnings.push(format!(
+                            "pinned file {} changed since the bundle was created",
+                            path.display()
+                        ));
+                    }
+                }
+                Err(err) => check.problems.push(format!(
+                    "pinned file {} cannot be read: {err}",
+                    path.display()
+                )),
+            }
+        }
+        check
+    }
+}
+
+/// The `sandbox_mode` setting `policy` corresponds to, or `None` for an
+/// external sandbox.
+pub fn sandbox_mode_of(policy: &SandboxPolicy) -> Option<SandboxMode> {
+    match policy {
+        SandboxPolicy::ReadOnly => Some(SandboxMode::ReadOnly),
+        SandboxPolicy::WorkspaceWrite { .. } => Some(SandboxMode::WorkspaceWrite),
+        SandboxPolicy::DangerFullAccess => Some(SandboxMode::DangerFullAccess),
+        SandboxPolicy::ExternalSandbox { .. } => None,
+    }
+}
+
+fn strictness(mode: SandboxMode) -> u8 {
+    match mode {
+        SandboxMode::DangerFullAccess => 0,
+        SandboxMode::WorkspaceWrite => 1,
+        SandboxMode::ReadOnly => 2,
+    }
+}
+
+/// Whether `path` is absolute or climbs out of the directory it is relative
+/// to. Pins are read from the working directory of whoever runs the bundle,
+/// so they must not reach anything else on that machine.
+fn leaves_cwd(path: &Path) -> bool {
+    let mut depth = 0usize;
+    for component in path.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => return true,
+            Component::CurDir => {}
+            Component::ParentDir => match depth.checked_sub(1) {
+                Some(parent) => depth = parent,
+                None => return true,
+            },
+            Component::Normal(_) => depth += 1,
+        }
+    }
+    false
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn bundle() -> TaskBundle {
+        let mut bundle = TaskBundle::new(
+            "Upgrade the parser to the 2024 edition.\nRun the tests afterwards.".to_string(),
+            BundleProfile {
+                model: Some("gpt-5-codex".to_string()),
+                model_reasoning_effort: Some(ReasoningEffort::High),
+                sandbox_mode: Some(SandboxMode::WorkspaceWrite),
+                ..Default::default()
+            },
+        );
+        bundle.pinned = vec![
+            BundlePin::File {
+                path: PathBuf::from("NOTES.md"),
+                sha256: Some(sha256_hex(b"keep the public API\n")),
+            },
+            BundlePin::Instruction {
+                text: "Do not touch the lexer.".to_string(),
+            },
+        ];
+        bundle
+    }
+
+    #[test]
+    fn bundles_round_trip_through_toml() {
+        let dir = TempDir::new().expect("tempdir");
+        let path = dir.path().join("task.codexbundle");
+        let bundle = bundle();
+        bundle.save(&path).expect("save");
+        assert_eq!(TaskBundle::load(&path).expect("load"), bundle);
+    }
+
+    #[test]
+    fn the_profile_becomes_config_overrides() {
+        assert_eq!(
+            bundle().config_overrides(),
+            vec![
+                (
+                    "model".to_string(),
+                    toml::Value::String("gpt-5-codex".to_string())
+                ),
+                (
+                    "model_reasoning_effort".to_string(),
+                    toml::Value::String("high".to_string())
+                ),
+            ]
+        );
+        assert_eq!(
+            bundle().pinned_items(),
+            vec![
+                PinnedContextItem::File {
+                    path: PathBuf::from("NOTES.md")
+                },
+                PinnedContextItem::Instruction {
+                    text: "Do not touch the lexer.".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn bundles_may_tighten_but_not_loosen_the_sandbox() {
+        let bundle = bundle();
+        assert_eq!(
+            bundle.sandbox_mode(Some(SandboxMode::DangerFullAccess), false),
+            Ok(Some(SandboxMode::WorkspaceWrite))
+        );
+        assert_eq!(
+            bundle.sandbox_mode(Some(SandboxMode::WorkspaceWrite), false),
+            Ok(None)
+        );
+        assert_eq!(
+            bundle.sandbox_mode(Some(SandboxMode::ReadOnly), false),
+            Err("the bundle asks for sandbox_mode workspace-write, which is looser than your read-only; pass --allow-bundle-sandbox to allow it".to_string())
+        );
+        assert_eq!(
+            bundle.sandbox_mode(Some(SandboxMode::ReadOnly), true),
+            Ok(Some(SandboxMode::WorkspaceWrite))
+        );
+        assert!(bundle.sandbox_mode(None, false).is_err());
+    }
+
+    #[test]
+    fn pins_must_stay_inside_the_working_directory() {
+        let dir = TempDir::new().expect("tempdir");
+        for path in ["/etc/passwd", "../secrets.txt", "docs/../../secrets.txt"] {
+            assert!(leaves_cwd(Path::new(path)), "{path}");
+            assert_eq!(
+                BundlePin::file(dir.path(), Path::new(path))
+                    .expect_err("pin outside the cwd")
+                    .kind(),
+                io::ErrorKind::InvalidInput
+            );
+        }
+        assert!(!leaves_cwd(Path::new("docs/../NOTES.md")));
+        assert!(!leaves_cwd(Path::new("./src/lib.rs")));
+    }
+
+    #[tokio::test]
+    async fn check_reports_unmet_requirements_and_drift() {
+        let dir = TempDir::new().expect("tempdir");
+        std::fs::write(dir.path().join("NOTES.md"), "keep the public API, mostly\n")
+            .expect("write pinned file");
+        let mut bundle = bundle();
+        bundle.requirements = BundleRequirements {
+            commands: vec!["codex-bundle-test-missing-command".to_string()],
+            env: vec!["CODEX_BUNDLE_TEST_MISSING_VAR".to_string()],
+            git_commit: None,
+        };
+        bundle.pinned.push(BundlePin::File {
+            path: PathBuf::from("missing.md"),
+            sha256: None,
+        });
+        bundle.pinned.push(BundlePin::File {
+            path: PathBuf::from("../outside.md"),
+            sha256: None,
+        });
+
+        let check = bundle.check(dir.path()).await;
+        assert_eq!(check.problems.len(), 4, "{check:?}");
+        assert_eq!(
+            check.warnings,
+            vec!["pinned file NOTES.md changed since the bundle was created".to_string()]
+        );
+    }
+}
@@ -24,6 +24,7 @@ use crate::protocol::EventMsg;
 use crate::protocol::TaskCompleteEvent;
 use crate::protocol::TurnAbortReason;
 use crate::protocol::TurnAbortedEvent;
+use crate::protocol::TurnTimedOutEvent;
 use crate::state::ActiveTurn;
 use crate::state::RunningTask;
 use crate::state::TaskKind;
@@ -116,6 +117,12 @@ impl Session {
 
         let cancellation_token = CancellationToken::new();
         let done = Arc::new(Notify::new());
+        // Cancelled when the task body finishes or is dropped, which stands
+        // the watchdog down.
+        let finished = CancellationToken::new();
+        if let Some(timeout) = self.turn_timeout().await {
+            self.spawn_turn_watchdog(Arc::clone(&turn_context), timeout, finished.clone());
+        }
 
         let done_clone = Arc::clone(&done);
         let handle = {
@@ -124,6 +131,7 @@ impl Session {
             let task_for_run = Arc::clone(&task);
             let task_cancellation_token = cancellation_token.child_token();
             tokio::spawn(async move {
+                let _finished = finished.drop_guard();
                 let ctx_for_finish = Arc::clone(&ctx);
                 let last_agent_message = task_for_run
                     .run(
@@ -155,6 +163,48 @@ impl Session {
         self.register_new_active_task(running_task).await;
     }
 
+    /// Aborts the task of `turn_context` once it has run for `timeout`,
+    /// unless `finished` is cancelled first.
+    fn spawn_turn_watchdog(
+        self: &Arc<Self>,
+        turn_context: Arc<TurnContext>,
+        timeout: Duration,
+        finished: CancellationToken,
+    ) {
+        let session = Arc::downgrade(self);
+        tokio::spawn(async move {
+            select! {
+                _ = finished.cancelled() => return,
+                _ = tokio::time::sleep(timeout) => {}
+            }
+            let Some(session) = session.upgrade() else {
+                return;
+            };
+            let still_running = session
+                .active_turn
+                .lock()
+                .await
+                .as_ref()
+                .is_some_and(|turn| turn.tasks.contains_key(&turn_context.sub_id));
+            if !still_running {
+                return;
+            }
+            let timeout_secs = timeout.as_secs();
+            warn!(
+                "task {} timed out after {timeout_secs}s",
+                turn_context.sub_id
+            );
+            let event = EventMsg::TurnTimedOut(TurnTimedOutEvent {
+                timeout_secs,
+                message: format!(
+                    "Turn timed out after {timeout_secs}s (turn_timeout_secs); the model request and any running tool calls were cancelled."
+                ),
+            });
+            session.send_event(turn_context.as_ref(), event).await;
+            session.abort_all_tasks(TurnAbortReason::TimedOut).await;
+        });
+    }
+
     pub async fn abort_all_tasks(self: &Arc<Self>, reason: TurnAbortReason) {
         for task in self.take_all_running_tasks().await {
             self.handle_task_abort(task, reason.clone()).await;
@@ -177,9 +227,11 @@ impl Session {
             false
         };
         drop(active);
+        self.mark_active().await;
         if should_close_sessions {
             self.close_unified_exec_sessions().await;
         }
+        self.record_session_summary().await;
         let event = EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message });
         self.send_event(turn_context.as_ref(), event).await;
     }
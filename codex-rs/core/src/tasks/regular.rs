@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Instant;
 
 use crate::codex::TurnContext;
 use crate::codex::run_task;
@@ -31,8 +32,12 @@ impl SessionTask for RegularTask {
         let sess = session.clone_session();
         let run_task_span =
             trace_span!(parent: sess.services.otel_manager.current_span(), "run_task");
-        run_task(sess, ctx, input, cancellation_token)
+        let otel_manager = ctx.client.get_otel_manager();
+        let started = Instant::now();
+        let last_agent_message = run_task(sess, ctx, input, cancellation_token)
             .instrument(run_task_span)
-            .await
+            .await;
+        otel_manager.turn_completed(started.elapsed());
+        last_agent_message
     }
 }
@@ -0,0 +1,731 @@
+//! Commands and output parsers behind the `run_tests` tool.
+//!
+//! Each supported runner is invoked in a mode whose output can be parsed
+//! into passed, failed and skipped tests: libtest's text output for
+//! `cargo test`, the `-rA` summary for pytest, `--json` for jest and
+//! `-json` for `go test`. Failures keep enough of their location that
+//! they can be re-run on their own.
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::python_kernel::python_program;
+
+/// Lines kept from each failure message.
+const MAX_FAILURE_LINES: usize = 40;
+
+/// Passing tests listed by name; beyond this only the count is given.
+const MAX_LISTED_PASSES: usize = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TestRunner {
+    Cargo,
+    Pytest,
+    Jest,
+    Go,
+}
+
+impl TestRunner {
+    /// Guesses the runner from the project files in `cwd`.
+    pub(crate) fn detect(cwd: &Path) -> Option<Self> {
+        if cwd.join("Cargo.toml").is_file() {
+            return Some(Self::Cargo);
+        }
+        if cwd.join("go.mod").is_file() {
+            return Some(Self::Go);
+        }
+        if std::fs::read_to_string(cwd.join("package.json"))
+            .is_ok_and(|package| package.contains("\"jest\""))
+        {
+            return Some(Self::Jest);
+        }
+        let python_markers = [
+            "pytest.ini",
+            "pyproject.toml",
+            "setup.cfg",
+            "tox.ini",
+            "conftest.py",
+            "setup.py",
+        ];
+        python_markers
+            .iter()
+            .any(|marker| cwd.join(marker).is_file())
+            .then_some(Self::Pytest)
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Cargo => "cargo test",
+            Self::Pytest => "pytest",
+            Self::Jest => "jest",
+            Self::Go => "go test",
+        }
+    }
+
+    /// The command that runs `targets`, or the whole suite when empty.
+    /// `extra` goes after the runner's own flags and before the test
+    /// selection.
+    pub(crate) fn command(self, targets: &[TestCase], extra: &[String]) -> Vec<String> {
+        let mut command: Vec<String> = match self {
+            Self::Cargo => vec!["cargo", "test", "--no-fail-fast"],
+            Self::Pytest => vec![python_program(), "-m", "pytest", "-rA", "--tb=short"],
+            Self::Jest => vec!["npx", "jest", "--json"],
+            Self::Go => vec!["go", "test", "-json"],
+        }
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+        if self == Self::Pytest {
+            // The cache is only used by pytest's own --lf; writing it can
+            // fail under a read-only sandbox.
+            command.extend(["-p".to_string(), "no:cacheprovider".to_string()]);
+        }
+        command.extend(extra.iter().cloned());
+
+        match self {
+            Self::Cargo => {
+                if !targets.is_empty() {
+                    command.extend(["--".to_string(), "--exact".to_string()]);
+                    command.extend(targets.iter().map(|target| target.name.clone()));
+                }
+            }
+            Self::Pytest => command.extend(targets.iter().map(|target| target.name.clone())),
+            Self::Jest => {
+                let mut files: Vec<String> = targets
+                    .iter()
+                    .filter_map(|target| target.location.clone())
+                    .collect();
+                files.sort();
+                files.dedup();
+                command.extend(files);
+                let names: Vec<String> = targets
+                    .iter()
+                    .filter(|target| !target.is_whole_location())
+                    .map(|target| regex_lite::escape(&target.name))
+                    .collect();
+                if !names.is_empty() {
+                    command.extend(["-t".to_string(), format!("^(?:{})$", names.join("|"))]);
+                }
+            }
+            Self::Go => {
+                // Subtests are re-run through their top-level test.
+                let mut names: Vec<String> = targets
+                    .iter()
+                    .filter(|target| !target.is_whole_location())
+                    .map(|target| {
+                        let top = target.name.split('/').next().unwrap_or(&target.name);
+                        regex_lite::escape(top)
+                    })
+                    .collect();
+                names.sort();
+                names.dedup();
+                if !names.is_empty() {
+                    command.extend(["-run".to_string(), format!("^(?:{})$", names.join("|"))]);
+                }
+                let mut packages: Vec<String> = targets
+                    .iter()
+                    .filter_map(|target| target.location.clone())
+                    .collect();
+                packages.sort();
+                packages.dedup();
+                if packages.is_empty() && !extra.iter().any(|arg| !arg.starts_with('-')) {
+                    packages.push("./...".to_string());
+                }
+                command.extend(packages);
+            }
+        }
+        command
+    }
+
+    /// Parses what the runner printed. `stdout` carries the machine-readable
+    /// reports of jest and `go test`; the other runners are parsed from
+    /// `output`, the combined stdout and stderr.
+    pub(crate) fn parse(self, stdout: &str, output: &str, cwd: &Path) -> TestReport {
+        match self {
+            Self::Cargo => parse_cargo(output),
+            Self::Pytest => parse_pytest(output),
+            Self::Jest => parse_jest(stdout, cwd),
+            Self::Go => parse_go(stdout),
+        }
+    }
+}
+
+/// A test as the runner names it. `location` is the file (jest) or package
+/// (`go test`) it lives in, where the name alone cannot select it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TestCase {
+    pub name: String,
+    pub location: Option<String>,
+}
+
+impl TestCase {
+    pub(crate) fn named(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            location: None,
+        }
+    }
+
+    /// Failures of a whole file or package, e.g. one that does not compile,
+    /// are named after their location and re-run by location alone.
+    fn is_whole_location(&self) -> bool {
+        self.location.as_deref() == Some(self.name.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TestFailure {
+    pub test: TestCase,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct TestReport {
+    pub passed: Vec<String>,
+    pub failed: Vec<TestFailure>,
+    pub skipped: usize,
+}
+
+impl TestReport {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.passed.is_empty() && self.failed.is_empty() && self.skipped == 0
+    }
+}
+
+/// The failures of the most recent `run_tests` call, for `rerun_failed`.
+#[derive(Debug, Clone)]
+pub(crate) struct LastTestRun {
+    pub runner: TestRunner,
+    pub cwd: PathBuf,
+    pub extra: Vec<String>,
+    pub failed: Vec<TestCase>,
+}
+
+pub(crate) fn format_report(runner: TestRunner, report: &TestReport, exit_code: i32) -> String {
+    let mut lines = vec![format!(
+        "{}: {} passed, {} failed, {} skipped (exit code {exit_code})",
+        runner.label(),
+        report.passed.len(),
+        report.failed.len(),
+        report.skipped
+    )];
+    if !report.failed.is_empty() {
+        lines.push(String::new());
+        lines.push("Failed:".to_string());
+        for failure in &report.failed {
+            let location = failure
+                .test
+                .location
+                .as_ref()
+                .map(|location| format!(" ({location})"))
+                .unwrap_or_default();
+            lines.push(format!("- {}{location}", failure.test.name));
+            let message: Vec<&str> = failure.message.lines().collect();
+            for line in message.iter().take(MAX_FAILURE_LINES) {
+                lines.push(format!("    {line}"));
+            }
+            if message.len() > MAX_FAILURE_LINES {
+                lines.push(format!(
+                    "    ... {} more lines",
+                    message.len() - MAX_FAILURE_LINES
+                ));
+            }
+        }
+    }
+    if !report.passed.is_empty() {
+        lines.push(String::new());
+        let listed: Vec<&str> = report
+            .passed
+            .iter()
+            .take(MAX_LISTED_PASSES)
+            .map(String::as_str)
+            .collect();
+        let more = report.passed.len().saturating_sub(MAX_LISTED_PASSES);
+        let more = if more > 0 {
+            format!(" and {more} more")
+        } else {
+            String::new()
+        };
+        lines.push(format!("Passed: {}{more}", listed.join(", ")));
+    }
+    if !report.failed.is_empty() {
+        lines.push(String::new());
+        lines.push("Call run_tests with rerun_failed to run only the failing tests.".to_string());
+    }
+    lines.join("\n")
+}
+
+/// libtest prints `test <name> ... ok|FAILED|ignored` per test and a
+/// `---- <name> stdout ----` section with the output of each failure.
+fn parse_cargo(output: &str) -> TestReport {
+    let mut report = TestReport::default();
+    let mut failed = Vec::new();
+    let mut messages: HashMap<String, String> = HashMap::new();
+    let mut section: Option<(String, Vec<&str>)> = None;
+
+    for line in output.lines() {
+        if let Some((name, body)) = section.take() {
+            let ends = line.starts_with("---- ") || line == "failures:" || line == "successes:";
+            if ends {
+                messages.insert(name, body.join("\n").trim().to_string());
+            } else {
+                let mut body = body;
+                body.push(line);
+                section = Some((name, body));
+                continue;
+            }
+        }
+        if let Some(name) = line
+            .strip_prefix("---- ")
+            .and_then(|rest| rest.strip_suffix(" stdout ----"))
+        {
+            section = Some((name.to_string(), Vec::new()));
+            continue;
+        }
+        let Some((name, status)) = line
+            .strip_prefix("test ")
+            .and_then(|rest| rest.rsplit_once(" ... "))
+        else {
+            continue;
+        };
+        match status.trim() {
+            "ok" => report.passed.push(name.to_string()),
+            "FAILED" => failed.push(name.to_string()),
+            status if status.starts_with("ignored") => report.skipped += 1,
+            _ => {}
+        }
+    }
+    if let Some((name, body)) = section {
+        messages.insert(name, body.join("\n").trim().to_string());
+    }
+
+    report.failed = failed
+        .into_iter()
+        .map(|name| TestFailure {
+            message: messages.remove(&name).unwrap_or_default(),
+            test: TestCase::named(name),
+        })
+        .collect();
+    report
+}
+
+/// Reads the `-rA` short summary, taking failure details from the
+/// `FAILURES` and `ERRORS` sections printed with `--tb=short`.
+fn parse_pytest(output: &str) -> TestReport {
+    let mut report = TestReport::default();
+    let mut details: Vec<(String, String)> = Vec::new();
+    let mut in_details = false;
+    let mut current: Option<(String, Vec<&str>)> = None;
+
+    for line in output.lines() {
+        if let Some(title) = pytest_banner(line, '=') {
+            if let Some((name, body)) = current.take() {
+                details.push((name, body.join("\n").trim().to_string()));
+            }
+            in_details = title == "FAILURES" || title == "ERRORS";
+            continue;
+        }
+        if !in_details {
+            if let Some((status, rest)) = line.split_once(' ') {
+                match status {
+                    "PASSED" | "XPASS" => report.passed.push(rest.to_string()),
+                    "FAILED" | "ERROR" => {
+                        let (node, message) = match rest.split_once(" - ") {
+                            Some((node, message)) => (node, message),
+                            None => (rest, ""),
+                        };
+                        report.failed.push(TestFailure {
+                            test: TestCase::named(node),
+                            message: message.to_string(),
+                        });
+                    }
+                    "SKIPPED" | "XFAIL" => {
+                        report.skipped += rest
+                            .strip_prefix('[')
+                            .and_then(|rest| rest.split_once(']'))
+                            .and_then(|(count, _)| count.parse().ok())
+                            .unwrap_or(1);
+                    }
+                    _ => {}
+                }
+            }
+            continue;
+        }
+        if let Some(title) = pytest_banner(line, '_') {
+            if let Some((name, body)) = current.take() {
+                details.push((name, body.join("\n").trim().to_string()));
+            }
+            current = Some((title.to_string(), Vec::new()));
+        } else if let Some((_, body)) = current.as_mut() {
+            body.push(line);
+        }
+    }
+
+    // Section titles are the test's name within its file, e.g.
+    // `TestClass.test_method` or `test_fn[param]`; error sections start
+    // with `ERROR at setup of`.
+    for failure in &mut report.failed {
+        let dotted = failure.test.name.replace("::", ".");
+        let detail = details.iter().find(|(title, _)| {
+            let title = title
+                .rsplit_once(" of ")
+                .map_or(title.as_str(), |(_, name)| name);
+            dotted.ends_with(&format!(".{title}"))
+        });
+        if let Some((_, body)) = detail {
+            failure.message = body.clone();
+        }
+    }
+    report
+}
+
+/// The title of a pytest banner such as `===== FAILURES =====`.
+fn pytest_banner(line: &str, fill: char) -> Option<&str> {
+    let title = line
+        .strip_prefix(fill)?
+        .trim_start_matches(fill)
+        .trim_end_matches(fill);
+    let title = title.strip_prefix(' ')?.strip_suffix(' ')?;
+    (!title.is_empty()).then_some(title)
+}
+
+/// Reads the report `jest --json` writes to stdout. Suites that fail before
+/// running any test, e.g. on a syntax error, are reported under their file.
+fn parse_jest(stdout: &str, cwd: &Path) -> TestReport {
+    let mut report = TestReport::default();
+    let Some(json) = stdout
+        .lines()
+        .rev()
+        .filter(|line| line.trim_start().starts_with('{'))
+        .find_map(|line| serde_json::from_str::<Value>(line.trim()).ok())
+        .or_else(|| serde_json::from_str(stdout.trim()).ok())
+    else {
+        return report;
+    };
+
+    for suite in json["testResults"].as_array().into_iter().flatten() {
+        let file = suite["name"].as_str().map(|name| {
+            Path::new(name)
+                .strip_prefix(cwd)
+                .unwrap_or(Path::new(name))
+                .display()
+                .to_string()
+        });
+        let assertions = suite["assertionResults"].as_array();
+        for assertion in assertions.into_iter().flatten() {
+            let name = assertion["fullName"]
+                .as_str()
+                .or_else(|| assertion["title"].as_str())
+                .unwrap_or_default()
+                .to_string();
+            match assertion["status"].as_str() {
+                Some("passed") => report.passed.push(name),
+                Some("failed") => {
+                    let message = assertion["failureMessages"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_str)
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    report.failed.push(TestFailure {
+                        test: TestCase {
+                            name,
+                            location: file.clone(),
+                        },
+                        message,
+                    });
+                }
+                _ => report.skipped += 1,
+            }
+        }
+        let suite_failed = suite["status"].as_str() == Some("failed");
+        if suite_failed && assertions.is_none_or(Vec::is_empty) {
+            let file = file.unwrap_or_default();
+            report.failed.push(TestFailure {
+                test: TestCase {
+                    name: file.clone(),
+                    location: Some(file),
+                },
+                message: suite["message"].as_str().unwrap_or_default().to_string(),
+            });
+        }
+    }
+    report
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GoTestEvent {
+    action: String,
+    #[serde(default)]
+    package: String,
+    #[serde(default)]
+    test: Option<String>,
+    #[serde(default)]
+    output: Option<String>,
+}
+
+/// Reads the `go test -json` event stream. A package that fails without a
+/// failing test, e.g. because it does not build, is reported as a failure
+/// named after the package.
+fn parse_go(stdout: &str) -> TestReport {
+    let mut report = TestReport::default();
+    let mut outputs: HashMap<(String, Option<String>), String> = HashMap::new();
+    let mut failed: Vec<(String, String)> = Vec::new();
+    let mut failed_packages: BTreeSet<String> = BTreeSet::new();
+
+    for line in stdout.lines() {
+        let Ok(event) = serde_json::from_str::<GoTestEvent>(line) else {
+            continue;
+        };
+        match (event.action.as_str(), event.test) {
+            ("output", test) => {
+                if let Some(output) = event.output {
+                    outputs
+                        .entry((event.package, test))
+                        .or_default()
+                        .push_str(&output);
+                }
+            }
+            ("pass", Some(test)) => report.passed.push(test),
+            ("skip", Some(_)) => report.skipped += 1,
+            ("fail", Some(test)) => failed.push((event.package, test)),
+            ("fail", None) => {
+                failed_packages.insert(event.package);
+            }
+            _ => {}
+        }
+    }
+
+    // A failing subtest also fails its parent; only the subtest is kept.
+    let leaves: Vec<&(String, String)> = failed
+        .iter()
+        .filter(|(package, test)| {
+            !failed.iter().any(|(other_package, other)| {
+                other_package == package && other.starts_with(&format!("{test}/"))
+            })
+        })
+        .collect();
+    for (package, test) in leaves {
+        let output = outputs
+            .remove(&(package.clone(), Some(test.clone())))
+            .unwrap_or_default();
+        report.failed.push(TestFailure {
+            test: TestCase {
+                name: test.clone(),
+                location: Some(package.clone()),
+            },
+            message: go_failure_message(&output),
+        });
+    }
+    for package in failed_packages {
+        if failed
+            .iter()
+            .any(|(failed_package, _)| *failed_package == package)
+        {
+            continue;
+        }
+        let output = outputs.remove(&(package.clone(), None)).unwrap_or_default();
+        report.failed.push(TestFailure {
+            test: TestCase {
+                name: package.clone(),
+                location: Some(package),
+            },
+            message: go_failure_message(&output),
+        });
+    }
+    report
+}
+
+/// Drops the `=== RUN`-style progress lines from a test's output.
+fn go_failure_message(output: &str) -> String {
+    output
+        .lines()
+        .filter(|line| {
+            let line = line.trim_start();
+            !line.starts_with("=== ") && !line.starts_with("--- FAIL") && line != "FAIL"
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_cargo_test_output() {
+        let output = "\
+running 3 tests
+test parser::parses_empty ... FAILED
+test parser::parses_list ... ok
+test slow ... ignored, takes a minute
+
+failures:
+
+---- parser::parses_empty stdout ----
+
+thread 'parser::parses_empty' panicked at src/parser.rs:10:5:
+assertion `left == right` failed
+  left: 1
+ right: 0
+
+failures:
+    parser::parses_empty
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out
+";
+        assert_eq!(
+            parse_cargo(output),
+            TestReport {
+                passed: vec!["parser::parses_list".to_string()],
+                failed: vec![TestFailure {
+                    test: TestCase::named("parser::parses_empty"),
+                    message: "thread 'parser::parses_empty' panicked at src/parser.rs:10:5:\nassertion `left == right` failed\n  left: 1\n right: 0".to_string(),
+                }],
+                skipped: 1,
+            }
+        );
+        assert_eq!(
+            TestRunner::Cargo.command(&[TestCase::named("parser::parses_empty")], &[]),
+            vec![
+                "cargo",
+                "test",
+                "--no-fail-fast",
+                "--",
+                "--exact",
+                "parser::parses_empty"
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_pytest_summary_and_failure_sections() {
+        let output = "\
+============================= test session starts ==============================
+collected 3 items
+
+tests/test_math.py .Fs                                                   [100%]
+
+=================================== FAILURES ===================================
+___________________________ TestMath.test_divide ____________________________
+tests/test_math.py:12: in test_divide
+    assert divide(1, 0) == 0
+E   ZeroDivisionError: division by zero
+=========================== short test summary info ============================
+PASSED tests/test_math.py::test_add
+SKIPPED [1] tests/test_math.py:20: needs gpu
+FAILED tests/test_math.py::TestMath::test_divide - ZeroDivisionError: division by zero
+=================== 1 failed, 1 passed, 1 skipped in 0.02s ===================
+";
+        assert_eq!(
+            parse_pytest(output),
+            TestReport {
+                passed: vec!["tests/test_math.py::test_add".to_string()],
+                failed: vec![TestFailure {
+                    test: TestCase::named("tests/test_math.py::TestMath::test_divide"),
+                    message: "tests/test_math.py:12: in test_divide\n    assert divide(1, 0) == 0\nE   ZeroDivisionError: division by zero".to_string(),
+                }],
+                skipped: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_jest_json_and_targets_failures_by_file() {
+        let stdout = r#"{"numFailedTests":1,"testResults":[{"name":"/repo/src/sum.test.js","status":"failed","message":"","assertionResults":[{"fullName":"sum adds","status":"passed","failureMessages":[]},{"fullName":"sum handles (negatives)","status":"failed","failureMessages":["Expected: -3\nReceived: 3"]},{"fullName":"sum later","status":"todo","failureMessages":[]}]}]}"#;
+        let report = parse_jest(stdout, Path::new("/repo"));
+        let failing = TestCase {
+            name: "sum handles (negatives)".to_string(),
+            location: Some("src/sum.test.js".to_string()),
+        };
+        assert_eq!(
+            report,
+            TestReport {
+                passed: vec!["sum adds".to_string()],
+                failed: vec![TestFailure {
+                    test: failing.clone(),
+                    message: "Expected: -3\nReceived: 3".to_string(),
+                }],
+                skipped: 1,
+            }
+        );
+        assert_eq!(
+            TestRunner::Jest.command(&[failing], &[]),
+            vec![
+                "npx",
+                "jest",
+                "--json",
+                "src/sum.test.js",
+                "-t",
+                r"^(?:sum handles \(negatives\))$"
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_go_events_keeping_failing_subtests() {
+        let stdout = r#"{"Action":"run","Package":"example.com/calc","Test":"TestAdd"}
+{"Action":"pass","Package":"example.com/calc","Test":"TestAdd"}
+{"Action":"run","Package":"example.com/calc","Test":"TestDiv"}
+{"Action":"output","Package":"example.com/calc","Test":"TestDiv/by_zero","Output":"=== RUN   TestDiv/by_zero\n"}
+{"Action":"output","Package":"example.com/calc","Test":"TestDiv/by_zero","Output":"    calc_test.go:20: got 1, want 0\n"}
+{"Action":"output","Package":"example.com/calc","Test":"TestDiv/by_zero","Output":"    --- FAIL: TestDiv/by_zero (0.00s)\n"}
+{"Action":"fail","Package":"example.com/calc","Test":"TestDiv/by_zero"}
+{"Action":"fail","Package":"example.com/calc","Test":"TestDiv"}
+{"Action":"output","Package":"example.com/broken","Output":"broken.go:3:1: syntax error\n"}
+{"Action":"fail","Package":"example.com/broken"}
+{"Action":"fail","Package":"example.com/calc"}
+"#;
+        let report = parse_go(stdout);
+        assert_eq!(
+            report,
+            TestReport {
+                passed: vec!["TestAdd".to_string()],
+                failed: vec![
+                    TestFailure {
+                        test: TestCase {
+                            name: "TestDiv/by_zero".to_string(),
+                            location: Some("example.com/calc".to_string()),
+                        },
+                        message: "calc_test.go:20: got 1, want 0".to_string(),
+                    },
+                    TestFailure {
+                        test: TestCase {
+                            name: "example.com/broken".to_string(),
+                            location: Some("example.com/broken".to_string()),
+                        },
+                        message: "broken.go:3:1: syntax error".to_string(),
+                    },
+                ],
+                skipped: 0,
+            }
+        );
+        let targets: Vec<TestCase> = report
+            .failed
+            .into_iter()
+            .map(|failure| failure.test)
+            .collect();
+        assert_eq!(
+            TestRunner::Go.command(&targets[..1], &[]),
+            vec![
+                "go",
+                "test",
+                "-json",
+                "-run",
+                "^(?:TestDiv)$",
+                "example.com/calc"
+            ]
+        );
+    }
+}
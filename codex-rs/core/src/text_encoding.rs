@@ -25,6 +25,11 @@ pub fn bytes_to_string_smart(bytes: &[u8]) -> String {
     decode_bytes(bytes, encoding)
 }
 
+/// Best guess at the legacy encoding of bytes that are not valid UTF-8, e.g. `windows-1252`.
+pub(crate) fn guess_encoding_name(bytes: &[u8]) -> &'static str {
+    detect_encoding(bytes).name()
+}
+
 // Windows-1252 reassigns a handful of 0x80-0x9F slots to smart punctuation (curly quotes, dashes,
 // ™). CP866 uses those *same byte values* for uppercase Cyrillic letters. When chardetng sees shell
 // snippets that mix these bytes with ASCII it sometimes guesses IBM866, so “smart quotes” render as
@@ -0,0 +1,506 @@
+//! `[tool_policy]`: tool call decisions delegated to an external policy
+//! engine. Open Policy Agent is the only backend so far.
+//!
+//! Before a call runs, Codex POSTs `{"input": <call>}` to the configured OPA
+//! Data API document, where `<call>` is a `tool-call` object serialised like
+//! hook payloads (kebab-case keys and `hook-protocol-version`), though no
+//! hook receives it. Tools listed in `skip_tools` are not sent. The document
+//! decides the call: either a decision string (`allow`,
+//! `ask`, `deny`) or an object with a `decision` and optional `reason` and
+//! `policy_id`. An undefined document leaves the call to `[[tool_rules]]`
+//! and the approval policy; an engine that cannot be reached, or answers
+//! with anything else, gets the configured `on_error` decision.
+//!
+//! Answers are reused for identical calls until `cache_ttl_secs` passes, and
+//! every decision is appended to `~/.codex/policy_audit.jsonl` together with
+//! the id of the policy that made it:
+//!
+//! ```text
+//! {"ts":<unix_seconds>,"thread_id":"<uuid>","turn_id":"<id>","call_id":"<id>","tool_name":"shell","decision":"deny","policy_id":"codex.no_force_push","cached":false}
+//! ```
+
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use tracing::warn;
+
+use crate::config::types::ToolPolicyConfig;
+use crate::config::types::ToolRuleDecision;
+use crate::default_client::build_reqwest_client;
+use crate::hooks;
+use crate::tool_rules::RuleSubject;
+use crate::tool_rules::RuleVerdict;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+/// Filename of the decision audit log inside `~/.codex`.
+pub const TOOL_POLICY_AUDIT_FILENAME: &str = "policy_audit.jsonl";
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60);
+/// Names verdicts whose answer carried no `policy_id`.
+const UNNAMED_POLICY: &str = "tool_policy";
+
+/// The engine a session's `[tool_policy]` points at.
+#[derive(Debug)]
+pub(crate) struct ToolPolicy {
+    client: reqwest::Client,
+    url: String,
+    token: Option<String>,
+    timeout: Duration,
+    cache_ttl: Duration,
+    on_error: ToolRuleDecision,
+    audit_path: PathBuf,
+    cache: Mutex<HashMap<String, CachedDecision>>,
+    skip_tools: Vec<String>,
+}
+
+#[derive(Debug)]
+struct CachedDecision {
+    decision: Option<PolicyDecision>,
+    expires_at: Instant,
+}
+
+/// The tool call sent to the engine.
+#[derive(Serialize)]
+#[serde(tag = "type", rename = "tool-call", rename_all = "kebab-case")]
+pub(crate) struct PolicyInput<'a> {
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: &'a str,
+    pub(crate) call_id: &'a str,
+    #[serde(flatten)]
+    pub(crate) call: &'a RuleSubject<'a>,
+}
+
+#[derive(Deserialize)]
+struct DataResponse {
+    /// Absent when the document is undefined for the input.
+    result: Option<PolicyResult>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PolicyResult {
+    Decision(ToolRuleDecision),
+    Detailed(PolicyDecision),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct PolicyDecision {
+    decision: ToolRuleDecision,
+    #[serde(default)]
+    reason: Option<String>,
+    #[serde(default)]
+    policy_id: Option<String>,
+}
+
+#[derive(Serialize)]
+struct AuditRecord<'a> {
+    ts: u64,
+    thread_id: &'a str,
+    turn_id: &'a str,
+    call_id: &'a str,
+    tool_name: &'a str,
+    /// `None` when the engine had no decision for the call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decision: Option<ToolRuleDecision>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    policy_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'a str>,
+    cached: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+}
+
+impl ToolPolicy {
+    /// Connects `config` to its engine, reading the bearer token now so a
+    /// missing one fails the session instead of every call.
+    pub(crate) fn new(config: &ToolPolicyConfig, codex_home: &Path) -> Result<Self, String> {
+        let token = match &config.token_env {
+            Some(env) => Some(
+                std::env::var(env)
+                    .ok()
+                    .filter(|token| !token.trim().is_empty())
+                    .ok_or_else(|| format!("set `{env}` to the `[tool_policy]` bearer token"))?,
+            ),
+            None => None,
+        };
+        Ok(Self {
+            client: build_reqwest_client(),
+            url: config.url.clone(),
+            token,
+            timeout: config
+                .timeout_ms
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_TIMEOUT),
+            cache_ttl: config
+                .cache_ttl_secs
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_CACHE_TTL),
+            on_error: config.on_error,
+            audit_path: codex_home.join(TOOL_POLICY_AUDIT_FILENAME),
+            cache: Mutex::new(HashMap::new()),
+            skip_tools: config.skip_tools.clone(),
+        })
+    }
+
+    /// Whether calls to `tool_name` are sent to the engine at all.
+    pub(crate) fn applies_to(&self, tool_name: &str) -> bool {
+        !self.skip_tools.iter().any(|skipped| skipped == tool_name)
+    }
+
+    /// The engine's verdict for `input`, from the cache when an identical
+    /// call was decided recently. Every outcome is written to the audit log.
+    pub(crate) async fn evaluate(&self, input: &PolicyInput<'_>) -> Option<RuleVerdict> {
+        let key = cache_key(input.call);
+        let (outcome, cached) = match key.as_deref().and_then(|key| self.cached(key)) {
+            Some(decision) => (Ok(decision), true),
+            None => {
+                let outcome = self.query(input).await;
+                if let (Ok(decision), Some(key)) = (&outcome, key) {
+                    self.remember(key, decision.clone());
+                }
+                (outcome, false)
+            }
+        };
+        self.audit(input, &outcome, cached).await;
+
+        match outcome {
+            Ok(decision) => decision.map(|decision| RuleVerdict {
+                decision: decision.decision,
+                rule: decision
+                    .policy_id
+                    .unwrap_or_else(|| UNNAMED_POLICY.to_string()),
+                reason: decision.reason,
+            }),
+            Err(err) => {
+                warn!("tool policy {err}; applying `on_error`");
+                Some(RuleVerdict {
+                    decision: self.on_error,
+                    rule: UNNAMED_POLICY.to_string(),
+                    reason: Some(format!("the policy engine gave no decision ({err})")),
+                })
+            }
+        }
+    }
+
+    async fn query(&self, input: &PolicyInput<'_>) -> Result<Option<PolicyDecision>, String> {
+        let payload = hooks::payload(input).ok_or("input could not be serialised")?;
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(format!(r#"{{"input":{payload}}}"#))
+            .timeout(self.timeout);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|err| format!("request failed: {err}"))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("returned {status}: {}", body.trim()));
+        }
+        let response: DataResponse = response
+            .json()
+            .await
+            .map_err(|err| format!("answered with an unknown decision: {err}"))?;
+        Ok(response.result.map(|result| match result {
+            PolicyResult::Decision(decision) => PolicyDecision {
+                decision,
+                reason: None,
+                policy_id: None,
+            },
+            PolicyResult::Detailed(decision) => decision,
+        }))
+    }
+
+    fn cached(&self, key: &str) -> Option<Option<PolicyDecision>> {
+        let cache = self.cache.lock().ok()?;
+        cache
+            .get(key)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .map(|entry| entry.decision.clone())
+    }
+
+    fn remember(&self, key: String, decision: Option<PolicyDecision>) {
+        if self.cache_ttl.is_zero() {
+            return;
+        }
+        if let Ok(mut cache) = self.cache.lock() {
+            let now = Instant::now();
+            cache.retain(|_, entry| entry.expires_at > now);
+            cache.insert(
+                key,
+                CachedDecision {
+                    decision,
+                    expires_at: now + self.cache_ttl,
+                },
+            );
+        }
+    }
+
+    async fn audit(
+        &self,
+        input: &PolicyInput<'_>,
+        outcome: &Result<Option<PolicyDecision>, String>,
+        cached: bool,
+    ) {
+        let (decision, error) = match outcome {
+            Ok(decision) => (decision.as_ref(), None),
+            Err(err) => (None, Some(err.as_str())),
+        };
+        let record = AuditRecord {
+            ts: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            thread_id: &input.thread_id,
+            turn_id: input.turn_id,
+            call_id: input.call_id,
+            tool_name: input.call.tool_name,
+            decision: decision
+                .map(|decision| decision.decision)
+                .or(error.map(|_| self.on_error)),
+            policy_id: decision.and_then(|decision| decision.policy_id.as_deref()),
+            reason: decision.and_then(|decision| decision.reason.as_deref()),
+            cached,
+            error,
+        };
+        let Ok(mut line) = serde_json::to_string(&record) else {
+            warn!("failed to serialise tool policy audit record");
+            return;
+        };
+        line.push('\n');
+
+        let path = self.audit_path.clone();
+        let written = tokio::task::spawn_blocking(move || -> std::io::Result<()> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut options = OpenOptions::new();
+            options.append(true).create(true);
+            #[cfg(unix)]
+            options.mode(0o600);
+            let mut file = options.open(&path)?;
+            file.write_all(line.as_bytes())?;
+            file.flush()
+        })
+        .await;
+        match written {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => warn!("failed to write tool policy audit log: {err}"),
+            Err(err) => warn!("tool policy audit log task failed: {err}"),
+        }
+    }
+}
+
+/// Identifies a call by everything but its ids, so repeats of the same call
+/// share a decision.
+fn cache_key(call: &RuleSubject<'_>) -> Option<String> {
+    let json = serde_json::to_string(call).ok()?;
+    Some(format!("{:x}", Sha256::digest(json.as_bytes())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::types::ToolPolicyBackend;
+    use codex_protocol::protocol::SandboxPolicy;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tempfile::TempDir;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::body_partial_json;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+
+    fn policy(server: &MockServer, codex_home: &Path) -> ToolPolicy {
+        ToolPolicy::new(
+            &ToolPolicyConfig {
+                backend: ToolPolicyBackend::Opa,
+                url: format!("{}/v1/data/codex/tool_call", server.uri()),
+                token_env: None,
+                timeout_ms: None,
+                cache_ttl_secs: None,
+                on_error: ToolRuleDecision::Ask,
+                skip_tools: vec!["view_image".to_string()],
+            },
+            codex_home,
+        )
+        .expect("policy builds")
+    }
+
+    fn call<'a>(tool_name: &'a str, sandbox_policy: &'a SandboxPolicy) -> RuleSubject<'a> {
+        RuleSubject {
+            tool_name,
+            command: None,
+            arguments: "{}",
+            paths: &[],
+            cwd: Path::new("/repo"),
+            sandbox_policy,
+        }
+    }
+
+    fn input<'a>(call_id: &'a str, call: &'a RuleSubject<'a>) -> PolicyInput<'a> {
+        PolicyInput {
+            thread_id: "thread-1".to_string(),
+            turn_id: "turn-1",
+            call_id,
+            call,
+        }
+    }
+
+    fn audit_log(codex_home: &Path) -> Vec<serde_json::Value> {
+        std::fs::read_to_string(codex_home.join(TOOL_POLICY_AUDIT_FILENAME))
+            .expect("audit log written")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("audit line is JSON"))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn decisions_are_cached_and_audited_with_the_policy_id() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/data/codex/tool_call"))
+            .and(body_partial_json(json!({
+                "input": {
+                    "type": "tool-call",
+                    "tool-name": "shell_command",
+                    "command": "git push --force",
+                    "sandbox-policy": {"type": "read-only"},
+                }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "result": {
+                    "decision": "deny",
+                    "reason": "force pushes rewrite shared history",
+                    "policy_id": "codex.no_force_push",
+                }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let codex_home = TempDir::new().expect("tempdir");
+        let policy = policy(&server, codex_home.path());
+        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let push = RuleSubject {
+            command: Some("git push --force"),
+            arguments: r#"{"command":"git push --force"}"#,
+            ..call("shell_command", &sandbox_policy)
+        };
+
+        let expected = Some(RuleVerdict {
+            decision: ToolRuleDecision::Deny,
+            rule: "codex.no_force_push".to_string(),
+            reason: Some("force pushes rewrite shared history".to_string()),
+        });
+        assert_eq!(policy.evaluate(&input("call-1", &push)).await, expected);
+        assert_eq!(policy.evaluate(&input("call-2", &push)).await, expected);
+
+        let log = audit_log(codex_home.path());
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0]["call_id"], "call-1");
+        assert_eq!(log[0]["decision"], "deny");
+        assert_eq!(log[0]["policy_id"], "codex.no_force_push");
+        assert_eq!(log[0]["cached"], false);
+        assert_eq!(log[1]["call_id"], "call-2");
+        assert_eq!(log[1]["cached"], true);
+    }
+
+    #[tokio::test]
+    async fn plain_and_undefined_results() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(
+                json!({"input": {"tool-name": "read_file"}}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"result": "allow"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(
+                json!({"input": {"tool-name": "list_dir"}}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .mount(&server)
+            .await;
+        let codex_home = TempDir::new().expect("tempdir");
+        let policy = policy(&server, codex_home.path());
+        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let read_file = call("read_file", &sandbox_policy);
+        assert_eq!(
+            policy.evaluate(&input("call-1", &read_file)).await,
+            Some(RuleVerdict {
+                decision: ToolRuleDecision::Allow,
+                rule: UNNAMED_POLICY.to_string(),
+                reason: None,
+            })
+        );
+        let list_dir = call("list_dir", &sandbox_policy);
+        assert_eq!(policy.evaluate(&input("call-2", &list_dir)).await, None);
+    }
+
+    #[tokio::test]
+    async fn skipped_tools_are_not_sent() {
+        let server = MockServer::start().await;
+        let codex_home = TempDir::new().expect("tempdir");
+        let policy = policy(&server, codex_home.path());
+        assert!(!policy.applies_to("view_image"));
+        assert!(policy.applies_to("read_file"));
+    }
+
+    #[tokio::test]
+    async fn engine_errors_apply_on_error_and_are_not_cached() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("boom"))
+            .expect(2)
+            .mount(&server)
+            .await;
+        let codex_home = TempDir::new().expect("tempdir");
+        let policy = policy(&server, codex_home.path());
+        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let patch = RuleSubject {
+            arguments: "*** Begin Patch",
+            ..call("apply_patch", &sandbox_policy)
+        };
+
+        for call_id in ["call-1", "call-2"] {
+            let verdict = policy
+                .evaluate(&input(call_id, &patch))
+                .await
+                .expect("on_error verdict");
+            assert_eq!(verdict.decision, ToolRuleDecision::Ask);
+        }
+
+        let log = audit_log(codex_home.path());
+        assert_eq!(log[1]["decision"], "ask");
+        assert_eq!(log[1]["cached"], false);
+        assert!(
+            log[1]["error"]
+                .as_str()
+                .is_some_and(|error| error.contains("500")),
+            "{log:?}"
+        );
+    }
+}
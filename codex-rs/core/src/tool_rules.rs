@@ -0,0 +1,487 @@
+//! `[[tool_rules]]`: allow/ask/deny decisions for tool calls, declared in
+//! config and checked in-process before a call runs and before any hook
+//! sees it.
+//!
+//! A rule matches a call when every condition it sets holds: the tool name,
+//! a regex over the command line or the raw arguments, globs over the paths
+//! the call writes, and the sandbox mode of the turn. Of the matching rules
+//! the strictest decision wins. `deny` refuses the call, `ask` always shows
+//! an approval prompt and skips the pre-approval hooks, and `allow` skips the
+//! prompt the approval policy would otherwise show. Calls no rule matches are
+//! left to the approval policy and hooks as before.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::protocol::SandboxPolicy;
+use regex_lite::Regex;
+use serde::Serialize;
+use wildmatch::WildMatch;
+
+use crate::codex::TurnContext;
+use crate::config::types::ToolRuleConfig;
+use crate::config::types::ToolRuleDecision;
+use crate::path_utils::glob_subjects;
+use crate::path_utils::normalize_lexically;
+use crate::tools::context::ToolPayload;
+use crate::tools::sandboxing::ExecApprovalRequirement;
+use crate::tools::spec::SHELL_TOOL_NAMES;
+
+const SANDBOX_MODES: &[&str] = &[
+    "read-only",
+    "workspace-write",
+    "danger-full-access",
+    "external-sandbox",
+];
+
+/// The compiled `[[tool_rules]]` of a session.
+#[derive(Debug, Default)]
+pub(crate) struct ToolRules {
+    rules: Vec<ToolRule>,
+}
+
+#[derive(Debug)]
+struct ToolRule {
+    name: String,
+    tools: Vec<String>,
+    command: Option<Regex>,
+    arguments: Option<Regex>,
+    paths: Vec<WildMatch>,
+    sandbox: Vec<String>,
+    decision: ToolRuleDecision,
+    reason: Option<String>,
+}
+
+/// What a tool call is checked against. Serialized as the call's part of
+/// the `[tool_policy]` input.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct RuleSubject<'a> {
+    pub(crate) tool_name: &'a str,
+    /// The command line of a shell tool call.
+    pub(crate) command: Option<&'a str>,
+    #[serde(rename = "input")]
+    pub(crate) arguments: &'a str,
+    /// Absolute paths the call writes.
+    pub(crate) paths: &'a [PathBuf],
+    pub(crate) cwd: &'a Path,
+    pub(crate) sandbox_policy: &'a SandboxPolicy,
+}
+
+/// The decision of the strictest rule that matched a call.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RuleVerdict {
+    pub(crate) decision: ToolRuleDecision,
+    pub(crate) rule: String,
+    pub(crate) reason: Option<String>,
+}
+
+/// `allow` and `ask` verdicts for the turn's tool calls, keyed by call id,
+/// for the approval steps that run after the router.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ToolRuleVerdicts(pub(crate) HashMap<String, RuleVerdict>);
+
+impl ToolRules {
+    /// Compiles `configs`, rejecting invalid regexes and sandbox modes.
+    pub(crate) fn new(configs: &[ToolRuleConfig]) -> Result<Self, String> {
+        let mut rules = Vec::with_capacity(configs.len());
+        for (index, config) in configs.iter().enumerate() {
+            let name = config
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("#{}", index + 1));
+            let compile = |pattern: &Option<String>, key: &str| {
+                pattern
+                    .as_deref()
+                    .map(Regex::new)
+                    .transpose()
+                    .map_err(|err| {
+                        format!("invalid `{key}` in `[[tool_rules]]` entry {name}: {err}")
+                    })
+            };
+            let command = compile(&config.command, "command")?;
+            let arguments = compile(&config.arguments, "arguments")?;
+            if let Some(mode) = config
+                .sandbox
+                .iter()
+                .find(|mode| !SANDBOX_MODES.contains(&mode.as_str()))
+            {
+                return Err(format!(
+                    "unknown sandbox mode `{mode}` in `[[tool_rules]]` entry {name}; expected one of {}",
+                    SANDBOX_MODES.join(", ")
+                ));
+            }
+            rules.push(ToolRule {
+                name,
+                tools: config.tools.clone(),
+                command,
+                arguments,
+                paths: config
+                    .paths
+                    .iter()
+                    .map(|pattern| WildMatch::new(pattern))
+                    .collect(),
+                sandbox: config.sandbox.clone(),
+                decision: config.decision,
+                reason: config.reason.clone(),
+            });
+        }
+        Ok(Self { rules })
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The strictest matching rule's verdict; the first one listed wins a tie.
+    pub(crate) fn evaluate(&self, subject: &RuleSubject<'_>) -> Option<RuleVerdict> {
+        let sandbox_mode = subject.sandbox_policy.to_string();
+        let mut strictest: Option<&ToolRule> = None;
+        for rule in &self.rules {
+            if strictest.is_some_and(|current| current.decision >= rule.decision) {
+                continue;
+            }
+            if rule.matches(subject, &sandbox_mode) {
+                strictest = Some(rule);
+            }
+        }
+        strictest.map(|rule| RuleVerdict {
+            decision: rule.decision,
+            rule: rule.name.clone(),
+            reason: rule.reason.clone(),
+        })
+    }
+}
+
+impl ToolRule {
+    fn matches(&self, subject: &RuleSubject<'_>, sandbox_mode: &str) -> bool {
+        if !self.tools.is_empty()
+            && !self.tools.iter().any(|tool| {
+                tool == subject.tool_name
+                    || (tool == "shell" && SHELL_TOOL_NAMES.contains(&subject.tool_name))
+            })
+        {
+            return false;
+        }
+        if !self.sandbox.is_empty() && !self.sandbox.iter().any(|mode| mode == sandbox_mode) {
+            return false;
+        }
+        if let Some(command) = &self.command
+            && !subject.command.is_some_and(|line| command.is_match(line))
+        {
+            return false;
+        }
+        if let Some(arguments) = &self.arguments
+            && !arguments.is_match(subject.arguments)
+        {
+            return false;
+        }
+        if !self.paths.is_empty()
+            && !subject
+                .paths
+                .iter()
+                .any(|path| self.matches_path(path, subject.cwd))
+        {
+            return false;
+        }
+        true
+    }
+
+    fn matches_path(&self, path: &Path, cwd: &Path) -> bool {
+        let (absolute, relative) = glob_subjects(path, cwd);
+        self.paths.iter().any(|glob| {
+            glob.matches(&absolute) || relative.as_deref().is_some_and(|rel| glob.matches(rel))
+        })
+    }
+}
+
+impl RuleVerdict {
+    /// Returned to the model when the rule refuses a call.
+    pub(crate) fn denial_message(&self, tool_name: &str) -> String {
+        match &self.reason {
+            Some(reason) => format!("{tool_name} was blocked by rule {}: {reason}", self.rule),
+            None => format!("{tool_name} was blocked by rule {}.", self.rule),
+        }
+    }
+
+    /// Shown with the approval prompt an `ask` rule forces.
+    pub(crate) fn prompt_reason(&self) -> String {
+        match &self.reason {
+            Some(reason) => format!("Rule {} asks for approval: {reason}", self.rule),
+            None => format!("Rule {} asks for approval", self.rule),
+        }
+    }
+
+    /// `requirement` with the verdict applied: `allow` drops a prompt and
+    /// `ask` adds one. Calls the policy forbids stay forbidden.
+    pub(crate) fn apply(&self, requirement: ExecApprovalRequirement) -> ExecApprovalRequirement {
+        match (self.decision, requirement) {
+            (ToolRuleDecision::Allow, ExecApprovalRequirement::NeedsApproval { .. }) => {
+                ExecApprovalRequirement::Skip {
+                    bypass_sandbox: false,
+                    proposed_execpolicy_amendment: None,
+                }
+            }
+            (ToolRuleDecision::Ask, ExecApprovalRequirement::Skip { .. }) => {
+                ExecApprovalRequirement::NeedsApproval {
+                    reason: Some(self.prompt_reason()),
+                    proposed_execpolicy_amendment: None,
+                }
+            }
+            (_, requirement) => requirement,
+        }
+    }
+}
+
+/// The stricter of two verdicts; `first` wins a tie.
+pub(crate) fn strictest(
+    first: Option<RuleVerdict>,
+    second: Option<RuleVerdict>,
+) -> Option<RuleVerdict> {
+    match (first, second) {
+        (Some(first), Some(second)) if second.decision > first.decision => Some(second),
+        (Some(first), _) => Some(first),
+        (None, second) => second,
+    }
+}
+
+/// The verdict the router recorded for `call_id`, if a rule matched it.
+pub(crate) fn verdict_for(turn_context: &TurnContext, call_id: &str) -> Option<RuleVerdict> {
+    turn_context
+        .extensions
+        .get::<ToolRuleVerdicts>()?
+        .0
+        .get(call_id)
+        .cloned()
+}
+
+/// Absolute paths a call writes: the files named in patch input and a
+/// top-level `path` argument.
+pub(crate) fn written_paths(payload: &ToolPayload, cwd: &Path) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    match payload {
+        ToolPayload::Custom { input } => paths.extend(patch_paths(input)),
+        ToolPayload::Function { arguments } => {
+            if let Ok(arguments) = serde_json::from_str::<serde_json::Value>(arguments) {
+                if let Some(input) = arguments.get("input").and_then(|value| value.as_str()) {
+                    paths.extend(patch_paths(input));
+                }
+                if let Some(path) = arguments.get("path").and_then(|value| value.as_str()) {
+                    paths.push(path.to_string());
+                }
+            }
+        }
+        ToolPayload::LocalShell { .. } | ToolPayload::Mcp { .. } => {}
+    }
+    paths
+        .into_iter()
+        .map(|path| normalize_lexically(Path::new(&path), cwd))
+        .collect()
+}
+
+fn patch_paths(input: &str) -> impl Iterator<Item = String> + '_ {
+    const HEADERS: &[&str] = &[
+        "*** Add File: ",
+        "*** Update File: ",
+        "*** Delete File: ",
+        "*** Move to: ",
+    ];
+    input.lines().filter_map(|line| {
+        let line = line.trim_start();
+        HEADERS
+            .iter()
+            .find_map(|header| line.strip_prefix(header))
+            .map(|path| path.trim().to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn rule(decision: ToolRuleDecision) -> ToolRuleConfig {
+        ToolRuleConfig {
+            name: None,
+            tools: Vec::new(),
+            command: None,
+            arguments: None,
+            paths: Vec::new(),
+            sandbox: Vec::new(),
+            decision,
+            reason: None,
+        }
+    }
+
+    fn subject<'a>(
+        tool_name: &'a str,
+        command: Option<&'a str>,
+        paths: &'a [PathBuf],
+        sandbox_policy: &'a SandboxPolicy,
+    ) -> RuleSubject<'a> {
+        RuleSubject {
+            tool_name,
+            command,
+            arguments: "{}",
+            paths,
+            cwd: Path::new("/repo"),
+            sandbox_policy,
+        }
+    }
+
+    #[test]
+    fn strictest_matching_rule_wins() {
+        let rules = ToolRules::new(&[
+            ToolRuleConfig {
+                tools: vec!["shell".to_string()],
+                ..rule(ToolRuleDecision::Allow)
+            },
+            ToolRuleConfig {
+                name: Some("no-force-push".to_string()),
+                command: Some(r"git push .*--force".to_string()),
+                reason: Some("force pushes rewrite shared history".to_string()),
+                ..rule(ToolRuleDecision::Deny)
+            },
+        ])
+        .expect("rules compile");
+        let policy = SandboxPolicy::ReadOnly;
+
+        let verdict = rules
+            .evaluate(&subject(
+                "shell_command",
+                Some("git push origin main --force"),
+                &[],
+                &policy,
+            ))
+            .expect("a rule matches");
+        assert_eq!(verdict.decision, ToolRuleDecision::Deny);
+        assert_eq!(
+            verdict.denial_message("shell_command"),
+            "shell_command was blocked by rule no-force-push: force pushes rewrite shared history"
+        );
+
+        let verdict = rules.evaluate(&subject("exec_command", Some("ls"), &[], &policy));
+        assert_eq!(
+            verdict.map(|verdict| (verdict.decision, verdict.rule)),
+            Some((ToolRuleDecision::Allow, "#1".to_string()))
+        );
+        assert_eq!(
+            rules.evaluate(&subject("read_file", None, &[], &policy)),
+            None
+        );
+    }
+
+    #[test]
+    fn every_condition_must_hold() {
+        let rules = ToolRules::new(&[ToolRuleConfig {
+            paths: vec!["**/.env".to_string(), "secrets/*".to_string()],
+            sandbox: vec!["danger-full-access".to_string()],
+            ..rule(ToolRuleDecision::Ask)
+        }])
+        .expect("rules compile");
+        let env = [PathBuf::from("/repo/app/.env")];
+        let secret = [PathBuf::from("/repo/secrets/token")];
+        let dotted = [PathBuf::from("/repo/x/../secrets/token")];
+        let other = [PathBuf::from("/repo/src/main.rs")];
+        let full = SandboxPolicy::DangerFullAccess;
+        let read_only = SandboxPolicy::ReadOnly;
+
+        assert!(
+            rules
+                .evaluate(&subject("apply_patch", None, &env, &full))
+                .is_some()
+        );
+        assert!(
+            rules
+                .evaluate(&subject("write_file", None, &secret, &full))
+                .is_some()
+        );
+        assert!(
+            rules
+                .evaluate(&subject("write_file", None, &dotted, &full))
+                .is_some()
+        );
+        assert!(
+            rules
+                .evaluate(&subject("apply_patch", None, &other, &full))
+                .is_none()
+        );
+        assert!(
+            rules
+                .evaluate(&subject("apply_patch", None, &env, &read_only))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn strictest_prefers_the_first_verdict_on_a_tie() {
+        let verdict = |decision, rule: &str| RuleVerdict {
+            decision,
+            rule: rule.to_string(),
+            reason: None,
+        };
+        let allow = verdict(ToolRuleDecision::Allow, "local");
+        let ask = verdict(ToolRuleDecision::Ask, "remote");
+
+        assert_eq!(
+            strictest(Some(allow.clone()), Some(ask.clone())),
+            Some(ask.clone())
+        );
+        assert_eq!(
+            strictest(
+                Some(allow.clone()),
+                Some(verdict(ToolRuleDecision::Allow, "remote"))
+            ),
+            Some(allow.clone())
+        );
+        assert_eq!(strictest(None, Some(ask.clone())), Some(ask));
+        assert_eq!(strictest(Some(allow.clone()), None), Some(allow));
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        let err = ToolRules::new(&[ToolRuleConfig {
+            command: Some("(".to_string()),
+            ..rule(ToolRuleDecision::Deny)
+        }])
+        .expect_err("bad regex");
+        assert!(err.contains("`command`"), "{err}");
+
+        let err = ToolRules::new(&[ToolRuleConfig {
+            sandbox: vec!["none".to_string()],
+            ..rule(ToolRuleDecision::Deny)
+        }])
+        .expect_err("bad sandbox mode");
+        assert!(err.contains("unknown sandbox mode `none`"), "{err}");
+    }
+
+    #[test]
+    fn patch_input_and_path_arguments_name_written_paths() {
+        let cwd = Path::new("/repo");
+        let patch = "*** Begin Patch\n*** Update File: src/lib.rs\n@@\n-a\n+b\n*** Move to: src/main.rs\n*** Add File: /tmp/x\n+y\n*** Update File: x/../secrets/token\n@@\n-a\n+b\n*** End Patch";
+        assert_eq!(
+            written_paths(
+                &ToolPayload::Custom {
+                    input: patch.to_string()
+                },
+                cwd
+            ),
+            vec![
+                PathBuf::from("/repo/src/lib.rs"),
+                PathBuf::from("/repo/src/main.rs"),
+                PathBuf::from("/tmp/x"),
+                PathBuf::from("/repo/secrets/token"),
+            ]
+        );
+        assert_eq!(
+            written_paths(
+                &ToolPayload::Function {
+                    arguments: r#"{"path":"notes.md","content":"hi"}"#.to_string()
+                },
+                cwd
+            ),
+            vec![PathBuf::from("/repo/notes.md")]
+        );
+    }
+}
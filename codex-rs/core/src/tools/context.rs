@@ -1,8 +1,10 @@
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::config::types::OtelToolPayloads;
 use crate::tools::TELEMETRY_PREVIEW_MAX_BYTES;
 use crate::tools::TELEMETRY_PREVIEW_MAX_LINES;
 use crate::tools::TELEMETRY_PREVIEW_TRUNCATION_NOTICE;
+use crate::tools::extensions::TurnExtensions;
 use crate::turn_diff_tracker::TurnDiffTracker;
 use codex_protocol::models::FunctionCallOutputContentItem;
 use codex_protocol::models::FunctionCallOutputPayload;
@@ -10,6 +12,8 @@ use codex_protocol::models::ResponseInputItem;
 use codex_protocol::models::ShellToolCallParams;
 use codex_utils_string::take_bytes_at_char_boundary;
 use mcp_types::CallToolResult;
+use sha2::Digest;
+use sha2::Sha256;
 use std::borrow::Cow;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -26,6 +30,13 @@ pub struct ToolInvocation {
     pub payload: ToolPayload,
 }
 
+impl ToolInvocation {
+    /// Values shared with hooks and other tool calls in the same turn.
+    pub fn extensions(&self) -> &TurnExtensions {
+        &self.turn.extensions
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum ToolPayload {
     Function {
@@ -53,6 +64,29 @@ impl ToolPayload {
             ToolPayload::Mcp { raw_arguments, .. } => Cow::Borrowed(raw_arguments),
         }
     }
+
+    /// [`Self::log_payload`] rewritten by the `[otel.tool_payloads]` policy,
+    /// for OTEL events and tracing.
+    pub fn telemetry_payload(&self, tool_name: &str, policy: &OtelToolPayloads) -> Cow<'_, str> {
+        let payload = self.log_payload();
+        if policy.omit.iter().any(|name| name == tool_name) {
+            return Cow::Owned(format!("[omitted {} bytes]", payload.len()));
+        }
+        if policy.hash.iter().any(|name| name == tool_name) {
+            let digest = Sha256::digest(payload.as_bytes());
+            return Cow::Owned(format!("[sha256:{digest:x}, {} bytes]", payload.len()));
+        }
+        match policy.max_bytes {
+            Some(max_bytes) if payload.len() > max_bytes => {
+                let kept = take_bytes_at_char_boundary(&payload, max_bytes);
+                Cow::Owned(format!(
+                    "{kept}[... {} more bytes]",
+                    payload.len() - kept.len()
+                ))
+            }
+            _ => payload,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -235,4 +269,34 @@ mod tests {
         assert!(lines.len() <= TELEMETRY_PREVIEW_MAX_LINES + 1);
         assert_eq!(lines.last(), Some(&TELEMETRY_PREVIEW_TRUNCATION_NOTICE));
     }
+
+    #[test]
+    fn telemetry_payload_applies_policy() {
+        let policy = OtelToolPayloads {
+            omit: vec!["shell".to_string()],
+            hash: vec!["apply_patch".to_string()],
+            max_bytes: Some(4),
+        };
+        let payload = ToolPayload::Function {
+            arguments: r#"{"command":["curl","-H","token"]}"#.to_string(),
+        };
+
+        assert_eq!(
+            payload.telemetry_payload("shell", &policy),
+            "[omitted 33 bytes]"
+        );
+        assert!(
+            payload
+                .telemetry_payload("apply_patch", &policy)
+                .starts_with("[sha256:")
+        );
+        assert_eq!(
+            payload.telemetry_payload("read_file", &policy),
+            "{\"co[... 29 more bytes]"
+        );
+        assert_eq!(
+            payload.telemetry_payload("shell", &OtelToolPayloads::default()),
+            payload.log_payload()
+        );
+    }
 }
@@ -3,6 +3,7 @@ use crate::codex::TurnContext;
 use crate::error::CodexErr;
 use crate::error::SandboxErr;
 use crate::exec::ExecToolCallOutput;
+use crate::features::Feature;
 use crate::function_tool::FunctionCallError;
 use crate::parse_command::parse_command;
 use crate::protocol::EventMsg;
@@ -178,6 +179,12 @@ impl ToolEmitter {
                     let mut guard = tracker.lock().await;
                     guard.on_patch_begin(changes);
                 }
+                ctx.session
+                    .services
+                    .session_diff_tracker
+                    .lock()
+                    .await
+                    .on_patch_begin(changes);
                 ctx.session
                     .send_event(
                         ctx.turn,
@@ -288,11 +295,16 @@ impl ToolEmitter {
                 };
                 (event, result)
             }
-            Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Timeout { output })))
-            | Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Denied { output }))) => {
+            Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Timeout { output }))) => {
+                let response = self.format_exec_output_for_model(&output, ctx);
+                let event = ToolEventStage::Failure(ToolEventFailure::Output(*output));
+                let result = Err(FunctionCallError::Timeout(response));
+                (event, result)
+            }
+            Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Denied { output }))) => {
                 let response = self.format_exec_output_for_model(&output, ctx);
                 let event = ToolEventStage::Failure(ToolEventFailure::Output(*output));
-                let result = Err(FunctionCallError::RespondToModel(response));
+                let result = Err(FunctionCallError::SandboxDenied(response));
                 (event, result)
             }
             Err(ToolError::Codex(err)) => {
@@ -437,6 +449,25 @@ async fn emit_patch_end(
     stderr: String,
     success: bool,
 ) {
+    if success && ctx.session.enabled(Feature::ExternalEdits) {
+        let external_edits = &ctx.session.services.external_edits;
+        for (path, change) in &changes {
+            match change {
+                FileChange::Delete { .. } => external_edits.forget(path),
+                FileChange::Update {
+                    move_path: Some(destination),
+                    ..
+                } => {
+                    external_edits.forget(path);
+                    external_edits.observe(destination).await;
+                }
+                FileChange::Add { .. } | FileChange::Update { .. } => {
+                    external_edits.observe(path).await;
+                }
+            }
+        }
+    }
+
     ctx.session
         .send_event(
             ctx.turn,
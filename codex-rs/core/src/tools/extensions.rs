@@ -0,0 +1,95 @@
+//! Turn-scoped values shared between hooks, the router and tool handlers.
+//!
+//! Each value is keyed by its type, so a feature that needs to hand data from
+//! one stage of a tool call to another defines a small type for it instead of
+//! adding a field to `TurnContext` or `ToolInvocation`. Values live until the
+//! turn ends.
+
+use std::any::Any;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Default)]
+pub struct TurnExtensions {
+    values: Mutex<HashMap<TypeId, Box<dyn Any + Send + Sync>>>,
+}
+
+impl TurnExtensions {
+    /// Stores `value`, returning the previous value of the same type.
+    pub fn insert<T: Any + Send + Sync>(&self, value: T) -> Option<T> {
+        let mut values = self.values.lock().ok()?;
+        values
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|previous| previous.downcast().ok())
+            .map(|previous| *previous)
+    }
+
+    /// A copy of the stored value of type `T`.
+    pub fn get<T: Any + Send + Sync + Clone>(&self) -> Option<T> {
+        let values = self.values.lock().ok()?;
+        values.get(&TypeId::of::<T>())?.downcast_ref::<T>().cloned()
+    }
+
+    /// Changes the stored value of type `T` in place, starting from
+    /// `T::default()` when there is none.
+    pub fn update<T: Any + Send + Sync + Default>(&self, f: impl FnOnce(&mut T)) {
+        let Ok(mut values) = self.values.lock() else {
+            return;
+        };
+        let value = values
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(T::default()));
+        if let Some(value) = value.downcast_mut::<T>() {
+            f(value);
+        }
+    }
+}
+
+impl std::fmt::Debug for TurnExtensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let len = self.values.lock().map(|values| values.len()).unwrap_or(0);
+        f.debug_struct("TurnExtensions").field("len", &len).finish()
+    }
+}
+
+/// Environment variables added to the shell commands that follow in the
+/// turn, e.g. a ticket id printed by a pre-approval hook.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct ToolEnv(pub(crate) HashMap<String, String>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct TicketId(String);
+
+    #[test]
+    fn values_are_keyed_by_type() {
+        let extensions = TurnExtensions::default();
+        assert_eq!(extensions.get::<TicketId>(), None);
+
+        assert_eq!(extensions.insert(TicketId("T-1".to_string())), None);
+        assert_eq!(
+            extensions.insert(TicketId("T-2".to_string())),
+            Some(TicketId("T-1".to_string()))
+        );
+        extensions.update::<ToolEnv>(|env| {
+            env.0.insert("TICKET".to_string(), "T-2".to_string());
+        });
+
+        assert_eq!(
+            extensions.get::<TicketId>(),
+            Some(TicketId("T-2".to_string()))
+        );
+        assert_eq!(
+            extensions.get::<ToolEnv>(),
+            Some(ToolEnv(HashMap::from([(
+                "TICKET".to_string(),
+                "T-2".to_string()
+            )])))
+        );
+    }
+}
@@ -10,7 +10,11 @@ use crate::client_common::tools::ResponsesApiTool;
 use crate::client_common::tools::ToolSpec;
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::exec_env::create_env;
 use crate::function_tool::FunctionCallError;
+use crate::lsp::format_report;
+use crate::protocol::ExecCommandSource;
+use crate::sandboxing::SandboxPermissions;
 use crate::tools::context::SharedTurnDiffTracker;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
@@ -22,10 +26,16 @@ use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
 use crate::tools::runtimes::apply_patch::ApplyPatchRequest;
 use crate::tools::runtimes::apply_patch::ApplyPatchRuntime;
+use crate::tools::runtimes::shell::ShellRequest;
+use crate::tools::runtimes::shell::ShellRuntime;
+use crate::tools::sandboxing::ExecApprovalRequirement;
 use crate::tools::sandboxing::ToolCtx;
 use crate::tools::spec::ApplyPatchToolArgs;
 use crate::tools::spec::JsonSchema;
+use crate::truncate::formatted_truncate_text;
 use async_trait::async_trait;
+use codex_apply_patch::ApplyPatchAction;
+use codex_apply_patch::ApplyPatchFileChange;
 
 pub struct ApplyPatchHandler;
 
@@ -61,7 +71,7 @@ impl ToolHandler for ApplyPatchHandler {
         let patch_input = match payload {
             ToolPayload::Function { arguments } => {
                 let args: ApplyPatchToolArgs = serde_json::from_str(&arguments).map_err(|e| {
-                    FunctionCallError::RespondToModel(format!(
+                    FunctionCallError::InvalidArguments(format!(
                         "failed to parse function arguments: {e:?}"
                     ))
                 })?;
@@ -75,86 +85,92 @@ impl ToolHandler for ApplyPatchHandler {
             }
         };
 
-        // Re-parse and verify the patch so we can compute changes and approval.
-        // Avoid building temporary ExecParams/command vectors; derive directly from inputs.
-        let cwd = turn.cwd.clone();
-        let command = vec!["apply_patch".to_string(), patch_input.clone()];
-        match codex_apply_patch::maybe_parse_apply_patch_verified(&command, &cwd) {
-            codex_apply_patch::MaybeApplyPatchVerified::Body(changes) => {
-                match apply_patch::apply_patch(session.as_ref(), turn.as_ref(), &call_id, changes)
-                    .await
-                {
-                    InternalApplyPatchInvocation::Output(item) => {
-                        let content = item?;
-                        Ok(ToolOutput::Function {
-                            content,
-                            content_items: None,
-                            success: Some(true),
-                        })
-                    }
-                    InternalApplyPatchInvocation::DelegateToExec(apply) => {
-                        let emitter = ToolEmitter::apply_patch(
-                            convert_apply_patch_to_protocol(&apply.action),
-                            !apply.user_explicitly_approved_this_action,
-                        );
-                        let event_ctx = ToolEventCtx::new(
-                            session.as_ref(),
-                            turn.as_ref(),
-                            &call_id,
-                            Some(&tracker),
-                        );
-                        emitter.begin(event_ctx).await;
-
-                        let req = ApplyPatchRequest {
-                            patch: apply.action.patch.clone(),
-                            cwd: apply.action.cwd.clone(),
-                            timeout_ms: None,
-                            user_explicitly_approved: apply.user_explicitly_approved_this_action,
-                            codex_exe: turn.codex_linux_sandbox_exe.clone(),
-                        };
-
-                        let mut orchestrator = ToolOrchestrator::new();
-                        let mut runtime = ApplyPatchRuntime::new();
-                        let tool_ctx = ToolCtx {
-                            session: session.as_ref(),
-                            turn: turn.as_ref(),
-                            call_id: call_id.clone(),
-                            tool_name: tool_name.to_string(),
-                        };
-                        let out = orchestrator
-                            .run(&mut runtime, &req, &tool_ctx, &turn, turn.approval_policy)
-                            .await;
-                        let event_ctx = ToolEventCtx::new(
-                            session.as_ref(),
-                            turn.as_ref(),
-                            &call_id,
-                            Some(&tracker),
-                        );
-                        let content = emitter.finish(event_ctx, out).await?;
-                        Ok(ToolOutput::Function {
-                            content,
-                            content_items: None,
-                            success: Some(true),
-                        })
-                    }
+        let content = apply_patch_input(
+            session.as_ref(),
+            turn.as_ref(),
+            &tracker,
+            &call_id,
+            &tool_name,
+            patch_input,
+        )
+        .await?;
+        Ok(ToolOutput::Function {
+            content,
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
+
+/// Verifies `patch_input`, asks for approval when the safety check requires
+/// it and applies the patch under the sandbox, returning the text for the
+/// model. Shared with tools that express their edits as patches.
+pub(crate) async fn apply_patch_input(
+    session: &Session,
+    turn: &TurnContext,
+    tracker: &SharedTurnDiffTracker,
+    call_id: &str,
+    tool_name: &str,
+    patch_input: String,
+) -> Result<String, FunctionCallError> {
+    // Re-parse and verify the patch so we can compute changes and approval.
+    // Avoid building temporary ExecParams/command vectors; derive directly from inputs.
+    let command = vec!["apply_patch".to_string(), patch_input];
+    match codex_apply_patch::maybe_parse_apply_patch_verified(&command, &turn.cwd) {
+        codex_apply_patch::MaybeApplyPatchVerified::Body(changes) => {
+            match apply_patch::apply_patch(session, turn, call_id, changes).await {
+                InternalApplyPatchInvocation::Output(item) => item,
+                InternalApplyPatchInvocation::DelegateToExec(apply) => {
+                    let emitter = ToolEmitter::apply_patch(
+                        convert_apply_patch_to_protocol(&apply.action),
+                        !apply.user_explicitly_approved_this_action,
+                    );
+                    let event_ctx = ToolEventCtx::new(session, turn, call_id, Some(tracker));
+                    emitter.begin(event_ctx).await;
+
+                    let req = ApplyPatchRequest {
+                        patch: apply.action.patch.clone(),
+                        cwd: apply.action.cwd.clone(),
+                        timeout_ms: None,
+                        user_explicitly_approved: apply.user_explicitly_approved_this_action,
+                        codex_exe: turn.codex_linux_sandbox_exe.clone(),
+                    };
+
+                    let mut orchestrator = ToolOrchestrator::new();
+                    let mut runtime = ApplyPatchRuntime::new();
+                    let tool_ctx = ToolCtx {
+                        session,
+                        turn,
+                        call_id: call_id.to_string(),
+                        tool_name: tool_name.to_string(),
+                    };
+                    let out = orchestrator
+                        .run(&mut runtime, &req, &tool_ctx, turn, turn.approval_policy)
+                        .await;
+                    let event_ctx = ToolEventCtx::new(session, turn, call_id, Some(tracker));
+                    let content = emitter.finish(event_ctx, out).await?;
+                    let content =
+                        append_verification(session, turn, call_id, &apply.action, content).await;
+                    let content = append_diagnostics(session, turn, &apply.action, content).await;
+                    Ok(append_review_note(content, apply.review_note))
                 }
             }
-            codex_apply_patch::MaybeApplyPatchVerified::CorrectnessError(parse_error) => {
-                Err(FunctionCallError::RespondToModel(format!(
-                    "apply_patch verification failed: {parse_error}"
-                )))
-            }
-            codex_apply_patch::MaybeApplyPatchVerified::ShellParseError(error) => {
-                tracing::trace!("Failed to parse apply_patch input, {error:?}");
-                Err(FunctionCallError::RespondToModel(
-                    "apply_patch handler received invalid patch input".to_string(),
-                ))
-            }
-            codex_apply_patch::MaybeApplyPatchVerified::NotApplyPatch => {
-                Err(FunctionCallError::RespondToModel(
-                    "apply_patch handler received non-apply_patch input".to_string(),
-                ))
-            }
+        }
+        codex_apply_patch::MaybeApplyPatchVerified::CorrectnessError(parse_error) => {
+            Err(FunctionCallError::RespondToModel(format!(
+                "apply_patch verification failed: {parse_error}"
+            )))
+        }
+        codex_apply_patch::MaybeApplyPatchVerified::ShellParseError(error) => {
+            tracing::trace!("Failed to parse apply_patch input, {error:?}");
+            Err(FunctionCallError::RespondToModel(
+                "apply_patch handler received invalid patch input".to_string(),
+            ))
+        }
+        codex_apply_patch::MaybeApplyPatchVerified::NotApplyPatch => {
+            Err(FunctionCallError::RespondToModel(
+                "apply_patch handler received non-apply_patch input".to_string(),
+            ))
         }
     }
 }
@@ -218,6 +234,10 @@ pub(crate) async fn intercept_apply_patch(
                     let event_ctx =
                         ToolEventCtx::new(session, turn, call_id, tracker.as_ref().copied());
                     let content = emitter.finish(event_ctx, out).await?;
+                    let content =
+                        append_verification(session, turn, call_id, &apply.action, content).await;
+                    let content = append_diagnostics(session, turn, &apply.action, content).await;
+                    let content = append_review_note(content, apply.review_note);
                     Ok(Some(ToolOutput::Function {
                         content,
                         content_items: None,
@@ -239,6 +259,179 @@ pub(crate) async fn intercept_apply_patch(
     }
 }
 
+/// Runs the configured `apply_patch_verify` command after a patch landed and
+/// appends its result to `content`. On failure the applied changes are listed
+/// as well, so the model can fix the edit without re-reading every file, and
+/// the model is asked to retry until `max_retries` checks in a row have failed.
+async fn append_verification(
+    session: &Session,
+    turn: &TurnContext,
+    call_id: &str,
+    action: &ApplyPatchAction,
+    content: String,
+) -> String {
+    let Some(verify) = turn.client.config().apply_patch_verify.clone() else {
+        return content;
+    };
+
+    let command = session.user_shell().derive_exec_args(&verify.command, true);
+    // The check gets its own call id so clients render it as a separate
+    // command rather than as more output of the patch.
+    let verify_call_id = format!("{call_id}-verify");
+    let emitter = ToolEmitter::shell(
+        command.clone(),
+        action.cwd.clone(),
+        ExecCommandSource::Agent,
+        false,
+    );
+    emitter
+        .begin(ToolEventCtx::new(session, turn, &verify_call_id, None))
+        .await;
+
+    let req = ShellRequest {
+        command,
+        cwd: action.cwd.clone(),
+        timeout_ms: Some(verify.timeout_ms),
+        env: create_env(&turn.shell_environment_policy),
+        sandbox_permissions: SandboxPermissions::UseDefault,
+        justification: None,
+        // The user configured this command, so it only needs the sandbox.
+        exec_approval_requirement: ExecApprovalRequirement::Skip {
+            bypass_sandbox: false,
+            proposed_execpolicy_amendment: None,
+        },
+        prompts_only_for_escalation: false,
+        pty: None,
+    };
+    let tool_ctx = ToolCtx {
+        session,
+        turn,
+        call_id: verify_call_id.clone(),
+        tool_name: "apply_patch".to_string(),
+    };
+    let out = ToolOrchestrator::new()
+        .run(
+            &mut ShellRuntime::new(),
+            &req,
+            &tool_ctx,
+            turn,
+            turn.approval_policy,
+        )
+        .await;
+    let result = emitter
+        .finish(ToolEventCtx::new(session, turn, &verify_call_id, None), out)
+        .await;
+
+    let failures = session
+        .record_patch_verification(&turn.sub_id, result.is_ok())
+        .await;
+    match result {
+        Ok(output) => format!(
+            "{content}\n\nVerification `{}` passed:\n{output}",
+            verify.command
+        ),
+        Err(err) => {
+            let next_step = if failures <= verify.max_retries {
+                format!(
+                    "Fix the failure and apply a corrected patch (retry {failures} of {}).",
+                    verify.max_retries
+                )
+            } else {
+                format!(
+                    "No retries left ({} allowed). Stop patching and tell the user what still fails.",
+                    verify.max_retries
+                )
+            };
+            format!(
+                "{content}\n\nVerification `{}` failed; the patch is still applied:\n{err}\n\nChanges made by this patch:\n{}\n\n{next_step}",
+                verify.command,
+                formatted_truncate_text(&describe_changes(action), turn.truncation_policy)
+            )
+        }
+    }
+}
+
+/// Appends what the `[lsp]` servers report for the files the patch left
+/// behind, so the model sees type errors right after the edit.
+async fn append_diagnostics(
+    session: &Session,
+    turn: &TurnContext,
+    action: &ApplyPatchAction,
+    content: String,
+) -> String {
+    let config = turn.client.config();
+    if config.lsp.is_empty() {
+        return content;
+    }
+
+    let mut edited = Vec::new();
+    let mut removed = Vec::new();
+    for (path, change) in action.changes() {
+        match change {
+            ApplyPatchFileChange::Add { .. } => edited.push(path.clone()),
+            ApplyPatchFileChange::Delete { .. } => removed.push(path.clone()),
+            ApplyPatchFileChange::Update { move_path, .. } => match move_path {
+                Some(dest) => {
+                    removed.push(path.clone());
+                    edited.push(dest.clone());
+                }
+                None => edited.push(path.clone()),
+            },
+        }
+    }
+    let report = session
+        .services
+        .lsp
+        .diagnostics(&config.lsp, &turn.cwd, &edited, &removed)
+        .await;
+    match format_report(&report, &action.cwd) {
+        Some(section) => format!(
+            "{content}\n\n{}",
+            formatted_truncate_text(&section, turn.truncation_policy)
+        ),
+        None => content,
+    }
+}
+
+fn append_review_note(content: String, note: Option<String>) -> String {
+    match note {
+        Some(note) => format!("{content}\n\n{note}"),
+        None => content,
+    }
+}
+
+/// One section per touched file, sorted by path, in a git-like layout.
+fn describe_changes(action: &ApplyPatchAction) -> String {
+    let cwd = action.cwd.as_path();
+    let display = |path: &Path| path.strip_prefix(cwd).unwrap_or(path).display().to_string();
+    let mut changes: Vec<_> = action.changes().iter().collect();
+    changes.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut sections = Vec::with_capacity(changes.len());
+    for (path, change) in changes {
+        let section = match change {
+            ApplyPatchFileChange::Add { content } => {
+                let lines: Vec<String> = content.lines().map(|line| format!("+{line}")).collect();
+                format!("added {}\n{}", display(path), lines.join("\n"))
+            }
+            ApplyPatchFileChange::Delete { .. } => format!("deleted {}", display(path)),
+            ApplyPatchFileChange::Update {
+                unified_diff,
+                move_path,
+                ..
+            } => {
+                let header = match move_path {
+                    Some(dest) => format!("moved {} -> {}", display(path), display(dest)),
+                    None => format!("modified {}", display(path)),
+                };
+                format!("{header}\n{}", unified_diff.trim_end())
+            }
+        };
+        sections.push(section);
+    }
+    sections.join("\n\n")
+}
+
 /// Returns a custom tool that can be used to edit files. Well-suited for GPT-5 models
 /// https://platform.openai.com/docs/guides/function-calling#custom-tools
 pub(crate) fn create_apply_patch_freeform_tool() -> ToolSpec {
@@ -0,0 +1,235 @@
+use async_trait::async_trait;
+use codex_protocol::protocol::AskForApproval;
+use serde::Deserialize;
+
+use crate::function_tool::FunctionCallError;
+use crate::is_safe_command::is_known_safe_command;
+use crate::sandboxing::SandboxPermissions;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::unified_exec::BackgroundOutput;
+use crate::unified_exec::BackgroundStartRequest;
+use crate::unified_exec::UnifiedExecContext;
+
+/// Handles `shell_bg_start`, `shell_bg_tail` and `shell_bg_kill`.
+pub struct BackgroundShellHandler;
+
+#[derive(Debug, Deserialize)]
+struct StartArgs {
+    cmd: String,
+    #[serde(default)]
+    workdir: Option<String>,
+    #[serde(default = "default_start_yield_time_ms")]
+    yield_time_ms: u64,
+    #[serde(default)]
+    max_output_tokens: Option<usize>,
+    #[serde(default)]
+    sandbox_permissions: SandboxPermissions,
+    #[serde(default)]
+    justification: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TailArgs {
+    id: String,
+    #[serde(default = "default_tail_yield_time_ms")]
+    yield_time_ms: u64,
+    #[serde(default)]
+    max_output_tokens: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KillArgs {
+    id: String,
+}
+
+fn default_start_yield_time_ms() -> u64 {
+    1000
+}
+
+fn default_tail_yield_time_ms() -> u64 {
+    250
+}
+
+#[async_trait]
+impl ToolHandler for BackgroundShellHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn is_mutating(&self, invocation: &ToolInvocation) -> bool {
+        let ToolPayload::Function { arguments } = &invocation.payload else {
+            return true;
+        };
+        match invocation.tool_name.as_str() {
+            "shell_bg_tail" => false,
+            "shell_bg_start" => {
+                let Ok(args) = serde_json::from_str::<StartArgs>(arguments) else {
+                    return true;
+                };
+                let command = invocation
+                    .session
+                    .user_shell()
+                    .derive_exec_args(&args.cmd, true);
+                !is_known_safe_command(&command)
+            }
+            _ => true,
+        }
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            call_id,
+            tool_name,
+            payload,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "{tool_name} handler received unsupported payload"
+                )));
+            }
+        };
+
+        let manager = &session.services.background_shells;
+        let output = match tool_name.as_str() {
+            "shell_bg_start" => {
+                let args: StartArgs = parse_arguments(&tool_name, &arguments)?;
+                if args.sandbox_permissions.requires_escalated_permissions()
+                    && !matches!(turn.approval_policy, AskForApproval::OnRequest)
+                {
+                    return Err(FunctionCallError::RespondToModel(format!(
+                        "approval policy is {policy:?}; reject command — you cannot ask for escalated permissions if the approval policy is {policy:?}",
+                        policy = turn.approval_policy
+                    )));
+                }
+
+                let command = session.user_shell().derive_exec_args(&args.cmd, true);
+                let cwd = match args.workdir.filter(|dir| !dir.is_empty()) {
+                    Some(dir) => turn.resolve_path(Some(dir)),
+                    None => turn.cwd.clone(),
+                };
+                let context = UnifiedExecContext::new(session.clone(), turn.clone(), call_id);
+                let output = manager
+                    .start(
+                        BackgroundStartRequest {
+                            command,
+                            cwd,
+                            yield_time_ms: args.yield_time_ms,
+                            max_output_tokens: args.max_output_tokens,
+                            sandbox_permissions: args.sandbox_permissions,
+                            justification: args.justification,
+                        },
+                        &context,
+                    )
+                    .await
+                    .map_err(|err| {
+                        FunctionCallError::RespondToModel(format!("shell_bg_start failed: {err}"))
+                    })?;
+                if let Some(id) = &output.id {
+                    session
+                        .notify_background_event(
+                            turn.as_ref(),
+                            format!("Started background process {id}: {}", args.cmd),
+                        )
+                        .await;
+                }
+                output
+            }
+            "shell_bg_tail" => {
+                let args: TailArgs = parse_arguments(&tool_name, &arguments)?;
+                manager
+                    .tail(&args.id, args.yield_time_ms, args.max_output_tokens)
+                    .await
+                    .map_err(|err| {
+                        FunctionCallError::RespondToModel(format!("shell_bg_tail failed: {err}"))
+                    })?
+            }
+            "shell_bg_kill" => {
+                let args: KillArgs = parse_arguments(&tool_name, &arguments)?;
+                let output = manager.kill(&args.id, None).await.map_err(|err| {
+                    FunctionCallError::RespondToModel(format!("shell_bg_kill failed: {err}"))
+                })?;
+                session
+                    .notify_background_event(
+                        turn.as_ref(),
+                        format!("Killed background process {}.", args.id),
+                    )
+                    .await;
+                output
+            }
+            other => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "background shell handler does not support tool {other}"
+                )));
+            }
+        };
+
+        Ok(ToolOutput::Function {
+            content: format_output(&output),
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
+
+fn parse_arguments<T: for<'de> Deserialize<'de>>(
+    tool_name: &str,
+    arguments: &str,
+) -> Result<T, FunctionCallError> {
+    serde_json::from_str(arguments).map_err(|err| {
+        FunctionCallError::RespondToModel(format!("failed to parse {tool_name} arguments: {err:?}"))
+    })
+}
+
+fn format_output(output: &BackgroundOutput) -> String {
+    let status = match (&output.id, output.running, output.exit_code) {
+        (Some(id), true, _) => format!("Background process {id} is running"),
+        (Some(id), false, Some(code)) => format!("Background process {id} exited with code {code}"),
+        (Some(id), false, None) => format!("Background process {id} stopped"),
+        (None, _, Some(code)) => {
+            format!("Process exited with code {code} before it could be backgrounded")
+        }
+        (None, _, None) => "Process exited before it could be backgrounded".to_string(),
+    };
+    format!("{status}\nOutput:\n{}", output.output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn format_output_reports_status() {
+        let running = BackgroundOutput {
+            id: Some("bg-1".to_string()),
+            output: "listening on :3000\n".to_string(),
+            exit_code: None,
+            running: true,
+        };
+        assert_eq!(
+            format_output(&running),
+            "Background process bg-1 is running\nOutput:\nlistening on :3000\n"
+        );
+
+        let exited_early = BackgroundOutput {
+            id: None,
+            output: "boom".to_string(),
+            exit_code: Some(1),
+            running: false,
+        };
+        assert_eq!(
+            format_output(&exited_early),
+            "Process exited with code 1 before it could be backgrounded\nOutput:\nboom"
+        );
+    }
+}
@@ -0,0 +1,432 @@
+//! `code_search`: regex search over the workspace that returns matching lines
+//! with context, most relevant files first, trimmed to a token budget.
+//!
+//! The walk honours `.gitignore` and friends the same way ripgrep does, and
+//! runs in-process so results do not depend on which grep is installed.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::time::Duration;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use ignore::WalkBuilder;
+use ignore::overrides::OverrideBuilder;
+use regex::Regex;
+use regex::RegexBuilder;
+use serde::Deserialize;
+
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::truncate::approx_token_count;
+
+pub struct CodeSearchHandler;
+
+const DEFAULT_CONTEXT_LINES: usize = 2;
+const MAX_CONTEXT_LINES: usize = 10;
+const DEFAULT_MAX_OUTPUT_TOKENS: usize = 4_000;
+const MAX_OUTPUT_TOKENS: usize = 20_000;
+/// Matches shown per file; the rest are only counted.
+const MAX_MATCHES_PER_FILE: usize = 10;
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+const MAX_LINE_CHARS: usize = 300;
+/// Files skipped once the search has been running this long.
+const SEARCH_TIME_LIMIT: Duration = Duration::from_secs(20);
+/// Omitted files named at the end of a trimmed result.
+const MAX_OMITTED_LISTED: usize = 20;
+
+/// Lines that look like they declare something, which usually makes them the
+/// match the model is after.
+static DEFINITION_LINE: LazyLock<Regex> = LazyLock::new(|| {
+    #[expect(clippy::expect_used)]
+    Regex::new(
+        r"^\s*(?:export\s+|pub(?:\([^)]*\))?\s+|async\s+|static\s+|default\s+)*(?:fn|struct|enum|trait|impl|type|mod|macro_rules!|class|def|func|function|interface|const)\b",
+    )
+    .expect("definition regex")
+});
+
+#[derive(Deserialize)]
+struct CodeSearchArgs {
+    pattern: String,
+    #[serde(default)]
+    path: Option<String>,
+    /// Glob filters; a leading `!` excludes.
+    #[serde(default)]
+    globs: Vec<String>,
+    #[serde(default)]
+    fixed_strings: bool,
+    /// Defaults to smart case: insensitive unless the pattern has uppercase.
+    #[serde(default)]
+    case_sensitive: Option<bool>,
+    #[serde(default)]
+    context_lines: Option<usize>,
+    #[serde(default)]
+    max_output_tokens: Option<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+struct FileMatches {
+    path: PathBuf,
+    /// 0-indexed line numbers of the first `MAX_MATCHES_PER_FILE` matches.
+    lines: Vec<usize>,
+    total_matches: usize,
+    definition_matches: usize,
+    /// The file's lines, kept so context can be rendered later.
+    content: Vec<String>,
+}
+
+impl FileMatches {
+    /// Higher is more relevant: declarations first, then files with more
+    /// hits, then shallower paths.
+    fn score(&self) -> i64 {
+        let depth = self.path.components().count() as i64;
+        (self.definition_matches.min(5) as i64) * 20 + (self.total_matches.min(20) as i64) * 3
+            - depth
+    }
+}
+
+#[async_trait]
+impl ToolHandler for CodeSearchHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation { payload, turn, .. } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "code_search handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+
+        let args: CodeSearchArgs = serde_json::from_str(&arguments).map_err(|err| {
+            FunctionCallError::InvalidArguments(format!(
+                "failed to parse function arguments: {err:?}"
+            ))
+        })?;
+
+        if args.pattern.is_empty() {
+            return Err(FunctionCallError::RespondToModel(
+                "pattern must not be empty".to_string(),
+            ));
+        }
+        let regex = build_regex(&args)?;
+        let root = turn.resolve_path(args.path.clone());
+        tokio::fs::metadata(&root).await.map_err(|err| {
+            FunctionCallError::RespondToModel(format!(
+                "unable to access `{}`: {err}",
+                root.display()
+            ))
+        })?;
+        let context_lines = args
+            .context_lines
+            .unwrap_or(DEFAULT_CONTEXT_LINES)
+            .min(MAX_CONTEXT_LINES);
+        let max_tokens = args
+            .max_output_tokens
+            .unwrap_or(DEFAULT_MAX_OUTPUT_TOKENS)
+            .min(MAX_OUTPUT_TOKENS);
+
+        let cwd = turn.cwd.clone();
+        let globs = args.globs;
+        let (mut files, timed_out) =
+            tokio::task::spawn_blocking(move || search(&root, &globs, &regex))
+                .await
+                .map_err(|err| {
+                    FunctionCallError::RespondToModel(format!("code_search failed: {err}"))
+                })??;
+
+        if files.is_empty() {
+            return Ok(ToolOutput::Function {
+                content: "No matches found.".to_string(),
+                content_items: None,
+                success: Some(false),
+            });
+        }
+
+        files.sort_by(|a, b| b.score().cmp(&a.score()).then_with(|| a.path.cmp(&b.path)));
+        let mut content = render(&files, &cwd, context_lines, max_tokens);
+        if timed_out {
+            content.push_str(&format!(
+                "\n[search stopped after {} seconds; narrow `path` or `globs` for complete results]",
+                SEARCH_TIME_LIMIT.as_secs()
+            ));
+        }
+        Ok(ToolOutput::Function {
+            content,
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
+
+fn build_regex(args: &CodeSearchArgs) -> Result<Regex, FunctionCallError> {
+    let pattern = if args.fixed_strings {
+        regex::escape(&args.pattern)
+    } else {
+        args.pattern.clone()
+    };
+    let case_sensitive = args
+        .case_sensitive
+        .unwrap_or_else(|| args.pattern.chars().any(char::is_uppercase));
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!case_sensitive)
+        .build()
+        .map_err(|err| FunctionCallError::RespondToModel(format!("invalid pattern: {err}")))
+}
+
+/// Walks `root` and collects every file with at least one match. Returns
+/// whether the time limit cut the walk short.
+fn search(
+    root: &Path,
+    globs: &[String],
+    regex: &Regex,
+) -> Result<(Vec<FileMatches>, bool), FunctionCallError> {
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .hidden(false)
+        .require_git(false)
+        // `.git` is not gitignored, but nobody wants matches from it.
+        .filter_entry(|entry| entry.file_name() != ".git");
+    if !globs.is_empty() {
+        let mut overrides = OverrideBuilder::new(root);
+        for glob in globs {
+            overrides.add(glob).map_err(|err| {
+                FunctionCallError::RespondToModel(format!("invalid glob `{glob}`: {err}"))
+            })?;
+        }
+        let overrides = overrides
+            .build()
+            .map_err(|err| FunctionCallError::RespondToModel(format!("invalid globs: {err}")))?;
+        builder.overrides(overrides);
+    }
+
+    let started = Instant::now();
+    let mut files = Vec::new();
+    for entry in builder.build() {
+        if started.elapsed() > SEARCH_TIME_LIMIT {
+            return Ok((files, true));
+        }
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+            continue;
+        }
+        if entry
+            .metadata()
+            .is_ok_and(|metadata| metadata.len() > MAX_FILE_BYTES)
+        {
+            continue;
+        }
+        let Ok(bytes) = std::fs::read(entry.path()) else {
+            continue;
+        };
+        // Same binary heuristic as ripgrep: a NUL byte near the start.
+        if bytes.iter().take(8192).any(|byte| *byte == 0) {
+            continue;
+        }
+        if let Some(matches) = match_file(entry.path(), &String::from_utf8_lossy(&bytes), regex) {
+            files.push(matches);
+        }
+    }
+    Ok((files, false))
+}
+
+fn match_file(path: &Path, text: &str, regex: &Regex) -> Option<FileMatches> {
+    let content: Vec<String> = text.lines().map(str::to_string).collect();
+    let mut lines = Vec::new();
+    let mut total_matches = 0;
+    let mut definition_matches = 0;
+    for (index, line) in content.iter().enumerate() {
+        if !regex.is_match(line) {
+            continue;
+        }
+        total_matches += 1;
+        if DEFINITION_LINE.is_match(line) {
+            definition_matches += 1;
+        }
+        if lines.len() < MAX_MATCHES_PER_FILE {
+            lines.push(index);
+        }
+    }
+    (total_matches > 0).then(|| FileMatches {
+        path: path.to_path_buf(),
+        lines,
+        total_matches,
+        definition_matches,
+        content,
+    })
+}
+
+/// Renders files in ranked order until `max_tokens` is used up, then lists
+/// the files that did not fit.
+fn render(files: &[FileMatches], cwd: &Path, context_lines: usize, max_tokens: usize) -> String {
+    let mut out = String::new();
+    let mut used_tokens = 0;
+    let mut omitted = Vec::new();
+    for file in files {
+        let display = file.path.strip_prefix(cwd).unwrap_or(&file.path);
+        let block = render_file(file, display, context_lines);
+        let block_tokens = approx_token_count(&block);
+        if !out.is_empty() && used_tokens + block_tokens > max_tokens {
+            omitted.push(display.display().to_string());
+            continue;
+        }
+        used_tokens += block_tokens;
+        out.push_str(&block);
+    }
+    if !omitted.is_empty() {
+        let listed: Vec<&str> = omitted
+            .iter()
+            .take(MAX_OMITTED_LISTED)
+            .map(String::as_str)
+            .collect();
+        let more = omitted.len().saturating_sub(MAX_OMITTED_LISTED);
+        out.push_str(&format!(
+            "[{} more files with matches not shown: {}{}]\n",
+            omitted.len(),
+            listed.join(", "),
+            if more > 0 {
+                format!(", and {more} others")
+            } else {
+                String::new()
+            }
+        ));
+    }
+    out.trim_end().to_string()
+}
+
+/// One file in grep style: `N:` marks a matching line, `N-` a context line,
+/// and `--` separates non-adjacent groups.
+fn render_file(file: &FileMatches, display: &Path, context_lines: usize) -> String {
+    let plural = if file.total_matches == 1 { "" } else { "es" };
+    let mut out = format!(
+        "{} ({} match{plural})\n",
+        display.display(),
+        file.total_matches
+    );
+    let mut printed_to: Option<usize> = None;
+    for &line in &file.lines {
+        let start = line.saturating_sub(context_lines);
+        let end = (line + context_lines).min(file.content.len().saturating_sub(1));
+        let start = match printed_to {
+            Some(printed) if start <= printed + 1 => printed + 1,
+            Some(_) => {
+                out.push_str("--\n");
+                start
+            }
+            None => start,
+        };
+        for index in start..=end {
+            if printed_to.is_some_and(|printed| index <= printed) {
+                continue;
+            }
+            let separator = if file.lines.contains(&index) {
+                ':'
+            } else {
+                '-'
+            };
+            let text = &file.content[index];
+            let text = match text.char_indices().nth(MAX_LINE_CHARS) {
+                Some((cut, _)) => format!("{}…", &text[..cut]),
+                None => text.clone(),
+            };
+            out.push_str(&format!("{}{separator}{text}\n", index + 1));
+            printed_to = Some(index);
+        }
+    }
+    if file.total_matches > file.lines.len() {
+        out.push_str(&format!(
+            "[{} more matches in this file]\n",
+            file.total_matches - file.lines.len()
+        ));
+    }
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn regex(pattern: &str) -> Regex {
+        build_regex(&CodeSearchArgs {
+            pattern: pattern.to_string(),
+            path: None,
+            globs: Vec::new(),
+            fixed_strings: false,
+            case_sensitive: None,
+            context_lines: None,
+            max_output_tokens: None,
+        })
+        .expect("valid pattern")
+    }
+
+    #[test]
+    fn renders_context_and_ranks_definitions_first() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir(dir.path().join("src"))?;
+        std::fs::write(
+            dir.path().join("usage.rs"),
+            "use crate::parse_config;\n\nfn main() {\n    parse_config();\n}\n",
+        )?;
+        std::fs::write(
+            dir.path().join("src/config.rs"),
+            "// Parsing.\npub fn parse_config() -> Config {\n    Config::default()\n}\n",
+        )?;
+        std::fs::write(dir.path().join("skip.txt"), "parse_config")?;
+
+        let (mut files, timed_out) =
+            search(dir.path(), &["*.rs".to_string()], &regex("parse_config"))?;
+        assert!(!timed_out);
+        files.sort_by(|a, b| b.score().cmp(&a.score()).then_with(|| a.path.cmp(&b.path)));
+
+        assert_eq!(
+            render(&files, dir.path(), 1, 1_000),
+            "src/config.rs (1 match)
+1-// Parsing.
+2:pub fn parse_config() -> Config {
+3-    Config::default()
+
+usage.rs (2 matches)
+1:use crate::parse_config;
+2-
+3-fn main() {
+4:    parse_config();
+5-}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn lists_files_that_do_not_fit_the_budget() {
+        let file = |name: &str| FileMatches {
+            path: PathBuf::from(name),
+            lines: vec![0],
+            total_matches: 1,
+            definition_matches: 0,
+            content: vec!["x".repeat(400)],
+        };
+        let files = vec![file("a.rs"), file("b.rs"), file("c.rs")];
+        let rendered = render(&files, Path::new("/"), 0, 150);
+        assert!(rendered.starts_with("a.rs (1 match)\n1:"));
+        assert!(rendered.ends_with("[2 more files with matches not shown: b.rs, c.rs]"));
+    }
+
+    #[test]
+    fn smart_case_only_ignores_case_for_lowercase_patterns() {
+        assert!(regex("config").is_match("CONFIG"));
+        assert!(!regex("Config").is_match("config"));
+    }
+}
@@ -0,0 +1,450 @@
+//! `write_file` and `edit_file`: whole-file writes and exact string
+//! replacement for edits that are awkward to express as a patch.
+//!
+//! Both tools turn the requested change into an `apply_patch` patch and run
+//! it through the same approval and sandbox flow, so they cannot write
+//! anywhere `apply_patch` could not.
+
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use similar::Algorithm;
+use similar::DiffTag;
+
+use crate::function_tool::FunctionCallError;
+use crate::text_encoding::guess_encoding_name;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::handlers::apply_patch::apply_patch_input;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct FileEditHandler;
+
+/// Largest file `edit_file` will load.
+const MAX_EDIT_FILE_BYTES: usize = 4 * 1024 * 1024;
+
+/// The patch is handed to the sandboxed `apply_patch` process as a single
+/// argument, and Linux rejects arguments over 128 KiB.
+const MAX_PATCH_BYTES: usize = 120 * 1024;
+
+/// Context lines kept around each change when there is no ambiguity.
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Deserialize)]
+struct WriteFileArgs {
+    /// Absolute path of the file to create or overwrite.
+    file_path: String,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct EditFileArgs {
+    /// Absolute path of the file to edit.
+    file_path: String,
+    old_string: String,
+    new_string: String,
+    /// Replace every occurrence instead of requiring exactly one.
+    #[serde(default)]
+    replace_all: bool,
+    /// 1-indexed first line an occurrence may start on.
+    #[serde(default)]
+    start_line: Option<usize>,
+    /// 1-indexed last line an occurrence may start on.
+    #[serde(default)]
+    end_line: Option<usize>,
+}
+
+#[async_trait]
+impl ToolHandler for FileEditHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn is_mutating(&self, _invocation: &ToolInvocation) -> bool {
+        true
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            tracker,
+            call_id,
+            tool_name,
+            payload,
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "{tool_name} handler received unsupported payload"
+                )));
+            }
+        };
+
+        let patch = match tool_name.as_str() {
+            "write_file" => {
+                let args: WriteFileArgs = parse_arguments(&arguments)?;
+                let path = absolute_path(&args.file_path)?;
+                if args.content.len() > MAX_PATCH_BYTES {
+                    return Err(FunctionCallError::RespondToModel(format!(
+                        "content is {} bytes; write_file accepts at most {MAX_PATCH_BYTES}. Write the file in parts with edit_file",
+                        args.content.len()
+                    )));
+                }
+                let patch_path = patch_path(&path, &turn.cwd);
+                match read_text(&path).await? {
+                    Some(existing) => update_patch(patch_path, &existing, &args.content)?,
+                    None => add_patch(patch_path, &args.content),
+                }
+            }
+            "edit_file" => {
+                let args: EditFileArgs = parse_arguments(&arguments)?;
+                let path = absolute_path(&args.file_path)?;
+                let Some(existing) = read_text(&path).await? else {
+                    return Err(FunctionCallError::RespondToModel(format!(
+                        "{} does not exist; create it with write_file",
+                        path.display()
+                    )));
+                };
+                let updated = replace(&existing, &args)?;
+                update_patch(patch_path(&path, &turn.cwd), &existing, &updated)?
+            }
+            other => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "file edit handler does not support tool {other}"
+                )));
+            }
+        };
+
+        if patch.len() > MAX_PATCH_BYTES {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "the change is too large to apply in one call ({} bytes of patch); split it into smaller edits",
+                patch.len()
+            )));
+        }
+
+        let content = apply_patch_input(
+            session.as_ref(),
+            turn.as_ref(),
+            &tracker,
+            &call_id,
+            &tool_name,
+            patch,
+        )
+        .await?;
+        Ok(ToolOutput::Function {
+            content,
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
+
+fn parse_arguments<T: for<'de> Deserialize<'de>>(arguments: &str) -> Result<T, FunctionCallError> {
+    serde_json::from_str(arguments).map_err(|err| {
+        FunctionCallError::InvalidArguments(format!("failed to parse function arguments: {err:?}"))
+    })
+}
+
+fn absolute_path(file_path: &str) -> Result<PathBuf, FunctionCallError> {
+    let path = PathBuf::from(file_path);
+    if !path.is_absolute() {
+        return Err(FunctionCallError::RespondToModel(
+            "file_path must be an absolute path".to_string(),
+        ));
+    }
+    if file_path.contains('\n') {
+        return Err(FunctionCallError::RespondToModel(
+            "file_path must not contain newlines".to_string(),
+        ));
+    }
+    Ok(path)
+}
+
+/// Paths inside the working directory go into the patch relative to it, so
+/// the result lists them the way `apply_patch` would.
+fn patch_path<'a>(path: &'a Path, cwd: &Path) -> &'a Path {
+    path.strip_prefix(cwd).unwrap_or(path)
+}
+
+/// Reads `path` as UTF-8, returning `None` when it does not exist. Files in
+/// other encodings are refused rather than silently re-encoded.
+async fn read_text(path: &Path) -> Result<Option<String>, FunctionCallError> {
+    let bytes = match tokio::fs::read(path).await {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "failed to read {}: {err}",
+                path.display()
+            )));
+        }
+    };
+    if bytes.len() > MAX_EDIT_FILE_BYTES {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "{} is {} bytes, over the {MAX_EDIT_FILE_BYTES} byte limit for this tool; use apply_patch",
+            path.display(),
+            bytes.len()
+        )));
+    }
+    String::from_utf8(bytes).map(Some).map_err(|err| {
+        FunctionCallError::RespondToModel(format!(
+            "{} is not UTF-8 (it looks like {}); edit it with a shell command instead",
+            path.display(),
+            guess_encoding_name(err.as_bytes())
+        ))
+    })
+}
+
+/// Applies an `edit_file` request to `content`, enforcing the occurrence
+/// count and line range.
+fn replace(content: &str, args: &EditFileArgs) -> Result<String, FunctionCallError> {
+    if args.old_string.is_empty() {
+        return Err(FunctionCallError::RespondToModel(
+            "old_string must not be empty".to_string(),
+        ));
+    }
+    if args.old_string == args.new_string {
+        return Err(FunctionCallError::RespondToModel(
+            "old_string and new_string are identical".to_string(),
+        ));
+    }
+    let first_line = args.start_line.unwrap_or(1);
+    let last_line = args.end_line.unwrap_or(usize::MAX);
+    if first_line == 0 || first_line > last_line {
+        return Err(FunctionCallError::RespondToModel(
+            "start_line and end_line must be 1-indexed with start_line <= end_line".to_string(),
+        ));
+    }
+
+    let line_of = |offset: usize| content[..offset].matches('\n').count() + 1;
+    let matches: Vec<usize> = content
+        .match_indices(&args.old_string)
+        .map(|(offset, _)| offset)
+        .filter(|offset| (first_line..=last_line).contains(&line_of(*offset)))
+        .collect();
+
+    match matches.len() {
+        0 => {
+            return Err(FunctionCallError::RespondToModel(
+                "old_string was not found in the file (or in the requested line range)".to_string(),
+            ));
+        }
+        1 => {}
+        count if !args.replace_all => {
+            let lines: Vec<String> = matches
+                .iter()
+                .map(|offset| line_of(*offset).to_string())
+                .collect();
+            return Err(FunctionCallError::RespondToModel(format!(
+                "old_string occurs {count} times (lines {}); include more surrounding text, narrow start_line/end_line, or set replace_all",
+                lines.join(", ")
+            )));
+        }
+        _ => {}
+    }
+
+    let mut updated = String::with_capacity(content.len());
+    let mut cursor = 0;
+    for offset in matches {
+        updated.push_str(&content[cursor..offset]);
+        updated.push_str(&args.new_string);
+        cursor = offset + args.old_string.len();
+    }
+    updated.push_str(&content[cursor..]);
+    Ok(updated)
+}
+
+fn add_patch(path: &Path, content: &str) -> String {
+    let mut patch = format!("*** Begin Patch\n*** Add File: {}\n", path.display());
+    for line in split_lines(content) {
+        patch.push('+');
+        patch.push_str(line);
+        patch.push('\n');
+    }
+    patch.push_str("*** End Patch\n");
+    patch
+}
+
+/// Builds an update patch whose hunks carry enough context that
+/// `apply_patch` finds each of them exactly where the change was made.
+fn update_patch(path: &Path, old: &str, new: &str) -> Result<String, FunctionCallError> {
+    let old_lines = split_lines(old);
+    let new_lines = split_lines(new);
+    if old_lines.is_empty() {
+        // A hunk needs at least one line to anchor on, so rewrite the file.
+        return Ok(add_patch(path, new));
+    }
+
+    let ops = similar::capture_diff_slices(Algorithm::Myers, &old_lines, &new_lines);
+    let mut changes: Vec<(Range<usize>, Range<usize>)> = Vec::new();
+    for op in ops {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        if tag == DiffTag::Equal {
+            continue;
+        }
+        match changes.last_mut() {
+            // Changes close enough for their context to overlap share a hunk.
+            Some((last_old, last_new)) if old_range.start - last_old.end <= 2 * CONTEXT_LINES => {
+                last_old.end = old_range.end;
+                last_new.end = new_range.end;
+            }
+            _ => changes.push((old_range, new_range)),
+        }
+    }
+    if changes.is_empty() {
+        return Err(FunctionCallError::RespondToModel(
+            "the file already has this content".to_string(),
+        ));
+    }
+
+    let mut patch = format!("*** Begin Patch\n*** Update File: {}\n", path.display());
+    let mut searched_to = 0;
+    for (old_range, new_range) in changes {
+        let after = CONTEXT_LINES.min(old_lines.len() - old_range.end);
+        let mut before = CONTEXT_LINES.min(old_range.start - searched_to);
+        // Widen the leading context until the first match from where
+        // `apply_patch` resumes searching is this one.
+        loop {
+            let start = old_range.start - before;
+            let pattern = &old_lines[start..old_range.end + after];
+            if start == searched_to || first_match(&old_lines, pattern, searched_to) == Some(start)
+            {
+                break;
+            }
+            before += 1;
+        }
+
+        patch.push_str("@@\n");
+        for line in &old_lines[old_range.start - before..old_range.start] {
+            push_patch_line(&mut patch, ' ', line);
+        }
+        for line in &old_lines[old_range.clone()] {
+            push_patch_line(&mut patch, '-', line);
+        }
+        for line in &new_lines[new_range] {
+            push_patch_line(&mut patch, '+', line);
+        }
+        for line in &old_lines[old_range.end..old_range.end + after] {
+            push_patch_line(&mut patch, ' ', line);
+        }
+        searched_to = old_range.end + after;
+    }
+    patch.push_str("*** End Patch\n");
+    Ok(patch)
+}
+
+/// Mirrors how `apply_patch` splits a file: on `\n`, without the empty
+/// element after a trailing newline.
+fn split_lines(content: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = content.split('\n').collect();
+    if lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// First position at or after `from` where `pattern` matches, ignoring
+/// trailing whitespace the way `apply_patch`'s lenient pass does.
+fn first_match(lines: &[&str], pattern: &[&str], from: usize) -> Option<usize> {
+    (from..=lines.len().saturating_sub(pattern.len())).find(|&start| {
+        lines[start..start + pattern.len()]
+            .iter()
+            .zip(pattern)
+            .all(|(line, expected)| line.trim_end() == expected.trim_end())
+    })
+}
+
+fn push_patch_line(patch: &mut String, prefix: char, line: &str) {
+    patch.push(prefix);
+    patch.push_str(line);
+    patch.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn edit(old_string: &str, new_string: &str) -> EditFileArgs {
+        EditFileArgs {
+            file_path: "/tmp/file.txt".to_string(),
+            old_string: old_string.to_string(),
+            new_string: new_string.to_string(),
+            replace_all: false,
+            start_line: None,
+            end_line: None,
+        }
+    }
+
+    #[test]
+    fn replace_requires_a_unique_match_unless_narrowed() {
+        let content = "let a = 1;\nlet b = 1;\nlet c = 2;\n";
+        assert_eq!(
+            replace(content, &edit("= 1", "= 3")),
+            Err(FunctionCallError::RespondToModel(
+                "old_string occurs 2 times (lines 1, 2); include more surrounding text, narrow start_line/end_line, or set replace_all"
+                    .to_string()
+            ))
+        );
+
+        let narrowed = EditFileArgs {
+            start_line: Some(2),
+            ..edit("= 1", "= 3")
+        };
+        assert_eq!(
+            replace(content, &narrowed),
+            Ok("let a = 1;\nlet b = 3;\nlet c = 2;\n".to_string())
+        );
+
+        let all = EditFileArgs {
+            replace_all: true,
+            ..edit("= 1", "= 3")
+        };
+        assert_eq!(
+            replace(content, &all),
+            Ok("let a = 3;\nlet b = 3;\nlet c = 2;\n".to_string())
+        );
+    }
+
+    #[test]
+    fn update_patch_widens_context_for_repeated_lines() -> anyhow::Result<()> {
+        let path = Path::new("/repo/lib.rs");
+        assert_eq!(
+            update_patch(path, "a\nb\nc\nd\ne\nf\n", "a\nb\nc\nD\ne\nf\n")?,
+            "*** Begin Patch\n*** Update File: /repo/lib.rs\n@@\n a\n b\n c\n-d\n+D\n e\n f\n*** End Patch\n"
+        );
+
+        // Three lines of context alone would match the first run of braces.
+        let old = "}\n}\n}\n}\n}\nx\n}\n}\n}\n}\n";
+        let new = "}\n}\n}\n}\n}\nx\n}\n}\n}\n};\n";
+        assert_eq!(
+            update_patch(path, old, new)?,
+            "*** Begin Patch\n*** Update File: /repo/lib.rs\n@@\n x\n }\n }\n }\n-}\n+};\n*** End Patch\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn update_patch_round_trips_through_apply_patch() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("notes.txt");
+        let old = "a\nb\n\nc\nd\ne\nf\ng\nh\ni\nj\nk\nb\n";
+        let new = "start\na\nB\n\nc\nd\ne\nf\ng\nh\ni\nj\nk\nb\nend\n";
+        std::fs::write(&path, old)?;
+
+        let patch = update_patch(&path, old, new)?;
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        codex_apply_patch::apply_patch(&patch, &mut stdout, &mut stderr)?;
+        assert_eq!(std::fs::read_to_string(&path)?, new);
+        Ok(())
+    }
+}
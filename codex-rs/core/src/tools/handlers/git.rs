@@ -0,0 +1,717 @@
+//! `git_status`, `git_diff`, `git_commit` and `git_create_branch`: git
+//! operations that return parsed JSON instead of porcelain text.
+//!
+//! Reads run in-process. Commits and branch creation go through the same
+//! approval and sandbox path as shell commands, and refuse to touch the
+//! branches listed in `git_protected_branches`. There is deliberately no
+//! push tool.
+
+use std::path::Path;
+use std::process::Output;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::process::Command;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::exec_env::create_env;
+use crate::exec_policy::create_exec_approval_requirement_for_command;
+use crate::function_tool::FunctionCallError;
+use crate::git_info::current_branch_name;
+use crate::protocol::ExecCommandSource;
+use crate::sandboxing::SandboxPermissions;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::events::ToolEmitter;
+use crate::tools::events::ToolEventCtx;
+use crate::tools::orchestrator::ToolOrchestrator;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::tools::runtimes::shell::ShellRequest;
+use crate::tools::runtimes::shell::ShellRuntime;
+use crate::tools::sandboxing::ToolCtx;
+use crate::truncate::formatted_truncate_text;
+
+/// Handles `git_status`, `git_diff`, `git_commit` and `git_create_branch`.
+pub struct GitHandler;
+
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+/// Commits can run hooks, so they get longer than reads.
+const WRITE_TIMEOUT_MS: u64 = 120_000;
+const DEFAULT_DIFF_CONTEXT_LINES: u32 = 3;
+
+#[derive(Debug, Deserialize)]
+struct DiffArgs {
+    #[serde(default)]
+    staged: bool,
+    #[serde(default)]
+    paths: Vec<String>,
+    #[serde(default)]
+    context_lines: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitArgs {
+    message: String,
+    /// Staged with `git add` before committing.
+    #[serde(default)]
+    paths: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateBranchArgs {
+    name: String,
+    #[serde(default)]
+    start_point: Option<String>,
+    #[serde(default = "default_checkout")]
+    checkout: bool,
+}
+
+fn default_checkout() -> bool {
+    true
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct GitStatus {
+    /// `None` on a detached HEAD.
+    branch: Option<String>,
+    /// `None` before the first commit.
+    head: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upstream: Option<String>,
+    ahead: u64,
+    behind: u64,
+    staged: Vec<FileChange>,
+    unstaged: Vec<FileChange>,
+    untracked: Vec<String>,
+    conflicted: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct FileChange {
+    path: String,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    original_path: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct DiffStat {
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    original_path: Option<String>,
+    /// `None` for binary files.
+    additions: Option<u64>,
+    deletions: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct GitDiff {
+    staged: bool,
+    files: Vec<DiffStat>,
+    patch: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CommitResult {
+    commit: String,
+    branch: Option<String>,
+    subject: String,
+    files: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CreateBranchResult {
+    branch: String,
+    checked_out: bool,
+    head: String,
+}
+
+#[async_trait]
+impl ToolHandler for GitHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn is_mutating(&self, invocation: &ToolInvocation) -> bool {
+        !matches!(invocation.tool_name.as_str(), "git_status" | "git_diff")
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            call_id,
+            tool_name,
+            payload,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "{tool_name} handler received unsupported payload"
+                )));
+            }
+        };
+
+        let cwd = turn.cwd.clone();
+        let content = match tool_name.as_str() {
+            "git_status" => to_json(&status(&cwd).await?)?,
+            "git_diff" => {
+                let args: DiffArgs = parse_arguments(&arguments)?;
+                let mut diff = diff(&cwd, &args).await?;
+                diff.patch = formatted_truncate_text(&diff.patch, turn.truncation_policy);
+                to_json(&diff)?
+            }
+            "git_commit" => {
+                let args: CommitArgs = parse_arguments(&arguments)?;
+                let ctx = MutationCtx {
+                    session: session.as_ref(),
+                    turn: turn.as_ref(),
+                    call_id: &call_id,
+                    tool_name: &tool_name,
+                };
+                to_json(&commit(&ctx, &cwd, args).await?)?
+            }
+            "git_create_branch" => {
+                let args: CreateBranchArgs = parse_arguments(&arguments)?;
+                let ctx = MutationCtx {
+                    session: session.as_ref(),
+                    turn: turn.as_ref(),
+                    call_id: &call_id,
+                    tool_name: &tool_name,
+                };
+                to_json(&create_branch(&ctx, &cwd, args).await?)?
+            }
+            other => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "unsupported git tool: {other}"
+                )));
+            }
+        };
+
+        Ok(ToolOutput::Function {
+            content,
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
+
+fn parse_arguments<'a, T: Deserialize<'a>>(arguments: &'a str) -> Result<T, FunctionCallError> {
+    serde_json::from_str(arguments).map_err(|err| {
+        FunctionCallError::InvalidArguments(format!("failed to parse function arguments: {err:?}"))
+    })
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, FunctionCallError> {
+    serde_json::to_string(value)
+        .map_err(|err| FunctionCallError::Fatal(format!("failed to serialize git output: {err}")))
+}
+
+async fn status(cwd: &Path) -> Result<GitStatus, FunctionCallError> {
+    let output = read_git(cwd, &["status", "--porcelain=v2", "--branch", "-z"]).await?;
+    Ok(parse_status(&String::from_utf8_lossy(&output.stdout)))
+}
+
+async fn diff(cwd: &Path, args: &DiffArgs) -> Result<GitDiff, FunctionCallError> {
+    let context = format!(
+        "-U{}",
+        args.context_lines.unwrap_or(DEFAULT_DIFF_CONTEXT_LINES)
+    );
+    let mut base = vec!["diff", "--no-color", "--no-ext-diff"];
+    if args.staged {
+        base.push("--cached");
+    }
+
+    let mut numstat = base.clone();
+    numstat.extend(["--numstat", "-z", "--"]);
+    numstat.extend(args.paths.iter().map(String::as_str));
+    let files = parse_numstat(&String::from_utf8_lossy(
+        &read_git(cwd, &numstat).await?.stdout,
+    ));
+
+    let mut patch = base;
+    patch.extend([context.as_str(), "--"]);
+    patch.extend(args.paths.iter().map(String::as_str));
+    let patch = String::from_utf8_lossy(&read_git(cwd, &patch).await?.stdout).into_owned();
+
+    Ok(GitDiff {
+        staged: args.staged,
+        files,
+        patch,
+    })
+}
+
+async fn commit(
+    ctx: &MutationCtx<'_>,
+    cwd: &Path,
+    args: CommitArgs,
+) -> Result<CommitResult, FunctionCallError> {
+    if args.message.trim().is_empty() {
+        return Err(FunctionCallError::RespondToModel(
+            "commit message must not be empty".to_string(),
+        ));
+    }
+    let branch = current_branch_name(cwd).await;
+    if let Some(branch) = &branch {
+        ensure_unprotected(ctx.turn, branch, "commit on")?;
+    }
+
+    if !args.paths.is_empty() {
+        let mut add = vec!["git".to_string(), "add".to_string(), "--".to_string()];
+        add.extend(args.paths);
+        run_git_mutation(ctx, cwd, add, "add").await?;
+    }
+    let nothing_staged = git(cwd, &["diff", "--cached", "--quiet"])
+        .await?
+        .status
+        .success();
+    if nothing_staged {
+        return Err(FunctionCallError::RespondToModel(
+            "nothing is staged; pass `paths` to stage files before committing".to_string(),
+        ));
+    }
+
+    run_git_mutation(
+        ctx,
+        cwd,
+        vec![
+            "git".to_string(),
+            "commit".to_string(),
+            "-m".to_string(),
+            args.message,
+        ],
+        "commit",
+    )
+    .await?;
+
+    let log = read_git(cwd, &["log", "-1", "--format=%H%n%s"]).await?;
+    let log = String::from_utf8_lossy(&log.stdout);
+    let (commit, subject) = log.trim_end().split_once('\n').unwrap_or((&log, ""));
+    let files = read_git(
+        cwd,
+        &[
+            "diff-tree",
+            "--root",
+            "--no-commit-id",
+            "--name-only",
+            "-r",
+            "-z",
+            "HEAD",
+        ],
+    )
+    .await?;
+    Ok(CommitResult {
+        commit: commit.trim().to_string(),
+        branch,
+        subject: subject.to_string(),
+        files: String::from_utf8_lossy(&files.stdout)
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(str::to_string)
+            .collect(),
+    })
+}
+
+async fn create_branch(
+    ctx: &MutationCtx<'_>,
+    cwd: &Path,
+    args: CreateBranchArgs,
+) -> Result<CreateBranchResult, FunctionCallError> {
+    let name = args.name.trim().to_string();
+    let valid_name = !name.starts_with('-')
+        && git(cwd, &["check-ref-format", "--branch", &name])
+            .await?
+            .status
+            .success();
+    if !valid_name {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "`{name}` is not a valid branch name"
+        )));
+    }
+    ensure_unprotected(ctx.turn, &name, "create")?;
+    let exists = git(
+        cwd,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{name}"),
+        ],
+    )
+    .await?
+    .status
+    .success();
+    if exists {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "branch `{name}` already exists"
+        )));
+    }
+    if let Some(start_point) = &args.start_point
+        && start_point.starts_with('-')
+    {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "`{start_point}` is not a valid start point"
+        )));
+    }
+
+    let mut command = if args.checkout {
+        vec!["git".to_string(), "switch".to_string(), "-c".to_string()]
+    } else {
+        vec!["git".to_string(), "branch".to_string()]
+    };
+    command.push(name.clone());
+    command.extend(args.start_point);
+    run_git_mutation(ctx, cwd, command, "branch").await?;
+
+    let head = read_git(cwd, &["rev-parse", &format!("refs/heads/{name}")]).await?;
+    Ok(CreateBranchResult {
+        branch: name,
+        checked_out: args.checkout,
+        head: String::from_utf8_lossy(&head.stdout).trim().to_string(),
+    })
+}
+
+fn ensure_unprotected(
+    turn: &TurnContext,
+    branch: &str,
+    action: &str,
+) -> Result<(), FunctionCallError> {
+    let config = turn.client.config();
+    if config
+        .git_protected_branches
+        .iter()
+        .any(|protected| protected == branch)
+    {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "refusing to {action} protected branch `{branch}`; create a new branch with git_create_branch instead"
+        )));
+    }
+    Ok(())
+}
+
+struct MutationCtx<'a> {
+    session: &'a Session,
+    turn: &'a TurnContext,
+    call_id: &'a str,
+    tool_name: &'a str,
+}
+
+/// Runs a git command that changes the repository the way the shell tool
+/// would, so it is shown to the user, sandboxed and approved like any other
+/// command. `step` distinguishes the events of multi-step tools.
+async fn run_git_mutation(
+    ctx: &MutationCtx<'_>,
+    cwd: &Path,
+    command: Vec<String>,
+    step: &str,
+) -> Result<String, FunctionCallError> {
+    let MutationCtx {
+        session,
+        turn,
+        call_id,
+        tool_name,
+    } = *ctx;
+    let call_id = format!("{call_id}-{step}");
+    let emitter = ToolEmitter::shell(
+        command.clone(),
+        cwd.to_path_buf(),
+        ExecCommandSource::Agent,
+        false,
+    );
+    emitter
+        .begin(ToolEventCtx::new(session, turn, &call_id, None))
+        .await;
+
+    let features = session.features();
+    let exec_approval_requirement = create_exec_approval_requirement_for_command(
+        &turn.exec_policy,
+        &features,
+        &turn.client.config().command_safety,
+        &session.services.approved_command_prefixes.read().await,
+        &command,
+        turn.approval_policy,
+        &turn.sandbox_policy,
+        SandboxPermissions::UseDefault,
+    )
+    .await;
+    let req = ShellRequest {
+        command,
+        cwd: cwd.to_path_buf(),
+        timeout_ms: Some(WRITE_TIMEOUT_MS),
+        env: create_env(&turn.shell_environment_policy),
+        sandbox_permissions: SandboxPermissions::UseDefault,
+        justification: None,
+        exec_approval_requirement,
+        prompts_only_for_escalation: false,
+        pty: None,
+    };
+    let tool_ctx = ToolCtx {
+        session,
+        turn,
+        call_id: call_id.clone(),
+        tool_name: tool_name.to_string(),
+    };
+    let out = ToolOrchestrator::new()
+        .run(
+            &mut ShellRuntime::new(),
+            &req,
+            &tool_ctx,
+            turn,
+            turn.approval_policy,
+        )
+        .await;
+    emitter
+        .finish(ToolEventCtx::new(session, turn, &call_id, None), out)
+        .await
+}
+
+/// Runs a read-only git command, failing with git's stderr when it exits
+/// non-zero.
+async fn read_git(cwd: &Path, args: &[&str]) -> Result<Output, FunctionCallError> {
+    let output = git(cwd, args).await?;
+    if !output.status.success() {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output)
+}
+
+async fn git(cwd: &Path, args: &[&str]) -> Result<Output, FunctionCallError> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(cwd)
+        .env("GIT_OPTIONAL_LOCKS", "0")
+        .kill_on_drop(true)
+        .output();
+    match tokio::time::timeout(READ_TIMEOUT, output).await {
+        Ok(Ok(output)) => Ok(output),
+        Ok(Err(err)) => Err(FunctionCallError::RespondToModel(format!(
+            "failed to run git: {err}"
+        ))),
+        Err(_) => Err(FunctionCallError::RespondToModel(format!(
+            "git {} timed out after {} seconds",
+            args.first().copied().unwrap_or_default(),
+            READ_TIMEOUT.as_secs()
+        ))),
+    }
+}
+
+/// Parses `git status --porcelain=v2 --branch -z`.
+fn parse_status(output: &str) -> GitStatus {
+    let mut status = GitStatus::default();
+    let mut records = output.split('\0').filter(|record| !record.is_empty());
+    while let Some(record) = records.next() {
+        if let Some(header) = record.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.oid" if value != "(initial)" => status.head = Some(value.to_string()),
+                "branch.head" if value != "(detached)" => status.branch = Some(value.to_string()),
+                "branch.upstream" => status.upstream = Some(value.to_string()),
+                "branch.ab" => {
+                    for count in value.split(' ') {
+                        if let Some(ahead) = count.strip_prefix('+') {
+                            status.ahead = ahead.parse().unwrap_or_default();
+                        } else if let Some(behind) = count.strip_prefix('-') {
+                            status.behind = behind.parse().unwrap_or_default();
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let (kind, rest) = record.split_at(1);
+        let rest = rest.trim_start();
+        match kind {
+            "1" | "2" => {
+                // XY sub mH mI mW hH hI [score] path
+                let fields = if kind == "1" { 8 } else { 9 };
+                let mut parts = rest.splitn(fields, ' ');
+                let xy = parts.next().unwrap_or("..");
+                let Some(path) = parts.nth(fields - 2) else {
+                    continue;
+                };
+                let original_path = if kind == "2" {
+                    records.next().map(str::to_string)
+                } else {
+                    None
+                };
+                let mut codes = xy.chars();
+                let (x, y) = (codes.next(), codes.next());
+                if let Some(change) = x.and_then(change_name) {
+                    status.staged.push(FileChange {
+                        path: path.to_string(),
+                        status: change,
+                        original_path: original_path.clone(),
+                    });
+                }
+                if let Some(change) = y.and_then(change_name) {
+                    status.unstaged.push(FileChange {
+                        path: path.to_string(),
+                        status: change,
+                        original_path,
+                    });
+                }
+            }
+            "u" => {
+                if let Some(path) = rest.splitn(10, ' ').nth(9) {
+                    status.conflicted.push(path.to_string());
+                }
+            }
+            "?" => status.untracked.push(rest.to_string()),
+            _ => {}
+        }
+    }
+    status
+}
+
+fn change_name(code: char) -> Option<&'static str> {
+    match code {
+        'M' => Some("modified"),
+        'T' => Some("type_changed"),
+        'A' => Some("added"),
+        'D' => Some("deleted"),
+        'R' => Some("renamed"),
+        'C' => Some("copied"),
+        _ => None,
+    }
+}
+
+/// Parses `git diff --numstat -z`. Renames put an empty path in the record
+/// and the old and new paths in the two records after it.
+fn parse_numstat(output: &str) -> Vec<DiffStat> {
+    let mut stats = Vec::new();
+    let mut records = output.split('\0');
+    while let Some(record) = records.next() {
+        let mut fields = record.splitn(3, '\t');
+        let (Some(additions), Some(deletions), Some(path)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let (path, original_path) = if path.is_empty() {
+            let original = records.next().unwrap_or_default().to_string();
+            (
+                records.next().unwrap_or_default().to_string(),
+                Some(original),
+            )
+        } else {
+            (path.to_string(), None)
+        };
+        stats.push(DiffStat {
+            path,
+            original_path,
+            additions: additions.parse().ok(),
+            deletions: deletions.parse().ok(),
+        });
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_porcelain_v2_status() {
+        let output = [
+            "# branch.oid 1234abcd",
+            "# branch.head feature/x",
+            "# branch.upstream origin/feature/x",
+            "# branch.ab +2 -1",
+            "1 M. N... 100644 100644 100644 aaa bbb src/lib.rs",
+            "1 .M N... 100644 100644 100644 aaa aaa README with space.md",
+            "2 R. N... 100644 100644 100644 aaa aaa R100 src/new.rs",
+            "src/old.rs",
+            "u UU N... 100644 100644 100644 100644 aaa bbb ccc conflict.txt",
+            "? notes.txt",
+            "",
+        ]
+        .join("\0");
+
+        assert_eq!(
+            parse_status(&output),
+            GitStatus {
+                branch: Some("feature/x".to_string()),
+                head: Some("1234abcd".to_string()),
+                upstream: Some("origin/feature/x".to_string()),
+                ahead: 2,
+                behind: 1,
+                staged: vec![
+                    FileChange {
+                        path: "src/lib.rs".to_string(),
+                        status: "modified",
+                        original_path: None,
+                    },
+                    FileChange {
+                        path: "src/new.rs".to_string(),
+                        status: "renamed",
+                        original_path: Some("src/old.rs".to_string()),
+                    },
+                ],
+                unstaged: vec![FileChange {
+                    path: "README with space.md".to_string(),
+                    status: "modified",
+                    original_path: None,
+                }],
+                untracked: vec!["notes.txt".to_string()],
+                conflicted: vec!["conflict.txt".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn parses_initial_and_detached_heads() {
+        let output = "# branch.oid (initial)\0# branch.head (detached)\0";
+        assert_eq!(parse_status(output), GitStatus::default());
+    }
+
+    #[test]
+    fn parses_numstat_with_renames_and_binaries() {
+        let output = [
+            "3\t1\tsrc/lib.rs",
+            "-\t-\tlogo.png",
+            "0\t0\t",
+            "old.rs",
+            "new.rs",
+            "",
+        ]
+        .join("\0");
+        assert_eq!(
+            parse_numstat(&output),
+            vec![
+                DiffStat {
+                    path: "src/lib.rs".to_string(),
+                    original_path: None,
+                    additions: Some(3),
+                    deletions: Some(1),
+                },
+                DiffStat {
+                    path: "logo.png".to_string(),
+                    original_path: None,
+                    additions: None,
+                    deletions: None,
+                },
+                DiffStat {
+                    path: "new.rs".to_string(),
+                    original_path: Some("old.rs".to_string()),
+                    additions: Some(0),
+                    deletions: Some(0),
+                },
+            ]
+        );
+    }
+}
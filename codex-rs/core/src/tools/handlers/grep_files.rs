@@ -53,7 +53,7 @@ impl ToolHandler for GrepFilesHandler {
         };
 
         let args: GrepFilesArgs = serde_json::from_str(&arguments).map_err(|err| {
-            FunctionCallError::RespondToModel(format!(
+            FunctionCallError::InvalidArguments(format!(
                 "failed to parse function arguments: {err:?}"
             ))
         })?;
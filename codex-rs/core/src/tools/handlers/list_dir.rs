@@ -63,7 +63,7 @@ impl ToolHandler for ListDirHandler {
         };
 
         let args: ListDirArgs = serde_json::from_str(&arguments).map_err(|err| {
-            FunctionCallError::RespondToModel(format!(
+            FunctionCallError::InvalidArguments(format!(
                 "failed to parse function arguments: {err:?}"
             ))
         })?;
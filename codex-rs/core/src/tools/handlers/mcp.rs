@@ -16,6 +16,20 @@ impl ToolHandler for McpHandler {
         ToolKind::Mcp
     }
 
+    async fn is_mutating(&self, invocation: &ToolInvocation) -> bool {
+        let ToolPayload::Mcp { server, tool, .. } = &invocation.payload else {
+            return true;
+        };
+        !invocation
+            .session
+            .services
+            .mcp_connection_manager
+            .read()
+            .await
+            .is_read_only_tool(server, tool)
+            .await
+    }
+
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
         let ToolInvocation {
             session,
@@ -271,7 +271,7 @@ async fn handle_list_resources(
                 .list_resources(&server_name, params)
                 .await
                 .map_err(|err| {
-                    FunctionCallError::RespondToModel(format!("resources/list failed: {err:#}"))
+                    FunctionCallError::McpTransport(format!("resources/list failed: {err:#}"))
                 })?;
             Ok(ListResourcesPayload::from_single_server(
                 server_name,
@@ -380,7 +380,7 @@ async fn handle_list_resource_templates(
                 .list_resource_templates(&server_name, params)
                 .await
                 .map_err(|err| {
-                    FunctionCallError::RespondToModel(format!(
+                    FunctionCallError::McpTransport(format!(
                         "resources/templates/list failed: {err:#}"
                     ))
                 })?;
@@ -485,7 +485,7 @@ async fn handle_read_resource(
             .read_resource(&server, ReadResourceRequestParams { uri: uri.clone() })
             .await
             .map_err(|err| {
-                FunctionCallError::RespondToModel(format!("resources/read failed: {err:#}"))
+                FunctionCallError::McpTransport(format!("resources/read failed: {err:#}"))
             })?;
 
         Ok(ReadResourcePayload {
@@ -641,7 +641,9 @@ fn parse_arguments(raw_args: &str) -> Result<Option<Value>, FunctionCallError> {
         Ok(None)
     } else {
         serde_json::from_str(raw_args).map(Some).map_err(|err| {
-            FunctionCallError::RespondToModel(format!("failed to parse function arguments: {err}"))
+            FunctionCallError::InvalidArguments(format!(
+                "failed to parse function arguments: {err}"
+            ))
         })
     }
 }
@@ -652,9 +654,11 @@ where
 {
     match arguments {
         Some(value) => serde_json::from_value(value).map_err(|err| {
-            FunctionCallError::RespondToModel(format!("failed to parse function arguments: {err}"))
+            FunctionCallError::InvalidArguments(format!(
+                "failed to parse function arguments: {err}"
+            ))
         }),
-        None => Err(FunctionCallError::RespondToModel(
+        None => Err(FunctionCallError::InvalidArguments(
             "failed to parse function arguments: expected value".to_string(),
         )),
     }
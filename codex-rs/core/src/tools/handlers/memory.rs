@@ -0,0 +1,145 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::function_tool::FunctionCallError;
+use crate::memory_store;
+use crate::memory_store::MemoryEntry;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub(crate) const MEMORY_SEARCH_DEFAULT_LIMIT: usize = 10;
+const MEMORY_SEARCH_MAX_LIMIT: usize = 50;
+
+/// Handles both `memory_write` and `memory_search`.
+pub struct MemoryHandler;
+
+#[derive(Deserialize)]
+struct MemoryWriteArgs {
+    text: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    ttl_days: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct MemorySearchArgs {
+    query: String,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+#[async_trait]
+impl ToolHandler for MemoryHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            turn,
+            tool_name,
+            payload,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "{tool_name} handler received unsupported payload"
+                )));
+            }
+        };
+
+        let config = turn.client.config();
+        let project = memory_store::project_key(&turn.cwd);
+        let now = memory_store::now_unix_seconds();
+
+        let content = match tool_name.as_str() {
+            "memory_write" => {
+                let args: MemoryWriteArgs = parse_arguments(&arguments)?;
+                let text = args.text.trim();
+                if text.is_empty() {
+                    return Err(FunctionCallError::RespondToModel(
+                        "memory text must not be empty".to_string(),
+                    ));
+                }
+                let entry =
+                    MemoryEntry::new(project, text.to_string(), args.tags, args.ttl_days, now);
+                memory_store::append_entry(&config.codex_home, &entry)
+                    .await
+                    .map_err(|err| {
+                        FunctionCallError::RespondToModel(format!("failed to save memory: {err}"))
+                    })?;
+                format!("Saved memory {}.", entry.id)
+            }
+            "memory_search" => {
+                let args: MemorySearchArgs = parse_arguments(&arguments)?;
+                let limit = args
+                    .limit
+                    .unwrap_or(MEMORY_SEARCH_DEFAULT_LIMIT)
+                    .clamp(1, MEMORY_SEARCH_MAX_LIMIT);
+                let codex_home = config.codex_home.clone();
+                let entries =
+                    tokio::task::spawn_blocking(move || memory_store::read_entries(&codex_home))
+                        .await
+                        .map_err(|err| {
+                            FunctionCallError::RespondToModel(format!(
+                                "failed to read memories: {err}"
+                            ))
+                        })?
+                        .map_err(|err| {
+                            FunctionCallError::RespondToModel(format!(
+                                "failed to read memories: {err}"
+                            ))
+                        })?;
+                let found = memory_store::search(&entries, &project, &args.query, limit, now);
+                render_search_results(&found)
+            }
+            other => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "memory handler does not support tool {other}"
+                )));
+            }
+        };
+
+        Ok(ToolOutput::Function {
+            content,
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
+
+fn parse_arguments<T: for<'de> Deserialize<'de>>(arguments: &str) -> Result<T, FunctionCallError> {
+    serde_json::from_str(arguments).map_err(|e| {
+        FunctionCallError::InvalidArguments(format!("failed to parse function arguments: {e:?}"))
+    })
+}
+
+fn render_search_results(found: &[&MemoryEntry]) -> String {
+    if found.is_empty() {
+        return "No matching memories.".to_string();
+    }
+    found
+        .iter()
+        .map(|entry| {
+            let created = entry.created_date();
+            if entry.tags.is_empty() {
+                format!("[{}] ({created}) {}", entry.id, entry.text)
+            } else {
+                format!(
+                    "[{}] ({created}) {} #{}",
+                    entry.id,
+                    entry.text,
+                    entry.tags.join(" #")
+                )
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -1,26 +1,61 @@
 pub mod apply_patch;
+mod background_shell;
+mod code_search;
+mod file_edit;
+mod git;
 mod grep_files;
 mod list_dir;
 mod mcp;
 mod mcp_resource;
+mod memory;
 mod plan;
+mod pull_requests;
+mod python;
 mod read_file;
+mod repo_map;
+mod run_task;
+mod run_tests;
+mod semantic_search;
 mod shell;
+mod shell_session;
+mod spawn_agent;
+mod terminal;
 mod test_sync;
 mod unified_exec;
 mod view_image;
+mod web;
 
+pub(crate) use memory::MEMORY_SEARCH_DEFAULT_LIMIT;
 pub use plan::PLAN_TOOL;
+pub(crate) use spawn_agent::SPAWN_AGENT_DEFAULT_MAX_TURNS;
+pub(crate) use spawn_agent::SPAWN_AGENT_MAX_TURNS;
+pub(crate) use spawn_agent::SPAWN_AGENTS_MAX_PARALLEL;
 
 pub use apply_patch::ApplyPatchHandler;
+pub use background_shell::BackgroundShellHandler;
+pub use code_search::CodeSearchHandler;
+pub use file_edit::FileEditHandler;
+pub use git::GitHandler;
 pub use grep_files::GrepFilesHandler;
 pub use list_dir::ListDirHandler;
 pub use mcp::McpHandler;
 pub use mcp_resource::McpResourceHandler;
+pub use memory::MemoryHandler;
 pub use plan::PlanHandler;
+pub use pull_requests::PullRequestHandler;
+pub use python::PythonHandler;
 pub use read_file::ReadFileHandler;
+pub use repo_map::RepoMapHandler;
+pub use run_task::RunTaskHandler;
+pub use run_tests::RunTestsHandler;
+pub use semantic_search::SemanticSearchHandler;
 pub use shell::ShellCommandHandler;
 pub use shell::ShellHandler;
+pub use shell_session::ShellSessionHandler;
+pub use spawn_agent::SpawnAgentHandler;
+pub use spawn_agent::SpawnAgentsHandler;
+pub use terminal::TerminalHandler;
 pub use test_sync::TestSyncHandler;
 pub use unified_exec::UnifiedExecHandler;
 pub use view_image::ViewImageHandler;
+pub use web::WebHandler;
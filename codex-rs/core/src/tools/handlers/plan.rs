@@ -3,6 +3,7 @@ use crate::client_common::tools::ToolSpec;
 use crate::codex::Session;
 use crate::codex::TurnContext;
 use crate::function_tool::FunctionCallError;
+use crate::plan_file;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
 use crate::tools::context::ToolPayload;
@@ -10,6 +11,7 @@ use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
 use crate::tools::spec::JsonSchema;
 use async_trait::async_trait;
+use codex_protocol::plan_tool::StepStatus;
 use codex_protocol::plan_tool::UpdatePlanArgs;
 use codex_protocol::protocol::EventMsg;
 use std::collections::BTreeMap;
@@ -104,6 +106,9 @@ pub(crate) async fn handle_update_plan(
     _call_id: String,
 ) -> Result<String, FunctionCallError> {
     let args = parse_update_plan_arguments(&arguments)?;
+    if turn_context.client.config().sync_plan_file {
+        plan_file::write(&turn_context.cwd, &args).await;
+    }
     session
         .send_event(turn_context, EventMsg::PlanUpdate(args))
         .await;
@@ -111,7 +116,66 @@ pub(crate) async fn handle_update_plan(
 }
 
 fn parse_update_plan_arguments(arguments: &str) -> Result<UpdatePlanArgs, FunctionCallError> {
-    serde_json::from_str::<UpdatePlanArgs>(arguments).map_err(|e| {
-        FunctionCallError::RespondToModel(format!("failed to parse function arguments: {e}"))
-    })
+    let args = serde_json::from_str::<UpdatePlanArgs>(arguments).map_err(|e| {
+        FunctionCallError::InvalidArguments(format!("failed to parse function arguments: {e}"))
+    })?;
+    validate_plan(&args).map_err(FunctionCallError::RespondToModel)?;
+    Ok(args)
+}
+
+/// Rejects plans clients cannot render as a checklist.
+fn validate_plan(args: &UpdatePlanArgs) -> Result<(), String> {
+    if args.plan.iter().any(|item| item.step.trim().is_empty()) {
+        return Err("plan steps must not be empty".to_string());
+    }
+    let in_progress = args
+        .plan
+        .iter()
+        .filter(|item| matches!(item.status, StepStatus::InProgress))
+        .count();
+    if in_progress > 1 {
+        return Err(format!(
+            "at most one step can be in_progress at a time, got {in_progress}"
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn rejects_more_than_one_step_in_progress() {
+        let err = parse_update_plan_arguments(
+            r#"{"plan":[{"step":"a","status":"in_progress"},{"step":"b","status":"in_progress"}]}"#,
+        )
+        .expect_err("two steps in progress");
+        assert_eq!(
+            err,
+            FunctionCallError::RespondToModel(
+                "at most one step can be in_progress at a time, got 2".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_blank_steps() {
+        let err = parse_update_plan_arguments(r#"{"plan":[{"step":"  ","status":"pending"}]}"#)
+            .expect_err("blank step");
+        assert_eq!(
+            err,
+            FunctionCallError::RespondToModel("plan steps must not be empty".to_string())
+        );
+    }
+
+    #[test]
+    fn accepts_a_well_formed_plan() {
+        let args = parse_update_plan_arguments(
+            r#"{"explanation":"x","plan":[{"step":"a","status":"completed"},{"step":"b","status":"in_progress"}]}"#,
+        )
+        .expect("valid plan");
+        assert_eq!(args.plan.len(), 2);
+    }
 }
@@ -0,0 +1,218 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::codex::TurnContext;
+use crate::function_tool::FunctionCallError;
+use crate::pull_requests::NewPullRequest;
+use crate::pull_requests::PullRequestClient;
+use crate::pull_requests::current_branch;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::handlers::web::approve_network_call;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::truncate::formatted_truncate_text;
+
+/// Handles `pr_create`, `pr_comments` and `pr_reply`.
+pub struct PullRequestHandler;
+
+#[derive(Debug, Deserialize)]
+struct CreateArgs {
+    title: String,
+    #[serde(default)]
+    body: String,
+    #[serde(default)]
+    base: Option<String>,
+    #[serde(default)]
+    draft: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommentsArgs {
+    #[serde(default)]
+    number: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReplyArgs {
+    body: String,
+    #[serde(default)]
+    number: Option<u64>,
+    #[serde(default)]
+    thread_id: Option<String>,
+}
+
+#[async_trait]
+impl ToolHandler for PullRequestHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn is_mutating(&self, invocation: &ToolInvocation) -> bool {
+        invocation.tool_name != "pr_comments"
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            tool_name,
+            payload,
+            call_id,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "{tool_name} handler received unsupported payload"
+                )));
+            }
+        };
+
+        let client = PullRequestClient::connect(&turn.cwd, &turn.client.config().pull_requests)
+            .await
+            .map_err(respond)?;
+        let content = match tool_name.as_str() {
+            "pr_create" => {
+                let args: CreateArgs = parse_arguments(&arguments)?;
+                let branch = current_branch(&turn.cwd).await.map_err(respond)?;
+                if turn
+                    .client
+                    .config()
+                    .git_protected_branches
+                    .contains(&branch)
+                {
+                    return Err(FunctionCallError::RespondToModel(format!(
+                        "`{branch}` is a protected branch; open pull requests from a feature branch"
+                    )));
+                }
+                approve_network_call(
+                    &session,
+                    &turn,
+                    &call_id,
+                    &tool_name,
+                    client.api_url(),
+                    Some(format!(
+                        "pr_create opens a pull request \"{}\" from `{branch}` under your account",
+                        args.title
+                    )),
+                )
+                .await?;
+                let pull_request = client
+                    .create(NewPullRequest {
+                        source_branch: &branch,
+                        target_branch: args.base.as_deref(),
+                        title: &args.title,
+                        body: &args.body,
+                        draft: args.draft,
+                    })
+                    .await
+                    .map_err(|err| {
+                        FunctionCallError::RespondToModel(format!(
+                            "failed to open a pull request from `{branch}` (is it pushed?): {err:#}"
+                        ))
+                    })?;
+                to_json(&pull_request)?
+            }
+            "pr_comments" => {
+                let args: CommentsArgs = parse_arguments(&arguments)?;
+                approve_network_call(
+                    &session,
+                    &turn,
+                    &call_id,
+                    &tool_name,
+                    client.api_url(),
+                    None,
+                )
+                .await?;
+                let number = resolve_number(&client, &turn, args.number).await?;
+                let conversation = client.conversation(number).await.map_err(respond)?;
+                formatted_truncate_text(&to_json(&conversation)?, turn.truncation_policy)
+            }
+            "pr_reply" => {
+                let args: ReplyArgs = parse_arguments(&arguments)?;
+                if args.body.trim().is_empty() {
+                    return Err(FunctionCallError::RespondToModel(
+                        "body must not be empty".to_string(),
+                    ));
+                }
+                approve_network_call(
+                    &session,
+                    &turn,
+                    &call_id,
+                    &tool_name,
+                    client.api_url(),
+                    None,
+                )
+                .await?;
+                let number = resolve_number(&client, &turn, args.number).await?;
+                approve_network_call(
+                    &session,
+                    &turn,
+                    &call_id,
+                    &tool_name,
+                    client.api_url(),
+                    Some(format!(
+                        "pr_reply posts a comment on #{number} under your account:\n\n{}",
+                        args.body
+                    )),
+                )
+                .await?;
+                let comment = client
+                    .reply(number, args.thread_id.as_deref(), &args.body)
+                    .await
+                    .map_err(respond)?;
+                to_json(&comment)?
+            }
+            other => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "unsupported pull request tool: {other}"
+                )));
+            }
+        };
+
+        Ok(ToolOutput::Function {
+            content,
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
+
+/// The given number, or the open pull request for the current branch.
+async fn resolve_number(
+    client: &PullRequestClient,
+    turn: &TurnContext,
+    number: Option<u64>,
+) -> Result<u64, FunctionCallError> {
+    if let Some(number) = number {
+        return Ok(number);
+    }
+    let branch = current_branch(&turn.cwd).await.map_err(respond)?;
+    match client.find_for_branch(&branch).await.map_err(respond)? {
+        Some(pull_request) => Ok(pull_request.number),
+        None => Err(FunctionCallError::RespondToModel(format!(
+            "no open pull request for branch `{branch}`; pass `number`"
+        ))),
+    }
+}
+
+fn respond(err: anyhow::Error) -> FunctionCallError {
+    FunctionCallError::RespondToModel(format!("{err:#}"))
+}
+
+fn parse_arguments<'a, T: Deserialize<'a>>(arguments: &'a str) -> Result<T, FunctionCallError> {
+    serde_json::from_str(arguments).map_err(|err| {
+        FunctionCallError::InvalidArguments(format!("failed to parse function arguments: {err:?}"))
+    })
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, FunctionCallError> {
+    serde_json::to_string(value).map_err(|err| {
+        FunctionCallError::Fatal(format!("failed to serialize pull request output: {err}"))
+    })
+}
@@ -0,0 +1,190 @@
+use async_trait::async_trait;
+use codex_protocol::models::FunctionCallOutputContentItem;
+use serde::Deserialize;
+
+use crate::exec::DEFAULT_EXEC_COMMAND_TIMEOUT_MS;
+use crate::exec_env::create_env;
+use crate::exec_policy::create_exec_approval_requirement_for_command;
+use crate::function_tool::FunctionCallError;
+use crate::python_kernel::KERNEL_DRIVER;
+use crate::python_kernel::PythonOutcome;
+use crate::python_kernel::python_program;
+use crate::sandboxing::SandboxPermissions;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::orchestrator::ToolOrchestrator;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::tools::runtimes::python::PythonRequest;
+use crate::tools::runtimes::python::PythonRuntime;
+use crate::tools::sandboxing::ToolCtx;
+use crate::tools::sandboxing::ToolError;
+
+pub struct PythonHandler;
+
+#[derive(Debug, Deserialize)]
+struct PythonArgs {
+    code: String,
+    #[serde(default)]
+    reset: bool,
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+    #[serde(default)]
+    justification: Option<String>,
+}
+
+#[async_trait]
+impl ToolHandler for PythonHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn is_mutating(&self, _invocation: &ToolInvocation) -> bool {
+        true
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            call_id,
+            tool_name,
+            payload,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "{tool_name} handler received unsupported payload"
+                )));
+            }
+        };
+        let args: PythonArgs = serde_json::from_str(&arguments).map_err(|err| {
+            FunctionCallError::InvalidArguments(format!(
+                "failed to parse python arguments: {err:?}"
+            ))
+        })?;
+
+        let command = vec![
+            python_program().to_string(),
+            "-c".to_string(),
+            args.code.clone(),
+        ];
+        let features = session.features();
+        let exec_approval_requirement = create_exec_approval_requirement_for_command(
+            &turn.exec_policy,
+            &features,
+            &turn.client.config().command_safety,
+            &session.services.approved_command_prefixes.read().await,
+            &command,
+            turn.approval_policy,
+            &turn.sandbox_policy,
+            SandboxPermissions::UseDefault,
+        )
+        .await;
+
+        let req = PythonRequest {
+            code: args.code,
+            reset: args.reset,
+            command,
+            cwd: turn.cwd.clone(),
+            kernel_command: vec![
+                python_program().to_string(),
+                "-u".to_string(),
+                "-c".to_string(),
+                KERNEL_DRIVER.to_string(),
+            ],
+            timeout_ms: args.timeout_ms.unwrap_or(DEFAULT_EXEC_COMMAND_TIMEOUT_MS),
+            env: create_env(&turn.shell_environment_policy),
+            justification: args.justification,
+            exec_approval_requirement,
+        };
+        let mut orchestrator = ToolOrchestrator::new();
+        let mut runtime = PythonRuntime;
+        let tool_ctx = ToolCtx {
+            session: session.as_ref(),
+            turn: turn.as_ref(),
+            call_id,
+            tool_name,
+        };
+        let outcome = orchestrator
+            .run(&mut runtime, &req, &tool_ctx, &turn, turn.approval_policy)
+            .await
+            .map_err(|err| match err {
+                ToolError::Rejected(reason) => FunctionCallError::Denied(reason),
+                ToolError::Codex(err) => {
+                    FunctionCallError::RespondToModel(format!("python failed: {err}"))
+                }
+            })?;
+
+        if outcome.timed_out {
+            return Err(FunctionCallError::Timeout(format_outcome(&outcome)));
+        }
+        let content = format_outcome(&outcome);
+        let content_items =
+            (!outcome.images.is_empty()).then(|| {
+                std::iter::once(FunctionCallOutputContentItem::InputText {
+                    text: content.clone(),
+                })
+                .chain(outcome.images.iter().map(|image| {
+                    FunctionCallOutputContentItem::InputImage {
+                        image_url: format!("data:image/png;base64,{image}"),
+                    }
+                }))
+                .collect()
+            });
+        Ok(ToolOutput::Function {
+            content,
+            content_items,
+            success: Some(outcome.error.is_none()),
+        })
+    }
+}
+
+fn format_outcome(outcome: &PythonOutcome) -> String {
+    let mut sections = Vec::new();
+    if !outcome.output.is_empty() {
+        sections.push(outcome.output.trim_end().to_string());
+    }
+    if let Some(result) = &outcome.result {
+        sections.push(format!("Out: {result}"));
+    }
+    if let Some(error) = &outcome.error {
+        sections.push(error.trim_end().to_string());
+    }
+    if !outcome.images.is_empty() {
+        sections.push(format!("[{} figure(s) attached]", outcome.images.len()));
+    }
+    if let Some(note) = &outcome.note {
+        sections.push(format!("[{note}]"));
+    }
+    if sections.is_empty() {
+        "(no output)".to_string()
+    } else {
+        sections.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn format_outcome_lists_each_part() {
+        assert_eq!(format_outcome(&PythonOutcome::default()), "(no output)");
+        assert_eq!(
+            format_outcome(&PythonOutcome {
+                output: "loaded 3 rows\n".to_string(),
+                result: Some("| a |\n|---|\n| 1 |".to_string()),
+                images: vec!["iVBORw0KGgo=".to_string()],
+                note: Some("The kernel exited.".to_string()),
+                ..Default::default()
+            }),
+            "loaded 3 rows\nOut: | a |\n|---|\n| 1 |\n[1 figure(s) attached]\n[The kernel exited.]"
+        );
+    }
+}
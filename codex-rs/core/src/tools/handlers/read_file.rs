@@ -5,6 +5,7 @@ use async_trait::async_trait;
 use codex_utils_string::take_bytes_at_char_boundary;
 use serde::Deserialize;
 
+use crate::features::Feature;
 use crate::function_tool::FunctionCallError;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
@@ -96,7 +97,9 @@ impl ToolHandler for ReadFileHandler {
     }
 
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
-        let ToolInvocation { payload, .. } = invocation;
+        let ToolInvocation {
+            session, payload, ..
+        } = invocation;
 
         let arguments = match payload {
             ToolPayload::Function { arguments } => arguments,
@@ -108,7 +111,7 @@ impl ToolHandler for ReadFileHandler {
         };
 
         let args: ReadFileArgs = serde_json::from_str(&arguments).map_err(|err| {
-            FunctionCallError::RespondToModel(format!(
+            FunctionCallError::InvalidArguments(format!(
                 "failed to parse function arguments: {err:?}"
             ))
         })?;
@@ -147,6 +150,9 @@ impl ToolHandler for ReadFileHandler {
                 indentation::read_block(&path, offset, limit, indentation).await?
             }
         };
+        if session.enabled(Feature::ExternalEdits) {
+            session.services.external_edits.observe(&path).await;
+        }
         Ok(ToolOutput::Function {
             content: collected.join("\n"),
             content_items: None,
@@ -0,0 +1,68 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::function_tool::FunctionCallError;
+use crate::repo_map;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct RepoMapHandler;
+
+/// Upper bound on `max_tokens`, so a refresh cannot flood the context.
+const MAX_OUTPUT_TOKENS: usize = 20_000;
+
+#[derive(Deserialize)]
+struct RepoMapArgs {
+    /// Only files under this path, relative to the project root.
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    max_tokens: Option<usize>,
+}
+
+#[async_trait]
+impl ToolHandler for RepoMapHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation { payload, turn, .. } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "repo_map handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+
+        let args: RepoMapArgs = serde_json::from_str(&arguments).map_err(|err| {
+            FunctionCallError::InvalidArguments(format!(
+                "failed to parse function arguments: {err:?}"
+            ))
+        })?;
+
+        let max_tokens = args
+            .max_tokens
+            .unwrap_or(turn.client.config().repo_map_max_tokens)
+            .min(MAX_OUTPUT_TOKENS);
+        let root = repo_map::project_root(&turn.cwd);
+        let codex_home = turn.client.config().codex_home.clone();
+        let map = tokio::task::spawn_blocking(move || repo_map::build(&codex_home, &root))
+            .await
+            .map_err(|err| FunctionCallError::RespondToModel(format!("repo_map failed: {err}")))?
+            .map_err(|err| FunctionCallError::RespondToModel(format!("repo_map failed: {err}")))?;
+
+        let prefix = args.path.unwrap_or_default();
+        Ok(ToolOutput::Function {
+            content: repo_map::render(&map, &prefix, max_tokens),
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
@@ -0,0 +1,153 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::exec_env::create_env;
+use crate::exec_policy::create_exec_approval_requirement_for_command;
+use crate::function_tool::FunctionCallError;
+use crate::project_tasks;
+use crate::protocol::ExecCommandSource;
+use crate::sandboxing::SandboxPermissions;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::events::ToolEmitter;
+use crate::tools::events::ToolEventCtx;
+use crate::tools::orchestrator::ToolOrchestrator;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::tools::runtimes::shell::ShellRequest;
+use crate::tools::runtimes::shell::ShellRuntime;
+use crate::tools::sandboxing::ToolCtx;
+
+/// Builds and test suites take far longer than the usual shell command.
+const DEFAULT_TIMEOUT_MS: u64 = 10 * 60 * 1000;
+
+pub struct RunTaskHandler;
+
+#[derive(Debug, Deserialize)]
+struct RunTaskArgs {
+    task: String,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+    #[serde(default)]
+    justification: Option<String>,
+}
+
+#[async_trait]
+impl ToolHandler for RunTaskHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn is_mutating(&self, _invocation: &ToolInvocation) -> bool {
+        true
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            call_id,
+            tool_name,
+            payload,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "{tool_name} handler received unsupported payload"
+                )));
+            }
+        };
+        let args: RunTaskArgs = serde_json::from_str(&arguments).map_err(|err| {
+            FunctionCallError::InvalidArguments(format!(
+                "failed to parse run_task arguments: {err:?}"
+            ))
+        })?;
+
+        // Re-read the task files so edits made during the turn are seen.
+        let tasks = project_tasks::discover(&turn.cwd);
+        let Some(task) = project_tasks::find(&tasks, &args.task) else {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "unknown task {:?}; available tasks:\n{}",
+                args.task,
+                project_tasks::describe(&tasks)
+            )));
+        };
+        if turn.tools_config.tasks_only && !args.args.is_empty() {
+            return Err(FunctionCallError::RespondToModel(
+                "tasks_only is set, so tasks run without extra arguments".to_string(),
+            ));
+        }
+
+        let command = task.command(&turn.cwd, &args.args);
+        let emitter = ToolEmitter::shell(
+            command.clone(),
+            turn.cwd.clone(),
+            ExecCommandSource::Agent,
+            false,
+        );
+        emitter
+            .begin(ToolEventCtx::new(
+                session.as_ref(),
+                turn.as_ref(),
+                &call_id,
+                None,
+            ))
+            .await;
+
+        let features = session.features();
+        let exec_approval_requirement = create_exec_approval_requirement_for_command(
+            &turn.exec_policy,
+            &features,
+            &turn.client.config().command_safety,
+            &session.services.approved_command_prefixes.read().await,
+            &command,
+            turn.approval_policy,
+            &turn.sandbox_policy,
+            SandboxPermissions::UseDefault,
+        )
+        .await;
+        let req = ShellRequest {
+            command,
+            cwd: turn.cwd.clone(),
+            timeout_ms: Some(args.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS)),
+            env: create_env(&turn.shell_environment_policy),
+            sandbox_permissions: SandboxPermissions::UseDefault,
+            justification: args.justification,
+            exec_approval_requirement,
+            prompts_only_for_escalation: false,
+            pty: None,
+        };
+        let tool_ctx = ToolCtx {
+            session: session.as_ref(),
+            turn: turn.as_ref(),
+            call_id: call_id.clone(),
+            tool_name,
+        };
+        let out = ToolOrchestrator::new()
+            .run(
+                &mut ShellRuntime::new(),
+                &req,
+                &tool_ctx,
+                &turn,
+                turn.approval_policy,
+            )
+            .await;
+        let content = emitter
+            .finish(
+                ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None),
+                out,
+            )
+            .await?;
+        Ok(ToolOutput::Function {
+            content,
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
@@ -0,0 +1,214 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::exec_env::create_env;
+use crate::exec_policy::create_exec_approval_requirement_for_command;
+use crate::function_tool::FunctionCallError;
+use crate::protocol::ExecCommandSource;
+use crate::sandboxing::SandboxPermissions;
+use crate::test_runner::LastTestRun;
+use crate::test_runner::TestCase;
+use crate::test_runner::TestRunner;
+use crate::test_runner::format_report;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::events::ToolEmitter;
+use crate::tools::events::ToolEventCtx;
+use crate::tools::orchestrator::ToolOrchestrator;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::tools::runtimes::shell::ShellRequest;
+use crate::tools::runtimes::shell::ShellRuntime;
+use crate::tools::sandboxing::ToolCtx;
+use crate::truncate::formatted_truncate_text;
+
+/// Test suites take far longer than the usual shell command.
+const DEFAULT_TIMEOUT_MS: u64 = 10 * 60 * 1000;
+
+pub struct RunTestsHandler;
+
+#[derive(Debug, Deserialize)]
+struct RunTestsArgs {
+    #[serde(default)]
+    runner: Option<TestRunner>,
+    #[serde(default)]
+    tests: Vec<String>,
+    #[serde(default)]
+    rerun_failed: bool,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    workdir: Option<String>,
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+    #[serde(default)]
+    justification: Option<String>,
+}
+
+#[async_trait]
+impl ToolHandler for RunTestsHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn is_mutating(&self, _invocation: &ToolInvocation) -> bool {
+        true
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            call_id,
+            tool_name,
+            payload,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "{tool_name} handler received unsupported payload"
+                )));
+            }
+        };
+        let args: RunTestsArgs = serde_json::from_str(&arguments).map_err(|err| {
+            FunctionCallError::InvalidArguments(format!(
+                "failed to parse run_tests arguments: {err:?}"
+            ))
+        })?;
+
+        let (runner, cwd, extra, targets) = if args.rerun_failed {
+            let last = session.services.last_test_run.lock().await.clone();
+            match last {
+                Some(last) if !last.failed.is_empty() => {
+                    (last.runner, last.cwd, last.extra, last.failed)
+                }
+                _ => {
+                    return Err(FunctionCallError::RespondToModel(
+                        "no failing tests from an earlier run_tests call to re-run".to_string(),
+                    ));
+                }
+            }
+        } else {
+            let cwd = match args.workdir.filter(|dir| !dir.is_empty()) {
+                Some(dir) => turn.resolve_path(Some(dir)),
+                None => turn.cwd.clone(),
+            };
+            let runner = match args.runner.or_else(|| TestRunner::detect(&cwd)) {
+                Some(runner) => runner,
+                None => {
+                    return Err(FunctionCallError::RespondToModel(format!(
+                        "could not tell which test runner {} uses; pass runner as cargo, pytest, jest or go",
+                        cwd.display()
+                    )));
+                }
+            };
+            let targets = args.tests.into_iter().map(TestCase::named).collect();
+            (runner, cwd, args.args, targets)
+        };
+
+        let command = runner.command(&targets, &extra);
+        let emitter = ToolEmitter::shell(
+            command.clone(),
+            cwd.clone(),
+            ExecCommandSource::Agent,
+            false,
+        );
+        emitter
+            .begin(ToolEventCtx::new(
+                session.as_ref(),
+                turn.as_ref(),
+                &call_id,
+                None,
+            ))
+            .await;
+
+        let features = session.features();
+        let exec_approval_requirement = create_exec_approval_requirement_for_command(
+            &turn.exec_policy,
+            &features,
+            &turn.client.config().command_safety,
+            &session.services.approved_command_prefixes.read().await,
+            &command,
+            turn.approval_policy,
+            &turn.sandbox_policy,
+            SandboxPermissions::UseDefault,
+        )
+        .await;
+        let req = ShellRequest {
+            command,
+            cwd: cwd.clone(),
+            timeout_ms: Some(args.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS)),
+            env: create_env(&turn.shell_environment_policy),
+            sandbox_permissions: SandboxPermissions::UseDefault,
+            justification: args.justification,
+            exec_approval_requirement,
+            prompts_only_for_escalation: false,
+            pty: None,
+        };
+        let tool_ctx = ToolCtx {
+            session: session.as_ref(),
+            turn: turn.as_ref(),
+            call_id: call_id.clone(),
+            tool_name,
+        };
+        let out = ToolOrchestrator::new()
+            .run(
+                &mut ShellRuntime::new(),
+                &req,
+                &tool_ctx,
+                &turn,
+                turn.approval_policy,
+            )
+            .await;
+        let output = out.as_ref().ok().cloned();
+        let finished = emitter
+            .finish(
+                ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None),
+                out,
+            )
+            .await;
+        // Rejections, timeouts and sandbox denials keep the usual shell
+        // output; only completed runs are parsed.
+        let Some(output) = output else {
+            return finished.map(|content| ToolOutput::Function {
+                content,
+                content_items: None,
+                success: Some(true),
+            });
+        };
+
+        let report = runner.parse(&output.stdout.text, &output.aggregated_output.text, &cwd);
+        if report.is_empty() {
+            let content = format!(
+                "{} ran but no test results could be read from its output (exit code {}):\n{}",
+                runner.label(),
+                output.exit_code,
+                formatted_truncate_text(&output.aggregated_output.text, turn.truncation_policy)
+            );
+            return Err(FunctionCallError::RespondToModel(content));
+        }
+
+        *session.services.last_test_run.lock().await = Some(LastTestRun {
+            runner,
+            cwd,
+            extra,
+            failed: report
+                .failed
+                .iter()
+                .map(|failure| failure.test.clone())
+                .collect(),
+        });
+        Ok(ToolOutput::Function {
+            content: formatted_truncate_text(
+                &format_report(runner, &report, output.exit_code),
+                turn.truncation_policy,
+            ),
+            content_items: None,
+            success: Some(report.failed.is_empty()),
+        })
+    }
+}
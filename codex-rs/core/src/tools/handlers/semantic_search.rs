@@ -0,0 +1,82 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::function_tool::FunctionCallError;
+use crate::semantic_index;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct SemanticSearchHandler;
+
+/// Upper bound on `max_results`.
+const MAX_RESULTS: usize = 20;
+/// Budget for the rendered chunks, so large results cannot flood the context.
+const MAX_OUTPUT_TOKENS: usize = 10_000;
+
+#[derive(Deserialize)]
+struct SemanticSearchArgs {
+    query: String,
+    #[serde(default)]
+    max_results: Option<usize>,
+}
+
+#[async_trait]
+impl ToolHandler for SemanticSearchHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation { payload, turn, .. } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "semantic_search handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+
+        let args: SemanticSearchArgs = serde_json::from_str(&arguments).map_err(|err| {
+            FunctionCallError::InvalidArguments(format!(
+                "failed to parse function arguments: {err:?}"
+            ))
+        })?;
+        if args.query.trim().is_empty() {
+            return Err(FunctionCallError::RespondToModel(
+                "query must not be empty".to_string(),
+            ));
+        }
+
+        let config = turn.client.config();
+        let Some(embeddings) = &config.embeddings else {
+            return Err(FunctionCallError::RespondToModel(
+                "semantic_search requires an [embeddings] section in config.toml".to_string(),
+            ));
+        };
+        let max_results = args
+            .max_results
+            .unwrap_or(embeddings.max_results)
+            .clamp(1, MAX_RESULTS);
+        let hits = semantic_index::search(&turn.cwd, embeddings, &args.query, max_results)
+            .await
+            .map_err(|err| {
+                FunctionCallError::RespondToModel(format!("semantic_search failed: {err:#}"))
+            })?;
+
+        let rendered = semantic_index::render_hits(&turn.cwd, &hits, MAX_OUTPUT_TOKENS);
+        Ok(ToolOutput::Function {
+            content: if rendered.is_empty() {
+                "No matches.".to_string()
+            } else {
+                rendered
+            },
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
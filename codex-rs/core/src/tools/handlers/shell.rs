@@ -1,12 +1,17 @@
 use async_trait::async_trait;
 use codex_protocol::models::ShellCommandToolCallParams;
 use codex_protocol::models::ShellToolCallParams;
+use codex_utils_pty::TerminalSize;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::codex::TurnContext;
+use crate::config::types::ShellPty;
 use crate::exec::ExecParams;
 use crate::exec_env::create_env;
 use crate::exec_policy::create_exec_approval_requirement_for_command;
+use crate::exec_policy::prompts_only_for_escalation;
+use crate::features::Feature;
 use crate::function_tool::FunctionCallError;
 use crate::is_safe_command::is_known_safe_command;
 use crate::protocol::ExecCommandSource;
@@ -16,6 +21,7 @@ use crate::tools::context::ToolOutput;
 use crate::tools::context::ToolPayload;
 use crate::tools::events::ToolEmitter;
 use crate::tools::events::ToolEventCtx;
+use crate::tools::extensions::ToolEnv;
 use crate::tools::handlers::apply_patch::intercept_apply_patch;
 use crate::tools::orchestrator::ToolOrchestrator;
 use crate::tools::registry::ToolHandler;
@@ -34,7 +40,7 @@ impl ShellHandler {
             command: params.command,
             cwd: turn_context.resolve_path(params.workdir.clone()),
             expiration: params.timeout_ms.into(),
-            env: create_env(&turn_context.shell_environment_policy),
+            env: exec_env(turn_context),
             sandbox_permissions: params.sandbox_permissions.unwrap_or_default(),
             justification: params.justification,
             arg0: None,
@@ -60,7 +66,7 @@ impl ShellCommandHandler {
             command,
             cwd: turn_context.resolve_path(params.workdir.clone()),
             expiration: params.timeout_ms.into(),
-            env: create_env(&turn_context.shell_environment_policy),
+            env: exec_env(turn_context),
             sandbox_permissions: params.sandbox_permissions.unwrap_or_default(),
             justification: params.justification,
             arg0: None,
@@ -68,6 +74,16 @@ impl ShellCommandHandler {
     }
 }
 
+/// The shell environment policy's variables plus any [`ToolEnv`] set earlier
+/// in the turn.
+fn exec_env(turn_context: &TurnContext) -> HashMap<String, String> {
+    let mut env = create_env(&turn_context.shell_environment_policy);
+    if let Some(ToolEnv(vars)) = turn_context.extensions.get::<ToolEnv>() {
+        env.extend(vars);
+    }
+    env
+}
+
 #[async_trait]
 impl ToolHandler for ShellHandler {
     fn kind(&self) -> ToolKind {
@@ -107,7 +123,7 @@ impl ToolHandler for ShellHandler {
             ToolPayload::Function { arguments } => {
                 let params: ShellToolCallParams =
                     serde_json::from_str(&arguments).map_err(|e| {
-                        FunctionCallError::RespondToModel(format!(
+                        FunctionCallError::InvalidArguments(format!(
                             "failed to parse function arguments: {e:?}"
                         ))
                     })?;
@@ -184,7 +200,9 @@ impl ToolHandler for ShellCommandHandler {
         };
 
         let params: ShellCommandToolCallParams = serde_json::from_str(&arguments).map_err(|e| {
-            FunctionCallError::RespondToModel(format!("failed to parse function arguments: {e:?}"))
+            FunctionCallError::InvalidArguments(format!(
+                "failed to parse function arguments: {e:?}"
+            ))
         })?;
         let exec_params = Self::to_exec_params(params, session.as_ref(), turn.as_ref());
         ShellHandler::run_exec_like(
@@ -252,15 +270,31 @@ impl ShellHandler {
         emitter.begin(event_ctx).await;
 
         let features = session.features();
+        let approved_prefixes = session.services.approved_command_prefixes.read().await;
         let exec_approval_requirement = create_exec_approval_requirement_for_command(
             &turn.exec_policy,
             &features,
+            &turn.client.config().command_safety,
+            &approved_prefixes,
+            &exec_params.command,
+            turn.approval_policy,
+            &turn.sandbox_policy,
+            exec_params.sandbox_permissions,
+        )
+        .await;
+        let prompts_only_for_escalation = prompts_only_for_escalation(
+            &turn.exec_policy,
+            &features,
+            &turn.client.config().command_safety,
+            &approved_prefixes,
             &exec_params.command,
             turn.approval_policy,
             &turn.sandbox_policy,
             exec_params.sandbox_permissions,
+            &exec_approval_requirement,
         )
         .await;
+        drop(approved_prefixes);
 
         let req = ShellRequest {
             command: exec_params.command.clone(),
@@ -270,6 +304,14 @@ impl ShellHandler {
             sandbox_permissions: exec_params.sandbox_permissions,
             justification: exec_params.justification.clone(),
             exec_approval_requirement,
+            prompts_only_for_escalation,
+            pty: features.enabled(Feature::ShellPty).then(|| {
+                let ShellPty { rows, columns } = turn.client.config().shell_pty;
+                TerminalSize {
+                    rows,
+                    cols: columns,
+                }
+            }),
         };
         let mut orchestrator = ToolOrchestrator::new();
         let mut runtime = ShellRuntime::new();
@@ -0,0 +1,169 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::exec::DEFAULT_EXEC_COMMAND_TIMEOUT_MS;
+use crate::exec_env::create_env;
+use crate::exec_policy::create_exec_approval_requirement_for_command;
+use crate::function_tool::FunctionCallError;
+use crate::is_safe_command::is_known_safe_command;
+use crate::protocol::ExecCommandSource;
+use crate::sandboxing::SandboxPermissions;
+use crate::shell::ShellType;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::events::ToolEmitter;
+use crate::tools::events::ToolEventCtx;
+use crate::tools::handlers::apply_patch::intercept_apply_patch;
+use crate::tools::orchestrator::ToolOrchestrator;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::tools::runtimes::shell_session::ShellSessionRequest;
+use crate::tools::runtimes::shell_session::ShellSessionRuntime;
+use crate::tools::sandboxing::ToolCtx;
+
+pub struct ShellSessionHandler;
+
+#[derive(Debug, Deserialize)]
+struct ShellSessionArgs {
+    cmd: String,
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+    #[serde(default)]
+    justification: Option<String>,
+}
+
+#[async_trait]
+impl ToolHandler for ShellSessionHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn is_mutating(&self, invocation: &ToolInvocation) -> bool {
+        let ToolPayload::Function { arguments } = &invocation.payload else {
+            return true;
+        };
+        let Ok(args) = serde_json::from_str::<ShellSessionArgs>(arguments) else {
+            return true;
+        };
+        let command = invocation
+            .session
+            .user_shell()
+            .derive_exec_args(&args.cmd, true);
+        !is_known_safe_command(&command)
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            tracker,
+            call_id,
+            tool_name,
+            payload,
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "{tool_name} handler received unsupported payload"
+                )));
+            }
+        };
+        let args: ShellSessionArgs = serde_json::from_str(&arguments).map_err(|err| {
+            FunctionCallError::RespondToModel(format!(
+                "failed to parse shell_session arguments: {err:?}"
+            ))
+        })?;
+
+        let shell = session.user_shell();
+        if !matches!(
+            shell.shell_type,
+            ShellType::Bash | ShellType::Zsh | ShellType::Sh
+        ) {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "shell_session needs a POSIX shell (bash, zsh or sh), but the session shell is {}; use the shell tool instead",
+                shell.name()
+            )));
+        }
+
+        let command = shell.derive_exec_args(&args.cmd, true);
+        let cwd = session
+            .services
+            .shell_session
+            .cwd()
+            .await
+            .unwrap_or_else(|| turn.cwd.clone());
+        let timeout_ms = args.timeout_ms.unwrap_or(DEFAULT_EXEC_COMMAND_TIMEOUT_MS);
+
+        if let Some(output) = intercept_apply_patch(
+            &command,
+            &cwd,
+            Some(timeout_ms),
+            session.as_ref(),
+            turn.as_ref(),
+            Some(&tracker),
+            &call_id,
+            tool_name.as_str(),
+        )
+        .await?
+        {
+            return Ok(output);
+        }
+
+        let emitter = ToolEmitter::shell(
+            command.clone(),
+            cwd.clone(),
+            ExecCommandSource::Agent,
+            false,
+        );
+        let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
+        emitter.begin(event_ctx).await;
+
+        let features = session.features();
+        let exec_approval_requirement = create_exec_approval_requirement_for_command(
+            &turn.exec_policy,
+            &features,
+            &turn.client.config().command_safety,
+            &session.services.approved_command_prefixes.read().await,
+            &command,
+            turn.approval_policy,
+            &turn.sandbox_policy,
+            SandboxPermissions::UseDefault,
+        )
+        .await;
+
+        let req = ShellSessionRequest {
+            script: args.cmd,
+            command,
+            cwd,
+            shell_command: vec![
+                shell.shell_path.to_string_lossy().to_string(),
+                "-l".to_string(),
+            ],
+            timeout_ms,
+            env: create_env(&turn.shell_environment_policy),
+            justification: args.justification,
+            exec_approval_requirement,
+        };
+        let mut orchestrator = ToolOrchestrator::new();
+        let mut runtime = ShellSessionRuntime;
+        let tool_ctx = ToolCtx {
+            session: session.as_ref(),
+            turn: turn.as_ref(),
+            call_id: call_id.clone(),
+            tool_name: tool_name.clone(),
+        };
+        let out = orchestrator
+            .run(&mut runtime, &req, &tool_ctx, &turn, turn.approval_policy)
+            .await;
+        let event_ctx = ToolEventCtx::new(session.as_ref(), turn.as_ref(), &call_id, None);
+        let content = emitter.finish(event_ctx, out).await?;
+        Ok(ToolOutput::Function {
+            content,
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
@@ -0,0 +1,518 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use async_trait::async_trait;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::SandboxPolicy;
+use codex_protocol::protocol::SubAgentEvent;
+use codex_protocol::protocol::SubAgentSource;
+use codex_protocol::user_input::UserInput;
+use futures::future::join_all;
+use serde::Deserialize;
+use tokio_util::sync::CancellationToken;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::codex_delegate::run_codex_conversation_one_shot;
+use crate::config::Config;
+use crate::features::Feature;
+use crate::function_tool::FunctionCallError;
+use crate::git_info::get_git_repo_root;
+use crate::git_worktree::AgentWorktree;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub(crate) const SPAWN_AGENT_DEFAULT_MAX_TURNS: u32 = 20;
+pub(crate) const SPAWN_AGENT_MAX_TURNS: u32 = 100;
+pub(crate) const SPAWN_AGENTS_MAX_PARALLEL: usize = 8;
+
+static NEXT_AGENT_ID: AtomicU64 = AtomicU64::new(1);
+
+pub struct SpawnAgentHandler;
+
+/// Runs several agents concurrently, each in its own git worktree.
+pub struct SpawnAgentsHandler;
+
+#[derive(Deserialize)]
+struct SpawnAgentArgs {
+    task: String,
+    #[serde(default)]
+    context: Option<String>,
+    #[serde(flatten)]
+    limits: ChildLimits,
+}
+
+#[derive(Deserialize)]
+struct SpawnAgentsArgs {
+    tasks: Vec<String>,
+    #[serde(default)]
+    context: Option<String>,
+    #[serde(flatten)]
+    limits: ChildLimits,
+}
+
+/// Restrictions shared by every child spawned from one tool call.
+#[derive(Deserialize)]
+struct ChildLimits {
+    #[serde(default)]
+    tools: Option<Vec<String>>,
+    #[serde(default)]
+    sandbox: Option<String>,
+    #[serde(default)]
+    max_turns: Option<u32>,
+}
+
+#[async_trait]
+impl ToolHandler for SpawnAgentHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    /// The child can edit the workspace the parent is working in.
+    async fn is_mutating(&self, _invocation: &ToolInvocation) -> bool {
+        true
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            payload,
+            ..
+        } = invocation;
+
+        let args: SpawnAgentArgs = parse_arguments("spawn_agent", payload)?;
+        if args.task.trim().is_empty() {
+            return Err(FunctionCallError::RespondToModel(
+                "spawn_agent requires a non-empty task".to_string(),
+            ));
+        }
+
+        let config = child_config(&turn, &args.limits, turn.cwd.clone())?;
+        let agent_id = next_agent_id();
+        let result = run_child(
+            &session,
+            &turn,
+            &agent_id,
+            config,
+            child_prompt(args.task, args.context.as_deref()),
+        )
+        .await;
+
+        let (content, success) = match result {
+            ChildResult::Completed(Some(summary)) => (summary, true),
+            ChildResult::Completed(None) => {
+                (format!("{agent_id} finished without a final message"), true)
+            }
+            ChildResult::Failed(message) => (format!("{agent_id} {message}"), false),
+        };
+
+        Ok(ToolOutput::Function {
+            content,
+            content_items: None,
+            success: Some(success),
+        })
+    }
+}
+
+#[async_trait]
+impl ToolHandler for SpawnAgentsHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    /// Children work in their own worktrees, but still write to the
+    /// repository's worktree list and `CODEX_HOME`, and run commands.
+    async fn is_mutating(&self, _invocation: &ToolInvocation) -> bool {
+        true
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            payload,
+            ..
+        } = invocation;
+
+        let args: SpawnAgentsArgs = parse_arguments("spawn_agents", payload)?;
+        if args.tasks.is_empty() || args.tasks.iter().any(|task| task.trim().is_empty()) {
+            return Err(FunctionCallError::RespondToModel(
+                "spawn_agents requires a list of non-empty tasks".to_string(),
+            ));
+        }
+        if args.tasks.len() > SPAWN_AGENTS_MAX_PARALLEL {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "spawn_agents accepts at most {SPAWN_AGENTS_MAX_PARALLEL} tasks"
+            )));
+        }
+        let Some(repo_root) = get_git_repo_root(&turn.cwd) else {
+            return Err(FunctionCallError::RespondToModel(
+                "spawn_agents requires the working directory to be inside a git repository"
+                    .to_string(),
+            ));
+        };
+
+        let codex_home = turn.client.config().codex_home.clone();
+        let conversation_id = session.conversation_id().to_string();
+        let worktrees_dir = codex_home.join("worktrees").join(&conversation_id);
+        let patches_dir = codex_home.join("agent-patches").join(&conversation_id);
+
+        // Validate the limits before touching the repository.
+        child_config(&turn, &args.limits, turn.cwd.clone())?;
+
+        let mut agents = Vec::with_capacity(args.tasks.len());
+        for task in &args.tasks {
+            let agent_id = next_agent_id();
+            match AgentWorktree::create(&repo_root, &worktrees_dir.join(&agent_id)).await {
+                Ok(worktree) => {
+                    let config = child_config(&turn, &args.limits, worktree.map_cwd(&turn.cwd))?;
+                    agents.push((agent_id, task.clone(), worktree, config));
+                }
+                Err(err) => {
+                    for (_, _, worktree, _) in agents {
+                        let _ = worktree.remove().await;
+                    }
+                    return Err(FunctionCallError::RespondToModel(format!(
+                        "failed to create a worktree for {agent_id}: {err}"
+                    )));
+                }
+            }
+        }
+
+        let runs = agents
+            .into_iter()
+            .map(|(agent_id, task, worktree, config)| {
+                let session = Arc::clone(&session);
+                let turn = Arc::clone(&turn);
+                let prompt = child_prompt(task.clone(), args.context.as_deref());
+                let patches_dir = patches_dir.clone();
+                async move {
+                    let result = run_child(&session, &turn, &agent_id, config, prompt).await;
+                    let patch = collect_patch(&worktree, &patches_dir, &agent_id).await;
+                    if let Err(err) = worktree.remove().await {
+                        tracing::warn!("failed to remove worktree for {agent_id}: {err}");
+                    }
+                    AgentReport {
+                        agent_id,
+                        task,
+                        result,
+                        patch,
+                    }
+                }
+            });
+        let reports = join_all(runs).await;
+
+        let success = reports
+            .iter()
+            .any(|report| matches!(report.result, ChildResult::Completed(_)));
+        Ok(ToolOutput::Function {
+            content: render_reports(&reports),
+            content_items: None,
+            success: Some(success),
+        })
+    }
+}
+
+enum ChildResult {
+    Completed(Option<String>),
+    Failed(String),
+}
+
+struct AgentReport {
+    agent_id: String,
+    task: String,
+    result: ChildResult,
+    patch: Result<Option<PatchSummary>, String>,
+}
+
+struct PatchSummary {
+    path: PathBuf,
+    files_changed: usize,
+}
+
+fn parse_arguments<T: for<'de> Deserialize<'de>>(
+    tool_name: &str,
+    payload: ToolPayload,
+) -> Result<T, FunctionCallError> {
+    let arguments = match payload {
+        ToolPayload::Function { arguments } => arguments,
+        _ => {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "{tool_name} handler received unsupported payload"
+            )));
+        }
+    };
+    serde_json::from_str(&arguments).map_err(|e| {
+        FunctionCallError::InvalidArguments(format!("failed to parse function arguments: {e:?}"))
+    })
+}
+
+fn next_agent_id() -> String {
+    format!("agent-{}", NEXT_AGENT_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+fn child_prompt(task: String, context: Option<&str>) -> String {
+    match context {
+        Some(context) if !context.trim().is_empty() => format!("{task}\n\nContext:\n{context}"),
+        _ => task,
+    }
+}
+
+fn child_config(
+    turn: &TurnContext,
+    limits: &ChildLimits,
+    cwd: PathBuf,
+) -> Result<Config, FunctionCallError> {
+    let sandbox_policy = child_sandbox_policy(&turn.sandbox_policy, limits.sandbox.as_deref())
+        .map_err(FunctionCallError::RespondToModel)?;
+    let max_turns = limits
+        .max_turns
+        .unwrap_or(SPAWN_AGENT_DEFAULT_MAX_TURNS)
+        .clamp(1, SPAWN_AGENT_MAX_TURNS);
+
+    let mut config = turn.client.config().as_ref().clone();
+    // Children cannot spawn further agents.
+    config.features.disable(Feature::SubAgents);
+    config.cwd = cwd;
+    config.allowed_tools = limits.tools.clone();
+    config.budget.max_turns = Some(max_turns);
+    config
+        .sandbox_policy
+        .set(sandbox_policy)
+        .map_err(|err| FunctionCallError::RespondToModel(err.to_string()))?;
+    Ok(config)
+}
+
+/// Run one child to completion, forwarding its events to the parent tagged
+/// with `agent_id`.
+async fn run_child(
+    session: &Arc<Session>,
+    turn: &Arc<TurnContext>,
+    agent_id: &str,
+    config: Config,
+    prompt: String,
+) -> ChildResult {
+    // Cancel the child if this tool call is dropped (e.g. the turn is interrupted).
+    let cancel_token = CancellationToken::new();
+    let _cancel_guard = cancel_token.clone().drop_guard();
+
+    let io = match run_codex_conversation_one_shot(
+        config,
+        Arc::clone(&session.services.auth_manager),
+        Arc::clone(&session.services.models_manager),
+        vec![UserInput::Text { text: prompt }],
+        Arc::clone(session),
+        Arc::clone(turn),
+        cancel_token,
+        None,
+        SubAgentSource::Other("spawn_agent".to_string()),
+    )
+    .await
+    {
+        Ok(io) => io,
+        Err(err) => return ChildResult::Failed(format!("failed to start: {err}")),
+    };
+
+    let mut result = ChildResult::Failed("was aborted".to_string());
+    let mut budget_message = None;
+    while let Ok(event) = io.next_event().await {
+        match &event.msg {
+            EventMsg::TaskComplete(complete) => {
+                result = ChildResult::Completed(complete.last_agent_message.clone());
+            }
+            EventMsg::BudgetExceeded(exceeded) => {
+                budget_message = Some(exceeded.message.clone());
+            }
+            _ => {}
+        }
+        if is_streaming_delta(&event.msg) {
+            continue;
+        }
+        let done = matches!(
+            event.msg,
+            EventMsg::TaskComplete(_) | EventMsg::TurnAborted(_)
+        );
+        session
+            .send_event(
+                turn.as_ref(),
+                EventMsg::SubAgent(SubAgentEvent {
+                    agent_id: agent_id.to_string(),
+                    msg: Box::new(event.msg),
+                }),
+            )
+            .await;
+        if done {
+            break;
+        }
+    }
+
+    match budget_message {
+        Some(message) => ChildResult::Failed(format!("stopped before finishing: {message}")),
+        None => result,
+    }
+}
+
+/// Save the worktree's changes as `<patches_dir>/<agent_id>.patch`.
+async fn collect_patch(
+    worktree: &AgentWorktree,
+    patches_dir: &Path,
+    agent_id: &str,
+) -> Result<Option<PatchSummary>, String> {
+    let diff = worktree.diff().await.map_err(|err| err.to_string())?;
+    if diff.trim().is_empty() {
+        return Ok(None);
+    }
+    tokio::fs::create_dir_all(patches_dir)
+        .await
+        .map_err(|err| err.to_string())?;
+    let path = patches_dir.join(format!("{agent_id}.patch"));
+    tokio::fs::write(&path, &diff)
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(Some(PatchSummary {
+        path,
+        files_changed: diff
+            .lines()
+            .filter(|line| line.starts_with("diff --git "))
+            .count(),
+    }))
+}
+
+fn render_reports(reports: &[AgentReport]) -> String {
+    let mut out = format!(
+        "Ran {} agent(s) in isolated worktrees. Nothing has been applied to the workspace; \
+         to keep an agent's changes, run `git apply <patch>` from the repository root.\n",
+        reports.len()
+    );
+    for report in reports {
+        let status = match &report.result {
+            ChildResult::Completed(_) => "completed",
+            ChildResult::Failed(_) => "failed",
+        };
+        out.push_str(&format!(
+            "\n## {} ({status})\nTask: {}\n",
+            report.agent_id, report.task
+        ));
+        match &report.result {
+            ChildResult::Completed(Some(summary)) => {
+                out.push_str(&format!("Summary: {summary}\n"));
+            }
+            ChildResult::Completed(None) => out.push_str("Summary: (no final message)\n"),
+            ChildResult::Failed(message) => out.push_str(&format!("Error: {message}\n")),
+        }
+        match &report.patch {
+            Ok(Some(patch)) => out.push_str(&format!(
+                "Changes: {} file(s), patch at {}\n",
+                patch.files_changed,
+                patch.path.display()
+            )),
+            Ok(None) => out.push_str("Changes: none\n"),
+            Err(err) => out.push_str(&format!("Changes: unavailable ({err})\n")),
+        }
+    }
+    out
+}
+
+/// Resolve the child's sandbox. Children may narrow the parent's sandbox but
+/// never widen it.
+fn child_sandbox_policy(
+    parent: &SandboxPolicy,
+    requested: Option<&str>,
+) -> Result<SandboxPolicy, String> {
+    match requested {
+        None => Ok(parent.clone()),
+        Some("read-only") => Ok(SandboxPolicy::new_read_only_policy()),
+        Some("workspace-write") => match parent {
+            // An external sandbox already confines the process; keep it as-is.
+            SandboxPolicy::WorkspaceWrite { .. } | SandboxPolicy::ExternalSandbox { .. } => {
+                Ok(parent.clone())
+            }
+            SandboxPolicy::DangerFullAccess => Ok(SandboxPolicy::new_workspace_write_policy()),
+            SandboxPolicy::ReadOnly => Err(
+                "sandbox \"workspace-write\" is more permissive than this session's read-only sandbox"
+                    .to_string(),
+            ),
+        },
+        Some(other) => Err(format!(
+            "unsupported sandbox {other:?}; expected \"read-only\" or \"workspace-write\""
+        )),
+    }
+}
+
+fn is_streaming_delta(msg: &EventMsg) -> bool {
+    matches!(
+        msg,
+        EventMsg::AgentMessageDelta(_)
+            | EventMsg::AgentReasoningDelta(_)
+            | EventMsg::AgentReasoningRawContentDelta(_)
+            | EventMsg::AgentMessageContentDelta(_)
+            | EventMsg::ReasoningContentDelta(_)
+            | EventMsg::ReasoningRawContentDelta(_)
+            | EventMsg::ExecCommandOutputDelta(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn child_sandbox_never_widens_parent() {
+        let read_only = SandboxPolicy::new_read_only_policy();
+        let workspace = SandboxPolicy::new_workspace_write_policy();
+
+        assert_eq!(
+            child_sandbox_policy(&workspace, None),
+            Ok(workspace.clone())
+        );
+        assert_eq!(
+            child_sandbox_policy(&workspace, Some("read-only")),
+            Ok(read_only.clone())
+        );
+        assert_eq!(
+            child_sandbox_policy(&SandboxPolicy::DangerFullAccess, Some("workspace-write")),
+            Ok(workspace)
+        );
+        assert!(child_sandbox_policy(&read_only, Some("workspace-write")).is_err());
+        assert!(child_sandbox_policy(&read_only, Some("danger-full-access")).is_err());
+    }
+
+    #[test]
+    fn reports_list_each_agent_with_its_patch() {
+        let reports = vec![
+            AgentReport {
+                agent_id: "agent-1".to_string(),
+                task: "fix the parser".to_string(),
+                result: ChildResult::Completed(Some("Fixed it.".to_string())),
+                patch: Ok(Some(PatchSummary {
+                    path: PathBuf::from("/tmp/agent-1.patch"),
+                    files_changed: 2,
+                })),
+            },
+            AgentReport {
+                agent_id: "agent-2".to_string(),
+                task: "try another approach".to_string(),
+                result: ChildResult::Failed("was aborted".to_string()),
+                patch: Ok(None),
+            },
+        ];
+
+        assert_eq!(
+            render_reports(&reports),
+            "Ran 2 agent(s) in isolated worktrees. Nothing has been applied to the workspace; \
+             to keep an agent's changes, run `git apply <patch>` from the repository root.\n\
+             \n## agent-1 (completed)\nTask: fix the parser\nSummary: Fixed it.\n\
+             Changes: 2 file(s), patch at /tmp/agent-1.patch\n\
+             \n## agent-2 (failed)\nTask: try another approach\nError: was aborted\n\
+             Changes: none\n"
+        );
+    }
+}
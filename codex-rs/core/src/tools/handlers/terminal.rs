@@ -0,0 +1,267 @@
+use async_trait::async_trait;
+use codex_protocol::protocol::AskForApproval;
+use serde::Deserialize;
+
+use crate::function_tool::FunctionCallError;
+use crate::sandboxing::SandboxPermissions;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::unified_exec::TerminalScreen;
+use crate::unified_exec::TerminalStartRequest;
+use crate::unified_exec::UnifiedExecContext;
+use crate::unified_exec::encode_key;
+
+/// Handles `terminal_start`, `terminal_send_keys`, `terminal_read_screen`
+/// and `terminal_kill`.
+pub struct TerminalHandler;
+
+#[derive(Debug, Deserialize)]
+struct StartArgs {
+    cmd: String,
+    #[serde(default)]
+    workdir: Option<String>,
+    #[serde(default = "default_start_yield_time_ms")]
+    yield_time_ms: u64,
+    #[serde(default)]
+    sandbox_permissions: SandboxPermissions,
+    #[serde(default)]
+    justification: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SendKeysArgs {
+    id: String,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    keys: Vec<String>,
+    #[serde(default = "default_yield_time_ms")]
+    yield_time_ms: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReadScreenArgs {
+    id: String,
+    #[serde(default = "default_yield_time_ms")]
+    yield_time_ms: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct KillArgs {
+    id: String,
+}
+
+fn default_start_yield_time_ms() -> u64 {
+    1000
+}
+
+fn default_yield_time_ms() -> u64 {
+    250
+}
+
+#[async_trait]
+impl ToolHandler for TerminalHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn is_mutating(&self, invocation: &ToolInvocation) -> bool {
+        invocation.tool_name != "terminal_read_screen"
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            call_id,
+            tool_name,
+            payload,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "{tool_name} handler received unsupported payload"
+                )));
+            }
+        };
+
+        let manager = &session.services.terminals;
+        let screen = match tool_name.as_str() {
+            "terminal_start" => {
+                let args: StartArgs = parse_arguments(&tool_name, &arguments)?;
+                if matches!(turn.approval_policy, AskForApproval::Never) {
+                    return Err(FunctionCallError::Denied(
+                        "terminal_start always needs the user's approval, and the approval policy is never".to_string(),
+                    ));
+                }
+                if args.sandbox_permissions.requires_escalated_permissions()
+                    && !matches!(turn.approval_policy, AskForApproval::OnRequest)
+                {
+                    return Err(FunctionCallError::RespondToModel(format!(
+                        "approval policy is {policy:?}; reject command — you cannot ask for escalated permissions if the approval policy is {policy:?}",
+                        policy = turn.approval_policy
+                    )));
+                }
+
+                let command = session.user_shell().derive_exec_args(&args.cmd, true);
+                let cwd = match args.workdir.filter(|dir| !dir.is_empty()) {
+                    Some(dir) => turn.resolve_path(Some(dir)),
+                    None => turn.cwd.clone(),
+                };
+                let context = UnifiedExecContext::new(session.clone(), turn.clone(), call_id);
+                let screen = manager
+                    .start(
+                        TerminalStartRequest {
+                            command,
+                            cwd,
+                            yield_time_ms: args.yield_time_ms,
+                            sandbox_permissions: args.sandbox_permissions,
+                            justification: args.justification,
+                        },
+                        &context,
+                    )
+                    .await
+                    .map_err(|err| {
+                        FunctionCallError::RespondToModel(format!("terminal_start failed: {err}"))
+                    })?;
+                if screen.running {
+                    session
+                        .notify_background_event(
+                            turn.as_ref(),
+                            format!("Started terminal {}: {}", screen.id, args.cmd),
+                        )
+                        .await;
+                }
+                screen
+            }
+            "terminal_send_keys" => {
+                let args: SendKeysArgs = parse_arguments(&tool_name, &arguments)?;
+                let input = encode_input(args.text.as_deref(), &args.keys)?;
+                manager
+                    .send_keys(&args.id, &input, args.yield_time_ms)
+                    .await
+                    .map_err(|err| {
+                        FunctionCallError::RespondToModel(format!(
+                            "terminal_send_keys failed: {err}"
+                        ))
+                    })?
+            }
+            "terminal_read_screen" => {
+                let args: ReadScreenArgs = parse_arguments(&tool_name, &arguments)?;
+                manager
+                    .read_screen(&args.id, args.yield_time_ms)
+                    .await
+                    .map_err(|err| {
+                        FunctionCallError::RespondToModel(format!(
+                            "terminal_read_screen failed: {err}"
+                        ))
+                    })?
+            }
+            "terminal_kill" => {
+                let args: KillArgs = parse_arguments(&tool_name, &arguments)?;
+                let screen = manager.kill(&args.id).await.map_err(|err| {
+                    FunctionCallError::RespondToModel(format!("terminal_kill failed: {err}"))
+                })?;
+                session
+                    .notify_background_event(turn.as_ref(), format!("Closed terminal {}.", args.id))
+                    .await;
+                screen
+            }
+            other => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "terminal handler does not support tool {other}"
+                )));
+            }
+        };
+
+        Ok(ToolOutput::Function {
+            content: format_screen(&screen),
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
+
+fn parse_arguments<T: for<'de> Deserialize<'de>>(
+    tool_name: &str,
+    arguments: &str,
+) -> Result<T, FunctionCallError> {
+    serde_json::from_str(arguments).map_err(|err| {
+        FunctionCallError::InvalidArguments(format!(
+            "failed to parse {tool_name} arguments: {err:?}"
+        ))
+    })
+}
+
+/// `text` verbatim, followed by each named key in `keys`.
+fn encode_input(text: Option<&str>, keys: &[String]) -> Result<Vec<u8>, FunctionCallError> {
+    let mut input = text.unwrap_or_default().as_bytes().to_vec();
+    for key in keys {
+        let bytes = encode_key(key).ok_or_else(|| {
+            FunctionCallError::InvalidArguments(format!(
+                "unknown key {key:?}; use Enter, Tab, Escape, Backspace, Delete, Up, Down, Left, Right, Home, End, PageUp, PageDown, Space or C-<letter>"
+            ))
+        })?;
+        input.extend(bytes);
+    }
+    Ok(input)
+}
+
+fn format_screen(screen: &TerminalScreen) -> String {
+    let id = &screen.id;
+    let status = match (screen.running, screen.exit_code) {
+        (true, _) => {
+            let (row, col) = screen.cursor;
+            format!("Terminal {id} is running; cursor at row {row}, column {col}")
+        }
+        (false, Some(code)) => format!("Terminal {id} exited with code {code}"),
+        (false, None) => format!("Terminal {id} stopped"),
+    };
+    format!("{status}\nScreen:\n{}", screen.contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn text_is_sent_before_named_keys() {
+        assert_eq!(
+            encode_input(Some("print(1)"), &["Enter".to_string()]).expect("input"),
+            b"print(1)\r".to_vec()
+        );
+        assert!(encode_input(None, &["F13".to_string()]).is_err());
+    }
+
+    #[test]
+    fn format_screen_reports_status() {
+        let running = TerminalScreen {
+            id: "term-1".to_string(),
+            contents: ">>> 1 + 1\n2\n>>>".to_string(),
+            cursor: (2, 4),
+            exit_code: None,
+            running: true,
+        };
+        assert_eq!(
+            format_screen(&running),
+            "Terminal term-1 is running; cursor at row 2, column 4\nScreen:\n>>> 1 + 1\n2\n>>>"
+        );
+
+        let exited = TerminalScreen {
+            exit_code: Some(0),
+            running: false,
+            ..running
+        };
+        assert_eq!(
+            format_screen(&exited),
+            "Terminal term-1 exited with code 0\nScreen:\n>>> 1 + 1\n2\n>>>"
+        );
+    }
+}
@@ -72,7 +72,7 @@ impl ToolHandler for TestSyncHandler {
         };
 
         let args: TestSyncArgs = serde_json::from_str(&arguments).map_err(|err| {
-            FunctionCallError::RespondToModel(format!(
+            FunctionCallError::InvalidArguments(format!(
                 "failed to parse function arguments: {err:?}"
             ))
         })?;
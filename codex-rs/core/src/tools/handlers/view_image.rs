@@ -44,7 +44,9 @@ impl ToolHandler for ViewImageHandler {
         };
 
         let args: ViewImageArgs = serde_json::from_str(&arguments).map_err(|e| {
-            FunctionCallError::RespondToModel(format!("failed to parse function arguments: {e:?}"))
+            FunctionCallError::InvalidArguments(format!(
+                "failed to parse function arguments: {e:?}"
+            ))
         })?;
 
         let abs_path = turn.resolve_path(Some(args.path));
@@ -0,0 +1,211 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use url::Url;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::function_tool::FunctionCallError;
+use crate::permissions::network_permission_for_url;
+use crate::protocol::AskForApproval;
+use crate::protocol::ReviewDecision;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::truncate::formatted_truncate_text;
+use crate::web::search;
+
+/// Handles `fetch_url` and the provider-backed `web_search`.
+pub struct WebHandler;
+
+#[derive(Debug, Deserialize)]
+struct FetchUrlArgs {
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebSearchArgs {
+    query: String,
+    #[serde(default)]
+    max_results: Option<usize>,
+}
+
+#[async_trait]
+impl ToolHandler for WebHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            tool_name,
+            payload,
+            call_id,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "{tool_name} handler received unsupported payload"
+                )));
+            }
+        };
+
+        let config = turn.client.config();
+        let content = match tool_name.as_str() {
+            "fetch_url" => {
+                let args: FetchUrlArgs = parse_arguments(&arguments)?;
+                approve_network_call(&session, &turn, &call_id, &tool_name, &args.url, None)
+                    .await?;
+                // Redirects may only lead to the approved host or to hosts
+                // the session already holds a grant for.
+                let full_network = turn.sandbox_policy.has_full_network_access();
+                let grants = session.services.permissions.read().await.clone();
+                let approved_host = Url::parse(&args.url)
+                    .ok()
+                    .and_then(|url| url.host_str().map(str::to_string));
+                let allow_host = |url: &Url| {
+                    full_network
+                        || url.host_str().is_some_and(|host| {
+                            approved_host.as_deref() == Some(host) || grants.allows_host(host)
+                        })
+                };
+                let page = session
+                    .services
+                    .web_cache
+                    .fetch(&args.url, &config.fetch_url, &allow_host)
+                    .await
+                    .map_err(|err| {
+                        FunctionCallError::RespondToModel(format!(
+                            "failed to fetch {}: {err:#}",
+                            args.url
+                        ))
+                    })?;
+                let mut content = String::new();
+                if let Some(title) = &page.title {
+                    content.push_str(&format!("# {title}\n"));
+                }
+                content.push_str(&format!("URL: {}\n\n{}", page.url, page.content));
+                if page.truncated {
+                    content.push_str(&format!(
+                        "\n\n[Page truncated after {} bytes]",
+                        config.fetch_url.max_bytes
+                    ));
+                }
+                formatted_truncate_text(&content, turn.truncation_policy)
+            }
+            "web_search" => {
+                if !turn.sandbox_policy.has_full_network_access() {
+                    return Err(network_unavailable(&tool_name));
+                }
+                let args: WebSearchArgs = parse_arguments(&arguments)?;
+                let Some(provider) = &config.web_search_provider else {
+                    return Err(FunctionCallError::RespondToModel(
+                        "no web search provider is configured".to_string(),
+                    ));
+                };
+                if args.query.trim().is_empty() {
+                    return Err(FunctionCallError::RespondToModel(
+                        "query must not be empty".to_string(),
+                    ));
+                }
+                let results = search::search(provider, &args.query, args.max_results)
+                    .await
+                    .map_err(|err| {
+                        FunctionCallError::RespondToModel(format!("web search failed: {err:#}"))
+                    })?;
+                formatted_truncate_text(&search::format_results(&results), turn.truncation_policy)
+            }
+            other => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "unsupported web tool: {other}"
+                )));
+            }
+        };
+
+        Ok(ToolOutput::Function {
+            content,
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
+
+/// Clears `tool_name` to reach `url`. Without network access in the
+/// sandbox, the call may still reach hosts the project granted, or that the
+/// user grants when asked. `action`, when set, describes something the call
+/// does on the user's behalf; it is always put to the user, unless the
+/// approval policy is `never`.
+pub(super) async fn approve_network_call(
+    session: &Session,
+    turn: &TurnContext,
+    call_id: &str,
+    tool_name: &str,
+    url: &str,
+    action: Option<String>,
+) -> Result<(), FunctionCallError> {
+    let permission = if turn.sandbox_policy.has_full_network_access() {
+        None
+    } else {
+        Some(network_permission_for_url(url).ok_or_else(|| network_unavailable(tool_name))?)
+    };
+    let granted = match &permission {
+        Some(permission) => session.services.permissions.read().await.allows(permission),
+        None => true,
+    };
+    if granted && action.is_none() {
+        return Ok(());
+    }
+    if turn.approval_policy == AskForApproval::Never {
+        return if granted {
+            Ok(())
+        } else {
+            Err(network_unavailable(tool_name))
+        };
+    }
+    let reason = match (action, &permission) {
+        (Some(action), _) => action,
+        (None, Some(permission)) => format!("{tool_name} needs {permission}"),
+        (None, None) => format!("{tool_name} needs network access"),
+    };
+    let decision = session
+        .request_command_approval(
+            turn,
+            call_id.to_string(),
+            vec![tool_name.to_string(), url.to_string()],
+            turn.cwd.clone(),
+            Some(reason),
+            None,
+            permission.into_iter().filter(|_| !granted).collect(),
+        )
+        .await;
+    match decision {
+        ReviewDecision::Approved
+        | ReviewDecision::ApprovedExecpolicyAmendment { .. }
+        | ReviewDecision::ApprovedPrefixForProject { .. }
+        | ReviewDecision::ApprovedForSession
+        | ReviewDecision::ApprovedPermissions { .. } => Ok(()),
+        ReviewDecision::Denied
+        | ReviewDecision::Abort
+        | ReviewDecision::PartiallyApproved { .. } => Err(FunctionCallError::RespondToModel(
+            format!("the user did not allow {tool_name} to reach {url}"),
+        )),
+    }
+}
+
+fn network_unavailable(tool_name: &str) -> FunctionCallError {
+    FunctionCallError::RespondToModel(format!(
+        "{tool_name} is unavailable: the sandbox policy does not allow network access"
+    ))
+}
+
+fn parse_arguments<'a, T: Deserialize<'a>>(arguments: &'a str) -> Result<T, FunctionCallError> {
+    serde_json::from_str(arguments).map_err(|err| {
+        FunctionCallError::InvalidArguments(format!("failed to parse function arguments: {err:?}"))
+    })
+}
@@ -0,0 +1,240 @@
+//! Notices when the model keeps issuing the same tool call (or alternates
+//! between two calls) without making progress, so the call can be answered
+//! with a nudge instead of being run again.
+
+use std::collections::VecDeque;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::protocol::ToolCallLoopDetectedEvent;
+use crate::tools::context::ToolPayload;
+
+/// Repetitions that count as a loop when `tool_call_loop_limit` is unset.
+pub(crate) const DEFAULT_TOOL_CALL_LOOP_LIMIT: usize = 5;
+
+/// A tool name and its arguments. JSON arguments are compared as values, so
+/// whitespace and key order don't make two calls differ.
+#[derive(Debug, PartialEq)]
+struct CallSignature {
+    tool_name: String,
+    arguments: Value,
+}
+
+impl CallSignature {
+    fn new(tool_name: &str, payload: &ToolPayload) -> Self {
+        let raw = payload.log_payload();
+        let arguments =
+            serde_json::from_str(&raw).unwrap_or_else(|_| Value::String(raw.into_owned()));
+        Self {
+            tool_name: tool_name.to_string(),
+            arguments,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ToolCallLoop {
+    /// One name for a repeated call, two for a ping-pong.
+    pub tools: Vec<String>,
+    /// Length of the run of calls that makes up the loop.
+    pub calls: usize,
+    /// Whether this call is the one that first completed the loop; later
+    /// calls that continue it are reported with `false`.
+    pub newly_detected: bool,
+}
+
+impl ToolCallLoop {
+    /// The event that tells front-ends the model is looping.
+    pub(crate) fn event(&self) -> ToolCallLoopDetectedEvent {
+        let message = match self.tools.as_slice() {
+            [tool] => format!(
+                "The model called `{tool}` {} times in a row with the same arguments.",
+                self.calls
+            ),
+            tools => format!(
+                "The model keeps alternating between `{}`.",
+                tools.join("` and `")
+            ),
+        };
+        ToolCallLoopDetectedEvent {
+            tools: self.tools.clone(),
+            calls: self.calls as u64,
+            message,
+        }
+    }
+
+    /// The tool output returned to the model instead of running the call.
+    pub(crate) fn model_message(&self) -> String {
+        #[derive(Serialize)]
+        struct LoopOutput<'a> {
+            error: &'static str,
+            message: String,
+            tools: &'a [String],
+            calls: usize,
+        }
+
+        let message = match self.tools.as_slice() {
+            [tool] => format!(
+                "You appear to be looping: `{tool}` was called {} times in a row with identical arguments, so this call was not run. Its result will not change; use the earlier output, try a different approach, or ask the user for help.",
+                self.calls
+            ),
+            tools => format!(
+                "You appear to be looping: the last {} tool calls alternated between `{}` with the same arguments each time, so this call was not run. Try a different approach, or ask the user for help.",
+                self.calls,
+                tools.join("` and `")
+            ),
+        };
+        serde_json::to_string(&LoopOutput {
+            error: "tool_call_loop",
+            message: message.clone(),
+            tools: &self.tools,
+            calls: self.calls,
+        })
+        .unwrap_or(message)
+    }
+}
+
+/// Recent tool calls of one task.
+#[derive(Debug, Default)]
+pub(crate) struct ToolCallLoopDetector {
+    recent: VecDeque<CallSignature>,
+    in_loop: bool,
+}
+
+impl ToolCallLoopDetector {
+    /// Records a call and reports whether it repeats the same call `limit`
+    /// times in a row, or completes `limit` round trips between two calls.
+    /// A `limit` of 0 disables detection; 1 is treated as 2, since a single
+    /// call can't repeat anything.
+    pub(crate) fn observe(
+        &mut self,
+        tool_name: &str,
+        payload: &ToolPayload,
+        limit: usize,
+    ) -> Option<ToolCallLoop> {
+        if limit == 0 {
+            return None;
+        }
+        let limit = limit.max(2);
+        let window = limit * 2;
+        self.recent
+            .push_back(CallSignature::new(tool_name, payload));
+        while self.recent.len() > window {
+            self.recent.pop_front();
+        }
+
+        let found = self.find_loop(limit);
+        let newly_detected = found.is_some() && !self.in_loop;
+        self.in_loop = found.is_some();
+        found.map(|(tools, calls)| ToolCallLoop {
+            tools,
+            calls,
+            newly_detected,
+        })
+    }
+
+    fn find_loop(&self, limit: usize) -> Option<(Vec<String>, usize)> {
+        let last = self.recent.back()?;
+        let repeated = self
+            .recent
+            .iter()
+            .rev()
+            .take_while(|call| *call == last)
+            .count();
+        if repeated >= limit {
+            return Some((vec![last.tool_name.clone()], repeated));
+        }
+
+        let other = self.recent.iter().rev().nth(1)?;
+        if other == last || self.recent.len() < limit * 2 {
+            return None;
+        }
+        let alternating = self
+            .recent
+            .iter()
+            .rev()
+            .enumerate()
+            .all(|(i, call)| call == if i % 2 == 0 { last } else { other });
+        alternating.then(|| {
+            (
+                vec![other.tool_name.clone(), last.tool_name.clone()],
+                self.recent.len(),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn call(arguments: &str) -> ToolPayload {
+        ToolPayload::Function {
+            arguments: arguments.to_string(),
+        }
+    }
+
+    #[test]
+    fn repeated_identical_calls_are_a_loop() {
+        let mut detector = ToolCallLoopDetector::default();
+        assert_eq!(
+            detector.observe("shell", &call(r#"{"a":1,"b":2}"#), 3),
+            None
+        );
+        assert_eq!(
+            detector.observe("shell", &call(r#"{ "b": 2, "a": 1 }"#), 3),
+            None
+        );
+        assert_eq!(
+            detector.observe("shell", &call(r#"{"a":1,"b":2}"#), 3),
+            Some(ToolCallLoop {
+                tools: vec!["shell".to_string()],
+                calls: 3,
+                newly_detected: true,
+            })
+        );
+        assert_eq!(
+            detector
+                .observe("shell", &call(r#"{"a":1,"b":2}"#), 3)
+                .map(|found| (found.calls, found.newly_detected)),
+            Some((4, false))
+        );
+
+        // Different arguments break the run.
+        assert_eq!(detector.observe("shell", &call(r#"{"a":2}"#), 3), None);
+    }
+
+    #[test]
+    fn alternating_between_two_calls_is_a_loop() {
+        let mut detector = ToolCallLoopDetector::default();
+        for _ in 0..2 {
+            assert_eq!(
+                detector.observe("read_file", &call(r#"{"p":"x"}"#), 3),
+                None
+            );
+            assert_eq!(detector.observe("shell", &call(r#"{"c":"ls"}"#), 3), None);
+        }
+        assert_eq!(
+            detector.observe("read_file", &call(r#"{"p":"x"}"#), 3),
+            None
+        );
+        assert_eq!(
+            detector.observe("shell", &call(r#"{"c":"ls"}"#), 3),
+            Some(ToolCallLoop {
+                tools: vec!["read_file".to_string(), "shell".to_string()],
+                calls: 6,
+                newly_detected: true,
+            })
+        );
+    }
+
+    #[test]
+    fn a_zero_limit_disables_detection() {
+        let mut detector = ToolCallLoopDetector::default();
+        for _ in 0..10 {
+            assert_eq!(detector.observe("shell", &call("{}"), 0), None);
+        }
+    }
+}
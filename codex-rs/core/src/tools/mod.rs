@@ -1,6 +1,8 @@
 pub mod context;
 pub mod events;
+pub mod extensions;
 pub(crate) mod handlers;
+pub(crate) mod loop_detection;
 pub mod orchestrator;
 pub mod parallel;
 pub mod registry;
@@ -5,10 +5,12 @@ Central place for approvals + sandbox selection + retry semantics. Drives a
 simple sequence for any ToolRuntime: approval → select sandbox → attempt →
 retry without sandbox on denial (no re‑approval thanks to caching).
 */
+use crate::config::types::ToolRuleDecision;
 use crate::error::CodexErr;
 use crate::error::SandboxErr;
 use crate::exec::ExecToolCallOutput;
 use crate::sandboxing::SandboxManager;
+use crate::tool_rules;
 use crate::tools::sandboxing::ApprovalCtx;
 use crate::tools::sandboxing::ExecApprovalRequirement;
 use crate::tools::sandboxing::SandboxAttempt;
@@ -17,6 +19,7 @@ use crate::tools::sandboxing::ToolCtx;
 use crate::tools::sandboxing::ToolError;
 use crate::tools::sandboxing::ToolRuntime;
 use crate::tools::sandboxing::default_exec_approval_requirement;
+use codex_protocol::approvals::ScopedPermission;
 use codex_protocol::protocol::AskForApproval;
 use codex_protocol::protocol::ReviewDecision;
 
@@ -51,9 +54,32 @@ impl ToolOrchestrator {
         // 1) Approval
         let mut already_approved = false;
 
-        let requirement = tool.exec_approval_requirement(req).unwrap_or_else(|| {
+        // Scoped permissions the call needs beyond the sandbox. When the
+        // project already grants all of them, the call runs in a sandbox
+        // widened to allow them, and a prompt that only asked to leave the
+        // sandbox is skipped.
+        let needed = tool.scoped_permissions(req, &turn_ctx.sandbox_policy);
+        let mut widen_network = !needed.is_empty()
+            && tool_ctx
+                .session
+                .services
+                .permissions
+                .read()
+                .await
+                .allows_all(&needed);
+        let mut replaces_escalation = false;
+
+        // A matching `[[tool_rules]]` entry can drop the prompt or force one;
+        // a forced prompt is shown even when grants would cover the call.
+        let mut requirement = tool.exec_approval_requirement(req).unwrap_or_else(|| {
             default_exec_approval_requirement(approval_policy, &turn_ctx.sandbox_policy)
         });
+        if let Some(verdict) = tool_rules::verdict_for(turn_ctx, &tool_ctx.call_id) {
+            requirement = verdict.apply(requirement);
+            if verdict.decision == ToolRuleDecision::Ask {
+                widen_network = false;
+            }
+        }
         match requirement {
             ExecApprovalRequirement::Skip { .. } => {
                 otel.tool_decision(otel_tn, otel_ci, &ReviewDecision::Approved, otel_cfg);
@@ -61,42 +87,69 @@ impl ToolOrchestrator {
             ExecApprovalRequirement::Forbidden { reason } => {
                 return Err(ToolError::Rejected(reason));
             }
+            ExecApprovalRequirement::NeedsApproval { .. }
+                if widen_network && tool.prompts_only_for_sandbox(req) =>
+            {
+                otel.tool_decision(otel_tn, otel_ci, &ReviewDecision::Approved, otel_cfg);
+                replaces_escalation = true;
+            }
             ExecApprovalRequirement::NeedsApproval { reason, .. } => {
                 let approval_ctx = ApprovalCtx {
                     session: tool_ctx.session,
                     turn: turn_ctx,
                     call_id: &tool_ctx.call_id,
                     retry_reason: reason,
+                    proposed_permissions: &needed,
                 };
                 let decision = tool.start_approval_async(req, approval_ctx).await;
 
                 otel.tool_decision(otel_tn, otel_ci, &decision, otel_user.clone());
 
                 match decision {
-                    ReviewDecision::Denied | ReviewDecision::Abort => {
+                    // Hunk-level decisions only apply to patches, which are
+                    // approved before they reach the orchestrator.
+                    ReviewDecision::Denied
+                    | ReviewDecision::Abort
+                    | ReviewDecision::PartiallyApproved { .. } => {
                         return Err(ToolError::Rejected("rejected by user".to_string()));
                     }
                     ReviewDecision::Approved
                     | ReviewDecision::ApprovedExecpolicyAmendment { .. }
-                    | ReviewDecision::ApprovedForSession => {}
+                    | ReviewDecision::ApprovedPrefixForProject { .. }
+                    | ReviewDecision::ApprovedForSession => {
+                        already_approved = true;
+                    }
+                    ReviewDecision::ApprovedPermissions { permissions, .. } => {
+                        widen_network = grants_network(&permissions);
+                        replaces_escalation = true;
+                    }
                 }
-                already_approved = true;
             }
         }
 
-        // 2) First attempt under the selected sandbox.
+        // 2) First attempt under the selected sandbox, widened by the
+        // project's granted permissions.
+        let policy = tool_ctx
+            .session
+            .services
+            .permissions
+            .read()
+            .await
+            .widen(&turn_ctx.sandbox_policy, widen_network);
         let initial_sandbox = match tool.sandbox_mode_for_first_attempt(req) {
-            SandboxOverride::BypassSandboxFirstAttempt => crate::exec::SandboxType::None,
-            SandboxOverride::NoOverride => self
+            SandboxOverride::BypassSandboxFirstAttempt if !replaces_escalation => {
+                crate::exec::SandboxType::None
+            }
+            _ => self
                 .sandbox
-                .select_initial(&turn_ctx.sandbox_policy, tool.sandbox_preference()),
+                .select_initial(&policy, tool.sandbox_preference()),
         };
 
         // Platform-specific flag gating is handled by SandboxManager::select_initial
         // via crate::safety::get_platform_sandbox().
         let initial_attempt = SandboxAttempt {
             sandbox: initial_sandbox,
-            policy: &turn_ctx.sandbox_policy,
+            policy: &policy,
             manager: &self.sandbox,
             sandbox_cwd: &turn_ctx.cwd,
             codex_linux_sandbox_exe: turn_ctx.codex_linux_sandbox_exe.as_ref(),
@@ -121,29 +174,60 @@ impl ToolOrchestrator {
                     })));
                 }
 
-                // Ask for approval before retrying without sandbox.
+                // Ask for approval before retrying without sandbox. Granting
+                // the offered permissions retries in the widened sandbox
+                // instead.
+                let mut retry_network = None;
                 if !tool.should_bypass_approval(approval_policy, already_approved) {
                     let reason_msg = build_denial_reason_from_output(output.as_ref());
+                    let proposed: &[ScopedPermission] = if widen_network { &[] } else { &needed };
                     let approval_ctx = ApprovalCtx {
                         session: tool_ctx.session,
                         turn: turn_ctx,
                         call_id: &tool_ctx.call_id,
                         retry_reason: Some(reason_msg),
+                        proposed_permissions: proposed,
                     };
 
                     let decision = tool.start_approval_async(req, approval_ctx).await;
                     otel.tool_decision(otel_tn, otel_ci, &decision, otel_user);
 
                     match decision {
-                        ReviewDecision::Denied | ReviewDecision::Abort => {
+                        ReviewDecision::Denied
+                        | ReviewDecision::Abort
+                        | ReviewDecision::PartiallyApproved { .. } => {
                             return Err(ToolError::Rejected("rejected by user".to_string()));
                         }
                         ReviewDecision::Approved
                         | ReviewDecision::ApprovedExecpolicyAmendment { .. }
+                        | ReviewDecision::ApprovedPrefixForProject { .. }
                         | ReviewDecision::ApprovedForSession => {}
+                        ReviewDecision::ApprovedPermissions { permissions, .. } => {
+                            retry_network = Some(grants_network(&permissions));
+                        }
                     }
                 }
 
+                if let Some(network) = retry_network {
+                    let widened = tool_ctx
+                        .session
+                        .services
+                        .permissions
+                        .read()
+                        .await
+                        .widen(&turn_ctx.sandbox_policy, network);
+                    let widened_attempt = SandboxAttempt {
+                        sandbox: self
+                            .sandbox
+                            .select_initial(&widened, tool.sandbox_preference()),
+                        policy: &widened,
+                        manager: &self.sandbox,
+                        sandbox_cwd: &turn_ctx.cwd,
+                        codex_linux_sandbox_exe: turn_ctx.codex_linux_sandbox_exe.as_ref(),
+                    };
+                    return (*tool).run(req, &widened_attempt, tool_ctx).await;
+                }
+
                 let escalated_attempt = SandboxAttempt {
                     sandbox: crate::exec::SandboxType::None,
                     policy: &turn_ctx.sandbox_policy,
@@ -160,6 +244,12 @@ impl ToolOrchestrator {
     }
 }
 
+fn grants_network(permissions: &[ScopedPermission]) -> bool {
+    permissions
+        .iter()
+        .any(|permission| matches!(permission, ScopedPermission::Network { .. }))
+}
+
 fn build_denial_reason_from_output(_output: &ExecToolCallOutput) -> String {
     // Keep approval reason terse and stable for UX/tests, but accept the
     // output so we can evolve heuristics later without touching call sites.
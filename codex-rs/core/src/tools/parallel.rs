@@ -59,6 +59,8 @@ impl ToolCallRuntime {
         let tracker = Arc::clone(&self.tracker);
         let lock = Arc::clone(&self.parallel_execution);
         let started = Instant::now();
+        let call_id = call.call_id.clone();
+        let payload_outputs_custom = matches!(call.payload, ToolPayload::Custom { .. });
 
         let dispatch_span = trace_span!(
             "dispatch_tool_call",
@@ -96,6 +98,11 @@ impl ToolCallRuntime {
                 Ok(Ok(response)) => Ok(response),
                 Ok(Err(FunctionCallError::Fatal(message))) => Err(CodexErr::Fatal(message)),
                 Ok(Err(other)) => Err(CodexErr::Fatal(other.to_string())),
+                Err(err) if err.is_panic() => Ok(ToolRouter::failure_response(
+                    call_id,
+                    payload_outputs_custom,
+                    FunctionCallError::HandlerPanic(format!("tool handler panicked: {err}")),
+                )),
                 Err(err) => Err(CodexErr::Fatal(format!(
                     "tool task failed to receive: {err:?}"
                 ))),
@@ -1,5 +1,10 @@
+use std::any::Any;
 use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
 use std::time::Duration;
 
 use crate::client_common::tools::ToolSpec;
@@ -10,6 +15,10 @@ use crate::tools::context::ToolPayload;
 use async_trait::async_trait;
 use codex_protocol::models::ResponseInputItem;
 use codex_utils_readiness::Readiness;
+use futures::FutureExt;
+use tokio_util::task::TaskTracker;
+use tokio_util::task::task_tracker::TaskTrackerToken;
+use tracing::error;
 use tracing::warn;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -66,22 +75,27 @@ impl ToolRegistry {
         let call_id_owned = invocation.call_id.clone();
         let otel = invocation.turn.client.get_otel_manager();
         let payload_for_response = invocation.payload.clone();
-        let log_payload = payload_for_response.log_payload();
+        let log_payload = payload_for_response.telemetry_payload(
+            tool_name.as_ref(),
+            &invocation.turn.client.config().otel.tool_payloads,
+        );
 
         let handler = match self.handler(tool_name.as_ref()) {
             Some(handler) => handler,
             None => {
                 let message =
                     unsupported_tool_call_message(&invocation.payload, tool_name.as_ref());
+                let err = FunctionCallError::RespondToModel(message);
                 otel.tool_result(
                     tool_name.as_ref(),
                     &call_id_owned,
                     log_payload.as_ref(),
                     Duration::ZERO,
                     false,
-                    &message,
+                    Some(err.kind()),
+                    &err.to_string(),
                 );
-                return Err(FunctionCallError::RespondToModel(message));
+                return Err(err);
             }
         };
 
@@ -93,6 +107,7 @@ impl ToolRegistry {
                 log_payload.as_ref(),
                 Duration::ZERO,
                 false,
+                Some("fatal"),
                 &message,
             );
             return Err(FunctionCallError::Fatal(message));
@@ -109,13 +124,60 @@ impl ToolRegistry {
                     let handler = handler.clone();
                     let output_cell = &output_cell;
                     let invocation = invocation;
+                    let tool_name = tool_name.clone();
+                    let call_id = call_id_owned.clone();
                     async move {
-                        if handler.is_mutating(&invocation).await {
+                        // Owned, so the in-flight guard does not keep
+                        // `invocation` borrowed once it moves into the handler.
+                        let session = Arc::clone(&invocation.session);
+                        let tool_calls = &session.services.mutating_tool_calls;
+                        if tool_calls.is_closed() {
+                            return Err(FunctionCallError::RespondToModel(
+                                "Codex is shutting down; the tool call was not run.".to_string(),
+                            ));
+                        }
+                        let mutating = handler.is_mutating(&invocation).await;
+                        // Checked against the live session as well, so
+                        // switching to read-only mode mid-turn takes effect
+                        // for the turn's remaining calls.
+                        if mutating
+                            && (invocation.turn.tools_config.read_only
+                                || invocation.session.read_only_mode().await)
+                        {
+                            return Err(FunctionCallError::RespondToModel(format!(
+                                "{tool_name} is disabled: the session is in read-only mode. \
+                                 Describe the change instead of making it."
+                            )));
+                        }
+                        // Held until the handler returns, so shutdown can let
+                        // writes in progress finish.
+                        let _in_flight = if mutating {
+                            let Some(in_flight) = tool_calls.start(&call_id) else {
+                                return Err(FunctionCallError::RespondToModel(
+                                    "Codex is shutting down; the tool call was not run."
+                                        .to_string(),
+                                ));
+                            };
                             tracing::trace!("waiting for tool gate");
                             invocation.turn.tool_call_gate.wait_ready().await;
                             tracing::trace!("tool gate released");
-                        }
-                        match handler.handle(invocation).await {
+                            Some(in_flight)
+                        } else {
+                            None
+                        };
+                        // A panicking handler fails only its own call; the
+                        // session task and the other calls in the turn carry on.
+                        let handled = AssertUnwindSafe(handler.handle(invocation))
+                            .catch_unwind()
+                            .await
+                            .unwrap_or_else(|panic| {
+                                let message = panic_message(panic.as_ref());
+                                error!("tool {tool_name} panicked: {message}");
+                                Err(FunctionCallError::HandlerPanic(format!(
+                                    "tool {tool_name} panicked: {message}"
+                                )))
+                            });
+                        match handled {
                             Ok(output) => {
                                 let preview = output.log_preview();
                                 let success = output.success_for_logging();
@@ -143,6 +205,80 @@ impl ToolRegistry {
     }
 }
 
+/// Mutating tool calls in flight, which shutdown lets finish. A call does not
+/// count while it waits for approval, so shutdown never waits on a prompt.
+#[derive(Default)]
+pub(crate) struct MutatingToolCalls {
+    tracker: TaskTracker,
+    /// The token of each call in flight, by call id. The lock also orders
+    /// `start` against `close`, so no call starts once shutdown has begun.
+    tokens: Mutex<HashMap<String, TaskTrackerToken>>,
+}
+
+impl MutatingToolCalls {
+    /// Counts `call_id` as in flight until the returned guard drops, or
+    /// returns `None` once shutdown has begun.
+    pub(crate) fn start<'a>(&'a self, call_id: &'a str) -> Option<InFlightToolCall<'a>> {
+        self.resume(call_id).then_some(InFlightToolCall {
+            calls: self,
+            call_id,
+        })
+    }
+
+    /// Stops counting `call_id`, for while it waits for approval. Returns
+    /// whether it was in flight.
+    pub(crate) fn pause(&self, call_id: &str) -> bool {
+        self.lock().remove(call_id).is_some()
+    }
+
+    /// Counts `call_id` again after [`Self::pause`]. Returns `false`, and
+    /// leaves it uncounted, once shutdown has begun.
+    pub(crate) fn resume(&self, call_id: &str) -> bool {
+        let mut tokens = self.lock();
+        if self.tracker.is_closed() {
+            return false;
+        }
+        tokens.insert(call_id.to_string(), self.tracker.token());
+        true
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.tracker.is_closed()
+    }
+
+    /// Refuses new calls from now on.
+    pub(crate) fn close(&self) {
+        let _tokens = self.lock();
+        self.tracker.close();
+    }
+
+    /// Resolves once every call in flight has finished; call after `close`.
+    pub(crate) async fn wait(&self) {
+        self.tracker.wait().await;
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, TaskTrackerToken>> {
+        self.tokens.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Keeps a call counted by [`MutatingToolCalls`] until dropped, including
+/// when the call is cancelled.
+pub(crate) struct InFlightToolCall<'a> {
+    calls: &'a MutatingToolCalls,
+    call_id: &'a str,
+}
+
+impl Drop for InFlightToolCall<'_> {
+    fn drop(&mut self) {
+        self.calls.pause(self.call_id);
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ConfiguredToolSpec {
     pub spec: ToolSpec,
@@ -195,6 +331,12 @@ impl ToolRegistryBuilder {
         }
     }
 
+    /// Drop every spec and handler whose tool name does not satisfy `keep`.
+    pub fn retain(&mut self, mut keep: impl FnMut(&str) -> bool) {
+        self.specs.retain(|configured| keep(configured.spec.name()));
+        self.handlers.retain(|name, _| keep(name));
+    }
+
     // TODO(jif) for dynamic tools.
     // pub fn register_many<I>(&mut self, names: I, handler: Arc<dyn ToolHandler>)
     // where
@@ -225,3 +367,53 @@ fn unsupported_tool_call_message(payload: &ToolPayload, tool_name: &str) -> Stri
         _ => format!("unsupported call: {tool_name}"),
     }
 }
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        (*message).to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn panics_are_caught_with_their_message() {
+        let panic = AssertUnwindSafe(async {
+            panic!("adapter bug: {}", 42);
+        })
+        .catch_unwind()
+        .await
+        .expect_err("future panics");
+        assert_eq!(panic_message(panic.as_ref()), "adapter bug: 42");
+
+        let panic = std::panic::catch_unwind(|| {
+            std::panic::panic_any(7_u8);
+        })
+        .expect_err("panics");
+        assert_eq!(panic_message(panic.as_ref()), "unknown panic");
+    }
+
+    #[tokio::test]
+    async fn calls_awaiting_approval_do_not_hold_up_shutdown() {
+        let calls = MutatingToolCalls::default();
+        let writing = calls.start("call-1").expect("not shut down");
+        let _approving = calls.start("call-2").expect("not shut down");
+        assert!(calls.pause("call-2"));
+        assert_eq!(calls.len(), 1);
+
+        calls.close();
+        assert!(calls.start("call-3").is_none());
+        assert!(!calls.resume("call-2"));
+        drop(writing);
+        tokio::time::timeout(Duration::from_secs(1), calls.wait())
+            .await
+            .expect("nothing left in flight");
+    }
+}
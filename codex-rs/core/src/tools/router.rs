@@ -1,8 +1,16 @@
 use crate::client_common::tools::ToolSpec;
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::config::types::ToolRuleDecision;
 use crate::function_tool::FunctionCallError;
+use crate::project_tasks;
+use crate::protocol::AskForApproval;
 use crate::sandboxing::SandboxPermissions;
+use crate::tool_policy::PolicyInput;
+use crate::tool_rules;
+use crate::tool_rules::RuleSubject;
+use crate::tool_rules::RuleVerdict;
+use crate::tool_rules::ToolRuleVerdicts;
 use crate::tools::context::SharedTurnDiffTracker;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolPayload;
@@ -141,33 +149,100 @@ impl ToolRouter {
         } = call;
         let payload_outputs_custom = matches!(payload, ToolPayload::Custom { .. });
         let failure_call_id = call_id.clone();
+        let hook_tool_name = tool_name.clone();
+        let hook_input = payload.log_payload().into_owned();
+        let hook_command = shell_command_line(&payload);
+
+        // With only the project's tasks to run, editing a task definition
+        // would amount to running arbitrary commands.
+        if turn.tools_config.tasks_only
+            && let Some(path) = tool_rules::written_paths(&payload, &turn.cwd)
+                .into_iter()
+                .find(|path| project_tasks::is_task_file(path, &turn.cwd))
+        {
+            return Ok(Self::failure_response(
+                call_id,
+                payload_outputs_custom,
+                FunctionCallError::RespondToModel(format!(
+                    "tasks_only is set, so {tool_name} cannot change {}, which defines the project's tasks",
+                    path.display()
+                )),
+            ));
+        }
+
+        if let Some(verdict) = rule_verdict(
+            &session,
+            &turn,
+            &call_id,
+            &tool_name,
+            &hook_input,
+            hook_command.as_deref(),
+            &payload,
+        )
+        .await
+        {
+            let refusal = match verdict.decision {
+                ToolRuleDecision::Deny => Some(verdict.denial_message(&tool_name)),
+                ToolRuleDecision::Ask if turn.approval_policy == AskForApproval::Never => {
+                    Some(format!(
+                        "{}; approval_policy is never, so {tool_name} was not run.",
+                        verdict.prompt_reason()
+                    ))
+                }
+                ToolRuleDecision::Allow | ToolRuleDecision::Ask => None,
+            };
+            if let Some(message) = refusal {
+                session
+                    .notify_background_event(
+                        &turn,
+                        format!("Rule {} blocked {tool_name}", verdict.rule),
+                    )
+                    .await;
+                return Ok(Self::failure_response(
+                    call_id,
+                    payload_outputs_custom,
+                    FunctionCallError::RespondToModel(message),
+                ));
+            }
+            turn.extensions.update::<ToolRuleVerdicts>(|verdicts| {
+                verdicts.0.insert(call_id.clone(), verdict);
+            });
+        }
 
         let invocation = ToolInvocation {
-            session,
-            turn,
+            session: Arc::clone(&session),
+            turn: Arc::clone(&turn),
             tracker,
             call_id,
             tool_name,
             payload,
         };
 
-        match self.registry.dispatch(invocation).await {
-            Ok(response) => Ok(response),
-            Err(FunctionCallError::Fatal(message)) => Err(FunctionCallError::Fatal(message)),
-            Err(err) => Ok(Self::failure_response(
-                failure_call_id,
-                payload_outputs_custom,
-                err,
-            )),
-        }
+        let mut response = match self.registry.dispatch(invocation).await {
+            Ok(response) => response,
+            Err(FunctionCallError::Fatal(message)) => {
+                return Err(FunctionCallError::Fatal(message));
+            }
+            Err(err) => Self::failure_response(failure_call_id, payload_outputs_custom, err),
+        };
+        session
+            .apply_after_tool_use_hooks(
+                &turn,
+                &hook_tool_name,
+                &hook_input,
+                hook_command,
+                &mut response,
+            )
+            .await;
+        Ok(response)
     }
 
-    fn failure_response(
+    pub(crate) fn failure_response(
         call_id: String,
         payload_outputs_custom: bool,
         err: FunctionCallError,
     ) -> ResponseInputItem {
-        let message = err.to_string();
+        let message = err.model_message();
         if payload_outputs_custom {
             ResponseInputItem::CustomToolCallOutput {
                 call_id,
@@ -185,3 +260,69 @@ impl ToolRouter {
         }
     }
 }
+
+/// The verdict for a call from `[[tool_rules]]` and the `[tool_policy]`
+/// engine, the stricter one winning.
+async fn rule_verdict(
+    session: &Session,
+    turn: &TurnContext,
+    call_id: &str,
+    tool_name: &str,
+    arguments: &str,
+    command: Option<&str>,
+    payload: &ToolPayload,
+) -> Option<RuleVerdict> {
+    let rules = &session.services.tool_rules;
+    let policy = session
+        .services
+        .tool_policy
+        .as_ref()
+        .filter(|policy| policy.applies_to(tool_name));
+    if rules.is_empty() && policy.is_none() {
+        return None;
+    }
+    let paths = tool_rules::written_paths(payload, &turn.cwd);
+    let subject = RuleSubject {
+        tool_name,
+        command,
+        arguments,
+        paths: &paths,
+        cwd: &turn.cwd,
+        sandbox_policy: &turn.sandbox_policy,
+    };
+    let verdict = rules.evaluate(&subject);
+    let Some(policy) = policy else {
+        return verdict;
+    };
+    let policy_verdict = policy
+        .evaluate(&PolicyInput {
+            thread_id: session.conversation_id().to_string(),
+            turn_id: &turn.sub_id,
+            call_id,
+            call: &subject,
+        })
+        .await;
+    tool_rules::strictest(verdict, policy_verdict)
+}
+
+/// The command line of a shell tool call, for hook placeholders.
+fn shell_command_line(payload: &ToolPayload) -> Option<String> {
+    match payload {
+        ToolPayload::LocalShell { params } => Some(params.command.join(" ")),
+        ToolPayload::Function { arguments } => {
+            let arguments: serde_json::Value = serde_json::from_str(arguments).ok()?;
+            match arguments.get("command")? {
+                serde_json::Value::String(command) => Some(command.clone()),
+                serde_json::Value::Array(parts) => Some(
+                    parts
+                        .iter()
+                        .filter_map(serde_json::Value::as_str)
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                ),
+                _ => None,
+            }
+        }
+        ToolPayload::Custom { .. } | ToolPayload::Mcp { .. } => None,
+    }
+}
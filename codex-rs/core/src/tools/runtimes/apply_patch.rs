@@ -105,6 +105,7 @@ impl Approvable<ApplyPatchRequest> for ApplyPatchRuntime {
         let session = ctx.session;
         let turn = ctx.turn;
         let call_id = ctx.call_id.to_string();
+        let proposed_permissions = ctx.proposed_permissions.to_vec();
         let cwd = req.cwd.clone();
         let retry_reason = ctx.retry_reason.clone();
         let user_explicitly_approved = req.user_explicitly_approved;
@@ -119,6 +120,7 @@ impl Approvable<ApplyPatchRequest> for ApplyPatchRuntime {
                             cwd,
                             Some(reason),
                             None,
+                            proposed_permissions,
                         )
                         .await
                 } else if user_explicitly_approved {
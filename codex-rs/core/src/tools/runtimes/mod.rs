@@ -13,7 +13,9 @@ use std::collections::HashMap;
 use std::path::Path;
 
 pub mod apply_patch;
+pub mod python;
 pub mod shell;
+pub mod shell_session;
 pub mod unified_exec;
 
 /// Shared helper to construct a CommandSpec from a tokenized command line.
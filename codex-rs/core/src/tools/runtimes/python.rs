@@ -0,0 +1,151 @@
+/*
+Runtime: python
+
+Runs a cell in the session's persistent Python kernel under the orchestrator.
+Each cell is approved like a `python3 -c` command; the kernel it runs in is
+started under the sandbox selected for the first attempt and restarted when
+that sandbox changes, so file and network access follow the sandbox policy.
+*/
+use crate::error::CodexErr;
+use crate::exec::ExecExpiration;
+use crate::python_kernel::PythonOutcome;
+use crate::sandboxing::SandboxPermissions;
+use crate::tools::runtimes::build_command_spec;
+use crate::tools::sandboxing::Approvable;
+use crate::tools::sandboxing::ApprovalCtx;
+use crate::tools::sandboxing::ExecApprovalRequirement;
+use crate::tools::sandboxing::SandboxAttempt;
+use crate::tools::sandboxing::Sandboxable;
+use crate::tools::sandboxing::SandboxablePreference;
+use crate::tools::sandboxing::ToolCtx;
+use crate::tools::sandboxing::ToolError;
+use crate::tools::sandboxing::ToolRuntime;
+use crate::tools::sandboxing::with_cached_approval;
+use codex_protocol::protocol::ReviewDecision;
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+pub struct PythonRequest {
+    /// Source of the cell, sent to the kernel as-is.
+    pub code: String,
+    /// Start a fresh kernel before running the cell.
+    pub reset: bool,
+    /// `code` as a standalone `python3 -c` invocation, used for approvals.
+    pub command: Vec<String>,
+    pub cwd: PathBuf,
+    /// Command that starts the kernel when none is running.
+    pub kernel_command: Vec<String>,
+    pub timeout_ms: u64,
+    pub env: HashMap<String, String>,
+    pub justification: Option<String>,
+    pub exec_approval_requirement: ExecApprovalRequirement,
+}
+
+#[derive(Default)]
+pub struct PythonRuntime;
+
+#[derive(serde::Serialize, Clone, Debug, Eq, PartialEq, Hash)]
+pub(crate) struct ApprovalKey {
+    command: Vec<String>,
+    cwd: PathBuf,
+}
+
+impl Sandboxable for PythonRuntime {
+    fn sandbox_preference(&self) -> SandboxablePreference {
+        SandboxablePreference::Auto
+    }
+
+    // The kernel outlives the call, so it is never restarted unsandboxed
+    // behind the user's back.
+    fn escalate_on_failure(&self) -> bool {
+        false
+    }
+}
+
+impl Approvable<PythonRequest> for PythonRuntime {
+    type ApprovalKey = ApprovalKey;
+
+    fn approval_key(&self, req: &PythonRequest) -> Self::ApprovalKey {
+        ApprovalKey {
+            command: req.command.clone(),
+            cwd: req.cwd.clone(),
+        }
+    }
+
+    fn start_approval_async<'a>(
+        &'a mut self,
+        req: &'a PythonRequest,
+        ctx: ApprovalCtx<'a>,
+    ) -> BoxFuture<'a, ReviewDecision> {
+        let key = self.approval_key(req);
+        let command = req.command.clone();
+        let cwd = req.cwd.clone();
+        let reason = ctx
+            .retry_reason
+            .clone()
+            .or_else(|| req.justification.clone());
+        let session = ctx.session;
+        let turn = ctx.turn;
+        let call_id = ctx.call_id.to_string();
+        let proposed_permissions = ctx.proposed_permissions.to_vec();
+        Box::pin(async move {
+            with_cached_approval(&session.services, key, move || async move {
+                session
+                    .request_command_approval(
+                        turn,
+                        call_id,
+                        command,
+                        cwd,
+                        reason,
+                        req.exec_approval_requirement
+                            .proposed_execpolicy_amendment()
+                            .cloned(),
+                        proposed_permissions,
+                    )
+                    .await
+            })
+            .await
+        })
+    }
+
+    fn exec_approval_requirement(&self, req: &PythonRequest) -> Option<ExecApprovalRequirement> {
+        Some(req.exec_approval_requirement.clone())
+    }
+}
+
+impl ToolRuntime<PythonRequest, PythonOutcome> for PythonRuntime {
+    async fn run(
+        &mut self,
+        req: &PythonRequest,
+        attempt: &SandboxAttempt<'_>,
+        ctx: &ToolCtx<'_>,
+    ) -> Result<PythonOutcome, ToolError> {
+        let spec = build_command_spec(
+            &req.kernel_command,
+            &req.cwd,
+            &req.env,
+            ExecExpiration::DefaultTimeout,
+            SandboxPermissions::UseDefault,
+            None,
+        )?;
+        let env = attempt
+            .env_for(spec)
+            .map_err(|err| ToolError::Codex(err.into()))?;
+
+        ctx.session
+            .services
+            .python_kernel
+            .run(
+                &req.code,
+                req.reset,
+                env,
+                attempt.policy,
+                Duration::from_millis(req.timeout_ms),
+            )
+            .await
+            .map_err(|err| ToolError::Codex(CodexErr::Io(err)))
+    }
+}
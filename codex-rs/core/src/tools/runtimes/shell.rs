@@ -5,6 +5,8 @@ Executes shell requests under the orchestrator: asks for approval when needed,
 builds a CommandSpec, and runs it under the current SandboxAttempt.
 */
 use crate::exec::ExecToolCallOutput;
+use crate::exec::execute_exec_env_in_pty;
+use crate::permissions::network_permissions_for_command;
 use crate::sandboxing::SandboxPermissions;
 use crate::sandboxing::execute_env;
 use crate::tools::runtimes::build_command_spec;
@@ -20,7 +22,10 @@ use crate::tools::sandboxing::ToolCtx;
 use crate::tools::sandboxing::ToolError;
 use crate::tools::sandboxing::ToolRuntime;
 use crate::tools::sandboxing::with_cached_approval;
+use codex_protocol::approvals::ScopedPermission;
 use codex_protocol::protocol::ReviewDecision;
+use codex_protocol::protocol::SandboxPolicy;
+use codex_utils_pty::TerminalSize;
 use futures::future::BoxFuture;
 use std::path::PathBuf;
 
@@ -33,6 +38,10 @@ pub struct ShellRequest {
     pub sandbox_permissions: SandboxPermissions,
     pub justification: Option<String>,
     pub exec_approval_requirement: ExecApprovalRequirement,
+    /// The requirement prompts only because escalation was requested.
+    pub prompts_only_for_escalation: bool,
+    /// Run attached to a pseudo-terminal of this size instead of pipes.
+    pub pty: Option<TerminalSize>,
 }
 
 #[derive(Default)]
@@ -94,6 +103,7 @@ impl Approvable<ShellRequest> for ShellRuntime {
         let session = ctx.session;
         let turn = ctx.turn;
         let call_id = ctx.call_id.to_string();
+        let proposed_permissions = ctx.proposed_permissions.to_vec();
         Box::pin(async move {
             with_cached_approval(&session.services, key, move || async move {
                 session
@@ -106,6 +116,7 @@ impl Approvable<ShellRequest> for ShellRuntime {
                         req.exec_approval_requirement
                             .proposed_execpolicy_amendment()
                             .cloned(),
+                        proposed_permissions,
                     )
                     .await
             })
@@ -113,6 +124,18 @@ impl Approvable<ShellRequest> for ShellRuntime {
         })
     }
 
+    fn scoped_permissions(
+        &self,
+        req: &ShellRequest,
+        policy: &SandboxPolicy,
+    ) -> Vec<ScopedPermission> {
+        network_permissions_for_command(&req.command, policy)
+    }
+
+    fn prompts_only_for_sandbox(&self, req: &ShellRequest) -> bool {
+        req.prompts_only_for_escalation
+    }
+
     fn exec_approval_requirement(&self, req: &ShellRequest) -> Option<ExecApprovalRequirement> {
         Some(req.exec_approval_requirement.clone())
     }
@@ -156,9 +179,13 @@ impl ToolRuntime<ShellRequest, ExecToolCallOutput> for ShellRuntime {
         let env = attempt
             .env_for(spec)
             .map_err(|err| ToolError::Codex(err.into()))?;
-        let out = execute_env(env, attempt.policy, Self::stdout_stream(ctx))
-            .await
-            .map_err(ToolError::Codex)?;
+        let out = match req.pty {
+            Some(size) => {
+                execute_exec_env_in_pty(env, attempt.policy, size, Self::stdout_stream(ctx)).await
+            }
+            None => execute_env(env, attempt.policy, Self::stdout_stream(ctx)).await,
+        }
+        .map_err(ToolError::Codex)?;
         Ok(out)
     }
 }
@@ -0,0 +1,206 @@
+/*
+Runtime: shell_session
+
+Runs a command in the session's persistent shell under the orchestrator. The
+command is approved on its own like any shell command; the shell it runs in is
+started under the sandbox selected for the first attempt and restarted when
+that sandbox changes.
+*/
+use crate::error::CodexErr;
+use crate::error::SandboxErr;
+use crate::exec::ExecExpiration;
+use crate::exec::ExecToolCallOutput;
+use crate::exec::StreamOutput;
+use crate::exec::is_likely_sandbox_denied;
+use crate::permissions::network_permissions_for_command;
+use crate::sandboxing::SandboxPermissions;
+use crate::tools::runtimes::build_command_spec;
+use crate::tools::sandboxing::Approvable;
+use crate::tools::sandboxing::ApprovalCtx;
+use crate::tools::sandboxing::ExecApprovalRequirement;
+use crate::tools::sandboxing::SandboxAttempt;
+use crate::tools::sandboxing::Sandboxable;
+use crate::tools::sandboxing::SandboxablePreference;
+use crate::tools::sandboxing::ToolCtx;
+use crate::tools::sandboxing::ToolError;
+use crate::tools::sandboxing::ToolRuntime;
+use crate::tools::sandboxing::with_cached_approval;
+use codex_protocol::approvals::ScopedPermission;
+use codex_protocol::protocol::ReviewDecision;
+use codex_protocol::protocol::SandboxPolicy;
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Exit code reported for commands that ran past their timeout.
+const TIMEOUT_EXIT_CODE: i32 = 124;
+
+#[derive(Clone, Debug)]
+pub struct ShellSessionRequest {
+    /// Script written to the shell as-is.
+    pub script: String,
+    /// `script` as a standalone shell invocation, used for approvals and events.
+    pub command: Vec<String>,
+    /// Working directory the shell is currently in.
+    pub cwd: PathBuf,
+    /// Command that starts the shell when none is running.
+    pub shell_command: Vec<String>,
+    pub timeout_ms: u64,
+    pub env: HashMap<String, String>,
+    pub justification: Option<String>,
+    pub exec_approval_requirement: ExecApprovalRequirement,
+}
+
+#[derive(Default)]
+pub struct ShellSessionRuntime;
+
+#[derive(serde::Serialize, Clone, Debug, Eq, PartialEq, Hash)]
+pub(crate) struct ApprovalKey {
+    command: Vec<String>,
+    cwd: PathBuf,
+}
+
+impl Sandboxable for ShellSessionRuntime {
+    fn sandbox_preference(&self) -> SandboxablePreference {
+        SandboxablePreference::Auto
+    }
+
+    // The shell outlives the call, so it is never restarted unsandboxed
+    // behind the user's back.
+    fn escalate_on_failure(&self) -> bool {
+        false
+    }
+}
+
+impl Approvable<ShellSessionRequest> for ShellSessionRuntime {
+    type ApprovalKey = ApprovalKey;
+
+    fn approval_key(&self, req: &ShellSessionRequest) -> Self::ApprovalKey {
+        ApprovalKey {
+            command: req.command.clone(),
+            cwd: req.cwd.clone(),
+        }
+    }
+
+    fn start_approval_async<'a>(
+        &'a mut self,
+        req: &'a ShellSessionRequest,
+        ctx: ApprovalCtx<'a>,
+    ) -> BoxFuture<'a, ReviewDecision> {
+        let key = self.approval_key(req);
+        let command = req.command.clone();
+        let cwd = req.cwd.clone();
+        let reason = ctx
+            .retry_reason
+            .clone()
+            .or_else(|| req.justification.clone());
+        let session = ctx.session;
+        let turn = ctx.turn;
+        let call_id = ctx.call_id.to_string();
+        let proposed_permissions = ctx.proposed_permissions.to_vec();
+        Box::pin(async move {
+            with_cached_approval(&session.services, key, move || async move {
+                session
+                    .request_command_approval(
+                        turn,
+                        call_id,
+                        command,
+                        cwd,
+                        reason,
+                        req.exec_approval_requirement
+                            .proposed_execpolicy_amendment()
+                            .cloned(),
+                        proposed_permissions,
+                    )
+                    .await
+            })
+            .await
+        })
+    }
+
+    fn scoped_permissions(
+        &self,
+        req: &ShellSessionRequest,
+        policy: &SandboxPolicy,
+    ) -> Vec<ScopedPermission> {
+        network_permissions_for_command(&req.command, policy)
+    }
+
+    fn exec_approval_requirement(
+        &self,
+        req: &ShellSessionRequest,
+    ) -> Option<ExecApprovalRequirement> {
+        Some(req.exec_approval_requirement.clone())
+    }
+}
+
+impl ToolRuntime<ShellSessionRequest, ExecToolCallOutput> for ShellSessionRuntime {
+    async fn run(
+        &mut self,
+        req: &ShellSessionRequest,
+        attempt: &SandboxAttempt<'_>,
+        ctx: &ToolCtx<'_>,
+    ) -> Result<ExecToolCallOutput, ToolError> {
+        let spec = build_command_spec(
+            &req.shell_command,
+            &ctx.turn.cwd,
+            &req.env,
+            ExecExpiration::DefaultTimeout,
+            SandboxPermissions::UseDefault,
+            None,
+        )?;
+        let env = attempt
+            .env_for(spec)
+            .map_err(|err| ToolError::Codex(err.into()))?;
+
+        let start = Instant::now();
+        let outcome = ctx
+            .session
+            .services
+            .shell_session
+            .run(
+                &req.script,
+                env,
+                attempt.policy,
+                Duration::from_millis(req.timeout_ms),
+            )
+            .await
+            .map_err(|err| ToolError::Codex(CodexErr::Io(err)))?;
+        let duration = start.elapsed();
+
+        let mut text = outcome.output;
+        if let Some(note) = outcome.note {
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push_str(&format!("[{note}]"));
+        }
+        let exit_code = match outcome.exit_code {
+            Some(code) => code,
+            None if outcome.timed_out => TIMEOUT_EXIT_CODE,
+            None => -1,
+        };
+        let output = ExecToolCallOutput {
+            exit_code,
+            stdout: StreamOutput::new(text.clone()),
+            stderr: StreamOutput::new(String::new()),
+            aggregated_output: StreamOutput::new(text),
+            duration,
+            timed_out: outcome.timed_out,
+        };
+
+        if outcome.timed_out {
+            return Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Timeout {
+                output: Box::new(output),
+            })));
+        }
+        if is_likely_sandbox_denied(attempt.sandbox, &output) {
+            return Err(ToolError::Codex(CodexErr::Sandbox(SandboxErr::Denied {
+                output: Box::new(output),
+            })));
+        }
+        Ok(output)
+    }
+}
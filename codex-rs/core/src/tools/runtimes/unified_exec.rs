@@ -7,6 +7,7 @@ the session manager to spawn PTYs once an ExecEnv is prepared.
 use crate::error::CodexErr;
 use crate::error::SandboxErr;
 use crate::exec::ExecExpiration;
+use crate::permissions::network_permissions_for_command;
 use crate::sandboxing::SandboxPermissions;
 use crate::tools::runtimes::build_command_spec;
 use crate::tools::runtimes::maybe_wrap_shell_lc_with_snapshot;
@@ -24,7 +25,9 @@ use crate::tools::sandboxing::with_cached_approval;
 use crate::unified_exec::UnifiedExecError;
 use crate::unified_exec::UnifiedExecSession;
 use crate::unified_exec::UnifiedExecSessionManager;
+use codex_protocol::approvals::ScopedPermission;
 use codex_protocol::protocol::ReviewDecision;
+use codex_protocol::protocol::SandboxPolicy;
 use futures::future::BoxFuture;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -37,6 +40,8 @@ pub struct UnifiedExecRequest {
     pub sandbox_permissions: SandboxPermissions,
     pub justification: Option<String>,
     pub exec_approval_requirement: ExecApprovalRequirement,
+    /// The requirement prompts only because escalation was requested.
+    pub prompts_only_for_escalation: bool,
 }
 
 #[derive(serde::Serialize, Clone, Debug, Eq, PartialEq, Hash)]
@@ -58,6 +63,7 @@ impl UnifiedExecRequest {
         sandbox_permissions: SandboxPermissions,
         justification: Option<String>,
         exec_approval_requirement: ExecApprovalRequirement,
+        prompts_only_for_escalation: bool,
     ) -> Self {
         Self {
             command,
@@ -66,6 +72,7 @@ impl UnifiedExecRequest {
             sandbox_permissions,
             justification,
             exec_approval_requirement,
+            prompts_only_for_escalation,
         }
     }
 }
@@ -106,6 +113,7 @@ impl Approvable<UnifiedExecRequest> for UnifiedExecRuntime<'_> {
         let session = ctx.session;
         let turn = ctx.turn;
         let call_id = ctx.call_id.to_string();
+        let proposed_permissions = ctx.proposed_permissions.to_vec();
         let command = req.command.clone();
         let cwd = req.cwd.clone();
         let reason = ctx
@@ -124,6 +132,7 @@ impl Approvable<UnifiedExecRequest> for UnifiedExecRuntime<'_> {
                         req.exec_approval_requirement
                             .proposed_execpolicy_amendment()
                             .cloned(),
+                        proposed_permissions,
                     )
                     .await
             })
@@ -131,6 +140,18 @@ impl Approvable<UnifiedExecRequest> for UnifiedExecRuntime<'_> {
         })
     }
 
+    fn scoped_permissions(
+        &self,
+        req: &UnifiedExecRequest,
+        policy: &SandboxPolicy,
+    ) -> Vec<ScopedPermission> {
+        network_permissions_for_command(&req.command, policy)
+    }
+
+    fn prompts_only_for_sandbox(&self, req: &UnifiedExecRequest) -> bool {
+        req.prompts_only_for_escalation
+    }
+
     fn exec_approval_requirement(
         &self,
         req: &UnifiedExecRequest,
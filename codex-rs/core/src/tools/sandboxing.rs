@@ -13,6 +13,7 @@ use crate::sandboxing::SandboxManager;
 use crate::sandboxing::SandboxTransformError;
 use crate::state::SessionServices;
 use codex_protocol::approvals::ExecPolicyAmendment;
+use codex_protocol::approvals::ScopedPermission;
 use codex_protocol::protocol::AskForApproval;
 use codex_protocol::protocol::ReviewDecision;
 use std::collections::HashMap;
@@ -82,6 +83,8 @@ pub(crate) struct ApprovalCtx<'a> {
     pub turn: &'a TurnContext,
     pub call_id: &'a str,
     pub retry_reason: Option<String>,
+    /// Scoped permissions the prompt offers to grant instead of escalating.
+    pub proposed_permissions: &'a [ScopedPermission],
 }
 
 // Specifies what tool orchestrator should do with a given tool call.
@@ -184,6 +187,20 @@ pub(crate) trait Approvable<Req> {
         None
     }
 
+    /// Scoped permissions this call needs beyond `policy`, e.g. network
+    /// access to the hosts a command names. When all of them are granted the
+    /// call runs in a sandbox widened to allow them instead of asking.
+    fn scoped_permissions(&self, _req: &Req, _policy: &SandboxPolicy) -> Vec<ScopedPermission> {
+        Vec::new()
+    }
+
+    /// Whether a `NeedsApproval` requirement is there only because the call
+    /// asked to leave the sandbox. Only such prompts are skipped when the
+    /// call's scoped permissions are all granted.
+    fn prompts_only_for_sandbox(&self, _req: &Req) -> bool {
+        false
+    }
+
     /// Decide we can request an approval for no-sandbox execution.
     fn wants_no_sandbox_approval(&self, policy: AskForApproval) -> bool {
         !matches!(policy, AskForApproval::Never | AskForApproval::OnRequest)
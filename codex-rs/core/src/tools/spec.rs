@@ -3,7 +3,13 @@ use crate::client_common::tools::ToolSpec;
 use crate::features::Feature;
 use crate::features::Features;
 use crate::models_manager::model_family::ModelFamily;
+use crate::project_tasks;
+use crate::project_tasks::ProjectTask;
+use crate::tools::handlers::MEMORY_SEARCH_DEFAULT_LIMIT;
 use crate::tools::handlers::PLAN_TOOL;
+use crate::tools::handlers::SPAWN_AGENT_DEFAULT_MAX_TURNS;
+use crate::tools::handlers::SPAWN_AGENT_MAX_TURNS;
+use crate::tools::handlers::SPAWN_AGENTS_MAX_PARALLEL;
 use crate::tools::handlers::apply_patch::create_apply_patch_freeform_tool;
 use crate::tools::handlers::apply_patch::create_apply_patch_json_tool;
 use crate::tools::registry::ToolRegistryBuilder;
@@ -23,6 +29,34 @@ pub(crate) struct ToolsConfig {
     pub web_search_request: bool,
     pub include_view_image_tool: bool,
     pub experimental_supported_tools: Vec<String>,
+    pub spawn_agent: bool,
+    pub memory_tools: bool,
+    pub background_shell: bool,
+    pub shell_session: bool,
+    pub terminal: bool,
+    pub python_kernel: bool,
+    pub run_tests: bool,
+    pub run_task: bool,
+    /// Tasks `run_task` lists; filled from the turn's working directory.
+    pub project_tasks: Vec<ProjectTask>,
+    /// Drops every shell tool, leaving `run_task` as the way to run commands.
+    pub tasks_only: bool,
+    /// Read-only "ask" mode: drops every tool that can only change the
+    /// workspace, and MCP tools not annotated as read-only.
+    pub read_only: bool,
+    pub file_edit_tools: bool,
+    pub code_search: bool,
+    pub repo_map: bool,
+    pub git_tools: bool,
+    pub pull_requests: bool,
+    pub web_tools: bool,
+    /// Whether `[web_search_provider]` is configured. With `web_tools`, the
+    /// `web_search` function tool replaces the hosted search.
+    pub web_search_provider: bool,
+    /// Requires `[embeddings]`; the session clears it when that is missing.
+    pub semantic_search: bool,
+    /// When set, only these tools are exposed. `"shell"` covers every shell variant.
+    pub allowed_tools: Option<Vec<String>>,
 }
 
 pub(crate) struct ToolsConfigParams<'a> {
@@ -39,6 +73,20 @@ impl ToolsConfig {
         let include_apply_patch_tool = features.enabled(Feature::ApplyPatchFreeform);
         let include_web_search_request = features.enabled(Feature::WebSearchRequest);
         let include_view_image_tool = features.enabled(Feature::ViewImageTool);
+        let include_spawn_agent = features.enabled(Feature::SubAgents);
+        let include_memory_tools = features.enabled(Feature::Memory);
+        let include_background_shell =
+            features.enabled(Feature::ShellTool) && features.enabled(Feature::BackgroundShell);
+        let include_shell_session =
+            features.enabled(Feature::ShellTool) && features.enabled(Feature::ShellSession);
+        let include_terminal =
+            features.enabled(Feature::ShellTool) && features.enabled(Feature::Terminal);
+        let include_python_kernel =
+            features.enabled(Feature::ShellTool) && features.enabled(Feature::PythonKernel);
+        let include_run_tests =
+            features.enabled(Feature::ShellTool) && features.enabled(Feature::RunTests);
+        let include_run_task =
+            features.enabled(Feature::ShellTool) && features.enabled(Feature::RunTask);
 
         let shell_type = if !features.enabled(Feature::ShellTool) {
             ConfigShellToolType::Disabled
@@ -71,6 +119,26 @@ impl ToolsConfig {
             web_search_request: include_web_search_request,
             include_view_image_tool,
             experimental_supported_tools: model_family.experimental_supported_tools.clone(),
+            spawn_agent: include_spawn_agent,
+            memory_tools: include_memory_tools,
+            background_shell: include_background_shell,
+            shell_session: include_shell_session,
+            terminal: include_terminal,
+            python_kernel: include_python_kernel,
+            run_tests: include_run_tests,
+            run_task: include_run_task,
+            project_tasks: Vec::new(),
+            tasks_only: false,
+            read_only: false,
+            file_edit_tools: features.enabled(Feature::FileEditTools),
+            code_search: features.enabled(Feature::CodeSearch),
+            repo_map: features.enabled(Feature::RepoMap),
+            git_tools: features.enabled(Feature::GitTools),
+            pull_requests: features.enabled(Feature::PullRequests),
+            web_tools: features.enabled(Feature::WebTools),
+            web_search_provider: false,
+            semantic_search: features.enabled(Feature::SemanticSearch),
+            allowed_tools: None,
         }
     }
 }
@@ -416,59 +484,1148 @@ fn create_view_image_tool() -> ToolSpec {
     })
 }
 
-fn create_test_sync_tool() -> ToolSpec {
+/// Properties shared by `spawn_agent` and `spawn_agents` that restrict what a
+/// child agent may do.
+fn child_agent_limit_properties() -> BTreeMap<String, JsonSchema> {
     let mut properties = BTreeMap::new();
     properties.insert(
-        "sleep_before_ms".to_string(),
+        "tools".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String { description: None }),
+            description: Some(
+                "Optional allowlist of tool names for the child (e.g. [\"shell\", \"apply_patch\"]). Defaults to the parent's tools."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "sandbox".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Optional sandbox for the child: \"read-only\" or \"workspace-write\". Cannot be more permissive than the parent's."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "max_turns".to_string(),
         JsonSchema::Number {
-            description: Some("Optional delay in milliseconds before any other action".to_string()),
+            description: Some(format!(
+                "Maximum number of model requests the child may make (default {SPAWN_AGENT_DEFAULT_MAX_TURNS}, at most {SPAWN_AGENT_MAX_TURNS})."
+            )),
         },
     );
+    properties
+}
+
+fn create_spawn_agent_tool() -> ToolSpec {
+    let mut properties = child_agent_limit_properties();
     properties.insert(
-        "sleep_after_ms".to_string(),
+        "task".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Self-contained description of the task the child agent should complete."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "context".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Optional background the child needs; it does not see this conversation."
+                    .to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "spawn_agent".to_string(),
+        description: "Delegate a scoped task to a child agent with its own context, tools, and sandbox. Blocks until the child finishes and returns its final summary."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["task".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_spawn_agents_tool() -> ToolSpec {
+    let mut properties = child_agent_limit_properties();
+    properties.insert(
+        "tasks".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String { description: None }),
+            description: Some(format!(
+                "One self-contained task per agent (at most {SPAWN_AGENTS_MAX_PARALLEL}). Give several agents the same task to compare alternative solutions."
+            )),
+        },
+    );
+    properties.insert(
+        "context".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Optional background shared by every agent; they do not see this conversation."
+                    .to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "spawn_agents".to_string(),
+        description: "Run several child agents concurrently, each in its own temporary git worktree of this repository checked out at HEAD (uncommitted changes are not included). Blocks until all finish and returns each agent's summary plus a patch file with its changes; nothing is applied to the workspace until you apply a patch."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["tasks".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_memory_write_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "text".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "The note to remember. Keep it short and self-contained.".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "tags".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String { description: None }),
+            description: Some("Optional keywords that help find the note later.".to_string()),
+        },
+    );
+    properties.insert(
+        "ttl_days".to_string(),
+        JsonSchema::Number {
+            description: Some("Optional number of days after which the note expires.".to_string()),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "memory_write".to_string(),
+        description: "Save a durable note about this project (build quirks, conventions, user preferences) that future sessions will see."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["text".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_memory_search_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "query".to_string(),
+        JsonSchema::String {
+            description: Some("Keywords to match against saved notes and their tags.".to_string()),
+        },
+    );
+    properties.insert(
+        "limit".to_string(),
         JsonSchema::Number {
+            description: Some(format!(
+                "Maximum number of notes to return (default {MEMORY_SEARCH_DEFAULT_LIMIT})."
+            )),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "memory_search".to_string(),
+        description: "Search notes saved in earlier sessions on this project.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["query".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_shell_bg_start_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "cmd".to_string(),
+        JsonSchema::String {
+            description: Some("Shell command to start in the background.".to_string()),
+        },
+    );
+    properties.insert(
+        "workdir".to_string(),
+        JsonSchema::String {
             description: Some(
-                "Optional delay in milliseconds after completing the barrier".to_string(),
+                "Optional working directory to run the command in; defaults to the turn cwd."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "yield_time_ms".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "How long to wait (in milliseconds) for startup output before returning."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "max_output_tokens".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "Maximum number of tokens to return. Excess output will be truncated.".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "sandbox_permissions".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Sandbox permissions for the command. Set to \"require_escalated\" to request running without sandbox restrictions; defaults to \"use_default\"."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "justification".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Only set if sandbox_permissions is \"require_escalated\". 1-sentence explanation of why we want to run this command."
+                    .to_string(),
             ),
         },
     );
 
-    let mut barrier_properties = BTreeMap::new();
-    barrier_properties.insert(
+    ToolSpec::Function(ResponsesApiTool {
+        name: "shell_bg_start".to_string(),
+        description: "Starts a long-running command (dev server, file watcher, slow build) in the background and returns its id. The process keeps running across turns until killed or the session ends; read its output with shell_bg_tail.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["cmd".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_shell_bg_tail_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
         "id".to_string(),
         JsonSchema::String {
+            description: Some("Id returned by shell_bg_start.".to_string()),
+        },
+    );
+    properties.insert(
+        "yield_time_ms".to_string(),
+        JsonSchema::Number {
             description: Some(
-                "Identifier shared by concurrent calls that should rendezvous".to_string(),
+                "How long to wait (in milliseconds) for new output before returning.".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "max_output_tokens".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "Maximum number of tokens to return. Excess output will be truncated.".to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "shell_bg_tail".to_string(),
+        description: "Returns output a background process produced since the last call, and whether it is still running.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["id".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_shell_bg_kill_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "id".to_string(),
+        JsonSchema::String {
+            description: Some("Id returned by shell_bg_start.".to_string()),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "shell_bg_kill".to_string(),
+        description: "Stops a background process and returns its remaining output.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["id".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_terminal_start_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "cmd".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Interactive program to start, e.g. `python3`, `gdb ./a.out` or `psql`."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "workdir".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Optional working directory to run the program in; defaults to the turn cwd."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "yield_time_ms".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "How long to wait (in milliseconds) for the program to draw its first screen."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "sandbox_permissions".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Sandbox permissions for the program. Set to \"require_escalated\" to request running without sandbox restrictions; defaults to \"use_default\"."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "justification".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "1-sentence explanation of why you need an interactive terminal for this program; shown to the user when asking for approval."
+                    .to_string(),
             ),
         },
     );
-    barrier_properties.insert(
-        "participants".to_string(),
-        JsonSchema::Number {
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "terminal_start".to_string(),
+        description: "Starts an interactive program (REPL, debugger, TUI) in an 80x24 terminal and returns its id and rendered screen. Always asks the user for approval. Drive it with terminal_send_keys and terminal_read_screen; close it with terminal_kill. Prefer one-shot shell commands when the program does not need input.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["cmd".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_terminal_send_keys_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "id".to_string(),
+        JsonSchema::String {
+            description: Some("Id returned by terminal_start.".to_string()),
+        },
+    );
+    properties.insert(
+        "text".to_string(),
+        JsonSchema::String {
+            description: Some("Text to type verbatim.".to_string()),
+        },
+    );
+    properties.insert(
+        "keys".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String { description: None }),
+            description: Some(
+                "Keys to press after the text: Enter, Tab, Escape, Backspace, Delete, Up, Down, Left, Right, Home, End, PageUp, PageDown, Space, or C-<letter> for control keys such as C-c."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "yield_time_ms".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "How long to wait (in milliseconds) for the program to react before returning the screen."
+                    .to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "terminal_send_keys".to_string(),
+        description: "Types text and key presses into a terminal started with terminal_start, then returns the rendered screen.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["id".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_terminal_read_screen_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "id".to_string(),
+        JsonSchema::String {
+            description: Some("Id returned by terminal_start.".to_string()),
+        },
+    );
+    properties.insert(
+        "yield_time_ms".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "How long to wait (in milliseconds) for further output before returning the screen."
+                    .to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "terminal_read_screen".to_string(),
+        description: "Returns the rendered screen of a terminal, its cursor position, and whether the program is still running.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["id".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_terminal_kill_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "id".to_string(),
+        JsonSchema::String {
+            description: Some("Id returned by terminal_start.".to_string()),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "terminal_kill".to_string(),
+        description: "Stops the program in a terminal and returns its last screen.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["id".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_run_tests_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "runner".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "One of cargo, pytest, jest or go. Detected from the project files when omitted."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "tests".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String { description: None }),
+            description: Some(
+                "Tests to run instead of the whole suite: exact libtest names for cargo, node ids for pytest, full test names for jest, top-level test names for go."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "rerun_failed".to_string(),
+        JsonSchema::Boolean {
+            description: Some(
+                "Run only the tests that failed in the previous run_tests call, with the same runner, directory and args; other arguments except timeout_ms are ignored."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "args".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String { description: None }),
+            description: Some(
+                "Extra arguments for the runner, e.g. [\"-p\", \"my-crate\"] for cargo or [\"./pkg/...\"] for go."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "workdir".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Directory to run the tests from. Defaults to the current working directory."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "timeout_ms".to_string(),
+        JsonSchema::Number {
+            description: Some("Timeout in milliseconds. Defaults to 10 minutes.".to_string()),
+        },
+    );
+    properties.insert(
+        "justification".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "1-sentence explanation of why the tests need to run; shown to the user if approval is needed."
+                    .to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "run_tests".to_string(),
+        description: "Runs the project's tests with cargo test, pytest, jest or go test and returns the passed, failed and skipped tests, with the message of each failure, instead of the raw log. Use rerun_failed to re-run just the failures after a fix. Runs under the same sandbox and approval rules as shell commands.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_run_task_tool(tasks: &[ProjectTask]) -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "task".to_string(),
+        JsonSchema::String {
+            description: Some("Id of the task to run, e.g. `make:build`.".to_string()),
+        },
+    );
+    properties.insert(
+        "args".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String { description: None }),
+            description: Some("Extra arguments appended to the task's command.".to_string()),
+        },
+    );
+    properties.insert(
+        "timeout_ms".to_string(),
+        JsonSchema::Number {
+            description: Some("Timeout in milliseconds. Defaults to 10 minutes.".to_string()),
+        },
+    );
+    properties.insert(
+        "justification".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "1-sentence explanation of why the task needs to run; shown to the user if approval is needed."
+                    .to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "run_task".to_string(),
+        description: format!(
+            "Runs one of the tasks this project defines in its Makefile, justfile, package.json scripts or cargo aliases, and returns its output. Runs under the same sandbox and approval rules as shell commands. Available tasks:\n{}",
+            project_tasks::describe(tasks)
+        ),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["task".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_python_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "code".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Python code to run. The value of a final expression is returned, like a notebook cell."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "reset".to_string(),
+        JsonSchema::Boolean {
+            description: Some(
+                "Start a fresh interpreter before running the code, discarding all variables."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "timeout_ms".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "Timeout in milliseconds. On timeout the interpreter is killed and its variables are lost."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "justification".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "1-sentence explanation of what the code does; shown to the user if approval is needed."
+                    .to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "python".to_string(),
+        description: "Runs Python code in a persistent interpreter: variables, imports and loaded data stay available across calls. Returns printed output, the value of the last expression (data frames as Markdown tables), tracebacks, and any open matplotlib figures as images. Runs under the same sandbox as shell commands.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["code".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_shell_session_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "cmd".to_string(),
+        JsonSchema::String {
+            description: Some("Shell command to run in the session shell.".to_string()),
+        },
+    );
+    properties.insert(
+        "timeout_ms".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "The timeout for the command in milliseconds. A command that times out kills the session shell."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "justification".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Optional 1-sentence explanation of why the command is needed, shown if it requires approval."
+                    .to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "shell_session".to_string(),
+        description: "Runs a command in a shell that persists for the whole session: the working directory, exported variables, activated virtualenvs and shell functions carry over to the next call. Stdin is not connected; use it for setup-dependent workflows rather than interactive programs.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["cmd".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_test_sync_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "sleep_before_ms".to_string(),
+        JsonSchema::Number {
+            description: Some("Optional delay in milliseconds before any other action".to_string()),
+        },
+    );
+    properties.insert(
+        "sleep_after_ms".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "Optional delay in milliseconds after completing the barrier".to_string(),
+            ),
+        },
+    );
+
+    let mut barrier_properties = BTreeMap::new();
+    barrier_properties.insert(
+        "id".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Identifier shared by concurrent calls that should rendezvous".to_string(),
+            ),
+        },
+    );
+    barrier_properties.insert(
+        "participants".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "Number of tool calls that must arrive before the barrier opens".to_string(),
+            ),
+        },
+    );
+    barrier_properties.insert(
+        "timeout_ms".to_string(),
+        JsonSchema::Number {
+            description: Some("Maximum time in milliseconds to wait at the barrier".to_string()),
+        },
+    );
+
+    properties.insert(
+        "barrier".to_string(),
+        JsonSchema::Object {
+            properties: barrier_properties,
+            required: Some(vec!["id".to_string(), "participants".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "test_sync_tool".to_string(),
+        description: "Internal synchronization helper used by Codex integration tests.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_grep_files_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "pattern".to_string(),
+        JsonSchema::String {
+            description: Some("Regular expression pattern to search for.".to_string()),
+        },
+    );
+    properties.insert(
+        "include".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Optional glob that limits which files are searched (e.g. \"*.rs\" or \
+                 \"*.{ts,tsx}\")."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "path".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Directory or file path to search. Defaults to the session's working directory."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "limit".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "Maximum number of file paths to return (defaults to 100).".to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "grep_files".to_string(),
+        description: "Finds files whose contents match the pattern and lists them by modification \
+                      time."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["pattern".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_read_file_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "file_path".to_string(),
+        JsonSchema::String {
+            description: Some("Absolute path to the file".to_string()),
+        },
+    );
+    properties.insert(
+        "offset".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "The line number to start reading from. Must be 1 or greater.".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "limit".to_string(),
+        JsonSchema::Number {
+            description: Some("The maximum number of lines to return.".to_string()),
+        },
+    );
+    properties.insert(
+        "mode".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Optional mode selector: \"slice\" for simple ranges (default) or \"indentation\" \
+                 to expand around an anchor line."
+                    .to_string(),
+            ),
+        },
+    );
+
+    let mut indentation_properties = BTreeMap::new();
+    indentation_properties.insert(
+        "anchor_line".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "Anchor line to center the indentation lookup on (defaults to offset).".to_string(),
+            ),
+        },
+    );
+    indentation_properties.insert(
+        "max_levels".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "How many parent indentation levels (smaller indents) to include.".to_string(),
+            ),
+        },
+    );
+    indentation_properties.insert(
+        "include_siblings".to_string(),
+        JsonSchema::Boolean {
+            description: Some(
+                "When true, include additional blocks that share the anchor indentation."
+                    .to_string(),
+            ),
+        },
+    );
+    indentation_properties.insert(
+        "include_header".to_string(),
+        JsonSchema::Boolean {
+            description: Some(
+                "Include doc comments or attributes directly above the selected block.".to_string(),
+            ),
+        },
+    );
+    indentation_properties.insert(
+        "max_lines".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "Hard cap on the number of lines returned when using indentation mode.".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "indentation".to_string(),
+        JsonSchema::Object {
+            properties: indentation_properties,
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "read_file".to_string(),
+        description:
+            "Reads a local file with 1-indexed line numbers, supporting slice and indentation-aware block modes."
+                .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["file_path".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_code_search_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "pattern".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Regular expression (Rust regex syntax) matched against each line".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "path".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Directory or file to search. Defaults to the working directory.".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "globs".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String { description: None }),
+            description: Some(
+                "Glob filters such as \"*.rs\" or \"src/**\"; prefix with ! to exclude".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "fixed_strings".to_string(),
+        JsonSchema::Boolean {
+            description: Some("Treat pattern as a literal string".to_string()),
+        },
+    );
+    properties.insert(
+        "case_sensitive".to_string(),
+        JsonSchema::Boolean {
+            description: Some(
+                "Defaults to case-insensitive unless pattern contains an uppercase letter"
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "context_lines".to_string(),
+        JsonSchema::Number {
+            description: Some("Lines of context around each match (default 2, max 10)".to_string()),
+        },
+    );
+    properties.insert(
+        "max_output_tokens".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "Approximate size of the result; lower-ranked files are listed by name only once it is reached (default 4000)"
+                    .to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "code_search".to_string(),
+        description: "Searches file contents in the workspace, respecting .gitignore. Returns matching lines with context, files with declarations and more matches first. Prefer this over running grep or rg in the shell.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["pattern".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_repo_map_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "path".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Only list files under this directory, relative to the repository root".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "max_tokens".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "Approximate size of the map; deeper files lose their symbols, then are omitted, once it is reached"
+                    .to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "repo_map".to_string(),
+        description: "Scans the repository and returns its files with sizes and top-level symbols (functions, types, classes). Call it to orient yourself in an unfamiliar repository, after large changes, or with `path` to see a part of the repository left out of the map.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_git_status_tool() -> ToolSpec {
+    ToolSpec::Function(ResponsesApiTool {
+        name: "git_status".to_string(),
+        description: "Returns the git status of the working directory as JSON: current branch, HEAD commit, upstream with ahead/behind counts, and staged, unstaged, untracked and conflicted files.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties: BTreeMap::new(),
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_git_diff_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "staged".to_string(),
+        JsonSchema::Boolean {
+            description: Some(
+                "Diff the staged changes instead of the unstaged ones (default false)".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "paths".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String { description: None }),
+            description: Some("Limit the diff to these paths".to_string()),
+        },
+    );
+    properties.insert(
+        "context_lines".to_string(),
+        JsonSchema::Number {
+            description: Some("Lines of context around each change (default 3)".to_string()),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "git_diff".to_string(),
+        description: "Returns the working tree diff as JSON: per-file addition and deletion counts (null for binary files) and the unified patch.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_git_commit_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "message".to_string(),
+        JsonSchema::String {
+            description: Some("Commit message".to_string()),
+        },
+    );
+    properties.insert(
+        "paths".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String { description: None }),
+            description: Some(
+                "Files to stage before committing. Omit to commit what is already staged."
+                    .to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "git_commit".to_string(),
+        description: "Stages the given paths and commits the staged changes on the current branch. Refuses to commit on protected branches such as main; create a branch first. Returns the new commit as JSON.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["message".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_git_create_branch_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "name".to_string(),
+        JsonSchema::String {
+            description: Some("Name of the new branch".to_string()),
+        },
+    );
+    properties.insert(
+        "start_point".to_string(),
+        JsonSchema::String {
+            description: Some("Commit or branch to start from (default HEAD)".to_string()),
+        },
+    );
+    properties.insert(
+        "checkout".to_string(),
+        JsonSchema::Boolean {
+            description: Some("Switch to the new branch (default true)".to_string()),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "git_create_branch".to_string(),
+        description: "Creates a new git branch and by default switches to it. Never overwrites an existing branch.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["name".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_pr_create_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "title".to_string(),
+        JsonSchema::String {
+            description: Some("Pull request title".to_string()),
+        },
+    );
+    properties.insert(
+        "body".to_string(),
+        JsonSchema::String {
+            description: Some("Pull request description in Markdown".to_string()),
+        },
+    );
+    properties.insert(
+        "base".to_string(),
+        JsonSchema::String {
             description: Some(
-                "Number of tool calls that must arrive before the barrier opens".to_string(),
+                "Branch to merge into. Defaults to the repository's default branch.".to_string(),
             ),
         },
     );
-    barrier_properties.insert(
-        "timeout_ms".to_string(),
-        JsonSchema::Number {
-            description: Some("Maximum time in milliseconds to wait at the barrier".to_string()),
+    properties.insert(
+        "draft".to_string(),
+        JsonSchema::Boolean {
+            description: Some("Open as a draft (default false)".to_string()),
         },
     );
 
-    properties.insert(
-        "barrier".to_string(),
-        JsonSchema::Object {
-            properties: barrier_properties,
-            required: Some(vec!["id".to_string(), "participants".to_string()]),
+    ToolSpec::Function(ResponsesApiTool {
+        name: "pr_create".to_string(),
+        description: "Opens a GitHub pull request or GitLab merge request from the current branch, which must already be pushed. Returns its number and URL.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["title".to_string()]),
             additional_properties: Some(false.into()),
         },
+    })
+}
+
+fn create_pr_comments_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "number".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "Pull request number. Defaults to the open pull request for the current branch."
+                    .to_string(),
+            ),
+        },
     );
 
     ToolSpec::Function(ResponsesApiTool {
-        name: "test_sync_tool".to_string(),
-        description: "Internal synchronization helper used by Codex integration tests.".to_string(),
+        name: "pr_comments".to_string(),
+        description: "Reads a pull request's comments and review threads (file, line and replies) as JSON. Use the thread ids with pr_reply.".to_string(),
         strict: false,
         parameters: JsonSchema::Object {
             properties,
@@ -478,149 +1635,209 @@ fn create_test_sync_tool() -> ToolSpec {
     })
 }
 
-fn create_grep_files_tool() -> ToolSpec {
+fn create_pr_reply_tool() -> ToolSpec {
     let mut properties = BTreeMap::new();
     properties.insert(
-        "pattern".to_string(),
+        "body".to_string(),
         JsonSchema::String {
-            description: Some("Regular expression pattern to search for.".to_string()),
+            description: Some("Reply in Markdown".to_string()),
         },
     );
     properties.insert(
-        "include".to_string(),
-        JsonSchema::String {
+        "number".to_string(),
+        JsonSchema::Number {
             description: Some(
-                "Optional glob that limits which files are searched (e.g. \"*.rs\" or \
-                 \"*.{ts,tsx}\")."
+                "Pull request number. Defaults to the open pull request for the current branch."
                     .to_string(),
             ),
         },
     );
     properties.insert(
-        "path".to_string(),
+        "thread_id".to_string(),
         JsonSchema::String {
             description: Some(
-                "Directory or file path to search. Defaults to the session's working directory."
+                "Review thread to reply in, from pr_comments. Omit to comment on the pull request itself."
                     .to_string(),
             ),
         },
     );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "pr_reply".to_string(),
+        description: "Posts a comment on a pull request, or a reply in one of its review threads."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["body".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_fetch_url_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
     properties.insert(
-        "limit".to_string(),
-        JsonSchema::Number {
-            description: Some(
-                "Maximum number of file paths to return (defaults to 100).".to_string(),
-            ),
+        "url".to_string(),
+        JsonSchema::String {
+            description: Some("http or https URL to fetch".to_string()),
         },
     );
 
     ToolSpec::Function(ResponsesApiTool {
-        name: "grep_files".to_string(),
-        description: "Finds files whose contents match the pattern and lists them by modification \
-                      time."
-            .to_string(),
+        name: "fetch_url".to_string(),
+        description: "Fetches a web page and returns its main content as Markdown, without navigation and other page furniture. Text, JSON and XML responses are returned as is.".to_string(),
         strict: false,
         parameters: JsonSchema::Object {
             properties,
-            required: Some(vec!["pattern".to_string()]),
+            required: Some(vec!["url".to_string()]),
             additional_properties: Some(false.into()),
         },
     })
 }
 
-fn create_read_file_tool() -> ToolSpec {
+fn create_web_search_tool() -> ToolSpec {
     let mut properties = BTreeMap::new();
     properties.insert(
-        "file_path".to_string(),
+        "query".to_string(),
         JsonSchema::String {
-            description: Some("Absolute path to the file".to_string()),
+            description: Some("Search query".to_string()),
         },
     );
     properties.insert(
-        "offset".to_string(),
+        "max_results".to_string(),
         JsonSchema::Number {
+            description: Some("Number of results to return".to_string()),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "web_search".to_string(),
+        description: "Searches the web and returns a numbered list of result titles, URLs and snippets. Use fetch_url to read a result.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["query".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_semantic_search_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "query".to_string(),
+        JsonSchema::String {
             description: Some(
-                "The line number to start reading from. Must be 1 or greater.".to_string(),
+                "What you are looking for, in natural language or code terms".to_string(),
             ),
         },
     );
     properties.insert(
-        "limit".to_string(),
+        "max_results".to_string(),
         JsonSchema::Number {
-            description: Some("The maximum number of lines to return.".to_string()),
+            description: Some("Maximum number of chunks to return".to_string()),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "semantic_search".to_string(),
+        description: "Searches an embeddings index of the workspace and returns the most relevant chunks of files as `path:start-end` followed by their lines. Use it to find code by meaning when you do not know the exact names to grep for.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["query".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_write_file_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "file_path".to_string(),
+        JsonSchema::String {
+            description: Some("Absolute path of the file to create or overwrite".to_string()),
         },
     );
     properties.insert(
-        "mode".to_string(),
+        "content".to_string(),
         JsonSchema::String {
-            description: Some(
-                "Optional mode selector: \"slice\" for simple ranges (default) or \"indentation\" \
-                 to expand around an anchor line."
-                    .to_string(),
-            ),
+            description: Some("The complete new contents of the file".to_string()),
         },
     );
 
-    let mut indentation_properties = BTreeMap::new();
-    indentation_properties.insert(
-        "anchor_line".to_string(),
-        JsonSchema::Number {
-            description: Some(
-                "Anchor line to center the indentation lookup on (defaults to offset).".to_string(),
-            ),
+    ToolSpec::Function(ResponsesApiTool {
+        name: "write_file".to_string(),
+        description: "Creates a file, or replaces all of an existing file's contents. Prefer edit_file for changes to part of a file.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["file_path".to_string(), "content".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_edit_file_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "file_path".to_string(),
+        JsonSchema::String {
+            description: Some("Absolute path of the file to edit".to_string()),
         },
     );
-    indentation_properties.insert(
-        "max_levels".to_string(),
-        JsonSchema::Number {
+    properties.insert(
+        "old_string".to_string(),
+        JsonSchema::String {
             description: Some(
-                "How many parent indentation levels (smaller indents) to include.".to_string(),
+                "Exact text to replace, including whitespace and indentation".to_string(),
             ),
         },
     );
-    indentation_properties.insert(
-        "include_siblings".to_string(),
-        JsonSchema::Boolean {
-            description: Some(
-                "When true, include additional blocks that share the anchor indentation."
-                    .to_string(),
-            ),
+    properties.insert(
+        "new_string".to_string(),
+        JsonSchema::String {
+            description: Some("Text to put in its place".to_string()),
         },
     );
-    indentation_properties.insert(
-        "include_header".to_string(),
+    properties.insert(
+        "replace_all".to_string(),
         JsonSchema::Boolean {
             description: Some(
-                "Include doc comments or attributes directly above the selected block.".to_string(),
+                "Replace every occurrence. By default old_string must occur exactly once."
+                    .to_string(),
             ),
         },
     );
-    indentation_properties.insert(
-        "max_lines".to_string(),
+    properties.insert(
+        "start_line".to_string(),
         JsonSchema::Number {
             description: Some(
-                "Hard cap on the number of lines returned when using indentation mode.".to_string(),
+                "Only consider occurrences starting on or after this 1-indexed line".to_string(),
             ),
         },
     );
     properties.insert(
-        "indentation".to_string(),
-        JsonSchema::Object {
-            properties: indentation_properties,
-            required: None,
-            additional_properties: Some(false.into()),
+        "end_line".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "Only consider occurrences starting on or before this 1-indexed line".to_string(),
+            ),
         },
     );
 
     ToolSpec::Function(ResponsesApiTool {
-        name: "read_file".to_string(),
-        description:
-            "Reads a local file with 1-indexed line numbers, supporting slice and indentation-aware block modes."
-                .to_string(),
+        name: "edit_file".to_string(),
+        description: "Replaces exact text in a file. Fails if old_string is missing, or occurs more than once without replace_all, so read the file first and quote enough surrounding text to make the match unique.".to_string(),
         strict: false,
         parameters: JsonSchema::Object {
             properties,
-            required: Some(vec!["file_path".to_string()]),
+            required: Some(vec![
+                "file_path".to_string(),
+                "old_string".to_string(),
+                "new_string".to_string(),
+            ]),
             additional_properties: Some(false.into()),
         },
     })
@@ -981,17 +2198,33 @@ pub(crate) fn build_specs(
     mcp_tools: Option<HashMap<String, mcp_types::Tool>>,
 ) -> ToolRegistryBuilder {
     use crate::tools::handlers::ApplyPatchHandler;
+    use crate::tools::handlers::BackgroundShellHandler;
+    use crate::tools::handlers::CodeSearchHandler;
+    use crate::tools::handlers::FileEditHandler;
+    use crate::tools::handlers::GitHandler;
     use crate::tools::handlers::GrepFilesHandler;
     use crate::tools::handlers::ListDirHandler;
     use crate::tools::handlers::McpHandler;
     use crate::tools::handlers::McpResourceHandler;
+    use crate::tools::handlers::MemoryHandler;
     use crate::tools::handlers::PlanHandler;
+    use crate::tools::handlers::PullRequestHandler;
+    use crate::tools::handlers::PythonHandler;
     use crate::tools::handlers::ReadFileHandler;
+    use crate::tools::handlers::RepoMapHandler;
+    use crate::tools::handlers::RunTaskHandler;
+    use crate::tools::handlers::RunTestsHandler;
+    use crate::tools::handlers::SemanticSearchHandler;
     use crate::tools::handlers::ShellCommandHandler;
     use crate::tools::handlers::ShellHandler;
+    use crate::tools::handlers::ShellSessionHandler;
+    use crate::tools::handlers::SpawnAgentHandler;
+    use crate::tools::handlers::SpawnAgentsHandler;
+    use crate::tools::handlers::TerminalHandler;
     use crate::tools::handlers::TestSyncHandler;
     use crate::tools::handlers::UnifiedExecHandler;
     use crate::tools::handlers::ViewImageHandler;
+    use crate::tools::handlers::WebHandler;
     use std::sync::Arc;
 
     let mut builder = ToolRegistryBuilder::new();
@@ -1065,9 +2298,10 @@ pub(crate) fn build_specs(
         builder.register_handler("grep_files", grep_files_handler);
     }
 
-    if config
-        .experimental_supported_tools
-        .contains(&"read_file".to_string())
+    if config.file_edit_tools
+        || config
+            .experimental_supported_tools
+            .contains(&"read_file".to_string())
     {
         let read_file_handler = Arc::new(ReadFileHandler);
         builder.push_spec_with_parallel_support(create_read_file_tool(), true);
@@ -1093,7 +2327,8 @@ pub(crate) fn build_specs(
         builder.register_handler("test_sync_tool", test_sync_handler);
     }
 
-    if config.web_search_request {
+    let provider_web_search = config.web_tools && config.web_search_provider;
+    if config.web_search_request && !provider_web_search {
         builder.push_spec(ToolSpec::WebSearch {});
     }
 
@@ -1102,11 +2337,126 @@ pub(crate) fn build_specs(
         builder.register_handler("view_image", view_image_handler);
     }
 
+    if config.spawn_agent {
+        builder.push_spec(create_spawn_agent_tool());
+        builder.register_handler("spawn_agent", Arc::new(SpawnAgentHandler));
+        builder.push_spec(create_spawn_agents_tool());
+        builder.register_handler("spawn_agents", Arc::new(SpawnAgentsHandler));
+    }
+
+    if config.memory_tools {
+        let memory_handler = Arc::new(MemoryHandler);
+        builder.push_spec(create_memory_write_tool());
+        builder.register_handler("memory_write", memory_handler.clone());
+        builder.push_spec(create_memory_search_tool());
+        builder.register_handler("memory_search", memory_handler);
+    }
+
+    if config.background_shell {
+        let background_shell_handler = Arc::new(BackgroundShellHandler);
+        builder.push_spec(create_shell_bg_start_tool());
+        builder.register_handler("shell_bg_start", background_shell_handler.clone());
+        builder.push_spec_with_parallel_support(create_shell_bg_tail_tool(), true);
+        builder.register_handler("shell_bg_tail", background_shell_handler.clone());
+        builder.push_spec(create_shell_bg_kill_tool());
+        builder.register_handler("shell_bg_kill", background_shell_handler);
+    }
+
+    if config.terminal {
+        let terminal_handler = Arc::new(TerminalHandler);
+        builder.push_spec(create_terminal_start_tool());
+        builder.register_handler("terminal_start", terminal_handler.clone());
+        builder.push_spec(create_terminal_send_keys_tool());
+        builder.register_handler("terminal_send_keys", terminal_handler.clone());
+        builder.push_spec_with_parallel_support(create_terminal_read_screen_tool(), true);
+        builder.register_handler("terminal_read_screen", terminal_handler.clone());
+        builder.push_spec(create_terminal_kill_tool());
+        builder.register_handler("terminal_kill", terminal_handler);
+    }
+
+    if config.python_kernel {
+        builder.push_spec(create_python_tool());
+        builder.register_handler("python", Arc::new(PythonHandler));
+    }
+
+    if config.run_tests {
+        builder.push_spec(create_run_tests_tool());
+        builder.register_handler("run_tests", Arc::new(RunTestsHandler));
+    }
+
+    if config.run_task && !config.project_tasks.is_empty() {
+        builder.push_spec(create_run_task_tool(&config.project_tasks));
+        builder.register_handler("run_task", Arc::new(RunTaskHandler));
+    }
+
+    if config.code_search {
+        builder.push_spec_with_parallel_support(create_code_search_tool(), true);
+        builder.register_handler("code_search", Arc::new(CodeSearchHandler));
+    }
+
+    if config.repo_map {
+        builder.push_spec_with_parallel_support(create_repo_map_tool(), true);
+        builder.register_handler("repo_map", Arc::new(RepoMapHandler));
+    }
+
+    if config.semantic_search {
+        builder.push_spec_with_parallel_support(create_semantic_search_tool(), true);
+        builder.register_handler("semantic_search", Arc::new(SemanticSearchHandler));
+    }
+
+    if config.git_tools {
+        let git_handler = Arc::new(GitHandler);
+        builder.push_spec_with_parallel_support(create_git_status_tool(), true);
+        builder.push_spec_with_parallel_support(create_git_diff_tool(), true);
+        builder.push_spec(create_git_commit_tool());
+        builder.push_spec(create_git_create_branch_tool());
+        builder.register_handler("git_status", git_handler.clone());
+        builder.register_handler("git_diff", git_handler.clone());
+        builder.register_handler("git_commit", git_handler.clone());
+        builder.register_handler("git_create_branch", git_handler);
+    }
+
+    if config.pull_requests {
+        let pull_request_handler = Arc::new(PullRequestHandler);
+        builder.push_spec(create_pr_create_tool());
+        builder.push_spec_with_parallel_support(create_pr_comments_tool(), true);
+        builder.push_spec(create_pr_reply_tool());
+        builder.register_handler("pr_create", pull_request_handler.clone());
+        builder.register_handler("pr_comments", pull_request_handler.clone());
+        builder.register_handler("pr_reply", pull_request_handler);
+    }
+
+    if config.web_tools {
+        let web_handler = Arc::new(WebHandler);
+        builder.push_spec_with_parallel_support(create_fetch_url_tool(), true);
+        builder.register_handler("fetch_url", web_handler.clone());
+        if provider_web_search {
+            builder.push_spec_with_parallel_support(create_web_search_tool(), true);
+            builder.register_handler("web_search", web_handler);
+        }
+    }
+
+    if config.file_edit_tools {
+        let file_edit_handler = Arc::new(FileEditHandler);
+        builder.push_spec(create_write_file_tool());
+        builder.push_spec(create_edit_file_tool());
+        builder.register_handler("write_file", file_edit_handler.clone());
+        builder.register_handler("edit_file", file_edit_handler);
+    }
+
+    if config.shell_session {
+        builder.push_spec(create_shell_session_tool());
+        builder.register_handler("shell_session", Arc::new(ShellSessionHandler));
+    }
+
     if let Some(mcp_tools) = mcp_tools {
         let mut entries: Vec<(String, mcp_types::Tool)> = mcp_tools.into_iter().collect();
         entries.sort_by(|a, b| a.0.cmp(&b.0));
 
         for (name, tool) in entries.into_iter() {
+            if config.read_only && !is_read_only_mcp_tool(&tool) {
+                continue;
+            }
             match mcp_tool_to_openai_tool(name.clone(), tool.clone()) {
                 Ok(converted_tool) => {
                     builder.push_spec(ToolSpec::Function(converted_tool));
@@ -1119,9 +2469,71 @@ pub(crate) fn build_specs(
         }
     }
 
+    if config.tasks_only {
+        builder.retain(|name| !SHELL_TOOL_NAMES.contains(&name));
+    }
+
+    if config.read_only {
+        builder.retain(|name| !WRITE_TOOL_NAMES.contains(&name));
+    }
+
+    if let Some(allowed_tools) = &config.allowed_tools {
+        builder.retain(|name| is_tool_allowed(allowed_tools, name));
+    }
+
     builder
 }
 
+pub(crate) const SHELL_TOOL_NAMES: &[&str] = &[
+    "shell",
+    "shell_command",
+    "local_shell",
+    "container.exec",
+    "exec_command",
+    "write_stdin",
+    "shell_bg_start",
+    "shell_bg_tail",
+    "shell_bg_kill",
+    "shell_session",
+    "terminal_start",
+    "terminal_send_keys",
+    "terminal_read_screen",
+    "terminal_kill",
+    "python",
+    "run_tests",
+];
+
+/// Tools whose every call changes the workspace (or spawns an agent that
+/// could), so read-only mode does not offer them. Shell-like tools stay:
+/// their mutating calls are refused when dispatched.
+const WRITE_TOOL_NAMES: &[&str] = &[
+    "apply_patch",
+    "write_file",
+    "edit_file",
+    "git_commit",
+    "git_create_branch",
+    "pr_create",
+    "pr_reply",
+    "python",
+    "run_tests",
+    "run_task",
+    "spawn_agent",
+    "spawn_agents",
+];
+
+fn is_read_only_mcp_tool(tool: &mcp_types::Tool) -> bool {
+    tool.annotations
+        .as_ref()
+        .and_then(|annotations| annotations.read_only_hint)
+        .unwrap_or(false)
+}
+
+fn is_tool_allowed(allowed_tools: &[String], name: &str) -> bool {
+    allowed_tools
+        .iter()
+        .any(|allowed| allowed == name || (allowed == "shell" && SHELL_TOOL_NAMES.contains(&name)))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::client_common::tools::FreeformTool;
@@ -1292,6 +2704,113 @@ mod tests {
         assert_eq!(&tool_names, &expected_tools,);
     }
 
+    #[test]
+    fn allowed_tools_filters_specs_and_treats_shell_as_alias() {
+        let config = test_config();
+        let model_family = ModelsManager::construct_model_family_offline("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        features.enable(Feature::SubAgents);
+        let mut tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            features: &features,
+        });
+        let (tools, _) = build_specs(&tools_config, None).build();
+        assert!(tools.iter().any(|t| t.spec.name() == "spawn_agent"));
+
+        tools_config.allowed_tools = Some(vec!["shell".to_string(), "update_plan".to_string()]);
+        let (tools, registry) = build_specs(&tools_config, None).build();
+        let tool_names = tools.iter().map(|t| t.spec.name()).collect::<Vec<_>>();
+        assert_eq!(tool_names, vec!["shell_command", "update_plan"]);
+        assert!(registry.handler("shell").is_some());
+        assert!(registry.handler("apply_patch").is_none());
+    }
+
+    #[test]
+    fn tasks_only_replaces_shell_tools_with_run_task() {
+        let config = test_config();
+        let model_family = ModelsManager::construct_model_family_offline("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        features.enable(Feature::RunTask);
+        let mut tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            features: &features,
+        });
+        tools_config.project_tasks = vec![ProjectTask {
+            source: project_tasks::TaskSource::Make,
+            name: "build".to_string(),
+            description: Some("Compile everything".to_string()),
+        }];
+        tools_config.tasks_only = true;
+
+        let (tools, registry) = build_specs(&tools_config, None).build();
+        let tool_names = tools.iter().map(|t| t.spec.name()).collect::<Vec<_>>();
+        assert!(tool_names.contains(&"run_task"));
+        assert!(!tool_names.contains(&"shell_command"));
+        assert!(registry.handler("shell").is_none());
+    }
+
+    #[test]
+    fn read_only_drops_write_tools_and_unannotated_mcp_tools() {
+        let config = test_config();
+        let model_family = ModelsManager::construct_model_family_offline("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        features.enable(Feature::FileEditTools);
+        features.enable(Feature::GitTools);
+        let mut tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            features: &features,
+        });
+        tools_config.read_only = true;
+        let mcp_tool = |read_only_hint| mcp_types::Tool {
+            name: "tool".to_string(),
+            input_schema: ToolInputSchema {
+                properties: None,
+                required: None,
+                r#type: "object".to_string(),
+            },
+            output_schema: None,
+            title: None,
+            annotations: Some(mcp_types::ToolAnnotations {
+                destructive_hint: None,
+                idempotent_hint: None,
+                open_world_hint: None,
+                read_only_hint,
+                title: None,
+            }),
+            description: None,
+        };
+
+        let (tools, registry) = build_specs(
+            &tools_config,
+            Some(HashMap::from([
+                ("docs/search".to_string(), mcp_tool(Some(true))),
+                ("docs/update".to_string(), mcp_tool(None)),
+            ])),
+        )
+        .build();
+        let tool_names = tools.iter().map(|t| t.spec.name()).collect::<Vec<_>>();
+        for name in [
+            "shell_command",
+            "read_file",
+            "git_status",
+            "git_diff",
+            "docs/search",
+        ] {
+            assert!(tool_names.contains(&name), "missing {name}");
+        }
+        for name in [
+            "apply_patch",
+            "write_file",
+            "edit_file",
+            "git_commit",
+            "docs/update",
+        ] {
+            assert!(!tool_names.contains(&name), "unexpected {name}");
+        }
+        assert!(registry.handler("apply_patch").is_none());
+        assert!(registry.handler("shell").is_some());
+    }
+
     #[test]
     fn test_build_specs_gpt5_codex_default() {
         assert_model_tools(
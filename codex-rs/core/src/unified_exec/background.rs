@@ -0,0 +1,229 @@
+//! Background processes started with `shell_bg_start`.
+//!
+//! These go through the same approval and sandbox flow as `exec_command`, but
+//! are kept in their own store: unified exec sessions are closed when a turn
+//! ends, while background processes keep running until the model kills them
+//! or the conversation shuts down.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use tokio::sync::Mutex;
+use tokio::time::Duration;
+use tokio::time::Instant;
+
+use crate::sandboxing::SandboxPermissions;
+use crate::truncate::TruncationPolicy;
+use crate::truncate::formatted_truncate_text;
+
+use super::UnifiedExecContext;
+use super::UnifiedExecError;
+use super::UnifiedExecSessionManager;
+use super::clamp_yield_time;
+use super::resolve_max_tokens;
+use super::session::OutputHandles;
+use super::session::UnifiedExecSession;
+
+pub(crate) const MAX_BACKGROUND_PROCESSES: usize = 16;
+
+/// Parameters for [`BackgroundShellManager::start`].
+#[derive(Debug)]
+pub(crate) struct BackgroundStartRequest {
+    pub command: Vec<String>,
+    pub cwd: PathBuf,
+    pub yield_time_ms: u64,
+    pub max_output_tokens: Option<usize>,
+    pub sandbox_permissions: SandboxPermissions,
+    pub justification: Option<String>,
+}
+
+/// Output collected from a background process along with its current status.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BackgroundOutput {
+    /// `None` when the process exited before `shell_bg_start` returned.
+    pub id: Option<String>,
+    pub output: String,
+    pub exit_code: Option<i32>,
+    pub running: bool,
+}
+
+struct BackgroundProcess {
+    session: Arc<UnifiedExecSession>,
+}
+
+#[derive(Default)]
+pub(crate) struct BackgroundShellManager {
+    processes: Mutex<HashMap<String, BackgroundProcess>>,
+    next_id: AtomicU64,
+}
+
+impl BackgroundShellManager {
+    pub(crate) async fn start(
+        &self,
+        request: BackgroundStartRequest,
+        context: &UnifiedExecContext,
+    ) -> Result<BackgroundOutput, UnifiedExecError> {
+        if self.processes.lock().await.len() >= MAX_BACKGROUND_PROCESSES {
+            return Err(UnifiedExecError::create_session(format!(
+                "too many background processes (limit {MAX_BACKGROUND_PROCESSES}); kill one with shell_bg_kill first"
+            )));
+        }
+
+        let session = context
+            .session
+            .services
+            .unified_exec_manager
+            .open_session_with_sandbox(
+                &request.command,
+                request.cwd,
+                request.sandbox_permissions,
+                request.justification,
+                false,
+                context,
+            )
+            .await?;
+        let session = Arc::new(session);
+
+        let (text, exit_code, running) =
+            collect_output(&session, clamp_yield_time(request.yield_time_ms)).await;
+        let output = formatted_truncate_text(
+            &text,
+            TruncationPolicy::Tokens(resolve_max_tokens(request.max_output_tokens)),
+        );
+
+        if !running {
+            session.check_for_sandbox_denial_with_text(&text).await?;
+            return Ok(BackgroundOutput {
+                id: None,
+                output,
+                exit_code,
+                running,
+            });
+        }
+
+        let id = format!("bg-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        self.processes
+            .lock()
+            .await
+            .insert(id.clone(), BackgroundProcess { session });
+
+        Ok(BackgroundOutput {
+            id: Some(id),
+            output,
+            exit_code,
+            running,
+        })
+    }
+
+    /// Returns output produced since the last call. Exited processes are
+    /// forgotten once their final output has been returned.
+    pub(crate) async fn tail(
+        &self,
+        id: &str,
+        yield_time_ms: u64,
+        max_output_tokens: Option<usize>,
+    ) -> Result<BackgroundOutput, UnifiedExecError> {
+        let session = self.session(id).await?;
+        let (text, exit_code, running) =
+            collect_output(&session, clamp_yield_time(yield_time_ms)).await;
+        if !running {
+            self.processes.lock().await.remove(id);
+        }
+
+        Ok(BackgroundOutput {
+            id: Some(id.to_string()),
+            output: formatted_truncate_text(
+                &text,
+                TruncationPolicy::Tokens(resolve_max_tokens(max_output_tokens)),
+            ),
+            exit_code,
+            running,
+        })
+    }
+
+    /// Terminates the process and returns whatever output was still buffered.
+    pub(crate) async fn kill(
+        &self,
+        id: &str,
+        max_output_tokens: Option<usize>,
+    ) -> Result<BackgroundOutput, UnifiedExecError> {
+        let Some(process) = self.processes.lock().await.remove(id) else {
+            return Err(UnifiedExecError::UnknownSessionId {
+                process_id: id.to_string(),
+            });
+        };
+        process.session.terminate();
+        let (text, exit_code, _) = collect_output(&process.session, 0).await;
+
+        Ok(BackgroundOutput {
+            id: Some(id.to_string()),
+            output: formatted_truncate_text(
+                &text,
+                TruncationPolicy::Tokens(resolve_max_tokens(max_output_tokens)),
+            ),
+            exit_code,
+            running: false,
+        })
+    }
+
+    pub(crate) async fn terminate_all(&self) {
+        let processes: Vec<BackgroundProcess> = self
+            .processes
+            .lock()
+            .await
+            .drain()
+            .map(|(_, process)| process)
+            .collect();
+        for process in processes {
+            process.session.terminate();
+        }
+    }
+
+    /// Pauses (or, with `false`, continues) every background process while
+    /// the session is suspended for idleness.
+    pub(crate) async fn set_paused(&self, paused: bool) {
+        for process in self.processes.lock().await.values() {
+            process.session.set_paused(paused);
+        }
+    }
+
+    async fn session(&self, id: &str) -> Result<Arc<UnifiedExecSession>, UnifiedExecError> {
+        self.processes
+            .lock()
+            .await
+            .get(id)
+            .map(|process| Arc::clone(&process.session))
+            .ok_or_else(|| UnifiedExecError::UnknownSessionId {
+                process_id: id.to_string(),
+            })
+    }
+}
+
+async fn collect_output(
+    session: &UnifiedExecSession,
+    yield_time_ms: u64,
+) -> (String, Option<i32>, bool) {
+    let OutputHandles {
+        output_buffer,
+        output_notify,
+        cancellation_token,
+    } = session.output_handles();
+    let deadline = Instant::now() + Duration::from_millis(yield_time_ms);
+    let collected = UnifiedExecSessionManager::collect_output_until_deadline(
+        &output_buffer,
+        &output_notify,
+        &cancellation_token,
+        deadline,
+    )
+    .await;
+    let exit_code = session.exit_code();
+    let running = !session.has_exited() && exit_code.is_none();
+    (
+        String::from_utf8_lossy(&collected).to_string(),
+        exit_code,
+        running,
+    )
+}
@@ -20,6 +20,7 @@
 //! concerns remain isolated here. The implementation is split between:
 //! - `session.rs`: PTY session lifecycle + output buffering.
 //! - `session_manager.rs`: orchestration (approvals, sandboxing, reuse) and request handling.
+//! - `background.rs`: processes started with `shell_bg_start` that outlive the turn.
 
 use std::collections::HashMap;
 use std::collections::HashSet;
@@ -36,12 +37,21 @@ use crate::codex::TurnContext;
 use crate::sandboxing::SandboxPermissions;
 
 mod async_watcher;
+mod background;
 mod errors;
 mod session;
 mod session_manager;
+mod terminal;
 
+pub(crate) use background::BackgroundOutput;
+pub(crate) use background::BackgroundShellManager;
+pub(crate) use background::BackgroundStartRequest;
 pub(crate) use errors::UnifiedExecError;
 pub(crate) use session::UnifiedExecSession;
+pub(crate) use terminal::TerminalManager;
+pub(crate) use terminal::TerminalScreen;
+pub(crate) use terminal::TerminalStartRequest;
+pub(crate) use terminal::encode_key;
 
 pub(crate) const MIN_YIELD_TIME_MS: u64 = 250;
 pub(crate) const MAX_YIELD_TIME_MS: u64 = 30_000;
@@ -475,4 +485,89 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn background_process_survives_turn_cleanup_until_killed() -> anyhow::Result<()> {
+        skip_if_sandbox!(Ok(()));
+
+        let (session, turn) = test_session_and_turn().await;
+        let context =
+            UnifiedExecContext::new(Arc::clone(&session), Arc::clone(&turn), "call".to_string());
+        let manager = &session.services.background_shells;
+
+        let started = manager
+            .start(
+                BackgroundStartRequest {
+                    command: vec![
+                        "bash".to_string(),
+                        "-c".to_string(),
+                        "echo ready; while true; do sleep 0.1; done".to_string(),
+                    ],
+                    cwd: turn.cwd.clone(),
+                    yield_time_ms: 2_500,
+                    max_output_tokens: None,
+                    sandbox_permissions: SandboxPermissions::UseDefault,
+                    justification: None,
+                },
+                &context,
+            )
+            .await?;
+        let id = started.id.clone().expect("expected background id");
+        assert!(started.running);
+        assert!(started.output.contains("ready"));
+
+        // Turn cleanup only closes unified exec sessions.
+        session
+            .services
+            .unified_exec_manager
+            .terminate_all_sessions()
+            .await;
+        let tailed = manager.tail(&id, 250, None).await?;
+        assert!(tailed.running);
+
+        let killed = manager.kill(&id, None).await?;
+        assert!(!killed.running);
+        match manager.tail(&id, 250, None).await {
+            Err(UnifiedExecError::UnknownSessionId { process_id }) => assert_eq!(process_id, id),
+            other => panic!("expected UnknownSessionId, got {other:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn background_process_that_exits_immediately_is_not_tracked() -> anyhow::Result<()> {
+        skip_if_sandbox!(Ok(()));
+
+        let (session, turn) = test_session_and_turn().await;
+        let context =
+            UnifiedExecContext::new(Arc::clone(&session), Arc::clone(&turn), "call".to_string());
+
+        let output = session
+            .services
+            .background_shells
+            .start(
+                BackgroundStartRequest {
+                    command: vec!["bash".to_string(), "-c".to_string(), "exit 3".to_string()],
+                    cwd: turn.cwd.clone(),
+                    yield_time_ms: 2_500,
+                    max_output_tokens: None,
+                    sandbox_permissions: SandboxPermissions::UseDefault,
+                    justification: None,
+                },
+                &context,
+            )
+            .await?;
+        assert_eq!(
+            output,
+            BackgroundOutput {
+                id: None,
+                output: String::new(),
+                exit_code: Some(3),
+                running: false,
+            }
+        );
+
+        Ok(())
+    }
 }
@@ -161,6 +161,23 @@ impl UnifiedExecSession {
         self.output_task.abort();
     }
 
+    /// Stops (`SIGSTOP`) or continues (`SIGCONT`) the process group, so a
+    /// process left running in an idle session uses no CPU.
+    #[cfg(unix)]
+    pub(super) fn set_paused(&self, paused: bool) {
+        if self.has_exited() {
+            return;
+        }
+        let Some(pid) = self.session.process_id() else {
+            return;
+        };
+        let signal = if paused { libc::SIGSTOP } else { libc::SIGCONT };
+        unsafe { libc::killpg(pid as libc::pid_t, signal) };
+    }
+
+    #[cfg(not(unix))]
+    pub(super) fn set_paused(&self, _paused: bool) {}
+
     async fn snapshot_output(&self) -> Vec<Vec<u8>> {
         let guard = self.output_buffer.lock().await;
         guard.snapshot()
@@ -15,6 +15,7 @@ use crate::codex::Session;
 use crate::codex::TurnContext;
 use crate::exec_env::create_env;
 use crate::exec_policy::create_exec_approval_requirement_for_command;
+use crate::exec_policy::prompts_only_for_escalation;
 use crate::protocol::BackgroundEventEvent;
 use crate::protocol::EventMsg;
 use crate::sandboxing::ExecEnv;
@@ -22,6 +23,7 @@ use crate::sandboxing::SandboxPermissions;
 use crate::tools::orchestrator::ToolOrchestrator;
 use crate::tools::runtimes::unified_exec::UnifiedExecRequest as UnifiedExecToolRequest;
 use crate::tools::runtimes::unified_exec::UnifiedExecRuntime;
+use crate::tools::sandboxing::ExecApprovalRequirement;
 use crate::tools::sandboxing::ToolCtx;
 use crate::truncate::TruncationPolicy;
 use crate::truncate::approx_token_count;
@@ -128,6 +130,7 @@ impl UnifiedExecSessionManager {
                 cwd.clone(),
                 request.sandbox_permissions,
                 request.justification,
+                false,
                 context,
             )
             .await;
@@ -472,27 +475,67 @@ impl UnifiedExecSessionManager {
         UnifiedExecSession::from_spawned(spawned, env.sandbox).await
     }
 
+    /// Opens a session through the approval and sandbox flow. With
+    /// `always_ask`, commands that policy would run without asking still
+    /// need the user's approval.
     pub(super) async fn open_session_with_sandbox(
         &self,
         command: &[String],
         cwd: PathBuf,
         sandbox_permissions: SandboxPermissions,
         justification: Option<String>,
+        always_ask: bool,
         context: &UnifiedExecContext,
     ) -> Result<UnifiedExecSession, UnifiedExecError> {
         let env = apply_unified_exec_env(create_env(&context.turn.shell_environment_policy));
         let features = context.session.features();
         let mut orchestrator = ToolOrchestrator::new();
         let mut runtime = UnifiedExecRuntime::new(self);
+        let approved_prefixes = context
+            .session
+            .services
+            .approved_command_prefixes
+            .read()
+            .await;
         let exec_approval_requirement = create_exec_approval_requirement_for_command(
             &context.turn.exec_policy,
             &features,
+            &context.turn.client.config().command_safety,
+            &approved_prefixes,
             command,
             context.turn.approval_policy,
             &context.turn.sandbox_policy,
             sandbox_permissions,
         )
         .await;
+        // Interactive terminals always ask, whatever the sandbox allows.
+        let prompts_only_for_escalation = !always_ask
+            && prompts_only_for_escalation(
+                &context.turn.exec_policy,
+                &features,
+                &context.turn.client.config().command_safety,
+                &approved_prefixes,
+                command,
+                context.turn.approval_policy,
+                &context.turn.sandbox_policy,
+                sandbox_permissions,
+                &exec_approval_requirement,
+            )
+            .await;
+        drop(approved_prefixes);
+        let exec_approval_requirement = match exec_approval_requirement {
+            ExecApprovalRequirement::Skip {
+                proposed_execpolicy_amendment,
+                ..
+            } if always_ask => ExecApprovalRequirement::NeedsApproval {
+                reason: Some(match &justification {
+                    Some(justification) => format!("interactive terminal: {justification}"),
+                    None => "interactive terminal the model can type into".to_string(),
+                }),
+                proposed_execpolicy_amendment,
+            },
+            requirement => requirement,
+        };
         let req = UnifiedExecToolRequest::new(
             command.to_vec(),
             cwd,
@@ -500,6 +543,7 @@ impl UnifiedExecSessionManager {
             sandbox_permissions,
             justification,
             exec_approval_requirement,
+            prompts_only_for_escalation,
         );
         let tool_ctx = ToolCtx {
             session: context.session.as_ref(),
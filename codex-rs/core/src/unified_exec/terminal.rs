@@ -0,0 +1,270 @@
+//! Interactive programs driven with `terminal_start`, `terminal_send_keys`,
+//! `terminal_read_screen` and `terminal_kill`.
+//!
+//! Each terminal is a unified exec session whose output is fed through a
+//! `vt100` parser, so the model reads the rendered screen of a REPL,
+//! debugger or TUI rather than the raw escape sequences. Starting one asks
+//! the user even for commands that policy would otherwise run unprompted,
+//! since the model can type anything into it afterwards.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use tokio::sync::Mutex;
+use tokio::time::Duration;
+use tokio::time::Instant;
+
+use crate::sandboxing::SandboxPermissions;
+
+use super::UnifiedExecContext;
+use super::UnifiedExecError;
+use super::UnifiedExecSessionManager;
+use super::clamp_yield_time;
+use super::session::OutputHandles;
+use super::session::UnifiedExecSession;
+
+pub(crate) const MAX_TERMINALS: usize = 4;
+
+/// Matches the size of the pseudo-terminal unified exec sessions run in.
+const SCREEN_ROWS: u16 = 24;
+const SCREEN_COLS: u16 = 80;
+
+/// Parameters for [`TerminalManager::start`].
+#[derive(Debug)]
+pub(crate) struct TerminalStartRequest {
+    pub command: Vec<String>,
+    pub cwd: PathBuf,
+    pub yield_time_ms: u64,
+    pub sandbox_permissions: SandboxPermissions,
+    pub justification: Option<String>,
+}
+
+/// The rendered screen of a terminal along with its current status.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TerminalScreen {
+    pub id: String,
+    pub contents: String,
+    /// Zero-based `(row, col)` of the cursor.
+    pub cursor: (u16, u16),
+    pub exit_code: Option<i32>,
+    pub running: bool,
+}
+
+#[derive(Clone)]
+struct Terminal {
+    session: Arc<UnifiedExecSession>,
+    parser: Arc<Mutex<vt100::Parser>>,
+}
+
+#[derive(Default)]
+pub(crate) struct TerminalManager {
+    terminals: Mutex<HashMap<String, Terminal>>,
+    next_id: AtomicU64,
+}
+
+impl TerminalManager {
+    pub(crate) async fn start(
+        &self,
+        request: TerminalStartRequest,
+        context: &UnifiedExecContext,
+    ) -> Result<TerminalScreen, UnifiedExecError> {
+        if self.terminals.lock().await.len() >= MAX_TERMINALS {
+            return Err(UnifiedExecError::create_session(format!(
+                "too many terminals (limit {MAX_TERMINALS}); close one with terminal_kill first"
+            )));
+        }
+
+        let session = context
+            .session
+            .services
+            .unified_exec_manager
+            .open_session_with_sandbox(
+                &request.command,
+                request.cwd,
+                request.sandbox_permissions,
+                request.justification,
+                true,
+                context,
+            )
+            .await?;
+        let terminal = Terminal {
+            session: Arc::new(session),
+            parser: Arc::new(Mutex::new(vt100::Parser::new(SCREEN_ROWS, SCREEN_COLS, 0))),
+        };
+
+        let id = format!("term-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let screen = render(&id, &terminal, clamp_yield_time(request.yield_time_ms)).await;
+        if screen.running {
+            self.terminals.lock().await.insert(id, terminal);
+        } else {
+            terminal
+                .session
+                .check_for_sandbox_denial_with_text(&screen.contents)
+                .await?;
+        }
+        Ok(screen)
+    }
+
+    /// Writes `input` to the program, then returns the screen once it has had
+    /// `yield_time_ms` to react.
+    pub(crate) async fn send_keys(
+        &self,
+        id: &str,
+        input: &[u8],
+        yield_time_ms: u64,
+    ) -> Result<TerminalScreen, UnifiedExecError> {
+        let terminal = self.terminal(id).await?;
+        if !input.is_empty()
+            && terminal
+                .session
+                .writer_sender()
+                .send(input.to_vec())
+                .await
+                .is_err()
+        {
+            return Err(UnifiedExecError::WriteToStdin);
+        }
+        Ok(self.screen_of(id, &terminal, yield_time_ms).await)
+    }
+
+    /// Returns the current screen. Exited terminals are forgotten once their
+    /// final screen has been returned.
+    pub(crate) async fn read_screen(
+        &self,
+        id: &str,
+        yield_time_ms: u64,
+    ) -> Result<TerminalScreen, UnifiedExecError> {
+        let terminal = self.terminal(id).await?;
+        Ok(self.screen_of(id, &terminal, yield_time_ms).await)
+    }
+
+    pub(crate) async fn kill(&self, id: &str) -> Result<TerminalScreen, UnifiedExecError> {
+        let Some(terminal) = self.terminals.lock().await.remove(id) else {
+            return Err(UnifiedExecError::UnknownSessionId {
+                process_id: id.to_string(),
+            });
+        };
+        terminal.session.terminate();
+        let mut screen = render(id, &terminal, 0).await;
+        screen.running = false;
+        Ok(screen)
+    }
+
+    pub(crate) async fn terminate_all(&self) {
+        let terminals: Vec<Terminal> = self
+            .terminals
+            .lock()
+            .await
+            .drain()
+            .map(|(_, terminal)| terminal)
+            .collect();
+        for terminal in terminals {
+            terminal.session.terminate();
+        }
+    }
+
+    /// Pauses (or, with `false`, continues) every terminal while the session
+    /// is suspended for idleness.
+    pub(crate) async fn set_paused(&self, paused: bool) {
+        for terminal in self.terminals.lock().await.values() {
+            terminal.session.set_paused(paused);
+        }
+    }
+
+    async fn screen_of(&self, id: &str, terminal: &Terminal, yield_time_ms: u64) -> TerminalScreen {
+        let screen = render(id, terminal, clamp_yield_time(yield_time_ms)).await;
+        if !screen.running {
+            self.terminals.lock().await.remove(id);
+        }
+        screen
+    }
+
+    async fn terminal(&self, id: &str) -> Result<Terminal, UnifiedExecError> {
+        self.terminals.lock().await.get(id).cloned().ok_or_else(|| {
+            UnifiedExecError::UnknownSessionId {
+                process_id: id.to_string(),
+            }
+        })
+    }
+}
+
+/// Feeds output produced within `yield_time_ms` through the terminal's
+/// parser and renders the resulting screen.
+async fn render(id: &str, terminal: &Terminal, yield_time_ms: u64) -> TerminalScreen {
+    let OutputHandles {
+        output_buffer,
+        output_notify,
+        cancellation_token,
+    } = terminal.session.output_handles();
+    let deadline = Instant::now() + Duration::from_millis(yield_time_ms);
+    let collected = UnifiedExecSessionManager::collect_output_until_deadline(
+        &output_buffer,
+        &output_notify,
+        &cancellation_token,
+        deadline,
+    )
+    .await;
+    let mut parser = terminal.parser.lock().await;
+    parser.process(&collected);
+    let screen = parser.screen();
+    let exit_code = terminal.session.exit_code();
+    TerminalScreen {
+        id: id.to_string(),
+        contents: screen.contents(),
+        cursor: screen.cursor_position(),
+        exit_code,
+        running: !terminal.session.has_exited() && exit_code.is_none(),
+    }
+}
+
+/// Translates key names such as `Enter`, `Up` or `C-c` into the bytes a
+/// terminal sends for them.
+pub(crate) fn encode_key(name: &str) -> Option<Vec<u8>> {
+    let bytes: &[u8] = match name {
+        "Enter" => b"\r",
+        "Tab" => b"\t",
+        "Escape" => b"\x1b",
+        "Backspace" => b"\x7f",
+        "Delete" => b"\x1b[3~",
+        "Up" => b"\x1b[A",
+        "Down" => b"\x1b[B",
+        "Right" => b"\x1b[C",
+        "Left" => b"\x1b[D",
+        "Home" => b"\x1b[H",
+        "End" => b"\x1b[F",
+        "PageUp" => b"\x1b[5~",
+        "PageDown" => b"\x1b[6~",
+        "Space" => b" ",
+        _ => {
+            let key = name.strip_prefix("C-")?;
+            let mut chars = key.chars();
+            let (Some(c), None) = (chars.next(), chars.next()) else {
+                return None;
+            };
+            if !c.is_ascii_alphabetic() {
+                return None;
+            }
+            return Some(vec![c.to_ascii_lowercase() as u8 & 0x1f]);
+        }
+    };
+    Some(bytes.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn encodes_named_and_control_keys() {
+        assert_eq!(encode_key("Enter"), Some(b"\r".to_vec()));
+        assert_eq!(encode_key("Up"), Some(b"\x1b[A".to_vec()));
+        assert_eq!(encode_key("C-c"), Some(vec![0x03]));
+        assert_eq!(encode_key("C-D"), Some(vec![0x04]));
+        assert_eq!(encode_key("C-1"), None);
+        assert_eq!(encode_key("Hyper"), None);
+    }
+}
@@ -0,0 +1,338 @@
+//! Token usage and cost accounting.
+//!
+//! Every model response that reports token usage is appended to
+//! `~/.codex/usage.jsonl` as a single JSON line:
+//!
+//! ```text
+//! {"ts":<unix_seconds>,"session_id":"<uuid>","turn_id":"<id>","model":"gpt-5.1","input_tokens":1200,"cached_input_tokens":800,"output_tokens":300,"reasoning_output_tokens":128,"cost_usd":0.0042}
+//! ```
+//!
+//! Costs are estimated from the per-model pricing table in
+//! [`crate::config::Config::model_pricing`]. Records written for models
+//! without known pricing omit `cost_usd`; readers may re-price them later.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Result;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use chrono::Local;
+use chrono::TimeZone;
+use codex_protocol::protocol::TokenUsage;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::config::types::ModelPricing;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+/// Filename of the usage ledger inside `~/.codex`.
+pub const USAGE_LEDGER_FILENAME: &str = "usage.jsonl";
+
+const TOKENS_PER_PRICE_UNIT: f64 = 1_000_000.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UsageRecord {
+    pub ts: u64,
+    pub session_id: String,
+    pub turn_id: String,
+    pub model: String,
+    pub input_tokens: i64,
+    pub cached_input_tokens: i64,
+    pub output_tokens: i64,
+    pub reasoning_output_tokens: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+impl UsageRecord {
+    fn token_usage(&self) -> TokenUsage {
+        TokenUsage {
+            input_tokens: self.input_tokens,
+            cached_input_tokens: self.cached_input_tokens,
+            output_tokens: self.output_tokens,
+            reasoning_output_tokens: self.reasoning_output_tokens,
+            total_tokens: self.input_tokens + self.output_tokens,
+        }
+    }
+}
+
+/// Aggregated token counts and spend over a set of ledger records.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub input_tokens: i64,
+    pub cached_input_tokens: i64,
+    pub output_tokens: i64,
+    pub reasoning_output_tokens: i64,
+    pub cost_usd: f64,
+    /// Requests whose model had no known pricing; excluded from `cost_usd`.
+    pub unpriced_requests: u64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, record: &UsageRecord, cost_usd: Option<f64>) {
+        self.requests += 1;
+        self.input_tokens += record.input_tokens;
+        self.cached_input_tokens += record.cached_input_tokens;
+        self.output_tokens += record.output_tokens;
+        self.reasoning_output_tokens += record.reasoning_output_tokens;
+        match cost_usd {
+            Some(cost) => self.cost_usd += cost,
+            None => self.unpriced_requests += 1,
+        }
+    }
+}
+
+/// Usage for a single local calendar day, broken down by model.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DailyUsage {
+    /// Local date formatted as `YYYY-MM-DD`.
+    pub date: String,
+    pub totals: UsageTotals,
+    pub by_model: BTreeMap<String, UsageTotals>,
+}
+
+/// Default prices (USD per million tokens) for OpenAI models. Entries are
+/// matched by exact slug first, then by the longest matching prefix.
+pub fn built_in_model_pricing() -> HashMap<String, ModelPricing> {
+    let price = |input: f64, cached_input: f64, output: f64| ModelPricing {
+        input,
+        cached_input: Some(cached_input),
+        output,
+    };
+    [
+        ("gpt-5", price(1.25, 0.125, 10.0)),
+        ("gpt-5-mini", price(0.25, 0.025, 2.0)),
+        ("gpt-5-nano", price(0.05, 0.005, 0.4)),
+        ("gpt-5.1-codex-mini", price(0.25, 0.025, 2.0)),
+        ("gpt-4.1", price(2.0, 0.5, 8.0)),
+        ("gpt-4.1-mini", price(0.4, 0.1, 1.6)),
+        ("gpt-4.1-nano", price(0.1, 0.025, 0.4)),
+        ("o3", price(2.0, 0.5, 8.0)),
+        ("o3-mini", price(1.1, 0.55, 4.4)),
+        ("o4-mini", price(1.1, 0.275, 4.4)),
+        ("codex-mini-latest", price(1.5, 0.375, 6.0)),
+    ]
+    .into_iter()
+    .map(|(model, pricing)| (model.to_string(), pricing))
+    .collect()
+}
+
+/// Look up pricing for `model`, preferring an exact match and falling back to
+/// the longest table key that prefixes the slug (e.g. `gpt-5` for `gpt-5.1-codex`).
+pub fn pricing_for_model<'a>(
+    pricing: &'a HashMap<String, ModelPricing>,
+    model: &str,
+) -> Option<&'a ModelPricing> {
+    pricing.get(model).or_else(|| {
+        pricing
+            .iter()
+            .filter(|(key, _)| model.starts_with(key.as_str()))
+            .max_by_key(|(key, _)| key.len())
+            .map(|(_, pricing)| pricing)
+    })
+}
+
+/// Estimated cost in USD of `usage` under `pricing`.
+pub fn cost_usd(pricing: &ModelPricing, usage: &TokenUsage) -> f64 {
+    let cached_rate = pricing.cached_input.unwrap_or(pricing.input);
+    let uncached = usage.non_cached_input() as f64;
+    let cached = usage.cached_input_tokens.max(0) as f64;
+    let output = usage.output_tokens.max(0) as f64;
+    (uncached * pricing.input + cached * cached_rate + output * pricing.output)
+        / TOKENS_PER_PRICE_UNIT
+}
+
+pub fn ledger_path(codex_home: &Path) -> PathBuf {
+    codex_home.join(USAGE_LEDGER_FILENAME)
+}
+
+/// Append `record` to the ledger. The full line is written with a single
+/// `write` call on an `O_APPEND` descriptor so concurrent sessions do not
+/// interleave records.
+pub(crate) async fn append_record(codex_home: &Path, record: &UsageRecord) -> Result<()> {
+    let path = ledger_path(codex_home);
+    let mut line = serde_json::to_string(record)
+        .map_err(|e| std::io::Error::other(format!("failed to serialise usage record: {e}")))?;
+    line.push('\n');
+
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut options = OpenOptions::new();
+        options.append(true).create(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options.open(&path)?;
+        file.write_all(line.as_bytes())?;
+        file.flush()
+    })
+    .await?
+}
+
+/// Read every well-formed record from the ledger. A missing ledger yields an
+/// empty list; malformed lines are skipped.
+pub fn read_records(codex_home: &Path) -> Result<Vec<UsageRecord>> {
+    let contents = match std::fs::read_to_string(ledger_path(codex_home)) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<UsageRecord>(line).ok())
+        .collect())
+}
+
+/// Unix timestamp of the most recent local midnight before `now`.
+pub fn start_of_local_day(now: DateTime<Local>) -> u64 {
+    let midnight = now
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+        .unwrap_or(now);
+    u64::try_from(midnight.timestamp()).unwrap_or(0)
+}
+
+/// Unix timestamp of local midnight `days_back` days before today; `0`
+/// means the start of today.
+pub fn start_of_local_day_days_ago(days_back: u32) -> u64 {
+    start_of_local_day(Local::now() - chrono::Duration::days(i64::from(days_back)))
+}
+
+/// Cost of a record, using its recorded cost or re-pricing it with `pricing`.
+fn record_cost(record: &UsageRecord, pricing: &HashMap<String, ModelPricing>) -> Option<f64> {
+    record.cost_usd.or_else(|| {
+        pricing_for_model(pricing, &record.model).map(|p| cost_usd(p, &record.token_usage()))
+    })
+}
+
+/// Total spend recorded since local midnight across all sessions.
+pub(crate) async fn today_spend_usd(
+    codex_home: &Path,
+    pricing: &HashMap<String, ModelPricing>,
+) -> f64 {
+    let codex_home = codex_home.to_path_buf();
+    let since = start_of_local_day(Local::now());
+    let records = tokio::task::spawn_blocking(move || read_records(&codex_home))
+        .await
+        .ok()
+        .and_then(Result::ok)
+        .unwrap_or_default();
+    records
+        .iter()
+        .filter(|record| record.ts >= since)
+        .filter_map(|record| record_cost(record, pricing))
+        .sum()
+}
+
+/// Group records at or after `since_ts` by local day, oldest first.
+pub fn summarize_by_day(
+    records: &[UsageRecord],
+    since_ts: u64,
+    pricing: &HashMap<String, ModelPricing>,
+) -> Vec<DailyUsage> {
+    let mut days: BTreeMap<String, DailyUsage> = BTreeMap::new();
+    for record in records.iter().filter(|record| record.ts >= since_ts) {
+        let Some(date) = i64::try_from(record.ts)
+            .ok()
+            .and_then(|ts| Local.timestamp_opt(ts, 0).single())
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+        else {
+            continue;
+        };
+        let cost = record_cost(record, pricing);
+        let day = days.entry(date.clone()).or_insert_with(|| DailyUsage {
+            date,
+            totals: UsageTotals::default(),
+            by_model: BTreeMap::new(),
+        });
+        day.totals.add(record, cost);
+        day.by_model
+            .entry(record.model.clone())
+            .or_default()
+            .add(record, cost);
+    }
+    days.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn record(ts: u64, model: &str, cost_usd: Option<f64>) -> UsageRecord {
+        UsageRecord {
+            ts,
+            session_id: "session".to_string(),
+            turn_id: "turn".to_string(),
+            model: model.to_string(),
+            input_tokens: 1_000_000,
+            cached_input_tokens: 500_000,
+            output_tokens: 100_000,
+            reasoning_output_tokens: 0,
+            cost_usd,
+        }
+    }
+
+    #[test]
+    fn pricing_prefers_exact_then_longest_prefix() {
+        let pricing = built_in_model_pricing();
+        assert_eq!(
+            pricing_for_model(&pricing, "gpt-5.1-codex-max"),
+            pricing.get("gpt-5")
+        );
+        assert_eq!(
+            pricing_for_model(&pricing, "gpt-5-mini-2025"),
+            pricing.get("gpt-5-mini")
+        );
+        assert_eq!(pricing_for_model(&pricing, "llama3"), None);
+    }
+
+    #[test]
+    fn cost_splits_cached_and_uncached_input() {
+        let pricing = ModelPricing {
+            input: 2.0,
+            cached_input: Some(0.5),
+            output: 8.0,
+        };
+        let usage = record(0, "m", None).token_usage();
+        // 500k uncached * $2 + 500k cached * $0.5 + 100k output * $8, per 1M tokens.
+        assert_eq!(cost_usd(&pricing, &usage), 1.0 + 0.25 + 0.8);
+    }
+
+    #[tokio::test]
+    async fn appended_records_round_trip_and_summarize() {
+        let home = TempDir::new().expect("tempdir");
+        let first = record(1_000, "gpt-5", Some(1.5));
+        let second = record(1_001, "unknown-model", None);
+        append_record(home.path(), &first).await.expect("append");
+        append_record(home.path(), &second).await.expect("append");
+
+        let records = read_records(home.path()).expect("read");
+        assert_eq!(records, vec![first.clone(), second.clone()]);
+
+        let days = summarize_by_day(&records, 0, &built_in_model_pricing());
+        assert_eq!(days.len(), 1);
+        let totals = &days[0].totals;
+        assert_eq!(
+            totals,
+            &UsageTotals {
+                requests: 2,
+                input_tokens: 2_000_000,
+                cached_input_tokens: 1_000_000,
+                output_tokens: 200_000,
+                reasoning_output_tokens: 0,
+                cost_usd: 1.5,
+                unpriced_requests: 1,
+            }
+        );
+    }
+}
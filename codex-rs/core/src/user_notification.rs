@@ -1,43 +1,39 @@
 use serde::Serialize;
 use tracing::error;
-use tracing::warn;
+
+use crate::hooks;
+use crate::hooks::HookCommand;
+use crate::hooks::HookPlaceholders;
+use crate::hooks::HookSandbox;
 
 #[derive(Debug, Default)]
 pub(crate) struct UserNotifier {
-    notify_command: Option<Vec<String>>,
+    notify_commands: Vec<HookCommand>,
 }
 
 impl UserNotifier {
-    pub(crate) fn notify(&self, notification: &UserNotification) {
-        if let Some(notify_command) = &self.notify_command
-            && !notify_command.is_empty()
-        {
-            self.invoke_notify(notify_command, notification)
+    pub(crate) fn notify(
+        &self,
+        notification: &UserNotification,
+        sandbox: &HookSandbox,
+        placeholders: &HookPlaceholders,
+    ) {
+        if self.notify_commands.is_empty() {
+            return;
         }
-    }
-
-    fn invoke_notify(&self, notify_command: &[String], notification: &UserNotification) {
-        let Ok(json) = serde_json::to_string(&notification) else {
+        let Some(json) = hooks::payload(notification) else {
             error!("failed to serialise notification payload");
             return;
         };
-
-        let mut command = std::process::Command::new(&notify_command[0]);
-        if notify_command.len() > 1 {
-            command.args(&notify_command[1..]);
-        }
-        command.arg(json);
-
-        // Fire-and-forget – we do not wait for completion.
-        if let Err(e) = command.spawn() {
-            warn!("failed to spawn notifier '{}': {e}", notify_command[0]);
+        for notify_command in &self.notify_commands {
+            hooks::spawn(&placeholders.expand(notify_command), json.clone(), sandbox);
         }
     }
 
-    pub(crate) fn new(notify: Option<Vec<String>>) -> Self {
-        Self {
-            notify_command: notify,
-        }
+    /// `notify_commands` are the `notify` program and `AgentTurnComplete`
+    /// hooks, all non-empty.
+    pub(crate) fn new(notify_commands: Vec<HookCommand>) -> Self {
+        Self { notify_commands }
     }
 }
 
@@ -0,0 +1,672 @@
+//! Turns an HTML page into Markdown, keeping the main content.
+//!
+//! This is a small tolerant tokenizer rather than a spec-compliant parser:
+//! unclosed elements are closed when an enclosing element ends, and anything
+//! it cannot make sense of is treated as text. Content is taken from the first
+//! `<article>`, then `<main>`, then the `<body>`, with navigation, page
+//! chrome and elements whose class or id marks them as such left out.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+use url::Url;
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct ExtractedPage {
+    pub title: Option<String>,
+    pub markdown: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open {
+        name: String,
+        attrs: Vec<(String, String)>,
+        self_closing: bool,
+    },
+    Close(String),
+    Text(String),
+}
+
+/// Elements whose contents are never page text.
+const RAW_TEXT_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "math", "iframe", "object", "canvas",
+];
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+/// Page chrome dropped wherever it appears.
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "head", "nav", "header", "footer", "aside", "form", "button", "select", "dialog", "menu",
+];
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "main",
+    "figure",
+    "figcaption",
+    "dl",
+    "dt",
+    "dd",
+    "table",
+    "ul",
+    "ol",
+    "address",
+    "details",
+    "summary",
+    "body",
+    "html",
+];
+
+static BOILERPLATE: LazyLock<Regex> = LazyLock::new(|| {
+    #[expect(clippy::expect_used)]
+    Regex::new(
+        r"(?i)(?:^|[\s_-])(?:nav|navbar|menu|sidebar|footer|header|cookie|cookies|banner|advert|ads|share|social|comments|related|breadcrumbs?|popup|modal|newsletter)(?:$|[\s_-])",
+    )
+    .expect("boilerplate regex")
+});
+
+pub(crate) fn html_to_markdown(html: &str, base_url: Option<&Url>) -> ExtractedPage {
+    let tokens = tokenize(html);
+    let title = extract_title(&tokens);
+    let content = main_content(&tokens);
+    let mut writer = MarkdownWriter::new(base_url);
+    writer.write(content);
+    ExtractedPage {
+        title,
+        markdown: writer.finish(),
+    }
+}
+
+fn tokenize(html: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            tokens.push(Token::Text(rest.to_string()));
+            break;
+        };
+        if lt > 0 {
+            tokens.push(Token::Text(rest[..lt].to_string()));
+            rest = &rest[lt..];
+        }
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment
+                .find("-->")
+                .map_or("", |end| &comment[end + "-->".len()..]);
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+            continue;
+        }
+
+        let closing = rest.starts_with("</");
+        let name_start = if closing { 2 } else { 1 };
+        let name_len = rest[name_start..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+            .unwrap_or(rest.len() - name_start);
+        if name_len == 0 {
+            // A stray `<` in text.
+            tokens.push(Token::Text("<".to_string()));
+            rest = &rest[1..];
+            continue;
+        }
+        let name = rest[name_start..name_start + name_len].to_ascii_lowercase();
+        let Some(tag_end) = find_tag_end(rest) else {
+            break;
+        };
+        let inside = &rest[name_start + name_len..tag_end];
+        rest = &rest[tag_end + 1..];
+
+        if closing {
+            tokens.push(Token::Close(name));
+            continue;
+        }
+        let self_closing = inside.trim_end().ends_with('/');
+        let attrs = parse_attrs(inside.trim_end().trim_end_matches('/'));
+        if RAW_TEXT_ELEMENTS.contains(&name.as_str()) && !self_closing {
+            // Skip to the matching close tag without tokenizing the contents.
+            let close = format!("</{name}");
+            let end = rest.to_ascii_lowercase().find(&close);
+            rest = match end {
+                Some(end) => {
+                    let after = &rest[end..];
+                    after.find('>').map_or("", |gt| &after[gt + 1..])
+                }
+                None => "",
+            };
+            continue;
+        }
+        tokens.push(Token::Open {
+            name,
+            attrs,
+            self_closing,
+        });
+    }
+    tokens
+}
+
+/// Index of the `>` ending the tag at the start of `text`, skipping quoted
+/// attribute values.
+fn find_tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_attrs(text: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    let mut chars = text.trim().chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let name: String =
+            std::iter::from_fn(|| chars.next_if(|c| !c.is_whitespace() && *c != '=')).collect();
+        if name.is_empty() {
+            if chars.next().is_none() {
+                break;
+            }
+            continue;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut value = String::new();
+        if chars.next_if_eq(&'=').is_some() {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            match chars.peek().copied() {
+                Some(quote @ ('"' | '\'')) => {
+                    chars.next();
+                    value = std::iter::from_fn(|| chars.next_if(|c| *c != quote)).collect();
+                    chars.next();
+                }
+                _ => {
+                    value = std::iter::from_fn(|| chars.next_if(|c| !c.is_whitespace())).collect();
+                }
+            }
+        }
+        attrs.push((name.to_ascii_lowercase(), decode_entities(&value)));
+    }
+    attrs
+}
+
+fn attr<'a>(attrs: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+fn extract_title(tokens: &[Token]) -> Option<String> {
+    let start = tokens
+        .iter()
+        .position(|token| matches!(token, Token::Open { name, .. } if name == "title"))?;
+    let title: String = tokens[start + 1..]
+        .iter()
+        .take_while(|token| !matches!(token, Token::Close(name) if name == "title"))
+        .filter_map(|token| match token {
+            Token::Text(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+    let title = collapse_whitespace(&decode_entities(&title));
+    let title = title.trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+/// The tokens inside the element most likely to hold the page's content.
+fn main_content(tokens: &[Token]) -> &[Token] {
+    let is_main = |token: &Token, wanted: &str| match token {
+        Token::Open { name, attrs, .. } => {
+            name == wanted || (wanted == "main" && attr(attrs, "role") == Some("main"))
+        }
+        _ => false,
+    };
+    for wanted in ["article", "main", "body"] {
+        let Some(start) = tokens.iter().position(|token| is_main(token, wanted)) else {
+            continue;
+        };
+        let Token::Open { name, .. } = &tokens[start] else {
+            continue;
+        };
+        let mut depth = 0usize;
+        for (offset, token) in tokens[start..].iter().enumerate() {
+            match token {
+                Token::Open {
+                    name: open,
+                    self_closing: false,
+                    ..
+                } if open == name => depth += 1,
+                Token::Close(close) if close == name => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        return &tokens[start + 1..start + offset];
+                    }
+                }
+                _ => {}
+            }
+        }
+        return &tokens[start + 1..];
+    }
+    tokens
+}
+
+fn is_boilerplate(name: &str, attrs: &[(String, String)]) -> bool {
+    if SKIPPED_ELEMENTS.contains(&name) {
+        return true;
+    }
+    if attr(attrs, "hidden").is_some() || attr(attrs, "aria-hidden") == Some("true") {
+        return true;
+    }
+    if !matches!(name, "div" | "section" | "ul" | "ol" | "span" | "table") {
+        return false;
+    }
+    ["class", "id", "role"]
+        .iter()
+        .filter_map(|key| attr(attrs, key))
+        .any(|value| BOILERPLATE.is_match(value) || value == "navigation")
+}
+
+struct MarkdownWriter<'a> {
+    base_url: Option<&'a Url>,
+    out: String,
+    /// Open elements, innermost last, with whether each one is skipped.
+    stack: Vec<(String, bool)>,
+    /// Link targets of open `<a>` elements.
+    links: Vec<Option<String>>,
+    /// Item counters for open lists; `None` for unordered lists.
+    lists: Vec<Option<usize>>,
+    quote_depth: usize,
+    pre_depth: usize,
+    /// Cells written in the current table row.
+    row_cells: usize,
+}
+
+impl<'a> MarkdownWriter<'a> {
+    fn new(base_url: Option<&'a Url>) -> Self {
+        Self {
+            base_url,
+            out: String::new(),
+            stack: Vec::new(),
+            links: Vec::new(),
+            lists: Vec::new(),
+            quote_depth: 0,
+            pre_depth: 0,
+            row_cells: 0,
+        }
+    }
+
+    fn skipping(&self) -> bool {
+        self.stack.iter().any(|(_, skipped)| *skipped)
+    }
+
+    fn write(&mut self, tokens: &[Token]) {
+        for token in tokens {
+            match token {
+                Token::Open {
+                    name,
+                    attrs,
+                    self_closing,
+                } => {
+                    let void = VOID_ELEMENTS.contains(&name.as_str()) || *self_closing;
+                    let skipped = is_boilerplate(name, attrs);
+                    if !void {
+                        self.stack.push((name.clone(), skipped));
+                    }
+                    if !(self.skipping() || void && skipped) {
+                        self.open(name, attrs);
+                    }
+                }
+                Token::Close(name) => {
+                    let Some(index) = self.stack.iter().rposition(|(open, _)| open == name) else {
+                        continue;
+                    };
+                    // Close anything left open inside it, innermost first.
+                    while self.stack.len() > index {
+                        let skipping = self.skipping();
+                        let Some((open, _)) = self.stack.pop() else {
+                            break;
+                        };
+                        if !skipping {
+                            self.close(&open);
+                        }
+                    }
+                }
+                Token::Text(text) if !self.skipping() => self.text(text),
+                Token::Text(_) => {}
+            }
+        }
+    }
+
+    fn open(&mut self, name: &str, attrs: &[(String, String)]) {
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.blank_line();
+                let level = name[1..].parse().unwrap_or(1);
+                self.out.push_str(&"#".repeat(level));
+                self.out.push(' ');
+            }
+            "br" => self.newline(),
+            "hr" => {
+                self.blank_line();
+                self.out.push_str("---");
+                self.blank_line();
+            }
+            "ul" => {
+                self.newline();
+                self.lists.push(None);
+            }
+            "ol" => {
+                self.newline();
+                self.lists.push(Some(0));
+            }
+            "li" => {
+                self.newline();
+                let depth = self.lists.len().saturating_sub(1);
+                self.out.push_str(&"  ".repeat(depth));
+                match self.lists.last_mut() {
+                    Some(Some(counter)) => {
+                        *counter += 1;
+                        self.out.push_str(&format!("{counter}. "));
+                    }
+                    _ => self.out.push_str("- "),
+                }
+            }
+            "blockquote" => {
+                self.blank_line();
+                self.quote_depth += 1;
+                self.out.push_str(&self.line_prefix());
+            }
+            "pre" => {
+                self.blank_line();
+                self.out.push_str("```");
+                self.newline();
+                self.pre_depth += 1;
+            }
+            "code" if self.pre_depth == 0 => self.out.push('`'),
+            "strong" | "b" => self.out.push_str("**"),
+            "em" | "i" => self.out.push('*'),
+            "a" => {
+                let href = attr(attrs, "href")
+                    .filter(|href| !href.starts_with('#') && !href.starts_with("javascript:"))
+                    .map(|href| self.resolve(href));
+                if href.is_some() {
+                    self.out.push('[');
+                }
+                self.links.push(href);
+            }
+            "img" => {
+                let alt = attr(attrs, "alt").map(str::trim).unwrap_or_default();
+                if let Some(src) = attr(attrs, "src")
+                    && !alt.is_empty()
+                {
+                    let src = self.resolve(src);
+                    self.out.push_str(&format!("![{alt}]({src})"));
+                }
+            }
+            "tr" => {
+                self.newline();
+                self.row_cells = 0;
+            }
+            "td" | "th" => {
+                self.out
+                    .push_str(if self.row_cells == 0 { "| " } else { " | " });
+                self.row_cells += 1;
+            }
+            name if BLOCK_ELEMENTS.contains(&name) => self.blank_line(),
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "p" => self.blank_line(),
+            "ul" | "ol" => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.blank_line();
+                }
+            }
+            "blockquote" => {
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+                self.blank_line();
+            }
+            "pre" => {
+                self.pre_depth = self.pre_depth.saturating_sub(1);
+                self.newline();
+                self.out.push_str("```");
+                self.blank_line();
+            }
+            "code" if self.pre_depth == 0 => self.out.push('`'),
+            "strong" | "b" => self.out.push_str("**"),
+            "em" | "i" => self.out.push('*'),
+            "a" => {
+                if let Some(Some(href)) = self.links.pop() {
+                    self.out.push_str(&format!("]({href})"));
+                }
+            }
+            "tr" if self.row_cells > 0 => self.out.push_str(" |"),
+            name if BLOCK_ELEMENTS.contains(&name) => self.blank_line(),
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        let text = decode_entities(text);
+        if self.pre_depth > 0 {
+            self.out.push_str(&text);
+            return;
+        }
+        let collapsed = collapse_whitespace(&text);
+        let at_line_start = self.out.is_empty()
+            || self.out.ends_with('\n')
+            || self.out.ends_with(' ')
+            || (self.quote_depth > 0 && self.out.ends_with(&self.line_prefix()));
+        let collapsed = if at_line_start {
+            collapsed.trim_start()
+        } else {
+            collapsed.as_str()
+        };
+        self.out.push_str(collapsed);
+    }
+
+    fn line_prefix(&self) -> String {
+        "> ".repeat(self.quote_depth)
+    }
+
+    fn newline(&mut self) {
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+        let prefix = self.line_prefix();
+        self.out.push_str(&prefix);
+    }
+
+    fn blank_line(&mut self) {
+        let prefix = self.line_prefix();
+        let trimmed = self.out.trim_end_matches([' ', '>']).len();
+        self.out.truncate(trimmed);
+        if self.out.is_empty() {
+            return;
+        }
+        while !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+        self.out.push_str(&prefix);
+    }
+
+    fn resolve(&self, href: &str) -> String {
+        self.base_url
+            .and_then(|base| base.join(href).ok())
+            .map_or_else(|| href.to_string(), |url| url.to_string())
+    }
+
+    fn finish(self) -> String {
+        let mut lines: Vec<&str> = Vec::new();
+        for line in self.out.lines().map(str::trim_end) {
+            let blank = line.is_empty() || line.chars().all(|c| c == '>');
+            let previous_blank = lines.last().is_none_or(|last| last.is_empty());
+            if blank {
+                if !previous_blank {
+                    lines.push("");
+                }
+            } else {
+                lines.push(line);
+            }
+        }
+        lines.join("\n").trim().to_string()
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_space {
+                out.push(' ');
+            }
+            in_space = true;
+        } else {
+            out.push(c);
+            in_space = false;
+        }
+    }
+    out
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| {
+                let entity = &rest[1..=end];
+                decode_entity(entity).map(|c| (c, end + 2))
+            });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "laquo" => '«',
+        "raquo" => '»',
+        "middot" => '·',
+        "bull" => '•',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "times" => '×',
+        "rarr" => '→',
+        "larr" => '←',
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn keeps_article_and_drops_page_chrome() {
+        let html = r#"<!doctype html>
+<html><head><title>Release notes &amp; more</title>
+<style>body { color: red }</style><script>var x = "<p>not text</p>";</script></head>
+<body>
+  <nav><a href="/">Home</a></nav>
+  <div class="cookie-banner">We use cookies</div>
+  <article>
+    <h1>Version 2.0</h1>
+    <p>The <strong>new</strong> release adds
+       <a href="/docs/upgrade">an upgrade guide</a> and <code>--fast</code>.</p>
+    <ul><li>Faster builds<li>Smaller binaries</ul>
+    <pre><code>cargo install tool
+  --locked</code></pre>
+    <blockquote><p>Breaking: drops Rust 1.70.</p></blockquote>
+    <aside>Related posts</aside>
+  </article>
+  <footer>© 2024</footer>
+</body></html>"#;
+        let base = Url::parse("https://example.com/blog/v2").ok();
+        let page = html_to_markdown(html, base.as_ref());
+        assert_eq!(page.title.as_deref(), Some("Release notes & more"));
+        assert_eq!(
+            page.markdown,
+            "# Version 2.0
+
+The **new** release adds [an upgrade guide](https://example.com/docs/upgrade) and `--fast`.
+
+- Faster builds
+- Smaller binaries
+
+```
+cargo install tool
+  --locked
+```
+
+> Breaking: drops Rust 1.70."
+        );
+    }
+
+    #[test]
+    fn renders_ordered_lists_tables_and_entities() {
+        let html = "<body><ol><li>one</li><li>two &lt;b&gt; &#x263A;</li></ol>\
+<table><tr><th>Name</th><th>Size</th></tr><tr><td>a.rs</td><td>3K</td></tr></table></body>";
+        assert_eq!(
+            html_to_markdown(html, None).markdown,
+            "1. one\n2. two <b> ☺\n\n| Name | Size |\n| a.rs | 3K |"
+        );
+    }
+}
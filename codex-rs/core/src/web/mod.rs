@@ -0,0 +1,346 @@
+//! Page fetching and web search behind the `fetch_url` and `web_search`
+//! function tools.
+//!
+//! Fetched pages are cached for the lifetime of the session, keyed by URL, up
+//! to [`MAX_CACHED_PAGES`] pages and [`MAX_CACHED_BYTES`], evicting the least
+//! recently used. Each host's robots.txt rules are cached too, and checked
+//! for every URL in a redirect chain. Every hop must also pass the caller's
+//! host check, so a redirect cannot reach a host the session was not granted.
+
+mod markdown;
+mod robots;
+pub(crate) mod search;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use indexmap::IndexMap;
+use tokio::sync::Mutex;
+use url::Url;
+
+use crate::config::types::FetchUrlConfig;
+use crate::default_client::build_reqwest_client_without_redirects;
+use markdown::html_to_markdown;
+use robots::RobotsRules;
+
+/// Product token matched against `User-agent` lines in robots.txt.
+const ROBOTS_USER_AGENT: &str = "codex";
+const ROBOTS_MAX_BYTES: usize = 512 * 1024;
+/// Redirects followed before giving up, as browsers and reqwest do.
+const MAX_REDIRECTS: usize = 10;
+const MAX_CACHED_PAGES: usize = 64;
+/// Bound on the summed content of cached pages.
+const MAX_CACHED_BYTES: usize = 32 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FetchedPage {
+    /// After redirects.
+    pub url: String,
+    pub title: Option<String>,
+    pub content: String,
+    /// Set when the body was cut off at `max_bytes`.
+    pub truncated: bool,
+}
+
+#[derive(Default)]
+pub(crate) struct WebCache {
+    pages: Mutex<PageCache>,
+    /// `None` when a host has no usable robots.txt.
+    robots: Mutex<HashMap<String, Option<Arc<RobotsRules>>>>,
+}
+
+impl WebCache {
+    /// Returns the page at `url` as Markdown (HTML) or text, from the cache
+    /// when it was fetched earlier in the session. A redirect, including one
+    /// for robots.txt, is only followed to URLs `allow_host` accepts.
+    pub(crate) async fn fetch(
+        &self,
+        url: &str,
+        config: &FetchUrlConfig,
+        allow_host: &(dyn Fn(&Url) -> bool + Send + Sync),
+    ) -> Result<Arc<FetchedPage>> {
+        let url = Url::parse(url)?;
+        if !matches!(url.scheme(), "http" | "https") {
+            bail!("only http and https URLs can be fetched");
+        }
+        if let Some(page) = self.pages.lock().await.get(url.as_str()) {
+            return Ok(page);
+        }
+
+        // Redirects are followed by hand so each hop is checked against the
+        // host check and its own host's robots.txt.
+        let client = build_reqwest_client_without_redirects();
+        let timeout = Duration::from_millis(config.timeout_ms);
+        let mut current = url.clone();
+        let mut redirects = 0;
+        let response = loop {
+            if !allow_host(&current) {
+                bail!(
+                    "redirected to {current}, whose host has not been granted network access; \
+                     fetch that URL directly to ask for access"
+                );
+            }
+            if config.respect_robots_txt {
+                let rules = self
+                    .robots_for(&client, &current, timeout, allow_host)
+                    .await;
+                if let Some(rules) = rules
+                    && !rules.allows(&robots_path(&current))
+                {
+                    bail!(
+                        "robots.txt for {} disallows fetching {current}",
+                        origin(&current)
+                    );
+                }
+            }
+            let response = client
+                .get(current.clone())
+                .header(
+                    "Accept",
+                    "text/html,application/xhtml+xml,text/plain;q=0.9,*/*;q=0.5",
+                )
+                .timeout(timeout)
+                .send()
+                .await?;
+            if !response.status().is_redirection() {
+                break response;
+            }
+            let Some(location) = response.headers().get(reqwest::header::LOCATION) else {
+                break response;
+            };
+            if redirects == MAX_REDIRECTS {
+                bail!("stopped after {MAX_REDIRECTS} redirects");
+            }
+            redirects += 1;
+            let location = location
+                .to_str()
+                .context("redirect with an unreadable Location")?;
+            current = current
+                .join(location)
+                .with_context(|| format!("redirect to an invalid URL `{location}`"))?;
+            if !matches!(current.scheme(), "http" | "https") {
+                bail!("redirected to {current}, which is not an http or https URL");
+            }
+        };
+        let status = response.status();
+        if !status.is_success() {
+            bail!("{status}");
+        }
+        let final_url = response.url().clone();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let kind = ContentKind::from_content_type(&content_type)
+            .ok_or_else(|| anyhow::anyhow!("cannot extract text from `{content_type}`"))?;
+        let (body, truncated) = read_limited(response, config.max_bytes).await?;
+        let body = String::from_utf8_lossy(&body);
+
+        let page = match kind {
+            ContentKind::Html => {
+                let extracted = html_to_markdown(&body, Some(&final_url));
+                FetchedPage {
+                    url: final_url.to_string(),
+                    title: extracted.title,
+                    content: extracted.markdown,
+                    truncated,
+                }
+            }
+            ContentKind::Text => FetchedPage {
+                url: final_url.to_string(),
+                title: None,
+                content: body.into_owned(),
+                truncated,
+            },
+        };
+        let page = Arc::new(page);
+        self.pages
+            .lock()
+            .await
+            .insert(url.to_string(), page.clone());
+        Ok(page)
+    }
+
+    async fn robots_for(
+        &self,
+        client: &reqwest::Client,
+        url: &Url,
+        timeout: Duration,
+        allow_host: &(dyn Fn(&Url) -> bool + Send + Sync),
+    ) -> Option<Arc<RobotsRules>> {
+        let origin = origin(url);
+        if let Some(rules) = self.robots.lock().await.get(&origin) {
+            return rules.clone();
+        }
+        let mut current = Url::parse(&format!("{origin}/robots.txt")).ok()?;
+        let mut redirects = 0;
+        // A missing or unreadable robots.txt allows everything.
+        let rules = loop {
+            let Ok(response) = client.get(current.clone()).timeout(timeout).send().await else {
+                break None;
+            };
+            if response.status().is_redirection() {
+                let next = response
+                    .headers()
+                    .get(reqwest::header::LOCATION)
+                    .and_then(|location| location.to_str().ok())
+                    .and_then(|location| current.join(location).ok())
+                    .filter(|next| matches!(next.scheme(), "http" | "https"));
+                match next {
+                    Some(next) if redirects < MAX_REDIRECTS => {
+                        // Not cached: a later grant may let the redirect through.
+                        if !allow_host(&next) {
+                            return None;
+                        }
+                        redirects += 1;
+                        current = next;
+                        continue;
+                    }
+                    _ => break None,
+                }
+            }
+            if !response.status().is_success() {
+                break None;
+            }
+            let Ok((body, _)) = read_limited(response, ROBOTS_MAX_BYTES).await else {
+                break None;
+            };
+            break Some(Arc::new(RobotsRules::parse(
+                &String::from_utf8_lossy(&body),
+                ROBOTS_USER_AGENT,
+            )));
+        };
+        self.robots.lock().await.insert(origin, rules.clone());
+        rules
+    }
+}
+
+/// Pages by requested URL, least recently used first.
+#[derive(Default)]
+struct PageCache {
+    pages: IndexMap<String, Arc<FetchedPage>>,
+    bytes: usize,
+}
+
+impl PageCache {
+    fn get(&mut self, url: &str) -> Option<Arc<FetchedPage>> {
+        let index = self.pages.get_index_of(url)?;
+        let last = self.pages.len() - 1;
+        self.pages.move_index(index, last);
+        self.pages.get_index(last).map(|(_, page)| page.clone())
+    }
+
+    fn insert(&mut self, url: String, page: Arc<FetchedPage>) {
+        if let Some(replaced) = self.pages.shift_remove(&url) {
+            self.bytes -= replaced.content.len();
+        }
+        self.bytes += page.content.len();
+        self.pages.insert(url, page);
+        while self.pages.len() > MAX_CACHED_PAGES
+            || (self.bytes > MAX_CACHED_BYTES && self.pages.len() > 1)
+        {
+            let Some((_, evicted)) = self.pages.shift_remove_index(0) else {
+                break;
+            };
+            self.bytes -= evicted.content.len();
+        }
+    }
+}
+
+enum ContentKind {
+    Html,
+    Text,
+}
+
+impl ContentKind {
+    fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next().unwrap_or_default().trim();
+        match mime {
+            "" | "text/html" | "application/xhtml+xml" => Some(Self::Html),
+            "application/json" | "application/xml" | "application/javascript" => Some(Self::Text),
+            mime if mime.starts_with("text/")
+                || mime.ends_with("+json")
+                || mime.ends_with("+xml") =>
+            {
+                Some(Self::Text)
+            }
+            _ => None,
+        }
+    }
+}
+
+async fn read_limited(
+    mut response: reqwest::Response,
+    max_bytes: usize,
+) -> Result<(Vec<u8>, bool)> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        let remaining = max_bytes - body.len();
+        if chunk.len() > remaining {
+            body.extend_from_slice(&chunk[..remaining]);
+            return Ok((body, true));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok((body, false))
+}
+
+fn origin(url: &Url) -> String {
+    url.origin().ascii_serialization()
+}
+
+fn robots_path(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn page(content_len: usize) -> Arc<FetchedPage> {
+        Arc::new(FetchedPage {
+            url: String::new(),
+            title: None,
+            content: "x".repeat(content_len),
+            truncated: false,
+        })
+    }
+
+    #[test]
+    fn page_cache_evicts_least_recently_used() {
+        let mut cache = PageCache::default();
+        for index in 0..MAX_CACHED_PAGES {
+            cache.insert(format!("https://example.com/{index}"), page(1));
+        }
+        // Reading the oldest page makes it the newest.
+        assert!(cache.get("https://example.com/0").is_some());
+        cache.insert("https://example.com/new".to_string(), page(1));
+
+        assert_eq!(cache.pages.len(), MAX_CACHED_PAGES);
+        assert!(cache.get("https://example.com/0").is_some());
+        assert!(cache.get("https://example.com/1").is_none());
+    }
+
+    #[test]
+    fn page_cache_bounds_total_content() {
+        let mut cache = PageCache::default();
+        let half = MAX_CACHED_BYTES / 2;
+        cache.insert("https://example.com/a".to_string(), page(half));
+        cache.insert("https://example.com/b".to_string(), page(half));
+        cache.insert("https://example.com/c".to_string(), page(half));
+
+        assert!(cache.get("https://example.com/a").is_none());
+        assert_eq!(cache.bytes, 2 * half);
+        assert_eq!(cache.pages.len(), 2);
+    }
+}
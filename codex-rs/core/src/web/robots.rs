@@ -0,0 +1,140 @@
+//! The subset of robots.txt that decides whether a path may be fetched:
+//! `User-agent` groups with `Allow` and `Disallow` rules, `*` wildcards and
+//! the `$` end anchor. The longest matching rule wins, and `Allow` wins ties.
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct RobotsRules {
+    /// `(allow, pattern)` for the group that applies to us.
+    rules: Vec<(bool, String)>,
+}
+
+impl RobotsRules {
+    /// Keeps the rules of the groups naming `user_agent`, or of the `*`
+    /// groups when none does.
+    pub(crate) fn parse(text: &str, user_agent: &str) -> Self {
+        let user_agent = user_agent.to_ascii_lowercase();
+        let mut specific = Vec::new();
+        let mut wildcard = Vec::new();
+        // Agents of the group being read; consecutive User-agent lines share
+        // one group.
+        let mut agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim();
+            match key.as_str() {
+                "user-agent" => {
+                    if in_rules {
+                        agents.clear();
+                        in_rules = false;
+                    }
+                    agents.push(value.to_ascii_lowercase());
+                }
+                "allow" | "disallow" => {
+                    in_rules = true;
+                    // An empty Disallow allows everything.
+                    if value.is_empty() {
+                        continue;
+                    }
+                    let rule = (key == "allow", value.to_string());
+                    if agents
+                        .iter()
+                        .any(|agent| user_agent.contains(agent.as_str()) && agent != "*")
+                    {
+                        specific.push(rule.clone());
+                    }
+                    if agents.iter().any(|agent| agent == "*") {
+                        wildcard.push(rule);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            rules: if specific.is_empty() {
+                wildcard
+            } else {
+                specific
+            },
+        }
+    }
+
+    /// `path` includes the query string.
+    pub(crate) fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|(_, pattern)| matches_pattern(pattern, path))
+            .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+            .is_none_or(|(allow, _)| *allow)
+    }
+}
+
+fn matches_pattern(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let Some(first) = parts.next() else {
+        return true;
+    };
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (index, part) in parts.iter().enumerate() {
+        let last = index + 1 == parts.len();
+        if last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(found) => rest = &rest[found + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn longest_match_wins_and_specific_groups_override_wildcard() {
+        let text = "\
+User-agent: *
+Disallow: /private/
+Allow: /private/public-*.html$
+Disallow: /*?session=
+
+User-agent: GPTBot
+User-agent: codex
+Disallow: /
+";
+        let wildcard = RobotsRules::parse(text, "otherbot");
+        assert!(wildcard.allows("/docs/index.html"));
+        assert!(!wildcard.allows("/private/notes.html"));
+        assert!(wildcard.allows("/private/public-faq.html"));
+        assert!(!wildcard.allows("/private/public-faq.html?x=1"));
+        assert!(!wildcard.allows("/search?session=abc"));
+
+        let specific = RobotsRules::parse(text, "codex");
+        assert!(!specific.allows("/docs/index.html"));
+    }
+
+    #[test]
+    fn empty_or_missing_rules_allow_everything() {
+        assert_eq!(
+            RobotsRules::parse("User-agent: *\nDisallow:\n", "codex"),
+            RobotsRules::default()
+        );
+        assert!(RobotsRules::default().allows("/anything"));
+    }
+}
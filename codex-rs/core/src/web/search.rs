@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use crate::config::types::WebSearchProvider;
+use crate::config::types::WebSearchProviderConfig;
+use crate::default_client::build_reqwest_client;
+
+const BRAVE_API_URL: &str = "https://api.search.brave.com/res/v1";
+const TAVILY_API_URL: &str = "https://api.tavily.com";
+const DEFAULT_BRAVE_API_KEY_ENV: &str = "BRAVE_API_KEY";
+const DEFAULT_TAVILY_API_KEY_ENV: &str = "TAVILY_API_KEY";
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct SearchResult {
+    pub title: String,
+    pub url: String,
+    /// Brave calls this `description`, Tavily and SearXNG `content`.
+    #[serde(default, alias = "description", alias = "content")]
+    pub snippet: String,
+}
+
+#[derive(Deserialize)]
+struct BraveResponse {
+    #[serde(default)]
+    web: Option<BraveWeb>,
+}
+
+#[derive(Deserialize)]
+struct BraveWeb {
+    #[serde(default)]
+    results: Vec<SearchResult>,
+}
+
+/// Tavily and SearXNG share this shape.
+#[derive(Deserialize)]
+struct ResultsResponse {
+    #[serde(default)]
+    results: Vec<SearchResult>,
+}
+
+pub(crate) async fn search(
+    config: &WebSearchProviderConfig,
+    query: &str,
+    max_results: Option<usize>,
+) -> Result<Vec<SearchResult>> {
+    let max_results = max_results.unwrap_or(config.max_results).max(1);
+    let client = build_reqwest_client();
+    let mut results = match &config.provider {
+        WebSearchProvider::Brave {
+            api_key_env,
+            base_url,
+        } => {
+            let api_key = api_key(api_key_env.as_deref(), DEFAULT_BRAVE_API_KEY_ENV)?;
+            let base_url = base_url.as_deref().unwrap_or(BRAVE_API_URL);
+            let request = client
+                .get(format!("{}/web/search", base_url.trim_end_matches('/')))
+                .query(&[("q", query), ("count", &max_results.to_string())])
+                .header("Accept", "application/json")
+                .header("X-Subscription-Token", api_key);
+            let response: BraveResponse = send(request).await?;
+            response.web.map(|web| web.results).unwrap_or_default()
+        }
+        WebSearchProvider::Tavily {
+            api_key_env,
+            base_url,
+        } => {
+            let api_key = api_key(api_key_env.as_deref(), DEFAULT_TAVILY_API_KEY_ENV)?;
+            let base_url = base_url.as_deref().unwrap_or(TAVILY_API_URL);
+            let request = client
+                .post(format!("{}/search", base_url.trim_end_matches('/')))
+                .json(&json!({
+                    "api_key": api_key,
+                    "query": query,
+                    "max_results": max_results,
+                }));
+            let response: ResultsResponse = send(request).await?;
+            response.results
+        }
+        WebSearchProvider::Searxng { base_url } => {
+            let request = client
+                .get(format!("{}/search", base_url.trim_end_matches('/')))
+                .query(&[("q", query), ("format", "json")]);
+            let response: ResultsResponse = send(request).await?;
+            response.results
+        }
+    };
+    results.truncate(max_results);
+    Ok(results)
+}
+
+fn api_key(configured_env: Option<&str>, default_env: &str) -> Result<String> {
+    let env = configured_env.unwrap_or(default_env);
+    std::env::var(env)
+        .ok()
+        .filter(|key| !key.trim().is_empty())
+        .with_context(|| format!("set `{env}` to the web search provider's API key"))
+}
+
+async fn send<T: DeserializeOwned>(request: reqwest::RequestBuilder) -> Result<T> {
+    let response = request.timeout(SEARCH_TIMEOUT).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        bail!("search provider returned {status}: {}", body.trim());
+    }
+    Ok(response.json().await?)
+}
+
+/// One numbered entry per result, with the snippet indented beneath it.
+pub(crate) fn format_results(results: &[SearchResult]) -> String {
+    if results.is_empty() {
+        return "No results.".to_string();
+    }
+    results
+        .iter()
+        .enumerate()
+        .map(|(index, result)| {
+            let snippet = result
+                .snippet
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            let title = if result.title.trim().is_empty() {
+                result.url.as_str()
+            } else {
+                result.title.trim()
+            };
+            if snippet.is_empty() {
+                format!("{}. [{title}]({})", index + 1, result.url)
+            } else {
+                format!("{}. [{title}]({})\n   {snippet}", index + 1, result.url)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn reads_each_provider_snippet_field_and_formats_a_numbered_list() {
+        let brave: BraveResponse = serde_json::from_value(json!({
+            "web": {"results": [
+                {"title": "Rust", "url": "https://www.rust-lang.org/", "description": "A  language\nempowering everyone"}
+            ]}
+        }))
+        .expect("brave response");
+        let searxng: ResultsResponse = serde_json::from_value(json!({
+            "results": [
+                {"title": "", "url": "https://docs.rs/", "content": "", "engine": "duckduckgo"}
+            ]
+        }))
+        .expect("searxng response");
+
+        let mut results = brave.web.expect("web results").results;
+        results.extend(searxng.results);
+        assert_eq!(
+            format_results(&results),
+            "1. [Rust](https://www.rust-lang.org/)\n   A language empowering everyone\n2. [https://docs.rs/](https://docs.rs/)"
+        );
+    }
+}
@@ -0,0 +1,94 @@
+"""Driver for the Codex `python` tool.
+
+Reads one JSON request per line from stdin, runs its code in a namespace that
+persists between requests, and answers with a single line on stdout:
+the marker passed as the first argument, a space, and a JSON object.
+"""
+
+import ast
+import base64
+import contextlib
+import io
+import json
+import os
+import sys
+import traceback
+
+os.environ.setdefault("MPLBACKEND", "Agg")
+
+MARKER = sys.argv[1]
+REQUESTS = sys.stdin
+RESPONSES = sys.stdout
+NAMESPACE = {"__name__": "__main__"}
+
+# Code run by the tool must not read the request stream.
+sys.stdin = open(os.devnull)
+
+
+def render(value):
+    to_markdown = getattr(value, "to_markdown", None)
+    if callable(to_markdown):
+        try:
+            return to_markdown()
+        except Exception:
+            pass
+    return repr(value)
+
+
+def format_error():
+    """The current exception, without the driver's own frames."""
+    kind, value, tb = sys.exc_info()
+    while tb is not None and tb.tb_frame.f_code.co_filename != "<python>":
+        tb = tb.tb_next
+    return "".join(traceback.format_exception(kind, value, tb))
+
+
+def figures():
+    plt = sys.modules.get("matplotlib.pyplot")
+    if plt is None:
+        return []
+    images = []
+    for number in plt.get_fignums():
+        buffer = io.BytesIO()
+        plt.figure(number).savefig(buffer, format="png", bbox_inches="tight")
+        images.append(base64.b64encode(buffer.getvalue()).decode("ascii"))
+    plt.close("all")
+    return images
+
+
+def run(code):
+    output = io.StringIO()
+    result = None
+    error = None
+    with contextlib.redirect_stdout(output), contextlib.redirect_stderr(output):
+        try:
+            tree = ast.parse(code, "<python>", "exec")
+            last = None
+            if tree.body and isinstance(tree.body[-1], ast.Expr):
+                last = ast.Expression(tree.body.pop().value)
+            exec(compile(tree, "<python>", "exec"), NAMESPACE)
+            if last is not None:
+                value = eval(compile(last, "<python>", "eval"), NAMESPACE)
+                if value is not None:
+                    NAMESPACE["_"] = value
+                    result = render(value)
+        except BaseException:
+            error = format_error()
+    try:
+        images = figures()
+    except Exception:
+        images = []
+        error = (error or "") + format_error()
+    return {
+        "output": output.getvalue(),
+        "result": result,
+        "error": error,
+        "images": images,
+    }
+
+
+for line in REQUESTS:
+    request = json.loads(line)
+    response = run(request["code"])
+    RESPONSES.write("\n" + MARKER + " " + json.dumps(response) + "\n")
+    RESPONSES.flush()
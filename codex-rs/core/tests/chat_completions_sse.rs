@@ -57,7 +57,15 @@ async fn run_stream_with_bytes(sse_body: &[u8]) -> Vec<ResponseEvent> {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        retry_rate_limited: None,
+        request_max_retry_time_ms: None,
         requires_openai_auth: false,
+        safety_settings: None,
+        local_quirks: None,
+        http: None,
+        azure: None,
+        bedrock: None,
+        persist_reasoning: None,
     };
 
     let codex_home = match TempDir::new() {
@@ -0,0 +1,31 @@
+//! Git helpers for tests that need a real repository in the session cwd.
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::Result;
+
+/// Runs `git args` in `cwd` with a fixed committer identity, failing when git
+/// exits non-zero.
+pub fn git(cwd: &Path, args: &[&str]) -> Result<()> {
+    let status = Command::new("git")
+        .args([
+            "-c",
+            "user.name=Codex",
+            "-c",
+            "user.email=codex@example.com",
+        ])
+        .args(args)
+        .current_dir(cwd)
+        .status()?;
+    anyhow::ensure!(status.success(), "git {args:?} failed");
+    Ok(())
+}
+
+/// Initializes a repository on `main` with one commit adding `README.md`.
+pub fn init_repo(cwd: &Path) -> Result<()> {
+    git(cwd, &["init", "-q", "--initial-branch=main"])?;
+    std::fs::write(cwd.join("README.md"), "hello\n")?;
+    git(cwd, &["add", "."])?;
+    git(cwd, &["commit", "-q", "-m", "init"])
+}
@@ -1,5 +1,7 @@
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 
 use anyhow::Result;
 use base64::Engine;
@@ -256,6 +258,21 @@ pub fn sse(events: Vec<Value>) -> String {
     out
 }
 
+/// Build an SSE stream body where every event carries a `data:` line, as the
+/// Anthropic Messages and Gemini streams do. Events with a `type` also get a
+/// matching `event:` line; unlike [`sse`], type-only events keep their data.
+pub fn sse_data(events: Vec<Value>) -> String {
+    use std::fmt::Write as _;
+    let mut out = String::new();
+    for ev in events {
+        if let Some(kind) = ev.get("type").and_then(Value::as_str) {
+            writeln!(&mut out, "event: {kind}").unwrap();
+        }
+        write!(&mut out, "data: {ev}\n\n").unwrap();
+    }
+    out
+}
+
 /// Convenience: SSE event for a completed response with a specific id.
 pub fn ev_completed(id: &str) -> Value {
     serde_json::json!({
@@ -751,40 +768,55 @@ pub async fn mount_function_call_agent_response(
 /// POST to `/v1/responses`. Panics if more requests are received than bodies
 /// provided. Also asserts the exact number of expected calls.
 pub async fn mount_sse_sequence(server: &MockServer, bodies: Vec<String>) -> ResponseMock {
-    use std::sync::atomic::AtomicUsize;
-    use std::sync::atomic::Ordering;
-
-    struct SeqResponder {
-        num_calls: AtomicUsize,
-        responses: Vec<String>,
-    }
-
-    impl Respond for SeqResponder {
-        fn respond(&self, _: &wiremock::Request) -> ResponseTemplate {
-            let call_num = self.num_calls.fetch_add(1, Ordering::SeqCst);
-            match self.responses.get(call_num) {
-                Some(body) => ResponseTemplate::new(200)
-                    .insert_header("content-type", "text/event-stream")
-                    .set_body_string(body.clone()),
-                None => panic!("no response for {call_num}"),
-            }
+    let (mock, response_mock) = base_mock();
+    mount_seq(mock, server, bodies).await;
+    response_mock
+}
+
+/// Like [`mount_sse_sequence`], but for POST requests accepted by `matcher`
+/// instead of `/responses`, e.g. the Anthropic or Gemini streaming endpoints.
+pub async fn mount_sse_sequence_matching<M>(
+    server: &MockServer,
+    matcher: M,
+    bodies: Vec<String>,
+) -> ResponseMock
+where
+    M: Match + 'static,
+{
+    let response_mock = ResponseMock::new();
+    let mock = Mock::given(method("POST"))
+        .and(matcher)
+        .and(response_mock.clone());
+    mount_seq(mock, server, bodies).await;
+    response_mock
+}
+
+struct SeqResponder {
+    num_calls: AtomicUsize,
+    responses: Vec<String>,
+}
+
+impl Respond for SeqResponder {
+    fn respond(&self, _: &wiremock::Request) -> ResponseTemplate {
+        let call_num = self.num_calls.fetch_add(1, Ordering::SeqCst);
+        match self.responses.get(call_num) {
+            Some(body) => sse_response(body.clone()),
+            None => panic!("no response for {call_num}"),
         }
     }
+}
 
+async fn mount_seq(mock: MockBuilder, server: &MockServer, bodies: Vec<String>) {
     let num_calls = bodies.len();
     let responder = SeqResponder {
         num_calls: AtomicUsize::new(0),
         responses: bodies,
     };
-
-    let (mock, response_mock) = base_mock();
     mock.respond_with(responder)
         .up_to_n_times(num_calls as u64)
         .expect(num_calls as u64)
         .mount(server)
         .await;
-
-    response_mock
 }
 
 /// Validate invariants on the request body sent to `/v1/responses`.
@@ -19,6 +19,7 @@ pub struct StreamingSseChunk {
 /// Minimal streaming SSE server for tests that need gated per-chunk delivery.
 pub struct StreamingSseServer {
     uri: String,
+    state: Arc<TokioMutex<StreamingSseState>>,
     shutdown: oneshot::Sender<()>,
     task: tokio::task::JoinHandle<()>,
 }
@@ -28,6 +29,18 @@ impl StreamingSseServer {
         &self.uri
     }
 
+    /// Bodies of the `POST /v1/responses` requests received so far, parsed as
+    /// JSON.
+    pub async fn responses_request_bodies(&self) -> Vec<serde_json::Value> {
+        self.state
+            .lock()
+            .await
+            .request_bodies
+            .iter()
+            .filter_map(|body| serde_json::from_slice(body).ok())
+            .collect()
+    }
+
     pub async fn shutdown(self) {
         let _ = self.shutdown.send(());
         let _ = self.task.await;
@@ -60,16 +73,18 @@ pub async fn start_streaming_sse_server(
     let state = Arc::new(TokioMutex::new(StreamingSseState {
         responses: VecDeque::from(responses),
         completions: VecDeque::from(completion_senders),
+        request_bodies: Vec::new(),
     }));
     let (shutdown_tx, mut shutdown_rx) = oneshot::channel();
 
+    let state_for_task = Arc::clone(&state);
     let task = tokio::spawn(async move {
         loop {
             tokio::select! {
                 _ = &mut shutdown_rx => break,
                 accept_res = listener.accept() => {
                     let (mut stream, _) = accept_res.expect("accept streaming SSE connection");
-                    let state = Arc::clone(&state);
+                    let state = Arc::clone(&state_for_task);
                     tokio::spawn(async move {
                         let (request, body_prefix) = read_http_request(&mut stream).await;
                         let Some((method, path)) = parse_request_line(&request) else {
@@ -95,13 +110,13 @@ pub async fn start_streaming_sse_server(
                         }
 
                         if method == "POST" && path == "/v1/responses" {
-                            if drain_request_body(&mut stream, &request, body_prefix)
+                            let Ok(body) = drain_request_body(&mut stream, &request, body_prefix)
                                 .await
-                                .is_err()
-                            {
+                            else {
                                 let _ = write_http_response(&mut stream, 400, "bad request", "text/plain").await;
                                 return;
-                            }
+                            };
+                            state.lock().await.request_bodies.push(body);
                             let Some((chunks, completion)) = take_next_stream(&state).await else {
                                 let _ = write_http_response(&mut stream, 500, "no responses queued", "text/plain").await;
                                 return;
@@ -137,6 +152,7 @@ pub async fn start_streaming_sse_server(
     (
         StreamingSseServer {
             uri,
+            state,
             shutdown: shutdown_tx,
             task,
         },
@@ -147,6 +163,7 @@ pub async fn start_streaming_sse_server(
 struct StreamingSseState {
     responses: VecDeque<Vec<StreamingSseChunk>>,
     completions: VecDeque<oneshot::Sender<i64>>,
+    request_bodies: Vec<Vec<u8>>,
 }
 
 async fn take_next_stream(
@@ -206,9 +223,9 @@ async fn drain_request_body(
     stream: &mut tokio::net::TcpStream,
     headers: &str,
     mut body_prefix: Vec<u8>,
-) -> std::io::Result<()> {
+) -> std::io::Result<Vec<u8>> {
     let Some(content_len) = content_length(headers) else {
-        return Ok(());
+        return Ok(body_prefix);
     };
 
     if body_prefix.len() > content_len {
@@ -217,12 +234,13 @@ async fn drain_request_body(
 
     let remaining = content_len.saturating_sub(body_prefix.len());
     if remaining == 0 {
-        return Ok(());
+        return Ok(body_prefix);
     }
 
     let mut rest = vec![0u8; remaining];
     stream.read_exact(&mut rest).await?;
-    Ok(())
+    body_prefix.extend_from_slice(&rest);
+    Ok(body_prefix)
 }
 
 async fn write_sse_headers(stream: &mut tokio::net::TcpStream) -> std::io::Result<()> {
@@ -653,6 +671,7 @@ data: {"type":"response.completed","response":{"id":"resp-1"}}
                 }],
             ]),
             completions: VecDeque::from(vec![first_tx, second_tx]),
+            request_bodies: Vec::new(),
         });
 
         let (first_chunks, first_completion) =
@@ -53,7 +53,15 @@ async fn responses_stream_includes_subagent_header_on_review() {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        retry_rate_limited: None,
+        request_max_retry_time_ms: None,
         requires_openai_auth: false,
+        safety_settings: None,
+        local_quirks: None,
+        http: None,
+        azure: None,
+        bedrock: None,
+        persist_reasoning: None,
     };
 
     let codex_home = TempDir::new().expect("failed to create TempDir");
@@ -147,7 +155,15 @@ async fn responses_stream_includes_subagent_header_on_other() {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        retry_rate_limited: None,
+        request_max_retry_time_ms: None,
         requires_openai_auth: false,
+        safety_settings: None,
+        local_quirks: None,
+        http: None,
+        azure: None,
+        bedrock: None,
+        persist_reasoning: None,
     };
 
     let codex_home = TempDir::new().expect("failed to create TempDir");
@@ -237,7 +253,15 @@ async fn responses_respects_model_family_overrides_from_config() {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        retry_rate_limited: None,
+        request_max_retry_time_ms: None,
         requires_openai_auth: false,
+        safety_settings: None,
+        local_quirks: None,
+        http: None,
+        azure: None,
+        bedrock: None,
+        persist_reasoning: None,
     };
 
     let codex_home = TempDir::new().expect("failed to create TempDir");
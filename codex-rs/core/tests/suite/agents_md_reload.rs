@@ -0,0 +1,53 @@
+use anyhow::Result;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+
+const RULE: &str = "Always answer in French.";
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn agents_md_edits_apply_to_the_next_turn() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let mock = mount_sse_sequence(
+        &server,
+        (1..=3)
+            .map(|i| {
+                sse(vec![
+                    ev_assistant_message(&format!("msg-{i}"), "ok"),
+                    ev_completed(&format!("resp-{i}")),
+                ])
+            })
+            .collect(),
+    )
+    .await;
+
+    let test = test_codex().build(&server).await?;
+
+    test.submit_turn("first").await?;
+    std::fs::write(test.workspace_path("AGENTS.md"), RULE)?;
+    test.submit_turn("second").await?;
+    test.submit_turn("third").await?;
+
+    let rule_messages = mock
+        .requests()
+        .iter()
+        .map(|request| {
+            request
+                .message_input_texts("user")
+                .iter()
+                .filter(|text| text.contains(RULE))
+                .count()
+        })
+        .collect::<Vec<_>>();
+    // Loaded once when the file appears and not re-sent on later turns.
+    assert_eq!(rule_messages, vec![0, 1, 1]);
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+#![cfg(not(target_os = "windows"))]
+
+use anyhow::Result;
+use codex_core::WireApi;
+use core_test_support::responses::mount_sse_sequence_matching;
+use core_test_support::responses::sse_data;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use wiremock::matchers::path;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn anthropic_provider_round_trips_tool_use() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let plan_args = json!({"plan": [{"step": "Write tests", "status": "in_progress"}]});
+    let tool_turn = sse_data(vec![
+        json!({"type": "message_start", "message": {"id": "msg_1", "usage": {"input_tokens": 50, "output_tokens": 1}}}),
+        json!({"type": "content_block_start", "index": 0, "content_block": {"type": "tool_use", "id": "toolu_1", "name": "update_plan", "input": {}}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "input_json_delta", "partial_json": plan_args.to_string()}}),
+        json!({"type": "content_block_stop", "index": 0}),
+        json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 12}}),
+        json!({"type": "message_stop"}),
+    ]);
+    let final_turn = sse_data(vec![
+        json!({"type": "message_start", "message": {"id": "msg_2", "usage": {"input_tokens": 80, "output_tokens": 1}}}),
+        json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Plan recorded."}}),
+        json!({"type": "content_block_stop", "index": 0}),
+        json!({"type": "message_delta", "delta": {"stop_reason": "end_turn"}, "usage": {"output_tokens": 4}}),
+        json!({"type": "message_stop"}),
+    ]);
+    let response_mock =
+        mount_sse_sequence_matching(&server, path("/v1/messages"), vec![tool_turn, final_turn])
+            .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.model_provider.wire_api = WireApi::Anthropic;
+            config.model_provider.experimental_bearer_token = Some("sk-ant-test".to_string());
+            config.model_max_output_tokens = Some(4_096);
+        })
+        .build(&server)
+        .await?;
+
+    test.submit_turn("make a plan").await?;
+
+    let requests = response_mock.requests();
+    assert_eq!(requests.len(), 2);
+
+    let first = &requests[0];
+    assert_eq!(first.header("x-api-key").as_deref(), Some("sk-ant-test"));
+    assert_eq!(
+        first.header("anthropic-version").as_deref(),
+        Some("2023-06-01")
+    );
+    let first_body = first.body_json();
+    assert_eq!(first_body["max_tokens"], json!(4_096));
+    assert_eq!(first_body["stream"], json!(true));
+    let system = first_body["system"].as_array().expect("system blocks");
+    assert!(system[0]["text"].as_str().is_some_and(|s| !s.is_empty()));
+    assert_eq!(system[0]["cache_control"], json!({"type": "ephemeral"}));
+    let plan_tool = first_body["tools"]
+        .as_array()
+        .and_then(|tools| tools.iter().find(|tool| tool["name"] == "update_plan"))
+        .expect("update_plan tool");
+    assert_eq!(plan_tool["input_schema"]["type"], json!("object"));
+
+    let second_body = requests[1].body_json();
+    let messages = second_body["messages"].as_array().expect("messages");
+    let [.., tool_use, tool_result] = messages.as_slice() else {
+        panic!("expected tool use and result messages, got {messages:?}");
+    };
+    assert_eq!(
+        tool_use,
+        &json!({
+            "role": "assistant",
+            "content": [{"type": "tool_use", "id": "toolu_1", "name": "update_plan", "input": plan_args}],
+        })
+    );
+    assert_eq!(
+        tool_result,
+        &json!({
+            "role": "user",
+            "content": [{
+                "type": "tool_result",
+                "tool_use_id": "toolu_1",
+                "content": "Plan updated",
+                "cache_control": {"type": "ephemeral"},
+            }],
+        })
+    );
+
+    Ok(())
+}
@@ -7,6 +7,7 @@ use core_test_support::test_codex::ApplyPatchModelOutput;
 use pretty_assertions::assert_eq;
 use std::fs;
 
+use codex_core::config::types::ApplyPatchVerify;
 use codex_core::features::Feature;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::EventMsg;
@@ -123,6 +124,86 @@ D delete.txt
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[test_case(ApplyPatchModelOutput::Freeform)]
+#[test_case(ApplyPatchModelOutput::Function)]
+async fn apply_patch_cli_reports_failed_verification_with_applied_changes(
+    output_type: ApplyPatchModelOutput,
+) -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let harness = apply_patch_harness_with(|builder| {
+        builder.with_model("gpt-5.1").with_config(|config| {
+            config.apply_patch_verify = Some(ApplyPatchVerify {
+                command: "grep -q ready status.txt".to_string(),
+                timeout_ms: 10_000,
+                max_retries: 3,
+            });
+        })
+    })
+    .await?;
+    fs::write(harness.path("notes.txt"), "one\ntwo\n")?;
+
+    let patch = "*** Begin Patch\n*** Add File: status.txt\n+broken\n*** Update File: notes.txt\n@@\n one\n-two\n+three\n*** End Patch";
+    let call_id = "apply-verify";
+    mount_apply_patch(&harness, call_id, patch, "done", output_type).await;
+
+    harness.submit("apply the patch").await?;
+
+    let out = harness.apply_patch_output(call_id, output_type).await;
+    let expected = r#"(?s)Success\. Updated the following files:\\nA status\.txt\\nM notes\.txt\\n.*
+Verification `grep -q ready status\.txt` failed; the patch is still applied:
+.*"exit_code":1.*
+Changes made by this patch:
+modified notes\.txt
+@@ -1,2 \+1,2 @@
+ one
+-two
+\+three
+
+added status\.txt
+\+broken
+
+Fix the failure and apply a corrected patch \(retry 1 of 3\)\.$"#;
+    assert_regex_match(expected, &out);
+    assert_eq!(fs::read_to_string(harness.path("status.txt"))?, "broken\n");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn apply_patch_cli_stops_retrying_once_verification_retries_are_spent() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let harness = apply_patch_harness_with(|builder| {
+        builder.with_model("gpt-5.1").with_config(|config| {
+            config.apply_patch_verify = Some(ApplyPatchVerify {
+                command: "false".to_string(),
+                timeout_ms: 10_000,
+                max_retries: 0,
+            });
+        })
+    })
+    .await?;
+
+    let patch = "*** Begin Patch\n*** Add File: status.txt\n+broken\n*** End Patch";
+    let call_id = "apply-verify-exhausted";
+    let output_type = ApplyPatchModelOutput::Function;
+    mount_apply_patch(&harness, call_id, patch, "done", output_type).await;
+
+    harness.submit("apply the patch").await?;
+
+    let out = harness.apply_patch_output(call_id, output_type).await;
+    assert!(
+        out.ends_with(
+            "No retries left (0 allowed). Stop patching and tell the user what still fails."
+        ),
+        "{out}"
+    );
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 #[test_case(ApplyPatchModelOutput::Freeform)]
 #[test_case(ApplyPatchModelOutput::Function)]
@@ -1250,3 +1331,73 @@ async fn apply_patch_change_context_disambiguates_target(
     assert_eq!(contents, "fn a\nx=10\ny=2\nfn b\nx=11\ny=20\n");
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn session_diff_accumulates_across_turns() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let harness = apply_patch_harness().await?;
+    let test = harness.test();
+    let codex = test.codex.clone();
+    let cwd = test.cwd.clone();
+
+    let patch1 = "*** Begin Patch\n*** Add File: session/a.txt\n+first\n*** End Patch";
+    let patch2 = "*** Begin Patch\n*** Add File: session/b.txt\n+second\n*** End Patch";
+    mount_sse_sequence(
+        harness.server(),
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_apply_patch_function_call("session-1", patch1),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "done"),
+                ev_completed("resp-2"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-3"),
+                ev_apply_patch_function_call("session-2", patch2),
+                ev_completed("resp-3"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-2", "done"),
+                ev_completed("resp-4"),
+            ]),
+        ],
+    )
+    .await;
+
+    let model = test.session_configured.model.clone();
+    let mut session_diffs = Vec::new();
+    for text in ["first turn", "second turn"] {
+        codex
+            .submit(Op::UserTurn {
+                items: vec![UserInput::Text { text: text.into() }],
+                final_output_json_schema: None,
+                cwd: cwd.path().to_path_buf(),
+                approval_policy: AskForApproval::Never,
+                sandbox_policy: SandboxPolicy::DangerFullAccess,
+                model: model.clone(),
+                effort: None,
+                summary: ReasoningSummary::Auto,
+            })
+            .await?;
+        wait_for_event(&codex, |event| match event {
+            EventMsg::SessionDiff(ev) => {
+                session_diffs.push(ev.unified_diff.clone());
+                false
+            }
+            EventMsg::TaskComplete(_) => true,
+            _ => false,
+        })
+        .await;
+    }
+
+    assert_eq!(session_diffs.len(), 2, "expected one SessionDiff per turn");
+    assert!(session_diffs[0].contains("session/a.txt"));
+    assert!(!session_diffs[0].contains("session/b.txt"));
+    assert!(session_diffs[1].contains("session/a.txt"));
+    assert!(session_diffs[1].contains("session/b.txt"));
+    Ok(())
+}
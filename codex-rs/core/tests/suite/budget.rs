@@ -0,0 +1,67 @@
+use anyhow::Result;
+use codex_core::protocol::BudgetExceededEvent;
+use codex_core::protocol::BudgetLimit;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn turn_budget_stops_further_model_requests() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let mock = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_assistant_message("m1", "first reply"),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.budget.max_turns = Some(1);
+        })
+        .build(&server)
+        .await?;
+
+    test.submit_turn("first").await?;
+
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "second".into(),
+            }],
+        })
+        .await?;
+    let exceeded = wait_for_event_match(&test.codex, |ev| match ev {
+        EventMsg::BudgetExceeded(ev) => Some(ev.clone()),
+        _ => None,
+    })
+    .await;
+    wait_for_event(&test.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    assert_eq!(
+        exceeded,
+        BudgetExceededEvent {
+            limit: BudgetLimit::Turns,
+            configured: 1.0,
+            observed: 1.0,
+            message: "Turn budget exhausted: 1 of 1 model requests issued.".to_string(),
+        }
+    );
+    assert_eq!(mock.requests().len(), 1);
+
+    Ok(())
+}
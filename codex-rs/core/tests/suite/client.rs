@@ -1115,7 +1115,15 @@ async fn azure_responses_request_includes_store_and_reasoning_ids() {
         request_max_retries: Some(0),
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
+        retry_rate_limited: None,
+        request_max_retry_time_ms: None,
         requires_openai_auth: false,
+        safety_settings: None,
+        local_quirks: None,
+        http: None,
+        azure: None,
+        bedrock: None,
+        persist_reasoning: None,
     };
 
     let codex_home = TempDir::new().unwrap();
@@ -1309,6 +1317,11 @@ async fn token_count_includes_rate_limits_snapshot() {
                 },
                 "credits": null,
                 "plan_type": null
+            },
+            // The default model is priced, so spend is reported from the start.
+            "spend": {
+                "session_usd": 0.0,
+                "today_usd": 0.0
             }
         })
     );
@@ -1358,6 +1371,11 @@ async fn token_count_includes_rate_limits_snapshot() {
                 },
                 "credits": null,
                 "plan_type": null
+            },
+            // 123 uncached input tokens at the built-in gpt-5 price of $1.25/M.
+            "spend": {
+                "session_usd": 0.00015375,
+                "today_usd": 0.00015375
             }
         })
     );
@@ -1611,7 +1629,15 @@ async fn azure_overrides_assign_properties_used_for_responses_url() {
         request_max_retries: None,
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
+        retry_rate_limited: None,
+        request_max_retry_time_ms: None,
         requires_openai_auth: false,
+        safety_settings: None,
+        local_quirks: None,
+        http: None,
+        azure: None,
+        bedrock: None,
+        persist_reasoning: None,
     };
 
     // Init session
@@ -1693,7 +1719,15 @@ async fn env_var_overrides_loaded_auth() {
         request_max_retries: None,
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
+        retry_rate_limited: None,
+        request_max_retry_time_ms: None,
         requires_openai_auth: false,
+        safety_settings: None,
+        local_quirks: None,
+        http: None,
+        azure: None,
+        bedrock: None,
+        persist_reasoning: None,
     };
 
     // Init session
@@ -0,0 +1,53 @@
+#![cfg(not(target_os = "windows"))]
+
+use codex_core::features::Feature;
+use core_test_support::responses::mount_function_call_agent_response;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn edit_file_replaces_a_unique_match() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::FileEditTools);
+        })
+        .build(&server)
+        .await?;
+
+    let file_path = test.cwd.path().join("sample.txt");
+    std::fs::write(&file_path, "fn main() {\n    println!(\"hi\");\n}\n")?;
+
+    let call_id = "edit-file-call";
+    let arguments = json!({
+        "file_path": file_path.to_string_lossy(),
+        "old_string": "\"hi\"",
+        "new_string": "\"hello\"",
+    })
+    .to_string();
+
+    let mocks = mount_function_call_agent_response(&server, call_id, &arguments, "edit_file").await;
+
+    test.submit_turn("please update sample.txt").await?;
+
+    let req = mocks.completion.single_request();
+    let (output_text, _) = req
+        .function_call_output_content_and_success(call_id)
+        .expect("output present");
+    let output_text = output_text.expect("output text present");
+    assert!(
+        output_text.contains("Success. Updated the following files:\\nM sample.txt\\n"),
+        "unexpected output: {output_text}"
+    );
+    assert_eq!(
+        std::fs::read_to_string(&file_path)?,
+        "fn main() {\n    println!(\"hello\");\n}\n"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use codex_core::WireApi;
+use core_test_support::responses::mount_sse_sequence_matching;
+use core_test_support::responses::sse_data;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use wiremock::matchers::path_regex;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn gemini_provider_round_trips_function_calls() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let plan_args = json!({"plan": [{"step": "Write tests", "status": "in_progress"}]});
+    let tool_turn = sse_data(vec![json!({
+        "responseId": "resp-1",
+        "candidates": [{
+            "content": {"role": "model", "parts": [
+                {"functionCall": {"name": "update_plan", "args": plan_args}, "thoughtSignature": "sig-1"},
+            ]},
+            "finishReason": "STOP",
+        }],
+        "usageMetadata": {"promptTokenCount": 50, "candidatesTokenCount": 12, "totalTokenCount": 62},
+    })]);
+    let final_turn = sse_data(vec![json!({
+        "responseId": "resp-2",
+        "candidates": [{
+            "content": {"role": "model", "parts": [{"text": "Plan recorded."}]},
+            "finishReason": "STOP",
+        }],
+    })]);
+    let response_mock = mount_sse_sequence_matching(
+        &server,
+        path_regex(r"^/v1/models/[^/]+:streamGenerateContent$"),
+        vec![tool_turn, final_turn],
+    )
+    .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.model_provider.wire_api = WireApi::Gemini;
+            config.model_provider.experimental_bearer_token = Some("gemini-key".to_string());
+            config.model_provider.safety_settings = Some(HashMap::from([(
+                "HARM_CATEGORY_DANGEROUS_CONTENT".to_string(),
+                "BLOCK_ONLY_HIGH".to_string(),
+            )]));
+        })
+        .build(&server)
+        .await?;
+
+    test.submit_turn("make a plan").await?;
+
+    let requests = response_mock.requests();
+    assert_eq!(requests.len(), 2);
+
+    let first = &requests[0];
+    assert_eq!(first.query_param("alt").as_deref(), Some("sse"));
+    assert_eq!(
+        first.header("x-goog-api-key").as_deref(),
+        Some("gemini-key")
+    );
+    let first_body = first.body_json();
+    assert_eq!(
+        first_body["safetySettings"],
+        json!([{"category": "HARM_CATEGORY_DANGEROUS_CONTENT", "threshold": "BLOCK_ONLY_HIGH"}])
+    );
+    assert!(
+        first_body["systemInstruction"]["parts"][0]["text"]
+            .as_str()
+            .is_some_and(|text| !text.is_empty())
+    );
+    let declarations = first_body["tools"][0]["functionDeclarations"]
+        .as_array()
+        .expect("function declarations");
+    assert!(
+        declarations
+            .iter()
+            .any(|declaration| declaration["name"] == "update_plan")
+    );
+
+    let second_body = requests[1].body_json();
+    let contents = second_body["contents"].as_array().expect("contents");
+    let [.., function_call, function_response] = contents.as_slice() else {
+        panic!("expected function call and response contents, got {contents:?}");
+    };
+    assert_eq!(
+        function_call,
+        &json!({
+            "role": "model",
+            "parts": [{
+                "functionCall": {"id": "resp-1-call-1", "name": "update_plan", "args": plan_args},
+                "thoughtSignature": "sig-1",
+            }],
+        })
+    );
+    assert_eq!(
+        function_response,
+        &json!({
+            "role": "user",
+            "parts": [{
+                "functionResponse": {
+                    "id": "resp-1-call-1",
+                    "name": "update_plan",
+                    "response": {"content": "Plan updated"},
+                },
+            }],
+        })
+    );
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+#![cfg(not(target_os = "windows"))]
+
+use anyhow::Result;
+use codex_core::features::Feature;
+use core_test_support::git::init_repo;
+use core_test_support::responses::mount_function_call_agent_response;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn git_status_returns_parsed_changes() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::GitTools);
+        })
+        .build(&server)
+        .await?;
+    init_repo(test.cwd_path())?;
+    std::fs::write(test.cwd_path().join("README.md"), "hello again\n")?;
+    std::fs::write(test.cwd_path().join("notes.txt"), "todo\n")?;
+
+    let call_id = "git-status-call";
+    let mocks = mount_function_call_agent_response(&server, call_id, "{}", "git_status").await;
+
+    test.submit_turn("what changed?").await?;
+
+    let req = mocks.completion.single_request();
+    let (output_text, _) = req
+        .function_call_output_content_and_success(call_id)
+        .expect("output present");
+    let status: Value = serde_json::from_str(&output_text.expect("output text present"))?;
+    assert_eq!(status["branch"], json!("main"));
+    assert_eq!(status["staged"], json!([]));
+    assert_eq!(
+        status["unstaged"],
+        json!([{ "path": "README.md", "status": "modified" }])
+    );
+    assert_eq!(status["untracked"], json!(["notes.txt"]));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn git_commit_refuses_protected_branch() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::GitTools);
+        })
+        .build(&server)
+        .await?;
+    init_repo(test.cwd_path())?;
+    std::fs::write(test.cwd_path().join("README.md"), "hello again\n")?;
+
+    let call_id = "git-commit-call";
+    let arguments = json!({
+        "message": "Update README",
+        "paths": ["README.md"],
+    })
+    .to_string();
+    let mocks =
+        mount_function_call_agent_response(&server, call_id, &arguments, "git_commit").await;
+
+    test.submit_turn("commit the README change").await?;
+
+    let req = mocks.completion.single_request();
+    let (output_text, _) = req
+        .function_call_output_content_and_success(call_id)
+        .expect("output present");
+    assert_eq!(
+        output_text.as_deref(),
+        Some(
+            "refusing to commit on protected branch `main`; create a new branch with git_create_branch instead"
+        )
+    );
+
+    Ok(())
+}
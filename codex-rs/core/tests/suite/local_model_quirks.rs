@@ -0,0 +1,111 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use anyhow::Result;
+use codex_core::LocalModelQuirks;
+use codex_core::WireApi;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use wiremock::Mock;
+use wiremock::Request;
+use wiremock::Respond;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+/// A Chat Completions stream whose whole assistant reply is `content`.
+fn chat_sse(content: &str) -> String {
+    let delta = json!({"choices": [{"delta": {"role": "assistant", "content": content}}]});
+    let finish = json!({"choices": [{"delta": {}, "finish_reason": "stop"}]});
+    format!("data: {delta}\n\ndata: {finish}\n\ndata: [DONE]\n\n")
+}
+
+struct ChatSeqResponder {
+    num_calls: AtomicUsize,
+    bodies: Vec<String>,
+}
+
+impl Respond for ChatSeqResponder {
+    fn respond(&self, _: &Request) -> ResponseTemplate {
+        let idx = self.num_calls.fetch_add(1, Ordering::SeqCst);
+        match self.bodies.get(idx) {
+            Some(body) => {
+                ResponseTemplate::new(200).set_body_raw(body.clone(), "text/event-stream")
+            }
+            None => panic!("no chat response for index {idx}"),
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn emulated_tool_calls_round_trip_through_json_replies() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let plan_args = json!({"plan": [{"step": "Write tests", "status": "in_progress"}]});
+    let tool_turn = chat_sse(
+        &json!({"type": "tool_call", "name": "update_plan", "arguments": plan_args}).to_string(),
+    );
+    let final_turn = chat_sse(&json!({"type": "message", "content": "Plan recorded."}).to_string());
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .respond_with(ChatSeqResponder {
+            num_calls: AtomicUsize::new(0),
+            bodies: vec![tool_turn, final_turn],
+        })
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.model_provider.wire_api = WireApi::Chat;
+            config.model_provider.local_quirks = Some(LocalModelQuirks {
+                emulate_tool_calls: true,
+                detect_context_window: false,
+            });
+        })
+        .build(&server)
+        .await?;
+
+    test.submit_turn("make a plan").await?;
+
+    let requests: Vec<Value> = server
+        .received_requests()
+        .await
+        .expect("recorded requests")
+        .iter()
+        .filter(|request| request.url.path() == "/v1/chat/completions")
+        .map(|request| request.body_json().expect("json body"))
+        .collect();
+    assert_eq!(requests.len(), 2);
+
+    let first = &requests[0];
+    assert_eq!(first.get("tools"), None);
+    assert_eq!(first["response_format"]["type"], json!("json_schema"));
+    let system = first["messages"][0]["content"].as_str().expect("system");
+    assert!(system.contains("## update_plan"));
+
+    let messages = requests[1]["messages"].as_array().expect("messages");
+    let [.., call, output] = messages.as_slice() else {
+        panic!("expected emulated call and output, got {messages:?}");
+    };
+    assert_eq!(call["role"], json!("assistant"));
+    let call: Value = serde_json::from_str(call["content"].as_str().expect("call text"))?;
+    assert_eq!(
+        call,
+        json!({"type": "tool_call", "name": "update_plan", "arguments": plan_args})
+    );
+    assert_eq!(
+        output,
+        &json!({"role": "user", "content": "Output of the `update_plan` tool call:\nPlan updated"})
+    );
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use anyhow::Result;
+use codex_core::features::Feature;
+use codex_core::memory_store::read_entries;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn memory_write_is_searchable_and_injected_into_new_sessions() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let write_args = json!({
+        "text": "Integration tests need `just db-up` first.",
+        "tags": ["testing"],
+    });
+    let search_args = json!({ "query": "integration tests" });
+    let mock = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_function_call("write-call", "memory_write", &write_args.to_string()),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_response_created("resp-2"),
+                ev_function_call("search-call", "memory_search", &search_args.to_string()),
+                ev_completed("resp-2"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "Noted."),
+                ev_completed("resp-3"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-2", "Hello again."),
+                ev_completed("resp-4"),
+            ]),
+        ],
+    )
+    .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::Memory);
+        })
+        .build(&server)
+        .await?;
+
+    test.submit_turn("remember how to run the integration tests")
+        .await?;
+
+    let entries = read_entries(test.codex_home_path())?;
+    assert_eq!(
+        entries
+            .iter()
+            .map(|entry| (entry.text.as_str(), entry.tags.clone()))
+            .collect::<Vec<_>>(),
+        vec![(
+            "Integration tests need `just db-up` first.",
+            vec!["testing".to_string()]
+        )]
+    );
+    let id = &entries[0].id;
+
+    let requests = mock.requests();
+    assert_eq!(
+        requests[1].function_call_output_text("write-call"),
+        Some(format!("Saved memory {id}."))
+    );
+    let search_output = requests[2]
+        .function_call_output_text("search-call")
+        .unwrap_or_default();
+    assert!(
+        search_output.contains(&format!("[{id}]")) && search_output.contains("#testing"),
+        "unexpected search output: {search_output}"
+    );
+
+    // A new session on the same project sees the memory in its instructions.
+    let second = test
+        .conversation_manager
+        .new_conversation(test.config.clone())
+        .await?;
+    second
+        .conversation
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await?;
+    wait_for_event(&second.conversation, |ev| {
+        matches!(ev, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    let requests = mock.requests();
+    let user_texts = requests[3].message_input_texts("user");
+    assert!(
+        user_texts.iter().any(|text| text.contains("## Memories")
+            && text.contains(&format!(
+                "- [{id}] Integration tests need `just db-up` first."
+            ))),
+        "memories missing from instructions: {user_texts:?}"
+    );
+
+    Ok(())
+}
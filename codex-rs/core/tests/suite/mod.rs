@@ -15,10 +15,13 @@ pub static CODEX_ALIASES_TEMP_DIR: TempDir = unsafe {
 
 #[cfg(not(target_os = "windows"))]
 mod abort_tasks;
+mod agents_md_reload;
+mod anthropic_messages;
 mod apply_patch_cli;
 #[cfg(not(target_os = "windows"))]
 mod approvals;
 mod auth_refresh;
+mod budget;
 mod cli_stream;
 mod client;
 mod codex_delegate;
@@ -28,39 +31,57 @@ mod compact_resume_fork;
 mod deprecation_notice;
 mod exec;
 mod exec_policy;
+mod file_edit;
 mod fork_conversation;
+mod gemini_generate_content;
+mod git_tools;
 mod grep_files;
 mod items;
 mod json_result;
 mod list_dir;
 mod list_models;
 mod live_cli;
+mod local_model_quirks;
+mod memory;
+mod model_cassette;
 mod model_overrides;
+mod model_routing;
 mod model_tools;
 mod otel;
+mod pinned_context;
 mod prompt_caching;
+mod pull_requests;
 mod quota_exceeded;
 mod read_file;
+mod redaction;
 mod remote_models;
 mod resume;
 mod resume_warning;
+mod retry_policy;
 mod review;
 mod rmcp_client;
 mod rollout_list_find;
 mod seatbelt;
+mod semantic_search;
 mod shell_command;
 mod shell_serialization;
 mod shell_snapshot;
 mod skills;
+mod spawn_agent;
+mod steer;
 mod stream_error_allows_next_turn;
 mod stream_no_completed;
 mod text_encoding_fix;
+mod tool_call_loop;
 mod tool_harness;
 mod tool_parallelism;
 mod tools;
 mod truncation;
+mod turn_timeout;
 mod undo;
 mod unified_exec;
+mod usage_ledger;
 mod user_notification;
 mod user_shell_cmd;
 mod view_image;
+mod web_tools;
@@ -0,0 +1,113 @@
+use anyhow::Result;
+use codex_api::CassetteMode;
+use codex_core::config::types::ModelCassette;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol_config_types::ReasoningSummary;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+use std::path::Path;
+use std::time::Duration;
+
+async fn run_turn(test: &TestCodex, prompt: &str) -> Result<Option<String>> {
+    test.codex
+        .submit(Op::UserTurn {
+            items: vec![UserInput::Text {
+                text: prompt.into(),
+            }],
+            final_output_json_schema: None,
+            cwd: test.cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: test.session_configured.model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+        })
+        .await?;
+
+    Ok(wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::TaskComplete(done) => Some(done.last_agent_message.clone()),
+        _ => None,
+    })
+    .await)
+}
+
+fn cassette(mode: CassetteMode, path: &Path) -> ModelCassette {
+    ModelCassette {
+        mode,
+        path: path.to_path_buf(),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn recorded_turn_replays_without_the_provider() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let dir = tempfile::tempdir()?;
+    let recorded = dir.path().join("recorded.jsonl");
+    let replayed = dir.path().join("replayed.jsonl");
+
+    let server = start_mock_server().await;
+    let mock = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_assistant_message("m1", "recorded answer"),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+    let record = cassette(CassetteMode::Record, &recorded);
+    let test = test_codex()
+        .with_config(move |config| config.model_cassette = Some(record))
+        .build(&server)
+        .await?;
+    let answer = run_turn(&test, "hello").await?;
+    assert_eq!(answer.as_deref(), Some("recorded answer"));
+    assert_eq!(mock.requests().len(), 1);
+
+    // The exchange is written once the response stream is dropped.
+    let mut contents = String::new();
+    for _ in 0..50 {
+        contents = std::fs::read_to_string(&recorded)?;
+        if !contents.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(contents.lines().count(), 1);
+    assert!(!contents.contains("Bearer"));
+
+    // Replay against a server that has nothing mounted: every model request
+    // must be answered from the cassette.
+    std::fs::copy(&recorded, &replayed)?;
+    let empty_server = start_mock_server().await;
+    let replay = cassette(CassetteMode::Replay, &replayed);
+    let test = test_codex()
+        .with_config(move |config| config.model_cassette = Some(replay))
+        .build(&empty_server)
+        .await?;
+    let answer = run_turn(&test, "hello").await?;
+    assert_eq!(answer.as_deref(), Some("recorded answer"));
+
+    let model_requests = empty_server
+        .received_requests()
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|req| req.url.path().ends_with("/responses"))
+        .count();
+    assert_eq!(model_requests, 0);
+
+    Ok(())
+}
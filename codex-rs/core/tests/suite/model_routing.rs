@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use codex_core::config::types::ModelRouteTarget;
+use codex_core::usage_ledger::read_records;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed_with_tokens;
+use core_test_support::responses::mount_response_once_match;
+use core_test_support::responses::mount_sse_once_match;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::body_partial_json;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn server_error_fails_over_to_next_model_and_records_it() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let primary = mount_response_once_match(
+        &server,
+        body_partial_json(json!({"model": "primary-model"})),
+        ResponseTemplate::new(503),
+    )
+    .await;
+    let backup = mount_sse_once_match(
+        &server,
+        body_partial_json(json!({"model": "backup-model"})),
+        sse(vec![
+            ev_assistant_message("m1", "served by backup"),
+            ev_completed_with_tokens("r1", 10),
+        ]),
+    )
+    .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.model = Some("primary-model".to_string());
+            config.model_provider.request_max_retries = Some(0);
+            config.model_provider.stream_max_retries = Some(0);
+            config
+                .model_providers
+                .insert("backup".to_string(), config.model_provider.clone());
+            config.model_routing.fallbacks = vec![ModelRouteTarget {
+                model: "backup-model".to_string(),
+                provider: Some("backup".to_string()),
+            }];
+        })
+        .build(&server)
+        .await?;
+
+    test.submit_turn("hello").await?;
+
+    assert_eq!(primary.requests().len(), 1);
+    assert_eq!(backup.requests().len(), 1);
+
+    // The ledger is appended in the background; give it a moment to land.
+    let mut records = Vec::new();
+    for _ in 0..50 {
+        records = read_records(test.home.path())?;
+        if !records.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].model, "backup-model");
+
+    Ok(())
+}
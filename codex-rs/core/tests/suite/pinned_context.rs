@@ -0,0 +1,125 @@
+use anyhow::Result;
+use codex_core::features::Feature;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::PinnedContextEntry;
+use codex_core::protocol::PinnedContextItem;
+use codex_core::protocol::PinnedContextUpdatedEvent;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+
+const PINNED_TEXT: &str = "always answer in haiku";
+const PINNED_MESSAGE: &str =
+    "<pinned_context id=\"pin-1\" kind=\"instruction\">\nalways answer in haiku\n</pinned_context>";
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pinned_context_survives_compaction() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let mock = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_assistant_message("m1", "first reply"),
+                ev_completed("r1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("m2", "summary"),
+                ev_completed("r2"),
+            ]),
+            sse(vec![
+                ev_assistant_message("m3", "second reply"),
+                ev_completed("r3"),
+            ]),
+        ],
+    )
+    .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.disable(Feature::RemoteCompaction);
+        })
+        .build(&server)
+        .await?;
+    let codex = test.codex.clone();
+
+    codex
+        .submit(Op::PinContext {
+            item: PinnedContextItem::Instruction {
+                text: PINNED_TEXT.to_string(),
+            },
+        })
+        .await?;
+    let pinned = wait_for_event_match(&codex, |ev| match ev {
+        EventMsg::PinnedContextUpdated(ev) => Some(ev.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(
+        pinned,
+        PinnedContextUpdatedEvent {
+            entries: vec![PinnedContextEntry {
+                id: "pin-1".to_string(),
+                item: PinnedContextItem::Instruction {
+                    text: PINNED_TEXT.to_string(),
+                },
+                tokens: pinned.total_tokens,
+            }],
+            total_tokens: pinned.total_tokens,
+        }
+    );
+
+    test.submit_turn("hello").await?;
+
+    codex.submit(Op::Compact).await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    test.submit_turn("after compaction").await?;
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 3);
+    let first = requests[0].message_input_texts("user");
+    let last = requests[2].message_input_texts("user");
+    assert!(
+        first.iter().any(|text| text == PINNED_MESSAGE),
+        "pinned item missing before compaction: {first:?}"
+    );
+    assert_eq!(
+        last.iter().filter(|text| *text == PINNED_MESSAGE).count(),
+        1,
+        "pinned item should be sent exactly once after compaction: {last:?}"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn unpinning_unknown_id_reports_error() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let test = test_codex().build(&server).await?;
+
+    test.codex
+        .submit(Op::UnpinContext {
+            id: "pin-42".to_string(),
+        })
+        .await?;
+    let message = wait_for_event_match(&test.codex, |ev| match ev {
+        EventMsg::Error(ev) => Some(ev.message.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(message, "no pinned context item with id `pin-42`");
+
+    Ok(())
+}
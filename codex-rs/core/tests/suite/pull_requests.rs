@@ -0,0 +1,174 @@
+#![cfg(not(target_os = "windows"))]
+
+use std::path::Path;
+
+use anyhow::Result;
+use codex_core::features::Feature;
+use core_test_support::git::git;
+use core_test_support::git::init_repo;
+use core_test_support::responses::mount_function_call_agent_response;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::body_json;
+use wiremock::matchers::header;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+use wiremock::matchers::query_param;
+
+/// A repository with a GitHub `origin`, checked out on the `fix-typo` branch.
+fn init_pr_repo(cwd: &Path) -> Result<()> {
+    init_repo(cwd)?;
+    git(
+        cwd,
+        &["remote", "add", "origin", "git@github.com:octo/widgets.git"],
+    )?;
+    git(cwd, &["switch", "-q", "-c", "fix-typo"])
+}
+
+fn pull() -> Value {
+    json!({
+        "number": 7,
+        "title": "Fix typo",
+        "html_url": "https://github.com/octo/widgets/pull/7",
+        "state": "open",
+        "draft": false,
+        "head": {"ref": "fix-typo"},
+        "base": {"ref": "main"},
+    })
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pr_comments_reads_threads_for_current_branch() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let github = MockServer::start().await;
+    let github_url = github.uri();
+    let test = test_codex()
+        .with_config(move |config| {
+            config.features.enable(Feature::PullRequests);
+            config.pull_requests.github_token = Some("test-token".to_string());
+            config.pull_requests.github_api_url = Some(github_url);
+        })
+        .build(&server)
+        .await?;
+    init_pr_repo(test.cwd_path())?;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/octo/widgets/pulls"))
+        .and(query_param("head", "octo:fix-typo"))
+        .and(header("authorization", "Bearer test-token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([pull()])))
+        .expect(1)
+        .mount(&github)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/octo/widgets/pulls/7"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(pull()))
+        .mount(&github)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/octo/widgets/issues/7/comments"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {"id": 11, "user": {"login": "maintainer"}, "body": "Thanks!", "created_at": "2024-01-01T00:00:00Z"}
+        ])))
+        .mount(&github)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/octo/widgets/pulls/7/reviews"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .mount(&github)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/octo/widgets/pulls/7/comments"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {"id": 21, "path": "README.md", "line": 3, "user": {"login": "maintainer"}, "body": "Also fix 'teh' here", "created_at": "2024-01-01T00:01:00Z"}
+        ])))
+        .mount(&github)
+        .await;
+
+    let call_id = "pr-comments-call";
+    let mocks = mount_function_call_agent_response(&server, call_id, "{}", "pr_comments").await;
+
+    test.submit_turn("address the review feedback").await?;
+
+    let req = mocks.completion.single_request();
+    let (output_text, _) = req
+        .function_call_output_content_and_success(call_id)
+        .expect("output present");
+    let conversation: Value = serde_json::from_str(&output_text.expect("output text present"))?;
+    assert_eq!(conversation["pull_request"]["number"], json!(7));
+    assert_eq!(
+        conversation["comments"],
+        json!([{"id": "11", "author": "maintainer", "body": "Thanks!", "created_at": "2024-01-01T00:00:00Z"}])
+    );
+    assert_eq!(
+        conversation["threads"],
+        json!([{
+            "id": "21",
+            "path": "README.md",
+            "line": 3,
+            "comments": [{"id": "21", "author": "maintainer", "body": "Also fix 'teh' here", "created_at": "2024-01-01T00:01:00Z"}],
+        }])
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pr_reply_posts_in_review_thread() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let github = MockServer::start().await;
+    let github_url = github.uri();
+    let test = test_codex()
+        .with_config(move |config| {
+            config.features.enable(Feature::PullRequests);
+            config.pull_requests.github_token = Some("test-token".to_string());
+            config.pull_requests.github_api_url = Some(github_url);
+        })
+        .build(&server)
+        .await?;
+    init_pr_repo(test.cwd_path())?;
+
+    Mock::given(method("POST"))
+        .and(path("/repos/octo/widgets/pulls/7/comments/21/replies"))
+        .and(body_json(json!({"body": "Fixed in the latest commit."})))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+            "id": 22,
+            "user": {"login": "codex"},
+            "body": "Fixed in the latest commit.",
+            "created_at": "2024-01-01T00:02:00Z",
+        })))
+        .expect(1)
+        .mount(&github)
+        .await;
+
+    let call_id = "pr-reply-call";
+    let arguments = json!({
+        "number": 7,
+        "thread_id": "21",
+        "body": "Fixed in the latest commit.",
+    })
+    .to_string();
+    let mocks = mount_function_call_agent_response(&server, call_id, &arguments, "pr_reply").await;
+
+    test.submit_turn("reply to the review").await?;
+
+    let req = mocks.completion.single_request();
+    let (output_text, _) = req
+        .function_call_output_content_and_success(call_id)
+        .expect("output present");
+    let comment: Value = serde_json::from_str(&output_text.expect("output text present"))?;
+    assert_eq!(comment["id"], json!("22"));
+
+    Ok(())
+}
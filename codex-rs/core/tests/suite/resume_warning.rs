@@ -75,3 +75,48 @@ async fn emits_warning_when_resumed_model_differs() {
     // The warning is emitted during initialization, so a short sleep is sufficient.
     tokio::time::sleep(Duration::from_millis(50)).await;
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn warns_and_notes_context_when_cwd_drifted() {
+    let home = TempDir::new().expect("tempdir");
+    let mut config = load_default_config_for_test(&home).await;
+    config.model = Some("current-model".to_string());
+
+    let rollout_path = home.path().join("rollout.jsonl");
+    std::fs::write(&rollout_path, "").expect("create rollout placeholder");
+
+    let mut initial_history = resume_history(&config, "current-model", &rollout_path);
+    let recorded_cwd = home.path().join("elsewhere");
+    if let InitialHistory::Resumed(resumed) = &mut initial_history
+        && let Some(RolloutItem::TurnContext(ctx)) = resumed.history.first_mut()
+    {
+        ctx.cwd = recorded_cwd.clone();
+    }
+
+    let conversation_manager = ConversationManager::with_models_provider(
+        CodexAuth::from_api_key("test"),
+        config.model_provider.clone(),
+    );
+    let auth_manager = AuthManager::from_auth_for_testing(CodexAuth::from_api_key("test"));
+    let current_cwd = config.cwd.clone();
+
+    let NewConversation { conversation, .. } = conversation_manager
+        .resume_conversation_with_history(config, initial_history, auth_manager)
+        .await
+        .expect("resume conversation");
+
+    let warning = wait_for_event(&conversation, |ev| matches!(ev, EventMsg::Warning(_))).await;
+    let EventMsg::Warning(WarningEvent { message }) = warning else {
+        panic!("expected warning event");
+    };
+    assert_eq!(
+        message,
+        format!(
+            "The environment changed since this session was last active:\n- working directory changed from {} to {}",
+            recorded_cwd.display(),
+            current_cwd.display()
+        )
+    );
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+}
@@ -0,0 +1,114 @@
+use anyhow::Result;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::RetryScheduledEvent;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::mount_response_once;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use wiremock::ResponseTemplate;
+
+async fn submit(codex: &codex_core::CodexConversation, text: &str) -> Result<()> {
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text { text: text.into() }],
+        })
+        .await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn rate_limited_request_waits_for_retry_after_and_reports_it() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let limited = mount_response_once(
+        &server,
+        ResponseTemplate::new(429)
+            .insert_header("retry-after", "0")
+            .set_body_json(json!({"error": {"type": "rate_limit_exceeded"}})),
+    )
+    .await;
+    let served = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_assistant_message("m1", "after the wait"),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+
+    let test = test_codex()
+        .with_config(|config| config.model_provider.request_max_retries = Some(2))
+        .build(&server)
+        .await?;
+    submit(&test.codex, "hello").await?;
+
+    let retry = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::RetryScheduled(ev) => Some(ev.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(
+        retry,
+        RetryScheduledEvent {
+            retry: 1,
+            max_retries: 2,
+            delay_ms: 0,
+            http_status_code: Some(429),
+            message: "rate limited, retrying in 0ms".to_string(),
+        }
+    );
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TaskComplete(_))
+    })
+    .await;
+
+    assert_eq!(limited.requests().len(), 1);
+    assert_eq!(served.requests().len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn exhausted_quota_is_not_retried() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let quota = mount_response_once(
+        &server,
+        ResponseTemplate::new(429)
+            .insert_header("retry-after", "0")
+            .set_body_json(json!({
+                "error": {"type": "insufficient_quota", "code": "insufficient_quota"}
+            })),
+    )
+    .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.model_provider.request_max_retries = Some(2);
+            config.model_provider.stream_max_retries = Some(0);
+        })
+        .build(&server)
+        .await?;
+    submit(&test.codex, "hello").await?;
+
+    let first = wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::RetryScheduled(_) | EventMsg::Error(_))
+    })
+    .await;
+    assert!(matches!(first, EventMsg::Error(_)), "got {first:?}");
+    assert_eq!(quota.requests().len(), 1);
+
+    Ok(())
+}
@@ -481,8 +481,8 @@ async fn stdio_image_completions_round_trip() -> anyhow::Result<()> {
 
     wait_for_event(&fixture.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
 
-    // Chat Completions assertion: the second POST should include a tool role message
-    // with an array `content` containing an item with the expected data URL.
+    // Chat Completions assertion: `tool` messages only carry text, so the image
+    // follows the tool message in a user message.
     let all_requests = server.received_requests().await.expect("requests captured");
     let requests: Vec<_> = all_requests
         .iter()
@@ -508,9 +508,23 @@ async fn stdio_image_completions_round_trip() -> anyhow::Result<()> {
         json!({
             "role": "tool",
             "tool_call_id": call_id,
-            "content": [{"type": "image_url", "image_url": {"url": OPENAI_PNG}}]
+            "content": "(image output attached in the next message)"
         })
     );
+    let tool_index = messages
+        .iter()
+        .position(|m| m == &tool_msg)
+        .expect("tool message index");
+    assert_eq!(
+        messages.get(tool_index + 1),
+        Some(&json!({
+            "role": "user",
+            "content": [
+                {"type": "text", "text": format!("Images returned by tool call {call_id}:")},
+                {"type": "image_url", "image_url": {"url": OPENAI_PNG}}
+            ]
+        }))
+    );
 
     Ok(())
 }
@@ -0,0 +1,217 @@
+use anyhow::Result;
+use codex_core::config::types::EmbeddingProvider;
+use codex_core::config::types::EmbeddingsConfig;
+use codex_core::features::Feature;
+use codex_core::semantic_index::SEMANTIC_INDEX_PATH;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::mount_function_call_agent_response;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::Request;
+use wiremock::Respond;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+/// Embeds each input as one dimension per keyword it mentions, plus a
+/// constant dimension for inputs that mention none.
+struct KeywordEmbeddings;
+
+impl Respond for KeywordEmbeddings {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let body: Value = serde_json::from_slice(&request.body).expect("json body");
+        let embeddings: Vec<Vec<f32>> = body["input"]
+            .as_array()
+            .expect("input array")
+            .iter()
+            .map(|input| {
+                let text = input.as_str().expect("input text").to_ascii_lowercase();
+                let invoice = text.contains("invoice");
+                let token = text.contains("token");
+                [invoice, token, !invoice && !token]
+                    .map(|hit| if hit { 1.0 } else { 0.0 })
+                    .to_vec()
+            })
+            .collect();
+        ResponseTemplate::new(200).set_body_json(json!({ "embeddings": embeddings }))
+    }
+}
+
+async fn start_embeddings_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/embed"))
+        .respond_with(KeywordEmbeddings)
+        .mount(&server)
+        .await;
+    server
+}
+
+fn embedded_inputs(server_requests: &[Request]) -> Vec<String> {
+    server_requests
+        .iter()
+        .flat_map(|request| {
+            let body: Value = serde_json::from_slice(&request.body).expect("json body");
+            body["input"]
+                .as_array()
+                .expect("input array")
+                .iter()
+                .map(|input| input.as_str().expect("input text").to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn relevant_chunks_are_retrieved_into_context() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let embeddings = start_embeddings_server().await;
+    let base_url = embeddings.uri();
+    let test = test_codex()
+        .with_config(move |config| {
+            config.features.enable(Feature::SemanticSearch);
+            config.embeddings = Some(EmbeddingsConfig {
+                provider: EmbeddingProvider::Ollama {
+                    model: "nomic-embed-text".to_string(),
+                    base_url: Some(base_url),
+                },
+                auto_retrieve: true,
+                max_results: 3,
+                retrieval_max_tokens: 2_000,
+            });
+        })
+        .build(&server)
+        .await?;
+    std::fs::write(
+        test.cwd_path().join("billing.rs"),
+        "fn invoice_total(lines: &[Line]) -> u64 {\n    lines.iter().map(|l| l.amount).sum()\n}\n",
+    )?;
+    std::fs::write(
+        test.cwd_path().join("lexer.rs"),
+        "fn next_token(input: &str) -> Token {\n    todo!()\n}\n",
+    )?;
+
+    let mock = mount_sse_once(
+        &server,
+        sse(vec![
+            ev_assistant_message("m1", "it sums the lines"),
+            ev_completed("r1"),
+        ]),
+    )
+    .await;
+    test.submit_turn("how is an invoice total computed?")
+        .await?;
+
+    let retrieved: Vec<String> = mock
+        .single_request()
+        .message_input_texts("user")
+        .into_iter()
+        .filter(|text| text.starts_with("<retrieved_context>"))
+        .collect();
+    assert_eq!(retrieved.len(), 1);
+    assert!(
+        retrieved[0].contains(
+            "billing.rs:1-3\nfn invoice_total(lines: &[Line]) -> u64 {\n    lines.iter().map(|l| l.amount).sum()\n}"
+        ),
+        "unexpected retrieved context: {}",
+        retrieved[0]
+    );
+    assert!(!retrieved[0].contains("lexer.rs"));
+    assert!(test.cwd_path().join(SEMANTIC_INDEX_PATH).exists());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn semantic_search_reindexes_only_changed_files() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let embeddings = start_embeddings_server().await;
+    let base_url = embeddings.uri();
+    let test = test_codex()
+        .with_config(move |config| {
+            config.features.enable(Feature::SemanticSearch);
+            config.embeddings = Some(EmbeddingsConfig {
+                provider: EmbeddingProvider::Ollama {
+                    model: "nomic-embed-text".to_string(),
+                    base_url: Some(base_url),
+                },
+                auto_retrieve: false,
+                max_results: 1,
+                retrieval_max_tokens: 2_000,
+            });
+        })
+        .build(&server)
+        .await?;
+    std::fs::write(
+        test.cwd_path().join("billing.rs"),
+        "fn invoice_total() {}\n",
+    )?;
+    std::fs::write(test.cwd_path().join("notes.md"), "meeting notes\n")?;
+
+    let call_id = "semantic-search-1";
+    let arguments = json!({ "query": "invoice totals" }).to_string();
+    let mocks =
+        mount_function_call_agent_response(&server, call_id, &arguments, "semantic_search").await;
+    test.submit_turn("find the invoice code").await?;
+
+    let (output, _) = mocks
+        .completion
+        .single_request()
+        .function_call_output_content_and_success(call_id)
+        .expect("output present");
+    assert_eq!(
+        output.expect("output text present"),
+        "billing.rs:1-1\nfn invoice_total() {}"
+    );
+
+    let before = embeddings
+        .received_requests()
+        .await
+        .expect("recorded requests")
+        .len();
+    std::fs::write(test.cwd_path().join("lexer.rs"), "fn next_token() {}\n")?;
+
+    let call_id = "semantic-search-2";
+    let arguments = json!({ "query": "token", "max_results": 1 }).to_string();
+    let mocks =
+        mount_function_call_agent_response(&server, call_id, &arguments, "semantic_search").await;
+    test.submit_turn("find the lexer").await?;
+
+    let (output, _) = mocks
+        .completion
+        .single_request()
+        .function_call_output_content_and_success(call_id)
+        .expect("output present");
+    assert_eq!(
+        output.expect("output text present"),
+        "lexer.rs:1-1\nfn next_token() {}"
+    );
+
+    // Only the new file and the query were embedded the second time.
+    let requests = embeddings
+        .received_requests()
+        .await
+        .expect("recorded requests");
+    assert_eq!(
+        embedded_inputs(&requests[before..]),
+        vec![
+            "lexer.rs\nfn next_token() {}".to_string(),
+            "token".to_string()
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,200 @@
+use anyhow::Result;
+use codex_core::features::Feature;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::SubAgentEvent;
+use codex_protocol::user_input::UserInput;
+use core_test_support::git::init_repo;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn spawn_agent_returns_child_summary_as_tool_output() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let call_id = "spawn-call";
+    let args = json!({
+        "task": "Count the TODOs in src/",
+        "context": "Only look at Rust files.",
+        "tools": ["update_plan"],
+        "sandbox": "read-only",
+    });
+    let mock = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("parent-1"),
+                ev_function_call(call_id, "spawn_agent", &args.to_string()),
+                ev_completed("parent-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("child-msg", "Found 3 TODOs."),
+                ev_completed("child-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("parent-msg", "The child found 3 TODOs."),
+                ev_completed("parent-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::SubAgents);
+        })
+        .build(&server)
+        .await?;
+
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "delegate the TODO count".into(),
+            }],
+        })
+        .await?;
+
+    let mut child_events = Vec::new();
+    wait_for_event(&test.codex, |ev| match ev {
+        EventMsg::SubAgent(SubAgentEvent { agent_id, msg }) => {
+            child_events.push((agent_id.clone(), msg.clone()));
+            false
+        }
+        EventMsg::TaskComplete(_) => true,
+        _ => false,
+    })
+    .await;
+    assert!(
+        child_events
+            .iter()
+            .all(|(agent_id, _)| agent_id.starts_with("agent-")),
+        "sub-agent events must carry an agent id: {child_events:?}"
+    );
+    assert!(
+        child_events
+            .iter()
+            .any(|(_, msg)| matches!(msg.as_ref(), EventMsg::TaskComplete(_))),
+        "expected the child's TaskComplete to be forwarded: {child_events:?}"
+    );
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 3);
+
+    let child = &requests[1];
+    assert_eq!(
+        child.message_input_texts("user").last().cloned(),
+        Some("Count the TODOs in src/\n\nContext:\nOnly look at Rust files.".to_string())
+    );
+    let child_tools = child.body_json()["tools"]
+        .as_array()
+        .expect("tools array")
+        .iter()
+        .filter_map(|tool| tool["name"].as_str().map(str::to_string))
+        .collect::<Vec<_>>();
+    assert_eq!(child_tools, vec!["update_plan".to_string()]);
+
+    assert_eq!(
+        requests[2].function_call_output_text(call_id),
+        Some("Found 3 TODOs.".to_string())
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn spawn_agents_runs_each_task_in_a_removed_worktree() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let call_id = "spawn-many";
+    let args = json!({
+        "tasks": ["Approach A", "Approach B"],
+        "tools": ["update_plan"],
+    });
+    // Both children receive the same reply, so the order they hit the server
+    // in does not matter.
+    let child_reply = || {
+        sse(vec![
+            ev_assistant_message("child-msg", "Looked around."),
+            ev_completed("child"),
+        ])
+    };
+    let mock = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("parent-1"),
+                ev_function_call(call_id, "spawn_agents", &args.to_string()),
+                ev_completed("parent-1"),
+            ]),
+            child_reply(),
+            child_reply(),
+            sse(vec![
+                ev_assistant_message("parent-msg", "Neither agent changed anything."),
+                ev_completed("parent-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::SubAgents);
+        })
+        .build(&server)
+        .await?;
+    init_repo(test.cwd_path())?;
+
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "try two approaches".into(),
+            }],
+        })
+        .await?;
+    wait_for_event(&test.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 4);
+    let mut child_prompts = requests[1..3]
+        .iter()
+        .filter_map(|request| request.message_input_texts("user").last().cloned())
+        .collect::<Vec<_>>();
+    child_prompts.sort();
+    assert_eq!(
+        child_prompts,
+        vec!["Approach A".to_string(), "Approach B".to_string()]
+    );
+
+    let output = requests[3]
+        .function_call_output_text(call_id)
+        .expect("spawn_agents output");
+    assert!(
+        output.contains("(completed)\nTask: Approach A\nSummary: Looked around.\nChanges: none"),
+        "unexpected output: {output}"
+    );
+    assert!(
+        output.contains("(completed)\nTask: Approach B\nSummary: Looked around.\nChanges: none"),
+        "unexpected output: {output}"
+    );
+
+    let worktrees = test
+        .codex_home_path()
+        .join("worktrees")
+        .join(test.session_configured.session_id.to_string());
+    let leftover = std::fs::read_dir(&worktrees)?.count();
+    assert_eq!(leftover, 0, "worktrees were not cleaned up");
+
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_message_item_added;
+use core_test_support::responses::ev_output_text_delta;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::sse;
+use core_test_support::skip_if_no_network;
+use core_test_support::streaming_sse::StreamingSseChunk;
+use core_test_support::streaming_sse::start_streaming_sse_server;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+fn message_texts(body: &Value) -> Vec<(String, String)> {
+    body["input"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|item| item["type"] == "message")
+        .filter_map(|item| {
+            let role = item["role"].as_str()?.to_string();
+            let text = item["content"]
+                .as_array()?
+                .iter()
+                .filter_map(|content| content["text"].as_str())
+                .collect::<String>();
+            Some((role, text))
+        })
+        .collect()
+}
+
+/// Steering mid-stream cancels the request, keeps the partial answer marked
+/// as interrupted, and restarts the same turn with the user's correction.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn steer_restarts_the_request_with_partial_output_in_history() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let partial = sse(vec![
+        ev_response_created("resp-1"),
+        ev_message_item_added("msg-1", ""),
+        ev_output_text_delta("Rewriting the parser with regexes"),
+    ]);
+    let rest = sse(vec![ev_completed("resp-1")]);
+    let restarted = sse(vec![
+        ev_response_created("resp-2"),
+        ev_assistant_message("msg-2", "Switching to nom."),
+        ev_completed("resp-2"),
+    ]);
+
+    // Never released: the first response stays open until the steer cancels it.
+    let (_rest_gate_tx, rest_gate_rx) = oneshot::channel();
+    let (server, _completions) = start_streaming_sse_server(vec![
+        vec![
+            StreamingSseChunk {
+                gate: None,
+                body: partial,
+            },
+            StreamingSseChunk {
+                gate: Some(rest_gate_rx),
+                body: rest,
+            },
+        ],
+        vec![StreamingSseChunk {
+            gate: None,
+            body: restarted,
+        }],
+    ])
+    .await;
+
+    let test = test_codex()
+        .with_model("gpt-5.1")
+        .build_with_streaming_server(&server)
+        .await?;
+    let codex = test.codex.clone();
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "rewrite the parser".into(),
+            }],
+        })
+        .await?;
+    wait_for_event(&codex, |ev| {
+        matches!(ev, EventMsg::AgentMessageContentDelta(_))
+    })
+    .await;
+
+    codex
+        .submit(Op::Steer {
+            items: vec![UserInput::Text {
+                text: "use nom instead".into(),
+            }],
+        })
+        .await?;
+
+    let mut aborted = false;
+    let complete = wait_for_event(&codex, |ev| {
+        aborted |= matches!(ev, EventMsg::TurnAborted(_));
+        matches!(ev, EventMsg::TaskComplete(_))
+    })
+    .await;
+    assert!(!aborted, "a steer must not abort the turn");
+    let EventMsg::TaskComplete(complete) = complete else {
+        unreachable!("waited for TaskComplete");
+    };
+    assert_eq!(
+        complete.last_agent_message.as_deref(),
+        Some("Switching to nom.")
+    );
+
+    let bodies = server.responses_request_bodies().await;
+    assert_eq!(bodies.len(), 2);
+    let messages = message_texts(&bodies[1]);
+    let tail = &messages[messages.len() - 3..];
+    assert_eq!(
+        tail,
+        &[
+            ("user".to_string(), "rewrite the parser".to_string()),
+            (
+                "assistant".to_string(),
+                "Rewriting the parser with regexes\n\n[response interrupted by the user]"
+                    .to_string()
+            ),
+            ("user".to_string(), "use nom instead".to_string()),
+        ]
+    );
+
+    server.shutdown().await;
+    Ok(())
+}
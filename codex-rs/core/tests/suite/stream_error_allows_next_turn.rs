@@ -72,7 +72,15 @@ async fn continue_after_stream_error() {
         request_max_retries: Some(1),
         stream_max_retries: Some(1),
         stream_idle_timeout_ms: Some(2_000),
+        retry_rate_limited: None,
+        request_max_retry_time_ms: None,
         requires_openai_auth: false,
+        safety_settings: None,
+        local_quirks: None,
+        http: None,
+        azure: None,
+        bedrock: None,
+        persist_reasoning: None,
     };
 
     let TestCodex { codex, .. } = test_codex()
@@ -0,0 +1,82 @@
+use anyhow::Result;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+
+/// The third identical call in a row is answered with a loop warning instead
+/// of being run, and front-ends hear about it once.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn repeated_identical_tool_calls_are_short_circuited() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let arguments = r#"{"status":"open"}"#;
+    let mut bodies: Vec<String> = (1..=3)
+        .map(|i| {
+            sse(vec![
+                ev_response_created(&format!("resp-{i}")),
+                ev_function_call(&format!("call-{i}"), "list_todos", arguments),
+                ev_completed(&format!("resp-{i}")),
+            ])
+        })
+        .collect();
+    bodies.push(sse(vec![
+        ev_assistant_message("msg-1", "I'll stop and ask."),
+        ev_completed("resp-4"),
+    ]));
+    let mock = mount_sse_sequence(&server, bodies).await;
+
+    let test = test_codex()
+        .with_config(|config| config.tool_call_loop_limit = 3)
+        .build(&server)
+        .await?;
+    let codex = test.codex.clone();
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "what is left to do?".into(),
+            }],
+        })
+        .await?;
+
+    let mut detected = Vec::new();
+    wait_for_event(&codex, |ev| {
+        if let EventMsg::ToolCallLoopDetected(ev) = ev {
+            detected.push((ev.tools.clone(), ev.calls));
+        }
+        matches!(ev, EventMsg::TaskComplete(_))
+    })
+    .await;
+    assert_eq!(detected, vec![(vec!["list_todos".to_string()], 3)]);
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 4);
+    let output = |request: usize, call_id: &str| {
+        requests[request]
+            .function_call_output_text(call_id)
+            .unwrap_or_default()
+    };
+    assert!(
+        !output(2, "call-2").contains("appear to be looping"),
+        "the second call should still run"
+    );
+    let looping = output(3, "call-3");
+    assert!(
+        looping.contains("You appear to be looping: `list_todos` was called 3 times"),
+        "unexpected output: {looping}"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::TurnAbortReason;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_message_item_added;
+use core_test_support::responses::ev_output_text_delta;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::sse;
+use core_test_support::skip_if_no_network;
+use core_test_support::streaming_sse::StreamingSseChunk;
+use core_test_support::streaming_sse::start_streaming_sse_server;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use tokio::sync::oneshot;
+
+/// A response that never finishes is cancelled once `turn_timeout` passes,
+/// and the turn ends with `TurnTimedOut` followed by a timed-out abort.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn stalled_turn_is_cancelled_after_the_timeout() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let started = sse(vec![
+        ev_response_created("resp-1"),
+        ev_message_item_added("msg-1", ""),
+        ev_output_text_delta("Still thinking"),
+    ]);
+    let (_rest_gate_tx, rest_gate_rx) = oneshot::channel();
+    let (server, _completions) = start_streaming_sse_server(vec![vec![
+        StreamingSseChunk {
+            gate: None,
+            body: started,
+        },
+        StreamingSseChunk {
+            gate: Some(rest_gate_rx),
+            body: sse(vec![ev_completed("resp-1")]),
+        },
+    ]])
+    .await;
+
+    let test = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(|config| config.turn_timeout = Some(Duration::from_secs(1)))
+        .build_with_streaming_server(&server)
+        .await?;
+    let codex = test.codex.clone();
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "loop forever".into(),
+            }],
+        })
+        .await?;
+
+    let timed_out = wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnTimedOut(_))).await;
+    let EventMsg::TurnTimedOut(timed_out) = timed_out else {
+        unreachable!("waited for TurnTimedOut");
+    };
+    assert_eq!(timed_out.timeout_secs, 1);
+
+    let aborted = wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnAborted(_))).await;
+    let EventMsg::TurnAborted(aborted) = aborted else {
+        unreachable!("waited for TurnAborted");
+    };
+    assert_eq!(aborted.reason, TurnAbortReason::TimedOut);
+
+    server.shutdown().await;
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use codex_core::config::types::ModelPricing;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::SpendSummary;
+use codex_core::usage_ledger::read_records;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed_with_tokens;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn token_usage_is_priced_and_recorded_in_ledger() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_assistant_message("m1", "done"),
+            ev_completed_with_tokens("r1", 500_000),
+        ]),
+    )
+    .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            let model = config.model.clone().unwrap_or_default();
+            config.model_pricing.clear();
+            config.model_pricing.insert(
+                model,
+                ModelPricing {
+                    input: 2.0,
+                    cached_input: None,
+                    output: 8.0,
+                },
+            );
+        })
+        .build(&server)
+        .await?;
+
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await?;
+
+    let spend = wait_for_event_match(&test.codex, |ev| match ev {
+        EventMsg::TokenCount(ev) if ev.info.is_some() => ev.spend,
+        _ => None,
+    })
+    .await;
+    wait_for_event(&test.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    assert_eq!(
+        spend,
+        SpendSummary {
+            session_usd: 1.0,
+            today_usd: 1.0,
+        }
+    );
+
+    // The ledger is appended in the background; give it a moment to land.
+    let mut records = Vec::new();
+    for _ in 0..50 {
+        records = read_records(test.home.path())?;
+        if !records.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].input_tokens, 500_000);
+    assert_eq!(records[0].cost_usd, Some(1.0));
+
+    Ok(())
+}
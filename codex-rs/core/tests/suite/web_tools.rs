@@ -0,0 +1,264 @@
+#![cfg(not(target_os = "windows"))]
+
+use anyhow::Result;
+use codex_core::config::types::WebSearchProvider;
+use codex_core::config::types::WebSearchProviderConfig;
+use codex_core::features::Feature;
+use codex_core::protocol::SandboxPolicy;
+use core_test_support::responses::mount_function_call_agent_response;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+use wiremock::matchers::query_param;
+
+const ARTICLE: &str = r#"<!doctype html>
+<html><head><title>Release notes</title></head>
+<body>
+  <nav><a href="/">Home</a> | <a href="/blog">Blog</a></nav>
+  <article>
+    <h1>Version 2.0</h1>
+    <p>Adds <strong>streaming</strong> and a <a href="/docs/streaming">guide</a>.</p>
+  </article>
+  <footer>Copyright Example Inc.</footer>
+</body></html>"#;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn fetch_url_returns_article_as_markdown_and_honours_robots() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let site = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /private/\n"),
+        )
+        .expect(1)
+        .mount(&site)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/blog/v2"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(ARTICLE, "text/html; charset=utf-8"))
+        .expect(1)
+        .mount(&site)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/go/notes"))
+        .respond_with(ResponseTemplate::new(302).insert_header("Location", "/private/notes"))
+        .expect(1)
+        .mount(&site)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/private/notes"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("secret"))
+        .expect(0)
+        .mount(&site)
+        .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::WebTools);
+        })
+        .build(&server)
+        .await?;
+
+    let call_id = "fetch-url-call";
+    let url = format!("{}/blog/v2", site.uri());
+    let arguments = json!({ "url": url }).to_string();
+    let mocks = mount_function_call_agent_response(&server, call_id, &arguments, "fetch_url").await;
+    test.submit_turn("read the release notes").await?;
+
+    let (output, _) = mocks
+        .completion
+        .single_request()
+        .function_call_output_content_and_success(call_id)
+        .expect("output present");
+    assert_eq!(
+        output.expect("output text present"),
+        format!(
+            "# Release notes\nURL: {url}\n\n# Version 2.0\n\nAdds **streaming** and a [guide]({}/docs/streaming).",
+            site.uri()
+        )
+    );
+
+    let call_id = "fetch-private-call";
+    let arguments = json!({ "url": format!("{}/private/notes", site.uri()) }).to_string();
+    let mocks = mount_function_call_agent_response(&server, call_id, &arguments, "fetch_url").await;
+    test.submit_turn("read the private notes").await?;
+
+    let (output, _) = mocks
+        .completion
+        .single_request()
+        .function_call_output_content_and_success(call_id)
+        .expect("output present");
+    assert!(
+        output.expect("output text present").contains("robots.txt"),
+        "fetch should be refused by robots.txt"
+    );
+
+    let call_id = "fetch-redirect-call";
+    let arguments = json!({ "url": format!("{}/go/notes", site.uri()) }).to_string();
+    let mocks = mount_function_call_agent_response(&server, call_id, &arguments, "fetch_url").await;
+    test.submit_turn("follow the notes link").await?;
+
+    let (output, _) = mocks
+        .completion
+        .single_request()
+        .function_call_output_content_and_success(call_id)
+        .expect("output present");
+    assert!(
+        output.expect("output text present").contains("robots.txt"),
+        "a redirect into a disallowed path should be refused by robots.txt"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn fetch_url_refuses_redirects_to_ungranted_hosts() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let site = MockServer::start().await;
+    let other = MockServer::start().await;
+    // Same listener, but `localhost` is a different host from the granted
+    // `127.0.0.1`.
+    let other_url = format!("http://localhost:{}/secret", other.address().port());
+    Mock::given(method("GET"))
+        .and(path("/go"))
+        .respond_with(ResponseTemplate::new(302).insert_header("Location", other_url.as_str()))
+        .expect(1)
+        .mount(&site)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("secret"))
+        .expect(0)
+        .mount(&other)
+        .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::WebTools);
+            config.active_project.allowed_network_hosts = vec!["127.0.0.1".to_string()];
+        })
+        .build(&server)
+        .await?;
+
+    let call_id = "fetch-redirect-call";
+    let arguments = json!({ "url": format!("{}/go", site.uri()) }).to_string();
+    let mocks = mount_function_call_agent_response(&server, call_id, &arguments, "fetch_url").await;
+    test.submit_turn_with_policy("follow the link", SandboxPolicy::ReadOnly)
+        .await?;
+
+    let (output, _) = mocks
+        .completion
+        .single_request()
+        .function_call_output_content_and_success(call_id)
+        .expect("output present");
+    let output = output.expect("output text present");
+    assert!(
+        output.contains(&format!(
+            "redirected to {other_url}, whose host has not been granted network access"
+        )),
+        "unexpected output: {output}"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn web_search_uses_configured_searxng_instance() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let searxng = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/search"))
+        .and(query_param("q", "rust async traits"))
+        .and(query_param("format", "json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "results": [
+                {"title": "Async traits", "url": "https://blog.rust-lang.org/async-traits", "content": "Stabilized in 1.75."},
+                {"title": "async-trait", "url": "https://docs.rs/async-trait", "content": "Type erasure for async trait methods."},
+                {"title": "Ignored", "url": "https://example.com/", "content": "over the limit"}
+            ]
+        })))
+        .expect(1)
+        .mount(&searxng)
+        .await;
+
+    let base_url = searxng.uri();
+    let test = test_codex()
+        .with_config(move |config| {
+            config.features.enable(Feature::WebTools);
+            config.web_search_provider = Some(WebSearchProviderConfig {
+                provider: WebSearchProvider::Searxng { base_url },
+                max_results: 2,
+            });
+        })
+        .build(&server)
+        .await?;
+
+    let call_id = "web-search-call";
+    let arguments = json!({ "query": "rust async traits" }).to_string();
+    let mocks =
+        mount_function_call_agent_response(&server, call_id, &arguments, "web_search").await;
+    test.submit_turn("look up async traits").await?;
+
+    let (output, _) = mocks
+        .completion
+        .single_request()
+        .function_call_output_content_and_success(call_id)
+        .expect("output present");
+    assert_eq!(
+        output.expect("output text present"),
+        "1. [Async traits](https://blog.rust-lang.org/async-traits)\n   Stabilized in 1.75.\n2. [async-trait](https://docs.rs/async-trait)\n   Type erasure for async trait methods."
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn web_tools_refuse_without_network_access() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let site = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("hello"))
+        .expect(0)
+        .mount(&site)
+        .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::WebTools);
+        })
+        .build(&server)
+        .await?;
+
+    let call_id = "fetch-offline-call";
+    let arguments = json!({ "url": format!("{}/page", site.uri()) }).to_string();
+    let mocks = mount_function_call_agent_response(&server, call_id, &arguments, "fetch_url").await;
+    test.submit_turn_with_policy("fetch the page", SandboxPolicy::ReadOnly)
+        .await?;
+
+    let (output, _) = mocks
+        .completion
+        .single_request()
+        .function_call_output_content_and_success(call_id)
+        .expect("output present");
+    assert_eq!(
+        output.expect("output text present"),
+        "fetch_url is unavailable: the sandbox policy does not allow network access"
+    );
+
+    Ok(())
+}
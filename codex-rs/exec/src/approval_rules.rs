@@ -0,0 +1,326 @@
+//! Answers approval requests in `codex exec`, where nobody can be prompted.
+//!
+//! `--approval-rules <FILE>` points at a TOML file listing the escalations a
+//! CI job has vetted:
+//!
+//! ```toml
+//! commands = [["cargo", "test"], ["npm", "ci"]]
+//! paths = ["docs/*", "CHANGELOG.md"]
+//! tools = ["apply_patch"]
+//! ```
+//!
+//! A request covered by a rule is approved; anything else aborts the run.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::bail;
+use codex_core::bash::extract_bash_command;
+use codex_core::bash::parse_shell_lc_plain_commands;
+use codex_core::path_utils::glob_subjects;
+use codex_core::protocol::FileChange;
+use codex_core::protocol::ReviewDecision;
+use serde::Deserialize;
+use wildmatch::WildMatch;
+
+const SHELL_TOOL: &str = "shell";
+const APPLY_PATCH_TOOL: &str = "apply_patch";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApprovalRules {
+    /// Argv prefixes of commands that may run with escalated permissions.
+    #[serde(default)]
+    commands: Vec<Vec<String>>,
+    /// Globs, relative to the working directory, of files a patch may touch.
+    /// `*` also matches `/`.
+    #[serde(default)]
+    paths: Vec<String>,
+    /// Tools whose requests are all approved: `shell` for commands and
+    /// `apply_patch` for file changes.
+    #[serde(default)]
+    tools: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleVerdict {
+    Approved { rule: String },
+    Rejected { reason: String },
+}
+
+impl ApprovalRules {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read approval rules {}", path.display()))?;
+        let rules: Self = toml::from_str(&contents)
+            .with_context(|| format!("failed to parse approval rules {}", path.display()))?;
+        if rules.commands.iter().any(Vec::is_empty) {
+            bail!("approval rules: command prefixes must not be empty");
+        }
+        if let Some(tool) = rules
+            .tools
+            .iter()
+            .find(|tool| !matches!(tool.as_str(), SHELL_TOOL | APPLY_PATCH_TOOL))
+        {
+            bail!("approval rules: unknown tool `{tool}`; expected `shell` or `apply_patch`");
+        }
+        Ok(rules)
+    }
+
+    /// Every command in `command`, including each one in a `bash -lc` script,
+    /// must start with an allowed prefix.
+    pub fn check_command(&self, command: &[String]) -> RuleVerdict {
+        if self.tools.iter().any(|tool| tool == SHELL_TOOL) {
+            return RuleVerdict::Approved {
+                rule: format!("tool `{SHELL_TOOL}`"),
+            };
+        }
+        let commands = match parse_shell_lc_plain_commands(command) {
+            Some(commands) => commands,
+            None if extract_bash_command(command).is_some() => {
+                return RuleVerdict::Rejected {
+                    reason: "the shell script is too complex to match against command prefixes"
+                        .to_string(),
+                };
+            }
+            None => vec![command.to_vec()],
+        };
+
+        let mut matched = Vec::new();
+        for command in &commands {
+            let Some(prefix) = self
+                .commands
+                .iter()
+                .find(|prefix| command.starts_with(prefix.as_slice()))
+            else {
+                return RuleVerdict::Rejected {
+                    reason: format!("`{}` matches no command prefix", join(command)),
+                };
+            };
+            let prefix = format!("`{}`", join(prefix));
+            if !matched.contains(&prefix) {
+                matched.push(prefix);
+            }
+        }
+        RuleVerdict::Approved {
+            rule: format!("command prefix {}", matched.join(", ")),
+        }
+    }
+
+    /// Every path the patch writes, and the root it asks to be granted, must
+    /// match a glob once `.` and `..` are folded away.
+    pub fn check_patch(
+        &self,
+        cwd: &Path,
+        changes: &HashMap<PathBuf, FileChange>,
+        grant_root: Option<&Path>,
+    ) -> RuleVerdict {
+        if self.tools.iter().any(|tool| tool == APPLY_PATCH_TOOL) {
+            return RuleVerdict::Approved {
+                rule: format!("tool `{APPLY_PATCH_TOOL}`"),
+            };
+        }
+
+        let mut paths: Vec<&Path> = Vec::new();
+        for (path, change) in changes {
+            paths.push(path);
+            if let FileChange::Update {
+                move_path: Some(move_path),
+                ..
+            } = change
+            {
+                paths.push(move_path);
+            }
+        }
+        paths.extend(grant_root);
+        paths.sort();
+        if paths.is_empty() {
+            return RuleVerdict::Rejected {
+                reason: "the request names no paths".to_string(),
+            };
+        }
+
+        let mut matched = Vec::new();
+        for path in paths {
+            let (absolute, relative) = glob_subjects(path, cwd);
+            let relative = relative.unwrap_or(absolute);
+            let Some(glob) = self
+                .paths
+                .iter()
+                .find(|glob| WildMatch::new(glob.as_str()).matches(&relative))
+            else {
+                return RuleVerdict::Rejected {
+                    reason: format!("{relative} matches no path glob"),
+                };
+            };
+            let glob = format!("`{glob}`");
+            if !matched.contains(&glob) {
+                matched.push(glob);
+            }
+        }
+        RuleVerdict::Approved {
+            rule: format!("path glob {}", matched.join(", ")),
+        }
+    }
+}
+
+/// Maps `verdict` to the decision sent back to the agent and logs it to
+/// stderr. Without rules every request is declined so the agent can carry on;
+/// a request the rules do not cover aborts the run.
+#[allow(clippy::print_stderr)]
+pub(crate) fn settle(subject: &str, verdict: Option<RuleVerdict>) -> ReviewDecision {
+    match verdict {
+        None => {
+            eprintln!("approval declined: {subject} (no --approval-rules given)");
+            ReviewDecision::Denied
+        }
+        Some(RuleVerdict::Approved { rule }) => {
+            eprintln!("approval granted: {subject} (matched {rule})");
+            ReviewDecision::Approved
+        }
+        Some(RuleVerdict::Rejected { reason }) => {
+            eprintln!("approval rejected: {subject}: {reason}; aborting");
+            ReviewDecision::Abort
+        }
+    }
+}
+
+fn join(command: &[String]) -> String {
+    shlex::try_join(command.iter().map(String::as_str)).unwrap_or_else(|_| command.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn rules(toml: &str) -> ApprovalRules {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("rules.toml");
+        std::fs::write(&path, toml).expect("write rules");
+        ApprovalRules::load(&path).expect("load rules")
+    }
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn commands_match_prefixes_including_shell_scripts() {
+        let rules = rules(r#"commands = [["cargo", "test"], ["git", "status"]]"#);
+
+        assert_eq!(
+            rules.check_command(&argv(&["cargo", "test", "-p", "codex-exec"])),
+            RuleVerdict::Approved {
+                rule: "command prefix `cargo test`".to_string()
+            }
+        );
+        assert_eq!(
+            rules.check_command(&argv(&["bash", "-lc", "git status && cargo test"])),
+            RuleVerdict::Approved {
+                rule: "command prefix `git status`, `cargo test`".to_string()
+            }
+        );
+        assert_eq!(
+            rules.check_command(&argv(&["bash", "-lc", "cargo test && rm -rf target"])),
+            RuleVerdict::Rejected {
+                reason: "`rm -rf target` matches no command prefix".to_string()
+            }
+        );
+        assert_eq!(
+            rules.check_command(&argv(&["bash", "-lc", "cargo test > $(mktemp)"])),
+            RuleVerdict::Rejected {
+                reason: "the shell script is too complex to match against command prefixes"
+                    .to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn patches_match_globs_relative_to_cwd() {
+        let rules = rules(r#"paths = ["docs/*", "CHANGELOG.md"]"#);
+        let cwd = Path::new("/repo");
+        let add = || FileChange::Add {
+            content: String::new(),
+        };
+
+        let changes = HashMap::from([
+            (PathBuf::from("/repo/docs/guide/intro.md"), add()),
+            (PathBuf::from("/repo/CHANGELOG.md"), add()),
+        ]);
+        assert_eq!(
+            rules.check_patch(cwd, &changes, None),
+            RuleVerdict::Approved {
+                rule: "path glob `CHANGELOG.md`, `docs/*`".to_string()
+            }
+        );
+
+        let changes = HashMap::from([(PathBuf::from("/repo/docs/a.md"), add())]);
+        assert_eq!(
+            rules.check_patch(cwd, &changes, Some(Path::new("/etc"))),
+            RuleVerdict::Rejected {
+                reason: "/etc matches no path glob".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn dot_segments_cannot_escape_a_glob() {
+        let rules = rules(r#"paths = ["docs/*"]"#);
+        let cwd = Path::new("/repo");
+        let add = || FileChange::Add {
+            content: String::new(),
+        };
+
+        let changes = HashMap::from([(PathBuf::from("/repo/docs/../../home/u/.bashrc"), add())]);
+        assert_eq!(
+            rules.check_patch(cwd, &changes, None),
+            RuleVerdict::Rejected {
+                reason: "/home/u/.bashrc matches no path glob".to_string()
+            }
+        );
+
+        let changes = HashMap::from([(PathBuf::from("/repo/src/../docs/./a.md"), add())]);
+        assert_eq!(
+            rules.check_patch(cwd, &changes, None),
+            RuleVerdict::Approved {
+                rule: "path glob `docs/*`".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn tools_approve_every_request_of_their_kind() {
+        let rules = rules(r#"tools = ["apply_patch"]"#);
+        assert_eq!(
+            rules.check_patch(Path::new("/repo"), &HashMap::new(), Some(Path::new("/tmp"))),
+            RuleVerdict::Approved {
+                rule: "tool `apply_patch`".to_string()
+            }
+        );
+        assert_eq!(
+            rules.check_command(&argv(&["rm", "-rf", "/"])),
+            RuleVerdict::Rejected {
+                reason: "`rm -rf /` matches no command prefix".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_tools_and_empty_prefixes() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("rules.toml");
+
+        std::fs::write(&path, r#"tools = ["web_search"]"#).expect("write rules");
+        let err = ApprovalRules::load(&path).expect_err("unknown tool");
+        assert_eq!(
+            err.to_string(),
+            "approval rules: unknown tool `web_search`; expected `shell` or `apply_patch`"
+        );
+
+        std::fs::write(&path, "commands = [[]]").expect("write rules");
+        assert!(ApprovalRules::load(&path).is_err());
+    }
+}
@@ -62,6 +62,11 @@ pub struct Cli {
     #[arg(long = "add-dir", value_name = "DIR", value_hint = clap::ValueHint::DirPath)]
     pub add_dir: Vec<PathBuf>,
 
+    /// TOML file of command prefixes, path globs and tools whose approval
+    /// requests are granted automatically; any other request aborts the run.
+    #[arg(long = "approval-rules", value_name = "FILE")]
+    pub approval_rules: Option<PathBuf>,
+
     /// Path to a JSON Schema file describing the model's final response shape.
     #[arg(long = "output-schema", value_name = "FILE")]
     pub output_schema: Option<PathBuf>,
@@ -94,6 +99,10 @@ pub enum Command {
 
     /// Run a code review against the current repository.
     Review(ReviewArgs),
+
+    /// Run a custom command from `commands/*.md`, e.g. `run /deploy api`, or
+    /// a task bundle with `run --bundle task.codexbundle`.
+    Run(RunArgs),
 }
 
 #[derive(Parser, Debug)]
@@ -145,6 +154,44 @@ pub struct ReviewArgs {
     /// Custom review instructions. If `-` is used, read from stdin.
     #[arg(value_name = "PROMPT", value_hint = clap::ValueHint::Other)]
     pub prompt: Option<String>,
+
+    /// How to print the findings. `sarif` writes a SARIF 2.1.0 log to stdout,
+    /// for GitHub code scanning and other review tooling.
+    #[arg(long = "format", value_enum, default_value_t = ReviewFormat::Text)]
+    pub format: ReviewFormat,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum ReviewFormat {
+    #[default]
+    Text,
+    Sarif,
+}
+
+#[derive(Parser, Debug)]
+pub struct RunArgs {
+    /// Name of the command, with or without the leading `/`.
+    #[arg(value_name = "COMMAND", required_unless_present = "bundle")]
+    pub command: Option<String>,
+
+    /// Run a task bundle created with `codex bundle create` instead of a
+    /// custom command.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["command", "args"])]
+    pub bundle: Option<PathBuf>,
+
+    /// Let the bundle's `sandbox_mode` loosen the sandbox your config sets.
+    /// Without it, a bundle can only tighten the sandbox.
+    #[arg(long, requires = "bundle")]
+    pub allow_bundle_sandbox: bool,
+
+    /// Arguments substituted for `$ARGUMENTS` in the command.
+    #[arg(
+        value_name = "ARGS",
+        trailing_var_arg = true,
+        allow_hyphen_values = true
+    )]
+    pub args: Vec<String>,
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
@@ -4,6 +4,8 @@ use codex_core::config::Config;
 use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
 use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::BudgetExceededEvent;
+use codex_core::protocol::ContextPressureEvent;
 use codex_core::protocol::DeprecationNoticeEvent;
 use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
@@ -16,11 +18,16 @@ use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
+use codex_core::protocol::ReadOnlyModeChangedEvent;
+use codex_core::protocol::RetryScheduledEvent;
 use codex_core::protocol::SessionConfiguredEvent;
 use codex_core::protocol::StreamErrorEvent;
+use codex_core::protocol::SubAgentEvent;
 use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::ToolCallLoopDetectedEvent;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnDiffEvent;
+use codex_core::protocol::TurnTimedOutEvent;
 use codex_core::protocol::WarningEvent;
 use codex_core::protocol::WebSearchEndEvent;
 use codex_protocol::num_format::format_with_separators;
@@ -166,6 +173,65 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 let prefix = "ERROR:".style(self.red);
                 ts_msg!(self, "{prefix} {message}");
             }
+            EventMsg::BudgetExceeded(BudgetExceededEvent { message, .. }) => {
+                ts_msg!(
+                    self,
+                    "{} {message}",
+                    "budget exceeded:".style(self.red).style(self.bold)
+                );
+            }
+            EventMsg::TurnTimedOut(TurnTimedOutEvent { message, .. }) => {
+                ts_msg!(
+                    self,
+                    "{} {message}",
+                    "timed out:".style(self.red).style(self.bold)
+                );
+            }
+            EventMsg::ContextPressure(ContextPressureEvent {
+                threshold_percent,
+                used_tokens,
+                context_window,
+                ..
+            }) => {
+                ts_msg!(
+                    self,
+                    "{} {threshold_percent}% of the context window used ({used_tokens} of {context_window} tokens)",
+                    "context:".style(self.yellow).style(self.bold)
+                );
+            }
+            EventMsg::ToolCallLoopDetected(ToolCallLoopDetectedEvent { message, .. }) => {
+                ts_msg!(
+                    self,
+                    "{} {message}",
+                    "loop detected:".style(self.yellow).style(self.bold)
+                );
+            }
+            EventMsg::SubAgent(SubAgentEvent { agent_id, msg }) => match *msg {
+                EventMsg::ExecCommandBegin(ExecCommandBeginEvent { command, .. }) => {
+                    ts_msg!(
+                        self,
+                        "{} exec {}",
+                        format!("[{agent_id}]").style(self.cyan),
+                        escape_command(&command).style(self.bold)
+                    );
+                }
+                EventMsg::PatchApplyBegin(PatchApplyBeginEvent { changes, .. }) => {
+                    ts_msg!(
+                        self,
+                        "{} apply_patch ({} file(s))",
+                        format!("[{agent_id}]").style(self.cyan),
+                        changes.len()
+                    );
+                }
+                EventMsg::TaskComplete(_) => {
+                    ts_msg!(
+                        self,
+                        "{} finished",
+                        format!("[{agent_id}]").style(self.cyan)
+                    );
+                }
+                _ => {}
+            },
             EventMsg::Warning(WarningEvent { message }) => {
                 ts_msg!(
                     self,
@@ -219,10 +285,12 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 };
                 ts_msg!(self, "{} {}", "mcp startup:".style(self.cyan), joined);
             }
-            EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
+            EventMsg::BackgroundEvent(BackgroundEventEvent { message })
+            | EventMsg::ReadOnlyModeChanged(ReadOnlyModeChangedEvent { message, .. }) => {
                 ts_msg!(self, "{}", message.style(self.dimmed));
             }
-            EventMsg::StreamError(StreamErrorEvent { message, .. }) => {
+            EventMsg::StreamError(StreamErrorEvent { message, .. })
+            | EventMsg::RetryScheduled(RetryScheduledEvent { message, .. }) => {
                 ts_msg!(self, "{}", message.style(self.dimmed));
             }
             EventMsg::TaskStarted(_) => {
@@ -559,6 +627,9 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 TurnAbortReason::ReviewEnded => {
                     ts_msg!(self, "task aborted: review ended");
                 }
+                TurnAbortReason::TimedOut => {
+                    ts_msg!(self, "task aborted: timed out");
+                }
             },
             EventMsg::ContextCompacted(_) => {
                 ts_msg!(self, "context compacted");
@@ -586,6 +657,10 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             | EventMsg::ReasoningContentDelta(_)
             | EventMsg::ReasoningRawContentDelta(_)
             | EventMsg::SkillsUpdateAvailable
+            | EventMsg::PinnedContextUpdated(_)
+            | EventMsg::SessionDiff(_)
+            | EventMsg::SecretsRedacted(_)
+            | EventMsg::ContextItemsDropped(_)
             | EventMsg::UndoCompleted(_)
             | EventMsg::UndoStarted(_) => {}
         }
@@ -594,10 +669,21 @@ impl EventProcessor for EventProcessorWithHumanOutput {
 
     fn print_final_output(&mut self) {
         if let Some(usage_info) = &self.last_total_token_usage {
+            let usage = &usage_info.total_token_usage;
+            // Cached input is left out of the total; show it separately so
+            // prompt caching can be verified.
+            let cached = if usage.cached_input() > 0 {
+                format!(
+                    " ({} cached input)",
+                    format_with_separators(usage.cached_input())
+                )
+            } else {
+                String::new()
+            };
             eprintln!(
-                "{}\n{}",
+                "{}\n{}{cached}",
                 "tokens used".style(self.magenta).style(self.italic),
-                format_with_separators(usage_info.total_token_usage.blended_total())
+                format_with_separators(usage.blended_total())
             );
         }
 
@@ -6,6 +6,8 @@ use crate::event_processor::CodexStatus;
 use crate::event_processor::EventProcessor;
 use crate::event_processor::handle_last_message;
 use crate::exec_events::AgentMessageItem;
+use crate::exec_events::ApprovalRequestDetails;
+use crate::exec_events::ApprovalRequestedEvent;
 use crate::exec_events::CommandExecutionItem;
 use crate::exec_events::CommandExecutionStatus;
 use crate::exec_events::ErrorItem;
@@ -36,8 +38,10 @@ use crate::exec_events::WebSearchItem;
 use codex_core::config::Config;
 use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningEvent;
+use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
+use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::FileChange;
@@ -119,6 +123,8 @@ impl EventProcessorWithJsonOutput {
             EventMsg::McpToolCallEnd(ev) => self.handle_mcp_tool_call_end(ev),
             EventMsg::PatchApplyBegin(ev) => self.handle_patch_apply_begin(ev),
             EventMsg::PatchApplyEnd(ev) => self.handle_patch_apply_end(ev),
+            EventMsg::ExecApprovalRequest(ev) => self.handle_exec_approval_request(ev),
+            EventMsg::ApplyPatchApprovalRequest(ev) => self.handle_patch_approval_request(ev),
             EventMsg::WebSearchBegin(_) => Vec::new(),
             EventMsg::WebSearchEnd(ev) => self.handle_web_search_end(ev),
             EventMsg::TokenCount(ev) => {
@@ -136,6 +142,20 @@ impl EventProcessorWithJsonOutput {
                 self.last_critical_error = Some(error.clone());
                 vec![ThreadEvent::Error(error)]
             }
+            EventMsg::BudgetExceeded(ev) => {
+                let error = ThreadErrorEvent {
+                    message: ev.message.clone(),
+                };
+                self.last_critical_error = Some(error.clone());
+                vec![ThreadEvent::Error(error)]
+            }
+            EventMsg::TurnTimedOut(ev) => {
+                let error = ThreadErrorEvent {
+                    message: ev.message.clone(),
+                };
+                self.last_critical_error = Some(error.clone());
+                vec![ThreadEvent::Error(error)]
+            }
             EventMsg::Warning(ev) => {
                 let item = ThreadItem {
                     id: self.get_next_item_id(),
@@ -145,6 +165,15 @@ impl EventProcessorWithJsonOutput {
                 };
                 vec![ThreadEvent::ItemCompleted(ItemCompletedEvent { item })]
             }
+            EventMsg::ToolCallLoopDetected(ev) => {
+                let item = ThreadItem {
+                    id: self.get_next_item_id(),
+                    details: ThreadItemDetails::Error(ErrorItem {
+                        message: ev.message.clone(),
+                    }),
+                };
+                vec![ThreadEvent::ItemCompleted(ItemCompletedEvent { item })]
+            }
             EventMsg::StreamError(ev) => vec![ThreadEvent::Error(ThreadErrorEvent {
                 message: ev.message.clone(),
             })],
@@ -214,16 +243,7 @@ impl EventProcessorWithJsonOutput {
     fn handle_exec_command_begin(&mut self, ev: &ExecCommandBeginEvent) -> Vec<ThreadEvent> {
         let item_id = self.get_next_item_id();
 
-        let command_string = match shlex::try_join(ev.command.iter().map(String::as_str)) {
-            Ok(command_string) => command_string,
-            Err(e) => {
-                warn!(
-                    call_id = ev.call_id,
-                    "Failed to stringify command: {e:?}; skipping item.started"
-                );
-                ev.command.join(" ")
-            }
-        };
+        let command_string = command_to_string(&ev.call_id, &ev.command);
 
         self.running_commands.insert(
             ev.call_id.clone(),
@@ -353,6 +373,36 @@ impl EventProcessorWithJsonOutput {
         }
     }
 
+    fn handle_exec_approval_request(&self, ev: &ExecApprovalRequestEvent) -> Vec<ThreadEvent> {
+        vec![ThreadEvent::ApprovalRequested(ApprovalRequestedEvent {
+            call_id: ev.call_id.clone(),
+            details: ApprovalRequestDetails::CommandExecution {
+                command: command_to_string(&ev.call_id, &ev.command),
+            },
+            reason: ev.reason.clone(),
+        })]
+    }
+
+    fn handle_patch_approval_request(
+        &self,
+        ev: &ApplyPatchApprovalRequestEvent,
+    ) -> Vec<ThreadEvent> {
+        vec![ThreadEvent::ApprovalRequested(ApprovalRequestedEvent {
+            call_id: ev.call_id.clone(),
+            details: ApprovalRequestDetails::FileChange {
+                changes: ev
+                    .changes
+                    .iter()
+                    .map(|(path, change)| FileUpdateChange {
+                        path: path.to_str().unwrap_or("").to_string(),
+                        kind: self.map_change_kind(change),
+                    })
+                    .collect(),
+            },
+            reason: ev.reason.clone(),
+        })]
+    }
+
     fn handle_patch_apply_end(&mut self, ev: &PatchApplyEndEvent) -> Vec<ThreadEvent> {
         if let Some(running_patch_apply) = self.running_patch_applies.remove(&ev.call_id) {
             let status = if ev.success {
@@ -540,3 +590,16 @@ impl EventProcessor for EventProcessorWithJsonOutput {
         }
     }
 }
+
+fn command_to_string(call_id: &str, command: &[String]) -> String {
+    match shlex::try_join(command.iter().map(String::as_str)) {
+        Ok(command_string) => command_string,
+        Err(e) => {
+            warn!(
+                call_id,
+                "Failed to stringify command: {e:?}; joining with spaces"
+            );
+            command.join(" ")
+        }
+    }
+}
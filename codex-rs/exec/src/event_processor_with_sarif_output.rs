@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+
+use codex_core::config::Config;
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::ReviewOutputEvent;
+use codex_core::protocol::SessionConfiguredEvent;
+use codex_core::review_sarif::review_output_to_sarif;
+
+use crate::event_processor::CodexStatus;
+use crate::event_processor::EventProcessor;
+use crate::event_processor_with_human_output::EventProcessorWithHumanOutput;
+
+/// Shows progress like the default mode, on stderr, and writes the review's
+/// findings to stdout as a SARIF log once the run ends.
+pub(crate) struct EventProcessorWithSarifOutput {
+    progress: EventProcessorWithHumanOutput,
+    /// Directory result paths are made relative to.
+    root: PathBuf,
+    review_output: Option<ReviewOutputEvent>,
+}
+
+impl EventProcessorWithSarifOutput {
+    pub(crate) fn new(progress: EventProcessorWithHumanOutput, root: PathBuf) -> Self {
+        Self {
+            progress,
+            root,
+            review_output: None,
+        }
+    }
+}
+
+impl EventProcessor for EventProcessorWithSarifOutput {
+    fn print_config_summary(
+        &mut self,
+        config: &Config,
+        prompt: &str,
+        session_configured: &SessionConfiguredEvent,
+    ) {
+        self.progress
+            .print_config_summary(config, prompt, session_configured);
+    }
+
+    fn process_event(&mut self, event: Event) -> CodexStatus {
+        if let EventMsg::ExitedReviewMode(ev) = &event.msg {
+            self.review_output = ev.review_output.clone();
+        }
+        self.progress.process_event(event)
+    }
+
+    fn print_final_output(&mut self) {
+        let sarif = review_output_to_sarif(
+            self.review_output.as_ref(),
+            &self.root,
+            env!("CARGO_PKG_VERSION"),
+        );
+        match serde_json::to_string_pretty(&sarif) {
+            #[allow(clippy::print_stdout)]
+            Ok(text) => println!("{text}"),
+            Err(err) => eprintln!("Failed to serialize SARIF output: {err}"),
+        }
+    }
+}
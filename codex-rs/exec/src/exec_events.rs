@@ -30,6 +30,10 @@ pub enum ThreadEvent {
     /// Signals that an item has reached a terminal state—either success or failure.
     #[serde(rename = "item.completed")]
     ItemCompleted(ItemCompletedEvent),
+    /// Emitted when the agent asks to run a command or apply a patch that needs
+    /// approval. `codex exec` cannot prompt, so the request is declined.
+    #[serde(rename = "approval.requested")]
+    ApprovalRequested(ApprovalRequestedEvent),
     /// Represents an unrecoverable error emitted directly by the event stream.
     #[serde(rename = "error")]
     Error(ThreadErrorEvent),
@@ -81,6 +85,24 @@ pub struct ItemUpdatedEvent {
     pub item: ThreadItem,
 }
 
+/// A command or patch that needed approval.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+pub struct ApprovalRequestedEvent {
+    /// Call id of the tool call awaiting approval.
+    pub call_id: String,
+    #[serde(flatten)]
+    pub details: ApprovalRequestDetails,
+    /// Why the agent needs approval, e.g. to retry without the sandbox.
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ApprovalRequestDetails {
+    CommandExecution { command: String },
+    FileChange { changes: Vec<FileUpdateChange> },
+}
+
 /// Fatal error emitted by the stream.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 pub struct ThreadErrorEvent {
@@ -0,0 +1,401 @@
+//! GitHub Actions integration for `codex exec`, enabled when the runner sets
+//! `GITHUB_ACTIONS=true` unless `CODEX_GITHUB_ACTIONS=0`.
+//!
+//! - Errors, warnings and review findings become workflow command
+//!   annotations (`::error file=...::message`). They are written to stderr,
+//!   which the runner scans like stdout, so stdout keeps only the final
+//!   message.
+//! - The final message, the session diff and token usage are appended to the
+//!   job summary (`$GITHUB_STEP_SUMMARY`).
+//! - `exit-code`, `status` and `changed-files` are set as step outputs
+//!   (`$GITHUB_OUTPUT`).
+
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::ReviewFinding;
+use codex_core::protocol::TokenUsage;
+use codex_protocol::num_format::format_with_separators;
+
+/// Diffs larger than this are cut short in the job summary, which GitHub
+/// limits to 1 MiB per step.
+const MAX_SUMMARY_DIFF_BYTES: usize = 256 * 1024;
+
+pub(crate) struct GithubActions {
+    /// Directory annotation paths are relative to.
+    workspace: PathBuf,
+    summary_path: Option<PathBuf>,
+    output_path: Option<PathBuf>,
+    /// Latest cumulative diff of the session.
+    diff: Option<String>,
+    usage: Option<TokenUsage>,
+    spend_usd: Option<f64>,
+    final_message: Option<String>,
+}
+
+impl GithubActions {
+    /// Returns `None` outside GitHub Actions or when turned off.
+    pub(crate) fn from_env(repo_root: &Path) -> Option<Self> {
+        if std::env::var("GITHUB_ACTIONS").ok().as_deref() != Some("true")
+            || std::env::var("CODEX_GITHUB_ACTIONS").ok().as_deref() == Some("0")
+        {
+            return None;
+        }
+        let env_path = |name: &str| {
+            std::env::var_os(name)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+        };
+        Some(Self {
+            workspace: env_path("GITHUB_WORKSPACE").unwrap_or_else(|| repo_root.to_path_buf()),
+            summary_path: env_path("GITHUB_STEP_SUMMARY"),
+            output_path: env_path("GITHUB_OUTPUT"),
+            diff: None,
+            usage: None,
+            spend_usd: None,
+            final_message: None,
+        })
+    }
+
+    pub(crate) fn observe(&mut self, event: &Event) {
+        match &event.msg {
+            EventMsg::Error(ev) => {
+                eprintln!(
+                    "{}",
+                    annotation("error", &[("title", "Codex")], &ev.message)
+                );
+            }
+            EventMsg::Warning(ev) => {
+                eprintln!(
+                    "{}",
+                    annotation("warning", &[("title", "Codex")], &ev.message)
+                );
+            }
+            EventMsg::ExitedReviewMode(ev) => {
+                for finding in ev.review_output.iter().flat_map(|output| &output.findings) {
+                    eprintln!("{}", self.finding_annotation(finding));
+                }
+            }
+            EventMsg::SessionDiff(ev) => self.diff = Some(ev.unified_diff.clone()),
+            EventMsg::TokenCount(ev) => {
+                if let Some(info) = &ev.info {
+                    self.usage = Some(info.total_token_usage.clone());
+                }
+                if let Some(spend) = ev.spend {
+                    self.spend_usd = Some(spend.session_usd);
+                }
+            }
+            EventMsg::TaskComplete(ev) => {
+                if ev.last_agent_message.is_some() {
+                    self.final_message = ev.last_agent_message.clone();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Writes the job summary and step outputs. Failures are reported but do
+    /// not change the exit code.
+    pub(crate) fn finish(&self, exit_code: i32) {
+        let changed_files = self.diff.as_deref().map(changed_files).unwrap_or_default();
+        if let Some(path) = &self.summary_path {
+            let summary = self.job_summary(exit_code, &changed_files);
+            if let Err(err) = append(path, &summary) {
+                eprintln!(
+                    "Failed to write the job summary to {}: {err}",
+                    path.display()
+                );
+            }
+        }
+        if let Some(path) = &self.output_path {
+            let outputs = step_outputs(exit_code, &changed_files);
+            if let Err(err) = append(path, &outputs) {
+                eprintln!("Failed to write step outputs to {}: {err}", path.display());
+            }
+        }
+    }
+
+    fn finding_annotation(&self, finding: &ReviewFinding) -> String {
+        let level = match finding.priority {
+            0 | 1 => "error",
+            3 => "notice",
+            _ => "warning",
+        };
+        let location = &finding.code_location;
+        let file = location
+            .absolute_file_path
+            .strip_prefix(&self.workspace)
+            .unwrap_or(&location.absolute_file_path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let start = location.line_range.start.max(1);
+        let end = location.line_range.end.max(start);
+        annotation(
+            level,
+            &[
+                ("file", &file),
+                ("line", &start.to_string()),
+                ("endLine", &end.to_string()),
+                ("title", finding.title.trim()),
+            ],
+            finding.body.trim(),
+        )
+    }
+
+    fn job_summary(&self, exit_code: i32, changed_files: &[String]) -> String {
+        let mut summary = String::from("## Codex\n\n");
+        let status = if exit_code == 0 {
+            "✅ Succeeded".to_string()
+        } else {
+            format!("❌ Failed (exit code {exit_code})")
+        };
+        let _ = writeln!(summary, "**Status:** {status}\n");
+        if let Some(message) = &self.final_message {
+            let _ = writeln!(summary, "{}\n", message.trim());
+        }
+
+        if let Some(diff) = self.diff.as_deref().filter(|diff| !diff.is_empty()) {
+            let _ = writeln!(
+                summary,
+                "### Changes\n\n<details><summary>{} file(s) changed</summary>\n",
+                changed_files.len()
+            );
+            let (shown, truncated) = truncate_at_char_boundary(diff, MAX_SUMMARY_DIFF_BYTES);
+            // A fence longer than any backtick run in the diff keeps it intact.
+            let fence = "`".repeat(longest_backtick_run(shown).max(2) + 1);
+            let _ = writeln!(summary, "{fence}diff\n{}\n{fence}", shown.trim_end());
+            if truncated {
+                summary.push_str("\n_The diff was truncated._\n");
+            }
+            summary.push_str("\n</details>\n\n");
+        }
+
+        if let Some(usage) = &self.usage {
+            summary.push_str("### Usage\n\n");
+            summary.push_str("| Input | Cached input | Output | Tokens used |");
+            if self.spend_usd.is_some() {
+                summary.push_str(" Estimated cost |");
+            }
+            summary.push_str("\n| ---: | ---: | ---: | ---: |");
+            if self.spend_usd.is_some() {
+                summary.push_str(" ---: |");
+            }
+            let _ = write!(
+                summary,
+                "\n| {} | {} | {} | {} |",
+                format_with_separators(usage.input_tokens),
+                format_with_separators(usage.cached_input()),
+                format_with_separators(usage.output_tokens),
+                format_with_separators(usage.blended_total()),
+            );
+            if let Some(spend) = self.spend_usd {
+                let _ = write!(summary, " ${spend:.2} |");
+            }
+            summary.push_str("\n\n");
+        }
+        summary
+    }
+}
+
+/// Formats a workflow command, escaping the message and property values the
+/// way the runner expects.
+fn annotation(level: &str, properties: &[(&str, &str)], message: &str) -> String {
+    let properties: Vec<String> = properties
+        .iter()
+        .map(|(key, value)| format!("{key}={}", escape_property(value)))
+        .collect();
+    let separator = if properties.is_empty() { "" } else { " " };
+    format!(
+        "::{level}{separator}{}::{}",
+        properties.join(","),
+        escape_data(message)
+    )
+}
+
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Files named in a unified diff produced by the diff trackers.
+fn changed_files(diff: &str) -> Vec<String> {
+    let mut files: Vec<String> = diff
+        .lines()
+        .filter_map(|line| line.strip_prefix("diff --git a/"))
+        .filter_map(|paths| paths.rsplit_once(" b/").map(|(_, right)| right.to_string()))
+        .collect();
+    files.dedup();
+    files
+}
+
+fn step_outputs(exit_code: i32, changed_files: &[String]) -> String {
+    let status = if exit_code == 0 { "success" } else { "failure" };
+    let mut delimiter = "CODEX_EOF".to_string();
+    while changed_files.iter().any(|file| *file == delimiter) {
+        delimiter.push('_');
+    }
+    let mut outputs = format!("exit-code={exit_code}\nstatus={status}\n");
+    let _ = writeln!(outputs, "changed-files<<{delimiter}");
+    for file in changed_files {
+        let _ = writeln!(outputs, "{file}");
+    }
+    let _ = writeln!(outputs, "{delimiter}");
+    outputs
+}
+
+fn truncate_at_char_boundary(text: &str, max_bytes: usize) -> (&str, bool) {
+    if text.len() <= max_bytes {
+        return (text, false);
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    (&text[..end], true)
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default()
+}
+
+fn append(path: &Path, contents: &str) -> std::io::Result<()> {
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(contents.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::protocol::ExitedReviewModeEvent;
+    use codex_core::protocol::ReviewCodeLocation;
+    use codex_core::protocol::ReviewLineRange;
+    use codex_core::protocol::ReviewOutputEvent;
+    use codex_core::protocol::SessionDiffEvent;
+    use codex_core::protocol::TaskCompleteEvent;
+    use codex_core::protocol::TokenCountEvent;
+    use codex_core::protocol::TokenUsageInfo;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    const DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-old\n+new\ndiff --git a/README.md b/README.md\n--- a/README.md\n+++ b/README.md\n@@ -1 +1 @@\n-a\n+b\n";
+
+    fn github_actions(dir: &Path) -> GithubActions {
+        GithubActions {
+            workspace: PathBuf::from("/work/repo"),
+            summary_path: Some(dir.join("summary.md")),
+            output_path: Some(dir.join("output")),
+            diff: None,
+            usage: None,
+            spend_usd: None,
+            final_message: None,
+        }
+    }
+
+    fn event(msg: EventMsg) -> Event {
+        Event {
+            id: "1".to_string(),
+            msg,
+        }
+    }
+
+    #[test]
+    fn annotations_escape_messages_and_properties() {
+        assert_eq!(
+            annotation(
+                "error",
+                &[("file", "src/a,b.rs"), ("title", "[P1] Fix: race")],
+                "50% of runs\nfail"
+            ),
+            "::error file=src/a%2Cb.rs,title=[P1] Fix%3A race::50%25 of runs%0Afail"
+        );
+        assert_eq!(annotation("warning", &[], "slow"), "::warning::slow");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn review_findings_are_annotated_relative_to_the_workspace() {
+        let dir = TempDir::new().expect("tempdir");
+        let finding = ReviewFinding {
+            title: "[P3] Typo".to_string(),
+            body: "`recieve` should be `receive`.".to_string(),
+            confidence_score: 0.9,
+            priority: 3,
+            code_location: ReviewCodeLocation {
+                absolute_file_path: PathBuf::from("/work/repo/src/net.rs"),
+                line_range: ReviewLineRange { start: 4, end: 4 },
+            },
+        };
+        assert_eq!(
+            github_actions(dir.path()).finding_annotation(&finding),
+            "::notice file=src/net.rs,line=4,endLine=4,title=[P3] Typo::`recieve` should be `receive`."
+        );
+        // Observing the review does not touch the summary or outputs.
+        github_actions(dir.path()).observe(&event(EventMsg::ExitedReviewMode(
+            ExitedReviewModeEvent {
+                review_output: Some(ReviewOutputEvent {
+                    findings: vec![finding],
+                    ..Default::default()
+                }),
+            },
+        )));
+        assert!(!dir.path().join("summary.md").exists());
+    }
+
+    #[test]
+    fn finish_writes_the_summary_and_outputs() {
+        let dir = TempDir::new().expect("tempdir");
+        let mut github_actions = github_actions(dir.path());
+        github_actions.observe(&event(EventMsg::SessionDiff(SessionDiffEvent {
+            unified_diff: DIFF.to_string(),
+        })));
+        github_actions.observe(&event(EventMsg::TokenCount(TokenCountEvent {
+            info: Some(TokenUsageInfo {
+                total_token_usage: TokenUsage {
+                    input_tokens: 12_000,
+                    cached_input_tokens: 2_000,
+                    output_tokens: 800,
+                    reasoning_output_tokens: 0,
+                    total_tokens: 12_800,
+                },
+                last_token_usage: TokenUsage::default(),
+                model_context_window: None,
+            }),
+            rate_limits: None,
+            pinned_context_tokens: None,
+            spend: None,
+        })));
+        github_actions.observe(&event(EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message: Some("Fixed the build.".to_string()),
+        })));
+        github_actions.finish(0);
+
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("output")).expect("outputs"),
+            "exit-code=0\nstatus=success\nchanged-files<<CODEX_EOF\nsrc/lib.rs\nREADME.md\nCODEX_EOF\n"
+        );
+        let summary = std::fs::read_to_string(dir.path().join("summary.md")).expect("summary");
+        assert_eq!(
+            summary,
+            format!(
+                "## Codex\n\n**Status:** ✅ Succeeded\n\nFixed the build.\n\n### Changes\n\n<details><summary>2 file(s) changed</summary>\n\n```diff\n{}\n```\n\n</details>\n\n### Usage\n\n| Input | Cached input | Output | Tokens used |\n| ---: | ---: | ---: | ---: |\n| 12,000 | 2,000 | 800 | 10,800 |\n\n",
+                DIFF.trim_end()
+            )
+        );
+    }
+}
@@ -4,15 +4,20 @@
 // For both modes, any other output must be written to stderr.
 #![deny(clippy::print_stdout)]
 
+mod approval_rules;
 mod cli;
 mod event_processor;
 mod event_processor_with_human_output;
 pub mod event_processor_with_jsonl_output;
+mod event_processor_with_sarif_output;
 pub mod exec_events;
+mod github_actions;
 
 pub use cli::Cli;
 pub use cli::Command;
 pub use cli::ReviewArgs;
+pub use cli::ReviewFormat;
+pub use cli::RunArgs;
 use codex_common::oss::ensure_oss_provider_ready;
 use codex_common::oss::get_default_model_for_oss_provider;
 use codex_core::AuthManager;
@@ -26,11 +31,14 @@ use codex_core::config::ConfigOverrides;
 use codex_core::config::find_codex_home;
 use codex_core::config::load_config_as_toml_with_cli_overrides;
 use codex_core::config::resolve_oss_provider;
+use codex_core::custom_prompts::discover_commands;
+use codex_core::custom_prompts::expand_command;
 use codex_core::git_info::get_git_repo_root;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
 use codex_core::protocol::ReviewRequest;
 use codex_core::protocol::ReviewTarget;
 use codex_core::protocol::SessionSource;
@@ -40,10 +48,14 @@ use codex_protocol::user_input::UserInput;
 use codex_utils_absolute_path::AbsolutePathBuf;
 use event_processor_with_human_output::EventProcessorWithHumanOutput;
 use event_processor_with_jsonl_output::EventProcessorWithJsonOutput;
+use event_processor_with_sarif_output::EventProcessorWithSarifOutput;
 use serde_json::Value;
 use std::io::IsTerminal;
 use std::io::Read;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicI32;
+use std::sync::atomic::Ordering;
 use supports_color::Stream;
 use tracing::debug;
 use tracing::error;
@@ -51,11 +63,17 @@ use tracing::info;
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::prelude::*;
 
+use crate::approval_rules::ApprovalRules;
 use crate::cli::Command as ExecCommand;
 use crate::event_processor::CodexStatus;
 use crate::event_processor::EventProcessor;
+use crate::github_actions::GithubActions;
+use codex_core::default_client::init_http_client_options;
 use codex_core::default_client::set_default_originator;
 use codex_core::find_conversation_path_by_id_str;
+use codex_core::spawn_session_vacuum;
+use codex_core::task_bundle::TaskBundle;
+use codex_core::task_bundle::sandbox_mode_of;
 
 enum InitialOperation {
     UserTurn {
@@ -90,6 +108,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         sandbox_mode: sandbox_mode_cli_arg,
         prompt,
         output_schema: output_schema_path,
+        approval_rules: approval_rules_path,
         config_overrides,
     } = cli;
 
@@ -124,7 +143,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     };
 
     // Parse `-c` overrides from the CLI.
-    let cli_kv_overrides = match config_overrides.parse_overrides() {
+    let mut cli_kv_overrides = match config_overrides.parse_overrides() {
         Ok(v) => v,
         #[allow(clippy::print_stderr)]
         Err(e) => {
@@ -133,6 +152,36 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         }
     };
 
+    // A task bundle's settings go first so that `-c` overrides still win.
+    #[allow(clippy::print_stderr)]
+    let task_bundle = match &command {
+        Some(ExecCommand::Run(RunArgs {
+            bundle: Some(path), ..
+        })) => match TaskBundle::load(path) {
+            Ok(bundle) => Some(bundle),
+            Err(err) => {
+                eprintln!("Error loading task bundle: {err}");
+                std::process::exit(1);
+            }
+        },
+        _ => None,
+    };
+    if let Some(bundle) = &task_bundle {
+        let mut overrides = bundle.config_overrides();
+        overrides.append(&mut cli_kv_overrides);
+        cli_kv_overrides = overrides;
+    }
+
+    #[allow(clippy::print_stderr)]
+    let approval_rules = match approval_rules_path.as_deref().map(ApprovalRules::load) {
+        Some(Ok(rules)) => Some(rules),
+        Some(Err(err)) => {
+            eprintln!("{err:#}");
+            std::process::exit(1);
+        }
+        None => None,
+    };
+
     let resolved_cwd = cwd.clone();
     let config_cwd = match resolved_cwd.as_deref() {
         Some(path) => AbsolutePathBuf::from_absolute_path(path.canonicalize()?)?,
@@ -201,7 +250,12 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         review_model: None,
         config_profile,
         // Default to never ask for approvals in headless mode. Feature flags can override.
-        approval_policy: Some(AskForApproval::Never),
+        // With approval rules the agent may request escalations for the rules to settle.
+        approval_policy: Some(if approval_rules.is_some() {
+            AskForApproval::OnRequest
+        } else {
+            AskForApproval::Never
+        }),
         sandbox_mode,
         cwd: resolved_cwd,
         model_provider: model_provider.clone(),
@@ -215,14 +269,65 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         additional_writable_roots: add_dir,
     };
 
-    let config =
-        Config::load_with_cli_overrides_and_harness_overrides(cli_kv_overrides, overrides).await?;
+    let mut config = Config::load_with_cli_overrides_and_harness_overrides(
+        cli_kv_overrides.clone(),
+        overrides.clone(),
+    )
+    .await?;
+
+    // `--sandbox` wins over a bundle's sandbox mode, which may only loosen
+    // the local sandbox with `--allow-bundle-sandbox`.
+    if let Some(bundle) = &task_bundle
+        && sandbox_mode.is_none()
+    {
+        let allow_looser = matches!(
+            &command,
+            Some(ExecCommand::Run(RunArgs {
+                allow_bundle_sandbox: true,
+                ..
+            }))
+        );
+        match bundle.sandbox_mode(sandbox_mode_of(config.sandbox_policy.get()), allow_looser) {
+            Ok(None) => {}
+            Ok(Some(mode)) => {
+                config = Config::load_with_cli_overrides_and_harness_overrides(
+                    cli_kv_overrides,
+                    ConfigOverrides {
+                        sandbox_mode: Some(mode),
+                        ..overrides
+                    },
+                )
+                .await?;
+            }
+            Err(problem) => {
+                eprintln!("The task bundle cannot run here:");
+                eprintln!("  - {problem}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    init_http_client_options(config.http.clone());
 
     if let Err(err) = enforce_login_restrictions(&config).await {
         eprintln!("{err}");
         std::process::exit(1);
     }
 
+    if let Some(bundle) = &task_bundle {
+        let check = bundle.check(&config.cwd).await;
+        for warning in &check.warnings {
+            eprintln!("Warning: {warning}");
+        }
+        if !check.problems.is_empty() {
+            eprintln!("The task bundle cannot run here:");
+            for problem in &check.problems {
+                eprintln!("  - {problem}");
+            }
+            std::process::exit(1);
+        }
+    }
+
     let otel = codex_core::otel_init::build_provider(&config, env!("CARGO_PKG_VERSION"));
 
     #[allow(clippy::print_stderr)]
@@ -244,14 +349,34 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         .with(otel_logger_layer)
         .try_init();
 
-    let mut event_processor: Box<dyn EventProcessor> = match json_mode {
-        true => Box::new(EventProcessorWithJsonOutput::new(last_message_file.clone())),
-        _ => Box::new(EventProcessorWithHumanOutput::create_with_ansi(
+    let sarif_mode = matches!(
+        &command,
+        Some(ExecCommand::Review(ReviewArgs {
+            format: ReviewFormat::Sarif,
+            ..
+        }))
+    );
+    let repo_root = get_git_repo_root(&config.cwd).unwrap_or_else(|| config.cwd.clone());
+    let mut event_processor: Box<dyn EventProcessor> = if sarif_mode {
+        // stdout carries only the SARIF log; progress goes to stderr.
+        Box::new(EventProcessorWithSarifOutput::new(
+            EventProcessorWithHumanOutput::create_with_ansi(
+                stderr_with_ansi,
+                &config,
+                last_message_file.clone(),
+            ),
+            repo_root.clone(),
+        ))
+    } else if json_mode {
+        Box::new(EventProcessorWithJsonOutput::new(last_message_file.clone()))
+    } else {
+        Box::new(EventProcessorWithHumanOutput::create_with_ansi(
             stdout_with_ansi,
             &config,
             last_message_file.clone(),
-        )),
+        ))
     };
+    let mut github_actions = GithubActions::from_env(&repo_root);
 
     if oss {
         // We're in the oss section, so provider_id should be Some
@@ -281,11 +406,9 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         std::process::exit(1);
     }
 
-    let auth_manager = AuthManager::shared(
-        config.codex_home.clone(),
-        true,
-        config.cli_auth_credentials_store_mode,
-    );
+    spawn_session_vacuum(&config);
+
+    let auth_manager = AuthManager::shared_from_config(&config, true);
     let conversation_manager = ConversationManager::new(auth_manager.clone(), SessionSource::Exec);
     let default_model = conversation_manager
         .get_models_manager()
@@ -314,12 +437,38 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
             .new_conversation(config.clone())
             .await?
     };
+    if let Some(bundle) = &task_bundle {
+        for item in bundle.pinned_items() {
+            conversation.submit(Op::PinContext { item }).await?;
+        }
+    }
     let (initial_operation, prompt_summary) = match (command, prompt, images) {
         (Some(ExecCommand::Review(review_cli)), _, _) => {
             let review_request = build_review_request(review_cli)?;
             let summary = codex_core::review_prompts::user_facing_hint(&review_request.target);
             (InitialOperation::Review { review_request }, summary)
         }
+        (Some(ExecCommand::Run(args)), _, imgs) => {
+            let prompt_text = match &task_bundle {
+                Some(bundle) => bundle.prompt.clone(),
+                None => resolve_custom_command(&config, &args).await?,
+            };
+            let mut items: Vec<UserInput> = imgs
+                .into_iter()
+                .map(|path| UserInput::LocalImage { path })
+                .collect();
+            items.push(UserInput::Text {
+                text: prompt_text.clone(),
+            });
+            let output_schema = load_output_schema(output_schema_path.clone());
+            (
+                InitialOperation::UserTurn {
+                    items,
+                    output_schema,
+                },
+                prompt_text,
+            )
+        }
         (Some(ExecCommand::Resume(args)), root_prompt, imgs) => {
             let prompt_arg = args
                 .prompt
@@ -376,19 +525,23 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     info!("Codex initialized with event: {session_configured:?}");
 
     let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Event>();
+    // Exit code owed to the signal that stopped the run, or 0.
+    let signal_exit_code = Arc::new(AtomicI32::new(0));
     {
         let conversation = conversation.clone();
+        let signal_exit_code = Arc::clone(&signal_exit_code);
         tokio::spawn(async move {
             loop {
                 tokio::select! {
-                    _ = tokio::signal::ctrl_c() => {
-                        tracing::debug!("Keyboard interrupt");
-                        // Immediately notify Codex to abort any in‑flight task.
-                        conversation.submit(Op::Interrupt).await.ok();
-
-                        // Exit the inner loop and return to the main input prompt. The codex
-                        // will emit a `TurnInterrupted` (Error) event which is drained later.
-                        break;
+                    code = shutdown_signal() => {
+                        if signal_exit_code.swap(code, Ordering::SeqCst) != 0 {
+                            // A second signal skips the drain.
+                            std::process::exit(code);
+                        }
+                        tracing::debug!("Received signal; shutting down");
+                        // Let in-flight writes finish and hooks run; events keep
+                        // flowing until `ShutdownComplete`.
+                        conversation.submit(Op::Shutdown).await.ok();
                     }
                     res = conversation.next_event() => match res {
                         Ok(event) => {
@@ -456,9 +609,53 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
                 })
                 .await?;
         }
-        if matches!(event.msg, EventMsg::Error(_)) {
+        // Nobody can answer an approval prompt in exec mode: the approval rules
+        // settle it, or it is declined so the agent carries on rather than wait.
+        let decision = match &event.msg {
+            EventMsg::ExecApprovalRequest(ev) => {
+                let subject = format!("command `{}`", ev.command.join(" "));
+                let verdict = approval_rules
+                    .as_ref()
+                    .map(|rules| rules.check_command(&ev.command));
+                let decision = approval_rules::settle(&subject, verdict);
+                conversation
+                    .submit(Op::ExecApproval {
+                        id: event.id.clone(),
+                        decision: decision.clone(),
+                    })
+                    .await?;
+                Some(decision)
+            }
+            EventMsg::ApplyPatchApprovalRequest(ev) => {
+                let verdict = approval_rules.as_ref().map(|rules| {
+                    rules.check_patch(&default_cwd, &ev.changes, ev.grant_root.as_deref())
+                });
+                let decision = approval_rules::settle("patch", verdict);
+                conversation
+                    .submit(Op::PatchApproval {
+                        id: event.id.clone(),
+                        decision: decision.clone(),
+                    })
+                    .await?;
+                Some(decision)
+            }
+            _ => None,
+        };
+        if decision == Some(ReviewDecision::Abort) {
+            error_seen = true;
+            conversation.submit(Op::Shutdown).await?;
+        }
+        if matches!(event.msg, EventMsg::Error(_) | EventMsg::BudgetExceeded(_)) {
             error_seen = true;
         }
+        // Nobody can answer a timed-out turn in exec mode, so fail the run.
+        if matches!(event.msg, EventMsg::TurnTimedOut(_)) {
+            error_seen = true;
+            conversation.submit(Op::Shutdown).await?;
+        }
+        if let Some(github_actions) = github_actions.as_mut() {
+            github_actions.observe(&event);
+        }
         let shutdown: CodexStatus = event_processor.process_event(event);
         match shutdown {
             CodexStatus::Running => continue,
@@ -471,13 +668,38 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         }
     }
     event_processor.print_final_output();
-    if error_seen {
-        std::process::exit(1);
+    let exit_code = match signal_exit_code.load(Ordering::SeqCst) {
+        0 if error_seen => 1,
+        code => code,
+    };
+    if let Some(github_actions) = &github_actions {
+        github_actions.finish(exit_code);
+    }
+    if exit_code != 0 {
+        std::process::exit(exit_code);
     }
 
     Ok(())
 }
 
+/// Resolves on SIGINT or SIGTERM with the exit code a shell reports for it.
+async fn shutdown_signal() -> i32 {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::SignalKind;
+        use tokio::signal::unix::signal;
+
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            return tokio::select! {
+                _ = tokio::signal::ctrl_c() => 130,
+                _ = terminate.recv() => 143,
+            };
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+    130
+}
+
 async fn resolve_resume_path(
     config: &Config,
     args: &crate::cli::ResumeArgs,
@@ -563,6 +785,28 @@ fn resolve_prompt(prompt_arg: Option<String>) -> String {
     }
 }
 
+async fn resolve_custom_command(config: &Config, args: &RunArgs) -> anyhow::Result<String> {
+    let Some(name) = args.command.as_deref() else {
+        anyhow::bail!("Specify a command to run or --bundle");
+    };
+    let name = name.strip_prefix('/').unwrap_or(name);
+    let commands = discover_commands(&config.codex_home, &config.cwd).await;
+    let Some(command) = commands.iter().find(|command| command.name == name) else {
+        let known: Vec<String> = commands
+            .iter()
+            .map(|command| format!("/{}", command.name))
+            .collect();
+        if known.is_empty() {
+            anyhow::bail!("Unknown command '/{name}'; no custom commands were found");
+        }
+        anyhow::bail!(
+            "Unknown command '/{name}'; available commands: {}",
+            known.join(", ")
+        );
+    };
+    Ok(expand_command(command, &args.args.join(" ")))
+}
+
 fn build_review_request(args: ReviewArgs) -> anyhow::Result<ReviewRequest> {
     let target = if args.uncommitted {
         ReviewTarget::UncommittedChanges
@@ -606,6 +850,7 @@ mod tests {
             commit: None,
             commit_title: None,
             prompt: None,
+            format: ReviewFormat::Text,
         })
         .expect("builds uncommitted review request");
 
@@ -625,6 +870,7 @@ mod tests {
             commit: Some("123456789".to_string()),
             commit_title: Some("Add review command".to_string()),
             prompt: None,
+            format: ReviewFormat::Text,
         })
         .expect("builds commit review request");
 
@@ -647,6 +893,7 @@ mod tests {
             commit: None,
             commit_title: None,
             prompt: Some("  custom review instructions  ".to_string()),
+            format: ReviewFormat::Text,
         })
         .expect("builds custom review request");
 
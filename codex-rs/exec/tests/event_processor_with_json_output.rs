@@ -1,9 +1,11 @@
 use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningEvent;
+use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
+use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::ExecCommandSource;
@@ -19,9 +21,12 @@ use codex_core::protocol::WarningEvent;
 use codex_core::protocol::WebSearchEndEvent;
 use codex_exec::event_processor_with_jsonl_output::EventProcessorWithJsonOutput;
 use codex_exec::exec_events::AgentMessageItem;
+use codex_exec::exec_events::ApprovalRequestDetails;
+use codex_exec::exec_events::ApprovalRequestedEvent;
 use codex_exec::exec_events::CommandExecutionItem;
 use codex_exec::exec_events::CommandExecutionStatus;
 use codex_exec::exec_events::ErrorItem;
+use codex_exec::exec_events::FileUpdateChange;
 use codex_exec::exec_events::ItemCompletedEvent;
 use codex_exec::exec_events::ItemStartedEvent;
 use codex_exec::exec_events::ItemUpdatedEvent;
@@ -1031,6 +1036,76 @@ fn patch_apply_failure_produces_item_completed_patchapply_failed() {
     }
 }
 
+#[test]
+fn approval_requests_produce_approval_requested_events() {
+    let mut ep = EventProcessorWithJsonOutput::new(None);
+
+    let exec = event(
+        "a1",
+        EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
+            call_id: "call-1".to_string(),
+            turn_id: "turn-1".to_string(),
+            command: vec!["rm".to_string(), "-rf".to_string(), "build dir".to_string()],
+            cwd: PathBuf::from("/repo"),
+            reason: Some("retry without sandbox".to_string()),
+            proposed_execpolicy_amendment: None,
+            parsed_cmd: Vec::new(),
+            proposed_permissions: Vec::new(),
+        }),
+    );
+    assert_eq!(
+        ep.collect_thread_events(&exec),
+        vec![ThreadEvent::ApprovalRequested(ApprovalRequestedEvent {
+            call_id: "call-1".to_string(),
+            details: ApprovalRequestDetails::CommandExecution {
+                command: "rm -rf 'build dir'".to_string(),
+            },
+            reason: Some("retry without sandbox".to_string()),
+        })]
+    );
+
+    let patch = event(
+        "a2",
+        EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
+            call_id: "call-2".to_string(),
+            turn_id: "turn-1".to_string(),
+            changes: std::collections::HashMap::from([(
+                PathBuf::from("src/lib.rs"),
+                FileChange::Add {
+                    content: "fn main() {}\n".to_string(),
+                },
+            )]),
+            reason: None,
+            grant_root: None,
+            proposed_permissions: Vec::new(),
+        }),
+    );
+    let out = ep.collect_thread_events(&patch);
+    assert_eq!(
+        out,
+        vec![ThreadEvent::ApprovalRequested(ApprovalRequestedEvent {
+            call_id: "call-2".to_string(),
+            details: ApprovalRequestDetails::FileChange {
+                changes: vec![FileUpdateChange {
+                    path: "src/lib.rs".to_string(),
+                    kind: PatchChangeKind::Add,
+                }],
+            },
+            reason: None,
+        })]
+    );
+    assert_eq!(
+        serde_json::to_value(&out[0]).expect("serialize event"),
+        json!({
+            "type": "approval.requested",
+            "call_id": "call-2",
+            "kind": "file_change",
+            "changes": [{"path": "src/lib.rs", "kind": "add"}],
+            "reason": null,
+        })
+    );
+}
+
 #[test]
 fn task_complete_produces_turn_completed_with_usage() {
     let mut ep = EventProcessorWithJsonOutput::new(None);
@@ -1053,6 +1128,8 @@ fn task_complete_produces_turn_completed_with_usage() {
         EventMsg::TokenCount(codex_core::protocol::TokenCountEvent {
             info: Some(info),
             rate_limits: None,
+            pinned_context_tokens: None,
+            spend: None,
         }),
     );
     assert!(ep.collect_thread_events(&token_count_event).is_empty());
@@ -60,3 +60,62 @@ async fn exec_includes_output_schema_in_request() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn exec_retries_answers_that_do_not_match_the_output_schema() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+
+    let schema_path = test.cwd_path().join("schema.json");
+    std::fs::write(
+        &schema_path,
+        serde_json::to_vec(&serde_json::json!({
+            "type": "object",
+            "properties": { "answer": { "type": "string" } },
+            "required": ["answer"],
+            "additionalProperties": false
+        }))?,
+    )?;
+
+    let server = responses::start_mock_server().await;
+    let response_mock = responses::mount_sse_sequence(
+        &server,
+        vec![
+            responses::sse(vec![
+                responses::ev_response_created("resp1"),
+                responses::ev_assistant_message("m1", "The answer is 42."),
+                responses::ev_completed("resp1"),
+            ]),
+            responses::sse(vec![
+                responses::ev_response_created("resp2"),
+                responses::ev_assistant_message("m2", "```json\n{\"answer\": \"42\"}\n```"),
+                responses::ev_completed("resp2"),
+            ]),
+        ],
+    )
+    .await;
+
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("-C")
+        .arg(test.cwd_path())
+        .arg("--output-schema")
+        .arg(&schema_path)
+        .arg("-m")
+        .arg("gpt-5.1")
+        .arg("what is the answer")
+        .assert()
+        .success()
+        .stdout("{\"answer\": \"42\"}\n");
+
+    let requests = response_mock.requests();
+    assert_eq!(requests.len(), 2);
+    let retry_notes = requests[1].message_input_texts("developer");
+    assert!(
+        retry_notes
+            .iter()
+            .any(|text| text.starts_with("The final message is not valid JSON.")),
+        "retry request should explain the mismatch: {retry_notes:?}"
+    );
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use codex_core::default_client::build_reqwest_client;
 use reqwest::StatusCode;
 use serde::Deserialize;
 use serde::Serialize;
@@ -151,7 +152,7 @@ fn print_device_code_prompt(code: &str) {
 
 /// Full device code login flow.
 pub async fn run_device_code_login(opts: ServerOptions) -> std::io::Result<()> {
-    let client = reqwest::Client::new();
+    let client = build_reqwest_client();
     let base_url = opts.issuer.trim_end_matches('/');
     let api_base_url = format!("{}/api/accounts", opts.issuer.trim_end_matches('/'));
     let uc = request_user_code(&client, &api_base_url, &opts.client_id).await?;
@@ -0,0 +1,403 @@
+//! Worker for the `codex_run_task` tool-call, which lets another agent hand
+//! Codex a whole task. The task runs without approvals; progress is reported
+//! as `notifications/progress` when the caller supplied a progress token, and
+//! the response carries the final message and the diff of the task's edits.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use codex_core::ConversationManager;
+use codex_core::NewConversation;
+use codex_core::config::Config as CodexConfig;
+use codex_core::parse_command::extract_shell_command;
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::Submission;
+use codex_protocol::ConversationId;
+use codex_protocol::plan_tool::StepStatus;
+use codex_protocol::user_input::UserInput;
+use mcp_types::CallToolResult;
+use mcp_types::ContentBlock;
+use mcp_types::ModelContextProtocolNotification;
+use mcp_types::ProgressNotification;
+use mcp_types::ProgressNotificationParams;
+use mcp_types::ProgressToken;
+use mcp_types::RequestId;
+use mcp_types::TextContent;
+use serde::Serialize;
+use serde_json::json;
+use tokio::sync::Mutex;
+
+use crate::outgoing_message::OutgoingMessageSender;
+use crate::outgoing_message::OutgoingNotification;
+use crate::outgoing_message::OutgoingNotificationMeta;
+
+/// How a task ended, as reported in `structuredContent.status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum TaskStatus {
+    Completed,
+    BudgetExceeded,
+    Aborted,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TaskOutcome {
+    conversation_id: ConversationId,
+    status: TaskStatus,
+    /// The agent's last message.
+    summary: String,
+    /// Why the task stopped early, for every status but `Completed`.
+    reason: Option<String>,
+    /// Cumulative diff of the files the task edited.
+    diff: Option<String>,
+}
+
+/// Reports progress to the caller when it asked for it.
+struct ProgressReporter {
+    outgoing: Arc<OutgoingMessageSender>,
+    token: Option<ProgressToken>,
+    progress: f64,
+}
+
+impl ProgressReporter {
+    async fn report(&mut self, message: String) {
+        let Some(token) = &self.token else {
+            return;
+        };
+        self.progress += 1.0;
+        let params = ProgressNotificationParams {
+            message: Some(message),
+            progress: self.progress,
+            progress_token: token.clone(),
+            total: None,
+        };
+        self.outgoing
+            .send_notification(OutgoingNotification {
+                method: ProgressNotification::METHOD.to_string(),
+                params: serde_json::to_value(params).ok(),
+            })
+            .await;
+    }
+}
+
+/// Run one task and answer the `tools/call` request `id` when it ends.
+pub(crate) async fn run_codex_task(
+    id: RequestId,
+    prompt: String,
+    config: CodexConfig,
+    progress_token: Option<ProgressToken>,
+    outgoing: Arc<OutgoingMessageSender>,
+    conversation_manager: Arc<ConversationManager>,
+    running_requests_id_to_codex_uuid: Arc<Mutex<HashMap<RequestId, ConversationId>>>,
+) {
+    let NewConversation {
+        conversation_id,
+        conversation,
+        session_configured,
+    } = match conversation_manager.new_conversation(config).await {
+        Ok(res) => res,
+        Err(e) => {
+            outgoing
+                .send_response(
+                    id,
+                    error_result(format!("Failed to start Codex session: {e}")),
+                )
+                .await;
+            return;
+        }
+    };
+    let meta = || Some(OutgoingNotificationMeta::new(Some(id.clone())));
+    outgoing
+        .send_event_as_notification(
+            &Event {
+                id: "".to_string(),
+                msg: EventMsg::SessionConfigured(session_configured),
+            },
+            meta(),
+        )
+        .await;
+
+    running_requests_id_to_codex_uuid
+        .lock()
+        .await
+        .insert(id.clone(), conversation_id);
+    let sub_id = match &id {
+        RequestId::String(s) => s.clone(),
+        RequestId::Integer(n) => n.to_string(),
+    };
+    let submission = Submission {
+        id: sub_id,
+        op: Op::UserInput {
+            items: vec![UserInput::Text { text: prompt }],
+        },
+    };
+    if let Err(e) = conversation.submit_with_id(submission).await {
+        running_requests_id_to_codex_uuid.lock().await.remove(&id);
+        outgoing
+            .send_response(id, error_result(format!("Failed to submit task: {e}")))
+            .await;
+        return;
+    }
+
+    let mut progress = ProgressReporter {
+        outgoing: outgoing.clone(),
+        token: progress_token,
+        progress: 0.0,
+    };
+    let mut diff = None;
+    let mut budget_exceeded = None;
+    let outcome = loop {
+        let event = match conversation.next_event().await {
+            Ok(event) => event,
+            Err(e) => {
+                break TaskOutcome {
+                    conversation_id,
+                    status: TaskStatus::Error,
+                    summary: String::new(),
+                    reason: Some(format!("Codex runtime error: {e}")),
+                    diff,
+                };
+            }
+        };
+        outgoing.send_event_as_notification(&event, meta()).await;
+        if let Some(message) = progress_message(&event.msg) {
+            progress.report(message).await;
+        }
+        match event.msg {
+            EventMsg::SessionDiff(session_diff) => diff = Some(session_diff.unified_diff),
+            EventMsg::BudgetExceeded(exceeded) => budget_exceeded = Some(exceeded.message),
+            EventMsg::TaskComplete(complete) => {
+                let status = if budget_exceeded.is_some() {
+                    TaskStatus::BudgetExceeded
+                } else {
+                    TaskStatus::Completed
+                };
+                break TaskOutcome {
+                    conversation_id,
+                    status,
+                    summary: complete.last_agent_message.unwrap_or_default(),
+                    reason: budget_exceeded,
+                    diff,
+                };
+            }
+            EventMsg::TurnAborted(aborted) => {
+                break TaskOutcome {
+                    conversation_id,
+                    status: TaskStatus::Aborted,
+                    summary: String::new(),
+                    reason: Some(format!("the task was aborted ({:?})", aborted.reason)),
+                    diff,
+                };
+            }
+            EventMsg::Error(error) => {
+                break TaskOutcome {
+                    conversation_id,
+                    status: TaskStatus::Error,
+                    summary: String::new(),
+                    reason: Some(error.message),
+                    diff,
+                };
+            }
+            _ => {}
+        }
+    };
+
+    running_requests_id_to_codex_uuid.lock().await.remove(&id);
+    outgoing.send_response(id, task_result(&outcome)).await;
+}
+
+/// One line describing `msg` for the caller, for the events that mark
+/// progress through a task.
+fn progress_message(msg: &EventMsg) -> Option<String> {
+    match msg {
+        EventMsg::TaskStarted(_) => Some("Task started".to_string()),
+        EventMsg::ExecCommandBegin(begin) => {
+            let command = match extract_shell_command(&begin.command) {
+                Some((_, script)) => script.to_string(),
+                None => shlex::try_join(begin.command.iter().map(String::as_str))
+                    .unwrap_or_else(|_| begin.command.join(" ")),
+            };
+            Some(format!("Running `{command}`"))
+        }
+        EventMsg::PatchApplyEnd(end) if end.success => {
+            let mut files: Vec<String> = end
+                .changes
+                .keys()
+                .map(|path| path.display().to_string())
+                .collect();
+            files.sort();
+            Some(format!("Edited {}", files.join(", ")))
+        }
+        EventMsg::PlanUpdate(plan) => {
+            let done = plan
+                .plan
+                .iter()
+                .filter(|item| matches!(item.status, StepStatus::Completed))
+                .count();
+            let current = plan
+                .plan
+                .iter()
+                .find(|item| matches!(item.status, StepStatus::InProgress));
+            Some(match current {
+                Some(item) => format!(
+                    "Plan: {done}/{} steps done; working on: {}",
+                    plan.plan.len(),
+                    item.step
+                ),
+                None => format!("Plan: {done}/{} steps done", plan.plan.len()),
+            })
+        }
+        EventMsg::AgentMessage(message) => Some(message.message.clone()),
+        EventMsg::BudgetExceeded(exceeded) => Some(exceeded.message.clone()),
+        _ => None,
+    }
+}
+
+/// The `tools/call` response for a finished task: the summary and diff as
+/// text for the calling model, and the same as `structuredContent`.
+fn task_result(outcome: &TaskOutcome) -> CallToolResult {
+    let mut text = outcome.summary.clone();
+    if let Some(reason) = &outcome.reason {
+        if !text.is_empty() {
+            text.push_str("\n\n");
+        }
+        text.push_str(&format!("Stopped early: {reason}"));
+    }
+    if let Some(diff) = &outcome.diff {
+        text.push_str(&format!("\n\n```diff\n{}\n```", diff.trim_end()));
+    }
+    let changed_files = outcome
+        .diff
+        .as_deref()
+        .map(changed_files)
+        .unwrap_or_default();
+    CallToolResult {
+        content: vec![ContentBlock::TextContent(TextContent {
+            r#type: "text".to_string(),
+            text: text.trim_start().to_string(),
+            annotations: None,
+        })],
+        is_error: matches!(outcome.status, TaskStatus::Aborted | TaskStatus::Error).then_some(true),
+        structured_content: Some(json!({
+            "conversationId": outcome.conversation_id.to_string(),
+            "status": outcome.status,
+            "summary": outcome.summary,
+            "reason": outcome.reason,
+            "diff": outcome.diff,
+            "changedFiles": changed_files,
+        })),
+    }
+}
+
+/// Paths named by the `diff --git` headers of `diff`, after the change.
+fn changed_files(diff: &str) -> Vec<String> {
+    diff.lines()
+        .filter_map(|line| line.strip_prefix("diff --git a/"))
+        .filter_map(|paths| paths.rsplit_once(" b/"))
+        .map(|(_, path)| path.to_string())
+        .collect()
+}
+
+fn error_result(text: String) -> CallToolResult {
+    CallToolResult {
+        content: vec![ContentBlock::TextContent(TextContent {
+            r#type: "text".to_string(),
+            text,
+            annotations: None,
+        })],
+        is_error: Some(true),
+        structured_content: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::protocol::ExecCommandBeginEvent;
+    use codex_protocol::plan_tool::PlanItemArg;
+    use codex_protocol::plan_tool::UpdatePlanArgs;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    #[test]
+    fn progress_is_reported_for_commands_and_plans() {
+        let begin = EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
+            call_id: "call-1".to_string(),
+            process_id: None,
+            turn_id: "turn-1".to_string(),
+            command: ["bash", "-lc", "cargo test -p codex-core"]
+                .map(String::from)
+                .to_vec(),
+            cwd: PathBuf::from("/repo"),
+            parsed_cmd: Vec::new(),
+            source: Default::default(),
+            interaction_input: None,
+        });
+        assert_eq!(
+            progress_message(&begin).as_deref(),
+            Some("Running `cargo test -p codex-core`")
+        );
+
+        let plan = EventMsg::PlanUpdate(UpdatePlanArgs {
+            explanation: None,
+            plan: vec![
+                PlanItemArg {
+                    step: "Write the parser".to_string(),
+                    status: StepStatus::Completed,
+                },
+                PlanItemArg {
+                    step: "Add tests".to_string(),
+                    status: StepStatus::InProgress,
+                },
+                PlanItemArg {
+                    step: "Update docs".to_string(),
+                    status: StepStatus::Pending,
+                },
+            ],
+        });
+        assert_eq!(
+            progress_message(&plan).as_deref(),
+            Some("Plan: 1/3 steps done; working on: Add tests")
+        );
+        assert_eq!(progress_message(&EventMsg::ShutdownComplete), None);
+    }
+
+    #[test]
+    fn results_carry_the_summary_and_diff() {
+        let conversation_id = ConversationId::new();
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-old\n+new\n";
+        let result = task_result(&TaskOutcome {
+            conversation_id,
+            status: TaskStatus::BudgetExceeded,
+            summary: "Fixed the parser.".to_string(),
+            reason: Some("Token budget exceeded".to_string()),
+            diff: Some(diff.to_string()),
+        });
+
+        assert_eq!(result.is_error, None);
+        let ContentBlock::TextContent(TextContent { text, .. }) = &result.content[0] else {
+            panic!("expected text content");
+        };
+        assert_eq!(
+            text,
+            &format!(
+                "Fixed the parser.\n\nStopped early: Token budget exceeded\n\n```diff\n{}\n```",
+                diff.trim_end()
+            )
+        );
+        assert_eq!(
+            result.structured_content,
+            Some(json!({
+                "conversationId": conversation_id.to_string(),
+                "status": "budget_exceeded",
+                "summary": "Fixed the parser.",
+                "reason": "Token budget exceeded",
+                "diff": diff,
+                "changedFiles": ["src/lib.rs"],
+            }))
+        );
+    }
+}
@@ -224,6 +224,131 @@ pub(crate) fn create_tool_for_codex_tool_call_reply_param() -> Tool {
     }
 }
 
+/// Arguments for a `codex_run_task` tool-call: a single, non-interactive
+/// Codex task driven by another agent.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct CodexRunTaskParam {
+    /// The task for Codex to carry out.
+    pub prompt: String,
+
+    /// Working directory for the task. If relative, it is resolved against
+    /// the server process's current working directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+
+    /// Sandbox mode: `read-only`, `workspace-write`, or `danger-full-access`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<CodexToolCallSandboxMode>,
+
+    /// Optional override for the model name (e.g. "o3", "o4-mini").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// Configuration profile from config.toml to specify default options.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
+    /// Stop the task once it has used this many tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<i64>,
+
+    /// Stop the task once its estimated spend reaches this many US dollars.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cost_usd: Option<f64>,
+
+    /// Stop the task after this many model requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_turns: Option<u32>,
+
+    /// Stop the task after this many seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_secs: Option<u64>,
+}
+
+/// Builds a `Tool` definition for the `codex_run_task` tool-call.
+pub(crate) fn create_tool_for_codex_run_task_param() -> Tool {
+    let schema = SchemaSettings::draft2019_09()
+        .with(|s| {
+            s.inline_subschemas = true;
+            s.option_add_null_type = false;
+        })
+        .into_generator()
+        .into_root_schema_for::<CodexRunTaskParam>();
+
+    #[expect(clippy::expect_used)]
+    let schema_value =
+        serde_json::to_value(&schema).expect("Codex run task schema should serialise to JSON");
+
+    let tool_input_schema =
+        serde_json::from_value::<ToolInputSchema>(schema_value).unwrap_or_else(|e| {
+            panic!("failed to create Tool from schema: {e}");
+        });
+
+    Tool {
+        name: "codex_run_task".to_string(),
+        title: Some("Run Codex Task".to_string()),
+        input_schema: tool_input_schema,
+        output_schema: None,
+        description: Some(
+            "Run a Codex task to completion without approvals, reporting progress as it goes. \
+             Returns a summary and the resulting diff."
+                .to_string(),
+        ),
+        annotations: None,
+    }
+}
+
+impl CodexRunTaskParam {
+    /// Returns the task prompt and the Config to run it with. Approvals are
+    /// turned off because nobody is there to answer them, and the per-call
+    /// limits can only tighten the configured `[budget]`.
+    pub async fn into_config(
+        self,
+        codex_linux_sandbox_exe: Option<PathBuf>,
+    ) -> std::io::Result<(String, Config)> {
+        let Self {
+            prompt,
+            cwd,
+            sandbox,
+            model,
+            profile,
+            max_tokens,
+            max_cost_usd,
+            max_turns,
+            max_duration_secs,
+        } = self;
+
+        let overrides = ConfigOverrides {
+            model,
+            config_profile: profile,
+            cwd: cwd.map(PathBuf::from),
+            approval_policy: Some(AskForApproval::Never),
+            sandbox_mode: sandbox.map(Into::into),
+            codex_linux_sandbox_exe,
+            ..Default::default()
+        };
+        let mut cfg =
+            Config::load_with_cli_overrides_and_harness_overrides(Vec::new(), overrides).await?;
+
+        let budget = &mut cfg.budget;
+        budget.max_tokens = tighter(budget.max_tokens, max_tokens, i64::min);
+        budget.max_cost_usd = tighter(budget.max_cost_usd, max_cost_usd, f64::min);
+        budget.max_turns = tighter(budget.max_turns, max_turns, u32::min);
+        budget.max_task_duration_secs =
+            tighter(budget.max_task_duration_secs, max_duration_secs, u64::min);
+
+        Ok((prompt, cfg))
+    }
+}
+
+fn tighter<T>(configured: Option<T>, requested: Option<T>, min: fn(T, T) -> T) -> Option<T> {
+    match (configured, requested) {
+        (Some(configured), Some(requested)) => Some(min(configured, requested)),
+        (configured, requested) => configured.or(requested),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -340,4 +465,80 @@ mod tests {
         });
         assert_eq!(expected_tool_json, tool_json);
     }
+
+    #[test]
+    fn verify_codex_run_task_json_schema() {
+        let tool = create_tool_for_codex_run_task_param();
+        let tool_json = serde_json::to_value(&tool).expect("tool serializes");
+        let expected_tool_json = serde_json::json!({
+          "name": "codex_run_task",
+          "title": "Run Codex Task",
+          "description": "Run a Codex task to completion without approvals, reporting progress as it goes. Returns a summary and the resulting diff.",
+          "inputSchema": {
+            "type": "object",
+            "properties": {
+              "prompt": {
+                "description": "The task for Codex to carry out.",
+                "type": "string"
+              },
+              "cwd": {
+                "description": "Working directory for the task. If relative, it is resolved against the server process's current working directory.",
+                "type": "string"
+              },
+              "sandbox": {
+                "description": "Sandbox mode: `read-only`, `workspace-write`, or `danger-full-access`.",
+                "enum": [
+                  "read-only",
+                  "workspace-write",
+                  "danger-full-access"
+                ],
+                "type": "string"
+              },
+              "model": {
+                "description": "Optional override for the model name (e.g. \"o3\", \"o4-mini\").",
+                "type": "string"
+              },
+              "profile": {
+                "description": "Configuration profile from config.toml to specify default options.",
+                "type": "string"
+              },
+              "max-tokens": {
+                "description": "Stop the task once it has used this many tokens.",
+                "format": "int64",
+                "type": "integer"
+              },
+              "max-cost-usd": {
+                "description": "Stop the task once its estimated spend reaches this many US dollars.",
+                "format": "double",
+                "type": "number"
+              },
+              "max-turns": {
+                "description": "Stop the task after this many model requests.",
+                "format": "uint32",
+                "minimum": 0.0,
+                "type": "integer"
+              },
+              "max-duration-secs": {
+                "description": "Stop the task after this many seconds.",
+                "format": "uint64",
+                "minimum": 0.0,
+                "type": "integer"
+              },
+            },
+            "required": [
+              "prompt"
+            ]
+          }
+        });
+        assert_eq!(expected_tool_json, tool_json);
+    }
+
+    #[test]
+    fn per_call_limits_only_tighten_the_budget() {
+        assert_eq!(tighter(Some(10), Some(4), u32::min), Some(4));
+        assert_eq!(tighter(Some(4), Some(10), u32::min), Some(4));
+        assert_eq!(tighter(None, Some(10), u32::min), Some(10));
+        assert_eq!(tighter(Some(2.5), None, f64::min), Some(2.5));
+        assert_eq!(tighter::<u64>(None, None, u64::min), None);
+    }
 }
@@ -181,6 +181,7 @@ async fn run_codex_tool_session_inner(
                         reason: _,
                         proposed_execpolicy_amendment: _,
                         parsed_cmd,
+                        proposed_permissions: _,
                     }) => {
                         handle_exec_approval_request(
                             command,
@@ -217,6 +218,7 @@ async fn run_codex_tool_session_inner(
                         reason,
                         grant_root,
                         changes,
+                        proposed_permissions: _,
                     }) => {
                         handle_patch_approval_request(
                             call_id,
@@ -286,9 +288,12 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::ExecCommandEnd(_)
                     | EventMsg::BackgroundEvent(_)
                     | EventMsg::StreamError(_)
+                    | EventMsg::RetryScheduled(_)
                     | EventMsg::PatchApplyBegin(_)
                     | EventMsg::PatchApplyEnd(_)
                     | EventMsg::TurnDiff(_)
+                    | EventMsg::SessionDiff(_)
+                    | EventMsg::SecretsRedacted(_)
                     | EventMsg::WebSearchBegin(_)
                     | EventMsg::WebSearchEnd(_)
                     | EventMsg::GetHistoryEntryResponse(_)
@@ -305,6 +310,14 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::ReasoningContentDelta(_)
                     | EventMsg::ReasoningRawContentDelta(_)
                     | EventMsg::SkillsUpdateAvailable
+                    | EventMsg::PinnedContextUpdated(_)
+                    | EventMsg::BudgetExceeded(_)
+                    | EventMsg::TurnTimedOut(_)
+                    | EventMsg::ToolCallLoopDetected(_)
+                    | EventMsg::ContextPressure(_)
+                    | EventMsg::ContextItemsDropped(_)
+                    | EventMsg::ReadOnlyModeChanged(_)
+                    | EventMsg::SubAgent(_)
                     | EventMsg::UndoStarted(_)
                     | EventMsg::UndoCompleted(_)
                     | EventMsg::ExitedReviewMode(_)
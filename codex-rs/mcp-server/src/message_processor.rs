@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::codex_tool_config::CodexRunTaskParam;
 use crate::codex_tool_config::CodexToolCallParam;
 use crate::codex_tool_config::CodexToolCallReplyParam;
+use crate::codex_tool_config::create_tool_for_codex_run_task_param;
 use crate::codex_tool_config::create_tool_for_codex_tool_call_param;
 use crate::codex_tool_config::create_tool_for_codex_tool_call_reply_param;
 use crate::error_code::INVALID_REQUEST_ERROR_CODE;
@@ -27,6 +29,7 @@ use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
 use mcp_types::ListToolsResult;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ProgressToken;
 use mcp_types::RequestId;
 use mcp_types::ServerCapabilitiesTools;
 use mcp_types::ServerNotification;
@@ -53,11 +56,7 @@ impl MessageProcessor {
         config: Arc<Config>,
     ) -> Self {
         let outgoing = Arc::new(outgoing);
-        let auth_manager = AuthManager::shared(
-            config.codex_home.clone(),
-            false,
-            config.cli_auth_credentials_store_mode,
-        );
+        let auth_manager = AuthManager::shared_from_config(&config, false);
         let conversation_manager =
             Arc::new(ConversationManager::new(auth_manager, SessionSource::Mcp));
         Self {
@@ -72,6 +71,14 @@ impl MessageProcessor {
     pub(crate) async fn process_request(&mut self, request: JSONRPCRequest) {
         // Hold on to the ID so we can respond.
         let request_id = request.id.clone();
+        // `_meta` is not part of the typed params, so read the progress token
+        // before converting.
+        let progress_token = request
+            .params
+            .as_ref()
+            .and_then(|params| params.get("_meta"))
+            .and_then(|meta| meta.get("progressToken"))
+            .and_then(|token| serde_json::from_value::<ProgressToken>(token.clone()).ok());
 
         let client_request = match McpClientRequest::try_from(request) {
             Ok(client_request) => client_request,
@@ -114,7 +121,8 @@ impl MessageProcessor {
                 self.handle_list_tools(request_id, params).await;
             }
             McpClientRequest::CallToolRequest(params) => {
-                self.handle_call_tool(request_id, params).await;
+                self.handle_call_tool(request_id, params, progress_token)
+                    .await;
             }
             McpClientRequest::SetLevelRequest(params) => {
                 self.handle_set_level(params);
@@ -306,6 +314,7 @@ impl MessageProcessor {
             tools: vec![
                 create_tool_for_codex_tool_call_param(),
                 create_tool_for_codex_tool_call_reply_param(),
+                create_tool_for_codex_run_task_param(),
             ],
             next_cursor: None,
         };
@@ -318,6 +327,7 @@ impl MessageProcessor {
         &self,
         id: RequestId,
         params: <mcp_types::CallToolRequest as mcp_types::ModelContextProtocolRequest>::Params,
+        progress_token: Option<ProgressToken>,
     ) {
         tracing::info!("tools/call -> params: {:?}", params);
         let CallToolRequestParams { name, arguments } = params;
@@ -328,6 +338,10 @@ impl MessageProcessor {
                 self.handle_tool_call_codex_session_reply(id, arguments)
                     .await
             }
+            "codex_run_task" => {
+                self.handle_tool_call_codex_run_task(id, arguments, progress_token)
+                    .await
+            }
             _ => {
                 let result = CallToolResult {
                     content: vec![ContentBlock::TextContent(TextContent {
@@ -422,6 +436,78 @@ impl MessageProcessor {
         });
     }
 
+    async fn handle_tool_call_codex_run_task(
+        &self,
+        id: RequestId,
+        arguments: Option<serde_json::Value>,
+        progress_token: Option<ProgressToken>,
+    ) {
+        let param = match arguments.map(serde_json::from_value::<CodexRunTaskParam>) {
+            Some(Ok(param)) => param,
+            Some(Err(e)) => {
+                self.send_call_tool_error(
+                    id,
+                    format!("Failed to parse arguments for codex_run_task: {e}"),
+                )
+                .await;
+                return;
+            }
+            None => {
+                self.send_call_tool_error(
+                    id,
+                    "Missing arguments for codex_run_task tool-call; the `prompt` field is required."
+                        .to_string(),
+                )
+                .await;
+                return;
+            }
+        };
+        let (prompt, config) = match param
+            .into_config(self.codex_linux_sandbox_exe.clone())
+            .await
+        {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                self.send_call_tool_error(
+                    id,
+                    format!("Failed to load Codex configuration for codex_run_task: {e}"),
+                )
+                .await;
+                return;
+            }
+        };
+
+        let outgoing = self.outgoing.clone();
+        let conversation_manager = self.conversation_manager.clone();
+        let running_requests_id_to_codex_uuid = self.running_requests_id_to_codex_uuid.clone();
+        task::spawn(async move {
+            crate::codex_run_task::run_codex_task(
+                id,
+                prompt,
+                config,
+                progress_token,
+                outgoing,
+                conversation_manager,
+                running_requests_id_to_codex_uuid,
+            )
+            .await;
+        });
+    }
+
+    async fn send_call_tool_error(&self, id: RequestId, text: String) {
+        let result = CallToolResult {
+            content: vec![ContentBlock::TextContent(TextContent {
+                r#type: "text".to_string(),
+                text,
+                annotations: None,
+            })],
+            is_error: Some(true),
+            structured_content: None,
+        };
+        self.send_response::<mcp_types::CallToolRequest>(id, result)
+            .await;
+    }
+
     async fn handle_tool_call_codex_session_reply(
         &self,
         request_id: RequestId,
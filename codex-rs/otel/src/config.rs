@@ -11,6 +11,7 @@ pub struct OtelSettings {
     pub codex_home: PathBuf,
     pub exporter: OtelExporter,
     pub trace_exporter: OtelExporter,
+    pub metrics_exporter: OtelExporter,
 }
 
 #[derive(Clone, Debug)]
@@ -1,4 +1,6 @@
 pub mod config;
 
+mod metrics;
+
 pub mod otel_manager;
 pub mod otel_provider;
@@ -0,0 +1,196 @@
+//! Counters and histograms exported through the OTLP metrics exporter.
+//!
+//! Instruments are created from the global meter provider, which
+//! `OtelProvider` installs when a metrics exporter is configured. Without
+//! one, recording goes to the no-op provider and costs next to nothing.
+
+use std::fmt;
+use std::time::Duration;
+
+use opentelemetry::KeyValue;
+use opentelemetry::global;
+use opentelemetry::metrics::Counter;
+use opentelemetry::metrics::Histogram;
+use opentelemetry::metrics::Meter;
+
+const METER_NAME: &str = "codex";
+
+#[derive(Clone)]
+pub(crate) struct Metrics {
+    turn_duration: Histogram<f64>,
+    tool_calls: Counter<u64>,
+    tool_duration: Histogram<f64>,
+    api_requests: Counter<u64>,
+    api_request_duration: Histogram<f64>,
+    tokens: Counter<u64>,
+    tool_decisions: Counter<u64>,
+}
+
+impl fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Metrics").finish_non_exhaustive()
+    }
+}
+
+impl Metrics {
+    pub(crate) fn global() -> Self {
+        Self::new(&global::meter(METER_NAME))
+    }
+
+    pub(crate) fn new(meter: &Meter) -> Self {
+        Self {
+            turn_duration: meter
+                .f64_histogram("codex.turn.duration")
+                .with_description("Wall-clock time of a turn, from user input to its end")
+                .with_unit("s")
+                .build(),
+            tool_calls: meter
+                .u64_counter("codex.tool.calls")
+                .with_description("Tool calls by tool and outcome")
+                .build(),
+            tool_duration: meter
+                .f64_histogram("codex.tool.duration")
+                .with_description("Execution time of a tool call")
+                .with_unit("s")
+                .build(),
+            api_requests: meter
+                .u64_counter("codex.api.requests")
+                .with_description("Model API requests by HTTP status")
+                .build(),
+            api_request_duration: meter
+                .f64_histogram("codex.api.request.duration")
+                .with_description("Time until the model API responded")
+                .with_unit("s")
+                .build(),
+            tokens: meter
+                .u64_counter("codex.token.usage")
+                .with_description("Tokens reported by the model, by type")
+                .with_unit("{token}")
+                .build(),
+            tool_decisions: meter
+                .u64_counter("codex.tool.decisions")
+                .with_description("Approval decisions for tool calls, by decision and source")
+                .build(),
+        }
+    }
+
+    pub(crate) fn turn(&self, model: &str, duration: Duration) {
+        self.turn_duration.record(
+            duration.as_secs_f64(),
+            &[KeyValue::new("model", model.to_string())],
+        );
+    }
+
+    /// `error_kind` is the failure category of a failed call.
+    pub(crate) fn tool_call(
+        &self,
+        tool_name: &str,
+        success: bool,
+        error_kind: Option<&str>,
+        duration: Duration,
+    ) {
+        let mut attributes = vec![
+            KeyValue::new("tool_name", tool_name.to_string()),
+            KeyValue::new("success", success),
+        ];
+        if let Some(error_kind) = error_kind {
+            attributes.push(KeyValue::new("error_kind", error_kind.to_string()));
+        }
+        self.tool_calls.add(1, &attributes);
+        self.tool_duration
+            .record(duration.as_secs_f64(), &attributes);
+    }
+
+    pub(crate) fn api_request(&self, model: &str, status: Option<u16>, duration: Duration) {
+        let status = status.map_or_else(|| "error".to_string(), |status| status.to_string());
+        let attributes = [
+            KeyValue::new("model", model.to_string()),
+            KeyValue::new("http.response.status_code", status),
+        ];
+        self.api_requests.add(1, &attributes);
+        self.api_request_duration
+            .record(duration.as_secs_f64(), &attributes);
+    }
+
+    /// `usage` pairs a token type (`input`, `output`, ...) with its count;
+    /// negative counts are ignored.
+    pub(crate) fn tokens(&self, model: &str, usage: &[(&'static str, i64)]) {
+        for (kind, count) in usage {
+            let Ok(count) = u64::try_from(*count) else {
+                continue;
+            };
+            self.tokens.add(
+                count,
+                &[
+                    KeyValue::new("model", model.to_string()),
+                    KeyValue::new("type", *kind),
+                ],
+            );
+        }
+    }
+
+    pub(crate) fn tool_decision(&self, tool_name: &str, decision: &str, source: &str) {
+        self.tool_decisions.add(
+            1,
+            &[
+                KeyValue::new("tool_name", tool_name.to_string()),
+                KeyValue::new("decision", decision.to_string()),
+                KeyValue::new("source", source.to_string()),
+            ],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::metrics::MeterProvider as _;
+    use opentelemetry_sdk::metrics::InMemoryMetricExporter;
+    use opentelemetry_sdk::metrics::PeriodicReader;
+    use opentelemetry_sdk::metrics::SdkMeterProvider;
+
+    #[test]
+    fn records_every_instrument() {
+        let exporter = InMemoryMetricExporter::default();
+        let provider = SdkMeterProvider::builder()
+            .with_reader(PeriodicReader::builder(exporter.clone()).build())
+            .build();
+        let metrics = Metrics::new(&provider.meter(METER_NAME));
+
+        metrics.turn("gpt-5", Duration::from_secs(3));
+        metrics.tool_call("shell", true, None, Duration::from_millis(250));
+        metrics.tool_call(
+            "shell",
+            false,
+            Some("sandbox_denied"),
+            Duration::from_millis(40),
+        );
+        metrics.api_request("gpt-5", Some(200), Duration::from_millis(800));
+        metrics.tokens("gpt-5", &[("input", 120), ("output", 30), ("cached", -1)]);
+        metrics.tool_decision("shell", "approved", "user");
+        provider.force_flush().expect("flush metrics");
+
+        let mut names: Vec<String> = exporter
+            .get_finished_metrics()
+            .expect("finished metrics")
+            .iter()
+            .flat_map(|resource| resource.scope_metrics())
+            .flat_map(|scope| scope.metrics())
+            .map(|metric| metric.name().to_string())
+            .collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(
+            names,
+            vec![
+                "codex.api.request.duration",
+                "codex.api.requests",
+                "codex.token.usage",
+                "codex.tool.calls",
+                "codex.tool.decisions",
+                "codex.tool.duration",
+                "codex.turn.duration",
+            ]
+        );
+    }
+}
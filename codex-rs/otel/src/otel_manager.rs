@@ -1,3 +1,4 @@
+use crate::metrics::Metrics;
 use crate::otel_provider::traceparent_context_from_env;
 use chrono::SecondsFormat;
 use chrono::Utc;
@@ -48,10 +49,17 @@ pub struct OtelEventMetadata {
     terminal_type: String,
 }
 
+/// Failure category of a tool call, reported as `error_kind` on
+/// `codex.tool_result` events and the tool call metrics.
+pub trait ToolErrorKind {
+    fn error_kind(&self) -> &'static str;
+}
+
 #[derive(Debug, Clone)]
 pub struct OtelManager {
     metadata: OtelEventMetadata,
     session_span: Span,
+    metrics: Metrics,
 }
 
 impl OtelManager {
@@ -86,6 +94,7 @@ impl OtelManager {
                 terminal_type,
             },
             session_span,
+            metrics: Metrics::global(),
         }
     }
 
@@ -182,6 +191,8 @@ impl OtelManager {
         error: Option<&str>,
         duration: Duration,
     ) {
+        self.metrics
+            .api_request(&self.metadata.model, status, duration);
         tracing::event!(
             tracing::Level::INFO,
             event.name = "codex.api_request",
@@ -334,6 +345,15 @@ impl OtelManager {
         reasoning_token_count: Option<i64>,
         tool_token_count: i64,
     ) {
+        self.metrics.tokens(
+            &self.metadata.model,
+            &[
+                ("input", input_token_count),
+                ("output", output_token_count),
+                ("cached", cached_token_count.unwrap_or_default()),
+                ("reasoning", reasoning_token_count.unwrap_or_default()),
+            ],
+        );
         tracing::event!(
             tracing::Level::INFO,
             event.name = "codex.sse_event",
@@ -355,6 +375,12 @@ impl OtelManager {
         );
     }
 
+    /// Records how long a turn took, from the user's input until the model's
+    /// final response. Interrupted turns are not recorded.
+    pub fn turn_completed(&self, duration: Duration) {
+        self.metrics.turn(&self.metadata.model, duration);
+    }
+
     pub fn user_prompt(&self, items: &[UserInput]) {
         let prompt = items
             .iter()
@@ -394,6 +420,9 @@ impl OtelManager {
         decision: &ReviewDecision,
         source: ToolDecisionSource,
     ) {
+        let decision = decision.clone().to_string().to_lowercase();
+        self.metrics
+            .tool_decision(tool_name, &decision, &source.to_string());
         tracing::event!(
             tracing::Level::INFO,
             event.name = "codex.tool_decision",
@@ -408,7 +437,7 @@ impl OtelManager {
             slug = %self.metadata.slug,
             tool_name = %tool_name,
             call_id = %call_id,
-            decision = %decision.clone().to_string().to_lowercase(),
+            decision = %decision,
             source = %source.to_string(),
         );
     }
@@ -423,15 +452,19 @@ impl OtelManager {
     where
         F: FnOnce() -> Fut,
         Fut: Future<Output = Result<(String, bool), E>>,
-        E: Display,
+        E: Display + ToolErrorKind,
     {
         let start = Instant::now();
         let result = f().await;
         let duration = start.elapsed();
 
-        let (output, success) = match &result {
-            Ok((preview, success)) => (Cow::Borrowed(preview.as_str()), *success),
-            Err(error) => (Cow::Owned(error.to_string()), false),
+        let (output, success, error_kind) = match &result {
+            Ok((preview, success)) => (Cow::Borrowed(preview.as_str()), *success, None),
+            Err(error) => (
+                Cow::Owned(error.to_string()),
+                false,
+                Some(error.error_kind()),
+            ),
         };
 
         self.tool_result(
@@ -440,13 +473,16 @@ impl OtelManager {
             arguments,
             duration,
             success,
+            error_kind,
             output.as_ref(),
         );
 
         result
     }
 
-    pub fn log_tool_failed(&self, tool_name: &str, error: &str) {
+    pub fn log_tool_failed(&self, tool_name: &str, error_kind: &str, error: &str) {
+        self.metrics
+            .tool_call(tool_name, false, Some(error_kind), Duration::ZERO);
         tracing::event!(
             tracing::Level::INFO,
             event.name = "codex.tool_result",
@@ -462,6 +498,7 @@ impl OtelManager {
             tool_name = %tool_name,
             duration_ms = %Duration::ZERO.as_millis(),
             success = %false,
+            error_kind = %error_kind,
             output = %error,
         );
     }
@@ -473,8 +510,11 @@ impl OtelManager {
         arguments: &str,
         duration: Duration,
         success: bool,
+        error_kind: Option<&str>,
         output: &str,
     ) {
+        self.metrics
+            .tool_call(tool_name, success, error_kind, duration);
         let success_str = if success { "true" } else { "false" };
 
         tracing::event!(
@@ -494,6 +534,7 @@ impl OtelManager {
             arguments = %arguments,
             duration_ms = %duration.as_millis(),
             success = %success_str,
+            error_kind = error_kind,
             output = %output,
         );
     }
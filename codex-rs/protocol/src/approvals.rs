@@ -37,6 +37,38 @@ impl From<Vec<String>> for ExecPolicyAmendment {
     }
 }
 
+/// A narrowly scoped permission the user can grant instead of approving a
+/// single call, e.g. write access to `src/**` or network access to
+/// `api.github.com`.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Hash, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScopedPermission {
+    /// Writing paths that match `pattern`, a glob relative to the project
+    /// root or an absolute one.
+    Write { pattern: String },
+    /// Connecting to `host`. `*.example.com` also matches subdomains.
+    Network { host: String },
+}
+
+impl std::fmt::Display for ScopedPermission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScopedPermission::Write { pattern } => write!(f, "write access to {pattern}"),
+            ScopedPermission::Network { host } => write!(f, "network access to {host}"),
+        }
+    }
+}
+
+/// How long granted [`ScopedPermission`]s are remembered.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionScope {
+    /// Until the session ends.
+    Session,
+    /// Saved for the current project in `config.toml`.
+    Project,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ExecApprovalRequestEvent {
     /// Identifier for the associated exec call, if available.
@@ -57,6 +89,10 @@ pub struct ExecApprovalRequestEvent {
     #[ts(optional)]
     pub proposed_execpolicy_amendment: Option<ExecPolicyAmendment>,
     pub parsed_cmd: Vec<ParsedCommand>,
+    /// Permissions that would let this command, and others like it, run
+    /// without a blanket approval.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub proposed_permissions: Vec<ScopedPermission>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
@@ -92,4 +128,8 @@ pub struct ApplyPatchApprovalRequestEvent {
     /// When set, the agent is asking the user to allow writes under this root for the remainder of the session.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub grant_root: Option<PathBuf>,
+    /// Permissions that would let this patch, and others like it, be applied
+    /// without an approval prompt.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub proposed_permissions: Vec<ScopedPermission>,
 }
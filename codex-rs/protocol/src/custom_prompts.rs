@@ -10,6 +10,10 @@ use ts_rs::TS;
 /// - Full slash prefix: `"/{PROMPTS_CMD_PREFIX}:"`
 pub const PROMPTS_CMD_PREFIX: &str = "prompts";
 
+/// Placeholder in a custom command body that is replaced by the text typed
+/// after the command name.
+pub const COMMAND_ARGUMENTS_PLACEHOLDER: &str = "$ARGUMENTS";
+
 #[derive(Serialize, Deserialize, Debug, Clone, JsonSchema, TS)]
 pub struct CustomPrompt {
     pub name: String,
@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use codex_utils_image::load_and_resize_to_fit;
 use mcp_types::CallToolResult;
 use mcp_types::ContentBlock;
+use mcp_types::EmbeddedResourceResource;
 use serde::Deserialize;
 use serde::Deserializer;
 use serde::Serialize;
@@ -331,6 +332,7 @@ impl From<Vec<UserInput>> for ResponseInputItem {
                         }
                     },
                     UserInput::Skill { .. } => None, // Skill bodies are injected later in core
+                    UserInput::FileMention { .. } => None, // Attached later in core
                 })
                 .collect::<Vec<ContentItem>>(),
         }
@@ -462,10 +464,20 @@ impl From<&CallToolResult> for FunctionCallOutputPayload {
         {
             match serde_json::to_string(structured_content) {
                 Ok(serialized_structured_content) => {
+                    // Structured content replaces the text blocks, but images
+                    // (screenshots, plots) have no structured form.
+                    let images: Vec<_> = content.iter().filter_map(image_block_to_item).collect();
+                    let content_items = (!images.is_empty()).then(|| {
+                        std::iter::once(FunctionCallOutputContentItem::InputText {
+                            text: serialized_structured_content.clone(),
+                        })
+                        .chain(images)
+                        .collect()
+                    });
                     return FunctionCallOutputPayload {
                         content: serialized_structured_content,
+                        content_items,
                         success: Some(is_success),
-                        ..Default::default()
                     };
                 }
                 Err(err) => {
@@ -499,37 +511,67 @@ impl From<&CallToolResult> for FunctionCallOutputPayload {
     }
 }
 
+/// Only returns items when at least one block is an image; otherwise the
+/// serialized blocks in `content` are sent as they are. Blocks the model
+/// cannot take (audio, links, binary resources) are described in text.
 fn convert_content_blocks_to_items(
     blocks: &[ContentBlock],
 ) -> Option<Vec<FunctionCallOutputContentItem>> {
     let mut saw_image = false;
     let mut items = Vec::with_capacity(blocks.len());
-    tracing::warn!("Blocks: {:?}", blocks);
     for block in blocks {
-        match block {
-            ContentBlock::TextContent(text) => {
-                items.push(FunctionCallOutputContentItem::InputText {
-                    text: text.text.clone(),
-                });
-            }
-            ContentBlock::ImageContent(image) => {
-                saw_image = true;
-                // Just in case the content doesn't include a data URL, add it.
-                let image_url = if image.data.starts_with("data:") {
-                    image.data.clone()
-                } else {
-                    format!("data:{};base64,{}", image.mime_type, image.data)
-                };
-                items.push(FunctionCallOutputContentItem::InputImage { image_url });
-            }
-            // TODO: render audio, resource, and embedded resource content to the model.
-            _ => return None,
+        if let Some(image) = image_block_to_item(block) {
+            saw_image = true;
+            items.push(image);
+            continue;
         }
+        let text = match block {
+            ContentBlock::TextContent(text) => text.text.clone(),
+            ContentBlock::AudioContent(audio) => {
+                format!("[audio ({}) omitted]", audio.mime_type)
+            }
+            ContentBlock::ResourceLink(link) => format!("[resource: {}]({})", link.name, link.uri),
+            ContentBlock::EmbeddedResource(resource) => match &resource.resource {
+                EmbeddedResourceResource::TextResourceContents(text) => text.text.clone(),
+                EmbeddedResourceResource::BlobResourceContents(blob) => format!(
+                    "[binary resource {} ({}) omitted]",
+                    blob.uri,
+                    blob.mime_type.as_deref().unwrap_or("unknown type")
+                ),
+            },
+            ContentBlock::ImageContent(_) => continue,
+        };
+        items.push(FunctionCallOutputContentItem::InputText { text });
     }
 
     if saw_image { Some(items) } else { None }
 }
 
+/// Image blocks and embedded `image/*` blobs as data URLs.
+fn image_block_to_item(block: &ContentBlock) -> Option<FunctionCallOutputContentItem> {
+    let (mime_type, data) = match block {
+        ContentBlock::ImageContent(image) => (image.mime_type.as_str(), &image.data),
+        ContentBlock::EmbeddedResource(resource) => match &resource.resource {
+            EmbeddedResourceResource::BlobResourceContents(blob) => {
+                let mime_type = blob.mime_type.as_deref()?;
+                if !mime_type.starts_with("image/") {
+                    return None;
+                }
+                (mime_type, &blob.blob)
+            }
+            EmbeddedResourceResource::TextResourceContents(_) => return None,
+        },
+        _ => return None,
+    };
+    // Just in case the content doesn't include a data URL, add it.
+    let image_url = if data.starts_with("data:") {
+        data.clone()
+    } else {
+        format!("data:{mime_type};base64,{data}")
+    };
+    Some(FunctionCallOutputContentItem::InputImage { image_url })
+}
+
 // Implement Display so callers can treat the payload like a plain string when logging or doing
 // trivial substring checks in tests (existing tests call `.contains()` on the output). Display
 // returns the raw `content` field.
@@ -553,6 +595,9 @@ impl std::ops::Deref for FunctionCallOutputPayload {
 mod tests {
     use super::*;
     use anyhow::Result;
+    use mcp_types::AudioContent;
+    use mcp_types::BlobResourceContents;
+    use mcp_types::EmbeddedResource;
     use mcp_types::ImageContent;
     use mcp_types::TextContent;
     use pretty_assertions::assert_eq;
@@ -643,6 +688,63 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn keeps_images_alongside_resources_and_structured_content() -> Result<()> {
+        let content = vec![
+            ContentBlock::EmbeddedResource(EmbeddedResource {
+                annotations: None,
+                resource: EmbeddedResourceResource::BlobResourceContents(BlobResourceContents {
+                    blob: "PNGDATA".into(),
+                    mime_type: Some("image/png".into()),
+                    uri: "file:///tmp/plot.png".into(),
+                }),
+                r#type: "resource".into(),
+            }),
+            ContentBlock::AudioContent(AudioContent {
+                annotations: None,
+                data: "AUDIO".into(),
+                mime_type: "audio/wav".into(),
+                r#type: "audio".into(),
+            }),
+        ];
+        let unstructured = FunctionCallOutputPayload::from(&CallToolResult {
+            content: content.clone(),
+            is_error: None,
+            structured_content: None,
+        });
+        assert_eq!(
+            unstructured.content_items,
+            Some(vec![
+                FunctionCallOutputContentItem::InputImage {
+                    image_url: "data:image/png;base64,PNGDATA".into(),
+                },
+                FunctionCallOutputContentItem::InputText {
+                    text: "[audio (audio/wav) omitted]".into(),
+                },
+            ])
+        );
+
+        let structured = FunctionCallOutputPayload::from(&CallToolResult {
+            content,
+            is_error: None,
+            structured_content: Some(serde_json::json!({"points": 3})),
+        });
+        assert_eq!(structured.content, r#"{"points":3}"#);
+        assert_eq!(
+            structured.content_items,
+            Some(vec![
+                FunctionCallOutputContentItem::InputText {
+                    text: r#"{"points":3}"#.into(),
+                },
+                FunctionCallOutputContentItem::InputImage {
+                    image_url: "data:image/png;base64,PNGDATA".into(),
+                },
+            ])
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn deserializes_array_payload_into_items() -> Result<()> {
         let json = r#"[
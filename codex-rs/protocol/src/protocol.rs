@@ -42,6 +42,8 @@ pub use crate::approvals::ApplyPatchApprovalRequestEvent;
 pub use crate::approvals::ElicitationAction;
 pub use crate::approvals::ExecApprovalRequestEvent;
 pub use crate::approvals::ExecPolicyAmendment;
+pub use crate::approvals::PermissionScope;
+pub use crate::approvals::ScopedPermission;
 
 /// Open/close tags for special user-input blocks. Used across crates to avoid
 /// duplicated hardcoded strings.
@@ -76,6 +78,14 @@ pub enum Op {
         items: Vec<UserInput>,
     },
 
+    /// Redirect the running task: cancel the in-flight model request, keep
+    /// its partial output in history marked as interrupted, and restart it
+    /// with `items` appended. Starts a new turn when no task is running.
+    Steer {
+        /// User input items, see `InputItem`
+        items: Vec<UserInput>,
+    },
+
     /// Similar to [`Op::UserInput`], but contains additional context required
     /// for a turn of a [`crate::codex_conversation::CodexConversation`].
     UserTurn {
@@ -141,6 +151,16 @@ pub enum Op {
         summary: Option<ReasoningSummaryConfig>,
     },
 
+    /// Use `effort` for the next task only; later tasks go back to the
+    /// session's effort. Providers that size reasoning in tokens (Anthropic
+    /// extended thinking, Gemini thinking) get the matching budget.
+    OverrideNextTurnReasoning { effort: ReasoningEffortConfig },
+
+    /// Switch the session into (or out of) read-only "ask" mode. While it is
+    /// on, mutating tools are neither offered nor run and commands use a
+    /// read-only sandbox. Reply is delivered via `EventMsg::ReadOnlyModeChanged`.
+    SetReadOnlyMode { enabled: bool },
+
     /// Approve a command execution
     ExecApproval {
         /// The id of the submission we are approving
@@ -213,6 +233,10 @@ pub enum Op {
     /// Request to shut down codex instance.
     Shutdown,
 
+    /// Shut down like `Shutdown`, but without waiting for in-flight tool
+    /// calls to finish, for a user quitting interactively.
+    Quit,
+
     /// Execute a user-initiated one-off shell command (triggered by "!cmd").
     ///
     /// The command string is executed using the user's default shell and may
@@ -225,6 +249,21 @@ pub enum Op {
 
     /// Request the list of available models.
     ListModels,
+
+    /// Pin a message, instruction, or file so it is included in every prompt
+    /// and is never dropped by compaction or history truncation.
+    /// Reply is delivered via `EventMsg::PinnedContextUpdated`.
+    PinContext { item: PinnedContextItem },
+
+    /// Remove a previously pinned item by its id.
+    /// Reply is delivered via `EventMsg::PinnedContextUpdated`.
+    UnpinContext { id: String },
+
+    /// Remove items from the conversation history, e.g. large tool outputs
+    /// listed by `EventMsg::ContextPressure`. Ids are those of
+    /// `ContextItemSummary`; dropping a tool call also drops its output.
+    /// Reply is delivered via `EventMsg::ContextItemsDropped`.
+    DropContextItems { ids: Vec<String> },
 }
 
 /// Determines the conditions under which the user is consulted to approve
@@ -622,6 +661,10 @@ pub enum EventMsg {
     /// and the system is handling it (e.g., retrying with backoff).
     StreamError(StreamErrorEvent),
 
+    /// A model request failed with a retryable error and will be retried
+    /// after `delay_ms` (e.g., after a rate limit's `Retry-After`).
+    RetryScheduled(RetryScheduledEvent),
+
     /// Notification that the agent is about to apply a code patch. Mirrors
     /// `ExecCommandBegin` so front‑ends can show progress indicators.
     PatchApplyBegin(PatchApplyBeginEvent),
@@ -631,6 +674,14 @@ pub enum EventMsg {
 
     TurnDiff(TurnDiffEvent),
 
+    /// Cumulative diff of the files tools changed during the session, sent
+    /// at the end of each turn that changed files.
+    SessionDiff(SessionDiffEvent),
+
+    /// Secrets were masked in a user message or tool output before it was
+    /// recorded or sent to the model.
+    SecretsRedacted(SecretsRedactedEvent),
+
     /// Response to GetHistoryEntryRequest.
     GetHistoryEntryResponse(GetHistoryEntryResponseEvent),
 
@@ -667,6 +718,35 @@ pub enum EventMsg {
     AgentMessageContentDelta(AgentMessageContentDeltaEvent),
     ReasoningContentDelta(ReasoningContentDeltaEvent),
     ReasoningRawContentDelta(ReasoningRawContentDeltaEvent),
+
+    /// The set of pinned context items changed.
+    PinnedContextUpdated(PinnedContextUpdatedEvent),
+
+    /// A configured budget was exhausted; no further model requests will be
+    /// issued for the task (or, for session-wide limits, the session).
+    BudgetExceeded(BudgetExceededEvent),
+
+    /// The task ran past `turn_timeout_secs`; its model request and tool
+    /// calls are cancelled and a `TurnAborted` with reason `timed_out`
+    /// follows.
+    TurnTimedOut(TurnTimedOutEvent),
+
+    /// The model repeated a tool call with identical arguments (or
+    /// alternated between two calls) `tool_call_loop_limit` times. The
+    /// repeats are answered with a loop warning instead of being run.
+    ToolCallLoopDetected(ToolCallLoopDetectedEvent),
+
+    /// Context window usage crossed one of `context_pressure_thresholds`.
+    ContextPressure(ContextPressureEvent),
+
+    /// Reply to `Op::DropContextItems`.
+    ContextItemsDropped(ContextItemsDroppedEvent),
+
+    /// Read-only "ask" mode was turned on or off with `Op::SetReadOnlyMode`.
+    ReadOnlyModeChanged(ReadOnlyModeChangedEvent),
+
+    /// An event emitted by a sub-agent started with the `spawn_agent` tool.
+    SubAgent(SubAgentEvent),
 }
 
 /// Codex errors that we expose to clients.
@@ -925,6 +1005,26 @@ impl TokenUsageInfo {
 pub struct TokenCountEvent {
     pub info: Option<TokenUsageInfo>,
     pub rate_limits: Option<RateLimitSnapshot>,
+    /// Estimated tokens consumed by pinned context items, reported separately
+    /// from the rest of the conversation history.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub pinned_context_tokens: Option<i64>,
+    /// Estimated spend in USD, present when pricing is known for the model
+    /// or priced usage has already been recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub spend: Option<SpendSummary>,
+}
+
+/// Estimated cost of model usage, derived from the configured pricing table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct SpendSummary {
+    /// Spend accumulated by the current session.
+    pub session_usd: f64,
+    /// Spend recorded in the usage ledger for the current local day, across
+    /// all sessions (including this one).
+    pub today_usd: f64,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
@@ -1294,6 +1394,8 @@ pub enum RolloutItem {
     Compacted(CompactedItem),
     TurnContext(TurnContextItem),
     EventMsg(EventMsg),
+    SessionSummary(SessionSummaryItem),
+    ToolOutputRef(ToolOutputRefItem),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
@@ -1303,6 +1405,22 @@ pub struct CompactedItem {
     pub replacement_history: Option<Vec<ResponseItem>>,
 }
 
+/// A short title and summary of the session, rewritten after each turn; the
+/// last one in a rollout file is current.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, TS)]
+pub struct SessionSummaryItem {
+    pub title: String,
+    pub summary: String,
+}
+
+/// Stands in for the output of tool call `call_id` when it is identical to the
+/// output of the earlier call `same_as` in the same rollout file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, TS)]
+pub struct ToolOutputRefItem {
+    pub call_id: String,
+    pub same_as: String,
+}
+
 impl From<CompactedItem> for ResponseItem {
     fn from(value: CompactedItem) -> Self {
         ResponseItem::Message {
@@ -1585,6 +1703,20 @@ pub struct StreamErrorEvent {
     pub codex_error_info: Option<CodexErrorInfo>,
 }
 
+/// Payload for `EventMsg::RetryScheduled`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct RetryScheduledEvent {
+    /// 1-based number of the retry that is about to be made.
+    pub retry: u64,
+    pub max_retries: u64,
+    pub delay_ms: u64,
+    /// HTTP status of the failed attempt, if it got a response.
+    #[serde(default)]
+    pub http_status_code: Option<u16>,
+    /// Human-readable summary, e.g. "rate limited, retrying in 20s".
+    pub message: String,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct StreamInfoEvent {
     pub message: String,
@@ -1628,6 +1760,19 @@ pub struct TurnDiffEvent {
     pub unified_diff: String,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct SessionDiffEvent {
+    pub unified_diff: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct SecretsRedactedEvent {
+    /// Number of values masked.
+    pub count: usize,
+    /// Names of the detectors that matched, sorted.
+    pub detectors: Vec<String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct GetHistoryEntryResponseEvent {
     pub offset: usize,
@@ -1702,10 +1847,167 @@ impl fmt::Display for McpAuthStatus {
     }
 }
 
+/// A piece of context the user asked to keep in every prompt.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type")]
+pub enum PinnedContextItem {
+    /// Free-form text supplied by the user.
+    Message { text: String },
+    /// A standing instruction the model should follow for the rest of the session.
+    Instruction { text: String },
+    /// A file whose contents are snapshotted when pinned. Relative paths are
+    /// resolved against the session working directory.
+    File { path: PathBuf },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct PinnedContextEntry {
+    pub id: String,
+    pub item: PinnedContextItem,
+    /// Estimated token cost of this item when rendered into the prompt.
+    #[ts(type = "number")]
+    pub tokens: i64,
+}
+
+/// Payload for `EventMsg::PinnedContextUpdated`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct PinnedContextUpdatedEvent {
+    pub entries: Vec<PinnedContextEntry>,
+    #[ts(type = "number")]
+    pub total_tokens: i64,
+}
+
+/// Payload for `EventMsg::ContextPressure`. Token counts are estimates.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ContextPressureEvent {
+    /// The threshold that was crossed, in percent of the context window.
+    pub threshold_percent: u8,
+    #[ts(type = "number")]
+    pub used_tokens: i64,
+    #[ts(type = "number")]
+    pub context_window: i64,
+    pub breakdown: ContextUsageBreakdown,
+    /// The largest items `Op::DropContextItems` can remove, biggest first.
+    pub largest_items: Vec<ContextItemSummary>,
+}
+
+/// Estimated tokens of each part of the prompt.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ContextUsageBreakdown {
+    /// Base instructions, AGENTS.md, skills and the environment context.
+    #[ts(type = "number")]
+    pub instructions: i64,
+    #[ts(type = "number")]
+    pub pinned: i64,
+    /// Outputs of tool calls.
+    #[ts(type = "number")]
+    pub tool_outputs: i64,
+    /// Messages, reasoning and tool calls.
+    #[ts(type = "number")]
+    pub history: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum ContextItemKind {
+    UserMessage,
+    AgentMessage,
+    Reasoning,
+    /// A tool call together with its output.
+    ToolCall,
+    Other,
+}
+
+/// One history item that can be dropped with `Op::DropContextItems`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ContextItemSummary {
+    /// The call id for tool calls, otherwise the item id (or a digest of the
+    /// item when it has none).
+    pub id: String,
+    pub kind: ContextItemKind,
+    #[ts(type = "number")]
+    pub tokens: i64,
+    /// The start of the item's text, or the tool name for tool calls.
+    pub preview: String,
+}
+
+/// Payload for `EventMsg::ContextItemsDropped`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ContextItemsDroppedEvent {
+    pub dropped: Vec<String>,
+    /// Requested ids that matched no droppable item.
+    pub not_found: Vec<String>,
+}
+
+/// Payload for `EventMsg::SubAgent`: an event from a delegated child agent,
+/// tagged with the id assigned when it was spawned.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct SubAgentEvent {
+    pub agent_id: String,
+    pub msg: Box<EventMsg>,
+}
+
+/// Which configured budget was exhausted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum BudgetLimit {
+    /// Total tokens used by the session (`budget.max_tokens`).
+    Tokens,
+    /// Estimated session spend in USD (`budget.max_cost_usd`).
+    CostUsd,
+    /// Model requests issued by the session (`budget.max_turns`).
+    Turns,
+    /// Wall-clock seconds spent on the current task (`budget.max_task_duration_secs`).
+    TaskDuration,
+}
+
+/// Payload for `EventMsg::ReadOnlyModeChanged`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ReadOnlyModeChangedEvent {
+    pub enabled: bool,
+    pub message: String,
+}
+
+/// Payload for `EventMsg::BudgetExceeded`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct BudgetExceededEvent {
+    pub limit: BudgetLimit,
+    /// The configured limit, in the unit implied by `limit`.
+    pub configured: f64,
+    /// The observed value that reached the limit.
+    pub observed: f64,
+    pub message: String,
+}
+
+/// Payload for `EventMsg::TurnTimedOut`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct TurnTimedOutEvent {
+    /// The configured `turn_timeout_secs`.
+    pub timeout_secs: u64,
+    pub message: String,
+}
+
+/// Payload for `EventMsg::ToolCallLoopDetected`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ToolCallLoopDetectedEvent {
+    /// The repeated tool, or both tools of a ping-pong.
+    pub tools: Vec<String>,
+    /// Calls in the loop so far.
+    pub calls: u64,
+    pub message: String,
+}
+
 /// Response payload for `Op::ListCustomPrompts`.
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ListCustomPromptsResponseEvent {
     pub custom_prompts: Vec<CustomPrompt>,
+    /// Custom slash commands from `$CODEX_HOME/commands` and the project's
+    /// `.codex/commands`, invoked as `/name` rather than `/prompts:name`.
+    #[serde(default)]
+    pub custom_commands: Vec<CustomPrompt>,
 }
 
 /// Response payload for `Op::ListSkills`.
@@ -1799,11 +2101,28 @@ pub enum ReviewDecision {
         proposed_execpolicy_amendment: ExecPolicyAmendment,
     },
 
+    /// User has approved this command and wants commands that start with
+    /// `prefix` to run without approval in the current project from now on.
+    ApprovedPrefixForProject { prefix: Vec<String> },
+
     /// User has approved this command and wants to automatically approve any
     /// future identical instances (`command` and `cwd` match exactly) for the
     /// remainder of the session.
     ApprovedForSession,
 
+    /// User has approved this request by granting `permissions`, which then
+    /// apply to similar requests for the given `scope`.
+    ApprovedPermissions {
+        permissions: Vec<ScopedPermission>,
+        scope: PermissionScope,
+    },
+
+    /// User has approved a patch except for the listed hunks, numbered per
+    /// file from zero in diff order; only the remaining hunks are applied.
+    PartiallyApproved {
+        rejected_hunks: HashMap<PathBuf, Vec<usize>>,
+    },
+
     /// User has denied this command and the agent should not execute it, but
     /// it should continue the session and try something else.
     #[default]
@@ -1849,6 +2168,7 @@ pub enum TurnAbortReason {
     Interrupted,
     Replaced,
     ReviewEnded,
+    TimedOut,
 }
 
 #[cfg(test)]
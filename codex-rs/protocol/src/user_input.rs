@@ -27,4 +27,10 @@ pub enum UserInput {
         name: String,
         path: std::path::PathBuf,
     },
+
+    /// File the user mentioned with `@path`, relative to the session cwd.
+    /// Its contents are attached to the turn in core.
+    FileMention {
+        path: std::path::PathBuf,
+    },
 }
@@ -143,10 +143,14 @@ pub(crate) fn build_default_headers(
     Ok(headers)
 }
 
+/// Sets the server's headers along with the process-wide proxy and CA
+/// settings from `[http]`; every HTTP client this crate builds goes through
+/// here.
 pub(crate) fn apply_default_headers(
     builder: ClientBuilder,
     default_headers: &HeaderMap,
 ) -> ClientBuilder {
+    let builder = codex_client::apply_http_client_options(builder);
     if default_headers.is_empty() {
         builder
     } else {
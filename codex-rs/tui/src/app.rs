@@ -7,6 +7,7 @@ use crate::diff_render::DiffSummary;
 use crate::exec_command::strip_bash_lc_and_escape;
 use crate::file_search::FileSearchManager;
 use crate::history_cell::HistoryCell;
+use crate::keymap::Keymap;
 use crate::model_migration::ModelMigrationOutcome;
 use crate::model_migration::migration_copy_for_models;
 use crate::model_migration::run_model_migration_prompt;
@@ -14,15 +15,19 @@ use crate::pager_overlay::Overlay;
 use crate::render::highlight::highlight_bash_to_lines;
 use crate::render::renderable::Renderable;
 use crate::resume_picker::ResumeSelection;
+use crate::theme::Theme;
 use crate::tui;
 use crate::tui::TuiEvent;
 use crate::update_action::UpdateAction;
 use codex_ansi_escape::ansi_escape_line;
 use codex_core::AuthManager;
 use codex_core::ConversationManager;
+use codex_core::config::CONFIG_TOML_FILE;
 use codex_core::config::Config;
 use codex_core::config::edit::ConfigEdit;
 use codex_core::config::edit::ConfigEditsBuilder;
+use codex_core::config::types::Tui;
+use codex_core::config::types::TuiTheme;
 #[cfg(target_os = "windows")]
 use codex_core::features::Feature;
 use codex_core::models_manager::manager::ModelsManager;
@@ -48,6 +53,7 @@ use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::widgets::Paragraph;
 use ratatui::widgets::Wrap;
+use serde::Deserialize;
 use std::collections::BTreeMap;
 use std::path::Path;
 use std::path::PathBuf;
@@ -116,6 +122,64 @@ fn emit_skill_load_warnings(app_event_tx: &AppEventSender, errors: &[SkillErrorI
     }
 }
 
+/// How often `config.toml` is checked for `[tui]` changes.
+const TUI_CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Activates the theme and keymap from the `[tui]` config, returning any
+/// problems found in it.
+fn apply_tui_appearance(
+    theme: Option<&str>,
+    themes: &BTreeMap<String, TuiTheme>,
+    keybindings: &BTreeMap<String, String>,
+) -> Vec<String> {
+    let (theme, mut warnings) = Theme::resolve(theme, themes);
+    crate::theme::set_theme(theme);
+    let (keymap, keymap_warnings) = Keymap::from_config(keybindings);
+    crate::keymap::set_keymap(keymap);
+    warnings.extend(keymap_warnings);
+    warnings
+}
+
+/// Polls `config.toml` and re-sends its `[tui]` section whenever the file
+/// changes, so theme and keybinding edits apply without a restart.
+fn spawn_tui_config_watcher(path: PathBuf, tx: AppEventSender) {
+    #[derive(Deserialize)]
+    struct TuiSection {
+        tui: Option<Tui>,
+    }
+
+    let modified = |path: &PathBuf| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    let mut last_modified = modified(&path);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TUI_CONFIG_POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            let current = modified(&path);
+            if current == last_modified {
+                continue;
+            }
+            last_modified = current;
+            let parsed = tokio::fs::read_to_string(&path)
+                .await
+                .map_err(|err| err.to_string())
+                .and_then(|contents| {
+                    toml::from_str::<TuiSection>(&contents).map_err(|err| err.to_string())
+                });
+            match parsed {
+                Ok(section) => {
+                    tx.send(AppEvent::TuiConfigReloaded(section.tui.unwrap_or_default()));
+                }
+                Err(err) => tx.send(AppEvent::InsertHistoryCell(Box::new(
+                    crate::history_cell::new_warning_event(format!(
+                        "Ignoring change to {}: {err}",
+                        path.display()
+                    )),
+                ))),
+            }
+        }
+    });
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct SessionSummary {
     usage_line: String,
@@ -343,6 +407,20 @@ impl App {
         let (app_event_tx, mut app_event_rx) = unbounded_channel();
         let app_event_tx = AppEventSender::new(app_event_tx);
 
+        for warning in apply_tui_appearance(
+            config.tui_theme.as_deref(),
+            &config.tui_themes,
+            &config.tui_keybindings,
+        ) {
+            app_event_tx.send(AppEvent::InsertHistoryCell(Box::new(
+                crate::history_cell::new_warning_event(warning),
+            )));
+        }
+        spawn_tui_config_watcher(
+            config.codex_home.join(CONFIG_TOML_FILE),
+            app_event_tx.clone(),
+        );
+
         let conversation_manager = Arc::new(ConversationManager::new(
             auth_manager.clone(),
             SessionSource::Cli,
@@ -510,6 +588,13 @@ impl App {
         tui: &mut tui::Tui,
         event: TuiEvent,
     ) -> Result<bool> {
+        let event = match event {
+            TuiEvent::Key(key_event) => match crate::keymap::translate(key_event) {
+                Some(key_event) => TuiEvent::Key(key_event),
+                None => return Ok(true),
+            },
+            other => other,
+        };
         if self.overlay.is_some() {
             let _ = self.handle_backtrack_overlay_event(tui, event).await?;
         } else {
@@ -726,6 +811,12 @@ impl App {
                 return Ok(false);
             }
             AppEvent::CodexOp(op) => self.chat_widget.submit_op(op),
+            AppEvent::VoiceTranscribed(result) => {
+                self.chat_widget.on_voice_transcribed(result);
+            }
+            AppEvent::CommitMessageGenerated(result) => {
+                self.chat_widget.on_commit_message_generated(result);
+            }
             AppEvent::DiffResult(text) => {
                 // Clear the in-progress state in the bottom pane
                 self.chat_widget.on_diff_complete();
@@ -1003,6 +1094,27 @@ impl App {
                 self.chat_widget
                     .set_world_writable_warning_acknowledged(ack);
             }
+            AppEvent::TuiConfigReloaded(settings) => {
+                if settings.theme == self.config.tui_theme
+                    && settings.themes == self.config.tui_themes
+                    && settings.keybindings == self.config.tui_keybindings
+                {
+                    return Ok(true);
+                }
+                for warning in apply_tui_appearance(
+                    settings.theme.as_deref(),
+                    &settings.themes,
+                    &settings.keybindings,
+                ) {
+                    self.app_event_tx.send(AppEvent::InsertHistoryCell(Box::new(
+                        crate::history_cell::new_warning_event(warning),
+                    )));
+                }
+                self.config.tui_theme = settings.theme;
+                self.config.tui_themes = settings.themes;
+                self.config.tui_keybindings = settings.keybindings;
+                tui.frame_requester().schedule_frame();
+            }
             AppEvent::UpdateRateLimitSwitchPromptHidden(hidden) => {
                 self.chat_widget.set_rate_limit_switch_prompt_hidden(hidden);
             }
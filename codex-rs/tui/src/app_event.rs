@@ -10,6 +10,7 @@ use codex_protocol::openai_models::ModelPreset;
 use crate::bottom_pane::ApprovalRequest;
 use crate::history_cell::HistoryCell;
 
+use codex_core::config::types::Tui;
 use codex_core::features::Feature;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::SandboxPolicy;
@@ -52,6 +53,13 @@ pub(crate) enum AppEvent {
     /// Result of computing a `/diff` command.
     DiffResult(String),
 
+    /// Transcript of a voice recording, or why it could not be transcribed.
+    VoiceTranscribed(Result<String, String>),
+
+    /// Message written by `/commit` for the staged changes, or why there is
+    /// none.
+    CommitMessageGenerated(Result<String, String>),
+
     InsertHistoryCell(Box<dyn HistoryCell>),
 
     StartCommitAnimation,
@@ -133,6 +141,10 @@ pub(crate) enum AppEvent {
     /// Update whether the rate limit switch prompt has been acknowledged for the session.
     UpdateRateLimitSwitchPromptHidden(bool),
 
+    /// The `[tui]` section of `config.toml` changed on disk; re-apply its
+    /// theme and keybindings.
+    TuiConfigReloaded(Tui),
+
     /// Persist the acknowledgement flag for the full access warning prompt.
     PersistFullAccessWarningAcknowledged,
 
@@ -5,6 +5,8 @@ use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::bottom_pane::BottomPaneView;
 use crate::bottom_pane::CancellationEvent;
+use crate::bottom_pane::hunk_review::HunkReview;
+use crate::bottom_pane::hunk_review::HunkReviewOutcome;
 use crate::bottom_pane::list_selection_view::ListSelectionView;
 use crate::bottom_pane::list_selection_view::SelectionItem;
 use crate::bottom_pane::list_selection_view::SelectionViewParams;
@@ -22,7 +24,9 @@ use codex_core::protocol::ElicitationAction;
 use codex_core::protocol::ExecPolicyAmendment;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::Op;
+use codex_core::protocol::PermissionScope;
 use codex_core::protocol::ReviewDecision;
+use codex_core::protocol::ScopedPermission;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
@@ -44,12 +48,14 @@ pub(crate) enum ApprovalRequest {
         command: Vec<String>,
         reason: Option<String>,
         proposed_execpolicy_amendment: Option<ExecPolicyAmendment>,
+        proposed_permissions: Vec<ScopedPermission>,
     },
     ApplyPatch {
         id: String,
         reason: Option<String>,
         cwd: PathBuf,
         changes: HashMap<PathBuf, FileChange>,
+        proposed_permissions: Vec<ScopedPermission>,
     },
     McpElicitation {
         server_name: String,
@@ -69,6 +75,8 @@ pub(crate) struct ApprovalOverlay {
     current_complete: bool,
     done: bool,
     features: Features,
+    /// Set while the user accepts or rejects the hunks of a patch.
+    hunk_review: Option<HunkReview>,
 }
 
 impl ApprovalOverlay {
@@ -83,6 +91,7 @@ impl ApprovalOverlay {
             current_complete: false,
             done: false,
             features,
+            hunk_review: None,
         };
         view.set_current(request);
         view
@@ -97,6 +106,7 @@ impl ApprovalOverlay {
         let ApprovalRequestState { variant, header } = ApprovalRequestState::from(request);
         self.current_variant = Some(variant.clone());
         self.current_complete = false;
+        self.hunk_review = None;
         let (options, params) = Self::build_options(variant, header, &self.features);
         self.options = options;
         self.list = ListSelectionView::new(params, self.app_event_tx.clone());
@@ -110,13 +120,21 @@ impl ApprovalOverlay {
         let (options, title) = match &variant {
             ApprovalVariant::Exec {
                 proposed_execpolicy_amendment,
+                proposed_permissions,
                 ..
             } => (
-                exec_options(proposed_execpolicy_amendment.clone(), features),
+                exec_options(
+                    proposed_execpolicy_amendment.clone(),
+                    proposed_permissions,
+                    features,
+                ),
                 "Would you like to run the following command?".to_string(),
             ),
-            ApprovalVariant::ApplyPatch { .. } => (
-                patch_options(),
+            ApprovalVariant::ApplyPatch {
+                proposed_permissions,
+                ..
+            } => (
+                patch_options(proposed_permissions),
                 "Would you like to make the following edits?".to_string(),
             ),
             ApprovalVariant::McpElicitation { server_name, .. } => (
@@ -174,6 +192,13 @@ impl ApprovalOverlay {
                 (ApprovalVariant::ApplyPatch { id, .. }, ApprovalDecision::Review(decision)) => {
                     self.handle_patch_decision(id, decision.clone());
                 }
+                (
+                    ApprovalVariant::ApplyPatch { cwd, changes, .. },
+                    ApprovalDecision::ReviewHunks,
+                ) => {
+                    self.hunk_review = Some(HunkReview::new(changes, cwd.clone()));
+                    return;
+                }
                 (
                     ApprovalVariant::McpElicitation {
                         server_name,
@@ -263,6 +288,20 @@ impl ApprovalOverlay {
 
 impl BottomPaneView for ApprovalOverlay {
     fn handle_key_event(&mut self, key_event: KeyEvent) {
+        if let Some(review) = self.hunk_review.as_mut() {
+            match review.handle_key_event(key_event) {
+                HunkReviewOutcome::Pending => {}
+                HunkReviewOutcome::Back => self.hunk_review = None,
+                HunkReviewOutcome::Submit(decision) => {
+                    if let Some(ApprovalVariant::ApplyPatch { id, .. }) = &self.current_variant {
+                        self.handle_patch_decision(id, decision);
+                    }
+                    self.current_complete = true;
+                    self.advance_queue();
+                }
+            }
+            return;
+        }
         if self.try_handle_shortcut(&key_event) {
             return;
         }
@@ -318,11 +357,17 @@ impl BottomPaneView for ApprovalOverlay {
 
 impl Renderable for ApprovalOverlay {
     fn desired_height(&self, width: u16) -> u16 {
-        self.list.desired_height(width)
+        match &self.hunk_review {
+            Some(review) => review.desired_height(width),
+            None => self.list.desired_height(width),
+        }
     }
 
     fn render(&self, area: Rect, buf: &mut Buffer) {
-        self.list.render(area, buf);
+        match &self.hunk_review {
+            Some(review) => review.render(area, buf),
+            None => self.list.render(area, buf),
+        }
     }
 
     fn cursor_pos(&self, area: Rect) -> Option<(u16, u16)> {
@@ -343,6 +388,7 @@ impl From<ApprovalRequest> for ApprovalRequestState {
                 command,
                 reason,
                 proposed_execpolicy_amendment,
+                proposed_permissions,
             } => {
                 let mut header: Vec<Line<'static>> = Vec::new();
                 if let Some(reason) = reason {
@@ -360,6 +406,7 @@ impl From<ApprovalRequest> for ApprovalRequestState {
                         id,
                         command,
                         proposed_execpolicy_amendment,
+                        proposed_permissions,
                     },
                     header: Box::new(Paragraph::new(header).wrap(Wrap { trim: false })),
                 }
@@ -369,6 +416,7 @@ impl From<ApprovalRequest> for ApprovalRequestState {
                 reason,
                 cwd,
                 changes,
+                proposed_permissions,
             } => {
                 let mut header: Vec<Box<dyn Renderable>> = Vec::new();
                 if let Some(reason) = reason
@@ -380,9 +428,14 @@ impl From<ApprovalRequest> for ApprovalRequestState {
                     ));
                     header.push(Box::new(Line::from("")));
                 }
-                header.push(DiffSummary::new(changes, cwd).into());
+                header.push(DiffSummary::new(changes.clone(), cwd.clone()).into());
                 Self {
-                    variant: ApprovalVariant::ApplyPatch { id },
+                    variant: ApprovalVariant::ApplyPatch {
+                        id,
+                        cwd,
+                        changes,
+                        proposed_permissions,
+                    },
                     header: Box::new(ColumnRenderable::with(header)),
                 }
             }
@@ -415,9 +468,13 @@ enum ApprovalVariant {
         id: String,
         command: Vec<String>,
         proposed_execpolicy_amendment: Option<ExecPolicyAmendment>,
+        proposed_permissions: Vec<ScopedPermission>,
     },
     ApplyPatch {
         id: String,
+        cwd: PathBuf,
+        changes: HashMap<PathBuf, FileChange>,
+        proposed_permissions: Vec<ScopedPermission>,
     },
     McpElicitation {
         server_name: String,
@@ -428,6 +485,8 @@ enum ApprovalVariant {
 #[derive(Clone)]
 enum ApprovalDecision {
     Review(ReviewDecision),
+    /// Open the per-hunk review instead of deciding on the whole patch.
+    ReviewHunks,
     McpElicitation(ElicitationAction),
 }
 
@@ -449,6 +508,7 @@ impl ApprovalOption {
 
 fn exec_options(
     proposed_execpolicy_amendment: Option<ExecPolicyAmendment>,
+    proposed_permissions: &[ScopedPermission],
     features: &Features,
 ) -> Vec<ApprovalOption> {
     vec![ApprovalOption {
@@ -461,22 +521,39 @@ fn exec_options(
     .chain(
         proposed_execpolicy_amendment
             .filter(|_| features.enabled(Feature::ExecPolicy))
-            .map(|prefix| {
+            .into_iter()
+            .flat_map(|prefix| {
                 let rendered_prefix = strip_bash_lc_and_escape(prefix.command());
-                ApprovalOption {
-                    label: format!(
-                        "Yes, and don't ask again for commands that start with `{rendered_prefix}`"
-                    ),
-                    decision: ApprovalDecision::Review(
-                        ReviewDecision::ApprovedExecpolicyAmendment {
-                            proposed_execpolicy_amendment: prefix,
-                        },
-                    ),
-                    display_shortcut: None,
-                    additional_shortcuts: vec![key_hint::plain(KeyCode::Char('p'))],
-                }
+                let project_prefix = prefix.command().to_vec();
+                [
+                    ApprovalOption {
+                        label: format!(
+                            "Yes, and don't ask again for commands that start with `{rendered_prefix}`"
+                        ),
+                        decision: ApprovalDecision::Review(
+                            ReviewDecision::ApprovedExecpolicyAmendment {
+                                proposed_execpolicy_amendment: prefix,
+                            },
+                        ),
+                        display_shortcut: None,
+                        additional_shortcuts: vec![key_hint::plain(KeyCode::Char('p'))],
+                    },
+                    ApprovalOption {
+                        label: format!(
+                            "Yes, and allow commands that start with `{rendered_prefix}` in this project"
+                        ),
+                        decision: ApprovalDecision::Review(
+                            ReviewDecision::ApprovedPrefixForProject {
+                                prefix: project_prefix,
+                            },
+                        ),
+                        display_shortcut: None,
+                        additional_shortcuts: vec![key_hint::plain(KeyCode::Char('a'))],
+                    },
+                ]
             }),
     )
+    .chain(permission_options(proposed_permissions))
     .chain([ApprovalOption {
         label: "No, and tell Codex what to do differently".to_string(),
         decision: ApprovalDecision::Review(ReviewDecision::Abort),
@@ -486,13 +563,20 @@ fn exec_options(
     .collect()
 }
 
-fn patch_options() -> Vec<ApprovalOption> {
-    vec![
+fn patch_options(proposed_permissions: &[ScopedPermission]) -> Vec<ApprovalOption> {
+    let mut options = vec![ApprovalOption {
+        label: "Yes, proceed".to_string(),
+        decision: ApprovalDecision::Review(ReviewDecision::Approved),
+        display_shortcut: None,
+        additional_shortcuts: vec![key_hint::plain(KeyCode::Char('y'))],
+    }];
+    options.extend(permission_options(proposed_permissions));
+    options.extend([
         ApprovalOption {
-            label: "Yes, proceed".to_string(),
-            decision: ApprovalDecision::Review(ReviewDecision::Approved),
+            label: "Review each hunk".to_string(),
+            decision: ApprovalDecision::ReviewHunks,
             display_shortcut: None,
-            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('y'))],
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('r'))],
         },
         ApprovalOption {
             label: "No, and tell Codex what to do differently".to_string(),
@@ -500,6 +584,40 @@ fn patch_options() -> Vec<ApprovalOption> {
             display_shortcut: Some(key_hint::plain(KeyCode::Esc)),
             additional_shortcuts: vec![key_hint::plain(KeyCode::Char('n'))],
         },
+    ]);
+    options
+}
+
+/// Options granting `permissions` for the session or the project, so similar
+/// requests stop asking. Empty when nothing is proposed.
+fn permission_options(permissions: &[ScopedPermission]) -> Vec<ApprovalOption> {
+    if permissions.is_empty() {
+        return Vec::new();
+    }
+    let described = permissions
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    vec![
+        ApprovalOption {
+            label: format!("Yes, and allow {described} for this session"),
+            decision: ApprovalDecision::Review(ReviewDecision::ApprovedPermissions {
+                permissions: permissions.to_vec(),
+                scope: PermissionScope::Session,
+            }),
+            display_shortcut: None,
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('s'))],
+        },
+        ApprovalOption {
+            label: format!("Yes, and always allow {described} in this project"),
+            decision: ApprovalDecision::Review(ReviewDecision::ApprovedPermissions {
+                permissions: permissions.to_vec(),
+                scope: PermissionScope::Project,
+            }),
+            display_shortcut: None,
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('m'))],
+        },
     ]
 }
 
@@ -539,6 +657,7 @@ mod tests {
             command: vec!["echo".to_string(), "hi".to_string()],
             reason: Some("reason".to_string()),
             proposed_execpolicy_amendment: None,
+            proposed_permissions: Vec::new(),
         }
     }
 
@@ -571,6 +690,47 @@ mod tests {
         assert!(saw_op, "expected approval decision to emit an op");
     }
 
+    #[test]
+    fn hunk_review_sends_partial_approval() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let cwd = PathBuf::from("/repo");
+        let request = ApprovalRequest::ApplyPatch {
+            id: "patch".to_string(),
+            reason: None,
+            cwd: cwd.clone(),
+            changes: HashMap::from([(
+                cwd.join("a.txt"),
+                FileChange::Update {
+                    unified_diff: "@@ -1,2 +1,2 @@\n x\n-y\n+Y\n@@ -9,2 +9,2 @@\n z\n-w\n+W\n"
+                        .to_string(),
+                    move_path: None,
+                },
+            )]),
+            proposed_permissions: Vec::new(),
+        };
+        let mut view = ApprovalOverlay::new(request, tx, Features::with_defaults());
+
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE));
+        assert!(!view.is_complete());
+        view.handle_key_event(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+        view.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(view.is_complete());
+
+        let mut decision = None;
+        while let Ok(ev) = rx.try_recv() {
+            if let AppEvent::CodexOp(Op::PatchApproval { decision: d, .. }) = ev {
+                decision = Some(d);
+            }
+        }
+        assert_eq!(
+            decision,
+            Some(ReviewDecision::PartiallyApproved {
+                rejected_hunks: HashMap::from([(cwd.join("a.txt"), vec![0])]),
+            })
+        );
+    }
+
     #[test]
     fn exec_prefix_option_emits_execpolicy_amendment() {
         let (tx, mut rx) = unbounded_channel::<AppEvent>();
@@ -583,6 +743,7 @@ mod tests {
                 proposed_execpolicy_amendment: Some(ExecPolicyAmendment::new(vec![
                     "echo".to_string(),
                 ])),
+                proposed_permissions: Vec::new(),
             },
             tx,
             Features::with_defaults(),
@@ -621,6 +782,7 @@ mod tests {
                 proposed_execpolicy_amendment: Some(ExecPolicyAmendment::new(vec![
                     "echo".to_string(),
                 ])),
+                proposed_permissions: Vec::new(),
             },
             tx,
             {
@@ -635,6 +797,44 @@ mod tests {
         assert!(rx.try_recv().is_err());
     }
 
+    #[test]
+    fn permission_option_grants_proposed_scope() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let permissions = vec![ScopedPermission::Network {
+            host: "api.github.com".to_string(),
+        }];
+        let mut view = ApprovalOverlay::new(
+            ApprovalRequest::Exec {
+                id: "test".to_string(),
+                command: vec!["curl".to_string(), "https://api.github.com".to_string()],
+                reason: None,
+                proposed_execpolicy_amendment: None,
+                proposed_permissions: permissions.clone(),
+            },
+            tx,
+            Features::with_defaults(),
+        );
+        assert_eq!(
+            view.options[1].label,
+            "Yes, and allow network access to api.github.com for this session"
+        );
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('m'), KeyModifiers::NONE));
+        let mut decision = None;
+        while let Ok(ev) = rx.try_recv() {
+            if let AppEvent::CodexOp(Op::ExecApproval { decision: d, .. }) = ev {
+                decision = Some(d);
+            }
+        }
+        assert_eq!(
+            decision,
+            Some(ReviewDecision::ApprovedPermissions {
+                permissions,
+                scope: PermissionScope::Project,
+            })
+        );
+    }
+
     #[test]
     fn header_includes_command_snippet() {
         let (tx, _rx) = unbounded_channel::<AppEvent>();
@@ -645,6 +845,7 @@ mod tests {
             command,
             reason: None,
             proposed_execpolicy_amendment: None,
+            proposed_permissions: Vec::new(),
         };
 
         let view = ApprovalOverlay::new(exec_request, tx, Features::with_defaults());
@@ -44,6 +44,8 @@ use crate::slash_command::SlashCommand;
 use crate::slash_command::built_in_slash_commands;
 use crate::style::user_message_style;
 use codex_common::fuzzy_match::fuzzy_match;
+use codex_core::custom_prompts::expand_command;
+use codex_protocol::custom_prompts::COMMAND_ARGUMENTS_PLACEHOLDER;
 use codex_protocol::custom_prompts::CustomPrompt;
 use codex_protocol::custom_prompts::PROMPTS_CMD_PREFIX;
 
@@ -55,6 +57,7 @@ use crate::clipboard_paste::normalize_pasted_path;
 use crate::clipboard_paste::pasted_image_format;
 use crate::history_cell;
 use crate::ui_consts::LIVE_PREFIX_COLS;
+use codex_core::protocol::SpendSummary;
 use codex_core::skills::model::SkillMetadata;
 use codex_file_search::FileMatch;
 use std::cell::RefCell;
@@ -72,7 +75,11 @@ const LARGE_PASTE_CHAR_THRESHOLD: usize = 1000;
 #[derive(Debug, PartialEq)]
 pub enum InputResult {
     Submitted(String),
+    /// Text typed while a task runs and sent with Esc, to redirect it.
+    Steered(String),
     Command(SlashCommand),
+    /// A built-in command that accepts arguments, along with the raw argument text.
+    CommandWithArgs(SlashCommand, String),
     None,
 }
 
@@ -107,6 +114,8 @@ pub(crate) struct ChatComposer {
     large_paste_counters: HashMap<usize, usize>,
     has_focus: bool,
     attached_images: Vec<AttachedImage>,
+    /// Paths picked from the `@` popup; their contents go with the message.
+    mentioned_files: Vec<String>,
     placeholder_text: String,
     is_task_running: bool,
     // Non-bracketed paste burst tracker.
@@ -114,10 +123,13 @@ pub(crate) struct ChatComposer {
     // When true, disables paste-burst logic and inserts characters immediately.
     disable_paste_burst: bool,
     custom_prompts: Vec<CustomPrompt>,
+    custom_commands: Vec<CustomPrompt>,
     footer_mode: FooterMode,
     footer_hint_override: Option<Vec<(String, String)>>,
     context_window_percent: Option<i64>,
     context_window_used_tokens: Option<i64>,
+    account: Option<String>,
+    spend: Option<SpendSummary>,
     skills: Option<Vec<SkillMetadata>>,
     dismissed_skill_popup_token: Option<String>,
 }
@@ -157,15 +169,19 @@ impl ChatComposer {
             large_paste_counters: HashMap::new(),
             has_focus: has_input_focus,
             attached_images: Vec::new(),
+            mentioned_files: Vec::new(),
             placeholder_text,
             is_task_running: false,
             paste_burst: PasteBurst::default(),
             disable_paste_burst: false,
             custom_prompts: Vec::new(),
+            custom_commands: Vec::new(),
             footer_mode: FooterMode::ShortcutSummary,
             footer_hint_override: None,
             context_window_percent: None,
             context_window_used_tokens: None,
+            account: None,
+            spend: None,
             skills: None,
             dismissed_skill_popup_token: None,
         };
@@ -294,6 +310,7 @@ impl ChatComposer {
         self.textarea.set_text("");
         self.pending_pastes.clear();
         self.attached_images.clear();
+        self.mentioned_files.clear();
         self.textarea.set_text(&text);
         self.textarea.set_cursor(0);
         self.sync_popups();
@@ -334,6 +351,14 @@ impl ChatComposer {
         images.into_iter().map(|img| img.path).collect()
     }
 
+    /// Files picked from the `@` popup whose paths are still in the submitted text.
+    pub(crate) fn take_recent_submission_mentions(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.mentioned_files)
+            .into_iter()
+            .map(PathBuf::from)
+            .collect()
+    }
+
     pub(crate) fn flush_paste_burst_if_due(&mut self) -> bool {
         self.handle_paste_burst_flush(Instant::now())
     }
@@ -500,6 +525,13 @@ impl ChatComposer {
                                 }
                             }
                         }
+                        CommandItem::UserCommand(idx) => {
+                            if let Some(command) = popup.command(idx) {
+                                let text = format!("/{} ", command.name);
+                                cursor_target = Some(text.len());
+                                self.textarea.set_text(&text);
+                            }
+                        }
                     }
                     if let Some(pos) = cursor_target {
                         self.textarea.set_cursor(pos);
@@ -553,6 +585,27 @@ impl ChatComposer {
                             }
                             return (InputResult::None, true);
                         }
+                        CommandItem::UserCommand(idx) => {
+                            if let Some(command) = popup.command(idx) {
+                                let typed = parse_slash_name(self.textarea.text())
+                                    .filter(|(name, _)| *name == command.name)
+                                    .map(|(_, rest)| rest.trim_end().to_string());
+                                // Commands that take arguments stay in the composer
+                                // until the full name has been typed.
+                                if typed.is_none()
+                                    && command.content.contains(COMMAND_ARGUMENTS_PLACEHOLDER)
+                                {
+                                    let text = format!("/{} ", command.name);
+                                    self.textarea.set_text(&text);
+                                    self.textarea.set_cursor(text.len());
+                                    return (InputResult::None, true);
+                                }
+                                let text = expand_command(command, &typed.unwrap_or_default());
+                                self.textarea.set_text("");
+                                return (InputResult::Submitted(text), true);
+                            }
+                            return (InputResult::None, true);
+                        }
                     }
                 }
                 // Fallback to default newline handling if no command selected.
@@ -975,6 +1028,13 @@ impl ChatComposer {
         self.textarea.set_text(&new_text);
         let new_cursor = start_idx.saturating_add(inserted.len()).saturating_add(1);
         self.textarea.set_cursor(new_cursor);
+        if !self
+            .mentioned_files
+            .iter()
+            .any(|mentioned| mentioned == path)
+        {
+            self.mentioned_files.push(path.to_string());
+        }
     }
 
     fn insert_selected_skill(&mut self, skill_name: &str) {
@@ -1084,13 +1144,23 @@ impl ChatComposer {
                 // literal text.
                 let first_line = self.textarea.text().lines().next().unwrap_or("");
                 if let Some((name, rest)) = parse_slash_name(first_line)
-                    && rest.is_empty()
                     && let Some((_n, cmd)) = built_in_slash_commands()
                         .into_iter()
                         .find(|(n, _)| *n == name)
                 {
-                    self.textarea.set_text("");
-                    return (InputResult::Command(cmd), true);
+                    if rest.is_empty() {
+                        self.textarea.set_text("");
+                        return (InputResult::Command(cmd), true);
+                    }
+                    if cmd.accepts_args() {
+                        // Arguments may span multiple lines, so take everything
+                        // after the command name rather than just the first line.
+                        let args = parse_slash_name(self.textarea.text())
+                            .map(|(_, rest)| rest.trim_end().to_string())
+                            .unwrap_or_default();
+                        self.textarea.set_text("");
+                        return (InputResult::CommandWithArgs(cmd, args), true);
+                    }
                 }
                 // If we're in a paste-like burst capture, treat Enter as part of the burst
                 // and accumulate it rather than submitting or inserting immediately.
@@ -1164,7 +1234,8 @@ impl ChatComposer {
                                     .any(|prompt| prompt.name == prompt_name)
                             })
                             .unwrap_or(false);
-                        if !is_builtin && !is_known_prompt {
+                        let is_known_command = self.custom_command(name).is_some();
+                        if !is_builtin && !is_known_prompt && !is_known_command {
                             let message = format!(
                                 r#"Unrecognized command '/{name}'. Type "/" for a list of supported commands."#
                             );
@@ -1178,6 +1249,13 @@ impl ChatComposer {
                     }
                 }
 
+                if !input_starts_with_space
+                    && let Some((name, rest)) = parse_slash_name(&text)
+                    && let Some(command) = self.custom_command(name)
+                {
+                    text = expand_command(command, rest);
+                }
+
                 let expanded_prompt = match expand_custom_prompt(&text, &self.custom_prompts) {
                     Ok(expanded) => expanded,
                     Err(err) => {
@@ -1198,6 +1276,8 @@ impl ChatComposer {
                 if !text.is_empty() {
                     self.history.record_local_submission(&text);
                 }
+                self.mentioned_files
+                    .retain(|path| text.contains(path.as_str()));
                 // Do not clear attached_images here; ChatWidget drains them via take_recent_submission_images().
                 (InputResult::Submitted(text), true)
             }
@@ -1524,7 +1604,7 @@ impl ChatComposer {
         changed
     }
 
-    fn footer_props(&self) -> FooterProps {
+    fn footer_props(&self) -> FooterProps<'_> {
         FooterProps {
             mode: self.footer_mode(),
             esc_backtrack_hint: self.esc_backtrack_hint,
@@ -1532,6 +1612,8 @@ impl ChatComposer {
             is_task_running: self.is_task_running,
             context_window_percent: self.context_window_percent,
             context_window_used_tokens: self.context_window_used_tokens,
+            account: self.account.as_deref(),
+            spend: self.spend,
         }
     }
 
@@ -1614,7 +1696,7 @@ impl ChatComposer {
     }
 
     /// Heuristic for whether the typed slash command looks like a valid
-    /// prefix for any known command (built-in or custom prompt).
+    /// prefix for any known command (built-in, custom prompt or custom command).
     /// Empty names only count when there is no extra content after the '/'.
     fn looks_like_slash_prefix(&self, name: &str, rest_after_name: &str) -> bool {
         if name.is_empty() {
@@ -1629,6 +1711,14 @@ impl ChatComposer {
             return true;
         }
 
+        if self
+            .custom_commands
+            .iter()
+            .any(|c| fuzzy_match(&c.name, name).is_some())
+        {
+            return true;
+        }
+
         let prompt_prefix = format!("{PROMPTS_CMD_PREFIX}:");
         self.custom_prompts
             .iter()
@@ -1678,6 +1768,7 @@ impl ChatComposer {
                     let skills_enabled = self.skills_enabled();
                     let mut command_popup =
                         CommandPopup::new(self.custom_prompts.clone(), skills_enabled);
+                    command_popup.set_commands(self.custom_commands.clone());
                     command_popup.on_composer_text_change(first_line.to_string());
                     self.active_popup = ActivePopup::Command(command_popup);
                 }
@@ -1692,6 +1783,25 @@ impl ChatComposer {
         }
     }
 
+    pub(crate) fn set_custom_commands(&mut self, commands: Vec<CustomPrompt>) {
+        self.custom_commands = commands.clone();
+        if let ActivePopup::Command(popup) = &mut self.active_popup {
+            popup.set_commands(commands);
+        }
+    }
+
+    /// The custom command invoked as `/name`. Built-in commands take
+    /// precedence over custom commands with the same name.
+    fn custom_command(&self, name: &str) -> Option<&CustomPrompt> {
+        let is_builtin = built_in_slash_commands()
+            .into_iter()
+            .any(|(command_name, _)| command_name == name);
+        if is_builtin {
+            return None;
+        }
+        self.custom_commands.iter().find(|c| c.name == name)
+    }
+
     /// Synchronize `self.file_search_popup` with the current text in the textarea.
     /// Note this is only called when self.active_popup is NOT Command.
     fn sync_file_search_popup(&mut self, query: String) {
@@ -1771,6 +1881,14 @@ impl ChatComposer {
         self.context_window_used_tokens = used_tokens;
     }
 
+    pub(crate) fn set_account(&mut self, account: Option<String>) {
+        self.account = account;
+    }
+
+    pub(crate) fn set_spend(&mut self, spend: Option<SpendSummary>) {
+        self.spend = spend;
+    }
+
     pub(crate) fn set_esc_backtrack_hint(&mut self, show: bool) {
         self.esc_backtrack_hint = show;
         if show {
@@ -2629,7 +2747,7 @@ mod tests {
                 Some(CommandItem::Builtin(cmd)) => {
                     assert_eq!(cmd.command(), "model")
                 }
-                Some(CommandItem::UserPrompt(_)) => {
+                Some(CommandItem::UserPrompt(_) | CommandItem::UserCommand(_)) => {
                     panic!("unexpected prompt selected for '/mo'")
                 }
                 None => panic!("no selected command for '/mo'"),
@@ -2685,7 +2803,7 @@ mod tests {
                 Some(CommandItem::Builtin(cmd)) => {
                     assert_eq!(cmd.command(), "resume")
                 }
-                Some(CommandItem::UserPrompt(_)) => {
+                Some(CommandItem::UserPrompt(_) | CommandItem::UserCommand(_)) => {
                     panic!("unexpected prompt selected for '/res'")
                 }
                 None => panic!("no selected command for '/res'"),
@@ -2738,6 +2856,9 @@ mod tests {
             InputResult::Submitted(text) => {
                 panic!("expected command dispatch, but composer submitted literal text: {text}")
             }
+            InputResult::CommandWithArgs(cmd, args) => {
+                panic!("expected bare command for '/init', got {cmd:?} with args {args:?}")
+            }
             InputResult::None => panic!("expected Command result for '/init'"),
         }
         assert!(composer.textarea.is_empty(), "composer should be cleared");
@@ -2811,6 +2932,9 @@ mod tests {
             InputResult::Submitted(text) => {
                 panic!("expected command dispatch after Tab completion, got literal submit: {text}")
             }
+            InputResult::CommandWithArgs(cmd, args) => {
+                panic!("expected bare command for '/diff', got {cmd:?} with args {args:?}")
+            }
             InputResult::None => panic!("expected Command result for '/diff'"),
         }
         assert!(composer.textarea.is_empty());
@@ -2844,6 +2968,9 @@ mod tests {
             InputResult::Submitted(text) => {
                 panic!("expected command dispatch, but composer submitted literal text: {text}")
             }
+            InputResult::CommandWithArgs(cmd, args) => {
+                panic!("expected bare command for '/mention', got {cmd:?} with args {args:?}")
+            }
             InputResult::None => panic!("expected Command result for '/mention'"),
         }
         assert!(composer.textarea.is_empty(), "composer should be cleared");
@@ -2851,6 +2978,37 @@ mod tests {
         assert_eq!(composer.textarea.text(), "@");
     }
 
+    #[test]
+    fn slash_pin_with_args_dispatches_command_with_args() {
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+
+        composer.insert_str("/pin instruction always run the tests");
+
+        let (result, _needs_redraw) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        assert_eq!(
+            result,
+            InputResult::CommandWithArgs(
+                SlashCommand::Pin,
+                "instruction always run the tests".to_string()
+            )
+        );
+        assert!(composer.textarea.is_empty(), "composer should be cleared");
+    }
+
     #[test]
     fn test_multiple_pastes_submission() {
         use crossterm::event::KeyCode;
@@ -3334,6 +3492,37 @@ mod tests {
         assert_eq!(imgs, vec![tmp_path]);
     }
 
+    #[test]
+    fn mentioned_files_follow_the_submitted_text() {
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+
+        composer.textarea.insert_str("explain @mai");
+        composer.insert_selected_path("src/main.rs");
+        composer.textarea.insert_str("@li");
+        composer.insert_selected_path("src/lib.rs");
+        assert_eq!(composer.textarea.text(), "explain src/main.rs src/lib.rs ");
+
+        composer.textarea.set_text("explain src/main.rs");
+        let (result, _) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        match result {
+            InputResult::Submitted(text) => assert_eq!(text, "explain src/main.rs"),
+            _ => panic!("expected Submitted"),
+        }
+        assert_eq!(
+            composer.take_recent_submission_mentions(),
+            vec![PathBuf::from("src/main.rs")]
+        );
+    }
+
     #[test]
     fn selecting_custom_prompt_without_args_submits_content() {
         let prompt_text = "Hello from saved prompt";
@@ -3372,6 +3561,42 @@ mod tests {
         assert!(composer.textarea.is_empty());
     }
 
+    #[test]
+    fn custom_command_waits_for_arguments_then_expands() {
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+
+        composer.set_custom_commands(vec![CustomPrompt {
+            name: "deploy".to_string(),
+            path: "/tmp/deploy.md".to_string().into(),
+            content: "Deploy $ARGUMENTS to staging".to_string(),
+            description: None,
+            argument_hint: None,
+        }]);
+
+        type_chars_humanlike(&mut composer, &['/', 'd', 'e', 'p']);
+        let (result, _) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(result, InputResult::None);
+        assert_eq!(composer.textarea.text(), "/deploy ");
+
+        type_chars_humanlike(&mut composer, &['a', 'p', 'i']);
+        let (result, _) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(
+            result,
+            InputResult::Submitted("Deploy api to staging".to_string())
+        );
+        assert!(composer.textarea.is_empty());
+    }
+
     #[test]
     fn custom_prompt_submission_expands_arguments() {
         let (tx, _rx) = unbounded_channel::<AppEvent>();
@@ -15,18 +15,22 @@ use codex_protocol::custom_prompts::CustomPrompt;
 use codex_protocol::custom_prompts::PROMPTS_CMD_PREFIX;
 use std::collections::HashSet;
 
-/// A selectable item in the popup: either a built-in command or a user prompt.
+/// A selectable item in the popup: a built-in command, a user prompt or a
+/// custom command.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) enum CommandItem {
     Builtin(SlashCommand),
     // Index into `prompts`
     UserPrompt(usize),
+    // Index into `commands`
+    UserCommand(usize),
 }
 
 pub(crate) struct CommandPopup {
     command_filter: String,
     builtins: Vec<(&'static str, SlashCommand)>,
     prompts: Vec<CustomPrompt>,
+    commands: Vec<CustomPrompt>,
     state: ScrollState,
 }
 
@@ -44,6 +48,7 @@ impl CommandPopup {
             command_filter: String::new(),
             builtins,
             prompts,
+            commands: Vec::new(),
             state: ScrollState::new(),
         }
     }
@@ -63,6 +68,23 @@ impl CommandPopup {
         self.prompts.get(idx)
     }
 
+    /// Custom commands from `commands/*.md`, shown as `/name`. Commands that
+    /// collide with builtin names are ignored.
+    pub(crate) fn set_commands(&mut self, mut commands: Vec<CustomPrompt>) {
+        let exclude: HashSet<String> = self
+            .builtins
+            .iter()
+            .map(|(n, _)| (*n).to_string())
+            .collect();
+        commands.retain(|c| !exclude.contains(&c.name));
+        commands.sort_by(|a, b| a.name.cmp(&b.name));
+        self.commands = commands;
+    }
+
+    pub(crate) fn command(&self, idx: usize) -> Option<&CustomPrompt> {
+        self.commands.get(idx)
+    }
+
     /// Update the filter string based on the current composer text. The text
     /// passed in is expected to start with a leading '/'. Everything after the
     /// *first* '/" on the *first* line becomes the active filter that is used
@@ -114,10 +136,13 @@ impl CommandPopup {
             for (_, cmd) in self.builtins.iter() {
                 out.push((CommandItem::Builtin(*cmd), None, 0));
             }
-            // Then prompts, already sorted by name.
+            // Then prompts and commands, already sorted by name.
             for idx in 0..self.prompts.len() {
                 out.push((CommandItem::UserPrompt(idx), None, 0));
             }
+            for idx in 0..self.commands.len() {
+                out.push((CommandItem::UserCommand(idx), None, 0));
+            }
             return out;
         }
 
@@ -135,16 +160,23 @@ impl CommandPopup {
                 out.push((CommandItem::UserPrompt(idx), Some(indices), score));
             }
         }
+        for (idx, c) in self.commands.iter().enumerate() {
+            if let Some((indices, score)) = fuzzy_match(&c.name, filter) {
+                out.push((CommandItem::UserCommand(idx), Some(indices), score));
+            }
+        }
         // When filtering, sort by ascending score and then by name for stability.
         out.sort_by(|a, b| {
             a.2.cmp(&b.2).then_with(|| {
                 let an = match a.0 {
                     CommandItem::Builtin(c) => c.command(),
                     CommandItem::UserPrompt(i) => &self.prompts[i].name,
+                    CommandItem::UserCommand(i) => &self.commands[i].name,
                 };
                 let bn = match b.0 {
                     CommandItem::Builtin(c) => c.command(),
                     CommandItem::UserPrompt(i) => &self.prompts[i].name,
+                    CommandItem::UserCommand(i) => &self.commands[i].name,
                 };
                 an.cmp(bn)
             })
@@ -178,6 +210,14 @@ impl CommandPopup {
                             description,
                         )
                     }
+                    CommandItem::UserCommand(i) => {
+                        let command = &self.commands[i];
+                        let description = command
+                            .description
+                            .clone()
+                            .unwrap_or_else(|| "run custom command".to_string());
+                        (format!("/{}", command.name), description)
+                    }
                 };
                 GenericDisplayRow {
                     name,
@@ -246,7 +286,7 @@ mod tests {
         let matches = popup.filtered_items();
         let has_init = matches.iter().any(|item| match item {
             CommandItem::Builtin(cmd) => cmd.command() == "init",
-            CommandItem::UserPrompt(_) => false,
+            CommandItem::UserPrompt(_) | CommandItem::UserCommand(_) => false,
         });
         assert!(
             has_init,
@@ -264,7 +304,9 @@ mod tests {
         let selected = popup.selected_item();
         match selected {
             Some(CommandItem::Builtin(cmd)) => assert_eq!(cmd.command(), "init"),
-            Some(CommandItem::UserPrompt(_)) => panic!("unexpected prompt selected for '/init'"),
+            Some(CommandItem::UserPrompt(_) | CommandItem::UserCommand(_)) => {
+                panic!("unexpected prompt selected for '/init'")
+            }
             None => panic!("expected a selected command for exact match"),
         }
     }
@@ -276,7 +318,7 @@ mod tests {
         let matches = popup.filtered_items();
         match matches.first() {
             Some(CommandItem::Builtin(cmd)) => assert_eq!(cmd.command(), "model"),
-            Some(CommandItem::UserPrompt(_)) => {
+            Some(CommandItem::UserPrompt(_) | CommandItem::UserCommand(_)) => {
                 panic!("unexpected prompt ranked before '/model' for '/mo'")
             }
             None => panic!("expected at least one match for '/mo'"),
@@ -385,7 +427,7 @@ mod tests {
             .into_iter()
             .filter_map(|item| match item {
                 CommandItem::Builtin(cmd) => Some(cmd.command()),
-                CommandItem::UserPrompt(_) => None,
+                CommandItem::UserPrompt(_) | CommandItem::UserCommand(_) => None,
             })
             .collect();
         assert!(
@@ -393,4 +435,33 @@ mod tests {
             "expected fuzzy search for '/ac' to include compact and feedback, got {cmds:?}"
         );
     }
+
+    #[test]
+    fn custom_commands_show_as_bare_slash_names() {
+        let mut popup = CommandPopup::new(Vec::new(), false);
+        popup.set_commands(vec![
+            CustomPrompt {
+                name: "deploy".to_string(),
+                path: "/tmp/deploy.md".to_string().into(),
+                content: "Deploy $ARGUMENTS".to_string(),
+                description: Some("deploy a service".to_string()),
+                argument_hint: Some("<service>".to_string()),
+            },
+            CustomPrompt {
+                name: "init".to_string(),
+                path: "/tmp/init.md".to_string().into(),
+                content: "should be ignored".to_string(),
+                description: None,
+                argument_hint: None,
+            },
+        ]);
+        popup.on_composer_text_change("/dep".to_string());
+
+        let items = popup.filtered_items();
+        assert_eq!(items, vec![CommandItem::UserCommand(0)]);
+        assert_eq!(popup.command(0).map(|c| c.name.as_str()), Some("deploy"));
+        let rows = popup.rows_from_matches(popup.filtered());
+        assert_eq!(rows[0].name, "/deploy");
+        assert_eq!(rows[0].description.as_deref(), Some("deploy a service"));
+    }
 }
@@ -54,6 +54,12 @@ impl CustomPromptView {
             complete: false,
         }
     }
+
+    /// Starts with `text` in the input, e.g. a draft for the user to edit.
+    pub(crate) fn with_text(mut self, text: &str) -> Self {
+        self.textarea.insert_str(text);
+        self
+    }
 }
 
 impl BottomPaneView for CustomPromptView {
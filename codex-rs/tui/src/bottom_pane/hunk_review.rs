@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use codex_core::patch_review::PatchHunk;
+use codex_core::patch_review::hunks;
+use codex_core::protocol::FileChange;
+use codex_core::protocol::ReviewDecision;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyEventKind;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Widget;
+
+use crate::diff_render::display_path_for;
+use crate::key_hint;
+use crate::render::renderable::Renderable;
+
+use super::popup_consts::MAX_POPUP_ROWS;
+
+/// Diff lines of the selected hunk shown below the list.
+const PREVIEW_LINES: usize = 12;
+
+struct ReviewHunk {
+    path: PathBuf,
+    index: usize,
+    hunk: PatchHunk,
+    accepted: bool,
+}
+
+pub(crate) enum HunkReviewOutcome {
+    Pending,
+    /// Return to the approval options without deciding.
+    Back,
+    Submit(ReviewDecision),
+}
+
+/// Lets the user accept or reject each hunk of a proposed patch before any
+/// of it is written.
+pub(crate) struct HunkReview {
+    hunks: Vec<ReviewHunk>,
+    selected: usize,
+    cwd: PathBuf,
+}
+
+impl HunkReview {
+    pub(crate) fn new(changes: &HashMap<PathBuf, FileChange>, cwd: PathBuf) -> Self {
+        let mut paths: Vec<&PathBuf> = changes.keys().collect();
+        paths.sort();
+        let hunks = paths
+            .into_iter()
+            .flat_map(|path| {
+                hunks(&changes[path])
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, hunk)| ReviewHunk {
+                        path: path.clone(),
+                        index,
+                        hunk,
+                        accepted: true,
+                    })
+            })
+            .collect();
+        Self {
+            hunks,
+            selected: 0,
+            cwd,
+        }
+    }
+
+    pub(crate) fn handle_key_event(&mut self, key_event: KeyEvent) -> HunkReviewOutcome {
+        if key_event.kind == KeyEventKind::Release {
+            return HunkReviewOutcome::Pending;
+        }
+        match key_event.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < self.hunks.len() {
+                    self.selected += 1;
+                }
+            }
+            KeyCode::Char(' ') => {
+                if let Some(hunk) = self.hunks.get_mut(self.selected) {
+                    hunk.accepted = !hunk.accepted;
+                }
+            }
+            KeyCode::Char('f') => self.toggle_file(),
+            KeyCode::Enter => return HunkReviewOutcome::Submit(self.decision()),
+            KeyCode::Esc => return HunkReviewOutcome::Back,
+            _ => {}
+        }
+        HunkReviewOutcome::Pending
+    }
+
+    /// Rejects every hunk of the selected file, or accepts them all when
+    /// they are already rejected.
+    fn toggle_file(&mut self) {
+        let Some(path) = self.hunks.get(self.selected).map(|hunk| hunk.path.clone()) else {
+            return;
+        };
+        let accept = self
+            .hunks
+            .iter()
+            .filter(|hunk| hunk.path == path)
+            .all(|hunk| !hunk.accepted);
+        for hunk in self.hunks.iter_mut().filter(|hunk| hunk.path == path) {
+            hunk.accepted = accept;
+        }
+    }
+
+    fn decision(&self) -> ReviewDecision {
+        if self.hunks.iter().all(|hunk| hunk.accepted) {
+            return ReviewDecision::Approved;
+        }
+        if self.hunks.iter().all(|hunk| !hunk.accepted) {
+            return ReviewDecision::Denied;
+        }
+        let mut rejected_hunks: HashMap<PathBuf, Vec<usize>> = HashMap::new();
+        for hunk in self.hunks.iter().filter(|hunk| !hunk.accepted) {
+            rejected_hunks
+                .entry(hunk.path.clone())
+                .or_default()
+                .push(hunk.index);
+        }
+        ReviewDecision::PartiallyApproved { rejected_hunks }
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let accepted = self.hunks.iter().filter(|hunk| hunk.accepted).count();
+        let mut lines = vec![
+            Line::from("Review the proposed edits".bold()),
+            Line::from(format!("{accepted} of {} hunks will be applied.", self.hunks.len()).dim()),
+            Line::from(""),
+        ];
+
+        // Keep the selection inside a window of MAX_POPUP_ROWS rows.
+        let first = self
+            .selected
+            .saturating_sub(MAX_POPUP_ROWS - 1)
+            .min(self.hunks.len().saturating_sub(MAX_POPUP_ROWS));
+        for (idx, hunk) in self
+            .hunks
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(MAX_POPUP_ROWS)
+        {
+            let prefix = if idx == self.selected { '›' } else { ' ' };
+            let marker = if hunk.accepted { 'x' } else { ' ' };
+            let row = format!(
+                "{prefix} [{marker}] {} {}",
+                display_path_for(&hunk.path, &self.cwd),
+                hunk.hunk.header
+            );
+            lines.push(if idx == self.selected {
+                Line::from(row.cyan())
+            } else {
+                Line::from(row)
+            });
+        }
+
+        if let Some(hunk) = self.hunks.get(self.selected) {
+            lines.push(Line::from(""));
+            for line in hunk.hunk.lines.iter().take(PREVIEW_LINES) {
+                let line = line.clone();
+                lines.push(match line.chars().next() {
+                    Some('+') => Line::from(line.green()),
+                    Some('-') => Line::from(line.red()),
+                    _ => Line::from(line.dim()),
+                });
+            }
+            if hunk.hunk.lines.len() > PREVIEW_LINES {
+                let more = hunk.hunk.lines.len() - PREVIEW_LINES;
+                lines.push(Line::from(format!("… {more} more lines").dim()));
+            }
+        }
+
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            key_hint::plain(KeyCode::Char(' ')).into(),
+            " toggle hunk  ".into(),
+            key_hint::plain(KeyCode::Char('f')).into(),
+            " toggle file  ".into(),
+            key_hint::plain(KeyCode::Enter).into(),
+            " apply accepted  ".into(),
+            key_hint::plain(KeyCode::Esc).into(),
+            " back".into(),
+        ]));
+        lines
+    }
+}
+
+impl Renderable for HunkReview {
+    fn desired_height(&self, _width: u16) -> u16 {
+        u16::try_from(self.lines().len()).unwrap_or(u16::MAX)
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.lines()).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+    use pretty_assertions::assert_eq;
+
+    fn press(review: &mut HunkReview, code: KeyCode) -> HunkReviewOutcome {
+        review.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn rejected_hunks_are_reported_per_file() {
+        let cwd = PathBuf::from("/repo");
+        let changes = HashMap::from([
+            (
+                cwd.join("a.rs"),
+                FileChange::Update {
+                    unified_diff: "@@ -1,2 +1,2 @@\n x\n-y\n+Y\n@@ -9,2 +9,2 @@\n z\n-w\n+W\n"
+                        .to_string(),
+                    move_path: None,
+                },
+            ),
+            (
+                cwd.join("b.rs"),
+                FileChange::Add {
+                    content: "new\n".to_string(),
+                },
+            ),
+        ]);
+        let mut review = HunkReview::new(&changes, cwd.clone());
+
+        press(&mut review, KeyCode::Down);
+        press(&mut review, KeyCode::Char(' '));
+        let HunkReviewOutcome::Submit(decision) = press(&mut review, KeyCode::Enter) else {
+            panic!("enter submits the review");
+        };
+        assert_eq!(
+            decision,
+            ReviewDecision::PartiallyApproved {
+                rejected_hunks: HashMap::from([(cwd.join("a.rs"), vec![1])]),
+            }
+        );
+
+        press(&mut review, KeyCode::Char('f'));
+        press(&mut review, KeyCode::Down);
+        press(&mut review, KeyCode::Char('f'));
+        let HunkReviewOutcome::Submit(decision) = press(&mut review, KeyCode::Enter) else {
+            panic!("enter submits the review");
+        };
+        assert_eq!(decision, ReviewDecision::Denied);
+    }
+}
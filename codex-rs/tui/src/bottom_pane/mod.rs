@@ -2,7 +2,10 @@
 use std::path::PathBuf;
 
 use crate::app_event_sender::AppEventSender;
+use crate::bottom_pane::plan_panel::PlanPanel;
 use crate::bottom_pane::queued_user_messages::QueuedUserMessages;
+use crate::bottom_pane::sub_agents_panel::SubAgentsPanel;
+use crate::bottom_pane::tool_output_panel::ToolOutputPanel;
 use crate::bottom_pane::unified_exec_footer::UnifiedExecFooter;
 use crate::render::renderable::FlexRenderable;
 use crate::render::renderable::Renderable;
@@ -10,8 +13,10 @@ use crate::render::renderable::RenderableItem;
 use crate::tui::FrameRequester;
 use bottom_pane_view::BottomPaneView;
 use codex_core::features::Features;
+use codex_core::protocol::SpendSummary;
 use codex_core::skills::model::SkillMetadata;
 use codex_file_search::FileMatch;
+use codex_protocol::plan_tool::PlanItemArg;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use ratatui::buffer::Buffer;
@@ -29,6 +34,7 @@ pub mod custom_prompt_view;
 mod experimental_features_view;
 mod file_search_popup;
 mod footer;
+mod hunk_review;
 mod list_selection_view;
 mod prompt_args;
 mod skill_popup;
@@ -37,11 +43,14 @@ mod feedback_view;
 pub(crate) use feedback_view::feedback_selection_params;
 pub(crate) use feedback_view::feedback_upload_consent_params;
 mod paste_burst;
+mod plan_panel;
 pub mod popup_consts;
 mod queued_user_messages;
 mod scroll_state;
 mod selection_popup_common;
+mod sub_agents_panel;
 mod textarea;
+mod tool_output_panel;
 mod unified_exec_footer;
 pub(crate) use feedback_view::FeedbackNoteView;
 
@@ -83,6 +92,10 @@ pub(crate) struct BottomPane {
     status: Option<StatusIndicatorWidget>,
     /// Unified exec session summary shown above the composer.
     unified_exec_footer: UnifiedExecFooter,
+    /// Live per-agent progress for running sub-agents.
+    sub_agents_panel: SubAgentsPanel,
+    plan_panel: PlanPanel,
+    tool_output_panel: ToolOutputPanel,
     /// Queued user messages to show above the composer while a turn is running.
     queued_user_messages: QueuedUserMessages,
     context_window_percent: Option<i64>,
@@ -131,6 +144,9 @@ impl BottomPane {
             ctrl_c_quit_hint: false,
             status: None,
             unified_exec_footer: UnifiedExecFooter::new(),
+            sub_agents_panel: SubAgentsPanel::new(),
+            plan_panel: PlanPanel::new(),
+            tool_output_panel: ToolOutputPanel::new(),
             queued_user_messages: QueuedUserMessages::new(),
             esc_backtrack_hint: false,
             animations_enabled,
@@ -191,6 +207,16 @@ impl BottomPane {
             self.request_redraw();
             InputResult::None
         } else {
+            // With a correction typed while a task runs, Esc steers the task:
+            // the response in flight restarts with the correction.
+            if matches!(key_event.code, crossterm::event::KeyCode::Esc) && self.is_task_running {
+                let correction = self.composer.current_text().trim().to_string();
+                if !correction.is_empty() {
+                    self.composer.set_text_content(String::new());
+                    self.request_redraw();
+                    return InputResult::Steered(correction);
+                }
+            }
             // If a task is running and a status line is visible, allow Esc to
             // send an interrupt even while the composer has focus.
             if matches!(key_event.code, crossterm::event::KeyCode::Esc)
@@ -202,6 +228,10 @@ impl BottomPane {
                 self.request_redraw();
                 return InputResult::None;
             }
+            if self.tool_output_panel.handle_key_event(key_event) {
+                self.request_redraw();
+                return InputResult::None;
+            }
             let (input_result, needs_redraw) = self.composer.handle_key_event(key_event);
             if needs_redraw {
                 self.request_redraw();
@@ -258,6 +288,13 @@ impl BottomPane {
         self.request_redraw();
     }
 
+    /// Override the footer hints beneath the composer; `None` restores the
+    /// default shortcuts.
+    pub(crate) fn set_footer_hint_override(&mut self, items: Option<Vec<(String, String)>>) {
+        self.composer.set_footer_hint_override(items);
+        self.request_redraw();
+    }
+
     /// Replace the composer text with `text`.
     pub(crate) fn set_composer_text(&mut self, text: String) {
         self.composer.set_text_content(text);
@@ -376,6 +413,18 @@ impl BottomPane {
         }
     }
 
+    /// Shows the named login in use in the footer.
+    pub(crate) fn set_account(&mut self, account: Option<String>) {
+        self.composer.set_account(account);
+        self.request_redraw();
+    }
+
+    /// Shows the estimated session and daily spend in the footer.
+    pub(crate) fn set_spend(&mut self, spend: Option<SpendSummary>) {
+        self.composer.set_spend(spend);
+        self.request_redraw();
+    }
+
     pub(crate) fn set_context_window(&mut self, percent: Option<i64>, used_tokens: Option<i64>) {
         if self.context_window_percent == percent && self.context_window_used_tokens == used_tokens
         {
@@ -407,12 +456,49 @@ impl BottomPane {
         }
     }
 
+    pub(crate) fn set_sub_agents(&mut self, agents: Vec<(String, String)>) {
+        if self.sub_agents_panel.set_agents(agents) {
+            self.request_redraw();
+        }
+    }
+
+    pub(crate) fn set_plan(&mut self, steps: Vec<PlanItemArg>) {
+        self.plan_panel.set_steps(steps);
+        self.request_redraw();
+    }
+
+    pub(crate) fn begin_tool_output(&mut self, call_id: String, title: String) {
+        self.tool_output_panel.begin(call_id, title);
+    }
+
+    pub(crate) fn push_tool_output(&mut self, call_id: &str, chunk: &[u8]) {
+        if self.tool_output_panel.push_output(call_id, chunk) {
+            self.request_redraw();
+        }
+    }
+
+    pub(crate) fn end_tool_output(&mut self, call_id: &str) {
+        if self.tool_output_panel.end(call_id) {
+            self.request_redraw();
+        }
+    }
+
+    pub(crate) fn clear_tool_output(&mut self) {
+        self.tool_output_panel.clear();
+        self.request_redraw();
+    }
+
     /// Update custom prompts available for the slash popup.
     pub(crate) fn set_custom_prompts(&mut self, prompts: Vec<CustomPrompt>) {
         self.composer.set_custom_prompts(prompts);
         self.request_redraw();
     }
 
+    pub(crate) fn set_custom_commands(&mut self, commands: Vec<CustomPrompt>) {
+        self.composer.set_custom_commands(commands);
+        self.request_redraw();
+    }
+
     pub(crate) fn composer_is_empty(&self) -> bool {
         self.composer.is_empty()
     }
@@ -529,6 +615,10 @@ impl BottomPane {
         self.composer.take_recent_submission_images()
     }
 
+    pub(crate) fn take_recent_submission_mentions(&mut self) -> Vec<PathBuf> {
+        self.composer.take_recent_submission_mentions()
+    }
+
     fn as_renderable(&'_ self) -> RenderableItem<'_> {
         if let Some(view) = self.active_view() {
             RenderableItem::Borrowed(view)
@@ -537,11 +627,23 @@ impl BottomPane {
             if let Some(status) = &self.status {
                 flex.push(0, RenderableItem::Borrowed(status));
             }
+            if !self.plan_panel.is_empty() {
+                flex.push(0, RenderableItem::Borrowed(&self.plan_panel));
+            }
+            if !self.sub_agents_panel.is_empty() {
+                flex.push(0, RenderableItem::Borrowed(&self.sub_agents_panel));
+            }
+            if !self.tool_output_panel.is_empty() {
+                flex.push(0, RenderableItem::Borrowed(&self.tool_output_panel));
+            }
             if !self.unified_exec_footer.is_empty() {
                 flex.push(0, RenderableItem::Borrowed(&self.unified_exec_footer));
             }
             flex.push(1, RenderableItem::Borrowed(&self.queued_user_messages));
             if self.status.is_some()
+                || !self.plan_panel.is_empty()
+                || !self.sub_agents_panel.is_empty()
+                || !self.tool_output_panel.is_empty()
                 || !self.unified_exec_footer.is_empty()
                 || !self.queued_user_messages.messages.is_empty()
             {
@@ -600,6 +702,7 @@ mod tests {
             command: vec!["echo".into(), "ok".into()],
             reason: None,
             proposed_execpolicy_amendment: None,
+            proposed_permissions: Vec::new(),
         }
     }
 
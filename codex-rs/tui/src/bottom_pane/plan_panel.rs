@@ -0,0 +1,127 @@
+use codex_protocol::plan_tool::PlanItemArg;
+use codex_protocol::plan_tool::StepStatus;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Paragraph;
+
+use crate::live_wrap::take_prefix_by_width;
+use crate::render::renderable::Renderable;
+
+/// Checklist of the model's current plan, shown above the composer until
+/// every step is completed.
+pub(crate) struct PlanPanel {
+    steps: Vec<PlanItemArg>,
+}
+
+impl PlanPanel {
+    pub(crate) fn new() -> Self {
+        Self { steps: Vec::new() }
+    }
+
+    pub(crate) fn set_steps(&mut self, steps: Vec<PlanItemArg>) {
+        self.steps = steps;
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.steps
+            .iter()
+            .all(|item| matches!(item.status, StepStatus::Completed))
+    }
+
+    fn render_lines(&self, width: u16) -> Vec<Line<'static>> {
+        if width < 8 || self.is_empty() {
+            return Vec::new();
+        }
+        let completed = self
+            .steps
+            .iter()
+            .filter(|item| matches!(item.status, StepStatus::Completed))
+            .count();
+        let mut lines = vec![Line::from(vec![
+            "  Plan ".bold(),
+            format!("{completed}/{}", self.steps.len()).dim(),
+        ])];
+        let available = (width as usize).saturating_sub(6);
+        for PlanItemArg { step, status } in &self.steps {
+            let (step, _, _) = take_prefix_by_width(step, available);
+            lines.push(match status {
+                StepStatus::Completed => Line::from(vec!["  ✔ ".dim(), step.dim().crossed_out()]),
+                StepStatus::InProgress => Line::from(vec!["  □ ".cyan(), step.cyan().bold()]),
+                StepStatus::Pending => Line::from(vec!["  □ ".dim(), step.dim()]),
+            });
+        }
+        lines
+    }
+}
+
+impl Renderable for PlanPanel {
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+
+        Paragraph::new(self.render_lines(area.width)).render(area, buf);
+    }
+
+    fn desired_height(&self, width: u16) -> u16 {
+        self.render_lines(width).len() as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn item(step: &str, status: StepStatus) -> PlanItemArg {
+        PlanItemArg {
+            step: step.to_string(),
+            status,
+        }
+    }
+
+    #[test]
+    fn hidden_once_every_step_is_completed() {
+        let mut panel = PlanPanel::new();
+        assert_eq!(panel.desired_height(40), 0);
+
+        panel.set_steps(vec![item("Explore", StepStatus::Completed)]);
+        assert_eq!(panel.desired_height(40), 0);
+    }
+
+    #[test]
+    fn renders_checklist_with_progress() {
+        let mut panel = PlanPanel::new();
+        panel.set_steps(vec![
+            item("Explore codebase", StepStatus::Completed),
+            item("Implement feature", StepStatus::InProgress),
+            item("Write tests", StepStatus::Pending),
+        ]);
+        let width = 30;
+        let height = panel.desired_height(width);
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        panel.render(area, &mut buf);
+
+        let rows: Vec<String> = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                "  Plan 1/3",
+                "  ✔ Explore codebase",
+                "  □ Implement feature",
+                "  □ Write tests",
+            ]
+        );
+    }
+}
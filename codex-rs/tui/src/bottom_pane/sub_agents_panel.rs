@@ -0,0 +1,91 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Paragraph;
+
+use crate::live_wrap::take_prefix_by_width;
+use crate::render::renderable::Renderable;
+
+/// One line per running sub-agent showing its latest activity.
+pub(crate) struct SubAgentsPanel {
+    agents: Vec<(String, String)>,
+}
+
+impl SubAgentsPanel {
+    pub(crate) fn new() -> Self {
+        Self { agents: Vec::new() }
+    }
+
+    /// Replace the `(agent_id, activity)` rows. Returns whether anything changed.
+    pub(crate) fn set_agents(&mut self, agents: Vec<(String, String)>) -> bool {
+        if self.agents == agents {
+            return false;
+        }
+        self.agents = agents;
+        true
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.agents.is_empty()
+    }
+
+    fn render_lines(&self, width: u16) -> Vec<Line<'static>> {
+        if width < 4 {
+            return Vec::new();
+        }
+        self.agents
+            .iter()
+            .map(|(agent_id, activity)| {
+                let prefix = format!("  {agent_id} ");
+                let available = (width as usize).saturating_sub(prefix.len() + 2);
+                let (activity, _, _) = take_prefix_by_width(activity, available);
+                Line::from(vec![prefix.cyan(), "· ".dim(), activity.dim()])
+            })
+            .collect()
+    }
+}
+
+impl Renderable for SubAgentsPanel {
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+
+        Paragraph::new(self.render_lines(area.width)).render(area, buf);
+    }
+
+    fn desired_height(&self, width: u16) -> u16 {
+        self.render_lines(width).len() as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn desired_height_empty() {
+        let panel = SubAgentsPanel::new();
+        assert_eq!(panel.desired_height(40), 0);
+    }
+
+    #[test]
+    fn render_running_agents() {
+        let mut panel = SubAgentsPanel::new();
+        panel.set_agents(vec![
+            (
+                "agent-1".to_string(),
+                "running cargo test -p codex-core".to_string(),
+            ),
+            ("agent-2".to_string(), "thinking".to_string()),
+        ]);
+        let width = 40;
+        let height = panel.desired_height(width);
+        let mut buf = Buffer::empty(Rect::new(0, 0, width, height));
+        panel.render(Rect::new(0, 0, width, height), &mut buf);
+        assert_snapshot!("render_running_agents", format!("{buf:?}"));
+    }
+}
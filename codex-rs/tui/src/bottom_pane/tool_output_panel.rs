@@ -0,0 +1,446 @@
+use std::collections::VecDeque;
+
+use codex_ansi_escape::ansi_escape_line;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyEventKind;
+use crossterm::event::KeyModifiers;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Paragraph;
+
+use crate::live_wrap::take_prefix_by_width;
+use crate::render::renderable::Renderable;
+
+/// Lines of scrollback kept per tool call; older lines are dropped.
+const MAX_LINES: usize = 1000;
+/// A line that never ends (e.g. a spinner without `\r`) is flushed once its
+/// pending bytes reach this size.
+const MAX_PARTIAL_BYTES: usize = 16 * 1024;
+const COLLAPSED_LINES: usize = 3;
+const EXPANDED_LINES: usize = 12;
+
+/// Live output of the tool calls that are still running, shown above the
+/// composer. Each call shows its last few lines and can be expanded into a
+/// scrollable view; colors from ANSI escapes are kept.
+pub(crate) struct ToolOutputPanel {
+    entries: Vec<ToolOutput>,
+    selected: Option<String>,
+}
+
+struct ToolOutput {
+    call_id: String,
+    title: String,
+    lines: VecDeque<String>,
+    partial: Vec<u8>,
+    expanded: bool,
+    /// Lines scrolled up from the tail while expanded.
+    scroll: usize,
+}
+
+impl ToolOutput {
+    fn has_output(&self) -> bool {
+        !self.lines.is_empty() || self.partial_line().is_some()
+    }
+
+    fn push(&mut self, chunk: &[u8]) {
+        self.partial.extend_from_slice(chunk);
+        while let Some(newline) = self.partial.iter().position(|b| *b == b'\n') {
+            let rest = self.partial.split_off(newline + 1);
+            let line = std::mem::replace(&mut self.partial, rest);
+            self.push_line(&line[..newline]);
+        }
+        if self.partial.len() >= MAX_PARTIAL_BYTES {
+            let line = std::mem::take(&mut self.partial);
+            self.push_line(&line);
+        }
+    }
+
+    fn push_line(&mut self, bytes: &[u8]) {
+        let line = overwritten_line(bytes);
+        if self.expanded && self.scroll > 0 {
+            // Keep the view pinned while the user reads older output.
+            self.scroll += 1;
+        }
+        self.lines.push_back(line);
+        if self.lines.len() > MAX_LINES {
+            self.lines.pop_front();
+        }
+        self.clamp_scroll();
+    }
+
+    fn all_lines(&self) -> Vec<&str> {
+        let mut lines: Vec<&str> = self.lines.iter().map(String::as_str).collect();
+        lines.extend(self.partial_line());
+        lines
+    }
+
+    fn partial_line(&self) -> Option<&str> {
+        if self.partial.is_empty() {
+            return None;
+        }
+        // Only complete UTF-8 is shown; the remainder arrives with the next chunk.
+        let valid = match std::str::from_utf8(&self.partial) {
+            Ok(text) => text,
+            Err(err) => std::str::from_utf8(&self.partial[..err.valid_up_to()]).unwrap_or(""),
+        };
+        valid.rsplit('\r').find(|segment| !segment.is_empty())
+    }
+
+    fn total_lines(&self) -> usize {
+        self.lines.len() + usize::from(self.partial_line().is_some())
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.total_lines().saturating_sub(EXPANDED_LINES)
+    }
+
+    fn clamp_scroll(&mut self) {
+        self.scroll = self.scroll.min(self.max_scroll());
+    }
+}
+
+/// Decodes one line of output, keeping only what was written after the last
+/// carriage return so progress bars show their latest state.
+fn overwritten_line(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.strip_suffix('\r').unwrap_or(&text);
+    text.rsplit('\r')
+        .find(|segment| !segment.is_empty())
+        .unwrap_or_default()
+        .to_string()
+}
+
+impl ToolOutputPanel {
+    pub(crate) fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            selected: None,
+        }
+    }
+
+    pub(crate) fn begin(&mut self, call_id: String, title: String) {
+        if self.entries.iter().any(|entry| entry.call_id == call_id) {
+            return;
+        }
+        self.entries.push(ToolOutput {
+            call_id,
+            title,
+            lines: VecDeque::new(),
+            partial: Vec::new(),
+            expanded: false,
+            scroll: 0,
+        });
+    }
+
+    /// Appends a chunk of raw output. Returns whether the panel changed.
+    pub(crate) fn push_output(&mut self, call_id: &str, chunk: &[u8]) -> bool {
+        let Some(entry) = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.call_id == call_id)
+        else {
+            return false;
+        };
+        entry.push(chunk);
+        true
+    }
+
+    /// Drops the output of a finished call. Returns whether the panel changed.
+    pub(crate) fn end(&mut self, call_id: &str) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.call_id != call_id);
+        if self.selected.as_deref() == Some(call_id) {
+            self.selected = None;
+        }
+        self.entries.len() != before
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.selected = None;
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        !self.entries.iter().any(ToolOutput::has_output)
+    }
+
+    /// Handles the panel's shortcuts: Ctrl+O expands or collapses the selected
+    /// call, PageUp/PageDown scroll it and Shift+Tab selects the next call.
+    /// Returns whether the key was consumed.
+    pub(crate) fn handle_key_event(&mut self, key_event: KeyEvent) -> bool {
+        if key_event.kind == KeyEventKind::Release || self.is_empty() {
+            return false;
+        }
+        match key_event {
+            KeyEvent {
+                code: KeyCode::Char('o'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                if let Some(entry) = self.selected_entry_mut() {
+                    entry.expanded = !entry.expanded;
+                    entry.scroll = 0;
+                }
+                true
+            }
+            KeyEvent {
+                code: KeyCode::PageUp,
+                ..
+            } => {
+                if let Some(entry) = self.selected_entry_mut() {
+                    if entry.expanded {
+                        entry.scroll += EXPANDED_LINES - 1;
+                        entry.clamp_scroll();
+                    } else {
+                        entry.expanded = true;
+                    }
+                }
+                true
+            }
+            KeyEvent {
+                code: KeyCode::PageDown,
+                ..
+            } => {
+                if let Some(entry) = self.selected_entry_mut() {
+                    entry.scroll = entry.scroll.saturating_sub(EXPANDED_LINES - 1);
+                }
+                true
+            }
+            KeyEvent {
+                code: KeyCode::BackTab,
+                ..
+            } => {
+                let visible: Vec<&str> = self.visible().map(|e| e.call_id.as_str()).collect();
+                if visible.len() < 2 {
+                    return false;
+                }
+                let current = self.selected_index().unwrap_or(0);
+                self.selected = Some(visible[(current + 1) % visible.len()].to_string());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn visible(&self) -> impl Iterator<Item = &ToolOutput> {
+        self.entries.iter().filter(|entry| entry.has_output())
+    }
+
+    /// Index into the visible entries; defaults to the most recent call.
+    fn selected_index(&self) -> Option<usize> {
+        let count = self.visible().count();
+        if count == 0 {
+            return None;
+        }
+        self.selected
+            .as_deref()
+            .and_then(|id| self.visible().position(|entry| entry.call_id == id))
+            .or(Some(count - 1))
+    }
+
+    fn selected_entry_mut(&mut self) -> Option<&mut ToolOutput> {
+        let index = self.selected_index()?;
+        self.entries
+            .iter_mut()
+            .filter(|entry| entry.has_output())
+            .nth(index)
+    }
+
+    fn render_lines(&self, width: u16) -> Vec<Line<'static>> {
+        if width < 12 {
+            return Vec::new();
+        }
+        let selected = self.selected_index();
+        let several = self.visible().count() > 1;
+        let mut out = Vec::new();
+        for (index, entry) in self.visible().enumerate() {
+            let is_selected = selected == Some(index);
+            let all = entry.all_lines();
+            let (start, end) = if entry.expanded {
+                let end = all.len().saturating_sub(entry.scroll);
+                (end.saturating_sub(EXPANDED_LINES), end)
+            } else {
+                (all.len().saturating_sub(COLLAPSED_LINES), all.len())
+            };
+
+            let mut hint = Vec::new();
+            if start > 0 {
+                hint.push(format!("+{start} lines"));
+            }
+            if is_selected {
+                hint.push(if entry.expanded {
+                    "ctrl+o collapse · pgup/pgdn scroll".to_string()
+                } else {
+                    "ctrl+o expand".to_string()
+                });
+                if several {
+                    hint.push("shift+tab next".to_string());
+                }
+            }
+            let hint = hint.join(" · ");
+            let marker = if several && is_selected { "› " } else { "  " };
+            let available = (width as usize)
+                .saturating_sub(marker.len() + 2 + hint.chars().count() + 2)
+                .max(4);
+            let (title, _, _) = take_prefix_by_width(&entry.title, available);
+            let mut header = vec![Span::from(marker), "$ ".dim()];
+            header.push(if is_selected {
+                title.bold()
+            } else {
+                title.into()
+            });
+            if !hint.is_empty() {
+                header.push("  ".into());
+                header.push(hint.dim());
+            }
+            out.push(Line::from(header));
+
+            for raw in &all[start..end] {
+                let mut line = ansi_escape_line(raw);
+                line.spans.insert(0, "    ".into());
+                out.push(line);
+            }
+        }
+        out
+    }
+}
+
+impl Renderable for ToolOutputPanel {
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+
+        Paragraph::new(self.render_lines(area.width)).render(area, buf);
+    }
+
+    fn desired_height(&self, width: u16) -> u16 {
+        self.render_lines(width).len() as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn rows(panel: &ToolOutputPanel, width: u16) -> Vec<String> {
+        let height = panel.desired_height(width);
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        panel.render(area, &mut buf);
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn hidden_until_the_call_produces_output() {
+        let mut panel = ToolOutputPanel::new();
+        panel.begin("call-1".to_string(), "cargo test".to_string());
+        assert_eq!(panel.desired_height(60), 0);
+        assert!(!panel.handle_key_event(key(KeyCode::Char('o'), KeyModifiers::CONTROL)));
+
+        assert!(panel.push_output("call-1", b"Compiling"));
+        assert_eq!(panel.desired_height(60), 2);
+
+        assert!(panel.end("call-1"));
+        assert!(panel.is_empty());
+    }
+
+    #[test]
+    fn collapsed_shows_the_tail_and_handles_carriage_returns() {
+        let mut panel = ToolOutputPanel::new();
+        panel.begin("call-1".to_string(), "cargo test".to_string());
+        panel.push_output("call-1", b"one\ntwo\nthree\nfour\n");
+        panel.push_output("call-1", b"10%\r50%\r\xE2\x9C");
+
+        assert_eq!(
+            rows(&panel, 60),
+            vec![
+                "  $ cargo test  +2 lines · ctrl+o expand",
+                "    three",
+                "    four",
+                "    50%",
+            ]
+        );
+    }
+
+    #[test]
+    fn expanded_view_scrolls_and_preserves_colors() {
+        let mut panel = ToolOutputPanel::new();
+        panel.begin("call-1".to_string(), "make".to_string());
+        for i in 1..=20 {
+            panel.push_output("call-1", format!("line {i}\n").as_bytes());
+        }
+        panel.push_output("call-1", b"\x1b[31merror\x1b[0m\n");
+
+        assert!(panel.handle_key_event(key(KeyCode::Char('o'), KeyModifiers::CONTROL)));
+        let expanded = rows(&panel, 60);
+        assert_eq!(expanded.len(), 1 + EXPANDED_LINES);
+        assert_eq!(
+            expanded[0],
+            "  $ make  +9 lines · ctrl+o collapse · pgup/pgdn scroll"
+        );
+        assert_eq!(expanded.last().map(String::as_str), Some("    error"));
+
+        let last = panel.render_lines(60).pop().expect("error line");
+        assert_eq!(
+            last.spans[1].style.fg,
+            Some(ratatui::style::Color::Red),
+            "ANSI colors are kept"
+        );
+
+        assert!(panel.handle_key_event(key(KeyCode::PageUp, KeyModifiers::NONE)));
+        let scrolled = rows(&panel, 60);
+        assert_eq!(scrolled[1], "    line 1");
+        assert_eq!(scrolled.last().map(String::as_str), Some("    line 12"));
+
+        assert!(panel.handle_key_event(key(KeyCode::PageDown, KeyModifiers::NONE)));
+        assert_eq!(rows(&panel, 60), expanded);
+    }
+
+    #[test]
+    fn shift_tab_cycles_between_running_calls() {
+        let mut panel = ToolOutputPanel::new();
+        panel.begin("call-1".to_string(), "npm test".to_string());
+        panel.begin("call-2".to_string(), "cargo build".to_string());
+        panel.push_output("call-1", b"ok\n");
+        assert!(
+            !panel.handle_key_event(key(KeyCode::BackTab, KeyModifiers::SHIFT)),
+            "nothing to cycle with a single call"
+        );
+        panel.push_output("call-2", b"done\n");
+
+        assert_eq!(
+            rows(&panel, 70),
+            vec![
+                "  $ npm test",
+                "    ok",
+                "› $ cargo build  ctrl+o expand · shift+tab next",
+                "    done",
+            ]
+        );
+
+        assert!(panel.handle_key_event(key(KeyCode::BackTab, KeyModifiers::SHIFT)));
+        assert!(panel.handle_key_event(key(KeyCode::Char('o'), KeyModifiers::CONTROL)));
+        assert_eq!(
+            rows(&panel, 70)[0],
+            "› $ npm test  ctrl+o collapse · pgup/pgdn scroll · shift+tab next"
+        );
+    }
+}
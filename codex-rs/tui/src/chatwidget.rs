@@ -4,9 +4,14 @@ use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
 
 use codex_app_server_protocol::AuthMode;
 use codex_backend_client::Client as BackendClient;
+use codex_core::commit_message::commit_staged;
+use codex_core::commit_message::conventional_commit_problem;
+use codex_core::commit_message::generate_commit_message;
+use codex_core::commit_message::staged_changes;
 use codex_core::config::Config;
 use codex_core::config::ConstraintResult;
 use codex_core::config::types::Notifications;
@@ -25,6 +30,10 @@ use codex_core::protocol::AgentReasoningRawContentDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::BudgetExceededEvent;
+use codex_core::protocol::ContextItemKind;
+use codex_core::protocol::ContextItemsDroppedEvent;
+use codex_core::protocol::ContextPressureEvent;
 use codex_core::protocol::CreditsSnapshot;
 use codex_core::protocol::DeprecationNoticeEvent;
 use codex_core::protocol::ErrorEvent;
@@ -45,17 +54,27 @@ use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
+use codex_core::protocol::PinnedContextItem;
+use codex_core::protocol::PinnedContextUpdatedEvent;
 use codex_core::protocol::RateLimitSnapshot;
+use codex_core::protocol::ReadOnlyModeChangedEvent;
+use codex_core::protocol::RetryScheduledEvent;
 use codex_core::protocol::ReviewRequest;
 use codex_core::protocol::ReviewTarget;
+use codex_core::protocol::SecretsRedactedEvent;
+use codex_core::protocol::SessionDiffEvent;
 use codex_core::protocol::SkillsListEntry;
+use codex_core::protocol::SpendSummary;
 use codex_core::protocol::StreamErrorEvent;
+use codex_core::protocol::SubAgentEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TerminalInteractionEvent;
 use codex_core::protocol::TokenUsage;
 use codex_core::protocol::TokenUsageInfo;
+use codex_core::protocol::ToolCallLoopDetectedEvent;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnDiffEvent;
+use codex_core::protocol::TurnTimedOutEvent;
 use codex_core::protocol::UndoCompletedEvent;
 use codex_core::protocol::UndoStartedEvent;
 use codex_core::protocol::UserMessageEvent;
@@ -79,6 +98,7 @@ use ratatui::layout::Rect;
 use ratatui::style::Color;
 use ratatui::style::Stylize;
 use ratatui::text::Line;
+use ratatui::text::Span;
 use ratatui::widgets::Paragraph;
 use ratatui::widgets::Wrap;
 use tokio::sync::mpsc::UnboundedSender;
@@ -111,6 +131,7 @@ use crate::history_cell::AgentMessageCell;
 use crate::history_cell::HistoryCell;
 use crate::history_cell::McpToolCallCell;
 use crate::history_cell::PlainHistoryCell;
+use crate::key_hint;
 use crate::markdown::append_markdown;
 use crate::render::Insets;
 use crate::render::renderable::ColumnRenderable;
@@ -120,8 +141,12 @@ use crate::render::renderable::RenderableExt;
 use crate::render::renderable::RenderableItem;
 use crate::slash_command::SlashCommand;
 use crate::status::RateLimitSnapshotDisplay;
+use crate::status::format_tokens_compact;
 use crate::text_formatting::truncate_text;
 use crate::tui::FrameRequester;
+use crate::voice;
+use crate::voice::VoiceInput;
+use crate::voice::VoiceRecording;
 mod interrupts;
 use self::interrupts::InterruptManager;
 mod agent;
@@ -314,6 +339,13 @@ pub(crate) struct ChatWidget {
     session_header: SessionHeader,
     initial_user_message: Option<UserMessage>,
     token_info: Option<TokenUsageInfo>,
+    pinned_context: Option<PinnedContextUpdatedEvent>,
+    /// Latest context pressure warning; `/trim` offers its largest items.
+    context_pressure: Option<ContextPressureEvent>,
+    /// Latest cumulative diff of the files tools changed, shown by `/changes`.
+    session_diff: Option<String>,
+    // Estimated spend from the latest token count event.
+    spend: Option<SpendSummary>,
     rate_limit_snapshot: Option<RateLimitSnapshotDisplay>,
     plan_type: Option<PlanType>,
     rate_limit_warnings: RateLimitWarningState,
@@ -322,6 +354,8 @@ pub(crate) struct ChatWidget {
     // Stream lifecycle controller
     stream_controller: Option<StreamController>,
     running_commands: HashMap<String, RunningCommand>,
+    /// Latest activity of each running sub-agent, in spawn order.
+    sub_agents: Vec<(String, String)>,
     suppressed_exec_calls: HashSet<String>,
     last_unified_wait: Option<UnifiedExecWaitState>,
     task_complete_pending: bool,
@@ -348,6 +382,8 @@ pub(crate) struct ChatWidget {
     queued_user_messages: VecDeque<UserMessage>,
     // Pending notification to show when unfocused on next Draw
     pending_notification: Option<Notification>,
+    // When the running turn started, to skip notifications for short turns
+    turn_started_at: Option<Instant>,
     // Simple review mode flag; used to adjust layout and banners.
     is_review_mode: bool,
     // Snapshot of token usage to restore after review mode exits.
@@ -360,11 +396,13 @@ pub(crate) struct ChatWidget {
     feedback: codex_feedback::CodexFeedback,
     // Current session rollout path (if known)
     current_rollout_path: Option<PathBuf>,
+    voice_input: VoiceInput,
 }
 
 struct UserMessage {
     text: String,
     image_paths: Vec<PathBuf>,
+    mentioned_files: Vec<PathBuf>,
 }
 
 impl From<String> for UserMessage {
@@ -372,6 +410,7 @@ impl From<String> for UserMessage {
         Self {
             text,
             image_paths: Vec::new(),
+            mentioned_files: Vec::new(),
         }
     }
 }
@@ -381,6 +420,7 @@ impl From<&str> for UserMessage {
         Self {
             text: text.to_string(),
             image_paths: Vec::new(),
+            mentioned_files: Vec::new(),
         }
     }
 }
@@ -389,7 +429,11 @@ fn create_initial_user_message(text: String, image_paths: Vec<PathBuf>) -> Optio
     if text.is_empty() && image_paths.is_empty() {
         None
     } else {
-        Some(UserMessage { text, image_paths })
+        Some(UserMessage {
+            text,
+            image_paths,
+            mentioned_files: Vec::new(),
+        })
     }
 }
 
@@ -528,6 +572,7 @@ impl ChatWidget {
             let cell = history_cell::new_reasoning_summary_block(
                 self.full_reasoning_buffer.clone(),
                 reasoning_summary_format,
+                self.config.collapse_reasoning,
             );
             self.add_boxed_history(cell);
         }
@@ -553,6 +598,7 @@ impl ChatWidget {
         self.set_status_header(String::from("Working"));
         self.full_reasoning_buffer.clear();
         self.reasoning_buffer.clear();
+        self.turn_started_at = Some(Instant::now());
         self.request_redraw();
     }
 
@@ -563,16 +609,25 @@ impl ChatWidget {
         // Mark task stopped and request redraw now that all content is in history.
         self.bottom_pane.set_task_running(false);
         self.running_commands.clear();
+        self.clear_sub_agents();
+        self.bottom_pane.clear_tool_output();
         self.suppressed_exec_calls.clear();
         self.last_unified_wait = None;
         self.request_redraw();
 
         // If there is a queued user message, send exactly one now to begin the next turn.
         self.maybe_send_next_queued_input();
-        // Emit a notification when the turn completes (suppressed if focused).
-        self.notify(Notification::AgentTurnComplete {
-            response: last_agent_message.unwrap_or_default(),
-        });
+        // Emit a notification when a long enough turn completes (suppressed if focused).
+        let min_turn = Duration::from_secs(self.config.tui_notification_min_turn_secs);
+        if self
+            .turn_started_at
+            .take()
+            .is_none_or(|started| started.elapsed() >= min_turn)
+        {
+            self.notify(Notification::AgentTurnComplete {
+                response: last_agent_message.unwrap_or_default(),
+            });
+        }
 
         self.maybe_show_pending_rate_limit_prompt();
     }
@@ -697,6 +752,8 @@ impl ChatWidget {
         // Reset running state and clear streaming buffers.
         self.bottom_pane.set_task_running(false);
         self.running_commands.clear();
+        self.clear_sub_agents();
+        self.bottom_pane.clear_tool_output();
         self.suppressed_exec_calls.clear();
         self.last_unified_wait = None;
         self.stream_controller = None;
@@ -715,6 +772,90 @@ impl ChatWidget {
         self.maybe_send_next_queued_input();
     }
 
+    fn on_budget_exceeded(&mut self, ev: BudgetExceededEvent) {
+        self.add_to_history(history_cell::new_error_event(ev.message));
+        self.request_redraw();
+    }
+
+    /// The `TurnAborted` that follows ends the turn; this only explains why
+    /// and what the user can do next.
+    fn on_turn_timed_out(&mut self, ev: TurnTimedOutEvent) {
+        self.add_to_history(history_cell::new_error_event(ev.message));
+        self.add_info_message(
+            "Send a message to let the agent continue, or try a narrower request.".to_string(),
+            Some("Raise turn_timeout_secs in config.toml if turns need more time.".to_string()),
+        );
+    }
+
+    /// The loop itself is answered in core; this only tells the user, who
+    /// may know what the agent is missing.
+    fn on_tool_call_loop_detected(&mut self, ev: ToolCallLoopDetectedEvent) {
+        self.on_warning(ev.message);
+        self.add_info_message(
+            "The agent appears to be stuck repeating itself.".to_string(),
+            Some(
+                "Type a hint and press Esc to steer it, or Esc on an empty prompt to interrupt."
+                    .to_string(),
+            ),
+        );
+    }
+
+    fn on_sub_agent_event(&mut self, ev: SubAgentEvent) {
+        let SubAgentEvent { agent_id, msg } = ev;
+        let (activity, history) = match *msg {
+            EventMsg::TaskStarted(_) => (Some("starting".to_string()), None),
+            EventMsg::AgentReasoning(_) => (Some("thinking".to_string()), None),
+            EventMsg::ExecCommandBegin(begin) => {
+                let command = strip_bash_lc_and_escape(&begin.command);
+                (
+                    Some(format!("running {command}")),
+                    Some(format!("ran {command}")),
+                )
+            }
+            EventMsg::PatchApplyBegin(begin) => {
+                let count = begin.changes.len();
+                (
+                    Some(format!("editing {count} file(s)")),
+                    Some(format!("edited {count} file(s)")),
+                )
+            }
+            EventMsg::McpToolCallBegin(begin) => (
+                Some(format!(
+                    "calling {}.{}",
+                    begin.invocation.server, begin.invocation.tool
+                )),
+                None,
+            ),
+            EventMsg::TaskComplete(_) => (None, Some("finished".to_string())),
+            EventMsg::TurnAborted(_) => (None, Some("aborted".to_string())),
+            _ => return,
+        };
+
+        match activity {
+            Some(activity) => match self.sub_agents.iter_mut().find(|(id, _)| *id == agent_id) {
+                Some(entry) => entry.1 = activity,
+                None => self.sub_agents.push((agent_id.clone(), activity)),
+            },
+            None => self.sub_agents.retain(|(id, _)| *id != agent_id),
+        }
+        self.bottom_pane.set_sub_agents(self.sub_agents.clone());
+
+        if let Some(summary) = history {
+            self.add_to_history(history_cell::new_info_event(
+                format!("{agent_id} {summary}"),
+                None,
+            ));
+        }
+        self.request_redraw();
+    }
+
+    fn clear_sub_agents(&mut self) {
+        if !self.sub_agents.is_empty() {
+            self.sub_agents.clear();
+            self.bottom_pane.set_sub_agents(Vec::new());
+        }
+    }
+
     fn on_warning(&mut self, message: impl Into<String>) {
         self.add_to_history(history_cell::new_warning_event(message.into()));
         self.request_redraw();
@@ -795,7 +936,11 @@ impl ChatWidget {
         // Finalize, log a gentle prompt, and clear running state.
         self.finalize_turn();
 
-        if reason != TurnAbortReason::ReviewEnded {
+        // A timeout was already explained by its `TurnTimedOut` event.
+        if !matches!(
+            reason,
+            TurnAbortReason::ReviewEnded | TurnAbortReason::TimedOut
+        ) {
             self.add_to_history(history_cell::new_error_event(
                 "Conversation interrupted - tell the model what to do differently. Something went wrong? Hit `/feedback` to report the issue.".to_owned(),
             ));
@@ -826,7 +971,17 @@ impl ChatWidget {
         self.request_redraw();
     }
 
+    fn on_secrets_redacted(&mut self, event: SecretsRedactedEvent) {
+        let SecretsRedactedEvent { count, detectors } = event;
+        let noun = if count == 1 { "secret" } else { "secrets" };
+        self.add_info_message(
+            format!("Redacted {count} {noun} before sending to the model"),
+            Some(detectors.join(", ")),
+        );
+    }
+
     fn on_plan_update(&mut self, update: UpdatePlanArgs) {
+        self.bottom_pane.set_plan(update.plan.clone());
         self.add_to_history(history_cell::new_plan_update(update));
     }
 
@@ -858,6 +1013,8 @@ impl ChatWidget {
 
     fn on_exec_command_begin(&mut self, ev: ExecCommandBeginEvent) {
         self.flush_answer_stream_with_separator();
+        self.bottom_pane
+            .begin_tool_output(ev.call_id.clone(), strip_bash_lc_and_escape(&ev.command));
         if is_unified_exec_source(ev.source) {
             self.track_unified_exec_session_begin(&ev);
             if !is_standard_tool_call(&ev.parsed_cmd) {
@@ -870,9 +1027,9 @@ impl ChatWidget {
 
     fn on_exec_command_output_delta(
         &mut self,
-        _ev: codex_core::protocol::ExecCommandOutputDeltaEvent,
+        ev: codex_core::protocol::ExecCommandOutputDeltaEvent,
     ) {
-        // TODO: Handle streaming exec output if/when implemented
+        self.bottom_pane.push_tool_output(&ev.call_id, &ev.chunk);
     }
 
     fn on_terminal_interaction(&mut self, ev: TerminalInteractionEvent) {
@@ -957,6 +1114,7 @@ impl ChatWidget {
     }
 
     fn on_exec_command_end(&mut self, ev: ExecCommandEndEvent) {
+        self.bottom_pane.end_tool_output(&ev.call_id);
         if is_unified_exec_source(ev.source) {
             self.track_unified_exec_session_end(&ev);
             if !self.bottom_pane.is_task_running() {
@@ -1241,6 +1399,7 @@ impl ChatWidget {
             command: ev.command,
             reason: ev.reason,
             proposed_execpolicy_amendment: ev.proposed_execpolicy_amendment,
+            proposed_permissions: ev.proposed_permissions,
         };
         self.bottom_pane
             .push_approval_request(request, &self.config.features);
@@ -1259,6 +1418,7 @@ impl ChatWidget {
             reason: ev.reason,
             changes: ev.changes.clone(),
             cwd: self.config.cwd.clone(),
+            proposed_permissions: ev.proposed_permissions,
         };
         self.bottom_pane
             .push_approval_request(request, &self.config.features);
@@ -1441,6 +1601,10 @@ impl ChatWidget {
                 initial_images,
             ),
             token_info: None,
+            pinned_context: None,
+            context_pressure: None,
+            session_diff: None,
+            spend: None,
             rate_limit_snapshot: None,
             plan_type: None,
             rate_limit_warnings: RateLimitWarningState::default(),
@@ -1448,6 +1612,7 @@ impl ChatWidget {
             rate_limit_poller: None,
             stream_controller: None,
             running_commands: HashMap::new(),
+            sub_agents: Vec::new(),
             suppressed_exec_calls: HashSet::new(),
             last_unified_wait: None,
             task_complete_pending: false,
@@ -1463,14 +1628,19 @@ impl ChatWidget {
             show_welcome_banner: is_first_run,
             suppress_session_configured_redraw: false,
             pending_notification: None,
+            turn_started_at: None,
             is_review_mode: false,
             pre_review_token_info: None,
             needs_final_message_separator: false,
             last_rendered_width: std::cell::Cell::new(None),
             feedback,
             current_rollout_path: None,
+            voice_input: VoiceInput::Idle,
         };
 
+        widget
+            .bottom_pane
+            .set_account(widget.auth_manager.account().map(str::to_string));
         widget.prefetch_rate_limits();
 
         widget
@@ -1527,6 +1697,10 @@ impl ChatWidget {
                 initial_images,
             ),
             token_info: None,
+            pinned_context: None,
+            context_pressure: None,
+            session_diff: None,
+            spend: None,
             rate_limit_snapshot: None,
             plan_type: None,
             rate_limit_warnings: RateLimitWarningState::default(),
@@ -1534,6 +1708,7 @@ impl ChatWidget {
             rate_limit_poller: None,
             stream_controller: None,
             running_commands: HashMap::new(),
+            sub_agents: Vec::new(),
             suppressed_exec_calls: HashSet::new(),
             last_unified_wait: None,
             task_complete_pending: false,
@@ -1549,14 +1724,19 @@ impl ChatWidget {
             show_welcome_banner: false,
             suppress_session_configured_redraw: true,
             pending_notification: None,
+            turn_started_at: None,
             is_review_mode: false,
             pre_review_token_info: None,
             needs_final_message_separator: false,
             last_rendered_width: std::cell::Cell::new(None),
             feedback,
             current_rollout_path: None,
+            voice_input: VoiceInput::Idle,
         };
 
+        widget
+            .bottom_pane
+            .set_account(widget.auth_manager.account().map(str::to_string));
         widget.prefetch_rate_limits();
 
         widget
@@ -1599,6 +1779,25 @@ impl ChatWidget {
                 }
                 return;
             }
+            KeyEvent {
+                code: KeyCode::Char(c),
+                modifiers: KeyModifiers::CONTROL,
+                kind: KeyEventKind::Press,
+                ..
+            } if c.eq_ignore_ascii_case(&'r') => {
+                self.toggle_voice_input();
+                return;
+            }
+            KeyEvent {
+                code: KeyCode::Esc,
+                kind: KeyEventKind::Press,
+                ..
+            } if matches!(self.voice_input, VoiceInput::Recording(_)) => {
+                // Dropping the recording stops the recorder and discards the audio.
+                self.voice_input = VoiceInput::Idle;
+                self.bottom_pane.set_footer_hint_override(None);
+                return;
+            }
             other if other.kind == KeyEventKind::Press => {
                 self.bottom_pane.clear_ctrl_c_quit_hint();
             }
@@ -1619,6 +1818,17 @@ impl ChatWidget {
                     self.request_redraw();
                 }
             }
+            KeyEvent {
+                code: KeyCode::Down,
+                modifiers: KeyModifiers::ALT,
+                kind: KeyEventKind::Press,
+                ..
+            } if !self.queued_user_messages.is_empty() => {
+                // Drop the most recently queued item without sending it.
+                self.queued_user_messages.pop_back();
+                self.refresh_queued_user_messages();
+                self.request_redraw();
+            }
             _ => {
                 match self.bottom_pane.handle_key_event(key_event) {
                     InputResult::Submitted(text) => {
@@ -1626,12 +1836,17 @@ impl ChatWidget {
                         let user_message = UserMessage {
                             text,
                             image_paths: self.bottom_pane.take_recent_submission_images(),
+                            mentioned_files: self.bottom_pane.take_recent_submission_mentions(),
                         };
                         self.queue_user_message(user_message);
                     }
+                    InputResult::Steered(text) => self.steer_running_task(text),
                     InputResult::Command(cmd) => {
                         self.dispatch_command(cmd);
                     }
+                    InputResult::CommandWithArgs(cmd, args) => {
+                        self.dispatch_command_with_args(cmd, args);
+                    }
                     InputResult::None => {}
                 }
             }
@@ -1653,6 +1868,33 @@ impl ChatWidget {
         self.request_redraw();
     }
 
+    fn dispatch_command_with_args(&mut self, cmd: SlashCommand, args: String) {
+        match cmd {
+            SlashCommand::Pin => {
+                let item = if let Some(path) = args.strip_prefix("file ") {
+                    PinnedContextItem::File {
+                        path: PathBuf::from(path.trim()),
+                    }
+                } else if let Some(text) = args.strip_prefix("instruction ") {
+                    PinnedContextItem::Instruction {
+                        text: text.trim().to_string(),
+                    }
+                } else {
+                    PinnedContextItem::Message { text: args }
+                };
+                self.submit_op(Op::PinContext { item });
+            }
+            SlashCommand::Unpin => {
+                self.submit_op(Op::UnpinContext {
+                    id: args.trim().to_string(),
+                });
+            }
+            SlashCommand::Think => self.think_next_turn(&args),
+            SlashCommand::Ask => self.set_read_only_mode(&args),
+            _ => self.dispatch_command(cmd),
+        }
+    }
+
     fn dispatch_command(&mut self, cmd: SlashCommand) {
         if !cmd.available_during_task() && self.bottom_pane.is_task_running() {
             let message = format!(
@@ -1693,6 +1935,19 @@ impl ChatWidget {
                 self.clear_token_usage();
                 self.app_event_tx.send(AppEvent::CodexOp(Op::Compact));
             }
+            SlashCommand::Trim => self.open_trim_popup(),
+            SlashCommand::Pin => {
+                let pinned = self.pinned_context.clone().unwrap_or_default();
+                self.add_to_history(history_cell::new_pinned_context_output(&pinned));
+            }
+            SlashCommand::Unpin => {
+                self.add_info_message(
+                    "Usage: /unpin <id>".to_string(),
+                    Some("Run /pin to list pinned items and their ids.".to_string()),
+                );
+            }
+            SlashCommand::Think => self.think_next_turn(""),
+            SlashCommand::Ask => self.set_read_only_mode(""),
             SlashCommand::Review => {
                 self.open_review_popup();
             }
@@ -1737,6 +1992,13 @@ impl ChatWidget {
                     tx.send(AppEvent::DiffResult(text));
                 });
             }
+            SlashCommand::Changes => {
+                self.add_diff_in_progress();
+                self.app_event_tx.send(AppEvent::DiffResult(
+                    self.session_diff.clone().unwrap_or_default(),
+                ));
+            }
+            SlashCommand::Commit => self.write_commit_message(),
             SlashCommand::Mention => {
                 self.insert_str("@");
             }
@@ -1797,6 +2059,7 @@ impl ChatWidget {
                         ]),
                         reason: None,
                         grant_root: Some(PathBuf::from("/tmp")),
+                        proposed_permissions: Vec::new(),
                     }),
                 }));
             }
@@ -1867,6 +2130,125 @@ impl ChatWidget {
         self.app_event_tx.send(AppEvent::InsertHistoryCell(cell));
     }
 
+    /// Starts recording, or stops the recording in progress and transcribes
+    /// it into the composer.
+    fn toggle_voice_input(&mut self) {
+        let Some(voice) = self.config.tui_voice.clone() else {
+            self.add_info_message(
+                "Voice input is off.".to_string(),
+                Some("Add a [tui.voice] table to config.toml to enable it.".to_string()),
+            );
+            return;
+        };
+        match std::mem::take(&mut self.voice_input) {
+            VoiceInput::Idle => match VoiceRecording::start(&voice) {
+                Ok(recording) => {
+                    self.voice_input = VoiceInput::Recording(recording);
+                    let stop_key = Span::from(key_hint::ctrl(KeyCode::Char('r')));
+                    self.bottom_pane.set_footer_hint_override(Some(vec![
+                        (
+                            stop_key.content.into_owned(),
+                            "stop and transcribe".to_string(),
+                        ),
+                        ("esc".to_string(), "cancel recording".to_string()),
+                    ]));
+                }
+                Err(err) => self.add_to_history(history_cell::new_error_event(err)),
+            },
+            VoiceInput::Recording(recording) => {
+                self.voice_input = VoiceInput::Transcribing;
+                self.bottom_pane.set_footer_hint_override(Some(vec![(
+                    "●".to_string(),
+                    "transcribing…".to_string(),
+                )]));
+                let tx = self.app_event_tx.clone();
+                tokio::spawn(async move {
+                    let result = match recording.finish().await {
+                        Ok(audio) => voice::transcribe(&voice, &audio).await,
+                        Err(err) => Err(err),
+                    };
+                    tx.send(AppEvent::VoiceTranscribed(result));
+                });
+            }
+            VoiceInput::Transcribing => self.voice_input = VoiceInput::Transcribing,
+        }
+        self.request_redraw();
+    }
+
+    pub(crate) fn on_voice_transcribed(&mut self, result: Result<String, String>) {
+        self.voice_input = VoiceInput::Idle;
+        self.bottom_pane.set_footer_hint_override(None);
+        match result {
+            Ok(transcript) => self.bottom_pane.insert_str(&transcript),
+            Err(err) => self.add_to_history(history_cell::new_error_event(err)),
+        }
+        self.request_redraw();
+    }
+
+    /// Asks the model for a commit message for the staged changes; the result
+    /// arrives as `AppEvent::CommitMessageGenerated`.
+    fn write_commit_message(&mut self) {
+        self.add_info_message(
+            "Writing a commit message for the staged changes…".to_string(),
+            None,
+        );
+        let config = self.config.clone();
+        let auth_manager = self.auth_manager.clone();
+        let tx = self.app_event_tx.clone();
+        tokio::spawn(async move {
+            let result = match staged_changes(&config.cwd).await {
+                Ok(Some(changes)) => generate_commit_message(&config, auth_manager, &changes)
+                    .await
+                    .map_err(|err| format!("Failed to write a commit message: {err}")),
+                Ok(None) => {
+                    Err("Nothing is staged. Stage changes with `git add` first.".to_string())
+                }
+                Err(err) => Err(format!("Failed to read the staged changes: {err}")),
+            };
+            tx.send(AppEvent::CommitMessageGenerated(result));
+        });
+    }
+
+    /// Opens the generated message for editing; submitting it commits.
+    pub(crate) fn on_commit_message_generated(&mut self, result: Result<String, String>) {
+        let message = match result {
+            Ok(message) => message,
+            Err(err) => {
+                self.add_error_message(err);
+                return;
+            }
+        };
+        let warning = if self.config.commit.template.is_none() {
+            conventional_commit_problem(&message)
+                .map(|problem| format!("Not a Conventional Commits message: {problem}"))
+        } else {
+            None
+        };
+        let cwd = self.config.cwd.clone();
+        let tx = self.app_event_tx.clone();
+        let view = CustomPromptView::new(
+            "Commit message (Enter commits, Shift+Enter adds a line)".to_string(),
+            "Write a commit message and press Enter".to_string(),
+            warning,
+            Box::new(move |message: String| {
+                let cwd = cwd.clone();
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    let cell = match commit_staged(&cwd, &message).await {
+                        Ok(summary) => history_cell::new_info_event(summary, None),
+                        Err(err) => {
+                            history_cell::new_error_event(format!("git commit failed: {err}"))
+                        }
+                    };
+                    tx.send(AppEvent::InsertHistoryCell(Box::new(cell)));
+                });
+            }),
+        )
+        .with_text(&message);
+        self.bottom_pane.show_view(Box::new(view));
+        self.request_redraw();
+    }
+
     fn queue_user_message(&mut self, user_message: UserMessage) {
         if self.bottom_pane.is_task_running() {
             self.queued_user_messages.push_back(user_message);
@@ -1876,8 +2258,24 @@ impl ChatWidget {
         }
     }
 
+    /// Sends `text` as a correction to the running task: core cancels the
+    /// response in flight and restarts it with the correction.
+    fn steer_running_task(&mut self, text: String) {
+        self.flush_answer_stream_with_separator();
+        self.submit_op(Op::Steer {
+            items: vec![UserInput::Text { text: text.clone() }],
+        });
+        self.add_to_history(history_cell::new_user_prompt(text));
+        self.needs_final_message_separator = false;
+        self.request_redraw();
+    }
+
     fn submit_user_message(&mut self, user_message: UserMessage) {
-        let UserMessage { text, image_paths } = user_message;
+        let UserMessage {
+            text,
+            image_paths,
+            mentioned_files,
+        } = user_message;
         if text.is_empty() && image_paths.is_empty() {
             return;
         }
@@ -1910,6 +2308,10 @@ impl ChatWidget {
             items.push(UserInput::LocalImage { path });
         }
 
+        for path in mentioned_files {
+            items.push(UserInput::FileMention { path });
+        }
+
         if let Some(skills) = self.bottom_pane.skills() {
             let skill_mentions = find_skill_mentions(&text, skills);
             for skill in skill_mentions {
@@ -1968,7 +2370,8 @@ impl ChatWidget {
     /// `replay_initial_messages()`. Callers should treat `None` as a "fake" id
     /// that must not be used to correlate follow-up actions.
     fn dispatch_event_msg(&mut self, id: Option<String>, msg: EventMsg, from_replay: bool) {
-        let is_stream_error = matches!(&msg, EventMsg::StreamError(_));
+        let is_stream_error =
+            matches!(&msg, EventMsg::StreamError(_) | EventMsg::RetryScheduled(_));
         if !is_stream_error {
             self.restore_retry_status_header_if_present();
         }
@@ -2006,6 +2409,8 @@ impl ChatWidget {
             EventMsg::TokenCount(ev) => {
                 self.set_token_info(ev.info);
                 self.on_rate_limit_snapshot(ev.rate_limits);
+                self.spend = ev.spend;
+                self.bottom_pane.set_spend(ev.spend);
             }
             EventMsg::Warning(WarningEvent { message }) => self.on_warning(message),
             EventMsg::Error(ErrorEvent { message, .. }) => self.on_error(message),
@@ -2018,7 +2423,7 @@ impl ChatWidget {
                 TurnAbortReason::Replaced => {
                     self.on_error("Turn aborted: replaced by a new task".to_owned())
                 }
-                TurnAbortReason::ReviewEnded => {
+                TurnAbortReason::ReviewEnded | TurnAbortReason::TimedOut => {
                     self.on_interrupted_turn(ev.reason);
                 }
             },
@@ -2056,13 +2461,18 @@ impl ChatWidget {
             }
             EventMsg::ShutdownComplete => self.on_shutdown_complete(),
             EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => self.on_turn_diff(unified_diff),
+            EventMsg::SessionDiff(SessionDiffEvent { unified_diff }) => {
+                self.session_diff = Some(unified_diff);
+            }
+            EventMsg::SecretsRedacted(ev) => self.on_secrets_redacted(ev),
             EventMsg::DeprecationNotice(ev) => self.on_deprecation_notice(ev),
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
                 self.on_background_event(message)
             }
             EventMsg::UndoStarted(ev) => self.on_undo_started(ev),
             EventMsg::UndoCompleted(ev) => self.on_undo_completed(ev),
-            EventMsg::StreamError(StreamErrorEvent { message, .. }) => {
+            EventMsg::StreamError(StreamErrorEvent { message, .. })
+            | EventMsg::RetryScheduled(RetryScheduledEvent { message, .. }) => {
                 self.on_stream_error(message)
             }
             EventMsg::UserMessage(ev) => {
@@ -2075,6 +2485,14 @@ impl ChatWidget {
             }
             EventMsg::ExitedReviewMode(review) => self.on_exited_review_mode(review),
             EventMsg::ContextCompacted(_) => self.on_agent_message("Context compacted".to_owned()),
+            EventMsg::PinnedContextUpdated(ev) => self.on_pinned_context_updated(ev, from_replay),
+            EventMsg::BudgetExceeded(ev) => self.on_budget_exceeded(ev),
+            EventMsg::TurnTimedOut(ev) => self.on_turn_timed_out(ev),
+            EventMsg::ToolCallLoopDetected(ev) => self.on_tool_call_loop_detected(ev),
+            EventMsg::ContextPressure(ev) => self.on_context_pressure(ev),
+            EventMsg::ContextItemsDropped(ev) => self.on_context_items_dropped(ev),
+            EventMsg::ReadOnlyModeChanged(ev) => self.on_read_only_mode_changed(ev),
+            EventMsg::SubAgent(ev) => self.on_sub_agent_event(ev),
             EventMsg::RawResponseItem(_)
             | EventMsg::ItemStarted(_)
             | EventMsg::ItemCompleted(_)
@@ -2084,6 +2502,121 @@ impl ChatWidget {
         }
     }
 
+    fn on_pinned_context_updated(&mut self, ev: PinnedContextUpdatedEvent, from_replay: bool) {
+        if !from_replay {
+            self.add_to_history(history_cell::new_pinned_context_output(&ev));
+        }
+        self.pinned_context = Some(ev);
+        self.request_redraw();
+    }
+
+    fn on_context_pressure(&mut self, ev: ContextPressureEvent) {
+        let breakdown = &ev.breakdown;
+        self.on_warning(format!(
+            "Context window is {}% full ({} of {} tokens): tool outputs {}, history {}, instructions {}, pinned {}.",
+            ev.threshold_percent,
+            format_tokens_compact(ev.used_tokens),
+            format_tokens_compact(ev.context_window),
+            format_tokens_compact(breakdown.tool_outputs),
+            format_tokens_compact(breakdown.history),
+            format_tokens_compact(breakdown.instructions),
+            format_tokens_compact(breakdown.pinned),
+        ));
+        self.add_info_message(
+            "Run /trim to drop the largest items, or /compact to summarize the conversation."
+                .to_string(),
+            None,
+        );
+        self.context_pressure = Some(ev);
+    }
+
+    fn on_context_items_dropped(&mut self, ev: ContextItemsDroppedEvent) {
+        if let Some(pressure) = self.context_pressure.as_mut() {
+            pressure
+                .largest_items
+                .retain(|item| !ev.dropped.contains(&item.id));
+        }
+        if !ev.dropped.is_empty() {
+            let count = ev.dropped.len();
+            let noun = if count == 1 { "item" } else { "items" };
+            self.add_info_message(
+                format!("Dropped {count} {noun} from the conversation."),
+                None,
+            );
+        }
+        if !ev.not_found.is_empty() {
+            self.on_warning(format!("Nothing to drop for {}.", ev.not_found.join(", ")));
+        }
+        self.request_redraw();
+    }
+
+    fn on_read_only_mode_changed(&mut self, ev: ReadOnlyModeChangedEvent) {
+        self.config.read_only_mode = ev.enabled;
+        let hint = ev
+            .enabled
+            .then(|| "Run /ask again to let Codex make changes.".to_string());
+        self.add_info_message(ev.message, hint);
+    }
+
+    /// Lists the largest items from the latest context pressure warning so
+    /// one can be dropped from the conversation.
+    fn open_trim_popup(&mut self) {
+        let Some(pressure) = self
+            .context_pressure
+            .as_ref()
+            .filter(|pressure| !pressure.largest_items.is_empty())
+        else {
+            self.add_info_message(
+                "Nothing to trim yet.".to_string(),
+                Some("/trim lists the largest items once the context window starts filling up; /compact summarizes the conversation instead.".to_string()),
+            );
+            return;
+        };
+
+        let items = pressure
+            .largest_items
+            .iter()
+            .map(|item| {
+                let kind = match item.kind {
+                    ContextItemKind::UserMessage => "your message",
+                    ContextItemKind::AgentMessage => "agent message",
+                    ContextItemKind::Reasoning => "reasoning",
+                    ContextItemKind::ToolCall => "tool call and output",
+                    ContextItemKind::Other => "item",
+                };
+                let name = if item.preview.is_empty() {
+                    item.id.clone()
+                } else {
+                    item.preview.clone()
+                };
+                let ids = vec![item.id.clone()];
+                let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
+                    tx.send(AppEvent::CodexOp(Op::DropContextItems { ids: ids.clone() }));
+                })];
+                SelectionItem {
+                    name,
+                    description: Some(format!(
+                        "{kind}, ~{} tokens",
+                        format_tokens_compact(item.tokens)
+                    )),
+                    actions,
+                    dismiss_on_select: true,
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        self.bottom_pane.show_selection_view(SelectionViewParams {
+            title: Some("Trim Conversation".to_string()),
+            subtitle: Some(
+                "Drop an item to free up context. The model won't see it again.".to_string(),
+            ),
+            footer_hint: Some(standard_popup_hint_line()),
+            items,
+            ..Default::default()
+        });
+    }
+
     fn on_entered_review_mode(&mut self, review: ReviewRequest) {
         // Enter review mode and emit a concise banner
         if self.pre_review_token_info.is_none() {
@@ -2223,6 +2756,11 @@ impl ChatWidget {
             self.plan_type,
             Local::now(),
             self.model_family.get_model_slug(),
+            self.pinned_context
+                .as_ref()
+                .filter(|pinned| !pinned.entries.is_empty())
+                .map(|pinned| pinned.total_tokens),
+            self.spend,
         ));
     }
 
@@ -3219,6 +3757,45 @@ impl ChatWidget {
         self.request_redraw();
     }
 
+    /// `/think [harder|hard|<effort>]`: sets the reasoning effort of the next
+    /// turn only. `harder` (the default) goes one level above the current
+    /// effort.
+    fn think_next_turn(&mut self, args: &str) {
+        let current = self
+            .config
+            .model_reasoning_effort
+            .or(self.model_family.default_reasoning_effort)
+            .unwrap_or_default();
+        let Some(effort) = think_effort(args, current) else {
+            self.add_error_message(format!(
+                "Unknown effort `{}`. Use /think, /think harder, or /think none|minimal|low|medium|high|xhigh.",
+                args.trim()
+            ));
+            return;
+        };
+        self.submit_op(Op::OverrideNextTurnReasoning { effort });
+        self.add_info_message(
+            format!("The next turn reasons with {effort} effort."),
+            Some("Later turns go back to the effort set with /model.".to_string()),
+        );
+    }
+
+    /// `/ask` toggles read-only mode; `/ask on` and `/ask off` set it.
+    fn set_read_only_mode(&mut self, args: &str) {
+        let enabled = match args.trim() {
+            "" => !self.config.read_only_mode,
+            "on" => true,
+            "off" => false,
+            other => {
+                self.add_error_message(format!(
+                    "Unknown argument `{other}`. Use /ask, /ask on, or /ask off."
+                ));
+                return;
+            }
+        };
+        self.submit_op(Op::SetReadOnlyMode { enabled });
+    }
+
     pub(crate) fn add_error_message(&mut self, message: String) {
         self.add_to_history(history_cell::new_error_event(message));
         self.request_redraw();
@@ -3249,7 +3826,9 @@ impl ChatWidget {
             return;
         }
 
-        self.submit_op(Op::Shutdown);
+        // The user is waiting to get their terminal back, so do not let
+        // in-flight tool calls hold up the exit.
+        self.submit_op(Op::Quit);
     }
 
     pub(crate) fn composer_is_empty(&self) -> bool {
@@ -3300,9 +3879,11 @@ impl ChatWidget {
 
     fn on_list_custom_prompts(&mut self, ev: ListCustomPromptsResponseEvent) {
         let len = ev.custom_prompts.len();
-        debug!("received {len} custom prompts");
+        let commands_len = ev.custom_commands.len();
+        debug!("received {len} custom prompts and {commands_len} custom commands");
         // Forward to bottom pane so the slash popup can show them now.
         self.bottom_pane.set_custom_prompts(ev.custom_prompts);
+        self.bottom_pane.set_custom_commands(ev.custom_commands);
     }
 
     fn on_list_skills(&mut self, ev: ListSkillsResponseEvent) {
@@ -3607,6 +4188,31 @@ const EXAMPLE_PROMPTS: [&str; 6] = [
     "Improve documentation in @filename",
 ];
 
+/// Effort for `/think <args>` given the `current` one; `None` if `args`
+/// names no effort.
+fn think_effort(args: &str, current: ReasoningEffortConfig) -> Option<ReasoningEffortConfig> {
+    let effort = match args.trim().to_ascii_lowercase().as_str() {
+        "" | "harder" => match current {
+            ReasoningEffortConfig::None
+            | ReasoningEffortConfig::Minimal
+            | ReasoningEffortConfig::Low => ReasoningEffortConfig::Medium,
+            ReasoningEffortConfig::Medium => ReasoningEffortConfig::High,
+            ReasoningEffortConfig::High | ReasoningEffortConfig::XHigh => {
+                ReasoningEffortConfig::XHigh
+            }
+        },
+        "hard" => ReasoningEffortConfig::High,
+        "none" => ReasoningEffortConfig::None,
+        "minimal" => ReasoningEffortConfig::Minimal,
+        "low" => ReasoningEffortConfig::Low,
+        "medium" => ReasoningEffortConfig::Medium,
+        "high" => ReasoningEffortConfig::High,
+        "xhigh" => ReasoningEffortConfig::XHigh,
+        _ => return None,
+    };
+    Some(effort)
+}
+
 // Extract the first bold (Markdown) element in the form **...** from `s`.
 // Returns the inner text if found; otherwise `None`.
 fn extract_first_bold(s: &str) -> Option<String> {
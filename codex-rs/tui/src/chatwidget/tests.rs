@@ -34,9 +34,11 @@ use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
 use codex_core::protocol::RateLimitWindow;
+use codex_core::protocol::ReadOnlyModeChangedEvent;
 use codex_core::protocol::ReviewRequest;
 use codex_core::protocol::ReviewTarget;
 use codex_core::protocol::StreamErrorEvent;
+use codex_core::protocol::SubAgentEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TaskStartedEvent;
 use codex_core::protocol::TerminalInteractionEvent;
@@ -56,6 +58,7 @@ use codex_protocol::plan_tool::PlanItemArg;
 use codex_protocol::plan_tool::StepStatus;
 use codex_protocol::plan_tool::UpdatePlanArgs;
 use codex_protocol::protocol::CodexErrorInfo;
+use codex_protocol::user_input::UserInput;
 use codex_utils_absolute_path::AbsolutePathBuf;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
@@ -206,6 +209,8 @@ async fn review_restores_context_window_indicator() {
         msg: EventMsg::TokenCount(TokenCountEvent {
             info: Some(make_token_info(pre_review_tokens, context_window)),
             rate_limits: None,
+            pinned_context_tokens: None,
+            spend: None,
         }),
     });
     assert_eq!(chat.bottom_pane.context_window_percent(), Some(30));
@@ -225,6 +230,8 @@ async fn review_restores_context_window_indicator() {
         msg: EventMsg::TokenCount(TokenCountEvent {
             info: Some(make_token_info(review_tokens, context_window)),
             rate_limits: None,
+            pinned_context_tokens: None,
+            spend: None,
         }),
     });
     assert_eq!(chat.bottom_pane.context_window_percent(), Some(97));
@@ -254,6 +261,8 @@ async fn token_count_none_resets_context_indicator() {
         msg: EventMsg::TokenCount(TokenCountEvent {
             info: Some(make_token_info(pre_compact_tokens, context_window)),
             rate_limits: None,
+            pinned_context_tokens: None,
+            spend: None,
         }),
     });
     assert_eq!(chat.bottom_pane.context_window_percent(), Some(30));
@@ -263,6 +272,8 @@ async fn token_count_none_resets_context_indicator() {
         msg: EventMsg::TokenCount(TokenCountEvent {
             info: None,
             rate_limits: None,
+            pinned_context_tokens: None,
+            spend: None,
         }),
     });
     assert_eq!(chat.bottom_pane.context_window_percent(), None);
@@ -293,6 +304,8 @@ async fn context_indicator_shows_used_tokens_when_window_unknown() {
         msg: EventMsg::TokenCount(TokenCountEvent {
             info: Some(token_info),
             rate_limits: None,
+            pinned_context_tokens: None,
+            spend: None,
         }),
     });
 
@@ -378,6 +391,9 @@ async fn make_chatwidget_manual(
         session_header: SessionHeader::new(resolved_model.clone()),
         initial_user_message: None,
         token_info: None,
+        pinned_context: None,
+        context_pressure: None,
+        spend: None,
         rate_limit_snapshot: None,
         plan_type: None,
         rate_limit_warnings: RateLimitWarningState::default(),
@@ -385,6 +401,7 @@ async fn make_chatwidget_manual(
         rate_limit_poller: None,
         stream_controller: None,
         running_commands: HashMap::new(),
+        sub_agents: Vec::new(),
         suppressed_exec_calls: HashSet::new(),
         last_unified_wait: None,
         task_complete_pending: false,
@@ -401,12 +418,14 @@ async fn make_chatwidget_manual(
         queued_user_messages: VecDeque::new(),
         suppress_session_configured_redraw: false,
         pending_notification: None,
+        turn_started_at: None,
         is_review_mode: false,
         pre_review_token_info: None,
         needs_final_message_separator: false,
         last_rendered_width: std::cell::Cell::new(None),
         feedback: codex_feedback::CodexFeedback::new(),
         current_rollout_path: None,
+        voice_input: VoiceInput::Idle,
     };
     (widget, rx, op_rx)
 }
@@ -723,6 +742,7 @@ async fn exec_approval_emits_proposed_command_and_decision_history() {
         ),
         proposed_execpolicy_amendment: None,
         parsed_cmd: vec![],
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-short".into(),
@@ -767,6 +787,7 @@ async fn exec_approval_decision_truncates_multiline_and_long_commands() {
         ),
         proposed_execpolicy_amendment: None,
         parsed_cmd: vec![],
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-multi".into(),
@@ -817,6 +838,7 @@ async fn exec_approval_decision_truncates_multiline_and_long_commands() {
         reason: None,
         proposed_execpolicy_amendment: None,
         parsed_cmd: vec![],
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-long".into(),
@@ -1015,6 +1037,80 @@ async fn alt_up_edits_most_recent_queued_message() {
     );
 }
 
+#[tokio::test]
+async fn alt_down_discards_most_recent_queued_message() {
+    let (mut chat, _rx, _op_rx) = make_chatwidget_manual(None).await;
+    chat.bottom_pane.set_task_running(true);
+    chat.bottom_pane.set_composer_text("draft".to_string());
+
+    chat.queued_user_messages
+        .push_back(UserMessage::from("first queued".to_string()));
+    chat.queued_user_messages
+        .push_back(UserMessage::from("second queued".to_string()));
+    chat.refresh_queued_user_messages();
+
+    chat.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::ALT));
+
+    // The newest message is gone and the composer draft is untouched.
+    assert_eq!(chat.queued_user_messages.len(), 1);
+    assert_eq!(
+        chat.queued_user_messages.front().unwrap().text,
+        "first queued"
+    );
+    assert_eq!(chat.bottom_pane.composer_text(), "draft".to_string());
+}
+
+#[tokio::test]
+async fn short_turns_skip_completion_notification() {
+    let (mut chat, _rx, _op_rx) = make_chatwidget_manual(None).await;
+    chat.config.tui_notification_min_turn_secs = 60;
+
+    let run_turn = |chat: &mut ChatWidget| {
+        chat.handle_codex_event(Event {
+            id: "turn-1".into(),
+            msg: EventMsg::TaskStarted(TaskStartedEvent {
+                model_context_window: None,
+            }),
+        });
+        chat.handle_codex_event(Event {
+            id: "turn-1".into(),
+            msg: EventMsg::TaskComplete(TaskCompleteEvent {
+                last_agent_message: Some("Done".to_string()),
+            }),
+        });
+    };
+
+    run_turn(&mut chat);
+    assert!(chat.pending_notification.is_none());
+
+    chat.config.tui_notification_min_turn_secs = 0;
+    run_turn(&mut chat);
+    assert!(matches!(
+        chat.pending_notification,
+        Some(Notification::AgentTurnComplete { .. })
+    ));
+}
+
+#[tokio::test]
+async fn voice_input_requires_config_and_fills_composer() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+
+    // Without [tui.voice] the shortcut only explains how to enable it.
+    chat.handle_key_event(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+    assert!(matches!(chat.voice_input, VoiceInput::Idle));
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1);
+    assert!(lines_to_single_string(&cells[0]).contains("Voice input is off."));
+
+    chat.voice_input = VoiceInput::Transcribing;
+    chat.on_voice_transcribed(Ok("fix the parser".to_string()));
+    assert!(matches!(chat.voice_input, VoiceInput::Idle));
+    assert_eq!(
+        chat.bottom_pane.composer_text(),
+        "fix the parser".to_string()
+    );
+}
+
 /// Pressing Up to recall the most recent history entry and immediately queuing
 /// it while a task is running should always enqueue the same text, even when it
 /// is queued repeatedly.
@@ -1074,6 +1170,141 @@ async fn streaming_final_answer_keeps_task_running_state() {
     assert!(chat.bottom_pane.ctrl_c_quit_hint_visible());
 }
 
+#[tokio::test]
+async fn esc_with_typed_correction_steers_running_task() {
+    let (mut chat, _rx, mut op_rx) = make_chatwidget_manual(None).await;
+
+    chat.on_task_started();
+    chat.on_agent_message_delta("Rewriting the parser\n".to_string());
+    chat.on_commit_tick();
+
+    chat.bottom_pane
+        .set_composer_text("use nom instead".to_string());
+    chat.handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+
+    match op_rx.try_recv() {
+        Ok(Op::Steer { items }) => assert_eq!(
+            items,
+            vec![UserInput::Text {
+                text: "use nom instead".to_string(),
+            }]
+        ),
+        other => panic!("expected Op::Steer, got {other:?}"),
+    }
+    assert!(chat.bottom_pane.is_task_running());
+    assert!(chat.bottom_pane.composer_text().is_empty());
+    assert!(chat.queued_user_messages.is_empty());
+}
+
+#[tokio::test]
+async fn think_raises_effort_for_the_next_turn_only() {
+    let (mut chat, _rx, mut op_rx) = make_chatwidget_manual(None).await;
+    chat.config.model_reasoning_effort = Some(ReasoningEffortConfig::Medium);
+
+    chat.dispatch_command(SlashCommand::Think);
+    match op_rx.try_recv() {
+        Ok(Op::OverrideNextTurnReasoning { effort }) => {
+            assert_eq!(effort, ReasoningEffortConfig::High)
+        }
+        other => panic!("expected Op::OverrideNextTurnReasoning, got {other:?}"),
+    }
+    assert_eq!(
+        chat.config.model_reasoning_effort,
+        Some(ReasoningEffortConfig::Medium)
+    );
+
+    chat.dispatch_command_with_args(SlashCommand::Think, "low".to_string());
+    match op_rx.try_recv() {
+        Ok(Op::OverrideNextTurnReasoning { effort }) => {
+            assert_eq!(effort, ReasoningEffortConfig::Low)
+        }
+        other => panic!("expected Op::OverrideNextTurnReasoning, got {other:?}"),
+    }
+
+    chat.dispatch_command_with_args(SlashCommand::Think, "very".to_string());
+    assert!(op_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn ask_toggles_read_only_mode() {
+    let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual(None).await;
+
+    chat.dispatch_command(SlashCommand::Ask);
+    match op_rx.try_recv() {
+        Ok(Op::SetReadOnlyMode { enabled }) => assert!(enabled),
+        other => panic!("expected Op::SetReadOnlyMode, got {other:?}"),
+    }
+
+    chat.handle_codex_event(Event {
+        id: "sub".into(),
+        msg: EventMsg::ReadOnlyModeChanged(ReadOnlyModeChangedEvent {
+            enabled: true,
+            message: "Read-only mode on".to_string(),
+        }),
+    });
+    assert!(chat.config.read_only_mode);
+    let cells = drain_insert_history(&mut rx);
+    let rendered = lines_to_single_string(&cells.concat());
+    assert!(rendered.contains("Read-only mode on"), "got {rendered:?}");
+
+    chat.dispatch_command(SlashCommand::Ask);
+    match op_rx.try_recv() {
+        Ok(Op::SetReadOnlyMode { enabled }) => assert!(!enabled),
+        other => panic!("expected Op::SetReadOnlyMode, got {other:?}"),
+    }
+
+    chat.dispatch_command_with_args(SlashCommand::Ask, "maybe".to_string());
+    assert!(op_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn trim_offers_the_largest_items_from_the_context_pressure_warning() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+
+    chat.dispatch_command(SlashCommand::Trim);
+    let blob = lines_to_single_string(&drain_insert_history(&mut rx).concat());
+    assert!(blob.contains("Nothing to trim yet."), "{blob}");
+
+    chat.handle_codex_event(Event {
+        id: "pressure".into(),
+        msg: EventMsg::ContextPressure(ContextPressureEvent {
+            threshold_percent: 70,
+            used_tokens: 190_000,
+            context_window: 272_000,
+            breakdown: codex_core::protocol::ContextUsageBreakdown {
+                instructions: 9_000,
+                pinned: 0,
+                tool_outputs: 150_000,
+                history: 31_000,
+            },
+            largest_items: vec![codex_core::protocol::ContextItemSummary {
+                id: "call-1".to_string(),
+                kind: ContextItemKind::ToolCall,
+                tokens: 120_000,
+                preview: "shell".to_string(),
+            }],
+        }),
+    });
+    let blob = lines_to_single_string(&drain_insert_history(&mut rx).concat());
+    assert!(
+        blob.contains("Context window is 70% full") && blob.contains("tool outputs 150K"),
+        "{blob}"
+    );
+
+    chat.dispatch_command(SlashCommand::Trim);
+    chat.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    let ops: Vec<Op> = std::iter::from_fn(|| rx.try_recv().ok())
+        .filter_map(|event| match event {
+            AppEvent::CodexOp(op) => Some(op),
+            _ => None,
+        })
+        .collect();
+    assert_matches!(
+        ops.as_slice(),
+        [Op::DropContextItems { ids }] if ids == &vec!["call-1".to_string()]
+    );
+}
+
 #[tokio::test]
 async fn ctrl_c_shutdown_ignores_caps_lock() {
     let (mut chat, _rx, mut op_rx) = make_chatwidget_manual(None).await;
@@ -1081,8 +1312,8 @@ async fn ctrl_c_shutdown_ignores_caps_lock() {
     chat.handle_key_event(KeyEvent::new(KeyCode::Char('C'), KeyModifiers::CONTROL));
 
     match op_rx.try_recv() {
-        Ok(Op::Shutdown) => {}
-        other => panic!("expected Op::Shutdown, got {other:?}"),
+        Ok(Op::Quit) => {}
+        other => panic!("expected Op::Quit, got {other:?}"),
     }
 }
 
@@ -2402,6 +2633,7 @@ async fn approval_modal_exec_snapshot() -> anyhow::Result<()> {
             "world".into(),
         ])),
         parsed_cmd: vec![],
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-approve".into(),
@@ -2455,6 +2687,7 @@ async fn approval_modal_exec_without_reason_snapshot() -> anyhow::Result<()> {
             "world".into(),
         ])),
         parsed_cmd: vec![],
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-approve-noreason".into(),
@@ -2497,6 +2730,7 @@ async fn approval_modal_patch_snapshot() -> anyhow::Result<()> {
         changes,
         reason: Some("The model wants to apply changes".into()),
         grant_root: Some(PathBuf::from("/tmp")),
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-approve-patch".into(),
@@ -2676,6 +2910,7 @@ async fn status_widget_and_approval_modal_snapshot() {
             "hello world".into(),
         ])),
         parsed_cmd: vec![],
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-approve-exec".into(),
@@ -2779,6 +3014,7 @@ async fn apply_patch_events_emit_history_cells() {
         changes,
         reason: None,
         grant_root: None,
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "s1".into(),
@@ -2878,6 +3114,7 @@ async fn apply_patch_manual_approval_adjusts_header() {
             changes: proposed_changes,
             reason: None,
             grant_root: None,
+            proposed_permissions: Vec::new(),
         }),
     });
     drain_insert_history(&mut rx);
@@ -2927,6 +3164,7 @@ async fn apply_patch_manual_flow_snapshot() {
             changes: proposed_changes,
             reason: Some("Manual review required".into()),
             grant_root: None,
+            proposed_permissions: Vec::new(),
         }),
     });
     let history_before_apply = drain_insert_history(&mut rx);
@@ -2978,6 +3216,7 @@ async fn apply_patch_approval_sends_op_with_submission_id() {
         changes,
         reason: None,
         grant_root: None,
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-123".into(),
@@ -3018,6 +3257,7 @@ async fn apply_patch_full_flow_integration_like() {
             changes,
             reason: None,
             grant_root: None,
+            proposed_permissions: Vec::new(),
         }),
     });
 
@@ -3098,6 +3338,7 @@ async fn apply_patch_untrusted_shows_approval_modal() -> anyhow::Result<()> {
             changes,
             reason: None,
             grant_root: None,
+            proposed_permissions: Vec::new(),
         }),
     });
 
@@ -3149,6 +3390,7 @@ async fn apply_patch_request_shows_diff_summary() -> anyhow::Result<()> {
             changes,
             reason: None,
             grant_root: None,
+            proposed_permissions: Vec::new(),
         }),
     });
 
@@ -3273,6 +3515,57 @@ async fn warning_event_adds_warning_history_cell() {
     );
 }
 
+#[tokio::test]
+async fn sub_agent_events_track_progress_and_add_history_cells() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+    let sub_agent = |msg| Event {
+        id: "sub-1".into(),
+        msg: EventMsg::SubAgent(SubAgentEvent {
+            agent_id: "agent-1".to_string(),
+            msg: Box::new(msg),
+        }),
+    };
+    chat.handle_codex_event(sub_agent(EventMsg::ExecCommandBegin(
+        ExecCommandBeginEvent {
+            call_id: "call-1".to_string(),
+            process_id: None,
+            turn_id: "turn-1".to_string(),
+            command: vec![
+                "bash".to_string(),
+                "-lc".to_string(),
+                "cargo test".to_string(),
+            ],
+            cwd: PathBuf::from("."),
+            parsed_cmd: Vec::new(),
+            source: ExecCommandSource::Agent,
+            interaction_input: None,
+        },
+    )));
+    assert_eq!(
+        chat.sub_agents,
+        vec![("agent-1".to_string(), "running cargo test".to_string())]
+    );
+    chat.handle_codex_event(sub_agent(EventMsg::AgentMessage(AgentMessageEvent {
+        message: "ignored".to_string(),
+    })));
+    chat.handle_codex_event(sub_agent(EventMsg::TaskComplete(TaskCompleteEvent {
+        last_agent_message: Some("done".to_string()),
+    })));
+    assert_eq!(chat.sub_agents, Vec::new());
+
+    let rendered = drain_insert_history(&mut rx)
+        .iter()
+        .map(|lines| lines_to_single_string(lines))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rendered,
+        vec![
+            "• agent-1 ran cargo test\n".to_string(),
+            "\n• agent-1 finished\n".to_string(),
+        ]
+    );
+}
+
 #[tokio::test]
 async fn stream_recovery_restores_previous_status_header() {
     let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
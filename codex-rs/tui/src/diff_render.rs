@@ -9,11 +9,14 @@ use ratatui::text::Line as RtLine;
 use ratatui::text::Span as RtSpan;
 use ratatui::widgets::Paragraph;
 use std::collections::HashMap;
+use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
 
 use crate::exec_command::relativize_to_home;
 use crate::render::Insets;
+use crate::render::highlight::CodeLanguage;
+use crate::render::highlight::highlight_code_line;
 use crate::render::line_utils::prefix_lines;
 use crate::render::renderable::ColumnRenderable;
 use crate::render::renderable::InsetRenderable;
@@ -21,6 +24,10 @@ use crate::render::renderable::Renderable;
 use codex_core::git_info::get_git_repo_root;
 use codex_core::protocol::FileChange;
 
+/// Diffs switch to two columns once this many columns are available.
+const SIDE_BY_SIDE_MIN_WIDTH: usize = 160;
+const SIDE_BY_SIDE_SEPARATOR: &str = " │ ";
+
 // Internal representation for diff line rendering
 enum DiffLineType {
     Insert,
@@ -39,16 +46,21 @@ impl DiffSummary {
     }
 }
 
-impl Renderable for FileChange {
+impl Renderable for Row {
     fn render(&self, area: Rect, buf: &mut Buffer) {
         let mut lines = vec![];
-        render_change(self, &mut lines, area.width as usize);
+        render_change(
+            &self.change,
+            self.language(),
+            &mut lines,
+            area.width as usize,
+        );
         Paragraph::new(lines).render(area, buf);
     }
 
     fn desired_height(&self, width: u16) -> u16 {
         let mut lines = vec![];
-        render_change(self, &mut lines, width as usize);
+        render_change(&self.change, self.language(), &mut lines, width as usize);
         lines.len() as u16
     }
 }
@@ -67,7 +79,7 @@ impl From<DiffSummary> for Box<dyn Renderable> {
             rows.push(Box::new(path));
             rows.push(Box::new(RtLine::from("")));
             rows.push(Box::new(InsetRenderable::new(
-                Box::new(row.change) as Box<dyn Renderable>,
+                Box::new(row) as Box<dyn Renderable>,
                 Insets::tlbr(0, 2, 0, 0),
             )));
         }
@@ -88,7 +100,6 @@ pub(crate) fn create_diff_summary(
 // Shared row for per-file presentation
 #[derive(Clone)]
 struct Row {
-    #[allow(dead_code)]
     path: PathBuf,
     move_path: Option<PathBuf>,
     added: usize,
@@ -96,6 +107,12 @@ struct Row {
     change: FileChange,
 }
 
+impl Row {
+    fn language(&self) -> Option<CodeLanguage> {
+        CodeLanguage::from_path(self.move_path.as_ref().unwrap_or(&self.path))
+    }
+}
+
 fn collect_rows(changes: &HashMap<PathBuf, FileChange>) -> Vec<Row> {
     let mut rows: Vec<Row> = Vec::new();
     for (path, change) in changes.iter() {
@@ -186,36 +203,39 @@ fn render_changes_block(rows: Vec<Row>, wrap_cols: usize, cwd: &Path) -> Vec<RtL
         }
 
         let mut lines = vec![];
-        render_change(&r.change, &mut lines, wrap_cols - 4);
+        render_change(&r.change, r.language(), &mut lines, wrap_cols - 4);
         out.extend(prefix_lines(lines, "    ".into(), "    ".into()));
     }
 
     out
 }
 
-fn render_change(change: &FileChange, out: &mut Vec<RtLine<'static>>, width: usize) {
+fn render_change(
+    change: &FileChange,
+    language: Option<CodeLanguage>,
+    out: &mut Vec<RtLine<'static>>,
+    width: usize,
+) {
     match change {
         FileChange::Add { content } => {
             let line_number_width = line_number_width(content.lines().count());
             for (i, raw) in content.lines().enumerate() {
-                out.extend(push_wrapped_diff_line(
-                    i + 1,
-                    DiffLineType::Insert,
-                    raw,
+                out.extend(push_styled_diff_line(
+                    &DiffLine::new(i + 1, DiffLineType::Insert, raw),
                     width,
                     line_number_width,
+                    language,
                 ));
             }
         }
         FileChange::Delete { content } => {
             let line_number_width = line_number_width(content.lines().count());
             for (i, raw) in content.lines().enumerate() {
-                out.extend(push_wrapped_diff_line(
-                    i + 1,
-                    DiffLineType::Delete,
-                    raw,
+                out.extend(push_styled_diff_line(
+                    &DiffLine::new(i + 1, DiffLineType::Delete, raw),
                     width,
                     line_number_width,
+                    language,
                 ));
             }
         }
@@ -244,6 +264,7 @@ fn render_change(change: &FileChange, out: &mut Vec<RtLine<'static>>, width: usi
                     }
                 }
                 let line_number_width = line_number_width(max_line_number);
+                let side_by_side = width >= SIDE_BY_SIDE_MIN_WIDTH;
                 let mut is_first_hunk = true;
                 for h in patch.hunks() {
                     if !is_first_hunk {
@@ -253,43 +274,33 @@ fn render_change(change: &FileChange, out: &mut Vec<RtLine<'static>>, width: usi
                     }
                     is_first_hunk = false;
 
-                    let mut old_ln = h.old_range().start();
-                    let mut new_ln = h.new_range().start();
-                    for l in h.lines() {
-                        match l {
-                            diffy::Line::Insert(text) => {
-                                let s = text.trim_end_matches('\n');
-                                out.extend(push_wrapped_diff_line(
-                                    new_ln,
-                                    DiffLineType::Insert,
-                                    s,
-                                    width,
-                                    line_number_width,
-                                ));
-                                new_ln += 1;
-                            }
-                            diffy::Line::Delete(text) => {
-                                let s = text.trim_end_matches('\n');
-                                out.extend(push_wrapped_diff_line(
-                                    old_ln,
-                                    DiffLineType::Delete,
-                                    s,
-                                    width,
-                                    line_number_width,
-                                ));
-                                old_ln += 1;
-                            }
-                            diffy::Line::Context(text) => {
-                                let s = text.trim_end_matches('\n');
-                                out.extend(push_wrapped_diff_line(
-                                    new_ln,
-                                    DiffLineType::Context,
-                                    s,
-                                    width,
-                                    line_number_width,
-                                ));
-                                old_ln += 1;
-                                new_ln += 1;
+                    for block in hunk_blocks(h) {
+                        if side_by_side {
+                            push_side_by_side_block(
+                                &block,
+                                out,
+                                width,
+                                line_number_width,
+                                language,
+                            );
+                            continue;
+                        }
+                        match block {
+                            HunkBlock::Context { new, .. } => out.extend(push_styled_diff_line(
+                                &new,
+                                width,
+                                line_number_width,
+                                language,
+                            )),
+                            HunkBlock::Change { deleted, inserted } => {
+                                for line in deleted.iter().chain(&inserted) {
+                                    out.extend(push_styled_diff_line(
+                                        line,
+                                        width,
+                                        line_number_width,
+                                        language,
+                                    ));
+                                }
                             }
                         }
                     }
@@ -299,6 +310,157 @@ fn render_change(change: &FileChange, out: &mut Vec<RtLine<'static>>, width: usi
     }
 }
 
+/// A diff line ready to render, with the byte range that differs from its
+/// counterpart on the other side of a one-to-one change.
+struct DiffLine<'a> {
+    line_number: usize,
+    kind: DiffLineType,
+    text: &'a str,
+    emphasis: Option<Range<usize>>,
+}
+
+impl<'a> DiffLine<'a> {
+    fn new(line_number: usize, kind: DiffLineType, text: &'a str) -> Self {
+        Self {
+            line_number,
+            kind,
+            text,
+            emphasis: None,
+        }
+    }
+}
+
+/// Consecutive hunk lines that render together: an unchanged line, or a run
+/// of deletions followed by the insertions that replace them.
+enum HunkBlock<'a> {
+    Context {
+        old: DiffLine<'a>,
+        new: DiffLine<'a>,
+    },
+    Change {
+        deleted: Vec<DiffLine<'a>>,
+        inserted: Vec<DiffLine<'a>>,
+    },
+}
+
+fn hunk_blocks<'a>(hunk: &Hunk<'a, str>) -> Vec<HunkBlock<'a>> {
+    fn flush<'a>(
+        blocks: &mut Vec<HunkBlock<'a>>,
+        deleted: &mut Vec<DiffLine<'a>>,
+        inserted: &mut Vec<DiffLine<'a>>,
+    ) {
+        if deleted.is_empty() && inserted.is_empty() {
+            return;
+        }
+        // Only one-to-one replacements get intra-line markers; pairing lines
+        // of uneven runs would mostly highlight unrelated text.
+        if deleted.len() == inserted.len() {
+            for (old, new) in deleted.iter_mut().zip(inserted.iter_mut()) {
+                if let Some((old_range, new_range)) = changed_ranges(old.text, new.text) {
+                    old.emphasis = Some(old_range);
+                    new.emphasis = Some(new_range);
+                }
+            }
+        }
+        blocks.push(HunkBlock::Change {
+            deleted: std::mem::take(deleted),
+            inserted: std::mem::take(inserted),
+        });
+    }
+
+    let mut blocks = Vec::new();
+    let mut deleted = Vec::new();
+    let mut inserted = Vec::new();
+    let mut old_ln = hunk.old_range().start();
+    let mut new_ln = hunk.new_range().start();
+    for l in hunk.lines() {
+        match l {
+            diffy::Line::Insert(text) => {
+                let text = text.trim_end_matches('\n');
+                inserted.push(DiffLine::new(new_ln, DiffLineType::Insert, text));
+                new_ln += 1;
+            }
+            diffy::Line::Delete(text) => {
+                if !inserted.is_empty() {
+                    flush(&mut blocks, &mut deleted, &mut inserted);
+                }
+                let text = text.trim_end_matches('\n');
+                deleted.push(DiffLine::new(old_ln, DiffLineType::Delete, text));
+                old_ln += 1;
+            }
+            diffy::Line::Context(text) => {
+                flush(&mut blocks, &mut deleted, &mut inserted);
+                let text = text.trim_end_matches('\n');
+                blocks.push(HunkBlock::Context {
+                    old: DiffLine::new(old_ln, DiffLineType::Context, text),
+                    new: DiffLine::new(new_ln, DiffLineType::Context, text),
+                });
+                old_ln += 1;
+                new_ln += 1;
+            }
+        }
+    }
+    flush(&mut blocks, &mut deleted, &mut inserted);
+    blocks
+}
+
+/// Byte ranges of `old` and `new` left after trimming their common prefix and
+/// suffix, or `None` when the lines share neither.
+fn changed_ranges(old: &str, new: &str) -> Option<(Range<usize>, Range<usize>)> {
+    let prefix: usize = old
+        .chars()
+        .zip(new.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum();
+    let suffix: usize = old[prefix..]
+        .chars()
+        .rev()
+        .zip(new[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum();
+    if prefix == 0 && suffix == 0 {
+        return None;
+    }
+    Some((prefix..old.len() - suffix, prefix..new.len() - suffix))
+}
+
+/// Renders one hunk block as two columns, old on the left and new on the
+/// right, padding whichever side wraps to fewer rows.
+fn push_side_by_side_block(
+    block: &HunkBlock<'_>,
+    out: &mut Vec<RtLine<'static>>,
+    width: usize,
+    line_number_width: usize,
+    language: Option<CodeLanguage>,
+) {
+    let column_width = width.saturating_sub(SIDE_BY_SIDE_SEPARATOR.chars().count()) / 2;
+    let render = |line: Option<&DiffLine<'_>>| {
+        line.map(|line| push_styled_diff_line(line, column_width, line_number_width, language))
+            .unwrap_or_default()
+    };
+    let rows: Vec<(Vec<RtLine<'static>>, Vec<RtLine<'static>>)> = match block {
+        HunkBlock::Context { old, new } => vec![(render(Some(old)), render(Some(new)))],
+        HunkBlock::Change { deleted, inserted } => (0..deleted.len().max(inserted.len()))
+            .map(|i| (render(deleted.get(i)), render(inserted.get(i))))
+            .collect(),
+    };
+    for (left, right) in rows {
+        let height = left.len().max(right.len());
+        let mut left = left.into_iter();
+        let mut right = right.into_iter();
+        for _ in 0..height {
+            let mut line = left.next().unwrap_or_default();
+            let padding = column_width.saturating_sub(line.width());
+            line.push_span(" ".repeat(padding));
+            line.push_span(SIDE_BY_SIDE_SEPARATOR.dim());
+            line.extend(right.next().unwrap_or_default().spans);
+            out.push(line);
+        }
+    }
+}
+
 pub(crate) fn display_path_for(path: &Path, cwd: &Path) -> String {
     let path_in_same_repo = match (get_git_repo_root(cwd), get_git_repo_root(path)) {
         (Some(cwd_repo), Some(path_repo)) => cwd_repo == path_repo,
@@ -338,58 +500,125 @@ fn push_wrapped_diff_line(
     width: usize,
     line_number_width: usize,
 ) -> Vec<RtLine<'static>> {
-    let ln_str = line_number.to_string();
-    let mut remaining_text: &str = text;
+    push_styled_diff_line(
+        &DiffLine::new(line_number, kind, text),
+        width,
+        line_number_width,
+        None,
+    )
+}
+
+fn push_styled_diff_line(
+    line: &DiffLine<'_>,
+    width: usize,
+    line_number_width: usize,
+    language: Option<CodeLanguage>,
+) -> Vec<RtLine<'static>> {
+    let ln_str = line.line_number.to_string();
 
     // Reserve a fixed number of spaces (equal to the widest line number plus a
     // trailing spacer) so the sign column stays aligned across the diff block.
     let gutter_width = line_number_width.max(1);
     let prefix_cols = gutter_width + 1;
 
-    let mut first = true;
-    let (sign_char, line_style) = match kind {
+    let (sign_char, line_style) = match line.kind {
         DiffLineType::Insert => ('+', style_add()),
         DiffLineType::Delete => ('-', style_del()),
         DiffLineType::Context => (' ', style_context()),
     };
-    let mut lines: Vec<RtLine<'static>> = Vec::new();
-
-    loop {
-        // Fit the content for the current terminal row:
-        // compute how many columns are available after the prefix, then split
-        // at a UTF-8 character boundary so this row's chunk fits exactly.
-        let available_content_cols = width.saturating_sub(prefix_cols + 1).max(1);
-        let split_at_byte_index = remaining_text
-            .char_indices()
-            .nth(available_content_cols)
-            .map(|(i, _)| i)
-            .unwrap_or_else(|| remaining_text.len());
-        let (chunk, rest) = remaining_text.split_at(split_at_byte_index);
-        remaining_text = rest;
-
-        if first {
-            // Build gutter (right-aligned line number plus spacer) as a dimmed span
-            let gutter = format!("{ln_str:>gutter_width$} ");
-            // Content with a sign ('+'/'-'/' ') styled per diff kind
-            let content = format!("{sign_char}{chunk}");
-            lines.push(RtLine::from(vec![
-                RtSpan::styled(gutter, style_gutter()),
-                RtSpan::styled(content, line_style),
-            ]));
-            first = false;
-        } else {
-            // Continuation lines keep a space for the sign column so content aligns
-            let gutter = format!("{:gutter_width$}  ", "");
-            lines.push(RtLine::from(vec![
-                RtSpan::styled(gutter, style_gutter()),
-                RtSpan::styled(chunk.to_string(), line_style),
-            ]));
+    let tokens = language
+        .map(|language| highlight_code_line(line.text, language))
+        .unwrap_or_default();
+    let segments = styled_segments(line.text, line_style, &tokens, line.emphasis.clone());
+
+    // Fit the content for each terminal row: compute how many columns are
+    // available after the prefix, then split at UTF-8 character boundaries so
+    // every row's chunk fits exactly.
+    let available_content_cols = width.saturating_sub(prefix_cols + 1).max(1);
+    let mut rows: Vec<Vec<RtSpan<'static>>> = vec![Vec::new()];
+    let mut used = 0;
+    for (range, style) in segments {
+        let mut start = range.start;
+        for (offset, _) in line.text[range.clone()].char_indices() {
+            let at = range.start + offset;
+            if used == available_content_cols {
+                if at > start {
+                    push_chunk(&mut rows, &line.text[start..at], style);
+                }
+                rows.push(Vec::new());
+                used = 0;
+                start = at;
+            }
+            used += 1;
         }
-        if remaining_text.is_empty() {
-            break;
+        if range.end > start {
+            push_chunk(&mut rows, &line.text[start..range.end], style);
         }
     }
-    lines
+
+    rows.into_iter()
+        .enumerate()
+        .map(|(i, chunks)| {
+            let mut spans = if i == 0 {
+                // Right-aligned line number plus spacer, then the sign styled
+                // per diff kind ('+'/'-'/' ').
+                vec![
+                    RtSpan::styled(format!("{ln_str:>gutter_width$} "), style_gutter()),
+                    RtSpan::styled(sign_char.to_string(), line_style),
+                ]
+            } else {
+                // Continuation lines keep a space for the sign column so content aligns
+                vec![RtSpan::styled(
+                    format!("{:gutter_width$}  ", ""),
+                    style_gutter(),
+                )]
+            };
+            spans.extend(chunks);
+            RtLine::from(spans)
+        })
+        .collect()
+}
+
+fn push_chunk(rows: &mut [Vec<RtSpan<'static>>], text: &str, style: Style) {
+    if let Some(row) = rows.last_mut() {
+        row.push(RtSpan::styled(text.to_string(), style));
+    }
+}
+
+/// Splits `text` into byte ranges that share one style, layering syntax
+/// tokens and the intra-line emphasis over the diff line's base style.
+fn styled_segments(
+    text: &str,
+    base: Style,
+    tokens: &[(Range<usize>, Style)],
+    emphasis: Option<Range<usize>>,
+) -> Vec<(Range<usize>, Style)> {
+    let mut bounds = vec![0, text.len()];
+    for (range, _) in tokens {
+        bounds.extend([range.start, range.end]);
+    }
+    if let Some(range) = &emphasis {
+        bounds.extend([range.start, range.end]);
+    }
+    bounds.sort_unstable();
+    bounds.dedup();
+    bounds
+        .windows(2)
+        .map(|pair| {
+            let (start, end) = (pair[0], pair[1]);
+            let mut style = base;
+            if let Some((_, token)) = tokens.iter().find(|(range, _)| range.contains(&start)) {
+                style = style.patch(*token);
+            }
+            if emphasis
+                .as_ref()
+                .is_some_and(|range| range.contains(&start))
+            {
+                style = style.patch(style_emphasis());
+            }
+            (start..end, style)
+        })
+        .collect()
 }
 
 fn line_number_width(max_line_number: usize) -> usize {
@@ -416,6 +645,10 @@ fn style_del() -> Style {
     Style::default().fg(Color::Red)
 }
 
+fn style_emphasis() -> Style {
+    Style::default().add_modifier(Modifier::REVERSED)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -670,4 +903,66 @@ mod tests {
 
         snapshot_lines("apply_update_block_relativizes_path", lines, 80, 10);
     }
+
+    #[test]
+    fn update_marks_changed_text_and_highlights_syntax() {
+        let patch = diffy::create_patch("let value = 1;\n", "let value = 2;\n").to_string();
+        let change = FileChange::Update {
+            unified_diff: patch,
+            move_path: None,
+        };
+        let mut lines = Vec::new();
+        render_change(&change, Some(CodeLanguage::Rust), &mut lines, 80);
+
+        let inserted = lines
+            .iter()
+            .find(|line| line.spans.iter().any(|span| span.content == "+"))
+            .expect("inserted line");
+        let style_of = |text: &str| {
+            inserted
+                .spans
+                .iter()
+                .find(|span| span.content == text)
+                .map(|span| span.style)
+        };
+        assert_eq!(
+            style_of("let"),
+            Some(style_add().add_modifier(Modifier::BOLD))
+        );
+        assert_eq!(style_of(" value = "), Some(style_add()));
+        assert_eq!(
+            style_of("2"),
+            Some(style_add().add_modifier(Modifier::REVERSED))
+        );
+    }
+
+    #[test]
+    fn wide_update_renders_side_by_side() {
+        let patch = diffy::create_patch("a\nb\nc\n", "a\nB\nc\n").to_string();
+        let change = FileChange::Update {
+            unified_diff: patch,
+            move_path: None,
+        };
+        let mut lines = Vec::new();
+        render_change(&change, None, &mut lines, SIDE_BY_SIDE_MIN_WIDTH);
+
+        let rows: Vec<String> = lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect();
+        let column = (SIDE_BY_SIDE_MIN_WIDTH - 3) / 2;
+        assert_eq!(
+            rows,
+            vec![
+                format!("{:<column$} │ 1  a", "1  a"),
+                format!("{:<column$} │ 2 +B", "2 -b"),
+                format!("{:<column$} │ 3  c", "3  c"),
+            ]
+        );
+    }
 }
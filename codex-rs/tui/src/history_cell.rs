@@ -7,6 +7,7 @@ use crate::exec_cell::output_lines;
 use crate::exec_cell::spinner;
 use crate::exec_command::relativize_to_home;
 use crate::exec_command::strip_bash_lc_and_escape;
+use crate::key_hint;
 use crate::live_wrap::take_prefix_by_width;
 use crate::markdown::append_markdown;
 use crate::render::line_utils::line_to_static;
@@ -14,6 +15,7 @@ use crate::render::line_utils::prefix_lines;
 use crate::render::line_utils::push_owned_lines;
 use crate::render::renderable::Renderable;
 use crate::shimmer::shimmer_spans;
+use crate::status::format_tokens_compact;
 use crate::style::user_message_style;
 use crate::text_formatting::format_and_truncate_tool_result;
 use crate::text_formatting::truncate_text;
@@ -31,12 +33,15 @@ use codex_core::config::types::McpServerTransportConfig;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::McpAuthStatus;
 use codex_core::protocol::McpInvocation;
+use codex_core::protocol::PinnedContextItem;
+use codex_core::protocol::PinnedContextUpdatedEvent;
 use codex_core::protocol::SessionConfiguredEvent;
 use codex_protocol::openai_models::ReasoningEffort as ReasoningEffortConfig;
 use codex_protocol::openai_models::ReasoningSummaryFormat;
 use codex_protocol::plan_tool::PlanItemArg;
 use codex_protocol::plan_tool::StepStatus;
 use codex_protocol::plan_tool::UpdatePlanArgs;
+use crossterm::event::KeyCode;
 use image::DynamicImage;
 use image::ImageReader;
 use mcp_types::EmbeddedResourceResource;
@@ -164,20 +169,36 @@ impl HistoryCell for UserHistoryCell {
 
 #[derive(Debug)]
 pub(crate) struct ReasoningSummaryCell {
-    _header: String,
+    header: String,
     content: String,
     transcript_only: bool,
+    /// Show only the header in the main view; the transcript keeps the
+    /// full summary.
+    collapsed: bool,
 }
 
 impl ReasoningSummaryCell {
     pub(crate) fn new(header: String, content: String, transcript_only: bool) -> Self {
         Self {
-            _header: header,
+            header,
             content,
             transcript_only,
+            collapsed: false,
         }
     }
 
+    fn collapsed_lines(&self) -> Vec<Line<'static>> {
+        let title = self.header.trim().trim_matches('*').trim();
+        let title = if title.is_empty() { "Reasoning" } else { title };
+        vec![Line::from(vec![
+            "• ".dim(),
+            title.to_string().dim().italic(),
+            " (".dim(),
+            key_hint::ctrl(KeyCode::Char('t')).into(),
+            " to expand)".dim(),
+        ])]
+    }
+
     fn lines(&self, width: u16) -> Vec<Line<'static>> {
         let mut lines: Vec<Line<'static>> = Vec::new();
         append_markdown(
@@ -211,17 +232,15 @@ impl HistoryCell for ReasoningSummaryCell {
     fn display_lines(&self, width: u16) -> Vec<Line<'static>> {
         if self.transcript_only {
             Vec::new()
+        } else if self.collapsed {
+            self.collapsed_lines()
         } else {
             self.lines(width)
         }
     }
 
     fn desired_height(&self, width: u16) -> u16 {
-        if self.transcript_only {
-            0
-        } else {
-            self.lines(width).len() as u16
-        }
+        self.display_lines(width).len() as u16
     }
 
     fn transcript_lines(&self, width: u16) -> Vec<Line<'static>> {
@@ -617,6 +636,46 @@ pub(crate) fn new_unified_exec_sessions_output(sessions: Vec<String>) -> Composi
     CompositeHistoryCell::new(vec![Box::new(command), Box::new(summary)])
 }
 
+pub(crate) fn new_pinned_context_output(pinned: &PinnedContextUpdatedEvent) -> PlainHistoryCell {
+    let mut lines: Vec<Line<'static>> = vec!["/pin".magenta().into()];
+    if pinned.entries.is_empty() {
+        lines.push(
+            "  • No pinned context. Use /pin <text>, /pin instruction <text>, or /pin file <path>."
+                .italic()
+                .into(),
+        );
+        return PlainHistoryCell::new(lines);
+    }
+    for entry in &pinned.entries {
+        let (kind, summary) = match &entry.item {
+            PinnedContextItem::Message { text } => ("message", truncate_exec_snippet(text)),
+            PinnedContextItem::Instruction { text } => ("instruction", truncate_exec_snippet(text)),
+            PinnedContextItem::File { path } => ("file", path.display().to_string()),
+        };
+        lines.push(
+            vec![
+                "  • ".into(),
+                entry.id.clone().cyan(),
+                " ".into(),
+                kind.dim(),
+                " ".into(),
+                summary.into(),
+                format!(" ({} tokens)", format_tokens_compact(entry.tokens)).dim(),
+            ]
+            .into(),
+        );
+    }
+    lines.push(
+        format!(
+            "  {} tokens pinned; compaction never drops these. Use /unpin <id> to remove one.",
+            format_tokens_compact(pinned.total_tokens)
+        )
+        .dim()
+        .into(),
+    );
+    PlainHistoryCell::new(lines)
+}
+
 fn truncate_exec_snippet(full_cmd: &str) -> String {
     let mut snippet = match full_cmd.split_once('\n') {
         Some((first, _)) => format!("{first} ..."),
@@ -664,6 +723,19 @@ pub fn new_approval_decision_cell(
                 ],
             )
         }
+        ApprovedPrefixForProject { .. } => {
+            let snippet = Span::from(exec_snippet(&command)).dim();
+            (
+                "✔ ".green(),
+                vec![
+                    "You ".into(),
+                    "approved".bold(),
+                    " codex to run ".into(),
+                    snippet,
+                    " and commands like it in this project".bold(),
+                ],
+            )
+        }
         ApprovedForSession => {
             let snippet = Span::from(exec_snippet(&command)).dim();
             (
@@ -677,7 +749,29 @@ pub fn new_approval_decision_cell(
                 ],
             )
         }
-        Denied => {
+        ApprovedPermissions { permissions, scope } => {
+            let snippet = Span::from(exec_snippet(&command)).dim();
+            let granted = permissions
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            let scope = match scope {
+                codex_core::protocol::PermissionScope::Session => "this session",
+                codex_core::protocol::PermissionScope::Project => "this project",
+            };
+            (
+                "✔ ".green(),
+                vec![
+                    "You ".into(),
+                    "approved".bold(),
+                    " codex to run ".into(),
+                    snippet,
+                    format!(" and allowed {granted} in {scope}").bold(),
+                ],
+            )
+        }
+        Denied | PartiallyApproved { .. } => {
             let snippet = Span::from(exec_snippet(&command)).dim();
             (
                 "✗ ".red(),
@@ -1661,9 +1755,11 @@ pub(crate) fn new_view_image_tool_call(path: PathBuf, cwd: &Path) -> PlainHistor
     PlainHistoryCell { lines }
 }
 
+/// `collapsed` shows only the summary's header outside the transcript.
 pub(crate) fn new_reasoning_summary_block(
     full_reasoning_buffer: String,
     reasoning_summary_format: ReasoningSummaryFormat,
+    collapsed: bool,
 ) -> Box<dyn HistoryCell> {
     if reasoning_summary_format == ReasoningSummaryFormat::Experimental {
         // Experimental format is following:
@@ -1682,11 +1778,9 @@ pub(crate) fn new_reasoning_summary_block(
                 if after_close_idx < full_reasoning_buffer.len() {
                     let header_buffer = full_reasoning_buffer[..after_close_idx].to_string();
                     let summary_buffer = full_reasoning_buffer[after_close_idx..].to_string();
-                    return Box::new(ReasoningSummaryCell::new(
-                        header_buffer,
-                        summary_buffer,
-                        false,
-                    ));
+                    let mut cell = ReasoningSummaryCell::new(header_buffer, summary_buffer, false);
+                    cell.collapsed = collapsed;
+                    return Box::new(cell);
                 }
             }
         }
@@ -2676,6 +2770,7 @@ mod tests {
         let cell = new_reasoning_summary_block(
             "**High level reasoning**\n\nDetailed reasoning goes here.".to_string(),
             reasoning_format,
+            false,
         );
 
         let rendered_display = render_lines(&cell.display_lines(80));
@@ -2691,6 +2786,7 @@ mod tests {
         let cell = new_reasoning_summary_block(
             "Detailed reasoning goes here.".to_string(),
             reasoning_format,
+            false,
         );
 
         let rendered = render_transcript(cell.as_ref());
@@ -2713,6 +2809,7 @@ mod tests {
         let cell = new_reasoning_summary_block(
             "**High level reasoning**\n\nDetailed reasoning goes here.".to_string(),
             model_family.reasoning_summary_format,
+            false,
         );
 
         let rendered_display = render_lines(&cell.display_lines(80));
@@ -2725,6 +2822,7 @@ mod tests {
         let cell = new_reasoning_summary_block(
             "**High level reasoning without closing".to_string(),
             reasoning_format,
+            false,
         );
 
         let rendered = render_transcript(cell.as_ref());
@@ -2737,6 +2835,7 @@ mod tests {
         let cell = new_reasoning_summary_block(
             "**High level reasoning without closing**".to_string(),
             reasoning_format.clone(),
+            false,
         );
 
         let rendered = render_transcript(cell.as_ref());
@@ -2745,18 +2844,39 @@ mod tests {
         let cell = new_reasoning_summary_block(
             "**High level reasoning without closing**\n\n  ".to_string(),
             reasoning_format,
+            false,
         );
 
         let rendered = render_transcript(cell.as_ref());
         assert_eq!(rendered, vec!["• High level reasoning without closing"]);
     }
 
+    #[test]
+    fn collapsed_reasoning_summary_shows_only_the_header() {
+        let cell = new_reasoning_summary_block(
+            "**Planning the fix**\n\nWe should fix the bug next.".to_string(),
+            ReasoningSummaryFormat::Experimental,
+            true,
+        );
+
+        let rendered_display = render_lines(&cell.display_lines(80));
+        assert_eq!(
+            rendered_display,
+            vec!["• Planning the fix (ctrl + t to expand)"]
+        );
+        assert_eq!(cell.desired_height(80), 1);
+
+        let rendered_transcript = render_transcript(cell.as_ref());
+        assert_eq!(rendered_transcript, vec!["• We should fix the bug next."]);
+    }
+
     #[test]
     fn reasoning_summary_block_splits_header_and_summary_when_present() {
         let reasoning_format = ReasoningSummaryFormat::Experimental;
         let cell = new_reasoning_summary_block(
             "**High level plan**\n\nWe should fix the bug next.".to_string(),
             reasoning_format,
+            false,
         );
 
         let rendered_display = render_lines(&cell.display_lines(80));
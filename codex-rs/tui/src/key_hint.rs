@@ -26,6 +26,14 @@ impl KeyBinding {
         Self { key, modifiers }
     }
 
+    pub(crate) const fn key(&self) -> KeyCode {
+        self.key
+    }
+
+    pub(crate) const fn modifiers(&self) -> KeyModifiers {
+        self.modifiers
+    }
+
     pub fn is_press(&self, event: KeyEvent) -> bool {
         self.key == event.code
             && self.modifiers == event.modifiers
@@ -74,8 +82,8 @@ impl From<KeyBinding> for Span<'static> {
 }
 impl From<&KeyBinding> for Span<'static> {
     fn from(binding: &KeyBinding) -> Self {
-        let KeyBinding { key, modifiers } = binding;
-        let modifiers = modifiers_to_string(*modifiers);
+        let KeyBinding { key, modifiers } = crate::keymap::displayed(*binding);
+        let modifiers = modifiers_to_string(modifiers);
         let key = match key {
             KeyCode::Enter => "enter".to_string(),
             KeyCode::Char(' ') => "space".to_string(),
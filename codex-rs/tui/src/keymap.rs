@@ -0,0 +1,202 @@
+//! Shortcuts remapped with `[tui.keybindings]`.
+//!
+//! Widgets keep matching the built-in keys. A remapped key is translated to
+//! the built-in key of its action before the event is dispatched, and the
+//! built-in key of a remapped action is dropped so it can be reused.
+
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+use std::sync::RwLock;
+
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+
+use crate::key_hint;
+use crate::key_hint::KeyBinding;
+
+/// Actions that can be remapped, with their built-in keys.
+const ACTIONS: &[(&str, KeyBinding)] = &[
+    ("transcript", key_hint::ctrl(KeyCode::Char('t'))),
+    ("tool_output", key_hint::ctrl(KeyCode::Char('o'))),
+    ("next_tool_output", key_hint::shift(KeyCode::BackTab)),
+    ("paste_image", key_hint::ctrl(KeyCode::Char('v'))),
+    ("edit_queued_message", key_hint::alt(KeyCode::Up)),
+    ("discard_queued_message", key_hint::alt(KeyCode::Down)),
+    ("voice_input", key_hint::ctrl(KeyCode::Char('r'))),
+];
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Keymap {
+    /// `(custom, built-in)` pairs for every remapped action.
+    remapped: Vec<(KeyBinding, KeyBinding)>,
+}
+
+static ACTIVE_KEYMAP: LazyLock<RwLock<Keymap>> = LazyLock::new(|| RwLock::new(Keymap::default()));
+
+impl Keymap {
+    /// Builds the keymap from config. Unknown actions and keys are returned as
+    /// warnings and keep their built-in binding.
+    pub(crate) fn from_config(bindings: &BTreeMap<String, String>) -> (Self, Vec<String>) {
+        let mut keymap = Self::default();
+        let mut warnings = Vec::new();
+        for (action, key) in bindings {
+            let Some((_, default)) = ACTIONS.iter().find(|(name, _)| name == action) else {
+                let known: Vec<&str> = ACTIONS.iter().map(|(name, _)| *name).collect();
+                warnings.push(format!(
+                    "Unknown keybinding action `{action}`; expected one of {}.",
+                    known.join(", ")
+                ));
+                continue;
+            };
+            match parse_key(key) {
+                Some(custom) if custom != *default => keymap.remapped.push((custom, *default)),
+                Some(_) => {}
+                None => warnings.push(format!("Invalid key `{key}` for `{action}`.")),
+            }
+        }
+        (keymap, warnings)
+    }
+
+    fn translate(&self, event: KeyEvent) -> Option<KeyEvent> {
+        let matches = |binding: &KeyBinding| {
+            binding.key() == event.code && binding.modifiers() == event.modifiers
+        };
+        if let Some((_, default)) = self.remapped.iter().find(|(custom, _)| matches(custom)) {
+            return Some(KeyEvent {
+                code: default.key(),
+                modifiers: default.modifiers(),
+                ..event
+            });
+        }
+        if self.remapped.iter().any(|(_, default)| matches(default)) {
+            return None;
+        }
+        Some(event)
+    }
+
+    fn displayed(&self, binding: KeyBinding) -> KeyBinding {
+        self.remapped
+            .iter()
+            .find(|(_, default)| *default == binding)
+            .map_or(binding, |(custom, _)| *custom)
+    }
+}
+
+/// Makes `keymap` the keymap used for all subsequent key events.
+pub(crate) fn set_keymap(keymap: Keymap) {
+    if let Ok(mut active) = ACTIVE_KEYMAP.write() {
+        *active = keymap;
+    }
+}
+
+/// Rewrites a key event according to the active keymap. Returns `None` when
+/// the key is the built-in key of a remapped action.
+pub(crate) fn translate(event: KeyEvent) -> Option<KeyEvent> {
+    match ACTIVE_KEYMAP.read() {
+        Ok(keymap) => keymap.translate(event),
+        Err(_) => Some(event),
+    }
+}
+
+/// The key the user presses for `binding`, for showing in key hints.
+pub(crate) fn displayed(binding: KeyBinding) -> KeyBinding {
+    match ACTIVE_KEYMAP.read() {
+        Ok(keymap) => keymap.displayed(binding),
+        Err(_) => binding,
+    }
+}
+
+/// Parses keys written as `ctrl+y`, `alt+shift+up` or `f5`.
+fn parse_key(spec: &str) -> Option<KeyBinding> {
+    let spec = spec.trim().to_ascii_lowercase();
+    let mut parts: Vec<&str> = spec.split('+').map(str::trim).collect();
+    // `ctrl++` binds the plus key itself.
+    if spec.ends_with("++") {
+        parts.truncate(parts.len() - 2);
+        parts.push("+");
+    }
+    let key = parts.pop()?;
+    let mut modifiers = KeyModifiers::NONE;
+    for modifier in parts {
+        modifiers |= match modifier {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" | "option" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => return None,
+        };
+    }
+    let code = match key {
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" | "ins" => KeyCode::Insert,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" | "pgup" => KeyCode::PageUp,
+        "pagedown" | "pgdn" => KeyCode::PageDown,
+        "space" => KeyCode::Char(' '),
+        _ => {
+            let mut chars = key.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                (Some('f'), Some(_)) => KeyCode::F(key[1..].parse().ok().filter(|n| *n >= 1)?),
+                _ => return None,
+            }
+        }
+    };
+    Some(KeyBinding::new(code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_key_specs() {
+        assert_eq!(
+            parse_key("Ctrl+Y"),
+            Some(key_hint::ctrl(KeyCode::Char('y')))
+        );
+        assert_eq!(
+            parse_key("shift+tab"),
+            Some(key_hint::shift(KeyCode::BackTab))
+        );
+        assert_eq!(parse_key("f5"), Some(key_hint::plain(KeyCode::F(5))));
+        assert_eq!(
+            parse_key("ctrl++"),
+            Some(key_hint::ctrl(KeyCode::Char('+')))
+        );
+        assert_eq!(parse_key("hyper+x"), None);
+        assert_eq!(parse_key("ctrl+nope"), None);
+    }
+
+    #[test]
+    fn remapped_key_replaces_builtin_key() {
+        let (keymap, warnings) = Keymap::from_config(&BTreeMap::from([
+            ("transcript".to_string(), "ctrl+y".to_string()),
+            ("nonsense".to_string(), "ctrl+x".to_string()),
+        ]));
+        assert_eq!(warnings.len(), 1);
+
+        let ctrl_y = KeyEvent::new(KeyCode::Char('y'), KeyModifiers::CONTROL);
+        let ctrl_t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL);
+        let plain_t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::NONE);
+        assert_eq!(keymap.translate(ctrl_y), Some(ctrl_t));
+        assert_eq!(keymap.translate(ctrl_t), None);
+        assert_eq!(keymap.translate(plain_t), Some(plain_t));
+        assert_eq!(
+            keymap.displayed(key_hint::ctrl(KeyCode::Char('t'))),
+            key_hint::ctrl(KeyCode::Char('y'))
+        );
+    }
+}
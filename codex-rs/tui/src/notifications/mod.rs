@@ -1,10 +1,13 @@
+mod native;
 mod osc9;
 mod windows_toast;
 
 use std::env;
 use std::io;
 
+use codex_core::config::types::NotificationBackend;
 use codex_core::env::is_wsl;
+use native::NativeBackend;
 use osc9::Osc9Backend;
 use windows_toast::WindowsToastBackend;
 
@@ -12,12 +15,14 @@ use windows_toast::WindowsToastBackend;
 pub enum NotificationBackendKind {
     Osc9,
     WindowsToast,
+    Native,
 }
 
 #[derive(Debug)]
 pub enum DesktopNotificationBackend {
     Osc9(Osc9Backend),
     WindowsToast(WindowsToastBackend),
+    Native(NativeBackend),
 }
 
 impl DesktopNotificationBackend {
@@ -29,10 +34,15 @@ impl DesktopNotificationBackend {
         Self::WindowsToast(WindowsToastBackend::default())
     }
 
+    pub fn native() -> Self {
+        Self::Native(NativeBackend)
+    }
+
     pub fn kind(&self) -> NotificationBackendKind {
         match self {
             DesktopNotificationBackend::Osc9(_) => NotificationBackendKind::Osc9,
             DesktopNotificationBackend::WindowsToast(_) => NotificationBackendKind::WindowsToast,
+            DesktopNotificationBackend::Native(_) => NotificationBackendKind::Native,
         }
     }
 
@@ -40,29 +50,58 @@ impl DesktopNotificationBackend {
         match self {
             DesktopNotificationBackend::Osc9(backend) => backend.notify(message),
             DesktopNotificationBackend::WindowsToast(backend) => backend.notify(message),
+            DesktopNotificationBackend::Native(backend) => backend.notify(message),
         }
     }
 }
 
+/// Picks the backend for the `tui.notification_backend` setting.
+pub fn select_backend(setting: NotificationBackend) -> DesktopNotificationBackend {
+    match setting {
+        NotificationBackend::Auto => detect_backend(),
+        NotificationBackend::Osc9 => DesktopNotificationBackend::osc9(),
+        NotificationBackend::Native => native_backend(),
+    }
+}
+
 pub fn detect_backend() -> DesktopNotificationBackend {
     if should_use_windows_toasts() {
         tracing::info!(
             "Windows Terminal session detected under WSL; using Windows toast notifications"
         );
         DesktopNotificationBackend::windows_toast()
+    } else if terminal_ignores_osc9() {
+        tracing::info!("Terminal.app ignores OSC 9; using native notifications");
+        DesktopNotificationBackend::native()
     } else {
         DesktopNotificationBackend::osc9()
     }
 }
 
+fn native_backend() -> DesktopNotificationBackend {
+    if cfg!(windows) || is_wsl() {
+        DesktopNotificationBackend::windows_toast()
+    } else {
+        DesktopNotificationBackend::native()
+    }
+}
+
 fn should_use_windows_toasts() -> bool {
     is_wsl() && env::var_os("WT_SESSION").is_some()
 }
 
+/// macOS Terminal.app silently drops OSC 9 notifications.
+fn terminal_ignores_osc9() -> bool {
+    cfg!(target_os = "macos")
+        && env::var_os("TERM_PROGRAM").is_some_and(|program| program == "Apple_Terminal")
+}
+
 #[cfg(test)]
 mod tests {
+    use super::NotificationBackend;
     use super::NotificationBackendKind;
     use super::detect_backend;
+    use super::select_backend;
     use serial_test::serial;
     use std::ffi::OsString;
 
@@ -103,6 +142,7 @@ mod tests {
     #[test]
     #[serial]
     fn defaults_to_osc9_outside_wsl() {
+        let _term_guard = EnvVarGuard::remove("TERM_PROGRAM");
         let _wsl_guard = EnvVarGuard::remove("WSL_DISTRO_NAME");
         let _wt_guard = EnvVarGuard::remove("WT_SESSION");
         assert_eq!(detect_backend().kind(), NotificationBackendKind::Osc9);
@@ -111,6 +151,7 @@ mod tests {
     #[test]
     #[serial]
     fn waits_for_windows_terminal() {
+        let _term_guard = EnvVarGuard::remove("TERM_PROGRAM");
         let _wsl_guard = EnvVarGuard::set("WSL_DISTRO_NAME", "Ubuntu");
         let _wt_guard = EnvVarGuard::remove("WT_SESSION");
         assert_eq!(detect_backend().kind(), NotificationBackendKind::Osc9);
@@ -132,8 +173,39 @@ mod tests {
     #[test]
     #[serial]
     fn stays_on_osc9_outside_linux_even_with_wsl_env() {
+        let _term_guard = EnvVarGuard::remove("TERM_PROGRAM");
         let _wsl_guard = EnvVarGuard::set("WSL_DISTRO_NAME", "Ubuntu");
         let _wt_guard = EnvVarGuard::set("WT_SESSION", "abc");
         assert_eq!(detect_backend().kind(), NotificationBackendKind::Osc9);
     }
+
+    #[test]
+    #[serial]
+    fn explicit_osc9_ignores_windows_terminal() {
+        let _wsl_guard = EnvVarGuard::set("WSL_DISTRO_NAME", "Ubuntu");
+        let _wt_guard = EnvVarGuard::set("WT_SESSION", "abc");
+        assert_eq!(
+            select_backend(NotificationBackend::Osc9).kind(),
+            NotificationBackendKind::Osc9
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[serial]
+    fn native_uses_notify_send_outside_wsl() {
+        let _wsl_guard = EnvVarGuard::remove("WSL_DISTRO_NAME");
+        assert_eq!(
+            select_backend(NotificationBackend::Native).kind(),
+            NotificationBackendKind::Native
+        );
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    #[serial]
+    fn auto_uses_native_in_terminal_app() {
+        let _term_guard = EnvVarGuard::set("TERM_PROGRAM", "Apple_Terminal");
+        assert_eq!(detect_backend().kind(), NotificationBackendKind::Native);
+    }
 }
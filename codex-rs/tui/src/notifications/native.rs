@@ -0,0 +1,74 @@
+use std::io;
+use std::process::Command;
+use std::process::Stdio;
+
+const APP_NAME: &str = "Codex";
+
+/// Posts notifications through the desktop's own notifier: `osascript` on
+/// macOS and `notify-send` elsewhere.
+#[derive(Debug, Default)]
+pub struct NativeBackend;
+
+impl NativeBackend {
+    pub fn notify(&mut self, message: &str) -> io::Result<()> {
+        let mut command = notifier_command(message);
+        let program = command.get_program().to_string_lossy().into_owned();
+        let status = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "{program} exited with status {status}"
+            )))
+        }
+    }
+}
+
+/// Passes the message as an argument so it never needs AppleScript quoting.
+#[cfg(target_os = "macos")]
+fn notifier_command(message: &str) -> Command {
+    let mut command = Command::new("osascript");
+    command
+        .arg("-e")
+        .arg("on run argv")
+        .arg("-e")
+        .arg(format!(
+            "display notification (item 1 of argv) with title \"{APP_NAME}\""
+        ))
+        .arg("-e")
+        .arg("end run")
+        .arg(message);
+    command
+}
+
+#[cfg(not(target_os = "macos"))]
+fn notifier_command(message: &str) -> Command {
+    let mut command = Command::new("notify-send");
+    command
+        .arg(format!("--app-name={APP_NAME}"))
+        .arg("--")
+        .arg(APP_NAME)
+        .arg(message);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn message_is_passed_as_a_single_argument() {
+        let message = "Approval requested: \"rm -rf build\"";
+        let command = notifier_command(message);
+        let args: Vec<String> = command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(args.last().map(String::as_str), Some(message));
+    }
+}
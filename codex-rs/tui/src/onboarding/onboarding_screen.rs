@@ -1,4 +1,5 @@
 use codex_core::AuthManager;
+use codex_core::auth::accounts;
 use codex_core::config::Config;
 use codex_core::git_info::get_git_repo_root;
 use crossterm::event::KeyCode;
@@ -82,6 +83,8 @@ impl OnboardingScreen {
         let cwd = config.cwd.clone();
         let forced_chatgpt_workspace_id = config.forced_chatgpt_workspace_id.clone();
         let forced_login_method = config.forced_login_method;
+        // Logins are saved to the account the profile or `codex auth use` selects.
+        let auth_home = accounts::auth_home(&config);
         let codex_home = config.codex_home;
         let cli_auth_credentials_store_mode = config.cli_auth_credentials_store_mode;
         let mut steps: Vec<Step> = Vec::new();
@@ -100,7 +103,7 @@ impl OnboardingScreen {
                 highlighted_mode,
                 error: None,
                 sign_in_state: Arc::new(RwLock::new(SignInState::PickMode)),
-                codex_home: codex_home.clone(),
+                codex_home: auth_home,
                 cli_auth_credentials_store_mode,
                 login_status,
                 auth_manager,
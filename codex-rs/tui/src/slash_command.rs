@@ -13,7 +13,9 @@ pub enum SlashCommand {
     // DO NOT ALPHA-SORT! Enum order is presentation order in the popup, so
     // more frequently used commands should be listed first.
     Model,
+    Think,
     Approvals,
+    Ask,
     Experimental,
     Skills,
     Review,
@@ -21,8 +23,13 @@ pub enum SlashCommand {
     Resume,
     Init,
     Compact,
+    Trim,
+    Pin,
+    Unpin,
     Undo,
     Diff,
+    Changes,
+    Commit,
     Mention,
     Status,
     Mcp,
@@ -46,14 +53,21 @@ impl SlashCommand {
             SlashCommand::Review => "review my current changes and find issues",
             SlashCommand::Resume => "resume a saved chat",
             SlashCommand::Undo => "ask Codex to undo a turn",
+            SlashCommand::Trim => "drop large items from the conversation to free up context",
+            SlashCommand::Pin => "pin a message, instruction, or file so compaction keeps it",
+            SlashCommand::Unpin => "remove a pinned context item",
             SlashCommand::Quit | SlashCommand::Exit => "exit Codex",
             SlashCommand::Diff => "show git diff (including untracked files)",
+            SlashCommand::Changes => "show every file change Codex made this session",
+            SlashCommand::Commit => "write a commit message for the staged changes and commit",
             SlashCommand::Mention => "mention a file",
             SlashCommand::Skills => "use skills to improve how Codex performs specific tasks",
             SlashCommand::Status => "show current session configuration and token usage",
             SlashCommand::Ps => "list background terminals",
             SlashCommand::Model => "choose what model and reasoning effort to use",
+            SlashCommand::Think => "reason harder on the next turn (or pass an effort)",
             SlashCommand::Approvals => "choose what Codex can do without approval",
+            SlashCommand::Ask => "toggle read-only mode: Codex explains but changes nothing",
             SlashCommand::Experimental => "toggle beta features",
             SlashCommand::Mcp => "list configured MCP tools",
             SlashCommand::Logout => "log out of Codex",
@@ -75,16 +89,23 @@ impl SlashCommand {
             | SlashCommand::Resume
             | SlashCommand::Init
             | SlashCommand::Compact
+            | SlashCommand::Trim
             | SlashCommand::Undo
             | SlashCommand::Model
             | SlashCommand::Approvals
             | SlashCommand::Experimental
             | SlashCommand::Review
+            | SlashCommand::Commit
             | SlashCommand::Logout => false,
             SlashCommand::Diff
+            | SlashCommand::Changes
             | SlashCommand::Mention
             | SlashCommand::Skills
             | SlashCommand::Status
+            | SlashCommand::Pin
+            | SlashCommand::Unpin
+            | SlashCommand::Think
+            | SlashCommand::Ask
             | SlashCommand::Ps
             | SlashCommand::Mcp
             | SlashCommand::Feedback
@@ -95,6 +116,15 @@ impl SlashCommand {
         }
     }
 
+    /// Whether text typed after the command name is passed to the command
+    /// instead of being submitted as a regular message.
+    pub fn accepts_args(self) -> bool {
+        matches!(
+            self,
+            SlashCommand::Pin | SlashCommand::Unpin | SlashCommand::Think | SlashCommand::Ask
+        )
+    }
+
     fn is_visible(self) -> bool {
         match self {
             SlashCommand::Rollout | SlashCommand::TestApproval => cfg!(debug_assertions),
@@ -10,6 +10,7 @@ use codex_core::config::Config;
 use codex_core::models_manager::model_family::ModelFamily;
 use codex_core::protocol::NetworkAccess;
 use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::SpendSummary;
 use codex_core::protocol::TokenUsage;
 use codex_protocol::ConversationId;
 use codex_protocol::account::PlanType;
@@ -52,6 +53,8 @@ pub(crate) struct StatusTokenUsageData {
     input: i64,
     output: i64,
     context_window: Option<StatusContextWindowData>,
+    pinned_context: Option<i64>,
+    spend: Option<SpendSummary>,
 }
 
 #[derive(Debug)]
@@ -80,6 +83,8 @@ pub(crate) fn new_status_output(
     plan_type: Option<PlanType>,
     now: DateTime<Local>,
     model_name: &str,
+    pinned_context_tokens: Option<i64>,
+    spend: Option<SpendSummary>,
 ) -> CompositeHistoryCell {
     let command = PlainHistoryCell::new(vec!["/status".magenta().into()]);
     let card = StatusHistoryCell::new(
@@ -93,6 +98,8 @@ pub(crate) fn new_status_output(
         plan_type,
         now,
         model_name,
+        pinned_context_tokens,
+        spend,
     );
 
     CompositeHistoryCell::new(vec![Box::new(command), Box::new(card)])
@@ -111,6 +118,8 @@ impl StatusHistoryCell {
         plan_type: Option<PlanType>,
         now: DateTime<Local>,
         model_name: &str,
+        pinned_context_tokens: Option<i64>,
+        spend: Option<SpendSummary>,
     ) -> Self {
         let config_entries = create_config_summary_entries(config, model_name);
         let (model_name, model_details) = compose_model_display(model_name, &config_entries);
@@ -147,6 +156,8 @@ impl StatusHistoryCell {
             input: total_usage.non_cached_input(),
             output: total_usage.output_tokens,
             context_window,
+            pinned_context: pinned_context_tokens,
+            spend,
         };
         let rate_limits = compose_rate_limit_data(rate_limits, now);
 
@@ -348,6 +359,12 @@ impl HistoryCell for StatusHistoryCell {
         if self.token_usage.context_window.is_some() {
             push_label(&mut labels, &mut seen, "Context window");
         }
+        if self.token_usage.pinned_context.is_some() {
+            push_label(&mut labels, &mut seen, "Pinned context");
+        }
+        if self.token_usage.spend.is_some() {
+            push_label(&mut labels, &mut seen, "Spend");
+        }
         self.collect_rate_limit_labels(&mut seen, &mut labels);
 
         let formatter = FieldFormatter::from_labels(labels.iter().map(String::as_str));
@@ -403,6 +420,29 @@ impl HistoryCell for StatusHistoryCell {
             lines.push(formatter.line("Context window", spans));
         }
 
+        if let Some(tokens) = self.token_usage.pinned_context {
+            lines.push(formatter.line(
+                "Pinned context",
+                vec![
+                    Span::from(format_tokens_compact(tokens)),
+                    Span::from(" tokens (never compacted)").dim(),
+                ],
+            ));
+        }
+
+        if let Some(spend) = self.token_usage.spend {
+            lines.push(formatter.line(
+                "Spend",
+                vec![
+                    Span::from(format!("${:.2}", spend.session_usd)),
+                    Span::from(" session").dim(),
+                    Span::from(" · ").dim(),
+                    Span::from(format!("${:.2}", spend.today_usd)),
+                    Span::from(" today (estimated)").dim(),
+                ],
+            ));
+        }
+
         lines.extend(self.rate_limit_lines(available_inner_width, &formatter));
 
         let content_width = lines.iter().map(line_display_width).max().unwrap_or(0);
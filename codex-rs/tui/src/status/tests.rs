@@ -13,6 +13,7 @@ use codex_core::protocol::CreditsSnapshot;
 use codex_core::protocol::RateLimitSnapshot;
 use codex_core::protocol::RateLimitWindow;
 use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::SpendSummary;
 use codex_core::protocol::TokenUsage;
 use codex_protocol::config_types::ReasoningSummary;
 use codex_protocol::openai_models::ReasoningEffort;
@@ -145,6 +146,8 @@ async fn status_snapshot_includes_reasoning_details() {
         None,
         captured_at,
         &model_slug,
+        None,
+        None,
     );
     let mut rendered_lines = render_lines(&composite.display_lines(80));
     if cfg!(windows) {
@@ -202,6 +205,103 @@ async fn status_snapshot_includes_monthly_limit() {
         None,
         captured_at,
         &model_slug,
+        None,
+        None,
+    );
+    let mut rendered_lines = render_lines(&composite.display_lines(80));
+    if cfg!(windows) {
+        for line in &mut rendered_lines {
+            *line = line.replace('\\', "/");
+        }
+    }
+    let sanitized = sanitize_directory(rendered_lines).join("\n");
+    assert_snapshot!(sanitized);
+}
+
+#[tokio::test]
+async fn status_snapshot_includes_pinned_context_tokens() {
+    let temp_home = TempDir::new().expect("temp home");
+    let mut config = test_config(&temp_home).await;
+    config.model = Some("gpt-5.1-codex-max".to_string());
+    config.cwd = PathBuf::from("/workspace/tests");
+
+    let auth_manager = test_auth_manager(&config);
+    let usage = TokenUsage {
+        input_tokens: 2_400,
+        cached_input_tokens: 0,
+        output_tokens: 600,
+        reasoning_output_tokens: 0,
+        total_tokens: 3_000,
+    };
+    let captured_at = chrono::Local
+        .with_ymd_and_hms(2024, 5, 6, 7, 8, 9)
+        .single()
+        .expect("timestamp");
+
+    let model_slug = ModelsManager::get_model_offline(config.model.as_deref());
+    let model_family = test_model_family(&model_slug, &config);
+    let composite = new_status_output(
+        &config,
+        &auth_manager,
+        &model_family,
+        &usage,
+        Some(&usage),
+        &None,
+        None,
+        None,
+        captured_at,
+        &model_slug,
+        Some(1_250),
+        None,
+    );
+    let mut rendered_lines = render_lines(&composite.display_lines(80));
+    if cfg!(windows) {
+        for line in &mut rendered_lines {
+            *line = line.replace('\\', "/");
+        }
+    }
+    let sanitized = sanitize_directory(rendered_lines).join("\n");
+    assert_snapshot!(sanitized);
+}
+
+#[tokio::test]
+async fn status_snapshot_includes_spend() {
+    let temp_home = TempDir::new().expect("temp home");
+    let mut config = test_config(&temp_home).await;
+    config.model = Some("gpt-5.1-codex-max".to_string());
+    config.cwd = PathBuf::from("/workspace/tests");
+
+    let auth_manager = test_auth_manager(&config);
+    let usage = TokenUsage {
+        input_tokens: 2_400,
+        cached_input_tokens: 0,
+        output_tokens: 600,
+        reasoning_output_tokens: 0,
+        total_tokens: 3_000,
+    };
+    let captured_at = chrono::Local
+        .with_ymd_and_hms(2024, 5, 6, 7, 8, 9)
+        .single()
+        .expect("timestamp");
+
+    let model_slug = ModelsManager::get_model_offline(config.model.as_deref());
+    let model_family = test_model_family(&model_slug, &config);
+    let composite = new_status_output(
+        &config,
+        &auth_manager,
+        &model_family,
+        &usage,
+        Some(&usage),
+        &None,
+        None,
+        None,
+        captured_at,
+        &model_slug,
+        None,
+        Some(SpendSummary {
+            session_usd: 0.4213,
+            today_usd: 3.1,
+        }),
     );
     let mut rendered_lines = render_lines(&composite.display_lines(80));
     if cfg!(windows) {
@@ -247,6 +347,8 @@ async fn status_snapshot_shows_unlimited_credits() {
         None,
         captured_at,
         &model_slug,
+        None,
+        None,
     );
     let rendered = render_lines(&composite.display_lines(120));
     assert!(
@@ -291,6 +393,8 @@ async fn status_snapshot_shows_positive_credits() {
         None,
         captured_at,
         &model_slug,
+        None,
+        None,
     );
     let rendered = render_lines(&composite.display_lines(120));
     assert!(
@@ -335,6 +439,8 @@ async fn status_snapshot_hides_zero_credits() {
         None,
         captured_at,
         &model_slug,
+        None,
+        None,
     );
     let rendered = render_lines(&composite.display_lines(120));
     assert!(
@@ -377,6 +483,8 @@ async fn status_snapshot_hides_when_has_no_credits_flag() {
         None,
         captured_at,
         &model_slug,
+        None,
+        None,
     );
     let rendered = render_lines(&composite.display_lines(120));
     assert!(
@@ -419,6 +527,8 @@ async fn status_card_token_usage_excludes_cached_tokens() {
         None,
         now,
         &model_slug,
+        None,
+        None,
     );
     let rendered = render_lines(&composite.display_lines(120));
 
@@ -476,6 +586,8 @@ async fn status_snapshot_truncates_in_narrow_terminal() {
         None,
         captured_at,
         &model_slug,
+        None,
+        None,
     );
     let mut rendered_lines = render_lines(&composite.display_lines(70));
     if cfg!(windows) {
@@ -522,6 +634,8 @@ async fn status_snapshot_shows_missing_limits_message() {
         None,
         now,
         &model_slug,
+        None,
+        None,
     );
     let mut rendered_lines = render_lines(&composite.display_lines(80));
     if cfg!(windows) {
@@ -586,6 +700,8 @@ async fn status_snapshot_includes_credits_and_limits() {
         None,
         captured_at,
         &model_slug,
+        None,
+        None,
     );
     let mut rendered_lines = render_lines(&composite.display_lines(80));
     if cfg!(windows) {
@@ -638,6 +754,8 @@ async fn status_snapshot_shows_empty_limits_message() {
         None,
         captured_at,
         &model_slug,
+        None,
+        None,
     );
     let mut rendered_lines = render_lines(&composite.display_lines(80));
     if cfg!(windows) {
@@ -699,6 +817,8 @@ async fn status_snapshot_shows_stale_limits_message() {
         None,
         now,
         &model_slug,
+        None,
+        None,
     );
     let mut rendered_lines = render_lines(&composite.display_lines(80));
     if cfg!(windows) {
@@ -764,6 +884,8 @@ async fn status_snapshot_cached_limits_hide_credits_without_flag() {
         None,
         now,
         &model_slug,
+        None,
+        None,
     );
     let mut rendered_lines = render_lines(&composite.display_lines(80));
     if cfg!(windows) {
@@ -815,6 +937,8 @@ async fn status_context_window_uses_last_usage() {
         None,
         now,
         &model_slug,
+        None,
+        None,
     );
     let rendered_lines = render_lines(&composite.display_lines(80));
     let context_line = rendered_lines
@@ -0,0 +1,256 @@
+//! Color themes selected with `tui.theme`.
+//!
+//! Widgets keep drawing with the terminal's ANSI palette. The active theme is
+//! applied to every finished frame and to lines before they are inserted into
+//! history, so a theme can remap or drop colors without each widget knowing
+//! about it.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::LazyLock;
+use std::sync::RwLock;
+
+use codex_core::config::types::TuiTheme;
+use ratatui::buffer::Buffer;
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::text::Line;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ThemeMode {
+    #[default]
+    Default,
+    /// Draw everything in the terminal's default colors.
+    NoColor,
+    /// Brighten colors and drop dimmed text.
+    HighContrast,
+}
+
+impl ThemeMode {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::Default),
+            "no-color" => Some(Self::NoColor),
+            "high-contrast" => Some(Self::HighContrast),
+            _ => None,
+        }
+    }
+
+    fn color(self, color: Color) -> Color {
+        match self {
+            Self::Default => color,
+            Self::NoColor => Color::Reset,
+            Self::HighContrast => match color {
+                Color::Gray | Color::DarkGray => Color::Reset,
+                Color::Red => Color::LightRed,
+                Color::Green => Color::LightGreen,
+                Color::Yellow => Color::LightYellow,
+                Color::Blue => Color::LightBlue,
+                Color::Magenta => Color::LightMagenta,
+                Color::Cyan => Color::LightCyan,
+                other => other,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Theme {
+    mode: ThemeMode,
+    /// Colors replaced by a custom theme; takes precedence over `mode`.
+    colors: HashMap<Color, Color>,
+}
+
+static ACTIVE_THEME: LazyLock<RwLock<Theme>> = LazyLock::new(|| RwLock::new(Theme::default()));
+
+impl Theme {
+    /// Resolves the theme named in config. Problems are returned as warnings
+    /// and fall back to the default theme.
+    pub(crate) fn resolve(
+        name: Option<&str>,
+        themes: &BTreeMap<String, TuiTheme>,
+    ) -> (Self, Vec<String>) {
+        let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let Some(name) = name else {
+            let mode = if no_color_env {
+                ThemeMode::NoColor
+            } else {
+                ThemeMode::Default
+            };
+            return (
+                Self {
+                    mode,
+                    colors: HashMap::new(),
+                },
+                Vec::new(),
+            );
+        };
+        if let Some(mode) = ThemeMode::from_name(name) {
+            return (
+                Self {
+                    mode,
+                    colors: HashMap::new(),
+                },
+                Vec::new(),
+            );
+        }
+        let Some(custom) = themes.get(name) else {
+            return (
+                Self::default(),
+                vec![format!(
+                    "Unknown theme `{name}`; expected default, no-color, high-contrast or a theme under [tui.themes]."
+                )],
+            );
+        };
+
+        let mut warnings = Vec::new();
+        let mode = match custom.base.as_deref() {
+            None => ThemeMode::Default,
+            Some(base) => ThemeMode::from_name(base).unwrap_or_else(|| {
+                warnings.push(format!(
+                    "Theme `{name}` has unknown base `{base}`; using default."
+                ));
+                ThemeMode::Default
+            }),
+        };
+        let mut colors = HashMap::new();
+        for (from, to) in &custom.colors {
+            match (Color::from_str(from), Color::from_str(to)) {
+                (Ok(from), Ok(to)) => {
+                    colors.insert(from, to);
+                }
+                (Err(_), _) => warnings.push(format!("Theme `{name}`: unknown color `{from}`.")),
+                (_, Err(_)) => warnings.push(format!("Theme `{name}`: unknown color `{to}`.")),
+            }
+        }
+        (Self { mode, colors }, warnings)
+    }
+
+    fn is_default(&self) -> bool {
+        self.mode == ThemeMode::Default && self.colors.is_empty()
+    }
+
+    fn color(&self, color: Color) -> Color {
+        match self.colors.get(&color) {
+            Some(replacement) => *replacement,
+            None => self.mode.color(color),
+        }
+    }
+
+    fn style(&self, mut style: Style) -> Style {
+        style.fg = style.fg.map(|color| self.color(color));
+        style.bg = style.bg.map(|color| self.color(color));
+        if self.mode == ThemeMode::HighContrast {
+            style.add_modifier.remove(Modifier::DIM);
+        }
+        style
+    }
+
+    fn apply_to_buffer(&self, buf: &mut Buffer) {
+        for cell in &mut buf.content {
+            cell.fg = self.color(cell.fg);
+            cell.bg = self.color(cell.bg);
+            if self.mode == ThemeMode::HighContrast {
+                cell.modifier.remove(Modifier::DIM);
+            }
+        }
+    }
+
+    fn apply_to_line(&self, mut line: Line<'static>) -> Line<'static> {
+        line.style = self.style(line.style);
+        for span in &mut line.spans {
+            span.style = self.style(span.style);
+        }
+        line
+    }
+}
+
+/// Makes `theme` the theme used for all subsequent drawing.
+pub(crate) fn set_theme(theme: Theme) {
+    if let Ok(mut active) = ACTIVE_THEME.write() {
+        *active = theme;
+    }
+}
+
+/// Recolors a finished frame with the active theme.
+pub(crate) fn apply_to_buffer(buf: &mut Buffer) {
+    let Ok(theme) = ACTIVE_THEME.read() else {
+        return;
+    };
+    if !theme.is_default() {
+        theme.apply_to_buffer(buf);
+    }
+}
+
+/// Recolors lines bound for the terminal scrollback with the active theme.
+pub(crate) fn apply_to_lines(lines: Vec<Line<'static>>) -> Vec<Line<'static>> {
+    let Ok(theme) = ACTIVE_THEME.read() else {
+        return lines;
+    };
+    if theme.is_default() {
+        return lines;
+    }
+    lines
+        .into_iter()
+        .map(|line| theme.apply_to_line(line))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use ratatui::style::Stylize;
+
+    #[test]
+    fn builtin_modes_recolor_lines() {
+        let line = Line::from(vec!["ok".green(), " hint".dim()]);
+
+        let (no_color, _) = Theme::resolve(Some("no-color"), &BTreeMap::new());
+        let recolored = no_color.apply_to_line(line.clone());
+        assert_eq!(recolored.spans[0].style.fg, Some(Color::Reset));
+
+        let (high_contrast, _) = Theme::resolve(Some("high-contrast"), &BTreeMap::new());
+        let recolored = high_contrast.apply_to_line(line);
+        assert_eq!(recolored.spans[0].style.fg, Some(Color::LightGreen));
+        assert!(
+            !recolored.spans[1]
+                .style
+                .add_modifier
+                .contains(Modifier::DIM)
+        );
+    }
+
+    #[test]
+    fn custom_theme_overrides_base_and_reports_bad_colors() {
+        let themes = BTreeMap::from([(
+            "mine".to_string(),
+            TuiTheme {
+                base: Some("high-contrast".to_string()),
+                colors: BTreeMap::from([
+                    ("cyan".to_string(), "#5fafff".to_string()),
+                    ("red".to_string(), "not-a-color".to_string()),
+                ]),
+            },
+        )]);
+
+        let (theme, warnings) = Theme::resolve(Some("mine"), &themes);
+
+        assert_eq!(theme.color(Color::Cyan), Color::Rgb(0x5f, 0xaf, 0xff));
+        assert_eq!(theme.color(Color::Red), Color::LightRed);
+        assert_eq!(
+            warnings,
+            vec!["Theme `mine`: unknown color `not-a-color`.".to_string()]
+        );
+    }
+
+    #[test]
+    fn unknown_theme_falls_back_to_default() {
+        let (theme, warnings) = Theme::resolve(Some("missing"), &BTreeMap::new());
+
+        assert!(theme.is_default());
+        assert_eq!(warnings.len(), 1);
+    }
+}
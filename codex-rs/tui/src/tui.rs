@@ -10,6 +10,7 @@ use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 
+use codex_core::config::types::NotificationBackend;
 use crossterm::Command;
 use crossterm::SynchronizedUpdate;
 use crossterm::event::DisableBracketedPaste;
@@ -40,6 +41,7 @@ use crate::custom_terminal::Terminal as CustomTerminal;
 use crate::notifications::DesktopNotificationBackend;
 use crate::notifications::NotificationBackendKind;
 use crate::notifications::detect_backend;
+use crate::notifications::select_backend;
 use crate::tui::event_stream::EventBroker;
 use crate::tui::event_stream::TuiEventStream;
 #[cfg(unix)]
@@ -227,6 +229,11 @@ impl Tui {
         self.event_broker.resume_events();
     }
 
+    /// Switches to the notification backend chosen in config.
+    pub fn set_notification_backend(&mut self, setting: NotificationBackend) {
+        self.notification_backend = Some(select_backend(setting));
+    }
+
     /// Emit a desktop notification now if the terminal is unfocused.
     /// Returns true if a notification was posted.
     pub fn notify(&mut self, message: impl AsRef<str>) -> bool {
@@ -242,17 +249,18 @@ impl Tui {
         match backend.notify(&message) {
             Ok(()) => true,
             Err(err) => match backend.kind() {
-                NotificationBackendKind::WindowsToast => {
+                kind
+                @ (NotificationBackendKind::WindowsToast | NotificationBackendKind::Native) => {
                     tracing::error!(
                         error = %err,
-                        "Failed to send Windows toast notification; falling back to OSC 9"
+                        "Failed to send {kind:?} notification; falling back to OSC 9"
                     );
                     self.notification_backend = Some(DesktopNotificationBackend::osc9());
                     if let Some(backend) = self.notification_backend.as_mut() {
                         if let Err(osc_err) = backend.notify(&message) {
                             tracing::warn!(
                                 error = %osc_err,
-                                "Failed to emit OSC 9 notification after toast fallback; \
+                                "Failed to emit OSC 9 notification after fallback; \
                                  disabling future notifications"
                             );
                             self.notification_backend = None;
@@ -378,7 +386,7 @@ impl Tui {
             if !self.pending_history_lines.is_empty() {
                 crate::insert_history::insert_history_lines(
                     terminal,
-                    self.pending_history_lines.clone(),
+                    crate::theme::apply_to_lines(self.pending_history_lines.clone()),
                 )?;
                 self.pending_history_lines.clear();
             }
@@ -398,6 +406,7 @@ impl Tui {
 
             terminal.draw(|frame| {
                 draw_fn(frame);
+                crate::theme::apply_to_buffer(frame.buffer_mut());
             })
         })?
     }
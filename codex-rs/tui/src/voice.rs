@@ -0,0 +1,273 @@
+//! Voice input for the composer, enabled with `[tui.voice]`.
+//!
+//! Codex does not link an audio stack. Recording runs an external command
+//! that writes a WAV file until it is interrupted, and the transcript comes
+//! from a local command such as whisper.cpp or from an OpenAI-compatible
+//! transcription API.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use codex_core::config::types::TuiVoice;
+use codex_core::default_client::create_client;
+use tempfile::TempDir;
+use tokio::process::Child;
+use tokio::process::Command;
+
+/// Replaced with the path of the recorded audio in command arguments.
+const FILE_PLACEHOLDER: &str = "{file}";
+const AUDIO_FILE_NAME: &str = "voice.wav";
+/// Size of a WAV header; files no larger than this hold no audio.
+const WAV_HEADER_LEN: u64 = 44;
+
+const DEFAULT_RECORD_COMMAND: &[&str] = &[
+    "sox",
+    "-q",
+    "-d",
+    "-c",
+    "1",
+    "-r",
+    "16000",
+    "-b",
+    "16",
+    FILE_PLACEHOLDER,
+];
+const DEFAULT_API_BASE_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_API_MODEL: &str = "whisper-1";
+const DEFAULT_API_KEY_ENV: &str = "OPENAI_API_KEY";
+
+/// Where the composer's voice input is in its record, transcribe cycle.
+#[derive(Default)]
+pub(crate) enum VoiceInput {
+    #[default]
+    Idle,
+    Recording(VoiceRecording),
+    Transcribing,
+}
+
+/// A recording in progress. Dropping it kills the recorder and deletes the
+/// audio recorded so far.
+pub(crate) struct VoiceRecording {
+    child: Child,
+    dir: TempDir,
+}
+
+/// Audio captured by a finished recording, deleted when dropped.
+pub(crate) struct RecordedAudio {
+    _dir: TempDir,
+    path: PathBuf,
+}
+
+impl VoiceRecording {
+    pub(crate) fn start(voice: &TuiVoice) -> Result<Self, String> {
+        let dir = tempfile::Builder::new()
+            .prefix("codex-voice")
+            .tempdir()
+            .map_err(|err| format!("Failed to create a directory for the recording: {err}"))?;
+        let argv = voice.record_command.clone().unwrap_or_else(|| {
+            DEFAULT_RECORD_COMMAND
+                .iter()
+                .map(|arg| (*arg).to_string())
+                .collect()
+        });
+        let child = command(&argv, &dir.path().join(AUDIO_FILE_NAME))?
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|err| format!("Failed to start recording with `{}`: {err}", argv[0]))?;
+        Ok(Self { child, dir })
+    }
+
+    /// Stops the recorder and returns the audio it wrote.
+    pub(crate) async fn finish(mut self) -> Result<RecordedAudio, String> {
+        interrupt(&mut self.child);
+        let output = self.child.wait_with_output().await;
+        let path = self.dir.path().join(AUDIO_FILE_NAME);
+        match tokio::fs::metadata(&path).await {
+            Ok(metadata) if metadata.len() > WAV_HEADER_LEN => Ok(RecordedAudio {
+                _dir: self.dir,
+                path,
+            }),
+            _ => {
+                let stderr = output
+                    .map(|output| String::from_utf8_lossy(&output.stderr).trim().to_string())
+                    .unwrap_or_default();
+                if stderr.is_empty() {
+                    Err("No audio was recorded.".to_string())
+                } else {
+                    Err(format!("No audio was recorded: {stderr}"))
+                }
+            }
+        }
+    }
+}
+
+/// Transcribes `audio` with the local command from config, or with the
+/// transcription API when no command is configured.
+pub(crate) async fn transcribe(voice: &TuiVoice, audio: &RecordedAudio) -> Result<String, String> {
+    let raw = match &voice.transcribe_command {
+        Some(argv) => transcribe_locally(argv, &audio.path).await?,
+        None => transcribe_with_api(voice, &audio.path).await?,
+    };
+    let transcript = normalize_transcript(&raw);
+    if transcript.is_empty() {
+        return Err("No speech was recognized.".to_string());
+    }
+    Ok(transcript)
+}
+
+async fn transcribe_locally(argv: &[String], audio: &Path) -> Result<String, String> {
+    let output = command(argv, audio)?
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|err| format!("Failed to run `{}`: {err}", argv[0]))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "`{}` failed ({}): {}",
+            argv[0],
+            output.status,
+            stderr.trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+async fn transcribe_with_api(voice: &TuiVoice, audio: &Path) -> Result<String, String> {
+    let key_env = voice.api_key_env.as_deref().unwrap_or(DEFAULT_API_KEY_ENV);
+    let api_key = std::env::var(key_env).map_err(|_| {
+        format!("Set {key_env} or tui.voice.transcribe_command to transcribe voice input.")
+    })?;
+    let base_url = voice
+        .api_base_url
+        .as_deref()
+        .unwrap_or(DEFAULT_API_BASE_URL)
+        .trim_end_matches('/');
+    let model = voice.api_model.as_deref().unwrap_or(DEFAULT_API_MODEL);
+    let audio = tokio::fs::read(audio)
+        .await
+        .map_err(|err| format!("Failed to read the recording: {err}"))?;
+
+    let boundary = format!(
+        "codex-voice-{:x}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default()
+    );
+    let response = create_client()
+        .post(format!("{base_url}/audio/transcriptions"))
+        .bearer_auth(api_key)
+        .header(
+            "Content-Type",
+            format!("multipart/form-data; boundary={boundary}"),
+        )
+        .body(multipart_body(&boundary, model, &audio))
+        .send()
+        .await
+        .map_err(|err| format!("Transcription request failed: {err}"))?;
+    let status = response.status();
+    let text = response
+        .text()
+        .await
+        .map_err(|err| format!("Failed to read the transcription: {err}"))?;
+    if !status.is_success() {
+        return Err(format!("Transcription failed ({status}): {}", text.trim()));
+    }
+    Ok(text)
+}
+
+/// Builds a `multipart/form-data` body asking for a plain-text transcript.
+fn multipart_body(boundary: &str, model: &str, audio: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(audio.len() + 512);
+    for (name, value) in [("model", model), ("response_format", "text")] {
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{AUDIO_FILE_NAME}\"\r\nContent-Type: audio/wav\r\n\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(audio);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    body
+}
+
+fn command(argv: &[String], audio: &Path) -> Result<Command, String> {
+    let Some((program, args)) = argv.split_first() else {
+        return Err("Voice input command in [tui.voice] is empty.".to_string());
+    };
+    let audio = audio.to_string_lossy();
+    let mut command = Command::new(program);
+    command.args(args.iter().map(|arg| arg.replace(FILE_PLACEHOLDER, &audio)));
+    Ok(command)
+}
+
+/// Joins the transcript into one line; whisper.cpp prints one line per
+/// segment with leading spaces.
+fn normalize_transcript(raw: &str) -> String {
+    raw.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Asks the recorder to stop so it can finish writing the WAV header.
+#[cfg(unix)]
+fn interrupt(child: &mut Child) {
+    if let Some(pid) = child.id() {
+        unsafe { libc::kill(pid as libc::pid_t, libc::SIGINT) };
+    }
+}
+
+#[cfg(not(unix))]
+fn interrupt(child: &mut Child) {
+    let _ = child.start_kill();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn command_substitutes_audio_path() {
+        let argv = ["whisper-cli", "-nt", "-f", FILE_PLACEHOLDER].map(String::from);
+        let cmd = command(&argv, Path::new("/tmp/voice.wav")).expect("command");
+        let args: Vec<String> = cmd
+            .as_std()
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(args, vec!["-nt", "-f", "/tmp/voice.wav"]);
+        assert!(command(&[], Path::new("/tmp/voice.wav")).is_err());
+    }
+
+    #[test]
+    fn transcript_is_joined_into_one_line() {
+        assert_eq!(
+            normalize_transcript(" Fix the failing\n test in parser.rs \n"),
+            "Fix the failing test in parser.rs"
+        );
+    }
+
+    #[test]
+    fn multipart_body_wraps_fields_and_audio() {
+        let body = multipart_body("b", "whisper-1", b"RIFF");
+        let body = String::from_utf8(body).expect("utf8 body");
+        assert!(body.starts_with(
+            "--b\r\nContent-Disposition: form-data; name=\"model\"\r\n\r\nwhisper-1\r\n"
+        ));
+        assert!(body.contains("name=\"response_format\"\r\n\r\ntext\r\n"));
+        assert!(body.ends_with("Content-Type: audio/wav\r\n\r\nRIFF\r\n--b--\r\n"));
+    }
+}
@@ -461,20 +461,36 @@ fn exec_options(
     .chain(
         proposed_execpolicy_amendment
             .filter(|_| features.enabled(Feature::ExecPolicy))
-            .map(|prefix| {
+            .into_iter()
+            .flat_map(|prefix| {
                 let rendered_prefix = strip_bash_lc_and_escape(prefix.command());
-                ApprovalOption {
-                    label: format!(
-                        "Yes, and don't ask again for commands that start with `{rendered_prefix}`"
-                    ),
-                    decision: ApprovalDecision::Review(
-                        ReviewDecision::ApprovedExecpolicyAmendment {
-                            proposed_execpolicy_amendment: prefix,
-                        },
-                    ),
-                    display_shortcut: None,
-                    additional_shortcuts: vec![key_hint::plain(KeyCode::Char('p'))],
-                }
+                let project_prefix = prefix.command().to_vec();
+                [
+                    ApprovalOption {
+                        label: format!(
+                            "Yes, and don't ask again for commands that start with `{rendered_prefix}`"
+                        ),
+                        decision: ApprovalDecision::Review(
+                            ReviewDecision::ApprovedExecpolicyAmendment {
+                                proposed_execpolicy_amendment: prefix,
+                            },
+                        ),
+                        display_shortcut: None,
+                        additional_shortcuts: vec![key_hint::plain(KeyCode::Char('p'))],
+                    },
+                    ApprovalOption {
+                        label: format!(
+                            "Yes, and allow commands that start with `{rendered_prefix}` in this project"
+                        ),
+                        decision: ApprovalDecision::Review(
+                            ReviewDecision::ApprovedPrefixForProject {
+                                prefix: project_prefix,
+                            },
+                        ),
+                        display_shortcut: None,
+                        additional_shortcuts: vec![key_hint::plain(KeyCode::Char('a'))],
+                    },
+                ]
             }),
     )
     .chain([ApprovalOption {
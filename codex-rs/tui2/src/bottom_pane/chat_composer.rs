@@ -44,6 +44,8 @@ use crate::slash_command::SlashCommand;
 use crate::slash_command::built_in_slash_commands;
 use crate::style::user_message_style;
 use codex_common::fuzzy_match::fuzzy_match;
+use codex_core::custom_prompts::expand_command;
+use codex_protocol::custom_prompts::COMMAND_ARGUMENTS_PLACEHOLDER;
 use codex_protocol::custom_prompts::CustomPrompt;
 use codex_protocol::custom_prompts::PROMPTS_CMD_PREFIX;
 
@@ -72,6 +74,8 @@ const LARGE_PASTE_CHAR_THRESHOLD: usize = 1000;
 #[derive(Debug, PartialEq)]
 pub enum InputResult {
     Submitted(String),
+    /// Text typed while a task runs and sent with Esc, to redirect it.
+    Steered(String),
     Command(SlashCommand),
     None,
 }
@@ -107,6 +111,8 @@ pub(crate) struct ChatComposer {
     large_paste_counters: HashMap<usize, usize>,
     has_focus: bool,
     attached_images: Vec<AttachedImage>,
+    /// Paths picked from the `@` popup; their contents go with the message.
+    mentioned_files: Vec<String>,
     placeholder_text: String,
     is_task_running: bool,
     // Non-bracketed paste burst tracker.
@@ -114,6 +120,7 @@ pub(crate) struct ChatComposer {
     // When true, disables paste-burst logic and inserts characters immediately.
     disable_paste_burst: bool,
     custom_prompts: Vec<CustomPrompt>,
+    custom_commands: Vec<CustomPrompt>,
     footer_mode: FooterMode,
     footer_hint_override: Option<Vec<(String, String)>>,
     context_window_percent: Option<i64>,
@@ -160,11 +167,13 @@ impl ChatComposer {
             large_paste_counters: HashMap::new(),
             has_focus: has_input_focus,
             attached_images: Vec::new(),
+            mentioned_files: Vec::new(),
             placeholder_text,
             is_task_running: false,
             paste_burst: PasteBurst::default(),
             disable_paste_burst: false,
             custom_prompts: Vec::new(),
+            custom_commands: Vec::new(),
             footer_mode: FooterMode::ShortcutSummary,
             footer_hint_override: None,
             context_window_percent: None,
@@ -300,6 +309,7 @@ impl ChatComposer {
         self.textarea.set_text("");
         self.pending_pastes.clear();
         self.attached_images.clear();
+        self.mentioned_files.clear();
         self.textarea.set_text(&text);
         self.textarea.set_cursor(0);
         self.sync_popups();
@@ -340,6 +350,14 @@ impl ChatComposer {
         images.into_iter().map(|img| img.path).collect()
     }
 
+    /// Files picked from the `@` popup whose paths are still in the submitted text.
+    pub(crate) fn take_recent_submission_mentions(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.mentioned_files)
+            .into_iter()
+            .map(PathBuf::from)
+            .collect()
+    }
+
     pub(crate) fn flush_paste_burst_if_due(&mut self) -> bool {
         self.handle_paste_burst_flush(Instant::now())
     }
@@ -506,6 +524,13 @@ impl ChatComposer {
                                 }
                             }
                         }
+                        CommandItem::UserCommand(idx) => {
+                            if let Some(command) = popup.command(idx) {
+                                let text = format!("/{} ", command.name);
+                                cursor_target = Some(text.len());
+                                self.textarea.set_text(&text);
+                            }
+                        }
                     }
                     if let Some(pos) = cursor_target {
                         self.textarea.set_cursor(pos);
@@ -559,6 +584,27 @@ impl ChatComposer {
                             }
                             return (InputResult::None, true);
                         }
+                        CommandItem::UserCommand(idx) => {
+                            if let Some(command) = popup.command(idx) {
+                                let typed = parse_slash_name(self.textarea.text())
+                                    .filter(|(name, _)| *name == command.name)
+                                    .map(|(_, rest)| rest.trim_end().to_string());
+                                // Commands that take arguments stay in the composer
+                                // until the full name has been typed.
+                                if typed.is_none()
+                                    && command.content.contains(COMMAND_ARGUMENTS_PLACEHOLDER)
+                                {
+                                    let text = format!("/{} ", command.name);
+                                    self.textarea.set_text(&text);
+                                    self.textarea.set_cursor(text.len());
+                                    return (InputResult::None, true);
+                                }
+                                let text = expand_command(command, &typed.unwrap_or_default());
+                                self.textarea.set_text("");
+                                return (InputResult::Submitted(text), true);
+                            }
+                            return (InputResult::None, true);
+                        }
                     }
                 }
                 // Fallback to default newline handling if no command selected.
@@ -981,6 +1027,13 @@ impl ChatComposer {
         self.textarea.set_text(&new_text);
         let new_cursor = start_idx.saturating_add(inserted.len()).saturating_add(1);
         self.textarea.set_cursor(new_cursor);
+        if !self
+            .mentioned_files
+            .iter()
+            .any(|mentioned| mentioned == path)
+        {
+            self.mentioned_files.push(path.to_string());
+        }
     }
 
     fn insert_selected_skill(&mut self, skill_name: &str) {
@@ -1170,7 +1223,8 @@ impl ChatComposer {
                                     .any(|prompt| prompt.name == prompt_name)
                             })
                             .unwrap_or(false);
-                        if !is_builtin && !is_known_prompt {
+                        let is_known_command = self.custom_command(name).is_some();
+                        if !is_builtin && !is_known_prompt && !is_known_command {
                             let message = format!(
                                 r#"Unrecognized command '/{name}'. Type "/" for a list of supported commands."#
                             );
@@ -1184,6 +1238,13 @@ impl ChatComposer {
                     }
                 }
 
+                if !input_starts_with_space
+                    && let Some((name, rest)) = parse_slash_name(&text)
+                    && let Some(command) = self.custom_command(name)
+                {
+                    text = expand_command(command, rest);
+                }
+
                 let expanded_prompt = match expand_custom_prompt(&text, &self.custom_prompts) {
                     Ok(expanded) => expanded,
                     Err(err) => {
@@ -1204,6 +1265,8 @@ impl ChatComposer {
                 if !text.is_empty() {
                     self.history.record_local_submission(&text);
                 }
+                self.mentioned_files
+                    .retain(|path| text.contains(path.as_str()));
                 // Do not clear attached_images here; ChatWidget drains them via take_recent_submission_images().
                 (InputResult::Submitted(text), true)
             }
@@ -1639,7 +1702,7 @@ impl ChatComposer {
     }
 
     /// Heuristic for whether the typed slash command looks like a valid
-    /// prefix for any known command (built-in or custom prompt).
+    /// prefix for any known command (built-in, custom prompt or custom command).
     /// Empty names only count when there is no extra content after the '/'.
     fn looks_like_slash_prefix(&self, name: &str, rest_after_name: &str) -> bool {
         if name.is_empty() {
@@ -1654,6 +1717,14 @@ impl ChatComposer {
             return true;
         }
 
+        if self
+            .custom_commands
+            .iter()
+            .any(|c| fuzzy_match(&c.name, name).is_some())
+        {
+            return true;
+        }
+
         let prompt_prefix = format!("{PROMPTS_CMD_PREFIX}:");
         self.custom_prompts
             .iter()
@@ -1703,6 +1774,7 @@ impl ChatComposer {
                     let skills_enabled = self.skills_enabled();
                     let mut command_popup =
                         CommandPopup::new(self.custom_prompts.clone(), skills_enabled);
+                    command_popup.set_commands(self.custom_commands.clone());
                     command_popup.on_composer_text_change(first_line.to_string());
                     self.active_popup = ActivePopup::Command(command_popup);
                 }
@@ -1717,6 +1789,25 @@ impl ChatComposer {
         }
     }
 
+    pub(crate) fn set_custom_commands(&mut self, commands: Vec<CustomPrompt>) {
+        self.custom_commands = commands.clone();
+        if let ActivePopup::Command(popup) = &mut self.active_popup {
+            popup.set_commands(commands);
+        }
+    }
+
+    /// The custom command invoked as `/name`. Built-in commands take
+    /// precedence over custom commands with the same name.
+    fn custom_command(&self, name: &str) -> Option<&CustomPrompt> {
+        let is_builtin = built_in_slash_commands()
+            .into_iter()
+            .any(|(command_name, _)| command_name == name);
+        if is_builtin {
+            return None;
+        }
+        self.custom_commands.iter().find(|c| c.name == name)
+    }
+
     /// Synchronize `self.file_search_popup` with the current text in the textarea.
     /// Note this is only called when self.active_popup is NOT Command.
     fn sync_file_search_popup(&mut self, query: String) {
@@ -2625,7 +2716,7 @@ mod tests {
                 Some(CommandItem::Builtin(cmd)) => {
                     assert_eq!(cmd.command(), "model")
                 }
-                Some(CommandItem::UserPrompt(_)) => {
+                Some(CommandItem::UserPrompt(_) | CommandItem::UserCommand(_)) => {
                     panic!("unexpected prompt selected for '/mo'")
                 }
                 None => panic!("no selected command for '/mo'"),
@@ -2681,7 +2772,7 @@ mod tests {
                 Some(CommandItem::Builtin(cmd)) => {
                     assert_eq!(cmd.command(), "resume")
                 }
-                Some(CommandItem::UserPrompt(_)) => {
+                Some(CommandItem::UserPrompt(_) | CommandItem::UserCommand(_)) => {
                     panic!("unexpected prompt selected for '/res'")
                 }
                 None => panic!("no selected command for '/res'"),
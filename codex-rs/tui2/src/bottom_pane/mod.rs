@@ -183,6 +183,16 @@ impl BottomPane {
             self.request_redraw();
             InputResult::None
         } else {
+            // With a correction typed while a task runs, Esc steers the task:
+            // the response in flight restarts with the correction.
+            if matches!(key_event.code, crossterm::event::KeyCode::Esc) && self.is_task_running {
+                let correction = self.composer.current_text().trim().to_string();
+                if !correction.is_empty() {
+                    self.composer.set_text_content(String::new());
+                    self.request_redraw();
+                    return InputResult::Steered(correction);
+                }
+            }
             // If a task is running and a status line is visible, allow Esc to
             // send an interrupt even while the composer has focus.
             if matches!(key_event.code, crossterm::event::KeyCode::Esc)
@@ -410,6 +420,11 @@ impl BottomPane {
         self.request_redraw();
     }
 
+    pub(crate) fn set_custom_commands(&mut self, commands: Vec<CustomPrompt>) {
+        self.composer.set_custom_commands(commands);
+        self.request_redraw();
+    }
+
     pub(crate) fn composer_is_empty(&self) -> bool {
         self.composer.is_empty()
     }
@@ -526,6 +541,10 @@ impl BottomPane {
         self.composer.take_recent_submission_images()
     }
 
+    pub(crate) fn take_recent_submission_mentions(&mut self) -> Vec<PathBuf> {
+        self.composer.take_recent_submission_mentions()
+    }
+
     fn as_renderable(&'_ self) -> RenderableItem<'_> {
         if let Some(view) = self.active_view() {
             RenderableItem::Borrowed(view)
@@ -44,6 +44,7 @@ use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::RateLimitSnapshot;
+use codex_core::protocol::RetryScheduledEvent;
 use codex_core::protocol::ReviewRequest;
 use codex_core::protocol::ReviewTarget;
 use codex_core::protocol::SkillsListEntry;
@@ -340,6 +341,7 @@ pub(crate) struct ChatWidget {
 struct UserMessage {
     text: String,
     image_paths: Vec<PathBuf>,
+    mentioned_files: Vec<PathBuf>,
 }
 
 impl From<String> for UserMessage {
@@ -347,6 +349,7 @@ impl From<String> for UserMessage {
         Self {
             text,
             image_paths: Vec::new(),
+            mentioned_files: Vec::new(),
         }
     }
 }
@@ -356,6 +359,7 @@ impl From<&str> for UserMessage {
         Self {
             text: text.to_string(),
             image_paths: Vec::new(),
+            mentioned_files: Vec::new(),
         }
     }
 }
@@ -364,7 +368,11 @@ fn create_initial_user_message(text: String, image_paths: Vec<PathBuf>) -> Optio
     if text.is_empty() && image_paths.is_empty() {
         None
     } else {
-        Some(UserMessage { text, image_paths })
+        Some(UserMessage {
+            text,
+            image_paths,
+            mentioned_files: Vec::new(),
+        })
     }
 }
 
@@ -769,7 +777,11 @@ impl ChatWidget {
         // Finalize, log a gentle prompt, and clear running state.
         self.finalize_turn();
 
-        if reason != TurnAbortReason::ReviewEnded {
+        // A timeout was already explained by its `TurnTimedOut` event.
+        if !matches!(
+            reason,
+            TurnAbortReason::ReviewEnded | TurnAbortReason::TimedOut
+        ) {
             self.add_to_history(history_cell::new_error_event(
                 "Conversation interrupted - tell the model what to do differently. Something went wrong? Hit `/feedback` to report the issue.".to_owned(),
             ));
@@ -1486,6 +1498,17 @@ impl ChatWidget {
                     self.request_redraw();
                 }
             }
+            KeyEvent {
+                code: KeyCode::Down,
+                modifiers: KeyModifiers::ALT,
+                kind: KeyEventKind::Press,
+                ..
+            } if !self.queued_user_messages.is_empty() => {
+                // Drop the most recently queued item without sending it.
+                self.queued_user_messages.pop_back();
+                self.refresh_queued_user_messages();
+                self.request_redraw();
+            }
             _ => {
                 match self.bottom_pane.handle_key_event(key_event) {
                     InputResult::Submitted(text) => {
@@ -1493,9 +1516,11 @@ impl ChatWidget {
                         let user_message = UserMessage {
                             text,
                             image_paths: self.bottom_pane.take_recent_submission_images(),
+                            mentioned_files: self.bottom_pane.take_recent_submission_mentions(),
                         };
                         self.queue_user_message(user_message);
                     }
+                    InputResult::Steered(text) => self.steer_running_task(text),
                     InputResult::Command(cmd) => {
                         self.dispatch_command(cmd);
                     }
@@ -1658,6 +1683,7 @@ impl ChatWidget {
                         ]),
                         reason: None,
                         grant_root: Some(PathBuf::from("/tmp")),
+                        proposed_permissions: Vec::new(),
                     }),
                 }));
             }
@@ -1715,8 +1741,24 @@ impl ChatWidget {
         }
     }
 
+    /// Sends `text` as a correction to the running task: core cancels the
+    /// response in flight and restarts it with the correction.
+    fn steer_running_task(&mut self, text: String) {
+        self.flush_answer_stream_with_separator();
+        self.submit_op(Op::Steer {
+            items: vec![UserInput::Text { text: text.clone() }],
+        });
+        self.add_to_history(history_cell::new_user_prompt(text));
+        self.needs_final_message_separator = false;
+        self.request_redraw();
+    }
+
     fn submit_user_message(&mut self, user_message: UserMessage) {
-        let UserMessage { text, image_paths } = user_message;
+        let UserMessage {
+            text,
+            image_paths,
+            mentioned_files,
+        } = user_message;
         if text.is_empty() && image_paths.is_empty() {
             return;
         }
@@ -1749,6 +1791,10 @@ impl ChatWidget {
             items.push(UserInput::LocalImage { path });
         }
 
+        for path in mentioned_files {
+            items.push(UserInput::FileMention { path });
+        }
+
         if let Some(skills) = self.bottom_pane.skills() {
             let skill_mentions = find_skill_mentions(&text, skills);
             for skill in skill_mentions {
@@ -1807,7 +1853,8 @@ impl ChatWidget {
     /// `replay_initial_messages()`. Callers should treat `None` as a "fake" id
     /// that must not be used to correlate follow-up actions.
     fn dispatch_event_msg(&mut self, id: Option<String>, msg: EventMsg, from_replay: bool) {
-        let is_stream_error = matches!(&msg, EventMsg::StreamError(_));
+        let is_stream_error =
+            matches!(&msg, EventMsg::StreamError(_) | EventMsg::RetryScheduled(_));
         if !is_stream_error {
             self.restore_retry_status_header_if_present();
         }
@@ -1857,7 +1904,7 @@ impl ChatWidget {
                 TurnAbortReason::Replaced => {
                     self.on_error("Turn aborted: replaced by a new task".to_owned())
                 }
-                TurnAbortReason::ReviewEnded => {
+                TurnAbortReason::ReviewEnded | TurnAbortReason::TimedOut => {
                     self.on_interrupted_turn(ev.reason);
                 }
             },
@@ -1901,7 +1948,8 @@ impl ChatWidget {
             }
             EventMsg::UndoStarted(ev) => self.on_undo_started(ev),
             EventMsg::UndoCompleted(ev) => self.on_undo_completed(ev),
-            EventMsg::StreamError(StreamErrorEvent { message, .. }) => {
+            EventMsg::StreamError(StreamErrorEvent { message, .. })
+            | EventMsg::RetryScheduled(RetryScheduledEvent { message, .. }) => {
                 self.on_stream_error(message)
             }
             EventMsg::UserMessage(ev) => {
@@ -1919,7 +1967,20 @@ impl ChatWidget {
             | EventMsg::ItemCompleted(_)
             | EventMsg::AgentMessageContentDelta(_)
             | EventMsg::ReasoningContentDelta(_)
-            | EventMsg::ReasoningRawContentDelta(_) => {}
+            | EventMsg::ReasoningRawContentDelta(_)
+            | EventMsg::PinnedContextUpdated(_)
+            | EventMsg::SessionDiff(_)
+            | EventMsg::SecretsRedacted(_)
+            | EventMsg::BudgetExceeded(_)
+            | EventMsg::ContextItemsDropped(_)
+            | EventMsg::ReadOnlyModeChanged(_)
+            | EventMsg::SubAgent(_) => {}
+            EventMsg::TurnTimedOut(ev) => self.on_error(ev.message),
+            EventMsg::ToolCallLoopDetected(ev) => self.on_warning(ev.message),
+            EventMsg::ContextPressure(ev) => self.on_warning(format!(
+                "Context window is {}% full ({} of {} tokens).",
+                ev.threshold_percent, ev.used_tokens, ev.context_window
+            )),
         }
     }
 
@@ -3047,7 +3108,9 @@ impl ChatWidget {
             return;
         }
 
-        self.submit_op(Op::Shutdown);
+        // The user is waiting to get their terminal back, so do not let
+        // in-flight tool calls hold up the exit.
+        self.submit_op(Op::Quit);
     }
 
     pub(crate) fn composer_is_empty(&self) -> bool {
@@ -3122,9 +3185,11 @@ impl ChatWidget {
 
     fn on_list_custom_prompts(&mut self, ev: ListCustomPromptsResponseEvent) {
         let len = ev.custom_prompts.len();
-        debug!("received {len} custom prompts");
+        let commands_len = ev.custom_commands.len();
+        debug!("received {len} custom prompts and {commands_len} custom commands");
         // Forward to bottom pane so the slash popup can show them now.
         self.bottom_pane.set_custom_prompts(ev.custom_prompts);
+        self.bottom_pane.set_custom_commands(ev.custom_commands);
     }
 
     fn on_list_skills(&mut self, ev: ListSkillsResponseEvent) {
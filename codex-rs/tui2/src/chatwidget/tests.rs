@@ -204,6 +204,8 @@ async fn review_restores_context_window_indicator() {
         msg: EventMsg::TokenCount(TokenCountEvent {
             info: Some(make_token_info(pre_review_tokens, context_window)),
             rate_limits: None,
+            pinned_context_tokens: None,
+            spend: None,
         }),
     });
     assert_eq!(chat.bottom_pane.context_window_percent(), Some(30));
@@ -223,6 +225,8 @@ async fn review_restores_context_window_indicator() {
         msg: EventMsg::TokenCount(TokenCountEvent {
             info: Some(make_token_info(review_tokens, context_window)),
             rate_limits: None,
+            pinned_context_tokens: None,
+            spend: None,
         }),
     });
     assert_eq!(chat.bottom_pane.context_window_percent(), Some(97));
@@ -252,6 +256,8 @@ async fn token_count_none_resets_context_indicator() {
         msg: EventMsg::TokenCount(TokenCountEvent {
             info: Some(make_token_info(pre_compact_tokens, context_window)),
             rate_limits: None,
+            pinned_context_tokens: None,
+            spend: None,
         }),
     });
     assert_eq!(chat.bottom_pane.context_window_percent(), Some(30));
@@ -261,6 +267,8 @@ async fn token_count_none_resets_context_indicator() {
         msg: EventMsg::TokenCount(TokenCountEvent {
             info: None,
             rate_limits: None,
+            pinned_context_tokens: None,
+            spend: None,
         }),
     });
     assert_eq!(chat.bottom_pane.context_window_percent(), None);
@@ -291,6 +299,8 @@ async fn context_indicator_shows_used_tokens_when_window_unknown() {
         msg: EventMsg::TokenCount(TokenCountEvent {
             info: Some(token_info),
             rate_limits: None,
+            pinned_context_tokens: None,
+            spend: None,
         }),
     });
 
@@ -720,6 +730,7 @@ async fn exec_approval_emits_proposed_command_and_decision_history() {
         ),
         proposed_execpolicy_amendment: None,
         parsed_cmd: vec![],
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-short".into(),
@@ -764,6 +775,7 @@ async fn exec_approval_decision_truncates_multiline_and_long_commands() {
         ),
         proposed_execpolicy_amendment: None,
         parsed_cmd: vec![],
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-multi".into(),
@@ -814,6 +826,7 @@ async fn exec_approval_decision_truncates_multiline_and_long_commands() {
         reason: None,
         proposed_execpolicy_amendment: None,
         parsed_cmd: vec![],
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-long".into(),
@@ -1042,8 +1055,8 @@ async fn ctrl_c_shutdown_ignores_caps_lock() {
     chat.handle_key_event(KeyEvent::new(KeyCode::Char('C'), KeyModifiers::CONTROL));
 
     match op_rx.try_recv() {
-        Ok(Op::Shutdown) => {}
-        other => panic!("expected Op::Shutdown, got {other:?}"),
+        Ok(Op::Quit) => {}
+        other => panic!("expected Op::Quit, got {other:?}"),
     }
 }
 
@@ -2065,6 +2078,7 @@ async fn approval_modal_exec_snapshot() {
             "world".into(),
         ])),
         parsed_cmd: vec![],
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-approve".into(),
@@ -2116,6 +2130,7 @@ async fn approval_modal_exec_without_reason_snapshot() {
             "world".into(),
         ])),
         parsed_cmd: vec![],
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-approve-noreason".into(),
@@ -2156,6 +2171,7 @@ async fn approval_modal_patch_snapshot() {
         changes,
         reason: Some("The model wants to apply changes".into()),
         grant_root: Some(PathBuf::from("/tmp")),
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-approve-patch".into(),
@@ -2333,6 +2349,7 @@ async fn status_widget_and_approval_modal_snapshot() {
             "hello world".into(),
         ])),
         parsed_cmd: vec![],
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-approve-exec".into(),
@@ -2436,6 +2453,7 @@ async fn apply_patch_events_emit_history_cells() {
         changes,
         reason: None,
         grant_root: None,
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "s1".into(),
@@ -2535,6 +2553,7 @@ async fn apply_patch_manual_approval_adjusts_header() {
             changes: proposed_changes,
             reason: None,
             grant_root: None,
+            proposed_permissions: Vec::new(),
         }),
     });
     drain_insert_history(&mut rx);
@@ -2584,6 +2603,7 @@ async fn apply_patch_manual_flow_snapshot() {
             changes: proposed_changes,
             reason: Some("Manual review required".into()),
             grant_root: None,
+            proposed_permissions: Vec::new(),
         }),
     });
     let history_before_apply = drain_insert_history(&mut rx);
@@ -2635,6 +2655,7 @@ async fn apply_patch_approval_sends_op_with_submission_id() {
         changes,
         reason: None,
         grant_root: None,
+        proposed_permissions: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "sub-123".into(),
@@ -2675,6 +2696,7 @@ async fn apply_patch_full_flow_integration_like() {
             changes,
             reason: None,
             grant_root: None,
+            proposed_permissions: Vec::new(),
         }),
     });
 
@@ -2755,6 +2777,7 @@ async fn apply_patch_untrusted_shows_approval_modal() {
             changes,
             reason: None,
             grant_root: None,
+            proposed_permissions: Vec::new(),
         }),
     });
 
@@ -2804,6 +2827,7 @@ async fn apply_patch_request_shows_diff_summary() {
             changes,
             reason: None,
             grant_root: None,
+            proposed_permissions: Vec::new(),
         }),
     });
 
@@ -422,6 +422,19 @@ pub fn new_approval_decision_cell(
                 ],
             )
         }
+        ApprovedPrefixForProject { .. } => {
+            let snippet = Span::from(exec_snippet(&command)).dim();
+            (
+                "✔ ".green(),
+                vec![
+                    "You ".into(),
+                    "approved".bold(),
+                    " codex to run ".into(),
+                    snippet,
+                    " and commands like it in this project".bold(),
+                ],
+            )
+        }
         ApprovedForSession => {
             let snippet = Span::from(exec_snippet(&command)).dim();
             (
@@ -435,7 +448,29 @@ pub fn new_approval_decision_cell(
                 ],
             )
         }
-        Denied => {
+        ApprovedPermissions { permissions, scope } => {
+            let snippet = Span::from(exec_snippet(&command)).dim();
+            let granted = permissions
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            let scope = match scope {
+                codex_core::protocol::PermissionScope::Session => "this session",
+                codex_core::protocol::PermissionScope::Project => "this project",
+            };
+            (
+                "✔ ".green(),
+                vec![
+                    "You ".into(),
+                    "approved".bold(),
+                    " codex to run ".into(),
+                    snippet,
+                    format!(" and allowed {granted} in {scope}").bold(),
+                ],
+            )
+        }
+        Denied | PartiallyApproved { .. } => {
             let snippet = Span::from(exec_snippet(&command)).dim();
             (
                 "✗ ".red(),
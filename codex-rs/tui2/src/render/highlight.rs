@@ -2,6 +2,8 @@ use ratatui::style::Style;
 use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::text::Span;
+use std::ops::Range;
+use std::path::Path;
 use std::sync::OnceLock;
 use tree_sitter_highlight::Highlight;
 use tree_sitter_highlight::HighlightConfiguration;
@@ -143,6 +145,152 @@ pub(crate) fn highlight_bash_to_lines(script: &str) -> Vec<Line<'static>> {
     }
 }
 
+/// Languages the diff renderer highlights, picked from a file extension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CodeLanguage {
+    C,
+    Go,
+    JavaScript,
+    Python,
+    Rust,
+    Shell,
+}
+
+impl CodeLanguage {
+    pub(crate) fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        Some(match extension.as_str() {
+            "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "java" | "cs" | "swift" | "kt" => Self::C,
+            "go" => Self::Go,
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" => Self::JavaScript,
+            "py" | "pyi" => Self::Python,
+            "rs" => Self::Rust,
+            "sh" | "bash" | "zsh" => Self::Shell,
+            _ => return None,
+        })
+    }
+
+    fn line_comments(self) -> &'static [&'static str] {
+        match self {
+            Self::Python | Self::Shell => &["#"],
+            Self::C | Self::Go | Self::JavaScript | Self::Rust => &["//"],
+        }
+    }
+
+    fn has_block_comments(self) -> bool {
+        !matches!(self, Self::Python | Self::Shell)
+    }
+
+    fn quotes(self) -> &'static [char] {
+        match self {
+            // `'` also starts lifetimes in Rust, so only double quotes are strings.
+            Self::Rust => &['"'],
+            Self::Go | Self::JavaScript => &['"', '\'', '`'],
+            Self::C | Self::Python | Self::Shell => &['"', '\''],
+        }
+    }
+
+    /// Space-separated keywords.
+    fn keywords(self) -> &'static str {
+        match self {
+            Self::C => {
+                "break case class const continue default do else enum extern for if import \
+                namespace new private public return static struct switch template typedef void \
+                while"
+            }
+            Self::Go => {
+                "break case chan const continue default defer else for func go if import \
+                interface map package range return select struct switch type var"
+            }
+            Self::JavaScript => {
+                "async await break case class const continue default else export extends for from \
+                function if import interface let new return switch throw try type var while"
+            }
+            Self::Python => {
+                "and as async await class def elif else except for from if import in is lambda \
+                not or pass raise return try while with yield"
+            }
+            Self::Rust => {
+                "as async await const crate dyn else enum fn for if impl let loop match mod move \
+                mut pub return self Self static struct super trait type unsafe use where while"
+            }
+            Self::Shell => {
+                "case do done elif else esac export fi for function if in local then while"
+            }
+        }
+    }
+}
+
+/// Styles for the tokens of a single line of source code, as byte ranges.
+///
+/// This is a small lexer rather than a grammar: it recognizes comments,
+/// strings and keywords one line at a time, which is enough for diff hunks
+/// that rarely contain whole constructs. Styles only add modifiers so the
+/// diff's own colors stay visible.
+pub(crate) fn highlight_code_line(
+    line: &str,
+    language: CodeLanguage,
+) -> Vec<(Range<usize>, Style)> {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < line.len() {
+        let rest = &line[i..];
+        if language
+            .line_comments()
+            .iter()
+            .any(|marker| rest.starts_with(marker))
+        {
+            tokens.push((i..line.len(), Style::default().dim()));
+            break;
+        }
+        if language.has_block_comments() && rest.starts_with("/*") {
+            let end = rest[2..]
+                .find("*/")
+                .map_or(line.len(), |offset| i + 2 + offset + 2);
+            tokens.push((i..end, Style::default().dim()));
+            i = end;
+            continue;
+        }
+        let Some(ch) = rest.chars().next() else {
+            break;
+        };
+        if language.quotes().contains(&ch) {
+            let mut end = line.len();
+            let mut escaped = false;
+            for (offset, c) in rest.char_indices().skip(1) {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == ch {
+                    end = i + offset + c.len_utf8();
+                    break;
+                }
+            }
+            tokens.push((i..end, Style::default().italic()));
+            i = end;
+            continue;
+        }
+        if ch.is_alphanumeric() || ch == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let word = &rest[..len];
+            if language
+                .keywords()
+                .split_whitespace()
+                .any(|keyword| keyword == word)
+            {
+                tokens.push((i..i + len, Style::default().bold()));
+            }
+            i += len;
+            continue;
+        }
+        i += ch.len_utf8();
+    }
+    tokens
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -219,6 +367,37 @@ mod tests {
         assert!(string_style.add_modifier.contains(Modifier::DIM));
     }
 
+    #[test]
+    fn highlights_code_comments_strings_and_keywords() {
+        let line = r#"let name = "fn"; // pub"#;
+        let tokens = highlight_code_line(line, CodeLanguage::Rust);
+        let styled: Vec<(&str, Style)> = tokens
+            .into_iter()
+            .map(|(range, style)| (&line[range], style))
+            .collect();
+        assert_eq!(
+            styled,
+            vec![
+                ("let", Style::default().bold()),
+                ("\"fn\"", Style::default().italic()),
+                ("// pub", Style::default().dim()),
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_languages_from_extensions() {
+        assert_eq!(
+            CodeLanguage::from_path(Path::new("src/main.rs")),
+            Some(CodeLanguage::Rust)
+        );
+        assert_eq!(
+            CodeLanguage::from_path(Path::new("app/index.TSX")),
+            Some(CodeLanguage::JavaScript)
+        );
+        assert_eq!(CodeLanguage::from_path(Path::new("README.md")), None);
+    }
+
     #[test]
     fn highlights_heredoc_body_as_string() {
         let s = "cat <<EOF\nheredoc body\nEOF";
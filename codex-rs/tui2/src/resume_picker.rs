@@ -11,6 +11,10 @@ use codex_core::Cursor;
 use codex_core::INTERACTIVE_SESSION_SOURCES;
 use codex_core::RolloutRecorder;
 use codex_core::path_utils;
+use codex_core::protocol::TokenUsage;
+use codex_core::read_session_summary;
+use codex_core::read_token_usage;
+use codex_core::rollout_index_path;
 use codex_protocol::items::TurnItem;
 use color_eyre::eyre::Result;
 use crossterm::event::KeyCode;
@@ -29,12 +33,14 @@ use unicode_width::UnicodeWidthStr;
 
 use crate::diff_render::display_path_for;
 use crate::key_hint;
+use crate::status::format_tokens_compact;
 use crate::text_formatting::truncate_text;
 use crate::tui::FrameRequester;
 use crate::tui::Tui;
 use crate::tui::TuiEvent;
 use codex_protocol::models::ResponseItem;
 use codex_protocol::protocol::SessionMetaLine;
+use codex_protocol::protocol::SessionSummaryItem;
 
 const PAGE_SIZE: usize = 25;
 const LOAD_NEAR_THRESHOLD: usize = 5;
@@ -63,11 +69,21 @@ enum BackgroundEvent {
         search_token: Option<usize>,
         page: std::io::Result<ConversationsPage>,
     },
+    TokenUsageLoaded {
+        path: PathBuf,
+        usage: TokenUsage,
+    },
+    SummaryLoaded {
+        path: PathBuf,
+        summary: SessionSummaryItem,
+    },
 }
 
 /// Interactive session picker that lists recorded rollout files with simple
-/// search and pagination. Shows the first user input as the preview, relative
-/// time (e.g., "5 seconds ago"), and the absolute path.
+/// search and pagination. Shows the session's title (the first user input
+/// until one is recorded), relative time (e.g., "5 seconds ago"), token usage,
+/// and the absolute path, with the selected session's summary underneath.
+/// Sessions can be deleted from the list.
 pub async fn run_resume_picker(
     tui: &mut Tui,
     codex_home: &Path,
@@ -98,11 +114,28 @@ pub async fn run_resume_picker(
                 request.default_provider.as_str(),
             )
             .await;
+            let paths: Vec<PathBuf> = page
+                .as_ref()
+                .map(|page| page.items.iter().map(|item| item.path.clone()).collect())
+                .unwrap_or_default();
             let _ = tx.send(BackgroundEvent::PageLoaded {
                 request_token: request.request_token,
                 search_token: request.search_token,
                 page,
             });
+            // Token usage and the summary live at the end of each rollout, so
+            // they trail the page.
+            for path in paths {
+                if let Ok(Some(usage)) = read_token_usage(&path).await {
+                    let _ = tx.send(BackgroundEvent::TokenUsageLoaded {
+                        path: path.clone(),
+                        usage,
+                    });
+                }
+                if let Ok(Some(summary)) = read_session_summary(&path).await {
+                    let _ = tx.send(BackgroundEvent::SummaryLoaded { path, summary });
+                }
+            }
         });
     });
 
@@ -134,7 +167,7 @@ pub async fn run_resume_picker(
                     }
                     TuiEvent::Draw => {
                         if let Ok(size) = alt.tui.terminal.size() {
-                            let list_height = size.height.saturating_sub(4) as usize;
+                            let list_height = size.height.saturating_sub(5) as usize;
                             state.update_view_rows(list_height);
                             state.ensure_minimum_rows_for_view(list_height);
                         }
@@ -190,6 +223,10 @@ struct PickerState {
     default_provider: String,
     show_all: bool,
     filter_cwd: Option<PathBuf>,
+    /// Session awaiting a second delete key press.
+    pending_delete: Option<PathBuf>,
+    /// One-off message shown in place of the key hints.
+    notice: Option<String>,
 }
 
 struct PaginationState {
@@ -249,6 +286,17 @@ struct Row {
     updated_at: Option<DateTime<Utc>>,
     cwd: Option<PathBuf>,
     git_branch: Option<String>,
+    token_usage: Option<TokenUsage>,
+    summary: Option<SessionSummaryItem>,
+}
+
+impl Row {
+    /// The recorded title, or the first user input for sessions without one.
+    fn title(&self) -> &str {
+        self.summary
+            .as_ref()
+            .map_or(self.preview.as_str(), |summary| summary.title.as_str())
+    }
 }
 
 impl PickerState {
@@ -283,6 +331,8 @@ impl PickerState {
             default_provider,
             show_all,
             filter_cwd,
+            pending_delete: None,
+            notice: None,
         }
     }
 
@@ -291,6 +341,10 @@ impl PickerState {
     }
 
     async fn handle_key(&mut self, key: KeyEvent) -> Result<Option<ResumeSelection>> {
+        let confirmed_delete = self.pending_delete.take();
+        if self.notice.take().is_some() || confirmed_delete.is_some() {
+            self.request_frame();
+        }
         match key.code {
             KeyCode::Esc => return Ok(Some(ResumeSelection::StartFresh)),
             KeyCode::Char('c')
@@ -305,6 +359,14 @@ impl PickerState {
                     return Ok(Some(ResumeSelection::Resume(row.path.clone())));
                 }
             }
+            KeyCode::Delete => self.delete_selected(confirmed_delete).await,
+            KeyCode::Char('d')
+                if key
+                    .modifiers
+                    .contains(crossterm::event::KeyModifiers::CONTROL) =>
+            {
+                self.delete_selected(confirmed_delete).await;
+            }
             KeyCode::Up => {
                 if self.selected > 0 {
                     self.selected -= 1;
@@ -384,6 +446,35 @@ impl PickerState {
         });
     }
 
+    /// Deletes the selected session's rollout file. The first press only asks
+    /// for confirmation; pressing the key again on the same row deletes it.
+    async fn delete_selected(&mut self, confirmed: Option<PathBuf>) {
+        let Some(path) = self
+            .filtered_rows
+            .get(self.selected)
+            .map(|row| row.path.clone())
+        else {
+            return;
+        };
+        if confirmed.as_ref() != Some(&path) {
+            self.pending_delete = Some(path);
+            self.request_frame();
+            return;
+        }
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => {
+                // The index is only an accelerator; a leftover one is harmless.
+                let _ = tokio::fs::remove_file(rollout_index_path(&path)).await;
+                self.all_rows.retain(|row| row.path != path);
+                self.apply_filter();
+            }
+            Err(err) => {
+                self.notice = Some(format!("Failed to delete session: {err}"));
+                self.request_frame();
+            }
+        }
+    }
+
     fn handle_background_event(&mut self, event: BackgroundEvent) -> Result<()> {
         match event {
             BackgroundEvent::PageLoaded {
@@ -404,6 +495,25 @@ impl PickerState {
                 let completed_token = pending.search_token.or(search_token);
                 self.continue_search_if_token_matches(completed_token);
             }
+            BackgroundEvent::TokenUsageLoaded { path, usage } => {
+                for row in self
+                    .all_rows
+                    .iter_mut()
+                    .chain(self.filtered_rows.iter_mut())
+                {
+                    if row.path == path {
+                        row.token_usage = Some(usage.clone());
+                    }
+                }
+                self.request_frame();
+            }
+            BackgroundEvent::SummaryLoaded { path, summary } => {
+                if let Some(row) = self.all_rows.iter_mut().find(|row| row.path == path) {
+                    row.summary = Some(summary);
+                }
+                // The summary can make a row match the current search.
+                self.apply_filter();
+            }
         }
         Ok(())
     }
@@ -449,7 +559,7 @@ impl PickerState {
         } else {
             let q = self.query.to_lowercase();
             self.filtered_rows = base_iter
-                .filter(|r| r.preview.to_lowercase().contains(&q))
+                .filter(|r| self.row_matches_query(r, &q))
                 .cloned()
                 .collect();
         }
@@ -463,6 +573,26 @@ impl PickerState {
         self.request_frame();
     }
 
+    /// Matches the lowercased `query` against the preview, title, summary and
+    /// branch, and against the project directory when sessions from all
+    /// projects are shown.
+    fn row_matches_query(&self, row: &Row, query: &str) -> bool {
+        row.preview.to_lowercase().contains(query)
+            || row.summary.as_ref().is_some_and(|summary| {
+                summary.title.to_lowercase().contains(query)
+                    || summary.summary.to_lowercase().contains(query)
+            })
+            || row
+                .git_branch
+                .as_ref()
+                .is_some_and(|branch| branch.to_lowercase().contains(query))
+            || (self.show_all
+                && row
+                    .cwd
+                    .as_ref()
+                    .is_some_and(|cwd| cwd.to_string_lossy().to_lowercase().contains(query)))
+    }
+
     fn row_matches_filter(&self, row: &Row) -> bool {
         if self.show_all {
             return true;
@@ -656,6 +786,8 @@ fn head_to_row(item: &ConversationItem) -> Row {
         updated_at,
         cwd,
         git_branch,
+        token_usage: None,
+        summary: None,
     }
 }
 
@@ -708,11 +840,12 @@ fn draw_picker(tui: &mut Tui, state: &PickerState) -> std::io::Result<()> {
     let height = tui.terminal.size()?.height;
     tui.draw(height, |frame| {
         let area = frame.area();
-        let [header, search, columns, list, hint] = Layout::vertical([
+        let [header, search, columns, list, summary, hint] = Layout::vertical([
+            Constraint::Length(1),
             Constraint::Length(1),
             Constraint::Length(1),
+            Constraint::Min(area.height.saturating_sub(5)),
             Constraint::Length(1),
-            Constraint::Min(area.height.saturating_sub(4)),
             Constraint::Length(1),
         ])
         .areas(area);
@@ -737,23 +870,46 @@ fn draw_picker(tui: &mut Tui, state: &PickerState) -> std::io::Result<()> {
         render_column_headers(frame, columns, &metrics);
         render_list(frame, list, state, &metrics);
 
+        // Summary of the selected session
+        if let Some(text) = state
+            .filtered_rows
+            .get(state.selected)
+            .and_then(|row| row.summary.as_ref())
+            .map(|row_summary| truncate_text(&row_summary.summary, summary.width as usize))
+        {
+            frame.render_widget_ref(Line::from(text.dim()), summary);
+        }
+
         // Hint line
-        let hint_line: Line = vec![
-            key_hint::plain(KeyCode::Enter).into(),
-            " to resume ".dim(),
-            "    ".dim(),
-            key_hint::plain(KeyCode::Esc).into(),
-            " to start new ".dim(),
-            "    ".dim(),
-            key_hint::ctrl(KeyCode::Char('c')).into(),
-            " to quit ".dim(),
-            "    ".dim(),
-            key_hint::plain(KeyCode::Up).into(),
-            "/".dim(),
-            key_hint::plain(KeyCode::Down).into(),
-            " to browse".dim(),
-        ]
-        .into();
+        let hint_line: Line = if state.pending_delete.is_some() {
+            vec![
+                key_hint::plain(KeyCode::Delete).into(),
+                " again to delete this session, any other key to cancel".dim(),
+            ]
+            .into()
+        } else if let Some(notice) = &state.notice {
+            Line::from(notice.clone().red())
+        } else {
+            vec![
+                key_hint::plain(KeyCode::Enter).into(),
+                " to resume ".dim(),
+                "    ".dim(),
+                key_hint::plain(KeyCode::Esc).into(),
+                " to start new ".dim(),
+                "    ".dim(),
+                key_hint::ctrl(KeyCode::Char('c')).into(),
+                " to quit ".dim(),
+                "    ".dim(),
+                key_hint::plain(KeyCode::Up).into(),
+                "/".dim(),
+                key_hint::plain(KeyCode::Down).into(),
+                " to browse ".dim(),
+                "    ".dim(),
+                key_hint::plain(KeyCode::Delete).into(),
+                " to delete".dim(),
+            ]
+            .into()
+        };
         frame.render_widget_ref(hint_line, hint);
     })
 }
@@ -782,10 +938,11 @@ fn render_list(
     let mut y = area.y;
 
     let max_updated_width = metrics.max_updated_width;
+    let max_tokens_width = metrics.max_tokens_width;
     let max_branch_width = metrics.max_branch_width;
     let max_cwd_width = metrics.max_cwd_width;
 
-    for (idx, (row, (updated_label, branch_label, cwd_label))) in rows[start..end]
+    for (idx, (row, (updated_label, tokens_label, branch_label, cwd_label))) in rows[start..end]
         .iter()
         .zip(labels[start..end].iter())
         .enumerate()
@@ -798,6 +955,20 @@ fn render_list(
         } else {
             Some(Span::from(format!("{updated_label:<max_updated_width$}")).dim())
         };
+        let tokens_span = if max_tokens_width == 0 {
+            None
+        } else if tokens_label.is_empty() {
+            Some(
+                Span::from(format!(
+                    "{empty:>width$}",
+                    empty = "-",
+                    width = max_tokens_width
+                ))
+                .dim(),
+            )
+        } else {
+            Some(Span::from(format!("{tokens_label:>max_tokens_width$}")).dim())
+        };
         let branch_span = if max_branch_width == 0 {
             None
         } else if branch_label.is_empty() {
@@ -832,22 +1003,32 @@ fn render_list(
         if max_updated_width > 0 {
             preview_width = preview_width.saturating_sub(max_updated_width + 2);
         }
+        if max_tokens_width > 0 {
+            preview_width = preview_width.saturating_sub(max_tokens_width + 2);
+        }
         if max_branch_width > 0 {
             preview_width = preview_width.saturating_sub(max_branch_width + 2);
         }
         if max_cwd_width > 0 {
             preview_width = preview_width.saturating_sub(max_cwd_width + 2);
         }
-        let add_leading_gap = max_updated_width == 0 && max_branch_width == 0 && max_cwd_width == 0;
+        let add_leading_gap = max_updated_width == 0
+            && max_tokens_width == 0
+            && max_branch_width == 0
+            && max_cwd_width == 0;
         if add_leading_gap {
             preview_width = preview_width.saturating_sub(2);
         }
-        let preview = truncate_text(&row.preview, preview_width);
+        let preview = truncate_text(row.title(), preview_width);
         let mut spans: Vec<Span> = vec![marker];
         if let Some(updated) = updated_span {
             spans.push(updated);
             spans.push("  ".into());
         }
+        if let Some(tokens) = tokens_span {
+            spans.push(tokens);
+            spans.push("  ".into());
+        }
         if let Some(branch) = branch_span {
             spans.push(branch);
             spans.push("  ".into());
@@ -964,6 +1145,15 @@ fn render_column_headers(
         spans.push(Span::from(label).bold());
         spans.push("  ".into());
     }
+    if metrics.max_tokens_width > 0 {
+        let label = format!(
+            "{text:>width$}",
+            text = "Tokens",
+            width = metrics.max_tokens_width
+        );
+        spans.push(Span::from(label).bold());
+        spans.push("  ".into());
+    }
     if metrics.max_branch_width > 0 {
         let label = format!(
             "{text:<width$}",
@@ -988,9 +1178,11 @@ fn render_column_headers(
 
 struct ColumnMetrics {
     max_updated_width: usize,
+    /// Zero until token usage is known for at least one row.
+    max_tokens_width: usize,
     max_branch_width: usize,
     max_cwd_width: usize,
-    labels: Vec<(String, String, String)>,
+    labels: Vec<(String, String, String, String)>,
 }
 
 fn calculate_column_metrics(rows: &[Row], include_cwd: bool) -> ColumnMetrics {
@@ -1013,8 +1205,9 @@ fn calculate_column_metrics(rows: &[Row], include_cwd: bool) -> ColumnMetrics {
         format!("…{tail}")
     }
 
-    let mut labels: Vec<(String, String, String)> = Vec::with_capacity(rows.len());
+    let mut labels: Vec<(String, String, String, String)> = Vec::with_capacity(rows.len());
     let mut max_updated_width = UnicodeWidthStr::width("Updated");
+    let mut max_tokens_width = 0;
     let mut max_branch_width = UnicodeWidthStr::width("Branch");
     let mut max_cwd_width = if include_cwd {
         UnicodeWidthStr::width("CWD")
@@ -1024,6 +1217,11 @@ fn calculate_column_metrics(rows: &[Row], include_cwd: bool) -> ColumnMetrics {
 
     for row in rows {
         let updated = format_updated_label(row);
+        let tokens = row
+            .token_usage
+            .as_ref()
+            .map(|usage| format_tokens_compact(usage.blended_total()))
+            .unwrap_or_default();
         let branch_raw = row.git_branch.clone().unwrap_or_default();
         let branch = right_elide(&branch_raw, 24);
         let cwd = if include_cwd {
@@ -1037,13 +1235,19 @@ fn calculate_column_metrics(rows: &[Row], include_cwd: bool) -> ColumnMetrics {
             String::new()
         };
         max_updated_width = max_updated_width.max(UnicodeWidthStr::width(updated.as_str()));
+        if !tokens.is_empty() {
+            max_tokens_width = max_tokens_width
+                .max(UnicodeWidthStr::width("Tokens"))
+                .max(UnicodeWidthStr::width(tokens.as_str()));
+        }
         max_branch_width = max_branch_width.max(UnicodeWidthStr::width(branch.as_str()));
         max_cwd_width = max_cwd_width.max(UnicodeWidthStr::width(cwd.as_str()));
-        labels.push((updated, branch, cwd));
+        labels.push((updated, tokens, branch, cwd));
     }
 
     ColumnMetrics {
         max_updated_width,
+        max_tokens_width,
         max_branch_width,
         max_cwd_width,
         labels,
@@ -1211,6 +1415,8 @@ mod tests {
                 updated_at: Some(now - Duration::seconds(42)),
                 cwd: None,
                 git_branch: None,
+                token_usage: None,
+                summary: None,
             },
             Row {
                 path: PathBuf::from("/tmp/b.jsonl"),
@@ -1219,6 +1425,8 @@ mod tests {
                 updated_at: Some(now - Duration::minutes(35)),
                 cwd: None,
                 git_branch: None,
+                token_usage: None,
+                summary: None,
             },
             Row {
                 path: PathBuf::from("/tmp/c.jsonl"),
@@ -1227,6 +1435,8 @@ mod tests {
                 updated_at: Some(now - Duration::hours(2)),
                 cwd: None,
                 git_branch: None,
+                token_usage: None,
+                summary: None,
             },
         ];
         state.all_rows = rows.clone();
@@ -1601,6 +1811,136 @@ mod tests {
         assert_eq!(state.selected, state.filtered_rows.len().saturating_sub(2));
     }
 
+    #[tokio::test]
+    async fn delete_needs_confirmation_and_removes_rollout() {
+        let tempdir = tempfile::tempdir().expect("tempdir");
+        let doomed = tempdir.path().join("doomed.jsonl");
+        let kept = tempdir.path().join("kept.jsonl");
+        std::fs::write(&doomed, "").expect("write");
+        std::fs::write(&kept, "").expect("write");
+
+        let loader: PageLoader = Arc::new(|_| {});
+        let mut state = PickerState::new(
+            tempdir.path().to_path_buf(),
+            FrameRequester::test_dummy(),
+            loader,
+            String::from("openai"),
+            true,
+            None,
+        );
+        state.reset_pagination();
+        state.ingest_page(page(
+            vec![
+                make_item(
+                    doomed.to_str().expect("utf8 path"),
+                    "2025-02-02T00:00:00Z",
+                    "doomed",
+                ),
+                make_item(
+                    kept.to_str().expect("utf8 path"),
+                    "2025-02-01T00:00:00Z",
+                    "kept",
+                ),
+            ],
+            None,
+            2,
+            false,
+        ));
+
+        let delete = KeyEvent::new(KeyCode::Delete, KeyModifiers::NONE);
+        state.handle_key(delete).await.unwrap();
+        assert_eq!(state.pending_delete.as_ref(), Some(&doomed));
+        assert!(doomed.exists());
+
+        state.handle_key(delete).await.unwrap();
+        assert!(!doomed.exists());
+        assert!(kept.exists());
+        let previews: Vec<&str> = state
+            .filtered_rows
+            .iter()
+            .map(|row| row.preview.as_str())
+            .collect();
+        assert_eq!(previews, vec!["kept"]);
+    }
+
+    #[test]
+    fn token_usage_column_appears_once_loaded() {
+        let loader: PageLoader = Arc::new(|_| {});
+        let mut state = PickerState::new(
+            PathBuf::from("/tmp"),
+            FrameRequester::test_dummy(),
+            loader,
+            String::from("openai"),
+            true,
+            None,
+        );
+        state.reset_pagination();
+        state.ingest_page(page(
+            vec![make_item("/tmp/a.jsonl", "2025-02-01T00:00:00Z", "a")],
+            None,
+            1,
+            false,
+        ));
+        let metrics = calculate_column_metrics(&state.filtered_rows, state.show_all);
+        assert_eq!(metrics.max_tokens_width, 0);
+
+        state
+            .handle_background_event(BackgroundEvent::TokenUsageLoaded {
+                path: PathBuf::from("/tmp/a.jsonl"),
+                usage: TokenUsage {
+                    input_tokens: 12_000,
+                    output_tokens: 400,
+                    total_tokens: 12_400,
+                    ..Default::default()
+                },
+            })
+            .unwrap();
+
+        let metrics = calculate_column_metrics(&state.filtered_rows, state.show_all);
+        assert_eq!(metrics.labels[0].1, "12.4K");
+        assert_eq!(metrics.max_tokens_width, "Tokens".len());
+    }
+
+    #[test]
+    fn recorded_summary_titles_the_row_and_is_searchable() {
+        let loader: PageLoader = Arc::new(|_| {});
+        let mut state = PickerState::new(
+            PathBuf::from("/tmp"),
+            FrameRequester::test_dummy(),
+            loader,
+            String::from("openai"),
+            true,
+            None,
+        );
+        state.reset_pagination();
+        state.ingest_page(page(
+            vec![
+                make_item("/tmp/a.jsonl", "2025-02-02T00:00:00Z", "hi"),
+                make_item("/tmp/b.jsonl", "2025-02-01T00:00:00Z", "other"),
+            ],
+            None,
+            2,
+            false,
+        ));
+
+        state.set_query("flaky".to_string());
+        assert!(state.filtered_rows.is_empty());
+
+        state
+            .handle_background_event(BackgroundEvent::SummaryLoaded {
+                path: PathBuf::from("/tmp/a.jsonl"),
+                summary: SessionSummaryItem {
+                    title: "Fix the resume picker".to_string(),
+                    summary: "Fix the resume picker → The flaky test now waits.".to_string(),
+                },
+            })
+            .unwrap();
+
+        let titles: Vec<&str> = state.filtered_rows.iter().map(Row::title).collect();
+        assert_eq!(titles, vec!["Fix the resume picker"]);
+        assert_eq!(state.filtered_rows[0].preview, "hi");
+    }
+
     #[test]
     fn set_query_loads_until_match_and_respects_scan_cap() {
         let recorded_requests: Arc<Mutex<Vec<PageLoadRequest>>> = Arc::new(Mutex::new(Vec::new()));
@@ -33,6 +33,7 @@ pub struct ExecCommandSession {
     writer_tx: mpsc::Sender<Vec<u8>>,
     output_tx: broadcast::Sender<Vec<u8>>,
     killer: StdMutex<Option<Box<dyn portable_pty::ChildKiller + Send + Sync>>>,
+    process_id: Option<u32>,
     reader_handle: StdMutex<Option<JoinHandle<()>>>,
     writer_handle: StdMutex<Option<JoinHandle<()>>>,
     wait_handle: StdMutex<Option<JoinHandle<()>>>,
@@ -56,6 +57,7 @@ impl ExecCommandSession {
         output_tx: broadcast::Sender<Vec<u8>>,
         initial_output_rx: broadcast::Receiver<Vec<u8>>,
         killer: Box<dyn portable_pty::ChildKiller + Send + Sync>,
+        process_id: Option<u32>,
         reader_handle: JoinHandle<()>,
         writer_handle: JoinHandle<()>,
         wait_handle: JoinHandle<()>,
@@ -68,6 +70,7 @@ impl ExecCommandSession {
                 writer_tx,
                 output_tx,
                 killer: StdMutex::new(Some(killer)),
+                process_id,
                 reader_handle: StdMutex::new(Some(reader_handle)),
                 writer_handle: StdMutex::new(Some(writer_handle)),
                 wait_handle: StdMutex::new(Some(wait_handle)),
@@ -87,6 +90,12 @@ impl ExecCommandSession {
         self.output_tx.subscribe()
     }
 
+    /// The child's pid. On Unix the child leads its own session, so this is
+    /// also its process group id.
+    pub fn process_id(&self) -> Option<u32> {
+        self.process_id
+    }
+
     pub fn has_exited(&self) -> bool {
         self.exit_status.load(std::sync::atomic::Ordering::SeqCst)
     }
@@ -152,12 +161,36 @@ fn platform_native_pty_system() -> Box<dyn portable_pty::PtySystem + Send> {
     native_pty_system()
 }
 
+/// Dimensions of the pseudo-terminal a process is spawned in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerminalSize {
+    pub rows: u16,
+    pub cols: u16,
+}
+
+impl Default for TerminalSize {
+    fn default() -> Self {
+        Self { rows: 24, cols: 80 }
+    }
+}
+
 pub async fn spawn_pty_process(
     program: &str,
     args: &[String],
     cwd: &Path,
     env: &HashMap<String, String>,
     arg0: &Option<String>,
+) -> Result<SpawnedPty> {
+    spawn_pty_process_with_size(program, args, cwd, env, arg0, TerminalSize::default()).await
+}
+
+pub async fn spawn_pty_process_with_size(
+    program: &str,
+    args: &[String],
+    cwd: &Path,
+    env: &HashMap<String, String>,
+    arg0: &Option<String>,
+    size: TerminalSize,
 ) -> Result<SpawnedPty> {
     if program.is_empty() {
         anyhow::bail!("missing program for PTY spawn");
@@ -165,8 +198,8 @@ pub async fn spawn_pty_process(
 
     let pty_system = platform_native_pty_system();
     let pair = pty_system.openpty(PtySize {
-        rows: 24,
-        cols: 80,
+        rows: size.rows,
+        cols: size.cols,
         pixel_width: 0,
         pixel_height: 0,
     })?;
@@ -183,6 +216,7 @@ pub async fn spawn_pty_process(
 
     let mut child = pair.slave.spawn_command(command_builder)?;
     let killer = child.clone_killer();
+    let process_id = child.process_id();
 
     let (writer_tx, mut writer_rx) = mpsc::channel::<Vec<u8>>(128);
     let (output_tx, _) = broadcast::channel::<Vec<u8>>(256);
@@ -255,6 +289,7 @@ pub async fn spawn_pty_process(
         output_tx,
         initial_output_rx,
         killer,
+        process_id,
         reader_handle,
         writer_handle,
         wait_handle,
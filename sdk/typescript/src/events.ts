@@ -1,6 +1,6 @@
 // based on event types from codex-rs/exec/src/exec_events.rs
 
-import type { ThreadItem } from "./items";
+import type { FileUpdateChange, ThreadItem } from "./items";
 
 /** Emitted when a new thread is started as the first event. */
 export type ThreadStartedEvent = {
@@ -57,6 +57,24 @@ export type ItemCompletedEvent = {
   item: ThreadItem;
 };
 
+/** Emitted when the agent asks for approval. `codex exec` declines these requests. */
+export type ApprovalRequestedEvent = {
+  type: "approval.requested";
+  /** Call id of the tool call awaiting approval. */
+  call_id: string;
+  /** Why the agent needs approval, e.g. to retry without the sandbox. */
+  reason: string | null;
+} & (
+  | {
+      kind: "command_execution";
+      command: string;
+    }
+  | {
+      kind: "file_change";
+      changes: FileUpdateChange[];
+    }
+);
+
 /** Fatal error emitted by the stream. */
 export type ThreadError = {
   message: string;
@@ -77,4 +95,5 @@ export type ThreadEvent =
   | ItemStartedEvent
   | ItemUpdatedEvent
   | ItemCompletedEvent
+  | ApprovalRequestedEvent
   | ThreadErrorEvent;
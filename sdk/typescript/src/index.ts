@@ -7,6 +7,7 @@ export type {
   ItemStartedEvent,
   ItemUpdatedEvent,
   ItemCompletedEvent,
+  ApprovalRequestedEvent,
   ThreadError,
   ThreadErrorEvent,
   Usage,